@@ -0,0 +1,256 @@
+use criterion::*;
+use wasmtime::*;
+
+criterion_main!(benches);
+criterion_group!(benches, bench_stack_switching);
+
+fn bench_stack_switching(c: &mut Criterion) {
+    bench_cont_new(c);
+    bench_suspend_resume(c, false);
+    bench_suspend_resume(c, true);
+    bench_handler_depth(c);
+    bench_calls(c);
+}
+
+fn engine(configure: impl FnOnce(&mut Config)) -> Engine {
+    let mut config = Config::new();
+    config.wasm_function_references(true);
+    config.wasm_exceptions(true);
+    config.wasm_stack_switching(true);
+    configure(&mut config);
+    Engine::new(&config).unwrap()
+}
+
+/// Instantiates `wat` in a new store, returning the store and its `run`
+/// export.
+fn instantiate<Params, Results>(
+    engine: &Engine,
+    wat: &str,
+) -> (Store<()>, TypedFunc<Params, Results>)
+where
+    Params: WasmParams,
+    Results: WasmResults,
+{
+    let module = Module::new(engine, wat).unwrap();
+    let mut store = Store::new(engine, ());
+    let instance = Instance::new(&mut store, &module, &[]).unwrap();
+    let run = instance.get_typed_func(&mut store, "run").unwrap();
+    (store, run)
+}
+
+/// Measures creating continuations with `cont.new` and running them to
+/// completion, with and without a stack cache.
+fn bench_cont_new(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cont-new");
+    let wat = r#"
+(module
+  (type $ft (func))
+  (type $ct (cont $ft))
+  (func $empty)
+  (elem declare func $empty)
+
+  (func (export "run") (param $n i32)
+    (loop $l
+      (resume $ct (cont.new $ct (ref.func $empty)))
+      (local.tee $n (i32.sub (local.get $n) (i32.const 1)))
+      (br_if $l)))
+)
+"#;
+
+    for (name, cache_size) in [("no-cache", 0), ("cache", 16)] {
+        let engine = engine(|config| {
+            config.wasmfx_stack_cache_size(cache_size);
+        });
+        group.bench_function(name, |b| {
+            let (mut store, run) = instantiate::<u32, ()>(&engine, wat);
+            b.iter(|| run.call(&mut store, 100).unwrap());
+        });
+    }
+
+    group.finish();
+}
+
+/// Measures a round trip of `suspend` and `resume` where the `resume`
+/// installs a varying number of handlers, and the tag being suspended with is
+/// handled by the last of them.
+fn bench_suspend_resume(c: &mut Criterion, dispatch_tables: bool) {
+    let name = if dispatch_tables {
+        "suspend-resume-dispatch-tables"
+    } else {
+        "suspend-resume"
+    };
+    let mut group = c.benchmark_group(name);
+    let engine = engine(|config| {
+        config.wasmfx_tag_dispatch_tables(dispatch_tables);
+    });
+
+    for handlers in [1, 4, 16, 64] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(handlers),
+            &handlers,
+            |b, &handlers| {
+                let module = module(&engine, handlers).unwrap();
+                let mut store = Store::new(&engine, ());
+                let instance = Instance::new(&mut store, &module, &[]).unwrap();
+                let run = instance
+                    .get_typed_func::<u32, ()>(&mut store, "run")
+                    .unwrap();
+                b.iter(|| run.call(&mut store, 100).unwrap());
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn module(engine: &Engine, handlers: usize) -> Result<Module> {
+    let last = handlers - 1;
+    let tags: String = (0..handlers).map(|i| format!("(tag $t{i})\n")).collect();
+    let clauses: String = (0..handlers).map(|i| format!("(on $t{i} $h) ")).collect();
+    let wat = format!(
+        r#"
+(module
+  (type $ft (func))
+  (type $ct (cont $ft))
+  {tags}
+  (func $yield-forever
+    (loop $l
+      (suspend $t{last})
+      (br $l)))
+  (elem declare func $yield-forever)
+
+  (func (export "run") (param $n i32)
+    (local $k (ref null $ct))
+    (local.set $k (cont.new $ct (ref.func $yield-forever)))
+    (loop $l
+      (block $h (result (ref $ct))
+        (resume $ct {clauses} (local.get $k))
+        (unreachable))
+      (local.set $k)
+      (local.tee $n (i32.sub (local.get $n) (i32.const 1)))
+      (br_if $l)))
+)
+"#
+    );
+    Module::new(engine, &wat)
+}
+
+/// Measures a round trip of `suspend` and `resume` where the suspending
+/// continuation is nested in a varying number of other continuations, none of
+/// which handle the tag, so the handler is found `depth` levels up.
+fn bench_handler_depth(c: &mut Criterion) {
+    let mut group = c.benchmark_group("handler-depth");
+    let engine = engine(|_| {});
+    let wat = r#"
+(module
+  (type $ft (func))
+  (type $ct (cont $ft))
+  (tag $outer)
+  (tag $inner)
+
+  (global $depth (mut i32) (i32.const 0))
+  (global $remaining (mut i32) (i32.const 0))
+
+  ;; Nests `$depth` continuations, each handling only `$inner`, the innermost
+  ;; of which suspends with `$outer` until `$remaining` reaches zero.
+  (func $descend
+    (if (i32.eqz (global.get $depth))
+      (then
+        (loop $l
+          (if (global.get $remaining)
+            (then
+              (global.set $remaining (i32.sub (global.get $remaining) (i32.const 1)))
+              (suspend $outer)
+              (br $l)))))
+      (else
+        (global.set $depth (i32.sub (global.get $depth) (i32.const 1)))
+        (block $h (result (ref $ct))
+          (resume $ct (on $inner $h) (cont.new $ct (ref.func $descend)))
+          (return))
+        (drop))))
+  (elem declare func $descend)
+
+  (func (export "run") (param $depth i32) (param $n i32)
+    (local $k (ref null $ct))
+    (global.set $depth (local.get $depth))
+    (global.set $remaining (local.get $n))
+    (local.set $k (cont.new $ct (ref.func $descend)))
+    (loop $l
+      (block $h (result (ref $ct))
+        (resume $ct (on $outer $h) (local.get $k))
+        (return))
+      (local.set $k)
+      (br $l)))
+)
+"#;
+
+    for depth in [0, 1, 4, 16, 64] {
+        group.bench_with_input(BenchmarkId::from_parameter(depth), &depth, |b, &depth| {
+            let (mut store, run) = instantiate::<(u32, u32), ()>(&engine, wat);
+            b.iter(|| run.call(&mut store, (depth, 100)).unwrap());
+        });
+    }
+
+    group.finish();
+}
+
+/// Compares calling a function with switching to a long-lived continuation
+/// which does the same work and suspends back, as a generator would.
+fn bench_calls(c: &mut Criterion) {
+    let mut group = c.benchmark_group("calls");
+    let engine = engine(|_| {});
+    let wat = r#"
+(module
+  (type $ft (func))
+  (type $ct (cont $ft))
+  (tag $yield)
+
+  (global $counter (mut i32) (i32.const 0))
+  (global $remaining (mut i32) (i32.const 0))
+
+  (func $work
+    (global.set $counter (i32.add (global.get $counter) (i32.const 1))))
+
+  (func $generator
+    (loop $l
+      (if (global.get $remaining)
+        (then
+          (global.set $remaining (i32.sub (global.get $remaining) (i32.const 1)))
+          (call $work)
+          (suspend $yield)
+          (br $l)))))
+  (elem declare func $generator)
+
+  (func (export "plain") (param $n i32)
+    (loop $l
+      (call $work)
+      (local.tee $n (i32.sub (local.get $n) (i32.const 1)))
+      (br_if $l)))
+
+  (func (export "continuation") (param $n i32)
+    (local $k (ref null $ct))
+    (global.set $remaining (local.get $n))
+    (local.set $k (cont.new $ct (ref.func $generator)))
+    (loop $l
+      (block $h (result (ref $ct))
+        (resume $ct (on $yield $h) (local.get $k))
+        (return))
+      (local.set $k)
+      (br $l)))
+)
+"#;
+
+    let module = Module::new(&engine, wat).unwrap();
+    for name in ["plain", "continuation"] {
+        group.bench_function(name, |b| {
+            let mut store = Store::new(&engine, ());
+            let instance = Instance::new(&mut store, &module, &[]).unwrap();
+            let run = instance
+                .get_typed_func::<u32, ()>(&mut store, name)
+                .unwrap();
+            b.iter(|| run.call(&mut store, 100).unwrap());
+        });
+    }
+
+    group.finish();
+}
@@ -209,6 +209,9 @@ fn run_compilation(compilation: &IsleCompilation) -> Result<(), Errors> {
         // include!()s it. (See
         // https://github.com/rust-lang/rust/issues/47995.)
         options.exclude_global_allow_pragmas = true;
+        // The mid-end reports which of its rewrite rules fire, for the
+        // `egraph_rule_stats` setting.
+        options.emit_rule_hooks = compilation.name == "opt";
 
         isle::compile::from_files(file_paths, &options)?
     };
@@ -112,6 +112,20 @@ pub(crate) fn define() -> TargetIsa {
         false,
     );
 
+    settings.add_bool(
+        "prefer_smaller_encodings",
+        "Prefer the shortest encodings of instructions when optimizing for size.",
+        r#"
+            When enabled and `opt_level` is `speed_and_size`, instructions are
+            emitted with their shortest available encodings, such as the
+            accumulator-specific forms of ALU instructions with immediates, or
+            the implicit-count form of shifts by one. These encodings are
+            equivalent to the default ones, but can differ in how they are
+            decoded, which is why they are not used by default.
+        "#,
+        false,
+    );
+
     settings.add_predicate("use_cmpxchg16b", predicate!(has_cmpxchg16b));
     settings.add_predicate("use_ssse3", predicate!(has_ssse3));
     settings.add_predicate("use_sse41", predicate!(has_sse41));
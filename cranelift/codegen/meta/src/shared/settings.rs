@@ -30,6 +30,19 @@ pub(crate) fn define() -> SettingGroup {
         false,
     );
 
+    settings.add_bool(
+        "egraph_rule_stats",
+        "Count which rewrite rules fire during egraph optimization.",
+        r#"
+            This records how many times each ISLE rule in the mid-end fired
+            while optimizing a function, which is available afterwards from
+            `Context::egraph_rule_stats`. It helps rule authors find rules which
+            never fire or which conflict with others. It is disabled by default
+            as it slows down optimization.
+        "#,
+        false,
+    );
+
     settings.add_enum(
         "regalloc_algorithm",
         "Algorithm to use in register allocator.",
@@ -67,6 +80,37 @@ pub(crate) fn define() -> SettingGroup {
         true,
     );
 
+    settings.add_bool(
+        "enable_redundant_check_elimination",
+        "Remove conditional traps that are implied by dominating checks.",
+        r#"
+            This enables a pass which uses a simple value-range analysis to remove
+            conditional traps, such as heap bounds checks, which can never trap because a
+            dominating check of a larger or equal index against the same bound has already
+            been performed. Only effective when `opt_level` is `speed` or `speed_and_size`.
+
+            Only `trapz`, `trapnz`, and `uadd_overflow_trap` are affected: bounds checks
+            which use `select_spectre_guard` are never removed, since a dominating check
+            may be mispredicted, so this has little effect on heap accesses unless
+            `enable_heap_access_spectre_mitigation` is disabled.
+        "#,
+        false,
+    );
+
+    settings.add_bool(
+        "enable_vmctx_invariant_loads",
+        "Treat loads from the `vmctx` alias region as invariant where nothing may modify it.",
+        r#"
+            When enabled, loads tagged with the `vmctx` alias region are marked `readonly` in
+            functions which contain no stores to that region and no calls other than to
+            functions declared `preserves_vmctx`, so that they can be deduplicated and hoisted
+            out of loops. Frontends may also use this setting to decide whether to tag loads of
+            runtime-maintained fields with the `vmctx` alias region in the first place. Only
+            effective when `opt_level` is `speed` or `speed_and_size`.
+        "#,
+        false,
+    );
+
     settings.add_bool(
         "enable_verifier",
         "Run the Cranelift IR verifier at strategic times during compilation.",
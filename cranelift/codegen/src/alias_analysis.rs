@@ -60,6 +60,16 @@
 //! and the conditions for doing it correctly when post-trap state
 //! must be correct likely reduce the potential benefit, we don't yet
 //! do this.
+//!
+//! Calls are otherwise treated as writing all abstract state, but a
+//! callee declared with `preserves_vmctx` leaves the "vmctx" category
+//! untouched. Loads of fields which only the runtime updates (e.g. a
+//! linear memory's current length) can then be reused across calls
+//! that cannot update them. Taking this one step further, if nothing in
+//! a function may write the "vmctx" category, then all loads from it
+//! observe the same memory and are marked `readonly` by
+//! [`mark_invariant_vmctx_loads`], which lets the egraph pass GVN them
+//! and hoist them out of loops.
 
 use crate::{
     cursor::{Cursor, FuncCursor},
@@ -67,7 +77,10 @@ use crate::{
     inst_predicates::{
         has_memory_fence_semantics, inst_addr_offset_type, inst_store_data, visit_block_succs,
     },
-    ir::{immediates::Offset32, AliasRegion, Block, Function, Inst, Opcode, Type, Value},
+    ir::{
+        immediates::Offset32, AliasRegion, Block, Function, Inst, InstructionData, Opcode, Type,
+        Value,
+    },
     trace,
 };
 use cranelift_entity::{packed_option::PackedOption, EntityRef};
@@ -89,7 +102,9 @@ impl LastStores {
         if has_memory_fence_semantics(opcode) {
             self.heap = inst.into();
             self.table = inst.into();
-            self.vmctx = inst.into();
+            if !call_preserves_vmctx(func, inst) {
+                self.vmctx = inst.into();
+            }
             self.other = inst.into();
         } else if opcode.can_store() {
             if let Some(memflags) = func.dfg.insts[inst].memflags() {
@@ -393,6 +408,78 @@ impl<'a> AliasAnalysis<'a> {
     }
 }
 
+/// Is the given instruction a direct call to a function which is declared not
+/// to modify the "vmctx" category of abstract state?
+fn call_preserves_vmctx(func: &Function, inst: Inst) -> bool {
+    match func.dfg.insts[inst] {
+        InstructionData::Call {
+            opcode: Opcode::Call,
+            func_ref,
+            ..
+        } => func.dfg.ext_funcs[func_ref].preserves_vmctx,
+        _ => false,
+    }
+}
+
+/// May the given instruction write to the "vmctx" category of abstract
+/// state?
+fn may_write_vmctx(func: &Function, inst: Inst) -> bool {
+    let data = &func.dfg.insts[inst];
+    let opcode = data.opcode();
+    if opcode.can_store() {
+        match data.memflags() {
+            Some(flags) => flags.alias_region() == Some(AliasRegion::Vmctx),
+            None => true,
+        }
+    } else if opcode.is_call() {
+        !call_preserves_vmctx(func, inst)
+    } else {
+        // Trapping instructions write no memory, but fences and
+        // everything else with unmodeled side effects might.
+        opcode.other_side_effects()
+    }
+}
+
+/// If no instruction in `func` may write to the "vmctx" category of
+/// abstract state, mark every non-trapping load from it as `readonly`.
+///
+/// Returns the number of loads marked.
+pub fn mark_invariant_vmctx_loads(func: &mut Function) -> usize {
+    let mut loads = vec![];
+    for block in func.layout.blocks() {
+        for inst in func.layout.block_insts(block) {
+            if may_write_vmctx(func, inst) {
+                trace!(
+                    "alias analysis: inst{} may write vmctx; not marking loads",
+                    inst.index()
+                );
+                return 0;
+            }
+            if let InstructionData::Load {
+                opcode: Opcode::Load,
+                flags,
+                ..
+            } = func.dfg.insts[inst]
+            {
+                if flags.alias_region() == Some(AliasRegion::Vmctx)
+                    && flags.notrap()
+                    && !flags.readonly()
+                {
+                    loads.push(inst);
+                }
+            }
+        }
+    }
+
+    for &inst in &loads {
+        trace!("alias analysis: marking inst{} readonly", inst.index());
+        if let InstructionData::Load { flags, .. } = &mut func.dfg.insts[inst] {
+            flags.set_readonly();
+        }
+    }
+    loads.len()
+}
+
 fn get_ext_opcode(op: Opcode) -> Option<Opcode> {
     debug_assert!(op.can_load() || op.can_store());
     match op {
@@ -9,16 +9,19 @@
 //! contexts concurrently. Typically, you would have one context per compilation thread and only a
 //! single ISA instance.
 
-use crate::alias_analysis::AliasAnalysis;
+use crate::alias_analysis::{mark_invariant_vmctx_loads, AliasAnalysis};
+use crate::divconst::do_divconst_strength_reduction;
 use crate::dominator_tree::DominatorTree;
-use crate::egraph::EgraphPass;
+use crate::egraph::{EgraphPass, RuleStats};
 use crate::flowgraph::ControlFlowGraph;
+use crate::inline::{do_inlining, Inline};
 use crate::ir::Function;
 use crate::isa::TargetIsa;
 use crate::legalizer::simple_legalize;
 use crate::loop_analysis::LoopAnalysis;
 use crate::machinst::{CompiledCode, CompiledCodeStencil};
 use crate::nan_canonicalization::do_nan_canonicalization;
+use crate::redundant_checks::do_remove_redundant_checks;
 use crate::remove_constant_phis::do_remove_constant_phis;
 use crate::result::{CodegenResult, CompileResult};
 use crate::settings::{FlagsOrIsa, OptLevel};
@@ -54,6 +57,9 @@ pub struct Context {
 
     /// Flag: do we want a disassembly with the CompiledCode?
     pub want_disasm: bool,
+
+    /// Mid-end rule statistics from the last egraph pass.
+    egraph_rule_stats: RuleStats,
 }
 
 impl Context {
@@ -77,6 +83,7 @@ impl Context {
             loop_analysis: LoopAnalysis::new(),
             compiled_code: None,
             want_disasm: false,
+            egraph_rule_stats: RuleStats::default(),
         }
     }
 
@@ -88,6 +95,15 @@ impl Context {
         self.loop_analysis.clear();
         self.compiled_code = None;
         self.want_disasm = false;
+        self.egraph_rule_stats = RuleStats::default();
+    }
+
+    /// Returns how many times each mid-end rewrite rule fired during the last
+    /// egraph pass.
+    ///
+    /// This is empty unless the `egraph_rule_stats` setting was enabled.
+    pub fn egraph_rule_stats(&self) -> &RuleStats {
+        &self.egraph_rule_stats
     }
 
     /// Returns the compilation result for this function, available after any `compile` function
@@ -170,6 +186,11 @@ impl Context {
         }
 
         self.legalize(isa)?;
+        // Proof-carrying code can't check the facts of the rewritten division
+        // sequences, so they're left for the backends.
+        if opt_level != OptLevel::None && !isa.flags().enable_pcc() {
+            self.divconst_strength_reduction(isa)?;
+        }
 
         self.compute_domtree();
         self.eliminate_unreachable_code(isa)?;
@@ -178,7 +199,13 @@ impl Context {
         self.func.dfg.resolve_all_aliases();
 
         if opt_level != OptLevel::None {
+            if isa.flags().enable_vmctx_invariant_loads() {
+                self.mark_invariant_vmctx_loads();
+            }
             self.egraph_pass(isa, ctrl_plane)?;
+            if isa.flags().enable_redundant_check_elimination() {
+                self.remove_redundant_checks(isa)?;
+            }
         }
 
         Ok(())
@@ -257,6 +284,26 @@ impl Context {
         Ok(())
     }
 
+    /// Inline the calls in the function that `inliner` asks for.
+    ///
+    /// Returns whether any call was inlined. See the [`inline`](crate::inline)
+    /// module for details.
+    pub fn inline<'a, FOI>(&mut self, fisa: FOI, inliner: impl Inline) -> CodegenResult<bool>
+    where
+        FOI: Into<FlagsOrIsa<'a>>,
+    {
+        // Inlining changes the CFG, so any existing analyses are stale.
+        self.cfg.clear();
+        self.domtree.clear();
+        self.loop_analysis.clear();
+
+        let inlined = do_inlining(&mut self.func, inliner);
+        if inlined {
+            self.verify_if(fisa)?;
+        }
+        Ok(inlined)
+    }
+
     /// Perform constant-phi removal on the function.
     pub fn remove_constant_phis<'a, FOI: Into<FlagsOrIsa<'a>>>(
         &mut self,
@@ -267,6 +314,28 @@ impl Context {
         Ok(())
     }
 
+    /// Remove conditional traps which are implied by dominating checks.
+    ///
+    /// See the `redundant_checks` module for details.
+    pub fn remove_redundant_checks<'a, FOI: Into<FlagsOrIsa<'a>>>(
+        &mut self,
+        fisa: FOI,
+    ) -> CodegenResult<()> {
+        do_remove_redundant_checks(&mut self.func, &self.domtree);
+        self.verify_if(fisa)
+    }
+
+    /// Rewrite division and remainder by constants into cheaper sequences.
+    ///
+    /// See the `divconst` module for details.
+    pub fn divconst_strength_reduction<'a, FOI: Into<FlagsOrIsa<'a>>>(
+        &mut self,
+        fisa: FOI,
+    ) -> CodegenResult<()> {
+        do_divconst_strength_reduction(&mut self.func);
+        self.verify_if(fisa)
+    }
+
     /// Perform NaN canonicalizing rewrites on the function.
     pub fn canonicalize_nans(&mut self, isa: &dyn TargetIsa) -> CodegenResult<()> {
         // Currently only RiscV64 is the only arch that may not have vector support.
@@ -335,6 +404,15 @@ impl Context {
         Ok(())
     }
 
+    /// Mark loads from the `vmctx` alias region as `readonly` if nothing in
+    /// the function may write to that region.
+    ///
+    /// See the `alias_analysis` module for details.
+    pub fn mark_invariant_vmctx_loads(&mut self) {
+        let marked = mark_invariant_vmctx_loads(&mut self.func);
+        log::debug!("Marked {marked} vmctx loads as readonly");
+    }
+
     /// Harvest candidate left-hand sides for superoptimization with Souper.
     #[cfg(feature = "souper-harvest")]
     pub fn souper_harvest(
@@ -374,6 +452,7 @@ impl Context {
         pass.run();
         log::debug!("egraph stats: {:?}", pass.stats);
         trace!("pinned_union_count: {}", pass.eclasses.pinned_union_count);
+        self.egraph_rule_stats = core::mem::take(&mut pass.stats.rules);
         trace!("After egraph optimization:\n{}", self.func.display());
 
         self.verify_if(fisa)
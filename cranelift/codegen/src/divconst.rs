@@ -0,0 +1,403 @@
+//! Strength reduction of division and remainder by constants.
+//!
+//! Division is one of the slowest integer operations on every target, and
+//! `udiv`, `sdiv`, `urem`, and `srem` by a constant divisor are common: hash
+//! tables index buckets with `urem`, interpreters decode operands with `udiv`,
+//! and so on. This pass rewrites them into multiplications by "magic numbers",
+//! shifts, and additions, following chapter 10 of Hacker's Delight (2nd
+//! edition) by Henry S. Warren, Jr.
+//!
+//! Besides being faster, the rewritten sequences can never trap, which is
+//! correct because the instructions being rewritten can't either: the divisor
+//! is a nonzero constant, and signed division by `-1`, which may overflow, is
+//! left alone. The trapping division instructions are part of the egraph's
+//! side-effecting skeleton and can't be rewritten by its rules, which is why
+//! this is a separate pass that runs before it.
+//!
+//! Only `i32` and `i64` are handled. Backends already avoid the checks for
+//! zero and overflow when the divisor is a constant, which covers the rest.
+
+use crate::cursor::{Cursor, FuncCursor};
+use crate::ir::types::{I32, I64};
+use crate::ir::{Function, InstBuilder, InstructionData, Opcode, Type, Value};
+use crate::timing;
+
+/// The magic numbers for unsigned division by a constant `d` which isn't a
+/// power of two, such that `x / d == umulhi(x, mul) >> shift` if `!add`.
+///
+/// If `add` is set then `mul` is really `2^bits + mul`, which doesn't fit in a
+/// register, and `x / d == (((x - t) >> 1) + t) >> (shift - 1)` where
+/// `t == umulhi(x, mul)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct MagicU {
+    mul: u64,
+    add: bool,
+    shift: u32,
+}
+
+/// The magic numbers for signed division by a constant `d` where `|d| >= 2`,
+/// such that `x / d == q + (q >>> (bits - 1))` with `q == smulhi(x, mul) >>
+/// shift`, after first adding `x` to the `smulhi` if `d > 0` and `mul < 0` or
+/// subtracting it if `d < 0` and `mul > 0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct MagicS {
+    mul: i64,
+    shift: u32,
+}
+
+/// Computes the magic numbers for unsigned division by `d` of `bits`-bit
+/// integers, see Figure 10-2 of Hacker's Delight.
+fn magic_u(d: u64, bits: u32) -> MagicU {
+    debug_assert!(bits == 32 || bits == 64);
+    debug_assert!(d >= 1 && !d.is_power_of_two());
+    // Intermediate values are computed in 128 bits and wrapped to `bits`.
+    let mask = u128::from(u64::MAX >> (64 - bits));
+    let min = 1u128 << (bits - 1);
+    let d = u128::from(d);
+
+    let mut add = false;
+    let nc = mask - ((mask + 1 - d) & mask) % d;
+    let mut p = bits - 1;
+    let mut q1 = min / nc;
+    let mut r1 = min - q1 * nc;
+    let mut q2 = (min - 1) / d;
+    let mut r2 = (min - 1) - q2 * d;
+    loop {
+        p += 1;
+        if r1 >= nc - r1 {
+            q1 = (2 * q1 + 1) & mask;
+            r1 = (2 * r1 - nc) & mask;
+        } else {
+            q1 = (2 * q1) & mask;
+            r1 = (2 * r1) & mask;
+        }
+        if r2 + 1 >= d - r2 {
+            if q2 >= min - 1 {
+                add = true;
+            }
+            q2 = (2 * q2 + 1) & mask;
+            r2 = (2 * r2 + 1 - d) & mask;
+        } else {
+            if q2 >= min {
+                add = true;
+            }
+            q2 = (2 * q2) & mask;
+            r2 = (2 * r2 + 1) & mask;
+        }
+        let delta = d - 1 - r2;
+        if !(p < 2 * bits && (q1 < delta || (q1 == delta && r1 == 0))) {
+            break;
+        }
+    }
+    MagicU {
+        mul: ((q2 + 1) & mask) as u64,
+        add,
+        shift: p - bits,
+    }
+}
+
+/// Computes the magic numbers for signed division by `d` of `bits`-bit
+/// integers, see Figure 10-1 of Hacker's Delight.
+fn magic_s(d: i64, bits: u32) -> MagicS {
+    debug_assert!(bits == 32 || bits == 64);
+    debug_assert!(d.unsigned_abs() >= 2);
+    // Intermediate values are computed in 128 bits and wrapped to `bits`.
+    let mask = u128::from(u64::MAX >> (64 - bits));
+    let min = 1u128 << (bits - 1);
+
+    let ad = u128::from(d.unsigned_abs());
+    let t = min + u128::from(d < 0);
+    let anc = t - 1 - t % ad;
+    let mut p = bits - 1;
+    let mut q1 = min / anc;
+    let mut r1 = min - q1 * anc;
+    let mut q2 = min / ad;
+    let mut r2 = min - q2 * ad;
+    loop {
+        p += 1;
+        q1 = (2 * q1) & mask;
+        r1 = (2 * r1) & mask;
+        if r1 >= anc {
+            q1 = (q1 + 1) & mask;
+            r1 -= anc;
+        }
+        q2 = (2 * q2) & mask;
+        r2 = (2 * r2) & mask;
+        if r2 >= ad {
+            q2 = (q2 + 1) & mask;
+            r2 -= ad;
+        }
+        let delta = ad - r2;
+        if !(q1 < delta || (q1 == delta && r1 == 0)) {
+            break;
+        }
+    }
+    // Sign-extend the `bits`-bit multiplier.
+    let mul = ((((q2 + 1) & mask) as u64) << (64 - bits)) as i64 >> (64 - bits);
+    MagicS {
+        mul: if d < 0 { mul.wrapping_neg() } else { mul },
+        shift: p - bits,
+    }
+}
+
+/// Rewrite every `udiv`, `sdiv`, `urem`, and `srem` of `i32` or `i64` whose
+/// divisor is a constant into a sequence of cheaper instructions.
+pub fn do_divconst_strength_reduction(func: &mut Function) {
+    let _tt = timing::divconst_strength_reduction();
+    let mut pos = FuncCursor::new(func);
+    while let Some(_block) = pos.next_block() {
+        while let Some(inst) = pos.next_inst() {
+            let (opcode, [x, y]) = match pos.func.dfg.insts[inst] {
+                InstructionData::Binary {
+                    opcode: opcode @ (Opcode::Udiv | Opcode::Urem | Opcode::Sdiv | Opcode::Srem),
+                    args,
+                } => (opcode, args),
+                _ => continue,
+            };
+            let ty = pos.func.dfg.value_type(x);
+            if ty != I32 && ty != I64 {
+                continue;
+            }
+            let Some(divisor) = iconst_value(pos.func, y) else {
+                continue;
+            };
+            let bits = ty.bits();
+
+            pos.use_srcloc(inst);
+            let result = match opcode {
+                Opcode::Udiv | Opcode::Urem => {
+                    if divisor == 0 {
+                        continue;
+                    }
+                    if opcode == Opcode::Urem && divisor.is_power_of_two() {
+                        let mask = iconst(&mut pos, ty, divisor - 1);
+                        pos.ins().band(x, mask)
+                    } else {
+                        let quotient = udiv_by_const(&mut pos, ty, x, divisor);
+                        if opcode == Opcode::Udiv {
+                            quotient
+                        } else {
+                            remainder(&mut pos, ty, x, quotient, divisor)
+                        }
+                    }
+                }
+                Opcode::Sdiv | Opcode::Srem => {
+                    // Sign-extend the constant to 64 bits.
+                    let divisor = ((divisor << (64 - bits)) as i64) >> (64 - bits);
+                    if divisor == 0 || divisor == -1 {
+                        continue;
+                    }
+                    let quotient = sdiv_by_const(&mut pos, ty, x, divisor);
+                    if opcode == Opcode::Sdiv {
+                        quotient
+                    } else {
+                        remainder(&mut pos, ty, x, quotient, divisor as u64)
+                    }
+                }
+                _ => unreachable!(),
+            };
+
+            let old = pos.func.dfg.first_result(inst);
+            pos.func.dfg.clear_results(inst);
+            pos.func.dfg.change_to_alias(old, result);
+            pos.remove_inst_and_step_back();
+        }
+    }
+}
+
+/// Returns the value of `value`, masked to its type, if it's an `iconst`.
+fn iconst_value(func: &Function, value: Value) -> Option<u64> {
+    let inst = func.dfg.value_def(value).inst()?;
+    match func.dfg.insts[inst] {
+        InstructionData::UnaryImm {
+            opcode: Opcode::Iconst,
+            imm,
+        } => {
+            let bits = func.dfg.value_type(value).bits();
+            Some(imm.bits() as u64 & (u64::MAX >> (64 - bits)))
+        }
+        _ => None,
+    }
+}
+
+/// Inserts an `iconst` of `value` truncated to `ty`.
+fn iconst(pos: &mut FuncCursor, ty: Type, value: u64) -> Value {
+    let value = value & (u64::MAX >> (64 - ty.bits()));
+    pos.ins().iconst(ty, value as i64)
+}
+
+/// Computes `x / d` for a nonzero unsigned `d`.
+fn udiv_by_const(pos: &mut FuncCursor, ty: Type, x: Value, d: u64) -> Value {
+    if d == 1 {
+        return x;
+    }
+    if d.is_power_of_two() {
+        let k = iconst(pos, ty, d.trailing_zeros().into());
+        return pos.ins().ushr(x, k);
+    }
+    let MagicU { mul, add, shift } = magic_u(d, ty.bits());
+    let mul = iconst(pos, ty, mul);
+    let t = pos.ins().umulhi(x, mul);
+    if add {
+        debug_assert!(shift >= 1);
+        let one = iconst(pos, ty, 1);
+        let q = pos.ins().isub(x, t);
+        let q = pos.ins().ushr(q, one);
+        let q = pos.ins().iadd(q, t);
+        let k = iconst(pos, ty, (shift - 1).into());
+        pos.ins().ushr(q, k)
+    } else if shift > 0 {
+        let k = iconst(pos, ty, shift.into());
+        pos.ins().ushr(t, k)
+    } else {
+        t
+    }
+}
+
+/// Computes `x / d` for a signed `d` which isn't 0 or -1.
+fn sdiv_by_const(pos: &mut FuncCursor, ty: Type, x: Value, d: i64) -> Value {
+    let bits = ty.bits();
+    if d == 1 {
+        return x;
+    }
+    let ad = d.unsigned_abs();
+    if ad.is_power_of_two() {
+        // Round towards zero by adding `|d| - 1` to negative dividends before
+        // shifting.
+        let k = ad.trailing_zeros();
+        let k_minus_one = iconst(pos, ty, (k - 1).into());
+        let sign = pos.ins().sshr(x, k_minus_one);
+        let bits_minus_k = iconst(pos, ty, (bits - k).into());
+        let bias = pos.ins().ushr(sign, bits_minus_k);
+        let t = pos.ins().iadd(x, bias);
+        let k = iconst(pos, ty, k.into());
+        let q = pos.ins().sshr(t, k);
+        return if d < 0 { pos.ins().ineg(q) } else { q };
+    }
+
+    let MagicS { mul, shift } = magic_s(d, bits);
+    let mul_val = iconst(pos, ty, mul as u64);
+    let mut q = pos.ins().smulhi(x, mul_val);
+    if d > 0 && mul < 0 {
+        q = pos.ins().iadd(q, x);
+    } else if d < 0 && mul > 0 {
+        q = pos.ins().isub(q, x);
+    }
+    if shift > 0 {
+        let k = iconst(pos, ty, shift.into());
+        q = pos.ins().sshr(q, k);
+    }
+    // Add one to negative quotients to round towards zero.
+    let k = iconst(pos, ty, (bits - 1).into());
+    let sign = pos.ins().ushr(q, k);
+    pos.ins().iadd(q, sign)
+}
+
+/// Computes `x - q * d`, the remainder of a division whose quotient is `q`.
+fn remainder(pos: &mut FuncCursor, ty: Type, x: Value, q: Value, d: u64) -> Value {
+    let d = iconst(pos, ty, d);
+    let product = pos.ins().imul(q, d);
+    pos.ins().isub(x, product)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn udiv(x: u64, d: u64, bits: u32) -> u64 {
+        let mask = u64::MAX >> (64 - bits);
+        let MagicU { mul, add, shift } = magic_u(d, bits);
+        let t = ((u128::from(x) * u128::from(mul)) >> bits) as u64;
+        if add {
+            ((((x - t) & mask) >> 1) + t) >> (shift - 1)
+        } else {
+            t >> shift
+        }
+    }
+
+    fn sdiv(x: i64, d: i64, bits: u32) -> i64 {
+        let wrap = |x: i64| (x << (64 - bits)) >> (64 - bits);
+        let MagicS { mul, shift } = magic_s(d, bits);
+        let mut q = ((i128::from(x) * i128::from(mul)) >> bits) as i64;
+        if d > 0 && mul < 0 {
+            q = wrap(q.wrapping_add(x));
+        } else if d < 0 && mul > 0 {
+            q = wrap(q.wrapping_sub(x));
+        }
+        q >>= shift;
+        wrap(q.wrapping_add(i64::from(q < 0)))
+    }
+
+    const DIVIDENDS: &[u64] = &[0, 1, 2, 3, 6, 7, 8, 100, 1 << 31, 1 << 32, u64::MAX - 1];
+
+    #[test]
+    fn known_magic_numbers() {
+        assert_eq!(
+            magic_u(3, 32),
+            MagicU {
+                mul: 0xaaaa_aaab,
+                add: false,
+                shift: 1
+            }
+        );
+        assert_eq!(
+            magic_u(7, 32),
+            MagicU {
+                mul: 0x2492_4925,
+                add: true,
+                shift: 3
+            }
+        );
+        assert_eq!(
+            magic_s(3, 32),
+            MagicS {
+                mul: 0x5555_5556,
+                shift: 0
+            }
+        );
+        assert_eq!(
+            magic_s(7, 32),
+            MagicS {
+                mul: 0x9249_2493u32 as i32 as i64,
+                shift: 2
+            }
+        );
+    }
+
+    #[test]
+    fn unsigned() {
+        for bits in [32, 64] {
+            let mask = u64::MAX >> (64 - bits);
+            for d in (3..1000).chain([mask / 3, mask - 2, mask]) {
+                if d.is_power_of_two() {
+                    continue;
+                }
+                for x in DIVIDENDS.iter().map(|x| x & mask).chain([mask, d, d - 1]) {
+                    assert_eq!(udiv(x, d, bits), x / d, "{x} / {d} ({bits} bits)");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn signed() {
+        for bits in [32, 64] {
+            let max = (u64::MAX >> (65 - bits)) as i64;
+            let min = -max - 1;
+            for d in (3..1000).chain([max / 3, max - 1, max]) {
+                for d in [d, -d] {
+                    if d.unsigned_abs().is_power_of_two() {
+                        continue;
+                    }
+                    let dividends = DIVIDENDS
+                        .iter()
+                        .map(|&x| ((x << (64 - bits)) as i64) >> (64 - bits))
+                        .chain([min, max, d, d - 1, -d]);
+                    for x in dividends {
+                        assert_eq!(sdiv(x, d, bits), x / d, "{x} / {d} ({bits} bits)");
+                    }
+                }
+            }
+        }
+    }
+}
@@ -17,7 +17,9 @@ use crate::scoped_hash_map::{Entry as ScopedEntry, ScopedHashMap};
 use crate::settings::Flags;
 use crate::trace;
 use crate::unionfind::UnionFind;
+use alloc::collections::BTreeMap;
 use core::cmp::Ordering;
+use core::fmt;
 use cranelift_control::ControlPlane;
 use cranelift_entity::packed_option::ReservedValue;
 use cranelift_entity::SecondaryMap;
@@ -129,6 +131,17 @@ impl<'opt, 'analysis> OptimizeCtx<'opt, 'analysis>
 where
     'analysis: 'opt,
 {
+    /// Record that the ISLE rule at `rule` produced a rewrite.
+    ///
+    /// This is called from the generated mid-end code; counts are only kept
+    /// when the `egraph_rule_stats` setting is enabled.
+    pub(crate) fn rule_fired(&mut self, rule: &'static str) {
+        trace!("rule fired: {}", rule);
+        if self.flags.egraph_rule_stats() {
+            self.stats.rules.record(rule);
+        }
+    }
+
     /// Optimization of a single instruction.
     ///
     /// This does a few things:
@@ -853,4 +866,83 @@ pub(crate) struct Stats {
     pub(crate) elaborate_func_pre_insts: u64,
     pub(crate) elaborate_func_post_insts: u64,
     pub(crate) elaborate_best_cost_fixpoint_iters: u64,
+    pub(crate) rules: RuleStats,
+}
+
+/// The number of times each mid-end ISLE rule fired while optimizing a
+/// function, keyed by the rule's source location.
+///
+/// This is only populated when the `egraph_rule_stats` setting is enabled; see
+/// [`Context::egraph_rule_stats`](crate::Context::egraph_rule_stats).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RuleStats {
+    fired: BTreeMap<&'static str, u64>,
+}
+
+impl RuleStats {
+    fn record(&mut self, rule: &'static str) {
+        *self.fired.entry(rule).or_insert(0) += 1;
+    }
+
+    /// Iterate over the rules which fired, ordered by source location, along
+    /// with the number of times each one fired.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, u64)> + '_ {
+        self.fired.iter().map(|(rule, count)| (*rule, *count))
+    }
+
+    /// The total number of rewrites performed by all rules.
+    pub fn total(&self) -> u64 {
+        self.fired.values().sum()
+    }
+
+    /// Returns `true` if no rule fired.
+    pub fn is_empty(&self) -> bool {
+        self.fired.is_empty()
+    }
+}
+
+impl fmt::Display for RuleStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (rule, count) in self.iter() {
+            writeln!(f, "{count:>6} {rule}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn empty_rule_stats() {
+        let stats = RuleStats::default();
+        assert!(stats.is_empty());
+        assert_eq!(stats.total(), 0);
+        assert_eq!(stats.iter().count(), 0);
+        assert_eq!(stats.to_string(), "");
+    }
+
+    #[test]
+    fn rule_stats_count_each_rule() {
+        let mut stats = RuleStats::default();
+        stats.record("src/opts/remat.isle line 5");
+        stats.record("src/opts/arithmetic.isle line 7");
+        stats.record("src/opts/remat.isle line 5");
+        assert!(!stats.is_empty());
+        assert_eq!(stats.total(), 3);
+        assert_eq!(
+            stats.iter().collect::<Vec<_>>(),
+            [
+                ("src/opts/arithmetic.isle line 7", 1),
+                ("src/opts/remat.isle line 5", 2),
+            ]
+        );
+        assert_eq!(
+            stats.to_string(),
+            "     1 src/opts/arithmetic.isle line 7\n     2 src/opts/remat.isle line 5\n"
+        );
+    }
 }
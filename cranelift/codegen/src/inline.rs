@@ -0,0 +1,573 @@
+//! Function inlining.
+//!
+//! Cranelift compiles one function at a time and has no knowledge of the
+//! bodies of the functions that a function calls, so it can't decide on its
+//! own which calls to inline. Instead, the embedder implements the [`Inline`]
+//! trait, which is consulted for each direct `call` in a function and which
+//! provides the CLIF body of the callee when the call should be inlined.
+//!
+//! Only the calls which are present in the function when the pass starts are
+//! considered: calls within inlined bodies are left alone. Embedders which want
+//! to inline more than one level deep should inline into callees before
+//! handing them out, that is, inline bottom-up.
+
+use crate::cursor::{Cursor, FuncCursor};
+use crate::dominator_tree::DominatorTree;
+use crate::flowgraph::ControlFlowGraph;
+use crate::ir::{
+    self, ArgumentPurpose, Block, BlockCall, ExternalName, FuncRef, Function, GlobalValue,
+    GlobalValueData, Inst, InstBuilder, InstructionData, JumpTableData, Opcode, SigRef, StackSlot,
+    UserStackMapEntry, Value, ValueList,
+};
+use crate::packed_option::PackedOption;
+use crate::{timing, trace};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use cranelift_entity::{EntityRef, PrimaryMap, SecondaryMap};
+use smallvec::SmallVec;
+
+/// A decision-maker for which calls to inline, and a source of callee bodies.
+pub trait Inline {
+    /// Decide whether to inline the direct call `inst` in `caller`, which
+    /// calls `callee`.
+    ///
+    /// To inline the call, return the callee's body. It must have the
+    /// signature that `callee` is called with in `caller`.
+    fn inline(&mut self, caller: &Function, inst: Inst, callee: FuncRef) -> InlineCommand<'_>;
+}
+
+impl<T: Inline + ?Sized> Inline for &mut T {
+    fn inline(&mut self, caller: &Function, inst: Inst, callee: FuncRef) -> InlineCommand<'_> {
+        (**self).inline(caller, inst, callee)
+    }
+}
+
+/// What to do with a call, as decided by an [`Inline`] implementation.
+pub enum InlineCommand<'a> {
+    /// Leave the call as it is.
+    KeepCall,
+
+    /// Replace the call with a copy of the given callee body.
+    Inline(Cow<'a, Function>),
+}
+
+/// Inline the calls in `func` that `inliner` asks for.
+///
+/// Returns whether any call was inlined.
+pub(crate) fn do_inlining(func: &mut Function, mut inliner: impl Inline) -> bool {
+    let _tt = timing::inline();
+
+    let calls: Vec<Inst> = func
+        .layout
+        .blocks()
+        .flat_map(|block| func.layout.block_insts(block))
+        .filter(|&inst| func.dfg.insts[inst].opcode() == Opcode::Call)
+        .collect();
+
+    let mut inlined = false;
+    for inst in calls {
+        let callee = match func.dfg.insts[inst] {
+            InstructionData::Call { func_ref, .. } => func_ref,
+            _ => unreachable!(),
+        };
+        let body = match inliner.inline(func, inst, callee) {
+            InlineCommand::KeepCall => continue,
+            InlineCommand::Inline(body) => body,
+        };
+        if !can_inline(func, inst, &body) {
+            trace!("Not inlining {inst}: {} can't be inlined here", body.name);
+            continue;
+        }
+        trace!("Inlining {} into {inst}", body.name);
+        inline_call(func, inst, &body);
+        inlined = true;
+    }
+    inlined
+}
+
+/// Check that the body of `callee` can replace the call `inst` in `func`.
+fn can_inline(func: &Function, inst: Inst, callee: &Function) -> bool {
+    let sig = &func.dfg.signatures[func.dfg.call_signature(inst).unwrap()];
+    if sig.params.len() != callee.signature.params.len()
+        || sig.returns.len() != callee.signature.returns.len()
+        || sig
+            .params
+            .iter()
+            .zip(&callee.signature.params)
+            .any(|(a, b)| a.value_type != b.value_type)
+        || sig
+            .returns
+            .iter()
+            .zip(&callee.signature.returns)
+            .any(|(a, b)| a.value_type != b.value_type)
+    {
+        return false;
+    }
+
+    // Dynamic types are not copied over.
+    if !callee.dynamic_stack_slots.is_empty() {
+        return false;
+    }
+
+    // These observe the callee's own frame, which no longer exists once it's
+    // inlined.
+    let uses_frame = callee.layout.blocks().any(|block| {
+        callee.layout.block_insts(block).any(|inst| {
+            matches!(
+                callee.dfg.insts[inst].opcode(),
+                Opcode::GetFramePointer | Opcode::GetStackPointer | Opcode::GetReturnAddress
+            )
+        })
+    });
+    if uses_frame {
+        return false;
+    }
+
+    // A `vmctx` global value refers to the function's own `vmctx` parameter,
+    // so in the inlined body it refers to the caller's. That's only the same
+    // value if the caller passes its own `vmctx` along.
+    let uses_vmctx = callee
+        .global_values
+        .values()
+        .any(|gv| matches!(gv, GlobalValueData::VMContext));
+    if uses_vmctx {
+        let passed = callee
+            .signature
+            .special_param_index(ArgumentPurpose::VMContext)
+            .map(|i| func.dfg.resolve_aliases(func.dfg.inst_args(inst)[i]));
+        let own = func
+            .special_param(ArgumentPurpose::VMContext)
+            .map(|v| func.dfg.resolve_aliases(v));
+        if passed.is_none() || passed != own {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Replace the call `inst` in `func` with a copy of the body of `callee`.
+///
+/// The call's block is split after the call: the callee's entry block is
+/// spliced into the first half in place of the call, the callee's other
+/// reachable blocks are placed in between the two halves and its returns
+/// become jumps to the second half, whose parameters take the place of the
+/// call's results.
+fn inline_call(func: &mut Function, inst: Inst, callee: &Function) {
+    let call_block = func.layout.inst_block(inst).unwrap();
+    let after = func.dfg.make_block();
+    let next = func.layout.next_inst(inst).unwrap();
+    func.layout.split_block(after, next);
+    if func.layout.is_cold(call_block) {
+        func.layout.set_cold(after);
+    }
+
+    let results: SmallVec<[Value; 4]> = func.dfg.inst_results(inst).iter().copied().collect();
+    func.dfg.clear_results(inst);
+    for result in results {
+        let ty = func.dfg.value_type(result);
+        let param = func.dfg.append_block_param(after, ty);
+        func.dfg.change_to_alias(result, param);
+    }
+
+    let args: SmallVec<[Value; 8]> = func.dfg.inst_args(inst).iter().copied().collect();
+    let stack_map = func.dfg.take_user_stack_map_entries(inst);
+    func.layout.remove_inst(inst);
+
+    let mut splicer = Splicer::new(func, callee, after, stack_map.as_deref().unwrap_or(&[]));
+
+    let cfg = ControlFlowGraph::with_function(callee);
+    let domtree = DominatorTree::with_function(callee, &cfg);
+    let entry = callee.layout.entry_block().unwrap();
+    for (param, arg) in callee.dfg.block_params(entry).iter().zip(args) {
+        splicer.values[*param] = arg.into();
+    }
+    splicer.blocks[entry] = call_block.into();
+    for block in callee.layout.blocks() {
+        if block == entry || !domtree.is_reachable(block) {
+            continue;
+        }
+        let new_block = splicer.func.dfg.make_block();
+        for param in callee.dfg.block_params(block) {
+            let ty = callee.dfg.value_type(*param);
+            splicer.values[*param] = splicer.func.dfg.append_block_param(new_block, ty).into();
+        }
+        splicer.func.layout.insert_block(new_block, after);
+        if callee.layout.is_cold(block) {
+            splicer.func.layout.set_cold(new_block);
+        }
+        splicer.blocks[block] = new_block.into();
+    }
+
+    // Visiting blocks in reverse post-order means that every value is defined
+    // before it's used, except for block parameters which were created above.
+    for block in domtree.cfg_rpo() {
+        for inst in callee.layout.block_insts(*block) {
+            splicer.copy_inst(inst);
+        }
+    }
+}
+
+/// State for copying the instructions of `callee` into `func`.
+struct Splicer<'a> {
+    func: &'a mut Function,
+    callee: &'a Function,
+
+    /// The block that the callee's returns jump to.
+    after: Block,
+
+    /// The stack map entries of the inlined call, which are live across all
+    /// calls in the inlined body.
+    stack_map: &'a [UserStackMapEntry],
+
+    values: SecondaryMap<Value, PackedOption<Value>>,
+    blocks: SecondaryMap<Block, PackedOption<Block>>,
+    sig_refs: PrimaryMap<SigRef, SigRef>,
+    func_refs: PrimaryMap<FuncRef, FuncRef>,
+    global_values: PrimaryMap<GlobalValue, GlobalValue>,
+    stack_slots: PrimaryMap<StackSlot, StackSlot>,
+}
+
+impl<'a> Splicer<'a> {
+    /// Create a new splicer, copying all of the callee's entities except for
+    /// blocks and values into `func` up front.
+    fn new(
+        func: &'a mut Function,
+        callee: &'a Function,
+        after: Block,
+        stack_map: &'a [UserStackMapEntry],
+    ) -> Self {
+        let sig_refs = callee
+            .dfg
+            .signatures
+            .values()
+            .map(|sig| func.import_signature(sig.clone()))
+            .collect::<PrimaryMap<_, _>>();
+
+        let func_refs = callee
+            .dfg
+            .ext_funcs
+            .values()
+            .map(|data| {
+                let mut data = data.clone();
+                data.signature = sig_refs[data.signature];
+                data.name = import_name(func, callee, &data.name);
+                func.import_function(data)
+            })
+            .collect();
+
+        // Global values may refer to each other in any order, so rely on
+        // them being allocated consecutively.
+        let base = func.global_values.len();
+        let global_values = callee
+            .global_values
+            .values()
+            .map(|data| {
+                let mut data = data.clone();
+                match &mut data {
+                    GlobalValueData::Load { base: gv, .. }
+                    | GlobalValueData::IAddImm { base: gv, .. } => {
+                        *gv = GlobalValue::new(base + gv.index());
+                    }
+                    GlobalValueData::Symbol { name, .. } => {
+                        *name = import_name(func, callee, name);
+                    }
+                    GlobalValueData::VMContext | GlobalValueData::DynScaleTargetConst { .. } => {}
+                }
+                func.create_global_value(data)
+            })
+            .collect();
+
+        let stack_slots = callee
+            .sized_stack_slots
+            .values()
+            .map(|data| func.create_sized_stack_slot(data.clone()))
+            .collect();
+
+        Self {
+            func,
+            callee,
+            after,
+            stack_map,
+            values: SecondaryMap::new(),
+            blocks: SecondaryMap::new(),
+            sig_refs,
+            func_refs,
+            global_values,
+            stack_slots,
+        }
+    }
+
+    fn value(&self, value: Value) -> Value {
+        let value = self.callee.dfg.resolve_aliases(value);
+        self.values[value].expect("value used before it was defined")
+    }
+
+    fn values(&self, values: &[Value]) -> SmallVec<[Value; 8]> {
+        values.iter().map(|v| self.value(*v)).collect()
+    }
+
+    fn block_call(&mut self, call: BlockCall) -> BlockCall {
+        let callee = self.callee;
+        let pool = &callee.dfg.value_lists;
+        let block = self.blocks[call.block(pool)].unwrap();
+        let args = self.values(call.args_slice(pool));
+        BlockCall::new(block, &args, &mut self.func.dfg.value_lists)
+    }
+
+    /// Returns a cursor which appends instructions to `block` in `func`.
+    fn cursor(&mut self, block: Block, srcloc: ir::SourceLoc) -> FuncCursor<'_> {
+        let mut pos = FuncCursor::new(self.func).at_bottom(block);
+        if !srcloc.is_default() {
+            pos.set_srcloc(srcloc);
+        }
+        pos
+    }
+
+    /// Copy `inst` to the end of its block's counterpart in `func`.
+    fn copy_inst(&mut self, inst: Inst) {
+        let callee = self.callee;
+        let after = self.after;
+        let block = self.blocks[callee.layout.inst_block(inst).unwrap()].unwrap();
+        let srcloc = callee.srcloc(inst);
+
+        // Returns continue after the inlined call, and tail calls become
+        // regular calls whose results are returned that way.
+        let new_inst = match callee.dfg.insts[inst] {
+            InstructionData::MultiAry {
+                opcode: Opcode::Return,
+                args,
+            } => {
+                let args = self.values(args.as_slice(&callee.dfg.value_lists));
+                self.cursor(block, srcloc).ins().jump(after, &args);
+                return;
+            }
+            InstructionData::Call {
+                opcode: Opcode::ReturnCall,
+                func_ref,
+                args,
+            } => {
+                let func_ref = self.func_refs[func_ref];
+                let args = self.values(args.as_slice(&callee.dfg.value_lists));
+                self.cursor(block, srcloc).ins().call(func_ref, &args)
+            }
+            InstructionData::CallIndirect {
+                opcode: Opcode::ReturnCallIndirect,
+                sig_ref,
+                args,
+            } => {
+                let sig_ref = self.sig_refs[sig_ref];
+                let args = self.values(args.as_slice(&callee.dfg.value_lists));
+                self.cursor(block, srcloc)
+                    .ins()
+                    .call_indirect(sig_ref, args[0], &args[1..])
+            }
+            mut data => {
+                self.remap_entities(&mut data);
+                let new_inst = self.func.dfg.make_inst(data);
+                self.func
+                    .dfg
+                    .make_inst_results(new_inst, callee.dfg.ctrl_typevar(inst));
+                self.func.layout.append_inst(new_inst, block);
+                if !srcloc.is_default() {
+                    self.func.set_srcloc(new_inst, srcloc);
+                }
+                for (old, new) in callee
+                    .dfg
+                    .inst_results(inst)
+                    .iter()
+                    .zip(self.func.dfg.inst_results(new_inst))
+                {
+                    self.values[*old] = (*new).into();
+                }
+                for entry in callee.dfg.user_stack_map_entries(inst).unwrap_or(&[]) {
+                    let entry = UserStackMapEntry {
+                        slot: self.stack_slots[entry.slot],
+                        ..entry.clone()
+                    };
+                    self.func.dfg.append_user_stack_map_entry(new_inst, entry);
+                }
+                new_inst
+            }
+        };
+
+        if self.func.dfg.insts[new_inst].opcode().is_safepoint() {
+            for entry in self.stack_map {
+                self.func
+                    .dfg
+                    .append_user_stack_map_entry(new_inst, entry.clone());
+            }
+        }
+
+        if callee.dfg.insts[inst].opcode().is_return() {
+            let results = self.func.dfg.inst_results(new_inst).to_vec();
+            self.cursor(block, srcloc).ins().jump(after, &results);
+        }
+    }
+
+    /// Rewrite the values, blocks and entities that `data` refers to into
+    /// their counterparts in `func`.
+    fn remap_entities(&mut self, data: &mut InstructionData) {
+        let callee = self.callee;
+        match data {
+            // Value lists and jump tables live in pools in the function, so
+            // these are copied into `func`'s pools.
+            InstructionData::MultiAry { args, .. } => {
+                let values = args.as_slice(&callee.dfg.value_lists);
+                *args = ValueList::from_slice(values, &mut self.func.dfg.value_lists);
+            }
+            InstructionData::Jump { destination, .. } => {
+                *destination = self.block_call(*destination);
+            }
+            InstructionData::Brif { blocks, .. } => {
+                for block in blocks.iter_mut() {
+                    *block = self.block_call(*block);
+                }
+            }
+            InstructionData::BranchTable { table, .. } => {
+                let old = &callee.dfg.jump_tables[*table];
+                let default = self.block_call(old.default_block());
+                let entries: Vec<BlockCall> =
+                    old.as_slice().iter().map(|b| self.block_call(*b)).collect();
+                *table = self
+                    .func
+                    .create_jump_table(JumpTableData::new(default, &entries));
+            }
+            InstructionData::Call { func_ref, args, .. } => {
+                *func_ref = self.func_refs[*func_ref];
+                let values = args.as_slice(&callee.dfg.value_lists);
+                *args = ValueList::from_slice(values, &mut self.func.dfg.value_lists);
+            }
+            InstructionData::CallIndirect { sig_ref, args, .. } => {
+                *sig_ref = self.sig_refs[*sig_ref];
+                let values = args.as_slice(&callee.dfg.value_lists);
+                *args = ValueList::from_slice(values, &mut self.func.dfg.value_lists);
+            }
+            InstructionData::FuncAddr { func_ref, .. } => {
+                *func_ref = self.func_refs[*func_ref];
+            }
+            InstructionData::UnaryGlobalValue { global_value, .. } => {
+                *global_value = self.global_values[*global_value];
+            }
+            InstructionData::StackLoad { stack_slot, .. }
+            | InstructionData::StackStore { stack_slot, .. } => {
+                *stack_slot = self.stack_slots[*stack_slot];
+            }
+            InstructionData::UnaryConst {
+                constant_handle, ..
+            } => {
+                let constant = callee.dfg.constants.get(*constant_handle).clone();
+                *constant_handle = self.func.dfg.constants.insert(constant);
+            }
+            InstructionData::Shuffle { imm, .. } => {
+                let mask = callee.dfg.immediates[*imm].clone();
+                *imm = self.func.dfg.immediates.push(mask);
+            }
+            _ => {}
+        }
+
+        // Block call arguments were mapped above, which leaves the
+        // instruction's own arguments.
+        let args = self.values(data.arguments(&self.func.dfg.value_lists));
+        data.arguments_mut(&mut self.func.dfg.value_lists)
+            .copy_from_slice(&args);
+    }
+}
+
+/// Copy `name` from `callee` into `func`, re-interning user-defined names.
+fn import_name(func: &mut Function, callee: &Function, name: &ExternalName) -> ExternalName {
+    match name {
+        ExternalName::User(name) => {
+            let name = callee.params.user_named_funcs()[*name].clone();
+            ExternalName::User(func.declare_imported_user_function(name))
+        }
+        _ => name.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::condcodes::IntCC;
+    use crate::ir::types::*;
+    use crate::ir::{AbiParam, ExtFuncData, Signature, UserExternalName, UserFuncName};
+    use crate::isa::CallConv;
+    use crate::settings;
+    use crate::verifier::verify_function;
+
+    struct Callee(Function);
+
+    impl Inline for Callee {
+        fn inline(&mut self, _: &Function, _: Inst, _: FuncRef) -> InlineCommand<'_> {
+            InlineCommand::Inline(Cow::Borrowed(&self.0))
+        }
+    }
+
+    fn signature() -> Signature {
+        let mut sig = Signature::new(CallConv::Fast);
+        sig.params.push(AbiParam::new(I32));
+        sig.returns.push(AbiParam::new(I32));
+        sig
+    }
+
+    #[test]
+    fn inline_branchy_callee() {
+        // The callee computes `x < 0 ? -x : x` with a branch.
+        let mut callee = Function::with_name_signature(
+            UserFuncName::User(UserExternalName::new(0, 1)),
+            signature(),
+        );
+        let block0 = callee.dfg.make_block();
+        let x = callee.dfg.append_block_param(block0, I32);
+        let block1 = callee.dfg.make_block();
+        let block2 = callee.dfg.make_block();
+        let mut cur = FuncCursor::new(&mut callee);
+        cur.insert_block(block0);
+        let zero = cur.ins().iconst(I32, 0);
+        let neg = cur.ins().icmp(IntCC::SignedLessThan, x, zero);
+        cur.ins().brif(neg, block1, &[], block2, &[]);
+        cur.insert_block(block1);
+        let y = cur.ins().ineg(x);
+        cur.ins().return_(&[y]);
+        cur.insert_block(block2);
+        cur.ins().return_(&[x]);
+
+        let mut caller = Function::with_name_signature(
+            UserFuncName::User(UserExternalName::new(0, 0)),
+            signature(),
+        );
+        let sig = caller.import_signature(signature());
+        let name = caller.declare_imported_user_function(UserExternalName::new(0, 1));
+        let func_ref = caller.import_function(ExtFuncData {
+            name: ExternalName::User(name),
+            signature: sig,
+            colocated: true,
+            preserves_vmctx: false,
+        });
+        let block0 = caller.dfg.make_block();
+        let a = caller.dfg.append_block_param(block0, I32);
+        let mut cur = FuncCursor::new(&mut caller);
+        cur.insert_block(block0);
+        let call = cur.ins().call(func_ref, &[a]);
+        let b = cur.func.dfg.first_result(call);
+        let c = cur.ins().iadd(a, b);
+        cur.ins().return_(&[c]);
+
+        assert!(do_inlining(&mut caller, Callee(callee)));
+
+        let flags = settings::Flags::new(settings::builder());
+        verify_function(&caller, &flags).unwrap();
+        let opcodes: Vec<Opcode> = caller
+            .layout
+            .blocks()
+            .flat_map(|block| caller.layout.block_insts(block))
+            .map(|inst| caller.dfg.insts[inst].opcode())
+            .collect();
+        assert!(!opcodes.contains(&Opcode::Call));
+        assert_eq!(
+            opcodes.iter().filter(|op| **op == Opcode::Return).count(),
+            1
+        );
+        assert_eq!(caller.layout.blocks().count(), 4);
+    }
+}
@@ -586,6 +586,14 @@ impl DataFlowGraph {
         assert!(opcode.is_safepoint());
         self.user_stack_maps.entry(inst).or_default().push(entry);
     }
+
+    /// Remove the stack map entries for the given instruction, returning them.
+    pub(crate) fn take_user_stack_map_entries(
+        &mut self,
+        inst: Inst,
+    ) -> Option<UserStackMapEntryVec> {
+        self.user_stack_maps.remove(&inst)
+    }
 }
 
 /// Where did a value come from?
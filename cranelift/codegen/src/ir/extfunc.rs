@@ -313,6 +313,11 @@ pub struct ExtFuncData {
     /// See the documentation for `RelocDistance` for more details. A `colocated` flag value of
     /// `true` implies `RelocDistance::Near`.
     pub colocated: bool,
+    /// Does calling this function leave all memory in the `vmctx` alias region unmodified?
+    ///
+    /// If so, alias analysis may reuse values loaded from that region before a call to this
+    /// function after the call, rather than reloading them. See `AliasRegion::Vmctx`.
+    pub preserves_vmctx: bool,
 }
 
 impl ExtFuncData {
@@ -340,6 +345,9 @@ impl<'a> fmt::Display for DisplayableExtFuncData<'a> {
         if self.ext_func.colocated {
             write!(f, "colocated ")?;
         }
+        if self.ext_func.preserves_vmctx {
+            write!(f, "preserves_vmctx ")?;
+        }
         write!(
             f,
             "{} {}",
@@ -77,6 +77,7 @@ use crate::ir::types::*;
 use crate::isa::TargetIsa;
 use crate::machinst::{BlockIndex, LowerBackend, VCode};
 use crate::trace;
+use alloc::vec::Vec;
 use regalloc2::Function as _;
 use std::fmt;
 
@@ -750,12 +751,47 @@ pub enum InequalityKind {
     Loose,
 }
 
+/// An inequality `lhs >= rhs` (if loose) or `lhs > rhs` (if strict)
+/// between the values described by two facts, which is known to hold
+/// at some program point, e.g. on one side of a conditional branch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Inequality {
+    /// The greater side of the inequality.
+    pub lhs: Fact,
+    /// The lesser side of the inequality.
+    pub rhs: Fact,
+    /// Whether the inequality admits equality.
+    pub kind: InequalityKind,
+}
+
+impl Inequality {
+    /// The inequality established by the comparison `lhs cc rhs`
+    /// being true, if `cc` is an unsigned ordering.
+    pub fn from_unsigned_cmp(cc: ir::condcodes::IntCC, lhs: &Fact, rhs: &Fact) -> Option<Self> {
+        use ir::condcodes::IntCC;
+        let (lhs, rhs, kind) = match cc {
+            IntCC::UnsignedGreaterThanOrEqual => (lhs, rhs, InequalityKind::Loose),
+            IntCC::UnsignedGreaterThan => (lhs, rhs, InequalityKind::Strict),
+            IntCC::UnsignedLessThanOrEqual => (rhs, lhs, InequalityKind::Loose),
+            IntCC::UnsignedLessThan => (rhs, lhs, InequalityKind::Strict),
+            _ => return None,
+        };
+        Some(Inequality {
+            lhs: lhs.clone(),
+            rhs: rhs.clone(),
+            kind,
+        })
+    }
+}
+
 /// A "context" in which we can evaluate and derive facts. This
 /// context carries environment/global properties, such as the machine
-/// pointer width.
+/// pointer width, and any inequalities known to hold at the point
+/// being checked.
 pub struct FactContext<'a> {
     function: &'a ir::Function,
     pointer_width: u16,
+    inequalities: &'a [Inequality],
 }
 
 impl<'a> FactContext<'a> {
@@ -764,9 +800,30 @@ impl<'a> FactContext<'a> {
         FactContext {
             function,
             pointer_width,
+            inequalities: &[],
         }
     }
 
+    /// Create a copy of this context in which `inequalities` are
+    /// known to hold, e.g. because the point being checked is
+    /// dominated by a bounds-check branch.
+    pub fn with_inequalities<'b>(&'b self, inequalities: &'b [Inequality]) -> FactContext<'b> {
+        FactContext {
+            function: self.function,
+            pointer_width: self.pointer_width,
+            inequalities,
+        }
+    }
+
+    /// Compute the facts, other than `fact` itself, which can be
+    /// derived from `fact` with one of the known inequalities.
+    fn refinements<'b>(&'b self, fact: &'b Fact) -> impl Iterator<Item = Fact> + 'b {
+        self.inequalities.iter().filter_map(move |ineq| {
+            let refined = self.apply_inequality(fact, &ineq.lhs, &ineq.rhs, ineq.kind);
+            (refined != *fact).then_some(refined)
+        })
+    }
+
     /// Computes whether `lhs` "subsumes" (implies) `rhs`.
     pub fn subsumes(&self, lhs: &Fact, rhs: &Fact) -> bool {
         match (lhs, rhs) {
@@ -885,7 +942,12 @@ impl<'a> FactContext<'a> {
             (None, None) => true,
             (Some(_), None) => true,
             (None, Some(_)) => false,
-            (Some(lhs), Some(rhs)) => self.subsumes(lhs, rhs),
+            (Some(lhs), Some(rhs)) => {
+                self.subsumes(lhs, rhs)
+                    || self
+                        .refinements(lhs)
+                        .any(|refined| self.subsumes(&refined, rhs))
+            }
         }
     }
 
@@ -1333,6 +1395,24 @@ impl<'a> FactContext<'a> {
     /// that this address accesses, if known, or `None` if the range
     /// doesn't constrain the access to exactly one location.
     fn check_address(&self, fact: &Fact, size: u32) -> PccResult<Option<(ir::MemoryType, u64)>> {
+        let result = self.check_address_fact(fact, size);
+        if result.is_err() {
+            // The address may still be known to be in bounds given a
+            // dominating bounds check.
+            for refined in self.refinements(fact) {
+                if let Ok(refined_result) = self.check_address_fact(&refined, size) {
+                    return Ok(refined_result);
+                }
+            }
+        }
+        result
+    }
+
+    fn check_address_fact(
+        &self,
+        fact: &Fact,
+        size: u32,
+    ) -> PccResult<Option<(ir::MemoryType, u64)>> {
         trace!("check_address: fact {:?} size {}", fact, size);
         match fact {
             Fact::Mem {
@@ -1647,11 +1727,16 @@ pub fn check_vcode_facts<B: LowerBackend + TargetIsa>(
 ) -> PccResult<()> {
     let ctx = FactContext::new(f, backend.triple().pointer_width().unwrap().bits().into());
 
+    // The flow state at the start of each block whose only predecessor
+    // has already been checked, which carries over what that
+    // predecessor's terminating branch established.
+    let mut entry_states: Vec<Option<B::FactFlowState>> = vec![None; vcode.num_blocks()];
+
     // Check that individual instructions are valid according to input
     // facts, and support the stated output facts.
     for block in 0..vcode.num_blocks() {
         let block = BlockIndex::new(block);
-        let mut flow_state = B::FactFlowState::default();
+        let mut flow_state = entry_states[block.index()].take().unwrap_or_default();
         for inst in vcode.block_insns(block).iter() {
             // Check any output facts on this inst.
             if let Err(e) = backend.check_fact(&ctx, vcode, inst, &mut flow_state) {
@@ -1674,6 +1759,10 @@ pub fn check_vcode_facts<B: LowerBackend + TargetIsa>(
                             return Err(PccError::UnsupportedBlockparam);
                         }
                     }
+                    if vcode.block_preds(*succ).len() == 1 {
+                        entry_states[succ.index()] =
+                            Some(backend.fact_flow_state_for_succ(&flow_state, *succ));
+                    }
                 }
             }
         }
@@ -141,5 +141,13 @@ impl LowerBackend for AArch64Backend {
         pcc::check(ctx, vcode, inst, state)
     }
 
+    fn fact_flow_state_for_succ(
+        &self,
+        state: &pcc::FactFlowState,
+        succ: BlockIndex,
+    ) -> pcc::FactFlowState {
+        pcc::succ_state(state, succ)
+    }
+
     type FactFlowState = pcc::FactFlowState;
 }
@@ -1,17 +1,19 @@
 //! Proof-carrying code checking for AArch64 VCode.
 
+use crate::ir::condcodes::IntCC;
 use crate::ir::pcc::*;
 use crate::ir::types::*;
 use crate::ir::MemFlags;
-use crate::isa::aarch64::inst::args::{Cond, PairAMode, ShiftOp};
+use crate::isa::aarch64::inst::args::{Cond, CondBrKind, PairAMode, ShiftOp};
 use crate::isa::aarch64::inst::regs::zero_reg;
 use crate::isa::aarch64::inst::Inst;
 use crate::isa::aarch64::inst::{ALUOp, MoveWideOp};
 use crate::isa::aarch64::inst::{AMode, ExtendOp};
 use crate::machinst::pcc::*;
 use crate::machinst::Reg;
-use crate::machinst::{InsnIndex, VCode};
+use crate::machinst::{BlockIndex, InsnIndex, MachLabel, VCode};
 use crate::trace;
+use alloc::vec::Vec;
 
 fn extend_fact(ctx: &FactContext, value: &Fact, mode: ExtendOp) -> Option<Fact> {
     match mode {
@@ -26,10 +28,46 @@ fn extend_fact(ctx: &FactContext, value: &Fact, mode: ExtendOp) -> Option<Fact>
     }
 }
 
+/// The inequality between the operands of a compare which holds when
+/// `cond` is true, if `cond` is an unsigned ordering.
+fn cond_inequality(cond: Cond, lhs: &Fact, rhs: &Fact) -> Option<Inequality> {
+    let cc = match cond {
+        Cond::Hs => IntCC::UnsignedGreaterThanOrEqual,
+        Cond::Hi => IntCC::UnsignedGreaterThan,
+        Cond::Lo => IntCC::UnsignedLessThan,
+        Cond::Ls => IntCC::UnsignedLessThanOrEqual,
+        _ => return None,
+    };
+    Inequality::from_unsigned_cmp(cc, lhs, rhs)
+}
+
 /// Flow-state between facts.
 #[derive(Clone, Debug, Default)]
 pub struct FactFlowState {
     cmp_flags: Option<(Fact, Fact)>,
+    /// Inequalities known to hold for the rest of the block.
+    inequalities: Vec<Inequality>,
+    /// Inequalities known to hold on entry to a successor of the
+    /// block's terminating conditional branch.
+    succ_inequalities: Vec<(MachLabel, Inequality)>,
+}
+
+/// Compute the flow state at the start of `succ`, whose only
+/// predecessor ends in the branch last checked with `state`.
+pub(crate) fn succ_state(state: &FactFlowState, succ: BlockIndex) -> FactFlowState {
+    let label = MachLabel::from_block(succ);
+    let mut inequalities = state.inequalities.clone();
+    inequalities.extend(
+        state
+            .succ_inequalities
+            .iter()
+            .filter(|(target, _)| *target == label)
+            .map(|(_, ineq)| ineq.clone()),
+    );
+    FactFlowState {
+        inequalities,
+        ..FactFlowState::default()
+    }
 }
 
 pub(crate) fn check(
@@ -48,6 +86,10 @@ pub(crate) fn check(
     let cmp_flags = state.cmp_flags.take();
     trace!(" * with cmp_flags = {cmp_flags:?}");
 
+    // Inequalities established by earlier conditional traps in this
+    // block, or by the branch into it, hold for every access below.
+    let ctx = &ctx.with_inequalities(&state.inequalities);
+
     match *inst {
         Inst::Args { .. } => {
             // Defs on the args have "axiomatic facts": we trust the
@@ -72,9 +114,21 @@ pub(crate) fn check(
             let access_ty = inst.mem_type().unwrap();
             check_load(ctx, None, flags, mem, vcode, access_ty)
         }
-        Inst::LoadP64 { ref mem, flags, .. } => check_load_pair(ctx, flags, mem, vcode, 16),
-        Inst::FpuLoadP64 { ref mem, flags, .. } => check_load_pair(ctx, flags, mem, vcode, 16),
-        Inst::FpuLoadP128 { ref mem, flags, .. } => check_load_pair(ctx, flags, mem, vcode, 32),
+        Inst::LoadP64 {
+            rt,
+            rt2,
+            ref mem,
+            flags,
+        } => {
+            let rts = Some((rt.to_reg(), rt2.to_reg()));
+            check_load_pair(ctx, rts, flags, mem, vcode, I64)
+        }
+        Inst::FpuLoadP64 { ref mem, flags, .. } => {
+            check_load_pair(ctx, None, flags, mem, vcode, F64)
+        }
+        Inst::FpuLoadP128 { ref mem, flags, .. } => {
+            check_load_pair(ctx, None, flags, mem, vcode, I8X16)
+        }
         Inst::VecLoadReplicate {
             rn, flags, size, ..
         } => check_load_addr(ctx, flags, rn, vcode, size.lane_size().ty()),
@@ -99,9 +153,18 @@ pub(crate) fn check(
             let access_ty = inst.mem_type().unwrap();
             check_store(ctx, None, flags, mem, vcode, access_ty)
         }
-        Inst::StoreP64 { ref mem, flags, .. } => check_store_pair(ctx, flags, mem, vcode, 16),
-        Inst::FpuStoreP64 { ref mem, flags, .. } => check_store_pair(ctx, flags, mem, vcode, 16),
-        Inst::FpuStoreP128 { ref mem, flags, .. } => check_store_pair(ctx, flags, mem, vcode, 32),
+        Inst::StoreP64 {
+            rt,
+            rt2,
+            ref mem,
+            flags,
+        } => check_store_pair(ctx, Some((rt, rt2)), flags, mem, vcode, I64),
+        Inst::FpuStoreP64 { ref mem, flags, .. } => {
+            check_store_pair(ctx, None, flags, mem, vcode, F64)
+        }
+        Inst::FpuStoreP128 { ref mem, flags, .. } => {
+            check_store_pair(ctx, None, flags, mem, vcode, I8X16)
+        }
         Inst::StoreRelease {
             access_ty,
             rn,
@@ -318,7 +381,9 @@ pub(crate) fn check(
         }
 
         Inst::CSel { rd, cond, rn, rm }
-            if (cond == Cond::Hs || cond == Cond::Hi) && cmp_flags.is_some() =>
+            if cmp_flags
+                .as_ref()
+                .is_some_and(|(lhs, rhs)| cond_inequality(cond, lhs, rhs).is_some()) =>
         {
             let (cmp_lhs, cmp_rhs) = cmp_flags.unwrap();
             trace!("CSel: cmp {cond:?} ({cmp_lhs:?}, {cmp_rhs:?})");
@@ -338,28 +403,51 @@ pub(crate) fn check(
                 // the false side we know the inequality is strict, so we
                 // can offset by one.
 
-                // True side: lhs >= rhs (Hs) or lhs > rhs (Hi).
+                // True side: e.g. lhs >= rhs (Hs) or lhs > rhs (Hi).
+                let if_true = cond_inequality(cond, &cmp_lhs, &cmp_rhs).unwrap();
                 let rn = get_fact_or_default(vcode, rn, 64);
-                let lhs_kind = match cond {
-                    Cond::Hs => InequalityKind::Loose,
-                    Cond::Hi => InequalityKind::Strict,
-                    _ => unreachable!(),
-                };
-                let rn = ctx.apply_inequality(&rn, &cmp_lhs, &cmp_rhs, lhs_kind);
-                // false side: rhs < lhs (Hs) or rhs <= lhs (Hi).
+                let rn = ctx.apply_inequality(&rn, &if_true.lhs, &if_true.rhs, if_true.kind);
+                // False side: e.g. rhs > lhs (Hs) or rhs >= lhs (Hi).
+                let if_false = cond_inequality(cond.invert(), &cmp_lhs, &cmp_rhs).unwrap();
                 let rm = get_fact_or_default(vcode, rm, 64);
-                let rhs_kind = match cond {
-                    Cond::Hs => InequalityKind::Strict,
-                    Cond::Hi => InequalityKind::Loose,
-                    _ => unreachable!(),
-                };
-                let rm = ctx.apply_inequality(&rm, &cmp_rhs, &cmp_lhs, rhs_kind);
+                let rm = ctx.apply_inequality(&rm, &if_false.lhs, &if_false.rhs, if_false.kind);
                 let union = ctx.union(&rn, &rm);
                 // Union the two facts.
                 clamp_range(ctx, 64, 64, union)
             })
         }
 
+        Inst::TrapIf {
+            kind: CondBrKind::Cond(cond),
+            ..
+        } => {
+            // Execution only continues past the trap if the condition
+            // is false.
+            if let Some((lhs, rhs)) = cmp_flags {
+                state
+                    .inequalities
+                    .extend(cond_inequality(cond.invert(), &lhs, &rhs));
+            }
+            Ok(())
+        }
+
+        Inst::CondBr {
+            taken,
+            not_taken,
+            kind: CondBrKind::Cond(cond),
+        } => {
+            if let Some((lhs, rhs)) = cmp_flags {
+                for (target, cond) in [(taken, cond), (not_taken, cond.invert())] {
+                    if let (Some(label), Some(ineq)) =
+                        (target.as_label(), cond_inequality(cond, &lhs, &rhs))
+                    {
+                        state.succ_inequalities.push((label, ineq));
+                    }
+                }
+            }
+            Ok(())
+        }
+
         _ if vcode.inst_defines_facts(inst_idx) => Err(PccError::UnsupportedFact),
 
         _ => Ok(()),
@@ -520,23 +608,62 @@ fn check_addr<'a>(
 }
 
 fn check_load_pair(
-    _ctx: &FactContext,
-    _flags: MemFlags,
-    _addr: &PairAMode,
-    _vcode: &VCode<Inst>,
-    _size: u8,
+    ctx: &FactContext,
+    rts: Option<(Reg, Reg)>,
+    flags: MemFlags,
+    addr: &PairAMode,
+    vcode: &VCode<Inst>,
+    ty: Type,
 ) -> PccResult<()> {
-    Err(PccError::UnimplementedInst)
+    let (rt, rt2) = rts.unzip();
+    let (first, second) = match pair_addrs(addr, ty) {
+        Some(addrs) => addrs,
+        None => return Ok(()),
+    };
+    check_load(ctx, rt, flags, &first, vcode, ty)?;
+    check_load(ctx, rt2, flags, &second, vcode, ty)
 }
 
 fn check_store_pair(
-    _ctx: &FactContext,
-    _flags: MemFlags,
-    _addr: &PairAMode,
-    _vcode: &VCode<Inst>,
-    _size: u8,
+    ctx: &FactContext,
+    rts: Option<(Reg, Reg)>,
+    flags: MemFlags,
+    addr: &PairAMode,
+    vcode: &VCode<Inst>,
+    ty: Type,
 ) -> PccResult<()> {
-    Err(PccError::UnimplementedInst)
+    let (rt, rt2) = rts.unzip();
+    let (first, second) = match pair_addrs(addr, ty) {
+        Some(addrs) => addrs,
+        None => return Ok(()),
+    };
+    check_store(ctx, rt, flags, &first, vcode, ty)?;
+    check_store(ctx, rt2, flags, &second, vcode, ty)
+}
+
+/// Splits a load or store of a pair of `ty` values into the addresses of the
+/// two individual accesses it performs, or returns `None` if the address is
+/// trusted.
+fn pair_addrs(addr: &PairAMode, ty: Type) -> Option<(AMode, AMode)> {
+    match *addr {
+        PairAMode::SignedOffset { reg, simm7 } => {
+            // As with `UImm12Scaled`, the immediate stores the already-scaled
+            // byte offset.
+            let off = i64::from(simm7.value);
+            Some((
+                AMode::RegOffset { rn: reg, off },
+                AMode::RegOffset {
+                    rn: reg,
+                    off: off + i64::from(ty.bytes()),
+                },
+            ))
+        }
+        PairAMode::SPPreIndexed { .. } | PairAMode::SPPostIndexed { .. } => {
+            // We trust ABI code (for now!), as for the equivalent `AMode`s
+            // in `check_addr`.
+            None
+        }
+    }
 }
 
 fn check_load_addr(
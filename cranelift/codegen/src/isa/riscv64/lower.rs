@@ -1,6 +1,8 @@
 //! Lowering rules for Riscv64.
+use crate::ir::pcc::{FactContext, PccResult};
 use crate::ir::Inst as IRInst;
 use crate::isa::riscv64::inst::*;
+use crate::isa::riscv64::pcc;
 use crate::isa::riscv64::Riscv64Backend;
 use crate::machinst::lower::*;
 use crate::machinst::*;
@@ -31,5 +33,15 @@ impl LowerBackend for Riscv64Backend {
         None
     }
 
+    fn check_fact(
+        &self,
+        ctx: &FactContext<'_>,
+        vcode: &mut VCode<Self::MInst>,
+        inst: InsnIndex,
+        _state: &mut Self::FactFlowState,
+    ) -> PccResult<()> {
+        pcc::check(ctx, vcode, inst)
+    }
+
     type FactFlowState = ();
 }
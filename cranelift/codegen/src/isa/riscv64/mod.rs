@@ -19,6 +19,7 @@ use target_lexicon::{Architecture, Triple};
 mod abi;
 pub(crate) mod inst;
 mod lower;
+mod pcc;
 mod settings;
 #[cfg(feature = "unwind")]
 use crate::isa::unwind::systemv;
@@ -0,0 +1,413 @@
+//! Proof-carrying code checking for riscv64 VCode.
+
+use crate::ir::pcc::*;
+use crate::ir::types::*;
+use crate::ir::MemFlags;
+use crate::isa::riscv64::inst::{
+    zero_reg, AMode, AluOPRRI, AluOPRRR, Imm12, Inst, LoadOP, StoreOP,
+};
+use crate::machinst::pcc::*;
+use crate::machinst::{InsnIndex, Reg, VCode};
+use crate::trace;
+
+pub(crate) fn check(
+    ctx: &FactContext,
+    vcode: &mut VCode<Inst>,
+    inst_idx: InsnIndex,
+) -> PccResult<()> {
+    trace!("Checking facts on inst: {:?}", vcode[inst_idx]);
+
+    match vcode[inst_idx] {
+        Inst::Nop0 | Inst::Nop4 => Ok(()),
+
+        Inst::Args { .. } => {
+            // Defs on the args have "axiomatic facts": we trust the
+            // ABI code to pass through the values unharmed, so the
+            // facts given to us in the CLIF should still be true.
+            Ok(())
+        }
+
+        Inst::Load {
+            rd,
+            op,
+            flags,
+            ref from,
+        } => {
+            let ty = load_op_ty(op);
+            let rd = ty.is_int().then(|| rd.to_reg());
+            check_load(ctx, rd, flags, from, vcode, ty)
+        }
+        Inst::Store {
+            ref to,
+            op,
+            flags,
+            src,
+        } => {
+            let ty = store_op_ty(op);
+            let src = ty.is_int().then_some(src);
+            check_store(ctx, src, flags, to, vcode, ty)
+        }
+        Inst::VecLoad { flags, .. } | Inst::VecStore { flags, .. } if flags.checked() => {
+            Err(PccError::UnimplementedInst)
+        }
+
+        Inst::AluRRR {
+            alu_op: AluOPRRR::Add,
+            rd,
+            rs1,
+            rs2,
+        } => check_binop(ctx, vcode, 64, rd, rs1, rs2, |rs1, rs2| {
+            clamp_range(ctx, 64, 64, ctx.add(rs1, rs2, 64))
+        }),
+        Inst::AluRRR {
+            alu_op: AluOPRRR::Sub,
+            rd,
+            rs1,
+            rs2,
+        } => check_binop(ctx, vcode, 64, rd, rs1, rs2, |rs1, rs2| {
+            if let Some(k) = rs2.as_const(64) {
+                clamp_range(ctx, 64, 64, ctx.offset(rs1, 64, -(k as i64)))
+            } else {
+                clamp_range(ctx, 64, 64, None)
+            }
+        }),
+        // Zba's `add.uw` zero-extends the low 32 bits of `rs1` before adding,
+        // and `zext.w` is `add.uw` with the zero register.
+        Inst::AluRRR {
+            alu_op: AluOPRRR::Adduw,
+            rd,
+            rs1,
+            rs2,
+        } if rs2 == zero_reg() => check_unop(ctx, vcode, 64, rd, rs1, |rs1| {
+            clamp_range(ctx, 64, 64, ctx.uextend(rs1, 32, 64))
+        }),
+        Inst::AluRRR {
+            alu_op: AluOPRRR::Adduw,
+            rd,
+            rs1,
+            rs2,
+        } => check_binop(ctx, vcode, 64, rd, rs1, rs2, |rs1, rs2| {
+            let rs1 = fail_if_missing(ctx.uextend(rs1, 32, 64))?;
+            clamp_range(ctx, 64, 64, ctx.add(&rs1, rs2, 64))
+        }),
+        Inst::AluRRR {
+            alu_op:
+                alu_op @ (AluOPRRR::Sh1add
+                | AluOPRRR::Sh2add
+                | AluOPRRR::Sh3add
+                | AluOPRRR::Sh1adduw
+                | AluOPRRR::Sh2adduw
+                | AluOPRRR::Sh3adduw),
+            rd,
+            rs1,
+            rs2,
+        } if has_fact(vcode, rs1) && has_fact(vcode, rs2) => {
+            check_binop(ctx, vcode, 64, rd, rs1, rs2, |rs1, rs2| {
+                let (amount, uw) = match alu_op {
+                    AluOPRRR::Sh1add => (1, false),
+                    AluOPRRR::Sh2add => (2, false),
+                    AluOPRRR::Sh3add => (3, false),
+                    AluOPRRR::Sh1adduw => (1, true),
+                    AluOPRRR::Sh2adduw => (2, true),
+                    AluOPRRR::Sh3adduw => (3, true),
+                    _ => unreachable!(),
+                };
+                let rs1 = if uw {
+                    fail_if_missing(ctx.uextend(rs1, 32, 64))?
+                } else {
+                    rs1.clone()
+                };
+                let rs1_shifted = fail_if_missing(ctx.shl(&rs1, 64, amount))?;
+                clamp_range(ctx, 64, 64, ctx.add(&rs1_shifted, rs2, 64))
+            })
+        }
+
+        Inst::AluRRImm12 {
+            alu_op: AluOPRRI::Addi,
+            rd,
+            rs,
+            imm12,
+        } if rs == zero_reg() => {
+            // `li` of a small constant.
+            let constant = i64::from(imm12.as_i16()) as u64;
+            check_constant(ctx, vcode, rd, 64, constant)
+        }
+        Inst::AluRRImm12 {
+            alu_op: AluOPRRI::Addi,
+            rd,
+            rs,
+            imm12,
+        } => check_unop(ctx, vcode, 64, rd, rs, |rs| {
+            clamp_range(ctx, 64, 64, ctx.offset(rs, 64, imm12.as_i16().into()))
+        }),
+        Inst::AluRRImm12 {
+            alu_op: AluOPRRI::Slli,
+            rd,
+            rs,
+            imm12,
+        } if has_fact(vcode, rs) => check_unop(ctx, vcode, 64, rd, rs, |rs| {
+            clamp_range(ctx, 64, 64, shl_fact(ctx, rs, shift_amount(imm12)))
+        }),
+        Inst::AluRRImm12 {
+            alu_op: AluOPRRI::Srli,
+            rd,
+            rs,
+            imm12,
+        } => check_unop(ctx, vcode, 64, rd, rs, |rs| {
+            clamp_range(ctx, 64, 64, ushr_fact(rs, shift_amount(imm12)))
+        }),
+        Inst::AluRRImm12 {
+            alu_op: AluOPRRI::Andi,
+            rd,
+            rs,
+            imm12,
+        } if imm12.as_i16() >= 0 => check_unop(ctx, vcode, 64, rd, rs, |rs| {
+            clamp_range(ctx, 64, 64, and_fact(rs, imm12.as_i16() as u64))
+        }),
+
+        Inst::AluRRR { rd, .. } | Inst::AluRRImm12 { rd, .. } => {
+            check_output(ctx, vcode, rd, &[], |_vcode| clamp_range(ctx, 64, 64, None))
+        }
+
+        Inst::Extend {
+            rd,
+            rn,
+            signed: false,
+            from_bits,
+            to_bits,
+        } if has_fact(vcode, rn) => check_unop(ctx, vcode, 64, rd, rn, |rn| {
+            clamp_range(
+                ctx,
+                64,
+                to_bits.into(),
+                ctx.uextend(rn, from_bits.into(), to_bits.into()),
+            )
+        }),
+        Inst::Extend {
+            rd,
+            from_bits,
+            to_bits,
+            ..
+        } => check_output(ctx, vcode, rd, &[], |_vcode| {
+            clamp_range(ctx, to_bits.into(), from_bits.into(), None)
+        }),
+
+        Inst::Lui { rd, imm } => {
+            let constant = (i64::from(imm.as_i32()) << 12) as u64;
+            check_constant(ctx, vcode, rd, 64, constant)
+        }
+        Inst::LoadInlineConst { rd, imm, .. } => check_constant(ctx, vcode, rd, 64, imm),
+
+        Inst::Mov { rd, rm, ty } if ty.is_int() => {
+            check_unop(ctx, vcode, 64, rd, rm, |rm| Ok(Some(rm.clone())))
+        }
+
+        _ if vcode.inst_defines_facts(inst_idx) => Err(PccError::UnsupportedFact),
+
+        _ => Ok(()),
+    }
+}
+
+fn load_op_ty(op: LoadOP) -> Type {
+    match op {
+        LoadOP::Lb | LoadOP::Lbu => I8,
+        LoadOP::Lh | LoadOP::Lhu => I16,
+        LoadOP::Lw | LoadOP::Lwu => I32,
+        LoadOP::Ld => I64,
+        LoadOP::Flh => F16,
+        LoadOP::Flw => F32,
+        LoadOP::Fld => F64,
+    }
+}
+
+fn store_op_ty(op: StoreOP) -> Type {
+    match op {
+        StoreOP::Sb => I8,
+        StoreOP::Sh => I16,
+        StoreOP::Sw => I32,
+        StoreOP::Sd => I64,
+        StoreOP::Fsh => F16,
+        StoreOP::Fsw => F32,
+        StoreOP::Fsd => F64,
+    }
+}
+
+fn shift_amount(imm12: Imm12) -> u16 {
+    // Shift amounts are masked to the low six bits by the hardware.
+    (imm12.as_i16() & 0x3f) as u16
+}
+
+/// Computes the fact for a 64-bit left shift.
+///
+/// Values narrower than 64 bits are kept in registers with undefined upper
+/// bits, so a fact about a narrower value only says something about the
+/// result when all of those upper bits are shifted out.
+fn shl_fact(ctx: &FactContext, fact: &Fact, amount: u16) -> Option<Fact> {
+    match *fact {
+        Fact::Range {
+            bit_width,
+            min,
+            max,
+        } if bit_width == 64 || bit_width + amount == 64 => {
+            if max.leading_zeros() < u32::from(amount) {
+                return None;
+            }
+            Some(Fact::Range {
+                bit_width: 64,
+                min: min << amount,
+                max: max << amount,
+            })
+        }
+        _ if amount < 32 => ctx.shl(fact, 64, amount),
+        _ => None,
+    }
+}
+
+/// Computes the fact for a 64-bit logical right shift.
+fn ushr_fact(fact: &Fact, amount: u16) -> Option<Fact> {
+    match *fact {
+        Fact::Range {
+            bit_width: 64,
+            min,
+            max,
+        } => Some(Fact::Range {
+            bit_width: 64,
+            min: min >> amount,
+            max: max >> amount,
+        }),
+        // Whatever the input, the result fits in the bits not shifted in. This
+        // is what covers zero-extensions lowered as `slli` then `srli`.
+        _ => Some(Fact::Range {
+            bit_width: 64,
+            min: 0,
+            max: u64::MAX >> amount,
+        }),
+    }
+}
+
+/// Computes the fact for a bitwise and with a non-negative immediate `mask`.
+fn and_fact(fact: &Fact, mask: u64) -> Option<Fact> {
+    match *fact {
+        // Masking exactly the width of the value, as when zero-extending an
+        // `i8`, or masking a value which already fits, leaves it unchanged.
+        Fact::Range {
+            bit_width,
+            min,
+            max,
+        } if (bit_width < 64 && mask == (1 << bit_width) - 1)
+            || (bit_width == 64 && max <= mask && (mask + 1).is_power_of_two()) =>
+        {
+            Some(Fact::Range {
+                bit_width: 64,
+                min,
+                max,
+            })
+        }
+        _ => Some(Fact::Range {
+            bit_width: 64,
+            min: 0,
+            max: mask,
+        }),
+    }
+}
+
+fn check_load(
+    ctx: &FactContext,
+    rd: Option<Reg>,
+    flags: MemFlags,
+    addr: &AMode,
+    vcode: &VCode<Inst>,
+    ty: Type,
+) -> PccResult<()> {
+    let result_fact = rd.and_then(|rd| vcode.vreg_fact(rd.into()));
+    let bits = u16::try_from(ty.bits()).unwrap();
+    check_addr(
+        ctx,
+        flags,
+        addr,
+        vcode,
+        ty,
+        LoadOrStore::Load {
+            result_fact,
+            from_bits: bits,
+            to_bits: bits,
+        },
+    )
+}
+
+fn check_store(
+    ctx: &FactContext,
+    src: Option<Reg>,
+    flags: MemFlags,
+    addr: &AMode,
+    vcode: &VCode<Inst>,
+    ty: Type,
+) -> PccResult<()> {
+    let stored_fact = src.and_then(|src| vcode.vreg_fact(src.into()));
+    check_addr(
+        ctx,
+        flags,
+        addr,
+        vcode,
+        ty,
+        LoadOrStore::Store { stored_fact },
+    )
+}
+
+fn check_addr<'a>(
+    ctx: &FactContext,
+    flags: MemFlags,
+    addr: &AMode,
+    vcode: &VCode<Inst>,
+    ty: Type,
+    op: LoadOrStore<'a>,
+) -> PccResult<()> {
+    if !flags.checked() {
+        return Ok(());
+    }
+
+    trace!("check_addr: {:?}", addr);
+
+    let check = |addr: &Fact, ty: Type| -> PccResult<()> {
+        match op {
+            LoadOrStore::Load {
+                result_fact,
+                from_bits,
+                to_bits,
+            } => {
+                let loaded_fact =
+                    clamp_range(ctx, to_bits, from_bits, ctx.load(addr, ty)?.cloned())?;
+                trace!(
+                    "checking a load: loaded_fact = {loaded_fact:?} result_fact = {result_fact:?}"
+                );
+                if ctx.subsumes_fact_optionals(loaded_fact.as_ref(), result_fact) {
+                    Ok(())
+                } else {
+                    Err(PccError::UnsupportedFact)
+                }
+            }
+            LoadOrStore::Store { stored_fact } => ctx.store(addr, ty, stored_fact),
+        }
+    };
+
+    match *addr {
+        AMode::RegOffset(rn, off) => {
+            let rn = get_fact_or_default(vcode, rn, 64);
+            let sum = fail_if_missing(ctx.offset(&rn, 64, off))?;
+            check(&sum, ty)
+        }
+        AMode::Label(..) | AMode::Const(..) => {
+            // Always accept: labels and constants must be within the
+            // generated code (else they won't be resolved).
+            Ok(())
+        }
+        AMode::SPOffset(..)
+        | AMode::FPOffset(..)
+        | AMode::SlotOffset(..)
+        | AMode::IncomingArg(..) => {
+            // We trust ABI code (for now!) and no lowering rules
+            // lower input value accesses directly to these.
+            Ok(())
+        }
+    }
+}
@@ -54,6 +54,25 @@ fn emit_reloc(sink: &mut MachBuffer<Inst>, kind: Reloc, name: &ExternalName, add
     sink.add_reloc(kind, name, addend);
 }
 
+/// Emits an instruction with the accumulator (`al`, `ax`, `eax` or `rax`) as its
+/// register operand and an immediate as its other operand, using the short
+/// encoding which has no ModRM byte.
+fn emit_accumulator_imm(
+    sink: &mut MachBuffer<Inst>,
+    prefix: LegacyPrefixes,
+    size: OperandSize,
+    opcode: u8,
+    simm32: u32,
+) {
+    prefix.emit(sink);
+    if size == OperandSize::Size64 {
+        // REX.W, and `rax` needs no other REX bits.
+        sink.put1(0x48);
+    }
+    sink.put1(opcode);
+    emit_simm(sink, size.to_bytes(), simm32);
+}
+
 /// The top-level emit function.
 ///
 /// Important!  Do not add improved (shortened) encoding cases to existing
@@ -204,6 +223,23 @@ pub(crate) fn emit(
                     emit_std_reg_mem(sink, prefix, opcode_m, 1, reg_g, &amode, rex, 0);
                 }
 
+                RegMemImm::Imm { simm32 }
+                    if info.prefer_smaller_encodings()
+                        && int_reg_enc(reg_g) == regs::ENC_RAX
+                        && (*size == OperandSize::Size8
+                            || !low8_will_sign_extend_to_32(simm32)) =>
+                {
+                    // The accumulator form saves the ModRM byte, which makes it
+                    // shorter than the forms below unless the immediate fits in
+                    // a sign-extended byte.
+                    let opcode = if *size == OperandSize::Size8 {
+                        (subopcode_i << 3) | 0x04
+                    } else {
+                        (subopcode_i << 3) | 0x05
+                    };
+                    emit_accumulator_imm(sink, prefix, *size, opcode, simm32);
+                }
+
                 RegMemImm::Imm { simm32 } => {
                     let imm_size = if *size == OperandSize::Size8 {
                         1
@@ -1237,6 +1273,24 @@ pub(crate) fn emit(
                     emit_std_enc_enc(sink, prefix, opcode, 1, subopcode, enc_dst, rex_flags);
                 }
 
+                &Imm8Reg::Imm8 { imm: 1 } if info.prefer_smaller_encodings() => {
+                    let (opcode, prefix) = match size {
+                        OperandSize::Size8 => (0xD0, LegacyPrefixes::None),
+                        OperandSize::Size16 => (0xD1, LegacyPrefixes::_66),
+                        OperandSize::Size32 => (0xD1, LegacyPrefixes::None),
+                        OperandSize::Size64 => (0xD1, LegacyPrefixes::None),
+                    };
+
+                    // SHL/SHR/SAR $1, reg8 is (REX.W==0) D0 /subopcode
+                    // SHL/SHR/SAR $1, reg16 is 66 (REX.W==0) D1 /subopcode
+                    // SHL/SHR/SAR $1, reg32 is (REX.W==0) D1 /subopcode
+                    // SHL/SHR/SAR $1, reg64 is (REX.W==1) D1 /subopcode
+                    //
+                    // These only differ from the forms below in how they set the
+                    // overflow flag, which we never read after a shift.
+                    emit_std_enc_enc(sink, prefix, opcode, 1, subopcode, enc_dst, rex_flags);
+                }
+
                 &Imm8Reg::Imm8 { imm: num_bits } => {
                     let (opcode, prefix) = match size {
                         OperandSize::Size8 => (0xC0, LegacyPrefixes::None),
@@ -1249,8 +1303,6 @@ pub(crate) fn emit(
                     // SHL/SHR/SAR $ib, reg16 is 66 (REX.W==0) C1 /subopcode
                     // SHL/SHR/SAR $ib, reg32 is (REX.W==0) C1 /subopcode ib
                     // SHL/SHR/SAR $ib, reg64 is (REX.W==1) C1 /subopcode ib
-                    // When the shift amount is 1, there's an even shorter encoding, but we don't
-                    // bother with that nicety here.
                     emit_std_enc_enc(sink, prefix, opcode, 1, subopcode, enc_dst, rex_flags);
                     sink.put1(num_bits);
                 }
@@ -1363,9 +1415,29 @@ pub(crate) fn emit(
                     emit_std_reg_mem(sink, prefix, opcode, 1, reg_g, addr, rex, 0);
                 }
 
+                RegMemImm::Imm { simm32 }
+                    if info.prefer_smaller_encodings()
+                        && int_reg_enc(reg_g) == regs::ENC_RAX
+                        && (!is_cmp
+                            || *size == OperandSize::Size8
+                            || !low8_will_sign_extend_to_32(simm32)) =>
+                {
+                    // As with `AluRmiR`, the accumulator forms are shorter unless
+                    // a `cmp` immediate fits in a sign-extended byte. `test` has
+                    // no sign-extended byte form, so it is always shorter.
+                    let opcode = match (*size, is_cmp) {
+                        (OperandSize::Size8, true) => 0x3C,
+                        (_, true) => 0x3D,
+                        (OperandSize::Size8, false) => 0xA8,
+                        (_, false) => 0xA9,
+                    };
+                    emit_accumulator_imm(sink, prefix, *size, opcode, simm32);
+                }
+
                 RegMemImm::Imm { simm32 } => {
-                    // FIXME JRS 2020Feb11: there are shorter encodings for
-                    // cmp $imm, rax/eax/ax/al.
+                    // There are shorter encodings for `cmp $imm, rax/eax/ax/al`
+                    // and `test $imm, rax/eax/ax/al` above, which are only used
+                    // when optimizing for size.
                     let use_imm8 = is_cmp && low8_will_sign_extend_to_32(simm32);
 
                     // And also here we use the "normal" G-E ordering.
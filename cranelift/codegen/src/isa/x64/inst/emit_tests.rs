@@ -5374,3 +5374,192 @@ fn test_x64_emit() {
         assert_eq!(expected_encoding, actual_encoding, "{expected_printing}");
     }
 }
+
+#[test]
+fn test_x64_emit_smaller_encodings() {
+    let rax = regs::rax();
+    let rbx = regs::rbx();
+    let rcx = regs::rcx();
+    let r8 = regs::r8();
+
+    let w_rax = Writable::<Reg>::from_reg(rax);
+    let w_rbx = Writable::<Reg>::from_reg(rbx);
+    let w_rcx = Writable::<Reg>::from_reg(rcx);
+    let w_r8 = Writable::<Reg>::from_reg(r8);
+
+    let mut insns = Vec::<(Inst, &str, &str)>::new();
+
+    // Accumulator forms of ALU instructions, unless the immediate fits in a
+    // sign-extended byte.
+    insns.push((
+        Inst::alu_rmi_r(
+            OperandSize::Size64,
+            AluRmiROpcode::Add,
+            RegMemImm::imm(76543210),
+            w_rax,
+        ),
+        "4805EAF48F04",
+        "addq    %rax, $76543210, %rax",
+    ));
+    insns.push((
+        Inst::alu_rmi_r(
+            OperandSize::Size64,
+            AluRmiROpcode::Add,
+            RegMemImm::imm(-127i32 as u32),
+            w_rax,
+        ),
+        "4883C081",
+        "addq    %rax, $-127, %rax",
+    ));
+    insns.push((
+        Inst::alu_rmi_r(
+            OperandSize::Size32,
+            AluRmiROpcode::And,
+            RegMemImm::imm(65536),
+            w_rax,
+        ),
+        "2500000100",
+        "andl    %eax, $65536, %eax",
+    ));
+    insns.push((
+        Inst::alu_rmi_r(
+            OperandSize::Size16,
+            AluRmiROpcode::Sub,
+            RegMemImm::imm(1000),
+            w_rax,
+        ),
+        "662DE803",
+        "subw    %ax, $1000, %ax",
+    ));
+    insns.push((
+        Inst::alu_rmi_r(
+            OperandSize::Size8,
+            AluRmiROpcode::Xor,
+            RegMemImm::imm(10),
+            w_rax,
+        ),
+        "340A",
+        "xorb    %al, $10, %al",
+    ));
+    insns.push((
+        Inst::alu_rmi_r(
+            OperandSize::Size64,
+            AluRmiROpcode::Add,
+            RegMemImm::imm(76543210),
+            w_rcx,
+        ),
+        "4881C1EAF48F04",
+        "addq    %rcx, $76543210, %rcx",
+    ));
+
+    // Accumulator forms of `cmp` and `test`.
+    insns.push((
+        Inst::cmp_rmi_r(OperandSize::Size64, rax, RegMemImm::imm(76543210)),
+        "483DEAF48F04",
+        "cmpq    $76543210, %rax",
+    ));
+    insns.push((
+        Inst::cmp_rmi_r(OperandSize::Size32, rax, RegMemImm::imm(1)),
+        "83F801",
+        "cmpl    $1, %eax",
+    ));
+    insns.push((
+        Inst::cmp_rmi_r(OperandSize::Size8, rax, RegMemImm::imm(1)),
+        "3C01",
+        "cmpb    $1, %al",
+    ));
+    insns.push((
+        Inst::CmpRmiR {
+            size: OperandSize::Size32,
+            opcode: CmpOpcode::Test,
+            src1: Gpr::unwrap_new(rax),
+            src2: GprMemImm::unwrap_new(RegMemImm::imm(256)),
+        },
+        "A900010000",
+        "testl   $256, %eax",
+    ));
+    insns.push((
+        Inst::CmpRmiR {
+            size: OperandSize::Size8,
+            opcode: CmpOpcode::Test,
+            src1: Gpr::unwrap_new(rax),
+            src2: GprMemImm::unwrap_new(RegMemImm::imm(1)),
+        },
+        "A801",
+        "testb   $1, %al",
+    ));
+
+    // Shifts by one.
+    insns.push((
+        Inst::shift_r(
+            OperandSize::Size32,
+            ShiftKind::ShiftLeft,
+            Imm8Gpr::unwrap_new(Imm8Reg::Imm8 { imm: 1 }),
+            r8,
+            w_r8,
+        ),
+        "41D1E0",
+        "shll    $1, %r8d, %r8d",
+    ));
+    insns.push((
+        Inst::shift_r(
+            OperandSize::Size64,
+            ShiftKind::ShiftRightArithmetic,
+            Imm8Gpr::unwrap_new(Imm8Reg::Imm8 { imm: 1 }),
+            rbx,
+            w_rbx,
+        ),
+        "48D1FB",
+        "sarq    $1, %rbx, %rbx",
+    ));
+    insns.push((
+        Inst::shift_r(
+            OperandSize::Size8,
+            ShiftKind::ShiftRightLogical,
+            Imm8Gpr::unwrap_new(Imm8Reg::Imm8 { imm: 1 }),
+            rcx,
+            w_rcx,
+        ),
+        "D0E9",
+        "shrb    $1, %cl, %cl",
+    ));
+    insns.push((
+        Inst::shift_r(
+            OperandSize::Size64,
+            ShiftKind::ShiftLeft,
+            Imm8Gpr::unwrap_new(Imm8Reg::Imm8 { imm: 2 }),
+            r8,
+            w_r8,
+        ),
+        "49C1E002",
+        "shlq    $2, %r8, %r8",
+    ));
+
+    // ========================================================
+    // Actually run the tests!
+    let ctrl_plane = &mut Default::default();
+    let constants = Default::default();
+    use crate::settings::Configurable;
+    let mut flag_builder = settings::builder();
+    flag_builder.set("opt_level", "speed_and_size").unwrap();
+    let flags = settings::Flags::new(flag_builder);
+    let mut isa_flag_builder = x64::settings::builder();
+    isa_flag_builder.enable("prefer_smaller_encodings").unwrap();
+    let isa_flags = x64::settings::Flags::new(&flags, &isa_flag_builder);
+
+    let emit_info = EmitInfo::new(flags, isa_flags);
+    for (insn, expected_encoding, expected_printing) in insns {
+        let actual_printing = insn.pretty_print_inst(&mut Default::default());
+        assert_eq!(expected_printing, actual_printing);
+        let mut buffer = MachBuffer::new();
+
+        insn.emit(&mut buffer, &emit_info, &mut Default::default());
+
+        let label = buffer.get_label();
+        buffer.bind_label(label, ctrl_plane);
+
+        let buffer = buffer.finish(&constants, ctrl_plane);
+        let actual_encoding = &buffer.stringify_code_bytes();
+        assert_eq!(expected_encoding, actual_encoding, "{expected_printing}");
+    }
+}
@@ -2911,6 +2911,13 @@ impl EmitInfo {
     pub fn new(flags: settings::Flags, isa_flags: x64_settings::Flags) -> Self {
         Self { flags, isa_flags }
     }
+
+    /// Whether to emit instructions with their shortest available encodings,
+    /// rather than the default ones.
+    pub(super) fn prefer_smaller_encodings(&self) -> bool {
+        self.isa_flags.prefer_smaller_encodings()
+            && self.flags.opt_level() == settings::OptLevel::SpeedAndSize
+    }
 }
 
 impl MachInstEmit for Inst {
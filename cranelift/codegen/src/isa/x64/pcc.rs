@@ -1,5 +1,6 @@
 //! Proof-carrying-code validation for x64 VCode.
 
+use crate::ir::condcodes::IntCC;
 use crate::ir::pcc::*;
 use crate::ir::types::*;
 use crate::isa::x64::args::AvxOpcode;
@@ -9,9 +10,10 @@ use crate::isa::x64::inst::args::{
 };
 use crate::isa::x64::inst::Inst;
 use crate::machinst::pcc::*;
-use crate::machinst::{InsnIndex, VCode, VCodeConstantData};
+use crate::machinst::{BlockIndex, InsnIndex, MachLabel, VCode, VCodeConstantData};
 use crate::machinst::{Reg, Writable};
 use crate::trace;
+use alloc::vec::Vec;
 
 fn undefined_result(
     ctx: &FactContext,
@@ -33,10 +35,46 @@ fn ensure_no_fact(vcode: &VCode<Inst>, reg: Reg) -> PccResult<()> {
     }
 }
 
+/// The inequality between the operands of a compare which holds when
+/// `cc` is true, if `cc` is an unsigned ordering.
+fn cc_inequality(cc: CC, lhs: &Fact, rhs: &Fact) -> Option<Inequality> {
+    let cc = match cc {
+        CC::NB => IntCC::UnsignedGreaterThanOrEqual,
+        CC::NBE => IntCC::UnsignedGreaterThan,
+        CC::B => IntCC::UnsignedLessThan,
+        CC::BE => IntCC::UnsignedLessThanOrEqual,
+        _ => return None,
+    };
+    Inequality::from_unsigned_cmp(cc, lhs, rhs)
+}
+
 /// Flow-state between facts.
 #[derive(Clone, Debug, Default)]
 pub(crate) struct FactFlowState {
     cmp_flags: Option<(Fact, Fact)>,
+    /// Inequalities known to hold for the rest of the block.
+    inequalities: Vec<Inequality>,
+    /// Inequalities known to hold on entry to a successor of the
+    /// block's terminating conditional branch.
+    succ_inequalities: Vec<(MachLabel, Inequality)>,
+}
+
+/// Compute the flow state at the start of `succ`, whose only
+/// predecessor ends in the branch last checked with `state`.
+pub(crate) fn succ_state(state: &FactFlowState, succ: BlockIndex) -> FactFlowState {
+    let label = MachLabel::from_block(succ);
+    let mut inequalities = state.inequalities.clone();
+    inequalities.extend(
+        state
+            .succ_inequalities
+            .iter()
+            .filter(|(target, _)| *target == label)
+            .map(|(_, ineq)| ineq.clone()),
+    );
+    FactFlowState {
+        inequalities,
+        ..FactFlowState::default()
+    }
 }
 
 pub(crate) fn check(
@@ -53,6 +91,10 @@ pub(crate) fn check(
     // remain.
     let cmp_flags = state.cmp_flags.take();
 
+    // Inequalities established by earlier conditional traps in this
+    // block, or by the branch into it, hold for every access below.
+    let ctx = &ctx.with_inequalities(&state.inequalities);
+
     match vcode[inst_idx] {
         Inst::Nop { .. } => Ok(()),
 
@@ -502,27 +544,28 @@ pub(crate) fn check(
                 check_load(ctx, None, addr, vcode, size.to_type(), 64)?;
                 Ok(())
             }
-            RegMem::Reg { reg } if (cc == CC::NB || cc == CC::NBE) && cmp_flags.is_some() => {
+            RegMem::Reg { reg }
+                if cmp_flags
+                    .as_ref()
+                    .is_some_and(|(lhs, rhs)| cc_inequality(cc, lhs, rhs).is_some()) =>
+            {
                 let (cmp_lhs, cmp_rhs) = cmp_flags.unwrap();
                 trace!("lhs = {:?} rhs = {:?}", cmp_lhs, cmp_rhs);
                 let reg = *reg;
                 check_output(ctx, vcode, dst.to_writable_reg(), &[], |vcode| {
                     // See comments in aarch64::pcc CSel for more details on this.
+                    let if_true = cc_inequality(cc, &cmp_lhs, &cmp_rhs).unwrap();
                     let in_true = get_fact_or_default(vcode, reg, 64);
-                    let in_true_kind = match cc {
-                        CC::NB => InequalityKind::Loose,
-                        CC::NBE => InequalityKind::Strict,
-                        _ => unreachable!(),
-                    };
-                    let in_true = ctx.apply_inequality(&in_true, &cmp_lhs, &cmp_rhs, in_true_kind);
+                    let in_true =
+                        ctx.apply_inequality(&in_true, &if_true.lhs, &if_true.rhs, if_true.kind);
+                    let if_false = cc_inequality(cc.invert(), &cmp_lhs, &cmp_rhs).unwrap();
                     let in_false = get_fact_or_default(vcode, alternative.to_reg(), 64);
-                    let in_false_kind = match cc {
-                        CC::NB => InequalityKind::Strict,
-                        CC::NBE => InequalityKind::Loose,
-                        _ => unreachable!(),
-                    };
-                    let in_false =
-                        ctx.apply_inequality(&in_false, &cmp_rhs, &cmp_lhs, in_false_kind);
+                    let in_false = ctx.apply_inequality(
+                        &in_false,
+                        &if_false.lhs,
+                        &if_false.rhs,
+                        if_false.kind,
+                    );
                     let union = ctx.union(&in_true, &in_false);
                     clamp_range(ctx, 64, 64, union)
                 })
@@ -841,14 +884,38 @@ pub(crate) fn check(
         | Inst::JmpKnown { .. }
         | Inst::Ret { .. }
         | Inst::JmpIf { .. }
-        | Inst::JmpCond { .. }
-        | Inst::TrapIf { .. }
         | Inst::TrapIfAnd { .. }
         | Inst::TrapIfOr { .. }
         | Inst::Hlt {}
         | Inst::Ud2 { .. } => Ok(()),
         Inst::Rets { .. } => Ok(()),
 
+        Inst::TrapIf { cc, .. } => {
+            // Execution only continues past the trap if the condition
+            // is false.
+            if let Some((lhs, rhs)) = cmp_flags {
+                state
+                    .inequalities
+                    .extend(cc_inequality(cc.invert(), &lhs, &rhs));
+            }
+            Ok(())
+        }
+
+        Inst::JmpCond {
+            cc,
+            taken,
+            not_taken,
+        } => {
+            if let Some((lhs, rhs)) = cmp_flags {
+                for (target, cc) in [(taken, cc), (not_taken, cc.invert())] {
+                    if let Some(ineq) = cc_inequality(cc, &lhs, &rhs) {
+                        state.succ_inequalities.push((target, ineq));
+                    }
+                }
+            }
+            Ok(())
+        }
+
         Inst::ReturnCallUnknown { .. } => Ok(()),
 
         Inst::CallUnknown { ref info } => match <&RegMem>::from(&info.dest) {
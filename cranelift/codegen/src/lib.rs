@@ -26,6 +26,7 @@ use hashbrown::{hash_map, HashMap};
 use std::collections::{hash_map, HashMap};
 
 pub use crate::context::Context;
+pub use crate::egraph::RuleStats;
 pub use crate::value_label::{LabelValueLoc, ValueLabelsRanges, ValueLocRange};
 pub use crate::verifier::verify_function;
 pub use crate::write::write_function;
@@ -47,6 +48,7 @@ pub mod data_value;
 pub mod dbg;
 pub mod dominator_tree;
 pub mod flowgraph;
+pub mod inline;
 pub mod ir;
 pub mod isa;
 pub mod loop_analysis;
@@ -72,6 +74,7 @@ mod alias_analysis;
 mod constant_hash;
 mod context;
 mod ctxhash;
+mod divconst;
 mod egraph;
 mod inst_predicates;
 mod isle_prelude;
@@ -79,6 +82,7 @@ mod legalizer;
 mod nan_canonicalization;
 mod opts;
 mod ranges;
+mod redundant_checks;
 mod remove_constant_phis;
 mod result;
 mod scoped_hash_map;
@@ -164,6 +164,17 @@ pub trait LowerBackend {
     ) -> PccResult<()> {
         Err(PccError::UnimplementedBackend)
     }
+
+    /// Compute the flow state at the start of `succ`, given the flow
+    /// state after checking the branch which ends its only
+    /// predecessor. By default nothing carries over.
+    fn fact_flow_state_for_succ(
+        &self,
+        _state: &Self::FactFlowState,
+        _succ: BlockIndex,
+    ) -> Self::FactFlowState {
+        Self::FactFlowState::default()
+    }
 }
 
 /// Machine-independent lowering driver / machine-instruction container. Maintains a correspondence
@@ -229,6 +229,10 @@ impl<'a, 'b, 'c> generated_code::Context for IsleContext<'a, 'b, 'c> {
         }
     }
 
+    fn rule_fired(&mut self, rule: &'static str) {
+        self.ctx.rule_fired(rule);
+    }
+
     fn remat(&mut self, value: Value) -> Value {
         trace!("remat: {}", value);
         self.ctx.remat_values.insert(value);
@@ -0,0 +1,427 @@
+//! Redundant check elimination.
+//!
+//! This pass removes conditional traps which can never trap because their
+//! condition is implied by a dominating conditional trap, or by the ranges of
+//! the values involved, and turns `uadd_overflow_trap`s which can never
+//! overflow into plain `iadd`s.
+//!
+//! The motivating example is WebAssembly linear memory bounds checks. An
+//! unrolled loop accessing `p[3]`, `p[2]`, `p[1]`, and `p[0]` emits one check
+//! per access against the memory's bound, but once the check that `p + 3` is
+//! in bounds has been passed the remaining checks can never trap.
+//!
+//! Every check is normalized to the form "trap if `x + a > y + b`", where `x`
+//! and `y` are SSA values, `y` is optional, and `a` and `b` are constants.
+//! This is only possible when the additions involved are known not to wrap,
+//! which is determined with a small value-range analysis: for example a
+//! `uextend` of an `i32` is always less than `2^32`, so adding a small constant
+//! to it in `i64` can't overflow. Once a check has been passed the fact
+//! `x - y <= b - a` holds at every program point it dominates, and any later
+//! check of the same `x` and `y` whose right-hand side `b - a` is at least as
+//! large is redundant.
+//!
+//! Note that `select_spectre_guard`s are never removed: they exist precisely
+//! because a dominating check may be mispredicted. This pass therefore only
+//! removes heap bounds checks when Spectre mitigations for heap accesses are
+//! disabled, although it still removes the `uadd_overflow_trap`s which
+//! compute the end of a Spectre-guarded access. It's disabled by default.
+
+use crate::dominator_tree::{DominatorTree, DominatorTreePreorder};
+use crate::ir::condcodes::{CondCode, IntCC};
+use crate::ir::{Function, Inst, InstructionData, Opcode, Type, Value, ValueDef};
+use crate::{timing, trace};
+use alloc::vec::Vec;
+use rustc_hash::FxHashMap;
+
+/// How many definitions deep to look when analyzing a value, to bound compile
+/// time on long chains of arithmetic.
+const MAX_DEPTH: u32 = 4;
+
+/// A value decomposed as `base + offset`, where the addition doesn't wrap.
+#[derive(Clone, Copy, Debug)]
+struct Affine {
+    base: Option<Value>,
+    offset: i128,
+}
+
+impl Affine {
+    fn add(self, other: Affine) -> Option<Affine> {
+        let base = match (self.base, other.base) {
+            (Some(_), Some(_)) => return None,
+            (base, None) | (None, base) => base,
+        };
+        Some(Affine {
+            base,
+            offset: self.offset + other.offset,
+        })
+    }
+}
+
+/// The key of a fact `x - y <= d`, or `x <= d` if `y` is `None`.
+type FactKey = (Value, Option<Value>);
+
+/// Facts established by the checks dominating the current program point.
+///
+/// Facts are only ever tightened, and all changes are recorded in an undo log
+/// so that they can be rolled back when leaving a subtree of the dominator
+/// tree.
+#[derive(Default)]
+struct Facts {
+    bounds: FxHashMap<FactKey, i128>,
+    undo: Vec<(FactKey, Option<i128>)>,
+}
+
+impl Facts {
+    fn get(&self, key: FactKey) -> Option<i128> {
+        self.bounds.get(&key).copied()
+    }
+
+    fn insert(&mut self, key: FactKey, bound: i128) {
+        let prev = self.get(key);
+        if prev.is_some_and(|prev| prev <= bound) {
+            return;
+        }
+        self.undo.push((key, prev));
+        self.bounds.insert(key, bound);
+    }
+
+    fn mark(&self) -> usize {
+        self.undo.len()
+    }
+
+    fn rollback(&mut self, mark: usize) {
+        while self.undo.len() > mark {
+            let (key, prev) = self.undo.pop().unwrap();
+            match prev {
+                Some(prev) => self.bounds.insert(key, prev),
+                None => self.bounds.remove(&key),
+            };
+        }
+    }
+}
+
+/// Remove redundant conditional traps and overflow checks from `func`.
+pub fn do_remove_redundant_checks(func: &mut Function, domtree: &DominatorTree) {
+    let _tt = timing::remove_redundant_checks();
+    let mut preorder = DominatorTreePreorder::new();
+    preorder.compute(domtree);
+
+    let mut facts = Facts::default();
+    let mut stack = Vec::new();
+    let entry = match func.layout.entry_block() {
+        Some(block) => block,
+        None => return,
+    };
+    stack.push(Some(entry));
+    let mut marks = Vec::new();
+    let mut to_remove = Vec::new();
+
+    while let Some(entry) = stack.pop() {
+        let block = match entry {
+            Some(block) => block,
+            None => {
+                facts.rollback(marks.pop().unwrap());
+                continue;
+            }
+        };
+        marks.push(facts.mark());
+        stack.push(None);
+
+        let mut next = func.layout.first_inst(block);
+        while let Some(inst) = next {
+            next = func.layout.next_inst(inst);
+            visit_inst(func, inst, &mut facts, &mut to_remove);
+        }
+
+        let children = stack.len();
+        stack.extend(preorder.children(block).map(Some));
+        stack[children..].reverse();
+    }
+
+    for inst in to_remove {
+        trace!("removing redundant check {}", func.dfg.display_inst(inst));
+        func.layout.remove_inst(inst);
+    }
+}
+
+fn visit_inst(func: &mut Function, inst: Inst, facts: &mut Facts, to_remove: &mut Vec<Inst>) {
+    match func.dfg.insts[inst] {
+        InstructionData::CondTrap { opcode, arg, .. } => {
+            let Some((lhs, rhs)) = normalize_trap(func, opcode, arg) else {
+                return;
+            };
+            if never_traps(func, facts, lhs, rhs) {
+                to_remove.push(inst);
+                return;
+            }
+
+            // Having passed this check, `lhs <= rhs` from here on out.
+            if let Some(x) = lhs.base {
+                let bound = rhs.offset - lhs.offset;
+                facts.insert((x, rhs.base), bound);
+                if let Some(y) = rhs.base {
+                    let (_, y_max) = range(func, y, MAX_DEPTH);
+                    facts.insert((x, None), bound + y_max);
+                }
+            }
+        }
+
+        InstructionData::IntAddTrap {
+            opcode: Opcode::UaddOverflowTrap,
+            args: [a, b],
+            ..
+        } => {
+            let ty = func.dfg.value_type(a);
+            if !is_small_int(ty) {
+                return;
+            }
+            let max = type_max(ty);
+            let a_max = upper_bound(func, facts, a);
+            let b_max = upper_bound(func, facts, b);
+            if a_max + b_max <= max {
+                trace!(
+                    "removing redundant overflow check {}",
+                    func.dfg.display_inst(inst)
+                );
+                func.dfg.replace(inst).iadd(a, b);
+                return;
+            }
+
+            // Having passed this check, `a + b` doesn't exceed the maximum of
+            // its type.
+            if let Some(sum) = decompose(func, a, MAX_DEPTH).add(decompose(func, b, MAX_DEPTH)) {
+                if let Some(x) = sum.base {
+                    facts.insert((x, None), max - sum.offset);
+                }
+            }
+        }
+
+        _ => {}
+    }
+}
+
+/// Normalizes the trap `opcode` of the condition `arg` into a pair of values
+/// `(lhs, rhs)` such that the instruction traps if `lhs > rhs`.
+fn normalize_trap(func: &Function, opcode: Opcode, arg: Value) -> Option<(Affine, Affine)> {
+    let negate = match opcode {
+        Opcode::Trapnz => false,
+        Opcode::Trapz => true,
+        _ => return None,
+    };
+    let ValueDef::Result(cmp, 0) = func.dfg.value_def(arg) else {
+        return None;
+    };
+    let InstructionData::IntCompare {
+        opcode: Opcode::Icmp,
+        args: [a, b],
+        cond,
+    } = func.dfg.insts[cmp]
+    else {
+        return None;
+    };
+    if !is_small_int(func.dfg.value_type(a)) {
+        return None;
+    }
+    let cond = if negate { cond.complement() } else { cond };
+
+    // Note that `a >= b` is the same as `a + 1 > b` since neither side can
+    // wrap once decomposed.
+    let (lhs, rhs, extra) = match cond {
+        IntCC::UnsignedGreaterThan => (a, b, 0),
+        IntCC::UnsignedGreaterThanOrEqual => (a, b, 1),
+        IntCC::UnsignedLessThan => (b, a, 0),
+        IntCC::UnsignedLessThanOrEqual => (b, a, 1),
+        _ => return None,
+    };
+    let mut lhs = decompose(func, lhs, MAX_DEPTH);
+    lhs.offset += extra;
+    Some((lhs, decompose(func, rhs, MAX_DEPTH)))
+}
+
+/// Returns whether a check which traps if `lhs > rhs` can never trap.
+fn never_traps(func: &Function, facts: &Facts, lhs: Affine, rhs: Affine) -> bool {
+    let bound = rhs.offset - lhs.offset;
+    match (lhs.base, rhs.base) {
+        // Traps if `x > y + bound`.
+        (Some(x), y) => {
+            if facts.get((x, y)).is_some_and(|known| known <= bound) {
+                return true;
+            }
+            let (_, x_max) = range(func, x, MAX_DEPTH);
+            match y {
+                Some(y) => {
+                    let (y_min, _) = range(func, y, MAX_DEPTH);
+                    x_max <= y_min + bound
+                }
+                None => x_max <= bound,
+            }
+        }
+
+        // Traps if `y < -bound`.
+        (None, Some(y)) => {
+            let (y_min, _) = range(func, y, MAX_DEPTH);
+            y_min >= -bound
+        }
+
+        // Constant conditions are left to the egraph pass.
+        (None, None) => false,
+    }
+}
+
+/// Returns an upper bound of `v`, using both its range and the facts known
+/// about it.
+fn upper_bound(func: &Function, facts: &Facts, v: Value) -> i128 {
+    let (_, mut max) = range(func, v, MAX_DEPTH);
+    let affine = decompose(func, v, MAX_DEPTH);
+    if let Some(x) = affine.base {
+        if let Some(known) = facts.get((x, None)) {
+            max = max.min(known + affine.offset);
+        }
+    }
+    max
+}
+
+/// Decomposes `v` into a base value plus a constant offset, looking through
+/// additions and subtractions of constants that are known not to wrap.
+fn decompose(func: &Function, v: Value, depth: u32) -> Affine {
+    let whole = Affine {
+        base: Some(v),
+        offset: 0,
+    };
+    if depth == 0 || !is_small_int(func.dfg.value_type(v)) {
+        return whole;
+    }
+    let ValueDef::Result(inst, 0) = func.dfg.value_def(v) else {
+        return whole;
+    };
+    let max = type_max(func.dfg.value_type(v));
+    let decomposed = match func.dfg.insts[inst] {
+        InstructionData::UnaryImm {
+            opcode: Opcode::Iconst,
+            imm,
+        } => Some(Affine {
+            base: None,
+            offset: i128::from(imm.bits() as u64) & max,
+        }),
+
+        // Zero-extension doesn't change the value.
+        InstructionData::Unary {
+            opcode: Opcode::Uextend,
+            arg,
+        } => Some(decompose(func, arg, depth - 1)),
+
+        InstructionData::Binary {
+            opcode: Opcode::Iadd,
+            args: [a, b],
+        } if range(func, a, depth - 1).1 + range(func, b, depth - 1).1 <= max => {
+            decompose(func, a, depth - 1).add(decompose(func, b, depth - 1))
+        }
+
+        // This traps rather than wrapping.
+        InstructionData::IntAddTrap {
+            opcode: Opcode::UaddOverflowTrap,
+            args: [a, b],
+            ..
+        } => decompose(func, a, depth - 1).add(decompose(func, b, depth - 1)),
+
+        InstructionData::Binary {
+            opcode: Opcode::Isub,
+            args: [a, b],
+        } if range(func, a, depth - 1).0 >= range(func, b, depth - 1).1 => {
+            match decompose(func, b, depth - 1) {
+                Affine { base: None, offset } => {
+                    let a = decompose(func, a, depth - 1);
+                    Some(Affine {
+                        base: a.base,
+                        offset: a.offset - offset,
+                    })
+                }
+                _ => None,
+            }
+        }
+
+        _ => None,
+    };
+    decomposed.unwrap_or(whole)
+}
+
+/// Computes the inclusive range `(min, max)` of the unsigned value `v`.
+fn range(func: &Function, v: Value, depth: u32) -> (i128, i128) {
+    let ty = func.dfg.value_type(v);
+    if !is_small_int(ty) {
+        return (0, i128::MAX);
+    }
+    let max = type_max(ty);
+    let full = (0, max);
+    if depth == 0 {
+        return full;
+    }
+    let ValueDef::Result(inst, 0) = func.dfg.value_def(v) else {
+        return full;
+    };
+    match func.dfg.insts[inst] {
+        InstructionData::UnaryImm {
+            opcode: Opcode::Iconst,
+            imm,
+        } => {
+            let k = i128::from(imm.bits() as u64) & max;
+            (k, k)
+        }
+        InstructionData::Unary {
+            opcode: Opcode::Uextend,
+            arg,
+        } => range(func, arg, depth - 1),
+        InstructionData::Binary {
+            opcode: Opcode::Band,
+            args: [a, b],
+        } => {
+            let (_, a_max) = range(func, a, depth - 1);
+            let (_, b_max) = range(func, b, depth - 1);
+            (0, a_max.min(b_max))
+        }
+        InstructionData::Binary {
+            opcode: Opcode::Ushr,
+            args: [a, b],
+        } => match range(func, b, depth - 1) {
+            (shift, shift_max) if shift == shift_max => {
+                let shift = (shift as u32) % ty.bits();
+                let (a_min, a_max) = range(func, a, depth - 1);
+                (a_min >> shift, a_max >> shift)
+            }
+            _ => full,
+        },
+        InstructionData::Binary {
+            opcode: Opcode::Iadd,
+            args: [a, b],
+        } => {
+            let (a_min, a_max) = range(func, a, depth - 1);
+            let (b_min, b_max) = range(func, b, depth - 1);
+            if a_max + b_max <= max {
+                (a_min + b_min, a_max + b_max)
+            } else {
+                full
+            }
+        }
+        InstructionData::IntAddTrap {
+            opcode: Opcode::UaddOverflowTrap,
+            args: [a, b],
+            ..
+        } => {
+            let (a_min, a_max) = range(func, a, depth - 1);
+            let (b_min, b_max) = range(func, b, depth - 1);
+            ((a_min + b_min).min(max), (a_max + b_max).min(max))
+        }
+        _ => full,
+    }
+}
+
+/// Returns whether `ty` is a scalar integer type small enough for its values
+/// and their sums to be represented in an `i128`.
+fn is_small_int(ty: Type) -> bool {
+    ty.is_int() && ty.bits() <= 64
+}
+
+/// Returns the maximum unsigned value of the integer type `ty`.
+fn type_max(ty: Type) -> i128 {
+    (1 << ty.bits()) - 1
+}
@@ -519,7 +519,10 @@ probestack_strategy = "outline"
 bb_padding_log2_minus_one = 0
 regalloc_checker = false
 regalloc_verbose_logs = false
+egraph_rule_stats = false
 enable_alias_analysis = true
+enable_redundant_check_elimination = false
+enable_vmctx_invariant_loads = false
 enable_verifier = true
 enable_pcc = false
 is_pic = false
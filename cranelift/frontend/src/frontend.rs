@@ -839,6 +839,7 @@ impl<'a> FunctionBuilder<'a> {
             name: ExternalName::LibCall(LibCall::Memcpy),
             signature,
             colocated: false,
+            preserves_vmctx: false,
         });
 
         self.ins().call(libc_memcpy, &[dest, src, size]);
@@ -940,6 +941,7 @@ impl<'a> FunctionBuilder<'a> {
             name: ExternalName::LibCall(LibCall::Memset),
             signature,
             colocated: false,
+            preserves_vmctx: false,
         });
 
         let ch = self.ins().uextend(types::I32, ch);
@@ -1037,6 +1039,7 @@ impl<'a> FunctionBuilder<'a> {
             name: ExternalName::LibCall(LibCall::Memmove),
             signature,
             colocated: false,
+            preserves_vmctx: false,
         });
 
         self.ins().call(libc_memmove, &[dest, source, size]);
@@ -1072,6 +1075,7 @@ impl<'a> FunctionBuilder<'a> {
             name: ExternalName::LibCall(LibCall::Memcmp),
             signature,
             colocated: false,
+            preserves_vmctx: false,
         });
 
         let call = self.ins().call(libc_memcmp, &[left, right, size]);
@@ -823,6 +823,7 @@ mod tests {
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         // Here the value `v1` is technically not live but our single-pass liveness
@@ -889,6 +890,7 @@ block0(v0: i32, v1: i32):
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         // At each `call` we are losing one more value as no longer live, so
@@ -981,6 +983,7 @@ block0:
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         // Here we rely on the post-order to make sure that we never visit block
@@ -1083,6 +1086,7 @@ block3:
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         // We should not have a stack map entry for `v1` in block 1 because it
@@ -1172,6 +1176,7 @@ block2:
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         let block0 = builder.create_block();
@@ -1241,6 +1246,7 @@ block2:
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         // Depending on which post-order traversal we take, we might consider
@@ -1328,6 +1334,7 @@ block2:
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         let block0 = builder.create_block();
@@ -1395,6 +1402,7 @@ block2:
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         // Create an if/else CFG diamond that and check that various things get
@@ -1536,6 +1544,7 @@ block3(v5: i64, v6: i64):
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         // Test that we support stack maps of heterogeneous types and properly
@@ -1621,6 +1630,7 @@ block0(v0: i8, v1: i16, v2: i32, v3: i64, v4: i128, v5: f32, v6: f64, v7: i8x16,
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         let name = builder
@@ -1636,6 +1646,7 @@ block0(v0: i8, v1: i16, v2: i32, v3: i64, v4: i128, v5: f32, v6: f64, v7: i8x16,
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         // Create a series of needs-stack-map values that do not have
@@ -1741,6 +1752,7 @@ block0:
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         // Use a variable, create a control flow diamond so that the variable
@@ -1901,6 +1913,7 @@ block3(v6: i32):
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         let block0 = builder.create_block();
@@ -1963,6 +1976,7 @@ block0(v0: i32):
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         // Regression test found via fuzzing in
@@ -2040,6 +2054,7 @@ block0(v0: i32):
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         let name = builder
@@ -2053,6 +2068,7 @@ block0(v0: i32):
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         // Test that we support stack maps in loops and that we properly handle
@@ -2139,6 +2155,7 @@ block1:
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         let name = builder
@@ -2154,6 +2171,7 @@ block1:
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         // Test an irreducible loop with multiple entry points, both block1 and
@@ -2278,6 +2296,7 @@ block4:
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         let name = builder
@@ -2293,6 +2312,7 @@ block4:
             name: ir::ExternalName::user(name),
             signature,
             colocated: true,
+            preserves_vmctx: false,
         });
 
         // Test that we detect the `block1 -> block2 -> block3 -> block2 ->
@@ -1602,6 +1602,7 @@ where
                 name,
                 signature: sig_ref,
                 colocated: self.u.arbitrary()?,
+                preserves_vmctx: false,
             });
 
             self.resources
@@ -184,6 +184,8 @@ where
         //   aarch64: https://github.com/bytecodealliance/wasmtime/issues/2735
         let bool_settings = [
             "enable_alias_analysis",
+            "enable_redundant_check_elimination",
+            "enable_vmctx_invariant_loads",
             "enable_safepoints",
             "unwind_info",
             "preserve_frame_pointers",
@@ -17,6 +17,11 @@ pub struct CodegenOptions {
     /// Do not include the `#![allow(...)]` pragmas in the generated
     /// source. Useful if it must be include!()'d elsewhere.
     pub exclude_global_allow_pragmas: bool,
+
+    /// Call `Context::rule_fired` with the source location of each rule
+    /// just before it's applied, so that users can trace or count which
+    /// rules fire.
+    pub emit_rule_hooks: bool,
 }
 
 /// Emit Rust source code for the given type and term environments.
@@ -46,16 +51,18 @@ enum Nested<'a> {
 struct BodyContext<'a, W> {
     out: &'a mut W,
     ruleset: &'a RuleSet,
+    rule_hooks: bool,
     indent: String,
     is_ref: StableSet<BindingId>,
     is_bound: StableSet<BindingId>,
 }
 
 impl<'a, W: Write> BodyContext<'a, W> {
-    fn new(out: &'a mut W, ruleset: &'a RuleSet) -> Self {
+    fn new(out: &'a mut W, ruleset: &'a RuleSet, rule_hooks: bool) -> Self {
         Self {
             out,
             ruleset,
+            rule_hooks,
             indent: Default::default(),
             is_ref: Default::default(),
             is_bound: Default::default(),
@@ -114,9 +121,10 @@ impl<'a> Codegen<'a> {
         let mut code = String::new();
 
         self.generate_header(&mut code, options);
-        self.generate_ctx_trait(&mut code);
+        self.generate_ctx_trait(&mut code, options);
         self.generate_internal_types(&mut code);
-        self.generate_internal_term_constructors(&mut code).unwrap();
+        self.generate_internal_term_constructors(&mut code, options)
+            .unwrap();
 
         code
     }
@@ -207,7 +215,7 @@ impl<'a> Codegen<'a> {
         .unwrap();
     }
 
-    fn generate_ctx_trait(&self, code: &mut String) {
+    fn generate_ctx_trait(&self, code: &mut String, options: &CodegenOptions) {
         writeln!(code).unwrap();
         writeln!(
             code,
@@ -235,6 +243,18 @@ impl<'a> Codegen<'a> {
                 self.generate_trait_sig(code, "    ", &ext_sig);
             }
         }
+        if options.emit_rule_hooks {
+            writeln!(
+                code,
+                "    /// Called with the source location of each rule just before it's applied."
+            )
+            .unwrap();
+            writeln!(
+                code,
+                "    fn rule_fired(&mut self, _rule: &'static str) {{}}"
+            )
+            .unwrap();
+        }
         writeln!(code, "}}").unwrap();
         writeln!(
             code,
@@ -390,10 +410,14 @@ impl<L: Length, C> Length for ContextIterWrapper<L, C> {{
         }
     }
 
-    fn generate_internal_term_constructors(&self, code: &mut String) -> std::fmt::Result {
+    fn generate_internal_term_constructors(
+        &self,
+        code: &mut String,
+        options: &CodegenOptions,
+    ) -> std::fmt::Result {
         for &(termid, ref ruleset) in self.terms.iter() {
             let root = crate::serialize::serialize(ruleset);
-            let mut ctx = BodyContext::new(code, ruleset);
+            let mut ctx = BodyContext::new(code, ruleset, options.emit_rule_hooks);
 
             let termdata = &self.termenv.terms[termid.index()];
             let term_name = &self.typeenv.syms[termdata.name.index()];
@@ -650,6 +674,14 @@ impl<L: Length, C> Length for ContextIterWrapper<L, C> {{
                                 &ctx.indent,
                                 pos.pretty_print_line(&self.files)
                             )?;
+                            if ctx.rule_hooks {
+                                writeln!(
+                                    ctx.out,
+                                    "{}ctx.rule_fired({:?});",
+                                    &ctx.indent,
+                                    pos.pretty_print_line(&self.files)
+                                )?;
+                            }
                             write!(ctx.out, "{}", &ctx.indent)?;
                             match ret_kind {
                                 ReturnKind::Plain | ReturnKind::Option => {
@@ -907,6 +907,7 @@ pub trait Module {
             name: ir::ExternalName::user(user_name_ref),
             signature,
             colocated,
+            preserves_vmctx: false,
         })
     }
 
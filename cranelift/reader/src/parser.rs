@@ -383,6 +383,7 @@ impl Context {
                 name: ExternalName::testcase(""),
                 signature: SigRef::reserved_value(),
                 colocated: false,
+                preserves_vmctx: false,
             });
         }
         self.function.dfg.ext_funcs[fn_] = data;
@@ -1816,7 +1817,7 @@ impl<'a> Parser<'a> {
     //
     // Two variants:
     //
-    // function-decl ::= FuncRef(fnref) "=" ["colocated"]" name function-decl-sig
+    // function-decl ::= FuncRef(fnref) "=" ["colocated"] ["preserves_vmctx"] name function-decl-sig
     // function-decl-sig ::= SigRef(sig) | signature
     //
     // The first variant allocates a new signature reference. The second references an existing
@@ -1828,13 +1829,14 @@ impl<'a> Parser<'a> {
 
         let loc = self.loc;
 
-        // function-decl ::= FuncRef(fnref) "=" * ["colocated"] name function-decl-sig
+        // function-decl ::= FuncRef(fnref) "=" * ["colocated"] ["preserves_vmctx"] name function-decl-sig
         let colocated = self.optional(Token::Identifier("colocated"));
+        let preserves_vmctx = self.optional(Token::Identifier("preserves_vmctx"));
 
-        // function-decl ::= FuncRef(fnref) "=" ["colocated"] * name function-decl-sig
+        // function-decl ::= FuncRef(fnref) "=" ["colocated"] ["preserves_vmctx"] * name function-decl-sig
         let name = self.parse_external_name()?;
 
-        // function-decl ::= FuncRef(fnref) "=" ["colocated"] name * function-decl-sig
+        // function-decl ::= FuncRef(fnref) "=" ["colocated"] ["preserves_vmctx"] name * function-decl-sig
         let data = match self.token() {
             Some(Token::LPar) => {
                 // function-decl ::= FuncRef(fnref) "=" ["colocated"] name * signature
@@ -1847,6 +1849,7 @@ impl<'a> Parser<'a> {
                     name,
                     signature: sigref,
                     colocated,
+                    preserves_vmctx,
                 }
             }
             Some(Token::SigRef(sig_src)) => {
@@ -1862,6 +1865,7 @@ impl<'a> Parser<'a> {
                     name,
                     signature: sig,
                     colocated,
+                    preserves_vmctx,
                 }
             }
             _ => return err!(self.loc, "expected 'function' or sig«n» in function decl"),
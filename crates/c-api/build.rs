@@ -8,6 +8,8 @@ const FEATURES: &[&str] = &[
     "CACHE",
     "PARALLEL_COMPILATION",
     "WASI",
+    "COMPONENT_MODEL",
+    "WASI_HTTP",
     "LOGGING",
     "DISABLE_LOGGING",
     "COREDUMP",
@@ -31,6 +31,8 @@ feature(wat ON)
 feature(cache ON)
 feature(parallel-compilation ON)
 feature(wasi ON)
+feature(component-model ON)
+feature(wasi-http ON)
 feature(logging ON)
 feature(disable-logging OFF)
 feature(coredump ON)
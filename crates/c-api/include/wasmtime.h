@@ -200,6 +200,8 @@
 #include <wasmtime/trap.h>
 #include <wasmtime/val.h>
 #include <wasmtime/async.h>
+#include <wasmtime/component.h>
+#include <wasmtime/wasi_http.h>
 // IWYU pragma: end_exports
 // clang-format on
 
@@ -197,6 +197,45 @@ typedef struct wasmtime_call_future wasmtime_call_future_t;
  */
 WASM_API_EXTERN bool wasmtime_call_future_poll(wasmtime_call_future_t *future);
 
+/**
+ * \brief A callback used to notify the embedder that a
+ * #wasmtime_call_future_t should be polled again.
+ *
+ * This is set on a future with #wasmtime_call_future_set_waker.
+ */
+typedef struct wasmtime_waker_t {
+  /// Callback invoked with `env` when the future should be polled again. This
+  /// may be invoked from within #wasmtime_call_future_poll, or from another
+  /// thread.
+  void (*wake)(void *env);
+  /// User-provided argument to pass to the callback.
+  void *env;
+  /// An optional finalizer for `env`, invoked once the waker is no longer
+  /// used.
+  void (*finalizer)(void *);
+} wasmtime_waker_t;
+
+/**
+ * \brief Sets the waker used when polling `future`.
+ *
+ * By default #wasmtime_call_future_poll gives the embedder no indication of
+ * when the future should be polled again. With a waker set, its `wake`
+ * callback is invoked whenever WebAssembly execution yields and is ready to
+ * resume right away, for example when it yields due to fuel or epoch
+ * interruption. This lets embedders with their own event loop, such as one
+ * built on `epoll` or libuv, schedule the next poll instead of polling in a
+ * loop. A `false` return from #wasmtime_call_future_poll without a wakeup means
+ * that execution is waiting on the continuation of an async host function, and
+ * the embedder should poll again once that continuation can make progress.
+ *
+ * Ownership of `waker.env` is transferred to the future, which invokes
+ * `waker.finalizer`, if any, once the waker is no longer used. This replaces
+ * any previously set waker.
+ */
+WASM_API_EXTERN void
+wasmtime_call_future_set_waker(wasmtime_call_future_t *future,
+                               wasmtime_waker_t waker);
+
 /**
  * /brief Frees the underlying memory for a future.
  *
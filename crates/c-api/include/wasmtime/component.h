@@ -0,0 +1,169 @@
+/**
+ * \file wasmtime/component.h
+ *
+ * APIs for compiling, linking and instantiating components in Wasmtime
+ */
+
+#ifndef WASMTIME_COMPONENT_H
+#define WASMTIME_COMPONENT_H
+
+#include <wasm.h>
+#include <wasmtime/conf.h>
+#include <wasmtime/error.h>
+#include <wasmtime/store.h>
+
+#ifdef WASMTIME_FEATURE_COMPONENT_MODEL
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/**
+ * \typedef wasmtime_component_t
+ * \brief Convenience alias for #wasmtime_component
+ *
+ * \struct wasmtime_component
+ * \brief A compiled Wasmtime component.
+ *
+ * This type represents a compiled WebAssembly component which is ready to be
+ * instantiated with a #wasmtime_component_linker_t. It is safe to use a
+ * component across multiple threads simultaneously.
+ */
+typedef struct wasmtime_component wasmtime_component_t;
+
+#ifdef WASMTIME_FEATURE_COMPILER
+
+/**
+ * \brief Compiles a WebAssembly component binary into a #wasmtime_component_t
+ *
+ * On success the returned #wasmtime_error_t is `NULL` and the `ret` pointer is
+ * filled in with a #wasmtime_component_t. On failure the #wasmtime_error_t is
+ * non-`NULL` and the `ret` pointer is unmodified.
+ *
+ * This function does not take ownership of any of its arguments, but the
+ * returned error and component are owned by the caller.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_component_new(const wasm_engine_t *engine, const uint8_t *wasm,
+                       size_t wasm_len, wasmtime_component_t **ret);
+
+#endif // WASMTIME_FEATURE_COMPILER
+
+/**
+ * \brief Deletes a component.
+ */
+WASM_API_EXTERN void wasmtime_component_delete(wasmtime_component_t *c);
+
+/**
+ * \brief Creates a shallow clone of the specified component, increasing the
+ * internal reference count.
+ */
+WASM_API_EXTERN wasmtime_component_t *
+wasmtime_component_clone(const wasmtime_component_t *c);
+
+/**
+ * \brief Representation of an instantiated component in Wasmtime.
+ *
+ * Like #wasmtime_instance_t this is only a handle to an instance owned by a
+ * store, and is only valid to use with the store that it came from.
+ */
+typedef struct wasmtime_component_instance {
+  /// Internal identifier of what store this belongs to, never zero.
+  uint64_t store_id;
+  /// Internal index within the store.
+  size_t index;
+} wasmtime_component_instance_t;
+
+/**
+ * \typedef wasmtime_component_linker_t
+ * \brief Convenience alias for #wasmtime_component_linker
+ *
+ * \struct wasmtime_component_linker
+ * \brief Object used to satisfy the imports of components by name.
+ *
+ * This is the component analogue of #wasmtime_linker_t. Instances created by
+ * a component linker must be created in a store whose data is the same as
+ * every other store in the C API, so linkers can be used with any
+ * #wasmtime_store_t of the same engine.
+ */
+typedef struct wasmtime_component_linker wasmtime_component_linker_t;
+
+/**
+ * \brief Creates a new component linker for the specified engine.
+ *
+ * This function does not take ownership of the engine argument, and the caller
+ * is expected to delete the returned linker.
+ */
+WASM_API_EXTERN wasmtime_component_linker_t *
+wasmtime_component_linker_new(const wasm_engine_t *engine);
+
+/**
+ * \brief Deletes a component linker.
+ */
+WASM_API_EXTERN void
+wasmtime_component_linker_delete(wasmtime_component_linker_t *linker);
+
+#ifdef WASMTIME_FEATURE_WASI
+
+/**
+ * \brief Defines the interfaces of WASIp2 in this linker.
+ *
+ * \return On success `NULL` is returned, otherwise an error is returned which
+ * describes why the definition failed.
+ *
+ * The WASI state used by instances is configured with
+ * #wasmtime_context_set_wasi. Stores where it isn't configured use a WASI
+ * context without any arguments, environment variables, preopened
+ * directories or standard streams.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_component_linker_add_wasip2(wasmtime_component_linker_t *linker);
+
+#endif // WASMTIME_FEATURE_WASI
+
+/**
+ * \brief Instantiates a component with the definitions in a linker.
+ *
+ * \param linker the linker used to satisfy the component's imports.
+ * \param context the store in which to create the instance.
+ * \param component the component to instantiate.
+ * \param instance where to store the returned instance.
+ *
+ * \return On success `NULL` is returned and `instance` is filled in, otherwise
+ * an error is returned which describes why instantiation failed, including if
+ * it trapped.
+ *
+ * This function does not take ownership of any of its arguments.
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_linker_instantiate(
+    const wasmtime_component_linker_t *linker, wasmtime_context_t *context,
+    const wasmtime_component_t *component,
+    wasmtime_component_instance_t *instance);
+
+#ifdef WASMTIME_FEATURE_WASI
+
+/**
+ * \brief Runs a component implementing the `wasi:cli/command` world.
+ *
+ * \param instance the instance whose `wasi:cli/run` export is called.
+ * \param context the store that owns `instance`.
+ * \param exit_ok where to store whether `run` returned success.
+ *
+ * \return On success `NULL` is returned and `exit_ok` is filled in, otherwise
+ * an error is returned if the instance doesn't export `wasi:cli/run` or if it
+ * trapped. An explicit exit with #wasmtime_error_exit_status is reported as
+ * an error as well.
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_instance_call_wasi_cli_run(
+    const wasmtime_component_instance_t *instance, wasmtime_context_t *context,
+    bool *exit_ok);
+
+#endif // WASMTIME_FEATURE_WASI
+
+#ifdef __cplusplus
+} // extern "C"
+#endif
+
+#endif // WASMTIME_FEATURE_COMPONENT_MODEL
+
+#endif // WASMTIME_COMPONENT_H
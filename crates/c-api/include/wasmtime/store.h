@@ -146,6 +146,33 @@ WASM_API_EXTERN void wasmtime_context_set_data(wasmtime_context_t *context,
  */
 WASM_API_EXTERN void wasmtime_context_gc(wasmtime_context_t *context);
 
+/**
+ * \brief Enters a new root scope within the given context.
+ *
+ * Every #wasmtime_anyref_t and #wasmtime_externref_t produced by the C API
+ * for this context while a root scope is active, including those within a
+ * #wasmtime_val_t, is owned by the innermost active scope. Such references are
+ * unrooted automatically when their scope is exited with
+ * #wasmtime_context_root_scope_pop, so they don't need to be unrooted
+ * individually. Unrooting one explicitly before then is still allowed.
+ *
+ * Returns the depth of the new scope, which must later be passed to
+ * #wasmtime_context_root_scope_pop.
+ */
+WASM_API_EXTERN size_t
+wasmtime_context_root_scope_push(wasmtime_context_t *context);
+
+/**
+ * \brief Exits the root scope at `depth` and every scope nested within it.
+ *
+ * All references owned by the exited scopes are unrooted, and must not be used
+ * afterwards. The `depth` must have been returned by
+ * #wasmtime_context_root_scope_push for this context, and the scope must not
+ * have been exited already.
+ */
+WASM_API_EXTERN void wasmtime_context_root_scope_pop(wasmtime_context_t *context,
+                                                     size_t depth);
+
 /**
  * \brief Set fuel to this context's store for wasm to consume while executing.
  *
@@ -150,6 +150,40 @@ WASM_API_EXTERN bool wasmtime_anyref_i31_get_s(wasmtime_context_t *context,
                                                const wasmtime_anyref_t *anyref,
                                                int32_t *dst);
 
+/**
+ * \brief Reads a field of a struct.
+ *
+ * \param context the store context that `anyref` belongs to
+ * \param anyref the struct to read from
+ * \param index the index of the field to read
+ * \param out where to store the field's value
+ *
+ * Returns an error if `anyref` is null, is not a `structref`, or if `index` is
+ * out of bounds, in which case `out` is not initialized. Otherwise `out` must
+ * eventually be unrooted with #wasmtime_val_unroot.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_anyref_struct_get(wasmtime_context_t *context,
+                           const wasmtime_anyref_t *anyref, size_t index,
+                           wasmtime_val_t *out);
+
+/**
+ * \brief Writes a field of a struct.
+ *
+ * \param context the store context that `anyref` belongs to
+ * \param anyref the struct to write to
+ * \param index the index of the field to write
+ * \param val the value to write, which is not consumed by this function
+ *
+ * Returns an error if `anyref` is null, is not a `structref`, if `index` is
+ * out of bounds, if the field is immutable, or if `val` does not match the
+ * field's type.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_anyref_struct_set(wasmtime_context_t *context,
+                           const wasmtime_anyref_t *anyref, size_t index,
+                           const wasmtime_val_t *val);
+
 /**
  * \typedef wasmtime_externref_t
  * \brief Convenience alias for #wasmtime_externref
@@ -220,6 +254,28 @@ WASM_API_EXTERN bool wasmtime_externref_new(wasmtime_context_t *context,
                                             void (*finalizer)(void *),
                                             wasmtime_externref_t *out);
 
+/**
+ * \brief Create a new `externref` value whose finalizer is deferred.
+ *
+ * This is the same as #wasmtime_externref_new except that `finalizer` is not
+ * invoked while the garbage collector is tracing the heap. Instead it is queued
+ * when the reference is reclaimed, and invoked once the collection has
+ * finished, or when the store is deleted.
+ *
+ * Finalizers still run from within #wasmtime_context_gc, other functions which
+ * may collect garbage, and #wasmtime_store_delete, so `finalizer` must not use
+ * the store the `externref` belongs to, or any of its contexts, in any way.
+ *
+ * \param context the store context to allocate this externref within
+ * \param data the host-specific data to wrap
+ * \param finalizer the finalizer for `data`, which must not be `NULL`
+ * \param out where to store the created value.
+ */
+WASM_API_EXTERN bool
+wasmtime_externref_new_with_finalizer(wasmtime_context_t *context, void *data,
+                                      void (*finalizer)(void *),
+                                      wasmtime_externref_t *out);
+
 /**
  * \brief Get an `externref`'s wrapped data
  *
@@ -229,6 +285,82 @@ WASM_API_EXTERN bool wasmtime_externref_new(wasmtime_context_t *context,
 WASM_API_EXTERN void *wasmtime_externref_data(wasmtime_context_t *context,
                                               const wasmtime_externref_t *data);
 
+/**
+ * \typedef wasmtime_externref_vtable_t
+ * \brief Convenience alias for #wasmtime_externref_vtable
+ *
+ * \struct wasmtime_externref_vtable
+ * \brief Callbacks describing the host data wrapped by an `externref`.
+ *
+ * This is used with #wasmtime_externref_new_with_vtable to give `externref`
+ * values created from C richer ownership semantics than a single finalizer.
+ * The vtable itself is not copied, and it must remain valid for as long as any
+ * `externref` created with it is alive, so it's typically a `static`.
+ */
+typedef struct wasmtime_externref_vtable {
+  /// An embedder-defined identifier for the type of data wrapped, which can be
+  /// used to check the type of an `externref` before casting its data.
+  uint64_t type_id;
+  /// An optional NUL-terminated, UTF-8 name of the type of data wrapped,
+  /// which is reported when debug-printing the `externref` from Rust.
+  const char *type_name;
+  /// An optional callback invoked with the wrapped data when the `externref`
+  /// is reclaimed.
+  void (*drop)(void *data);
+  /// An optional callback which returns a deep copy of the wrapped data, used
+  /// by #wasmtime_externref_clone_data.
+  void *(*clone)(void *data);
+  /// An optional callback which initializes `out`, for example with
+  /// `wasm_name_new`, to a human-readable description of the wrapped data.
+  /// Wasmtime takes ownership of `out` afterwards.
+  void (*debug)(void *data, wasm_name_t *out);
+} wasmtime_externref_vtable_t;
+
+/**
+ * \brief Create a new `externref` value whose data is described by a vtable.
+ *
+ * This is the same as #wasmtime_externref_new except that the `drop` callback
+ * of `vtable` is used as the finalizer for `data`, and `vtable` can later be
+ * retrieved with #wasmtime_externref_vtable.
+ *
+ * \param context the store context to allocate this externref within
+ * \param data the host-specific data to wrap
+ * \param vtable callbacks describing `data`, which must outlive the externref
+ * \param out where to store the created value.
+ */
+WASM_API_EXTERN bool
+wasmtime_externref_new_with_vtable(wasmtime_context_t *context, void *data,
+                                   const wasmtime_externref_vtable_t *vtable,
+                                   wasmtime_externref_t *out);
+
+/**
+ * \brief Get the vtable an `externref` was created with.
+ *
+ * Returns the `vtable` passed to #wasmtime_externref_new_with_vtable, or `NULL`
+ * if `ref` is null, was created with #wasmtime_externref_new, or was not
+ * created through the C API.
+ */
+WASM_API_EXTERN const wasmtime_externref_vtable_t *
+wasmtime_externref_vtable(wasmtime_context_t *context,
+                          const wasmtime_externref_t *ref);
+
+/**
+ * \brief Creates a new `externref` wrapping a deep copy of the data of `ref`.
+ *
+ * Unlike #wasmtime_externref_clone, which creates a new reference to the same
+ * data, this uses the `clone` callback of the vtable `ref` was created with to
+ * copy its data, and wraps the copy in a new `externref` with the same vtable.
+ *
+ * Returns `false` if `ref` is null, has no `clone` callback, or if a new
+ * externref couldn't be allocated, in which case `out` is not initialized.
+ * Otherwise `out` must eventually be unrooted with
+ * #wasmtime_externref_unroot.
+ */
+WASM_API_EXTERN bool
+wasmtime_externref_clone_data(wasmtime_context_t *context,
+                              const wasmtime_externref_t *ref,
+                              wasmtime_externref_t *out);
+
 /**
  * \brief Creates a new reference pointing to the same data that `ref` points
  * to (depending on the configured collector this might increase a reference
@@ -0,0 +1,110 @@
+/**
+ * \file wasmtime/wasi_http.h
+ *
+ * APIs for providing `wasi:http` to components in Wasmtime
+ */
+
+#ifndef WASMTIME_WASI_HTTP_H
+#define WASMTIME_WASI_HTTP_H
+
+#include <wasmtime/component.h>
+#include <wasmtime/conf.h>
+#include <wasmtime/error.h>
+#include <wasmtime/store.h>
+
+#ifdef WASMTIME_FEATURE_WASI_HTTP
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/**
+ * \typedef wasmtime_wasi_http_config_t
+ * \brief Convenience alias for #wasmtime_wasi_http_config
+ *
+ * \struct wasmtime_wasi_http_config
+ * \brief Configuration of the `wasi:http` state of a store.
+ *
+ * A configuration is created with #wasmtime_wasi_http_config_new and then
+ * handed to a store with #wasmtime_context_set_wasi_http.
+ */
+typedef struct wasmtime_wasi_http_config wasmtime_wasi_http_config_t;
+
+/**
+ * \brief Creates a new configuration with the default settings.
+ *
+ * By default outgoing requests negotiate their HTTP version and bodies are
+ * unlimited.
+ */
+WASM_API_EXTERN wasmtime_wasi_http_config_t *wasmtime_wasi_http_config_new(void);
+
+/**
+ * \brief Deletes a configuration.
+ */
+WASM_API_EXTERN void
+wasmtime_wasi_http_config_delete(wasmtime_wasi_http_config_t *config);
+
+/// \brief Which HTTP versions outgoing requests may be sent with.
+typedef uint8_t wasmtime_wasi_http_version_policy_t;
+
+/// \brief Always use HTTP/1.1.
+#define WASMTIME_WASI_HTTP_VERSION_HTTP1_ONLY 0
+/// \brief Offer HTTP/2 and HTTP/1.1 to `https` origins and use whichever the
+/// server picks, which is the default.
+#define WASMTIME_WASI_HTTP_VERSION_NEGOTIATE 1
+/// \brief Like #WASMTIME_WASI_HTTP_VERSION_NEGOTIATE, but also send plain
+/// `http` requests over HTTP/2 with prior knowledge.
+#define WASMTIME_WASI_HTTP_VERSION_HTTP2_PRIOR_KNOWLEDGE 2
+
+/**
+ * \brief Configures which HTTP versions outgoing requests may be sent with.
+ *
+ * Returns `false` if `policy` isn't one of the
+ * `WASMTIME_WASI_HTTP_VERSION_*` constants, in which case the configuration
+ * is unchanged.
+ */
+WASM_API_EXTERN bool wasmtime_wasi_http_config_set_http_versions(
+    wasmtime_wasi_http_config_t *config,
+    wasmtime_wasi_http_version_policy_t policy);
+
+/**
+ * \brief Limits the number of bytes which may be read from an incoming body or
+ * written to an outgoing body.
+ */
+WASM_API_EXTERN void
+wasmtime_wasi_http_config_set_max_body_bytes(wasmtime_wasi_http_config_t *config,
+                                             uint64_t max_body_bytes);
+
+/**
+ * \brief Configures the `wasi:http` state of the specified store.
+ *
+ * Stores where this isn't called use the default configuration.
+ *
+ * This function does not take ownership of `context` but it does take
+ * ownership of `config`. The caller should no longer use `config` after
+ * calling this function.
+ */
+WASM_API_EXTERN void
+wasmtime_context_set_wasi_http(wasmtime_context_t *context,
+                               wasmtime_wasi_http_config_t *config);
+
+/**
+ * \brief Defines the `wasi:http/types` and `wasi:http/outgoing-handler`
+ * interfaces in this linker.
+ *
+ * \return On success `NULL` is returned, otherwise an error is returned which
+ * describes why the definition failed.
+ *
+ * The WASIp2 interfaces that `wasi:http` depends on must be defined as well,
+ * with #wasmtime_component_linker_add_wasip2.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_component_linker_add_wasi_http(wasmtime_component_linker_t *linker);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif
+
+#endif // WASMTIME_FEATURE_WASI_HTTP
+
+#endif // WASMTIME_WASI_HTTP_H
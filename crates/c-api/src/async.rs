@@ -5,7 +5,7 @@ use std::num::NonZeroU64;
 use std::ops::Range;
 use std::pin::Pin;
 use std::sync::Arc;
-use std::task::{Context, Poll};
+use std::task::{Context, Poll, Wake, Waker};
 use std::{ptr, str};
 use wasmtime::{
     AsContextMut, Func, Instance, Result, RootScope, StackCreator, StackMemory, Trap, Val,
@@ -14,8 +14,8 @@ use wasmtime::{
 use crate::{
     bad_utf8, handle_result, to_str, translate_args, wasm_config_t, wasm_functype_t, wasm_trap_t,
     wasmtime_caller_t, wasmtime_error_t, wasmtime_instance_pre_t, wasmtime_linker_t,
-    wasmtime_module_t, wasmtime_val_t, wasmtime_val_union, WasmtimeCaller, WasmtimeStoreContextMut,
-    WASMTIME_I32,
+    wasmtime_module_t, wasmtime_val_t, wasmtime_val_union, ForeignData, WasmtimeCaller,
+    WasmtimeStoreContextMut, WASMTIME_I32,
 };
 
 #[unsafe(no_mangle)]
@@ -189,17 +189,67 @@ unsafe fn c_async_callback_to_rust_fn(
     }
 }
 
-#[repr(transparent)]
 pub struct wasmtime_call_future_t<'a> {
     underlying: Pin<Box<dyn Future<Output = ()> + 'a>>,
+    waker: Option<Waker>,
+}
+
+impl<'a> wasmtime_call_future_t<'a> {
+    fn new(underlying: Pin<Box<dyn Future<Output = ()> + 'a>>) -> Box<wasmtime_call_future_t<'a>> {
+        Box::new(wasmtime_call_future_t {
+            underlying,
+            waker: None,
+        })
+    }
+}
+
+#[repr(C)]
+pub struct wasmtime_waker_t {
+    pub wake: extern "C" fn(*mut c_void),
+    pub env: *mut c_void,
+    pub finalizer: Option<extern "C" fn(*mut c_void)>,
+}
+
+/// A `Waker` which invokes a C callback, created from a `wasmtime_waker_t`.
+struct CWaker {
+    wake: extern "C" fn(*mut c_void),
+    foreign: ForeignData,
+}
+
+impl Wake for CWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        (self.wake)(self.foreign.data);
+    }
 }
 
 #[unsafe(no_mangle)]
 pub extern "C" fn wasmtime_call_future_delete(_future: Box<wasmtime_call_future_t>) {}
 
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_call_future_set_waker(
+    future: &mut wasmtime_call_future_t,
+    waker: wasmtime_waker_t,
+) {
+    let waker = CWaker {
+        wake: waker.wake,
+        foreign: ForeignData {
+            data: waker.env,
+            finalizer: waker.finalizer,
+        },
+    };
+    future.waker = Some(Waker::from(Arc::new(waker)));
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn wasmtime_call_future_poll(future: &mut wasmtime_call_future_t) -> bool {
-    let w = futures::task::noop_waker_ref();
+    let w = future
+        .waker
+        .as_ref()
+        .unwrap_or(futures::task::noop_waker_ref());
     match future.underlying.as_mut().poll(&mut Context::from_waker(w)) {
         Poll::Ready(()) => true,
         Poll::Pending => false,
@@ -267,7 +317,7 @@ pub unsafe extern "C" fn wasmtime_func_call_async<'a>(
         trap_ret,
         err_ret,
     ));
-    Box::new(wasmtime_call_future_t { underlying: fut })
+    wasmtime_call_future_t::new(fut)
 }
 
 #[unsafe(no_mangle)]
@@ -325,7 +375,7 @@ pub extern "C" fn wasmtime_linker_instantiate_async<'a>(
         trap_ret,
         err_ret,
     ));
-    Box::new(crate::wasmtime_call_future_t { underlying: fut })
+    wasmtime_call_future_t::new(fut)
 }
 
 async fn do_instance_pre_instantiate_async(
@@ -357,7 +407,7 @@ pub extern "C" fn wasmtime_instance_pre_instantiate_async<'a>(
         trap_ret,
         err_ret,
     ));
-    Box::new(crate::wasmtime_call_future_t { underlying: fut })
+    wasmtime_call_future_t::new(fut)
 }
 
 pub type wasmtime_stack_memory_get_callback_t =
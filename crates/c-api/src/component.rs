@@ -0,0 +1,84 @@
+use crate::{handle_result, wasm_engine_t, wasmtime_error_t, WasmtimeStoreContextMut};
+use wasmtime::component::{Component, Instance, Linker};
+
+#[derive(Clone)]
+pub struct wasmtime_component_t {
+    pub(crate) component: Component,
+}
+
+wasmtime_c_api_macros::declare_own!(wasmtime_component_t);
+
+#[unsafe(no_mangle)]
+#[cfg(any(feature = "cranelift", feature = "winch"))]
+pub unsafe extern "C" fn wasmtime_component_new(
+    engine: &wasm_engine_t,
+    wasm: *const u8,
+    len: usize,
+    out: &mut *mut wasmtime_component_t,
+) -> Option<Box<wasmtime_error_t>> {
+    handle_result(
+        Component::from_binary(&engine.engine, crate::slice_from_raw_parts(wasm, len)),
+        |component| {
+            *out = Box::into_raw(Box::new(wasmtime_component_t { component }));
+        },
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_clone(
+    component: &wasmtime_component_t,
+) -> Box<wasmtime_component_t> {
+    Box::new(component.clone())
+}
+
+#[repr(C)]
+pub struct wasmtime_component_linker_t {
+    pub(crate) linker: Linker<crate::WasmtimeStoreData>,
+}
+
+wasmtime_c_api_macros::declare_own!(wasmtime_component_linker_t);
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_linker_new(
+    engine: &wasm_engine_t,
+) -> Box<wasmtime_component_linker_t> {
+    Box::new(wasmtime_component_linker_t {
+        linker: Linker::new(&engine.engine),
+    })
+}
+
+#[cfg(feature = "wasi")]
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_linker_add_wasip2(
+    linker: &mut wasmtime_component_linker_t,
+) -> Option<Box<wasmtime_error_t>> {
+    handle_result(
+        wasmtime_wasi::add_to_linker_sync(&mut linker.linker),
+        |()| (),
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_linker_instantiate(
+    linker: &wasmtime_component_linker_t,
+    store: WasmtimeStoreContextMut<'_>,
+    component: &wasmtime_component_t,
+    instance_ptr: &mut Instance,
+) -> Option<Box<wasmtime_error_t>> {
+    handle_result(
+        linker.linker.instantiate(store, &component.component),
+        |instance| *instance_ptr = instance,
+    )
+}
+
+#[cfg(feature = "wasi")]
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_instance_call_wasi_cli_run(
+    instance: &Instance,
+    mut store: WasmtimeStoreContextMut<'_>,
+    exit_ok: &mut bool,
+) -> Option<Box<wasmtime_error_t>> {
+    let result = wasmtime_wasi::bindings::sync::Command::new(&mut store, instance)
+        .and_then(|command| command.wasi_cli_run().call_run(&mut store));
+    handle_result(result, |result| *exit_ok = result.is_ok())
+}
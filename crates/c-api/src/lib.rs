@@ -60,11 +60,21 @@ mod r#async;
 #[cfg(feature = "async")]
 pub use crate::r#async::*;
 
+#[cfg(feature = "component-model")]
+mod component;
+#[cfg(feature = "component-model")]
+pub use crate::component::*;
+
 #[cfg(feature = "wasi")]
 mod wasi;
 #[cfg(feature = "wasi")]
 pub use crate::wasi::*;
 
+#[cfg(feature = "wasi-http")]
+mod wasi_http;
+#[cfg(feature = "wasi-http")]
+pub use crate::wasi_http::*;
+
 #[cfg(feature = "wat")]
 mod wat2wasm;
 #[cfg(feature = "wat")]
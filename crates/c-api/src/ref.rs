@@ -1,6 +1,17 @@
-use crate::{abort, WasmtimeStoreContextMut};
+use crate::{
+    abort, handle_result, wasm_name_t, wasmtime_error_t, wasmtime_val_t, ForeignData,
+    WasmtimeStoreContextMut, WasmtimeStoreData,
+};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::c_char;
 use std::{mem::MaybeUninit, num::NonZeroU64, os::raw::c_void, ptr};
-use wasmtime::{AnyRef, ExternRef, ManuallyRooted, Ref, RootScope, Val, I31};
+use wasmtime::{
+    AnyRef, AsContext, AsContextMut, ExternRef, ManuallyRooted, Ref, RootScope, Rooted, StructRef,
+    Val, I31,
+};
 
 /// `*mut wasm_ref_t` is a reference type (`externref` or `funcref`), as seen by
 /// the C API. Because we do not have a uniform representation for `funcref`s
@@ -205,6 +216,37 @@ macro_rules! ref_wrapper {
                     store_id, self.a, self.b,
                 ))
             }
+
+            /// Creates a new C representation of `rooted`, which is owned by
+            /// the innermost root scope of `cx` if one is active.
+            pub(crate) fn new(
+                mut cx: impl AsContextMut<Data = WasmtimeStoreData>,
+                rooted: Option<ManuallyRooted<$wasmtime>>,
+            ) -> $c {
+                let ret = $c::from(rooted);
+                if ret.store_id != 0 {
+                    cx.as_context_mut()
+                        .data_mut()
+                        .root_scopes
+                        .track(ScopedRoot::$wasmtime(ret.store_id, ret.a, ret.b));
+                }
+                ret
+            }
+
+            /// Unroots this reference, if it isn't null, removing it from the
+            /// root scope that owns it, if any.
+            pub(crate) unsafe fn unroot(
+                &self,
+                mut cx: impl AsContextMut<Data = WasmtimeStoreData>,
+            ) {
+                if let Some(rooted) = self.as_wasmtime() {
+                    cx.as_context_mut()
+                        .data_mut()
+                        .root_scopes
+                        .untrack(&ScopedRoot::$wasmtime(self.store_id, self.a, self.b));
+                    rooted.unroot(cx);
+                }
+            }
         }
 
         impl From<Option<ManuallyRooted<$wasmtime>>> for $c {
@@ -229,14 +271,98 @@ macro_rules! ref_wrapper {
 ref_wrapper!(AnyRef => wasmtime_anyref_t);
 ref_wrapper!(ExternRef => wasmtime_externref_t);
 
+/// The root scopes entered through `wasmtime_context_root_scope_push`.
+///
+/// This is the C API's equivalent of `RootScope`: every `anyref` and
+/// `externref` handed out to C while a scope is active is owned by the
+/// innermost scope, and is unrooted when that scope is popped unless it was
+/// already unrooted explicitly.
+#[derive(Default)]
+pub(crate) struct RootScopes {
+    /// The roots tracked by each scope, which may include roots that have
+    /// since been untracked.
+    scopes: Vec<Vec<ScopedRoot>>,
+    /// The depth of the scope owning each root which is still tracked.
+    owners: HashMap<ScopedRoot, usize>,
+}
+
+/// The raw parts of a reference owned by a root scope.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum ScopedRoot {
+    AnyRef(u64, u32, u32),
+    ExternRef(u64, u32, u32),
+}
+
+impl RootScopes {
+    fn push(&mut self) -> usize {
+        self.scopes.push(Vec::new());
+        self.scopes.len() - 1
+    }
+
+    fn pop(&mut self, depth: usize) -> Vec<ScopedRoot> {
+        assert!(
+            depth < self.scopes.len(),
+            "popped root scope {depth} which is not active"
+        );
+        let mut roots = Vec::new();
+        for (i, scope) in self.scopes.split_off(depth).into_iter().enumerate() {
+            for root in scope {
+                if self.owners.get(&root) == Some(&(depth + i)) {
+                    self.owners.remove(&root);
+                    roots.push(root);
+                }
+            }
+        }
+        roots
+    }
+
+    fn track(&mut self, root: ScopedRoot) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(root);
+            self.owners.insert(root, self.scopes.len() - 1);
+        }
+    }
+
+    fn untrack(&mut self, root: &ScopedRoot) {
+        self.owners.remove(root);
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_context_root_scope_push(mut cx: WasmtimeStoreContextMut<'_>) -> usize {
+    cx.data_mut().root_scopes.push()
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_context_root_scope_pop(
+    mut cx: WasmtimeStoreContextMut<'_>,
+    depth: usize,
+) {
+    for root in cx.data_mut().root_scopes.pop(depth) {
+        match root {
+            ScopedRoot::AnyRef(store_id, a, b) => {
+                let store_id = NonZeroU64::new(store_id).unwrap();
+                ManuallyRooted::<AnyRef>::from_raw_parts_for_c_api(store_id, a, b).unroot(&mut cx);
+            }
+            ScopedRoot::ExternRef(store_id, a, b) => {
+                let store_id = NonZeroU64::new(store_id).unwrap();
+                ManuallyRooted::<ExternRef>::from_raw_parts_for_c_api(store_id, a, b)
+                    .unroot(&mut cx);
+            }
+        }
+    }
+}
+
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn wasmtime_anyref_clone(
-    cx: WasmtimeStoreContextMut<'_>,
+    mut cx: WasmtimeStoreContextMut<'_>,
     anyref: Option<&wasmtime_anyref_t>,
     out: &mut MaybeUninit<wasmtime_anyref_t>,
 ) {
-    let anyref = anyref.and_then(|a| a.as_wasmtime()).map(|a| a.clone(cx));
-    crate::initialize(out, anyref.into());
+    let anyref = anyref
+        .and_then(|a| a.as_wasmtime())
+        .map(|a| a.clone(&mut cx));
+    crate::initialize(out, wasmtime_anyref_t::new(cx, anyref));
 }
 
 #[unsafe(no_mangle)]
@@ -244,8 +370,8 @@ pub unsafe extern "C" fn wasmtime_anyref_unroot(
     cx: WasmtimeStoreContextMut<'_>,
     val: Option<&mut MaybeUninit<wasmtime_anyref_t>>,
 ) {
-    if let Some(val) = val.and_then(|v| v.assume_init_read().as_wasmtime()) {
-        val.unroot(cx);
+    if let Some(val) = val {
+        val.assume_init_read().unroot(cx);
     }
 }
 
@@ -268,7 +394,7 @@ pub unsafe extern "C" fn wasmtime_anyref_from_raw(
     let mut scope = RootScope::new(cx);
     let anyref = AnyRef::from_raw(&mut scope, raw)
         .map(|a| a.to_manually_rooted(&mut scope).expect("in scope"));
-    crate::initialize(val, anyref.into());
+    crate::initialize(val, wasmtime_anyref_t::new(scope, anyref));
 }
 
 #[unsafe(no_mangle)]
@@ -280,7 +406,7 @@ pub extern "C" fn wasmtime_anyref_from_i31(
     let mut scope = RootScope::new(cx);
     let anyref = AnyRef::from_i31(&mut scope, I31::wrapping_u32(val));
     let anyref = anyref.to_manually_rooted(&mut scope).expect("in scope");
-    crate::initialize(out, Some(anyref).into())
+    crate::initialize(out, wasmtime_anyref_t::new(scope, Some(anyref)))
 }
 
 #[unsafe(no_mangle)]
@@ -321,6 +447,130 @@ pub unsafe extern "C" fn wasmtime_anyref_i31_get_s(
     }
 }
 
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_anyref_struct_get(
+    cx: WasmtimeStoreContextMut<'_>,
+    anyref: Option<&wasmtime_anyref_t>,
+    index: usize,
+    out: &mut MaybeUninit<wasmtime_val_t>,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let result = anyref_as_struct(&scope, anyref).and_then(|s| s.field(&mut scope, index));
+    handle_result(result, |val| {
+        crate::initialize(out, wasmtime_val_t::from_val(&mut scope, val))
+    })
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_anyref_struct_set(
+    cx: WasmtimeStoreContextMut<'_>,
+    anyref: Option<&wasmtime_anyref_t>,
+    index: usize,
+    val: &wasmtime_val_t,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let val = val.to_val(&mut scope);
+    let result = anyref_as_struct(&scope, anyref).and_then(|s| s.set_field(&mut scope, index, val));
+    handle_result(result, |()| {})
+}
+
+unsafe fn anyref_as_struct(
+    cx: impl AsContext,
+    anyref: Option<&wasmtime_anyref_t>,
+) -> Result<Rooted<StructRef>> {
+    let anyref = anyref
+        .and_then(|a| a.as_wasmtime())
+        .ok_or_else(|| anyhow!("null reference"))?;
+    anyref
+        .as_struct(&cx)?
+        .ok_or_else(|| anyhow!("`anyref` is not a struct"))
+}
+
+#[repr(C)]
+pub struct wasmtime_externref_vtable_t {
+    pub type_id: u64,
+    pub type_name: *const c_char,
+    pub drop: Option<extern "C" fn(*mut c_void)>,
+    pub clone: Option<extern "C" fn(*mut c_void) -> *mut c_void>,
+    pub debug: Option<extern "C" fn(*mut c_void, &mut MaybeUninit<wasm_name_t>)>,
+}
+
+/// The host data of an `externref` created through the C API.
+///
+/// This is what [`ExternRef::data`] returns for such references, so Rust code
+/// embedding the C API can downcast to this type to inspect references
+/// created from C.
+pub struct ForeignExternRef {
+    foreign: ForeignData,
+    vtable: Option<&'static wasmtime_externref_vtable_t>,
+}
+
+// The C API requires that the data and vtable of an `externref` may be used
+// from any thread.
+unsafe impl Send for ForeignExternRef {}
+unsafe impl Sync for ForeignExternRef {}
+
+impl ForeignExternRef {
+    /// Returns the pointer to the data wrapped by this reference.
+    pub fn data(&self) -> *mut c_void {
+        self.foreign.data
+    }
+
+    /// Returns the type identifier of the data, if this reference was created
+    /// with a vtable.
+    pub fn type_id(&self) -> Option<u64> {
+        self.vtable.map(|v| v.type_id)
+    }
+
+    /// Returns the name of the type of the data, if this reference was
+    /// created with a vtable which names it.
+    pub fn type_name(&self) -> Option<&str> {
+        let name = self.vtable?.type_name;
+        if name.is_null() {
+            return None;
+        }
+        unsafe { CStr::from_ptr(name).to_str().ok() }
+    }
+}
+
+impl fmt::Debug for ForeignExternRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(debug) = self.vtable.and_then(|v| v.debug) {
+            let mut out = MaybeUninit::uninit();
+            debug(self.data(), &mut out);
+            let out = unsafe { out.assume_init() };
+            return f.write_str(&String::from_utf8_lossy(out.as_slice()));
+        }
+        f.debug_struct("ForeignExternRef")
+            .field("type_name", &self.type_name())
+            .field("data", &self.data())
+            .finish()
+    }
+}
+
+fn new_foreign_externref(
+    cx: WasmtimeStoreContextMut<'_>,
+    foreign: ForeignExternRef,
+    out: &mut MaybeUninit<wasmtime_externref_t>,
+) -> bool {
+    let mut scope = RootScope::new(cx);
+    let e = match ExternRef::new(&mut scope, foreign) {
+        Ok(e) => e,
+        Err(_) => return false,
+    };
+    let e = e.to_manually_rooted(&mut scope).expect("in scope");
+    crate::initialize(out, wasmtime_externref_t::new(scope, Some(e)));
+    true
+}
+
+fn foreign_externref<'a>(
+    cx: WasmtimeStoreContextMut<'a>,
+    externref: Option<&wasmtime_externref_t>,
+) -> Option<&'a ForeignExternRef> {
+    let data = externref?.as_wasmtime()?.data(cx).ok()??;
+    data.downcast_ref::<ForeignExternRef>()
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn wasmtime_externref_new(
     cx: WasmtimeStoreContextMut<'_>,
@@ -328,13 +578,51 @@ pub extern "C" fn wasmtime_externref_new(
     finalizer: Option<extern "C" fn(*mut c_void)>,
     out: &mut MaybeUninit<wasmtime_externref_t>,
 ) -> bool {
+    let foreign = ForeignExternRef {
+        foreign: ForeignData { data, finalizer },
+        vtable: None,
+    };
+    new_foreign_externref(cx, foreign, out)
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_externref_new_with_vtable(
+    cx: WasmtimeStoreContextMut<'_>,
+    data: *mut c_void,
+    vtable: &'static wasmtime_externref_vtable_t,
+    out: &mut MaybeUninit<wasmtime_externref_t>,
+) -> bool {
+    let foreign = ForeignExternRef {
+        foreign: ForeignData {
+            data,
+            finalizer: vtable.drop,
+        },
+        vtable: Some(vtable),
+    };
+    new_foreign_externref(cx, foreign, out)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_externref_new_with_finalizer(
+    cx: WasmtimeStoreContextMut<'_>,
+    data: *mut c_void,
+    finalizer: extern "C" fn(*mut c_void),
+    out: &mut MaybeUninit<wasmtime_externref_t>,
+) -> bool {
+    let foreign = ForeignExternRef {
+        foreign: ForeignData {
+            data,
+            finalizer: None,
+        },
+        vtable: None,
+    };
     let mut scope = RootScope::new(cx);
-    let e = match ExternRef::new(&mut scope, crate::ForeignData { data, finalizer }) {
+    let e = match ExternRef::new_with_finalizer(&mut scope, foreign, move |f| finalizer(f.data())) {
         Ok(e) => e,
         Err(_) => return false,
     };
     let e = e.to_manually_rooted(&mut scope).expect("in scope");
-    crate::initialize(out, Some(e).into());
+    crate::initialize(out, wasmtime_externref_t::new(scope, Some(e)));
     true
 }
 
@@ -343,23 +631,54 @@ pub unsafe extern "C" fn wasmtime_externref_data(
     cx: WasmtimeStoreContextMut<'_>,
     externref: Option<&wasmtime_externref_t>,
 ) -> *mut c_void {
-    externref
-        .and_then(|e| e.as_wasmtime())
-        .and_then(|e| {
-            let data = e.data(cx).ok()??;
-            Some(data.downcast_ref::<crate::ForeignData>().unwrap().data)
-        })
+    foreign_externref(cx, externref)
+        .map(|e| e.data())
         .unwrap_or(ptr::null_mut())
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn wasmtime_externref_clone(
+pub unsafe extern "C" fn wasmtime_externref_vtable(
     cx: WasmtimeStoreContextMut<'_>,
     externref: Option<&wasmtime_externref_t>,
+) -> Option<&'static wasmtime_externref_vtable_t> {
+    foreign_externref(cx, externref)?.vtable
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_externref_clone_data(
+    mut cx: WasmtimeStoreContextMut<'_>,
+    externref: Option<&wasmtime_externref_t>,
+    out: &mut MaybeUninit<wasmtime_externref_t>,
+) -> bool {
+    let Some(e) = foreign_externref(cx.as_context_mut(), externref) else {
+        return false;
+    };
+    let Some(vtable) = e.vtable else {
+        return false;
+    };
+    let Some(clone) = vtable.clone else {
+        return false;
+    };
+    let foreign = ForeignExternRef {
+        foreign: ForeignData {
+            data: clone(e.data()),
+            finalizer: vtable.drop,
+        },
+        vtable: Some(vtable),
+    };
+    new_foreign_externref(cx, foreign, out)
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_externref_clone(
+    mut cx: WasmtimeStoreContextMut<'_>,
+    externref: Option<&wasmtime_externref_t>,
     out: &mut MaybeUninit<wasmtime_externref_t>,
 ) {
-    let externref = externref.and_then(|e| e.as_wasmtime()).map(|e| e.clone(cx));
-    crate::initialize(out, externref.into());
+    let externref = externref
+        .and_then(|e| e.as_wasmtime())
+        .map(|e| e.clone(&mut cx));
+    crate::initialize(out, wasmtime_externref_t::new(cx, externref));
 }
 
 #[unsafe(no_mangle)]
@@ -367,8 +686,8 @@ pub unsafe extern "C" fn wasmtime_externref_unroot(
     cx: WasmtimeStoreContextMut<'_>,
     val: Option<&mut MaybeUninit<wasmtime_externref_t>>,
 ) {
-    if let Some(val) = val.and_then(|v| v.assume_init_read().as_wasmtime()) {
-        val.unroot(cx);
+    if let Some(val) = val {
+        val.assume_init_read().unroot(cx);
     }
 }
 
@@ -391,5 +710,5 @@ pub unsafe extern "C" fn wasmtime_externref_from_raw(
     let mut scope = RootScope::new(cx);
     let rooted = ExternRef::from_raw(&mut scope, raw)
         .map(|e| e.to_manually_rooted(&mut scope).expect("in scope"));
-    crate::initialize(val, rooted.into());
+    crate::initialize(val, wasmtime_externref_t::new(scope, rooted));
 }
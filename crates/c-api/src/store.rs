@@ -84,6 +84,8 @@ pub struct WasmtimeStoreData {
     foreign: crate::ForeignData,
     #[cfg(feature = "wasi")]
     pub(crate) wasi: Option<wasmtime_wasi::preview1::WasiP1Ctx>,
+    #[cfg(feature = "wasi-http")]
+    pub(crate) wasi_http: Option<wasmtime_wasi_http::WasiHttpCtx>,
 
     /// Temporary storage for usage during a wasm->host call to store values
     /// in a slice we pass to the C API.
@@ -95,6 +97,39 @@ pub struct WasmtimeStoreData {
 
     /// Limits for the store.
     pub store_limits: StoreLimits,
+
+    /// Root scopes entered with `wasmtime_context_root_scope_push`.
+    pub(crate) root_scopes: crate::RootScopes,
+}
+
+// Components use the WASI context configured with `wasmtime_context_set_wasi`,
+// or one without any capabilities if there isn't one.
+#[cfg(feature = "wasi")]
+impl wasmtime_wasi::IoView for WasmtimeStoreData {
+    fn table(&mut self) -> &mut wasmtime::component::ResourceTable {
+        wasmtime_wasi::IoView::table(
+            self.wasi
+                .get_or_insert_with(|| wasmtime_wasi::WasiCtxBuilder::new().build_p1()),
+        )
+    }
+}
+
+#[cfg(feature = "wasi")]
+impl wasmtime_wasi::WasiView for WasmtimeStoreData {
+    fn ctx(&mut self) -> &mut wasmtime_wasi::WasiCtx {
+        wasmtime_wasi::WasiView::ctx(
+            self.wasi
+                .get_or_insert_with(|| wasmtime_wasi::WasiCtxBuilder::new().build_p1()),
+        )
+    }
+}
+
+#[cfg(feature = "wasi-http")]
+impl wasmtime_wasi_http::WasiHttpView for WasmtimeStoreData {
+    fn ctx(&mut self) -> &mut wasmtime_wasi_http::WasiHttpCtx {
+        self.wasi_http
+            .get_or_insert_with(wasmtime_wasi_http::WasiHttpCtx::new)
+    }
 }
 
 #[unsafe(no_mangle)]
@@ -110,9 +145,12 @@ pub extern "C" fn wasmtime_store_new(
                 foreign: ForeignData { data, finalizer },
                 #[cfg(feature = "wasi")]
                 wasi: None,
+                #[cfg(feature = "wasi-http")]
+                wasi_http: None,
                 hostcall_val_storage: Vec::new(),
                 wasm_val_storage: Vec::new(),
                 store_limits: StoreLimits::default(),
+                root_scopes: Default::default(),
             },
         ),
     })
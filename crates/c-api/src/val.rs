@@ -1,7 +1,7 @@
 use crate::r#ref::ref_to_val;
 use crate::{
     from_valtype, into_valtype, wasm_ref_t, wasm_valkind_t, wasmtime_anyref_t,
-    wasmtime_externref_t, wasmtime_valkind_t, WasmtimeStoreContextMut, WASM_I32,
+    wasmtime_externref_t, wasmtime_valkind_t, WasmtimeStoreContextMut, WasmtimeStoreData, WASM_I32,
 };
 use std::mem::{ManuallyDrop, MaybeUninit};
 use std::ptr;
@@ -202,7 +202,10 @@ impl wasmtime_val_t {
     /// that `val` is not require to be rooted in the store itself which would
     /// prevent GC. Callers should prefer this API where possible, creating a
     /// temporary `RootScope` when needed.
-    pub fn from_val(cx: &mut RootScope<impl AsContextMut>, val: Val) -> wasmtime_val_t {
+    pub fn from_val(
+        cx: &mut RootScope<impl AsContextMut<Data = WasmtimeStoreData>>,
+        val: Val,
+    ) -> wasmtime_val_t {
         Self::from_val_unscoped(cx, val)
     }
 
@@ -213,7 +216,10 @@ impl wasmtime_val_t {
     /// elsewhere on the stack. For example this is used when we call back out
     /// to the embedder. In such a situation we know we previously entered with
     /// some other call so the root scope is on the stack there.
-    pub fn from_val_unscoped(cx: impl AsContextMut, val: Val) -> wasmtime_val_t {
+    pub fn from_val_unscoped(
+        mut cx: impl AsContextMut<Data = WasmtimeStoreData>,
+        val: Val,
+    ) -> wasmtime_val_t {
         match val {
             Val::I32(i) => wasmtime_val_t {
                 kind: crate::WASMTIME_I32,
@@ -234,15 +240,19 @@ impl wasmtime_val_t {
             Val::AnyRef(a) => wasmtime_val_t {
                 kind: crate::WASMTIME_ANYREF,
                 of: wasmtime_val_union {
-                    anyref: ManuallyDrop::new(a.and_then(|a| a.to_manually_rooted(cx).ok()).into()),
+                    anyref: ManuallyDrop::new(wasmtime_anyref_t::new(
+                        &mut cx,
+                        a.and_then(|a| a.to_manually_rooted(&mut cx).ok()),
+                    )),
                 },
             },
             Val::ExternRef(e) => wasmtime_val_t {
                 kind: crate::WASMTIME_EXTERNREF,
                 of: wasmtime_val_union {
-                    externref: ManuallyDrop::new(
-                        e.and_then(|e| e.to_manually_rooted(cx).ok()).into(),
-                    ),
+                    externref: ManuallyDrop::new(wasmtime_externref_t::new(
+                        &mut cx,
+                        e.and_then(|e| e.to_manually_rooted(&mut cx).ok()),
+                    )),
                 },
             },
             Val::FuncRef(func) => wasmtime_val_t {
@@ -301,16 +311,8 @@ pub unsafe extern "C" fn wasmtime_val_unroot(
 ) {
     let val = val.assume_init_read();
     match val.kind {
-        crate::WASMTIME_ANYREF => {
-            if let Some(val) = ManuallyDrop::into_inner(val.of.anyref).as_wasmtime() {
-                val.unroot(cx);
-            }
-        }
-        crate::WASMTIME_EXTERNREF => {
-            if let Some(val) = ManuallyDrop::into_inner(val.of.externref).as_wasmtime() {
-                val.unroot(cx);
-            }
-        }
+        crate::WASMTIME_ANYREF => ManuallyDrop::into_inner(val.of.anyref).unroot(cx),
+        crate::WASMTIME_EXTERNREF => ManuallyDrop::into_inner(val.of.externref).unroot(cx),
         _ => {}
     }
 }
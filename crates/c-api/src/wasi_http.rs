@@ -0,0 +1,70 @@
+//! The wasi-http embedding API definitions for Wasmtime.
+
+use crate::{
+    handle_result, wasmtime_component_linker_t, wasmtime_error_t, WasmtimeStoreContextMut,
+};
+use wasmtime_wasi_http::body::BodyLimits;
+use wasmtime_wasi_http::{HttpVersionPolicy, WasiHttpCtx};
+
+#[repr(C)]
+pub struct wasmtime_wasi_http_config_t {
+    ctx: WasiHttpCtx,
+}
+
+wasmtime_c_api_macros::declare_own!(wasmtime_wasi_http_config_t);
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_wasi_http_config_new() -> Box<wasmtime_wasi_http_config_t> {
+    Box::new(wasmtime_wasi_http_config_t {
+        ctx: WasiHttpCtx::new(),
+    })
+}
+
+pub type wasmtime_wasi_http_version_policy_t = u8;
+pub const WASMTIME_WASI_HTTP_VERSION_HTTP1_ONLY: wasmtime_wasi_http_version_policy_t = 0;
+pub const WASMTIME_WASI_HTTP_VERSION_NEGOTIATE: wasmtime_wasi_http_version_policy_t = 1;
+pub const WASMTIME_WASI_HTTP_VERSION_HTTP2_PRIOR_KNOWLEDGE: wasmtime_wasi_http_version_policy_t = 2;
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_wasi_http_config_set_http_versions(
+    config: &mut wasmtime_wasi_http_config_t,
+    policy: wasmtime_wasi_http_version_policy_t,
+) -> bool {
+    let policy = match policy {
+        WASMTIME_WASI_HTTP_VERSION_HTTP1_ONLY => HttpVersionPolicy::Http1Only,
+        WASMTIME_WASI_HTTP_VERSION_NEGOTIATE => HttpVersionPolicy::Negotiate,
+        WASMTIME_WASI_HTTP_VERSION_HTTP2_PRIOR_KNOWLEDGE => HttpVersionPolicy::Http2PriorKnowledge,
+        _ => return false,
+    };
+    config.ctx.set_http_versions(policy);
+    true
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_wasi_http_config_set_max_body_bytes(
+    config: &mut wasmtime_wasi_http_config_t,
+    max_body_bytes: u64,
+) {
+    config.ctx.set_body_limits(BodyLimits {
+        max_body_bytes: Some(max_body_bytes),
+        ..config.ctx.body_limits()
+    });
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_context_set_wasi_http(
+    mut context: WasmtimeStoreContextMut<'_>,
+    config: Box<wasmtime_wasi_http_config_t>,
+) {
+    context.data_mut().wasi_http = Some(config.ctx);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_linker_add_wasi_http(
+    linker: &mut wasmtime_component_linker_t,
+) -> Option<Box<wasmtime_error_t>> {
+    handle_result(
+        wasmtime_wasi_http::add_only_http_to_linker_sync(&mut linker.linker),
+        |()| (),
+    )
+}
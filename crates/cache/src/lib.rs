@@ -83,9 +83,7 @@ impl<'config> ModuleCacheEntry<'config> {
             None => return compute(state),
         };
 
-        let mut hasher = Sha256Hasher(Sha256::new());
-        state.hash(&mut hasher);
-        let hash: [u8; 32] = hasher.0.finalize().into();
+        let hash = hash_state(state);
         // standard encoding uses '/' which can't be used for filename
         let hash = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(&hash);
 
@@ -107,6 +105,17 @@ impl<'config> ModuleCacheEntry<'config> {
     }
 }
 
+/// Computes the SHA-256 digest of `state`, which is the key that
+/// [`ModuleCacheEntry`] stores data for `state` under.
+///
+/// This is stable across processes running the same build, so it may be used
+/// to key caches shared between multiple hosts.
+pub fn hash_state<T: Hash + ?Sized>(state: &T) -> [u8; 32] {
+    let mut hasher = Sha256Hasher(Sha256::new());
+    state.hash(&mut hasher);
+    hasher.0.finalize().into()
+}
+
 impl<'config> ModuleCacheEntryInner<'config> {
     fn new(compiler_name: &str, cache_config: &'config CacheConfig) -> Self {
         // If debug assertions are enabled then assume that we're some sort of
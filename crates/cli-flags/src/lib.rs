@@ -45,6 +45,20 @@ wasmtime_option_group! {
         /// Register allocator algorithm choice.
         pub regalloc_algorithm: Option<wasmtime::RegallocAlgorithm>,
 
+        /// Maximum size, in bytes of wasm code, of functions to inline into
+        /// their callers; 0 disables inlining. Requires `-D backtrace=n` and
+        /// `-D address-map=n`. (default: 0)
+        pub inline_threshold: Option<u32>,
+
+        /// Maximum depth of nested calls to inline into a single function.
+        /// (default: 2)
+        pub inline_max_depth: Option<u32>,
+
+        /// Remove bounds checks and other conditional traps which are
+        /// implied by a dominating check. Checks with Spectre mitigations are
+        /// never removed. (default: no)
+        pub redundant_check_elimination: Option<bool>,
+
         /// Do not allow Wasm linear memories to move in the host process's
         /// address space.
         pub memory_may_move: Option<bool>,
@@ -234,6 +248,8 @@ wasmtime_option_group! {
         pub debug_info: Option<bool>,
         /// Configure whether compiled code can map native addresses to wasm.
         pub address_map: Option<bool>,
+        /// Configure whether traps capture a backtrace of wasm frames.
+        pub backtrace: Option<bool>,
         /// Configure whether logging is enabled.
         pub logging: Option<bool>,
         /// Configure whether logs are emitted to files
@@ -313,6 +329,15 @@ wasmtime_option_group! {
         /// function while running on a continuation stack.
         /// Must be smaller than the `wasmfx_stack_size` option above.
         pub wasmfx_red_zone_size: Option<usize>,
+        /// Initial accessible size of stacks created with cont.new
+        /// instructions, which then grow on demand up to `wasmfx_stack_size`.
+        pub wasmfx_initial_stack_size: Option<usize>,
+        /// Maximum number of stacks of returned continuations that each store
+        /// keeps for reuse.
+        pub wasmfx_stack_cache_size: Option<usize>,
+        /// Whether suspend handlers installed by `resume` are looked up through
+        /// tag-indexed dispatch tables instead of a linear search.
+        pub wasmfx_tag_dispatch_tables: Option<bool>,
         /// Configures support for all WebAssembly proposals implemented.
         pub all_proposals: Option<bool>,
         /// Configure support for the bulk memory proposal.
@@ -423,6 +448,11 @@ wasmtime_option_group! {
         pub udp: Option<bool>,
         /// Enable WASI APIs marked as: @unstable(feature = network-error-code)
         pub network_error_code: Option<bool>,
+        /// Enable WASI APIs marked as: @unstable(feature = udp-multicast)
+        ///
+        /// This additionally allows UDP sockets to join multicast groups and
+        /// to reuse addresses.
+        pub udp_multicast: Option<bool>,
         /// Allows imports from the `wasi_unstable` core wasm module.
         pub preview0: Option<bool>,
         /// Inherit all environment variables from the parent process.
@@ -612,6 +642,21 @@ impl CommonOptions {
             algo => config.cranelift_regalloc_algorithm(algo),
             _ => err,
         }
+        match_feature! {
+            ["cranelift" : self.opts.inline_threshold]
+            bytes => config.cranelift_inline_threshold(bytes),
+            _ => err,
+        }
+        match_feature! {
+            ["cranelift" : self.opts.inline_max_depth]
+            depth => config.cranelift_inline_max_depth(depth),
+            _ => err,
+        }
+        match_feature! {
+            ["cranelift" : self.opts.redundant_check_elimination]
+            enable => config.cranelift_redundant_check_elimination(enable),
+            true => err,
+        }
         match_feature! {
             ["cranelift" : self.wasm.nan_canonicalization]
             enable => config.cranelift_nan_canonicalization(enable),
@@ -715,6 +760,9 @@ impl CommonOptions {
         if let Some(enable) = self.debug.address_map {
             config.generate_address_map(enable);
         }
+        if let Some(enable) = self.debug.backtrace {
+            config.wasm_backtrace(enable);
+        }
         if let Some(enable) = self.opts.memory_init_cow {
             config.memory_init_cow(enable);
         }
@@ -731,6 +779,15 @@ impl CommonOptions {
         if let Some(wasmfx_red_zone_size) = self.wasm.wasmfx_red_zone_size {
             config.wasmfx_red_zone_size(wasmfx_red_zone_size);
         }
+        if let Some(wasmfx_initial_stack_size) = self.wasm.wasmfx_initial_stack_size {
+            config.wasmfx_initial_stack_size(wasmfx_initial_stack_size);
+        }
+        if let Some(wasmfx_stack_cache_size) = self.wasm.wasmfx_stack_cache_size {
+            config.wasmfx_stack_cache_size(wasmfx_stack_cache_size);
+        }
+        if let Some(enable) = self.wasm.wasmfx_tag_dispatch_tables {
+            config.wasmfx_tag_dispatch_tables(enable);
+        }
 
         match_feature! {
             ["pooling-allocator" : self.opts.pooling_allocator.or(pooling_allocator_default)]
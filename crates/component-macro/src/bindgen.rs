@@ -9,7 +9,7 @@ use syn::parse::{Error, Parse, ParseStream, Result};
 use syn::punctuated::Punctuated;
 use syn::{braced, token, Token};
 use wasmtime_wit_bindgen::{AsyncConfig, Opts, Ownership, TrappableError, TrappableImports};
-use wit_parser::{PackageId, Resolve, UnresolvedPackageGroup, WorldId};
+use wit_parser::{PackageId, PackageName, Resolve, UnresolvedPackageGroup, WorldId};
 
 pub struct Config {
     opts: Opts,
@@ -81,6 +81,9 @@ impl Parse for Config {
         let mut world = None;
         let mut inline = None;
         let mut paths = Vec::new();
+        let mut deps = Vec::new();
+        let mut features = Vec::new();
+        let mut all_features = None;
         let mut async_configured = false;
         let mut include_generated_code_from_file = false;
 
@@ -93,6 +96,13 @@ impl Parse for Config {
                     Opt::Path(p) => {
                         paths.extend(p.into_iter().map(|p| p.value()));
                     }
+                    Opt::Deps(p) => {
+                        deps.extend(p.into_iter().map(|p| p.value()));
+                    }
+                    Opt::Features(f) => {
+                        features.extend(f.into_iter().map(|f| f.value()));
+                    }
+                    Opt::AllFeatures(val) => all_features = Some(val),
                     Opt::World(s) => {
                         if world.is_some() {
                             return Err(Error::new(s.span(), "cannot specify second world"));
@@ -152,6 +162,7 @@ impl Parse for Config {
                     Opt::Stringify(val) => opts.stringify = val,
                     Opt::SkipMutForwardingImpls(val) => opts.skip_mut_forwarding_impls = val,
                     Opt::RequireStoreDataSend(val) => opts.require_store_data_send = val,
+                    Opt::Mocks(val) => opts.mocks = val,
                     Opt::WasmtimeCrate(f) => {
                         opts.wasmtime_crate = Some(f.into_token_stream().to_string())
                     }
@@ -164,7 +175,11 @@ impl Parse for Config {
                 paths.push(input.parse::<syn::LitStr>()?.value());
             }
         }
-        let (resolve, pkgs, files) = parse_source(&paths, &inline)
+        // Unless specific features are requested, all `@unstable` items are
+        // enabled, matching the behavior before feature gating was
+        // configurable.
+        let all_features = all_features.unwrap_or(features.is_empty());
+        let (resolve, pkgs, files) = parse_source(&paths, &inline, &deps, &features, all_features)
             .map_err(|err| Error::new(call_site, format!("{err:?}")))?;
 
         let world = select_world(&resolve, &pkgs, world.as_deref())
@@ -182,13 +197,22 @@ impl Parse for Config {
 fn parse_source(
     paths: &Vec<String>,
     inline: &Option<String>,
+    deps: &[String],
+    features: &[String],
+    all_features: bool,
 ) -> anyhow::Result<(Resolve, Vec<PackageId>, Vec<PathBuf>)> {
     let mut resolve = Resolve::default();
-    resolve.all_features = true;
+    resolve.all_features = all_features;
+    resolve.features.extend(features.iter().cloned());
     let mut files = Vec::new();
     let mut pkgs = Vec::new();
     let root = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
 
+    if !deps.is_empty() {
+        let deps = deps.iter().map(|d| root.join(d)).collect::<Vec<_>>();
+        push_deps(&mut resolve, &mut files, &deps)?;
+    }
+
     let parse = |resolve: &mut Resolve,
                  files: &mut Vec<PathBuf>,
                  pkgs: &mut Vec<PackageId>,
@@ -203,7 +227,9 @@ fn parse_source(
                 Ok(p) => p,
                 Err(_) => p.to_path_buf(),
             };
-            let (pkg, sources) = resolve.push_path(normalized_path)?;
+            let (pkg, sources) = resolve
+                .push_path(&normalized_path)
+                .map_err(|e| with_known_packages(e, resolve, &normalized_path))?;
             pkgs.push(pkg);
             files.extend(sources.paths().map(|p| p.to_owned()));
         }
@@ -215,7 +241,11 @@ fn parse_source(
     }
 
     if let Some(inline) = inline {
-        pkgs.push(resolve.push_group(UnresolvedPackageGroup::parse("macro-input", inline)?)?);
+        let group = UnresolvedPackageGroup::parse("macro-input", inline)?;
+        let pkg = resolve
+            .push_group(group)
+            .map_err(|e| with_known_packages(e, &resolve, Path::new("macro-input")))?;
+        pkgs.push(pkg);
     }
 
     if pkgs.is_empty() {
@@ -225,10 +255,185 @@ fn parse_source(
     Ok((resolve, pkgs, files))
 }
 
+/// Parse every WIT package found in the `deps` search paths and push them into
+/// `resolve`, ordered such that each package's dependencies are pushed before
+/// it.
+///
+/// Each search path may either be a single WIT package (a `*.wit` file or a
+/// directory of `*.wit` files) or, like a `deps` folder, a directory whose
+/// entries are packages.
+fn push_deps(
+    resolve: &mut Resolve,
+    files: &mut Vec<PathBuf>,
+    deps: &[PathBuf],
+) -> anyhow::Result<()> {
+    let mut groups = Vec::new();
+    for dir in deps {
+        if !dir.exists() {
+            anyhow::bail!("dependency path does not exist: {}", dir.display());
+        }
+        if dir.is_file() || has_wit_files(dir)? {
+            groups.push(UnresolvedPackageGroup::parse_path(dir)?);
+            continue;
+        }
+        let mut entries = dir
+            .read_dir()
+            .and_then(|i| i.collect::<std::io::Result<Vec<_>>>())
+            .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", dir.display()))?;
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            let path = entry.path();
+            if path.is_dir() || path.extension().is_some_and(|e| e == "wit") {
+                groups.push(UnresolvedPackageGroup::parse_path(&path)?);
+            }
+        }
+    }
+
+    // Map each package name to the group defining it so the groups can be
+    // visited in topological order.
+    let mut defined = HashMap::new();
+    for (i, group) in groups.iter().enumerate() {
+        for pkg in group.nested.iter().chain([&group.main]) {
+            if let Some(prev) = defined.insert(pkg.name.clone(), i) {
+                if prev != i {
+                    anyhow::bail!(
+                        "package `{}` is defined in more than one dependency path",
+                        pkg.name
+                    );
+                }
+            }
+        }
+    }
+
+    fn visit(
+        i: usize,
+        groups: &[UnresolvedPackageGroup],
+        defined: &HashMap<PackageName, usize>,
+        resolve: &Resolve,
+        deps: &[PathBuf],
+        visiting: &mut HashSet<usize>,
+        order: &mut Vec<usize>,
+    ) -> anyhow::Result<()> {
+        if order.contains(&i) {
+            return Ok(());
+        }
+        if !visiting.insert(i) {
+            anyhow::bail!(
+                "package `{}` has a cyclic dependency on itself",
+                groups[i].main.name
+            );
+        }
+        let group = &groups[i];
+        for pkg in group.nested.iter().chain([&group.main]) {
+            for dep in pkg.foreign_deps.keys() {
+                match defined.get(dep) {
+                    Some(j) if *j == i => {}
+                    Some(j) => visit(*j, groups, defined, resolve, deps, visiting, order)?,
+                    None if resolve.package_names.contains_key(dep) => {}
+                    None => anyhow::bail!(
+                        "package `{dep}`, used by `{}`, was not found in any \
+                         dependency path: [{}]",
+                        pkg.name,
+                        deps.iter()
+                            .map(|d| d.display().to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                }
+            }
+        }
+        order.push(i);
+        Ok(())
+    }
+
+    let mut order = Vec::new();
+    let mut visiting = HashSet::new();
+    for i in 0..groups.len() {
+        visit(
+            i,
+            &groups,
+            &defined,
+            resolve,
+            deps,
+            &mut visiting,
+            &mut order,
+        )?;
+    }
+
+    let mut groups = groups.into_iter().map(Some).collect::<Vec<_>>();
+    for i in order {
+        let group = groups[i].take().unwrap();
+        files.extend(group.source_map.source_files().map(|p| p.to_owned()));
+        resolve.push_group(group)?;
+    }
+    Ok(())
+}
+
+/// Returns whether `dir` directly contains `*.wit` files, in which case it is a
+/// package itself rather than a directory of packages.
+fn has_wit_files(dir: &Path) -> anyhow::Result<bool> {
+    for entry in dir
+        .read_dir()
+        .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|e| e == "wit") {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Augment a resolution error for `path` with the list of packages that were
+/// known at the time, which helps track down a missing `use` or `include`
+/// target.
+fn with_known_packages(err: anyhow::Error, resolve: &Resolve, path: &Path) -> anyhow::Error {
+    let known = resolve
+        .package_names
+        .keys()
+        .map(|name| format!("`{name}`"))
+        .collect::<Vec<_>>();
+    let known = if known.is_empty() {
+        "none".to_string()
+    } else {
+        known.join(", ")
+    };
+    err.context(format!(
+        "failed to resolve WIT at {} (known packages: {known}); \
+         use the `deps` option to add search paths for external packages",
+        path.display()
+    ))
+}
+
 fn select_world(
     resolve: &Resolve,
     pkgs: &[PackageId],
     world: Option<&str>,
+) -> anyhow::Result<WorldId> {
+    _select_world(resolve, pkgs, world).map_err(|e| {
+        let available = resolve
+            .worlds
+            .iter()
+            .filter_map(|(_, w)| {
+                let pkg = w.package?;
+                Some(format!("`{}/{}`", resolve.packages[pkg].name, w.name))
+            })
+            .collect::<Vec<_>>();
+        let requested = match world {
+            Some(name) => format!("`{name}`"),
+            None => "the default world".to_string(),
+        };
+        e.context(format!(
+            "failed to select {requested}; available worlds: [{}]",
+            available.join(", ")
+        ))
+    })
+}
+
+fn _select_world(
+    resolve: &Resolve,
+    pkgs: &[PackageId],
+    world: Option<&str>,
 ) -> anyhow::Result<WorldId> {
     if pkgs.len() == 1 {
         resolve.select_world(pkgs[0], world)
@@ -265,6 +470,9 @@ fn select_world(
 mod kw {
     syn::custom_keyword!(inline);
     syn::custom_keyword!(path);
+    syn::custom_keyword!(deps);
+    syn::custom_keyword!(features);
+    syn::custom_keyword!(all_features);
     syn::custom_keyword!(tracing);
     syn::custom_keyword!(verbose_tracing);
     syn::custom_keyword!(trappable_error_type);
@@ -279,6 +487,7 @@ mod kw {
     syn::custom_keyword!(stringify);
     syn::custom_keyword!(skip_mut_forwarding_impls);
     syn::custom_keyword!(require_store_data_send);
+    syn::custom_keyword!(mocks);
     syn::custom_keyword!(wasmtime_crate);
     syn::custom_keyword!(include_generated_code_from_file);
 }
@@ -286,6 +495,9 @@ mod kw {
 enum Opt {
     World(syn::LitStr),
     Path(Vec<syn::LitStr>),
+    Deps(Vec<syn::LitStr>),
+    Features(Vec<syn::LitStr>),
+    AllFeatures(bool),
     Inline(syn::LitStr),
     Tracing(bool),
     VerboseTracing(bool),
@@ -299,6 +511,7 @@ enum Opt {
     Stringify(bool),
     SkipMutForwardingImpls(bool),
     RequireStoreDataSend(bool),
+    Mocks(bool),
     WasmtimeCrate(syn::Path),
     IncludeGeneratedCodeFromFile(bool),
 }
@@ -326,6 +539,24 @@ impl Parse for Opt {
             };
 
             Ok(Opt::Path(paths))
+        } else if l.peek(kw::deps) {
+            input.parse::<kw::deps>()?;
+            input.parse::<Token![:]>()?;
+            let contents;
+            syn::bracketed!(contents in input);
+            let list = Punctuated::<_, Token![,]>::parse_terminated(&contents)?;
+            Ok(Opt::Deps(list.into_iter().collect()))
+        } else if l.peek(kw::features) {
+            input.parse::<kw::features>()?;
+            input.parse::<Token![:]>()?;
+            let contents;
+            syn::bracketed!(contents in input);
+            let list = Punctuated::<_, Token![,]>::parse_terminated(&contents)?;
+            Ok(Opt::Features(list.into_iter().collect()))
+        } else if l.peek(kw::all_features) {
+            input.parse::<kw::all_features>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::AllFeatures(input.parse::<syn::LitBool>()?.value))
         } else if l.peek(kw::inline) {
             input.parse::<kw::inline>()?;
             input.parse::<Token![:]>()?;
@@ -477,6 +708,10 @@ impl Parse for Opt {
             Ok(Opt::RequireStoreDataSend(
                 input.parse::<syn::LitBool>()?.value,
             ))
+        } else if l.peek(kw::mocks) {
+            input.parse::<kw::mocks>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::Mocks(input.parse::<syn::LitBool>()?.value))
         } else if l.peek(kw::wasmtime_crate) {
             input.parse::<kw::wasmtime_crate>()?;
             input.parse::<Token![:]>()?;
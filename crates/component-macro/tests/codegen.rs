@@ -22,6 +22,19 @@ macro_rules! gentest {
                     }
                 });
             }
+            mod mocks {
+                wasmtime::component::bindgen!({
+                    path: $path,
+                    mocks: true,
+                });
+            }
+            mod async_mocks {
+                wasmtime::component::bindgen!({
+                    path: $path,
+                    async: true,
+                    mocks: true,
+                });
+            }
         }
     };
 }
@@ -712,3 +725,64 @@ mod paths {
         });
     }
 }
+
+mod features {
+    mod subset {
+        wasmtime::component::bindgen!({
+            path: "tests/codegen/unstable-features.wit",
+            features: ["experimental-world", "experimental-world-function-import"],
+        });
+
+        // Only `foo` is enabled; the gated `baz` resource is not part of the
+        // imports.
+        struct Host;
+
+        impl TheWorldImports for Host {
+            fn foo(&mut self) {}
+        }
+    }
+
+    mod none {
+        wasmtime::component::bindgen!({
+            inline: "
+                package test:features;
+
+                world test {
+                    import a: func();
+                    @unstable(feature = gated)
+                    import b: func();
+                }
+            ",
+            all_features: false,
+        });
+
+        struct Host;
+
+        impl TestImports for Host {
+            fn a(&mut self) {}
+        }
+    }
+}
+
+mod deps {
+    mod search_path {
+        wasmtime::component::bindgen!({
+            inline: "
+                package test:deps;
+
+                world test {
+                    include deps:base/base;
+                }
+            ",
+            deps: ["tests/wit-deps"],
+        });
+
+        struct Host;
+
+        impl deps::base::api::Host for Host {
+            fn lookup(&mut self, id: deps::shared::types::Id) -> String {
+                id.to_string()
+            }
+        }
+    }
+}
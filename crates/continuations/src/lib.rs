@@ -49,6 +49,15 @@ pub struct WasmFXConfig {
     /// function while running on a continuation stack.
     /// Must be smaller than the value of `stack_size` above.
     pub red_zone_size: usize,
+
+    /// If set, continuation stacks initially only make this many bytes
+    /// accessible, and grow on demand up to `stack_size` when execution
+    /// reaches beyond the accessible part.
+    pub initial_stack_size: Option<usize>,
+
+    /// The maximum number of stacks of returned continuations that each store
+    /// keeps around to reuse for new continuations. Zero disables caching.
+    pub stack_cache_size: usize,
 }
 
 /// This type is used to save (and subsequently restore) a subset of the data in
@@ -95,6 +104,10 @@ pub struct CommonStackInformation {
     /// VMTagDefinition).
     /// Further, `first_switch_handler_index` (see below) is set to n (i.e., the
     /// 0-based index of the first switch handler).
+    ///
+    /// With `wasmfx_tag_dispatch_tables` enabled, the suspend handlers may
+    /// instead form a dispatch table laid out by the compiler, which is
+    /// indicated by setting the highest bit of `first_switch_handler_index`.
     pub handlers: HandlerList,
 
     /// Only used when state is `Parent`. See documentation of `handlers` above.
@@ -315,7 +328,7 @@ pub mod offsets {
 
     /// Size of wasmtime_runtime::continuation::FiberStack.
     /// We test there that this value is correct.
-    pub const FIBER_STACK_SIZE: usize = 3 * core::mem::size_of::<usize>();
+    pub const FIBER_STACK_SIZE: usize = 4 * core::mem::size_of::<usize>();
 
     /// Size of type `wasmtime_runtime::continuation::StackChain`.
     /// We test there that this value is correct.
@@ -1,3 +1,4 @@
+use self::inline::WasmInliner;
 use crate::debug::DwarfSectionRelocTarget;
 use crate::func_environ::FuncEnvironment;
 use crate::translate::FuncTranslator;
@@ -14,6 +15,7 @@ use cranelift_codegen::isa::{
     OwnedTargetIsa, TargetIsa,
 };
 use cranelift_codegen::print_errors::pretty_error;
+use cranelift_codegen::settings::OptLevel;
 use cranelift_codegen::{CompiledCode, Context};
 use cranelift_entity::PrimaryMap;
 use cranelift_frontend::FunctionBuilder;
@@ -25,16 +27,17 @@ use std::collections::HashMap;
 use std::mem;
 use std::path;
 use std::sync::{Arc, Mutex};
-use wasmparser::{FuncValidatorAllocations, FunctionBody};
+use wasmparser::{FuncToValidate, FuncValidatorAllocations, FunctionBody};
 use wasmtime_environ::{
     AddressMapSection, BuiltinFunctionIndex, CacheStore, CompileError, DefinedFuncIndex, FlagValue,
-    FunctionBodyData, FunctionLoc, HostCall, ModuleTranslation, ModuleTypesBuilder, PtrSize,
-    RelocationTarget, StackMapInformation, StaticModuleIndex, TrapEncodingBuilder, TrapSentinel,
-    TripleExt, Tunables, VMOffsets, WasmFuncType, WasmFunctionInfo, WasmValType,
+    FuncIndex, FunctionBodyData, FunctionLoc, HostCall, ModuleTranslation, ModuleTypesBuilder,
+    PtrSize, RelocationTarget, StackMapInformation, StaticModuleIndex, TrapEncodingBuilder,
+    TrapSentinel, TripleExt, Tunables, VMOffsets, WasmFuncType, WasmFunctionInfo, WasmValType,
 };
 
 #[cfg(feature = "component-model")]
 mod component;
+mod inline;
 
 struct IncrementalCacheContext {
     #[cfg(feature = "incremental-cache")]
@@ -170,6 +173,7 @@ impl Compiler {
                 // This is the signal that a special `call_indirect_host`
                 // opcode is used to jump from pulley to the host.
                 colocated: false,
+                preserves_vmctx: false,
             });
             let mut raw_args = vec![addr];
             raw_args.extend_from_slice(args);
@@ -264,8 +268,14 @@ impl wasmtime_environ::Compiler for Compiler {
             }
         }
         let FunctionBodyData { validator, body } = input;
-        let mut validator =
-            validator.into_validator(mem::take(&mut compiler.cx.validator_allocations));
+        let FuncToValidate {
+            resources,
+            index,
+            ty,
+            features,
+        } = validator;
+        let mut validator = FuncToValidate::new(index, ty, &resources, features)
+            .into_validator(mem::take(&mut compiler.cx.validator_allocations));
         compiler.cx.func_translator.translate_body(
             &mut validator,
             body.clone(),
@@ -273,6 +283,31 @@ impl wasmtime_environ::Compiler for Compiler {
             &mut func_env,
         )?;
 
+        // Small functions defined in this module are inlined into this one,
+        // translating them from wasm again for each call. Value labels and
+        // facts aren't carried over into inlined code, so this is skipped when
+        // native debuginfo or proof-carrying code is enabled.
+        if self.tunables.inline_threshold > 0
+            && isa.flags().opt_level() != OptLevel::None
+            && !self.tunables.generate_native_debuginfo
+            && !isa.flags().enable_pcc()
+        {
+            let inliner =
+                WasmInliner::new(self, translation, types, &resources, features, func_index);
+            context
+                .inline(isa, inliner)
+                .map_err(|error| CompileError::Codegen(pretty_error(&context.func, error)))?;
+        }
+
+        // NaN canonicalization may be requested for just this module, in which
+        // case the pass won't be run as part of compilation since the ISA's
+        // flags don't enable it.
+        if translation.nan_canonicalization && !isa.flags().enable_nan_canonicalization() {
+            context
+                .canonicalize_nans(isa)
+                .map_err(|error| CompileError::Codegen(pretty_error(&context.func, error)))?;
+        }
+
         let (info, func) = compiler.finish_with_info(
             Some((&body, &self.tunables)),
             &format!("wasm_func_{}", func_index.as_u32()),
@@ -1050,8 +1085,12 @@ impl FunctionCompiler<'_> {
             }
         }
 
+        // The frame size reported by Cranelift doesn't include the return
+        // address and saved frame pointer, so account for those too.
+        let frame_size = compiled_code.frame_size + 2 * u32::from(isa.pointer_bytes());
         let stack_maps =
             clif_to_env_stack_maps(compiled_code.buffer.take_user_stack_maps().into_iter());
+        let direct_calls = direct_calls(&context.func, &compiled_function);
         compiled_function
             .set_sized_stack_slots(std::mem::take(&mut context.func.sized_stack_slots));
         self.compiler.contexts.lock().unwrap().push(self.cx);
@@ -1060,12 +1099,38 @@ impl FunctionCompiler<'_> {
             WasmFunctionInfo {
                 start_srcloc: compiled_function.metadata().address_map.start_srcloc,
                 stack_maps: stack_maps.into(),
+                frame_size,
+                direct_calls,
             },
             compiled_function,
         ))
     }
 }
 
+/// Returns the wasm functions that `func` calls directly, or `None` if it also
+/// makes calls whose callee isn't known statically, such as with
+/// `call_indirect`, to imports, or to the host.
+fn direct_calls(func: &ir::Function, compiled: &CompiledFunction) -> Option<Box<[FuncIndex]>> {
+    for block in func.layout.blocks() {
+        for inst in func.layout.block_insts(block) {
+            match func.dfg.insts[inst].opcode() {
+                ir::Opcode::CallIndirect | ir::Opcode::ReturnCallIndirect => return None,
+                _ => {}
+            }
+        }
+    }
+    let mut calls = Vec::new();
+    for reloc in compiled.relocations() {
+        match reloc.reloc_target {
+            RelocationTarget::Wasm(index) => calls.push(index),
+            _ => return None,
+        }
+    }
+    calls.sort();
+    calls.dedup();
+    Some(calls.into())
+}
+
 /// Convert from Cranelift's representation of a stack map to Wasmtime's
 /// compiler-agnostic representation.
 fn clif_to_env_stack_maps(
@@ -1108,6 +1173,7 @@ fn declare_and_call(
         name,
         signature,
         colocated: true,
+        preserves_vmctx: false,
     });
     builder.ins().call(callee, &args)
 }
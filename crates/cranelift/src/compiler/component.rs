@@ -115,6 +115,19 @@ impl<'a> TrampolineCompiler<'a> {
                     me.raise_if_host_trapped(rets.pop().unwrap());
                 })
             }
+            Trampoline::FutureTransfer => {
+                self.translate_resource_libcall(host::future_transfer, |me, rets| {
+                    rets[0] = me.raise_if_resource_trapped(rets[0]);
+                })
+            }
+            Trampoline::StreamTransfer => {
+                self.translate_resource_libcall(host::stream_transfer, |me, rets| {
+                    rets[0] = me.raise_if_resource_trapped(rets[0]);
+                })
+            }
+            Trampoline::TraceRealloc => {
+                self.translate_resource_libcall(host::trace_realloc, |_, _| {})
+            }
         }
     }
 
@@ -792,7 +805,7 @@ impl TrampolineCompiler<'_> {
         let from_base = self.load_runtime_memory_base(vmctx, from);
         let to_base = self.load_runtime_memory_base(vmctx, to);
 
-        let mut args = Vec::new();
+        let mut args = vec![vmctx];
 
         let uses_retptr = match op {
             Transcode::Utf16ToUtf8
@@ -0,0 +1,130 @@
+//! Inlining of small wasm functions into their callers.
+
+use crate::compiler::Compiler;
+use crate::func_environ::FuncEnvironment;
+use crate::translate::FuncTranslator;
+use crate::{wasm_call_signature, NS_WASM_FUNC};
+use anyhow::Result;
+use cranelift_codegen::inline::{Inline, InlineCommand};
+use cranelift_codegen::ir::{self, ExternalName, UserExternalName, UserFuncName};
+use cranelift_codegen::Context;
+use std::borrow::Cow;
+use wasmparser::{FuncToValidate, ValidatorResources, WasmFeatures};
+use wasmtime_environ::{FuncIndex, InlineCandidate, ModuleTranslation, ModuleTypesBuilder};
+
+/// Provides the bodies of the small functions of a module for inlining into
+/// their callers, see `Tunables::inline_threshold`.
+///
+/// Callees are translated from wasm again at each call site and are inlined
+/// into before they're handed out, up to `Tunables::inline_max_depth` calls
+/// deep.
+pub(crate) struct WasmInliner<'a> {
+    compiler: &'a Compiler,
+    translation: &'a ModuleTranslation<'a>,
+    types: &'a ModuleTypesBuilder,
+    resources: &'a ValidatorResources,
+    features: WasmFeatures,
+    translator: FuncTranslator,
+
+    /// The function being compiled followed by the functions currently being
+    /// inlined into it, which are never inlined again so that recursion
+    /// terminates.
+    stack: Vec<FuncIndex>,
+}
+
+impl<'a> WasmInliner<'a> {
+    pub fn new(
+        compiler: &'a Compiler,
+        translation: &'a ModuleTranslation<'a>,
+        types: &'a ModuleTypesBuilder,
+        resources: &'a ValidatorResources,
+        features: WasmFeatures,
+        func_index: FuncIndex,
+    ) -> Self {
+        WasmInliner {
+            compiler,
+            translation,
+            types,
+            resources,
+            features,
+            translator: FuncTranslator::new(),
+            stack: vec![func_index],
+        }
+    }
+
+    /// Translates the body of `index` to CLIF, with its own calls inlined.
+    fn translate(
+        &mut self,
+        index: FuncIndex,
+        candidate: &InlineCandidate<'_>,
+    ) -> Result<ir::Function> {
+        let compiler = self.compiler;
+        let isa = &*compiler.isa;
+        let sig = self.translation.module.functions[index].signature;
+        let wasm_func_ty = self.types[sig].unwrap_func();
+
+        let mut func = ir::Function::with_name_signature(
+            UserFuncName::User(UserExternalName {
+                namespace: NS_WASM_FUNC,
+                index: index.as_u32(),
+            }),
+            wasm_call_signature(isa, wasm_func_ty, &compiler.tunables),
+        );
+        let mut func_env =
+            FuncEnvironment::new(compiler, self.translation, self.types, wasm_func_ty);
+        let mut validator =
+            FuncToValidate::new(index.as_u32(), candidate.ty, self.resources, self.features)
+                .into_validator(Default::default());
+        self.translator.translate_body(
+            &mut validator,
+            candidate.body.clone(),
+            &mut func,
+            &mut func_env,
+        )?;
+
+        self.stack.push(index);
+        let mut context = Context::for_function(func);
+        let result = context.inline(isa, &mut *self);
+        self.stack.pop();
+        result?;
+        Ok(context.func)
+    }
+}
+
+impl Inline for WasmInliner<'_> {
+    fn inline(
+        &mut self,
+        caller: &ir::Function,
+        _inst: ir::Inst,
+        callee: ir::FuncRef,
+    ) -> InlineCommand<'_> {
+        let name = match caller.dfg.ext_funcs[callee].name {
+            ExternalName::User(name) => &caller.params.user_named_funcs()[name],
+            _ => return InlineCommand::KeepCall,
+        };
+        if name.namespace != NS_WASM_FUNC {
+            return InlineCommand::KeepCall;
+        }
+        let index = FuncIndex::from_u32(name.index);
+        if self.stack.contains(&index)
+            || self.stack.len() > self.compiler.tunables.inline_max_depth as usize
+        {
+            return InlineCommand::KeepCall;
+        }
+        let translation = self.translation;
+        let candidate = match translation.module.defined_func_index(index) {
+            Some(def) => match translation.inline_candidates.get(&def) {
+                Some(candidate) => candidate,
+                None => return InlineCommand::KeepCall,
+            },
+            None => return InlineCommand::KeepCall,
+        };
+        match self.translate(index, candidate) {
+            Ok(func) => InlineCommand::Inline(Cow::Owned(func)),
+            Err(e) => {
+                log::debug!("not inlining {index:?}: {e:?}");
+                InlineCommand::KeepCall
+            }
+        }
+    }
+}
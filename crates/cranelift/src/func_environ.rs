@@ -41,6 +41,10 @@ cfg_if::cfg_if! {
 pub(crate) struct BuiltinFunctions {
     types: BuiltinFunctionSignatures,
 
+    /// Whether to declare builtins which can't modify `vmctx`-resident
+    /// fields as `preserves_vmctx`.
+    vmctx_invariant_loads: bool,
+
     builtins: [Option<ir::FuncRef>; BuiltinFunctionIndex::len() as usize],
 }
 
@@ -48,6 +52,7 @@ impl BuiltinFunctions {
     fn new(compiler: &Compiler) -> Self {
         Self {
             types: BuiltinFunctionSignatures::new(compiler),
+            vmctx_invariant_loads: compiler.isa().flags().enable_vmctx_invariant_loads(),
             builtins: [None; BuiltinFunctionIndex::len() as usize],
         }
     }
@@ -67,12 +72,35 @@ impl BuiltinFunctions {
             name,
             signature,
             colocated: true,
+            preserves_vmctx: self.vmctx_invariant_loads && builtin_preserves_vmctx(index),
         });
         *cache = Some(f);
         f
     }
 }
 
+/// Can the given builtin be declared `preserves_vmctx`?
+///
+/// Loads of linear memory and table bases and bounds are tagged with the
+/// `vmctx` alias region when `enable_vmctx_invariant_loads` is set, so this
+/// must be `false` for any builtin which may grow a memory or table, or which
+/// may run arbitrary Wasm or host code that could do so.
+fn builtin_preserves_vmctx(index: BuiltinFunctionIndex) -> bool {
+    [
+        BuiltinFunctionIndex::table_copy(),
+        BuiltinFunctionIndex::table_init(),
+        BuiltinFunctionIndex::elem_drop(),
+        BuiltinFunctionIndex::memory_copy(),
+        BuiltinFunctionIndex::memory_fill(),
+        BuiltinFunctionIndex::memory_init(),
+        BuiltinFunctionIndex::ref_func(),
+        BuiltinFunctionIndex::data_drop(),
+        BuiltinFunctionIndex::table_get_lazy_init_func_ref(),
+        BuiltinFunctionIndex::table_fill_func_ref(),
+    ]
+    .contains(&index)
+}
+
 // Generate helper methods on `BuiltinFunctions` above for each named builtin
 // as well.
 macro_rules! declare_function_signatures {
@@ -166,6 +194,10 @@ pub struct FuncEnvironment<'module_environment> {
     /// always present even if this is a "leaf" function, as we have to call
     /// into the host to trap when signal handlers are disabled.
     pub(crate) stack_limit_at_function_entry: Option<ir::GlobalValue>,
+
+    /// Whether the next operator starts a basic block of the original wasm
+    /// code, whose incoming edge is recorded with coverage instrumentation.
+    coverage_block_start: bool,
 }
 
 impl<'module_environment> FuncEnvironment<'module_environment> {
@@ -213,6 +245,8 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
             translation,
 
             stack_limit_at_function_entry: None,
+
+            coverage_block_start: true,
         }
     }
 
@@ -383,13 +417,20 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
             // Before this we need to update the fuel counter from our own cost
             // leading up to this function call, and then we can store
             // `self.fuel_var` into `VMRuntimeLimits`.
+            //
+            // The same applies to switching to another stack, where code for
+            // other continuations consumes fuel from the same `VMRuntimeLimits`.
             Operator::Unreachable
             | Operator::Return
             | Operator::CallIndirect { .. }
             | Operator::Call { .. }
+            | Operator::CallRef { .. }
             | Operator::ReturnCall { .. }
             | Operator::ReturnCallRef { .. }
-            | Operator::ReturnCallIndirect { .. } => {
+            | Operator::ReturnCallIndirect { .. }
+            | Operator::Resume { .. }
+            | Operator::Suspend { .. }
+            | Operator::Switch { .. } => {
                 self.fuel_increment_var(builder);
                 self.fuel_save_from_var(builder);
             }
@@ -446,8 +487,12 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
     fn fuel_after_op(&mut self, op: &Operator<'_>, builder: &mut FunctionBuilder<'_>) {
         // After a function call we need to reload our fuel value since the
         // function may have changed it.
+        //
+        // Note that stack switching instructions instead reload fuel in
+        // `after_stack_switch`, since control may come back to this stack at
+        // a handler block rather than after the instruction.
         match op {
-            Operator::Call { .. } | Operator::CallIndirect { .. } => {
+            Operator::Call { .. } | Operator::CallIndirect { .. } | Operator::CallRef { .. } => {
                 self.fuel_load_into_var(builder);
             }
             _ => {}
@@ -538,6 +583,69 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         builder.switch_to_block(continuation_block);
     }
 
+    /// Emits a call to the `single_step` builtin before the operator at
+    /// `offset` in the original wasm module.
+    fn single_step(&mut self, builder: &mut FunctionBuilder<'_>, offset: usize) {
+        // The step callback may observe or modify fuel, so flush the cached
+        // value around the call like `out_of_gas` does.
+        if self.tunables.consume_fuel {
+            self.fuel_save_from_var(builder);
+        }
+        let single_step = self.builtin_functions.single_step(builder.func);
+        let vmctx = self.vmctx_val(&mut builder.cursor());
+        let offset = builder
+            .ins()
+            .iconst(I32, i64::from(u32::try_from(offset).unwrap()));
+        builder.ins().call(single_step, &[vmctx, offset]);
+        if self.tunables.consume_fuel {
+            self.fuel_load_into_var(builder);
+        }
+    }
+
+    /// Records the edge taken into the basic block starting at `offset` in
+    /// the original wasm module in the store's coverage map.
+    ///
+    /// This is the AFL scheme described in `Config::coverage_instrumentation`:
+    /// the counter at `prev ^ id` is incremented and `prev` is then set to
+    /// `id >> 1`, where both live in `VMRuntimeLimits`.
+    fn coverage_edge(&mut self, builder: &mut FunctionBuilder<'_>, offset: usize) {
+        // Hash the offset so that the ids of nearby blocks are spread across
+        // the low bits used to index the map.
+        let id = u32::try_from(offset).unwrap().wrapping_mul(0x9e37_79b9);
+        let id = id ^ (id >> 16);
+
+        let pointer_type = self.pointer_type();
+        let limits = self.vmruntime_limits_ptr;
+        let flags = ir::MemFlags::trusted();
+        let prev_offset = i32::from(self.offsets.ptr.vmruntime_limits_coverage_prev());
+        let prev = builder.ins().load(I32, flags, limits, prev_offset);
+        let mask = builder.ins().load(
+            I32,
+            flags,
+            limits,
+            i32::from(self.offsets.ptr.vmruntime_limits_coverage_mask()),
+        );
+        let map = builder.ins().load(
+            pointer_type,
+            flags,
+            limits,
+            i32::from(self.offsets.ptr.vmruntime_limits_coverage_map()),
+        );
+
+        let index = builder.ins().bxor_imm(prev, i64::from(id));
+        let mut index = builder.ins().band(index, mask);
+        if pointer_type != I32 {
+            index = builder.ins().uextend(pointer_type, index);
+        }
+        let counter = builder.ins().iadd(map, index);
+        let count = builder.ins().load(I8, flags, counter, 0);
+        let count = builder.ins().iadd_imm(count, 1);
+        builder.ins().store(flags, count, counter, 0);
+
+        let prev = builder.ins().iconst(I32, i64::from(id >> 1));
+        builder.ins().store(flags, prev, limits, prev_offset);
+    }
+
     fn epoch_function_entry(&mut self, builder: &mut FunctionBuilder<'_>) {
         builder.declare_var(self.epoch_deadline_var, ir::types::I64);
         // Let epoch_check_full load the current deadline and call def_var
@@ -630,6 +738,21 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         )
     }
 
+    /// Loads the epoch deadline from `VMRuntimeLimits` into
+    /// `self.epoch_deadline_var`.
+    fn epoch_load_deadline_into_var(&mut self, builder: &mut FunctionBuilder<'_>) {
+        let deadline =
+            builder.ins().load(
+                ir::types::I64,
+                ir::MemFlags::trusted(),
+                self.vmruntime_limits_ptr,
+                ir::immediates::Offset32::new(
+                    self.offsets.ptr.vmruntime_limits_epoch_deadline() as i32
+                ),
+            );
+        builder.def_var(self.epoch_deadline_var, deadline);
+    }
+
     fn epoch_check(&mut self, builder: &mut FunctionBuilder<'_>) {
         let continuation_block = builder.create_block();
 
@@ -676,16 +799,7 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         // We keep the deadline cached in a register to speed the checks
         // in the common case (between epoch ticks) but we want to do a
         // precise check here by reloading the cache first.
-        let deadline =
-            builder.ins().load(
-                ir::types::I64,
-                ir::MemFlags::trusted(),
-                self.vmruntime_limits_ptr,
-                ir::immediates::Offset32::new(
-                    self.offsets.ptr.vmruntime_limits_epoch_deadline() as i32
-                ),
-            );
-        builder.def_var(self.epoch_deadline_var, deadline);
+        self.epoch_load_deadline_into_var(builder);
         self.epoch_check_cached(builder, cur_epoch_value, continuation_block);
 
         let new_epoch = self.builtin_functions.new_epoch(builder.func);
@@ -832,6 +946,10 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
             self.reference_type(table.ref_type.heap_type).0.bytes()
         };
 
+        // The base and bound of a table are only updated by the runtime when
+        // the table grows, so optionally put them in the `vmctx` alias region
+        // to reuse their loads across calls that can't grow it.
+        let region = self.vmctx_field_alias_region();
         let base_gv = func.create_global_value(ir::GlobalValueData::Load {
             base: ptr,
             offset: Offset32::new(base_offset),
@@ -841,7 +959,7 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
                 // change.
                 MemFlags::trusted().with_readonly()
             } else {
-                MemFlags::trusted()
+                MemFlags::trusted().with_alias_region(region)
             },
         });
 
@@ -858,7 +976,7 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
                         u16::from(self.offsets.size_of_vmtable_definition_current_elements()) * 8,
                     )
                     .unwrap(),
-                    flags: MemFlags::trusted(),
+                    flags: MemFlags::trusted().with_alias_region(region),
                 }),
             }
         };
@@ -1270,6 +1388,12 @@ impl<'a, 'func, 'module_env> Call<'a, 'func, 'module_env> {
             return Ok(self.direct_call_inst(callee, &real_call_args));
         }
 
+        // Imports of host intrinsics are called directly with the native
+        // calling convention.
+        if let Some(&intrinsic) = self.env.translation.host_intrinsics.get(&callee_index) {
+            return Ok(self.host_intrinsic_call(intrinsic, callee_index, call_args));
+        }
+
         // Handle direct calls to imported functions. We use an indirect call
         // so that we don't have to patch the code at runtime.
         let pointer_type = self.env.pointer_type();
@@ -1628,6 +1752,53 @@ impl<'a, 'func, 'module_env> Call<'a, 'func, 'module_env> {
         Ok(self.indirect_call_inst(sig_ref, func_addr, &real_call_args))
     }
 
+    /// Calls the host intrinsic at index `intrinsic`, which is imported as the
+    /// function `callee_index`.
+    ///
+    /// Intrinsics take exactly the wasm-level arguments and use the host's
+    /// default calling convention, so no `VMContext` is passed. This is never
+    /// a tail call since the calling conventions differ, so tail calls are
+    /// emulated with a return of the call's results.
+    fn host_intrinsic_call(
+        &mut self,
+        intrinsic: u32,
+        callee_index: FuncIndex,
+        args: &[ir::Value],
+    ) -> ir::Inst {
+        let isa = self.env.isa;
+        let sig = self.env.module.functions[callee_index].signature;
+        let wasm_func_ty = self.env.types[sig].unwrap_func();
+        let mut sig = ir::Signature::new(isa.default_call_conv());
+        for ty in wasm_func_ty.params() {
+            sig.params
+                .push(ir::AbiParam::new(crate::value_type(isa, *ty)));
+        }
+        for ty in wasm_func_ty.returns() {
+            sig.returns
+                .push(ir::AbiParam::new(crate::value_type(isa, *ty)));
+        }
+        let signature = self.builder.func.import_signature(sig);
+        let name = ir::ExternalName::User(self.builder.func.declare_imported_user_function(
+            ir::UserExternalName {
+                namespace: crate::NS_HOST_INTRINSIC,
+                index: intrinsic,
+            },
+        ));
+        let func_ref = self.builder.func.import_function(ir::ExtFuncData {
+            name,
+            signature,
+            colocated: false,
+            preserves_vmctx: false,
+        });
+        let inst = self.builder.ins().call(func_ref, args);
+        if self.tail {
+            let results: SmallVec<[_; 4]> =
+                self.builder.inst_results(inst).iter().copied().collect();
+            self.builder.ins().return_(&results);
+        }
+        inst
+    }
+
     fn direct_call_inst(&mut self, callee: ir::FuncRef, args: &[ir::Value]) -> ir::Inst {
         if self.tail {
             self.builder.ins().return_call(callee, args)
@@ -2395,6 +2566,17 @@ impl FuncEnvironment<'_> {
         )
     }
 
+    /// The alias region for loads of runtime-maintained `vmctx` fields, such
+    /// as the base and bound of a memory or table, which generated code never
+    /// writes.
+    fn vmctx_field_alias_region(&self) -> Option<ir::AliasRegion> {
+        if self.isa.flags().enable_vmctx_invariant_loads() {
+            Some(ir::AliasRegion::Vmctx)
+        } else {
+            None
+        }
+    }
+
     pub fn make_heap(&mut self, func: &mut ir::Function, index: MemoryIndex) -> WasmResult<Heap> {
         let pointer_type = self.pointer_type();
         let memory = self.module.memories[index];
@@ -2452,11 +2634,19 @@ impl FuncEnvironment<'_> {
             }
         };
 
+        // As with tables, the base and bound of a memory are only updated by
+        // the runtime when it grows. Shared memories may be grown concurrently
+        // by other threads though, so their fields are never invariant.
+        let region = if is_shared {
+            None
+        } else {
+            self.vmctx_field_alias_region()
+        };
         let heap_bound = func.create_global_value(ir::GlobalValueData::Load {
             base: ptr,
             offset: Offset32::new(current_length_offset),
             global_type: pointer_type,
-            flags: MemFlags::trusted(),
+            flags: MemFlags::trusted().with_alias_region(region),
         });
 
         // If we have a declared maximum, we can make this a "static" heap, which is
@@ -2569,6 +2759,8 @@ impl FuncEnvironment<'_> {
         let mut flags = MemFlags::trusted().with_checked();
         if !memory.memory_may_move(self.tunables) {
             flags.set_readonly();
+        } else {
+            flags.set_alias_region(region);
         }
         let heap_base = func.create_global_value(ir::GlobalValueData::Load {
             base: ptr,
@@ -2658,6 +2850,7 @@ impl FuncEnvironment<'_> {
             // the `vmcontext` as relative jumps (hence no relocations) or
             // they're libcalls with absolute relocations.
             colocated: self.module.defined_func_index(index).is_some(),
+            preserves_vmctx: false,
         }))
     }
 
@@ -2815,8 +3008,9 @@ impl FuncEnvironment<'_> {
                             .vmctx_vmmemory_definition_current_length(owned_index),
                     )
                     .unwrap();
-                    pos.ins()
-                        .load(pointer_type, ir::MemFlags::trusted(), base, offset)
+                    let flags =
+                        ir::MemFlags::trusted().with_alias_region(self.vmctx_field_alias_region());
+                    pos.ins().load(pointer_type, flags, base, offset)
                 }
             }
             None => {
@@ -2837,7 +3031,7 @@ impl FuncEnvironment<'_> {
                 } else {
                     pos.ins().load(
                         pointer_type,
-                        ir::MemFlags::trusted(),
+                        ir::MemFlags::trusted().with_alias_region(self.vmctx_field_alias_region()),
                         vmmemory_ptr,
                         i32::from(self.offsets.ptr.vmmemory_definition_current_length()),
                     )
@@ -3129,9 +3323,19 @@ impl FuncEnvironment<'_> {
     pub fn before_translate_operator(
         &mut self,
         op: &Operator,
+        offset: usize,
         builder: &mut FunctionBuilder,
         state: &FuncTranslationState,
     ) -> WasmResult<()> {
+        if self.tunables.coverage_instrumentation
+            && mem::take(&mut self.coverage_block_start)
+            && state.reachable()
+        {
+            self.coverage_edge(builder, offset);
+        }
+        if self.tunables.single_step && state.reachable() {
+            self.single_step(builder, offset);
+        }
         if self.tunables.consume_fuel {
             self.fuel_before_op(op, builder, state.reachable());
         }
@@ -3147,6 +3351,22 @@ impl FuncEnvironment<'_> {
         if self.tunables.consume_fuel && state.reachable() {
             self.fuel_after_op(op, builder);
         }
+        if self.tunables.coverage_instrumentation {
+            // Operators which are branch targets, or which may branch, end
+            // the current basic block.
+            self.coverage_block_start = matches!(
+                op,
+                Operator::Loop { .. }
+                    | Operator::If { .. }
+                    | Operator::Else
+                    | Operator::End
+                    | Operator::BrIf { .. }
+                    | Operator::BrOnNull { .. }
+                    | Operator::BrOnNonNull { .. }
+                    | Operator::BrOnCast { .. }
+                    | Operator::BrOnCastFail { .. }
+            );
+        }
         Ok(())
     }
 
@@ -3177,7 +3397,10 @@ impl FuncEnvironment<'_> {
 
         // If the `vmruntime_limits_ptr` variable will get used then we initialize
         // it here.
-        if self.tunables.consume_fuel || self.tunables.epoch_interruption {
+        if self.tunables.consume_fuel
+            || self.tunables.epoch_interruption
+            || self.tunables.coverage_instrumentation
+        {
             // TODO(frank-emrich) Ideally, we would like to use this global
             // variable in the translation of `resume` instructions. However, in
             // order to decide whether to declare the variable or not we would
@@ -3258,6 +3481,24 @@ impl FuncEnvironment<'_> {
     // `translate_load_builtin_function_address` multiple times, which
     // causes repeated allocation of values pointing to the vmctx. We
     // should refactor or inline this logic at some point.
+    /// Reloads the fuel and epoch deadline cached in variables of this
+    /// function after control returns to the current stack from a stack
+    /// switch.
+    ///
+    /// While this stack was switched away from, other continuations may have
+    /// consumed fuel, and the host may have changed the fuel or the epoch
+    /// deadline before resuming a suspended continuation. Without reloading
+    /// them here, code running after a switch would keep enforcing stale
+    /// limits.
+    pub(crate) fn after_stack_switch(&mut self, builder: &mut FunctionBuilder<'_>) {
+        if self.tunables.consume_fuel {
+            self.fuel_load_into_var(builder);
+        }
+        if self.tunables.epoch_interruption {
+            self.epoch_load_deadline_into_var(builder);
+        }
+    }
+
     pub fn translate_resume(
         &mut self,
         builder: &mut FunctionBuilder<'_>,
@@ -3419,7 +3660,22 @@ impl FuncEnvironment<'_> {
             }
         }
         #[cfg(not(feature = "wmemcheck"))]
-        let _ = (builder, global_index, value);
+        let _ = value;
+
+        if self.tunables.global_watchpoints {
+            // Watchpoint callbacks may observe or modify fuel, so flush the
+            // cached value around the call like `out_of_gas` does.
+            if self.tunables.consume_fuel {
+                self.fuel_save_from_var(builder);
+            }
+            let global_set_notify = self.builtin_functions.global_set_notify(builder.func);
+            let vmctx = self.vmctx_val(&mut builder.cursor());
+            let index = builder.ins().iconst(I32, i64::from(global_index));
+            builder.ins().call(global_set_notify, &[vmctx, index]);
+            if self.tunables.consume_fuel {
+                self.fuel_load_into_var(builder);
+            }
+        }
     }
 
     pub fn before_memory_grow(
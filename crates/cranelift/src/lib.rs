@@ -231,6 +231,11 @@ pub const NS_WASMTIME_BUILTIN: u32 = 1;
 /// in the pulley_interpreter crate.
 pub const NS_PULLEY_HOSTCALL: u32 = 2;
 
+/// Namespace for direct calls to host intrinsics. The index is the index of the
+/// intrinsic in the engine's list of configured intrinsics, and the address of
+/// the intrinsic is filled in with an absolute relocation at load time.
+pub const NS_HOST_INTRINSIC: u32 = 3;
+
 /// A record of a relocation to perform.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Relocation {
@@ -310,6 +315,7 @@ fn mach_reloc_to_reloc(
                     RelocationTarget::Builtin(BuiltinFunctionIndex::from_u32(name.index))
                 }
                 NS_PULLEY_HOSTCALL => RelocationTarget::PulleyHostcall(name.index),
+                NS_HOST_INTRINSIC => RelocationTarget::HostIntrinsic(name.index),
                 _ => panic!("unknown namespace {}", name.namespace),
             }
         }
@@ -60,6 +60,10 @@ pub struct ModuleTextBuilder<'a> {
     /// builds without SIMD on x86_64 right now.
     libcall_symbols: HashMap<LibCall, SymbolId>,
 
+    /// Symbols defined in the object for host intrinsics that relocations are
+    /// applied against, keyed by the index of the intrinsic.
+    host_intrinsic_symbols: HashMap<u32, SymbolId>,
+
     ctrl_plane: ControlPlane,
 }
 
@@ -102,6 +106,7 @@ impl<'a> ModuleTextBuilder<'a> {
             unwind_info: Default::default(),
             text,
             libcall_symbols: HashMap::default(),
+            host_intrinsic_symbols: HashMap::default(),
             ctrl_plane: ControlPlane::default(),
         }
     }
@@ -178,25 +183,27 @@ impl<'a> ModuleTextBuilder<'a> {
                 // Relocations against libcalls are not common at this time and
                 // are only used in non-default configurations that disable wasm
                 // SIMD, disable SSE features, and for wasm modules that still
-                // use floating point operations.
+                // use floating point operations. Relocations against host
+                // intrinsics are only present for modules which import an
+                // intrinsic configured in the engine.
                 //
                 // Currently these relocations are all expected to be absolute
                 // 8-byte relocations so that's asserted here and then encoded
                 // directly into the object as a normal object relocation. This
                 // is processed at module load time to resolve the relocations.
-                RelocationTarget::HostLibcall(call) => {
-                    let symbol = *self.libcall_symbols.entry(call).or_insert_with(|| {
-                        self.obj.add_symbol(Symbol {
-                            name: call.symbol().as_bytes().to_vec(),
-                            value: 0,
-                            size: 0,
-                            kind: SymbolKind::Text,
-                            scope: SymbolScope::Linkage,
-                            weak: false,
-                            section: SymbolSection::Undefined,
-                            flags: SymbolFlags::None,
-                        })
-                    });
+                RelocationTarget::HostLibcall(_) | RelocationTarget::HostIntrinsic(_) => {
+                    let symbol = match r.reloc_target {
+                        RelocationTarget::HostLibcall(call) => *self
+                            .libcall_symbols
+                            .entry(call)
+                            .or_insert_with(|| add_undefined_symbol(self.obj, call.symbol())),
+                        RelocationTarget::HostIntrinsic(index) => {
+                            *self.host_intrinsic_symbols.entry(index).or_insert_with(|| {
+                                add_undefined_symbol(self.obj, &obj::host_intrinsic_symbol(index))
+                            })
+                        }
+                        _ => unreachable!(),
+                    };
                     let flags = match r.reloc {
                         Reloc::Abs8 => object::RelocationFlags::Generic {
                             encoding: object::RelocationEncoding::Generic,
@@ -290,6 +297,21 @@ impl<'a> ModuleTextBuilder<'a> {
     }
 }
 
+/// Adds an undefined symbol named `name` to `obj`, to be resolved when the
+/// object is loaded.
+fn add_undefined_symbol(obj: &mut Object<'static>, name: &str) -> SymbolId {
+    obj.add_symbol(Symbol {
+        name: name.as_bytes().to_vec(),
+        value: 0,
+        size: 0,
+        kind: SymbolKind::Text,
+        scope: SymbolScope::Linkage,
+        weak: false,
+        section: SymbolSection::Undefined,
+        flags: SymbolFlags::None,
+    })
+}
+
 /// Builder used to create unwind information for a set of functions added to a
 /// text section.
 #[derive(Default)]
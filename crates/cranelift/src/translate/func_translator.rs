@@ -248,7 +248,7 @@ fn parse_function_body(
         builder.set_srcloc(cur_srcloc(&reader));
         let op = reader.read_operator()?;
         validator.op(pos, &op)?;
-        environ.before_translate_operator(&op, builder, state)?;
+        environ.before_translate_operator(&op, pos, builder, state)?;
         translate_operator(validator, &op, builder, state, environ)?;
         environ.after_translate_operator(&op, builder, state)?;
     }
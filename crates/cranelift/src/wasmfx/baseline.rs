@@ -266,6 +266,7 @@ pub(crate) fn translate_resume<'a>(
 
         // Load the builtin continuation resume function.
         call_builtin!(builder, env, let result = tc_baseline_resume(resumee_fiber));
+        env.after_stack_switch(builder);
 
         // The result encodes whether the return happens via ordinary
         // means or via a suspend. If the high bit is set, then it is
@@ -350,6 +351,7 @@ pub(crate) fn translate_resume<'a>(
 
         // Load the builtin forwarding function.
         call_builtin!(builder, env, tc_baseline_forward(tag, resumee_fiber));
+        env.after_stack_switch(builder);
 
         builder.ins().jump(resume_block, &[resumee_fiber]);
         builder.seal_block(resume_block);
@@ -449,6 +451,7 @@ pub(crate) fn translate_suspend<'a>(
     let tag_index_val = builder.ins().iconst(I32, tag_index as i64);
     typed_continuations_store_payloads(env, builder, suspend_args);
     call_builtin!(builder, env, tc_baseline_suspend(tag_index_val));
+    env.after_stack_switch(builder);
     let contref = typed_continuations_load_continuation_reference(env, builder);
 
     let return_values =
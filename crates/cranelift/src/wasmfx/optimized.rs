@@ -826,6 +826,168 @@ pub(crate) mod typed_continuation_helpers {
         }
     }
 
+    /// Returns the number of slots of the dispatch table built for a resume
+    /// instruction with `suspend_handler_count` suspend handlers, see
+    /// `HandlerList::store_dispatch_table`.
+    ///
+    /// For at least one handler this is a power of two and at least twice the
+    /// number of handlers, so that probing always reaches an empty slot.
+    pub fn dispatch_table_size(suspend_handler_count: usize) -> usize {
+        if suspend_handler_count == 0 {
+            0
+        } else {
+            (2 * suspend_handler_count).next_power_of_two()
+        }
+    }
+
+    /// Set in the `first_switch_handler_index` of a stack whose handler list
+    /// holds a dispatch table rather than a plain list of tags.
+    pub const DISPATCH_TABLE_FLAG: u32 = 1 << 31;
+
+    /// Returns the slot of the dispatch table at which probing for
+    /// `tag_address` starts, where `mask` is the size of the table minus one.
+    pub fn dispatch_table_slot(
+        builder: &mut FunctionBuilder,
+        pointer_type: ir::Type,
+        tag_address: ir::Value,
+        mask: ir::Value,
+    ) -> ir::Value {
+        // A `VMTagDefinition` is just a `u32`, so the lowest two bits of tag
+        // addresses are always zero and tags defined by the same instance are
+        // four bytes apart.
+        let hash = builder.ins().ushr_imm(tag_address, 2);
+        let hash = if pointer_type == I32 {
+            hash
+        } else {
+            builder.ins().ireduce(I32, hash)
+        };
+        builder.ins().band(hash, mask)
+    }
+
+    /// Lays out the dispatch table for suspend handlers whose tags are all
+    /// defined by the instance, at `tag_offsets` from the start of its
+    /// `VMContext`, returning the index of the handler in each slot.
+    ///
+    /// Since both the `VMContext` and the offsets of tags within it are
+    /// multiples of four bytes, the slot of each tag's address is the slot of
+    /// the `VMContext` itself plus the slot of the tag's offset, modulo the
+    /// size of the table. The table is thus
+    /// the same for every instance up to a rotation by the slot of the
+    /// `VMContext`, and slot i of the returned layout ends up in slot
+    /// `(dispatch_table_slot(vmctx) + i) & mask` at runtime. If several
+    /// handlers have the same tag, the first one takes precedence and the
+    /// others are left out.
+    pub fn dispatch_table_layout(tag_offsets: &[u32]) -> Vec<Option<u32>> {
+        let size = dispatch_table_size(tag_offsets.len());
+        let mut slots = vec![None; size];
+        for (index, offset) in tag_offsets.iter().enumerate() {
+            let mut slot = (offset >> 2) as usize & (size - 1);
+            loop {
+                match slots[slot] {
+                    None => {
+                        slots[slot] = Some(u32::try_from(index).unwrap());
+                        break;
+                    }
+                    Some(other) if tag_offsets[other as usize] == *offset => break,
+                    Some(_) => slot = (slot + 1) & (size - 1),
+                }
+            }
+        }
+        slots
+    }
+
+    impl HandlerList {
+        /// Stores the handlers of a resume instruction as a dispatch table,
+        /// which allows finding the suspend handler for a tag without
+        /// comparing it against all handlers.
+        ///
+        /// With n = `dispatch_table_size(suspend_tag_offsets.len())`, the
+        /// first n entries form an open-addressing hash table of tag
+        /// addresses, with null marking empty slots. Entry n + i then holds,
+        /// as a u32, the index of the handler whose tag is stored in slot i.
+        /// The 2n entries of the table are followed by `switch_tags`, which
+        /// are searched linearly as usual.
+        ///
+        /// The suspend handlers' tags must all be defined by the instance of
+        /// `vmctx`, at `suspend_tag_offsets` from its start, so that the
+        /// table is laid out while compiling (see `dispatch_table_layout`)
+        /// and only needs to be rotated into place here, without probing.
+        ///
+        /// Returns the index of the first switch handler, i.e., 2n, with
+        /// `DISPATCH_TABLE_FLAG` set. Like `store_data_entries`, this expects
+        /// the list to be empty and of sufficient capacity.
+        #[allow(clippy::cast_possible_truncation, reason = "TODO")]
+        pub fn store_dispatch_table<'a>(
+            &self,
+            env: &mut crate::func_environ::FuncEnvironment<'a>,
+            builder: &mut FunctionBuilder,
+            vmctx: ir::Value,
+            suspend_tag_offsets: &[u32],
+            switch_tags: &[ir::Value],
+        ) -> u32 {
+            let layout = dispatch_table_layout(suspend_tag_offsets);
+            let table_size = layout.len();
+            let entry_count = 2 * table_size + switch_tags.len();
+            let entry_size = mem::size_of::<*mut u8>() as i32;
+
+            if cfg!(debug_assertions) {
+                let store_count = builder.ins().iconst(I32, entry_count as i64);
+                let capacity = self.get_capacity(env, builder);
+                let length = self.get_length(env, builder);
+                let zero = builder.ins().iconst(I32, 0);
+                emit_debug_assert_ule!(env, builder, store_count, capacity);
+                emit_debug_assert_eq!(env, builder, length, zero);
+            }
+
+            let memflags = ir::MemFlags::trusted();
+            let data = self.get_data(env, builder);
+
+            // Mark all slots of the table as empty.
+            let null = builder.ins().iconst(env.pointer_type(), 0);
+            for slot in 0..table_size {
+                builder
+                    .ins()
+                    .store(memflags, null, data, slot as i32 * entry_size);
+            }
+
+            // Store each handler in its slot, rotated by the slot of `vmctx`.
+            if table_size > 0 {
+                let mask = builder.ins().iconst(I32, table_size as i64 - 1);
+                let base = dispatch_table_slot(builder, env.pointer_type(), vmctx, mask);
+                for (relative_slot, index) in layout.iter().enumerate() {
+                    let Some(index) = *index else { continue };
+                    let slot = builder.ins().iadd_imm(base, relative_slot as i64);
+                    let slot = builder.ins().band(slot, mask);
+                    let offset = builder.ins().imul_imm(slot, entry_size as i64);
+                    let offset = builder.ins().uextend(I64, offset);
+                    let entry_address = builder.ins().iadd(data, offset);
+
+                    let tag_offset = suspend_tag_offsets[index as usize];
+                    let tag_address = builder.ins().iadd_imm(vmctx, i64::from(tag_offset));
+                    builder.ins().store(memflags, tag_address, entry_address, 0);
+                    let index = builder.ins().iconst(I32, i64::from(index));
+                    builder.ins().store(
+                        memflags,
+                        index,
+                        entry_address,
+                        table_size as i32 * entry_size,
+                    );
+                }
+            }
+
+            let mut offset = 2 * table_size as i32 * entry_size;
+            for tag_address in switch_tags {
+                builder.ins().store(memflags, *tag_address, data, offset);
+                offset += entry_size;
+            }
+
+            let length = builder.ins().iconst(I32, entry_count as i64);
+            self.set_length(builder, length);
+
+            2 * table_size as u32 | DISPATCH_TABLE_FLAG
+        }
+    }
+
     impl VMContext {
         pub fn new(address: ir::Value, pointer_type: ir::Type) -> VMContext {
             VMContext {
@@ -1550,7 +1712,8 @@ pub(crate) fn vmctx_store_payloads<'a>(
 /// suspend or switch handler. Concretely, this influences which part of each
 /// handler list we will search.
 ///
-/// We trap if no handler was found.
+/// We trap if no handler was found, unless `unhandled` is given, in which
+/// case we jump to that block instead.
 ///
 /// The returned values are:
 /// 1. The stack (continuation or main stack, represented as a StackChain) in
@@ -1585,17 +1748,25 @@ pub(crate) fn vmctx_store_payloads<'a>(
 /// on_match(conref : VMContRef, handler_index : u32)
 /// ... execution continues here here ...
 ///
+/// If `Tunables::wasmfx_tag_dispatch_tables` is enabled, the suspend handlers
+/// of a handler list may form a dispatch table instead (see
+/// `HandlerList::store_dispatch_table`), as indicated by
+/// `tc::DISPATCH_TABLE_FLAG`, so that rather than comparing `tag_address`
+/// against every suspend handler in turn, we only probe the table until
+/// reaching the tag or an empty slot. Switch handlers are always searched
+/// linearly.
 fn search_handler<'a>(
     env: &mut crate::func_environ::FuncEnvironment<'a>,
     builder: &mut FunctionBuilder,
     start: &tc::StackChain,
     tag_address: ir::Value,
     search_suspend_handlers: bool,
+    unhandled: Option<ir::Block>,
 ) -> (StackChain, ir::Value, ir::Value) {
+    let use_dispatch_table = search_suspend_handlers && env.tunables.wasmfx_tag_dispatch_tables;
+
     let handle_link = builder.create_block();
     let begin_search_handler_list = builder.create_block();
-    let try_index = builder.create_block();
-    let compare_tags = builder.create_block();
     let on_match = builder.create_block();
     let on_no_match = builder.create_block();
 
@@ -1623,7 +1794,7 @@ fn search_handler<'a>(
     };
 
     // Block begin_search_handler_list
-    let (contref, parent_link, handler_list_data_ptr, end_range) = {
+    let (contref, parent_link, handlers, handler_list_data_ptr, first_switch_handler_index) = {
         builder.switch_to_block(begin_search_handler_list);
         let contref = chain_link.unchecked_get_continuation(env, builder);
         let contref = tc::VMContRef::new(contref);
@@ -1644,6 +1815,60 @@ fn search_handler<'a>(
 
         let first_switch_handler_index = parent_csi.get_first_switch_handler_index(env, builder);
 
+        (
+            contref,
+            parent_link,
+            handlers,
+            handler_list_data_ptr,
+            first_switch_handler_index,
+        )
+    };
+
+    builder.append_block_param(on_match, I32);
+    if use_dispatch_table {
+        // Handler lists only hold a dispatch table if all the suspend
+        // handlers' tags were known while compiling the resume instruction,
+        // and are searched linearly otherwise.
+        let probe_table = builder.create_block();
+        let search_list = builder.create_block();
+        let flag = builder.ins().band_imm(
+            first_switch_handler_index,
+            i64::from(tc::DISPATCH_TABLE_FLAG),
+        );
+        let first_switch_handler_index = builder.ins().band_imm(
+            first_switch_handler_index,
+            i64::from(!tc::DISPATCH_TABLE_FLAG),
+        );
+        builder.ins().brif(flag, probe_table, &[], search_list, &[]);
+
+        builder.switch_to_block(probe_table);
+        builder.seal_block(probe_table);
+        probe_dispatch_table(
+            env,
+            builder,
+            handler_list_data_ptr,
+            first_switch_handler_index,
+            tag_address,
+            on_match,
+            handle_link,
+            &parent_link,
+        );
+
+        builder.switch_to_block(search_list);
+        builder.seal_block(search_list);
+        let zero = builder.ins().iconst(I32, 0);
+        search_handler_list(
+            env,
+            builder,
+            handler_list_data_ptr,
+            zero,
+            first_switch_handler_index,
+            tag_address,
+            on_match,
+            handle_link,
+            &parent_link,
+        );
+    } else {
         // Note that these indices are inclusive-exclusive, i.e. [begin_range, end_range).
         let (begin_range, end_range) = if search_suspend_handlers {
             let zero = builder.ins().iconst(I32, 0);
@@ -1653,14 +1878,90 @@ fn search_handler<'a>(
             }
             (zero, first_switch_handler_index)
         } else {
+            // The switch handlers follow a dispatch table just like they
+            // follow a plain list of suspend handlers.
+            let first_switch_handler_index = if env.tunables.wasmfx_tag_dispatch_tables {
+                builder.ins().band_imm(
+                    first_switch_handler_index,
+                    i64::from(!tc::DISPATCH_TABLE_FLAG),
+                )
+            } else {
+                first_switch_handler_index
+            };
             let length = handlers.get_length(env, builder);
             (first_switch_handler_index, length)
         };
 
-        builder.ins().jump(try_index, &[begin_range]);
+        search_handler_list(
+            env,
+            builder,
+            handler_list_data_ptr,
+            begin_range,
+            end_range,
+            tag_address,
+            on_match,
+            handle_link,
+            &parent_link,
+        );
+    }
+
+    // Block on_no_match
+    {
+        builder.switch_to_block(on_no_match);
+        builder.set_cold_block(on_no_match);
+        match unhandled {
+            Some(block) => {
+                builder.ins().jump(block, &[]);
+            }
+            None => {
+                builder.ins().trap(crate::TRAP_UNHANDLED_TAG);
+            }
+        }
+    }
 
-        (contref, parent_link, handler_list_data_ptr, end_range)
-    };
+    builder.seal_block(handle_link);
+    builder.seal_block(begin_search_handler_list);
+    builder.seal_block(on_match);
+    builder.seal_block(on_no_match);
+
+    // final block: on_match
+    builder.switch_to_block(on_match);
+    let index = builder.block_params(on_match)[0];
+
+    emit_debug_println!(
+        env,
+        builder,
+        "[search_handler] found handler at stack chain ({}, {:p}), whose child continuation is {:p}, index is {}",
+        parent_link.to_raw_parts()[0],
+        parent_link.to_raw_parts()[1],
+        contref.address,
+        index
+    );
+
+    (parent_link, contref.address, index)
+}
+
+/// Generates code that compares `tag_address` against the entries of the
+/// handler list `data` in the range [`begin_range`, `end_range`).
+///
+/// Jumps to `on_match` with the index of the first matching entry, and to
+/// `not_found` with the raw parts of `parent_link` if there is none.
+#[allow(clippy::too_many_arguments, reason = "TODO")]
+fn search_handler_list<'a>(
+    env: &mut crate::func_environ::FuncEnvironment<'a>,
+    builder: &mut FunctionBuilder,
+    data: ir::Value,
+    begin_range: ir::Value,
+    end_range: ir::Value,
+    tag_address: ir::Value,
+    on_match: Block,
+    not_found: Block,
+    parent_link: &StackChain,
+) {
+    let try_index = builder.create_block();
+    let compare_tags = builder.create_block();
+
+    builder.ins().jump(try_index, &[begin_range]);
 
     // Block try_index
     let index = {
@@ -1675,7 +1976,7 @@ fn search_handler<'a>(
             in_bounds,
             compare_tags,
             &[],
-            handle_link,
+            not_found,
             &parent_link.to_raw_parts(),
         );
         index
@@ -1685,11 +1986,10 @@ fn search_handler<'a>(
     {
         builder.switch_to_block(compare_tags);
 
-        let base = handler_list_data_ptr;
         let entry_size = std::mem::size_of::<*mut u8>();
         let offset = builder.ins().imul_imm(index, entry_size as i64);
         let offset = builder.ins().uextend(I64, offset);
-        let entry_address = builder.ins().iadd(base, offset);
+        let entry_address = builder.ins().iadd(data, offset);
 
         let memflags = ir::MemFlags::trusted();
 
@@ -1699,39 +1999,101 @@ fn search_handler<'a>(
 
         let tags_match = builder.ins().icmp(IntCC::Equal, handled_tag, tag_address);
         let incremented_index = builder.ins().iadd_imm(index, 1);
-        builder
-            .ins()
-            .brif(tags_match, on_match, &[], try_index, &[incremented_index]);
-    }
-
-    // Block on_no_match
-    {
-        builder.switch_to_block(on_no_match);
-        builder.set_cold_block(on_no_match);
-        builder.ins().trap(crate::TRAP_UNHANDLED_TAG);
+        builder.ins().brif(
+            tags_match,
+            on_match,
+            &[index],
+            try_index,
+            &[incremented_index],
+        );
     }
 
-    builder.seal_block(handle_link);
-    builder.seal_block(begin_search_handler_list);
     builder.seal_block(try_index);
     builder.seal_block(compare_tags);
-    builder.seal_block(on_match);
-    builder.seal_block(on_no_match);
+}
 
-    // final block: on_match
-    builder.switch_to_block(on_match);
+/// Generates code that looks up `tag_address` in the dispatch table at the
+/// beginning of the handler list `data`, whose suspend handlers end at
+/// `first_switch_handler_index`.
+///
+/// Jumps to `on_match` with the index of the handler if the tag is found, and
+/// to `not_found` with the raw parts of `parent_link` otherwise.
+#[allow(clippy::too_many_arguments, reason = "TODO")]
+fn probe_dispatch_table<'a>(
+    env: &mut crate::func_environ::FuncEnvironment<'a>,
+    builder: &mut FunctionBuilder,
+    data: ir::Value,
+    first_switch_handler_index: ir::Value,
+    tag_address: ir::Value,
+    on_match: Block,
+    not_found: Block,
+    parent_link: &StackChain,
+) {
+    let probe = builder.create_block();
+    let check_empty = builder.create_block();
+    let found = builder.create_block();
 
-    emit_debug_println!(
-        env,
-        builder,
-        "[search_handler] found handler at stack chain ({}, {:p}), whose child continuation is {:p}, index is {}",
-        parent_link.to_raw_parts()[0],
-        parent_link.to_raw_parts()[1],
-        contref.address,
-        index
+    let entry_size = std::mem::size_of::<*mut u8>() as i64;
+    let memflags = ir::MemFlags::trusted();
+
+    // The table and the handler indices following it take up all the entries
+    // before the switch handlers.
+    let table_size = builder.ins().ushr_imm(first_switch_handler_index, 1);
+    let mask = builder.ins().iadd_imm(table_size, -1);
+    let slot = tc::dispatch_table_slot(builder, env.pointer_type(), tag_address, mask);
+    let no_suspend_handlers = builder.ins().icmp_imm(IntCC::Equal, table_size, 0);
+    builder.append_block_param(probe, I32);
+    builder.ins().brif(
+        no_suspend_handlers,
+        not_found,
+        &parent_link.to_raw_parts(),
+        probe,
+        &[slot],
     );
 
-    (parent_link, contref.address, index)
+    // Block probe
+    let (slot, entry_address, entry) = {
+        builder.switch_to_block(probe);
+        let slot = builder.block_params(probe)[0];
+        let offset = builder.ins().imul_imm(slot, entry_size);
+        let offset = builder.ins().uextend(I64, offset);
+        let entry_address = builder.ins().iadd(data, offset);
+        let entry = builder
+            .ins()
+            .load(env.pointer_type(), memflags, entry_address, 0);
+        let tags_match = builder.ins().icmp(IntCC::Equal, entry, tag_address);
+        builder.ins().brif(tags_match, found, &[], check_empty, &[]);
+        (slot, entry_address, entry)
+    };
+
+    // Block check_empty
+    {
+        builder.switch_to_block(check_empty);
+        let is_empty = builder.ins().icmp_imm(IntCC::Equal, entry, 0);
+        let next_slot = builder.ins().iadd_imm(slot, 1);
+        let next_slot = builder.ins().band(next_slot, mask);
+        builder.ins().brif(
+            is_empty,
+            not_found,
+            &parent_link.to_raw_parts(),
+            probe,
+            &[next_slot],
+        );
+    }
+
+    // Block found
+    {
+        builder.switch_to_block(found);
+        let index_offset = builder.ins().imul_imm(table_size, entry_size);
+        let index_offset = builder.ins().uextend(I64, index_offset);
+        let index_address = builder.ins().iadd(entry_address, index_offset);
+        let index = builder.ins().load(I32, memflags, index_address, 0);
+        builder.ins().jump(on_match, &[index]);
+    }
+
+    builder.seal_block(probe);
+    builder.seal_block(check_empty);
+    builder.seal_block(found);
 }
 
 pub(crate) fn translate_cont_bind<'a>(
@@ -1784,6 +2146,7 @@ pub(crate) fn translate_cont_new<'a>(
     Ok(contobj)
 }
 
+#[allow(clippy::cast_possible_truncation, reason = "TODO")]
 pub(crate) fn translate_resume<'a>(
     env: &mut crate::func_environ::FuncEnvironment<'a>,
     builder: &mut FunctionBuilder,
@@ -1954,33 +2317,74 @@ pub(crate) fn translate_resume<'a>(
         let handler_list = parent_csi.get_handler_list();
 
         if resumetable.len() > 0 {
-            // Total number of handlers (suspend and switch).
-            let handler_count = builder.ins().iconst(I32, resumetable.len() as i64);
-
-            // If the existing list is too small, reallocate (in runtime).
-            handler_list.ensure_capacity(env, builder, handler_count);
-
             let suspend_handler_count = suspend_handlers.len();
 
-            // All handlers, represented by the indices of the tags they handle.
-            // All the suspend handlers come first, followed by all the switch handlers.
-            let all_handlers = suspend_handlers
-                .iter()
-                .map(|(tag_index, _block)| *tag_index)
-                .chain(switch_tags);
-
-            // Translate all tag indices to tag addresses (i.e., the corresponding *mut VMTagDefinition).
-            let all_tag_addresses: Vec<ir::Value> = all_handlers
+            let switch_tag_addresses: Vec<ir::Value> = switch_tags
+                .into_iter()
                 .map(|tag_index| shared::tag_address(env, builder, tag_index))
                 .collect();
 
-            // Store all tag addresess in the handler list.
-            handler_list.store_data_entries(env, builder, &all_tag_addresses, false);
+            // A dispatch table can only be laid out while compiling if all
+            // suspend handlers' tags are defined by this instance, as the
+            // addresses of imported tags are only known at runtime. Otherwise
+            // the handlers are searched linearly.
+            let suspend_tag_offsets = suspend_handlers
+                .iter()
+                .map(|(tag_index, _block)| {
+                    let tag_index = wasmtime_environ::TagIndex::from_u32(*tag_index);
+                    let def_index = env.module.defined_tag_index(tag_index)?;
+                    Some(env.offsets.vmctx_vmtag_definition(def_index))
+                })
+                .collect::<Option<Vec<u32>>>()
+                .filter(|offsets| env.tunables.wasmfx_tag_dispatch_tables && !offsets.is_empty());
+
+            let first_switch_handler_index = if let Some(offsets) = suspend_tag_offsets {
+                // The dispatch table takes up twice as many entries as it
+                // has slots, to store the index of each slot's handler.
+                let table_size = tc::dispatch_table_size(suspend_handler_count);
+                let entry_count = 2 * table_size + switch_tag_addresses.len();
+                let entry_count = builder.ins().iconst(I32, entry_count as i64);
+
+                // If the existing list is too small, reallocate (in runtime).
+                handler_list.ensure_capacity(env, builder, entry_count);
+
+                handler_list.store_dispatch_table(
+                    env,
+                    builder,
+                    vmctx.address,
+                    &offsets,
+                    &switch_tag_addresses,
+                )
+            } else {
+                // Translate all tag indices to tag addresses (i.e., the corresponding *mut VMTagDefinition).
+                let suspend_tag_addresses: Vec<ir::Value> = suspend_handlers
+                    .iter()
+                    .map(|(tag_index, _block)| shared::tag_address(env, builder, *tag_index))
+                    .collect();
+
+                // Total number of handlers (suspend and switch).
+                let handler_count = builder.ins().iconst(I32, resumetable.len() as i64);
+
+                // If the existing list is too small, reallocate (in runtime).
+                handler_list.ensure_capacity(env, builder, handler_count);
+
+                // All handlers, represented by their tag addresses.
+                // All the suspend handlers come first, followed by all the switch handlers.
+                let all_tag_addresses: Vec<ir::Value> = suspend_tag_addresses
+                    .into_iter()
+                    .chain(switch_tag_addresses)
+                    .collect();
+
+                // Store all tag addresess in the handler list.
+                handler_list.store_data_entries(env, builder, &all_tag_addresses, false);
+
+                suspend_handler_count as u32
+            };
 
             // To enable distinguishing switch and suspend handlers when searching the handler list:
             // Store at which index the switch handlers start.
             let first_switch_handler_index =
-                builder.ins().iconst(I32, suspend_handler_count as i64);
+                builder.ins().iconst(I32, first_switch_handler_index as i64);
             parent_csi.set_first_switch_handler_index(env, builder, first_switch_handler_index);
         }
 
@@ -2002,6 +2406,7 @@ pub(crate) fn translate_resume<'a>(
             builder
                 .ins()
                 .stack_switch(control_context_ptr, control_context_ptr, resume_payload);
+        env.after_stack_switch(builder);
 
         emit_debug_println!(
             env,
@@ -2208,8 +2613,17 @@ pub(crate) fn translate_suspend<'a>(
     let vmctx = tc::VMContext::new(vmctx, env.pointer_type());
     let active_stack_chain = vmctx.load_stack_chain(env, builder);
 
-    let (_, end_of_chain_contref, handler_index) =
-        search_handler(env, builder, &active_stack_chain, tag_addr, true);
+    // If no handler is found in wasm, the suspension is handed over to the
+    // host, which traps unless it has a handler for the tag.
+    let host_suspend = builder.create_block();
+    let (_, end_of_chain_contref, handler_index) = search_handler(
+        env,
+        builder,
+        &active_stack_chain,
+        tag_addr,
+        true,
+        Some(host_suspend),
+    );
 
     emit_debug_println!(
         env,
@@ -2253,11 +2667,34 @@ pub(crate) fn translate_suspend<'a>(
     builder
         .ins()
         .stack_switch(control_context_ptr, control_context_ptr, suspend_payload);
+    env.after_stack_switch(builder);
 
     let return_values =
         vmcontref_load_values(env, builder, active_contref.address, tag_return_types);
 
-    return_values
+    let return_types = tag_return_types
+        .iter()
+        .map(|ty| crate::value_type(env.isa, *ty))
+        .collect::<Vec<_>>();
+    let resumed = builder.create_block();
+    for ty in &return_types {
+        builder.append_block_param(resumed, *ty);
+    }
+    builder.ins().jump(resumed, &return_values);
+
+    {
+        builder.switch_to_block(host_suspend);
+        builder.seal_block(host_suspend);
+        builder.set_cold_block(host_suspend);
+
+        call_builtin!(builder, env, tc_suspend_to_host(tag_addr));
+        let return_values = vmctx_load_payloads(env, builder, &return_types);
+        builder.ins().jump(resumed, &return_values);
+    }
+
+    builder.switch_to_block(resumed);
+    builder.seal_block(resumed);
+    builder.block_params(resumed).to_vec()
 }
 
 #[allow(clippy::cast_possible_truncation, reason = "TODO")]
@@ -2313,7 +2750,7 @@ pub(crate) fn translate_switch<'a>(
         let tag_addr = shared::tag_address(env, builder, tag_index);
         let active_stack_chain = vmctx.load_stack_chain(env, builder);
         let (handler_stack_chain, last_ancestor, _handler_index) =
-            search_handler(env, builder, &active_stack_chain, tag_addr, false);
+            search_handler(env, builder, &active_stack_chain, tag_addr, false, None);
         let mut last_ancestor = tc::VMContRef::new(last_ancestor);
 
         // If we get here, the search_handler logic succeeded (i.e., did not trap).
@@ -2504,6 +2941,7 @@ pub(crate) fn translate_switch<'a>(
             tmp_control_context,
             switch_payload,
         );
+        env.after_stack_switch(builder);
 
         emit_debug_println!(
             env,
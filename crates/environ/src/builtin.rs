@@ -41,6 +41,12 @@ macro_rules! foreach_builtin_function {
             out_of_gas(vmctx: vmctx) -> bool;
             // Invoked when we reach a new epoch.
             new_epoch(vmctx: vmctx) -> i64;
+            // Invoked after wasm's `global.set` when global watchpoints are
+            // enabled.
+            global_set_notify(vmctx: vmctx, global: i32) -> bool;
+            // Invoked before each wasm instruction when single-stepping is
+            // enabled.
+            single_step(vmctx: vmctx, offset: i32) -> bool;
             // Invoked before malloc returns.
             #[cfg(feature = "wmemcheck")]
             check_malloc(vmctx: vmctx, addr: i32, len: i32) -> bool;
@@ -224,6 +230,9 @@ macro_rules! foreach_builtin_function {
             // and do not actually deallocate.
             // `old_size` must be smaller than `new_size`
             tc_reallocate(vmctx: vmctx, ptr: pointer, old_size: i64, new_size: i64, align: i64) -> pointer;
+            // Hands a suspension to a tag with no handler in wasm over to the
+            // host handler registered for the tag, trapping if there is none.
+            tc_suspend_to_host(vmctx: vmctx, tag: pointer) -> bool;
 
             // TC baseline
             // Baseline resume
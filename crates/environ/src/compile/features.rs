@@ -0,0 +1,239 @@
+//! Detection of the WebAssembly proposals which a module actually uses.
+
+use wasmparser::{
+    AbstractHeapType, BlockType, CompositeInnerType, FunctionBody, HeapType, MemoryType, Operator,
+    RecGroup, RefType, StorageType, TableType, ValType, WasmFeatures,
+};
+
+/// Accumulates the set of proposals used by a module as it is translated.
+///
+/// This is conservative in the sense that every proposal recorded is
+/// definitely used by the module, but constant expressions are not inspected,
+/// so for example use of the extended-const proposal isn't detected.
+#[derive(Debug)]
+pub(crate) struct FeatureDetector {
+    features: WasmFeatures,
+    memories: u32,
+    tables: u32,
+}
+
+impl Default for FeatureDetector {
+    fn default() -> Self {
+        // Note that `WasmFeatures::default()` is the default set of features
+        // enabled by `wasmparser`, not the empty set.
+        Self {
+            features: WasmFeatures::empty(),
+            memories: 0,
+            tables: 0,
+        }
+    }
+}
+
+impl FeatureDetector {
+    /// Returns the set of features detected so far.
+    pub fn features(&self) -> WasmFeatures {
+        self.features
+    }
+
+    pub fn rec_group(&mut self, group: &RecGroup) {
+        if group.is_explicit_rec_group() {
+            self.features |= WasmFeatures::GC;
+        }
+        for ty in group.types() {
+            if !ty.is_final || ty.supertype_idx.is_some() {
+                self.features |= WasmFeatures::GC;
+            }
+            match &ty.composite_type.inner {
+                CompositeInnerType::Func(f) => {
+                    if f.results().len() > 1 {
+                        self.features |= WasmFeatures::MULTI_VALUE;
+                    }
+                    for ty in f.params().iter().chain(f.results()) {
+                        self.valtype(*ty);
+                    }
+                }
+                CompositeInnerType::Array(a) => {
+                    self.features |= WasmFeatures::GC;
+                    self.storage_type(a.0.element_type);
+                }
+                CompositeInnerType::Struct(s) => {
+                    self.features |= WasmFeatures::GC;
+                    for field in s.fields.iter() {
+                        self.storage_type(field.element_type);
+                    }
+                }
+                CompositeInnerType::Cont(_) => {
+                    self.features |= WasmFeatures::STACK_SWITCHING;
+                }
+            }
+        }
+    }
+
+    pub fn memory(&mut self, ty: &MemoryType) {
+        self.memories += 1;
+        if self.memories > 1 {
+            self.features |= WasmFeatures::MULTI_MEMORY;
+        }
+        if ty.memory64 {
+            self.features |= WasmFeatures::MEMORY64;
+        }
+        if ty.shared {
+            self.features |= WasmFeatures::THREADS;
+        }
+        if ty.page_size_log2.is_some() {
+            self.features |= WasmFeatures::CUSTOM_PAGE_SIZES;
+        }
+    }
+
+    pub fn table(&mut self, ty: &TableType) {
+        self.tables += 1;
+        if self.tables > 1 {
+            self.features |= WasmFeatures::REFERENCE_TYPES;
+        }
+        if ty.table64 {
+            self.features |= WasmFeatures::MEMORY64;
+        }
+        if ty.element_type != RefType::FUNCREF {
+            self.reftype(ty.element_type);
+        }
+    }
+
+    pub fn valtype(&mut self, ty: ValType) {
+        match ty {
+            ValType::I32 | ValType::I64 | ValType::F32 | ValType::F64 => {}
+            ValType::V128 => self.features |= WasmFeatures::SIMD,
+            ValType::Ref(r) => self.reftype(r),
+        }
+    }
+
+    fn storage_type(&mut self, ty: StorageType) {
+        if let StorageType::Val(ty) = ty {
+            self.valtype(ty);
+        }
+    }
+
+    fn reftype(&mut self, ty: RefType) {
+        self.features |= WasmFeatures::REFERENCE_TYPES;
+        if !ty.is_nullable() {
+            self.features |= WasmFeatures::FUNCTION_REFERENCES;
+        }
+        self.heap_type(ty.heap_type());
+    }
+
+    fn heap_type(&mut self, ty: HeapType) {
+        let ty = match ty {
+            HeapType::Concrete(_) => {
+                self.features |= WasmFeatures::FUNCTION_REFERENCES;
+                return;
+            }
+            HeapType::Abstract { shared, ty } => {
+                if shared {
+                    self.features |= WasmFeatures::SHARED_EVERYTHING_THREADS;
+                }
+                ty
+            }
+        };
+        self.features |= match ty {
+            AbstractHeapType::Func | AbstractHeapType::Extern => WasmFeatures::REFERENCE_TYPES,
+            AbstractHeapType::Any
+            | AbstractHeapType::Eq
+            | AbstractHeapType::I31
+            | AbstractHeapType::Array
+            | AbstractHeapType::Struct
+            | AbstractHeapType::None
+            | AbstractHeapType::NoFunc
+            | AbstractHeapType::NoExtern => WasmFeatures::GC,
+            AbstractHeapType::Exn | AbstractHeapType::NoExn => WasmFeatures::EXCEPTIONS,
+            AbstractHeapType::Cont | AbstractHeapType::NoCont => WasmFeatures::STACK_SWITCHING,
+        };
+    }
+
+    fn block_type(&mut self, ty: BlockType) {
+        match ty {
+            BlockType::Empty => {}
+            BlockType::Type(ty) => self.valtype(ty),
+            BlockType::FuncType(_) => self.features |= WasmFeatures::MULTI_VALUE,
+        }
+    }
+
+    /// Records the features used by the locals and instructions of `body`.
+    pub fn func_body(&mut self, body: &FunctionBody<'_>) -> wasmparser::Result<()> {
+        for pair in body.get_locals_reader()? {
+            let (_, ty) = pair?;
+            self.valtype(ty);
+        }
+        let mut ops = body.get_operators_reader()?;
+        while !ops.eof() {
+            self.operator(&ops.read()?);
+        }
+        Ok(())
+    }
+
+    fn operator(&mut self, op: &Operator<'_>) {
+        let proposal = visit_proposal(op).or_else(|| simd_proposal(op));
+        if let Some(proposal) = proposal {
+            self.features |= proposal_feature(proposal);
+        }
+        match op {
+            Operator::Block { blockty }
+            | Operator::Loop { blockty }
+            | Operator::If { blockty }
+            | Operator::Try { blockty }
+            | Operator::TryTable {
+                try_table: wasmparser::TryTable { ty: blockty, .. },
+            } => self.block_type(*blockty),
+            Operator::TypedSelect { ty } => self.valtype(*ty),
+            Operator::RefNull { hty } => self.heap_type(*hty),
+            _ => {}
+        }
+    }
+}
+
+/// Maps the name of a proposal, as used by `wasmparser`'s operator macros, to
+/// the corresponding feature.
+fn proposal_feature(proposal: &str) -> WasmFeatures {
+    match proposal {
+        "sign_extension" => WasmFeatures::SIGN_EXTENSION,
+        "saturating_float_to_int" => WasmFeatures::SATURATING_FLOAT_TO_INT,
+        "bulk_memory" => WasmFeatures::BULK_MEMORY,
+        "reference_types" => WasmFeatures::REFERENCE_TYPES,
+        "simd" => WasmFeatures::SIMD,
+        "relaxed_simd" => WasmFeatures::RELAXED_SIMD,
+        "threads" => WasmFeatures::THREADS,
+        "tail_call" => WasmFeatures::TAIL_CALL,
+        "exceptions" => WasmFeatures::EXCEPTIONS,
+        "legacy_exceptions" => WasmFeatures::LEGACY_EXCEPTIONS,
+        "function_references" => WasmFeatures::FUNCTION_REFERENCES,
+        "gc" => WasmFeatures::GC,
+        "memory_control" => WasmFeatures::MEMORY_CONTROL,
+        "shared_everything_threads" => WasmFeatures::SHARED_EVERYTHING_THREADS,
+        "stack_switching" => WasmFeatures::STACK_SWITCHING,
+        "wide_arithmetic" => WasmFeatures::WIDE_ARITHMETIC,
+        _ => WasmFeatures::empty(),
+    }
+}
+
+macro_rules! define_proposal {
+    ($name:ident $( @$proposal:ident $op:ident $({ $($arg:ident: $argty:ty),* })? => $visit:ident $ann:tt)*) => {
+        /// Returns the proposal which introduced `op`, if it's covered by this
+        /// group of operators.
+        fn $name(op: &Operator<'_>) -> Option<&'static str> {
+            match op {
+                $(Operator::$op { .. } => Some(stringify!($proposal)),)*
+                #[allow(unreachable_patterns, reason = "depends on the operator group")]
+                _ => None,
+            }
+        }
+    };
+}
+
+macro_rules! define_visit_proposal {
+    ($($t:tt)*) => { define_proposal!(visit_proposal $($t)*); };
+}
+
+macro_rules! define_simd_proposal {
+    ($($t:tt)*) => { define_proposal!(simd_proposal $($t)*); };
+}
+
+wasmparser::for_each_visit_operator!(define_visit_proposal);
+wasmparser::for_each_visit_simd_operator!(define_simd_proposal);
@@ -17,6 +17,7 @@ use std::path;
 use std::sync::Arc;
 
 mod address_map;
+mod features;
 mod module_artifacts;
 mod module_environ;
 mod module_types;
@@ -81,6 +82,9 @@ pub enum RelocationTarget {
     HostLibcall(obj::LibCall),
     /// A pulley->host call from the interpreter.
     PulleyHostcall(u32),
+    /// A direct call to a host intrinsic, where the index is the position of
+    /// the intrinsic in the engine's configured list of intrinsics.
+    HostIntrinsic(u32),
 }
 
 /// Implementation of an incremental compilation's key/value cache store.
@@ -97,6 +101,20 @@ pub trait CacheStore: Send + Sync + std::fmt::Debug {
     ///
     /// Returns false when insertion in the cache failed.
     fn insert(&self, key: &[u8], value: Vec<u8>) -> bool;
+
+    /// Evicts the entry for `key` from the cache, if any.
+    ///
+    /// This is called when an entry returned by `Self::get` turned out to be
+    /// unusable, for example because it was corrupted or produced by an
+    /// incompatible build. Beyond this, eviction is left entirely to the
+    /// implementation of the store.
+    ///
+    /// Returns false when the entry could not be removed. The default
+    /// implementation does nothing and returns false.
+    fn remove(&self, key: &[u8]) -> bool {
+        let _ = key;
+        false
+    }
 }
 
 /// Abstract trait representing the ability to create a `Compiler` below.
@@ -3,9 +3,9 @@
 
 use crate::prelude::*;
 use crate::{
-    obj, CompiledFunctionInfo, CompiledModuleInfo, DebugInfoData, DefinedFuncIndex, FunctionLoc,
-    FunctionName, MemoryInitialization, Metadata, ModuleInternedTypeIndex, ModuleTranslation,
-    PrimaryMap, Tunables,
+    obj, CompiledFunctionInfo, CompiledModuleInfo, DebugInfoData, DefinedFuncIndex, EntityRef,
+    FunctionLoc, FunctionName, MemoryInitialization, Metadata, Module, ModuleInternedTypeIndex,
+    ModuleTranslation, PrimaryMap, Tunables,
 };
 use anyhow::{bail, Result};
 use object::write::{Object, SectionId, StandardSegment, WritableBuffer};
@@ -119,6 +119,7 @@ impl<'a> ObjectBuilder<'a> {
         funcs: PrimaryMap<DefinedFuncIndex, CompiledFunctionInfo>,
         wasm_to_array_trampolines: Vec<(ModuleInternedTypeIndex, FunctionLoc)>,
     ) -> Result<CompiledModuleInfo> {
+        let required_features = translation.required_features().bits().into();
         let ModuleTranslation {
             mut module,
             debuginfo,
@@ -188,12 +189,11 @@ impl<'a> ObjectBuilder<'a> {
                     segment.data.end = segment.data.end.checked_add(data_offset).unwrap();
                 }
             }
-            MemoryInitialization::Static { map } => {
-                for (_, segment) in map {
-                    if let Some(segment) = segment {
-                        segment.data.start = segment.data.start.checked_add(data_offset).unwrap();
-                        segment.data.end = segment.data.end.checked_add(data_offset).unwrap();
-                    }
+            MemoryInitialization::Static { map, segments } => {
+                let images = map.values_mut().filter_map(|segment| segment.as_mut());
+                for segment in images.chain(segments.iter_mut().map(|(_, segment)| segment)) {
+                    segment.data.start = segment.data.start.checked_add(data_offset).unwrap();
+                    segment.data.end = segment.data.end.checked_add(data_offset).unwrap();
                 }
             }
         }
@@ -217,6 +217,8 @@ impl<'a> ObjectBuilder<'a> {
             self.push_debuginfo(&mut dwarf, &debuginfo);
         }
 
+        let max_stack_size = max_stack_size(&module, &funcs);
+
         Ok(CompiledModuleInfo {
             module,
             funcs,
@@ -227,6 +229,8 @@ impl<'a> ObjectBuilder<'a> {
                 code_section_offset: debuginfo.wasm_file.code_section_offset,
                 has_wasm_debuginfo: self.tunables.parse_wasm_debuginfo,
                 dwarf,
+                max_stack_size,
+                required_features,
             },
         })
     }
@@ -316,3 +320,65 @@ impl FinishedObject for Vec<u8> {
         }
     }
 }
+
+/// Computes the maximum native stack size used by a call into any of `funcs`,
+/// following direct calls between them.
+///
+/// Returns `None` if this can't be bounded because a function is recursive or
+/// makes calls whose callee isn't known statically.
+fn max_stack_size(
+    module: &Module,
+    funcs: &PrimaryMap<DefinedFuncIndex, CompiledFunctionInfo>,
+) -> Option<u32> {
+    #[derive(Clone, Copy)]
+    enum State {
+        Unvisited,
+        Visiting,
+        Done(u32),
+    }
+    let mut states = vec![State::Unvisited; funcs.len()];
+
+    // The call graph is walked with an explicit stack, as it may be deep
+    // enough to overflow the native stack, where each entry is a function and
+    // the index of the next of its callees to visit.
+    let mut max = 0;
+    let mut stack = Vec::new();
+    for root in funcs.keys() {
+        if let State::Unvisited = states[root.index()] {
+            states[root.index()] = State::Visiting;
+            stack.push((root, 0));
+        }
+        while let Some(&(func, next)) = stack.last() {
+            let info = &funcs[func].wasm_func_info;
+            let calls = info.direct_calls.as_deref()?;
+            if let Some(callee) = calls.get(next) {
+                stack.last_mut().unwrap().1 += 1;
+                // Calls to imports are never direct calls, so this is only
+                // `None` for invalid metadata.
+                let callee = module.defined_func_index(*callee)?;
+                match states[callee.index()] {
+                    State::Unvisited => {
+                        states[callee.index()] = State::Visiting;
+                        stack.push((callee, 0));
+                    }
+                    State::Visiting => return None,
+                    State::Done(_) => {}
+                }
+                continue;
+            }
+
+            let mut callees = 0;
+            for callee in calls {
+                let callee = module.defined_func_index(*callee)?;
+                if let State::Done(size) = states[callee.index()] {
+                    callees = callees.max(size);
+                }
+            }
+            let size = info.frame_size.checked_add(callees)?;
+            states[func.index()] = State::Done(size);
+            max = max.max(size);
+            stack.pop();
+        }
+    }
+    Some(max)
+}
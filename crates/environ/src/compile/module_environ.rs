@@ -1,3 +1,4 @@
+use crate::compile::features::FeatureDetector;
 use crate::module::{
     FuncRefIndex, Initializer, MemoryInitialization, MemoryInitializer, Module, TableSegment,
     TableSegmentElements,
@@ -10,7 +11,7 @@ use crate::{
     TableIndex, TableInitialValue, TagIndex, Tunables, TypeConvert, TypeIndex, Unsigned, WasmError,
     WasmHeapTopType, WasmHeapType, WasmResult, WasmValType, WasmparserTypeConverter,
 };
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use cranelift_entity::packed_option::ReservedValue;
 use std::borrow::Cow;
 use std::collections::HashMap;
@@ -34,6 +35,10 @@ pub struct ModuleEnvironment<'a, 'data> {
     // Various bits and pieces of configuration
     validator: &'a mut Validator,
     tunables: &'a Tunables,
+
+    /// A wasm binary holding debug information for the module being
+    /// translated, which was split out of it by its toolchain.
+    external_debug_info: Option<&'data [u8]>,
 }
 
 /// The result of translating via `ModuleEnvironment`. Function bodies are not
@@ -54,6 +59,10 @@ pub struct ModuleTranslation<'data> {
     /// References to the function bodies.
     pub function_body_inputs: PrimaryMap<DefinedFuncIndex, FunctionBodyData<'data>>,
 
+    /// The functions which are small enough to be inlined into their callers,
+    /// see `Tunables::inline_threshold`.
+    pub inline_candidates: HashMap<DefinedFuncIndex, InlineCandidate<'data>>,
+
     /// A list of type signatures which are considered exported from this
     /// module, or those that can possibly be called. This list is sorted, and
     /// trampolines for each of these signatures are required.
@@ -66,6 +75,14 @@ pub struct ModuleTranslation<'data> {
     /// configuration.
     pub has_unparsed_debuginfo: bool,
 
+    /// Whether NaN canonicalization was requested for this module in
+    /// particular, regardless of the compiler's own configuration.
+    pub nan_canonicalization: bool,
+
+    /// Imported functions which are host intrinsics and should be called
+    /// directly, mapped to the index of the intrinsic being called.
+    pub host_intrinsics: HashMap<FuncIndex, u32>,
+
     /// List of data segments found in this module which should be concatenated
     /// together for the final compiled artifact.
     ///
@@ -95,6 +112,9 @@ pub struct ModuleTranslation<'data> {
     /// which function is currently being defined.
     code_index: u32,
 
+    /// The proposals found to be used by this module so far.
+    features: FeatureDetector,
+
     /// The type information of the current module made available at the end of the
     /// validation process.
     types: Option<Types>,
@@ -107,6 +127,12 @@ impl<'data> ModuleTranslation<'data> {
             .as_ref()
             .expect("module type information to be available")
     }
+
+    /// Returns the set of WebAssembly proposals this module was found to use
+    /// during translation.
+    pub fn required_features(&self) -> wasmparser::WasmFeatures {
+        self.features.features()
+    }
 }
 
 /// Contains function data: byte code and its offset in the module.
@@ -117,6 +143,18 @@ pub struct FunctionBodyData<'a> {
     pub validator: FuncToValidate<ValidatorResources>,
 }
 
+/// The body of a function which may be inlined into its callers.
+///
+/// Unlike `FunctionBodyData` this can be translated any number of times, using
+/// the `ValidatorResources` of any other function in the same module.
+#[derive(Clone)]
+pub struct InlineCandidate<'a> {
+    /// The body of the function, containing code and locals.
+    pub body: FunctionBody<'a>,
+    /// The type index to validate the body with, see `FuncToValidate::ty`.
+    pub ty: u32,
+}
+
 #[derive(Debug, Default)]
 #[allow(missing_docs, reason = "self-describing fields")]
 pub struct DebugInfoData<'a> {
@@ -172,9 +210,21 @@ impl<'a, 'data> ModuleEnvironment<'a, 'data> {
             types,
             tunables,
             validator,
+            external_debug_info: None,
         }
     }
 
+    /// Configures a wasm binary whose DWARF custom sections describe the
+    /// module being translated, for toolchains which split debug information
+    /// out of the module itself.
+    ///
+    /// Debug sections found in `wasm` take precedence over those in the
+    /// module. All other sections of `wasm` are ignored.
+    pub fn external_debug_info(mut self, wasm: &'data [u8]) -> Self {
+        self.external_debug_info = Some(wasm);
+        self
+    }
+
     /// Translate a wasm module using this environment.
     ///
     /// This function will translate the `data` provided with `parser`,
@@ -194,6 +244,11 @@ impl<'a, 'data> ModuleEnvironment<'a, 'data> {
             self.translate_payload(payload?)?;
         }
 
+        if let Some(wasm) = self.external_debug_info {
+            self.translate_external_debug_info(wasm)
+                .context("failed to parse external debug info")?;
+        }
+
         Ok(self.result)
     }
 
@@ -238,6 +293,9 @@ impl<'a, 'data> ModuleEnvironment<'a, 'data> {
 
             Payload::TypeSection(types) => {
                 self.validator.type_section(&types)?;
+                for group in types.clone() {
+                    self.result.features.rec_group(&group?);
+                }
 
                 let count = self.validator.types(0).unwrap().core_type_count_in_module();
                 log::trace!("interning {count} Wasm types");
@@ -307,14 +365,17 @@ impl<'a, 'data> ModuleEnvironment<'a, 'data> {
                             EntityType::Function(EngineOrModuleTypeIndex::Module(interned_index))
                         }
                         TypeRef::Memory(ty) => {
+                            self.result.features.memory(&ty);
                             self.result.module.num_imported_memories += 1;
                             EntityType::Memory(ty.into())
                         }
                         TypeRef::Global(ty) => {
+                            self.result.features.valtype(ty.content_type);
                             self.result.module.num_imported_globals += 1;
                             EntityType::Global(self.convert_global_type(&ty))
                         }
                         TypeRef::Table(ty) => {
+                            self.result.features.table(&ty);
                             self.result.module.num_imported_tables += 1;
                             EntityType::Table(self.convert_table_type(&ty)?)
                         }
@@ -357,6 +418,7 @@ impl<'a, 'data> ModuleEnvironment<'a, 'data> {
 
                 for entry in tables {
                     let wasmparser::Table { ty, init } = entry?;
+                    self.result.features.table(&ty);
                     let table = self.convert_table_type(&ty)?;
                     self.result.module.tables.push(table);
                     let init = match init {
@@ -387,6 +449,7 @@ impl<'a, 'data> ModuleEnvironment<'a, 'data> {
 
                 for entry in memories {
                     let memory = entry?;
+                    self.result.features.memory(&memory);
                     self.result.module.memories.push(memory.into());
                 }
             }
@@ -417,6 +480,7 @@ impl<'a, 'data> ModuleEnvironment<'a, 'data> {
 
                 for entry in globals {
                     let wasmparser::Global { ty, init_expr } = entry?;
+                    self.result.features.valtype(ty.content_type);
                     let (initializer, escaped) = ConstExpr::from_wasmparser(init_expr)?;
                     for f in escaped {
                         self.flag_func_escaped(f);
@@ -546,6 +610,7 @@ impl<'a, 'data> ModuleEnvironment<'a, 'data> {
 
             Payload::CodeSectionEntry(body) => {
                 let validator = self.validator.code_section_entry(&body)?;
+                self.result.features.func_body(&body)?;
                 let func_index =
                     self.result.code_index + self.result.module.num_imported_funcs as u32;
                 let func_index = FuncIndex::from_u32(func_index);
@@ -568,6 +633,15 @@ impl<'a, 'data> ModuleEnvironment<'a, 'data> {
                             params: sig.params().into(),
                         });
                 }
+                let threshold = self.tunables.inline_threshold;
+                if threshold > 0 && body.range().len() <= threshold as usize {
+                    let index = DefinedFuncIndex::from_u32(self.result.code_index);
+                    let candidate = InlineCandidate {
+                        body: body.clone(),
+                        ty: validator.ty,
+                    };
+                    self.result.inline_candidates.insert(index, candidate);
+                }
                 self.result
                     .function_body_inputs
                     .push(FunctionBodyData { validator, body });
@@ -682,6 +756,18 @@ and for re-adding support for interface types you can see this issue:
         Ok(())
     }
 
+    fn translate_external_debug_info(&mut self, wasm: &'data [u8]) -> Result<()> {
+        for payload in Parser::new(0).parse_all(wasm) {
+            if let Payload::CustomSection(s) = payload? {
+                let name = s.name().trim_end_matches(".dwo");
+                if name.starts_with(".debug_") {
+                    self.dwarf_section(name, &s);
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn register_custom_section(&mut self, section: &CustomSectionReader<'data>) {
         match section.as_known() {
             KnownCustom::Name(name) => {
@@ -947,6 +1033,7 @@ impl ModuleTranslation<'_> {
         struct InitMemoryAtCompileTime<'a> {
             module: &'a Module,
             info: &'a mut PrimaryMap<MemoryIndex, Memory>,
+            segments: &'a mut Vec<(MemoryIndex, StaticMemoryInitializer)>,
             idx: usize,
         }
         impl InitMemory for InitMemoryAtCompileTime<'_> {
@@ -983,10 +1070,12 @@ impl ModuleTranslation<'_> {
                     info.max_addr = info.max_addr.max(init.offset + data_len);
                     info.segments.push((self.idx, init.clone()));
                 }
+                self.segments.push((memory, init.clone()));
                 self.idx += 1;
                 true
             }
         }
+        let mut segments = Vec::new();
         let ok = self
             .module
             .memory_initialization
@@ -994,6 +1083,7 @@ impl ModuleTranslation<'_> {
                 idx: 0,
                 module: &self.module,
                 info: &mut info,
+                segments: &mut segments,
             });
         if !ok {
             return;
@@ -1044,6 +1134,7 @@ impl ModuleTranslation<'_> {
         let data = mem::replace(&mut self.data, Vec::new());
         let mut map = PrimaryMap::with_capacity(info.len());
         let mut module_data_size = 0u32;
+        let mut found = vec![false; segments.len()];
         for (memory, info) in info.iter() {
             // Create the in-memory `image` which is the initialized contents of
             // this linear memory.
@@ -1088,22 +1179,47 @@ impl ModuleTranslation<'_> {
                 image.drain(..i);
             }
             let mut len = u64::try_from(image.len()).unwrap();
+            let image_offset = offset;
 
             // The goal is to enable mapping this image directly into memory, so
             // the offset into linear memory must be a multiple of the page
             // size. If that's not already the case then the image is padded at
             // the front and back with extra zeros as necessary
-            if offset % page_size != 0 {
-                let zero_padding = offset % page_size;
-                self.data.push(vec![0; zero_padding as usize].into());
-                offset -= zero_padding;
-                len += zero_padding;
+            let front_padding = offset % page_size;
+            offset -= front_padding;
+            len += front_padding;
+            let back_padding = (page_size - len % page_size) % page_size;
+            len += back_padding;
+
+            // Segments whose contents survived intact in the image are found
+            // there, rather than storing their contents a second time.
+            for (i, (segment_memory, init)) in segments.iter_mut().enumerate() {
+                let contents = &data[i];
+                if *segment_memory != memory || contents.is_empty() {
+                    continue;
+                }
+                let end = init.offset + u64::try_from(contents.len()).unwrap();
+                let in_image = init.offset >= offset
+                    && end <= offset + len
+                    && (init.offset..end).zip(contents.iter()).all(|(addr, byte)| {
+                        let image_byte = addr
+                            .checked_sub(image_offset)
+                            .and_then(|i| image.get(usize::try_from(i).unwrap()));
+                        image_byte.copied().unwrap_or(0) == *byte
+                    });
+                if in_image {
+                    let start = module_data_size + u32::try_from(init.offset - offset).unwrap();
+                    init.data = start..start + u32::try_from(contents.len()).unwrap();
+                    found[i] = true;
+                }
+            }
+
+            if front_padding != 0 {
+                self.data.push(vec![0; front_padding as usize].into());
             }
             self.data.push(image.into());
-            if len % page_size != 0 {
-                let zero_padding = page_size - (len % page_size);
-                self.data.push(vec![0; zero_padding as usize].into());
-                len += zero_padding;
+            if back_padding != 0 {
+                self.data.push(vec![0; back_padding as usize].into());
             }
 
             // Offset/length should now always be page-aligned.
@@ -1130,8 +1246,25 @@ impl ModuleTranslation<'_> {
             assert_eq!(idx, memory);
             module_data_size += len;
         }
+
+        // The contents of any other segments, which were overwritten by later
+        // segments, are stored after all of the images.
+        for (i, (_, init)) in segments.iter_mut().enumerate() {
+            if data[i].is_empty() {
+                init.data = 0..0;
+                continue;
+            }
+            if found[i] {
+                continue;
+            }
+            let len = u32::try_from(data[i].len()).unwrap();
+            init.data = module_data_size..module_data_size + len;
+            self.data.push(data[i].clone());
+            module_data_size += len;
+        }
+
         self.data_align = Some(page_size);
-        self.module.memory_initialization = MemoryInitialization::Static { map };
+        self.module.memory_initialization = MemoryInitialization::Static { map, segments };
     }
 
     /// Attempts to convert the module's table initializers to
@@ -2,7 +2,7 @@
 //! which are serialized with `bincode` into output ELF files.
 
 use crate::{
-    component::{Component, ComponentTypes, TrampolineIndex, TypeComponentIndex},
+    component::{Component, ComponentTypes, TrampolineIndex, TrampolineKind, TypeComponentIndex},
     CompiledModuleInfo, FunctionLoc, PrimaryMap, StaticModuleIndex,
 };
 use serde_derive::{Deserialize, Serialize};
@@ -38,6 +38,10 @@ pub struct CompiledComponentInfo {
     /// lowering.
     pub trampolines: PrimaryMap<TrampolineIndex, AllCallFunc<FunctionLoc>>,
 
+    /// What each trampoline in `trampolines` does, used to describe where the
+    /// imports of core wasm modules come from.
+    pub trampoline_kinds: PrimaryMap<TrampolineIndex, TrampolineKind>,
+
     /// The location of the wasm-to-array trampoline for the `resource.drop`
     /// intrinsic.
     pub resource_drop_wasm_to_array_trampoline: Option<FunctionLoc>,
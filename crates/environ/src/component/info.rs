@@ -661,6 +661,19 @@ pub enum Trampoline {
 
     /// Same as `ResourceEnterCall` except for when exiting a call.
     ResourceExitCall,
+
+    /// An intrinsic used by FACT-generated modules which will transfer the
+    /// readable end of a future from one table to another. Used in
+    /// component-to-component adapter trampolines.
+    FutureTransfer,
+
+    /// Same as `FutureTransfer` but for streams.
+    StreamTransfer,
+
+    /// An intrinsic used by FACT-generated modules which reports each call
+    /// they make to a `realloc` function, along with its arguments and result,
+    /// so it can be logged if canonical ABI tracing is enabled for the store.
+    TraceRealloc,
 }
 
 impl Trampoline {
@@ -688,6 +701,74 @@ impl Trampoline {
             ResourceTransferBorrow => format!("component-resource-transfer-borrow"),
             ResourceEnterCall => format!("component-resource-enter-call"),
             ResourceExitCall => format!("component-resource-exit-call"),
+            FutureTransfer => format!("component-future-transfer"),
+            StreamTransfer => format!("component-stream-transfer"),
+            TraceRealloc => format!("component-trace-realloc"),
+        }
+    }
+
+    /// Returns a serializable summary of what this trampoline does.
+    pub fn kind(&self) -> TrampolineKind {
+        match self {
+            Trampoline::LowerImport {
+                index,
+                lower_ty,
+                options,
+            } => TrampolineKind::LowerImport {
+                index: *index,
+                lower_ty: *lower_ty,
+                string_encoding: options.string_encoding,
+            },
+            Trampoline::Transcoder { .. } => TrampolineKind::Transcoder,
+            Trampoline::AlwaysTrap => TrampolineKind::AlwaysTrap,
+            Trampoline::ResourceNew(_) => TrampolineKind::ResourceNew,
+            Trampoline::ResourceRep(_) => TrampolineKind::ResourceRep,
+            Trampoline::ResourceDrop(_) => TrampolineKind::ResourceDrop,
+            Trampoline::ResourceTransferOwn
+            | Trampoline::ResourceTransferBorrow
+            | Trampoline::ResourceEnterCall
+            | Trampoline::ResourceExitCall => TrampolineKind::ResourceIntrinsic,
+            Trampoline::FutureTransfer | Trampoline::StreamTransfer => {
+                TrampolineKind::AsyncIntrinsic
+            }
+            Trampoline::TraceRealloc => TrampolineKind::TraceIntrinsic,
         }
     }
 }
+
+/// A summary of a [`Trampoline`] which, unlike `Trampoline`, is kept in the
+/// final compilation artifact.
+///
+/// This is only used to describe the origin of core wasm imports within a
+/// component for debugging and isn't needed to instantiate the component.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum TrampolineKind {
+    /// See [`Trampoline::LowerImport`].
+    LowerImport {
+        /// The runtime lowering state that this trampoline will access.
+        index: LoweredIndex,
+        /// The type of the function that is being lowered.
+        lower_ty: TypeFuncIndex,
+        /// The string encoding used when lowering this function.
+        string_encoding: StringEncoding,
+    },
+    /// See [`Trampoline::Transcoder`].
+    Transcoder,
+    /// See [`Trampoline::AlwaysTrap`].
+    AlwaysTrap,
+    /// See [`Trampoline::ResourceNew`].
+    ResourceNew,
+    /// See [`Trampoline::ResourceRep`].
+    ResourceRep,
+    /// See [`Trampoline::ResourceDrop`].
+    ResourceDrop,
+    /// Any of the resource-related intrinsics used by FACT-generated adapter
+    /// modules, such as [`Trampoline::ResourceTransferOwn`].
+    ResourceIntrinsic,
+    /// Any of the future- and stream-related intrinsics used by FACT-generated
+    /// adapter modules, such as [`Trampoline::FutureTransfer`].
+    AsyncIntrinsic,
+    /// The [`Trampoline::TraceRealloc`] intrinsic used by FACT-generated
+    /// adapter modules.
+    TraceIntrinsic,
+}
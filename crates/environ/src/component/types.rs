@@ -89,6 +89,10 @@ indices! {
     pub struct TypeResultIndex(u32);
     /// Index pointing to a list type in the component model.
     pub struct TypeListIndex(u32);
+    /// Index pointing to a future type in the component model.
+    pub struct TypeFutureIndex(u32);
+    /// Index pointing to a stream type in the component model.
+    pub struct TypeStreamIndex(u32);
 
     /// Index pointing to a resource table within a component.
     ///
@@ -106,6 +110,20 @@ indices! {
     /// component instance that it's recorded for.
     pub struct TypeResourceTableIndex(u32);
 
+    /// Index pointing to a table of future handles within a component.
+    ///
+    /// Like `TypeResourceTableIndex` this is a Wasmtime-specific index which
+    /// pairs a future type with the component instance whose table holds
+    /// handles of that type. Moving a future between two component instances
+    /// moves its handle from the source instance's table into the
+    /// destination's.
+    pub struct TypeFutureTableIndex(u32);
+
+    /// Index pointing to a table of stream handles within a component.
+    ///
+    /// This is the same as `TypeFutureTableIndex` except for streams.
+    pub struct TypeStreamTableIndex(u32);
+
     /// Index pointing to a resource within a component.
     ///
     /// This index space covers all unique resource type definitions. For
@@ -237,6 +255,10 @@ pub struct ComponentTypes {
     pub(super) options: PrimaryMap<TypeOptionIndex, TypeOption>,
     pub(super) results: PrimaryMap<TypeResultIndex, TypeResult>,
     pub(super) resource_tables: PrimaryMap<TypeResourceTableIndex, TypeResourceTable>,
+    pub(super) futures: PrimaryMap<TypeFutureIndex, TypeFuture>,
+    pub(super) streams: PrimaryMap<TypeStreamIndex, TypeStream>,
+    pub(super) future_tables: PrimaryMap<TypeFutureTableIndex, TypeFutureTable>,
+    pub(super) stream_tables: PrimaryMap<TypeStreamTableIndex, TypeStreamTable>,
 
     pub(super) module_types: Option<ModuleTypes>,
 }
@@ -261,7 +283,9 @@ impl ComponentTypes {
             | InterfaceType::Float32
             | InterfaceType::Char
             | InterfaceType::Own(_)
-            | InterfaceType::Borrow(_) => &CanonicalAbiInfo::SCALAR4,
+            | InterfaceType::Borrow(_)
+            | InterfaceType::Future(_)
+            | InterfaceType::Stream(_) => &CanonicalAbiInfo::SCALAR4,
 
             InterfaceType::U64 | InterfaceType::S64 | InterfaceType::Float64 => {
                 &CanonicalAbiInfo::SCALAR8
@@ -320,6 +344,10 @@ impl_index! {
     impl Index<TypeResultIndex> for ComponentTypes { TypeResult => results }
     impl Index<TypeListIndex> for ComponentTypes { TypeList => lists }
     impl Index<TypeResourceTableIndex> for ComponentTypes { TypeResourceTable => resource_tables }
+    impl Index<TypeFutureIndex> for ComponentTypes { TypeFuture => futures }
+    impl Index<TypeStreamIndex> for ComponentTypes { TypeStream => streams }
+    impl Index<TypeFutureTableIndex> for ComponentTypes { TypeFutureTable => future_tables }
+    impl Index<TypeStreamTableIndex> for ComponentTypes { TypeStreamTable => stream_tables }
 }
 
 // Additionally forward anything that can index `ModuleTypes` to `ModuleTypes`
@@ -464,6 +492,8 @@ pub enum InterfaceType {
     Result(TypeResultIndex),
     Own(TypeResourceTableIndex),
     Borrow(TypeResourceTableIndex),
+    Future(TypeFutureTableIndex),
+    Stream(TypeStreamTableIndex),
 }
 
 impl From<&wasmparser::PrimitiveValType> for InterfaceType {
@@ -992,6 +1022,40 @@ pub struct TypeList {
     pub element: InterfaceType,
 }
 
+/// Shape of a "future" interface type.
+#[derive(Serialize, Deserialize, Clone, Hash, Eq, PartialEq, Debug)]
+pub struct TypeFuture {
+    /// The type of the value eventually produced by the future, if any.
+    pub payload: Option<InterfaceType>,
+}
+
+/// Shape of a "stream" interface type.
+#[derive(Serialize, Deserialize, Clone, Hash, Eq, PartialEq, Debug)]
+pub struct TypeStream {
+    /// The type of the elements produced by the stream.
+    pub payload: InterfaceType,
+}
+
+/// Metadata about a table of future handles added to a component.
+#[derive(Serialize, Deserialize, Clone, Hash, Eq, PartialEq, Debug)]
+pub struct TypeFutureTable {
+    /// The type of the futures in this table.
+    pub ty: TypeFutureIndex,
+
+    /// The component instance that contains this table.
+    pub instance: RuntimeComponentInstanceIndex,
+}
+
+/// Metadata about a table of stream handles added to a component.
+#[derive(Serialize, Deserialize, Clone, Hash, Eq, PartialEq, Debug)]
+pub struct TypeStreamTable {
+    /// The type of the streams in this table.
+    pub ty: TypeStreamIndex,
+
+    /// The component instance that contains this table.
+    pub instance: RuntimeComponentInstanceIndex,
+}
+
 /// Maximum number of flat types, for either params or results.
 pub const MAX_FLAT_TYPES: usize = if MAX_FLAT_PARAMS > MAX_FLAT_RESULTS {
     MAX_FLAT_PARAMS
@@ -226,6 +226,14 @@ impl ResourcesBuilder {
         assert!(prev.is_none());
     }
 
+    /// Returns the component instance that's currently being visited.
+    ///
+    /// Like `convert` this requires that `set_current_instance` has been
+    /// previously called.
+    pub fn current_instance(&self) -> RuntimeComponentInstanceIndex {
+        self.current_instance.expect("current instance not set")
+    }
+
     /// Updates the `current_instance` field to assign instance fields of future
     /// `TypeResourceTableIndex` values produced via `convert`.
     pub fn set_current_instance(&mut self, instance: RuntimeComponentInstanceIndex) {
@@ -64,6 +64,14 @@ pub struct Module<'a> {
     imported_resource_enter_call: Option<FuncIndex>,
     imported_resource_exit_call: Option<FuncIndex>,
 
+    /// Cached versions of imported trampolines for working with futures and
+    /// streams.
+    imported_future_transfer: Option<FuncIndex>,
+    imported_stream_transfer: Option<FuncIndex>,
+
+    /// Cached version of the imported trampoline used to trace `realloc`.
+    imported_trace_realloc: Option<FuncIndex>,
+
     // Current status of index spaces from the imports generated so far.
     imported_funcs: PrimaryMap<FuncIndex, Option<CoreDef>>,
     imported_memories: PrimaryMap<MemoryIndex, CoreDef>,
@@ -186,6 +194,9 @@ impl<'a> Module<'a> {
             imported_resource_transfer_borrow: None,
             imported_resource_enter_call: None,
             imported_resource_exit_call: None,
+            imported_future_transfer: None,
+            imported_stream_transfer: None,
+            imported_trace_realloc: None,
         }
     }
 
@@ -418,6 +429,28 @@ impl<'a> Module<'a> {
         )
     }
 
+    fn import_future_transfer(&mut self) -> FuncIndex {
+        self.import_simple(
+            "future",
+            "transfer",
+            &[ValType::I32, ValType::I32, ValType::I32],
+            &[ValType::I32],
+            Import::FutureTransfer,
+            |me| &mut me.imported_future_transfer,
+        )
+    }
+
+    fn import_stream_transfer(&mut self) -> FuncIndex {
+        self.import_simple(
+            "stream",
+            "transfer",
+            &[ValType::I32, ValType::I32, ValType::I32],
+            &[ValType::I32],
+            Import::StreamTransfer,
+            |me| &mut me.imported_stream_transfer,
+        )
+    }
+
     fn import_resource_enter_call(&mut self) -> FuncIndex {
         self.import_simple(
             "resource",
@@ -440,6 +473,17 @@ impl<'a> Module<'a> {
         )
     }
 
+    fn import_trace_realloc(&mut self) -> FuncIndex {
+        self.import_simple(
+            "trace",
+            "realloc",
+            &[ValType::I64; 5],
+            &[],
+            Import::TraceRealloc,
+            |me| &mut me.imported_trace_realloc,
+        )
+    }
+
     fn translate_helper(&mut self, helper: Helper) -> FunctionId {
         *self.helper_funcs.entry(helper).or_insert_with(|| {
             // Generate a fresh `Function` with a unique id for what we're about to
@@ -560,6 +604,13 @@ pub enum Import {
     /// Tears down a previous entry and handles checking borrow-related
     /// metadata.
     ResourceExitCall,
+    /// Transfers the readable end of a future from one table to another.
+    FutureTransfer,
+    /// Transfers the readable end of a stream from one table to another.
+    StreamTransfer,
+    /// Reports the arguments and result of a call to `realloc` so it can be
+    /// traced.
+    TraceRealloc,
 }
 
 impl Options {
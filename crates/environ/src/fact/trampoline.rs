@@ -17,9 +17,10 @@
 
 use crate::component::{
     CanonicalAbiInfo, ComponentTypesBuilder, FixedEncoding as FE, FlatType, InterfaceType,
-    StringEncoding, Transcode, TypeEnumIndex, TypeFlagsIndex, TypeListIndex, TypeOptionIndex,
-    TypeRecordIndex, TypeResourceTableIndex, TypeResultIndex, TypeTupleIndex, TypeVariantIndex,
-    VariantInfo, FLAG_MAY_ENTER, FLAG_MAY_LEAVE, MAX_FLAT_PARAMS, MAX_FLAT_RESULTS,
+    StringEncoding, Transcode, TypeEnumIndex, TypeFlagsIndex, TypeFutureTableIndex, TypeListIndex,
+    TypeOptionIndex, TypeRecordIndex, TypeResourceTableIndex, TypeResultIndex,
+    TypeStreamTableIndex, TypeTupleIndex, TypeVariantIndex, VariantInfo, FLAG_MAY_ENTER,
+    FLAG_MAY_LEAVE, MAX_FLAT_PARAMS, MAX_FLAT_RESULTS,
 };
 use crate::fact::signature::Signature;
 use crate::fact::transcode::Transcoder;
@@ -588,6 +589,7 @@ impl Compiler<'_, '_> {
 
             // TODO(#6696) - something nonzero, is 1 right?
             InterfaceType::Own(_) | InterfaceType::Borrow(_) => 1,
+            InterfaceType::Future(_) | InterfaceType::Stream(_) => 1,
         };
 
         match self.fuel.checked_sub(cost) {
@@ -622,6 +624,8 @@ impl Compiler<'_, '_> {
                     InterfaceType::Result(t) => self.translate_result(*t, src, dst_ty, dst),
                     InterfaceType::Own(t) => self.translate_own(*t, src, dst_ty, dst),
                     InterfaceType::Borrow(t) => self.translate_borrow(*t, src, dst_ty, dst),
+                    InterfaceType::Future(t) => self.translate_future(*t, src, dst_ty, dst),
+                    InterfaceType::Stream(t) => self.translate_stream(*t, src, dst_ty, dst),
                 }
             }
 
@@ -1301,7 +1305,7 @@ impl Compiler<'_, '_> {
         self.ptr_uconst(dst_opts, factor.into());
         self.ptr_mul(dst_opts);
         self.instruction(LocalTee(dst_byte_len.idx));
-        self.instruction(Call(dst_opts.realloc.unwrap().as_u32()));
+        self.call_realloc(dst_opts);
         self.instruction(LocalSet(dst.ptr.idx));
 
         // Verify that the destination is still in-bounds
@@ -1359,7 +1363,7 @@ impl Compiler<'_, '_> {
         self.instruction(LocalGet(dst_byte_len.idx)); // old_size
         self.ptr_uconst(dst.opts, 1); // align
         self.instruction(LocalGet(dst.len.idx)); // new_size
-        self.instruction(Call(dst.opts.realloc.unwrap().as_u32()));
+        self.call_realloc(dst.opts);
         self.instruction(LocalSet(dst.ptr.idx));
         self.instruction(End);
 
@@ -1448,7 +1452,7 @@ impl Compiler<'_, '_> {
         self.instruction(LocalGet(dst.len.idx));
         self.ptr_uconst(dst.opts, 1);
         self.ptr_shl(dst.opts);
-        self.instruction(Call(dst.opts.realloc.unwrap().as_u32()));
+        self.call_realloc(dst.opts);
         self.instruction(LocalSet(dst.ptr.idx));
         self.instruction(End); // end of shrink-to-fit
 
@@ -1529,7 +1533,7 @@ impl Compiler<'_, '_> {
         self.instruction(LocalGet(dst_byte_len.idx)); // old_size
         self.ptr_uconst(dst.opts, 2); // align
         self.instruction(LocalGet(dst.len.idx)); // new_size
-        self.instruction(Call(dst.opts.realloc.unwrap().as_u32()));
+        self.call_realloc(dst.opts);
         self.instruction(LocalSet(dst.ptr.idx));
 
         self.free_temp_local(dst_byte_len);
@@ -1601,7 +1605,7 @@ impl Compiler<'_, '_> {
         self.instruction(LocalGet(dst_byte_len.idx)); // old_size
         self.ptr_uconst(dst.opts, 2); // align
         self.instruction(LocalGet(dst.len.idx)); // new_size
-        self.instruction(Call(dst.opts.realloc.unwrap().as_u32()));
+        self.call_realloc(dst.opts);
         self.instruction(LocalSet(dst.ptr.idx));
         self.instruction(End);
 
@@ -1626,7 +1630,7 @@ impl Compiler<'_, '_> {
         self.ptr_uconst(dst.opts, 1);
         self.ptr_shl(dst.opts);
         self.instruction(LocalTee(dst_byte_len.idx));
-        self.instruction(Call(dst.opts.realloc.unwrap().as_u32()));
+        self.call_realloc(dst.opts);
         self.instruction(LocalSet(dst.ptr.idx));
 
         // Call the host utf16 transcoding function. This will inflate the
@@ -1665,7 +1669,7 @@ impl Compiler<'_, '_> {
         self.instruction(LocalGet(dst.len.idx));
         self.ptr_uconst(dst.opts, 1);
         self.ptr_shl(dst.opts);
-        self.instruction(Call(dst.opts.realloc.unwrap().as_u32()));
+        self.call_realloc(dst.opts);
         self.instruction(LocalSet(dst.ptr.idx));
         self.instruction(End);
 
@@ -2448,6 +2452,60 @@ impl Compiler<'_, '_> {
         }
     }
 
+    fn translate_future(
+        &mut self,
+        src_ty: TypeFutureTableIndex,
+        src: &Source<'_>,
+        dst_ty: &InterfaceType,
+        dst: &Destination,
+    ) {
+        let dst_ty = match dst_ty {
+            InterfaceType::Future(t) => *t,
+            _ => panic!("expected a `Future`"),
+        };
+        let transfer = self.module.import_future_transfer();
+        self.translate_handle(src_ty.as_u32(), src, dst_ty.as_u32(), dst, transfer);
+    }
+
+    fn translate_stream(
+        &mut self,
+        src_ty: TypeStreamTableIndex,
+        src: &Source<'_>,
+        dst_ty: &InterfaceType,
+        dst: &Destination,
+    ) {
+        let dst_ty = match dst_ty {
+            InterfaceType::Stream(t) => *t,
+            _ => panic!("expected a `Stream`"),
+        };
+        let transfer = self.module.import_stream_transfer();
+        self.translate_handle(src_ty.as_u32(), src, dst_ty.as_u32(), dst, transfer);
+    }
+
+    /// Moves a handle from the table `src_table` into `dst_table` by calling
+    /// the `transfer` intrinsic, storing the new handle into `dst`.
+    fn translate_handle(
+        &mut self,
+        src_table: u32,
+        src: &Source<'_>,
+        dst_table: u32,
+        dst: &Destination,
+        transfer: FuncIndex,
+    ) {
+        self.push_dst_addr(dst);
+        match src {
+            Source::Memory(mem) => self.i32_load(mem),
+            Source::Stack(stack) => self.stack_get(stack, ValType::I32),
+        }
+        self.instruction(I32Const(src_table as i32));
+        self.instruction(I32Const(dst_table as i32));
+        self.instruction(Call(transfer.as_u32()));
+        match dst {
+            Destination::Memory(mem) => self.i32_store(mem),
+            Destination::Stack(stack, _) => self.stack_set(stack, ValType::I32),
+        }
+    }
+
     fn translate_own(
         &mut self,
         src_ty: TypeResourceTableIndex,
@@ -2494,18 +2552,7 @@ impl Compiler<'_, '_> {
         dst: &Destination,
         transfer: FuncIndex,
     ) {
-        self.push_dst_addr(dst);
-        match src {
-            Source::Memory(mem) => self.i32_load(mem),
-            Source::Stack(stack) => self.stack_get(stack, ValType::I32),
-        }
-        self.instruction(I32Const(src_ty.as_u32() as i32));
-        self.instruction(I32Const(dst_ty.as_u32() as i32));
-        self.instruction(Call(transfer.as_u32()));
-        match dst {
-            Destination::Memory(mem) => self.i32_store(mem),
-            Destination::Stack(stack, _) => self.stack_set(stack, ValType::I32),
-        }
+        self.translate_handle(src_ty.as_u32(), src, dst_ty.as_u32(), dst, transfer);
     }
 
     fn trap_if_not_flag(&mut self, flags_global: GlobalIndex, flag_to_test: i32, trap: Trap) {
@@ -2574,7 +2621,6 @@ impl Compiler<'_, '_> {
     }
 
     fn malloc<'a>(&mut self, opts: &'a Options, size: MallocSize, align: u32) -> Memory<'a> {
-        let realloc = opts.realloc.unwrap();
         self.ptr_uconst(opts, 0);
         self.ptr_uconst(opts, 0);
         self.ptr_uconst(opts, align);
@@ -2582,11 +2628,44 @@ impl Compiler<'_, '_> {
             MallocSize::Const(size) => self.ptr_uconst(opts, size),
             MallocSize::Local(idx) => self.instruction(LocalGet(idx)),
         }
-        self.instruction(Call(realloc.as_u32()));
+        self.call_realloc(opts);
         let addr = self.local_set_new_tmp(opts.ptr());
         self.memory_operand(opts, addr, align)
     }
 
+    /// Calls the `realloc` function of `opts` with the old pointer, old size,
+    /// alignment, and new size on the stack, leaving the returned pointer on
+    /// the stack.
+    ///
+    /// The arguments and result are also handed to the host so the call can
+    /// be logged if canonical ABI tracing is enabled for the store.
+    fn call_realloc(&mut self, opts: &Options) {
+        let ty = opts.ptr();
+        let new_size = self.local_set_new_tmp(ty);
+        let align = self.local_set_new_tmp(ty);
+        let old_size = self.local_set_new_tmp(ty);
+        let old_ptr = self.local_set_new_tmp(ty);
+        for local in [&old_ptr, &old_size, &align, &new_size] {
+            self.instruction(LocalGet(local.idx));
+        }
+        self.instruction(Call(opts.realloc.unwrap().as_u32()));
+        let ret = self.local_set_new_tmp(ty);
+
+        let trace = self.module.import_trace_realloc();
+        for local in [&old_ptr, &old_size, &align, &new_size, &ret] {
+            self.instruction(LocalGet(local.idx));
+            if ty == ValType::I32 {
+                self.instruction(I64ExtendI32U);
+            }
+        }
+        self.instruction(Call(trace.as_u32()));
+        self.instruction(LocalGet(ret.idx));
+
+        for local in [old_ptr, old_size, align, new_size, ret] {
+            self.free_temp_local(local);
+        }
+    }
+
     fn memory_operand<'a>(&mut self, opts: &'a Options, addr: TempLocal, align: u32) -> Memory<'a> {
         let ret = Memory {
             addr,
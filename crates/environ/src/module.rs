@@ -83,6 +83,15 @@ pub enum MemoryInitialization {
         /// The offset, range base, and range end are all guaranteed to be page
         /// aligned to the page size passed in to `try_static_init`.
         map: PrimaryMap<MemoryIndex, Option<StaticMemoryInitializer>>,
+
+        /// The active data segments that the images in `map` were built from,
+        /// in order, along with the memory that each one initializes.
+        ///
+        /// These aren't needed to initialize memory but let embedders find
+        /// and override individual segments. The data range of each segment
+        /// is its original contents within the final data section, which is
+        /// usually part of an image in `map`.
+        segments: Vec<(MemoryIndex, StaticMemoryInitializer)>,
     },
 }
 
@@ -127,7 +136,7 @@ impl MemoryInitialization {
             //
             // Note that existence of `Static` already guarantees that all
             // indices are in-bounds.
-            MemoryInitialization::Static { map } => {
+            MemoryInitialization::Static { map, .. } => {
                 for (index, init) in map {
                     if let Some(init) = init {
                         let result = state.write(index, init);
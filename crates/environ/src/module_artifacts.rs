@@ -29,6 +29,13 @@ pub struct CompiledFunctionInfo {
 pub struct WasmFunctionInfo {
     pub start_srcloc: FilePos,
     pub stack_maps: Box<[StackMapInformation]>,
+    /// The size, in bytes, of this function's native stack frame, including
+    /// its return address and saved frame pointer but not its callees.
+    pub frame_size: u32,
+    /// The functions this function calls directly, or `None` if it also makes
+    /// calls whose callee isn't known statically, such as `call_indirect`,
+    /// calls to imports, or calls to the host.
+    pub direct_calls: Option<Box<[FuncIndex]>>,
 }
 
 /// Description of where a function is located in the text section of a
@@ -110,6 +117,15 @@ pub struct Metadata {
     /// Dwarf sections and the offsets at which they're stored in the
     /// ELF_WASMTIME_DWARF
     pub dwarf: Vec<(u8, Range<u64>)>,
+
+    /// The maximum native stack size, in bytes, used by a call into any wasm
+    /// function of this module, or `None` if that can't be bounded statically
+    /// due to recursion or calls to functions which aren't known.
+    pub max_stack_size: Option<u32>,
+
+    /// The bits of the `wasmparser::WasmFeatures` found to be used by the
+    /// module during translation.
+    pub required_features: u64,
 }
 
 /// Value of a configured setting for a [`Compiler`](crate::Compiler)
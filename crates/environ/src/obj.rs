@@ -180,6 +180,25 @@ libcalls! {
     X86Pshufb = "libcall_x86_pshufb"
 }
 
+/// Prefix of the names of symbols in object files which relocations against
+/// host intrinsics refer to.
+///
+/// The full symbol name is this prefix followed by the index of the intrinsic
+/// in the engine's configured list of intrinsics.
+const HOST_INTRINSIC_SYMBOL_PREFIX: &str = "wasmtime_host_intrinsic";
+
+/// Returns the symbol name in object files associated with the host intrinsic
+/// at `index`.
+pub fn host_intrinsic_symbol(index: u32) -> alloc::string::String {
+    alloc::format!("{HOST_INTRINSIC_SYMBOL_PREFIX}{index}")
+}
+
+/// Returns the index of the host intrinsic that the symbol `s` refers to, if
+/// it refers to one.
+pub fn host_intrinsic_from_symbol(s: &str) -> Option<u32> {
+    s.strip_prefix(HOST_INTRINSIC_SYMBOL_PREFIX)?.parse().ok()
+}
+
 /// Workaround to implement `core::error::Error` until
 /// gimli-rs/object#747 is settled.
 pub struct ObjectCrateErrorWrapper(pub object::Error);
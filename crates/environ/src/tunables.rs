@@ -26,13 +26,20 @@ macro_rules! define_tunables {
         }
 
         /// Optional tunable configuration options used in `wasmtime::Config`
-        #[derive(Default, Clone)]
+        #[derive(Default, Clone, Serialize, Deserialize)]
+        #[serde(default)]
         #[allow(missing_docs, reason = "macro-generated fields")]
         pub struct $config_tunables {
-            $(pub $field: Option<$field_ty>,)*
+            $(
+                #[serde(skip_serializing_if = "Option::is_none")]
+                pub $field: Option<$field_ty>,
+            )*
         }
 
         impl $config_tunables {
+            /// The names of all tunables, as used when serializing.
+            pub const NAMES: &'static [&'static str] = &[$(stringify!($field),)*];
+
             /// Formats configured fields into `f`.
             pub fn format(&self, f: &mut fmt::DebugStruct<'_,'_>) {
                 $(
@@ -120,6 +127,31 @@ define_tunables! {
 
         /// Whether CoW images might be used to initialize linear memories.
         pub memory_init_cow: bool,
+
+        /// Whether every `global.set` notifies the runtime so that host
+        /// watchpoints on globals can be triggered.
+        pub global_watchpoints: bool,
+
+        /// Whether compiled code calls into the runtime before each wasm
+        /// instruction to support single-stepping.
+        pub single_step: bool,
+
+        /// Whether compiled code records the control-flow edges it takes in
+        /// the coverage map of its store.
+        pub coverage_instrumentation: bool,
+
+        /// Whether `resume` instructions install their suspend handlers as a
+        /// dispatch table indexed by tag, rather than as a list that
+        /// `suspend` searches linearly.
+        pub wasmfx_tag_dispatch_tables: bool,
+
+        /// The maximum size, in bytes of wasm code, of functions which are
+        /// inlined into their callers. Zero disables inlining.
+        pub inline_threshold: u32,
+
+        /// The maximum depth of nested calls which are inlined into a single
+        /// function.
+        pub inline_max_depth: u32,
     }
 
     pub struct ConfigTunables {
@@ -182,6 +214,12 @@ impl Tunables {
             winch_callable: false,
             signals_based_traps: true,
             memory_init_cow: true,
+            global_watchpoints: false,
+            single_step: false,
+            coverage_instrumentation: false,
+            wasmfx_tag_dispatch_tables: false,
+            inline_threshold: 0,
+            inline_max_depth: 2,
         }
     }
 
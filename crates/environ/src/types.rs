@@ -791,6 +791,11 @@ impl WasmContType {
         WasmContType(idx)
     }
 
+    /// Returns the index to the underlying function type.
+    pub fn func_type_index(&self) -> EngineOrModuleTypeIndex {
+        self.0
+    }
+
     /// Returns the (module interned) index to the underlying function type.
     pub fn interned_type_index(self) -> ModuleInternedTypeIndex {
         match self.0 {
@@ -213,6 +213,21 @@ pub trait PtrSize {
         self.vmruntime_limits_last_wasm_exit_pc() + self.size()
     }
 
+    /// Return the offset of the `coverage_map` field of `VMRuntimeLimits`.
+    fn vmruntime_limits_coverage_map(&self) -> u8 {
+        self.vmruntime_limits_last_wasm_entry_fp() + self.size()
+    }
+
+    /// Return the offset of the `coverage_mask` field of `VMRuntimeLimits`.
+    fn vmruntime_limits_coverage_mask(&self) -> u8 {
+        self.vmruntime_limits_coverage_map() + self.size()
+    }
+
+    /// Return the offset of the `coverage_prev` field of `VMRuntimeLimits`.
+    fn vmruntime_limits_coverage_prev(&self) -> u8 {
+        self.vmruntime_limits_coverage_mask() + 4
+    }
+
     // Offsets within `VMMemoryDefinition`
 
     /// The offset of the `base` field.
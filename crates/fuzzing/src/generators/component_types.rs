@@ -108,8 +108,8 @@ pub fn arbitrary_val(ty: &component::Type, input: &mut Unstructured) -> arbitrar
                 .collect::<arbitrary::Result<_>>()?,
         ),
 
-        // Resources aren't fuzzed at this time.
-        Type::Own(_) | Type::Borrow(_) => unreachable!(),
+        // Resources, futures, and streams aren't fuzzed at this time.
+        Type::Own(_) | Type::Borrow(_) | Type::Future(_) | Type::Stream(_) => unreachable!(),
     })
 }
 
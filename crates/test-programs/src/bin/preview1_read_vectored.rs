@@ -0,0 +1,81 @@
+use std::{env, process};
+use test_programs::preview1::{config, open_scratch_directory};
+
+unsafe fn test_read_vectored(dir_fd: wasi::Fd) {
+    // Create a file in the scratch directory.
+    let file_fd = wasi::path_open(
+        dir_fd,
+        0,
+        "file",
+        wasi::OFLAGS_CREAT,
+        wasi::RIGHTS_FD_READ | wasi::RIGHTS_FD_WRITE | wasi::RIGHTS_FD_SEEK,
+        0,
+        0,
+    )
+    .expect("opening a file");
+    assert!(
+        file_fd > libc::STDERR_FILENO as wasi::Fd,
+        "file descriptor range check",
+    );
+
+    let contents = b"hello, world!";
+    let ciovec = wasi::Ciovec {
+        buf: contents.as_ptr() as *const _,
+        buf_len: contents.len(),
+    };
+    let nwritten = wasi::fd_write(file_fd, &[ciovec]).expect("writing to a file");
+    assert_eq!(nwritten, contents.len(), "nwritten bytes check");
+    wasi::fd_seek(file_fd, 0, wasi::WHENCE_SET).expect("seeking to the beginning of the file");
+
+    // Read into two buffers, the first of which is too small for the whole
+    // file.
+    let a = &mut [0u8; 4];
+    let b = &mut [0u8; 16];
+    let iovecs = [
+        wasi::Iovec {
+            buf: a.as_mut_ptr(),
+            buf_len: a.len(),
+        },
+        wasi::Iovec {
+            buf: b.as_mut_ptr(),
+            buf_len: b.len(),
+        },
+    ];
+    let nread = wasi::fd_read(file_fd, &iovecs).expect("reading a file");
+    assert_eq!(a, b"hell", "first buffer check");
+    if config().support_vectored_reads() {
+        assert_eq!(nread, contents.len(), "nread bytes check");
+        assert_eq!(&b[..9], b"o, world!", "second buffer check");
+    } else {
+        assert!(nread >= a.len(), "nread bytes check");
+    }
+
+    let offset = wasi::fd_tell(file_fd).expect("getting file offset after reading");
+    assert_eq!(offset, nread as u64, "offset after reading");
+
+    wasi::fd_close(file_fd).expect("closing a file");
+    wasi::path_unlink_file(dir_fd, "file").expect("removing a file");
+}
+
+fn main() {
+    let mut args = env::args();
+    let prog = args.next().unwrap();
+    let arg = if let Some(arg) = args.next() {
+        arg
+    } else {
+        eprintln!("usage: {prog} <scratch directory>");
+        process::exit(1);
+    };
+
+    // Open scratch directory
+    let dir_fd = match open_scratch_directory(&arg) {
+        Ok(dir_fd) => dir_fd,
+        Err(err) => {
+            eprintln!("{err}");
+            process::exit(1)
+        }
+    };
+
+    // Run the tests.
+    unsafe { test_read_vectored(dir_fd) }
+}
@@ -127,6 +127,7 @@ pub struct TestConfig {
     fs_time_precision: u64,
     no_dangling_filesystem: bool,
     no_rename_dir_to_empty_dir: bool,
+    vectored_reads: bool,
 }
 
 enum ErrnoMode {
@@ -153,11 +154,13 @@ impl TestConfig {
         };
         let no_dangling_filesystem = std::env::var("NO_DANGLING_FILESYSTEM").is_ok();
         let no_rename_dir_to_empty_dir = std::env::var("NO_RENAME_DIR_TO_EMPTY_DIR").is_ok();
+        let vectored_reads = std::env::var("VECTORED_READS").is_ok();
         TestConfig {
             errno_mode,
             fs_time_precision,
             no_dangling_filesystem,
             no_rename_dir_to_empty_dir,
+            vectored_reads,
         }
     }
     pub fn errno_expect_unix(&self) -> bool {
@@ -187,4 +190,7 @@ impl TestConfig {
     pub fn support_rename_dir_to_empty_dir(&self) -> bool {
         !self.no_rename_dir_to_empty_dir
     }
+    pub fn support_vectored_reads(&self) -> bool {
+        self.vectored_reads
+    }
 }
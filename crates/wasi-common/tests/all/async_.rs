@@ -226,6 +226,10 @@ async fn preview1_readlink() {
     run(PREVIEW1_READLINK, true).await.unwrap()
 }
 #[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_read_vectored() {
+    run(PREVIEW1_READ_VECTORED, true).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
 async fn preview1_remove_directory() {
     run(PREVIEW1_REMOVE_DIRECTORY, true).await.unwrap()
 }
@@ -217,6 +217,10 @@ fn preview1_readlink() {
     run(PREVIEW1_READLINK, true).unwrap()
 }
 #[test_log::test]
+fn preview1_read_vectored() {
+    run(PREVIEW1_READ_VECTORED, true).unwrap()
+}
+#[test_log::test]
 fn preview1_remove_directory() {
     run(PREVIEW1_REMOVE_DIRECTORY, true).unwrap()
 }
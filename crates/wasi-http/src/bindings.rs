@@ -49,6 +49,28 @@ pub use self::generated::exports;
 /// Bindings to the `wasi:http/proxy` world.
 pub use self::generated::{Proxy, ProxyIndices, ProxyPre};
 
+/// Bindings to Wasmtime-specific interfaces which extend `wasi:http`.
+///
+/// These are added to a linker alongside `wasi:http` by
+/// [`add_only_http_to_linker_async`](crate::add_only_http_to_linker_async)
+/// and [`add_only_http_to_linker_sync`](crate::add_only_http_to_linker_sync).
+pub mod extensions {
+    #[allow(missing_docs)]
+    mod generated {
+        wasmtime::component::bindgen!({
+            world: "wasmtime:wasi-http/extensions",
+            tracing: true,
+            trappable_imports: true,
+            with: {
+                "wasi:http": crate::bindings::http,
+                "wasi": wasmtime_wasi::bindings,
+            },
+        });
+    }
+
+    pub use self::generated::wasmtime::wasi_http::*;
+}
+
 /// Sync implementation of the `wasi:http/proxy` world.
 pub mod sync {
     #[allow(missing_docs)]
@@ -7,10 +7,12 @@ use http_body::{Body, Frame};
 use http_body_util::combinators::BoxBody;
 use http_body_util::BodyExt;
 use std::future::Future;
+use std::io::IoSliceMut;
 use std::mem;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::task::{Context, Poll};
 use std::{pin::Pin, sync::Arc, time::Duration};
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{mpsc, oneshot, Notify};
 use wasmtime_wasi::{
     runtime::{poll_noop, AbortOnDropJoinHandle},
     HostInputStream, HostOutputStream, StreamError, Subscribe,
@@ -22,6 +24,35 @@ pub type HyperIncomingBody = BoxBody<Bytes, types::ErrorCode>;
 /// Common type for outgoing bodies.
 pub type HyperOutgoingBody = BoxBody<Bytes, types::ErrorCode>;
 
+/// Limits on the bodies of requests and responses, configured with
+/// [`WasiHttpCtx::set_body_limits`](crate::WasiHttpCtx::set_body_limits).
+///
+/// These bound the resources a guest can consume through bodies, such as
+/// when several guests share a host.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BodyLimits {
+    /// The maximum number of bytes which may be read from an incoming body or
+    /// written to an outgoing body.
+    ///
+    /// Reading or writing more fails the stream operation with an
+    /// `http-request-body-size` or `http-response-body-size` error code.
+    /// Default: unlimited.
+    pub max_body_bytes: Option<u64>,
+
+    /// The maximum number of bytes which may have been written to an outgoing
+    /// body without having been sent yet.
+    ///
+    /// Once reached, `check-write` on the body's output stream returns `0`
+    /// until the bytes have been sent, and writing more fails with an
+    /// `internal-error` error code. Default: only bounded by
+    /// [`WasiHttpView::outgoing_body_buffer_chunks`] and
+    /// [`WasiHttpView::outgoing_body_chunk_size`].
+    ///
+    /// [`WasiHttpView::outgoing_body_buffer_chunks`]: crate::WasiHttpView::outgoing_body_buffer_chunks
+    /// [`WasiHttpView::outgoing_body_chunk_size`]: crate::WasiHttpView::outgoing_body_chunk_size
+    pub max_in_flight_write_bytes: Option<usize>,
+}
+
 /// The concrete type behind a `was:http/types/incoming-body` resource.
 #[derive(Debug)]
 pub struct HostIncomingBody {
@@ -42,6 +73,18 @@ impl HostIncomingBody {
         }
     }
 
+    /// Limit the number of bytes which may be read from this body to
+    /// `max_bytes`, after which reading fails with the body size error of
+    /// `context`.
+    ///
+    /// Must be called before the body's stream is taken.
+    pub fn limit_size(&mut self, context: StreamContext, max_bytes: u64) {
+        match &mut self.body {
+            IncomingBodyState::Start(body) => body.limit = Some((context, max_bytes)),
+            IncomingBodyState::InBodyStream(_) => panic!("body stream has already been taken"),
+        }
+    }
+
     /// Retain a worker task that needs to be kept alive while this body is being read.
     pub fn retain_worker(&mut self, worker: AbortOnDropJoinHandle<()>) {
         assert!(self.worker.is_none());
@@ -98,6 +141,11 @@ struct BodyWithTimeout {
     /// Maximal duration between when a frame is first requested and when it's
     /// allowed to arrive.
     between_bytes_timeout: Duration,
+    /// The number of data bytes received so far.
+    received: u64,
+    /// The maximum number of data bytes which may be received, and whether
+    /// this is a request or response body for the error returned otherwise.
+    limit: Option<(StreamContext, u64)>,
 }
 
 impl BodyWithTimeout {
@@ -105,6 +153,8 @@ impl BodyWithTimeout {
         BodyWithTimeout {
             inner,
             between_bytes_timeout,
+            received: 0,
+            limit: None,
             reset_sleep: true,
             timeout: Box::pin(wasmtime_wasi::runtime::with_ambient_tokio_runtime(|| {
                 tokio::time::sleep(Duration::new(0, 0))
@@ -143,6 +193,19 @@ impl Body for BodyWithTimeout {
         // arrives then the sleep timer will be reset on the next frame.
         let result = Pin::new(&mut me.inner).poll_frame(cx);
         me.reset_sleep = result.is_ready();
+
+        // Count the data received against the size limit, if any, and fail
+        // the body once it's surpassed.
+        if let Poll::Ready(Some(Ok(frame))) = &result {
+            if let Some(data) = frame.data_ref() {
+                me.received += data.len() as u64;
+                if let Some((context, max)) = me.limit {
+                    if me.received > max {
+                        return Poll::Ready(Some(Err(context.as_body_size_error(me.received))));
+                    }
+                }
+            }
+        }
         result
     }
 }
@@ -268,6 +331,35 @@ impl HostInputStream for HostIncomingBodyStream {
             }
         }
     }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> Result<usize, StreamError> {
+        // Copy straight out of the frames received from hyper, continuing on
+        // to any further frames which are already available until `bufs` are
+        // full.
+        let mut read = 0;
+        for buf in bufs.iter_mut() {
+            let mut buf = &mut buf[..];
+            while !buf.is_empty() {
+                let chunk = match self.read(buf.len()) {
+                    Ok(chunk) if chunk.is_empty() => return Ok(read),
+                    Ok(chunk) => chunk,
+                    Err(e) if read == 0 => return Err(e),
+                    // Report the error on the next read instead, once the
+                    // bytes read so far have been returned.
+                    Err(StreamError::LastOperationFailed(e)) => {
+                        self.error = Some(e);
+                        return Ok(read);
+                    }
+                    Err(_) => return Ok(read),
+                };
+                let (head, tail) = mem::take(&mut buf).split_at_mut(chunk.len());
+                head.copy_from_slice(&chunk);
+                buf = tail;
+                read += chunk.len();
+            }
+        }
+        Ok(read)
+    }
 }
 
 #[async_trait::async_trait]
@@ -412,12 +504,35 @@ impl WrittenState {
     }
 }
 
+/// Counters of the bytes written to an outgoing body, shared between its
+/// output stream and the body sent by hyper.
+#[derive(Debug, Default)]
+struct OutgoingBodyStats {
+    /// The number of bytes written to the output stream.
+    written: AtomicU64,
+    /// The number of bytes written which hyper hasn't picked up yet.
+    in_flight: AtomicUsize,
+    /// Notified when hyper picks up bytes, or stops picking them up.
+    drained: Notify,
+}
+
+impl OutgoingBodyStats {
+    fn written(&self) -> u64 {
+        self.written.load(Ordering::Relaxed)
+    }
+
+    fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+}
+
 /// The concrete type behind a `wasi:http/types/outgoing-body` resource.
 pub struct HostOutgoingBody {
     /// The output stream that the body is written to.
     body_output_stream: Option<Box<dyn HostOutputStream>>,
     context: StreamContext,
     written: Option<WrittenState>,
+    stats: Arc<OutgoingBodyStats>,
     finish_sender: Option<tokio::sync::oneshot::Sender<FinishMessage>>,
 }
 
@@ -428,15 +543,26 @@ impl HostOutgoingBody {
         size: Option<u64>,
         buffer_chunks: usize,
         chunk_size: usize,
+        limits: BodyLimits,
     ) -> (Self, HyperOutgoingBody) {
         assert!(buffer_chunks >= 1);
 
         let written = size.map(WrittenState::new);
+        let stats = Arc::new(OutgoingBodyStats::default());
 
         use tokio::sync::oneshot::error::RecvError;
         struct BodyImpl {
             body_receiver: mpsc::Receiver<Bytes>,
             finish_receiver: Option<oneshot::Receiver<FinishMessage>>,
+            stats: Arc<OutgoingBodyStats>,
+        }
+        impl Drop for BodyImpl {
+            fn drop(&mut self) {
+                // Close the channel before waking up a writer waiting for
+                // bytes to be drained, so it observes that no more will be.
+                self.body_receiver.close();
+                self.stats.drained.notify_one();
+            }
         }
         impl Body for BodyImpl {
             type Data = Bytes;
@@ -447,7 +573,13 @@ impl HostOutgoingBody {
             ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
                 match self.as_mut().body_receiver.poll_recv(cx) {
                     Poll::Pending => Poll::Pending,
-                    Poll::Ready(Some(frame)) => Poll::Ready(Some(Ok(Frame::data(frame)))),
+                    Poll::Ready(Some(frame)) => {
+                        self.stats
+                            .in_flight
+                            .fetch_sub(frame.len(), Ordering::AcqRel);
+                        self.stats.drained.notify_one();
+                        Poll::Ready(Some(Ok(Frame::data(frame))))
+                    }
 
                     // This means that the `body_sender` end of the channel has been dropped.
                     Poll::Ready(None) => {
@@ -482,22 +614,44 @@ impl HostOutgoingBody {
         let body_impl = BodyImpl {
             body_receiver,
             finish_receiver: Some(finish_receiver),
+            stats: stats.clone(),
         }
         .boxed();
 
-        let output_stream = BodyWriteStream::new(context, chunk_size, body_sender, written.clone());
+        let output_stream = BodyWriteStream::new(
+            context,
+            chunk_size,
+            body_sender,
+            written.clone(),
+            limits,
+            stats.clone(),
+        );
 
         (
             Self {
                 body_output_stream: Some(Box::new(output_stream)),
                 context,
                 written,
+                stats,
                 finish_sender: Some(finish_sender),
             },
             body_impl,
         )
     }
 
+    /// The number of bytes which have been written to this body's output
+    /// stream.
+    pub fn bytes_written(&self) -> u64 {
+        self.stats.written()
+    }
+
+    /// The number of bytes which have been written to this body's output
+    /// stream but not yet picked up to be sent, which is the data currently
+    /// buffered on behalf of the guest.
+    pub fn bytes_in_flight(&self) -> usize {
+        self.stats.in_flight()
+    }
+
     /// Take the output stream, if it's available.
     pub fn take_output_stream(&mut self) -> Option<Box<dyn HostOutputStream>> {
         self.body_output_stream.take()
@@ -583,6 +737,8 @@ struct BodyWriteStream {
     writer: mpsc::Sender<Bytes>,
     write_budget: usize,
     written: Option<WrittenState>,
+    limits: BodyLimits,
+    stats: Arc<OutgoingBodyStats>,
 }
 
 impl BodyWriteStream {
@@ -592,6 +748,8 @@ impl BodyWriteStream {
         write_budget: usize,
         writer: mpsc::Sender<Bytes>,
         written: Option<WrittenState>,
+        limits: BodyLimits,
+        stats: Arc<OutgoingBodyStats>,
     ) -> Self {
         // at least one capacity is required to send a message
         assert!(writer.max_capacity() >= 1);
@@ -600,6 +758,42 @@ impl BodyWriteStream {
             writer,
             write_budget,
             written,
+            limits,
+            stats,
+        }
+    }
+
+    /// The number of bytes which may be written right now.
+    ///
+    /// This only grows until the next write, since hyper only ever removes
+    /// bytes from the channel, so a write of at most the amount returned by
+    /// the last `check-write` always fits.
+    fn budget(&self) -> usize {
+        if self.writer.capacity() == 0 {
+            // If there is no more capacity in this sender channel then don't
+            // allow any more writes because the hyper task needs to catch up
+            // now.
+            //
+            // Note that this relies on this task being the only one sending
+            // data to ensure that no one else can steal a write into this
+            // channel.
+            return 0;
+        }
+        match self.limits.max_in_flight_write_bytes {
+            // Only allow writes which fit within the in-flight limit.
+            Some(max) => self
+                .write_budget
+                .min(max.saturating_sub(self.stats.in_flight())),
+            None => self.write_budget,
+        }
+    }
+
+    /// Whether the in-flight limit leaves no room for more writes until
+    /// hyper picks up the bytes already written.
+    fn in_flight_full(&self) -> bool {
+        match self.limits.max_in_flight_write_bytes {
+            Some(max) => self.stats.in_flight() >= max,
+            None => false,
         }
     }
 }
@@ -608,10 +802,37 @@ impl BodyWriteStream {
 impl HostOutputStream for BodyWriteStream {
     fn write(&mut self, bytes: Bytes) -> Result<(), StreamError> {
         let len = bytes.len();
+
+        // Writes beyond the limits are rejected before any of their bytes are
+        // sent.
+        if let Some(max) = self.limits.max_body_bytes {
+            let total = self.stats.written() + len as u64;
+            if total > max {
+                return Err(StreamError::LastOperationFailed(anyhow!(self
+                    .context
+                    .as_body_size_error(total))));
+            }
+        }
+        // Guests must not write more than `check-write` permitted. Rejecting
+        // larger writes here, rather than buffering them, is what keeps the
+        // memory used by a body bounded by its write budget.
+        let budget = self.budget();
+        if len > budget {
+            return Err(StreamError::LastOperationFailed(anyhow!(
+                types::ErrorCode::InternalError(Some(format!(
+                    "write of {len} bytes exceeds the write budget of {budget} bytes"
+                )))
+            )));
+        }
+
+        // Count the bytes as in flight before sending them so that hyper never
+        // picks up bytes which haven't been counted yet.
+        self.stats.in_flight.fetch_add(len, Ordering::AcqRel);
         match self.writer.try_send(bytes) {
             // If the message was sent then it's queued up now in hyper to get
             // received.
             Ok(()) => {
+                self.stats.written.fetch_add(len as u64, Ordering::Relaxed);
                 if let Some(written) = self.written.as_ref() {
                     if !written.update(len) {
                         let total = written.written();
@@ -624,15 +845,18 @@ impl HostOutputStream for BodyWriteStream {
                 Ok(())
             }
 
-            // If this channel is full then that means `check_write` wasn't
-            // called. The call to `check_write` always guarantees that there's
-            // at least one capacity if a write is allowed.
+            // The budget is zero while this channel is full, so this can only
+            // happen for an empty write when `check_write` returned zero.
             Err(mpsc::error::TrySendError::Full(_)) => {
+                self.stats.in_flight.fetch_sub(len, Ordering::AcqRel);
                 Err(StreamError::Trap(anyhow!("write exceeded budget")))
             }
 
             // Hyper is gone so this stream is now closed.
-            Err(mpsc::error::TrySendError::Closed(_)) => Err(StreamError::Closed),
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                self.stats.in_flight.fetch_sub(len, Ordering::AcqRel);
+                Err(StreamError::Closed)
+            }
         }
     }
 
@@ -649,17 +873,8 @@ impl HostOutputStream for BodyWriteStream {
     fn check_write(&mut self) -> Result<usize, StreamError> {
         if self.writer.is_closed() {
             Err(StreamError::Closed)
-        } else if self.writer.capacity() == 0 {
-            // If there is no more capacity in this sender channel then don't
-            // allow any more writes because the hyper task needs to catch up
-            // now.
-            //
-            // Note that this relies on this task being the only one sending
-            // data to ensure that no one else can steal a write into this
-            // channel.
-            Ok(0)
         } else {
-            Ok(self.write_budget)
+            Ok(self.budget())
         }
     }
 }
@@ -671,5 +886,11 @@ impl Subscribe for BodyWriteStream {
         // the channel or it's already closed then this will return immediately.
         // If the channel is full this will block until capacity opens up.
         let _ = self.writer.reserve().await;
+
+        // Additionally wait for hyper to pick up bytes if the in-flight limit
+        // has been reached, unless it's gone and won't pick up any more.
+        while self.in_flight_full() && !self.writer.is_closed() {
+            self.stats.drained.notified().await;
+        }
     }
 }
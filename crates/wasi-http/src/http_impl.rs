@@ -14,11 +14,11 @@ use bytes::Bytes;
 use http_body_util::{BodyExt, Empty};
 use hyper::Method;
 use wasmtime::component::Resource;
-use wasmtime_wasi::IoView;
+use wasmtime_wasi::{IoView, WasiView};
 
 impl<T> outgoing_handler::Host for WasiHttpImpl<T>
 where
-    T: WasiHttpView,
+    T: WasiHttpView + WasiView,
 {
     fn handle(
         &mut self,
@@ -78,7 +78,15 @@ where
             uri = uri.path_and_query(path);
         }
 
-        builder = builder.uri(uri.build().map_err(http_request_error)?);
+        let uri = uri.build().map_err(http_request_error)?;
+        let pool = self.ctx().connection_pool().cloned();
+        let http_versions = self.ctx().http_versions();
+        let tasks = WasiView::ctx(&mut self.0 .0).tasks().clone();
+        let proxy = uri.host().and_then(|host| {
+            let port = uri.port_u16().unwrap_or(if use_tls { 443 } else { 80 });
+            self.ctx().proxy().proxy_for(host, port, use_tls).cloned()
+        });
+        builder = builder.uri(uri);
 
         for (k, v) in req.headers.iter() {
             builder = builder.header(k, v);
@@ -90,19 +98,28 @@ where
                 .boxed()
         });
 
-        let request = builder
+        let mut request = builder
             .body(body)
             .map_err(|err| internal_error(err.to_string()))?;
+        let config = OutgoingRequestConfig {
+            use_tls,
+            connect_timeout,
+            first_byte_timeout,
+            between_bytes_timeout,
+            proxy,
+            pool,
+            http_versions,
+            tasks,
+        };
 
-        let future = self.send_request(
-            request,
-            OutgoingRequestConfig {
-                use_tls,
-                connect_timeout,
-                first_byte_timeout,
-                between_bytes_timeout,
-            },
-        )?;
+        let mirrored = self
+            .ctx()
+            .mirror()
+            .and_then(|mirror| mirror.mirror(&mut request, &config));
+        let mut future = self.send_request(request, config)?;
+        if let Some(mirrored) = mirrored {
+            future = mirrored.compare(future);
+        }
 
         Ok(self.table().push(future)?)
     }
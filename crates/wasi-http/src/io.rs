@@ -1,6 +1,7 @@
 //! I/O utility for bridging between `tokio::io` and `hyper::rt`.
 
-use hyper::rt::{Read, ReadBufCursor, Write};
+use hyper::rt::{Executor, Read, ReadBufCursor, Write};
+use std::future::Future;
 use std::io::Error;
 use std::pin::Pin;
 use std::task::{Context, Poll};
@@ -53,3 +54,20 @@ impl<T: AsyncWrite + Unpin> Write for TokioIo<T> {
         Pin::new(&mut self.inner).poll_shutdown(cx)
     }
 }
+
+/// An implementation of [`hyper::rt::Executor`] which spawns futures onto the
+/// ambient tokio runtime, as required for HTTP/2 connections.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TokioExecutor;
+
+impl<F> Executor<F> for TokioExecutor
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    fn execute(&self, fut: F) {
+        drop(wasmtime_wasi::runtime::with_ambient_tokio_runtime(|| {
+            tokio::task::spawn(fut)
+        }));
+    }
+}
@@ -60,7 +60,8 @@
 //!    before serving requests.
 //! 4. When serving requests use
 //!    [`ProxyPre::instantiate_async`](bindings::ProxyPre::instantiate_async)
-//!    to create instances and handle HTTP requests.
+//!    to create instances and handle HTTP requests. Alternatively [`serve`]
+//!    does this for each request as a [`hyper::service::Service`].
 //!
 //! A standalone example of doing all this looks like:
 //!
@@ -218,6 +219,10 @@
 
 mod error;
 mod http_impl;
+mod mirror;
+mod pool;
+mod proxy;
+mod service;
 mod types_impl;
 
 pub mod body;
@@ -229,10 +234,18 @@ pub mod bindings;
 pub use crate::error::{
     http_request_error, hyper_request_error, hyper_response_error, HttpError, HttpResult,
 };
+pub use crate::mirror::{
+    MirrorComparison, MirrorConfig, MirroredRequest, MIRROR_BODY_BUFFER_FRAMES,
+};
+pub use crate::pool::{
+    ConnectionPool, DEFAULT_POOL_IDLE_TIMEOUT, DEFAULT_POOL_MAX_IDLE_PER_ORIGIN,
+};
+pub use crate::proxy::{Proxy, ProxyConfig};
+pub use crate::service::{serve, ProxyService};
 #[doc(inline)]
 pub use crate::types::{
-    WasiHttpCtx, WasiHttpImpl, WasiHttpView, DEFAULT_OUTGOING_BODY_BUFFER_CHUNKS,
-    DEFAULT_OUTGOING_BODY_CHUNK_SIZE,
+    HttpVersionPolicy, WasiHttpCtx, WasiHttpImpl, WasiHttpView,
+    DEFAULT_OUTGOING_BODY_BUFFER_CHUNKS, DEFAULT_OUTGOING_BODY_CHUNK_SIZE,
 };
 use wasmtime_wasi::IoImpl;
 /// Add all of the `wasi:http/proxy` world's interfaces to a [`wasmtime::component::Linker`].
@@ -321,7 +334,8 @@ where
 }
 
 /// A slimmed down version of [`add_to_linker_async`] which only adds
-/// `wasi:http` interfaces to the linker.
+/// `wasi:http` interfaces, and Wasmtime's [extensions](bindings::extensions)
+/// to them, to the linker.
 ///
 /// This is useful when using [`wasmtime_wasi::add_to_linker_async`] for
 /// example to avoid re-adding the same interfaces twice.
@@ -329,11 +343,12 @@ pub fn add_only_http_to_linker_async<T>(
     l: &mut wasmtime::component::Linker<T>,
 ) -> anyhow::Result<()>
 where
-    T: WasiHttpView,
+    T: WasiHttpView + wasmtime_wasi::WasiView,
 {
     let closure = type_annotate_http::<T, _>(|t| WasiHttpImpl(IoImpl(t)));
     crate::bindings::http::outgoing_handler::add_to_linker_get_host(l, closure)?;
     crate::bindings::http::types::add_to_linker_get_host(l, closure)?;
+    crate::bindings::extensions::http_version::add_to_linker_get_host(l, closure)?;
 
     Ok(())
 }
@@ -401,18 +416,20 @@ where
 }
 
 /// A slimmed down version of [`add_to_linker_sync`] which only adds
-/// `wasi:http` interfaces to the linker.
+/// `wasi:http` interfaces, and Wasmtime's [extensions](bindings::extensions)
+/// to them, to the linker.
 ///
 /// This is useful when using [`wasmtime_wasi::add_to_linker_sync`] for
 /// example to avoid re-adding the same interfaces twice.
 pub fn add_only_http_to_linker_sync<T>(l: &mut wasmtime::component::Linker<T>) -> anyhow::Result<()>
 where
-    T: WasiHttpView,
+    T: WasiHttpView + wasmtime_wasi::WasiView,
 {
     let closure = type_annotate_http::<T, _>(|t| WasiHttpImpl(IoImpl(t)));
 
     crate::bindings::http::outgoing_handler::add_to_linker_get_host(l, closure)?;
     crate::bindings::http::types::add_to_linker_get_host(l, closure)?;
+    crate::bindings::extensions::http_version::add_to_linker_get_host(l, closure)?;
 
     Ok(())
 }
@@ -0,0 +1,377 @@
+//! Mirroring of outgoing requests to a shadow authority.
+
+use crate::bindings::http::types::ErrorCode;
+use crate::body::HyperOutgoingBody;
+use crate::types::{
+    default_send_request_handler, HostFutureIncomingResponse, IncomingResponse,
+    OutgoingRequestConfig,
+};
+use anyhow::{ensure, Context as _};
+use bytes::Bytes;
+use http_body_util::{BodyExt, Empty};
+use hyper::body::{Body, Frame, SizeHint};
+use hyper::header::HeaderValue;
+use std::fmt;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tokio::sync::{mpsc, oneshot};
+use wasmtime_wasi::runtime::TaskSupervisor;
+
+/// How many frames of a request's body may be buffered for its shadow request
+/// before the shadow request is abandoned, see [`MirrorConfig`].
+pub const MIRROR_BODY_BUFFER_FRAMES: usize = 16;
+
+/// Duplicates a percentage of `wasi:http/outgoing-handler` requests to a
+/// shadow authority, see
+/// [`WasiHttpCtx::set_mirror`](crate::WasiHttpCtx::set_mirror).
+///
+/// Copies of mirrored requests, "shadow requests", have the same method,
+/// path, headers and body as the original and use the same scheme unless
+/// configured otherwise with [`MirrorConfig::use_tls`]. They're always sent
+/// directly, never through a proxy. The guest never sees their responses,
+/// which are discarded, or reported to the callback configured with
+/// [`MirrorConfig::compare`] along with the original response.
+///
+/// Requests are sampled deterministically, so that exactly `percent` out of
+/// every 100 requests are mirrored. Clones of a config share their sampling.
+///
+/// The body of a mirrored request is copied to its shadow request as the
+/// guest writes it. If the shadow request falls more than
+/// [`MIRROR_BODY_BUFFER_FRAMES`] frames behind, it's abandoned rather than
+/// slowing down the original request or buffering without bound.
+#[derive(Clone)]
+pub struct MirrorConfig {
+    authority: http::uri::Authority,
+    use_tls: Option<bool>,
+    percent: u8,
+    sampled: Arc<AtomicU64>,
+    compare: Option<Arc<dyn Fn(MirrorComparison) + Send + Sync>>,
+}
+
+impl MirrorConfig {
+    /// Creates a config which mirrors `percent` percent of requests to
+    /// `authority`, such as `canary.example.com:8080`.
+    ///
+    /// The port defaults to the default port of the shadow request's scheme.
+    pub fn new(authority: &str, percent: u8) -> anyhow::Result<MirrorConfig> {
+        ensure!(
+            percent <= 100,
+            "mirrored percentage must be at most 100, got {percent}"
+        );
+        let authority = authority
+            .parse::<http::uri::Authority>()
+            .with_context(|| format!("invalid mirror authority `{authority}`"))?;
+        Ok(MirrorConfig {
+            authority,
+            use_tls: None,
+            percent,
+            sampled: Arc::new(AtomicU64::new(0)),
+            compare: None,
+        })
+    }
+
+    /// Configures whether shadow requests use TLS, regardless of the scheme
+    /// of the original request.
+    pub fn use_tls(mut self, use_tls: bool) -> MirrorConfig {
+        self.use_tls = Some(use_tls);
+        self
+    }
+
+    /// Configures a callback which compares the response to each mirrored
+    /// request with the response to its shadow request.
+    ///
+    /// The callback is invoked from a background task once both responses'
+    /// heads have been received, or have failed. It's not invoked if the
+    /// guest drops the original request before its response arrives.
+    pub fn compare(
+        mut self,
+        compare: impl Fn(MirrorComparison) + Send + Sync + 'static,
+    ) -> MirrorConfig {
+        self.compare = Some(Arc::new(compare));
+        self
+    }
+
+    /// Returns the authority that shadow requests are sent to.
+    pub fn authority(&self) -> &str {
+        self.authority.as_str()
+    }
+
+    /// Returns the percentage of requests which are mirrored.
+    pub fn percent(&self) -> u8 {
+        self.percent
+    }
+
+    /// Returns whether the next request should be mirrored.
+    fn sample(&self) -> bool {
+        let n = self.sampled.fetch_add(1, Ordering::Relaxed);
+        let percent = u64::from(self.percent);
+        (n + 1) * percent / 100 > n * percent / 100
+    }
+
+    /// Decides whether to mirror `request`, which is about to be sent with
+    /// `config`, and if so starts sending its shadow request.
+    ///
+    /// The body of `request` is replaced with one that copies it to the shadow
+    /// request as it's read. The returned [`MirroredRequest`] should be given
+    /// the original request's response with [`MirroredRequest::compare`].
+    ///
+    /// The shadow request is sent by a task of [`OutgoingRequestConfig::tasks`],
+    /// and isn't sent at all if that supervisor is at its limit.
+    pub fn mirror(
+        &self,
+        request: &mut hyper::Request<HyperOutgoingBody>,
+        config: &OutgoingRequestConfig,
+    ) -> Option<MirroredRequest> {
+        if !self.sample() {
+            return None;
+        }
+
+        let use_tls = self.use_tls.unwrap_or(config.use_tls);
+        let mut uri = request.uri().clone().into_parts();
+        uri.scheme = Some(if use_tls {
+            http::uri::Scheme::HTTPS
+        } else {
+            http::uri::Scheme::HTTP
+        });
+        uri.authority = Some(self.authority.clone());
+        let uri = http::Uri::from_parts(uri).ok()?;
+
+        let (frames, rx) = mpsc::channel(MIRROR_BODY_BUFFER_FRAMES);
+        let mut shadow = hyper::Request::new(ShadowBody { rx, done: false }.boxed());
+        *shadow.method_mut() = request.method().clone();
+        *shadow.uri_mut() = uri;
+        *shadow.headers_mut() = request.headers().clone();
+        shadow.headers_mut().insert(
+            hyper::header::HOST,
+            HeaderValue::from_str(self.authority.as_str()).expect("authority is a valid header"),
+        );
+        let shadow_config = OutgoingRequestConfig {
+            use_tls,
+            connect_timeout: config.connect_timeout,
+            first_byte_timeout: config.first_byte_timeout,
+            between_bytes_timeout: config.between_bytes_timeout,
+            proxy: None,
+            pool: config.pool.clone(),
+            http_versions: config.http_versions,
+            tasks: config.tasks.clone(),
+        };
+
+        let (tx, shadow_status) = match &self.compare {
+            Some(_) => {
+                let (tx, rx) = oneshot::channel();
+                (Some(tx), Some(rx))
+            }
+            None => (None, None),
+        };
+        // The shadow request belongs to the same store as the original one,
+        // so it's cancelled along with it. If the store can't run another
+        // task the request just isn't mirrored.
+        let spawned = config.tasks.spawn_detached("wasi-http-mirror", async move {
+            // The shadow response's body is discarded without being read.
+            let status = default_send_request_handler(shadow, shadow_config)
+                .await
+                .map(|response| response.resp.status());
+            if let Some(tx) = tx {
+                let _ = tx.send(status);
+            }
+        });
+        if spawned.is_err() {
+            return None;
+        }
+
+        let body = std::mem::replace(
+            request.body_mut(),
+            Empty::new().map_err(|_| unreachable!()).boxed(),
+        );
+        *request.body_mut() = TeeBody {
+            inner: body,
+            frames: Some(frames),
+        }
+        .boxed();
+
+        Some(MirroredRequest {
+            method: request.method().clone(),
+            uri: request.uri().clone(),
+            tasks: config.tasks.clone(),
+            compare: self.compare.clone().zip(shadow_status),
+        })
+    }
+}
+
+impl fmt::Debug for MirrorConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MirrorConfig")
+            .field("authority", &self.authority)
+            .field("use_tls", &self.use_tls)
+            .field("percent", &self.percent)
+            .field("compare", &self.compare.is_some())
+            .finish()
+    }
+}
+
+/// The outcome of a mirrored request and of its shadow request, passed to the
+/// callback configured with [`MirrorConfig::compare`].
+#[derive(Debug)]
+pub struct MirrorComparison {
+    /// The method of the request.
+    pub method: http::Method,
+    /// The URI of the original request.
+    pub uri: http::Uri,
+    /// The status of the response to the original request.
+    pub primary: Result<http::StatusCode, ErrorCode>,
+    /// The status of the response to the shadow request.
+    pub shadow: Result<http::StatusCode, ErrorCode>,
+}
+
+/// A request which is being mirrored, see [`MirrorConfig::mirror`].
+pub struct MirroredRequest {
+    method: http::Method,
+    uri: http::Uri,
+    tasks: TaskSupervisor,
+    compare: Option<(
+        Arc<dyn Fn(MirrorComparison) + Send + Sync>,
+        oneshot::Receiver<Result<http::StatusCode, ErrorCode>>,
+    )>,
+}
+
+impl MirroredRequest {
+    /// Arranges for `response`, the response to the original request, to be
+    /// compared with the response to the shadow request once both are
+    /// available, returning the original response to be given to the guest.
+    ///
+    /// This does nothing if no comparison callback is configured.
+    pub fn compare(self, response: HostFutureIncomingResponse) -> HostFutureIncomingResponse {
+        if self.compare.is_none() {
+            return response;
+        }
+        match response {
+            HostFutureIncomingResponse::Pending(mut handle) => {
+                let tasks = self.tasks.clone();
+                let task = tasks.spawn("wasi-http-mirror-compare", async move {
+                    let result = crate::types::join_response(&mut handle).await;
+                    self.finish(&result);
+                    result
+                });
+                match task {
+                    Ok(task) => HostFutureIncomingResponse::pending(task),
+                    Err(e) => HostFutureIncomingResponse::ready(Ok(Err(ErrorCode::InternalError(
+                        Some(e.to_string()),
+                    )))),
+                }
+            }
+            HostFutureIncomingResponse::Ready(result) => {
+                self.finish(&result);
+                HostFutureIncomingResponse::ready(result)
+            }
+            HostFutureIncomingResponse::Consumed => HostFutureIncomingResponse::Consumed,
+        }
+    }
+
+    fn finish(self, result: &anyhow::Result<Result<IncomingResponse, ErrorCode>>) {
+        let Some((compare, shadow)) = self.compare else {
+            return;
+        };
+        let primary = match result {
+            Ok(Ok(response)) => Ok(response.resp.status()),
+            Ok(Err(e)) => Err(e.clone()),
+            Err(e) => Err(ErrorCode::InternalError(Some(e.to_string()))),
+        };
+        let (method, uri) = (self.method, self.uri);
+        // The comparison is skipped if the store can't run another task.
+        let _ = self
+            .tasks
+            .spawn_detached("wasi-http-mirror-compare", async move {
+                let shadow = shadow.await.unwrap_or_else(|_| {
+                    Err(ErrorCode::InternalError(Some(
+                        "shadow request was cancelled".to_string(),
+                    )))
+                });
+                compare(MirrorComparison {
+                    method,
+                    uri,
+                    primary,
+                    shadow,
+                });
+            });
+    }
+}
+
+/// The body of a mirrored request, which copies each frame of the original
+/// body to the shadow request.
+struct TeeBody {
+    inner: HyperOutgoingBody,
+    frames: Option<mpsc::Sender<Option<Result<Frame<Bytes>, ErrorCode>>>>,
+}
+
+impl Body for TeeBody {
+    type Data = Bytes;
+    type Error = ErrorCode;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, ErrorCode>>> {
+        let frame = ready!(Pin::new(&mut self.inner).poll_frame(cx));
+        if let Some(frames) = &self.frames {
+            // `None` marks the end of the body, as opposed to the channel being
+            // closed, which means the shadow request was abandoned.
+            let copy = match &frame {
+                Some(Ok(frame)) => match (frame.data_ref(), frame.trailers_ref()) {
+                    (Some(data), _) => Some(Some(Ok(Frame::data(data.clone())))),
+                    (None, Some(trailers)) => Some(Some(Ok(Frame::trailers(trailers.clone())))),
+                    (None, None) => None,
+                },
+                Some(Err(e)) => Some(Some(Err(e.clone()))),
+                None => Some(None),
+            };
+            if let Some(copy) = copy {
+                // A full channel means the shadow request fell behind, so
+                // it's abandoned by closing the channel.
+                if frames.try_send(copy).is_err() || frame.is_none() {
+                    self.frames = None;
+                }
+            }
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// The body of a shadow request, which is received from a [`TeeBody`].
+struct ShadowBody {
+    rx: mpsc::Receiver<Option<Result<Frame<Bytes>, ErrorCode>>>,
+    done: bool,
+}
+
+impl Body for ShadowBody {
+    type Data = Bytes;
+    type Error = ErrorCode;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, ErrorCode>>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+        match ready!(self.rx.poll_recv(cx)) {
+            Some(Some(frame)) => Poll::Ready(Some(frame)),
+            Some(None) => {
+                self.done = true;
+                Poll::Ready(None)
+            }
+            None => Poll::Ready(Some(Err(ErrorCode::InternalError(Some(
+                "mirrored request body was abandoned".to_string(),
+            ))))),
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! Reuse of connections between outgoing requests.
+
+use crate::body::HyperOutgoingBody;
+use crate::types::HttpVersionPolicy;
+use hyper::body::Incoming;
+use hyper::client::conn::{http1, http2};
+use hyper::header::HeaderValue;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use wasmtime_wasi::runtime::{AbortOnDropJoinHandle, TaskSupervisor};
+
+/// The default value of [`ConnectionPool::idle_timeout`].
+pub const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+/// The default value of [`ConnectionPool::max_idle_per_origin`].
+pub const DEFAULT_POOL_MAX_IDLE_PER_ORIGIN: usize = 32;
+
+/// A pool of idle connections which outgoing requests to the same origin are
+/// sent over, see
+/// [`WasiHttpCtx::set_connection_pool`](crate::WasiHttpCtx::set_connection_pool).
+///
+/// Once the response to a request sent over a pooled HTTP/1.1 connection has
+/// been read in full the connection goes back into the pool, where it's kept
+/// open for the next request to the same scheme and authority. HTTP/2
+/// connections are instead shared by all requests to their origin at the same
+/// time. Connections which are closed by the server or stay idle for longer
+/// than the idle timeout are discarded.
+///
+/// Clones of a pool share the same set of connections.
+#[derive(Clone)]
+pub struct ConnectionPool {
+    idle_timeout: Duration,
+    max_idle_per_origin: usize,
+    idle: Arc<Mutex<HashMap<PoolKey, Vec<IdleConnection>>>>,
+}
+
+/// The origin, and the proxy used to reach it, that a pooled connection is
+/// connected to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct PoolKey {
+    pub(crate) use_tls: bool,
+    pub(crate) authority: String,
+    pub(crate) proxy: Option<String>,
+    /// The credentials sent to the proxy, as a tunnel established with one set
+    /// of credentials mustn't be reused by requests with another.
+    pub(crate) proxy_authorization: Option<HeaderValue>,
+    pub(crate) versions: HttpVersionPolicy,
+}
+
+/// An open connection along with the task driving it.
+pub(crate) struct Connection {
+    pub(crate) sender: Sender,
+    /// The task driving an HTTP/1.1 connection, dropping which closes the
+    /// connection. Pooled HTTP/2 connections are driven by a detached task
+    /// instead, which finishes once all senders and streams of the connection
+    /// are gone.
+    pub(crate) worker: Option<AbortOnDropJoinHandle<()>>,
+}
+
+/// The sending half of a connection of either HTTP version.
+pub(crate) enum Sender {
+    Http1(http1::SendRequest<HyperOutgoingBody>),
+    Http2(http2::SendRequest<HyperOutgoingBody>),
+}
+
+impl Sender {
+    pub(crate) fn is_http2(&self) -> bool {
+        matches!(self, Sender::Http2(_))
+    }
+
+    pub(crate) async fn send_request(
+        &mut self,
+        request: hyper::Request<HyperOutgoingBody>,
+    ) -> hyper::Result<hyper::Response<Incoming>> {
+        match self {
+            Sender::Http1(sender) => sender.send_request(request).await,
+            Sender::Http2(sender) => sender.send_request(request).await,
+        }
+    }
+
+    pub(crate) async fn ready(&mut self) -> hyper::Result<()> {
+        match self {
+            Sender::Http1(sender) => sender.ready().await,
+            Sender::Http2(sender) => sender.ready().await,
+        }
+    }
+
+    fn is_ready(&self) -> bool {
+        match self {
+            Sender::Http1(sender) => sender.is_ready(),
+            Sender::Http2(sender) => sender.is_ready(),
+        }
+    }
+}
+
+struct IdleConnection {
+    conn: Connection,
+    idle_since: Instant,
+}
+
+impl ConnectionPool {
+    /// Creates an empty pool with the default configuration.
+    pub fn new() -> ConnectionPool {
+        ConnectionPool {
+            idle_timeout: DEFAULT_POOL_IDLE_TIMEOUT,
+            max_idle_per_origin: DEFAULT_POOL_MAX_IDLE_PER_ORIGIN,
+            idle: Default::default(),
+        }
+    }
+
+    /// Configures how long a connection may stay idle in the pool before it's
+    /// closed.
+    ///
+    /// Expired connections are closed when the pool is next used.
+    /// Default: [`DEFAULT_POOL_IDLE_TIMEOUT`].
+    pub fn idle_timeout(mut self, timeout: Duration) -> ConnectionPool {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// Configures the maximum number of idle connections kept open to each
+    /// origin.
+    ///
+    /// Connections which are done with beyond this limit are closed. A limit
+    /// of zero disables reuse of connections entirely.
+    /// Default: [`DEFAULT_POOL_MAX_IDLE_PER_ORIGIN`].
+    pub fn max_idle_per_origin(mut self, max: usize) -> ConnectionPool {
+        self.max_idle_per_origin = max;
+        self
+    }
+
+    /// Returns the number of idle connections currently in the pool.
+    pub fn idle_connections(&self) -> usize {
+        let mut idle = self.idle.lock().unwrap();
+        self.prune(&mut idle);
+        idle.values().map(|conns| conns.len()).sum()
+    }
+
+    /// Takes an idle connection to `key` out of the pool, if there is one.
+    pub(crate) fn checkout(&self, key: &PoolKey) -> Option<Connection> {
+        let mut idle = self.idle.lock().unwrap();
+        self.prune(&mut idle);
+        let conns = idle.get_mut(key)?;
+        // HTTP/2 connections stay in the pool while they're used, and are
+        // only ever pooled on their own as they serve all requests.
+        if let Some(idle) = conns.last_mut() {
+            if let Sender::Http2(sender) = &idle.conn.sender {
+                idle.idle_since = Instant::now();
+                return Some(Connection {
+                    sender: Sender::Http2(sender.clone()),
+                    worker: None,
+                });
+            }
+        }
+        // Prefer the most recently used connection, which is the least likely
+        // to have been closed by the server in the meantime.
+        let conn = conns.pop().map(|idle| idle.conn);
+        if conns.is_empty() {
+            idle.remove(key);
+        }
+        conn
+    }
+
+    /// Returns `conn` to the pool once the response to the request it's
+    /// sending has been read in full.
+    ///
+    /// If the connection is closed before then, for example because the
+    /// response body was dropped early, it's discarded instead. Waiting for
+    /// the response to be read is tracked by `tasks`, the supervisor of the
+    /// store which sent the request, and the connection is discarded if that
+    /// store goes away first.
+    pub(crate) fn checkin(&self, key: PoolKey, mut conn: Connection, tasks: &TaskSupervisor) {
+        if conn.sender.is_http2() {
+            let mut idle = self.idle.lock().unwrap();
+            let conns = idle.entry(key).or_default();
+            if conns.is_empty() && self.max_idle_per_origin > 0 {
+                conns.push(IdleConnection {
+                    conn,
+                    idle_since: Instant::now(),
+                });
+            }
+            return;
+        }
+
+        let pool = self.clone();
+        // This task is detached rather than kept in the response: it owns
+        // the connection, and so needs to outlive the response body which is
+        // being read over it, until the connection can be handed back.
+        let task = async move {
+            if conn.sender.ready().await.is_err() {
+                return;
+            }
+            let mut idle = pool.idle.lock().unwrap();
+            if idle.get(&key).map_or(0, |conns| conns.len()) < pool.max_idle_per_origin {
+                idle.entry(key).or_default().push(IdleConnection {
+                    conn,
+                    idle_since: Instant::now(),
+                });
+            }
+        };
+        // If the store can't run another task the connection isn't reused.
+        let _ = tasks.spawn_detached("wasi-http-pool-checkin", task);
+    }
+
+    /// Discards connections which have expired or been closed.
+    fn prune(&self, idle: &mut HashMap<PoolKey, Vec<IdleConnection>>) {
+        idle.retain(|_, conns| {
+            conns.retain(|idle| {
+                idle.idle_since.elapsed() < self.idle_timeout && idle.conn.sender.is_ready()
+            });
+            !conns.is_empty()
+        });
+    }
+}
+
+impl Default for ConnectionPool {
+    fn default() -> ConnectionPool {
+        ConnectionPool::new()
+    }
+}
+
+impl fmt::Debug for ConnectionPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnectionPool")
+            .field("idle_timeout", &self.idle_timeout)
+            .field("max_idle_per_origin", &self.max_idle_per_origin)
+            .finish_non_exhaustive()
+    }
+}
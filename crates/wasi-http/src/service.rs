@@ -0,0 +1,212 @@
+//! Serving `wasi:http/incoming-handler` components with [`hyper`].
+
+use crate::bindings::http::types::Scheme;
+use crate::bindings::ProxyPre;
+use crate::body::HyperOutgoingBody;
+use crate::WasiHttpView;
+use anyhow::bail;
+use bytes::Bytes;
+use hyper::body::Body;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use wasmtime::{Engine, Store};
+
+/// Creates a [`hyper::service::Service`] which handles each request by
+/// calling the `wasi:http/incoming-handler` export of a new instance of `pre`.
+///
+/// A new [`Store`] is created for every request with `new_store`, which is
+/// where per-request state such as the [`WasiHttpCtx`](crate::WasiHttpCtx) is
+/// set up. The response is returned as soon as the guest calls
+/// `response-outparam.set`, after which the guest keeps running in a separate
+/// task to stream the body of the response.
+///
+/// The engine of `pre` must have async support enabled, and `wasi:http` must
+/// have been added to its linker with [`add_to_linker_async`] or similar.
+///
+/// [`add_to_linker_async`]: crate::add_to_linker_async
+///
+/// # Example
+///
+/// ```no_run
+/// use hyper::server::conn::http1;
+/// use tokio::net::TcpListener;
+/// use wasmtime::component::{Component, Linker, ResourceTable};
+/// use wasmtime::{Config, Engine, Result, Store};
+/// use wasmtime_wasi::{IoView, WasiCtx, WasiCtxBuilder, WasiView};
+/// use wasmtime_wasi_http::bindings::ProxyPre;
+/// use wasmtime_wasi_http::io::TokioIo;
+/// use wasmtime_wasi_http::{WasiHttpCtx, WasiHttpView};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let mut config = Config::new();
+///     config.async_support(true);
+///     let engine = Engine::new(&config)?;
+///     let component = Component::from_file(&engine, "proxy.wasm")?;
+///
+///     let mut linker = Linker::new(&engine);
+///     wasmtime_wasi_http::add_to_linker_async(&mut linker)?;
+///     let pre = ProxyPre::new(linker.instantiate_pre(&component)?)?;
+///
+///     let service = wasmtime_wasi_http::serve(pre, |engine| {
+///         Ok(Store::new(
+///             engine,
+///             MyClientState {
+///                 table: ResourceTable::new(),
+///                 wasi: WasiCtxBuilder::new().inherit_stdio().build(),
+///                 http: WasiHttpCtx::new(),
+///             },
+///         ))
+///     });
+///
+///     let listener = TcpListener::bind("127.0.0.1:8000").await?;
+///     loop {
+///         let (client, _) = listener.accept().await?;
+///         let service = service.clone();
+///         tokio::task::spawn(async move {
+///             http1::Builder::new()
+///                 .serve_connection(TokioIo::new(client), service)
+///                 .await
+///         });
+///     }
+/// }
+///
+/// struct MyClientState {
+///     wasi: WasiCtx,
+///     http: WasiHttpCtx,
+///     table: ResourceTable,
+/// }
+/// impl IoView for MyClientState {
+///     fn table(&mut self) -> &mut ResourceTable {
+///         &mut self.table
+///     }
+/// }
+/// impl WasiView for MyClientState {
+///     fn ctx(&mut self) -> &mut WasiCtx {
+///         &mut self.wasi
+///     }
+/// }
+/// impl WasiHttpView for MyClientState {
+///     fn ctx(&mut self) -> &mut WasiHttpCtx {
+///         &mut self.http
+///     }
+/// }
+/// ```
+pub fn serve<T, F>(pre: ProxyPre<T>, new_store: F) -> ProxyService<T>
+where
+    T: WasiHttpView + Send + 'static,
+    F: Fn(&Engine) -> wasmtime::Result<Store<T>> + Send + Sync + 'static,
+{
+    ProxyService {
+        pre,
+        new_store: Arc::new(new_store),
+        scheme: Scheme::Http,
+    }
+}
+
+/// A [`hyper::service::Service`] serving requests with a
+/// `wasi:http/incoming-handler` component, created with [`serve`].
+///
+/// Clones of a service share the same component and store callback.
+pub struct ProxyService<T: 'static> {
+    pre: ProxyPre<T>,
+    new_store: Arc<dyn Fn(&Engine) -> wasmtime::Result<Store<T>> + Send + Sync>,
+    scheme: Scheme,
+}
+
+impl<T> ProxyService<T>
+where
+    T: WasiHttpView + Send + 'static,
+{
+    /// Configures the scheme that incoming requests are reported to the guest
+    /// with, such as [`Scheme::Https`] when requests are received over TLS.
+    ///
+    /// Default: [`Scheme::Http`].
+    pub fn scheme(mut self, scheme: Scheme) -> ProxyService<T> {
+        self.scheme = scheme;
+        self
+    }
+
+    /// Handles `req` with a new instance of the component.
+    ///
+    /// Returns an error if the guest traps or returns without setting a
+    /// response, or if it sets an error as its response.
+    pub async fn handle<B>(
+        &self,
+        req: hyper::Request<B>,
+    ) -> wasmtime::Result<hyper::Response<HyperOutgoingBody>>
+    where
+        B: Body<Data = Bytes, Error = hyper::Error> + Send + Sync + 'static,
+    {
+        let mut store = (self.new_store)(self.pre.engine())?;
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        let req = store
+            .data_mut()
+            .new_incoming_request(self.scheme.clone(), req)?;
+        let out = store.data_mut().new_response_outparam(sender)?;
+        let pre = self.pre.clone();
+
+        // The guest runs in a separate task so it can continue to execute,
+        // streaming the response body, after the response has been returned.
+        let task = tokio::task::spawn(async move {
+            let proxy = pre.instantiate_async(&mut store).await?;
+            proxy
+                .wasi_http_incoming_handler()
+                .call_handle(&mut store, req, out)
+                .await
+        });
+
+        match receiver.await {
+            Ok(Ok(resp)) => {
+                // Nothing is waiting on the guest anymore, so errors after the
+                // response has been set can only be logged.
+                tokio::task::spawn(async move {
+                    match task.await {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => tracing::warn!("guest failed after sending response: {e:?}"),
+                        Err(e) => tracing::warn!("guest task failed: {e}"),
+                    }
+                });
+                Ok(resp)
+            }
+            Ok(Err(e)) => Err(e.into()),
+
+            // Otherwise the `sender` was dropped along with the `Store`
+            // without a response being set, and the task's result says why.
+            Err(_) => {
+                let e = match task.await {
+                    Ok(Ok(())) => anyhow::anyhow!("guest returned"),
+                    Ok(Err(e)) => e,
+                    Err(e) => e.into(),
+                };
+                bail!("guest never invoked `response-outparam::set` method: {e:?}")
+            }
+        }
+    }
+}
+
+impl<T: 'static> Clone for ProxyService<T> {
+    fn clone(&self) -> ProxyService<T> {
+        ProxyService {
+            pre: self.pre.clone(),
+            new_store: self.new_store.clone(),
+            scheme: self.scheme.clone(),
+        }
+    }
+}
+
+impl<T, B> hyper::service::Service<hyper::Request<B>> for ProxyService<T>
+where
+    T: WasiHttpView + Send + 'static,
+    B: Body<Data = Bytes, Error = hyper::Error> + Send + Sync + 'static,
+{
+    type Response = hyper::Response<HyperOutgoingBody>;
+    type Error = anyhow::Error;
+    type Future = Pin<Box<dyn Future<Output = wasmtime::Result<Self::Response>> + Send>>;
+
+    fn call(&self, req: hyper::Request<B>) -> Self::Future {
+        let service = self.clone();
+        Box::pin(async move { service.handle(req).await })
+    }
+}
@@ -1,36 +1,149 @@
 //! Implements the base structure (i.e. [WasiHttpCtx]) that will provide the
 //! implementation of the wasi-http API.
 
-use crate::io::TokioIo;
+use crate::io::{TokioExecutor, TokioIo};
 use crate::{
     bindings::http::types::{self, Method, Scheme},
-    body::{HostIncomingBody, HyperIncomingBody, HyperOutgoingBody},
+    body::{BodyLimits, HostIncomingBody, HyperIncomingBody, HyperOutgoingBody, StreamContext},
     error::dns_error,
     hyper_request_error,
+    mirror::MirrorConfig,
+    pool::{Connection, ConnectionPool, PoolKey, Sender},
+    proxy::{Proxy, ProxyConfig},
 };
 use anyhow::bail;
 use bytes::Bytes;
 use http_body_util::BodyExt;
 use hyper::body::Body;
 use hyper::header::HeaderName;
+use hyper::rt::Executor;
 use std::any::Any;
+use std::future::Future;
 use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpStream;
 use tokio::time::timeout;
 use wasmtime::component::{Resource, ResourceTable};
-use wasmtime_wasi::{runtime::AbortOnDropJoinHandle, IoImpl, IoView, Subscribe};
+use wasmtime_wasi::{
+    runtime::{AbortOnDropJoinHandle, TaskSupervisor},
+    IoImpl, IoView, Subscribe,
+};
 
 /// Capture the state necessary for use in the wasi-http API implementation.
 #[derive(Debug)]
 pub struct WasiHttpCtx {
-    _priv: (),
+    proxy: ProxyConfig,
+    pool: Option<ConnectionPool>,
+    http_versions: HttpVersionPolicy,
+    body_limits: BodyLimits,
+    mirror: Option<MirrorConfig>,
 }
 
 impl WasiHttpCtx {
     /// Create a new context.
     pub fn new() -> Self {
-        Self { _priv: () }
+        Self {
+            proxy: ProxyConfig::new(),
+            pool: None,
+            http_versions: HttpVersionPolicy::default(),
+            body_limits: BodyLimits::default(),
+            mirror: None,
+        }
+    }
+
+    /// Configures the proxies that `wasi:http/outgoing-handler` requests are
+    /// routed through.
+    ///
+    /// By default requests are sent directly to their destination. The proxy
+    /// chosen for a request is passed to [`WasiHttpView::send_request`] in
+    /// [`OutgoingRequestConfig::proxy`].
+    pub fn set_proxy(&mut self, proxy: ProxyConfig) {
+        self.proxy = proxy;
+    }
+
+    /// Returns the proxy configuration of this context.
+    pub fn proxy(&self) -> &ProxyConfig {
+        &self.proxy
+    }
+
+    /// Configures a pool of connections which `wasi:http/outgoing-handler`
+    /// requests reuse, keeping connections to each origin open between
+    /// requests.
+    ///
+    /// By default every request opens a new connection, including a new TLS
+    /// session for `https` requests. The pool is passed to
+    /// [`WasiHttpView::send_request`] in [`OutgoingRequestConfig::pool`], and
+    /// may be shared with other contexts by cloning it.
+    pub fn set_connection_pool(&mut self, pool: ConnectionPool) {
+        self.pool = Some(pool);
+    }
+
+    /// Returns the connection pool of this context, if any.
+    pub fn connection_pool(&self) -> Option<&ConnectionPool> {
+        self.pool.as_ref()
+    }
+
+    /// Configures which HTTP versions `wasi:http/outgoing-handler` requests
+    /// may be sent with.
+    ///
+    /// The policy is passed to [`WasiHttpView::send_request`] in
+    /// [`OutgoingRequestConfig::http_versions`].
+    /// Default: [`HttpVersionPolicy::Negotiate`].
+    pub fn set_http_versions(&mut self, versions: HttpVersionPolicy) {
+        self.http_versions = versions;
     }
+
+    /// Returns the HTTP version policy of this context.
+    pub fn http_versions(&self) -> HttpVersionPolicy {
+        self.http_versions
+    }
+
+    /// Configures limits on the bodies of incoming and outgoing requests and
+    /// responses.
+    ///
+    /// The limits apply to bodies created after they're configured. By default
+    /// bodies are unlimited.
+    pub fn set_body_limits(&mut self, limits: BodyLimits) {
+        self.body_limits = limits;
+    }
+
+    /// Returns the body limits of this context.
+    pub fn body_limits(&self) -> BodyLimits {
+        self.body_limits
+    }
+
+    /// Configures a percentage of `wasi:http/outgoing-handler` requests to be
+    /// mirrored to a shadow authority, for example to canary a new backend.
+    ///
+    /// Requests are mirrored before being passed to
+    /// [`WasiHttpView::send_request`], and their shadow requests are sent by
+    /// [`default_send_request_handler`]. By default no requests are mirrored.
+    pub fn set_mirror(&mut self, mirror: MirrorConfig) {
+        self.mirror = Some(mirror);
+    }
+
+    /// Returns the mirroring configuration of this context, if any.
+    pub fn mirror(&self) -> Option<&MirrorConfig> {
+        self.mirror.as_ref()
+    }
+}
+
+/// Which HTTP versions outgoing requests are sent with, see
+/// [`WasiHttpCtx::set_http_versions`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum HttpVersionPolicy {
+    /// Always use HTTP/1.1.
+    Http1Only,
+    /// Offer both HTTP/2 and HTTP/1.1 to `https` origins with ALPN and use
+    /// whichever the server picks. Plain `http` requests use HTTP/1.1.
+    #[default]
+    Negotiate,
+    /// Like [`HttpVersionPolicy::Negotiate`], but additionally send plain
+    /// `http` requests over HTTP/2 without upgrading first (h2c with prior
+    /// knowledge), for origins known to support it.
+    ///
+    /// Requests sent through a proxy still use HTTP/1.1.
+    Http2PriorKnowledge,
 }
 
 /// A trait which provides internal WASI HTTP state.
@@ -90,11 +203,14 @@ pub trait WasiHttpView: IoView {
     {
         let (parts, body) = req.into_parts();
         let body = body.map_err(crate::hyper_response_error).boxed();
-        let body = HostIncomingBody::new(
+        let mut body = HostIncomingBody::new(
             body,
             // TODO: this needs to be plumbed through
             std::time::Duration::from_millis(600 * 1000),
         );
+        if let Some(max) = self.ctx().body_limits().max_body_bytes {
+            body.limit_size(StreamContext::Request, max);
+        }
         let incoming_req = HostIncomingRequest::new(self, parts, scheme, Some(body))?;
         Ok(self.table().push(incoming_req)?)
     }
@@ -132,6 +248,10 @@ pub trait WasiHttpView: IoView {
     }
 
     /// Maximum size allowed in a write call to the outgoing body's output-stream.
+    ///
+    /// This is the budget returned by `check-write`, and writes which exceed
+    /// it fail with an `internal-error` error code rather than being buffered.
+    /// It's queried for each outgoing body, so it may differ between bodies.
     /// Default: 1024 * 1024.
     fn outgoing_body_chunk_size(&mut self) -> usize {
         DEFAULT_OUTGOING_BODY_CHUNK_SIZE
@@ -314,6 +434,18 @@ pub struct OutgoingRequestConfig {
     pub first_byte_timeout: Duration,
     /// The timeout between chunks of a streaming body
     pub between_bytes_timeout: Duration,
+    /// The proxy to send the request through, if any, as chosen by the
+    /// [`ProxyConfig`] of the [`WasiHttpCtx`].
+    pub proxy: Option<Proxy>,
+    /// The pool to reuse connections from, and to return the connection used
+    /// to once the response has been read, if any.
+    pub pool: Option<ConnectionPool>,
+    /// The HTTP versions the request may be sent with.
+    pub http_versions: HttpVersionPolicy,
+    /// The supervisor of the store sending the request, which tracks the
+    /// tasks spawned to send it. This is the supervisor of the store's
+    /// `WasiCtx`, see [`WasiCtx::tasks`](wasmtime_wasi::WasiCtx::tasks).
+    pub tasks: TaskSupervisor,
 }
 
 /// The default implementation of how an outgoing request is sent.
@@ -324,10 +456,14 @@ pub fn default_send_request(
     request: hyper::Request<HyperOutgoingBody>,
     config: OutgoingRequestConfig,
 ) -> HostFutureIncomingResponse {
-    let handle = wasmtime_wasi::runtime::spawn(async move {
-        Ok(default_send_request_handler(request, config).await)
-    });
-    HostFutureIncomingResponse::pending(handle)
+    let tasks = config.tasks.clone();
+    let task = async move { Ok(default_send_request_handler(request, config).await) };
+    match tasks.spawn("wasi-http-outgoing-request", task) {
+        Ok(handle) => HostFutureIncomingResponse::pending(handle),
+        Err(e) => HostFutureIncomingResponse::ready(Ok(Err(types::ErrorCode::InternalError(
+            Some(e.to_string()),
+        )))),
+    }
 }
 
 /// The underlying implementation of how an outgoing request is sent. This should likely be spawned
@@ -341,6 +477,10 @@ pub async fn default_send_request_handler(
         connect_timeout,
         first_byte_timeout,
         between_bytes_timeout,
+        proxy,
+        pool,
+        http_versions,
+        tasks,
     }: OutgoingRequestConfig,
 ) -> Result<IncomingResponse, types::ErrorCode> {
     let authority = if let Some(authority) = request.uri().authority() {
@@ -353,7 +493,106 @@ pub async fn default_send_request_handler(
     } else {
         return Err(types::ErrorCode::HttpRequestUriInvalid);
     };
-    let tcp_stream = timeout(connect_timeout, TcpStream::connect(&authority))
+    let key = PoolKey {
+        use_tls,
+        authority,
+        proxy: proxy.as_ref().map(|p| p.authority().to_string()),
+        proxy_authorization: proxy.as_ref().and_then(|p| p.authorization().cloned()),
+        versions: http_versions,
+    };
+    let pooled = match pool.as_ref().and_then(|pool| pool.checkout(&key)) {
+        // The server may have closed a pooled connection since it was
+        // returned to the pool. Nothing has been sent over it yet, so the
+        // request is retried once over a new connection instead.
+        Some(mut conn) => match timeout(connect_timeout, conn.sender.ready()).await {
+            Ok(Ok(())) => Some(conn),
+            Ok(Err(_)) | Err(_) => None,
+        },
+        None => None,
+    };
+    let mut conn = match pooled {
+        Some(conn) => conn,
+        None => {
+            // Connections which go into the pool are owned by the pool,
+            // which may be shared between stores, rather than by the store
+            // sending this request. They're closed when the pool is dropped.
+            let tasks = match &pool {
+                Some(_) => None,
+                None => Some(&tasks),
+            };
+            connect(
+                &key.authority,
+                use_tls,
+                connect_timeout,
+                proxy.as_ref(),
+                http_versions,
+                tasks,
+            )
+            .await?
+        }
+    };
+
+    // at this point, the request contains the scheme and the authority, but
+    // the http packet should only include those if addressing a proxy, so
+    // remove them here unless that's the case, since
+    // SendRequest::send_request does not do it for us. Requests tunneled
+    // through a proxy are addressed to the destination itself. HTTP/2 always
+    // sends them, as the `:scheme` and `:authority` pseudo-headers.
+    match &proxy {
+        _ if conn.sender.is_http2() => {
+            *request.version_mut() = http::Version::HTTP_2;
+            request.headers_mut().remove(hyper::header::HOST);
+        }
+        Some(proxy) if !use_tls => {
+            if let Some(authorization) = proxy.authorization() {
+                request
+                    .headers_mut()
+                    .insert(hyper::header::PROXY_AUTHORIZATION, authorization.clone());
+            }
+        }
+        _ => strip_scheme_and_authority(&mut request),
+    }
+
+    let resp = timeout(first_byte_timeout, conn.sender.send_request(request))
+        .await
+        .map_err(|_| types::ErrorCode::ConnectionReadTimeout)?
+        .map_err(hyper_request_error)?
+        .map(|body| body.map_err(hyper_request_error).boxed());
+
+    // Pooled connections are kept open by the pool rather than the response.
+    let worker = match &pool {
+        Some(pool) => {
+            pool.checkin(key, conn, &tasks);
+            None
+        }
+        None => conn.worker,
+    };
+
+    Ok(IncomingResponse {
+        resp,
+        worker,
+        between_bytes_timeout,
+    })
+}
+
+/// Opens a new connection to `authority`, which includes a port, or to the
+/// `proxy` which it's reached through.
+///
+/// The connection uses HTTP/2 if the server agrees to it during the TLS
+/// handshake, or for plain connections if `versions` says it supports it. The
+/// task driving the connection is tracked by `tasks` if it's given.
+async fn connect(
+    authority: &str,
+    use_tls: bool,
+    connect_timeout: Duration,
+    proxy: Option<&Proxy>,
+    versions: HttpVersionPolicy,
+    tasks: Option<&TaskSupervisor>,
+) -> Result<Connection, types::ErrorCode> {
+    // When going through a proxy the connection is made to the proxy instead,
+    // and for TLS a tunnel to the destination is then opened through it.
+    let connect_authority = proxy.map_or(authority, |p| p.authority());
+    let mut tcp_stream = timeout(connect_timeout, TcpStream::connect(connect_authority))
         .await
         .map_err(|_| types::ErrorCode::ConnectionTimeout)?
         .map_err(|e| match e.kind() {
@@ -371,8 +610,16 @@ pub async fn default_send_request_handler(
                 }
             }
         })?;
+    if let (Some(proxy), true) = (proxy, use_tls) {
+        timeout(
+            connect_timeout,
+            proxy.connect_tunnel(&mut tcp_stream, authority),
+        )
+        .await
+        .map_err(|_| types::ErrorCode::ConnectionTimeout)??;
+    }
 
-    let (mut sender, worker) = if use_tls {
+    if use_tls {
         #[cfg(any(target_arch = "riscv64", target_arch = "s390x"))]
         {
             return Err(crate::bindings::http::types::ErrorCode::InternalError(
@@ -388,12 +635,15 @@ pub async fn default_send_request_handler(
             let root_cert_store = rustls::RootCertStore {
                 roots: webpki_roots::TLS_SERVER_ROOTS.into(),
             };
-            let config = rustls::ClientConfig::builder()
+            let mut config = rustls::ClientConfig::builder()
                 .with_root_certificates(root_cert_store)
                 .with_no_client_auth();
+            if versions != HttpVersionPolicy::Http1Only {
+                config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+            }
             let connector = tokio_rustls::TlsConnector::from(std::sync::Arc::new(config));
             let mut parts = authority.split(":");
-            let host = parts.next().unwrap_or(&authority);
+            let host = parts.next().unwrap_or(authority);
             let domain = ServerName::try_from(host)
                 .map_err(|e| {
                     tracing::warn!("dns lookup error: {e:?}");
@@ -404,52 +654,101 @@ pub async fn default_send_request_handler(
                 tracing::warn!("tls protocol error: {e:?}");
                 types::ErrorCode::TlsProtocolError
             })?;
-            let stream = TokioIo::new(stream);
+            let http2 = stream.get_ref().1.alpn_protocol() == Some(b"h2");
+            handshake(stream, http2, connect_timeout, tasks).await
+        }
+    } else {
+        let http2 = versions == HttpVersionPolicy::Http2PriorKnowledge && proxy.is_none();
+        handshake(tcp_stream, http2, connect_timeout, tasks).await
+    }
+}
 
-            let (sender, conn) = timeout(
-                connect_timeout,
-                hyper::client::conn::http1::handshake(stream),
-            )
-            .await
-            .map_err(|_| types::ErrorCode::ConnectionTimeout)?
-            .map_err(hyper_request_error)?;
-
-            let worker = wasmtime_wasi::runtime::spawn(async move {
-                match conn.await {
-                    Ok(()) => {}
-                    // TODO: shouldn't throw away this error and ideally should
-                    // surface somewhere.
-                    Err(e) => tracing::warn!("dropping error {e}"),
-                }
-            });
+/// Performs the HTTP handshake over `stream`, with HTTP/2 if `http2` and
+/// HTTP/1.1 otherwise.
+async fn handshake<T>(
+    stream: T,
+    http2: bool,
+    connect_timeout: Duration,
+    tasks: Option<&TaskSupervisor>,
+) -> Result<Connection, types::ErrorCode>
+where
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let stream = TokioIo::new(stream);
+    if http2 {
+        let (sender, conn) = timeout(
+            connect_timeout,
+            hyper::client::conn::http2::handshake(TokioExecutor, stream),
+        )
+        .await
+        .map_err(|_| types::ErrorCode::ConnectionTimeout)?
+        .map_err(hyper_request_error)?;
 
-            (sender, worker)
-        }
+        let conn = async move {
+            if let Err(e) = conn.await {
+                tracing::warn!("dropping error {e}");
+            }
+        };
+        let worker = match tasks {
+            Some(tasks) => Some(spawn_worker(tasks, conn)?),
+            // Many requests and responses share a pooled HTTP/2 connection,
+            // so rather than belonging to any one of them it's driven by a
+            // detached task, which finishes once all senders and streams are
+            // gone.
+            None => {
+                TokioExecutor.execute(conn);
+                None
+            }
+        };
+
+        Ok(Connection {
+            sender: Sender::Http2(sender),
+            worker,
+        })
     } else {
-        let tcp_stream = TokioIo::new(tcp_stream);
         let (sender, conn) = timeout(
             connect_timeout,
             // TODO: we should plumb the builder through the http context, and use it here
-            hyper::client::conn::http1::handshake(tcp_stream),
+            hyper::client::conn::http1::handshake(stream),
         )
         .await
         .map_err(|_| types::ErrorCode::ConnectionTimeout)?
         .map_err(hyper_request_error)?;
 
-        let worker = wasmtime_wasi::runtime::spawn(async move {
+        let conn = async move {
             match conn.await {
                 Ok(()) => {}
-                // TODO: same as above, shouldn't throw this error away.
+                // TODO: shouldn't throw away this error and ideally should
+                // surface somewhere.
                 Err(e) => tracing::warn!("dropping error {e}"),
             }
-        });
+        };
+        let worker = match tasks {
+            Some(tasks) => spawn_worker(tasks, conn)?,
+            None => wasmtime_wasi::runtime::spawn(conn),
+        };
 
-        (sender, worker)
-    };
+        Ok(Connection {
+            sender: Sender::Http1(sender),
+            worker: Some(worker),
+        })
+    }
+}
 
-    // at this point, the request contains the scheme and the authority, but
-    // the http packet should only include those if addressing a proxy, so
-    // remove them here, since SendRequest::send_request does not do it for us
+/// Spawns the task driving a connection which isn't pooled, and so belongs to
+/// the store sending the request over it.
+fn spawn_worker(
+    tasks: &TaskSupervisor,
+    conn: impl Future<Output = ()> + Send + 'static,
+) -> Result<AbortOnDropJoinHandle<()>, types::ErrorCode> {
+    tasks
+        .spawn("wasi-http-connection", conn)
+        .map_err(|e| types::ErrorCode::InternalError(Some(e.to_string())))
+}
+
+/// Replaces the URI of `request` with just its path and query, as sent in
+/// requests to anything other than a proxy.
+fn strip_scheme_and_authority(request: &mut hyper::Request<HyperOutgoingBody>) {
     *request.uri_mut() = http::Uri::builder()
         .path_and_query(
             request
@@ -460,18 +759,6 @@ pub async fn default_send_request_handler(
         )
         .build()
         .expect("comes from valid request");
-
-    let resp = timeout(first_byte_timeout, sender.send_request(request))
-        .await
-        .map_err(|_| types::ErrorCode::ConnectionReadTimeout)?
-        .map_err(hyper_request_error)?
-        .map(|body| body.map_err(hyper_request_error).boxed());
-
-    Ok(IncomingResponse {
-        resp,
-        worker: Some(worker),
-        between_bytes_timeout,
-    })
 }
 
 impl From<http::Method> for types::Method {
@@ -628,6 +915,11 @@ pub struct HostRequestOptions {
 pub struct HostIncomingResponse {
     /// The response status
     pub status: u16,
+    /// The HTTP version the response was received with.
+    ///
+    /// `wasi:http` has no way for guests to query this, so guests read it
+    /// through Wasmtime's `http-version` extension instead.
+    pub version: http::Version,
     /// The response headers
     pub headers: FieldMap,
     /// The response body
@@ -674,6 +966,17 @@ pub struct IncomingResponse {
     pub between_bytes_timeout: std::time::Duration,
 }
 
+impl IncomingResponse {
+    /// Returns the HTTP version this response was received with.
+    ///
+    /// For responses returned by [`default_send_request_handler`] this is
+    /// the version negotiated with the server, which is HTTP/2 if the server
+    /// agreed to it or if prior knowledge was configured.
+    pub fn version(&self) -> http::Version {
+        self.resp.version()
+    }
+}
+
 /// The concrete type behind a `wasi:http/types/future-incoming-response` resource.
 #[derive(Debug)]
 pub enum HostFutureIncomingResponse {
@@ -714,11 +1017,25 @@ impl HostFutureIncomingResponse {
     }
 }
 
+/// Waits for the task behind `handle`, treating its cancellation through a
+/// [`TaskSupervisor`] as a failed request.
+pub(crate) async fn join_response(
+    handle: &mut FutureIncomingResponseHandle,
+) -> anyhow::Result<Result<IncomingResponse, types::ErrorCode>> {
+    match (&mut **handle).await {
+        Ok(result) => result,
+        Err(e) if e.is_cancelled() => Ok(Err(types::ErrorCode::InternalError(Some(
+            "outgoing request was cancelled".to_string(),
+        )))),
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
 #[async_trait::async_trait]
 impl Subscribe for HostFutureIncomingResponse {
     async fn ready(&mut self) {
         if let Self::Pending(handle) = self {
-            *self = Self::Ready(handle.await);
+            *self = Self::Ready(join_response(handle).await);
         }
     }
 }
@@ -1,6 +1,8 @@
-//! Implementation for the `wasi:http/types` interface.
+//! Implementation for the `wasi:http/types` interface and Wasmtime's
+//! extensions to it.
 
 use crate::{
+    bindings::extensions::http_version::{self, Version},
     bindings::http::types::{self, Headers, Method, Scheme, StatusCode, Trailers},
     body::{HostFutureTrailers, HostIncomingBody, HostOutgoingBody, StreamContext},
     types::{
@@ -393,6 +395,7 @@ where
     ) -> wasmtime::Result<Result<Resource<HostOutgoingBody>, ()>> {
         let buffer_chunks = self.outgoing_body_buffer_chunks();
         let chunk_size = self.outgoing_body_chunk_size();
+        let limits = self.ctx().body_limits();
         let req = self
             .table()
             .get_mut(&request)
@@ -407,8 +410,13 @@ where
             Err(e) => return Ok(Err(e)),
         };
 
-        let (host_body, hyper_body) =
-            HostOutgoingBody::new(StreamContext::Request, size, buffer_chunks, chunk_size);
+        let (host_body, hyper_body) = HostOutgoingBody::new(
+            StreamContext::Request,
+            size,
+            buffer_chunks,
+            chunk_size,
+            limits,
+        );
 
         req.body = Some(hyper_body);
 
@@ -756,6 +764,7 @@ where
     ) -> wasmtime::Result<Result<Resource<HostOutgoingBody>, ()>> {
         let buffer_chunks = self.outgoing_body_buffer_chunks();
         let chunk_size = self.outgoing_body_chunk_size();
+        let limits = self.ctx().body_limits();
         let resp = self.table().get_mut(&id)?;
 
         if resp.body.is_some() {
@@ -767,8 +776,13 @@ where
             Err(e) => return Ok(Err(e)),
         };
 
-        let (host, body) =
-            HostOutgoingBody::new(StreamContext::Response, size, buffer_chunks, chunk_size);
+        let (host, body) = HostOutgoingBody::new(
+            StreamContext::Response,
+            size,
+            buffer_chunks,
+            chunk_size,
+            limits,
+        );
 
         resp.body.replace(body);
 
@@ -864,12 +878,17 @@ where
         let (mut parts, body) = resp.resp.into_parts();
 
         remove_forbidden_headers(self, &mut parts.headers);
+        let max_body_bytes = self.ctx().body_limits().max_body_bytes;
 
         let resp = self.table().push(HostIncomingResponse {
             status: parts.status.as_u16(),
+            version: parts.version,
             headers: parts.headers,
             body: Some({
                 let mut body = HostIncomingBody::new(body, resp.between_bytes_timeout);
+                if let Some(max) = max_body_bytes {
+                    body.limit_size(StreamContext::Response, max);
+                }
                 if let Some(worker) = resp.worker {
                     body.retain_worker(worker);
                 }
@@ -1023,3 +1042,26 @@ where
         Ok(())
     }
 }
+
+impl<T> http_version::Host for WasiHttpImpl<T>
+where
+    T: WasiHttpView,
+{
+    fn response_version(
+        &mut self,
+        response: Resource<HostIncomingResponse>,
+    ) -> wasmtime::Result<Version> {
+        let r = self
+            .table()
+            .get(&response)
+            .context("[incoming_response_version] getting response")?;
+        Ok(match r.version {
+            http::Version::HTTP_09 => Version::Http09,
+            http::Version::HTTP_10 => Version::Http10,
+            http::Version::HTTP_11 => Version::Http11,
+            http::Version::HTTP_2 => Version::Http2,
+            http::Version::HTTP_3 => Version::Http3,
+            version => anyhow::bail!("unknown HTTP version {version:?}"),
+        })
+    }
+}
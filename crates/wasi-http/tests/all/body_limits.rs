@@ -0,0 +1,116 @@
+use anyhow::Result;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Bytes;
+use std::time::Duration;
+use wasmtime_wasi::{HostInputStream, HostOutputStream, StreamError, Subscribe};
+use wasmtime_wasi_http::{
+    bindings::http::types::ErrorCode,
+    body::{BodyLimits, HostIncomingBody, HostOutgoingBody, StreamContext},
+};
+
+fn error_code(e: StreamError) -> ErrorCode {
+    match e {
+        StreamError::LastOperationFailed(e) => e.downcast().unwrap(),
+        e => panic!("unexpected stream error: {e:?}"),
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn outgoing_body_size_limit() -> Result<()> {
+    let limits = BodyLimits {
+        max_body_bytes: Some(10),
+        ..BodyLimits::default()
+    };
+    let (mut body, _hyper_body) =
+        HostOutgoingBody::new(StreamContext::Response, None, 4, 1024, limits);
+    let mut stream = body.take_output_stream().unwrap();
+
+    stream.write(Bytes::from_static(b"hello "))?;
+    let err = stream.write(Bytes::from_static(b"world")).unwrap_err();
+    assert!(matches!(
+        error_code(err),
+        ErrorCode::HttpResponseBodySize(Some(11))
+    ));
+    // The rejected write wasn't sent.
+    assert_eq!(body.bytes_written(), 6);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn outgoing_body_in_flight_limit() -> Result<()> {
+    let limits = BodyLimits {
+        max_in_flight_write_bytes: Some(10),
+        ..BodyLimits::default()
+    };
+    let (mut body, mut hyper_body) =
+        HostOutgoingBody::new(StreamContext::Request, None, 4, 1024, limits);
+    let mut stream = body.take_output_stream().unwrap();
+
+    assert_eq!(stream.check_write()?, 10);
+    stream.write(Bytes::from_static(b"hello "))?;
+    assert_eq!(body.bytes_in_flight(), 6);
+    assert_eq!(stream.check_write()?, 4);
+
+    let err = stream.write(Bytes::from_static(b"world")).unwrap_err();
+    assert!(matches!(error_code(err), ErrorCode::InternalError(Some(_))));
+
+    // Once hyper picks up the bytes they're no longer in flight and writes
+    // are allowed again.
+    let frame = hyper_body.frame().await.unwrap().unwrap();
+    assert_eq!(frame.into_data().unwrap(), "hello ");
+    assert_eq!(body.bytes_in_flight(), 0);
+    stream.ready().await;
+    assert_eq!(stream.check_write()?, 10);
+    stream.write(Bytes::from_static(b"world"))?;
+    assert_eq!(body.bytes_written(), 11);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn outgoing_body_write_budget() -> Result<()> {
+    let (mut body, mut hyper_body) =
+        HostOutgoingBody::new(StreamContext::Request, None, 1, 8, BodyLimits::default());
+    let mut stream = body.take_output_stream().unwrap();
+
+    // Writes larger than the budget are rejected rather than buffered.
+    assert_eq!(stream.check_write()?, 8);
+    let err = stream
+        .write(Bytes::from_static(b"hello, world"))
+        .unwrap_err();
+    assert!(matches!(error_code(err), ErrorCode::InternalError(Some(_))));
+    assert_eq!(body.bytes_written(), 0);
+
+    // Once the buffered chunks are full no more writes are permitted until
+    // hyper catches up.
+    stream.write(Bytes::from_static(b"hello, "))?;
+    stream.write(Bytes::from_static(b"world"))?;
+    assert_eq!(stream.check_write()?, 0);
+    let err = stream.write(Bytes::from_static(b"!")).unwrap_err();
+    assert!(matches!(error_code(err), ErrorCode::InternalError(Some(_))));
+
+    let frame = hyper_body.frame().await.unwrap().unwrap();
+    assert_eq!(frame.into_data().unwrap(), "hello, ");
+    stream.ready().await;
+    assert_eq!(stream.check_write()?, 8);
+    stream.write(Bytes::from_static(b"!"))?;
+    assert_eq!(body.bytes_written(), 13);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn incoming_body_size_limit() -> Result<()> {
+    let hyper_body = Full::new(Bytes::from_static(b"hello, world!"))
+        .map_err(|_| unreachable!())
+        .boxed();
+    let mut body = HostIncomingBody::new(hyper_body, Duration::from_secs(10));
+    body.limit_size(StreamContext::Request, 4);
+    let mut stream = body.take_stream().unwrap();
+
+    stream.ready().await;
+    let err = stream.read(1024).unwrap_err();
+    assert!(matches!(
+        error_code(err),
+        ErrorCode::HttpRequestBodySize(Some(13))
+    ));
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use anyhow::Result;
+use futures::stream;
+use http_body::Frame;
+use http_body_util::{BodyExt, StreamBody};
+use hyper::body::Bytes;
+use std::io::IoSliceMut;
+use std::time::Duration;
+use wasmtime::component::ResourceTable;
+use wasmtime_wasi::bindings::io::streams::HostInputStream as _;
+use wasmtime_wasi::{HostInputStream, InputStream, IoImpl, IoView, Subscribe};
+use wasmtime_wasi_http::{bindings::http::types::ErrorCode, body::HostIncomingBody};
+
+fn incoming_body(frames: &[Bytes]) -> HostIncomingBody {
+    let frames = frames
+        .iter()
+        .map(|bytes| Ok::<_, ErrorCode>(Frame::data(bytes.clone())))
+        .collect::<Vec<_>>();
+    HostIncomingBody::new(
+        StreamBody::new(stream::iter(frames)).boxed(),
+        Duration::from_secs(10),
+    )
+}
+
+#[test_log::test(tokio::test)]
+async fn incoming_frames_are_not_copied() -> Result<()> {
+    let frames = [Bytes::from(vec![1; 100]), Bytes::from(vec![2; 100])];
+    let mut body = incoming_body(&frames);
+    let mut stream = body.take_stream().unwrap();
+
+    // Reads hand out the buffers of the frames received from hyper as they
+    // are, or parts of them.
+    stream.ready().await;
+    let chunk = stream.read(40)?;
+    assert_eq!(chunk.as_ptr(), frames[0].as_ptr());
+    let chunk = stream.read(1024)?;
+    assert_eq!(chunk.as_ptr(), frames[0][40..].as_ptr());
+    assert_eq!(chunk.len(), 60);
+    stream.ready().await;
+    let chunk = stream.read(1024)?;
+    assert_eq!(chunk.as_ptr(), frames[1].as_ptr());
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn incoming_frames_read_vectored() -> Result<()> {
+    let frames = [
+        Bytes::from_static(b"hello, "),
+        Bytes::from_static(b"world!"),
+    ];
+    let mut body = incoming_body(&frames);
+    let mut stream = body.take_stream().unwrap();
+
+    // All frames which are already available are read at once.
+    let (mut a, mut b) = ([0; 4], [0; 16]);
+    let n = stream
+        .blocking_read_vectored(&mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)])
+        .await?;
+    assert_eq!(n, 13);
+    assert_eq!(&a, b"hell");
+    assert_eq!(&b[..9], b"o, world!");
+    Ok(())
+}
+
+struct Ctx {
+    table: ResourceTable,
+}
+
+impl IoView for Ctx {
+    fn table(&mut self) -> &mut ResourceTable {
+        &mut self.table
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn incoming_frames_coalesce_in_guest_reads() -> Result<()> {
+    let frames = [
+        Bytes::from_static(b"hello, "),
+        Bytes::from_static(b"world!"),
+    ];
+    let mut body = incoming_body(&frames);
+    let stream: InputStream = Box::new(body.take_stream().unwrap());
+    let mut ctx = Ctx {
+        table: ResourceTable::new(),
+    };
+    let stream = ctx.table.push(stream)?;
+
+    // A guest's `blocking-read` returns all of the frames which are available
+    // rather than one frame at a time.
+    let mut io = IoImpl(&mut ctx);
+    let bytes = io.blocking_read(stream, 1024).await?;
+    assert_eq!(bytes, b"hello, world!");
+    Ok(())
+}
@@ -0,0 +1,145 @@
+use anyhow::Result;
+use http_body_util::{BodyExt, Empty, Full};
+use hyper::{body::Bytes, server::conn::http2, service::service_fn};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use wasmtime::component::ResourceTable;
+use wasmtime_wasi::{pipe::MemoryOutputPipe, IoImpl, IoView, WasiCtx};
+use wasmtime_wasi_http::{
+    bindings::extensions::http_version::{self, Version},
+    bindings::http::types::{ErrorCode, HostFutureIncomingResponse as _},
+    io::{TokioExecutor, TokioIo},
+    types::{
+        default_send_request_handler, HostFutureIncomingResponse, IncomingResponse,
+        OutgoingRequestConfig,
+    },
+    ConnectionPool, HttpVersionPolicy, WasiHttpCtx, WasiHttpImpl,
+};
+
+/// Starts an HTTP/2-only server, returning its address and the number of
+/// connections it has accepted.
+async fn server() -> Result<(SocketAddr, Arc<AtomicUsize>)> {
+    let listener = TcpListener::bind((Ipv4Addr::new(127, 0, 0, 1), 0)).await?;
+    let addr = listener.local_addr()?;
+    let connections = Arc::new(AtomicUsize::new(0));
+    let accepted = connections.clone();
+    tokio::task::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            accepted.fetch_add(1, Ordering::SeqCst);
+            tokio::task::spawn(async move {
+                http2::Builder::new(TokioExecutor)
+                    .serve_connection(
+                        TokioIo::new(stream),
+                        service_fn(
+                            |request: hyper::Request<hyper::body::Incoming>| async move {
+                                let authority = request.uri().authority().unwrap().to_string();
+                                Ok::<_, hyper::Error>(hyper::Response::new(Full::new(Bytes::from(
+                                    authority,
+                                ))))
+                            },
+                        ),
+                    )
+                    .await
+            });
+        }
+    });
+    Ok((addr, connections))
+}
+
+async fn get(
+    addr: SocketAddr,
+    http_versions: HttpVersionPolicy,
+    pool: Option<&ConnectionPool>,
+) -> Result<IncomingResponse, ErrorCode> {
+    let request = hyper::Request::builder()
+        .uri(format!("http://{addr}/"))
+        .body(Empty::<Bytes>::new().map_err(|_| unreachable!()).boxed())
+        .unwrap();
+    let config = OutgoingRequestConfig {
+        use_tls: false,
+        connect_timeout: Duration::from_secs(10),
+        first_byte_timeout: Duration::from_secs(10),
+        between_bytes_timeout: Duration::from_secs(10),
+        proxy: None,
+        pool: pool.cloned(),
+        http_versions,
+        tasks: crate::test_tasks(),
+    };
+    default_send_request_handler(request, config).await
+}
+
+#[test_log::test(tokio::test)]
+async fn prior_knowledge() -> Result<()> {
+    let (addr, _) = server().await?;
+
+    let response = get(addr, HttpVersionPolicy::Http2PriorKnowledge, None)
+        .await
+        .map_err(|e| anyhow::anyhow!("{e:?}"))?;
+    assert_eq!(response.version(), http::Version::HTTP_2);
+    // The authority is sent in the `:authority` pseudo-header.
+    let body = response.resp.into_body().collect().await?.to_bytes();
+    assert_eq!(body, addr.to_string());
+
+    // Without prior knowledge plain requests are sent with HTTP/1.1, which
+    // this server doesn't understand.
+    assert!(get(addr, HttpVersionPolicy::Negotiate, None).await.is_err());
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn pooled_connections_are_shared() -> Result<()> {
+    let (addr, connections) = server().await?;
+    let pool = ConnectionPool::new();
+
+    // Responses which are still being read don't prevent other requests
+    // from being sent over the same connection.
+    let mut responses = Vec::new();
+    for _ in 0..3 {
+        let response = get(addr, HttpVersionPolicy::Http2PriorKnowledge, Some(&pool))
+            .await
+            .map_err(|e| anyhow::anyhow!("{e:?}"))?;
+        responses.push(response);
+    }
+    assert_eq!(pool.idle_connections(), 1);
+    for response in responses {
+        response.resp.into_body().collect().await?;
+    }
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn guests_see_negotiated_version() -> Result<()> {
+    let (addr, _) = server().await?;
+    let response = get(addr, HttpVersionPolicy::Http2PriorKnowledge, None)
+        .await
+        .map_err(|e| anyhow::anyhow!("{e:?}"))?;
+
+    let mut ctx = crate::Ctx {
+        table: ResourceTable::new(),
+        wasi: WasiCtx::builder().build(),
+        http: WasiHttpCtx::new(),
+        stdout: MemoryOutputPipe::new(4096),
+        stderr: MemoryOutputPipe::new(4096),
+        send_request: None,
+        rejected_authority: None,
+    };
+    let mut host = WasiHttpImpl(IoImpl(&mut ctx));
+
+    // Hand the response to the guest the same way `outgoing-handler` does.
+    let future = host
+        .table()
+        .push(HostFutureIncomingResponse::ready(Ok(Ok(response))))?;
+    let response = match host.get(future)? {
+        Some(Ok(Ok(response))) => response,
+        other => panic!("response wasn't ready: {other:?}"),
+    };
+    assert_eq!(
+        http_version::Host::response_version(&mut host, response)?,
+        Version::Http2
+    );
+    Ok(())
+}
@@ -5,13 +5,22 @@ use http_body::Frame;
 use http_body_util::{combinators::BoxBody, BodyExt, Collected, Empty, StreamBody};
 use hyper::{body::Bytes, server::conn::http1, service::service_fn, Method, StatusCode};
 use sha2::{Digest, Sha256};
-use std::{collections::HashMap, iter, net::Ipv4Addr, str, sync::Arc};
+use std::{
+    collections::HashMap,
+    iter,
+    net::Ipv4Addr,
+    str,
+    sync::{Arc, LazyLock},
+};
 use tokio::task;
 use wasmtime::{
     component::{Component, Linker, ResourceTable},
     Config, Engine, Store,
 };
-use wasmtime_wasi::{self, pipe::MemoryOutputPipe, IoView, WasiCtx, WasiCtxBuilder, WasiView};
+use wasmtime_wasi::{
+    self, pipe::MemoryOutputPipe, runtime::TaskSupervisor, IoView, WasiCtx, WasiCtxBuilder,
+    WasiView,
+};
 use wasmtime_wasi_http::{
     bindings::http::types::{ErrorCode, Scheme},
     body::HyperOutgoingBody,
@@ -20,7 +29,15 @@ use wasmtime_wasi_http::{
     HttpResult, WasiHttpCtx, WasiHttpView,
 };
 
+mod body_limits;
+mod body_streams;
+mod http2;
 mod http_server;
+mod mirror;
+mod pool;
+mod proxy;
+mod serve;
+mod supervisor;
 
 type RequestSender = Arc<
     dyn Fn(hyper::Request<HyperOutgoingBody>, OutgoingRequestConfig) -> HostFutureIncomingResponse
@@ -112,6 +129,17 @@ impl Drop for Ctx {
     }
 }
 
+/// Returns a supervisor standing in for the `WasiCtx` of a store, for tests
+/// which send requests without one.
+///
+/// It's never dropped, so tasks which outlive sending a request, such as those
+/// driving its connection, aren't cancelled while the test is still using
+/// them.
+fn test_tasks() -> TaskSupervisor {
+    static TASKS: LazyLock<TaskSupervisor> = LazyLock::new(TaskSupervisor::new);
+    TASKS.clone()
+}
+
 // Assert that each of `sync` and `async` below are testing everything through
 // assertion of the existence of the test function itself.
 macro_rules! assert_test_exists {
@@ -0,0 +1,157 @@
+use anyhow::Result;
+use http_body_util::{BodyExt, Full};
+use hyper::{body::Bytes, server::conn::http1, service::service_fn, StatusCode};
+use std::net::Ipv4Addr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use wasmtime_wasi_http::{
+    io::TokioIo,
+    types::{default_send_request, HostFutureIncomingResponse, OutgoingRequestConfig},
+    HttpVersionPolicy, MirrorConfig,
+};
+
+fn config() -> OutgoingRequestConfig {
+    OutgoingRequestConfig {
+        use_tls: false,
+        connect_timeout: Duration::from_secs(10),
+        first_byte_timeout: Duration::from_secs(10),
+        between_bytes_timeout: Duration::from_secs(10),
+        proxy: None,
+        pool: None,
+        http_versions: HttpVersionPolicy::default(),
+        tasks: crate::test_tasks(),
+    }
+}
+
+fn request(
+    uri: &str,
+    body: &'static str,
+) -> Result<hyper::Request<wasmtime_wasi_http::body::HyperOutgoingBody>> {
+    let uri = uri.parse::<hyper::Uri>()?;
+    // Like the outgoing handler, address the request with a `Host` header.
+    Ok(hyper::Request::builder()
+        .method(hyper::Method::POST)
+        .header(hyper::header::HOST, uri.authority().unwrap().as_str())
+        .uri(uri)
+        .body(
+            Full::new(Bytes::from(body))
+                .map_err(|_| unreachable!())
+                .boxed(),
+        )?)
+}
+
+/// Serves a single request, recording its path, `Host` header and body.
+async fn serve_one(
+    status: StatusCode,
+) -> Result<(
+    String,
+    tokio::task::JoinHandle<Result<()>>,
+    Arc<Mutex<Option<(String, String, Bytes)>>>,
+)> {
+    let listener = TcpListener::bind((Ipv4Addr::new(127, 0, 0, 1), 0)).await?;
+    let addr = listener.local_addr()?.to_string();
+    let seen = Arc::new(Mutex::new(None));
+    let server = {
+        let seen = seen.clone();
+        tokio::task::spawn(async move {
+            let (stream, _) = listener.accept().await?;
+            http1::Builder::new()
+                .serve_connection(
+                    TokioIo::new(stream),
+                    service_fn(move |request: hyper::Request<hyper::body::Incoming>| {
+                        let seen = seen.clone();
+                        async move {
+                            let path = request.uri().path().to_string();
+                            let host = request.headers()[hyper::header::HOST]
+                                .to_str()
+                                .unwrap()
+                                .to_string();
+                            let body = request.into_body().collect().await?.to_bytes();
+                            *seen.lock().unwrap() = Some((path, host, body));
+                            let mut response = hyper::Response::new(
+                                Full::new(Bytes::new()).map_err(|_| unreachable!()).boxed(),
+                            );
+                            *response.status_mut() = status;
+                            Ok::<_, hyper::Error>(response)
+                        }
+                    }),
+                )
+                .await?;
+            Ok(())
+        })
+    };
+    Ok((addr, server, seen))
+}
+
+#[test]
+fn parse_mirror() -> Result<()> {
+    let mirror = MirrorConfig::new("canary.example.com:8080", 10)?;
+    assert_eq!(mirror.authority(), "canary.example.com:8080");
+    assert_eq!(mirror.percent(), 10);
+
+    assert!(MirrorConfig::new("canary.example.com", 101).is_err());
+    assert!(MirrorConfig::new("not an authority", 10).is_err());
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn mirror_sampling() -> Result<()> {
+    // Shadow requests to the discard port fail in the background.
+    let mirror = MirrorConfig::new("127.0.0.1:9", 25)?;
+    let mut mirrored = Vec::new();
+    for _ in 0..8 {
+        let mut request = request("http://example.invalid/", "")?;
+        mirrored.push(mirror.mirror(&mut request, &config()).is_some());
+    }
+    assert_eq!(
+        mirrored,
+        [false, false, false, true, false, false, false, true]
+    );
+
+    let mirror = MirrorConfig::new("127.0.0.1:9", 0)?;
+    let mut request = request("http://example.invalid/", "")?;
+    assert!(mirror.mirror(&mut request, &config()).is_none());
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn mirror_request() -> Result<()> {
+    let (primary_addr, primary, primary_seen) = serve_one(StatusCode::OK).await?;
+    let (shadow_addr, shadow, shadow_seen) = serve_one(StatusCode::SERVICE_UNAVAILABLE).await?;
+
+    let (tx, mut comparisons) = mpsc::unbounded_channel();
+    let mirror = MirrorConfig::new(&shadow_addr, 100)?.compare(move |comparison| {
+        tx.send(comparison).unwrap();
+    });
+
+    let mut request = request(&format!("http://{primary_addr}/path"), "hello")?;
+    let config = config();
+    let mirrored = mirror.mirror(&mut request, &config).unwrap();
+    let response = mirrored.compare(default_send_request(request, config));
+    let response = match response {
+        HostFutureIncomingResponse::Pending(handle) => handle.await,
+        HostFutureIncomingResponse::Ready(result) => result,
+        HostFutureIncomingResponse::Consumed => unreachable!(),
+    }?
+    .map_err(|e| anyhow::anyhow!("{e:?}"))?;
+    assert_eq!(response.resp.status(), StatusCode::OK);
+    drop(response);
+
+    let comparison = comparisons.recv().await.unwrap();
+    assert_eq!(comparison.method, hyper::Method::POST);
+    assert_eq!(comparison.uri.path(), "/path");
+    assert_eq!(comparison.primary.unwrap(), StatusCode::OK);
+    assert_eq!(comparison.shadow.unwrap(), StatusCode::SERVICE_UNAVAILABLE);
+
+    primary.await??;
+    shadow.await??;
+    let (path, host, body) = primary_seen.lock().unwrap().take().unwrap();
+    assert_eq!((path.as_str(), &body[..]), ("/path", &b"hello"[..]));
+    assert_eq!(host, primary_addr);
+    let (path, host, body) = shadow_seen.lock().unwrap().take().unwrap();
+    assert_eq!((path.as_str(), &body[..]), ("/path", &b"hello"[..]));
+    assert_eq!(host, shadow_addr);
+    Ok(())
+}
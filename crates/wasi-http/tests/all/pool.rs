@@ -0,0 +1,170 @@
+use anyhow::Result;
+use http_body_util::{BodyExt, Empty, Full};
+use hyper::{body::Bytes, server::conn::http1, service::service_fn};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use wasmtime_wasi_http::{
+    io::TokioIo,
+    types::{default_send_request_handler, OutgoingRequestConfig},
+    ConnectionPool, HttpVersionPolicy, Proxy,
+};
+
+/// A keep-alive server, along with the number of connections it has accepted
+/// and the tasks serving them, aborting which closes the connections.
+struct TestServer {
+    addr: SocketAddr,
+    connections: Arc<AtomicUsize>,
+    tasks: Arc<Mutex<Vec<JoinHandle<hyper::Result<()>>>>>,
+}
+
+/// Starts a keep-alive server which responds to any request.
+async fn server() -> Result<TestServer> {
+    let listener = TcpListener::bind((Ipv4Addr::new(127, 0, 0, 1), 0)).await?;
+    let addr = listener.local_addr()?;
+    let connections = Arc::new(AtomicUsize::new(0));
+    let tasks = Arc::new(Mutex::new(Vec::new()));
+    let accepted = connections.clone();
+    let serving = tasks.clone();
+    tokio::task::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            accepted.fetch_add(1, Ordering::SeqCst);
+            let task = tokio::task::spawn(async move {
+                http1::Builder::new()
+                    .keep_alive(true)
+                    .serve_connection(
+                        TokioIo::new(stream),
+                        service_fn(|_request| async {
+                            Ok::<_, hyper::Error>(hyper::Response::new(Full::new(
+                                Bytes::from_static(b"hello"),
+                            )))
+                        }),
+                    )
+                    .await
+            });
+            serving.lock().unwrap().push(task);
+        }
+    });
+    Ok(TestServer {
+        addr,
+        connections,
+        tasks,
+    })
+}
+
+async fn get(addr: SocketAddr, pool: &ConnectionPool) -> Result<()> {
+    send(&format!("http://{addr}/"), pool, None).await
+}
+
+async fn send(uri: &str, pool: &ConnectionPool, proxy: Option<Proxy>) -> Result<()> {
+    let request = hyper::Request::builder()
+        .uri(uri)
+        .body(Empty::<Bytes>::new().map_err(|_| unreachable!()).boxed())?;
+    let config = OutgoingRequestConfig {
+        use_tls: false,
+        connect_timeout: Duration::from_secs(10),
+        first_byte_timeout: Duration::from_secs(10),
+        between_bytes_timeout: Duration::from_secs(10),
+        proxy,
+        pool: Some(pool.clone()),
+        http_versions: HttpVersionPolicy::default(),
+        tasks: crate::test_tasks(),
+    };
+    let response = default_send_request_handler(request, config)
+        .await
+        .map_err(|e| anyhow::anyhow!("{e:?}"))?;
+    let body = response.resp.into_body().collect().await?.to_bytes();
+    assert_eq!(body, "hello");
+    Ok(())
+}
+
+/// Waits for the connection used by a finished request to be returned to
+/// `pool`, which happens in the background.
+async fn wait_for_idle(pool: &ConnectionPool, n: usize) {
+    while pool.idle_connections() != n {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn connections_are_reused() -> Result<()> {
+    let TestServer {
+        addr, connections, ..
+    } = server().await?;
+    let pool = ConnectionPool::new();
+
+    for _ in 0..3 {
+        get(addr, &pool).await?;
+        wait_for_idle(&pool, 1).await;
+    }
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn idle_connections_expire() -> Result<()> {
+    let TestServer {
+        addr, connections, ..
+    } = server().await?;
+    let pool = ConnectionPool::new().idle_timeout(Duration::from_millis(10));
+
+    get(addr, &pool).await?;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(pool.idle_connections(), 0);
+    get(addr, &pool).await?;
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn max_idle_per_origin() -> Result<()> {
+    let TestServer {
+        addr, connections, ..
+    } = server().await?;
+    let pool = ConnectionPool::new().max_idle_per_origin(0);
+
+    get(addr, &pool).await?;
+    get(addr, &pool).await?;
+    assert_eq!(pool.idle_connections(), 0);
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn closed_connections_are_replaced() -> Result<()> {
+    let server = server().await?;
+    let pool = ConnectionPool::new();
+
+    get(server.addr, &pool).await?;
+    wait_for_idle(&pool, 1).await;
+    for task in server.tasks.lock().unwrap().drain(..) {
+        task.abort();
+    }
+    get(server.addr, &pool).await?;
+    assert_eq!(server.connections.load(Ordering::SeqCst), 2);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn proxy_credentials_are_not_shared() -> Result<()> {
+    let TestServer {
+        addr, connections, ..
+    } = server().await?;
+    let pool = ConnectionPool::new();
+    let proxy = |username: &str| -> Result<Proxy> {
+        Ok(Proxy::new(&addr.to_string())?.basic_auth(username, "pass"))
+    };
+
+    send("http://example.invalid/", &pool, Some(proxy("alice")?)).await?;
+    wait_for_idle(&pool, 1).await;
+    send("http://example.invalid/", &pool, Some(proxy("bob")?)).await?;
+    wait_for_idle(&pool, 2).await;
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+
+    send("http://example.invalid/", &pool, Some(proxy("alice")?)).await?;
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use crate::{body, Ctx};
+use anyhow::Result;
+use http_body_util::BodyExt;
+use hyper::service::Service;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use wasmtime::{
+    component::{Component, Linker, ResourceTable},
+    Config, Engine, Store,
+};
+use wasmtime_wasi::{pipe::MemoryOutputPipe, WasiCtxBuilder};
+use wasmtime_wasi_http::{bindings::ProxyPre, WasiHttpCtx};
+
+fn pre() -> Result<ProxyPre<Ctx>> {
+    let mut config = Config::new();
+    config.async_support(true);
+    let engine = Engine::new(&config)?;
+    let component = Component::from_file(&engine, test_programs_artifacts::API_PROXY_COMPONENT)?;
+    let mut linker = Linker::new(&engine);
+    wasmtime_wasi_http::add_to_linker_async(&mut linker)?;
+    ProxyPre::new(linker.instantiate_pre(&component)?)
+}
+
+fn ctx() -> Ctx {
+    let stdout = MemoryOutputPipe::new(4096);
+    let stderr = MemoryOutputPipe::new(4096);
+    let mut builder = WasiCtxBuilder::new();
+    builder.stdout(stdout.clone());
+    builder.stderr(stderr.clone());
+    Ctx {
+        table: ResourceTable::new(),
+        wasi: builder.build(),
+        http: WasiHttpCtx::new(),
+        stdout,
+        stderr,
+        send_request: None,
+        rejected_authority: None,
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn serve_creates_a_store_per_request() -> Result<()> {
+    let stores = Arc::new(AtomicUsize::new(0));
+    let created = stores.clone();
+    let service = wasmtime_wasi_http::serve(pre()?, move |engine| {
+        created.fetch_add(1, Ordering::SeqCst);
+        Ok(Store::new(engine, ctx()))
+    });
+
+    for _ in 0..2 {
+        let request = hyper::Request::builder()
+            .uri("http://example.com:8080/test-path")
+            .body(body::empty())?;
+        let response = service.call(request).await?;
+        let body = response.into_body().collect().await?.to_bytes();
+        assert_eq!(body, "hello, world!");
+    }
+    assert_eq!(stores.load(Ordering::SeqCst), 2);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn serve_store_errors_are_returned() -> Result<()> {
+    let service = wasmtime_wasi_http::serve(pre()?, |_| -> wasmtime::Result<Store<Ctx>> {
+        anyhow::bail!("no stores available")
+    });
+    let request = hyper::Request::builder()
+        .uri("http://example.com:8080/test-path")
+        .body(body::empty())?;
+    let err = service.call(request).await.unwrap_err();
+    assert!(err.to_string().contains("no stores available"), "{err:?}");
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use crate::Ctx;
+use anyhow::Result;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Bytes;
+use std::net::Ipv4Addr;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use wasmtime::component::ResourceTable;
+use wasmtime::{Engine, Store};
+use wasmtime_wasi::pipe::MemoryOutputPipe;
+use wasmtime_wasi::WasiCtxBuilder;
+use wasmtime_wasi_http::{
+    types::{default_send_request, OutgoingRequestConfig},
+    HttpVersionPolicy, MirrorConfig, WasiHttpCtx,
+};
+
+/// Accepts connections without ever responding, and reports each connection
+/// once it's been accepted and once the client has closed it.
+async fn unresponsive_server() -> Result<(String, mpsc::UnboundedReceiver<bool>)> {
+    let listener = TcpListener::bind((Ipv4Addr::new(127, 0, 0, 1), 0)).await?;
+    let addr = listener.local_addr()?.to_string();
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::task::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let tx = tx.clone();
+            let _ = tx.send(true);
+            tokio::task::spawn(async move {
+                let mut buf = [0; 1024];
+                while matches!(stream.read(&mut buf).await, Ok(n) if n > 0) {}
+                let _ = tx.send(false);
+            });
+        }
+    });
+    Ok((addr, rx))
+}
+
+#[test_log::test(tokio::test)]
+async fn requests_cancelled_with_store() -> Result<()> {
+    let (addr, mut events) = unresponsive_server().await?;
+
+    let stdout = MemoryOutputPipe::new(4096);
+    let stderr = MemoryOutputPipe::new(4096);
+    let mut store = Store::new(
+        &Engine::default(),
+        Ctx {
+            table: ResourceTable::new(),
+            wasi: WasiCtxBuilder::new().build(),
+            http: WasiHttpCtx::new(),
+            stdout,
+            stderr,
+            send_request: None,
+            rejected_authority: None,
+        },
+    );
+
+    let mut request = hyper::Request::builder()
+        .uri(format!("http://{addr}/"))
+        .body(
+            Full::new(Bytes::from("hello"))
+                .map_err(|_| unreachable!())
+                .boxed(),
+        )?;
+    let config = OutgoingRequestConfig {
+        use_tls: false,
+        connect_timeout: Duration::from_secs(10),
+        first_byte_timeout: Duration::from_secs(60),
+        between_bytes_timeout: Duration::from_secs(60),
+        proxy: None,
+        pool: None,
+        http_versions: HttpVersionPolicy::default(),
+        tasks: store.data().wasi.tasks().clone(),
+    };
+
+    // The shadow request of a mirrored request isn't owned by any resource,
+    // so only the store's supervisor can cancel it.
+    let mirrored = MirrorConfig::new(&addr, 100)?.mirror(&mut request, &config);
+    assert!(mirrored.is_some());
+    let response = default_send_request(request, config);
+    store.data_mut().table.push(response)?;
+
+    // Wait for both requests to be sent.
+    assert_eq!(events.recv().await, Some(true));
+    assert_eq!(events.recv().await, Some(true));
+    let names = store
+        .data()
+        .wasi
+        .tasks()
+        .tasks()
+        .iter()
+        .map(|t| t.name())
+        .collect::<Vec<_>>();
+    assert!(names.contains(&"wasi-http-mirror"), "{names:?}");
+    assert!(names.contains(&"wasi-http-outgoing-request"), "{names:?}");
+
+    drop(store);
+    for _ in 0..2 {
+        let closed = tokio::time::timeout(Duration::from_secs(10), events.recv()).await?;
+        assert_eq!(closed, Some(false));
+    }
+    Ok(())
+}
@@ -21,7 +21,7 @@ use self::pytorch::PytorchBackend;
 use self::winml::WinMLBackend;
 
 use crate::wit::{ExecutionTarget, GraphEncoding, Tensor};
-use crate::{Backend, ExecutionContext, Graph};
+use crate::{Backend, Device, ExecutionContext, Graph};
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
@@ -56,6 +56,9 @@ pub trait BackendInner: Send + Sync {
     fn encoding(&self) -> GraphEncoding;
     fn load(&mut self, builders: &[&[u8]], target: ExecutionTarget) -> Result<Graph, BackendError>;
     fn as_dir_loadable<'a>(&'a mut self) -> Option<&'a mut dyn BackendFromDir>;
+
+    /// The devices this backend can currently load graphs on.
+    fn devices(&self) -> Result<Vec<Device>, BackendError>;
 }
 
 /// Some [Backend]s support loading a [Graph] from a directory on the
@@ -84,7 +87,7 @@ pub trait BackendExecutionContext: Send + Sync {
 }
 
 /// An identifier for a tensor in a [Graph].
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Id {
     Index(u32),
     Name(String),
@@ -110,6 +113,8 @@ impl Id {
 pub enum BackendError {
     #[error("Failed while accessing backend")]
     BackendAccess(#[from] anyhow::Error),
+    #[error("Failed to decrypt graph builders")]
+    ModelDecryption(#[source] anyhow::Error),
     #[error("Failed while accessing guest module")]
     GuestAccess(#[from] GuestError),
     #[error("The backend expects {0} buffers, passed {1}")]
@@ -118,6 +123,8 @@ pub enum BackendError {
     NotEnoughMemory(usize),
     #[error("Unsupported tensor type: {0}")]
     UnsupportedTensorType(String),
+    #[error("No device available for execution target: {0:?}")]
+    UnsupportedExecutionTarget(ExecutionTarget),
 }
 
 /// Read a file into a byte vector.
@@ -3,7 +3,7 @@
 use super::{BackendError, BackendExecutionContext, BackendFromDir, BackendGraph, BackendInner};
 use crate::backend::{read, Id};
 use crate::wit::types::{ExecutionTarget, GraphEncoding, Tensor, TensorType};
-use crate::{ExecutionContext, Graph};
+use crate::{Device, ExecutionContext, Graph};
 use anyhow::Context;
 use ort::{inputs, GraphOptimizationLevel, Session};
 use std::path::Path;
@@ -36,6 +36,12 @@ impl BackendInner for OnnxBackend {
     fn as_dir_loadable<'a>(&'a mut self) -> Option<&'a mut dyn BackendFromDir> {
         Some(self)
     }
+
+    fn devices(&self) -> Result<Vec<Device>, BackendError> {
+        // Sessions are built without any execution providers, so `ort` always
+        // runs them with its CPU provider.
+        Ok(vec![Device::new("CPU", ExecutionTarget::Cpu)])
+    }
 }
 
 impl BackendFromDir for OnnxBackend {
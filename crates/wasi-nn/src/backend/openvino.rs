@@ -4,7 +4,7 @@ use super::{
     read, BackendError, BackendExecutionContext, BackendFromDir, BackendGraph, BackendInner, Id,
 };
 use crate::wit::{ExecutionTarget, GraphEncoding, Tensor, TensorType};
-use crate::{ExecutionContext, Graph};
+use crate::{Device, ExecutionContext, Graph};
 use openvino::{DeviceType, ElementType, InferenceError, SetupError, Shape, Tensor as OvTensor};
 use std::path::Path;
 use std::sync::{Arc, Mutex};
@@ -56,6 +56,31 @@ impl BackendInner for OpenvinoBackend {
     fn as_dir_loadable(&mut self) -> Option<&mut dyn BackendFromDir> {
         Some(self)
     }
+
+    fn devices(&self) -> Result<Vec<Device>, BackendError> {
+        // Ask OpenVINO which of its plugins found a device; a temporary core
+        // is used if no graph has been loaded yet.
+        let devices = match &self.0 {
+            Some(core) => core.available_devices()?,
+            None => openvino::Core::new()?.available_devices()?,
+        };
+        Ok(devices
+            .into_iter()
+            .filter_map(|device| {
+                // Multiple GPUs are named `GPU.0`, `GPU.1`, etc.; loading
+                // graphs on a GPU picks OpenVINO's default one.
+                let name = device.to_string();
+                let target = if name == "CPU" {
+                    ExecutionTarget::Cpu
+                } else if name == "GPU" || name.starts_with("GPU.") {
+                    ExecutionTarget::Gpu
+                } else {
+                    return None;
+                };
+                Some(Device::new(name, target))
+            })
+            .collect())
+    }
 }
 
 impl BackendFromDir for OpenvinoBackend {
@@ -45,6 +45,14 @@ impl BackendInner for PytorchBackend {
     fn as_dir_loadable<'a>(&'a mut self) -> Option<&'a mut dyn BackendFromDir> {
         Some(self)
     }
+
+    fn devices(&self) -> Result<Vec<crate::Device>, BackendError> {
+        let mut devices = vec![crate::Device::new("CPU", ExecutionTarget::Cpu)];
+        if tch::Cuda::is_available() {
+            devices.push(crate::Device::new("CUDA", ExecutionTarget::Gpu));
+        }
+        Ok(devices)
+    }
 }
 
 impl BackendFromDir for PytorchBackend {
@@ -181,9 +189,7 @@ impl BackendExecutionContext for PytorchExecutionContext {
 fn map_execution_target_to_string(target: ExecutionTarget) -> Device {
     match target {
         ExecutionTarget::Cpu => Device::Cpu,
-        ExecutionTarget::Gpu => {
-            unimplemented!("the pytorch backend does not yet support GPU execution targets")
-        }
+        ExecutionTarget::Gpu => Device::Cuda(0),
         ExecutionTarget::Tpu => {
             unimplemented!("the pytorch backend does not yet support TPU execution targets")
         }
@@ -11,7 +11,7 @@ use crate::backend::{
     BackendError, BackendExecutionContext, BackendFromDir, BackendGraph, BackendInner, Id,
 };
 use crate::wit::{ExecutionTarget, GraphEncoding, Tensor, TensorType};
-use crate::{ExecutionContext, Graph};
+use crate::{Device, ExecutionContext, Graph};
 use std::{fs::File, io::Read, mem::size_of, path::Path};
 use windows::core::{ComInterface, Error, IInspectable, HSTRING};
 use windows::Foundation::Collections::IVectorView;
@@ -59,6 +59,15 @@ impl BackendInner for WinMLBackend {
     fn as_dir_loadable(&mut self) -> Option<&mut dyn BackendFromDir> {
         Some(self)
     }
+
+    fn devices(&self) -> Result<Vec<Device>, BackendError> {
+        let mut devices = vec![Device::new("CPU", ExecutionTarget::Cpu)];
+        // WinML fails to create a DirectX device if there's no suitable GPU.
+        if LearningModelDevice::Create(LearningModelDeviceKind::DirectX).is_ok() {
+            devices.push(Device::new("DirectX", ExecutionTarget::Gpu));
+        }
+        Ok(devices)
+    }
 }
 
 impl BackendFromDir for WinMLBackend {
@@ -0,0 +1,517 @@
+//! Automatically batch inference requests made against the same graph.
+//!
+//! When many component instances run the same model, each `compute` call
+//! normally results in a separate backend invocation. A [`Batcher`] instead
+//! holds `compute` calls open for a short, configurable window, stacks the
+//! inputs of all compatible requests along their first (batch) dimension and
+//! runs a single backend computation. Each caller then retrieves the slice of
+//! the outputs that corresponds to its own inputs.
+//!
+//! Guests opt into this simply by calling `compute`: a guest wanting to
+//! enqueue several inputs at once sets tensors whose leading dimension is
+//! greater than one, and the host coalesces those rows with the rows of any
+//! other request arriving within the window.
+
+use crate::backend::{BackendError, BackendExecutionContext, Id};
+use crate::{ExecutionContext, Graph, Tensor};
+use anyhow::anyhow;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::time::Duration;
+
+/// Configure how a [`Batcher`] coalesces requests.
+#[derive(Clone, Copy, Debug)]
+pub struct BatchConfig {
+    /// How long the first request of a batch waits for others to join it.
+    pub window: Duration,
+    /// The maximum number of requests coalesced into a single backend call;
+    /// once reached, the batch is computed without waiting for the window to
+    /// elapse.
+    pub max_batch_size: usize,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_millis(5),
+            max_batch_size: 32,
+        }
+    }
+}
+
+/// Coalesces `compute` calls into batched backend calls.
+///
+/// A `Batcher` is meant to be shared, through an `Arc`, by the `WasiNnCtx` of
+/// every store that should participate in batching. Only requests against the
+/// same [`Graph`] (i.e., clones of the same loaded graph, such as those handed
+/// out by a shared registry) whose inputs have identical names, types and
+/// trailing dimensions are coalesced; all other requests are computed in
+/// separate batches.
+pub struct Batcher {
+    config: BatchConfig,
+    open: Mutex<HashMap<BatchKey, Arc<Batch>>>,
+}
+
+/// Batches are keyed by the identity of the graph and the signature of the
+/// inputs (see [`signature`]).
+type BatchKey = (usize, String);
+
+#[derive(Default)]
+struct Batch {
+    state: Mutex<BatchState>,
+    cond: Condvar,
+}
+
+#[derive(Default)]
+struct BatchState {
+    requests: Vec<Vec<(Id, Tensor)>>,
+    closed: bool,
+    result: Option<Result<Arc<BatchOutput>, String>>,
+}
+
+impl Batcher {
+    /// Create a new batcher.
+    pub fn new(config: BatchConfig) -> Self {
+        Self {
+            config,
+            open: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Retrieve the configuration of this batcher.
+    pub fn config(&self) -> &BatchConfig {
+        &self.config
+    }
+
+    /// Compute `inputs` against `graph`, possibly as part of a larger batch.
+    ///
+    /// This blocks until the batch this request joined has been computed. The
+    /// first request of a batch waits at most for the configured window.
+    fn submit(
+        &self,
+        graph: &Graph,
+        inputs: Vec<(Id, Tensor)>,
+    ) -> Result<(Arc<BatchOutput>, usize), BackendError> {
+        let Some(signature) = signature(&inputs) else {
+            tracing::debug!("inputs cannot be batched; computing immediately");
+            return Ok((Arc::new(run(graph, vec![inputs])?), 0));
+        };
+        let key = (Arc::as_ptr(&graph.0) as *const () as usize, signature);
+
+        // Join the open batch for this key, if any, or open a new one. Batches
+        // are only ever closed while holding the `open` lock so a batch found
+        // below cannot be closed before this request is added to it.
+        let (batch, index) = {
+            let mut open = self.open.lock().unwrap();
+            let batch = match open.get(&key) {
+                Some(batch) if !batch.state.lock().unwrap().closed => batch.clone(),
+                _ => {
+                    let batch = Arc::new(Batch::default());
+                    open.insert(key.clone(), batch.clone());
+                    batch
+                }
+            };
+            let mut state = batch.state.lock().unwrap();
+            let index = state.requests.len();
+            state.requests.push(inputs);
+            if state.requests.len() >= self.config.max_batch_size {
+                state.closed = true;
+                open.remove(&key);
+                batch.cond.notify_all();
+            }
+            drop(state);
+            (batch, index)
+        };
+
+        if index != 0 {
+            // Followers wait for the leader to compute the batch.
+            let state = batch.state.lock().unwrap_or_else(PoisonError::into_inner);
+            let state = batch
+                .cond
+                .wait_while(state, |s| s.result.is_none())
+                .unwrap_or_else(PoisonError::into_inner);
+            return match state.result.as_ref().unwrap() {
+                Ok(output) => Ok((output.clone(), index)),
+                Err(msg) => Err(anyhow!("batched computation failed: {msg}").into()),
+            };
+        }
+
+        // The leader waits for the window to elapse (or the batch to fill up),
+        // closes the batch and computes it on behalf of all its members. If
+        // the leader unwinds before publishing a result, e.g. because the
+        // backend panicked, the guard fails the batch so that its followers
+        // don't wait forever.
+        let guard = LeaderGuard(&batch);
+        let state = batch.state.lock().unwrap();
+        let (state, _) = batch
+            .cond
+            .wait_timeout_while(state, self.config.window, |s| !s.closed)
+            .unwrap();
+        drop(state);
+        let requests = {
+            let mut open = self.open.lock().unwrap();
+            let mut state = batch.state.lock().unwrap();
+            if !state.closed {
+                state.closed = true;
+                if open.get(&key).is_some_and(|b| Arc::ptr_eq(b, &batch)) {
+                    open.remove(&key);
+                }
+            }
+            std::mem::take(&mut state.requests)
+        };
+        tracing::debug!("computing a batch of {} requests", requests.len());
+        let result = run(graph, requests).map(Arc::new);
+        batch.finish(match &result {
+            Ok(output) => Ok(output.clone()),
+            Err(e) => Err(format!("{e:?}")),
+        });
+        drop(guard);
+        Ok((result?, 0))
+    }
+}
+
+impl Batch {
+    /// Publish the result of this batch to its followers, unless one was
+    /// published already.
+    fn finish(&self, result: Result<Arc<BatchOutput>, String>) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if state.result.is_none() {
+            state.result = Some(result);
+        }
+        self.cond.notify_all();
+    }
+}
+
+/// Fails a batch whose leader unwinds without computing it.
+struct LeaderGuard<'a>(&'a Batch);
+
+impl Drop for LeaderGuard<'_> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.0
+                .finish(Err("the request computing the batch panicked".to_string()));
+        }
+    }
+}
+
+/// Describe the inputs of a request such that two requests with the same
+/// signature can be stacked along their first dimension; returns `None` if the
+/// inputs cannot be batched at all.
+///
+/// Inputs can only be batched if every one of them has the same, non-zero
+/// number of rows in its first dimension and its data splits evenly into
+/// those rows.
+///
+/// This expects `inputs` to be sorted by [`Id`].
+fn signature(inputs: &[(Id, Tensor)]) -> Option<String> {
+    let rows = rows(inputs)?;
+    let mut signature = String::new();
+    for (id, tensor) in inputs {
+        let (first, rest) = tensor.dimensions.split_first()?;
+        if *first != rows || tensor.data.len() % rows as usize != 0 {
+            return None;
+        }
+        write!(signature, "{id:?}:{:?}:{rest:?};", tensor.ty).unwrap();
+    }
+    Some(signature)
+}
+
+/// The number of rows of a request, i.e., the first dimension of its inputs.
+fn rows(inputs: &[(Id, Tensor)]) -> Option<u32> {
+    let (_, tensor) = inputs.first()?;
+    tensor.dimensions.first().copied().filter(|rows| *rows > 0)
+}
+
+/// Stack the inputs of all `requests` and compute them in a single execution
+/// context.
+fn run(graph: &Graph, requests: Vec<Vec<(Id, Tensor)>>) -> Result<BatchOutput, BackendError> {
+    let mut context = graph.init_execution_context()?;
+    let mut ranges = None;
+    let batched = requests.len() > 1;
+    let mut requests = requests.into_iter();
+    let mut merged = requests.next().unwrap_or_default();
+    if batched {
+        let mut start = 0;
+        let mut rows = vec![];
+        // All inputs of a request have the same number of rows, which
+        // `signature` checked before the request joined the batch.
+        let mut record = |inputs: &[(Id, Tensor)]| {
+            let len = self::rows(inputs).unwrap();
+            rows.push((start, len));
+            start += len;
+        };
+        record(&merged);
+        for request in requests {
+            record(&request);
+            for ((_, into), (_, tensor)) in merged.iter_mut().zip(request) {
+                into.dimensions[0] += tensor.dimensions[0];
+                into.data.extend_from_slice(&tensor.data);
+            }
+        }
+        ranges = Some(rows);
+    }
+    for (id, tensor) in merged {
+        context.set_input(id, &tensor)?;
+    }
+    context.compute()?;
+    Ok(BatchOutput {
+        context: Mutex::new(context),
+        ranges,
+        outputs: Mutex::new(HashMap::new()),
+    })
+}
+
+/// The result of a batched computation, shared by all members of the batch.
+struct BatchOutput {
+    context: Mutex<ExecutionContext>,
+    /// The `(start, len)` rows belonging to each request; `None` if the batch
+    /// only contained a single request and no slicing is necessary.
+    ranges: Option<Vec<(u32, u32)>>,
+    /// Outputs retrieved so far, cached so that each output is copied out of
+    /// the backend only once for the whole batch.
+    outputs: Mutex<HashMap<Id, Tensor>>,
+}
+
+impl BatchOutput {
+    /// Retrieve the slice of output `id` belonging to request `index`.
+    fn get(&self, index: usize, id: Id) -> Result<Tensor, BackendError> {
+        let Some(ranges) = &self.ranges else {
+            return self.context.lock().unwrap().get_output(id);
+        };
+        let mut outputs = self.outputs.lock().unwrap();
+        if !outputs.contains_key(&id) {
+            let tensor = self.context.lock().unwrap().get_output(id.clone())?;
+            outputs.insert(id.clone(), tensor);
+        }
+        let tensor = &outputs[&id];
+        let total = ranges.iter().map(|(_, len)| len).sum::<u32>();
+        if tensor.dimensions.first() != Some(&total) || tensor.data.len() % total as usize != 0 {
+            return Err(anyhow!(
+                "batched output {id:?} has dimensions {:?}; expected a leading batch dimension of {total}",
+                tensor.dimensions
+            )
+            .into());
+        }
+        let row_bytes = tensor.data.len() / total as usize;
+        let (start, len) = ranges[index];
+        let mut dimensions = tensor.dimensions.clone();
+        dimensions[0] = len;
+        let data =
+            tensor.data[start as usize * row_bytes..(start + len) as usize * row_bytes].to_vec();
+        Ok(Tensor {
+            dimensions,
+            ty: tensor.ty,
+            data,
+        })
+    }
+}
+
+/// An execution context which defers computation to a [`Batcher`].
+struct BatchedExecutionContext {
+    graph: Graph,
+    batcher: Arc<Batcher>,
+    inputs: Vec<(Id, Tensor)>,
+    output: Option<(Arc<BatchOutput>, usize)>,
+}
+
+impl BackendExecutionContext for BatchedExecutionContext {
+    fn set_input(&mut self, id: Id, tensor: &Tensor) -> Result<(), BackendError> {
+        self.inputs.retain(|(i, _)| *i != id);
+        self.inputs.push((id, tensor.clone()));
+        self.inputs.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(())
+    }
+
+    fn compute(&mut self) -> Result<(), BackendError> {
+        self.output = None;
+        let output = self.batcher.submit(&self.graph, self.inputs.clone())?;
+        self.output = Some(output);
+        Ok(())
+    }
+
+    fn get_output(&mut self, id: Id) -> Result<Tensor, BackendError> {
+        match &self.output {
+            Some((output, index)) => output.get(*index, id),
+            None => Err(anyhow!("no outputs available; has `compute` been called?").into()),
+        }
+    }
+}
+
+/// Create an execution context for `graph`, routing its computations through
+/// `batcher` if one is configured.
+pub(crate) fn init_execution_context(
+    graph: &Graph,
+    batcher: Option<&Arc<Batcher>>,
+) -> Result<ExecutionContext, BackendError> {
+    match batcher {
+        Some(batcher) => {
+            let context: Box<dyn BackendExecutionContext> = Box::new(BatchedExecutionContext {
+                graph: graph.clone(),
+                batcher: batcher.clone(),
+                inputs: vec![],
+                output: None,
+            });
+            Ok(context.into())
+        }
+        None => graph.init_execution_context(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::BackendGraph;
+    use crate::wit::TensorType;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    /// A graph which negates its single `u8` input and counts computations.
+    struct NegateGraph(Arc<AtomicUsize>);
+    struct NegateContext(Arc<AtomicUsize>, Option<Tensor>);
+
+    impl BackendGraph for NegateGraph {
+        fn init_execution_context(&self) -> Result<ExecutionContext, BackendError> {
+            let context: Box<dyn BackendExecutionContext> =
+                Box::new(NegateContext(self.0.clone(), None));
+            Ok(context.into())
+        }
+    }
+
+    impl BackendExecutionContext for NegateContext {
+        fn set_input(&mut self, _: Id, tensor: &Tensor) -> Result<(), BackendError> {
+            self.1 = Some(tensor.clone());
+            Ok(())
+        }
+        fn compute(&mut self) -> Result<(), BackendError> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            let tensor = self.1.as_mut().unwrap();
+            tensor.data.iter_mut().for_each(|b| *b = !*b);
+            Ok(())
+        }
+        fn get_output(&mut self, _: Id) -> Result<Tensor, BackendError> {
+            Ok(self.1.clone().unwrap())
+        }
+    }
+
+    /// A graph whose computations panic.
+    struct PanicGraph;
+    struct PanicContext;
+
+    impl BackendGraph for PanicGraph {
+        fn init_execution_context(&self) -> Result<ExecutionContext, BackendError> {
+            let context: Box<dyn BackendExecutionContext> = Box::new(PanicContext);
+            Ok(context.into())
+        }
+    }
+
+    impl BackendExecutionContext for PanicContext {
+        fn set_input(&mut self, _: Id, _: &Tensor) -> Result<(), BackendError> {
+            Ok(())
+        }
+        fn compute(&mut self) -> Result<(), BackendError> {
+            panic!("backend failure");
+        }
+        fn get_output(&mut self, _: Id) -> Result<Tensor, BackendError> {
+            unreachable!()
+        }
+    }
+
+    fn tensor(rows: u32, data: &[u8]) -> Tensor {
+        Tensor {
+            dimensions: vec![rows, data.len() as u32 / rows],
+            ty: TensorType::U8,
+            data: data.to_vec(),
+        }
+    }
+
+    #[test]
+    fn coalesces_requests() {
+        let computations = Arc::new(AtomicUsize::new(0));
+        let graph: Box<dyn BackendGraph> = Box::new(NegateGraph(computations.clone()));
+        let graph = Graph::from(graph);
+        let batcher = Arc::new(Batcher::new(BatchConfig {
+            window: Duration::from_secs(60),
+            max_batch_size: 2,
+        }));
+
+        let handles = [tensor(1, &[1, 2]), tensor(2, &[3, 4, 5, 6])].map(|input| {
+            let graph = graph.clone();
+            let batcher = batcher.clone();
+            thread::spawn(move || {
+                let mut context = init_execution_context(&graph, Some(&batcher)).unwrap();
+                context.set_input(Id::Index(0), &input).unwrap();
+                context.compute().unwrap();
+                (input, context.get_output(Id::Index(0)).unwrap())
+            })
+        });
+        for handle in handles {
+            let (input, output) = handle.join().unwrap();
+            assert_eq!(output.dimensions, input.dimensions);
+            let expected = input.data.iter().map(|b| !b).collect::<Vec<_>>();
+            assert_eq!(output.data, expected);
+        }
+        assert_eq!(computations.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn incompatible_requests_are_not_coalesced() {
+        let computations = Arc::new(AtomicUsize::new(0));
+        let graph: Box<dyn BackendGraph> = Box::new(NegateGraph(computations.clone()));
+        let graph = Graph::from(graph);
+        let batcher = Arc::new(Batcher::new(BatchConfig {
+            window: Duration::from_millis(1),
+            max_batch_size: 2,
+        }));
+
+        for input in [tensor(1, &[1, 2]), tensor(1, &[3, 4, 5])] {
+            let mut context = init_execution_context(&graph, Some(&batcher)).unwrap();
+            context.set_input(Id::Index(0), &input).unwrap();
+            context.compute().unwrap();
+            let output = context.get_output(Id::Index(0)).unwrap();
+            assert_eq!(output.dimensions, input.dimensions);
+        }
+        assert_eq!(computations.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn leader_panic_fails_followers() {
+        let graph: Box<dyn BackendGraph> = Box::new(PanicGraph);
+        let graph = Graph::from(graph);
+        let batcher = Arc::new(Batcher::new(BatchConfig {
+            window: Duration::from_secs(60),
+            max_batch_size: 2,
+        }));
+
+        let handles = [0, 1].map(|_| {
+            let graph = graph.clone();
+            let batcher = batcher.clone();
+            thread::spawn(move || {
+                let mut context = init_execution_context(&graph, Some(&batcher)).unwrap();
+                context.set_input(Id::Index(0), &tensor(1, &[1])).unwrap();
+                context.compute().is_ok()
+            })
+        });
+        let results = handles.map(|handle| handle.join());
+
+        // The leader panics and its follower is told that the batch failed
+        // instead of waiting for it forever.
+        assert_eq!(results.iter().filter(|r| r.is_err()).count(), 1);
+        assert!(results.iter().any(|r| matches!(r, Ok(false))));
+    }
+
+    #[test]
+    fn only_inputs_with_the_same_rows_are_batched() {
+        let inputs = |tensors: [Tensor; 2]| {
+            let [a, b] = tensors;
+            vec![(Id::Index(0), a), (Id::Index(1), b)]
+        };
+        assert!(signature(&inputs([tensor(2, &[1, 2]), tensor(2, &[3, 4, 5, 6])])).is_some());
+        assert!(signature(&inputs([tensor(1, &[1, 2]), tensor(2, &[3, 4])])).is_none());
+
+        // The data of each input must split evenly into its rows.
+        let mut uneven = tensor(2, &[1, 2]);
+        uneven.data.push(3);
+        assert!(signature(&inputs([tensor(2, &[1, 2]), uneven])).is_none());
+    }
+}
@@ -0,0 +1,116 @@
+//! Decrypt graph builders before they are handed to a backend.
+//!
+//! Embedders that ship encrypted model weights can register a
+//! [`ModelDecryptor`] on a `WasiNnCtx`; every guest-initiated `load` then
+//! passes its graph builders through the decryptor so that plaintext weights
+//! only ever exist in host memory, immediately before the backend sees them.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+
+/// The signature of a decryption callback: it receives the opaque key
+/// identifier configured on the [`ModelDecryptor`] and the encrypted bytes of
+/// a single graph builder, returning the decrypted bytes.
+pub type DecryptFn = dyn Fn(&str, &[u8]) -> anyhow::Result<Vec<u8>> + Send + Sync;
+
+/// A host hook for decrypting model bytes just before backend load.
+///
+/// The key identifier is opaque to this crate; it is only passed back to the
+/// callback so that the embedder can look up the actual key material (e.g., in
+/// a KMS or a hardware enclave).
+#[derive(Clone)]
+pub struct ModelDecryptor {
+    key_id: String,
+    decrypt: Arc<DecryptFn>,
+}
+
+impl ModelDecryptor {
+    /// Create a new decryptor using `key_id` to identify the key material to
+    /// the `decrypt` callback.
+    pub fn new(
+        key_id: impl Into<String>,
+        decrypt: impl Fn(&str, &[u8]) -> anyhow::Result<Vec<u8>> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            key_id: key_id.into(),
+            decrypt: Arc::new(decrypt),
+        }
+    }
+
+    /// The opaque key identifier passed to the decryption callback.
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Decrypt each of the graph `builders`, in order.
+    pub fn decrypt(&self, builders: &[&[u8]]) -> anyhow::Result<Vec<Vec<u8>>> {
+        builders
+            .iter()
+            .map(|b| (self.decrypt)(&self.key_id, b))
+            .collect()
+    }
+}
+
+impl fmt::Debug for ModelDecryptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ModelDecryptor")
+            .field("key_id", &self.key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Decrypt the graph `builders` if a `decryptor` is present; otherwise, borrow
+/// the builders as-is.
+pub(crate) fn decrypt_builders<'a>(
+    decryptor: Option<&ModelDecryptor>,
+    builders: &[&'a [u8]],
+) -> anyhow::Result<Vec<Cow<'a, [u8]>>> {
+    match decryptor {
+        Some(decryptor) => {
+            tracing::debug!("decrypting graph builders with key {:?}", decryptor.key_id);
+            Ok(decryptor
+                .decrypt(builders)?
+                .into_iter()
+                .map(Cow::Owned)
+                .collect())
+        }
+        None => Ok(builders.iter().map(|b| Cow::Borrowed(*b)).collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passthrough_without_decryptor() {
+        let builders: &[&[u8]] = &[b"xml", b"weights"];
+        let out = decrypt_builders(None, builders).unwrap();
+        assert!(out.iter().all(|b| matches!(b, Cow::Borrowed(_))));
+        assert_eq!(out[1].as_ref(), b"weights");
+    }
+
+    #[test]
+    fn decrypts_every_builder() {
+        let decryptor = ModelDecryptor::new("key-1", |key_id, bytes| {
+            assert_eq!(key_id, "key-1");
+            Ok(bytes.iter().map(|b| b ^ 0xff).collect())
+        });
+        let encrypted = [b'a' ^ 0xff, b'b' ^ 0xff];
+        let builders: &[&[u8]] = &[&encrypted, &encrypted[..1]];
+        let out = decrypt_builders(Some(&decryptor), builders).unwrap();
+        assert_eq!(out[0].as_ref(), b"ab");
+        assert_eq!(out[1].as_ref(), b"a");
+    }
+
+    #[test]
+    fn propagates_errors() {
+        let decryptor = ModelDecryptor::new("missing", |key_id, _| {
+            anyhow::bail!("unknown key: {key_id}")
+        });
+        let builders: &[&[u8]] = &[b"data"];
+        let err = decrypt_builders(Some(&decryptor), builders).unwrap_err();
+        assert_eq!(err.to_string(), "unknown key: missing");
+    }
+}
@@ -1,13 +1,20 @@
 pub mod backend;
+mod batch;
+mod decrypt;
 mod registry;
+mod stream;
+mod target;
 pub mod wit;
 pub mod witx;
 
 use anyhow::anyhow;
+pub use batch::{BatchConfig, Batcher};
 use core::fmt;
+pub use decrypt::{DecryptFn, ModelDecryptor};
 pub use registry::{GraphRegistry, InMemoryRegistry};
 use std::path::Path;
 use std::sync::Arc;
+pub use target::{Device, TargetRegistry};
 
 /// Construct an in-memory registry from the available backends and a list of
 /// `(<backend name>, <graph directory>)`. This assumes graphs can be loaded
@@ -1,9 +1,8 @@
 //! Implement a [`GraphRegistry`] with a hash map.
 
-use super::{Graph, GraphRegistry};
+use super::{load_dir, Graph, GraphRegistry};
 use crate::backend::BackendFromDir;
 use crate::wit::ExecutionTarget;
-use anyhow::{anyhow, bail};
 use std::{collections::HashMap, path::Path};
 
 pub struct InMemoryRegistry(HashMap<String, Graph>);
@@ -19,19 +18,19 @@ impl InMemoryRegistry {
     /// suffix: if the backend can find the files it expects in `/my/model/foo`,
     /// the registry will contain a new graph named `foo`.
     pub fn load(&mut self, backend: &mut dyn BackendFromDir, path: &Path) -> anyhow::Result<()> {
-        if !path.is_dir() {
-            bail!(
-                "preload directory is not a valid directory: {}",
-                path.display()
-            );
-        }
-        let name = path
-            .file_name()
-            .map(|s| s.to_string_lossy())
-            .ok_or(anyhow!("no file name in path"))?;
+        self.load_with_target(backend, path, ExecutionTarget::Cpu)
+    }
 
-        let graph = backend.load_from_dir(path, ExecutionTarget::Cpu)?;
-        self.0.insert(name.into_owned(), graph);
+    /// Like [`InMemoryRegistry::load`], but pins the graph to `target` rather
+    /// than the CPU.
+    pub fn load_with_target(
+        &mut self,
+        backend: &mut dyn BackendFromDir,
+        path: &Path,
+        target: ExecutionTarget,
+    ) -> anyhow::Result<()> {
+        let (name, graph) = load_dir(backend, path, target)?;
+        self.0.insert(name, graph);
         Ok(())
     }
 }
@@ -8,10 +8,165 @@
 
 mod in_memory;
 
-use crate::Graph;
+use crate::backend::BackendFromDir;
+use crate::wit::{ExecutionTarget, GraphEncoding};
+use crate::{Backend, Graph, TargetRegistry};
+use anyhow::{anyhow, bail};
+use std::collections::HashMap;
+use std::path::Path;
+
 pub use in_memory::InMemoryRegistry;
 
 pub trait GraphRegistry: Send + Sync {
     fn get(&self, name: &str) -> Option<&Graph>;
     fn get_mut(&mut self, name: &str) -> Option<&mut Graph>;
 }
+
+/// Load a graph from the files contained in the `path` directory on `target`,
+/// returning it along with its name: the directory's last suffix.
+pub(crate) fn load_dir(
+    backend: &mut dyn BackendFromDir,
+    path: &Path,
+    target: ExecutionTarget,
+) -> anyhow::Result<(String, Graph)> {
+    if !path.is_dir() {
+        bail!(
+            "preload directory is not a valid directory: {}",
+            path.display()
+        );
+    }
+    let name = path
+        .file_name()
+        .map(|s| s.to_string_lossy())
+        .ok_or(anyhow!("no file name in path"))?;
+
+    if !backend.devices()?.iter().any(|d| d.target() == target) {
+        bail!("{} has no device for {target:?}", backend.encoding());
+    }
+    let graph = backend.load_from_dir(path, target)?;
+    Ok((name.into_owned(), graph))
+}
+
+/// Load a graph from the `path` directory with the backend for `encoding`,
+/// on the target pinned in `targets` or otherwise the CPU.
+pub(crate) fn preload_dir(
+    backends: &mut HashMap<GraphEncoding, Backend>,
+    targets: &TargetRegistry,
+    encoding: GraphEncoding,
+    path: &Path,
+) -> anyhow::Result<(String, Graph)> {
+    let backend = backends
+        .get_mut(&encoding)
+        .ok_or_else(|| anyhow!("unsupported backend: {encoding}"))?
+        .as_dir_loadable()
+        .ok_or_else(|| anyhow!("{encoding} does not support directory loading"))?;
+    let target = targets.pinned(encoding).unwrap_or(ExecutionTarget::Cpu);
+    load_dir(backend, path, target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{BackendError, BackendExecutionContext, BackendGraph, BackendInner, Id};
+    use crate::wit::Tensor;
+    use crate::ExecutionContext;
+    use std::sync::{Arc, Mutex};
+
+    /// A backend recording where it loads graphs from and on which targets.
+    #[derive(Default)]
+    struct DirBackend(Arc<Mutex<Vec<(String, ExecutionTarget)>>>);
+
+    /// A graph without any inputs or outputs.
+    struct EmptyGraph;
+    struct EmptyContext;
+
+    impl BackendInner for DirBackend {
+        fn encoding(&self) -> GraphEncoding {
+            GraphEncoding::Openvino
+        }
+        fn load(
+            &mut self,
+            builders: &[&[u8]],
+            target: ExecutionTarget,
+        ) -> Result<Graph, BackendError> {
+            let source = format!("{} builders", builders.len());
+            self.0.lock().unwrap().push((source, target));
+            let graph: Box<dyn BackendGraph> = Box::new(EmptyGraph);
+            Ok(graph.into())
+        }
+        fn as_dir_loadable(&mut self) -> Option<&mut dyn BackendFromDir> {
+            Some(self)
+        }
+        fn devices(&self) -> Result<Vec<crate::Device>, BackendError> {
+            Ok(vec![
+                crate::Device::new("CPU", ExecutionTarget::Cpu),
+                crate::Device::new("GPU", ExecutionTarget::Gpu),
+            ])
+        }
+    }
+
+    impl BackendFromDir for DirBackend {
+        fn load_from_dir(
+            &mut self,
+            path: &Path,
+            target: ExecutionTarget,
+        ) -> Result<Graph, BackendError> {
+            let path = path.display().to_string();
+            self.0.lock().unwrap().push((path, target));
+            let graph: Box<dyn BackendGraph> = Box::new(EmptyGraph);
+            Ok(graph.into())
+        }
+    }
+
+    impl BackendGraph for EmptyGraph {
+        fn init_execution_context(&self) -> Result<ExecutionContext, BackendError> {
+            let context: Box<dyn BackendExecutionContext> = Box::new(EmptyContext);
+            Ok(context.into())
+        }
+    }
+
+    impl BackendExecutionContext for EmptyContext {
+        fn set_input(&mut self, id: Id, _: &Tensor) -> Result<(), BackendError> {
+            Err(anyhow!("graph has no input {id:?}").into())
+        }
+        fn compute(&mut self) -> Result<(), BackendError> {
+            Ok(())
+        }
+        fn get_output(&mut self, id: Id) -> Result<Tensor, BackendError> {
+            Err(anyhow!("graph has no output {id:?}").into())
+        }
+    }
+
+    #[test]
+    fn preloads_from_directory() {
+        let loads = Arc::new(Mutex::new(Vec::new()));
+        let backend = Backend::from(DirBackend(loads.clone()));
+        let mut backends = HashMap::from([(GraphEncoding::Openvino, backend)]);
+        let mut targets = TargetRegistry::new();
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+
+        let (name, graph) =
+            preload_dir(&mut backends, &targets, GraphEncoding::Openvino, &dir).unwrap();
+        assert_eq!(name, "fixtures");
+        let mut context = graph.init_execution_context().unwrap();
+        context.compute().unwrap();
+        assert!(context.get_output(Id::Index(0)).is_err());
+        targets.pin(GraphEncoding::Openvino, ExecutionTarget::Gpu);
+        preload_dir(&mut backends, &targets, GraphEncoding::Openvino, &dir).unwrap();
+        let loads = loads.lock().unwrap();
+        assert_eq!(loads.len(), 2);
+        assert_eq!(loads[0].1, ExecutionTarget::Cpu);
+        assert_eq!(loads[1].1, ExecutionTarget::Gpu);
+    }
+
+    #[test]
+    fn preload_errors() {
+        let mut backends: HashMap<GraphEncoding, Backend> =
+            HashMap::from([(GraphEncoding::Openvino, DirBackend::default().into())]);
+        let targets = TargetRegistry::new();
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        assert!(preload_dir(&mut backends, &targets, GraphEncoding::Onnx, &dir).is_err());
+        let file = dir.join("000000062808.rgb");
+        assert!(preload_dir(&mut backends, &targets, GraphEncoding::Openvino, &file).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+//! Read tensor inputs from `wasi:io` streams.
+//!
+//! Rather than copying a tensor out of guest memory, the host reads the
+//! tensor's data from an input stream into the buffer it hands to the backend.
+//! This is used by the `set-input-stream` function of the WIT-based API.
+//!
+//! The size of a tensor is implied by its dimensions and type, which are chosen
+//! by the guest, so the buffer only grows as data actually arrives from the
+//! stream, and callers check the size against a limit before reading.
+
+use crate::wit::TensorType;
+use anyhow::anyhow;
+use wasmtime_wasi::{HostInputStream, StreamError, Subscribe};
+
+/// The most bytes read from a stream at once.
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// Size, in bytes, of a single element of a tensor of type `ty`.
+fn element_size(ty: TensorType) -> usize {
+    match ty {
+        TensorType::Fp16 | TensorType::Bf16 => 2,
+        TensorType::Fp32 | TensorType::I32 => 4,
+        TensorType::Fp64 | TensorType::I64 => 8,
+        TensorType::U8 => 1,
+    }
+}
+
+/// Size, in bytes, of the data of a tensor with `dimensions` and `ty`, or
+/// `None` if it overflows.
+pub(crate) fn tensor_size(dimensions: &[u32], ty: TensorType) -> Option<usize> {
+    dimensions.iter().try_fold(element_size(ty), |size, d| {
+        size.checked_mul(usize::try_from(*d).ok()?)
+    })
+}
+
+/// Read exactly `size` bytes of tensor data from `stream`, waiting until all of
+/// them are available.
+///
+/// Bytes past the end of the tensor are left in the stream. A stream closing
+/// early is reported as `StreamError::LastOperationFailed`.
+pub(crate) async fn read_tensor_data(
+    stream: &mut dyn HostInputStream,
+    size: usize,
+) -> Result<Vec<u8>, StreamError> {
+    let mut data = Vec::new();
+    while data.len() < size {
+        stream.ready().await;
+        let bytes = match stream.read((size - data.len()).min(CHUNK_SIZE)) {
+            Ok(bytes) => bytes,
+            Err(StreamError::Closed) => {
+                return Err(StreamError::LastOperationFailed(anyhow!(
+                    "stream closed after {} of {size} bytes of tensor data",
+                    data.len()
+                )));
+            }
+            Err(e) => return Err(e),
+        };
+        if bytes.len() == size {
+            // The whole tensor arrived at once, so take over the stream's
+            // buffer rather than copying it.
+            return Ok(bytes.into());
+        }
+        data.extend_from_slice(&bytes);
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasmtime_wasi::pipe::MemoryInputPipe;
+    use wasmtime_wasi::runtime::in_tokio;
+
+    #[test]
+    fn tensor_sizes() {
+        assert_eq!(tensor_size(&[2, 3], TensorType::Fp32), Some(24));
+        assert_eq!(tensor_size(&[1], TensorType::U8), Some(1));
+        assert_eq!(tensor_size(&[], TensorType::I64), Some(8));
+        assert_eq!(tensor_size(&[u32::MAX; 4], TensorType::Fp64), None);
+    }
+
+    #[test]
+    fn reads_exactly_one_tensor() {
+        let bytes: Vec<u8> = (0..20).collect();
+        let mut stream = MemoryInputPipe::new(bytes);
+        let size = tensor_size(&[2, 2], TensorType::Fp32).unwrap();
+        let data = in_tokio(read_tensor_data(&mut stream, size)).unwrap();
+        assert_eq!(data, (0..16).collect::<Vec<u8>>());
+
+        // The remaining bytes are left for the next read.
+        let rest = stream.read(16).unwrap();
+        assert_eq!(&rest[..], [16, 17, 18, 19]);
+    }
+
+    #[test]
+    fn reads_in_chunks() {
+        let bytes: Vec<u8> = (0..CHUNK_SIZE * 5 / 2).map(|i| i as u8).collect();
+        let mut stream = MemoryInputPipe::new(bytes.clone());
+        let data = in_tokio(read_tensor_data(&mut stream, bytes.len())).unwrap();
+        assert_eq!(data, bytes);
+    }
+
+    #[test]
+    fn short_stream_is_an_error() {
+        let mut stream = MemoryInputPipe::new(vec![0; 7]);
+        let err = in_tokio(read_tensor_data(&mut stream, 8)).unwrap_err();
+        match err {
+            StreamError::LastOperationFailed(e) => {
+                assert!(e.to_string().contains("7 of 8 bytes"), "{e}")
+            }
+            e => panic!("unexpected error: {e:?}"),
+        }
+    }
+}
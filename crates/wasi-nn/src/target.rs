@@ -0,0 +1,250 @@
+//! Select the device a graph is executed on.
+//!
+//! Each backend reports the [`Device`]s it can execute graphs on, which
+//! embedders can list with `WasiNnCtx::devices`. A [`TargetRegistry`] pins the
+//! graphs of an encoding to a specific [`ExecutionTarget`]; otherwise the
+//! target a guest passes to `load` is checked against the backend's devices
+//! rather than handed to the backend as-is.
+
+use crate::backend::{BackendError, BackendInner};
+use crate::wit::{ExecutionTarget, GraphEncoding};
+use std::collections::HashMap;
+
+/// A device that a backend can execute graphs on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Device {
+    name: String,
+    target: ExecutionTarget,
+}
+
+impl Device {
+    /// Create a device named `name`, which graphs are loaded on by requesting
+    /// `target`.
+    pub fn new(name: impl Into<String>, target: ExecutionTarget) -> Self {
+        Self {
+            name: name.into(),
+            target,
+        }
+    }
+
+    /// The backend-specific name of this device, e.g., `"GPU"`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The execution target which selects this device.
+    pub fn target(&self) -> ExecutionTarget {
+        self.target
+    }
+}
+
+/// The execution targets that graphs are pinned to, by encoding.
+#[derive(Clone, Debug, Default)]
+pub struct TargetRegistry {
+    pinned: HashMap<GraphEncoding, ExecutionTarget>,
+}
+
+impl TargetRegistry {
+    /// Create a registry which pins no graphs, leaving the choice of target to
+    /// the guest.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load all graphs with `encoding` on `target`, regardless of the target
+    /// the guest requests.
+    pub fn pin(&mut self, encoding: GraphEncoding, target: ExecutionTarget) -> &mut Self {
+        self.pinned.insert(encoding, target);
+        self
+    }
+
+    /// The target graphs with `encoding` are pinned to, if any.
+    pub fn pinned(&self, encoding: GraphEncoding) -> Option<ExecutionTarget> {
+        self.pinned.get(&encoding).copied()
+    }
+}
+
+/// Choose the target to load a graph on with `backend` when the guest asks for
+/// `requested`, failing if the backend has no device for it.
+pub(crate) fn select_target(
+    targets: &TargetRegistry,
+    backend: &dyn BackendInner,
+    requested: ExecutionTarget,
+) -> Result<ExecutionTarget, BackendError> {
+    let target = match targets.pinned(backend.encoding()) {
+        Some(pinned) => {
+            if pinned != requested {
+                tracing::debug!(
+                    "loading graph on pinned target {pinned:?} instead of {requested:?}"
+                );
+            }
+            pinned
+        }
+        None => requested,
+    };
+    if backend.devices()?.iter().any(|d| d.target == target) {
+        Ok(target)
+    } else {
+        Err(BackendError::UnsupportedExecutionTarget(target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{BackendExecutionContext, BackendFromDir, BackendGraph, Id};
+    use crate::wit::{Tensor, TensorType};
+    use crate::{ExecutionContext, Graph};
+
+    /// A backend which loads graphs on any of its devices, recording the
+    /// targets it loaded them on.
+    struct DeviceBackend {
+        devices: Vec<Device>,
+        loaded: Vec<ExecutionTarget>,
+    }
+
+    impl DeviceBackend {
+        fn new(devices: Vec<Device>) -> Self {
+            Self {
+                devices,
+                loaded: Vec::new(),
+            }
+        }
+    }
+
+    impl BackendInner for DeviceBackend {
+        fn encoding(&self) -> GraphEncoding {
+            GraphEncoding::Openvino
+        }
+        fn load(
+            &mut self,
+            builders: &[&[u8]],
+            target: ExecutionTarget,
+        ) -> Result<Graph, BackendError> {
+            if builders.len() != 1 {
+                return Err(BackendError::InvalidNumberOfBuilders(1, builders.len()));
+            }
+            if !self.devices.iter().any(|d| d.target == target) {
+                return Err(BackendError::UnsupportedExecutionTarget(target));
+            }
+            self.loaded.push(target);
+            let graph: Box<dyn BackendGraph> = Box::new(IdentityGraph);
+            Ok(graph.into())
+        }
+        fn as_dir_loadable(&mut self) -> Option<&mut dyn BackendFromDir> {
+            None
+        }
+        fn devices(&self) -> Result<Vec<Device>, BackendError> {
+            Ok(self.devices.clone())
+        }
+    }
+
+    /// A graph whose single output is its single input.
+    struct IdentityGraph;
+
+    impl BackendGraph for IdentityGraph {
+        fn init_execution_context(&self) -> Result<ExecutionContext, BackendError> {
+            let context: Box<dyn BackendExecutionContext> = Box::new(IdentityContext::default());
+            Ok(context.into())
+        }
+    }
+
+    #[derive(Default)]
+    struct IdentityContext {
+        input: Option<Tensor>,
+        output: Option<Tensor>,
+    }
+
+    impl BackendExecutionContext for IdentityContext {
+        fn set_input(&mut self, id: Id, tensor: &Tensor) -> Result<(), BackendError> {
+            if id != Id::Index(0) {
+                return Err(anyhow::anyhow!("no input {id:?}").into());
+            }
+            self.input = Some(tensor.clone());
+            Ok(())
+        }
+        fn compute(&mut self) -> Result<(), BackendError> {
+            let input = self
+                .input
+                .take()
+                .ok_or_else(|| anyhow::anyhow!("input not set"))?;
+            self.output = Some(input);
+            Ok(())
+        }
+        fn get_output(&mut self, id: Id) -> Result<Tensor, BackendError> {
+            if id != Id::Index(0) {
+                return Err(anyhow::anyhow!("no output {id:?}").into());
+            }
+            self.output
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("not computed").into())
+        }
+    }
+
+    fn cpu_and_gpu() -> DeviceBackend {
+        DeviceBackend::new(vec![
+            Device::new("CPU", ExecutionTarget::Cpu),
+            Device::new("GPU", ExecutionTarget::Gpu),
+        ])
+    }
+
+    #[test]
+    fn requested_target_is_validated() {
+        let targets = TargetRegistry::new();
+        let backend = cpu_and_gpu();
+        let target = select_target(&targets, &backend, ExecutionTarget::Gpu).unwrap();
+        assert_eq!(target, ExecutionTarget::Gpu);
+        let err = select_target(&targets, &backend, ExecutionTarget::Tpu).unwrap_err();
+        assert!(matches!(
+            err,
+            BackendError::UnsupportedExecutionTarget(ExecutionTarget::Tpu)
+        ));
+    }
+
+    #[test]
+    fn pinned_target_overrides_request() {
+        let mut targets = TargetRegistry::new();
+        targets.pin(GraphEncoding::Openvino, ExecutionTarget::Gpu);
+        targets.pin(GraphEncoding::Onnx, ExecutionTarget::Tpu);
+        let backend = cpu_and_gpu();
+        let target = select_target(&targets, &backend, ExecutionTarget::Cpu).unwrap();
+        assert_eq!(target, ExecutionTarget::Gpu);
+        let target = select_target(&targets, &backend, ExecutionTarget::Tpu).unwrap();
+        assert_eq!(target, ExecutionTarget::Gpu);
+    }
+
+    #[test]
+    fn pinned_target_must_be_supported() {
+        let mut targets = TargetRegistry::new();
+        targets.pin(GraphEncoding::Openvino, ExecutionTarget::Gpu);
+        let backend = DeviceBackend::new(vec![Device::new("CPU", ExecutionTarget::Cpu)]);
+        let err = select_target(&targets, &backend, ExecutionTarget::Cpu).unwrap_err();
+        assert!(matches!(
+            err,
+            BackendError::UnsupportedExecutionTarget(ExecutionTarget::Gpu)
+        ));
+    }
+
+    #[test]
+    fn graphs_load_and_run_on_selected_target() {
+        let mut targets = TargetRegistry::new();
+        let mut backend = cpu_and_gpu();
+        let target = select_target(&targets, &backend, ExecutionTarget::Gpu).unwrap();
+        let graph = backend.load(&[b"model".as_slice()], target).unwrap();
+        targets.pin(GraphEncoding::Openvino, ExecutionTarget::Cpu);
+        let target = select_target(&targets, &backend, ExecutionTarget::Gpu).unwrap();
+        backend.load(&[b"model".as_slice()], target).unwrap();
+        assert_eq!(backend.loaded, [ExecutionTarget::Gpu, ExecutionTarget::Cpu]);
+
+        let tensor = Tensor {
+            dimensions: vec![1, 2],
+            ty: TensorType::U8,
+            data: vec![3, 4],
+        };
+        let mut context = graph.init_execution_context().unwrap();
+        context.set_input(Id::Index(0), &tensor).unwrap();
+        context.compute().unwrap();
+        assert_eq!(context.get_output(Id::Index(0)).unwrap(), tensor);
+        assert!(context.get_output(Id::Index(1)).is_err());
+    }
+}
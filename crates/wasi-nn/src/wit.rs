@@ -16,24 +16,117 @@
 //! [`types`]: crate::wit::types
 
 use crate::backend::Id;
-use crate::{Backend, Registry};
+use crate::batch;
+use crate::decrypt::decrypt_builders;
+use crate::registry;
+use crate::stream;
+use crate::target::select_target;
+use crate::{Backend, Batcher, Device, ModelDecryptor, Registry, TargetRegistry};
 use anyhow::anyhow;
 use std::collections::HashMap;
 use std::hash::Hash;
+use std::path::Path;
+use std::sync::Arc;
 use std::{fmt, str::FromStr};
 use wasmtime::component::{Resource, ResourceTable};
+use wasmtime_wasi::runtime::in_tokio;
+use wasmtime_wasi::{InputStream, StreamError};
+
+/// The default limit on the size of a tensor read by `set-input-stream`.
+const DEFAULT_MAX_STREAM_TENSOR_SIZE: usize = 256 << 20;
 
 /// Capture the state necessary for calling into the backend ML libraries.
 pub struct WasiNnCtx {
     pub(crate) backends: HashMap<GraphEncoding, Backend>,
     pub(crate) registry: Registry,
+    pub(crate) decryptor: Option<ModelDecryptor>,
+    pub(crate) batcher: Option<Arc<Batcher>>,
+    pub(crate) targets: TargetRegistry,
+    pub(crate) preloaded: HashMap<String, Graph>,
+    pub(crate) max_stream_tensor_size: usize,
 }
 
 impl WasiNnCtx {
     /// Make a new context from the default state.
     pub fn new(backends: impl IntoIterator<Item = Backend>, registry: Registry) -> Self {
         let backends = backends.into_iter().map(|b| (b.encoding(), b)).collect();
-        Self { backends, registry }
+        Self {
+            backends,
+            registry,
+            decryptor: None,
+            batcher: None,
+            targets: TargetRegistry::new(),
+            preloaded: HashMap::new(),
+            max_stream_tensor_size: DEFAULT_MAX_STREAM_TENSOR_SIZE,
+        }
+    }
+
+    /// Decrypt the graph builders passed to `load` with `decryptor` before
+    /// handing them to the backend.
+    pub fn with_decryptor(mut self, decryptor: ModelDecryptor) -> Self {
+        self.decryptor = Some(decryptor);
+        self
+    }
+
+    /// Route the `compute` calls of all execution contexts initialized from
+    /// this context through `batcher`; sharing a single [`Batcher`] between
+    /// the contexts of many stores allows their requests to be coalesced into
+    /// batched backend calls.
+    pub fn with_batcher(mut self, batcher: Arc<Batcher>) -> Self {
+        self.batcher = Some(batcher);
+        self
+    }
+
+    /// Load graphs on the execution targets pinned in `targets`; the targets
+    /// guests request for other graphs must still be supported by a device of
+    /// their backend.
+    pub fn with_targets(mut self, targets: TargetRegistry) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    /// Limit the size, in bytes, of the tensors which guests can read from
+    /// streams with `set-input-stream` to `bytes`; larger tensors are rejected
+    /// with a `too-large` error before anything is read. Defaults to 256 MiB.
+    pub fn with_max_stream_tensor_size(mut self, bytes: usize) -> Self {
+        self.max_stream_tensor_size = bytes;
+        self
+    }
+
+    /// Make `graph` available to guests as `name` with `load_by_name`, taking
+    /// precedence over any graph of the same name in the [`Registry`].
+    pub fn preload_graph(mut self, name: impl Into<String>, graph: Graph) -> Self {
+        self.preloaded.insert(name.into(), graph);
+        self
+    }
+
+    /// Load the graph stored in the `path` directory with the backend for
+    /// `encoding` and make it available to `load_by_name`, named after the
+    /// directory as with [`InMemoryRegistry::load`].
+    ///
+    /// The graph is loaded by the host, so guests don't need access to the
+    /// files holding its weights. It's loaded on the target pinned for
+    /// `encoding` with [`WasiNnCtx::with_targets`], or the CPU otherwise.
+    ///
+    /// [`InMemoryRegistry::load`]: crate::InMemoryRegistry::load
+    pub fn preload_dir(
+        mut self,
+        encoding: GraphEncoding,
+        path: impl AsRef<Path>,
+    ) -> anyhow::Result<Self> {
+        let (name, graph) =
+            registry::preload_dir(&mut self.backends, &self.targets, encoding, path.as_ref())?;
+        Ok(self.preload_graph(name, graph))
+    }
+
+    /// List the devices each backend can currently load graphs on, by the
+    /// encoding of the graphs they load.
+    pub fn devices(&self) -> anyhow::Result<Vec<(GraphEncoding, Device)>> {
+        let mut devices = Vec::new();
+        for (encoding, backend) in self.backends.iter() {
+            devices.extend(backend.devices()?.into_iter().map(|d| (*encoding, d)));
+        }
+        Ok(devices)
     }
 }
 
@@ -117,10 +210,13 @@ pub enum ErrorCode {
 /// Generate the traits and types from the `wasi-nn` WIT specification.
 mod generated_ {
     wasmtime::component::bindgen!({
-        world: "ml",
-        path: "wit/wasi-nn.wit",
+        world: "ml-streaming",
+        path: "wit",
         trappable_imports: true,
         with: {
+            // Upstream package dependencies
+            "wasi:io": wasmtime_wasi::bindings::sync::io,
+
             // Configure all WIT http resources to be defined types in this
             // crate to use the `ResourceTable` helper methods.
             "wasi:nn/graph/graph": crate::Graph,
@@ -135,6 +231,28 @@ mod generated_ {
 }
 use generated_::wasi::nn::{self as generated}; // Shortcut to the module containing the types we need.
 
+/// Generate an `async` version of the `streaming` interface, for use with
+/// `wasmtime_wasi`'s asynchronous `wasi:io` implementation; the rest of the
+/// API never blocks and is shared with the bindings above.
+mod generated_async_ {
+    wasmtime::component::bindgen!({
+        world: "ml-streaming",
+        path: "wit",
+        trappable_imports: true,
+        async: {
+            only_imports: ["set-input-stream"],
+        },
+        with: {
+            "wasi:io": wasmtime_wasi::bindings::io,
+            "wasi:nn/graph": crate::wit::generated_::wasi::nn::graph,
+            "wasi:nn/tensor": crate::wit::generated_::wasi::nn::tensor,
+            "wasi:nn/inference": crate::wit::generated_::wasi::nn::inference,
+            "wasi:nn/errors": crate::wit::generated_::wasi::nn::errors,
+        },
+    });
+}
+use generated_async_::wasi::nn as generated_async;
+
 // Export the `types` used in this crate as well as `ML::add_to_linker`.
 pub mod types {
     use super::generated;
@@ -146,10 +264,15 @@ pub mod types {
 pub use generated::graph::{ExecutionTarget, Graph, GraphBuilder, GraphEncoding};
 pub use generated::inference::GraphExecutionContext;
 pub use generated::tensor::{Tensor, TensorData, TensorDimensions, TensorType};
-pub use generated_::Ml as ML;
+pub use generated_::MlStreaming as ML;
 
 /// Add the WIT-based version of the `wasi-nn` API to a
 /// [`wasmtime::component::Linker`].
+///
+/// This includes the `set-input-stream` extension, which reads tensors from the
+/// `wasi:io` input streams in the [`ResourceTable`] of the [`WasiNnView`]; the
+/// `wasi:io` interfaces themselves must be added separately, e.g., with
+/// `wasmtime_wasi::add_to_linker_sync`, using the same table.
 pub fn add_to_linker<T>(
     l: &mut wasmtime::component::Linker<T>,
     f: impl Fn(&mut T) -> WasiNnView<'_> + Send + Sync + Copy + 'static,
@@ -158,6 +281,26 @@ pub fn add_to_linker<T>(
     generated::tensor::add_to_linker_get_host(l, f)?;
     generated::inference::add_to_linker_get_host(l, f)?;
     generated::errors::add_to_linker_get_host(l, f)?;
+    generated::streaming::add_to_linker_get_host(l, f)?;
+    Ok(())
+}
+
+/// Add the WIT-based version of the `wasi-nn` API to a
+/// [`wasmtime::component::Linker`] for use with
+/// [`Config::async_support`](wasmtime::Config::async_support).
+///
+/// This is the same as [`add_to_linker`] except that `set-input-stream` waits
+/// for the `wasi:io` input stream asynchronously; the `wasi:io` interfaces
+/// should likewise be added with, e.g., `wasmtime_wasi::add_to_linker_async`.
+pub fn add_to_linker_async<T: Send>(
+    l: &mut wasmtime::component::Linker<T>,
+    f: impl Fn(&mut T) -> WasiNnView<'_> + Send + Sync + Copy + 'static,
+) -> anyhow::Result<()> {
+    generated::graph::add_to_linker_get_host(l, f)?;
+    generated::tensor::add_to_linker_get_host(l, f)?;
+    generated::inference::add_to_linker_get_host(l, f)?;
+    generated::errors::add_to_linker_get_host(l, f)?;
+    generated_async::streaming::add_to_linker_get_host(l, f)?;
     Ok(())
 }
 
@@ -170,8 +313,21 @@ impl generated::graph::Host for WasiNnView<'_> {
     ) -> wasmtime::Result<Result<Resource<Graph>, Resource<Error>>> {
         tracing::debug!("load {encoding:?} {target:?}");
         if let Some(backend) = self.ctx.backends.get_mut(&encoding) {
+            let target = match select_target(&self.ctx.targets, &**backend, target) {
+                Ok(target) => target,
+                Err(error) => {
+                    bail!(self, ErrorCode::InvalidArgument, error);
+                }
+            };
             let slices = builders.iter().map(|s| s.as_slice()).collect::<Vec<_>>();
-            match backend.load(&slices, target.into()) {
+            let decrypted = match decrypt_builders(self.ctx.decryptor.as_ref(), &slices) {
+                Ok(decrypted) => decrypted,
+                Err(error) => {
+                    bail!(self, ErrorCode::InvalidArgument, error);
+                }
+            };
+            let slices = decrypted.iter().map(|s| s.as_ref()).collect::<Vec<_>>();
+            match backend.load(&slices, target) {
                 Ok(graph) => {
                     let graph = self.table.push(graph)?;
                     Ok(Ok(graph))
@@ -196,7 +352,8 @@ impl generated::graph::Host for WasiNnView<'_> {
         use core::result::Result::*;
         tracing::debug!("load by name {name:?}");
         let registry = &self.ctx.registry;
-        if let Some(graph) = registry.get(&name) {
+        let preloaded = self.ctx.preloaded.get(&name);
+        if let Some(graph) = preloaded.or_else(|| registry.get(&name)) {
             let graph = graph.clone();
             let graph = self.table.push(graph)?;
             Ok(Ok(graph))
@@ -218,7 +375,7 @@ impl generated::graph::HostGraph for WasiNnView<'_> {
         use core::result::Result::*;
         tracing::debug!("initialize execution context");
         let graph = self.table.get(&graph)?;
-        match graph.init_execution_context() {
+        match batch::init_execution_context(graph, self.ctx.batcher.as_ref()) {
             Ok(exec_context) => {
                 let exec_context = self.table.push(exec_context)?;
                 Ok(Ok(exec_context))
@@ -291,6 +448,76 @@ impl generated::inference::HostGraphExecutionContext for WasiNnView<'_> {
     }
 }
 
+impl WasiNnView<'_> {
+    async fn set_input_from_stream(
+        &mut self,
+        exec_context: Resource<GraphExecutionContext>,
+        name: String,
+        dimensions: TensorDimensions,
+        ty: TensorType,
+        data: Resource<InputStream>,
+    ) -> wasmtime::Result<Result<(), Resource<Error>>> {
+        tracing::debug!("set input {name:?} from stream: {dimensions:?} {ty:?}");
+        let limit = self.ctx.max_stream_tensor_size;
+        let size = match stream::tensor_size(&dimensions, ty) {
+            Some(size) if size <= limit => size,
+            _ => {
+                bail!(
+                    self,
+                    ErrorCode::TooLarge,
+                    anyhow!("tensor of {dimensions:?} {ty:?} exceeds the limit of {limit} bytes")
+                );
+            }
+        };
+        let stream = self.table.get_mut(&data)?;
+        let data = match stream::read_tensor_data(&mut **stream, size).await {
+            Ok(data) => data,
+            Err(StreamError::Trap(e)) => return Err(e),
+            Err(error) => {
+                bail!(self, ErrorCode::InvalidArgument, error);
+            }
+        };
+        let tensor = Tensor {
+            dimensions,
+            ty,
+            data,
+        };
+        let exec_context = self.table.get_mut(&exec_context)?;
+        if let Err(error) = exec_context.set_input(Id::Name(name), &tensor) {
+            bail!(self, ErrorCode::InvalidArgument, error);
+        } else {
+            Ok(Ok(()))
+        }
+    }
+}
+
+impl generated::streaming::Host for WasiNnView<'_> {
+    fn set_input_stream(
+        &mut self,
+        exec_context: Resource<GraphExecutionContext>,
+        name: String,
+        dimensions: TensorDimensions,
+        ty: TensorType,
+        data: Resource<InputStream>,
+    ) -> wasmtime::Result<Result<(), Resource<Error>>> {
+        in_tokio(self.set_input_from_stream(exec_context, name, dimensions, ty, data))
+    }
+}
+
+impl generated_async::streaming::Host for WasiNnView<'_> {
+    async fn set_input_stream(
+        &mut self,
+        exec_context: Resource<GraphExecutionContext>,
+        name: String,
+        dimensions: TensorDimensions,
+        ty: TensorType,
+        data: Resource<InputStream>,
+    ) -> wasmtime::Result<Result<(), Resource<Error>>> {
+        self.set_input_from_stream(exec_context, name, dimensions, ty, data)
+            .await
+    }
+}
+
 impl generated::tensor::HostTensor for WasiNnView<'_> {
     fn new(
         &mut self,
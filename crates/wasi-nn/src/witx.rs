@@ -15,10 +15,18 @@
 
 use crate::backend::BackendError;
 use crate::backend::Id;
+use crate::batch;
+use crate::decrypt::decrypt_builders;
+use crate::registry;
+use crate::target::select_target;
 use crate::wit::GraphEncoding;
-use crate::{Backend, ExecutionContext, Graph, Registry};
+use crate::{
+    Backend, Batcher, Device, ExecutionContext, Graph, ModelDecryptor, Registry, TargetRegistry,
+};
 use std::collections::HashMap;
 use std::hash::Hash;
+use std::path::Path;
+use std::sync::Arc;
 use thiserror::Error;
 use wiggle::{GuestError, GuestMemory, GuestPtr};
 
@@ -35,6 +43,10 @@ pub struct WasiNnCtx {
     pub(crate) registry: Registry,
     pub(crate) graphs: Table<GraphId, Graph>,
     pub(crate) executions: Table<GraphExecutionContextId, ExecutionContext>,
+    pub(crate) decryptor: Option<ModelDecryptor>,
+    pub(crate) batcher: Option<Arc<Batcher>>,
+    pub(crate) targets: TargetRegistry,
+    pub(crate) preloaded: HashMap<String, Graph>,
 }
 
 impl WasiNnCtx {
@@ -46,8 +58,72 @@ impl WasiNnCtx {
             registry,
             graphs: Table::default(),
             executions: Table::default(),
+            decryptor: None,
+            batcher: None,
+            targets: TargetRegistry::new(),
+            preloaded: HashMap::new(),
         }
     }
+
+    /// Decrypt the graph builders passed to `load` with `decryptor` before
+    /// handing them to the backend.
+    pub fn with_decryptor(mut self, decryptor: ModelDecryptor) -> Self {
+        self.decryptor = Some(decryptor);
+        self
+    }
+
+    /// Route the `compute` calls of all execution contexts initialized from
+    /// this context through `batcher`; sharing a single [`Batcher`] between
+    /// the contexts of many stores allows their requests to be coalesced into
+    /// batched backend calls.
+    pub fn with_batcher(mut self, batcher: Arc<Batcher>) -> Self {
+        self.batcher = Some(batcher);
+        self
+    }
+
+    /// Load graphs on the execution targets pinned in `targets`; the targets
+    /// guests request for other graphs must still be supported by a device of
+    /// their backend.
+    pub fn with_targets(mut self, targets: TargetRegistry) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    /// Make `graph` available to guests as `name` with `load_by_name`, taking
+    /// precedence over any graph of the same name in the [`Registry`].
+    pub fn preload_graph(mut self, name: impl Into<String>, graph: Graph) -> Self {
+        self.preloaded.insert(name.into(), graph);
+        self
+    }
+
+    /// Load the graph stored in the `path` directory with the backend for
+    /// `encoding` and make it available to `load_by_name`, named after the
+    /// directory as with [`InMemoryRegistry::load`].
+    ///
+    /// The graph is loaded by the host, so guests don't need access to the
+    /// files holding its weights. It's loaded on the target pinned for
+    /// `encoding` with [`WasiNnCtx::with_targets`], or the CPU otherwise.
+    ///
+    /// [`InMemoryRegistry::load`]: crate::InMemoryRegistry::load
+    pub fn preload_dir(
+        mut self,
+        encoding: GraphEncoding,
+        path: impl AsRef<Path>,
+    ) -> anyhow::Result<Self> {
+        let (name, graph) =
+            registry::preload_dir(&mut self.backends, &self.targets, encoding, path.as_ref())?;
+        Ok(self.preload_graph(name, graph))
+    }
+
+    /// List the devices each backend can currently load graphs on, by the
+    /// encoding of the graphs they load.
+    pub fn devices(&self) -> anyhow::Result<Vec<(GraphEncoding, Device)>> {
+        let mut devices = Vec::new();
+        for (encoding, backend) in self.backends.iter() {
+            devices.extend(backend.devices()?.into_iter().map(|d| (*encoding, d)));
+        }
+        Ok(devices)
+    }
 }
 
 /// Record handle entries in a table.
@@ -114,6 +190,9 @@ mod generated {
         ) -> anyhow::Result<types::NnErrno> {
             tracing::debug!("host error: {:?}", e);
             match e {
+                WasiNnError::BackendError(BackendError::UnsupportedExecutionTarget(_)) => {
+                    Ok(types::NnErrno::InvalidArgument)
+                }
                 WasiNnError::BackendError(_) => Ok(types::NnErrno::RuntimeError),
                 WasiNnError::GuestError(_) => unimplemented!("guest error conversion"),
                 WasiNnError::UsageError(_) => Ok(types::NnErrno::UnsupportedOperation),
@@ -133,6 +212,7 @@ impl generated::wasi_ephemeral_nn::WasiEphemeralNn for WasiNnCtx {
         target: generated::types::ExecutionTarget,
     ) -> Result<generated::types::Graph> {
         let graph = if let Some(backend) = self.backends.get_mut(&encoding.into()) {
+            let target = select_target(&self.targets, &**backend, target.into())?;
             // Retrieve all of the "builder lists" from the Wasm memory (see
             // $graph_builder_array) as slices for a backend to operate on.
             let mut slices = vec![];
@@ -145,7 +225,10 @@ impl generated::wasi_ephemeral_nn::WasiEphemeralNn for WasiNnCtx {
                 slices.push(slice);
             }
             let slice_refs = slices.iter().map(|s| s.as_ref()).collect::<Vec<_>>();
-            backend.load(&slice_refs, target.into())?
+            let decrypted = decrypt_builders(self.decryptor.as_ref(), &slice_refs)
+                .map_err(BackendError::ModelDecryption)?;
+            let slice_refs = decrypted.iter().map(|s| s.as_ref()).collect::<Vec<_>>();
+            backend.load(&slice_refs, target)?
         } else {
             return Err(UsageError::InvalidEncoding(encoding.into()).into());
         };
@@ -159,7 +242,8 @@ impl generated::wasi_ephemeral_nn::WasiEphemeralNn for WasiNnCtx {
         name: wiggle::GuestPtr<str>,
     ) -> Result<generated::types::Graph> {
         let name = memory.as_str(name)?.unwrap();
-        if let Some(graph) = self.registry.get_mut(&name) {
+        let preloaded = self.preloaded.get(name);
+        if let Some(graph) = preloaded.or_else(|| self.registry.get(&name)) {
             let graph_id = self.graphs.insert(graph.clone().into());
             Ok(graph_id.into())
         } else {
@@ -173,7 +257,7 @@ impl generated::wasi_ephemeral_nn::WasiEphemeralNn for WasiNnCtx {
         graph_id: generated::types::Graph,
     ) -> Result<generated::types::GraphExecutionContext> {
         let exec_context = if let Some(graph) = self.graphs.get_mut(graph_id.into()) {
-            graph.init_execution_context()?
+            batch::init_execution_context(graph, self.batcher.as_ref())?
         } else {
             return Err(UsageError::InvalidGraphHandle.into());
         };
@@ -0,0 +1,105 @@
+//! `wasi:io` stream implementations for the `stream<u8>` type of the async
+//! component model.
+//!
+//! This allows a host to hand a component a `stream<u8>` whose other end is a
+//! `wasi:io` stream, for example to expose an `output-stream` that writes into
+//! a `stream<u8>` returned from a component, or the other way around.
+
+use crate::poll::Subscribe;
+use crate::stream::{HostInputStream, HostOutputStream, StreamError, StreamResult};
+use bytes::Bytes;
+use wasmtime::component::{StreamReader, StreamWriter};
+
+#[async_trait::async_trait]
+impl Subscribe for StreamReader<u8> {
+    async fn ready(&mut self) {
+        StreamReader::ready(self).await
+    }
+}
+
+impl HostInputStream for StreamReader<u8> {
+    fn read(&mut self, size: usize) -> StreamResult<Bytes> {
+        match StreamReader::read(self, size) {
+            Ok(Some(bytes)) => Ok(bytes.into()),
+            Ok(None) => Err(StreamError::Closed),
+            Err(e) => Err(StreamError::Trap(e)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Subscribe for StreamWriter<u8> {
+    async fn ready(&mut self) {
+        // Writes are buffered without a limit so they're always ready.
+    }
+}
+
+impl HostOutputStream for StreamWriter<u8> {
+    fn write(&mut self, bytes: Bytes) -> StreamResult<()> {
+        if self.is_closed() {
+            return Err(StreamError::Closed);
+        }
+        StreamWriter::write(self, bytes).map_err(StreamError::LastOperationFailed)
+    }
+
+    fn flush(&mut self) -> StreamResult<()> {
+        self.check_write().map(drop)
+    }
+
+    fn check_write(&mut self) -> StreamResult<usize> {
+        if self.is_closed() {
+            return Err(StreamError::Closed);
+        }
+        Ok(usize::MAX)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasmtime::component::stream;
+
+    #[test_log::test(tokio::test)]
+    async fn input_stream_reads_written_bytes() {
+        let (writer, mut reader) = stream::<u8>();
+        writer.write(*b"hello").unwrap();
+        let bytes = HostInputStream::read(&mut reader, 3).unwrap();
+        assert_eq!(&bytes[..], b"hel");
+
+        writer.close();
+        let bytes = HostInputStream::blocking_read(&mut reader, 10)
+            .await
+            .unwrap();
+        assert_eq!(&bytes[..], b"lo");
+        assert!(matches!(
+            HostInputStream::read(&mut reader, 10),
+            Err(StreamError::Closed)
+        ));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn input_stream_waits_for_writer() {
+        let (writer, mut reader) = stream::<u8>();
+        let read =
+            tokio::spawn(async move { HostInputStream::blocking_read(&mut reader, 10).await });
+        tokio::task::yield_now().await;
+        writer.write(*b"hi").unwrap();
+        assert_eq!(&read.await.unwrap().unwrap()[..], b"hi");
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn output_stream_writes_into_stream() {
+        let (mut writer, reader) = stream::<u8>();
+        writer
+            .blocking_write_and_flush(Bytes::from_static(b"data"))
+            .await
+            .unwrap();
+        assert_eq!(reader.read(10).unwrap(), Some(b"data".to_vec()));
+
+        reader.close().unwrap();
+        assert!(matches!(
+            HostOutputStream::check_write(&mut writer),
+            Err(StreamError::Closed)
+        ));
+    }
+}
@@ -394,6 +394,7 @@ mod async_io {
                 "[method]tcp-socket.start-bind",
                 "[method]tcp-socket.start-connect",
                 "[method]udp-socket.start-bind",
+                "[method]udp-socket.join-multicast-group",
                 "[method]udp-socket.stream",
                 "[method]outgoing-datagram-stream.send",
             ],
@@ -1,5 +1,8 @@
 pub mod host;
+mod manual;
+
 use cap_std::time::Duration;
+pub use manual::ManualClock;
 
 pub trait HostWallClock: Send {
     fn resolution(&self) -> Duration;
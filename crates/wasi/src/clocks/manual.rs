@@ -0,0 +1,91 @@
+use super::{HostMonotonicClock, HostWallClock};
+use cap_std::time::Duration;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// A virtual clock which only advances when the embedder asks it to.
+///
+/// This implements both [`HostWallClock`] and [`HostMonotonicClock`] and is
+/// intended for reproducible execution, for example when replaying or fuzzing
+/// components. The monotonic clock starts at zero and the wall clock reports
+/// the `epoch` this clock was created with plus the monotonic time elapsed.
+///
+/// Clones of a `ManualClock` share the same underlying time, so a clone can be
+/// kept by the embedder to [`advance`](ManualClock::advance) a clock that was
+/// handed to a [`WasiCtxBuilder`](crate::WasiCtxBuilder). Timers created by
+/// the guest with `wasi:clocks/monotonic-clock` become ready only once the
+/// clock has been advanced past their deadline.
+#[derive(Clone)]
+pub struct ManualClock {
+    epoch: Duration,
+    now: Arc<watch::Sender<u64>>,
+}
+
+impl ManualClock {
+    /// Creates a new clock whose monotonic time is zero and whose wall time is
+    /// `epoch`, measured since the Unix epoch.
+    pub fn new(epoch: Duration) -> Self {
+        Self {
+            epoch,
+            now: Arc::new(watch::Sender::new(0)),
+        }
+    }
+
+    /// Advances this clock, and all of its clones, by `duration`.
+    ///
+    /// Saturates rather than overflowing the 64-bit nanosecond counter used by
+    /// the monotonic clock.
+    pub fn advance(&self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.now.send_modify(|now| *now = now.saturating_add(nanos));
+    }
+
+    /// Returns the number of nanoseconds this clock has been advanced by.
+    pub fn elapsed(&self) -> u64 {
+        *self.now.borrow()
+    }
+
+    /// Returns a receiver which observes every update of this clock, used to
+    /// implement timers.
+    pub(crate) fn subscribe(&self) -> watch::Receiver<u64> {
+        self.now.subscribe()
+    }
+}
+
+impl HostWallClock for ManualClock {
+    fn resolution(&self) -> Duration {
+        Duration::from_nanos(1)
+    }
+
+    fn now(&self) -> Duration {
+        self.epoch + Duration::from_nanos(self.elapsed())
+    }
+}
+
+impl HostMonotonicClock for ManualClock {
+    fn resolution(&self) -> u64 {
+        1
+    }
+
+    fn now(&self) -> u64 {
+        self.elapsed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn clones_share_time() {
+        let clock = ManualClock::new(Duration::from_secs(100));
+        let clone = clock.clone();
+        assert_eq!(HostMonotonicClock::now(&clone), 0);
+        clock.advance(Duration::from_millis(5));
+        assert_eq!(HostMonotonicClock::now(&clone), 5_000_000);
+        assert_eq!(
+            HostWallClock::now(&clone),
+            Duration::from_secs(100) + Duration::from_millis(5)
+        );
+    }
+}
@@ -1,11 +1,13 @@
 use crate::{
     clocks::{
         host::{monotonic_clock, wall_clock},
-        HostMonotonicClock, HostWallClock,
+        HostMonotonicClock, HostWallClock, ManualClock,
     },
-    filesystem::{Dir, OpenMode},
+    filesystem::{Dir, MountOptions, OpenMode, Overlay, OverlayMount, Quota},
     network::{SocketAddrCheck, SocketAddrUse},
-    pipe, random, stdio,
+    pipe, random,
+    runtime::TaskSupervisor,
+    stdio,
     stdio::{StdinStream, StdoutStream},
     DirPerms, FilePerms,
 };
@@ -50,8 +52,10 @@ pub struct WasiCtxBuilder {
     insecure_random_seed: u128,
     wall_clock: Box<dyn HostWallClock + Send>,
     monotonic_clock: Box<dyn HostMonotonicClock + Send>,
+    manual_clock: Option<ManualClock>,
     allowed_network_uses: AllowedNetworkUses,
     allow_blocking_current_thread: bool,
+    tasks: TaskSupervisor,
     built: bool,
 }
 
@@ -65,7 +69,8 @@ impl WasiCtxBuilder {
     /// * no env vars
     /// * no arguments
     /// * no preopens
-    /// * clocks use the host implementation of wall/monotonic clocks
+    /// * clocks use the host implementation of wall/monotonic clocks, see
+    ///   [`WasiCtxBuilder::deterministic`] for virtualized clocks
     /// * RNGs are all initialized with random state and suitable generator
     ///   quality to satisfy the requirements of WASI APIs.
     /// * TCP/UDP are allowed but all addresses are denied by default.
@@ -99,8 +104,10 @@ impl WasiCtxBuilder {
             insecure_random_seed,
             wall_clock: wall_clock(),
             monotonic_clock: monotonic_clock(),
+            manual_clock: None,
             allowed_network_uses: AllowedNetworkUses::default(),
             allow_blocking_current_thread: false,
+            tasks: TaskSupervisor::new(),
             built: false,
         }
     }
@@ -205,6 +212,35 @@ impl WasiCtxBuilder {
         self
     }
 
+    /// Limits the number of background tasks the resulting [`WasiCtx`] may
+    /// have running at once.
+    ///
+    /// Some operations continue in a background task after the call that
+    /// started them returns, for example writes to files, pipes and TCP
+    /// sockets which can't complete immediately, IP name lookups and outgoing
+    /// HTTP requests. These tasks are tracked by
+    /// the context's [`TaskSupervisor`], see [`WasiCtx::tasks`], and are
+    /// cancelled when the context is dropped.
+    ///
+    /// Once `limit` tasks are running operations which would spawn another
+    /// one fail instead. By default there is no limit.
+    pub fn max_background_tasks(&mut self, limit: usize) -> &mut Self {
+        self.tasks.set_limit(Some(limit));
+        self
+    }
+
+    /// Returns the supervisor which the resulting [`WasiCtx`] will track its
+    /// background tasks with.
+    ///
+    /// This allows streams which spawn a task of their own, such as
+    /// [`AsyncWriteStream::new_supervised`], to be created for use as stdio
+    /// before the context is built and still be cancelled with it.
+    ///
+    /// [`AsyncWriteStream::new_supervised`]: crate::pipe::AsyncWriteStream::new_supervised
+    pub fn tasks(&self) -> &TaskSupervisor {
+        &self.tasks
+    }
+
     /// Appends multiple environment variables at once for this builder.
     ///
     /// All environment variables are appended to the list of environment
@@ -351,6 +387,89 @@ impl WasiCtxBuilder {
         Ok(self)
     }
 
+    /// Configures a "preopened directory" like
+    /// [`preopened_dir`](WasiCtxBuilder::preopened_dir), with the sandboxing
+    /// configured by `options`.
+    ///
+    /// This can be used to give the guest a read-only view of a directory, or
+    /// a writable scratch layer over an immutable base image with a limit on
+    /// how much data can be written to it. See [`MountOptions`] for details.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if `host_path` or the directory of
+    /// the overlay cannot be opened, if the size of the files in the
+    /// directory a quota applies to cannot be determined, or if a read-only
+    /// mount is configured with an overlay.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wasmtime_wasi::{MountOptions, WasiCtxBuilder};
+    ///
+    /// # fn main() {}
+    /// # fn foo() -> wasmtime::Result<()> {
+    /// let mut wasi = WasiCtxBuilder::new();
+    ///
+    /// // Make `./image` available in the guest as `/`, with changes written to
+    /// // `./scratch` instead.
+    /// wasi.mount("./image", "/", MountOptions::new().overlay("./scratch"))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn mount(
+        &mut self,
+        host_path: impl AsRef<Path>,
+        guest_path: impl AsRef<str>,
+        options: MountOptions,
+    ) -> Result<&mut Self> {
+        if options.read_only && options.overlay.is_some() {
+            anyhow::bail!("a read-only mount can't have an overlay");
+        }
+        let (dir_perms, file_perms, open_mode) = if options.read_only {
+            (DirPerms::READ, FilePerms::READ, OpenMode::READ)
+        } else {
+            (
+                DirPerms::all(),
+                FilePerms::all(),
+                OpenMode::READ | OpenMode::WRITE,
+            )
+        };
+
+        let host_dir = cap_std::fs::Dir::open_ambient_dir(host_path.as_ref(), ambient_authority())?;
+        let (writable, lower) = match &options.overlay {
+            Some(upper) => (
+                cap_std::fs::Dir::open_ambient_dir(upper, ambient_authority())?,
+                Some(host_dir),
+            ),
+            None => (host_dir, None),
+        };
+        let quota = match options.quota {
+            Some(limit) => Some(Arc::new(Quota::new(limit, &writable)?)),
+            None => None,
+        };
+        let overlay = match lower {
+            Some(lower) => Some(Overlay::root(OverlayMount::new(
+                writable.try_clone()?,
+                lower,
+                quota.clone(),
+            ))),
+            None => None,
+        };
+
+        let mut dir = Dir::new(
+            writable,
+            dir_perms,
+            file_perms,
+            open_mode,
+            self.allow_blocking_current_thread,
+        );
+        dir.overlay = overlay;
+        dir.quota = quota;
+        self.preopens.push((dir, guest_path.as_ref().to_owned()));
+        Ok(self)
+    }
+
     /// Set the generator for the `wasi:random/random` number generator to the
     /// custom generator specified.
     ///
@@ -397,6 +516,46 @@ impl WasiCtxBuilder {
     /// By default the host's monotonic clock is used.
     pub fn monotonic_clock(&mut self, clock: impl HostMonotonicClock + 'static) -> &mut Self {
         self.monotonic_clock = Box::new(clock);
+        self.manual_clock = None;
+        self
+    }
+
+    /// Configures clocks and random number generators to be fully
+    /// deterministic, for reproducible execution of components.
+    ///
+    /// This installs `clock` as both `wasi:clocks/wall-clock` and
+    /// `wasi:clocks/monotonic-clock`. Time only passes when the embedder calls
+    /// [`ManualClock::advance`] on a clone of `clock`, and timers created by
+    /// the guest only fire once the clock is advanced past their deadline.
+    ///
+    /// All of `wasi:random/random`, `wasi:random/insecure` and
+    /// `wasi:random/insecure-seed` are derived from `seed`, so two contexts
+    /// configured with the same seed observe the same random data.
+    ///
+    /// This is intended for replay debugging and fuzzing. Guest code relying
+    /// on `wasi:random/random` for security will be compromised, as its
+    /// output is entirely predictable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use wasmtime_wasi::{ManualClock, WasiCtxBuilder};
+    ///
+    /// let clock = ManualClock::new(Duration::from_secs(1_700_000_000));
+    /// let wasi = WasiCtxBuilder::new().deterministic(42, &clock).build();
+    ///
+    /// // ... later, after running some guest code ...
+    /// clock.advance(Duration::from_millis(10));
+    /// ```
+    pub fn deterministic(&mut self, seed: u64, clock: &ManualClock) -> &mut Self {
+        let mut rng = cap_rand::rngs::StdRng::seed_from_u64(seed);
+        self.insecure_random = Box::new(cap_rand::rngs::StdRng::from_seed(rng.r#gen()));
+        self.insecure_random_seed = rng.r#gen();
+        self.random = Box::new(rng);
+        self.wall_clock = Box::new(clock.clone());
+        self.monotonic_clock = Box::new(clock.clone());
+        self.manual_clock = Some(clock.clone());
         self
     }
 
@@ -452,6 +611,32 @@ impl WasiCtxBuilder {
         self
     }
 
+    /// Allow UDP sockets to join multicast groups.
+    ///
+    /// This is disabled by default. When enabled each group joined is
+    /// additionally checked with the [`socket_addr_check`] using
+    /// [`SocketAddrUse::UdpJoinMulticastGroup`].
+    ///
+    /// Joining groups is part of the `udp-multicast` unstable feature of
+    /// `wasi:sockets/udp`, which must also be enabled in the `LinkOptions`
+    /// used to add WASI to a linker.
+    ///
+    /// [`socket_addr_check`]: WasiCtxBuilder::socket_addr_check
+    pub fn allow_udp_multicast(&mut self, enable: bool) -> &mut Self {
+        self.allowed_network_uses.udp_multicast = enable;
+        self
+    }
+
+    /// Allow UDP sockets to share their local address with other sockets
+    /// through the `set-reuse-address` method of `wasi:sockets/udp`.
+    ///
+    /// This is disabled by default since it lets guests receive datagrams
+    /// sent to addresses that other programs on the host are bound to.
+    pub fn allow_udp_reuse_address(&mut self, enable: bool) -> &mut Self {
+        self.allowed_network_uses.udp_reuse_address = enable;
+        self
+    }
+
     /// Uses the configured context so far to construct the final [`WasiCtx`].
     ///
     /// Note that each `WasiCtxBuilder` can only be used to "build" once, and
@@ -471,19 +656,25 @@ impl WasiCtxBuilder {
             stderr,
             env,
             args,
-            preopens,
+            mut preopens,
             socket_addr_check,
             random,
             insecure_random,
             insecure_random_seed,
             wall_clock,
             monotonic_clock,
+            manual_clock,
             allowed_network_uses,
             allow_blocking_current_thread,
+            tasks,
             built: _,
         } = mem::replace(self, Self::new());
         self.built = true;
 
+        for (dir, _) in preopens.iter_mut() {
+            dir.tasks = Some(tasks.clone());
+        }
+
         WasiCtx {
             stdin,
             stdout,
@@ -497,8 +688,10 @@ impl WasiCtxBuilder {
             insecure_random_seed,
             wall_clock,
             monotonic_clock,
+            manual_clock,
             allowed_network_uses,
             allow_blocking_current_thread,
+            tasks,
         }
     }
 
@@ -575,6 +768,7 @@ pub struct WasiCtx {
     pub(crate) insecure_random_seed: u128,
     pub(crate) wall_clock: Box<dyn HostWallClock + Send>,
     pub(crate) monotonic_clock: Box<dyn HostMonotonicClock + Send>,
+    pub(crate) manual_clock: Option<ManualClock>,
     pub(crate) env: Vec<(String, String)>,
     pub(crate) args: Vec<String>,
     pub(crate) preopens: Vec<(Dir, String)>,
@@ -584,6 +778,7 @@ pub struct WasiCtx {
     pub(crate) socket_addr_check: SocketAddrCheck,
     pub(crate) allowed_network_uses: AllowedNetworkUses,
     pub(crate) allow_blocking_current_thread: bool,
+    pub(crate) tasks: TaskSupervisor,
 }
 
 impl WasiCtx {
@@ -591,12 +786,31 @@ impl WasiCtx {
     pub fn builder() -> WasiCtxBuilder {
         WasiCtxBuilder::new()
     }
+
+    /// Returns the supervisor of the background tasks spawned on behalf of
+    /// this context.
+    ///
+    /// This can be used to enumerate and cancel the tasks, or to change their
+    /// limit configured with [`WasiCtxBuilder::max_background_tasks`].
+    pub fn tasks(&self) -> &TaskSupervisor {
+        &self.tasks
+    }
+}
+
+impl Drop for WasiCtx {
+    fn drop(&mut self) {
+        // Tasks may hold clones of the supervisor themselves, so they're
+        // cancelled explicitly rather than once the last clone goes away.
+        self.tasks.cancel_all();
+    }
 }
 
 pub struct AllowedNetworkUses {
     pub ip_name_lookup: bool,
     pub udp: bool,
     pub tcp: bool,
+    pub udp_multicast: bool,
+    pub udp_reuse_address: bool,
 }
 
 impl Default for AllowedNetworkUses {
@@ -605,6 +819,8 @@ impl Default for AllowedNetworkUses {
             ip_name_lookup: false,
             udp: true,
             tcp: true,
+            udp_multicast: false,
+            udp_reuse_address: false,
         }
     }
 }
@@ -621,6 +837,28 @@ impl AllowedNetworkUses {
         Ok(())
     }
 
+    pub(crate) fn check_allowed_udp_multicast(&self) -> std::io::Result<()> {
+        if !self.udp_multicast {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                "UDP multicast is not allowed",
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn check_allowed_udp_reuse_address(&self) -> std::io::Result<()> {
+        if !self.udp_reuse_address {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                "UDP address reuse is not allowed",
+            ));
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn check_allowed_tcp(&self) -> std::io::Result<()> {
         if !self.tcp {
             return Err(std::io::Error::new(
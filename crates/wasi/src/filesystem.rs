@@ -1,14 +1,21 @@
 use crate::bindings::filesystem::types;
-use crate::runtime::{spawn_blocking, AbortOnDropJoinHandle};
+use crate::runtime::{spawn_blocking, AbortOnDropJoinHandle, TaskSupervisor};
 use crate::{
     HostInputStream, HostOutputStream, StreamError, StreamResult, Subscribe, TrappableError,
 };
 use anyhow::anyhow;
 use bytes::{Bytes, BytesMut};
+use std::fmt;
 use std::io;
 use std::mem;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
+mod overlay;
+
+pub(crate) use self::overlay::{Overlay, OverlayMount};
+
 pub type FsResult<T> = Result<T, FsError>;
 
 pub type FsError = TrappableError<types::ErrorCode>;
@@ -95,6 +102,12 @@ pub struct File {
     /// doesn't presently provide a cross-platform equivalent of reading the
     /// oflags back out using fcntl.
     pub open_mode: OpenMode,
+    /// The quota of the mount the file was opened in, which growing the file
+    /// is charged to.
+    pub(crate) quota: Option<Arc<Quota>>,
+    /// The supervisor of the store the file belongs to, which tracks the
+    /// blocking tasks spawned to access it.
+    pub(crate) tasks: Option<TaskSupervisor>,
 
     allow_blocking_current_thread: bool,
 }
@@ -110,6 +123,8 @@ impl File {
             file: Arc::new(file),
             perms,
             open_mode,
+            quota: None,
+            tasks: None,
             allow_blocking_current_thread,
         }
     }
@@ -128,24 +143,39 @@ impl File {
     /// - [`crate::WasiCtxBuilder::allow_blocking_current_thread`]
     /// - [Poor performance of wasmtime file I/O maybe because tokio](https://github.com/bytecodealliance/wasmtime/issues/7973)
     /// - [Implement opt-in for enabling WASI to block the current thread](https://github.com/bytecodealliance/wasmtime/pull/8190)
-    pub(crate) async fn run_blocking<F, R>(&self, body: F) -> R
+    ///
+    /// Fails if the task can't be spawned because the store's
+    /// [`TaskSupervisor`] is at its limit.
+    pub(crate) async fn run_blocking<F, T, E>(&self, body: F) -> Result<T, E>
     where
-        F: FnOnce(&cap_std::fs::File) -> R + Send + 'static,
-        R: Send + 'static,
+        F: FnOnce(&cap_std::fs::File) -> Result<T, E> + Send + 'static,
+        T: Send + 'static,
+        E: From<io::Error> + Send + 'static,
     {
         match self.as_blocking_file() {
             Some(file) => body(file),
-            None => self.spawn_blocking(body).await,
+            None => self.spawn_blocking("filesystem", body)?.await,
         }
     }
 
-    pub(crate) fn spawn_blocking<F, R>(&self, body: F) -> AbortOnDropJoinHandle<R>
+    /// Spawns the blocking `body` function on a background thread, tracked
+    /// under `name` by the store's [`TaskSupervisor`] if there is one.
+    pub(crate) fn spawn_blocking<F, R>(
+        &self,
+        name: &'static str,
+        body: F,
+    ) -> io::Result<AbortOnDropJoinHandle<R>>
     where
         F: FnOnce(&cap_std::fs::File) -> R + Send + 'static,
         R: Send + 'static,
     {
         let f = self.file.clone();
-        spawn_blocking(move || body(&f))
+        match &self.tasks {
+            Some(tasks) => tasks
+                .spawn_blocking(name, move || body(&f))
+                .map_err(io::Error::other),
+            None => Ok(spawn_blocking(move || body(&f))),
+        }
     }
 
     /// Returns `Some` when the current thread is allowed to block in filesystem
@@ -200,6 +230,16 @@ pub struct Dir {
     /// doesn't presently provide a cross-platform equivalent of reading the
     /// oflags back out using fcntl.
     pub open_mode: OpenMode,
+    /// The read-only layer beneath this directory, if it's part of an
+    /// overlay mount. In that case `dir` is the directory at the same path in
+    /// the writable layer.
+    pub(crate) overlay: Option<Overlay>,
+    /// The quota of the mount this directory is part of.
+    pub(crate) quota: Option<Arc<Quota>>,
+    /// The supervisor of the store the directory belongs to, which tracks the
+    /// blocking tasks spawned to access it and is inherited by everything
+    /// opened under it.
+    pub(crate) tasks: Option<TaskSupervisor>,
 
     allow_blocking_current_thread: bool,
 }
@@ -217,10 +257,30 @@ impl Dir {
             perms,
             file_perms,
             open_mode,
+            overlay: None,
+            quota: None,
+            tasks: None,
             allow_blocking_current_thread,
         }
     }
 
+    /// Whether entries can be renamed or linked between this directory and
+    /// `other`, which isn't the case if they're in different overlay mounts
+    /// or are subject to different quotas.
+    pub(crate) fn same_mount(&self, other: &Dir) -> bool {
+        let same_overlay = match (&self.overlay, &other.overlay) {
+            (Some(a), Some(b)) => Arc::ptr_eq(&a.mount, &b.mount),
+            (None, None) => true,
+            _ => false,
+        };
+        let same_quota = match (&self.quota, &other.quota) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        same_overlay && same_quota
+    }
+
     /// Execute the blocking `body` function.
     ///
     /// Depending on how the WasiCtx was configured, the body may either be:
@@ -235,20 +295,242 @@ impl Dir {
     /// - [`crate::WasiCtxBuilder::allow_blocking_current_thread`]
     /// - [Poor performance of wasmtime file I/O maybe because tokio](https://github.com/bytecodealliance/wasmtime/issues/7973)
     /// - [Implement opt-in for enabling WASI to block the current thread](https://github.com/bytecodealliance/wasmtime/pull/8190)
-    pub(crate) async fn run_blocking<F, R>(&self, body: F) -> R
+    ///
+    /// Fails if the task can't be spawned because the store's
+    /// [`TaskSupervisor`] is at its limit.
+    pub(crate) async fn run_blocking<F, T, E>(&self, body: F) -> Result<T, E>
     where
-        F: FnOnce(&cap_std::fs::Dir) -> R + Send + 'static,
-        R: Send + 'static,
+        F: FnOnce(&cap_std::fs::Dir) -> Result<T, E> + Send + 'static,
+        T: Send + 'static,
+        E: From<io::Error> + Send + 'static,
     {
         if self.allow_blocking_current_thread {
-            body(&self.dir)
+            return body(&self.dir);
+        }
+        let d = self.dir.clone();
+        let task = match &self.tasks {
+            Some(tasks) => tasks
+                .spawn_blocking("filesystem", move || body(&d))
+                .map_err(io::Error::other)?,
+            None => spawn_blocking(move || body(&d)),
+        };
+        task.await
+    }
+}
+
+/// Options for making a host directory available to the guest with
+/// [`WasiCtxBuilder::mount`](crate::WasiCtxBuilder::mount).
+///
+/// By default a mount gives the guest full access to the host directory.
+///
+/// # Examples
+///
+/// ```
+/// use wasmtime_wasi::{MountOptions, WasiCtxBuilder};
+///
+/// # fn main() {}
+/// # fn foo() -> wasmtime::Result<()> {
+/// let mut wasi = WasiCtxBuilder::new();
+///
+/// // Make `./image` available in the guest as `/`, with writes going to
+/// // `./scratch` instead, which may grow to at most 64 MiB.
+/// wasi.mount(
+///     "./image",
+///     "/",
+///     MountOptions::new().overlay("./scratch").quota(64 << 20),
+/// )?;
+///
+/// // Make `./config` available in the guest as `/etc`, without allowing any
+/// // changes to it.
+/// wasi.mount("./config", "/etc", MountOptions::new().read_only(true))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MountOptions {
+    pub(crate) read_only: bool,
+    pub(crate) overlay: Option<PathBuf>,
+    pub(crate) quota: Option<u64>,
+}
+
+impl MountOptions {
+    /// Creates options for a writable mount without an overlay or a quota.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Configures whether the guest is prevented from modifying the mount.
+    ///
+    /// Read-only mounts can't have an overlay.
+    pub fn read_only(mut self, enable: bool) -> Self {
+        self.read_only = enable;
+        self
+    }
+
+    /// Configures a copy-on-write overlay for the mount: the mounted host
+    /// directory becomes an immutable base layer, and all changes the guest
+    /// makes are written to the host directory `upper` instead.
+    ///
+    /// The guest sees the contents of `upper` merged over those of the base
+    /// layer. Opening a file of the base layer for writing first copies it to
+    /// `upper`, and removed entries of the base layer are hidden for the
+    /// lifetime of the [`WasiCtx`](crate::WasiCtx) without touching the base
+    /// layer. Directories of the base layer are created in `upper` when they
+    /// are opened. Renaming directories of the base layer is not supported
+    /// and fails with `error-code::cross-device`, like in overlay filesystems
+    /// on Linux. Paths are resolved lexically, so `..` always refers to the
+    /// parent directory in the mount even after a symbolic link.
+    pub fn overlay(mut self, upper: impl Into<PathBuf>) -> Self {
+        self.overlay = Some(upper.into());
+        self
+    }
+
+    /// Limits the total size of the files in the mount, or in its overlay if
+    /// it has one, to `bytes`.
+    ///
+    /// Writes which would grow the files beyond the limit fail with
+    /// `error-code::insufficient-space`. Files already in the directory when
+    /// it's mounted count towards the limit, and changes made to the
+    /// directory by the host or by other contexts are not tracked. Entries
+    /// can't be renamed or linked between a mount with a quota and other
+    /// directories.
+    pub fn quota(mut self, bytes: u64) -> Self {
+        self.quota = Some(bytes);
+        self
+    }
+}
+
+/// A limit on the total size of the files in a mount, see
+/// [`MountOptions::quota`].
+pub(crate) struct Quota {
+    limit: u64,
+    used: AtomicU64,
+}
+
+impl Quota {
+    /// Creates a quota of `limit` bytes for the files in `dir`, which count
+    /// towards the limit.
+    pub(crate) fn new(limit: u64, dir: &cap_std::fs::Dir) -> io::Result<Quota> {
+        fn dir_size(dir: &cap_std::fs::Dir) -> io::Result<u64> {
+            let mut size = 0;
+            for entry in dir.entries()? {
+                let entry = entry?;
+                let file_type = entry.file_type()?;
+                if file_type.is_dir() {
+                    size += dir_size(&entry.open_dir()?)?;
+                } else if file_type.is_file() {
+                    size += entry.metadata()?.len();
+                }
+            }
+            Ok(size)
+        }
+
+        Ok(Quota {
+            limit,
+            used: AtomicU64::new(dir_size(dir)?),
+        })
+    }
+
+    /// Charges `bytes` to the quota, failing if that would exceed its limit.
+    pub(crate) fn reserve(&self, bytes: u64) -> io::Result<()> {
+        self.used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                used.checked_add(bytes).filter(|used| *used <= self.limit)
+            })
+            .map(drop)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, QuotaExceeded))
+    }
+
+    /// Returns `bytes` previously charged to the quota.
+    pub(crate) fn release(&self, bytes: u64) {
+        let _ = self
+            .used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                Some(used.saturating_sub(bytes))
+            });
+    }
+
+    /// Returns the number of bytes which removing the entry with metadata
+    /// `meta` frees.
+    pub(crate) fn freed_by_removing(meta: &cap_std::fs::Metadata) -> u64 {
+        use cap_fs_ext::MetadataExt;
+
+        if meta.is_file() && meta.nlink() <= 1 {
+            meta.len()
         } else {
-            let d = self.dir.clone();
-            spawn_blocking(move || body(&d)).await
+            0
         }
     }
 }
 
+/// The error for operations which would exceed a [`Quota`].
+#[derive(Debug)]
+struct QuotaExceeded;
+
+impl fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the size quota of the directory was exceeded")
+    }
+}
+
+impl std::error::Error for QuotaExceeded {}
+
+/// Returns whether `err` was caused by exceeding a [`Quota`].
+pub(crate) fn is_quota_exceeded(err: &io::Error) -> bool {
+    err.get_ref().is_some_and(|err| err.is::<QuotaExceeded>())
+}
+
+/// Performs `write` on `file`, which writes `len` bytes at `offset`, or at the
+/// end of the file if `offset` is `None`, charging any growth of the file to
+/// `quota`.
+pub(crate) fn write_within_quota(
+    quota: Option<&Quota>,
+    file: &cap_std::fs::File,
+    offset: Option<u64>,
+    len: usize,
+    write: impl FnOnce(&cap_std::fs::File) -> io::Result<usize>,
+) -> io::Result<usize> {
+    let Some(quota) = quota else {
+        return write(file);
+    };
+    let size = file.metadata()?.len();
+    let end = offset.unwrap_or(size).saturating_add(len as u64);
+    let reserved = end.saturating_sub(size);
+    quota.reserve(reserved)?;
+    let result = write(file);
+    // Writes may be short or fail, so only the actual growth stays charged.
+    let grown = match file.metadata() {
+        Ok(meta) => meta.len().saturating_sub(size),
+        Err(_) => reserved,
+    };
+    quota.release(reserved.saturating_sub(grown));
+    result
+}
+
+/// Sets the size of `file` to `size`, charging any growth of the file to
+/// `quota`.
+pub(crate) fn set_len_within_quota(
+    quota: Option<&Quota>,
+    file: &cap_std::fs::File,
+    size: u64,
+) -> io::Result<()> {
+    let Some(quota) = quota else {
+        return file.set_len(size);
+    };
+    let old = file.metadata()?.len();
+    if size > old {
+        quota.reserve(size - old)?;
+        if let Err(e) = file.set_len(size) {
+            quota.release(size - old);
+            return Err(e);
+        }
+    } else {
+        file.set_len(size)?;
+        quota.release(old - size);
+    }
+    Ok(())
+}
+
 pub struct FileInputStream {
     file: File,
     position: u64,
@@ -289,6 +571,18 @@ impl FileInputStream {
         }
     }
 
+    /// Starts `read` on a background task, failing the stream if the task
+    /// can't be spawned.
+    fn spawn_read<F>(&self, read: F) -> ReadState
+    where
+        F: FnOnce(&cap_std::fs::File) -> ReadState + Send + 'static,
+    {
+        match self.file.spawn_blocking("file-read", read) {
+            Ok(task) => ReadState::Waiting(task),
+            Err(e) => ReadState::Error(e),
+        }
+    }
+
     /// Wait for existing background task to finish, without starting any new background reads.
     async fn wait_ready(&mut self) {
         match &mut self.state {
@@ -309,10 +603,7 @@ impl HostInputStream for FileInputStream {
                 }
 
                 let p = self.position;
-                self.state = ReadState::Waiting(
-                    self.file
-                        .spawn_blocking(move |f| Self::blocking_read(f, p, size)),
-                );
+                self.state = self.spawn_read(move |f| Self::blocking_read(f, p, size));
                 Ok(Bytes::new())
             }
             ReadState::DataAvailable(b) => {
@@ -340,10 +631,13 @@ impl HostInputStream for FileInputStream {
         // Before we defer to the regular `read`, make sure it has data ready to go:
         if let ReadState::Idle = self.state {
             let p = self.position;
-            self.state = self
-                .file
-                .run_blocking(move |f| Self::blocking_read(f, p, size))
-                .await;
+            self.state = match self.file.as_blocking_file() {
+                Some(f) => Self::blocking_read(f, p, size),
+                None => match self.spawn_read(move |f| Self::blocking_read(f, p, size)) {
+                    ReadState::Waiting(task) => task.await,
+                    state => state,
+                },
+            };
         }
 
         self.read(size)
@@ -376,10 +670,7 @@ impl Subscribe for FileInputStream {
 
             const DEFAULT_READ_SIZE: usize = 4096;
             let p = self.position;
-            self.state = ReadState::Waiting(
-                self.file
-                    .spawn_blocking(move |f| Self::blocking_read(f, p, DEFAULT_READ_SIZE)),
-            );
+            self.state = self.spawn_read(move |f| Self::blocking_read(f, p, DEFAULT_READ_SIZE));
         }
 
         self.wait_ready().await
@@ -427,12 +718,17 @@ impl FileOutputStream {
 
     fn blocking_write(
         file: &cap_std::fs::File,
+        quota: Option<&Quota>,
         mut buf: Bytes,
         mode: FileOutputMode,
     ) -> io::Result<usize> {
         use system_interface::fs::FileIoExt;
 
-        match mode {
+        let offset = match mode {
+            FileOutputMode::Position(p) => Some(p),
+            FileOutputMode::Append => None,
+        };
+        write_within_quota(quota, file, offset, buf.len(), |file| match mode {
             FileOutputMode::Position(mut p) => {
                 let mut total = 0;
                 loop {
@@ -459,7 +755,7 @@ impl FileOutputStream {
                 }
                 Ok(total)
             }
-        }
+        })
     }
 }
 
@@ -481,10 +777,14 @@ impl HostOutputStream for FileOutputStream {
         }
 
         let m = self.mode;
-        self.state = OutputState::Waiting(
-            self.file
-                .spawn_blocking(move |f| Self::blocking_write(f, buf, m)),
-        );
+        let quota = self.file.quota.clone();
+        let task = self.file.spawn_blocking("file-write", move |f| {
+            Self::blocking_write(f, quota.as_deref(), buf, m)
+        });
+        self.state = match task {
+            Ok(task) => OutputState::Waiting(task),
+            Err(e) => OutputState::Error(e),
+        };
         Ok(())
     }
     /// Specialized blocking_* variant to bypass tokio's task spawning & joining
@@ -503,9 +803,10 @@ impl HostOutputStream for FileOutputStream {
         }
 
         let m = self.mode;
+        let quota = self.file.quota.clone();
         match self
             .file
-            .run_blocking(move |f| Self::blocking_write(f, buf, m))
+            .run_blocking(move |f| Self::blocking_write(f, quota.as_deref(), buf, m))
             .await
         {
             Ok(nwritten) => {
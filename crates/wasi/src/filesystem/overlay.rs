@@ -0,0 +1,410 @@
+//! Copy-on-write overlay mounts, see [`MountOptions::overlay`].
+//!
+//! [`MountOptions::overlay`]: crate::MountOptions::overlay
+
+use super::{FsResult, Quota};
+use crate::bindings::filesystem::types::ErrorCode;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// The layers of an overlay mount.
+///
+/// All paths taken by the methods here are relative to the root of the
+/// mount, see [`Overlay::at`].
+pub(crate) struct OverlayMount {
+    /// The writable layer, which all changes are made in.
+    upper: cap_std::fs::Dir,
+    /// The read-only base layer.
+    lower: cap_std::fs::Dir,
+    /// Paths removed from the mount, at and below which the base layer is
+    /// hidden.
+    whiteouts: Mutex<HashSet<PathBuf>>,
+    /// The quota of the mount, which copying files to the writable layer is
+    /// charged to.
+    quota: Option<Arc<Quota>>,
+}
+
+/// A directory in an overlay mount.
+#[derive(Clone)]
+pub(crate) struct Overlay {
+    pub(crate) mount: Arc<OverlayMount>,
+    /// The path of the directory relative to the root of the mount.
+    pub(crate) path: PathBuf,
+}
+
+/// The layer an entry of an overlay mount is in.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Layer {
+    Upper,
+    Lower,
+    Missing,
+}
+
+impl Overlay {
+    /// Returns the root directory of `mount`.
+    pub(crate) fn root(mount: OverlayMount) -> Overlay {
+        Overlay {
+            mount: Arc::new(mount),
+            path: PathBuf::new(),
+        }
+    }
+
+    /// Returns the entry at `path` relative to this directory.
+    ///
+    /// Paths are resolved lexically, and may not leave the mount.
+    pub(crate) fn at(&self, path: &str) -> FsResult<Overlay> {
+        let mut resolved = self.path.clone();
+        for component in Path::new(path).components() {
+            match component {
+                Component::Normal(name) => resolved.push(name),
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    if !resolved.pop() {
+                        return Err(ErrorCode::NotPermitted.into());
+                    }
+                }
+                Component::RootDir | Component::Prefix(_) => {
+                    return Err(ErrorCode::NotPermitted.into())
+                }
+            }
+        }
+        Ok(Overlay {
+            mount: self.mount.clone(),
+            path: resolved,
+        })
+    }
+}
+
+/// Returns `path` in the form accepted by `cap_std`, which doesn't accept
+/// empty paths for the root of a directory.
+fn at(path: &Path) -> &Path {
+    if path.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        path
+    }
+}
+
+fn symlink(dir: &cap_std::fs::Dir, original: &Path, link: &Path) -> io::Result<()> {
+    // On windows, Dir.symlink is provided by DirExt
+    #[cfg(windows)]
+    use cap_fs_ext::DirExt;
+
+    dir.symlink(original, link)
+}
+
+impl OverlayMount {
+    /// Creates a mount of `upper` over the read-only `lower`.
+    pub(crate) fn new(
+        upper: cap_std::fs::Dir,
+        lower: cap_std::fs::Dir,
+        quota: Option<Arc<Quota>>,
+    ) -> OverlayMount {
+        OverlayMount {
+            upper,
+            lower,
+            whiteouts: Mutex::new(HashSet::new()),
+            quota,
+        }
+    }
+
+    /// Whether the base layer is hidden at `path`.
+    fn hidden(&self, path: &Path) -> bool {
+        let whiteouts = self.whiteouts.lock().unwrap();
+        path.ancestors().any(|p| whiteouts.contains(p))
+    }
+
+    fn whiteout(&self, path: &Path) {
+        self.whiteouts.lock().unwrap().insert(path.to_path_buf());
+    }
+
+    /// Whether the base layer has a visible entry at `path`.
+    fn in_lower(&self, path: &Path) -> bool {
+        !self.hidden(path) && self.lower.symlink_metadata(at(path)).is_ok()
+    }
+
+    fn layer(&self, path: &Path) -> Layer {
+        if self.upper.symlink_metadata(at(path)).is_ok() {
+            Layer::Upper
+        } else if self.in_lower(path) {
+            Layer::Lower
+        } else {
+            Layer::Missing
+        }
+    }
+
+    /// Returns the layer which the entry at `path` is in.
+    fn layer_dir(&self, path: &Path) -> FsResult<&cap_std::fs::Dir> {
+        match self.layer(path) {
+            Layer::Upper => Ok(&self.upper),
+            Layer::Lower => Ok(&self.lower),
+            Layer::Missing => Err(ErrorCode::NoEntry.into()),
+        }
+    }
+
+    /// Whether the directory at `path` has entries of the base layer which
+    /// aren't hidden.
+    fn lower_children_visible(&self, path: &Path) -> bool {
+        if self.hidden(path) {
+            return false;
+        }
+        match self.lower.read_dir(at(path)) {
+            Ok(entries) => entries
+                .filter_map(Result::ok)
+                .any(|entry| !self.hidden(&path.join(entry.file_name()))),
+            Err(_) => false,
+        }
+    }
+
+    /// Copies the entry at `path`, and the directories containing it, from
+    /// the base layer to the writable layer, unless it's already there.
+    ///
+    /// Directories are copied without their contents.
+    fn copy_up(&self, path: &Path) -> FsResult<()> {
+        if self.upper.symlink_metadata(at(path)).is_ok() {
+            return Ok(());
+        }
+        self.copy_up_parent(path)?;
+        if self.hidden(path) {
+            return Err(ErrorCode::NoEntry.into());
+        }
+
+        let meta = self.lower.symlink_metadata(path)?;
+        if meta.is_dir() {
+            self.upper.create_dir(path)?;
+        } else if meta.is_symlink() {
+            symlink(&self.upper, &self.lower.read_link(path)?, path)?;
+        } else {
+            let size = meta.len();
+            if let Some(quota) = &self.quota {
+                quota.reserve(size)?;
+            }
+            let copy = || -> io::Result<()> {
+                let mut src = self.lower.open(path)?;
+                let mut dst = self.upper.create(path)?;
+                io::copy(&mut src, &mut dst)?;
+                Ok(())
+            };
+            if let Err(e) = copy() {
+                if let Some(quota) = &self.quota {
+                    quota.release(size);
+                }
+                let _ = self.upper.remove_file(path);
+                return Err(e.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Copies the directories containing `path` to the writable layer.
+    fn copy_up_parent(&self, path: &Path) -> FsResult<()> {
+        match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => self.copy_up(parent),
+            _ => Ok(()),
+        }
+    }
+
+    /// Opens `path` with `opts`, copying it to the writable layer first if
+    /// it's a directory or if `write` is set. The containing directories are
+    /// copied if `create` is set.
+    pub(crate) fn open(
+        &self,
+        path: &Path,
+        opts: &cap_std::fs::OpenOptions,
+        create: bool,
+        write: bool,
+    ) -> FsResult<cap_std::fs::File> {
+        match self.layer(path) {
+            Layer::Lower => {
+                let is_dir = self
+                    .lower
+                    .metadata(path)
+                    .map(|meta| meta.is_dir())
+                    .unwrap_or(false);
+                if !write && !is_dir {
+                    return Ok(self.lower.open_with(path, opts)?);
+                }
+                self.copy_up(path)?;
+            }
+            Layer::Missing if create => self.copy_up_parent(path)?,
+            Layer::Upper | Layer::Missing => {}
+        }
+        Ok(self.upper.open_with(at(path), opts)?)
+    }
+
+    pub(crate) fn metadata(&self, path: &Path, follow: bool) -> FsResult<cap_std::fs::Metadata> {
+        let dir = self.layer_dir(path)?;
+        if follow {
+            Ok(dir.metadata(at(path))?)
+        } else {
+            Ok(dir.symlink_metadata(at(path))?)
+        }
+    }
+
+    pub(crate) fn read_link(&self, path: &Path) -> FsResult<PathBuf> {
+        Ok(self.layer_dir(path)?.read_link(path)?)
+    }
+
+    /// Returns the names and types of the entries of the directory at `path`,
+    /// merged from both layers.
+    pub(crate) fn read_dir(&self, path: &Path) -> FsResult<Vec<(OsString, cap_std::fs::FileType)>> {
+        let mut entries = Vec::new();
+        let mut names = HashSet::new();
+        for entry in self.upper.read_dir(at(path))? {
+            let entry = entry?;
+            names.insert(entry.file_name());
+            entries.push((entry.file_name(), entry.metadata()?.file_type()));
+        }
+        if self.hidden(path) {
+            return Ok(entries);
+        }
+        if let Ok(lower) = self.lower.read_dir(at(path)) {
+            for entry in lower {
+                let entry = entry?;
+                let name = entry.file_name();
+                if names.contains(&name) || self.hidden(&path.join(&name)) {
+                    continue;
+                }
+                entries.push((name, entry.metadata()?.file_type()));
+            }
+        }
+        Ok(entries)
+    }
+
+    pub(crate) fn create_dir(&self, path: &Path) -> FsResult<()> {
+        if self.layer(path) != Layer::Missing {
+            return Err(ErrorCode::Exist.into());
+        }
+        self.copy_up_parent(path)?;
+        Ok(self.upper.create_dir(path)?)
+    }
+
+    pub(crate) fn remove_file(&self, path: &Path) -> FsResult<()> {
+        use cap_fs_ext::DirExt;
+
+        match self.layer(path) {
+            Layer::Upper => {
+                let meta = self.upper.symlink_metadata(path)?;
+                self.upper.remove_file_or_symlink(path)?;
+                if let Some(quota) = &self.quota {
+                    quota.release(Quota::freed_by_removing(&meta));
+                }
+            }
+            Layer::Lower => {
+                if self.lower.symlink_metadata(path)?.is_dir() {
+                    return Err(ErrorCode::IsDirectory.into());
+                }
+            }
+            Layer::Missing => return Err(ErrorCode::NoEntry.into()),
+        }
+        if self.in_lower(path) {
+            self.whiteout(path);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn remove_dir(&self, path: &Path) -> FsResult<()> {
+        let layer = self.layer(path);
+        if layer == Layer::Missing {
+            return Err(ErrorCode::NoEntry.into());
+        }
+        if self.lower_children_visible(path) {
+            return Err(ErrorCode::NotEmpty.into());
+        }
+        if layer == Layer::Upper {
+            self.upper.remove_dir(path)?;
+        } else if !self.lower.symlink_metadata(path)?.is_dir() {
+            return Err(ErrorCode::NotDirectory.into());
+        }
+        if self.in_lower(path) {
+            self.whiteout(path);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn rename(&self, old: &Path, new: &Path) -> FsResult<()> {
+        let old_is_dir = match self.layer(old) {
+            Layer::Upper => self.upper.symlink_metadata(old)?.is_dir(),
+            Layer::Lower => self.lower.symlink_metadata(old)?.is_dir(),
+            Layer::Missing => return Err(ErrorCode::NoEntry.into()),
+        };
+        // Moving the contents of a directory in the base layer would require
+        // copying all of them, which overlay filesystems don't do either.
+        if old_is_dir && self.lower_children_visible(old) {
+            return Err(ErrorCode::CrossDevice.into());
+        }
+        if self.layer(new) == Layer::Lower {
+            let new_is_dir = self.lower.symlink_metadata(new)?.is_dir();
+            if new_is_dir && !old_is_dir {
+                return Err(ErrorCode::IsDirectory.into());
+            }
+            if !new_is_dir && old_is_dir {
+                return Err(ErrorCode::NotDirectory.into());
+            }
+        }
+        if old_is_dir && self.lower_children_visible(new) {
+            return Err(ErrorCode::NotEmpty.into());
+        }
+
+        self.copy_up(old)?;
+        self.copy_up_parent(new)?;
+        let replaced = self.upper.symlink_metadata(new).ok();
+        self.upper.rename(old, &self.upper, new)?;
+        if let (Some(quota), Some(meta)) = (&self.quota, replaced) {
+            quota.release(Quota::freed_by_removing(&meta));
+        }
+        if self.in_lower(old) {
+            self.whiteout(old);
+        }
+        if self.in_lower(new) {
+            self.whiteout(new);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn hard_link(&self, old: &Path, new: &Path) -> FsResult<()> {
+        if self.layer(new) != Layer::Missing {
+            return Err(ErrorCode::Exist.into());
+        }
+        match self.layer(old) {
+            Layer::Lower => self.copy_up(old)?,
+            Layer::Upper => {}
+            Layer::Missing => return Err(ErrorCode::NoEntry.into()),
+        }
+        self.copy_up_parent(new)?;
+        Ok(self.upper.hard_link(old, &self.upper, new)?)
+    }
+
+    pub(crate) fn symlink(&self, original: &str, link: &Path) -> FsResult<()> {
+        if self.layer(link) != Layer::Missing {
+            return Err(ErrorCode::Exist.into());
+        }
+        self.copy_up_parent(link)?;
+        Ok(symlink(&self.upper, Path::new(original), link)?)
+    }
+
+    pub(crate) fn set_times(
+        &self,
+        path: &Path,
+        follow: bool,
+        atim: Option<cap_fs_ext::SystemTimeSpec>,
+        mtim: Option<cap_fs_ext::SystemTimeSpec>,
+    ) -> FsResult<()> {
+        use cap_fs_ext::DirExt;
+
+        match self.layer(path) {
+            Layer::Lower => self.copy_up(path)?,
+            Layer::Upper => {}
+            Layer::Missing => return Err(ErrorCode::NoEntry.into()),
+        }
+        if follow {
+            self.upper.set_times(at(path), atim, mtim)?;
+        } else {
+            self.upper.set_symlink_times(at(path), atim, mtim)?;
+        }
+        Ok(())
+    }
+}
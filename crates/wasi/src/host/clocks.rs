@@ -64,6 +64,14 @@ fn subscribe_to_duration(
     subscribe(table, sleep)
 }
 
+fn subscribe_to_deadline(
+    table: &mut wasmtime::component::ResourceTable,
+    deadline: Deadline,
+) -> anyhow::Result<Resource<Pollable>> {
+    let deadline = table.push(deadline)?;
+    subscribe(table, deadline)
+}
+
 impl<T> monotonic_clock::Host for WasiImpl<T>
 where
     T: WasiView,
@@ -77,6 +85,10 @@ where
     }
 
     fn subscribe_instant(&mut self, when: Instant) -> anyhow::Result<Resource<Pollable>> {
+        if let Some(clock) = self.ctx().manual_clock.clone() {
+            let deadline = Deadline::Manual(clock.subscribe(), when);
+            return subscribe_to_deadline(self.table(), deadline);
+        }
         let clock_now = self.ctx().monotonic_clock.now();
         let duration = if when > clock_now {
             Duration::from_nanos(when - clock_now)
@@ -87,6 +99,13 @@ where
     }
 
     fn subscribe_duration(&mut self, duration: WasiDuration) -> anyhow::Result<Resource<Pollable>> {
+        if let Some(clock) = self.ctx().manual_clock.clone() {
+            let deadline = match clock.elapsed().checked_add(duration) {
+                Some(when) => Deadline::Manual(clock.subscribe(), when),
+                None => Deadline::Never,
+            };
+            return subscribe_to_deadline(self.table(), deadline);
+        }
         subscribe_to_duration(&mut self.table(), Duration::from_nanos(duration))
     }
 }
@@ -94,6 +113,9 @@ where
 enum Deadline {
     Past,
     Instant(tokio::time::Instant),
+    /// A deadline on a `ManualClock`, ready once the clock has been advanced
+    /// to at least the given number of nanoseconds.
+    Manual(tokio::sync::watch::Receiver<u64>, u64),
     Never,
 }
 
@@ -103,6 +125,13 @@ impl Subscribe for Deadline {
         match self {
             Deadline::Past => {}
             Deadline::Instant(instant) => tokio::time::sleep_until(*instant).await,
+            Deadline::Manual(now, when) => {
+                let when = *when;
+                if now.wait_for(|now| *now >= when).await.is_err() {
+                    // The clock can no longer be advanced.
+                    std::future::pending().await
+                }
+            }
             Deadline::Never => std::future::pending().await,
         }
     }
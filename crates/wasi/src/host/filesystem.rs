@@ -5,7 +5,8 @@ use crate::bindings::filesystem::types::{
 };
 use crate::bindings::io::streams::{InputStream, OutputStream};
 use crate::filesystem::{
-    Descriptor, Dir, File, FileInputStream, FileOutputStream, OpenMode, ReaddirIterator,
+    is_quota_exceeded, set_len_within_quota, write_within_quota, Descriptor, Dir, File,
+    FileInputStream, FileOutputStream, OpenMode, Quota, ReaddirIterator,
 };
 use crate::{DirPerms, FilePerms, FsError, FsResult, IoView, WasiImpl, WasiView};
 use anyhow::Context;
@@ -184,7 +185,9 @@ where
         if !f.perms.contains(FilePerms::WRITE) {
             Err(ErrorCode::NotPermitted)?;
         }
-        f.run_blocking(move |f| f.set_len(size)).await?;
+        let quota = f.quota.clone();
+        f.run_blocking(move |f| set_len_within_quota(quota.as_deref(), f, size))
+            .await?;
         Ok(())
     }
 
@@ -235,15 +238,15 @@ where
             return Err(ErrorCode::NotPermitted.into());
         }
 
-        let (mut buffer, r) = f
+        let (mut buffer, bytes_read) = f
             .run_blocking(move |f| {
                 let mut buffer = vec![0; len.try_into().unwrap_or(usize::MAX)];
                 let r = f.read_vectored_at(&mut [IoSliceMut::new(&mut buffer)], offset);
-                (buffer, r)
+                r.map(|n| (buffer, n))
             })
-            .await;
+            .await?;
 
-        let (bytes_read, state) = match r? {
+        let (bytes_read, state) = match bytes_read {
             0 => (0, true),
             n => (n, false),
         };
@@ -272,8 +275,13 @@ where
             return Err(ErrorCode::NotPermitted.into());
         }
 
+        let quota = f.quota.clone();
         let bytes_written = f
-            .run_blocking(move |f| f.write_vectored_at(&[IoSlice::new(&buf)], offset))
+            .run_blocking(move |f| {
+                write_within_quota(quota.as_deref(), f, Some(offset), buf.len(), |f| {
+                    f.write_vectored_at(&[IoSlice::new(&buf)], offset)
+                })
+            })
             .await?;
 
         Ok(types::Filesize::try_from(bytes_written).expect("usize fits in Filesize"))
@@ -289,6 +297,22 @@ where
             return Err(ErrorCode::NotPermitted.into());
         }
 
+        if let Some(overlay) = &d.overlay {
+            let overlay = overlay.clone();
+            let entries = d
+                .run_blocking(move |_| overlay.mount.read_dir(&overlay.path))
+                .await?
+                .into_iter()
+                .map(|(name, file_type)| -> FsResult<types::DirectoryEntry> {
+                    let type_ = descriptortype_from(file_type);
+                    let name = name
+                        .into_string()
+                        .map_err(|_| ErrorCode::IllegalByteSequence)?;
+                    Ok(types::DirectoryEntry { type_, name })
+                });
+            return Ok(table.push(ReaddirIterator::new(entries))?);
+        }
+
         enum ReaddirError {
             Io(std::io::Error),
             IllegalSequence,
@@ -381,7 +405,14 @@ where
         if !d.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
-        d.run_blocking(move |d| d.create_dir(&path)).await?;
+        match &d.overlay {
+            Some(overlay) => {
+                let target = overlay.at(&path)?;
+                d.run_blocking(move |_| target.mount.create_dir(&target.path))
+                    .await?
+            }
+            None => d.run_blocking(move |d| d.create_dir(&path)).await?,
+        }
         Ok(())
     }
 
@@ -413,7 +444,12 @@ where
             return Err(ErrorCode::NotPermitted.into());
         }
 
-        let meta = if symlink_follow(path_flags) {
+        let meta = if let Some(overlay) = &d.overlay {
+            let target = overlay.at(&path)?;
+            let follow = symlink_follow(path_flags);
+            d.run_blocking(move |_| target.mount.metadata(&target.path, follow))
+                .await?
+        } else if symlink_follow(path_flags) {
             d.run_blocking(move |d| d.metadata(&path)).await?
         } else {
             d.run_blocking(move |d| d.symlink_metadata(&path)).await?
@@ -438,7 +474,19 @@ where
         }
         let atim = systemtimespec_from(atim)?;
         let mtim = systemtimespec_from(mtim)?;
-        if symlink_follow(path_flags) {
+        if let Some(overlay) = &d.overlay {
+            let target = overlay.at(&path)?;
+            let follow = symlink_follow(path_flags);
+            d.run_blocking(move |_| {
+                target.mount.set_times(
+                    &target.path,
+                    follow,
+                    atim.map(cap_fs_ext::SystemTimeSpec::from_std),
+                    mtim.map(cap_fs_ext::SystemTimeSpec::from_std),
+                )
+            })
+            .await?;
+        } else if symlink_follow(path_flags) {
             d.run_blocking(move |d| {
                 d.set_times(
                     &path,
@@ -481,6 +529,17 @@ where
         if symlink_follow(old_path_flags) {
             return Err(ErrorCode::Invalid.into());
         }
+        if !old_dir.same_mount(new_dir) {
+            return Err(ErrorCode::CrossDevice.into());
+        }
+        if let (Some(old_overlay), Some(new_overlay)) = (&old_dir.overlay, &new_dir.overlay) {
+            let old = old_overlay.at(&old_path)?;
+            let new = new_overlay.at(&new_path)?;
+            old_dir
+                .run_blocking(move |_| old.mount.hard_link(&old.path, &new.path))
+                .await?;
+            return Ok(());
+        }
         let new_dir_handle = std::sync::Arc::clone(&new_dir.dir);
         old_dir
             .run_blocking(move |d| d.hard_link(&old_path, &new_dir_handle, &new_path))
@@ -583,6 +642,17 @@ where
             Err(ErrorCode::NotPermitted)?;
         }
 
+        // With a quota, files are truncated after opening them so that their
+        // previous size can be released.
+        let quota = d.quota.clone();
+        let truncate = oflags.contains(OpenFlags::TRUNCATE) && quota.is_some();
+        if truncate {
+            opts.truncate(false);
+        }
+        let write = open_mode.contains(OpenMode::WRITE) || oflags.contains(OpenFlags::TRUNCATE);
+        let overlay = d.overlay.as_ref().map(|o| o.at(&path)).transpose()?;
+        let target = overlay.clone();
+
         // Represents each possible outcome from the spawn_blocking operation.
         // This makes sure we don't have to give spawn_blocking any way to
         // manipulate the table.
@@ -593,8 +663,11 @@ where
         }
 
         let opened = d
-            .run_blocking::<_, std::io::Result<OpenResult>>(move |d| {
-                let mut opened = d.open_with(&path, &opts)?;
+            .run_blocking::<_, OpenResult, FsError>(move |d| {
+                let mut opened = match &target {
+                    Some(target) => target.mount.open(&target.path, &opts, create, write)?,
+                    None => d.open_with(&path, &opts)?,
+                };
                 if opened.metadata()?.is_dir() {
                     Ok(OpenResult::Dir(cap_std::fs::Dir::from_std_file(
                         opened.into_std(),
@@ -602,6 +675,9 @@ where
                 } else if oflags.contains(OpenFlags::DIRECTORY) {
                     Ok(OpenResult::NotDir)
                 } else {
+                    if truncate {
+                        set_len_within_quota(quota.as_deref(), &opened, 0)?;
+                    }
                     // FIXME cap-std needs a nonblocking open option so that files reads and writes
                     // are nonblocking. Instead we set it after opening here:
                     let set_fd_flags = opened.new_set_fd_flags(FdFlags::NONBLOCK)?;
@@ -612,20 +688,27 @@ where
             .await?;
 
         match opened {
-            OpenResult::Dir(dir) => Ok(table.push(Descriptor::Dir(Dir::new(
-                dir,
-                d.perms,
-                d.file_perms,
-                open_mode,
-                allow_blocking_current_thread,
-            )))?),
-
-            OpenResult::File(file) => Ok(table.push(Descriptor::File(File::new(
-                file,
-                d.file_perms,
-                open_mode,
-                allow_blocking_current_thread,
-            )))?),
+            OpenResult::Dir(dir) => {
+                let mut dir = Dir::new(
+                    dir,
+                    d.perms,
+                    d.file_perms,
+                    open_mode,
+                    allow_blocking_current_thread,
+                );
+                dir.overlay = overlay;
+                dir.quota = d.quota.clone();
+                dir.tasks = d.tasks.clone();
+                Ok(table.push(Descriptor::Dir(dir))?)
+            }
+
+            OpenResult::File(file) => {
+                let mut file =
+                    File::new(file, d.file_perms, open_mode, allow_blocking_current_thread);
+                file.quota = d.quota.clone();
+                file.tasks = d.tasks.clone();
+                Ok(table.push(Descriptor::File(file))?)
+            }
 
             OpenResult::NotDir => Err(ErrorCode::NotDirectory.into()),
         }
@@ -654,7 +737,14 @@ where
         if !d.perms.contains(DirPerms::READ) {
             return Err(ErrorCode::NotPermitted.into());
         }
-        let link = d.run_blocking(move |d| d.read_link(&path)).await?;
+        let link = match &d.overlay {
+            Some(overlay) => {
+                let target = overlay.at(&path)?;
+                d.run_blocking(move |_| target.mount.read_link(&target.path))
+                    .await?
+            }
+            None => d.run_blocking(move |d| d.read_link(&path)).await?,
+        };
         Ok(link
             .into_os_string()
             .into_string()
@@ -671,7 +761,14 @@ where
         if !d.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
-        Ok(d.run_blocking(move |d| d.remove_dir(&path)).await?)
+        match &d.overlay {
+            Some(overlay) => {
+                let target = overlay.at(&path)?;
+                d.run_blocking(move |_| target.mount.remove_dir(&target.path))
+                    .await
+            }
+            None => Ok(d.run_blocking(move |d| d.remove_dir(&path)).await?),
+        }
     }
 
     async fn rename_at(
@@ -690,9 +787,30 @@ where
         if !new_dir.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
+        if !old_dir.same_mount(new_dir) {
+            return Err(ErrorCode::CrossDevice.into());
+        }
+        if let (Some(old_overlay), Some(new_overlay)) = (&old_dir.overlay, &new_dir.overlay) {
+            let old = old_overlay.at(&old_path)?;
+            let new = new_overlay.at(&new_path)?;
+            return old_dir
+                .run_blocking(move |_| old.mount.rename(&old.path, &new.path))
+                .await;
+        }
         let new_dir_handle = std::sync::Arc::clone(&new_dir.dir);
+        let quota = old_dir.quota.clone();
         Ok(old_dir
-            .run_blocking(move |d| d.rename(&old_path, &new_dir_handle, &new_path))
+            .run_blocking(move |d| {
+                let replaced = match &quota {
+                    Some(_) => new_dir_handle.symlink_metadata(&new_path).ok(),
+                    None => None,
+                };
+                d.rename(&old_path, &new_dir_handle, &new_path)?;
+                if let (Some(quota), Some(meta)) = (&quota, replaced) {
+                    quota.release(Quota::freed_by_removing(&meta));
+                }
+                Ok::<_, std::io::Error>(())
+            })
             .await?)
     }
 
@@ -711,8 +829,16 @@ where
         if !d.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
-        Ok(d.run_blocking(move |d| d.symlink(&src_path, &dest_path))
-            .await?)
+        match &d.overlay {
+            Some(overlay) => {
+                let target = overlay.at(&dest_path)?;
+                d.run_blocking(move |_| target.mount.symlink(&src_path, &target.path))
+                    .await
+            }
+            None => Ok(d
+                .run_blocking(move |d| d.symlink(&src_path, &dest_path))
+                .await?),
+        }
     }
 
     async fn unlink_file_at(
@@ -727,8 +853,25 @@ where
         if !d.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
-        Ok(d.run_blocking(move |d| d.remove_file_or_symlink(&path))
-            .await?)
+        if let Some(overlay) = &d.overlay {
+            let target = overlay.at(&path)?;
+            return d
+                .run_blocking(move |_| target.mount.remove_file(&target.path))
+                .await;
+        }
+        let quota = d.quota.clone();
+        Ok(d.run_blocking(move |d| {
+            let freed = match &quota {
+                Some(_) => Quota::freed_by_removing(&d.symlink_metadata(&path)?),
+                None => 0,
+            };
+            d.remove_file_or_symlink(&path)?;
+            if let Some(quota) = &quota {
+                quota.release(freed);
+            }
+            Ok::<_, std::io::Error>(())
+        })
+        .await?)
     }
 
     fn read_via_stream(
@@ -839,6 +982,14 @@ where
         let table = self.table();
         let d = table.get(&fd)?.dir()?;
         // No permissions check on metadata: if dir opened, allowed to stat it
+        if let Some(overlay) = &d.overlay {
+            let target = overlay.at(&path)?;
+            let follow = symlink_follow(path_flags);
+            let meta = d
+                .run_blocking(move |_| target.mount.metadata(&target.path, follow))
+                .await?;
+            return Ok(calculate_metadata_hash(&meta));
+        }
         let meta = d
             .run_blocking(move |d| {
                 if symlink_follow(path_flags) {
@@ -986,6 +1137,9 @@ impl From<std::io::Error> for ErrorCode {
 
 impl<'a> From<&'a std::io::Error> for ErrorCode {
     fn from(err: &'a std::io::Error) -> ErrorCode {
+        if is_quota_exceeded(err) {
+            return ErrorCode::InsufficientSpace;
+        }
         match from_raw_os_error(err.raw_os_error()) {
             Some(errno) => errno,
             None => {
@@ -4,6 +4,7 @@ use crate::{
     poll::subscribe,
     IoImpl, IoView, Pollable, StreamError, StreamResult,
 };
+use std::io::IoSliceMut;
 use wasmtime::component::Resource;
 
 impl<T> error::Host for IoImpl<T> where T: IoView {}
@@ -170,6 +171,18 @@ where
     }
 }
 
+/// Reads of up to this many bytes are performed with `read_vectored` into a
+/// buffer of the requested size, so that streams which can fill it from
+/// several chunks at once, such as HTTP bodies, do so. Larger reads use `read`
+/// to avoid allocating more than a stream has available.
+const MAX_VECTORED_READ: usize = 64 * 1024;
+
+fn is_vectored_read(len: usize) -> bool {
+    // Zero-length reads still go through `read` so that they report whether
+    // the stream is closed.
+    0 < len && len <= MAX_VECTORED_READ
+}
+
 impl<T> streams::HostInputStream for IoImpl<T>
 where
     T: IoView,
@@ -181,7 +194,14 @@ where
 
     fn read(&mut self, stream: Resource<InputStream>, len: u64) -> StreamResult<Vec<u8>> {
         let len = len.try_into().unwrap_or(usize::MAX);
-        let bytes = self.table().get_mut(&stream)?.read(len)?;
+        let stream = self.table().get_mut(&stream)?;
+        if is_vectored_read(len) {
+            let mut buf = vec![0; len];
+            let n = stream.read_vectored(&mut [IoSliceMut::new(&mut buf)])?;
+            buf.truncate(n);
+            return Ok(buf);
+        }
+        let bytes = stream.read(len)?;
         debug_assert!(bytes.len() <= len);
         Ok(bytes.into())
     }
@@ -192,7 +212,16 @@ where
         len: u64,
     ) -> StreamResult<Vec<u8>> {
         let len = len.try_into().unwrap_or(usize::MAX);
-        let bytes = self.table().get_mut(&stream)?.blocking_read(len).await?;
+        let stream = self.table().get_mut(&stream)?;
+        if is_vectored_read(len) {
+            let mut buf = vec![0; len];
+            let n = stream
+                .blocking_read_vectored(&mut [IoSliceMut::new(&mut buf)])
+                .await?;
+            buf.truncate(n);
+            return Ok(buf);
+        }
+        let bytes = stream.blocking_read(len).await?;
         debug_assert!(bytes.len() <= len);
         Ok(bytes.into())
     }
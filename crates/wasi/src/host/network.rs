@@ -143,6 +143,15 @@ impl From<std::net::IpAddr> for IpAddress {
     }
 }
 
+impl From<IpAddress> for std::net::IpAddr {
+    fn from(addr: IpAddress) -> Self {
+        match addr {
+            IpAddress::Ipv4(v4) => Self::V4(to_ipv4_addr(v4)),
+            IpAddress::Ipv6(v6) => Self::V6(to_ipv6_addr(v6)),
+        }
+    }
+}
+
 impl From<IpSocketAddress> for std::net::SocketAddr {
     fn from(addr: IpSocketAddress) -> Self {
         match addr {
@@ -242,7 +251,7 @@ impl From<cap_net_ext::AddressFamily> for IpAddressFamily {
 
 pub(crate) mod util {
     use std::io;
-    use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
     use std::time::Duration;
 
     use crate::network::SocketAddressFamily;
@@ -495,6 +504,48 @@ pub(crate) mod util {
         }
     }
 
+    pub fn join_multicast_group<Fd: AsFd>(
+        sockfd: Fd,
+        group: &IpAddr,
+        interface: u32,
+    ) -> rustix::io::Result<()> {
+        match group {
+            // IPv4 selects interfaces by address rather than by index, so only
+            // the default interface is supported.
+            IpAddr::V4(group) => match interface {
+                0 => sockopt::set_ip_add_membership(sockfd, group, &Ipv4Addr::UNSPECIFIED),
+                _ => Err(Errno::OPNOTSUPP),
+            },
+            IpAddr::V6(group) => sockopt::set_ipv6_add_membership(sockfd, group, interface),
+        }
+    }
+
+    pub fn leave_multicast_group<Fd: AsFd>(
+        sockfd: Fd,
+        group: &IpAddr,
+        interface: u32,
+    ) -> rustix::io::Result<()> {
+        match group {
+            IpAddr::V4(group) => match interface {
+                0 => sockopt::set_ip_drop_membership(sockfd, group, &Ipv4Addr::UNSPECIFIED),
+                _ => Err(Errno::OPNOTSUPP),
+            },
+            IpAddr::V6(group) => sockopt::set_ipv6_drop_membership(sockfd, group, interface),
+        }
+    }
+
+    pub fn get_ip_multicast_ttl<Fd: AsFd>(sockfd: Fd) -> rustix::io::Result<u8> {
+        sockopt::get_ip_multicast_ttl(sockfd)?
+            .try_into()
+            .map_err(|_| Errno::OPNOTSUPP)
+    }
+
+    pub fn get_ipv6_multicast_hops<Fd: AsFd>(sockfd: Fd) -> rustix::io::Result<u8> {
+        sockopt::get_ipv6_multicast_hops(sockfd)?
+            .try_into()
+            .map_err(|_| Errno::OPNOTSUPP)
+    }
+
     fn normalize_get_buffer_size(value: usize) -> usize {
         if cfg!(target_os = "linux") {
             // Linux doubles the value passed to setsockopt to allow space for bookkeeping overhead.
@@ -11,7 +11,8 @@ where
         &mut self,
         address_family: IpAddressFamily,
     ) -> SocketResult<Resource<TcpSocket>> {
-        let socket = TcpSocket::new(address_family.into())?;
+        let mut socket = TcpSocket::new(address_family.into())?;
+        socket.set_task_supervisor(self.ctx().tasks.clone());
         let socket = self.table().push(socket)?;
         Ok(socket)
     }
@@ -2,7 +2,7 @@ use crate::host::network::util;
 use crate::network::{SocketAddrUse, SocketAddressFamily};
 use crate::{
     bindings::{
-        sockets::network::{ErrorCode, IpAddressFamily, IpSocketAddress, Network},
+        sockets::network::{ErrorCode, IpAddress, IpAddressFamily, IpSocketAddress, Network},
         sockets::udp,
     },
     udp::{IncomingDatagramStream, OutgoingDatagramStream, SendState, UdpState},
@@ -13,7 +13,8 @@ use anyhow::anyhow;
 use async_trait::async_trait;
 use io_lifetimes::AsSocketlike;
 use rustix::io::Errno;
-use std::net::SocketAddr;
+use rustix::net::sockopt;
+use std::net::{IpAddr, SocketAddr};
 use tokio::io::Interest;
 use wasmtime::component::Resource;
 
@@ -287,6 +288,147 @@ where
         Ok(())
     }
 
+    async fn join_multicast_group(
+        &mut self,
+        this: Resource<udp::UdpSocket>,
+        group: IpAddress,
+        interface_index: u32,
+    ) -> SocketResult<()> {
+        self.ctx().allowed_network_uses.check_allowed_udp()?;
+        self.ctx()
+            .allowed_network_uses
+            .check_allowed_udp_multicast()?;
+        let table = self.table();
+        let socket = table.get(&this)?;
+
+        match socket.udp_state {
+            UdpState::Bound | UdpState::Connected => {}
+            UdpState::BindStarted => return Err(ErrorCode::ConcurrencyConflict.into()),
+            UdpState::Default => return Err(ErrorCode::InvalidState.into()),
+        }
+
+        let group = validate_multicast_group(group, &socket.family)?;
+        let Some(check) = socket.socket_addr_check.as_ref() else {
+            return Err(ErrorCode::InvalidState.into());
+        };
+        check
+            .check(
+                SocketAddr::new(group, 0),
+                SocketAddrUse::UdpJoinMulticastGroup,
+            )
+            .await?;
+
+        util::join_multicast_group(socket.udp_socket(), &group, interface_index)?;
+        Ok(())
+    }
+
+    fn leave_multicast_group(
+        &mut self,
+        this: Resource<udp::UdpSocket>,
+        group: IpAddress,
+        interface_index: u32,
+    ) -> SocketResult<()> {
+        let table = self.table();
+        let socket = table.get(&this)?;
+
+        let group = validate_multicast_group(group, &socket.family)?;
+        util::leave_multicast_group(socket.udp_socket(), &group, interface_index).map_err(
+            |error| match error {
+                // The socket is not a member of the group.
+                Errno::ADDRNOTAVAIL => ErrorCode::InvalidArgument,
+                _ => ErrorCode::from(error),
+            },
+        )?;
+        Ok(())
+    }
+
+    fn multicast_hop_limit(&mut self, this: Resource<udp::UdpSocket>) -> SocketResult<u8> {
+        let table = self.table();
+        let socket = table.get(&this)?;
+
+        let ttl = match socket.family {
+            SocketAddressFamily::Ipv4 => util::get_ip_multicast_ttl(socket.udp_socket())?,
+            SocketAddressFamily::Ipv6 => util::get_ipv6_multicast_hops(socket.udp_socket())?,
+        };
+
+        Ok(ttl)
+    }
+
+    fn set_multicast_hop_limit(
+        &mut self,
+        this: Resource<udp::UdpSocket>,
+        value: u8,
+    ) -> SocketResult<()> {
+        let table = self.table();
+        let socket = table.get(&this)?;
+        let fd = socket.udp_socket();
+
+        match socket.family {
+            SocketAddressFamily::Ipv4 => sockopt::set_ip_multicast_ttl(fd, value.into())?,
+            SocketAddressFamily::Ipv6 => sockopt::set_ipv6_multicast_hops(fd, value.into())?,
+        }
+
+        Ok(())
+    }
+
+    fn multicast_loopback(&mut self, this: Resource<udp::UdpSocket>) -> SocketResult<bool> {
+        let table = self.table();
+        let socket = table.get(&this)?;
+        let fd = socket.udp_socket();
+
+        let value = match socket.family {
+            SocketAddressFamily::Ipv4 => sockopt::get_ip_multicast_loop(fd)?,
+            SocketAddressFamily::Ipv6 => sockopt::get_ipv6_multicast_loop(fd)?,
+        };
+
+        Ok(value)
+    }
+
+    fn set_multicast_loopback(
+        &mut self,
+        this: Resource<udp::UdpSocket>,
+        value: bool,
+    ) -> SocketResult<()> {
+        let table = self.table();
+        let socket = table.get(&this)?;
+        let fd = socket.udp_socket();
+
+        match socket.family {
+            SocketAddressFamily::Ipv4 => sockopt::set_ip_multicast_loop(fd, value)?,
+            SocketAddressFamily::Ipv6 => sockopt::set_ipv6_multicast_loop(fd, value)?,
+        }
+
+        Ok(())
+    }
+
+    fn reuse_address(&mut self, this: Resource<udp::UdpSocket>) -> SocketResult<bool> {
+        let table = self.table();
+        let socket = table.get(&this)?;
+
+        Ok(sockopt::get_socket_reuseaddr(socket.udp_socket())?)
+    }
+
+    fn set_reuse_address(
+        &mut self,
+        this: Resource<udp::UdpSocket>,
+        value: bool,
+    ) -> SocketResult<()> {
+        self.ctx()
+            .allowed_network_uses
+            .check_allowed_udp_reuse_address()?;
+        let table = self.table();
+        let socket = table.get(&this)?;
+
+        match socket.udp_state {
+            UdpState::Default => {}
+            UdpState::BindStarted => return Err(ErrorCode::ConcurrencyConflict.into()),
+            UdpState::Bound | UdpState::Connected => return Err(ErrorCode::InvalidState.into()),
+        }
+
+        sockopt::set_socket_reuseaddr(socket.udp_socket(), value)?;
+        Ok(())
+    }
+
     fn subscribe(&mut self, this: Resource<udp::UdpSocket>) -> anyhow::Result<Resource<Pollable>> {
         crate::poll::subscribe(self.table(), this)
     }
@@ -542,6 +684,19 @@ impl Subscribe for OutgoingDatagramStream {
     }
 }
 
+/// Checks that `group` is a multicast address of the socket's address family.
+fn validate_multicast_group(
+    group: IpAddress,
+    family: &SocketAddressFamily,
+) -> SocketResult<IpAddr> {
+    let group = IpAddr::from(group);
+    if !group.is_multicast() {
+        return Err(ErrorCode::InvalidArgument.into());
+    }
+    util::validate_address_family(&SocketAddr::new(group, 0), family)?;
+    Ok(group)
+}
+
 pub mod sync {
     use wasmtime::component::Resource;
 
@@ -559,8 +714,8 @@ pub mod sync {
             },
             sync::sockets::udp::{
                 self, HostIncomingDatagramStream, HostOutgoingDatagramStream, HostUdpSocket,
-                IncomingDatagram, IpAddressFamily, IpSocketAddress, OutgoingDatagram, Pollable,
-                UdpSocket,
+                IncomingDatagram, IpAddress, IpAddressFamily, IpSocketAddress, OutgoingDatagram,
+                Pollable, UdpSocket,
             },
         },
         runtime::in_tokio,
@@ -659,6 +814,62 @@ pub mod sync {
             AsyncHostUdpSocket::set_send_buffer_size(self, self_, value)
         }
 
+        fn join_multicast_group(
+            &mut self,
+            self_: Resource<UdpSocket>,
+            group: IpAddress,
+            interface_index: u32,
+        ) -> Result<(), SocketError> {
+            in_tokio(async {
+                AsyncHostUdpSocket::join_multicast_group(self, self_, group, interface_index).await
+            })
+        }
+
+        fn leave_multicast_group(
+            &mut self,
+            self_: Resource<UdpSocket>,
+            group: IpAddress,
+            interface_index: u32,
+        ) -> Result<(), SocketError> {
+            AsyncHostUdpSocket::leave_multicast_group(self, self_, group, interface_index)
+        }
+
+        fn multicast_hop_limit(&mut self, self_: Resource<UdpSocket>) -> Result<u8, SocketError> {
+            AsyncHostUdpSocket::multicast_hop_limit(self, self_)
+        }
+
+        fn set_multicast_hop_limit(
+            &mut self,
+            self_: Resource<UdpSocket>,
+            value: u8,
+        ) -> Result<(), SocketError> {
+            AsyncHostUdpSocket::set_multicast_hop_limit(self, self_, value)
+        }
+
+        fn multicast_loopback(&mut self, self_: Resource<UdpSocket>) -> Result<bool, SocketError> {
+            AsyncHostUdpSocket::multicast_loopback(self, self_)
+        }
+
+        fn set_multicast_loopback(
+            &mut self,
+            self_: Resource<UdpSocket>,
+            value: bool,
+        ) -> Result<(), SocketError> {
+            AsyncHostUdpSocket::set_multicast_loopback(self, self_, value)
+        }
+
+        fn reuse_address(&mut self, self_: Resource<UdpSocket>) -> Result<bool, SocketError> {
+            AsyncHostUdpSocket::reuse_address(self, self_)
+        }
+
+        fn set_reuse_address(
+            &mut self,
+            self_: Resource<UdpSocket>,
+            value: bool,
+        ) -> Result<(), SocketError> {
+            AsyncHostUdpSocket::set_reuse_address(self, self_, value)
+        }
+
         fn subscribe(
             &mut self,
             self_: Resource<UdpSocket>,
@@ -2,7 +2,7 @@ use crate::bindings::sockets::ip_name_lookup::{Host, HostResolveAddressStream};
 use crate::bindings::sockets::network::{ErrorCode, IpAddress, Network};
 use crate::host::network::util;
 use crate::poll::{subscribe, Pollable, Subscribe};
-use crate::runtime::{spawn_blocking, AbortOnDropJoinHandle};
+use crate::runtime::AbortOnDropJoinHandle;
 use crate::{IoView, SocketError, WasiImpl, WasiView};
 use anyhow::Result;
 use std::mem;
@@ -10,6 +10,7 @@ use std::net::{Ipv6Addr, ToSocketAddrs};
 use std::pin::Pin;
 use std::str::FromStr;
 use std::vec;
+use tokio::task::JoinError;
 use wasmtime::component::Resource;
 
 use super::network::{from_ipv4_addr, from_ipv6_addr};
@@ -36,7 +37,11 @@ where
             return Err(ErrorCode::PermanentResolverFailure.into());
         }
 
-        let task = spawn_blocking(move || blocking_resolve(&host));
+        let task = self
+            .ctx()
+            .tasks
+            .spawn_blocking("ip-name-lookup", move || blocking_resolve(&host))
+            .map_err(SocketError::trap)?;
         let resource = self.table().push(ResolveAddressStream::Waiting(task))?;
         Ok(resource)
     }
@@ -54,9 +59,9 @@ where
         loop {
             match stream {
                 ResolveAddressStream::Waiting(future) => {
-                    match crate::runtime::poll_noop(Pin::new(future)) {
+                    match crate::runtime::poll_noop(Pin::new(&mut **future)) {
                         Some(result) => {
-                            *stream = ResolveAddressStream::Done(result.map(|v| v.into_iter()));
+                            *stream = ResolveAddressStream::Done(lookup_result(result));
                         }
                         None => return Err(ErrorCode::WouldBlock.into()),
                     }
@@ -87,11 +92,23 @@ where
 impl Subscribe for ResolveAddressStream {
     async fn ready(&mut self) {
         if let ResolveAddressStream::Waiting(future) = self {
-            *self = ResolveAddressStream::Done(future.await.map(|v| v.into_iter()));
+            *self = ResolveAddressStream::Done(lookup_result((&mut **future).await));
         }
     }
 }
 
+/// Unwraps the result of a lookup task, treating its cancellation through a
+/// [`TaskSupervisor`](crate::runtime::TaskSupervisor) as a failed lookup.
+fn lookup_result(
+    result: Result<Result<Vec<IpAddress>, SocketError>, JoinError>,
+) -> Result<vec::IntoIter<IpAddress>, SocketError> {
+    match result {
+        Ok(result) => result.map(|v| v.into_iter()),
+        Err(e) if e.is_cancelled() => Err(ErrorCode::TemporaryResolverFailure.into()),
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
 fn parse(name: &str) -> Result<url::Host, SocketError> {
     // `url::Host::parse` serves us two functions:
     // 1. validate the input is a valid domain name or IP,
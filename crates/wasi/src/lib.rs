@@ -185,6 +185,8 @@
 
 use wasmtime::component::Linker;
 
+#[cfg(feature = "component-model-async")]
+mod async_stream;
 pub mod bindings;
 mod clocks;
 mod ctx;
@@ -208,10 +210,10 @@ mod udp;
 mod view;
 mod write_stream;
 
-pub use self::clocks::{HostMonotonicClock, HostWallClock};
+pub use self::clocks::{HostMonotonicClock, HostWallClock, ManualClock};
 pub use self::ctx::{WasiCtx, WasiCtxBuilder};
 pub use self::error::{I32Exit, TrappableError};
-pub use self::filesystem::{DirPerms, FileInputStream, FilePerms, FsError, FsResult};
+pub use self::filesystem::{DirPerms, FileInputStream, FilePerms, FsError, FsResult, MountOptions};
 pub use self::network::{Network, SocketAddrUse, SocketError, SocketResult};
 pub use self::poll::{subscribe, ClosureFuture, MakeFuture, Pollable, PollableFuture, Subscribe};
 pub use self::random::{thread_rng, Deterministic};
@@ -326,7 +328,7 @@ pub fn add_to_linker_with_options_async<T: WasiView>(
     crate::bindings::cli::terminal_stderr::add_to_linker_get_host(l, closure)?;
     crate::bindings::sockets::tcp::add_to_linker_get_host(l, closure)?;
     crate::bindings::sockets::tcp_create_socket::add_to_linker_get_host(l, closure)?;
-    crate::bindings::sockets::udp::add_to_linker_get_host(l, closure)?;
+    crate::bindings::sockets::udp::add_to_linker_get_host(l, &options.into(), closure)?;
     crate::bindings::sockets::udp_create_socket::add_to_linker_get_host(l, closure)?;
     crate::bindings::sockets::instance_network::add_to_linker_get_host(l, closure)?;
     crate::bindings::sockets::network::add_to_linker_get_host(l, &options.into(), closure)?;
@@ -427,7 +429,7 @@ pub fn add_to_linker_with_options_sync<T: WasiView>(
     crate::bindings::cli::terminal_stderr::add_to_linker_get_host(l, closure)?;
     crate::bindings::sync::sockets::tcp::add_to_linker_get_host(l, closure)?;
     crate::bindings::sockets::tcp_create_socket::add_to_linker_get_host(l, closure)?;
-    crate::bindings::sync::sockets::udp::add_to_linker_get_host(l, closure)?;
+    crate::bindings::sync::sockets::udp::add_to_linker_get_host(l, &options.into(), closure)?;
     crate::bindings::sockets::udp_create_socket::add_to_linker_get_host(l, closure)?;
     crate::bindings::sockets::instance_network::add_to_linker_get_host(l, closure)?;
     crate::bindings::sockets::network::add_to_linker_get_host(l, &options.into(), closure)?;
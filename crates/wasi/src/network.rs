@@ -62,6 +62,10 @@ pub enum SocketAddrUse {
     UdpConnect,
     /// Sending datagram on non-connected UDP socket
     UdpOutgoingDatagram,
+    /// Joining a multicast group with a UDP socket
+    ///
+    /// The port of the address is always 0.
+    UdpJoinMulticastGroup,
 }
 
 pub type SocketResult<T> = Result<T, SocketError>;
@@ -8,10 +8,15 @@
 //! but the virtual pipes can be instantiated with any `Read` or `Write` type.
 //!
 use crate::poll::Subscribe;
+use crate::runtime::{AbortOnDropJoinHandle, TaskLimitExceeded, TaskSupervisor};
 use crate::{HostInputStream, HostOutputStream, StreamError};
 use anyhow::anyhow;
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
+use std::io::IoSliceMut;
+use std::pin::Pin;
 use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, ReadBuf};
 use tokio::sync::mpsc;
 
 pub use crate::write_stream::AsyncWriteStream;
@@ -45,6 +50,17 @@ impl HostInputStream for MemoryInputPipe {
         let read = buffer.split_to(size);
         Ok(read)
     }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> Result<usize, StreamError> {
+        let mut buffer = self.buffer.lock().unwrap();
+        if buffer.is_empty() {
+            return Err(StreamError::Closed);
+        }
+
+        let n = crate::stream::copy_to_slices(&buffer, bufs);
+        let _ = buffer.split_to(n);
+        Ok(n)
+    }
 }
 
 #[async_trait::async_trait]
@@ -113,33 +129,35 @@ pub struct AsyncReadStream {
     closed: bool,
     buffer: Option<Result<Bytes, StreamError>>,
     receiver: mpsc::Receiver<Result<Bytes, StreamError>>,
-    join_handle: Option<crate::runtime::AbortOnDropJoinHandle<()>>,
+    join_handle: Option<AbortOnDropJoinHandle<()>>,
 }
 
 impl AsyncReadStream {
     /// Create a [`AsyncReadStream`]. In order to use the [`HostInputStream`] impl
     /// provided by this struct, the argument must impl [`tokio::io::AsyncRead`].
-    pub fn new<T: tokio::io::AsyncRead + Send + Unpin + 'static>(mut reader: T) -> Self {
+    pub fn new<T: tokio::io::AsyncRead + Send + Unpin + 'static>(reader: T) -> Self {
         let (sender, receiver) = mpsc::channel(1);
-        let join_handle = crate::runtime::spawn(async move {
-            loop {
-                use tokio::io::AsyncReadExt;
-                let mut buf = bytes::BytesMut::with_capacity(4096);
-                let sent = match reader.read_buf(&mut buf).await {
-                    Ok(nbytes) if nbytes == 0 => sender.send(Err(StreamError::Closed)).await,
-                    Ok(_) => sender.send(Ok(buf.freeze())).await,
-                    Err(e) => {
-                        sender
-                            .send(Err(StreamError::LastOperationFailed(e.into())))
-                            .await
-                    }
-                };
-                if sent.is_err() {
-                    // no more receiver - stop trying to read
-                    break;
-                }
-            }
-        });
+        let join_handle = crate::runtime::spawn(Self::read_ahead(reader, sender));
+        Self::with_task(receiver, join_handle)
+    }
+
+    /// Same as [`AsyncReadStream::new`], but the task reading from `reader`
+    /// is tracked by `tasks`.
+    ///
+    /// Fails if `tasks` is already running as many tasks as its limit allows.
+    pub fn new_supervised<T: tokio::io::AsyncRead + Send + Unpin + 'static>(
+        reader: T,
+        tasks: &TaskSupervisor,
+    ) -> Result<Self, TaskLimitExceeded> {
+        let (sender, receiver) = mpsc::channel(1);
+        let join_handle = tasks.spawn("async-read-stream", Self::read_ahead(reader, sender))?;
+        Ok(Self::with_task(receiver, join_handle))
+    }
+
+    fn with_task(
+        receiver: mpsc::Receiver<Result<Bytes, StreamError>>,
+        join_handle: AbortOnDropJoinHandle<()>,
+    ) -> Self {
         AsyncReadStream {
             closed: false,
             buffer: None,
@@ -147,6 +165,29 @@ impl AsyncReadStream {
             join_handle: Some(join_handle),
         }
     }
+
+    async fn read_ahead<T: tokio::io::AsyncRead + Send + Unpin + 'static>(
+        mut reader: T,
+        sender: mpsc::Sender<Result<Bytes, StreamError>>,
+    ) {
+        loop {
+            use tokio::io::AsyncReadExt;
+            let mut buf = bytes::BytesMut::with_capacity(4096);
+            let sent = match reader.read_buf(&mut buf).await {
+                Ok(nbytes) if nbytes == 0 => sender.send(Err(StreamError::Closed)).await,
+                Ok(_) => sender.send(Ok(buf.freeze())).await,
+                Err(e) => {
+                    sender
+                        .send(Err(StreamError::LastOperationFailed(e.into())))
+                        .await
+                }
+            };
+            if sent.is_err() {
+                // no more receiver - stop trying to read
+                break;
+            }
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -192,6 +233,35 @@ impl HostInputStream for AsyncReadStream {
         }
     }
 
+    fn read_vectored(&mut self, mut bufs: &mut [IoSliceMut<'_>]) -> Result<usize, StreamError> {
+        // Keep copying out the chunks which have already been read ahead
+        // until either `bufs` is full or no more are available without
+        // blocking. An error after some bytes were read is saved for the next
+        // read.
+        let mut read = 0;
+        loop {
+            let size = bufs.iter().map(|buf| buf.len()).sum();
+            if size == 0 {
+                break;
+            }
+            let bytes = match self.read(size) {
+                Ok(bytes) => bytes,
+                Err(e) if read == 0 => return Err(e),
+                Err(e) => {
+                    self.buffer = Some(Err(e));
+                    break;
+                }
+            };
+            if bytes.is_empty() {
+                break;
+            }
+            let n = crate::stream::copy_to_slices(&bytes, bufs);
+            IoSliceMut::advance_slices(&mut bufs, n);
+            read += n;
+        }
+        Ok(read)
+    }
+
     async fn cancel(&mut self) {
         match self.join_handle.take() {
             Some(task) => _ = task.cancel().await,
@@ -214,6 +284,189 @@ impl Subscribe for AsyncReadStream {
     }
 }
 
+/// Provides a [`HostInputStream`] impl from a [`tokio::io::AsyncRead`] impl
+/// which reads directly into the buffers passed to
+/// [`HostInputStream::read_vectored`].
+///
+/// Unlike [`AsyncReadStream`] no background task is spawned to read ahead
+/// into host buffers. Instead `reader` is polled whenever bytes are requested,
+/// so that reads into guest memory, such as with WASI preview1's `fd_read`,
+/// don't need to be copied from an intermediate buffer.
+pub struct AsyncReadIntoStream<T> {
+    reader: T,
+    buffer: BytesMut,
+    closed: bool,
+    error: Option<StreamError>,
+}
+
+impl<T: AsyncRead + Send + Unpin + 'static> AsyncReadIntoStream<T> {
+    /// Create a [`AsyncReadIntoStream`] reading from `reader`.
+    pub fn new(reader: T) -> Self {
+        AsyncReadIntoStream {
+            reader,
+            buffer: BytesMut::new(),
+            closed: false,
+            error: None,
+        }
+    }
+
+    /// Returns the error or closure which ended the last read, if there are no
+    /// more buffered bytes.
+    fn take_end(&mut self) -> Option<StreamError> {
+        if !self.buffer.is_empty() {
+            return None;
+        }
+        if let Some(e) = self.error.take() {
+            self.closed = true;
+            return Some(e);
+        }
+        if self.closed {
+            return Some(StreamError::Closed);
+        }
+        None
+    }
+
+    /// Polls `reader` once, without blocking, to read into `buf`.
+    ///
+    /// Returns `None` if no bytes are available yet.
+    fn poll_read_into(&mut self, buf: &mut [u8]) -> Option<Result<usize, StreamError>> {
+        let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+        let mut buf = ReadBuf::new(buf);
+        match Pin::new(&mut self.reader).poll_read(&mut cx, &mut buf) {
+            Poll::Ready(Ok(())) => Some(Ok(buf.filled().len())),
+            Poll::Ready(Err(e)) => Some(Err(StreamError::LastOperationFailed(e.into()))),
+            Poll::Pending => None,
+        }
+    }
+
+    /// Records the result of a read from `reader` after `read` bytes were
+    /// already read, returning the total.
+    ///
+    /// Errors after some bytes were read are remembered and returned by the
+    /// next read instead.
+    fn finish_read(
+        &mut self,
+        read: usize,
+        result: Result<usize, StreamError>,
+    ) -> Result<usize, StreamError> {
+        match result {
+            Ok(0) => {
+                self.closed = true;
+                if read == 0 {
+                    return Err(StreamError::Closed);
+                }
+                Ok(read)
+            }
+            Ok(n) => Ok(read + n),
+            Err(e) if read == 0 => {
+                self.closed = true;
+                Err(e)
+            }
+            Err(e) => {
+                self.error = Some(e);
+                Ok(read)
+            }
+        }
+    }
+
+    /// Reads from `reader` into each of `bufs` in turn, without blocking, after
+    /// `read` bytes were already read.
+    fn read_into(
+        &mut self,
+        mut read: usize,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Result<usize, StreamError> {
+        for buf in bufs.iter_mut().filter(|buf| !buf.is_empty()) {
+            let len = buf.len();
+            match self.poll_read_into(buf) {
+                Some(Ok(n)) if n == len => read += n,
+                Some(result) => return self.finish_read(read, result),
+                None => break,
+            }
+        }
+        Ok(read)
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: AsyncRead + Send + Unpin + 'static> HostInputStream for AsyncReadIntoStream<T> {
+    fn read(&mut self, size: usize) -> Result<Bytes, StreamError> {
+        if let Some(e) = self.take_end() {
+            return Err(e);
+        }
+        if !self.buffer.is_empty() {
+            let len = self.buffer.len().min(size);
+            return Ok(self.buffer.split_to(len).freeze());
+        }
+        if size == 0 {
+            return Ok(Bytes::new());
+        }
+        let mut buf = vec![0; size];
+        match self.poll_read_into(&mut buf) {
+            Some(result) => {
+                let n = self.finish_read(0, result)?;
+                buf.truncate(n);
+                Ok(buf.into())
+            }
+            None => Ok(Bytes::new()),
+        }
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> Result<usize, StreamError> {
+        if let Some(e) = self.take_end() {
+            return Err(e);
+        }
+        if !self.buffer.is_empty() {
+            let n = crate::stream::copy_to_slices(&self.buffer, bufs);
+            let _ = self.buffer.split_to(n);
+            return Ok(n);
+        }
+        self.read_into(0, bufs)
+    }
+
+    async fn blocking_read_vectored(
+        &mut self,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Result<usize, StreamError> {
+        if let Some(e) = self.take_end() {
+            return Err(e);
+        }
+        if !self.buffer.is_empty() {
+            return self.read_vectored(bufs);
+        }
+        let Some(i) = bufs.iter().position(|buf| !buf.is_empty()) else {
+            return Ok(0);
+        };
+        let result = self
+            .reader
+            .read(&mut bufs[i])
+            .await
+            .map_err(|e| StreamError::LastOperationFailed(e.into()));
+        let n = self.finish_read(0, result)?;
+        if n < bufs[i].len() {
+            return Ok(n);
+        }
+        // Fill in the rest of the buffers with whatever else is available
+        // without blocking.
+        self.read_into(n, &mut bufs[i + 1..])
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: AsyncRead + Send + Unpin + 'static> Subscribe for AsyncReadIntoStream<T> {
+    async fn ready(&mut self) {
+        if !self.buffer.is_empty() || self.closed || self.error.is_some() {
+            return;
+        }
+        self.buffer.reserve(4096);
+        match self.reader.read_buf(&mut self.buffer).await {
+            Ok(0) => self.closed = true,
+            Ok(_) => {}
+            Err(e) => self.error = Some(StreamError::LastOperationFailed(e.into())),
+        }
+    }
+}
+
 /// An output stream that consumes all input written to it, and is always ready.
 #[derive(Copy, Clone)]
 pub struct SinkOutputStream;
@@ -464,6 +717,115 @@ mod test {
         assert!(matches!(reader.read(1), Err(StreamError::Closed)));
     }
 
+    #[test]
+    fn memory_input_pipe_vectored() {
+        let mut reader = MemoryInputPipe::new((0..10).collect::<Vec<u8>>());
+
+        let mut a = [0; 4];
+        let mut b = [0; 4];
+        let mut bufs = [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)];
+        assert_eq!(reader.read_vectored(&mut bufs).unwrap(), 8);
+        assert_eq!(a, [0, 1, 2, 3]);
+        assert_eq!(b, [4, 5, 6, 7]);
+
+        let mut bufs = [IoSliceMut::new(&mut a)];
+        assert_eq!(reader.read_vectored(&mut bufs).unwrap(), 2);
+        assert_eq!(a[..2], [8, 9]);
+        assert!(matches!(
+            reader.read_vectored(&mut bufs),
+            Err(StreamError::Closed)
+        ));
+    }
+
+    #[test_log::test(tokio::test(flavor = "multi_thread"))]
+    async fn read_stream_vectored() {
+        let (r, mut w) = simplex(1024);
+        let mut reader = AsyncReadStream::new(r);
+
+        w.write_all(&[1, 2, 3, 4, 5, 6]).await.unwrap();
+        resolves_immediately(reader.ready()).await;
+
+        // Everything which was read ahead is spread across the buffers.
+        let mut a = [0; 4];
+        let mut b = [0; 4];
+        let mut bufs = [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)];
+        assert_eq!(reader.read_vectored(&mut bufs).unwrap(), 6);
+        assert_eq!(a, [1, 2, 3, 4]);
+        assert_eq!(b[..2], [5, 6]);
+
+        // Nothing is available but the stream is still open.
+        let mut bufs = [IoSliceMut::new(&mut a)];
+        assert_eq!(reader.read_vectored(&mut bufs).unwrap(), 0);
+
+        drop(w);
+        resolves_immediately(reader.ready()).await;
+        assert!(matches!(
+            reader.read_vectored(&mut bufs),
+            Err(StreamError::Closed)
+        ));
+    }
+
+    #[test_log::test(tokio::test(flavor = "multi_thread"))]
+    async fn read_into_stream_vectored() {
+        let contents = (0..10).collect::<Vec<u8>>();
+        let mut reader = AsyncReadIntoStream::new(finite_async_reader(&contents).await);
+
+        // The first buffer is filled by blocking, and the rest of the bytes
+        // which are available go into the second.
+        let mut a = [0; 4];
+        let mut b = [0; 8];
+        let mut bufs = [
+            IoSliceMut::new(&mut []),
+            IoSliceMut::new(&mut a),
+            IoSliceMut::new(&mut b),
+        ];
+        let n = resolves_immediately(reader.blocking_read_vectored(&mut bufs)).await;
+        assert_eq!(n.unwrap(), 10);
+        assert_eq!(a, [0, 1, 2, 3]);
+        assert_eq!(b[..6], [4, 5, 6, 7, 8, 9]);
+
+        let mut bufs = [IoSliceMut::new(&mut a)];
+        assert!(matches!(
+            reader.read_vectored(&mut bufs),
+            Err(StreamError::Closed)
+        ));
+        assert!(matches!(reader.read(1), Err(StreamError::Closed)));
+    }
+
+    #[test_log::test(tokio::test(flavor = "multi_thread"))]
+    async fn read_into_stream_buffers_when_ready() {
+        let (r, mut w) = simplex(1024);
+        let mut reader = AsyncReadIntoStream::new(r);
+
+        // Nothing has been written, so nothing can be read yet.
+        assert!(reader.read(10).unwrap().is_empty());
+        let mut a = [0; 4];
+        assert_eq!(
+            reader
+                .read_vectored(&mut [IoSliceMut::new(&mut a)])
+                .unwrap(),
+            0
+        );
+        never_resolves(reader.ready()).await;
+
+        w.write_all(&[1, 2, 3, 4, 5, 6]).await.unwrap();
+        resolves_immediately(reader.ready()).await;
+
+        // Bytes buffered by `ready` are returned first.
+        assert_eq!(
+            reader
+                .read_vectored(&mut [IoSliceMut::new(&mut a)])
+                .unwrap(),
+            4
+        );
+        assert_eq!(a, [1, 2, 3, 4]);
+        assert_eq!(reader.read(10).unwrap(), Bytes::from_static(&[5, 6]));
+
+        drop(w);
+        resolves_immediately(reader.ready()).await;
+        assert!(matches!(reader.read(1), Err(StreamError::Closed)));
+    }
+
     #[test_log::test(tokio::test(flavor = "multi_thread"))]
     // At the moment we are restricting AsyncReadStream from buffering more than 4k. This isn't a
     // suitable design for all applications, and we will probably make a knob or change the
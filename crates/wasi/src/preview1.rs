@@ -78,6 +78,7 @@ use crate::{
 };
 use anyhow::{bail, Context};
 use std::collections::{BTreeMap, HashSet};
+use std::io::{self, IoSliceMut};
 use std::mem::{self, size_of, size_of_val};
 use std::ops::{Deref, DerefMut};
 use std::slice;
@@ -222,6 +223,18 @@ impl BlockingMode {
             Err(e) => Err(e.into()),
         }
     }
+    async fn read_vectored(
+        &self,
+        stream: &mut streams::InputStream,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Result<usize, types::Error> {
+        match stream.blocking_read_vectored(bufs).await {
+            Ok(0) if bufs.iter().any(|buf| !buf.is_empty()) => Err(types::Errno::Intr.into()),
+            Ok(n) => Ok(n),
+            Err(StreamError::Closed) => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    }
     async fn write(
         &self,
         memory: &mut GuestMemory<'_>,
@@ -603,13 +616,26 @@ impl WasiP1Ctx {
                 let f = self.table().get(&fd)?.file()?;
                 let buf = first_non_empty_ciovec(memory, ciovs)?;
 
-                let do_write = move |f: &cap_std::fs::File, buf: &[u8]| match (append, write) {
-                    // Note that this is implementing Linux semantics of
-                    // `pwrite` where the offset is ignored if the file was
-                    // opened in append mode.
-                    (true, _) => f.append(&buf),
-                    (false, FdWrite::At(pos)) => f.write_at(&buf, pos),
-                    (false, FdWrite::AtCur) => f.write_at(&buf, pos),
+                // Note that this is implementing Linux semantics of `pwrite`
+                // where the offset is ignored if the file was opened in
+                // append mode.
+                let offset = match (append, write) {
+                    (true, _) => None,
+                    (false, FdWrite::At(pos)) => Some(pos),
+                    (false, FdWrite::AtCur) => Some(pos),
+                };
+                let quota = f.quota.clone();
+                let do_write = move |f: &cap_std::fs::File, buf: &[u8]| {
+                    crate::filesystem::write_within_quota(
+                        quota.as_deref(),
+                        f,
+                        offset,
+                        buf.len(),
+                        |f| match offset {
+                            Some(offset) => f.write_at(buf, offset),
+                            None => f.append(buf),
+                        },
+                    )
                 };
 
                 let nwritten = match f.as_blocking_file() {
@@ -1142,6 +1168,81 @@ fn first_non_empty_iovec(
     Ok(GuestPtr::new((0, 0)))
 }
 
+// Returns the total length of the buffers in `iovs`, capped at the largest
+// possible size of a 32-bit memory.
+fn iovecs_len(memory: &GuestMemory<'_>, iovs: types::IovecArray) -> Result<usize> {
+    let mut len = 0u32;
+    for iov in iovs.iter() {
+        len = len.saturating_add(memory.read(iov?)?.buf_len);
+    }
+    Ok(usize::try_from(len)?)
+}
+
+// Copies `bytes` into the buffers in `iovs`, filling each one in turn.
+fn copy_to_iovecs(
+    memory: &mut GuestMemory<'_>,
+    iovs: types::IovecArray,
+    mut bytes: &[u8],
+) -> Result<()> {
+    for iov in iovs.iter() {
+        if bytes.is_empty() {
+            break;
+        }
+        let iov = memory.read(iov?)?;
+        let len = bytes.len().min(usize::try_from(iov.buf_len)?);
+        let (chunk, rest) = bytes.split_at(len);
+        let buf = iov.buf.as_array(u32::try_from(len)?);
+        memory.copy_from_slice(chunk, buf)?;
+        bytes = rest;
+    }
+    Ok(())
+}
+
+// Returns the non-empty buffers in `iovs` as slices of `memory`, in order, so
+// they can be read into directly. Returns `None` if `memory` is shared or if any
+// of the buffers overlap, since they can't all be borrowed mutably at once then,
+// in which case data must be copied into memory instead.
+fn iovec_slices_mut<'a>(
+    memory: &'a mut GuestMemory<'_>,
+    iovs: types::IovecArray,
+) -> Result<Option<Vec<IoSliceMut<'a>>>> {
+    let mut ranges = Vec::new();
+    for iov in iovs.iter() {
+        let iov = memory.read(iov?)?;
+        if iov.buf_len == 0 {
+            continue;
+        }
+        let buf = iov.buf.as_array(iov.buf_len);
+        // This also checks that the buffer is in bounds.
+        if memory.as_slice(buf)?.is_none() {
+            return Ok(None);
+        }
+        let start = usize::try_from(buf.offset_base())?;
+        let end = start + usize::try_from(buf.len())?;
+        ranges.push((ranges.len(), start..end));
+    }
+
+    let GuestMemory::Unshared(memory) = memory else {
+        return Ok(None);
+    };
+    let mut rest: &'a mut [u8] = memory;
+    let mut rest_start = 0;
+    let mut slices = Vec::with_capacity(ranges.len());
+    ranges.sort_by_key(|(_, range)| range.start);
+    for (i, range) in ranges {
+        if range.start < rest_start {
+            return Ok(None);
+        }
+        let (_, tail) = mem::take(&mut rest).split_at_mut(range.start - rest_start);
+        let (buf, tail) = tail.split_at_mut(range.len());
+        rest = tail;
+        rest_start = range.end;
+        slices.push((i, IoSliceMut::new(buf)));
+    }
+    slices.sort_by_key(|(i, _)| *i);
+    Ok(Some(slices.into_iter().map(|(_, buf)| buf).collect()))
+}
+
 #[async_trait::async_trait]
 // Implement the WasiSnapshotPreview1 trait using only the traits that are
 // required for T, i.e., in terms of the preview 2 wit interface, and state
@@ -1644,31 +1745,29 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for WasiP1Ctx {
                 drop(t);
                 let pos = position.load(Ordering::Relaxed);
                 let file = self.table().get(&fd)?.file()?;
-                let iov = first_non_empty_iovec(memory, iovs)?;
-                let bytes_read = match (file.as_blocking_file(), memory.as_slice_mut(iov)?) {
-                    // Try to read directly into wasm memory where possible
-                    // when the current thread can block and additionally wasm
-                    // memory isn't shared.
-                    (Some(file), Some(mut buf)) => file
-                        .read_at(&mut buf, pos)
+                let bytes_read = match (file.as_blocking_file(), iovec_slices_mut(memory, iovs)?) {
+                    // Try to read directly into all of the buffers in wasm
+                    // memory where possible when the current thread can block
+                    // and additionally wasm memory isn't shared.
+                    (Some(file), Some(mut bufs)) => file
+                        .read_vectored_at(&mut bufs, pos)
                         .map_err(|e| StreamError::LastOperationFailed(e.into()))?,
                     // ... otherwise fall back to performing the read on a
                     // blocking thread and which copies the data back into wasm
-                    // memory.
-                    (_, buf) => {
-                        drop(buf);
-                        let mut buf = vec![0; iov.len() as usize];
+                    // memory, buffer by buffer.
+                    (_, bufs) => {
+                        drop(bufs);
+                        let len = iovecs_len(memory, iovs)?;
+                        let mut buf = vec![0; len];
                         let buf = file
-                            .run_blocking(move |file| -> Result<_, types::Error> {
-                                let bytes_read = file
-                                    .read_at(&mut buf, pos)
-                                    .map_err(|e| StreamError::LastOperationFailed(e.into()))?;
+                            .run_blocking(move |file| -> io::Result<_> {
+                                let bytes_read = file.read_at(&mut buf, pos)?;
                                 buf.truncate(bytes_read);
                                 Ok(buf)
                             })
-                            .await?;
-                        let iov = iov.get_range(0..u32::try_from(buf.len())?).unwrap();
-                        memory.copy_from_slice(&buf, iov)?;
+                            .await
+                            .map_err(|e| StreamError::LastOperationFailed(e.into()))?;
+                        copy_to_iovecs(memory, iovs, &buf)?;
                         buf.len()
                     }
                 };
@@ -1683,6 +1782,19 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for WasiP1Ctx {
             Descriptor::Stdin { stream, .. } => {
                 let stream = stream.borrowed();
                 drop(t);
+                // Read directly into wasm memory where possible, which is only
+                // the case if it isn't shared and none of the buffers alias
+                // each other. Memory stays borrowed for the whole read, so the
+                // guest can't observe or modify it in the meantime.
+                if let Some(mut bufs) = iovec_slices_mut(memory, iovs)? {
+                    if !bufs.is_empty() {
+                        let stream = self.table().get_mut(&stream)?;
+                        let n = BlockingMode::Blocking
+                            .read_vectored(stream, &mut bufs)
+                            .await?;
+                        return Ok(n.try_into()?);
+                    }
+                }
                 let buf = first_non_empty_iovec(memory, iovs)?;
                 let read = BlockingMode::Blocking
                     .read(&mut self.as_io_impl(), stream, buf.len().try_into()?)
@@ -16,13 +16,20 @@
 //! [`spawn_blocking`] over tokio's. so we wanted the type name to stick out
 //! if someone misses it.
 //!
+//! Tasks which are spawned on behalf of a guest can additionally be tracked
+//! by a [`TaskSupervisor`], which is owned by the store they belong to. This
+//! lets embedders enumerate them, cancel them, and bound how many run at once.
+//!
 //! Each of these facilities should be used by dependencies of wasmtime-wasi
 //! which when implementing component bindings.
 
+use std::collections::BTreeMap;
+use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock, Mutex, Weak};
 use std::task::{Context, Poll};
+use std::time::Instant;
 
 pub(crate) static RUNTIME: LazyLock<tokio::runtime::Runtime> = LazyLock::new(|| {
     tokio::runtime::Builder::new_multi_thread()
@@ -187,3 +194,307 @@ where
         Poll::Pending => None,
     }
 }
+
+/// Tracks the background tasks spawned on behalf of a single store.
+///
+/// Host implementations spawn tasks for work which continues after a call
+/// returns, such as finishing a write to a TCP socket or resolving a name. A
+/// `TaskSupervisor` records each of these tasks while it's running so they can
+/// be enumerated with [`TaskSupervisor::tasks`], cancelled, and limited with
+/// [`TaskSupervisor::set_limit`].
+///
+/// Clones of a `TaskSupervisor` share the same set of tasks. All tasks still
+/// running are cancelled when the [`WasiCtx`](crate::WasiCtx) owning the
+/// supervisor is dropped, usually along with its store, or otherwise once all
+/// clones have been dropped.
+#[derive(Clone, Default)]
+pub struct TaskSupervisor {
+    inner: Arc<Mutex<SupervisorInner>>,
+}
+
+#[derive(Default)]
+struct SupervisorInner {
+    next_id: u64,
+    limit: Option<usize>,
+    tasks: BTreeMap<u64, SupervisedTask>,
+}
+
+struct SupervisedTask {
+    name: &'static str,
+    spawned_at: Instant,
+    // `None` until the task has been spawned.
+    abort: Option<tokio::task::AbortHandle>,
+}
+
+impl TaskSupervisor {
+    /// Creates a new supervisor without any tasks or a limit.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the number of tasks which may run at once.
+    ///
+    /// Spawning a task while `limit` tasks are already running fails with
+    /// [`TaskLimitExceeded`]. Tasks which are already running are unaffected
+    /// by lowering the limit. Defaults to `None`, or no limit.
+    pub fn set_limit(&self, limit: Option<usize>) {
+        self.lock().limit = limit;
+    }
+
+    /// Returns the limit configured with [`TaskSupervisor::set_limit`].
+    pub fn limit(&self) -> Option<usize> {
+        self.lock().limit
+    }
+
+    /// Returns the number of tasks currently running.
+    pub fn len(&self) -> usize {
+        self.lock().tasks.len()
+    }
+
+    /// Returns whether no tasks are currently running.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the tasks currently running, in the order they were spawned.
+    pub fn tasks(&self) -> Vec<TaskInfo> {
+        self.lock()
+            .tasks
+            .iter()
+            .map(|(id, task)| TaskInfo {
+                id: TaskId(*id),
+                name: task.name,
+                spawned_at: task.spawned_at,
+            })
+            .collect()
+    }
+
+    /// Cancels the task `id`, returning whether it was still running.
+    ///
+    /// The owner of the task, for example a stream resource, observes the
+    /// cancellation as a failure of the operation the task was performing.
+    pub fn cancel(&self, id: TaskId) -> bool {
+        let task = self.lock().tasks.remove(&id.0);
+        match task {
+            Some(task) => {
+                task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cancels all tasks which are currently running.
+    pub fn cancel_all(&self) {
+        let tasks = std::mem::take(&mut self.lock().tasks);
+        for task in tasks.into_values() {
+            task.abort();
+        }
+    }
+
+    /// Same as [`spawn`], but the task is tracked by this supervisor under
+    /// `name`.
+    pub fn spawn<F>(
+        &self,
+        name: &'static str,
+        f: F,
+    ) -> Result<AbortOnDropJoinHandle<F::Output>, TaskLimitExceeded>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let guard = self.register(name)?;
+        let id = guard.id;
+        let j = with_ambient_tokio_runtime(|| {
+            tokio::task::spawn(async move {
+                let _guard = guard;
+                f.await
+            })
+        });
+        self.started(id, &j);
+        Ok(AbortOnDropJoinHandle(j))
+    }
+
+    /// Same as [`TaskSupervisor::spawn`], but without a handle to the task.
+    ///
+    /// The task keeps running until it finishes or is cancelled through this
+    /// supervisor, which includes all clones of the supervisor being dropped.
+    pub fn spawn_detached<F>(&self, name: &'static str, f: F) -> Result<(), TaskLimitExceeded>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let guard = self.register(name)?;
+        let id = guard.id;
+        let j = with_ambient_tokio_runtime(|| {
+            tokio::task::spawn(async move {
+                let _guard = guard;
+                f.await
+            })
+        });
+        self.started(id, &j);
+        Ok(())
+    }
+
+    /// Same as [`spawn_blocking`], but the task is tracked by this supervisor
+    /// under `name`.
+    ///
+    /// Note that blocking tasks can't be interrupted once they've started
+    /// running, so cancelling them only has an effect if they haven't started
+    /// yet.
+    pub fn spawn_blocking<F, R>(
+        &self,
+        name: &'static str,
+        f: F,
+    ) -> Result<AbortOnDropJoinHandle<R>, TaskLimitExceeded>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let guard = self.register(name)?;
+        let id = guard.id;
+        let j = with_ambient_tokio_runtime(|| {
+            tokio::task::spawn_blocking(move || {
+                let _guard = guard;
+                f()
+            })
+        });
+        self.started(id, &j);
+        Ok(AbortOnDropJoinHandle(j))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SupervisorInner> {
+        self.inner.lock().unwrap()
+    }
+
+    fn register(&self, name: &'static str) -> Result<TaskGuard, TaskLimitExceeded> {
+        let mut inner = self.lock();
+        if let Some(limit) = inner.limit {
+            if inner.tasks.len() >= limit {
+                return Err(TaskLimitExceeded { limit });
+            }
+        }
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.tasks.insert(
+            id,
+            SupervisedTask {
+                name,
+                spawned_at: Instant::now(),
+                abort: None,
+            },
+        );
+        Ok(TaskGuard {
+            supervisor: Arc::downgrade(&self.inner),
+            id,
+        })
+    }
+
+    fn started<T>(&self, id: u64, j: &tokio::task::JoinHandle<T>) {
+        // If the task has already finished, or was cancelled before it was
+        // spawned, then it's no longer tracked here.
+        let tracked = match self.lock().tasks.get_mut(&id) {
+            Some(task) => {
+                task.abort = Some(j.abort_handle());
+                true
+            }
+            None => false,
+        };
+        if !tracked {
+            j.abort();
+        }
+    }
+}
+
+impl fmt::Debug for TaskSupervisor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskSupervisor")
+            .field("limit", &self.limit())
+            .field("tasks", &self.tasks())
+            .finish()
+    }
+}
+
+impl SupervisedTask {
+    fn abort(self) {
+        if let Some(abort) = self.abort {
+            abort.abort();
+        }
+    }
+}
+
+impl Drop for SupervisorInner {
+    fn drop(&mut self) {
+        for task in std::mem::take(&mut self.tasks).into_values() {
+            task.abort();
+        }
+    }
+}
+
+/// Removes a task from its supervisor once the task finishes.
+struct TaskGuard {
+    supervisor: Weak<Mutex<SupervisorInner>>,
+    id: u64,
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        if let Some(supervisor) = self.supervisor.upgrade() {
+            supervisor.lock().unwrap().tasks.remove(&self.id);
+        }
+    }
+}
+
+/// Identifies a task tracked by a [`TaskSupervisor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TaskId(u64);
+
+/// A task currently tracked by a [`TaskSupervisor`].
+#[derive(Debug, Clone)]
+pub struct TaskInfo {
+    id: TaskId,
+    name: &'static str,
+    spawned_at: Instant,
+}
+
+impl TaskInfo {
+    /// The id to pass to [`TaskSupervisor::cancel`] to cancel this task.
+    pub fn id(&self) -> TaskId {
+        self.id
+    }
+
+    /// A short description of what the task is doing, such as `"tcp-write"`.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// When the task was spawned.
+    pub fn spawned_at(&self) -> Instant {
+        self.spawned_at
+    }
+}
+
+/// Error returned when spawning a task on a [`TaskSupervisor`] which has
+/// already reached its limit.
+#[derive(Debug, Clone)]
+pub struct TaskLimitExceeded {
+    limit: usize,
+}
+
+impl TaskLimitExceeded {
+    /// The limit of the supervisor at the time the task was spawned.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl fmt::Display for TaskLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "limit of {} concurrent background tasks reached",
+            self.limit
+        )
+    }
+}
+
+impl std::error::Error for TaskLimitExceeded {}
@@ -9,7 +9,7 @@ use crate::{
     WasiView,
 };
 use bytes::Bytes;
-use std::io::IsTerminal;
+use std::io::{IoSliceMut, IsTerminal};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use wasmtime::component::Resource;
@@ -112,6 +112,12 @@ impl HostInputStream for AsyncStdinStream {
             Err(_) => Err(StreamError::trap("concurrent reads are not supported")),
         }
     }
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> Result<usize, StreamError> {
+        match self.0.try_lock() {
+            Ok(mut stream) => stream.read_vectored(bufs),
+            Err(_) => Err(StreamError::trap("concurrent reads are not supported")),
+        }
+    }
     fn skip(&mut self, size: usize) -> Result<usize, StreamError> {
         match self.0.try_lock() {
             Ok(mut stream) => stream.skip(size),
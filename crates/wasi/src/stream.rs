@@ -1,6 +1,7 @@
 use crate::poll::Subscribe;
 use anyhow::Result;
 use bytes::Bytes;
+use std::io::IoSliceMut;
 
 /// Host trait for implementing the `wasi:io/streams.input-stream` resource: A
 /// bytestream which can be read from.
@@ -27,6 +28,31 @@ pub trait HostInputStream: Subscribe {
         self.read(size)
     }
 
+    /// Reads bytes directly into `bufs`, filling each buffer in turn, and
+    /// returns the number of bytes read.
+    ///
+    /// This is used to read straight into the memory of a guest, such as for
+    /// WASI preview1's `fd_read`. The default implementation performs a `read`
+    /// and copies the bytes into `bufs`, but streams which can read without
+    /// buffering the bytes on the host first, like
+    /// [`AsyncReadIntoStream`](crate::pipe::AsyncReadIntoStream), override
+    /// this to avoid the copy.
+    ///
+    /// Note that this method is non-blocking like `read` and returns the same
+    /// errors.
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> StreamResult<usize> {
+        let size = bufs.iter().map(|buf| buf.len()).sum();
+        let bytes = self.read(size)?;
+        Ok(copy_to_slices(&bytes, bufs))
+    }
+
+    /// Similar to `read_vectored`, except that it blocks until at least one
+    /// byte can be read.
+    async fn blocking_read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> StreamResult<usize> {
+        self.ready().await;
+        self.read_vectored(bufs)
+    }
+
     /// Same as the `read` method except that bytes are skipped.
     ///
     /// Note that this method is non-blocking like `read` and returns the same
@@ -47,6 +73,23 @@ pub trait HostInputStream: Subscribe {
     async fn cancel(&mut self) {}
 }
 
+/// Copies `bytes` into `bufs`, filling each buffer in turn, and returns the
+/// number of bytes copied.
+pub(crate) fn copy_to_slices(mut bytes: &[u8], bufs: &mut [IoSliceMut<'_>]) -> usize {
+    let mut copied = 0;
+    for buf in bufs {
+        if bytes.is_empty() {
+            break;
+        }
+        let len = buf.len().min(bytes.len());
+        let (chunk, rest) = bytes.split_at(len);
+        buf[..len].copy_from_slice(chunk);
+        bytes = rest;
+        copied += len;
+    }
+    copied
+}
+
 /// Representation of the `error` resource type in the `wasi:io/error`
 /// interface.
 ///
@@ -1,7 +1,7 @@
 use crate::bindings::sockets::tcp::ErrorCode;
 use crate::host::network;
 use crate::network::SocketAddressFamily;
-use crate::runtime::{with_ambient_tokio_runtime, AbortOnDropJoinHandle};
+use crate::runtime::{with_ambient_tokio_runtime, AbortOnDropJoinHandle, TaskSupervisor};
 use crate::{
     HostInputStream, HostOutputStream, InputStream, OutputStream, SocketError, SocketResult,
     StreamError, Subscribe,
@@ -95,6 +95,9 @@ pub struct TcpSocket {
 
     family: SocketAddressFamily,
 
+    /// The supervisor to spawn background writes on, if any.
+    tasks: Option<TaskSupervisor>,
+
     // The socket options below are not automatically inherited from the listener
     // on all platforms. So we keep track of which options have been explicitly
     // set and manually apply those values to newly accepted clients.
@@ -128,12 +131,19 @@ impl TcpSocket {
         })
     }
 
+    /// Tracks the background tasks of this socket and the sockets it accepts
+    /// with `tasks`.
+    pub(crate) fn set_task_supervisor(&mut self, tasks: TaskSupervisor) {
+        self.tasks = Some(tasks);
+    }
+
     /// Create a `TcpSocket` from an existing socket.
     fn from_state(state: TcpState, family: SocketAddressFamily) -> io::Result<Self> {
         Ok(Self {
             tcp_state: state,
             listen_backlog_size: DEFAULT_BACKLOG,
             family,
+            tasks: None,
             #[cfg(target_os = "macos")]
             receive_buffer_size: None,
             #[cfg(target_os = "macos")]
@@ -287,7 +297,10 @@ impl TcpSocket {
             Ok(stream) => {
                 let stream = Arc::new(stream);
                 let reader = Arc::new(Mutex::new(TcpReader::new(stream.clone())));
-                let writer = Arc::new(Mutex::new(TcpWriter::new(stream.clone())));
+                let writer = Arc::new(Mutex::new(TcpWriter::new(
+                    stream.clone(),
+                    self.tasks.clone(),
+                )));
                 self.tcp_state = TcpState::Connected {
                     stream,
                     reader: reader.clone(),
@@ -443,11 +456,14 @@ impl TcpSocket {
         let client = Arc::new(client);
 
         let reader = Arc::new(Mutex::new(TcpReader::new(client.clone())));
-        let writer = Arc::new(Mutex::new(TcpWriter::new(client.clone())));
+        let writer = Arc::new(Mutex::new(TcpWriter::new(
+            client.clone(),
+            self.tasks.clone(),
+        )));
 
         let input: InputStream = Box::new(TcpReadStream(reader.clone()));
         let output: OutputStream = Box::new(TcpWriteStream(writer.clone()));
-        let tcp_socket = TcpSocket::from_state(
+        let mut tcp_socket = TcpSocket::from_state(
             TcpState::Connected {
                 stream: client,
                 reader,
@@ -455,6 +471,7 @@ impl TcpSocket {
             },
             self.family,
         )?;
+        tcp_socket.tasks = self.tasks.clone();
 
         Ok((tcp_socket, input, output))
     }
@@ -766,6 +783,7 @@ const SOCKET_READY_SIZE: usize = 1024 * 1024 * 1024;
 struct TcpWriter {
     stream: Arc<tokio::net::TcpStream>,
     state: WriteState,
+    tasks: Option<TaskSupervisor>,
 }
 
 enum WriteState {
@@ -777,10 +795,22 @@ enum WriteState {
 }
 
 impl TcpWriter {
-    fn new(stream: Arc<tokio::net::TcpStream>) -> Self {
+    fn new(stream: Arc<tokio::net::TcpStream>, tasks: Option<TaskSupervisor>) -> Self {
         Self {
             stream,
             state: WriteState::Ready,
+            tasks,
+        }
+    }
+
+    fn spawn<F>(&self, name: &'static str, f: F) -> io::Result<AbortOnDropJoinHandle<F::Output>>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        match &self.tasks {
+            Some(tasks) => tasks.spawn(name, f).map_err(io::Error::other),
+            None => Ok(crate::runtime::spawn(f)),
         }
     }
 
@@ -804,7 +834,7 @@ impl TcpWriter {
         assert!(matches!(self.state, WriteState::Ready));
 
         let stream = self.stream.clone();
-        self.state = WriteState::Writing(crate::runtime::spawn(async move {
+        let task = self.spawn("tcp-write", async move {
             // Note: we are not using the AsyncWrite impl here, and instead using the TcpStream
             // primitive try_write, which goes directly to attempt a write with mio. This has
             // two advantages: 1. this operation takes a &TcpStream instead of a &mut TcpStream
@@ -817,12 +847,16 @@ impl TcpWriter {
                         let _ = bytes.split_to(n);
                     }
                     Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => continue,
-                    Err(e) => return Err(e.into()),
+                    Err(e) => return Err(e),
                 }
             }
 
             Ok(())
-        }));
+        });
+        self.state = match task {
+            Ok(task) => WriteState::Writing(task),
+            Err(e) => WriteState::Error(e),
+        };
     }
 
     fn write(&mut self, mut bytes: bytes::Bytes) -> Result<(), StreamError> {
@@ -908,13 +942,22 @@ impl TcpWriter {
             }
 
             // Schedule the shutdown after the current write has finished:
-            WriteState::Writing(write) => {
+            WriteState::Writing(mut write) => {
                 let stream = self.stream.clone();
-                WriteState::Closing(crate::runtime::spawn(async move {
-                    let result = write.await;
+                let task = self.spawn("tcp-shutdown", async move {
+                    let result = join(&mut write).await;
                     native_shutdown(&stream, Shutdown::Write);
                     result
-                }))
+                });
+                match task {
+                    Ok(task) => WriteState::Closing(task),
+                    // The write in progress is cancelled when `task` is
+                    // dropped.
+                    Err(e) => {
+                        native_shutdown(&self.stream, Shutdown::Write);
+                        WriteState::Error(e)
+                    }
+                }
             }
 
             s => s,
@@ -931,13 +974,13 @@ impl TcpWriter {
     async fn ready(&mut self) {
         match &mut self.state {
             WriteState::Writing(task) => {
-                self.state = match task.await {
+                self.state = match join(task).await {
                     Ok(()) => WriteState::Ready,
                     Err(e) => WriteState::Error(e),
                 }
             }
             WriteState::Closing(task) => {
-                self.state = match task.await {
+                self.state = match join(task).await {
                     Ok(()) => WriteState::Closed,
                     Err(e) => WriteState::Error(e),
                 }
@@ -951,6 +994,19 @@ impl TcpWriter {
     }
 }
 
+/// Waits for a background write, treating its cancellation through a
+/// [`TaskSupervisor`] as a failed write.
+async fn join(task: &mut AbortOnDropJoinHandle<io::Result<()>>) -> io::Result<()> {
+    match (&mut **task).await {
+        Ok(result) => result,
+        Err(e) if e.is_cancelled() => Err(io::Error::new(
+            io::ErrorKind::Interrupted,
+            "background write was cancelled",
+        )),
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
 struct TcpWriteStream(Arc<Mutex<TcpWriter>>);
 
 #[async_trait::async_trait]
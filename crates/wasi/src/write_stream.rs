@@ -1,3 +1,4 @@
+use crate::runtime::{TaskLimitExceeded, TaskSupervisor};
 use crate::{HostOutputStream, StreamError, Subscribe};
 use anyhow::anyhow;
 use bytes::Bytes;
@@ -159,6 +160,26 @@ impl AsyncWriteStream {
             join_handle: Some(join_handle),
         }
     }
+
+    /// Same as [`AsyncWriteStream::new`], but the task writing to `writer` is
+    /// tracked by `tasks`.
+    ///
+    /// Fails if `tasks` is already running as many tasks as its limit allows.
+    pub fn new_supervised<T: tokio::io::AsyncWrite + Send + Unpin + 'static>(
+        write_budget: usize,
+        writer: T,
+        tasks: &TaskSupervisor,
+    ) -> Result<Self, TaskLimitExceeded> {
+        let worker = Arc::new(Worker::new(write_budget));
+
+        let w = Arc::clone(&worker);
+        let join_handle = tasks.spawn("async-write-stream", async move { w.work(writer).await })?;
+
+        Ok(AsyncWriteStream {
+            worker,
+            join_handle: Some(join_handle),
+        })
+    }
 }
 
 #[async_trait::async_trait]
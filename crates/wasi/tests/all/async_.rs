@@ -220,6 +220,10 @@ async fn preview1_readlink() {
     run(PREVIEW1_READLINK_COMPONENT, false).await.unwrap()
 }
 #[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_read_vectored() {
+    run(PREVIEW1_READ_VECTORED_COMPONENT, false).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
 async fn preview1_remove_directory() {
     run(PREVIEW1_REMOVE_DIRECTORY_COMPONENT, false)
         .await
@@ -89,5 +89,8 @@ macro_rules! assert_test_exists {
 
 mod api;
 mod async_;
+mod mount;
+mod multicast;
 mod preview1;
+mod supervisor;
 mod sync;
@@ -0,0 +1,186 @@
+use anyhow::Result;
+use wasmtime::component::{Resource, ResourceTable};
+use wasmtime_wasi::bindings::filesystem::preopens::Host as _;
+use wasmtime_wasi::bindings::filesystem::types::{
+    Descriptor, DescriptorFlags, ErrorCode, HostDescriptor, OpenFlags, PathFlags,
+};
+use wasmtime_wasi::{
+    FsResult, IoImpl, IoView, MountOptions, WasiCtx, WasiCtxBuilder, WasiImpl, WasiView,
+};
+
+struct Ctx {
+    table: ResourceTable,
+    wasi: WasiCtx,
+}
+
+impl IoView for Ctx {
+    fn table(&mut self) -> &mut ResourceTable {
+        &mut self.table
+    }
+}
+impl WasiView for Ctx {
+    fn ctx(&mut self) -> &mut WasiCtx {
+        &mut self.wasi
+    }
+}
+
+fn ctx(configure: impl FnOnce(&mut WasiCtxBuilder) -> Result<()>) -> Result<Ctx> {
+    let mut builder = WasiCtxBuilder::new();
+    configure(&mut builder)?;
+    Ok(Ctx {
+        table: ResourceTable::new(),
+        wasi: builder.build(),
+    })
+}
+
+fn host(ctx: &mut Ctx) -> WasiImpl<&mut Ctx> {
+    WasiImpl(IoImpl(ctx))
+}
+
+fn root(ctx: &mut Ctx) -> Result<Resource<Descriptor>> {
+    let (fd, _) = host(ctx).get_directories()?.remove(0);
+    Ok(fd)
+}
+
+async fn write_file(
+    ctx: &mut Ctx,
+    dir: &Resource<Descriptor>,
+    path: &str,
+    contents: &[u8],
+) -> FsResult<()> {
+    let fd = host(ctx)
+        .open_at(
+            Resource::new_borrow(dir.rep()),
+            PathFlags::empty(),
+            path.to_string(),
+            OpenFlags::CREATE | OpenFlags::TRUNCATE,
+            DescriptorFlags::WRITE,
+        )
+        .await?;
+    let result = host(ctx)
+        .write(Resource::new_borrow(fd.rep()), contents.to_vec(), 0)
+        .await;
+    HostDescriptor::drop(&mut host(ctx), fd)?;
+    result?;
+    Ok(())
+}
+
+async fn read_file(ctx: &mut Ctx, dir: &Resource<Descriptor>, path: &str) -> FsResult<Vec<u8>> {
+    let fd = host(ctx)
+        .open_at(
+            Resource::new_borrow(dir.rep()),
+            PathFlags::empty(),
+            path.to_string(),
+            OpenFlags::empty(),
+            DescriptorFlags::READ,
+        )
+        .await?;
+    let result = host(ctx)
+        .read(Resource::new_borrow(fd.rep()), 1024, 0)
+        .await;
+    HostDescriptor::drop(&mut host(ctx), fd)?;
+    Ok(result?.0)
+}
+
+fn error_code(result: FsResult<impl std::fmt::Debug>) -> ErrorCode {
+    result.unwrap_err().downcast().unwrap()
+}
+
+#[tokio::test]
+async fn read_only() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    std::fs::write(dir.path().join("a.txt"), "hello")?;
+
+    let mut ctx = ctx(|b| {
+        b.mount(dir.path(), "/", MountOptions::new().read_only(true))?;
+        Ok(())
+    })?;
+    let root = root(&mut ctx)?;
+
+    assert_eq!(read_file(&mut ctx, &root, "a.txt").await?, b"hello");
+    assert_eq!(
+        error_code(write_file(&mut ctx, &root, "a.txt", b"bye").await),
+        ErrorCode::NotPermitted
+    );
+    assert_eq!(
+        error_code(
+            host(&mut ctx)
+                .unlink_file_at(Resource::new_borrow(root.rep()), "a.txt".to_string())
+                .await
+        ),
+        ErrorCode::NotPermitted
+    );
+    assert_eq!(std::fs::read(dir.path().join("a.txt"))?, b"hello");
+    Ok(())
+}
+
+#[tokio::test]
+async fn overlay() -> Result<()> {
+    let lower = tempfile::tempdir()?;
+    let upper = tempfile::tempdir()?;
+    std::fs::write(lower.path().join("a.txt"), "lower")?;
+    std::fs::write(lower.path().join("b.txt"), "lower")?;
+
+    let mut ctx = ctx(|b| {
+        b.mount(lower.path(), "/", MountOptions::new().overlay(upper.path()))?;
+        Ok(())
+    })?;
+    let root = root(&mut ctx)?;
+
+    // Writes are copied up, leaving the lower layer untouched.
+    write_file(&mut ctx, &root, "a.txt", b"upper").await?;
+    assert_eq!(read_file(&mut ctx, &root, "a.txt").await?, b"upper");
+    assert_eq!(std::fs::read(upper.path().join("a.txt"))?, b"upper");
+    assert_eq!(std::fs::read(lower.path().join("a.txt"))?, b"lower");
+
+    // Removing a lower file hides it without deleting it.
+    host(&mut ctx)
+        .unlink_file_at(Resource::new_borrow(root.rep()), "b.txt".to_string())
+        .await?;
+    assert_eq!(
+        error_code(read_file(&mut ctx, &root, "b.txt").await),
+        ErrorCode::NoEntry
+    );
+    assert!(lower.path().join("b.txt").exists());
+
+    // Escaping the mount is rejected.
+    assert_eq!(
+        error_code(read_file(&mut ctx, &root, "../a.txt").await),
+        ErrorCode::NotPermitted
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn quota() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    std::fs::write(dir.path().join("a.txt"), [0; 6])?;
+
+    let mut ctx = ctx(|b| {
+        b.mount(dir.path(), "/", MountOptions::new().quota(10))?;
+        Ok(())
+    })?;
+    let root = root(&mut ctx)?;
+
+    assert_eq!(
+        error_code(write_file(&mut ctx, &root, "b.txt", &[0; 5]).await),
+        ErrorCode::InsufficientSpace
+    );
+    write_file(&mut ctx, &root, "b.txt", &[0; 4]).await?;
+
+    // Removing files releases their space.
+    host(&mut ctx)
+        .unlink_file_at(Resource::new_borrow(root.rep()), "a.txt".to_string())
+        .await?;
+    write_file(&mut ctx, &root, "c.txt", &[0; 6]).await?;
+    Ok(())
+}
+
+#[test]
+fn read_only_overlay_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let options = MountOptions::new().read_only(true).overlay(dir.path());
+    assert!(WasiCtxBuilder::new()
+        .mount(dir.path(), "/", options)
+        .is_err());
+}
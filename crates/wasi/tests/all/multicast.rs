@@ -0,0 +1,186 @@
+use anyhow::Result;
+use std::net::{Ipv4Addr, SocketAddr};
+use wasmtime::component::{Resource, ResourceTable};
+use wasmtime_wasi::bindings::sockets::instance_network::Host as _;
+use wasmtime_wasi::bindings::sockets::network::{
+    ErrorCode, IpAddress, IpAddressFamily, IpSocketAddress, Ipv4SocketAddress,
+};
+use wasmtime_wasi::bindings::sockets::udp::{HostUdpSocket, UdpSocket};
+use wasmtime_wasi::bindings::sockets::udp_create_socket::Host as _;
+use wasmtime_wasi::{IoImpl, IoView, SocketResult, WasiCtx, WasiCtxBuilder, WasiImpl, WasiView};
+
+const GROUP: IpAddress = IpAddress::Ipv4((239, 255, 0, 1));
+
+struct Ctx {
+    table: ResourceTable,
+    wasi: WasiCtx,
+}
+
+impl IoView for Ctx {
+    fn table(&mut self) -> &mut ResourceTable {
+        &mut self.table
+    }
+}
+impl WasiView for Ctx {
+    fn ctx(&mut self) -> &mut WasiCtx {
+        &mut self.wasi
+    }
+}
+
+fn ctx(configure: impl FnOnce(&mut WasiCtxBuilder)) -> Ctx {
+    let mut builder = WasiCtxBuilder::new();
+    builder.inherit_network();
+    configure(&mut builder);
+    Ctx {
+        table: ResourceTable::new(),
+        wasi: builder.build(),
+    }
+}
+
+fn host(ctx: &mut Ctx) -> WasiImpl<&mut Ctx> {
+    WasiImpl(IoImpl(ctx))
+}
+
+fn socket(ctx: &mut Ctx) -> Result<Resource<UdpSocket>> {
+    Ok(host(ctx).create_udp_socket(IpAddressFamily::Ipv4)?)
+}
+
+async fn bind(ctx: &mut Ctx, socket: &Resource<UdpSocket>) -> Result<()> {
+    let network = host(ctx).instance_network()?;
+    let local_address = IpSocketAddress::Ipv4(Ipv4SocketAddress {
+        port: 0,
+        address: (127, 0, 0, 1),
+    });
+    host(ctx)
+        .start_bind(Resource::new_borrow(socket.rep()), network, local_address)
+        .await?;
+    host(ctx).finish_bind(Resource::new_borrow(socket.rep()))?;
+    Ok(())
+}
+
+fn error_code(result: SocketResult<impl std::fmt::Debug>) -> ErrorCode {
+    result.unwrap_err().downcast().unwrap()
+}
+
+#[tokio::test]
+async fn denied_by_default() -> Result<()> {
+    let mut ctx = ctx(|_| {});
+    let socket = socket(&mut ctx)?;
+
+    assert_eq!(
+        error_code(host(&mut ctx).set_reuse_address(Resource::new_borrow(socket.rep()), true)),
+        ErrorCode::AccessDenied
+    );
+    bind(&mut ctx, &socket).await?;
+    assert_eq!(
+        error_code(
+            host(&mut ctx)
+                .join_multicast_group(Resource::new_borrow(socket.rep()), GROUP, 0)
+                .await
+        ),
+        ErrorCode::AccessDenied
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn join_group() -> Result<()> {
+    let mut ctx = ctx(|b| {
+        b.allow_udp_multicast(true);
+    });
+    let socket = socket(&mut ctx)?;
+
+    // The socket must be bound first.
+    assert_eq!(
+        error_code(
+            host(&mut ctx)
+                .join_multicast_group(Resource::new_borrow(socket.rep()), GROUP, 0)
+                .await
+        ),
+        ErrorCode::InvalidState
+    );
+    bind(&mut ctx, &socket).await?;
+
+    assert_eq!(
+        error_code(
+            host(&mut ctx)
+                .join_multicast_group(
+                    Resource::new_borrow(socket.rep()),
+                    IpAddress::Ipv4((127, 0, 0, 1)),
+                    0
+                )
+                .await
+        ),
+        ErrorCode::InvalidArgument
+    );
+    assert_eq!(
+        error_code(
+            host(&mut ctx)
+                .join_multicast_group(Resource::new_borrow(socket.rep()), GROUP, 1)
+                .await
+        ),
+        ErrorCode::NotSupported
+    );
+
+    // Hosts without a multicast route can't join groups, so only check that
+    // joining and leaving agree with each other.
+    if host(&mut ctx)
+        .join_multicast_group(Resource::new_borrow(socket.rep()), GROUP, 0)
+        .await
+        .is_ok()
+    {
+        host(&mut ctx).leave_multicast_group(Resource::new_borrow(socket.rep()), GROUP, 0)?;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn socket_addr_check() -> Result<()> {
+    let mut ctx = ctx(|b| {
+        b.allow_udp_multicast(true).socket_addr_check(|addr, _| {
+            Box::pin(
+                async move { addr != SocketAddr::new(Ipv4Addr::new(239, 255, 0, 1).into(), 0) },
+            )
+        });
+    });
+    let socket = socket(&mut ctx)?;
+    bind(&mut ctx, &socket).await?;
+
+    assert_eq!(
+        error_code(
+            host(&mut ctx)
+                .join_multicast_group(Resource::new_borrow(socket.rep()), GROUP, 0)
+                .await
+        ),
+        ErrorCode::AccessDenied
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn socket_options() -> Result<()> {
+    let mut ctx = ctx(|b| {
+        b.allow_udp_reuse_address(true);
+    });
+    let socket = socket(&mut ctx)?;
+    let mut udp = host(&mut ctx);
+
+    udp.set_multicast_hop_limit(Resource::new_borrow(socket.rep()), 0)?;
+    assert_eq!(
+        udp.multicast_hop_limit(Resource::new_borrow(socket.rep()))?,
+        0
+    );
+    udp.set_multicast_loopback(Resource::new_borrow(socket.rep()), false)?;
+    assert!(!udp.multicast_loopback(Resource::new_borrow(socket.rep()))?);
+
+    udp.set_reuse_address(Resource::new_borrow(socket.rep()), true)?;
+    assert!(udp.reuse_address(Resource::new_borrow(socket.rep()))?);
+    drop(udp);
+
+    bind(&mut ctx, &socket).await?;
+    assert_eq!(
+        error_code(host(&mut ctx).set_reuse_address(Resource::new_borrow(socket.rep()), false)),
+        ErrorCode::InvalidState
+    );
+    Ok(())
+}
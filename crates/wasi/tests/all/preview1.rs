@@ -15,6 +15,9 @@ async fn run(path: &str, inherit_stdio: bool) -> Result<()> {
 
     let module = Module::from_file(&engine, path)?;
     let (mut store, _td) = store(&engine, name, |builder| {
+        // Native preview1 reads into all of the buffers passed to `fd_read`,
+        // unlike the component adapter.
+        builder.env("VECTORED_READS", "1");
         if inherit_stdio {
             builder.inherit_stdio();
         }
@@ -181,6 +184,10 @@ async fn preview1_readlink() {
     run(PREVIEW1_READLINK, false).await.unwrap()
 }
 #[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_read_vectored() {
+    run(PREVIEW1_READ_VECTORED, false).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
 async fn preview1_remove_directory() {
     run(PREVIEW1_REMOVE_DIRECTORY, false).await.unwrap()
 }
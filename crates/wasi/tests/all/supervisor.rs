@@ -0,0 +1,98 @@
+use std::time::Duration;
+use wasmtime_wasi::pipe::{AsyncReadStream, AsyncWriteStream};
+use wasmtime_wasi::runtime::TaskSupervisor;
+use wasmtime_wasi::WasiCtxBuilder;
+
+#[tokio::test]
+async fn enumerate_and_cancel() {
+    let tasks = TaskSupervisor::new();
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+
+    let done = tasks.spawn("done", async {}).unwrap();
+    let mut pending = tasks
+        .spawn("pending", std::future::pending::<()>())
+        .unwrap();
+    let waiting = tasks.spawn("waiting", rx).unwrap();
+    done.await;
+
+    let names = tasks.tasks().iter().map(|t| t.name()).collect::<Vec<_>>();
+    assert_eq!(names, ["pending", "waiting"]);
+
+    let id = tasks.tasks()[0].id();
+    assert!(tasks.cancel(id));
+    assert!(!tasks.cancel(id));
+    assert!((&mut *pending).await.unwrap_err().is_cancelled());
+
+    tx.send(()).unwrap();
+    waiting.await.unwrap();
+    assert!(tasks.is_empty());
+}
+
+#[tokio::test]
+async fn limit() {
+    let tasks = TaskSupervisor::new();
+    tasks.set_limit(Some(1));
+
+    let first = tasks.spawn("first", std::future::pending::<()>()).unwrap();
+    let err = tasks
+        .spawn("second", std::future::pending::<()>())
+        .unwrap_err();
+    assert_eq!(err.limit(), 1);
+
+    // Dropping a task's handle cancels it, freeing up a slot.
+    drop(first);
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while !tasks.is_empty() {
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .unwrap();
+    tasks.spawn_blocking("third", || {}).unwrap().await;
+}
+
+#[tokio::test]
+async fn cancelled_on_drop() {
+    let tasks = TaskSupervisor::new();
+    let mut handle = tasks
+        .spawn("pending", std::future::pending::<()>())
+        .unwrap();
+    drop(tasks);
+    assert!((&mut *handle).await.unwrap_err().is_cancelled());
+}
+
+#[test]
+fn ctx_limit() {
+    let ctx = WasiCtxBuilder::new().max_background_tasks(3).build();
+    assert_eq!(ctx.tasks().limit(), Some(3));
+    assert!(ctx.tasks().is_empty());
+}
+
+#[tokio::test]
+async fn supervised_streams() {
+    let mut builder = WasiCtxBuilder::new();
+    let (_read, write) = tokio::io::duplex(32);
+    let stream = AsyncWriteStream::new_supervised(32, write, builder.tasks()).unwrap();
+    let ctx = builder.build();
+    let names = ctx
+        .tasks()
+        .tasks()
+        .iter()
+        .map(|t| t.name())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["async-write-stream"]);
+
+    // Dropping the context cancels its tasks even though the stream, and a
+    // clone of the supervisor, are still alive.
+    let tasks = ctx.tasks().clone();
+    drop(ctx);
+    assert!(tasks.is_empty());
+    drop(stream);
+
+    let mut builder = WasiCtxBuilder::new();
+    builder.max_background_tasks(0);
+    let err = AsyncReadStream::new_supervised(tokio::io::empty(), builder.tasks())
+        .err()
+        .unwrap();
+    assert_eq!(err.limit(), 0);
+}
@@ -180,6 +180,10 @@ fn preview1_readlink() {
     run(PREVIEW1_READLINK_COMPONENT, false).unwrap()
 }
 #[test_log::test]
+fn preview1_read_vectored() {
+    run(PREVIEW1_READ_VECTORED_COMPONENT, false).unwrap()
+}
+#[test_log::test]
 fn preview1_remove_directory() {
     run(PREVIEW1_REMOVE_DIRECTORY_COMPONENT, false).unwrap()
 }
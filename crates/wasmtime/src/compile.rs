@@ -37,14 +37,22 @@ use std::{
 use wasmtime_environ::component::Translator;
 use wasmtime_environ::{
     BuiltinFunctionIndex, CompiledFunctionInfo, CompiledModuleInfo, Compiler, DefinedFuncIndex,
-    FinishedObject, FunctionBodyData, ModuleEnvironment, ModuleInternedTypeIndex,
+    EntityIndex, FinishedObject, FunctionBodyData, ModuleEnvironment, ModuleInternedTypeIndex,
     ModuleTranslation, ModuleTypes, ModuleTypesBuilder, ObjectKind, PrimaryMap, RelocationTarget,
     StaticModuleIndex, WasmFunctionInfo,
 };
 
 mod code_builder;
+pub(crate) use self::code_builder::CodeOptions;
 pub use self::code_builder::{CodeBuilder, CodeHint, HashedEngineCompileEnv};
 
+#[cfg(feature = "runtime")]
+mod determinism;
+mod lint;
+#[cfg(feature = "runtime")]
+pub use self::determinism::{DeterminismReport, NondeterminismSource};
+pub use self::lint::{Lint, LintConfig};
+
 #[cfg(feature = "runtime")]
 mod runtime;
 
@@ -64,6 +72,8 @@ pub(crate) fn build_artifacts<T: FinishedObject>(
     engine: &Engine,
     wasm: &[u8],
     dwarf_package: Option<&[u8]>,
+    external_debug_info: Option<&[u8]>,
+    options: &CodeOptions,
     obj_state: &T::State,
 ) -> Result<(T, Option<(CompiledModuleInfo, ModuleTypes)>)> {
     let tunables = engine.tunables();
@@ -76,9 +86,15 @@ pub(crate) fn build_artifacts<T: FinishedObject>(
     let mut validator = wasmparser::Validator::new_with_features(engine.features());
     parser.set_features(*validator.features());
     let mut types = ModuleTypesBuilder::new(&validator);
-    let mut translation = ModuleEnvironment::new(tunables, &mut validator, &mut types)
+    let mut environ = ModuleEnvironment::new(tunables, &mut validator, &mut types);
+    if let Some(debug_wasm) = external_debug_info {
+        environ = environ.external_debug_info(debug_wasm);
+    }
+    let mut translation = environ
         .translate(parser, wasm)
         .context("failed to parse WebAssembly module")?;
+    translation.nan_canonicalization = options.nan_canonicalization;
+    resolve_host_intrinsics(engine, &types, &mut translation);
     let functions = mem::take(&mut translation.function_body_inputs);
 
     let compile_inputs = CompileInputs::for_module(&types, &translation, functions);
@@ -117,6 +133,43 @@ pub(crate) fn build_artifacts<T: FinishedObject>(
     Ok((result, Some((info, types))))
 }
 
+/// Records which of the imported functions of `translation` are host
+/// intrinsics configured in `engine` and should be called directly.
+///
+/// This is only done for core modules: imports of core modules within a
+/// component are satisfied by the component itself, never by the host.
+fn resolve_host_intrinsics(
+    engine: &Engine,
+    types: &ModuleTypesBuilder,
+    translation: &mut ModuleTranslation<'_>,
+) {
+    let intrinsics = &engine.config().host_intrinsics;
+    if intrinsics.is_empty() || engine.is_pulley() {
+        return;
+    }
+    for initializer in translation.module.initializers.iter() {
+        let wasmtime_environ::Initializer::Import {
+            name,
+            field,
+            index: EntityIndex::Function(func),
+        } = initializer
+        else {
+            continue;
+        };
+        let ty = types[translation.module.functions[*func].signature].unwrap_func();
+        let intrinsic = intrinsics
+            .iter()
+            .position(|(module, intrinsic_name, intrinsic)| {
+                module == name && intrinsic_name == field && intrinsic.ty() == ty
+            });
+        if let Some(intrinsic) = intrinsic {
+            translation
+                .host_intrinsics
+                .insert(*func, u32::try_from(intrinsic).unwrap());
+        }
+    }
+}
+
 /// Performs the compilation phase for a component, translating and
 /// validating the provided wasm binary to machine code.
 ///
@@ -129,6 +182,8 @@ pub(crate) fn build_component_artifacts<T: FinishedObject>(
     engine: &Engine,
     binary: &[u8],
     _dwarf_package: Option<&[u8]>,
+    _external_debug_info: Option<&[u8]>,
+    options: &CodeOptions,
     obj_state: &T::State,
 ) -> Result<(T, Option<wasmtime_environ::component::ComponentArtifacts>)> {
     use wasmtime_environ::component::{
@@ -146,6 +201,9 @@ pub(crate) fn build_component_artifacts<T: FinishedObject>(
         Translator::new(tunables, &mut validator, &mut types, &scope)
             .translate(binary)
             .context("failed to parse WebAssembly module")?;
+    for (_, translation) in module_translations.iter_mut() {
+        translation.nan_canonicalization = options.nan_canonicalization;
+    }
 
     let compile_inputs = CompileInputs::for_component(
         engine,
@@ -174,9 +232,11 @@ pub(crate) fn build_component_artifacts<T: FinishedObject>(
     )?;
     let (types, ty) = types.finish(&component.component);
 
+    let trampoline_kinds = component.trampolines.values().map(|t| t.kind()).collect();
     let info = CompiledComponentInfo {
         component: component.component,
         trampolines: compilation_artifacts.trampolines,
+        trampoline_kinds,
         resource_drop_wasm_to_array_trampoline: compilation_artifacts
             .resource_drop_wasm_to_array_trampoline,
     };
@@ -698,7 +758,9 @@ impl FunctionIndices {
                     [&CompileKey::WASM_TO_BUILTIN_TRAMPOLINE_KIND]
                     [&CompileKey::wasm_to_builtin_trampoline(builtin)]
                     .unwrap_function(),
-                RelocationTarget::HostLibcall(_) | RelocationTarget::PulleyHostcall(_) => {
+                RelocationTarget::HostLibcall(_)
+                | RelocationTarget::PulleyHostcall(_)
+                | RelocationTarget::HostIntrinsic(_) => {
                     unreachable!("relocation is resolved at runtime, not compile time");
                 }
             },
@@ -1,5 +1,5 @@
 use crate::prelude::*;
-use crate::Engine;
+use crate::{Engine, Lint, LintConfig};
 use std::borrow::Cow;
 use std::path::Path;
 
@@ -20,7 +20,9 @@ use std::path::Path;
 /// A [`CodeBuilder`] requires a source of WebAssembly bytes to be configured
 /// before calling [`compile_module_serialized`] or [`compile_module`]. This can
 /// be provided with either the [`wasm_binary`] or [`wasm_binary_file`] method.
-/// Note that only a single source of bytes can be provided.
+/// Note that only a single source of bytes can be provided, except when
+/// compiling a batch of modules with [`compile_modules_serialized`], for
+/// which more sources can be added with [`wasm_binaries`].
 ///
 /// # WebAssembly Text Format
 ///
@@ -32,6 +34,8 @@ use std::path::Path;
 ///
 /// [`compile_module_serialized`]: CodeBuilder::compile_module_serialized
 /// [`compile_module`]: CodeBuilder::compile_module
+/// [`compile_modules_serialized`]: CodeBuilder::compile_modules_serialized
+/// [`wasm_binaries`]: CodeBuilder::wasm_binaries
 /// [`wasm_binary`]: CodeBuilder::wasm_binary
 /// [`wasm_binary_file`]: CodeBuilder::wasm_binary_file
 pub struct CodeBuilder<'a> {
@@ -40,6 +44,17 @@ pub struct CodeBuilder<'a> {
     wasm_path: Option<Cow<'a, Path>>,
     dwarf_package: Option<Cow<'a, [u8]>>,
     dwarf_package_path: Option<Cow<'a, Path>>,
+    external_debug_info: Option<Cow<'a, [u8]>>,
+    batch: Vec<Cow<'a, [u8]>>,
+    pub(super) options: CodeOptions,
+}
+
+/// Compilation options configured on a [`CodeBuilder`] which apply on top of
+/// the engine-wide configuration.
+#[derive(Copy, Clone, Debug, Default, Hash)]
+pub(crate) struct CodeOptions {
+    /// Whether NaN canonicalization is enabled for the code being compiled.
+    pub(crate) nan_canonicalization: bool,
 }
 
 /// Return value of [`CodeBuilder::hint`]
@@ -60,9 +75,31 @@ impl<'a> CodeBuilder<'a> {
             wasm_path: None,
             dwarf_package: None,
             dwarf_package_path: None,
+            external_debug_info: None,
+            batch: Vec::new(),
+            options: CodeOptions::default(),
         }
     }
 
+    /// Configures whether Cranelift should perform a NaN-canonicalization
+    /// pass on the code being compiled.
+    ///
+    /// This is the same as
+    /// [`Config::cranelift_nan_canonicalization`](crate::Config::cranelift_nan_canonicalization)
+    /// except that it only applies to the module or component compiled by
+    /// this builder, so deterministic NaN behavior can be requested for some
+    /// modules without paying its cost for all modules in an engine. Note that
+    /// this can only enable canonicalization: if it's enabled for the engine
+    /// then passing `false` here has no effect.
+    ///
+    /// This option is ignored by the Winch compiler.
+    ///
+    /// The default value for this is `false`.
+    pub fn cranelift_nan_canonicalization(&mut self, enable: bool) -> &mut Self {
+        self.options.nan_canonicalization = enable;
+        self
+    }
+
     /// Configures the WebAssembly binary that is being compiled.
     ///
     /// The `wasm_bytes` parameter must be a binary WebAssembly file.
@@ -175,6 +212,24 @@ impl<'a> CodeBuilder<'a> {
         }
     }
 
+    /// Adds WebAssembly binaries to be compiled as a batch with
+    /// [`CodeBuilder::compile_modules_serialized`].
+    ///
+    /// Each of `wasm_binaries` must be a binary WebAssembly module. They're
+    /// compiled after the module configured with [`CodeBuilder::wasm_binary`]
+    /// or related methods, if any, and this method may be called multiple
+    /// times to add more modules. These modules are ignored by all other
+    /// compilation methods.
+    pub fn wasm_binaries<I>(&mut self, wasm_binaries: I) -> &mut Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Cow<'a, [u8]>>,
+    {
+        self.batch
+            .extend(wasm_binaries.into_iter().map(|b| b.into()));
+        self
+    }
+
     pub(super) fn get_wasm(&self) -> Result<&[u8]> {
         self.wasm
             .as_deref()
@@ -236,6 +291,59 @@ impl<'a> CodeBuilder<'a> {
         Ok(self)
     }
 
+    /// Configures a WebAssembly binary holding the DWARF debug information
+    /// for the module being compiled.
+    ///
+    /// Some toolchains, for example Emscripten with `-gseparate-dwarf` or
+    /// Binaryen's `wasm-split`, move the `.debug_*` custom sections of a
+    /// module into a separate wasm file so the module itself can be shipped
+    /// without them. Supplying that file here re-associates the debug
+    /// information with the module during compilation, acting as the source
+    /// map for the compiled code. This is used when
+    /// [`Config::debug_info`](crate::Config::debug_info) or
+    /// [`Config::wasm_backtrace_details`](crate::Config::wasm_backtrace_details)
+    /// is enabled, for example to symbolicate backtraces with file and line
+    /// information.
+    ///
+    /// Only the DWARF custom sections of `wasm_bytes` are used and they take
+    /// precedence over any found in the module itself. The remainder of
+    /// `wasm_bytes` is neither used nor validated. This is not used when
+    /// compiling components.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if external debug information has already been
+    /// configured.
+    pub fn external_debug_info(&mut self, wasm_bytes: &'a [u8]) -> Result<&mut Self> {
+        if self.external_debug_info.is_some() {
+            bail!("cannot configure external debug info twice");
+        }
+        self.external_debug_info = Some(wasm_bytes.into());
+        Ok(self)
+    }
+
+    /// Same as [`CodeBuilder::external_debug_info`] except that the debug
+    /// information is read from `file`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if external debug information has already been
+    /// configured or if `file` cannot be read.
+    pub fn external_debug_info_file(&mut self, file: &Path) -> Result<&mut Self> {
+        if self.external_debug_info.is_some() {
+            bail!("cannot configure external debug info twice");
+        }
+        let wasm = std::fs::read(file)
+            .with_context(|| format!("failed to read external debug info: {}", file.display()))?;
+        self.external_debug_info = Some(wasm.into());
+        Ok(self)
+    }
+
+    /// Gets the external debug information, if any.
+    pub(super) fn get_external_debug_info(&self) -> Option<&[u8]> {
+        self.external_debug_info.as_deref()
+    }
+
     /// Returns a hint, if possible, of what the provided bytes are.
     ///
     /// This method can be use to detect what the previously supplied bytes to
@@ -256,6 +364,87 @@ impl<'a> CodeBuilder<'a> {
         }
     }
 
+    /// Analyzes the configured module for suspicious traits, such as very
+    /// large data segments or a start function which never terminates.
+    ///
+    /// This doesn't compile the module, and is intended to help hosts triage
+    /// third-party modules before or alongside compiling them. Thresholds for
+    /// what is considered suspicious are configured with `config`. Note that
+    /// modules are only partially validated here, and a module without lints
+    /// may still fail to compile.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no wasm bytes have been configured, if they're not
+    /// a core wasm module, or if they fail to parse.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn main() -> Result<()> {
+    /// let engine = Engine::default();
+    /// let wat = r#"(module (func $f (loop br 0)) (start $f))"#;
+    /// let lints = CodeBuilder::new(&engine)
+    ///     .wasm_binary_or_text(wat.as_bytes(), None)?
+    ///     .lint(&LintConfig::new())?;
+    /// assert_eq!(lints, [Lint::NonTerminatingStart { func_index: 0 }]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn lint(&self, config: &LintConfig) -> Result<Vec<Lint>> {
+        super::lint::lint(self.get_wasm()?, config)
+    }
+
+    /// Lists the sources of nondeterminism the configured module can observe
+    /// when instantiated with `linker` and compiled with this builder's
+    /// engine and options.
+    ///
+    /// This is intended for deployments, such as consensus systems, which
+    /// require all executions of a module to produce identical results. The
+    /// report includes imports from the linker which provide clocks, random
+    /// data, or other host functionality, imports which `linker` doesn't
+    /// define, floating-point and relaxed SIMD instructions whose results
+    /// depend on the host with the current configuration, shared memories,
+    /// and memory and table growth, which may fail depending on resource
+    /// limits. Like [`CodeBuilder::lint`] this doesn't compile the module.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no wasm bytes have been configured, if they're not
+    /// a core wasm module, or if they fail to parse.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn main() -> Result<()> {
+    /// let engine = Engine::default();
+    /// let linker = Linker::<()>::new(&engine);
+    /// let wat = r#"(module (func (param i32) (result i32) local.get 0 memory.grow) (memory 1))"#;
+    /// let report = CodeBuilder::new(&engine)
+    ///     .wasm_binary_or_text(wat.as_bytes(), None)?
+    ///     .audit_determinism(&linker)?;
+    /// assert!(matches!(
+    ///     report.sources(),
+    ///     [NondeterminismSource::MemoryGrowth { func_index: 0, .. }],
+    /// ));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "runtime")]
+    pub fn audit_determinism<T>(
+        &self,
+        linker: &crate::Linker<T>,
+    ) -> Result<crate::DeterminismReport> {
+        super::determinism::audit(
+            self.engine,
+            self.get_wasm()?,
+            self.options.nan_canonicalization,
+            &|module, name| linker.is_defined(module, name),
+        )
+    }
+
     /// Finishes this compilation and produces a serialized list of bytes.
     ///
     /// This method requires that either [`CodeBuilder::wasm_binary`] or
@@ -274,17 +463,84 @@ impl<'a> CodeBuilder<'a> {
     pub fn compile_module_serialized(&self) -> Result<Vec<u8>> {
         let wasm = self.get_wasm()?;
         let dwarf_package = self.get_dwarf_package();
-        let (v, _) = super::build_artifacts(self.engine, &wasm, dwarf_package.as_deref(), &())?;
+        let external_debug_info = self.get_external_debug_info();
+        let (v, _) = super::build_artifacts(
+            self.engine,
+            &wasm,
+            dwarf_package.as_deref(),
+            external_debug_info.as_deref(),
+            &self.options,
+            &(),
+        )?;
         Ok(v)
     }
 
+    /// Compiles a batch of modules, producing a serialized list of bytes for
+    /// each of them.
+    ///
+    /// The modules compiled are the one configured with
+    /// [`CodeBuilder::wasm_binary`] or related methods, if any, followed by
+    /// those added with [`CodeBuilder::wasm_binaries`], and the returned
+    /// artifacts are in the same order. Modules are compiled in parallel with
+    /// each other, in addition to their functions being compiled in parallel,
+    /// if [`Config::parallel_compilation`](crate::Config::parallel_compilation)
+    /// is enabled. This reduces the total time taken to compile many small
+    /// modules compared to compiling them one at a time.
+    ///
+    /// Note that DWARF packages and external debug information are not used
+    /// when compiling a batch, and like
+    /// [`CodeBuilder::compile_module_serialized`] this method never caches
+    /// compilations.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no wasm bytes have been configured, or if any of
+    /// the modules fails to compile, in which case the error for the first
+    /// such module is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn main() -> Result<()> {
+    /// let engine = Engine::default();
+    /// let empty = b"\0asm\x01\0\0\0";
+    /// let artifacts = CodeBuilder::new(&engine)
+    ///     .wasm_binary_or_text(r#"(module (func (export "a")))"#.as_bytes(), None)?
+    ///     .wasm_binaries([&empty[..], &empty[..]])
+    ///     .compile_modules_serialized()?;
+    /// assert_eq!(artifacts.len(), 3);
+    /// let a = unsafe { Module::deserialize(&engine, &artifacts[0])? };
+    /// assert!(a.get_export("a").is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn compile_modules_serialized(&self) -> Result<Vec<Vec<u8>>> {
+        let wasms = self
+            .wasm
+            .iter()
+            .chain(&self.batch)
+            .map(|wasm| &wasm[..])
+            .enumerate()
+            .collect::<Vec<_>>();
+        if wasms.is_empty() {
+            bail!("no wasm bytes have been configured");
+        }
+        self.engine.run_maybe_parallel(wasms, |(i, wasm)| {
+            let (v, _) = super::build_artifacts(self.engine, wasm, None, None, &self.options, &())
+                .with_context(|| format!("failed to compile module {i} of batch"))?;
+            Ok(v)
+        })
+    }
+
     /// Same as [`CodeBuilder::compile_module_serialized`] except that it
     /// compiles a serialized [`Component`](crate::component::Component)
     /// instead of a module.
     #[cfg(feature = "component-model")]
     pub fn compile_component_serialized(&self) -> Result<Vec<u8>> {
         let bytes = self.get_wasm()?;
-        let (v, _) = super::build_component_artifacts(self.engine, &bytes, None, &())?;
+        let (v, _) =
+            super::build_component_artifacts(self.engine, &bytes, None, None, &self.options, &())?;
         Ok(v)
     }
 }
@@ -310,6 +566,11 @@ impl std::hash::Hash for HashedEngineCompileEnv<'_> {
         self.0.tunables().hash(hasher);
         self.0.features().hash(hasher);
         config.wmemcheck.hash(hasher);
+        for (module, name, intrinsic) in config.host_intrinsics.iter() {
+            module.hash(hasher);
+            name.hash(hasher);
+            intrinsic.ty().hash(hasher);
+        }
 
         // Catch accidental bugs of reusing across crate versions.
         config.module_version.hash(hasher);
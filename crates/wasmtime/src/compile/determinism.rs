@@ -0,0 +1,373 @@
+//! Detection of the sources of nondeterminism a module can observe, see
+//! [`CodeBuilder::audit_determinism`].
+//!
+//! [`CodeBuilder::audit_determinism`]: crate::CodeBuilder::audit_determinism
+
+use crate::prelude::*;
+use crate::Engine;
+use core::fmt;
+use serde_derive::Serialize;
+use wasmparser::{Operator, Parser, Payload, TypeRef};
+use wasmtime_environ::FlagValue;
+
+/// The sources of nondeterminism a module can observe, as reported by
+/// [`CodeBuilder::audit_determinism`](crate::CodeBuilder::audit_determinism).
+///
+/// A module without any sources executes deterministically: given the same
+/// arguments and the same imported values it always produces the same
+/// results, on any host. The report implements `serde::Serialize`, so it can
+/// be exported in a machine-readable format for deployments, such as
+/// consensus systems, which must reject nondeterministic modules.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeterminismReport {
+    sources: Vec<NondeterminismSource>,
+}
+
+impl DeterminismReport {
+    /// The sources of nondeterminism found, in the order they appear in the
+    /// module.
+    pub fn sources(&self) -> &[NondeterminismSource] {
+        &self.sources
+    }
+
+    /// Whether no sources of nondeterminism were found.
+    pub fn is_deterministic(&self) -> bool {
+        self.sources.is_empty()
+    }
+}
+
+impl fmt::Display for DeterminismReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.sources.is_empty() {
+            return write!(f, "no sources of nondeterminism found");
+        }
+        for (i, source) in self.sources.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{source}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A source of nondeterminism in a [`DeterminismReport`].
+///
+/// Sources within function bodies are reported once per function, at the
+/// first instruction which is affected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum NondeterminismSource {
+    /// An imported WASI function which reads a clock, such as
+    /// `clock_time_get`, or which waits for a timeout, such as `poll_oneoff`.
+    Clock {
+        /// The module of the import.
+        module: String,
+        /// The name of the import.
+        name: String,
+    },
+    /// An imported WASI function which returns random data.
+    Random {
+        /// The module of the import.
+        module: String,
+        /// The name of the import.
+        name: String,
+    },
+    /// A function import defined by the host in the linker, whose behavior
+    /// can't be audited.
+    HostImport {
+        /// The module of the import.
+        module: String,
+        /// The name of the import.
+        name: String,
+    },
+    /// An import which isn't defined in the linker, so whatever is provided
+    /// for it at instantiation can't be audited.
+    UnresolvedImport {
+        /// The module of the import.
+        module: String,
+        /// The name of the import.
+        name: String,
+    },
+    /// A floating-point instruction which may produce a NaN whose bits differ
+    /// between hosts, because NaN canonicalization isn't enabled, see
+    /// [`Config::cranelift_nan_canonicalization`](crate::Config::cranelift_nan_canonicalization).
+    NanBits {
+        /// The index of the function containing the instruction.
+        func_index: u32,
+        /// The offset of the instruction in the module.
+        offset: usize,
+    },
+    /// A relaxed SIMD instruction whose results differ between hosts, because
+    /// [`Config::relaxed_simd_deterministic`](crate::Config::relaxed_simd_deterministic)
+    /// isn't enabled.
+    RelaxedSimd {
+        /// The index of the function containing the instruction.
+        func_index: u32,
+        /// The offset of the instruction in the module.
+        offset: usize,
+    },
+    /// A shared memory, whose contents may be modified concurrently by other
+    /// threads in an order which depends on scheduling.
+    SharedMemory {
+        /// The index of the memory.
+        memory_index: u32,
+    },
+    /// A `memory.grow` instruction, which may fail depending on the limits of
+    /// the store and the memory available to the host.
+    MemoryGrowth {
+        /// The index of the function containing the instruction.
+        func_index: u32,
+        /// The offset of the instruction in the module.
+        offset: usize,
+    },
+    /// A `table.grow` instruction, which may fail depending on the limits of
+    /// the store and the memory available to the host.
+    TableGrowth {
+        /// The index of the function containing the instruction.
+        func_index: u32,
+        /// The offset of the instruction in the module.
+        offset: usize,
+    },
+}
+
+impl fmt::Display for NondeterminismSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NondeterminismSource::Clock { module, name } => {
+                write!(f, "import `{module}::{name}` reads a clock")
+            }
+            NondeterminismSource::Random { module, name } => {
+                write!(f, "import `{module}::{name}` returns random data")
+            }
+            NondeterminismSource::HostImport { module, name } => {
+                write!(f, "import `{module}::{name}` is defined by the host")
+            }
+            NondeterminismSource::UnresolvedImport { module, name } => {
+                write!(f, "import `{module}::{name}` is not defined in the linker")
+            }
+            NondeterminismSource::NanBits { func_index, offset } => write!(
+                f,
+                "function {func_index} may produce NaNs with nondeterministic bits at offset {offset:#x}"
+            ),
+            NondeterminismSource::RelaxedSimd { func_index, offset } => write!(
+                f,
+                "function {func_index} uses relaxed SIMD at offset {offset:#x}"
+            ),
+            NondeterminismSource::SharedMemory { memory_index } => {
+                write!(f, "memory {memory_index} is shared between threads")
+            }
+            NondeterminismSource::MemoryGrowth { func_index, offset } => write!(
+                f,
+                "function {func_index} grows a memory at offset {offset:#x}, which may fail"
+            ),
+            NondeterminismSource::TableGrowth { func_index, offset } => write!(
+                f,
+                "function {func_index} grows a table at offset {offset:#x}, which may fail"
+            ),
+        }
+    }
+}
+
+/// The WASI preview1 functions which read clocks or wait for timeouts.
+const WASI_CLOCK_FUNCTIONS: &[&str] = &["clock_res_get", "clock_time_get", "poll_oneoff"];
+
+/// The WASI preview1 functions which return random data.
+const WASI_RANDOM_FUNCTIONS: &[&str] = &["random_get"];
+
+/// Reports the sources of nondeterminism of the core wasm module `wasm`,
+/// whose imports are looked up with `defined`, when compiled by `engine`.
+pub(super) fn audit(
+    engine: &Engine,
+    wasm: &[u8],
+    nan_canonicalization: bool,
+    defined: &dyn Fn(&str, &str) -> bool,
+) -> Result<DeterminismReport> {
+    let nan_canonicalization = nan_canonicalization
+        || engine.compiler().flags().iter().any(|(name, value)| {
+            *name == "enable_nan_canonicalization" && *value == FlagValue::Bool(true)
+        });
+    let relaxed_simd_deterministic = engine.tunables().relaxed_simd_deterministic;
+
+    let mut sources = Vec::new();
+    let mut imported_funcs = 0;
+    let mut defined_funcs = 0;
+    let mut memories = 0;
+
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload? {
+            Payload::Version { encoding, .. } => {
+                if encoding != wasmparser::Encoding::Module {
+                    bail!("only core wasm modules can be audited");
+                }
+            }
+            Payload::ImportSection(imports) => {
+                for import in imports {
+                    let import = import?;
+                    let module = import.module.to_string();
+                    let name = import.name.to_string();
+                    let is_func = matches!(import.ty, TypeRef::Func(_));
+                    if is_func {
+                        imported_funcs += 1;
+                    }
+                    if let TypeRef::Memory(ty) = import.ty {
+                        if ty.shared {
+                            sources.push(NondeterminismSource::SharedMemory {
+                                memory_index: memories,
+                            });
+                        }
+                        memories += 1;
+                    }
+                    if !defined(&module, &name) {
+                        sources.push(NondeterminismSource::UnresolvedImport { module, name });
+                        continue;
+                    }
+                    // Other imported items are values provided once at
+                    // instantiation, which are deterministic if the host
+                    // provides the same ones every time.
+                    if !is_func {
+                        continue;
+                    }
+                    let is_wasi = super::lint::WASI_MODULES.contains(&import.module);
+                    sources.push(if is_wasi && WASI_CLOCK_FUNCTIONS.contains(&import.name) {
+                        NondeterminismSource::Clock { module, name }
+                    } else if is_wasi && WASI_RANDOM_FUNCTIONS.contains(&import.name) {
+                        NondeterminismSource::Random { module, name }
+                    } else {
+                        NondeterminismSource::HostImport { module, name }
+                    });
+                }
+            }
+            Payload::MemorySection(section) => {
+                for ty in section {
+                    if ty?.shared {
+                        sources.push(NondeterminismSource::SharedMemory {
+                            memory_index: memories,
+                        });
+                    }
+                    memories += 1;
+                }
+            }
+            Payload::CodeSectionEntry(body) => {
+                let func_index = imported_funcs + defined_funcs;
+                defined_funcs += 1;
+
+                let mut nan_bits = nan_canonicalization;
+                let mut relaxed_simd = relaxed_simd_deterministic;
+                let mut memory_growth = false;
+                let mut table_growth = false;
+                let mut ops = body.get_operators_reader()?;
+                while !ops.eof() {
+                    let offset = ops.original_position();
+                    let op = ops.read()?;
+                    if !nan_bits && may_produce_nan(&op) {
+                        nan_bits = true;
+                        sources.push(NondeterminismSource::NanBits { func_index, offset });
+                    }
+                    if !relaxed_simd && is_relaxed_simd(&op) {
+                        relaxed_simd = true;
+                        sources.push(NondeterminismSource::RelaxedSimd { func_index, offset });
+                    }
+                    match op {
+                        Operator::MemoryGrow { .. } if !memory_growth => {
+                            memory_growth = true;
+                            sources.push(NondeterminismSource::MemoryGrowth { func_index, offset });
+                        }
+                        Operator::TableGrow { .. } if !table_growth => {
+                            table_growth = true;
+                            sources.push(NondeterminismSource::TableGrowth { func_index, offset });
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(DeterminismReport { sources })
+}
+
+/// Whether `op` may produce a NaN whose sign and payload bits aren't
+/// specified by WebAssembly.
+fn may_produce_nan(op: &Operator<'_>) -> bool {
+    matches!(
+        op,
+        Operator::F32Add
+            | Operator::F32Sub
+            | Operator::F32Mul
+            | Operator::F32Div
+            | Operator::F32Sqrt
+            | Operator::F32Min
+            | Operator::F32Max
+            | Operator::F32Ceil
+            | Operator::F32Floor
+            | Operator::F32Trunc
+            | Operator::F32Nearest
+            | Operator::F32DemoteF64
+            | Operator::F64Add
+            | Operator::F64Sub
+            | Operator::F64Mul
+            | Operator::F64Div
+            | Operator::F64Sqrt
+            | Operator::F64Min
+            | Operator::F64Max
+            | Operator::F64Ceil
+            | Operator::F64Floor
+            | Operator::F64Trunc
+            | Operator::F64Nearest
+            | Operator::F64PromoteF32
+            | Operator::F32x4Add
+            | Operator::F32x4Sub
+            | Operator::F32x4Mul
+            | Operator::F32x4Div
+            | Operator::F32x4Sqrt
+            | Operator::F32x4Min
+            | Operator::F32x4Max
+            | Operator::F32x4Ceil
+            | Operator::F32x4Floor
+            | Operator::F32x4Trunc
+            | Operator::F32x4Nearest
+            | Operator::F32x4DemoteF64x2Zero
+            | Operator::F64x2Add
+            | Operator::F64x2Sub
+            | Operator::F64x2Mul
+            | Operator::F64x2Div
+            | Operator::F64x2Sqrt
+            | Operator::F64x2Min
+            | Operator::F64x2Max
+            | Operator::F64x2Ceil
+            | Operator::F64x2Floor
+            | Operator::F64x2Trunc
+            | Operator::F64x2Nearest
+            | Operator::F64x2PromoteLowF32x4
+    )
+}
+
+/// Whether `op` is a relaxed SIMD instruction.
+fn is_relaxed_simd(op: &Operator<'_>) -> bool {
+    matches!(
+        op,
+        Operator::I8x16RelaxedSwizzle
+            | Operator::I32x4RelaxedTruncF32x4S
+            | Operator::I32x4RelaxedTruncF32x4U
+            | Operator::I32x4RelaxedTruncF64x2SZero
+            | Operator::I32x4RelaxedTruncF64x2UZero
+            | Operator::F32x4RelaxedMadd
+            | Operator::F32x4RelaxedNmadd
+            | Operator::F64x2RelaxedMadd
+            | Operator::F64x2RelaxedNmadd
+            | Operator::I8x16RelaxedLaneselect
+            | Operator::I16x8RelaxedLaneselect
+            | Operator::I32x4RelaxedLaneselect
+            | Operator::I64x2RelaxedLaneselect
+            | Operator::F32x4RelaxedMin
+            | Operator::F32x4RelaxedMax
+            | Operator::F64x2RelaxedMin
+            | Operator::F64x2RelaxedMax
+            | Operator::I16x8RelaxedQ15mulrS
+            | Operator::I16x8RelaxedDotI8x16I7x16S
+            | Operator::I32x4RelaxedDotI8x16I7x16AddS
+    )
+}
@@ -0,0 +1,333 @@
+//! Detection of suspicious traits of modules, see [`CodeBuilder::lint`].
+//!
+//! [`CodeBuilder::lint`]: crate::CodeBuilder::lint
+
+use crate::prelude::*;
+use core::fmt;
+use wasmparser::{Operator, Parser, Payload, TypeRef};
+
+/// Thresholds used by [`CodeBuilder::lint`](crate::CodeBuilder::lint) to
+/// decide which traits of a module are suspicious.
+#[derive(Debug, Clone)]
+pub struct LintConfig {
+    max_data_segment_size: usize,
+    max_br_table_targets: u32,
+    max_function_size: usize,
+}
+
+impl LintConfig {
+    /// Creates a configuration with the default thresholds.
+    pub fn new() -> LintConfig {
+        LintConfig {
+            max_data_segment_size: 16 << 20,
+            max_br_table_targets: 10_000,
+            max_function_size: 1 << 20,
+        }
+    }
+
+    /// The size in bytes above which a data segment is reported with
+    /// [`Lint::LargeDataSegment`].
+    ///
+    /// Default: 16 MiB.
+    pub fn max_data_segment_size(mut self, size: usize) -> LintConfig {
+        self.max_data_segment_size = size;
+        self
+    }
+
+    /// The number of targets above which a `br_table` is reported with
+    /// [`Lint::LargeBrTable`].
+    ///
+    /// Default: 10,000.
+    pub fn max_br_table_targets(mut self, targets: u32) -> LintConfig {
+        self.max_br_table_targets = targets;
+        self
+    }
+
+    /// The size in bytes of a function's body above which it's reported with
+    /// [`Lint::LargeFunction`].
+    ///
+    /// Default: 1 MiB.
+    pub fn max_function_size(mut self, size: usize) -> LintConfig {
+        self.max_function_size = size;
+        self
+    }
+}
+
+impl Default for LintConfig {
+    fn default() -> LintConfig {
+        LintConfig::new()
+    }
+}
+
+/// A suspicious trait of a module reported by
+/// [`CodeBuilder::lint`](crate::CodeBuilder::lint).
+///
+/// None of these make a module invalid, but they're unusual for modules
+/// produced by typical toolchains and may indicate a module which is
+/// malicious or will perform poorly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Lint {
+    /// A data segment is larger than
+    /// [`LintConfig::max_data_segment_size`].
+    LargeDataSegment {
+        /// The index of the data segment.
+        index: u32,
+        /// The size of the data segment, in bytes.
+        size: usize,
+    },
+    /// The start function looks like it never terminates: it contains a loop
+    /// unconditionally branching back to itself and has no other way to exit,
+    /// so instantiating the module would hang.
+    NonTerminatingStart {
+        /// The index of the start function.
+        func_index: u32,
+    },
+    /// A `br_table` has more targets than
+    /// [`LintConfig::max_br_table_targets`].
+    LargeBrTable {
+        /// The index of the function containing the `br_table`.
+        func_index: u32,
+        /// The offset of the `br_table` in the module.
+        offset: usize,
+        /// The number of targets, excluding the default target.
+        targets: u32,
+    },
+    /// A function's body is larger than [`LintConfig::max_function_size`].
+    LargeFunction {
+        /// The index of the function.
+        func_index: u32,
+        /// The size of the function's body, in bytes.
+        size: usize,
+    },
+    /// An import from a WASI preview1 module which isn't part of WASI, such
+    /// as a misspelled function, which a host implementing WASI won't
+    /// recognize but a host defining its own functions in the same namespace
+    /// might.
+    ShadowedWasiImport {
+        /// The module of the import.
+        module: String,
+        /// The name of the import.
+        name: String,
+    },
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lint::LargeDataSegment { index, size } => {
+                write!(f, "data segment {index} is {size} bytes large")
+            }
+            Lint::NonTerminatingStart { func_index } => {
+                write!(f, "start function {func_index} may never terminate")
+            }
+            Lint::LargeBrTable {
+                func_index,
+                offset,
+                targets,
+            } => write!(
+                f,
+                "function {func_index} has a `br_table` with {targets} targets at offset {offset:#x}"
+            ),
+            Lint::LargeFunction { func_index, size } => {
+                write!(f, "function {func_index} is {size} bytes large")
+            }
+            Lint::ShadowedWasiImport { module, name } => {
+                write!(f, "import `{module}::{name}` is not a WASI function")
+            }
+        }
+    }
+}
+
+/// The core wasm modules which WASI preview1 is imported from.
+pub(super) const WASI_MODULES: &[&str] = &["wasi_snapshot_preview1", "wasi_unstable"];
+
+/// The functions of WASI preview1.
+const WASI_FUNCTIONS: &[&str] = &[
+    "args_get",
+    "args_sizes_get",
+    "clock_res_get",
+    "clock_time_get",
+    "environ_get",
+    "environ_sizes_get",
+    "fd_advise",
+    "fd_allocate",
+    "fd_close",
+    "fd_datasync",
+    "fd_fdstat_get",
+    "fd_fdstat_set_flags",
+    "fd_fdstat_set_rights",
+    "fd_filestat_get",
+    "fd_filestat_set_size",
+    "fd_filestat_set_times",
+    "fd_pread",
+    "fd_prestat_dir_name",
+    "fd_prestat_get",
+    "fd_pwrite",
+    "fd_read",
+    "fd_readdir",
+    "fd_renumber",
+    "fd_seek",
+    "fd_sync",
+    "fd_tell",
+    "fd_write",
+    "path_create_directory",
+    "path_filestat_get",
+    "path_filestat_set_times",
+    "path_link",
+    "path_open",
+    "path_readlink",
+    "path_remove_directory",
+    "path_rename",
+    "path_symlink",
+    "path_unlink_file",
+    "poll_oneoff",
+    "proc_exit",
+    "proc_raise",
+    "random_get",
+    "sched_yield",
+    "sock_accept",
+    "sock_recv",
+    "sock_send",
+    "sock_shutdown",
+];
+
+/// Reports the suspicious traits of the core wasm module `wasm`.
+pub(super) fn lint(wasm: &[u8], config: &LintConfig) -> Result<Vec<Lint>> {
+    let mut lints = Vec::new();
+    let mut imported_funcs = 0;
+    let mut defined_funcs = 0;
+    let mut start = None;
+    let mut start_lint = None;
+
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload? {
+            Payload::Version { encoding, .. } => {
+                if encoding != wasmparser::Encoding::Module {
+                    bail!("only core wasm modules can be linted");
+                }
+            }
+            Payload::ImportSection(imports) => {
+                for import in imports {
+                    let import = import?;
+                    let is_func = matches!(import.ty, TypeRef::Func(_));
+                    if is_func {
+                        imported_funcs += 1;
+                    }
+                    if WASI_MODULES.contains(&import.module)
+                        && !(is_func && WASI_FUNCTIONS.contains(&import.name))
+                    {
+                        lints.push(Lint::ShadowedWasiImport {
+                            module: import.module.to_string(),
+                            name: import.name.to_string(),
+                        });
+                    }
+                }
+            }
+            Payload::StartSection { func, .. } => start = Some(func),
+            Payload::DataSection(data) => {
+                for (index, segment) in (0..).zip(data) {
+                    let size = segment?.data.len();
+                    if size > config.max_data_segment_size {
+                        lints.push(Lint::LargeDataSegment { index, size });
+                    }
+                }
+            }
+            Payload::CodeSectionEntry(body) => {
+                let func_index = imported_funcs + defined_funcs;
+                defined_funcs += 1;
+
+                let size = body.range().len();
+                if size > config.max_function_size {
+                    lints.push(Lint::LargeFunction { func_index, size });
+                }
+
+                let is_start = start == Some(func_index);
+                let mut loops = LoopDetector::default();
+                let mut ops = body.get_operators_reader()?;
+                while !ops.eof() {
+                    let offset = ops.original_position();
+                    let op = ops.read()?;
+                    if let Operator::BrTable { targets } = &op {
+                        if targets.len() > config.max_br_table_targets {
+                            lints.push(Lint::LargeBrTable {
+                                func_index,
+                                offset,
+                                targets: targets.len(),
+                            });
+                        }
+                    }
+                    if is_start {
+                        loops.visit(&op);
+                    }
+                }
+                if is_start && loops.never_terminates() {
+                    start_lint = Some(Lint::NonTerminatingStart { func_index });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    lints.extend(start_lint);
+    Ok(lints)
+}
+
+/// A heuristic for functions which never terminate: the function
+/// unconditionally branches back to a loop, and has no control flow which may
+/// leave it.
+#[derive(Default)]
+struct LoopDetector {
+    /// Whether each enclosing block is a `loop`.
+    blocks: Vec<bool>,
+    backwards_branch: bool,
+    may_exit: bool,
+}
+
+impl LoopDetector {
+    fn visit(&mut self, op: &Operator<'_>) {
+        match op {
+            Operator::Block { .. } | Operator::If { .. } | Operator::TryTable { .. } => {
+                self.blocks.push(false)
+            }
+            Operator::Loop { .. } => self.blocks.push(true),
+            Operator::End => {
+                self.blocks.pop();
+            }
+            Operator::Br { relative_depth } => {
+                let depth = usize::try_from(*relative_depth).unwrap();
+                match self.blocks.iter().rev().nth(depth) {
+                    Some(true) => self.backwards_branch = true,
+                    _ => self.may_exit = true,
+                }
+            }
+            // Anything which may conditionally branch, leave the function or
+            // call other code which may do so could end the loop.
+            Operator::BrIf { .. }
+            | Operator::BrTable { .. }
+            | Operator::BrOnNull { .. }
+            | Operator::BrOnNonNull { .. }
+            | Operator::BrOnCast { .. }
+            | Operator::BrOnCastFail { .. }
+            | Operator::Return
+            | Operator::Unreachable
+            | Operator::Call { .. }
+            | Operator::CallIndirect { .. }
+            | Operator::CallRef { .. }
+            | Operator::ReturnCall { .. }
+            | Operator::ReturnCallIndirect { .. }
+            | Operator::ReturnCallRef { .. }
+            | Operator::Throw { .. }
+            | Operator::ThrowRef
+            | Operator::Suspend { .. }
+            | Operator::Resume { .. }
+            | Operator::ResumeThrow { .. }
+            | Operator::Switch { .. } => self.may_exit = true,
+            _ => {}
+        }
+    }
+
+    fn never_terminates(&self) -> bool {
+        self.backwards_branch && !self.may_exit
+    }
+}
@@ -1,4 +1,4 @@
-use crate::compile::HashedEngineCompileEnv;
+use crate::compile::{CodeOptions, HashedEngineCompileEnv};
 #[cfg(feature = "component-model")]
 use crate::component::Component;
 use crate::prelude::*;
@@ -15,16 +15,20 @@ impl<'a> CodeBuilder<'a> {
             &Engine,
             &[u8],
             Option<&[u8]>,
+            Option<&[u8]>,
+            &CodeOptions,
             &S,
         ) -> Result<(MmapVecWrapper, Option<T>)>,
         state: &S,
     ) -> Result<(Arc<CodeMemory>, Option<T>)> {
         let wasm = self.get_wasm()?;
         let dwarf_package = self.get_dwarf_package();
+        let external_debug_info = self.get_external_debug_info();
 
         self.engine
             .check_compatible_with_native_host()
-            .context("compilation settings are not compatible with the native host")?;
+            .context("compilation settings are not compatible with the native host")
+            .map_err(|e| crate::ErrorKind::Configuration.tag(e))?;
 
         #[cfg(feature = "cache")]
         {
@@ -32,6 +36,8 @@ impl<'a> CodeBuilder<'a> {
                 HashedEngineCompileEnv(self.engine),
                 &wasm,
                 &dwarf_package,
+                &external_debug_info,
+                &self.options,
                 // Don't hash this as it's just its own "pure" function pointer.
                 NotHashed(build_artifacts),
                 // Don't hash the FinishedObject state: this contains
@@ -39,33 +45,60 @@ impl<'a> CodeBuilder<'a> {
                 // not impact the compilation result itself.
                 NotHashed(state),
             );
+            if let Some(store) = self.engine.module_cache_store() {
+                let (_, _, _, _, _, _, NotHashed(obj_state)) = &state;
+                let key = wasmtime_cache::hash_state(&state);
+                if let Some(bytes) = store.get(&key) {
+                    match self.engine.load_code_bytes(&bytes, object_kind(&wasm)) {
+                        Ok(code) => return Ok((code, None)),
+                        Err(e) => {
+                            log::warn!("failed to load cached module, evicting it: {e:?}");
+                            store.remove(&key);
+                        }
+                    }
+                }
+                let (mmap, info_and_types) = build_artifacts(
+                    self.engine,
+                    &wasm,
+                    dwarf_package.as_deref(),
+                    external_debug_info.as_deref(),
+                    &self.options,
+                    obj_state,
+                )?;
+                let code = publish_mmap(self.engine, mmap.0)?;
+                if !store.insert(&key, code.mmap().to_vec()) {
+                    log::warn!("failed to insert compiled module into cache store");
+                }
+                return Ok((code, info_and_types));
+            }
+
             let (code, info_and_types) =
                 wasmtime_cache::ModuleCacheEntry::new("wasmtime", self.engine.cache_config())
                     .get_data_raw(
                         &state,
                         // Cache miss, compute the actual artifacts
-                        |(engine, wasm, dwarf_package, build_artifacts, state)| -> Result<_> {
+                        |(engine, wasm, dwp, debug, options, build_artifacts, state)| -> Result<_> {
                             let (mmap, info) = (build_artifacts.0)(
                                 engine.0,
                                 wasm,
-                                dwarf_package.as_deref(),
+                                dwp.as_deref(),
+                                debug.as_deref(),
+                                options,
                                 state.0,
                             )?;
                             let code = publish_mmap(engine.0, mmap.0)?;
                             Ok((code, info))
                         },
                         // Implementation of how to serialize artifacts
-                        |(_engine, _wasm, _, _, _), (code, _info_and_types)| {
+                        |(_engine, _wasm, _, _, _, _, _), (code, _info_and_types)| {
                             Some(code.mmap().to_vec())
                         },
                         // Cache hit, deserialize the provided artifacts
-                        |(engine, wasm, _, _, _), serialized_bytes| {
-                            let kind = if wasmparser::Parser::is_component(&wasm) {
-                                ObjectKind::Component
-                            } else {
-                                ObjectKind::Module
-                            };
-                            let code = engine.0.load_code_bytes(&serialized_bytes, kind).ok()?;
+                        |(engine, wasm, _, _, _, _, _), serialized_bytes| {
+                            let code = engine
+                                .0
+                                .load_code_bytes(&serialized_bytes, object_kind(wasm))
+                                .ok()?;
                             Some((code, None))
                         },
                     )?;
@@ -74,8 +107,14 @@ impl<'a> CodeBuilder<'a> {
 
         #[cfg(not(feature = "cache"))]
         {
-            let (mmap, info_and_types) =
-                build_artifacts(self.engine, &wasm, dwarf_package.as_deref(), state)?;
+            let (mmap, info_and_types) = build_artifacts(
+                self.engine,
+                &wasm,
+                dwarf_package.as_deref(),
+                external_debug_info.as_deref(),
+                &self.options,
+                state,
+            )?;
             let code = publish_mmap(self.engine, mmap.0)?;
             return Ok((code, info_and_types));
         }
@@ -120,6 +159,15 @@ impl<'a> CodeBuilder<'a> {
     }
 }
 
+#[cfg(feature = "cache")]
+fn object_kind(wasm: &[u8]) -> ObjectKind {
+    if wasmparser::Parser::is_component(wasm) {
+        ObjectKind::Component
+    } else {
+        ObjectKind::Module
+    }
+}
+
 fn publish_mmap(engine: &Engine, mmap: MmapVec) -> Result<Arc<CodeMemory>> {
     let mut code = CodeMemory::new(engine, mmap)?;
     code.publish()?;
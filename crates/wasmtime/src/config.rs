@@ -12,7 +12,10 @@ use wasmparser::WasmFeatures;
 #[cfg(feature = "cache")]
 use wasmtime_cache::CacheConfig;
 use wasmtime_continuations::WasmFXConfig;
-use wasmtime_environ::{ConfigTunables, TripleExt, Tunables};
+use wasmtime_environ::{ConfigTunables, TripleExt, Tunables, WasmFuncType, WasmValType};
+
+#[cfg(feature = "toml")]
+mod toml;
 
 #[cfg(feature = "runtime")]
 use crate::memory::MemoryCreator;
@@ -34,7 +37,10 @@ use wasmtime_fiber::RuntimeFiberStackCreator;
 pub use crate::runtime::code_memory::CustomCodeMemory;
 #[cfg(feature = "pooling-allocator")]
 pub use crate::runtime::vm::MpkEnabled;
-#[cfg(all(feature = "incremental-cache", feature = "cranelift"))]
+#[cfg(any(
+    feature = "cache",
+    all(feature = "incremental-cache", feature = "cranelift")
+))]
 pub use wasmtime_environ::CacheStore;
 
 /// Represents the module instance allocation strategy to use.
@@ -142,6 +148,8 @@ pub struct Config {
 
     #[cfg(feature = "cache")]
     pub(crate) cache_config: CacheConfig,
+    #[cfg(feature = "cache")]
+    pub(crate) module_cache_store: Option<Arc<dyn CacheStore>>,
     #[cfg(feature = "runtime")]
     pub(crate) mem_creator: Option<Arc<dyn RuntimeMemoryCreator>>,
     #[cfg(feature = "runtime")]
@@ -173,8 +181,13 @@ pub struct Config {
     pub(crate) force_memory_init_memfd: bool,
     pub(crate) wmemcheck: bool,
     pub(crate) coredump_on_trap: bool,
+    pub(crate) reset_fp_environment: bool,
+    pub(crate) host_intrinsics: Vec<(String, String, HostIntrinsic)>,
+    pub(crate) metrics: bool,
     pub(crate) macos_use_mach_ports: bool,
     pub(crate) detect_host_feature: Option<fn(&str) -> Option<bool>>,
+    #[cfg(feature = "runtime")]
+    pub(crate) crash_handler: Option<fn(&crate::WasmCrashInfo<'_>)>,
 }
 
 /// User-provided configuration for the compiler.
@@ -242,12 +255,16 @@ impl Config {
             wasmfx_config: WasmFXConfig {
                 stack_size: wasmtime_continuations::DEFAULT_FIBER_SIZE,
                 red_zone_size: wasmtime_continuations::DEFAULT_RED_ZONE_SIZE,
+                initial_stack_size: None,
+                stack_cache_size: 0,
             },
             target: None,
             #[cfg(feature = "gc")]
             collector: Collector::default(),
             #[cfg(feature = "cache")]
             cache_config: CacheConfig::new_cache_disabled(),
+            #[cfg(feature = "cache")]
+            module_cache_store: None,
             profiling_strategy: ProfilingStrategy::None,
             #[cfg(feature = "runtime")]
             mem_creator: None,
@@ -281,11 +298,16 @@ impl Config {
             force_memory_init_memfd: false,
             wmemcheck: false,
             coredump_on_trap: false,
+            reset_fp_environment: false,
+            host_intrinsics: Vec::new(),
+            metrics: false,
             macos_use_mach_ports: !cfg!(miri),
             #[cfg(feature = "std")]
             detect_host_feature: Some(detect_host_feature),
             #[cfg(not(feature = "std"))]
             detect_host_feature: None,
+            #[cfg(feature = "runtime")]
+            crash_handler: None,
         };
         #[cfg(any(feature = "cranelift", feature = "winch"))]
         {
@@ -679,6 +701,76 @@ impl Config {
         self
     }
 
+    /// Configures whether compiled code notifies the host on every
+    /// `global.set` so that [`Global::watch`](crate::Global::watch)
+    /// callbacks are invoked.
+    ///
+    /// This is a debugging aid: when enabled each `global.set` instruction is
+    /// followed by a call into the runtime, which noticeably slows down code
+    /// that writes globals frequently (for example the stack pointer global of
+    /// most toolchains). Watchpoints are looked up at runtime, so enabling
+    /// this has no effect on behavior until a callback is registered.
+    ///
+    /// This is `false` by default.
+    ///
+    /// **Note** Enabling this option is not compatible with the Winch compiler.
+    pub fn global_watchpoints(&mut self, enable: bool) -> &mut Self {
+        self.tunables.global_watchpoints = Some(enable);
+        self
+    }
+
+    /// Configures whether compiled code can be single-stepped one WebAssembly
+    /// instruction at a time.
+    ///
+    /// When enabled compiled code calls into the runtime before executing
+    /// each wasm instruction. Each store then tracks a budget of
+    /// instructions, configured with
+    /// [`Store::set_single_step_budget`](crate::Store::set_single_step_budget),
+    /// and once that budget is exhausted the callback configured with
+    /// [`Store::single_step_callback`](crate::Store::single_step_callback) is
+    /// invoked with the offset of the next instruction in the original wasm
+    /// module. This makes execution resumable at instruction granularity and,
+    /// combined with fuel or a deterministic host, is a building block for
+    /// debuggers and other tools which need to replay or inspect execution.
+    ///
+    /// This is intended for debugging only: every instruction incurs a call
+    /// into the host and executes orders of magnitude slower as a result.
+    ///
+    /// This is `false` by default.
+    ///
+    /// **Note** Enabling this option is not compatible with the Winch compiler.
+    pub fn wasm_single_step(&mut self, enable: bool) -> &mut Self {
+        self.tunables.single_step = Some(enable);
+        self
+    }
+
+    /// Configures whether compiled code records the control-flow edges it
+    /// takes, for coverage-guided fuzzing of WebAssembly modules.
+    ///
+    /// When enabled compiled code increments a byte-sized counter in the
+    /// coverage map of its store, configured with
+    /// [`Store::set_coverage_map`](crate::Store::set_coverage_map), every time
+    /// it takes an edge between two basic blocks of the original wasm
+    /// module. Counters are indexed in the same way as AFL's shared memory
+    /// map: each block is assigned an id by hashing its offset in the module,
+    /// and an edge from block `a` to block `b` increments the counter at
+    /// `(a >> 1) ^ b`, masked to the size of the map, with counters wrapping
+    /// around on overflow. This makes the map usable directly as AFL's
+    /// `__afl_area_ptr` or as libFuzzer's extra 8-bit counters, so the wasm
+    /// code executed by a fuzz target guides the fuzzer like native code
+    /// compiled with its instrumentation would.
+    ///
+    /// Counters are updated inline without calling into the runtime, which
+    /// costs a few instructions at the start of every basic block.
+    ///
+    /// This is `false` by default.
+    ///
+    /// **Note** Enabling this option is not compatible with the Winch compiler.
+    pub fn coverage_instrumentation(&mut self, enable: bool) -> &mut Self {
+        self.tunables.coverage_instrumentation = Some(enable);
+        self
+    }
+
     /// Configures the maximum amount of stack space available for
     /// executing WebAssembly code.
     ///
@@ -765,6 +857,73 @@ impl Config {
         self
     }
 
+    /// Configures stacks created with cont.new instructions to be allocated
+    /// lazily and grown on demand, starting with `size` bytes.
+    ///
+    /// By default each continuation stack is fully accessible up to
+    /// [`Config::wasmfx_stack_size`] from the start. With this option set
+    /// only address space is reserved up front for that size, and only the
+    /// topmost `size` bytes are made accessible. When execution reaches
+    /// beyond the accessible part of a stack the fault is caught and the stack
+    /// is grown, at least doubling its accessible size each time, up to
+    /// [`Config::wasmfx_stack_size`]. This greatly reduces the initial
+    /// footprint of workloads which create many small continuations.
+    ///
+    /// # Errors
+    ///
+    /// The `Engine::new` method will fail if `size` exceeds
+    /// [`Config::wasmfx_stack_size`], if signals-based traps are disabled, or
+    /// if this build of Wasmtime doesn't support growable continuation stacks.
+    /// Growable stacks are only supported on Unix platforms with the optimized
+    /// stack switching implementation, and not with pooled or
+    /// `malloc`-allocated continuation stacks.
+    pub fn wasmfx_initial_stack_size(&mut self, size: usize) -> &mut Self {
+        self.wasmfx_config.initial_stack_size = Some(size);
+        self
+    }
+
+    /// Configures how many stacks of continuations which have returned each
+    /// [`Store`](crate::Store) keeps around for reuse.
+    ///
+    /// Creating a continuation with `cont.new` requires a stack, which is
+    /// normally mapped fresh, guard page included, for every continuation and
+    /// unmapped again once it returns. Programs which rapidly create and
+    /// finish continuations, such as generators or async runtimes, spend a
+    /// significant amount of time doing so. With this option each store
+    /// instead caches up to `size` stacks, grouped by their size, and hands
+    /// them out to new continuations first. Cached stacks are only freed when
+    /// the store is dropped or when the cache is trimmed with
+    /// [`Store::trim_wasmfx_stack_cache`](crate::Store::trim_wasmfx_stack_cache).
+    ///
+    /// The cache is not used with the WasmFX pooling allocator, whose stacks
+    /// are always reused.
+    ///
+    /// By default this option is 0, which disables caching.
+    pub fn wasmfx_stack_cache_size(&mut self, size: usize) -> &mut Self {
+        self.wasmfx_config.stack_cache_size = size;
+        self
+    }
+
+    /// Configures whether handlers installed by `resume` instructions are
+    /// looked up through tag-indexed dispatch tables.
+    ///
+    /// By default a `suspend` instruction finds its handler by comparing its
+    /// tag against each handler of each enclosing `resume` in turn, so its
+    /// cost grows with the number of handlers. With this option enabled each
+    /// `resume` instead stores its suspend handlers in a small hash table
+    /// keyed by tag, making the lookup in each enclosing `resume` take
+    /// constant time in the common case at the expense of a little more work
+    /// to install the handlers. This pays off for `resume` instructions with
+    /// many handlers.
+    ///
+    /// This option only affects the optimized stack switching implementation.
+    ///
+    /// This is `false` by default.
+    pub fn wasmfx_tag_dispatch_tables(&mut self, enable: bool) -> &mut Self {
+        self.tunables.wasmfx_tag_dispatch_tables = Some(enable);
+        self
+    }
+
     /// Configures whether or not stacks used for async futures are zeroed
     /// before (re)use.
     ///
@@ -1154,6 +1313,23 @@ impl Config {
         self
     }
 
+    /// Configures whether components may use the `stream<T>` and `future<T>`
+    /// types of the async component model [proposal].
+    ///
+    /// Note that this is a preview of a work-in-progress proposal: values of
+    /// these types can be passed between the host and components, but the
+    /// canonical built-ins for reading and writing them from within a
+    /// component are not yet supported.
+    ///
+    /// This is `false` by default.
+    ///
+    /// [proposal]: https://github.com/WebAssembly/component-model/blob/main/design/mvp/Async.md
+    #[cfg(feature = "component-model-async")]
+    pub fn wasm_component_model_async(&mut self, enable: bool) -> &mut Self {
+        self.wasm_feature(WasmFeatures::COMPONENT_MODEL_ASYNC, enable);
+        self
+    }
+
     /// Configures which compilation strategy will be used for wasm modules.
     ///
     /// This method can be used to configure which compiler is used for wasm
@@ -1279,6 +1455,34 @@ impl Config {
         self
     }
 
+    /// Configures whether Cranelift removes bounds checks and other
+    /// conditional traps which can never trap because a dominating check
+    /// already covers them.
+    ///
+    /// For example an unrolled loop accessing `p[3]`, `p[2]`, `p[1]`, and
+    /// `p[0]` only needs its first bounds check. Overflow checks on
+    /// additions which can't overflow are removed too.
+    ///
+    /// Note that bounds checks with Spectre mitigations are never removed,
+    /// since they exist precisely because a dominating check may be
+    /// mispredicted. Heap accesses are Spectre-guarded by default on most
+    /// platforms, and memories relying on guard pages have no explicit
+    /// bounds checks at all, so this mostly helps embeddings which disable
+    /// the `enable_heap_access_spectre_mitigation` Cranelift setting and use
+    /// memories without large guard regions. This only has an effect when
+    /// optimizations are enabled with [`Config::cranelift_opt_level`].
+    ///
+    /// The default value for this is `false`.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn cranelift_redundant_check_elimination(&mut self, enable: bool) -> &mut Self {
+        let val = if enable { "true" } else { "false" };
+        self.compiler_config.settings.insert(
+            "enable_redundant_check_elimination".to_string(),
+            val.to_string(),
+        );
+        self
+    }
+
     /// Controls whether proof-carrying code (PCC) is used to validate
     /// lowering of Wasm sandbox checks.
     ///
@@ -1301,6 +1505,46 @@ impl Config {
         self
     }
 
+    /// Configures the maximum size of WebAssembly functions which Cranelift
+    /// inlines into their callers.
+    ///
+    /// Direct calls to functions defined in the same module whose body is at
+    /// most `bytes` bytes of WebAssembly are replaced with a copy of the
+    /// callee's body. This removes the call overhead of small functions such
+    /// as accessors and arithmetic helpers, and lets their code be optimized
+    /// together with the caller's, at the expense of larger code and longer
+    /// compile times. Calls within inlined functions are inlined as well, up
+    /// to the depth configured with [`Config::cranelift_inline_max_depth`].
+    ///
+    /// Inlining only happens when optimizations are enabled with
+    /// [`Config::cranelift_opt_level`], and not when native debug information
+    /// or proof-carrying code is enabled. Since inlined functions wouldn't
+    /// have frames of their own, inlining also requires both
+    /// [`Config::wasm_backtrace`] and [`Config::generate_address_map`] to be
+    /// disabled, which are enabled by default. Otherwise the threshold is
+    /// ignored and a warning is logged when the engine is created.
+    ///
+    /// The default value for this is `0`, which disables inlining.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn cranelift_inline_threshold(&mut self, bytes: u32) -> &mut Self {
+        self.tunables.inline_threshold = Some(bytes);
+        self
+    }
+
+    /// Configures the maximum depth of nested calls which Cranelift inlines
+    /// into a single function.
+    ///
+    /// A depth of `1` only inlines the calls made by a function itself, while
+    /// larger depths also inline the calls made by the functions it inlines.
+    /// See [`Config::cranelift_inline_threshold`] for more information.
+    ///
+    /// The default value for this is `2`.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn cranelift_inline_max_depth(&mut self, depth: u32) -> &mut Self {
+        self.tunables.inline_max_depth = Some(depth);
+        self
+    }
+
     /// Allows setting a Cranelift boolean flag or preset. This allows
     /// fine-tuning of Cranelift settings.
     ///
@@ -1417,6 +1661,46 @@ impl Config {
         Ok(self)
     }
 
+    /// Configures a custom store in which compiled modules and components are
+    /// cached.
+    ///
+    /// Unlike [`Config::cache_config_load`], which caches artifacts on the
+    /// local filesystem, this enables embedders to keep compiled artifacts in
+    /// any storage they like, for example a key/value store shared between
+    /// multiple hosts. Artifacts are keyed by a digest of the input wasm and
+    /// of all engine settings which affect compilation, so engines configured
+    /// differently will never observe each other's artifacts.
+    ///
+    /// Values stored are serialized artifacts in the same format as
+    /// [`Module::serialize`](crate::Module::serialize). Eviction is left to
+    /// the `store`, except that entries which fail to load, for example
+    /// because they are corrupt, are removed with [`CacheStore::remove`].
+    ///
+    /// When configured this takes precedence over the filesystem cache, which
+    /// is then not consulted.
+    ///
+    /// This method is only available when the `cache` feature of this crate is
+    /// enabled.
+    ///
+    /// # Unsafety
+    ///
+    /// Artifacts returned from [`CacheStore::get`] are loaded the same way as
+    /// [`Module::deserialize`](crate::Module::deserialize), which only lightly
+    /// validates them and can be used to execute arbitrary code if given
+    /// arbitrary input. The key of an artifact is not a signature of it, so
+    /// Wasmtime can't tell whether a value was modified.
+    ///
+    /// Callers must ensure that `store` only ever returns values previously
+    /// given to [`CacheStore::insert`] for the same key by an engine of this
+    /// same version of Wasmtime, unmodified. In particular a store shared
+    /// between hosts must only be writable by hosts trusted to the same extent
+    /// as this one.
+    #[cfg(feature = "cache")]
+    pub unsafe fn module_cache_store(&mut self, store: Arc<dyn CacheStore>) -> &mut Self {
+        self.module_cache_store = Some(store);
+        self
+    }
+
     /// Sets a custom memory creator.
     ///
     /// Custom memory creators are used when creating host `Memory` objects or when
@@ -1977,6 +2261,116 @@ impl Config {
         self
     }
 
+    /// Configures whether the floating-point environment of the host thread
+    /// is reset to its default each time wasm is entered.
+    ///
+    /// WebAssembly requires floating-point operations to round to nearest and
+    /// to preserve denormal values, and code generated by Wasmtime assumes
+    /// that the host thread is configured accordingly. Some native code,
+    /// however, changes the thread's floating-point environment, for example
+    /// to enable flush-to-zero for performance, and wasm executing on that
+    /// thread afterwards will compute incorrect results. When this option is
+    /// enabled the rounding mode is set to round-to-nearest and
+    /// flush-denormals-to-zero modes are disabled on each entry into wasm from
+    /// the host, and the host's original environment is restored once wasm
+    /// returns.
+    ///
+    /// This is currently only implemented for x86, x86\_64, and aarch64
+    /// hosts. On other platforms this option has no effect.
+    ///
+    /// This option is disabled by default.
+    pub fn reset_fp_environment(&mut self, enable: bool) -> &mut Self {
+        self.reset_fp_environment = enable;
+        self
+    }
+
+    /// Configures whether the engine collects metrics about the execution of
+    /// wasm in all of its stores.
+    ///
+    /// When enabled, [`Engine::metrics`](crate::Engine::metrics) returns a
+    /// registry of counters and histograms for instantiation time, calls into
+    /// wasm, traps by code, garbage collection pauses, fuel consumption and
+    /// epoch deadlines. These can be exported, for example in the Prometheus
+    /// text format with [`Metrics::to_prometheus`](crate::Metrics::to_prometheus),
+    /// without having to wrap every API call in the embedder.
+    ///
+    /// Collection adds a few atomic operations to calls into wasm and to
+    /// instantiation, plus reading the clock around instantiation and garbage
+    /// collection.
+    ///
+    /// This option is disabled by default.
+    #[cfg(feature = "runtime")]
+    pub fn metrics(&mut self, enable: bool) -> &mut Self {
+        self.metrics = enable;
+        self
+    }
+
+    /// Registers `intrinsic` as a host intrinsic for imports named `name` from
+    /// the module `module`.
+    ///
+    /// Calls from wasm to imported host functions normally go through the
+    /// import's `VMContext` and an indirect call to a trampoline which invokes
+    /// the host closure. For small and hot host functions, such as math or
+    /// hashing routines replacing a guest library, that overhead can dominate
+    /// the cost of the call itself. Host intrinsics are instead declared up
+    /// front with their ABI, and Cranelift compiles `call` instructions to an
+    /// import with this name and a matching type as a direct call to the
+    /// native function.
+    ///
+    /// Direct calls to the intrinsic are made regardless of the value which is
+    /// provided for the import at instantiation time, so the import must still
+    /// be defined to instantiate a module and should behave the same as the
+    /// intrinsic. [`Linker::define_host_intrinsics`] can be used to define all
+    /// intrinsics of an engine as imports. Other uses of the import, such as
+    /// through `ref.func` or when it's re-exported, use the defined import.
+    ///
+    /// Intrinsics have no access to the store or to the instance calling them,
+    /// cannot trap, and run on the wasm stack, so they are only suitable for
+    /// small leaf functions. Intrinsics are not called directly when using
+    /// Winch or Pulley, in which case calls use the defined import instead.
+    ///
+    /// Modules compiled with intrinsics can only be deserialized into an
+    /// engine configured with the same intrinsics, in the same order.
+    /// Registering an intrinsic for a name already registered replaces the
+    /// previous intrinsic.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wasmtime::{Config, HostIntrinsic};
+    ///
+    /// extern "C" fn hypot(x: f64, y: f64) -> f64 {
+    ///     x.hypot(y)
+    /// }
+    ///
+    /// let mut config = Config::new();
+    /// config.host_intrinsic(
+    ///     "math",
+    ///     "hypot",
+    ///     HostIntrinsic::new(hypot as extern "C" fn(f64, f64) -> f64),
+    /// );
+    /// ```
+    ///
+    /// [`Linker::define_host_intrinsics`]: crate::Linker::define_host_intrinsics
+    pub fn host_intrinsic(
+        &mut self,
+        module: &str,
+        name: &str,
+        intrinsic: HostIntrinsic,
+    ) -> &mut Self {
+        match self
+            .host_intrinsics
+            .iter_mut()
+            .find(|(m, n, _)| m == module && n == name)
+        {
+            Some((_, _, prev)) => *prev = intrinsic,
+            None => self
+                .host_intrinsics
+                .push((module.to_string(), name.to_string(), intrinsic)),
+        }
+        self
+    }
+
     /// Enables memory error checking for wasm programs.
     ///
     /// This option is disabled by default.
@@ -2144,6 +2538,14 @@ impl Config {
         features
     }
 
+    /// Returns whether wasm is compiled with Winch rather than Cranelift.
+    pub(crate) fn uses_winch(&self) -> bool {
+        #[cfg(any(feature = "cranelift", feature = "winch"))]
+        return self.compiler_config.strategy == Some(Strategy::Winch);
+        #[cfg(not(any(feature = "cranelift", feature = "winch")))]
+        return false;
+    }
+
     /// Returns the configured compiler target for this `Config`.
     pub(crate) fn compiler_target(&self) -> target_lexicon::Triple {
         // If a target is explicitly configured, always use that.
@@ -2223,6 +2625,20 @@ impl Config {
             tunables.winch_callable = self.compiler_config.strategy == Some(Strategy::Winch);
         }
 
+        // Inlined functions don't have frames of their own, and the offsets
+        // of their instructions lie outside of the function they're inlined
+        // into, so inlining is disabled when either is needed.
+        if tunables.inline_threshold > 0 && (self.wasm_backtrace || tunables.generate_address_map) {
+            if self.tunables.inline_threshold.is_some() {
+                log::warn!(
+                    "ignoring the configured inline threshold of {} bytes since inlining \
+                     requires both `wasm_backtrace` and `generate_address_map` to be disabled",
+                    tunables.inline_threshold,
+                );
+            }
+            tunables.inline_threshold = 0;
+        }
+
         tunables.collector = if features.gc_types() {
             #[cfg(feature = "gc")]
             {
@@ -2248,6 +2664,29 @@ impl Config {
             bail!("virtual memory disabled at compile time -- cannot enable CoW");
         }
 
+        if let Some(initial_stack_size) = self.wasmfx_config.initial_stack_size {
+            if cfg!(any(
+                not(unix),
+                all(
+                    feature = "wasmfx_baseline",
+                    not(feature = "wasmfx_no_baseline")
+                ),
+                feature = "wasmfx_pooling_allocator",
+                feature = "unsafe_wasmfx_stacks",
+            )) {
+                bail!("growable continuation stacks are not supported in this build");
+            }
+            if initial_stack_size > self.wasmfx_config.stack_size {
+                bail!("wasmfx_initial_stack_size cannot exceed the wasmfx_stack_size");
+            }
+            if !tunables.signals_based_traps {
+                bail!("growable continuation stacks require signals-based traps");
+            }
+            if cfg!(target_vendor = "apple") && self.macos_use_mach_ports {
+                bail!("growable continuation stacks are not supported with mach ports");
+            }
+        }
+
         Ok((tunables, features))
     }
 
@@ -2580,6 +3019,40 @@ impl Config {
         self
     }
 
+    /// Registers a function which is called when a fault occurs in JIT code
+    /// from which Wasmtime can't recover.
+    ///
+    /// Faults in wasm code which are expected, such as out-of-bounds memory
+    /// accesses, are caught and turned into traps. A fault anywhere else in
+    /// JIT code indicates a bug in Cranelift or Winch. Such faults are
+    /// forwarded to the signal handler which was installed before Wasmtime's,
+    /// or the default disposition which terminates the process. Before that
+    /// happens `handler` is invoked with a [`WasmCrashInfo`] describing the
+    /// faulting wasm PC, store and module. This allows crash reporters such as
+    /// Breakpad or Crashpad to annotate the dump they're about to write with
+    /// wasm context, for example.
+    ///
+    /// The handler is only invoked for faults which are caught by signal
+    /// handlers, so it's never called when
+    /// [`Config::signals_based_traps`] is disabled.
+    ///
+    /// By default no crash handler is registered.
+    ///
+    /// # Unsafety
+    ///
+    /// This function is `unsafe` because `handler` is called from within a
+    /// signal handler, with the state of the faulting thread and of the store
+    /// it was executing in unknown. `handler` must be async-signal-safe: it
+    /// must not allocate, take locks, panic or otherwise call functions which
+    /// aren't async-signal-safe.
+    ///
+    /// [`WasmCrashInfo`]: crate::WasmCrashInfo
+    #[cfg(feature = "runtime")]
+    pub unsafe fn crash_handler(&mut self, handler: fn(&crate::WasmCrashInfo<'_>)) -> &mut Self {
+        self.crash_handler = Some(handler);
+        self
+    }
+
     /// Configures Wasmtime to not use signals-based trap handlers, for example
     /// disables `SIGILL` and `SIGSEGV` handler registration on Unix platforms.
     ///
@@ -2901,6 +3374,187 @@ pub enum WasmBacktraceDetails {
     Environment,
 }
 
+/// A native host function which compiled wasm can call directly.
+///
+/// Intrinsics are registered with [`Config::host_intrinsic`], see its
+/// documentation for more information.
+#[derive(Clone)]
+pub struct HostIntrinsic {
+    ty: WasmFuncType,
+    address: usize,
+    #[cfg(feature = "runtime")]
+    call: fn(usize, &[crate::Val], &mut [crate::Val]),
+}
+
+impl HostIntrinsic {
+    /// Creates a new intrinsic which invokes `func`.
+    ///
+    /// The `func` provided must be an `extern "C"` function pointer with at
+    /// most six arguments and at most one result, all of which are `i32`,
+    /// `i64`, `f32`, or `f64`. These map to the wasm types of the same name,
+    /// and the type of an import must match exactly for calls to it to be
+    /// compiled as calls to the intrinsic.
+    ///
+    /// Note that `func` must not unwind, and a panic in `func` will abort the
+    /// process.
+    pub fn new(func: impl IntrinsicFunc) -> HostIntrinsic {
+        func.into_intrinsic()
+    }
+
+    pub(crate) fn ty(&self) -> &WasmFuncType {
+        &self.ty
+    }
+
+    pub(crate) fn address(&self) -> usize {
+        self.address
+    }
+
+    #[cfg(feature = "runtime")]
+    pub(crate) fn call(&self, params: &[crate::Val], results: &mut [crate::Val]) {
+        (self.call)(self.address, params, results)
+    }
+}
+
+impl fmt::Debug for HostIntrinsic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostIntrinsic")
+            .field("ty", &self.ty)
+            .field("address", &(self.address as *const u8))
+            .finish()
+    }
+}
+
+mod intrinsic_sealed {
+    pub trait Sealed {}
+    pub trait SealedResult {}
+}
+
+/// Function pointer types which can be used to create a [`HostIntrinsic`].
+///
+/// This trait is sealed and implemented for `extern "C"` function pointers
+/// whose arguments implement [`IntrinsicValue`] and whose result is either `()`
+/// or implements [`IntrinsicValue`].
+pub trait IntrinsicFunc: intrinsic_sealed::Sealed {
+    #[doc(hidden)]
+    fn into_intrinsic(self) -> HostIntrinsic;
+}
+
+/// Types which can be the arguments and results of a [`HostIntrinsic`].
+///
+/// This trait is sealed and implemented for `i32`, `i64`, `f32`, and `f64`.
+pub trait IntrinsicValue: Copy + intrinsic_sealed::Sealed + 'static {
+    #[doc(hidden)]
+    const TYPE: WasmValType;
+    #[cfg(feature = "runtime")]
+    #[doc(hidden)]
+    fn from_val(val: &crate::Val) -> Self;
+    #[cfg(feature = "runtime")]
+    #[doc(hidden)]
+    fn into_val(self) -> crate::Val;
+}
+
+macro_rules! intrinsic_values {
+    ($($ty:ident $wasm:ident $unwrap:ident $val:expr;)*) => {$(
+        impl intrinsic_sealed::Sealed for $ty {}
+
+        impl IntrinsicValue for $ty {
+            const TYPE: WasmValType = WasmValType::$wasm;
+
+            #[cfg(feature = "runtime")]
+            fn from_val(val: &crate::Val) -> Self {
+                val.$unwrap()
+            }
+
+            #[cfg(feature = "runtime")]
+            fn into_val(self) -> crate::Val {
+                $val(self)
+            }
+        }
+    )*};
+}
+
+intrinsic_values! {
+    i32 I32 unwrap_i32 crate::Val::I32;
+    i64 I64 unwrap_i64 crate::Val::I64;
+    f32 F32 unwrap_f32 |v: f32| crate::Val::F32(v.to_bits());
+    f64 F64 unwrap_f64 |v: f64| crate::Val::F64(v.to_bits());
+}
+
+/// The result of a [`HostIntrinsic`], either `()` or an [`IntrinsicValue`].
+#[doc(hidden)]
+pub trait IntrinsicResult: intrinsic_sealed::SealedResult + 'static {
+    const TYPE: Option<WasmValType>;
+    #[cfg(feature = "runtime")]
+    fn store(self, results: &mut [crate::Val]);
+}
+
+impl intrinsic_sealed::SealedResult for () {}
+
+impl IntrinsicResult for () {
+    const TYPE: Option<WasmValType> = None;
+
+    #[cfg(feature = "runtime")]
+    fn store(self, _results: &mut [crate::Val]) {}
+}
+
+impl<T: IntrinsicValue> intrinsic_sealed::SealedResult for T {}
+
+impl<T: IntrinsicValue> IntrinsicResult for T {
+    const TYPE: Option<WasmValType> = Some(T::TYPE);
+
+    #[cfg(feature = "runtime")]
+    fn store(self, results: &mut [crate::Val]) {
+        results[0] = self.into_val();
+    }
+}
+
+macro_rules! intrinsic_funcs {
+    ($($args:ident)*) => {
+        impl<R, $($args,)*> intrinsic_sealed::Sealed for extern "C" fn($($args),*) -> R
+        where
+            R: IntrinsicResult,
+            $($args: IntrinsicValue,)*
+        {
+        }
+
+        impl<R, $($args,)*> IntrinsicFunc for extern "C" fn($($args),*) -> R
+        where
+            R: IntrinsicResult,
+            $($args: IntrinsicValue,)*
+        {
+            fn into_intrinsic(self) -> HostIntrinsic {
+                HostIntrinsic {
+                    ty: WasmFuncType::new(
+                        vec![$(<$args as IntrinsicValue>::TYPE),*].into_boxed_slice(),
+                        R::TYPE.into_iter().collect(),
+                    ),
+                    address: self as usize,
+                    #[cfg(feature = "runtime")]
+                    call: |address, params, results| {
+                        // SAFETY: `address` is always the address that this
+                        // intrinsic was created with, which has this type.
+                        let func = unsafe {
+                            core::mem::transmute::<usize, extern "C" fn($($args),*) -> R>(address)
+                        };
+                        #[allow(unused_mut, unused_variables, reason = "macro-generated code")]
+                        let mut params = params.iter();
+                        let result = func($($args::from_val(params.next().unwrap()),)*);
+                        result.store(results);
+                    },
+                }
+            }
+        }
+    };
+}
+
+intrinsic_funcs!();
+intrinsic_funcs!(A1);
+intrinsic_funcs!(A1 A2);
+intrinsic_funcs!(A1 A2 A3);
+intrinsic_funcs!(A1 A2 A3 A4);
+intrinsic_funcs!(A1 A2 A3 A4 A5);
+intrinsic_funcs!(A1 A2 A3 A4 A5 A6);
+
 /// Configuration options used with [`InstanceAllocationStrategy::Pooling`] to
 /// change the behavior of the pooling instance allocator.
 ///
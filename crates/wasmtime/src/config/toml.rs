@@ -0,0 +1,333 @@
+//! Saving and loading a [`Config`] as TOML.
+
+use super::*;
+use ::toml::{Table, Value};
+
+impl Config {
+    /// Serializes the options configured in this `Config` as a TOML document.
+    ///
+    /// The returned document can be loaded with [`Config::from_toml`] to
+    /// recreate this configuration, for example so deployments can keep engine
+    /// configuration in version control or so the exact configuration used by
+    /// the `wasmtime` CLI or a test can be shared with an embedder.
+    ///
+    /// The document covers tunables, WebAssembly features, the compilation
+    /// strategy, garbage collector and profiler selections, Cranelift settings
+    /// and a number of other scalar options. Only WebAssembly features which
+    /// have been explicitly enabled or disabled are included, so features left
+    /// at their defaults follow the defaults of whichever version of Wasmtime
+    /// loads the document. Options which cannot be represented as data, such
+    /// as [`Config::with_host_memory`] or [`Config::module_cache_store`], are
+    /// not included.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an option can't be represented in TOML, for example
+    /// an integer which does not fit in an `i64`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn main() -> Result<()> {
+    /// let mut config = Config::new();
+    /// config.wasm_simd(false).consume_fuel(true).max_wasm_stack(256 << 10);
+    /// let toml = config.to_toml()?;
+    /// assert!(toml.contains("consume_fuel = true"));
+    ///
+    /// let config = Config::from_toml(&toml)?;
+    /// assert_eq!(config.to_toml()?, toml);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_toml(&self) -> Result<String> {
+        let mut doc = Table::new();
+
+        #[cfg(any(feature = "cranelift", feature = "winch"))]
+        if let Some(strategy) = self.compiler_config.strategy {
+            doc.insert("strategy".into(), strategy_name(strategy).into());
+        }
+        #[cfg(feature = "gc")]
+        doc.insert("collector".into(), collector_name(self.collector).into());
+        doc.insert(
+            "profiler".into(),
+            profiler_name(self.profiling_strategy).into(),
+        );
+        if let Some(target) = &self.target {
+            doc.insert("target".into(), target.to_string().into());
+        }
+
+        doc.insert("async_support".into(), self.async_support.into());
+        #[cfg(feature = "async")]
+        doc.insert("async_stack_size".into(), int(self.async_stack_size)?);
+        doc.insert("max_wasm_stack".into(), int(self.max_wasm_stack)?);
+        doc.insert(
+            "parallel_compilation".into(),
+            self.parallel_compilation.into(),
+        );
+        doc.insert("wasm_backtrace".into(), self.wasm_backtrace.into());
+        if let Some(enable) = self.native_unwind_info {
+            doc.insert("native_unwind_info".into(), enable.into());
+        }
+        doc.insert("coredump_on_trap".into(), self.coredump_on_trap.into());
+        doc.insert(
+            "memory_guaranteed_dense_image_size".into(),
+            int(self.memory_guaranteed_dense_image_size)?,
+        );
+        doc.insert(
+            "wasmfx_stack_size".into(),
+            int(self.wasmfx_config.stack_size)?,
+        );
+        doc.insert(
+            "wasmfx_red_zone_size".into(),
+            int(self.wasmfx_config.red_zone_size)?,
+        );
+        if let Some(size) = self.wasmfx_config.initial_stack_size {
+            doc.insert("wasmfx_initial_stack_size".into(), int(size)?);
+        }
+        doc.insert(
+            "wasmfx_stack_cache_size".into(),
+            int(self.wasmfx_config.stack_cache_size)?,
+        );
+
+        let mut features = Table::new();
+        for flag in WasmFeatures::FLAGS.iter() {
+            let enabled = self.enabled_features.contains(*flag.value());
+            let disabled = self.disabled_features.contains(*flag.value());
+            if enabled || disabled {
+                features.insert(flag.name().to_lowercase(), enabled.into());
+            }
+        }
+        if !features.is_empty() {
+            doc.insert("features".into(), features.into());
+        }
+
+        let tunables = Value::try_from(&self.tunables).context("failed to serialize tunables")?;
+        if tunables.as_table().is_some_and(|t| !t.is_empty()) {
+            doc.insert("tunables".into(), tunables);
+        }
+
+        #[cfg(any(feature = "cranelift", feature = "winch"))]
+        {
+            let mut cranelift = Table::new();
+            if !self.compiler_config.settings.is_empty() {
+                let settings = self
+                    .compiler_config
+                    .settings
+                    .iter()
+                    .map(|(k, v)| (k.clone(), Value::from(v.clone())))
+                    .collect::<Table>();
+                cranelift.insert("settings".into(), settings.into());
+            }
+            if !self.compiler_config.flags.is_empty() {
+                let mut flags = self.compiler_config.flags.iter().collect::<Vec<_>>();
+                flags.sort();
+                let flags = flags.into_iter().map(|f| Value::from(f.clone()));
+                cranelift.insert("flags".into(), Value::Array(flags.collect()));
+            }
+            if !cranelift.is_empty() {
+                doc.insert("cranelift".into(), cranelift.into());
+            }
+        }
+
+        Ok(::toml::to_string(&doc)?)
+    }
+
+    /// Creates a `Config` from a TOML document previously produced by
+    /// [`Config::to_toml`].
+    ///
+    /// Options which aren't present in `toml` keep their default values, so a
+    /// hand-written document need only contain the options it changes. Keys
+    /// which aren't recognized, for example because they were written by a
+    /// newer version of Wasmtime or require a Cargo feature which isn't
+    /// enabled, are skipped with a warning logged through the `log` crate.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `toml` is not valid TOML or if a recognized key has
+    /// a value of the wrong type or an unknown variant, such as an unknown
+    /// compilation strategy.
+    pub fn from_toml(toml: &str) -> Result<Config> {
+        let doc: Table = toml.parse().context("failed to parse configuration")?;
+        let mut config = Config::new();
+        for (key, value) in doc {
+            match key.as_str() {
+                #[cfg(any(feature = "cranelift", feature = "winch"))]
+                "strategy" => {
+                    config.strategy(parse_strategy(&get::<String>(&key, value)?)?);
+                }
+                #[cfg(feature = "gc")]
+                "collector" => {
+                    config.collector(parse_collector(&get::<String>(&key, value)?)?);
+                }
+                "profiler" => {
+                    config.profiler(parse_profiler(&get::<String>(&key, value)?)?);
+                }
+                "target" => {
+                    config.target(&get::<String>(&key, value)?)?;
+                }
+                "async_support" => {
+                    config.async_support(get(&key, value)?);
+                }
+                #[cfg(feature = "async")]
+                "async_stack_size" => {
+                    config.async_stack_size(get(&key, value)?);
+                }
+                "max_wasm_stack" => {
+                    config.max_wasm_stack(get(&key, value)?);
+                }
+                "parallel_compilation" => {
+                    config.parallel_compilation(get(&key, value)?);
+                }
+                "wasm_backtrace" => {
+                    config.wasm_backtrace(get(&key, value)?);
+                }
+                "native_unwind_info" => {
+                    config.native_unwind_info(get(&key, value)?);
+                }
+                "coredump_on_trap" => {
+                    config.coredump_on_trap(get(&key, value)?);
+                }
+                "memory_guaranteed_dense_image_size" => {
+                    config.memory_guaranteed_dense_image_size(get(&key, value)?);
+                }
+                "wasmfx_stack_size" => {
+                    config.wasmfx_stack_size(get(&key, value)?);
+                }
+                "wasmfx_red_zone_size" => {
+                    config.wasmfx_red_zone_size(get(&key, value)?);
+                }
+                "wasmfx_initial_stack_size" => {
+                    config.wasmfx_initial_stack_size(get(&key, value)?);
+                }
+                "wasmfx_stack_cache_size" => {
+                    config.wasmfx_stack_cache_size(get(&key, value)?);
+                }
+                "features" => {
+                    for (name, value) in get::<Table>(&key, value)? {
+                        let flag = WasmFeatures::FLAGS
+                            .iter()
+                            .find(|flag| flag.name().eq_ignore_ascii_case(&name));
+                        match flag {
+                            Some(flag) => {
+                                let enable = get(&format!("features.{name}"), value)?;
+                                config.wasm_feature(*flag.value(), enable);
+                            }
+                            None => log::warn!("ignoring unknown wasm feature `{name}`"),
+                        }
+                    }
+                }
+                "tunables" => {
+                    // Merge with the tunables already configured by
+                    // `Config::new` rather than replacing them.
+                    let mut tunables = match Value::try_from(&config.tunables)? {
+                        Value::Table(t) => t,
+                        _ => unreachable!(),
+                    };
+                    for (name, value) in get::<Table>(&key, value)? {
+                        if ConfigTunables::NAMES.contains(&name.as_str()) {
+                            tunables.insert(name, value);
+                        } else {
+                            log::warn!("ignoring unknown tunable `{name}`");
+                        }
+                    }
+                    config.tunables = get(&key, Value::Table(tunables))?;
+                }
+                #[cfg(any(feature = "cranelift", feature = "winch"))]
+                "cranelift" => {
+                    for (name, value) in get::<Table>(&key, value)? {
+                        match name.as_str() {
+                            "settings" => {
+                                for (name, value) in get::<Table>("cranelift.settings", value)? {
+                                    let value = get(&format!("cranelift.settings.{name}"), value)?;
+                                    config.compiler_config.settings.insert(name, value);
+                                }
+                            }
+                            "flags" => {
+                                let flags: Vec<String> = get("cranelift.flags", value)?;
+                                config.compiler_config.flags.extend(flags);
+                            }
+                            _ => {
+                                log::warn!("ignoring unknown configuration key `cranelift.{name}`")
+                            }
+                        }
+                    }
+                }
+                _ => log::warn!("ignoring unknown configuration key `{key}`"),
+            }
+        }
+        Ok(config)
+    }
+}
+
+fn get<T: serde::de::DeserializeOwned>(key: &str, value: Value) -> Result<T> {
+    value
+        .try_into()
+        .with_context(|| format!("invalid value for configuration key `{key}`"))
+}
+
+fn int<T: TryInto<i64>>(n: T) -> Result<Value> {
+    match n.try_into() {
+        Ok(n) => Ok(Value::Integer(n)),
+        Err(_) => bail!("integer is too large to be represented in TOML"),
+    }
+}
+
+#[cfg(any(feature = "cranelift", feature = "winch"))]
+fn strategy_name(strategy: Strategy) -> &'static str {
+    match strategy {
+        Strategy::Auto => "auto",
+        Strategy::Cranelift => "cranelift",
+        Strategy::Winch => "winch",
+    }
+}
+
+#[cfg(any(feature = "cranelift", feature = "winch"))]
+fn parse_strategy(name: &str) -> Result<Strategy> {
+    Ok(match name {
+        "auto" => Strategy::Auto,
+        "cranelift" => Strategy::Cranelift,
+        "winch" => Strategy::Winch,
+        other => bail!("unknown compilation strategy `{other}`"),
+    })
+}
+
+#[cfg(feature = "gc")]
+fn collector_name(collector: Collector) -> &'static str {
+    match collector {
+        Collector::Auto => "auto",
+        Collector::DeferredReferenceCounting => "drc",
+        Collector::Null => "null",
+    }
+}
+
+#[cfg(feature = "gc")]
+fn parse_collector(name: &str) -> Result<Collector> {
+    Ok(match name {
+        "auto" => Collector::Auto,
+        "drc" => Collector::DeferredReferenceCounting,
+        "null" => Collector::Null,
+        other => bail!("unknown garbage collector `{other}`"),
+    })
+}
+
+fn profiler_name(profiler: ProfilingStrategy) -> &'static str {
+    match profiler {
+        ProfilingStrategy::None => "none",
+        ProfilingStrategy::PerfMap => "perfmap",
+        ProfilingStrategy::JitDump => "jitdump",
+        ProfilingStrategy::VTune => "vtune",
+        ProfilingStrategy::Pulley => "pulley",
+    }
+}
+
+fn parse_profiler(name: &str) -> Result<ProfilingStrategy> {
+    Ok(match name {
+        "none" => ProfilingStrategy::None,
+        "perfmap" => ProfilingStrategy::PerfMap,
+        "jitdump" => ProfilingStrategy::JitDump,
+        "vtune" => ProfilingStrategy::VTune,
+        "pulley" => ProfilingStrategy::Pulley,
+        other => bail!("unknown profiler `{other}`"),
+    })
+}
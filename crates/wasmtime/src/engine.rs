@@ -6,7 +6,7 @@ use crate::runtime::type_registry::TypeRegistry;
 #[cfg(feature = "runtime")]
 use crate::runtime::vm::GcRuntime;
 use crate::sync::OnceLock;
-use crate::Config;
+use crate::{Config, ErrorKind};
 use alloc::sync::Arc;
 use core::sync::atomic::{AtomicU64, Ordering};
 #[cfg(any(feature = "cranelift", feature = "winch"))]
@@ -18,8 +18,11 @@ use wasmparser::WasmFeatures;
 use wasmtime_environ::obj;
 use wasmtime_environ::{FlagValue, ObjectKind, TripleExt, Tunables};
 
+mod capabilities;
 mod serialization;
 
+pub use self::capabilities::{FeatureSupport, TargetCapabilities};
+
 /// An `Engine` which is a global context for compilation and management of wasm
 /// modules.
 ///
@@ -63,6 +66,8 @@ struct EngineInner {
     signatures: TypeRegistry,
     #[cfg(feature = "runtime")]
     epoch: AtomicU64,
+    #[cfg(feature = "runtime")]
+    metrics: Option<crate::Metrics>,
 
     /// One-time check of whether the compiler's settings, if present, are
     /// compatible with the native host.
@@ -91,7 +96,9 @@ impl Engine {
     /// will cause errors.
     pub fn new(config: &Config) -> Result<Engine> {
         let config = config.clone();
-        let (tunables, features) = config.validate()?;
+        let (tunables, features) = config
+            .validate()
+            .map_err(|e| ErrorKind::Configuration.tag(e))?;
 
         #[cfg(feature = "runtime")]
         if tunables.signals_based_traps {
@@ -108,7 +115,9 @@ impl Engine {
         }
 
         #[cfg(any(feature = "cranelift", feature = "winch"))]
-        let (config, compiler) = config.build_compiler(&tunables, features)?;
+        let (config, compiler) = config
+            .build_compiler(&tunables, features)
+            .map_err(|e| ErrorKind::Configuration.tag(e))?;
 
         Ok(Engine {
             inner: Arc::new(EngineInner {
@@ -124,6 +133,8 @@ impl Engine {
                 signatures: TypeRegistry::new(),
                 #[cfg(feature = "runtime")]
                 epoch: AtomicU64::new(0),
+                #[cfg(feature = "runtime")]
+                metrics: config.metrics.then(crate::Metrics::new),
                 #[cfg(any(feature = "cranelift", feature = "winch"))]
                 compatible_with_native_host: OnceLock::new(),
                 config,
@@ -393,6 +404,7 @@ impl Engine {
             | "enable_pcc"
             | "regalloc_checker"
             | "regalloc_verbose_logs"
+            | "egraph_rule_stats"
             | "regalloc_algorithm"
             | "is_pic"
             | "bb_padding_log2_minus_one"
@@ -400,6 +412,8 @@ impl Engine {
             | "tls_model" // wasmtime doesn't use tls right now
             | "opt_level" // opt level doesn't change semantics
             | "enable_alias_analysis" // alias analysis-based opts don't change semantics
+            | "enable_redundant_check_elimination" // only removes checks that can't trap
+            | "enable_vmctx_invariant_loads" // only reuses loads of unmodified fields
             | "probestack_size_log2" // probestack above asserted disabled
             | "regalloc" // shouldn't change semantics
             | "enable_incremental_compilation_cache_checks" // shouldn't change semantics
@@ -485,6 +499,10 @@ impl Engine {
             "has_vxrs_ext2" => "vxrs_ext2",
             "has_mie2" => "mie2",
 
+            // x64 settings which don't need detection
+            // Only changes which of several equivalent encodings are used.
+            "prefer_smaller_encodings" => return Ok(()),
+
             // x64 features to detect
             "has_cmpxchg16b" => "cmpxchg16b",
             "has_sse3" => "sse3",
@@ -659,7 +677,7 @@ impl Engine {
         crate::runtime::vm::tls_eager_initialize();
     }
 
-    pub(crate) fn allocator(&self) -> &dyn crate::runtime::vm::InstanceAllocator {
+    pub(crate) fn allocator(&self) -> &(dyn crate::runtime::vm::InstanceAllocator + Send + Sync) {
         self.inner.allocator.as_ref()
     }
 
@@ -680,6 +698,11 @@ impl Engine {
         &self.config().cache_config
     }
 
+    #[cfg(feature = "cache")]
+    pub(crate) fn module_cache_store(&self) -> Option<&Arc<dyn crate::CacheStore>> {
+        self.config().module_cache_store.as_ref()
+    }
+
     pub(crate) fn signatures(&self) -> &TypeRegistry {
         &self.inner.signatures
     }
@@ -726,6 +749,14 @@ impl Engine {
         self.inner.epoch.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Returns the metrics collected for all stores of this engine, or `None`
+    /// if metrics are not enabled with
+    /// [`Config::metrics`](crate::Config::metrics).
+    #[inline]
+    pub fn metrics(&self) -> Option<&crate::Metrics> {
+        self.inner.metrics.as_ref()
+    }
+
     /// Returns a [`std::hash::Hash`] that can be used to check precompiled WebAssembly compatibility.
     ///
     /// The outputs of [`Engine::precompile_module`] and [`Engine::precompile_component`]
@@ -0,0 +1,152 @@
+//! Introspection of how an `Engine` executes WebAssembly features on its
+//! target, see [`Engine::target_capabilities`].
+
+use crate::Engine;
+use target_lexicon::Architecture;
+use wasmparser::WasmFeatures;
+use wasmtime_environ::TripleExt;
+
+/// How a WebAssembly feature is executed by an [`Engine`], as reported by
+/// [`Engine::target_capabilities`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FeatureSupport {
+    /// The feature's instructions are lowered to native instructions of the
+    /// target.
+    Native,
+    /// The feature is supported, but its instructions are emulated, for
+    /// example with longer sequences of more widely available instructions
+    /// or by an interpreter, so they may be much slower than native ones.
+    Emulated,
+    /// The feature is disabled in the engine's configuration or can't be
+    /// executed on its target, so modules using it fail to compile.
+    Unsupported,
+}
+
+/// Describes how WebAssembly features which guests commonly have alternative
+/// builds for are executed by an [`Engine`].
+///
+/// This is returned by [`Engine::target_capabilities`] and takes into
+/// account the engine's target, compiler, enabled WebAssembly features, and
+/// target-specific settings, such as the CPU features which compiled code may
+/// use. It allows a host to pick, for example, between a build of a guest
+/// using SIMD and a scalar one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TargetCapabilities {
+    simd: FeatureSupport,
+    threads: FeatureSupport,
+    tail_calls: FeatureSupport,
+    stack_switching: FeatureSupport,
+}
+
+impl TargetCapabilities {
+    /// How the instructions of the fixed-width SIMD proposal are executed.
+    pub fn simd(&self) -> FeatureSupport {
+        self.simd
+    }
+
+    /// How the atomic instructions and shared memories of the threads
+    /// proposal are executed.
+    pub fn threads(&self) -> FeatureSupport {
+        self.threads
+    }
+
+    /// How the tail calls of the tail-call proposal are executed.
+    pub fn tail_calls(&self) -> FeatureSupport {
+        self.tail_calls
+    }
+
+    /// How the continuations of the stack-switching proposal are executed.
+    pub fn stack_switching(&self) -> FeatureSupport {
+        self.stack_switching
+    }
+}
+
+impl Engine {
+    /// Returns how WebAssembly features which guests commonly have
+    /// alternative builds for, such as SIMD, are executed by this engine.
+    ///
+    /// See [`TargetCapabilities`] for more information.
+    pub fn target_capabilities(&self) -> TargetCapabilities {
+        let features = self.features();
+        let target = self.target();
+        let pulley = target.is_pulley();
+        let winch = self.config().uses_winch();
+        let x86_64 = target.architecture == Architecture::X86_64;
+
+        let support = |feature: WasmFeatures, support: FeatureSupport| {
+            if features.contains(feature) {
+                support
+            } else {
+                FeatureSupport::Unsupported
+            }
+        };
+        let native_if = |native: bool| {
+            if native {
+                FeatureSupport::Native
+            } else {
+                FeatureSupport::Unsupported
+            }
+        };
+
+        let simd = if pulley {
+            FeatureSupport::Emulated
+        } else if winch {
+            // Winch only implements SIMD on x86-64 with AVX.
+            native_if(x86_64 && self.isa_flag_enabled("has_avx"))
+        } else {
+            match target.architecture {
+                // Without SSE4.1 many SIMD instructions are lowered to longer
+                // sequences of SSE2 instructions or to libcalls.
+                Architecture::X86_64 if self.isa_flag_enabled("has_sse41") => {
+                    FeatureSupport::Native
+                }
+                Architecture::X86_64 => FeatureSupport::Emulated,
+                Architecture::Riscv64(_) => native_if(self.isa_flag_enabled("has_v")),
+                Architecture::Aarch64(_) | Architecture::S390x => FeatureSupport::Native,
+                _ => FeatureSupport::Unsupported,
+            }
+        };
+
+        // Pulley can't implement shared memories, see
+        // `Config::compiler_panicking_wasm_features`.
+        let threads = native_if(
+            !pulley && !(winch && matches!(target.architecture, Architecture::Aarch64(_))),
+        );
+
+        let tail_calls = if winch {
+            FeatureSupport::Unsupported
+        } else if pulley {
+            FeatureSupport::Emulated
+        } else {
+            FeatureSupport::Native
+        };
+
+        // Cranelift only lowers `stack_switch` on x86-64.
+        let stack_switching = native_if(!winch && !pulley && x86_64);
+
+        TargetCapabilities {
+            simd: support(WasmFeatures::SIMD, simd),
+            threads: support(WasmFeatures::THREADS, threads),
+            tail_calls: support(WasmFeatures::TAIL_CALL, tail_calls),
+            stack_switching: support(WasmFeatures::STACK_SWITCHING, stack_switching),
+        }
+    }
+
+    /// Returns whether the target-specific setting `flag` is enabled for the
+    /// code compiled by this engine.
+    ///
+    /// Without a compiler this engine can only load precompiled code, which
+    /// was checked to be compatible with the host when it was loaded, so the
+    /// setting is assumed to be available.
+    fn isa_flag_enabled(&self, flag: &str) -> bool {
+        #[cfg(any(feature = "cranelift", feature = "winch"))]
+        return self.compiler().isa_flags().iter().any(|(name, value)| {
+            *name == flag && *value == wasmtime_environ::FlagValue::Bool(true)
+        });
+        #[cfg(not(any(feature = "cranelift", feature = "winch")))]
+        {
+            let _ = flag;
+            return true;
+        }
+    }
+}
@@ -30,7 +30,7 @@ use object::write::{Object, StandardSegment};
 use object::{read::elf::ElfFile64, FileFlags, Object as _, ObjectSection, SectionKind};
 use serde_derive::{Deserialize, Serialize};
 use wasmtime_environ::obj;
-use wasmtime_environ::{FlagValue, ObjectKind, Tunables};
+use wasmtime_environ::{FlagValue, ObjectKind, Tunables, WasmFuncType};
 
 const VERSION: u8 = 0;
 
@@ -180,6 +180,7 @@ pub struct Metadata<'a> {
     isa_flags: Vec<(&'a str, FlagValue<'a>)>,
     tunables: Tunables,
     features: WasmFeatures,
+    host_intrinsics: Vec<(String, String, WasmFuncType)>,
 }
 
 // This exists because `wasmparser::WasmFeatures` isn't serializable
@@ -203,6 +204,7 @@ struct WasmFeatures {
     custom_page_sizes: bool,
     component_model_more_flags: bool,
     component_model_multiple_returns: bool,
+    component_model_async: bool,
     gc_types: bool,
     wide_arithmetic: bool,
 }
@@ -254,7 +256,6 @@ impl Metadata<'_> {
         assert!(!shared_everything_threads);
         assert!(!legacy_exceptions);
         //assert!(!stack_switching); // NOTE(dhil): we are working on implementing it!
-        assert!(!component_model_async);
 
         Metadata {
             target: engine.compiler().triple().to_string(),
@@ -280,9 +281,18 @@ impl Metadata<'_> {
                 custom_page_sizes,
                 component_model_more_flags,
                 component_model_multiple_returns,
+                component_model_async,
                 gc_types,
                 wide_arithmetic,
             },
+            host_intrinsics: engine
+                .config()
+                .host_intrinsics
+                .iter()
+                .map(|(module, name, intrinsic)| {
+                    (module.clone(), name.clone(), intrinsic.ty().clone())
+                })
+                .collect(),
         }
     }
 
@@ -292,6 +302,7 @@ impl Metadata<'_> {
         self.check_isa_flags(engine)?;
         self.check_tunables(&engine.tunables())?;
         self.check_features(&engine.features())?;
+        self.check_host_intrinsics(engine)?;
         Ok(())
     }
 
@@ -337,6 +348,22 @@ impl Metadata<'_> {
         Ok(())
     }
 
+    fn check_host_intrinsics(&self, engine: &Engine) -> Result<()> {
+        // Compiled code refers to intrinsics by their index in the engine's
+        // list of intrinsics, so the lists must be exactly the same.
+        let expected = &engine.config().host_intrinsics;
+        let matches = self.host_intrinsics.len() == expected.len()
+            && self.host_intrinsics.iter().zip(expected).all(
+                |((module, name, ty), (expected_module, expected_name, intrinsic))| {
+                    module == expected_module && name == expected_name && ty == intrinsic.ty()
+                },
+            );
+        if !matches {
+            bail!("Module was compiled with different host intrinsics than are configured for the host");
+        }
+        Ok(())
+    }
+
     fn check_int<T: Eq + core::fmt::Display>(found: T, expected: T, feature: &str) -> Result<()> {
         if found == expected {
             return Ok(());
@@ -379,6 +406,10 @@ impl Metadata<'_> {
             winch_callable,
             signals_based_traps,
             memory_init_cow,
+            global_watchpoints,
+            single_step,
+            coverage_instrumentation,
+            wasmfx_tag_dispatch_tables,
             // This doesn't affect compilation, it's just a runtime setting.
             memory_reservation_for_growth: _,
 
@@ -390,6 +421,11 @@ impl Metadata<'_> {
 
             // Just a debugging aid, doesn't affect functionality at all.
             debug_adapter_modules: _,
+
+            // Inlining only changes how code is optimized, and the inlined
+            // code is still compatible with the engine.
+            inline_threshold: _,
+            inline_max_depth: _,
         } = self.tunables;
 
         Self::check_collector(collector, other.collector)?;
@@ -446,6 +482,22 @@ impl Metadata<'_> {
             other.memory_init_cow,
             "memory initialization with CoW",
         )?;
+        Self::check_bool(
+            global_watchpoints,
+            other.global_watchpoints,
+            "global watchpoints",
+        )?;
+        Self::check_bool(single_step, other.single_step, "single-stepping")?;
+        Self::check_bool(
+            coverage_instrumentation,
+            other.coverage_instrumentation,
+            "coverage instrumentation",
+        )?;
+        Self::check_bool(
+            wasmfx_tag_dispatch_tables,
+            other.wasmfx_tag_dispatch_tables,
+            "tag dispatch tables",
+        )?;
 
         Ok(())
     }
@@ -490,6 +542,7 @@ impl Metadata<'_> {
             custom_page_sizes,
             component_model_more_flags,
             component_model_multiple_returns,
+            component_model_async,
             gc_types,
             stack_switching,
             wide_arithmetic,
@@ -582,6 +635,11 @@ impl Metadata<'_> {
             other.contains(F::COMPONENT_MODEL_MULTIPLE_RETURNS),
             "WebAssembly component model support for multiple returns",
         )?;
+        Self::check_bool(
+            component_model_async,
+            other.contains(F::COMPONENT_MODEL_ASYNC),
+            "WebAssembly component model support for async",
+        )?;
         Self::check_cfg_bool(
             cfg!(feature = "gc"),
             "gc",
@@ -774,6 +832,38 @@ Caused by:
         Ok(())
     }
 
+    #[test]
+    fn test_host_intrinsics_mismatch() -> Result<()> {
+        extern "C" fn add(a: i32, b: i32) -> i32 {
+            a.wrapping_add(b)
+        }
+
+        let mut config = Config::new();
+        config.host_intrinsic(
+            "env",
+            "add",
+            crate::HostIntrinsic::new(add as extern "C" fn(i32, i32) -> i32),
+        );
+
+        let engine = Engine::new(&config)?;
+        let mut metadata = Metadata::new(&engine);
+        metadata.host_intrinsics[0].1 = "sub".to_string();
+
+        match metadata.check_compatible(&engine) {
+            Ok(_) => unreachable!(),
+            Err(e) => assert_eq!(
+                e.to_string(),
+                "Module was compiled with different host intrinsics than are configured for the host"
+            ),
+        }
+
+        let engine = Engine::default();
+        let metadata = Metadata::new(&Engine::new(&config)?);
+        assert!(metadata.check_compatible(&engine).is_err());
+
+        Ok(())
+    }
+
     #[test]
     #[cfg(target_arch = "x86_64")] // test on a platform that is known to
                                    // implement threads
@@ -954,4 +1044,77 @@ Caused by:
 
         Ok(())
     }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    #[cfg(feature = "cache")]
+    fn modules_are_cached_in_custom_store() -> Result<()> {
+        use crate::CacheStore;
+        use std::borrow::Cow;
+        use std::collections::HashMap;
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Debug, Default)]
+        struct MemoryStore {
+            entries: Mutex<HashMap<Vec<u8>, Vec<u8>>>,
+            hits: Mutex<usize>,
+        }
+
+        impl CacheStore for MemoryStore {
+            fn get(&self, key: &[u8]) -> Option<Cow<[u8]>> {
+                let value = self.entries.lock().unwrap().get(key).cloned()?;
+                *self.hits.lock().unwrap() += 1;
+                Some(value.into())
+            }
+
+            fn insert(&self, key: &[u8], value: Vec<u8>) -> bool {
+                self.entries.lock().unwrap().insert(key.to_vec(), value);
+                true
+            }
+
+            fn remove(&self, key: &[u8]) -> bool {
+                self.entries.lock().unwrap().remove(key).is_some()
+            }
+        }
+
+        let store = Arc::new(MemoryStore::default());
+        let mut cfg = Config::new();
+        // SAFETY: the store only ever returns artifacts inserted by this test,
+        // aside from the truncated ones below which are too short to be
+        // mistaken for a valid artifact.
+        unsafe {
+            cfg.module_cache_store(store.clone());
+        }
+        let engine = Engine::new(&cfg)?;
+        Module::new(&engine, "(module (func))")?;
+        assert_eq!(store.entries.lock().unwrap().len(), 1);
+        assert_eq!(*store.hits.lock().unwrap(), 0);
+        Module::new(&engine, "(module (func))")?;
+        assert_eq!(store.entries.lock().unwrap().len(), 1);
+        assert_eq!(*store.hits.lock().unwrap(), 1);
+
+        // Differently configured engines don't share artifacts.
+        let mut cfg = Config::new();
+        cfg.cranelift_opt_level(OptLevel::None);
+        // SAFETY: see above.
+        unsafe {
+            cfg.module_cache_store(store.clone());
+        }
+        let engine = Engine::new(&cfg)?;
+        Module::new(&engine, "(module (func))")?;
+        assert_eq!(store.entries.lock().unwrap().len(), 2);
+        assert_eq!(*store.hits.lock().unwrap(), 1);
+
+        // Corrupt entries are evicted and replaced.
+        for value in store.entries.lock().unwrap().values_mut() {
+            value.truncate(8);
+        }
+        Module::new(&engine, "(module (func))")?;
+        assert_eq!(*store.hits.lock().unwrap(), 2);
+        assert_eq!(store.entries.lock().unwrap().len(), 2);
+        Module::new(&engine, "(module (func))")?;
+        assert_eq!(*store.hits.lock().unwrap(), 3);
+
+        Ok(())
+    }
 }
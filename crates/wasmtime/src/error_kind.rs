@@ -0,0 +1,151 @@
+//! Broad categories of errors returned by Wasmtime's APIs.
+
+use crate::prelude::*;
+use core::fmt;
+
+/// The broad category of an error returned by Wasmtime.
+///
+/// Errors in Wasmtime are [`anyhow::Error`] values, and the precise error
+/// types contained within them are generally an implementation detail. This
+/// enumeration can be used to programmatically branch on what went wrong
+/// without resorting to matching on error messages. Use [`ErrorKind::of`] to
+/// learn the kind of an error:
+///
+/// ```
+/// # use wasmtime::*;
+/// # fn main() -> Result<()> {
+/// let engine = Engine::default();
+/// let error = Module::new(&engine, "(module (func (drop)))").unwrap_err();
+/// assert_eq!(ErrorKind::of(&error), Some(ErrorKind::Validate));
+///
+/// let module = Module::new(&engine, r#"(module (import "" "" (func)))"#)?;
+/// let mut store = Store::new(&engine, ());
+/// let error = Instance::new(&mut store, &module, &[]).unwrap_err();
+/// assert_eq!(ErrorKind::of(&error), Some(ErrorKind::Link));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Errors which don't originate from Wasmtime itself, such as errors returned
+/// by host functions, have no kind unless the host function's error is
+/// itself, for example, a [`Trap`](crate::Trap).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// WebAssembly could not be compiled, for example because it uses a
+    /// proposal which isn't supported or because it exceeds an implementation
+    /// limit of the compiler.
+    Compile,
+    /// WebAssembly failed to parse or validate.
+    Validate,
+    /// Imports did not satisfy a module's or component's requirements, for
+    /// example because an import is missing, has the wrong type, or was
+    /// defined twice in a [`Linker`](crate::Linker).
+    Link,
+    /// WebAssembly trapped; the [`Trap`](crate::Trap) can be recovered with
+    /// [`anyhow::Error::downcast_ref`].
+    Trap,
+    /// A resource limit was exceeded, for example a
+    /// [`ResourceLimiter`](crate::ResourceLimiter) denied the creation of a
+    /// memory or the GC heap is out of memory.
+    ResourceLimit,
+    /// The [`Config`](crate::Config) used to create an
+    /// [`Engine`](crate::Engine) is invalid or not supported on this host.
+    Configuration,
+}
+
+impl ErrorKind {
+    /// Returns the kind of `error`, or `None` if it's not known.
+    ///
+    /// The whole [`chain`](anyhow::Error::chain) of `error` is searched, so
+    /// context added to Wasmtime's errors, for example by host functions, does
+    /// not hide their kind.
+    pub fn of(error: &Error) -> Option<ErrorKind> {
+        error.chain().find_map(|cause| {
+            if let Some(tagged) = cause.downcast_ref::<Tagged>() {
+                return Some(tagged.kind);
+            }
+            if cause.is::<crate::Trap>() {
+                return Some(ErrorKind::Trap);
+            }
+            if let Some(error) = cause.downcast_ref::<wasmtime_environ::WasmError>() {
+                return Some(match error {
+                    wasmtime_environ::WasmError::InvalidWebAssembly { .. } => ErrorKind::Validate,
+                    _ => ErrorKind::Compile,
+                });
+            }
+            if cause.is::<wasmparser::BinaryReaderError>() {
+                return Some(ErrorKind::Validate);
+            }
+            #[cfg(any(feature = "cranelift", feature = "winch"))]
+            if let Some(error) = cause.downcast_ref::<wasmtime_environ::CompileError>() {
+                // Translation errors are classified by the `WasmError` which
+                // is next in the chain.
+                if !matches!(error, wasmtime_environ::CompileError::Wasm(_)) {
+                    return Some(ErrorKind::Compile);
+                }
+            }
+            #[cfg(feature = "runtime")]
+            if cause.is::<crate::UnknownImportError>() || cause.is::<crate::ImportErrors>() {
+                return Some(ErrorKind::Link);
+            }
+            #[cfg(feature = "runtime")]
+            if cause.is::<crate::GcHeapOutOfMemory<()>>() {
+                return Some(ErrorKind::ResourceLimit);
+            }
+            #[cfg(feature = "pooling-allocator")]
+            if cause.is::<crate::PoolConcurrencyLimitError>() {
+                return Some(ErrorKind::ResourceLimit);
+            }
+            None
+        })
+    }
+
+    /// Tags `error` as being of this kind.
+    ///
+    /// The returned error displays exactly as `error` does and `error` is its
+    /// source, followed by the rest of its chain of causes. Note that types
+    /// within `error` can then only be recovered by searching the chain rather
+    /// than with [`anyhow::Error::downcast_ref`], so this is only used for
+    /// errors created from messages.
+    pub(crate) fn tag(self, error: Error) -> Error {
+        Error::new(Tagged { kind: self, error })
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ErrorKind::Compile => "compilation error",
+            ErrorKind::Validate => "validation error",
+            ErrorKind::Link => "link error",
+            ErrorKind::Trap => "trap",
+            ErrorKind::ResourceLimit => "resource limit exceeded",
+            ErrorKind::Configuration => "configuration error",
+        })
+    }
+}
+
+/// An error tagged with its kind, which is otherwise transparent.
+struct Tagged {
+    kind: ErrorKind,
+    error: Error,
+}
+
+impl fmt::Debug for Tagged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.error, f)
+    }
+}
+
+impl fmt::Display for Tagged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl core::error::Error for Tagged {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&*self.error)
+    }
+}
@@ -272,6 +272,9 @@
 //!   in segfaults. This is implicitly enabled by the `std` feature and is the
 //!   best way to get high-performance WebAssembly.
 //!
+//! * `toml` - Disabled by default, this enables [`Config::to_toml`] and
+//!   [`Config::from_toml`] to save and load engine configuration as TOML.
+//!
 //! More crate features can be found in the [manifest] of Wasmtime itself for
 //! seeing what can be enabled and disabled.
 //!
@@ -372,14 +375,18 @@ pub use runtime::*;
 #[cfg(any(feature = "cranelift", feature = "winch"))]
 mod compile;
 #[cfg(any(feature = "cranelift", feature = "winch"))]
-pub use compile::{CodeBuilder, CodeHint};
+pub use compile::{CodeBuilder, CodeHint, Lint, LintConfig};
+#[cfg(all(feature = "runtime", any(feature = "cranelift", feature = "winch")))]
+pub use compile::{DeterminismReport, NondeterminismSource};
 
 mod config;
 mod engine;
+mod error_kind;
 mod profiling_agent;
 
 pub use crate::config::*;
 pub use crate::engine::*;
+pub use crate::error_kind::ErrorKind;
 
 #[cfg(feature = "std")]
 mod sync_std;
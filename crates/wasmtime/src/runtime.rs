@@ -29,18 +29,27 @@
 #[macro_use]
 pub(crate) mod func;
 
+pub(crate) mod blobs;
 pub(crate) mod code;
 pub(crate) mod code_memory;
+pub(crate) mod continuation;
+#[cfg(feature = "threads")]
+pub(crate) mod deadlock;
 pub(crate) mod debug;
+pub(crate) mod dylink;
+pub(crate) mod error_policy;
 pub(crate) mod externals;
 pub(crate) mod gc;
+pub(crate) mod hostcall_batch;
 pub(crate) mod instance;
 pub(crate) mod instantiate;
 pub(crate) mod limits;
 pub(crate) mod linker;
 pub(crate) mod memory;
 pub(crate) mod module;
+pub(crate) mod quota;
 pub(crate) mod resources;
+pub(crate) mod snapshot;
 pub(crate) mod store;
 pub(crate) mod trampoline;
 pub(crate) mod trap;
@@ -66,17 +75,29 @@ cfg_if::cfg_if! {
     }
 }
 
+pub use blobs::{BlobContext, BlobEviction, BlobId, BlobStore, BlobStoreBuilder};
 pub use code_memory::CodeMemory;
+pub use continuation::{Continuation, Resumed};
+#[cfg(feature = "threads")]
+pub use deadlock::{BlockedThread, DeadlockMonitor, DeadlockReport};
+pub use dylink::{LibraryHandle, LibrarySymbol, SharedLibrary};
+pub use error_policy::{ErrnoRet, ErrorPolicy, HostError};
 pub use externals::*;
 pub use func::*;
 pub use gc::*;
-pub use instance::{Instance, InstancePre};
+pub use hostcall_batch::{BatchOp, HostcallBatch};
+pub use instance::{AllocatedInstance, DataSegment, Instance, InstancePre};
 pub use instantiate::CompiledModule;
 pub use limits::*;
 pub use linker::*;
 pub use memory::*;
+#[cfg(feature = "disas")]
+pub use module::DisassembledInstruction;
 pub use module::{Module, ModuleExport};
+pub use quota::{QuotaGroup, QuotaGroupBuilder, QuotaPolicy};
 pub use resources::*;
+#[cfg(feature = "async")]
+pub use store::AsyncPollStats;
 #[cfg(all(feature = "async", feature = "call-hook"))]
 pub use store::CallHookHandler;
 pub use store::{
@@ -92,6 +113,17 @@ pub(crate) use uninhabited::*;
 #[cfg(feature = "pooling-allocator")]
 pub use vm::PoolConcurrencyLimitError;
 
+pub use vm::WasmFXStackCacheStats;
+
+mod backtrace_throttle;
+pub use backtrace_throttle::{BacktraceCaptureStats, BacktraceThrottle};
+
+pub(crate) mod crash;
+pub use crash::WasmCrashInfo;
+
+pub(crate) mod metrics;
+pub use metrics::{Counter, Histogram, Metrics};
+
 #[cfg(feature = "profiling")]
 mod profiling;
 #[cfg(feature = "profiling")]
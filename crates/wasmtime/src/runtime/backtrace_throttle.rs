@@ -0,0 +1,154 @@
+use core::cell::Cell;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+/// Configuration for limiting how often a store captures backtraces for
+/// traps.
+///
+/// When [`Config::wasm_backtrace`](crate::Config::wasm_backtrace) is enabled
+/// every trap walks the stack to capture a [`WasmBacktrace`], which is
+/// relatively expensive, and symbolicating those backtraces later is even more
+/// so. Guests which trap in a tight loop, as fuzzer-generated programs often
+/// do, can turn this into a significant cost for the host. Installing a
+/// throttle on a store with [`Store::set_backtrace_throttle`] bounds that cost
+/// while still capturing some backtraces for diagnostics.
+///
+/// Traps whose backtrace capture is suppressed are still reported as usual,
+/// just without a [`WasmBacktrace`] attached. The number of captured and
+/// suppressed backtraces is available from
+/// [`Store::backtrace_capture_stats`].
+///
+/// [`WasmBacktrace`]: crate::WasmBacktrace
+/// [`Store::set_backtrace_throttle`]: crate::Store::set_backtrace_throttle
+/// [`Store::backtrace_capture_stats`]: crate::Store::backtrace_capture_stats
+#[derive(Clone, Debug)]
+pub struct BacktraceThrottle {
+    #[cfg(feature = "std")]
+    max_per_second: Option<u32>,
+    sample_every: u32,
+}
+
+impl Default for BacktraceThrottle {
+    fn default() -> BacktraceThrottle {
+        BacktraceThrottle::new()
+    }
+}
+
+impl BacktraceThrottle {
+    /// Creates a new throttle which, until configured otherwise, captures a
+    /// backtrace for every trap.
+    pub fn new() -> BacktraceThrottle {
+        BacktraceThrottle {
+            #[cfg(feature = "std")]
+            max_per_second: None,
+            sample_every: 1,
+        }
+    }
+
+    /// Configures the maximum number of backtraces captured in any one-second
+    /// window.
+    ///
+    /// Once `max` backtraces have been captured within a window all further
+    /// captures are suppressed until the window ends. This is unlimited by
+    /// default.
+    #[cfg(feature = "std")]
+    pub fn max_per_second(&mut self, max: u32) -> &mut Self {
+        self.max_per_second = Some(max);
+        self
+    }
+
+    /// Configures this throttle to only consider capturing a backtrace for
+    /// one out of every `n` traps.
+    ///
+    /// This is applied before the [`BacktraceThrottle::max_per_second`] limit.
+    /// A value of `0` or `1`, the default, considers every trap.
+    pub fn sample_every(&mut self, n: u32) -> &mut Self {
+        self.sample_every = n.max(1);
+        self
+    }
+}
+
+/// Counters describing the backtraces captured for traps in a store with a
+/// [`BacktraceThrottle`] installed.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BacktraceCaptureStats {
+    /// The number of traps for which a backtrace was captured.
+    pub captured: u64,
+    /// The number of traps for which backtrace capture was suppressed by the
+    /// throttle.
+    pub suppressed: u64,
+}
+
+/// Per-store state of a [`BacktraceThrottle`].
+///
+/// This is consulted from the trap handler while wasm is on the stack, hence
+/// the use of `Cell` for all mutable state: the store itself is not otherwise
+/// accessible at that point. For the same reason this state is never
+/// deallocated while its store is alive, and removing a throttle only resets
+/// it to `None`.
+pub(crate) struct BacktraceThrottleState {
+    config: Option<BacktraceThrottle>,
+    traps: Cell<u64>,
+    #[cfg(feature = "std")]
+    window: Cell<Option<(Instant, u32)>>,
+    stats: Cell<BacktraceCaptureStats>,
+}
+
+impl BacktraceThrottleState {
+    pub(crate) fn new(config: Option<BacktraceThrottle>) -> BacktraceThrottleState {
+        BacktraceThrottleState {
+            config,
+            traps: Cell::new(0),
+            #[cfg(feature = "std")]
+            window: Cell::new(None),
+            stats: Cell::new(BacktraceCaptureStats::default()),
+        }
+    }
+
+    pub(crate) fn stats(&self) -> Option<BacktraceCaptureStats> {
+        self.config.as_ref().map(|_| self.stats.get())
+    }
+
+    /// Records a trap, returning whether its backtrace should be captured.
+    pub(crate) fn should_capture(&self) -> bool {
+        let Some(config) = &self.config else {
+            return true;
+        };
+        let capture = self.admit(config);
+        let mut stats = self.stats.get();
+        if capture {
+            stats.captured += 1;
+        } else {
+            stats.suppressed += 1;
+        }
+        self.stats.set(stats);
+        capture
+    }
+
+    fn admit(&self, config: &BacktraceThrottle) -> bool {
+        let traps = self.traps.get();
+        self.traps.set(traps.wrapping_add(1));
+        if traps % u64::from(config.sample_every) != 0 {
+            return false;
+        }
+
+        #[cfg(feature = "std")]
+        if let Some(max) = config.max_per_second {
+            let now = Instant::now();
+            let count = match self.window.get() {
+                Some((start, count)) if now.duration_since(start) < Duration::from_secs(1) => count,
+                _ => {
+                    self.window.set(Some((now, 0)));
+                    0
+                }
+            };
+            if count >= max {
+                return false;
+            }
+            let start = self.window.get().unwrap().0;
+            self.window.set(Some((start, count + 1)));
+        }
+
+        true
+    }
+}
@@ -0,0 +1,581 @@
+use crate::hash_map::HashMap;
+use crate::hash_set::HashSet;
+use crate::prelude::*;
+use crate::sync::RwLock;
+use crate::{Caller, Engine, ErrorKind, Extern, Linker, Memory};
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use core::fmt;
+
+/// What a [`BlobStore`] does with blobs which are no longer referenced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlobEviction {
+    /// Unreferenced blobs are kept, so that interning the same contents again
+    /// is cheap and returns the same [`BlobId`], until their space is needed
+    /// for new blobs, at which point the least recently used ones are evicted
+    /// first.
+    #[default]
+    LeastRecentlyUsed,
+    /// Blobs are evicted as soon as their last reference is released.
+    OnRelease,
+}
+
+/// Used to build a [`BlobStore`].
+#[derive(Debug, Clone, Default)]
+pub struct BlobStoreBuilder {
+    capacity: Option<usize>,
+    tenant_quota: Option<usize>,
+    eviction: BlobEviction,
+}
+
+impl BlobStoreBuilder {
+    /// Creates a new [`BlobStoreBuilder`] with no limits configured.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The maximum number of bytes of blobs held by the store, counting each
+    /// distinct blob once no matter how many references it has.
+    ///
+    /// Interning new contents fails if this limit would be exceeded even
+    /// after evicting unreferenced blobs.
+    ///
+    /// By default, the size of the store will not be limited.
+    pub fn capacity(mut self, bytes: usize) -> Self {
+        self.capacity = Some(bytes);
+        self
+    }
+
+    /// The maximum number of bytes of blobs which each tenant may reference.
+    ///
+    /// Each distinct blob referenced by any of a tenant's [`BlobContext`]s is
+    /// charged to the tenant once, even when it's shared with other tenants.
+    ///
+    /// By default, tenants will not be limited.
+    pub fn tenant_quota(mut self, bytes: usize) -> Self {
+        self.tenant_quota = Some(bytes);
+        self
+    }
+
+    /// What happens to blobs which are no longer referenced.
+    ///
+    /// This defaults to [`BlobEviction::LeastRecentlyUsed`].
+    pub fn eviction(mut self, eviction: BlobEviction) -> Self {
+        self.eviction = eviction;
+        self
+    }
+
+    /// Consumes this builder and returns a [`BlobStore`] for stores of
+    /// `engine`.
+    pub fn build(self, engine: &Engine) -> BlobStore {
+        BlobStore(Arc::new(BlobStoreInner {
+            engine: engine.clone(),
+            capacity: self.capacity.unwrap_or(usize::MAX),
+            tenant_quota: self.tenant_quota.unwrap_or(usize::MAX),
+            eviction: self.eviction,
+            state: RwLock::new(BlobState::default()),
+        }))
+    }
+}
+
+/// The identifier of a blob in a [`BlobStore`].
+///
+/// Identifiers are never reused by a store, so an identifier refers to the
+/// same contents for as long as the blob is live. Identifiers alone don't grant
+/// access to blobs: a [`BlobContext`] can only acquire a blob which its tenant
+/// interned itself or which was shared with it by [`BlobContext::share`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlobId(u64);
+
+impl BlobId {
+    /// Returns the raw value of this identifier, as seen by guests.
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+
+    /// Creates an identifier from its raw value.
+    pub fn from_u64(id: u64) -> BlobId {
+        BlobId(id)
+    }
+}
+
+impl fmt::Display for BlobId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "blob{}", self.0)
+    }
+}
+
+/// An engine-wide store of immutable byte blobs which are deduplicated by
+/// content and shared between [`Store`](crate::Store)s.
+///
+/// Large read-only assets, such as vocabulary files or templates, are often
+/// loaded by every instance of a program. Interning them in a `BlobStore`
+/// instead keeps a single copy which all stores refer to by [`BlobId`].
+///
+/// Blobs are referenced through [`BlobContext`]s, usually one per store,
+/// which are created on behalf of a tenant with [`BlobStore::context`] and
+/// release their references when dropped. Guests access blobs through the
+/// host functions defined by [`BlobStore::add_to_linker`].
+///
+/// This type is cheaply clonable and all clones refer to the same store.
+///
+/// # Examples
+///
+/// ```
+/// use wasmtime::*;
+///
+/// let engine = Engine::default();
+/// let blobs = BlobStoreBuilder::new().tenant_quota(1 << 20).build(&engine);
+///
+/// let mut a = blobs.context("tenant-a");
+/// let mut b = blobs.context("tenant-b");
+/// let id = a.intern(b"shared asset").unwrap();
+/// assert_eq!(b.intern(b"shared asset").unwrap(), id);
+/// assert_eq!(blobs.len(), 1);
+///
+/// drop(a);
+/// assert_eq!(&b.get(id).unwrap()[..], b"shared asset");
+///
+/// // Other tenants can only acquire the blob once it's shared with them.
+/// let mut c = blobs.context("tenant-c");
+/// assert!(c.acquire(id).is_err());
+/// b.share(id, "tenant-c").unwrap();
+/// c.acquire(id).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct BlobStore(Arc<BlobStoreInner>);
+
+struct BlobStoreInner {
+    engine: Engine,
+    capacity: usize,
+    tenant_quota: usize,
+    eviction: BlobEviction,
+    state: RwLock<BlobState>,
+}
+
+#[derive(Default)]
+struct BlobState {
+    blobs: HashMap<BlobId, Blob>,
+    by_contents: HashMap<Arc<[u8]>, BlobId>,
+    tenants: HashMap<Arc<str>, TenantUsage>,
+    /// Unreferenced blobs, keyed by when they were last released, so that the
+    /// least recently used one is first.
+    unreferenced: BTreeMap<u64, BlobId>,
+    next_id: u64,
+    size: usize,
+    clock: u64,
+}
+
+struct Blob {
+    contents: Arc<[u8]>,
+    refs: usize,
+    last_used: u64,
+    /// The tenants which may acquire this blob by its identifier.
+    tenants: HashSet<Arc<str>>,
+}
+
+#[derive(Default)]
+struct TenantUsage {
+    size: usize,
+    refs: HashMap<BlobId, usize>,
+}
+
+impl BlobStore {
+    /// Returns a new context for referencing blobs on behalf of `tenant`.
+    pub fn context(&self, tenant: &str) -> BlobContext {
+        BlobContext {
+            store: self.clone(),
+            tenant: tenant.into(),
+            refs: HashMap::new(),
+        }
+    }
+
+    /// Returns the number of blobs currently held by this store, including
+    /// unreferenced blobs which haven't been evicted yet.
+    pub fn len(&self) -> usize {
+        self.0.state.read().blobs.len()
+    }
+
+    /// Returns whether this store holds no blobs.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of bytes of blobs currently held by this store.
+    pub fn size(&self) -> usize {
+        self.0.state.read().size
+    }
+
+    /// Returns the number of bytes of blobs currently charged to `tenant`.
+    pub fn tenant_size(&self, tenant: &str) -> usize {
+        self.0
+            .state
+            .read()
+            .tenants
+            .get(tenant)
+            .map_or(0, |usage| usage.size)
+    }
+
+    /// Defines host functions in `linker` through which guests can access
+    /// this store, using the [`BlobContext`] returned by `get` for the store
+    /// they're called from.
+    ///
+    /// The functions are defined in the `wasmtime:blobs` module and access the
+    /// guest's exported `memory`:
+    ///
+    /// * `intern(ptr: i32, len: i32) -> i64` interns the given bytes and
+    ///   returns their identifier, or `-1` if a limit of this store was
+    ///   reached.
+    /// * `acquire(id: i64) -> i32` adds a reference to the blob `id`, returning
+    ///   `1` on success and `0` if no such blob exists, the caller's tenant may
+    ///   not acquire it, or a limit was reached.
+    /// * `size(id: i64) -> i64` returns the size of the blob `id`, or `-1` if
+    ///   the caller doesn't reference it.
+    /// * `read(id: i64, offset: i64, ptr: i32, len: i32) -> i64` copies up to
+    ///   `len` bytes of the blob `id` starting at `offset` into memory and
+    ///   returns how many were copied, or `-1` if the caller doesn't reference
+    ///   the blob or `offset` is out of bounds.
+    /// * `release(id: i64) -> i32` releases one reference to the blob `id`,
+    ///   returning `1` on success and `0` if the caller doesn't reference it.
+    ///
+    /// Accesses outside of the guest's memory trap.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `linker` is for a different engine than this store,
+    /// or if any of the functions are already defined in `linker`.
+    pub fn add_to_linker<T: 'static>(
+        &self,
+        linker: &mut Linker<T>,
+        get: impl Fn(&mut T) -> &mut BlobContext + Send + Sync + Copy + 'static,
+    ) -> Result<()> {
+        ensure!(
+            Engine::same(linker.engine(), &self.0.engine),
+            "cannot use a `BlobStore` with a `Linker` for a different engine"
+        );
+        const MODULE: &str = "wasmtime:blobs";
+        linker.func_wrap(
+            MODULE,
+            "intern",
+            move |mut caller: Caller<'_, T>, ptr: u32, len: u32| -> Result<i64> {
+                let memory = guest_memory(&mut caller)?;
+                let (data, host) = memory.data_and_store_mut(&mut caller);
+                let bytes = usize::try_from(ptr)
+                    .ok()
+                    .and_then(|ptr| data.get(ptr..)?.get(..usize::try_from(len).ok()?))
+                    .ok_or_else(|| anyhow!("out of bounds memory access"))?;
+                Ok(match get(host).intern(bytes) {
+                    Ok(id) => i64::try_from(id.0)?,
+                    Err(_) => -1,
+                })
+            },
+        )?;
+        linker.func_wrap(
+            MODULE,
+            "acquire",
+            move |mut caller: Caller<'_, T>, id: u64| -> i32 {
+                get(caller.data_mut()).acquire(BlobId(id)).is_ok().into()
+            },
+        )?;
+        linker.func_wrap(
+            MODULE,
+            "size",
+            move |mut caller: Caller<'_, T>, id: u64| -> Result<i64> {
+                match get(caller.data_mut()).get(BlobId(id)) {
+                    Some(contents) => Ok(i64::try_from(contents.len())?),
+                    None => Ok(-1),
+                }
+            },
+        )?;
+        linker.func_wrap(
+            MODULE,
+            "read",
+            move |mut caller: Caller<'_, T>,
+                  id: u64,
+                  offset: u64,
+                  ptr: u32,
+                  len: u32|
+                  -> Result<i64> {
+                let contents = match get(caller.data_mut()).get(BlobId(id)) {
+                    Some(contents) => contents,
+                    None => return Ok(-1),
+                };
+                let rest = match usize::try_from(offset).ok().and_then(|o| contents.get(o..)) {
+                    Some(rest) => rest,
+                    None => return Ok(-1),
+                };
+                let bytes = &rest[..rest.len().min(usize::try_from(len)?)];
+                let memory = guest_memory(&mut caller)?;
+                memory.write(&mut caller, usize::try_from(ptr)?, bytes)?;
+                Ok(i64::try_from(bytes.len())?)
+            },
+        )?;
+        linker.func_wrap(
+            MODULE,
+            "release",
+            move |mut caller: Caller<'_, T>, id: u64| -> i32 {
+                get(caller.data_mut()).release(BlobId(id)).into()
+            },
+        )?;
+        Ok(())
+    }
+}
+
+fn guest_memory<T>(caller: &mut Caller<'_, T>) -> Result<Memory> {
+    match caller.get_export("memory") {
+        Some(Extern::Memory(memory)) => Ok(memory),
+        _ => bail!("`wasmtime:blobs` functions require an exported `memory`"),
+    }
+}
+
+impl fmt::Debug for BlobStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlobStore")
+            .field("capacity", &self.0.capacity)
+            .field("tenant_quota", &self.0.tenant_quota)
+            .field("eviction", &self.0.eviction)
+            .finish_non_exhaustive()
+    }
+}
+
+impl BlobState {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Evicts unreferenced blobs, least recently used first, until `size`
+    /// more bytes fit within `capacity`.
+    fn make_room(&mut self, size: usize, capacity: usize) -> Result<()> {
+        while self.size.saturating_add(size) > capacity {
+            match self.unreferenced.first_key_value() {
+                Some((_, id)) => self.remove(*id),
+                None => {
+                    return Err(ErrorKind::ResourceLimit
+                        .tag(anyhow!("blob store capacity of {capacity} bytes exceeded")))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, id: BlobId) {
+        let blob = self.blobs.remove(&id).unwrap();
+        if blob.refs == 0 {
+            self.unreferenced.remove(&blob.last_used);
+        }
+        self.by_contents.remove(&blob.contents);
+        self.size -= blob.contents.len();
+    }
+}
+
+/// A set of references to the blobs of a [`BlobStore`], held on behalf of a
+/// tenant.
+///
+/// Blobs referenced by a context are kept alive, and charged to its tenant,
+/// until the context releases them or is dropped. A context is typically kept
+/// in the data of each [`Store`](crate::Store) which uses blobs.
+pub struct BlobContext {
+    store: BlobStore,
+    tenant: Arc<str>,
+    refs: HashMap<BlobId, usize>,
+}
+
+impl BlobContext {
+    /// Returns the tenant on whose behalf this context references blobs.
+    pub fn tenant(&self) -> &str {
+        &self.tenant
+    }
+
+    /// Interns `bytes`, returning the identifier of the blob holding them and
+    /// adding a reference to it.
+    ///
+    /// If a blob with the same contents is already held by the store, it's
+    /// reused rather than copying `bytes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if referencing the blob would exceed the tenant's
+    /// quota, or if a new blob is needed and it doesn't fit within the store's
+    /// capacity.
+    pub fn intern(&mut self, bytes: &[u8]) -> Result<BlobId> {
+        let inner = self.store.0.clone();
+        let mut state = inner.state.write();
+        let id = match state.by_contents.get(bytes) {
+            Some(id) => *id,
+            None => {
+                self.check_quota(&state, None, bytes.len())?;
+                state.make_room(bytes.len(), inner.capacity)?;
+                let id = BlobId(state.next_id);
+                state.next_id += 1;
+                let contents: Arc<[u8]> = bytes.into();
+                state.by_contents.insert(contents.clone(), id);
+                state.size += contents.len();
+                state.blobs.insert(
+                    id,
+                    Blob {
+                        contents,
+                        refs: 0,
+                        last_used: 0,
+                        tenants: HashSet::new(),
+                    },
+                );
+                id
+            }
+        };
+        self.add_ref(&mut state, id)?;
+        // Having the contents of a blob is as good as being granted it.
+        let tenant = self.tenant.clone();
+        state.blobs.get_mut(&id).unwrap().tenants.insert(tenant);
+        Ok(id)
+    }
+
+    /// Adds a reference to the existing blob `id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no such blob exists, if it was neither interned by
+    /// this context's tenant nor shared with it, or if referencing it would
+    /// exceed the tenant's quota.
+    pub fn acquire(&mut self, id: BlobId) -> Result<()> {
+        let inner = self.store.0.clone();
+        let mut state = inner.state.write();
+        let permitted = state
+            .blobs
+            .get(&id)
+            .is_some_and(|blob| blob.tenants.contains(&self.tenant));
+        ensure!(permitted, "unknown blob {id}");
+        self.add_ref(&mut state, id)
+    }
+
+    /// Allows `tenant` to acquire the blob `id` by its identifier.
+    ///
+    /// The permission lasts for as long as the blob is held by the store.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this context doesn't reference the blob `id`.
+    pub fn share(&self, id: BlobId, tenant: &str) -> Result<()> {
+        ensure!(self.refs.contains_key(&id), "unknown blob {id}");
+        let mut state = self.store.0.state.write();
+        state
+            .blobs
+            .get_mut(&id)
+            .unwrap()
+            .tenants
+            .insert(tenant.into());
+        Ok(())
+    }
+
+    /// Returns the contents of the blob `id`, if this context references it.
+    pub fn get(&self, id: BlobId) -> Option<Arc<[u8]>> {
+        if !self.refs.contains_key(&id) {
+            return None;
+        }
+        let state = self.store.0.state.read();
+        Some(state.blobs[&id].contents.clone())
+    }
+
+    /// Releases one reference to the blob `id`, returning whether this
+    /// context referenced it.
+    pub fn release(&mut self, id: BlobId) -> bool {
+        match self.refs.get_mut(&id) {
+            Some(refs) if *refs > 1 => *refs -= 1,
+            Some(_) => {
+                self.refs.remove(&id);
+            }
+            None => return false,
+        }
+        let inner = &*self.store.0;
+        let mut state = inner.state.write();
+        Self::drop_ref(&mut state, inner.eviction, &self.tenant, id);
+        true
+    }
+
+    fn check_quota(&self, state: &BlobState, id: Option<BlobId>, size: usize) -> Result<()> {
+        let usage = state.tenants.get(&self.tenant);
+        if let (Some(usage), Some(id)) = (usage, id) {
+            if usage.refs.contains_key(&id) {
+                return Ok(());
+            }
+        }
+        let used = usage.map_or(0, |usage| usage.size);
+        let quota = self.store.0.tenant_quota;
+        if used.saturating_add(size) > quota {
+            return Err(ErrorKind::ResourceLimit.tag(anyhow!(
+                "blob quota of {quota} bytes exceeded for tenant `{}`",
+                self.tenant
+            )));
+        }
+        Ok(())
+    }
+
+    fn add_ref(&mut self, state: &mut BlobState, id: BlobId) -> Result<()> {
+        let size = state.blobs[&id].contents.len();
+        self.check_quota(state, Some(id), size)?;
+        let blob = state.blobs.get_mut(&id).unwrap();
+        blob.refs += 1;
+        if blob.refs == 1 {
+            let last_used = blob.last_used;
+            state.unreferenced.remove(&last_used);
+        }
+        let usage = state.tenants.entry(self.tenant.clone()).or_default();
+        let refs = usage.refs.entry(id).or_insert(0);
+        if *refs == 0 {
+            usage.size += size;
+        }
+        *refs += 1;
+        *self.refs.entry(id).or_insert(0) += 1;
+        Ok(())
+    }
+
+    fn drop_ref(state: &mut BlobState, eviction: BlobEviction, tenant: &str, id: BlobId) {
+        let size = state.blobs[&id].contents.len();
+        let usage = state.tenants.get_mut(tenant).unwrap();
+        let refs = usage.refs.get_mut(&id).unwrap();
+        *refs -= 1;
+        if *refs == 0 {
+            usage.refs.remove(&id);
+            usage.size -= size;
+            if usage.refs.is_empty() {
+                state.tenants.remove(tenant);
+            }
+        }
+        let blob = state.blobs.get_mut(&id).unwrap();
+        blob.refs -= 1;
+        if blob.refs == 0 {
+            match eviction {
+                BlobEviction::OnRelease => state.remove(id),
+                BlobEviction::LeastRecentlyUsed => {
+                    let now = state.tick();
+                    state.blobs.get_mut(&id).unwrap().last_used = now;
+                    state.unreferenced.insert(now, id);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for BlobContext {
+    fn drop(&mut self) {
+        if self.refs.is_empty() {
+            return;
+        }
+        let inner = &*self.store.0;
+        let mut state = inner.state.write();
+        for (id, refs) in self.refs.drain() {
+            for _ in 0..refs {
+                Self::drop_ref(&mut state, inner.eviction, &self.tenant, id);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for BlobContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlobContext")
+            .field("tenant", &self.tenant)
+            .field("blobs", &self.refs.len())
+            .finish()
+    }
+}
@@ -27,6 +27,7 @@ pub struct CodeMemory {
     custom_code_memory: Option<Arc<dyn CustomCodeMemory>>,
 
     relocations: Vec<(usize, obj::LibCall)>,
+    host_intrinsic_relocations: Vec<(usize, usize)>,
 
     // Ranges within `self.mmap` of where the particular sections lie.
     text: Range<usize>,
@@ -110,6 +111,7 @@ impl CodeMemory {
             .with_context(|| "failed to parse internal compilation artifact")?;
 
         let mut relocations = Vec::new();
+        let mut host_intrinsic_relocations = Vec::new();
         let mut text = 0..0;
         let mut unwind = 0..0;
         let mut enable_branch_protection = None;
@@ -168,10 +170,24 @@ impl CodeMemory {
                             other => panic!("unknown relocation target {other:?}"),
                         };
                         let sym = obj.symbol_by_index(sym).unwrap().name().unwrap();
+                        let offset = usize::try_from(offset).unwrap();
+
+                        // Host intrinsics are resolved here using the
+                        // engine's configuration, which deserialization has
+                        // already checked matches the configuration this
+                        // object was compiled with.
+                        if let Some(index) = obj::host_intrinsic_from_symbol(sym) {
+                            let (_, _, intrinsic) = engine
+                                .config()
+                                .host_intrinsics
+                                .get(usize::try_from(index).unwrap())
+                                .ok_or_else(|| anyhow!("unknown host intrinsic {index}"))?;
+                            host_intrinsic_relocations.push((offset, intrinsic.address()));
+                            continue;
+                        }
+
                         let libcall = obj::LibCall::from_str(sym)
                             .unwrap_or_else(|| panic!("unknown symbol relocation: {sym}"));
-
-                        let offset = usize::try_from(offset).unwrap();
                         relocations.push((offset, libcall));
                     }
                 }
@@ -210,6 +226,7 @@ impl CodeMemory {
             info_data,
             wasm_data,
             relocations,
+            host_intrinsic_relocations,
         })
     }
 
@@ -226,6 +243,52 @@ impl CodeMemory {
         &self.mmap[self.text.clone()]
     }
 
+    /// Returns a hash of the text section and wasm data of this image, which
+    /// identifies the module it was compiled from.
+    ///
+    /// The hash is stable across processes: relocated words of the text
+    /// section are hashed as zeros so that the addresses of libcalls and host
+    /// intrinsics don't affect it.
+    pub fn content_hash(&self) -> u64 {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0100_0000_01b3;
+
+        let mut relocated = self
+            .relocations
+            .iter()
+            .map(|(offset, _)| *offset)
+            .chain(
+                self.host_intrinsic_relocations
+                    .iter()
+                    .map(|(offset, _)| *offset),
+            )
+            .collect::<Vec<_>>();
+        relocated.sort_unstable();
+
+        // FNV-1a, which is good enough to tell modules apart and doesn't need
+        // an extra dependency.
+        let mut hash = OFFSET_BASIS;
+        let mut relocated = relocated.into_iter().peekable();
+        let mut masked = 0;
+        for (i, byte) in self.text().iter().enumerate() {
+            if relocated.peek() == Some(&i) {
+                relocated.next();
+                masked = core::mem::size_of::<usize>();
+            }
+            let byte = if masked > 0 {
+                masked -= 1;
+                0
+            } else {
+                *byte
+            };
+            hash = (hash ^ u64::from(byte)).wrapping_mul(PRIME);
+        }
+        for byte in self.wasm_data() {
+            hash = (hash ^ u64::from(*byte)).wrapping_mul(PRIME);
+        }
+        hash
+    }
+
     /// Returns the contents of the `ELF_WASMTIME_DWARF` section.
     #[inline]
     pub fn wasm_dwarf(&self) -> &[u8] {
@@ -383,7 +446,7 @@ impl CodeMemory {
     }
 
     unsafe fn apply_relocations(&mut self) -> Result<()> {
-        if self.relocations.is_empty() {
+        if self.relocations.is_empty() && self.host_intrinsic_relocations.is_empty() {
             return Ok(());
         }
 
@@ -412,6 +475,15 @@ impl CodeMemory {
                 .cast::<usize>()
                 .write_unaligned(libcall);
         }
+        for (offset, address) in self.host_intrinsic_relocations.iter() {
+            let offset = self.text.start + offset;
+            self.mmap
+                .as_mut_slice()
+                .as_mut_ptr()
+                .add(offset)
+                .cast::<usize>()
+                .write_unaligned(*address);
+        }
         Ok(())
     }
 
@@ -1,6 +1,7 @@
 use crate::component::matching::InstanceType;
 use crate::component::types;
 use crate::component::InstanceExportLookup;
+use crate::hash_map::HashMap;
 use crate::prelude::*;
 use crate::runtime::vm::component::ComponentRuntimeInfo;
 #[cfg(feature = "std")]
@@ -20,11 +21,12 @@ use core::ptr::NonNull;
 #[cfg(feature = "std")]
 use std::path::Path;
 use wasmtime_environ::component::{
-    AllCallFunc, CompiledComponentInfo, ComponentArtifacts, ComponentTypes, Export, ExportIndex,
-    GlobalInitializer, InstantiateModule, NameMapNoIntern, StaticModuleIndex, TrampolineIndex,
-    TypeComponentIndex, TypeDef, VMComponentOffsets,
+    AllCallFunc, CompiledComponentInfo, ComponentArtifacts, ComponentTypes, CoreDef, Export,
+    ExportIndex, ExportItem, GlobalInitializer, InstantiateModule, LoweredIndex, NameMapNoIntern,
+    RuntimeImportIndex, StaticModuleIndex, TrampolineIndex, TrampolineKind, TypeComponentIndex,
+    TypeDef, VMComponentOffsets,
 };
-use wasmtime_environ::{FunctionLoc, HostPtr, ObjectKind, PrimaryMap};
+use wasmtime_environ::{EntityIndex, FunctionLoc, HostPtr, ObjectKind, PrimaryMap};
 
 /// A compiled WebAssembly Component.
 ///
@@ -760,6 +762,244 @@ impl Component {
     pub fn engine(&self) -> &Engine {
         &self.inner.engine
     }
+
+    /// Returns the imports of every core wasm instance created when this
+    /// component is instantiated, along with where each import is defined.
+    ///
+    /// The imports of core modules within a component are usually given
+    /// mangled names by the toolchain which produced the component, and the
+    /// modules of a component which lowers functions from another component
+    /// additionally import from adapter modules generated by Wasmtime. This
+    /// method maps each such import back to its origin, such as the WIT
+    /// interface and function that was lowered to provide it, which can help
+    /// when debugging how a component is linked together.
+    ///
+    /// Core instances are numbered in the order that they're created, which
+    /// is the order they're yielded in here.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # use wasmtime::component::{Component, CoreImportOrigin};
+    /// # fn main() -> Result<()> {
+    /// let mut config = Config::new();
+    /// config.wasm_component_model(true);
+    /// let engine = Engine::new(&config)?;
+    /// let component = Component::new(
+    ///     &engine,
+    ///     r#"
+    ///         (component
+    ///             (import "my:pkg/host" (instance $host
+    ///                 (export "log" (func (param "x" u32)))
+    ///             ))
+    ///             (core func $log (canon lower (func $host "log")))
+    ///             (core module $m (import "$mangled" "log-u32" (func (param i32))))
+    ///             (core instance (instantiate $m
+    ///                 (with "$mangled" (instance (export "log-u32" (func $log))))
+    ///             ))
+    ///         )
+    ///     "#,
+    /// )?;
+    ///
+    /// let import = component.core_imports().next().unwrap();
+    /// assert_eq!(import.module(), "$mangled");
+    /// assert_eq!(import.name(), "log-u32");
+    /// match import.origin() {
+    ///     CoreImportOrigin::Lowered { interface, function, .. } => {
+    ///         assert_eq!(*interface, Some("my:pkg/host"));
+    ///         assert_eq!(*function, "log");
+    ///     }
+    ///     other => panic!("unexpected origin {other:?}"),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn core_imports(&self) -> impl Iterator<Item = CoreImport<'_>> + '_ {
+        let info = self.env_component();
+        let lowerings = info
+            .initializers
+            .iter()
+            .filter_map(|init| match init {
+                GlobalInitializer::LowerImport { index, import } => Some((*index, *import)),
+                _ => None,
+            })
+            .collect::<HashMap<LoweredIndex, RuntimeImportIndex>>();
+
+        // The statically known module of each core instance created so far,
+        // used to find the names of exports which are referred to by index.
+        let mut instances = Vec::new();
+        let mut imports = Vec::new();
+        for init in info.initializers.iter() {
+            let GlobalInitializer::InstantiateModule(init) = init else {
+                continue;
+            };
+            let instance = instances.len();
+            match init {
+                InstantiateModule::Static(idx, args) => {
+                    let module = self.static_module(*idx);
+                    for (import, def) in module.imports().zip(args.iter()) {
+                        imports.push(CoreImport {
+                            instance,
+                            module: import.module(),
+                            name: import.name(),
+                            origin: self.core_import_origin(&lowerings, &instances, def),
+                        });
+                    }
+                    instances.push(Some(module));
+                }
+                InstantiateModule::Import(_, args) => {
+                    for (module, args) in args {
+                        for (name, def) in args {
+                            imports.push(CoreImport {
+                                instance,
+                                module,
+                                name,
+                                origin: self.core_import_origin(&lowerings, &instances, def),
+                            });
+                        }
+                    }
+                    instances.push(None);
+                }
+            }
+        }
+        imports.into_iter()
+    }
+
+    fn core_import_origin<'a>(
+        &'a self,
+        lowerings: &HashMap<LoweredIndex, RuntimeImportIndex>,
+        instances: &[Option<&'a Module>],
+        def: &'a CoreDef,
+    ) -> CoreImportOrigin<'a> {
+        let idx = match def {
+            CoreDef::Export(export) => {
+                let name = match &export.item {
+                    ExportItem::Name(name) => Some(name.as_str()),
+                    ExportItem::Index(idx) => instances[export.instance.as_u32() as usize]
+                        .and_then(|module| export_name(module, *idx)),
+                };
+                return CoreImportOrigin::CoreExport {
+                    instance: export.instance.as_u32() as usize,
+                    name,
+                };
+            }
+            CoreDef::InstanceFlags(_) => return CoreImportOrigin::InstanceFlags,
+            CoreDef::Trampoline(idx) => *idx,
+        };
+        match self.inner.info.trampoline_kinds[idx] {
+            TrampolineKind::LowerImport {
+                index, lower_ty, ..
+            } => {
+                let info = self.env_component();
+                let (import, path) = &info.imports[lowerings[&index]];
+                let import = info.import_types[*import].0.as_str();
+                let (interface, function) = match path.last() {
+                    Some(function) => (Some(import), function.as_str()),
+                    None => (None, import),
+                };
+                CoreImportOrigin::Lowered {
+                    interface,
+                    function,
+                    ty: self.with_uninstantiated_instance_type(|instance| {
+                        types::ComponentFunc::from(lower_ty, instance)
+                    }),
+                }
+            }
+            TrampolineKind::ResourceNew => CoreImportOrigin::ResourceNew,
+            TrampolineKind::ResourceRep => CoreImportOrigin::ResourceRep,
+            TrampolineKind::ResourceDrop => CoreImportOrigin::ResourceDrop,
+            TrampolineKind::AlwaysTrap => CoreImportOrigin::AlwaysTrap,
+            TrampolineKind::Transcoder
+            | TrampolineKind::ResourceIntrinsic
+            | TrampolineKind::AsyncIntrinsic
+            | TrampolineKind::TraceIntrinsic => CoreImportOrigin::AdapterIntrinsic,
+        }
+    }
+}
+
+/// Returns the name that `module` exports the item at `idx` with, if any.
+fn export_name(module: &Module, idx: EntityIndex) -> Option<&str> {
+    module
+        .env_module()
+        .exports
+        .iter()
+        .find(|(_, i)| **i == idx)
+        .map(|(name, _)| name.as_str())
+}
+
+/// An import of a core wasm module within a [`Component`], as returned by
+/// [`Component::core_imports`].
+#[derive(Clone, Debug)]
+pub struct CoreImport<'a> {
+    instance: usize,
+    module: &'a str,
+    name: &'a str,
+    origin: CoreImportOrigin<'a>,
+}
+
+impl<'a> CoreImport<'a> {
+    /// Returns the index of the core instance which this import belongs to,
+    /// counting core instances in the order they're created.
+    pub fn instance(&self) -> usize {
+        self.instance
+    }
+
+    /// Returns the module name of this import.
+    pub fn module(&self) -> &'a str {
+        self.module
+    }
+
+    /// Returns the field name of this import.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// Returns where the definition of this import comes from.
+    pub fn origin(&self) -> &CoreImportOrigin<'a> {
+        &self.origin
+    }
+}
+
+/// Where the definition of a [`CoreImport`] comes from.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum CoreImportOrigin<'a> {
+    /// A function imported by the component and lowered into a core wasm
+    /// function with `canon lower`.
+    Lowered {
+        /// The name of the import which `function` is part of, such as
+        /// `wasi:cli/environment@0.2.0`, or `None` if the component imports
+        /// `function` directly.
+        interface: Option<&'a str>,
+        /// The name of the lowered function.
+        function: &'a str,
+        /// The component-level type of the lowered function.
+        ty: types::ComponentFunc,
+    },
+    /// An export of a core instance created earlier, such as an adapter
+    /// module which lowers a function lifted by another component.
+    CoreExport {
+        /// The index of the core instance, see [`CoreImport::instance`].
+        instance: usize,
+        /// The name of the export, if it has one.
+        name: Option<&'a str>,
+    },
+    /// The `resource.new` intrinsic.
+    ResourceNew,
+    /// The `resource.rep` intrinsic.
+    ResourceRep,
+    /// The `resource.drop` intrinsic.
+    ResourceDrop,
+    /// The flags which Wasmtime-generated adapter modules use to track
+    /// whether a component instance may be entered.
+    InstanceFlags,
+    /// A function which always traps, used when a function is lifted and then
+    /// immediately lowered again within one component instance.
+    AlwaysTrap,
+    /// A function used by Wasmtime-generated adapter modules to transcode
+    /// strings or transfer resources between components.
+    AdapterIntrinsic,
 }
 
 /// A value which represents a known export of a component.
@@ -0,0 +1,410 @@
+//! Composition of components when they're instantiated, see [`Compose`].
+
+use crate::component::types::{ComponentFunc, ComponentItem};
+use crate::component::{Component, ComponentExportIndex, Instance, Linker, LinkerInstance, Type};
+use crate::prelude::*;
+use crate::{AsContextMut, Engine, StoreContextMut};
+
+/// A builder which links several [`Component`]s against each other,
+/// producing a [`Composition`].
+///
+/// Each component is added under a name with [`Compose::component`], and
+/// then imports of components are wired to exports of other components with
+/// [`Compose::wire`]. The import and export may have different names, and
+/// either a function or an instance of functions may be wired. Finally
+/// [`Compose::build`] selects the component whose exports are those of the
+/// composition, which can then be instantiated in a single step.
+///
+/// Imports which aren't wired are resolved by the [`Linker`] that the
+/// composition is instantiated with, as usual.
+///
+/// Note that this doesn't produce a new component binary like
+/// `wasm-tools compose` does. A [`Composition`] is a plan for instantiating
+/// each of its components separately, in an order where exports are
+/// available before they're imported, with wired imports defined as host
+/// functions which call the export they're wired to. As a result calls
+/// between the composed components go through the host and their arguments
+/// and results are converted to [`Val`](crate::component::Val)s, resources
+/// can't be passed between composed components, and wired functions must be
+/// called from a synchronous store. Use external tooling to create a single
+/// component, for example to serialize it or to avoid these limitations.
+///
+/// # Examples
+///
+/// ```
+/// use wasmtime::{Engine, Store};
+/// use wasmtime::component::{Component, Compose, Linker};
+///
+/// # fn main() -> wasmtime::Result<()> {
+/// let engine = Engine::default();
+/// let library = Component::new(
+///     &engine,
+///     r#"
+///         (component
+///             (core module $m
+///                 (func (export "double") (param i32) (result i32)
+///                     (i32.add (local.get 0) (local.get 0)))
+///             )
+///             (core instance $i (instantiate $m))
+///             (func (export "double") (param "x" u32) (result u32)
+///                 (canon lift (core func $i "double")))
+///         )
+///     "#,
+/// )?;
+/// let app = Component::new(
+///     &engine,
+///     r#"
+///         (component
+///             (import "times-two" (func $f (param "x" u32) (result u32)))
+///             (core func $f (canon lower (func $f)))
+///             (core module $m
+///                 (import "" "f" (func $f (param i32) (result i32)))
+///                 (func (export "run") (result i32)
+///                     (call $f (i32.const 21)))
+///             )
+///             (core instance $i (instantiate $m
+///                 (with "" (instance (export "f" (func $f))))
+///             ))
+///             (func (export "run") (result u32)
+///                 (canon lift (core func $i "run")))
+///         )
+///     "#,
+/// )?;
+///
+/// let composed = Compose::new(&engine)
+///     .component("library", &library)?
+///     .component("app", &app)?
+///     .wire("app", "times-two", "library", "double")?
+///     .build("app")?;
+///
+/// let mut store = Store::new(&engine, ());
+/// let instance = composed.instantiate(&mut store, &Linker::new(&engine))?;
+/// let run = instance.get_typed_func::<(), (u32,)>(&mut store, "run")?;
+/// assert_eq!(run.call(&mut store, ())?, (42,));
+/// # Ok(())
+/// # }
+/// ```
+pub struct Compose {
+    engine: Engine,
+    components: Vec<(String, Component)>,
+    wires: Vec<Wire>,
+}
+
+/// An import of one component which is provided by an export of another.
+#[derive(Clone)]
+struct Wire {
+    importer: usize,
+    import: String,
+    import_ty: ComponentItem,
+    exporter: usize,
+    export: String,
+}
+
+impl Compose {
+    /// Creates a new, empty, composition of components compiled by `engine`.
+    pub fn new(engine: &Engine) -> Compose {
+        Compose {
+            engine: engine.clone(),
+            components: Vec::new(),
+            wires: Vec::new(),
+        }
+    }
+
+    /// Adds `component` to the composition under the name `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if another component was already added under `name`
+    /// or if `component` was compiled by a different engine.
+    pub fn component(&mut self, name: &str, component: &Component) -> Result<&mut Self> {
+        ensure!(
+            Engine::same(&self.engine, component.engine()),
+            "component `{name}` was compiled by a different engine"
+        );
+        ensure!(
+            self.index(name).is_err(),
+            "component `{name}` added to the composition twice"
+        );
+        self.components.push((name.to_string(), component.clone()));
+        Ok(self)
+    }
+
+    /// Wires the import `import` of the component `importer` to the export
+    /// `export` of the component `exporter`.
+    ///
+    /// The import and export must either both be functions of the same type
+    /// or both be instances, in which case every function the import expects
+    /// must be exported by the exported instance with the same type.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either component hasn't been added, if the import
+    /// or export doesn't exist or was already wired, or if their types are
+    /// incompatible.
+    pub fn wire(
+        &mut self,
+        importer: &str,
+        import: &str,
+        exporter: &str,
+        export: &str,
+    ) -> Result<&mut Self> {
+        let importer = self.index(importer)?;
+        let exporter = self.index(exporter)?;
+        let (importer_name, importer_component) = &self.components[importer];
+        let (exporter_name, exporter_component) = &self.components[exporter];
+        let import_ty = importer_component
+            .component_type()
+            .get_import(&self.engine, import)
+            .ok_or_else(|| anyhow!("component `{importer_name}` has no import named `{import}`"))?;
+        let export_ty = exporter_component
+            .component_type()
+            .get_export(&self.engine, export)
+            .ok_or_else(|| anyhow!("component `{exporter_name}` has no export named `{export}`"))?;
+        ensure!(
+            !self
+                .wires
+                .iter()
+                .any(|w| w.importer == importer && w.import == import),
+            "import `{import}` of component `{importer_name}` wired twice"
+        );
+        check_compatible(&self.engine, &import_ty, &export_ty).with_context(|| {
+            format!(
+                "cannot wire import `{import}` of component `{importer_name}` to \
+                 export `{export}` of component `{exporter_name}`"
+            )
+        })?;
+        self.wires.push(Wire {
+            importer,
+            import: import.to_string(),
+            import_ty,
+            exporter,
+            export: export.to_string(),
+        });
+        Ok(self)
+    }
+
+    /// Finishes the composition, whose exports are those of the component
+    /// `root`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `root` hasn't been added or if the wiring between
+    /// components contains a cycle, in which case there's no order in which
+    /// they can be instantiated.
+    pub fn build(&self, root: &str) -> Result<Composition> {
+        let root = self.index(root)?;
+
+        // Instantiate components in an order where every exporter precedes
+        // the components importing from it.
+        let mut order = Vec::with_capacity(self.components.len());
+        let mut pending = (0..self.components.len()).collect::<Vec<_>>();
+        while !pending.is_empty() {
+            let ready = pending.iter().position(|&i| {
+                self.wires
+                    .iter()
+                    .filter(|w| w.importer == i)
+                    .all(|w| order.contains(&w.exporter))
+            });
+            match ready {
+                Some(pos) => order.push(pending.remove(pos)),
+                None => {
+                    let names = pending
+                        .iter()
+                        .map(|&i| format!("`{}`", self.components[i].0))
+                        .collect::<Vec<_>>();
+                    bail!("components {} are wired in a cycle", names.join(", "));
+                }
+            }
+        }
+
+        Ok(Composition {
+            components: self.components.iter().map(|(_, c)| c.clone()).collect(),
+            wires: self.wires.clone(),
+            order,
+            root,
+        })
+    }
+
+    fn index(&self, name: &str) -> Result<usize> {
+        self.components
+            .iter()
+            .position(|(n, _)| n == name)
+            .ok_or_else(|| anyhow!("no component named `{name}` in the composition"))
+    }
+}
+
+/// Several components linked against each other, created with [`Compose`].
+///
+/// This isn't a [`Component`] itself, see [`Compose`] for how it's
+/// instantiated instead.
+#[derive(Clone)]
+pub struct Composition {
+    components: Vec<Component>,
+    wires: Vec<Wire>,
+    order: Vec<usize>,
+    root: usize,
+}
+
+impl Composition {
+    /// Returns the component whose exports are those of the composition.
+    pub fn root(&self) -> &Component {
+        &self.components[self.root]
+    }
+
+    /// Instantiates all of the composed components within `store`, returning
+    /// the instance of the root component.
+    ///
+    /// Imports which aren't wired to another component are resolved with
+    /// `linker`, as with [`Linker::instantiate`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any component fails to instantiate, for example
+    /// because `linker` doesn't define one of its imports.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` has async support enabled, or if `linker` belongs to
+    /// a different engine than the components.
+    pub fn instantiate<T>(
+        &self,
+        mut store: impl AsContextMut<Data = T>,
+        linker: &Linker<T>,
+    ) -> Result<Instance> {
+        let mut store = store.as_context_mut();
+        let engine = store.engine().clone();
+        let mut instances = vec![None; self.components.len()];
+        for &i in &self.order {
+            let mut linker = linker.clone();
+            linker.allow_shadowing(true);
+            for wire in self.wires.iter().filter(|w| w.importer == i) {
+                let exporter: Instance = instances[wire.exporter].unwrap();
+                let export = exporter.get_export(&mut store, None, &wire.export).unwrap();
+                forward(
+                    &engine,
+                    &mut store,
+                    &mut linker.root(),
+                    &wire.import,
+                    &wire.import_ty,
+                    exporter,
+                    &export,
+                )?;
+            }
+            instances[i] = Some(linker.instantiate(&mut store, &self.components[i])?);
+        }
+        Ok(instances[self.root].unwrap())
+    }
+}
+
+/// Checks that an export of type `export` can provide an import of type
+/// `import`.
+fn check_compatible(engine: &Engine, import: &ComponentItem, export: &ComponentItem) -> Result<()> {
+    match (import, export) {
+        (ComponentItem::ComponentFunc(import), ComponentItem::ComponentFunc(export)) => {
+            let types = |f: &ComponentFunc| {
+                let params = f.params().map(|(_, ty)| ty).collect::<Vec<_>>();
+                (params, f.results().collect::<Vec<_>>())
+            };
+            let (import_params, import_results) = types(import);
+            ensure!(
+                !import_params
+                    .iter()
+                    .chain(&import_results)
+                    .any(mentions_resource),
+                "resources cannot be passed between composed components"
+            );
+            ensure!(
+                (import_params, import_results) == types(export),
+                "function types are incompatible"
+            );
+            Ok(())
+        }
+        (ComponentItem::ComponentInstance(import), ComponentItem::ComponentInstance(export)) => {
+            for (name, ty) in import.exports(engine) {
+                if let ComponentItem::Type(_) = ty {
+                    continue;
+                }
+                let export = export
+                    .get_export(engine, name)
+                    .ok_or_else(|| anyhow!("instance has no export named `{name}`"))?;
+                check_compatible(engine, &ty, &export)
+                    .with_context(|| format!("incompatible instance export `{name}`"))?;
+            }
+            Ok(())
+        }
+        (ComponentItem::Resource(_), _) | (_, ComponentItem::Resource(_)) => {
+            bail!("resources cannot be passed between composed components")
+        }
+        _ => bail!(
+            "expected {}, found {}",
+            item_desc(import),
+            item_desc(export)
+        ),
+    }
+}
+
+fn mentions_resource(ty: &Type) -> bool {
+    match ty {
+        Type::Own(_) | Type::Borrow(_) => true,
+        Type::List(l) => mentions_resource(&l.ty()),
+        Type::Record(r) => r.fields().any(|f| mentions_resource(&f.ty)),
+        Type::Tuple(t) => t.types().any(|t| mentions_resource(&t)),
+        Type::Variant(v) => v
+            .cases()
+            .any(|c| c.ty.as_ref().is_some_and(mentions_resource)),
+        Type::Option(o) => mentions_resource(&o.ty()),
+        Type::Result(r) => {
+            r.ok().as_ref().is_some_and(mentions_resource)
+                || r.err().as_ref().is_some_and(mentions_resource)
+        }
+        _ => false,
+    }
+}
+
+fn item_desc(item: &ComponentItem) -> &'static str {
+    match item {
+        ComponentItem::ComponentFunc(_) => "function",
+        ComponentItem::CoreFunc(_) => "core function",
+        ComponentItem::Module(_) => "module",
+        ComponentItem::Component(_) => "component",
+        ComponentItem::ComponentInstance(_) => "instance",
+        ComponentItem::Type(_) => "type",
+        ComponentItem::Resource(_) => "resource",
+    }
+}
+
+/// Defines `name` in `linker` to forward to the export `export` of
+/// `exporter`, which was checked to be compatible with `ty`.
+fn forward<T>(
+    engine: &Engine,
+    store: &mut StoreContextMut<'_, T>,
+    linker: &mut LinkerInstance<'_, T>,
+    name: &str,
+    ty: &ComponentItem,
+    exporter: Instance,
+    export: &ComponentExportIndex,
+) -> Result<()> {
+    match ty {
+        ComponentItem::ComponentFunc(_) => {
+            let func = exporter.get_func(&mut *store, export).unwrap();
+            linker.func_new(name, move |mut store, params, results| {
+                func.call(&mut store, params, results)?;
+                func.post_return(&mut store)
+            })
+        }
+        ComponentItem::ComponentInstance(instance) => {
+            let mut linker = linker.instance(name)?;
+            for (name, ty) in instance.exports(engine) {
+                if let ComponentItem::Type(_) = ty {
+                    continue;
+                }
+                let export = exporter
+                    .get_export(&mut *store, Some(export), name)
+                    .unwrap();
+                forward(engine, store, &mut linker, name, &ty, exporter, &export)?;
+            }
+            Ok(())
+        }
+        _ => unreachable!(),
+    }
+}
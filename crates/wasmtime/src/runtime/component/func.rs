@@ -8,6 +8,8 @@ use crate::runtime::vm::{Export, ExportFunction};
 use crate::store::{StoreOpaque, Stored};
 use crate::{AsContext, AsContextMut, StoreContextMut, ValRaw};
 use alloc::sync::Arc;
+#[cfg(feature = "async")]
+use core::future::Future;
 use core::mem::{self, MaybeUninit};
 use core::ptr::NonNull;
 use wasmtime_environ::component::{
@@ -15,9 +17,13 @@ use wasmtime_environ::component::{
     TypeFuncIndex, TypeTuple, MAX_FLAT_PARAMS, MAX_FLAT_RESULTS,
 };
 
+#[cfg(feature = "async")]
+mod cancel;
 mod host;
 mod options;
 mod typed;
+#[cfg(feature = "async")]
+pub use self::cancel::*;
 pub use self::host::*;
 pub use self::options::*;
 pub use self::typed::*;
@@ -252,6 +258,22 @@ impl Func {
     /// For more detailed information see the documentation of
     /// [`TypedFunc::call`].
     ///
+    /// # Resources
+    ///
+    /// Resource handles are passed as [`Val::Resource`]. A handle passed for
+    /// an `own` parameter transfers ownership of the resource to the callee
+    /// and can't be used afterwards, while a handle passed for a `borrow`
+    /// parameter, which may itself be an owned handle, is only lent for the
+    /// duration of the call. Constructors and methods of resources exported
+    /// by an instance can be looked up with
+    /// [`Instance::get_resource_constructor`] and
+    /// [`Instance::get_resource_method`], and handles returned to the host
+    /// must be destroyed with [`ResourceAny::resource_drop`].
+    ///
+    /// [`Instance::get_resource_constructor`]: crate::component::Instance::get_resource_constructor
+    /// [`Instance::get_resource_method`]: crate::component::Instance::get_resource_method
+    /// [`ResourceAny::resource_drop`]: crate::component::ResourceAny::resource_drop
+    ///
     /// # Errors
     ///
     /// Returns an error in situations including but not limited to:
@@ -313,6 +335,44 @@ impl Func {
             .await?
     }
 
+    /// Exactly like [`Self::call_async`] except that the call can be
+    /// cancelled with the returned [`CallHandle`].
+    ///
+    /// Cancellation is observed at the store's next epoch deadline, see
+    /// [`CallHandle`] for details. This allows, for example, enforcing a
+    /// timeout on a request handled by a component without discarding the
+    /// whole store when the timeout expires.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is called on a function in a synchronous store. This
+    /// only works with functions defined within an asynchronous store. Also
+    /// panics if `store` does not own this function.
+    #[cfg(feature = "async")]
+    pub fn call_async_cancellable<'a, T>(
+        &'a self,
+        mut store: impl AsContextMut<Data = T> + 'a,
+        params: &'a [Val],
+        results: &'a mut [Val],
+    ) -> (CallHandle, impl Future<Output = Result<()>> + 'a)
+    where
+        T: Send + 'a,
+    {
+        let handle = CallHandle::default();
+        let call = handle.clone();
+        let future = async move {
+            let mut store = store.as_context_mut();
+            assert!(
+                store.0.async_support(),
+                "cannot use `call_async_cancellable` without enabling async support in the config"
+            );
+            store
+                .on_fiber(|store| call.run(store, |store| self.call_impl(store, params, results)))
+                .await?
+        };
+        (handle, future)
+    }
+
     fn call_impl(
         &self,
         mut store: impl AsContextMut,
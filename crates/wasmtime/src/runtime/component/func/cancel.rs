@@ -0,0 +1,76 @@
+use crate::prelude::*;
+use crate::runtime::vm::component::ResourceTables;
+use crate::StoreContextMut;
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// A handle to an in-progress component function call which can be used to
+/// cancel it.
+///
+/// Handles are returned by [`Func::call_async_cancellable`] and
+/// [`TypedFunc::call_async_cancellable`].
+///
+/// Cancelling a call makes it trap with [`Trap::Interrupt`] the next time its
+/// store reaches an epoch deadline, whatever the store's configured deadline
+/// behavior. This means that cancellation requires
+/// [`Config::epoch_interruption`] to be enabled and the engine's epoch to be
+/// incremented, and cancellation is only as prompt as the store's epoch
+/// deadlines. Calls blocked on a host future aren't interrupted until the
+/// future completes.
+///
+/// After the call is cancelled its component instance can no longer be
+/// entered, like after any other trap, but the store remains usable: any host
+/// resources which were lent to the call are released, so that they can be
+/// dropped, and other instances in the store can still be called.
+///
+/// [`Func::call_async_cancellable`]: crate::component::Func::call_async_cancellable
+/// [`TypedFunc::call_async_cancellable`]: crate::component::TypedFunc::call_async_cancellable
+/// [`Trap::Interrupt`]: crate::Trap::Interrupt
+/// [`Config::epoch_interruption`]: crate::Config::epoch_interruption
+#[derive(Clone, Debug, Default)]
+pub struct CallHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CallHandle {
+    /// Requests that the call is cancelled.
+    ///
+    /// This has no effect if the call has already finished.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether [`CallHandle::cancel`] has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Runs `call` on behalf of this handle, such that cancelling it traps at
+    /// the next epoch deadline and the store's resource state is cleaned up
+    /// if the call fails.
+    pub(crate) fn run<T, R>(
+        &self,
+        store: &mut StoreContextMut<'_, T>,
+        call: impl FnOnce(&mut StoreContextMut<'_, T>) -> Result<R>,
+    ) -> Result<R> {
+        let depth = store.0.component_resource_state().0.depth();
+        let prev = store
+            .0
+            .replace_call_cancellation(Some(self.cancelled.clone()));
+        let result = call(store);
+        store.0.replace_call_cancellation(prev);
+
+        // A failed call leaves the calling contexts it entered behind, which
+        // would keep host resources lent to it borrowed forever.
+        if result.is_err() {
+            let (calls, host_table, _) = store.0.component_resource_state();
+            ResourceTables {
+                calls,
+                host_table: Some(host_table),
+                tables: None,
+            }
+            .abandon_calls(depth);
+        }
+        result
+    }
+}
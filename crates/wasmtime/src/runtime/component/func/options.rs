@@ -3,14 +3,26 @@ use crate::component::resources::{HostResourceData, HostResourceIndex, HostResou
 use crate::component::ResourceType;
 use crate::prelude::*;
 use crate::runtime::vm::component::{
-    CallContexts, ComponentInstance, InstanceFlags, ResourceTable, ResourceTables,
+    AsyncHandleTable, CallContexts, ComponentInstance, InstanceFlags, ResourceTable, ResourceTables,
 };
 use crate::runtime::vm::{VMFuncRef, VMMemoryDefinition};
 use crate::store::{StoreId, StoreOpaque};
 use crate::{FuncType, StoreContextMut};
 use alloc::sync::Arc;
 use core::ptr::NonNull;
-use wasmtime_environ::component::{ComponentTypes, StringEncoding, TypeResourceTableIndex};
+use wasmtime_environ::component::{
+    ComponentTypes, RuntimeComponentInstanceIndex, StringEncoding, TypeResourceTableIndex,
+};
+
+/// Logs a canonical ABI operation if tracing was enabled for the store via
+/// `Store::trace_canonical_abi`.
+macro_rules! abi_trace {
+    ($enabled:expr, $($arg:tt)*) => {
+        if $enabled {
+            log::debug!(target: "wasmtime::component::abi", $($arg)*);
+        }
+    };
+}
 
 /// Runtime representation of canonical ABI options in the component model.
 ///
@@ -248,16 +260,25 @@ impl<'a, T> LowerContext<'a, T> {
     ) -> Result<usize> {
         let realloc_func_ty = Arc::clone(unsafe { (*self.instance).realloc_func_ty() });
         let realloc_func_ty = realloc_func_ty.downcast_ref::<FuncType>().unwrap();
-        self.options
-            .realloc(
-                &mut self.store,
-                &realloc_func_ty,
-                old,
-                old_size,
-                old_align,
-                new_size,
-            )
-            .map(|(_, ptr)| ptr)
+        let (_, ptr) = self.options.realloc(
+            &mut self.store,
+            &realloc_func_ty,
+            old,
+            old_size,
+            old_align,
+            new_size,
+        )?;
+        abi_trace!(
+            self.abi_trace(),
+            "realloc({old:#x}, {old_size}, {old_align}, {new_size}) => {ptr:#x}"
+        );
+        Ok(ptr)
+    }
+
+    /// Returns whether canonical ABI operations performed with this context
+    /// should be logged, see `Store::trace_canonical_abi`.
+    pub fn abi_trace(&self) -> bool {
+        self.store.0.component_abi_trace()
     }
 
     /// Returns a fixed mutable slice of memory `N` bytes large starting at
@@ -293,7 +314,13 @@ impl<'a, T> LowerContext<'a, T> {
         ty: TypeResourceTableIndex,
         rep: u32,
     ) -> Result<u32> {
-        self.resource_tables().guest_resource_lower_own(rep, ty)
+        let idx = self.resource_tables().guest_resource_lower_own(rep, ty)?;
+        abi_trace!(
+            self.abi_trace(),
+            "lower own: rep {rep} => handle {idx} in table {}",
+            ty.as_u32()
+        );
+        Ok(idx)
     }
 
     /// Lowers a `borrow` resource into the guest, converting the `rep` to a
@@ -315,9 +342,22 @@ impl<'a, T> LowerContext<'a, T> {
         // Note that the unsafety here should be valid given the contract of
         // `LowerContext::new`.
         if unsafe { (*self.instance).resource_owned_by_own_instance(ty) } {
+            abi_trace!(
+                self.abi_trace(),
+                "lower borrow: rep {rep} passed directly to owner of table {}",
+                ty.as_u32()
+            );
             return Ok(rep);
         }
-        self.resource_tables().guest_resource_lower_borrow(rep, ty)
+        let idx = self
+            .resource_tables()
+            .guest_resource_lower_borrow(rep, ty)?;
+        abi_trace!(
+            self.abi_trace(),
+            "lower borrow: rep {rep} => handle {idx} in table {}",
+            ty.as_u32()
+        );
+        Ok(idx)
     }
 
     /// Lifts a host-owned `own` resource at the `idx` specified into the
@@ -360,6 +400,32 @@ impl<'a, T> LowerContext<'a, T> {
         InstanceType::new(unsafe { &*self.instance })
     }
 
+    /// Returns a description of `ty` for use in error messages, naming it as
+    /// it's exported from the instance being lowered into if possible.
+    pub fn describe_resource_type(&self, ty: ResourceType) -> String {
+        // Note that the unsafety here should be valid given the contract of
+        // `LowerContext::new`.
+        ty.describe(unsafe { &*self.instance })
+    }
+
+    /// Returns where the host-owned resource `idx` came from, if that's being
+    /// tracked, see `Store::track_resource_origins`.
+    pub fn resource_origin(&mut self, idx: HostResourceIndex) -> Option<String> {
+        let (_, _, host_resource_data) = self.store.0.component_resource_state();
+        host_resource_data.origin(idx).map(|s| s.to_string())
+    }
+
+    /// Returns the table of futures and streams owned by `instance`, one of
+    /// the runtime instances of the component being lowered into.
+    pub(crate) fn async_handle_table(
+        &mut self,
+        instance: RuntimeComponentInstanceIndex,
+    ) -> &mut AsyncHandleTable {
+        // Note that the unsafety here should be valid given the contract of
+        // `LowerContext::new`.
+        unsafe { (*self.instance).async_handle_table(instance) }
+    }
+
     fn resource_tables(&mut self) -> HostResourceTables<'_> {
         let (calls, host_table, host_resource_data) = self.store.0.component_resource_state();
         HostResourceTables::from_parts(
@@ -408,6 +474,8 @@ pub struct LiftContext<'a> {
     host_resource_data: &'a mut HostResourceData,
 
     calls: &'a mut CallContexts,
+
+    abi_trace: bool,
 }
 
 #[doc(hidden)]
@@ -433,6 +501,7 @@ impl<'a> LiftContext<'a> {
         // at this time.
         let (calls, host_table, host_resource_data) =
             (&mut *(store as *mut StoreOpaque)).component_resource_state();
+        let abi_trace = store.component_abi_trace();
         let memory = options.memory.map(|_| options.memory(store));
 
         LiftContext {
@@ -443,6 +512,7 @@ impl<'a> LiftContext<'a> {
             calls,
             host_table,
             host_resource_data,
+            abi_trace,
         }
     }
 
@@ -468,6 +538,23 @@ impl<'a> LiftContext<'a> {
         self.instance
     }
 
+    /// Returns whether canonical ABI operations performed with this context
+    /// should be logged, see `Store::trace_canonical_abi`.
+    pub fn abi_trace(&self) -> bool {
+        self.abi_trace
+    }
+
+    /// Returns the table of futures and streams owned by `instance`, one of
+    /// the runtime instances of the component being lifted from.
+    pub(crate) fn async_handle_table(
+        &mut self,
+        instance: RuntimeComponentInstanceIndex,
+    ) -> &mut AsyncHandleTable {
+        // Note that the unsafety here should be valid given the contract of
+        // `LiftContext::new`.
+        unsafe { (*self.instance).async_handle_table(instance) }
+    }
+
     /// Lifts an `own` resource from the guest at the `idx` specified into its
     /// representation.
     ///
@@ -478,11 +565,16 @@ impl<'a> LiftContext<'a> {
         ty: TypeResourceTableIndex,
         idx: u32,
     ) -> Result<(u32, Option<NonNull<VMFuncRef>>, Option<InstanceFlags>)> {
-        let idx = self.resource_tables().guest_resource_lift_own(idx, ty)?;
+        let rep = self.resource_tables().guest_resource_lift_own(idx, ty)?;
+        abi_trace!(
+            self.abi_trace,
+            "lift own: handle {idx} in table {} => rep {rep}",
+            ty.as_u32()
+        );
         // Note that the unsafety here should be valid given the contract of
         // `LiftContext::new`.
         let (dtor, flags) = unsafe { (*self.instance).dtor_and_flags(ty) };
-        Ok((idx, dtor, flags))
+        Ok((rep, dtor, flags))
     }
 
     /// Lifts a `borrow` resource from the guest at the `idx` specified.
@@ -491,7 +583,13 @@ impl<'a> LiftContext<'a> {
         ty: TypeResourceTableIndex,
         idx: u32,
     ) -> Result<u32> {
-        self.resource_tables().guest_resource_lift_borrow(idx, ty)
+        let rep = self.resource_tables().guest_resource_lift_borrow(idx, ty)?;
+        abi_trace!(
+            self.abi_trace,
+            "lift borrow: handle {idx} in table {} => rep {rep}",
+            ty.as_u32()
+        );
+        Ok(rep)
     }
 
     /// Lowers a resource into the host-owned table, returning the index it was
@@ -512,6 +610,28 @@ impl<'a> LiftContext<'a> {
         self.resource_tables().host_resource_lower_borrow(rep)
     }
 
+    /// Records that the host-owned resource `idx`, of type `ty`, was lifted
+    /// from this instance, if the origins of resources are being tracked.
+    ///
+    /// See `Store::track_resource_origins`.
+    pub fn record_resource_origin(
+        &mut self,
+        idx: HostResourceIndex,
+        ty: ResourceType,
+        owned: bool,
+    ) {
+        // Note that the unsafety here should be valid given the contract of
+        // `LiftContext::new`.
+        let instance = unsafe { &*self.instance };
+        self.host_resource_data.record_origin(idx, || {
+            let kind = if owned { "own" } else { "borrow" };
+            format!(
+                "`{kind}` handle to {} lifted from a component instance",
+                ty.describe(instance)
+            )
+        });
+    }
+
     /// Returns the underlying type of the resource table specified by `ty`.
     pub fn resource_type(&self, ty: TypeResourceTableIndex) -> ResourceType {
         self.instance_type().resource_type(ty)
@@ -190,6 +190,47 @@ where
             .await?
     }
 
+    /// Exactly like [`Self::call_async`] except that the call can be
+    /// cancelled with the returned [`CallHandle`].
+    ///
+    /// See [`Func::call_async_cancellable`] for more information.
+    ///
+    /// [`CallHandle`]: crate::component::CallHandle
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is called on a function in a synchronous store. This
+    /// only works with functions defined within an asynchronous store. Also
+    /// panics if `store` does not own this function.
+    #[cfg(feature = "async")]
+    pub fn call_async_cancellable<'a, T>(
+        &'a self,
+        mut store: impl AsContextMut<Data = T> + 'a,
+        params: Params,
+    ) -> (
+        super::CallHandle,
+        impl core::future::Future<Output = Result<Return>> + 'a,
+    )
+    where
+        T: Send + 'a,
+        Params: Send + Sync + 'a,
+        Return: Send + Sync,
+    {
+        let handle = super::CallHandle::default();
+        let call = handle.clone();
+        let future = async move {
+            let mut store = store.as_context_mut();
+            assert!(
+                store.0.async_support(),
+                "cannot use `call_async_cancellable` when async support is not enabled on the config"
+            );
+            store
+                .on_fiber(|store| call.run(store, |store| self.call_impl(store, params)))
+                .await?
+        };
+        (handle, future)
+    }
+
     fn call_impl(&self, mut store: impl AsContextMut, params: Params) -> Result<Return> {
         let store = &mut store.as_context_mut();
         // Note that this is in theory simpler than it might read at this time.
@@ -385,6 +426,8 @@ pub unsafe trait ComponentNamedList: ComponentType {}
 /// | `string`                          | `String`, `&str`, or [`WasmStr`]     |
 /// | `list<T>`                         | `Vec<T>`, `&[T]`, or [`WasmList`]    |
 /// | `own<T>`, `borrow<T>`             | [`Resource<T>`] or [`ResourceAny`]   |
+/// | `future<T>`                       | [`FutureReader<T>`]                  |
+/// | `stream<T>`                       | [`StreamReader<T>`]                  |
 /// | `record`                          | [`#[derive(ComponentType)]`][d-cm]   |
 /// | `variant`                         | [`#[derive(ComponentType)]`][d-cm]   |
 /// | `enum`                            | [`#[derive(ComponentType)]`][d-cm]   |
@@ -392,6 +435,8 @@ pub unsafe trait ComponentNamedList: ComponentType {}
 ///
 /// [`Resource<T>`]: crate::component::Resource
 /// [`ResourceAny`]: crate::component::ResourceAny
+/// [`FutureReader<T>`]: crate::component::FutureReader
+/// [`StreamReader<T>`]: crate::component::StreamReader
 /// [d-cm]: macro@crate::component::ComponentType
 /// [f-m]: crate::component::flags
 ///
@@ -1104,6 +1149,15 @@ unsafe impl Lower for str {
 }
 
 fn lower_string<T>(cx: &mut LowerContext<'_, T>, string: &str) -> Result<(usize, usize)> {
+    if cx.abi_trace() {
+        log::debug!(
+            target: "wasmtime::component::abi",
+            "lower string: {} utf-8 bytes from host as {:?}",
+            string.len(),
+            cx.options.string_encoding(),
+        );
+    }
+
     // Note that in general the wasm module can't assume anything about what the
     // host strings are encoded as. Additionally hosts are allowed to have
     // differently-encoded strings at runtime. Finally when copying a string
@@ -1272,6 +1326,14 @@ impl WasmStr {
             Some(n) if n <= cx.memory().len() => {}
             _ => bail!("string pointer/length out of bounds of memory"),
         }
+        if cx.abi_trace() {
+            log::debug!(
+                target: "wasmtime::component::abi",
+                "lift string: ptr {ptr:#x}, len {len}, {} bytes as {:?}",
+                byte_len.unwrap(),
+                cx.options.string_encoding(),
+            );
+        }
         Ok(WasmStr {
             ptr,
             len,
@@ -2456,6 +2518,8 @@ pub fn desc(ty: &InterfaceType) -> &'static str {
         InterfaceType::Enum(_) => "enum",
         InterfaceType::Own(_) => "owned resource",
         InterfaceType::Borrow(_) => "borrowed resource",
+        InterfaceType::Future(_) => "future",
+        InterfaceType::Stream(_) => "stream",
     }
 }
 
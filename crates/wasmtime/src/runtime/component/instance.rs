@@ -31,6 +31,7 @@ use wasmtime_environ::{EntityIndex, EntityType, Global, PrimaryMap, WasmValType}
 /// [`wasmtime::Instance`](crate::Instance) except that it represents an
 /// instantiated component instead of an instantiated module.
 #[derive(Copy, Clone)]
+#[repr(transparent)] // here for the C API
 pub struct Instance(pub(crate) Stored<Option<Box<InstanceData>>>);
 
 pub(crate) struct InstanceData {
@@ -275,6 +276,143 @@ impl Instance {
         }
     }
 
+    /// Looks up the constructor of the resource `resource` exported from
+    /// `instance`, or from the root of this instance if `instance` is `None`.
+    ///
+    /// This is the function named `[constructor]{resource}` in the
+    /// [canonical ABI](https://github.com/WebAssembly/component-model/blob/main/design/mvp/CanonicalABI.md),
+    /// which is what a constructor in a WIT interface is compiled to. It
+    /// returns an owned handle to a new resource as a [`Val::Resource`]
+    /// which must eventually be destroyed, for example with
+    /// [`ResourceAny::resource_drop`].
+    ///
+    /// This allows calling resource functions with [`Func::call`] without
+    /// knowing the component's world ahead of time, see
+    /// [`Instance::get_resource_method`] for an example.
+    ///
+    /// [`Val::Resource`]: crate::component::Val::Resource
+    /// [`ResourceAny::resource_drop`]: crate::component::ResourceAny::resource_drop
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this instance.
+    pub fn get_resource_constructor(
+        &self,
+        store: impl AsContextMut,
+        instance: Option<&ComponentExportIndex>,
+        resource: &str,
+    ) -> Option<Func> {
+        let name = format!("[constructor]{resource}");
+        self.get_resource_func(store, instance, &name)
+    }
+
+    /// Looks up the method `method` of the resource `resource` exported from
+    /// `instance`, or from the root of this instance if `instance` is `None`.
+    ///
+    /// This is the function named `[method]{resource}.{method}` in the
+    /// canonical ABI. Its first parameter is the resource the method is
+    /// called on, which is passed as a [`Val::Resource`] holding either an
+    /// owned or a borrowed handle. An owned handle is only lent to the method
+    /// and can still be used after the call.
+    ///
+    /// [`Val::Resource`]: crate::component::Val::Resource
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this instance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wasmtime::{Engine, Store};
+    /// use wasmtime::component::{Component, Linker, Val};
+    ///
+    /// # fn main() -> wasmtime::Result<()> {
+    /// let engine = Engine::default();
+    /// let component = Component::new(
+    ///     &engine,
+    ///     r#"
+    ///         (component
+    ///             (type $counter' (resource (rep i32)))
+    ///             (export $counter "counter" (type $counter'))
+    ///
+    ///             (core module $m
+    ///                 (func (export "get") (param i32) (result i32)
+    ///                     local.get 0)
+    ///             )
+    ///             (core instance $i (instantiate $m))
+    ///             (core func $new (canon resource.new $counter))
+    ///             (func (export "[constructor]counter") (param "start" u32) (result (own $counter))
+    ///                 (canon lift (core func $new)))
+    ///             ;; borrows of a component's own resources are lifted as
+    ///             ;; their representation
+    ///             (func (export "[method]counter.get") (param "self" (borrow $counter)) (result u32)
+    ///                 (canon lift (core func $i "get")))
+    ///         )
+    ///     "#,
+    /// )?;
+    ///
+    /// let mut store = Store::new(&engine, ());
+    /// let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    ///
+    /// let new = instance.get_resource_constructor(&mut store, None, "counter").unwrap();
+    /// let mut results = [Val::Bool(false)];
+    /// new.call(&mut store, &[Val::U32(42)], &mut results)?;
+    /// new.post_return(&mut store)?;
+    /// let Val::Resource(counter) = results[0] else { unreachable!() };
+    ///
+    /// let get = instance.get_resource_method(&mut store, None, "counter", "get").unwrap();
+    /// get.call(&mut store, &[Val::Resource(counter)], &mut results)?;
+    /// get.post_return(&mut store)?;
+    /// assert_eq!(results[0], Val::U32(42));
+    ///
+    /// counter.resource_drop(&mut store)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_resource_method(
+        &self,
+        store: impl AsContextMut,
+        instance: Option<&ComponentExportIndex>,
+        resource: &str,
+        method: &str,
+    ) -> Option<Func> {
+        let name = format!("[method]{resource}.{method}");
+        self.get_resource_func(store, instance, &name)
+    }
+
+    /// Looks up the static function `func` of the resource `resource`
+    /// exported from `instance`, or from the root of this instance if
+    /// `instance` is `None`.
+    ///
+    /// This is the function named `[static]{resource}.{func}` in the
+    /// canonical ABI, see [`Instance::get_resource_method`] for more
+    /// information.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this instance.
+    pub fn get_resource_static(
+        &self,
+        store: impl AsContextMut,
+        instance: Option<&ComponentExportIndex>,
+        resource: &str,
+        func: &str,
+    ) -> Option<Func> {
+        let name = format!("[static]{resource}.{func}");
+        self.get_resource_func(store, instance, &name)
+    }
+
+    fn get_resource_func(
+        &self,
+        mut store: impl AsContextMut,
+        instance: Option<&ComponentExportIndex>,
+        name: &str,
+    ) -> Option<Func> {
+        let index = self._get_export(store.as_context_mut().0, instance, name)?;
+        self.get_func(store, &index)
+    }
+
     /// A methods similar to [`Component::export_index`] except for this
     /// instance.
     ///
@@ -588,7 +726,7 @@ impl<'a> Instantiator<'a> {
                     // if required.
 
                     let i = unsafe {
-                        crate::Instance::new_started_impl(store, module, imports.as_ref())?
+                        crate::Instance::new_started_impl(store, module, imports.as_ref(), None)?
                     };
                     self.data.instances.push(i);
                 }
@@ -3,12 +3,14 @@ use crate::component::instance::RuntimeImport;
 use crate::component::matching::{InstanceType, TypeChecker};
 use crate::component::types;
 use crate::component::{
-    Component, ComponentNamedList, Instance, InstancePre, Lift, Lower, ResourceType, Val,
+    Component, ComponentNamedList, ComponentType, Instance, InstancePre, Lift, Lower, ResourceType,
+    Val,
 };
 use crate::hash_map::HashMap;
 use crate::prelude::*;
-use crate::{AsContextMut, Engine, Module, StoreContextMut};
+use crate::{AsContextMut, Engine, ErrorPolicy, Module, StoreContextMut};
 use alloc::sync::Arc;
+use core::fmt;
 use core::future::Future;
 use core::marker;
 use core::pin::Pin;
@@ -62,6 +64,7 @@ pub struct Linker<T> {
     map: NameMap<usize, Definition>,
     path: Vec<usize>,
     allow_shadowing: bool,
+    error_policies: HashMap<Vec<usize>, ErrorPolicy>,
     _marker: marker::PhantomData<fn() -> T>,
 }
 
@@ -73,6 +76,7 @@ impl<T> Clone for Linker<T> {
             map: self.map.clone(),
             path: self.path.clone(),
             allow_shadowing: self.allow_shadowing,
+            error_policies: self.error_policies.clone(),
             _marker: self._marker,
         }
     }
@@ -96,6 +100,7 @@ pub struct LinkerInstance<'a, T> {
     strings: &'a mut Strings,
     map: &'a mut NameMap<usize, Definition>,
     allow_shadowing: bool,
+    error_policies: &'a mut HashMap<Vec<usize>, ErrorPolicy>,
     _marker: marker::PhantomData<fn() -> T>,
 }
 
@@ -117,6 +122,7 @@ impl<T> Linker<T> {
             map: NameMap::default(),
             allow_shadowing: false,
             path: Vec::new(),
+            error_policies: HashMap::new(),
             _marker: marker::PhantomData,
         }
     }
@@ -145,6 +151,7 @@ impl<T> Linker<T> {
             strings: &mut self.strings,
             map: &mut self.map,
             allow_shadowing: self.allow_shadowing,
+            error_policies: &mut self.error_policies,
             _marker: self._marker,
         }
     }
@@ -172,11 +179,39 @@ impl<T> Linker<T> {
         for (_idx, (name, ty)) in env_component.import_types.iter() {
             let import = self.map.get(name, &self.strings);
             cx.definition(ty, import)
-                .with_context(|| format!("component imports {desc} `{name}`, but a matching implementation was not found in the linker", desc = ty.desc()))?;
+                .with_context(|| format!("component imports {desc} `{name}`, but a matching implementation was not found in the linker", desc = ty.desc()))
+                .map_err(|e| crate::ErrorKind::Link.tag(e))?;
         }
         Ok(cx)
     }
 
+    /// Checks whether this linker satisfies all of the imports of
+    /// `component` without instantiating it.
+    ///
+    /// Unlike [`Linker::instantiate_pre`], which stops at the first import
+    /// that isn't satisfied, this walks every import of `component` and
+    /// returns a [`MissingImportsReport`] listing each item which is either
+    /// not defined in this linker or defined with the wrong type. For type
+    /// mismatches the report contains the mismatching WIT-level types. This
+    /// can be used, for example, at deployment time to reject a component
+    /// version that is incompatible with the host before it's ever run.
+    ///
+    /// An empty report means that [`Linker::instantiate_pre`] will succeed.
+    pub fn check(&self, component: &Component) -> Result<MissingImportsReport> {
+        let mut cx = TypeChecker {
+            types: component.types(),
+            strings: &self.strings,
+            imported_resources: Default::default(),
+        };
+        let mut imports = Vec::new();
+        let env_component = component.env_component();
+        for (_idx, (name, ty)) in env_component.import_types.iter() {
+            let import = self.map.get(name, &self.strings);
+            cx.report(name, ty, import, &mut imports);
+        }
+        Ok(MissingImportsReport { imports })
+    }
+
     /// Returns the [`types::Component`] corresponding to `component` with resource
     /// types imported by it replaced using imports present in [`Self`].
     pub fn substituted_component_type(&self, component: &Component) -> Result<types::Component> {
@@ -369,6 +404,112 @@ impl<T> Linker<T> {
     }
 }
 
+/// A list of the imports of a [`Component`] which a [`Linker`] doesn't
+/// satisfy, as returned by [`Linker::check`].
+#[derive(Debug, Default)]
+pub struct MissingImportsReport {
+    imports: Vec<MissingImport>,
+}
+
+impl MissingImportsReport {
+    /// Returns whether all imports were satisfied.
+    pub fn is_empty(&self) -> bool {
+        self.imports.is_empty()
+    }
+
+    /// Returns the number of unsatisfied imports.
+    pub fn len(&self) -> usize {
+        self.imports.len()
+    }
+
+    /// Returns an iterator over all unsatisfied imports, in the order that
+    /// the component imports them.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &MissingImport> + '_ {
+        self.imports.iter()
+    }
+
+    /// Converts this report into an error if any import was unsatisfied.
+    pub fn into_result(self) -> Result<()> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("{self}"))
+        }
+    }
+}
+
+impl fmt::Display for MissingImportsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "all imports are satisfied");
+        }
+        write!(
+            f,
+            "{} import(s) are not satisfied by the linker:",
+            self.len()
+        )?;
+        for import in self.iter() {
+            write!(f, "\n  * {import}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A single import of a [`Component`] which a [`Linker`] doesn't satisfy.
+#[derive(Debug)]
+pub struct MissingImport {
+    name: String,
+    desc: String,
+    missing: bool,
+    error: Error,
+}
+
+impl MissingImport {
+    pub(crate) fn new(name: &str, desc: &str, missing: bool, error: Error) -> MissingImport {
+        MissingImport {
+            name: name.to_string(),
+            desc: desc.to_string(),
+            missing,
+            error,
+        }
+    }
+
+    /// The name of the import; items nested within an imported instance are
+    /// named `instance#item`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// A description of the kind of item imported, such as `function` or
+    /// `resource`.
+    pub fn desc(&self) -> &str {
+        &self.desc
+    }
+
+    /// Returns `true` if the linker doesn't define this import at all, or
+    /// `false` if it's defined but with a mismatched type.
+    pub fn is_missing(&self) -> bool {
+        self.missing
+    }
+
+    /// The error describing why this import isn't satisfied, including the
+    /// expected and actual types in the case of a type mismatch.
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+}
+
+impl fmt::Display for MissingImport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = if self.missing {
+            "is not defined"
+        } else {
+            "has the wrong type"
+        };
+        write!(f, "{} `{}` {what}: {:#}", self.desc, self.name, self.error)
+    }
+}
+
 impl<T> LinkerInstance<'_, T> {
     fn as_mut(&mut self) -> LinkerInstance<'_, T> {
         LinkerInstance {
@@ -378,6 +519,7 @@ impl<T> LinkerInstance<'_, T> {
             strings: self.strings,
             map: self.map,
             allow_shadowing: self.allow_shadowing,
+            error_policies: self.error_policies,
             _marker: self._marker,
         }
     }
@@ -410,6 +552,65 @@ impl<T> LinkerInstance<'_, T> {
         Ok(())
     }
 
+    /// Configures how errors of functions defined with
+    /// [`LinkerInstance::func_wrap_fallible`] in this instance, and in
+    /// instances nested within it, are reported to components.
+    ///
+    /// This only affects functions defined after the policy is configured.
+    /// By default errors trap, as with [`ErrorPolicy::Trap`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for [`ErrorPolicy::ErrorCode`], which is only
+    /// supported by core wasm linkers.
+    pub fn error_policy(&mut self, policy: ErrorPolicy) -> Result<()> {
+        if policy == ErrorPolicy::ErrorCode {
+            bail!("`ErrorPolicy::ErrorCode` is only supported for core wasm");
+        }
+        self.error_policies
+            .insert(self.path[..self.path_len].to_vec(), policy);
+        Ok(())
+    }
+
+    /// Defines a new host-provided function returning a domain-specific error
+    /// into this [`Linker`].
+    ///
+    /// This is like [`Self::func_wrap`] except that `func` returns a
+    /// `Result<R, E>` whose errors are reported according to the
+    /// [`ErrorPolicy`] of this instance, see [`LinkerInstance::error_policy`].
+    /// With [`ErrorPolicy::Trap`] the function returns `R` and errors trap,
+    /// while with [`ErrorPolicy::Result`] it returns `result<R, E>` and errors
+    /// are returned to the component.
+    pub fn func_wrap_fallible<F, Params, R, E>(&mut self, name: &str, func: F) -> Result<()>
+    where
+        F: Fn(StoreContextMut<T>, Params) -> Result<R, E> + Send + Sync + 'static,
+        Params: ComponentNamedList + Lift + 'static,
+        R: ComponentType + Lower + 'static,
+        E: ComponentType + Lower + core::error::Error + Send + Sync + 'static,
+    {
+        match self.current_error_policy() {
+            ErrorPolicy::Result => self.func_wrap(
+                name,
+                move |store: StoreContextMut<'_, T>, params: Params| Ok((func(store, params),)),
+            ),
+            _ => self.func_wrap(
+                name,
+                move |store: StoreContextMut<'_, T>, params: Params| {
+                    func(store, params).map(|ret| (ret,)).map_err(Error::new)
+                },
+            ),
+        }
+    }
+
+    /// Returns the error policy of this instance, inherited from the closest
+    /// enclosing instance which configures one.
+    fn current_error_policy(&self) -> ErrorPolicy {
+        (0..=self.path_len)
+            .rev()
+            .find_map(|len| self.error_policies.get(&self.path[..len]).copied())
+            .unwrap_or_default()
+    }
+
     /// Defines a new host-provided async function into this [`Linker`].
     ///
     /// This is exactly like [`Self::func_wrap`] except it takes an async
@@ -1,5 +1,5 @@
 use crate::component::func::HostFunc;
-use crate::component::linker::{Definition, Strings};
+use crate::component::linker::{Definition, MissingImport, Strings};
 use crate::component::ResourceType;
 use crate::prelude::*;
 use crate::runtime::vm::component::ComponentInstance;
@@ -111,6 +111,70 @@ impl TypeChecker<'_> {
         }
     }
 
+    /// Like [`TypeChecker::definition`] except that instead of stopping at the
+    /// first error every unsatisfied or mismatched item is recorded in
+    /// `report`, named by `name`.
+    pub(crate) fn report(
+        &mut self,
+        name: &str,
+        expected: &TypeDef,
+        actual: Option<&Definition>,
+        report: &mut Vec<MissingImport>,
+    ) {
+        match (expected, actual) {
+            // Recurse into instances to report each of their exports
+            // individually. If the instance isn't defined at all, though, only
+            // report the instance itself rather than all of its contents.
+            (TypeDef::ComponentInstance(t), None | Some(Definition::Instance(_))) => {
+                let types = self.types;
+                let actual = match actual {
+                    Some(Definition::Instance(map)) => Some(map),
+                    _ => None,
+                };
+                let mut nested = Vec::new();
+                for (export, ty) in types[*t].exports.iter() {
+                    if let TypeDef::Interface(_) = ty {
+                        continue;
+                    }
+                    let def = actual.and_then(|map| map.get(export, self.strings));
+                    self.report(&format!("{name}#{export}"), ty, def, &mut nested);
+                }
+                if actual.is_none() && !nested.is_empty() {
+                    report.push(MissingImport::new(
+                        name,
+                        expected.desc(),
+                        true,
+                        anyhow!("instance implementation is missing"),
+                    ));
+                } else {
+                    report.extend(nested);
+                }
+            }
+            _ => {
+                if let Err(error) = self.definition(expected, actual) {
+                    // Keep the resource indices assigned so far in sync with
+                    // the component's resource types so that checking can
+                    // continue past a missing resource.
+                    if let TypeDef::Resource(i) = *expected {
+                        let i = self.types[i].ty;
+                        if self.imported_resources.get(i).is_none() {
+                            let placeholder = ResourceType::uninstantiated(self.types, i);
+                            let resources = Arc::get_mut(&mut self.imported_resources).unwrap();
+                            let id = resources.push(placeholder);
+                            assert_eq!(id, i);
+                        }
+                    }
+                    report.push(MissingImport::new(
+                        name,
+                        expected.desc(),
+                        actual.is_none(),
+                        error,
+                    ));
+                }
+            }
+        }
+    }
+
     fn module(&self, expected: &TypeModule, actual: &Module) -> Result<()> {
         let actual_types = actual.types();
         let actual = actual.env_module();
@@ -101,6 +101,7 @@
 #![allow(rustdoc::redundant_explicit_links)]
 
 mod component;
+mod compose;
 mod func;
 mod instance;
 mod linker;
@@ -109,16 +110,25 @@ mod resource_table;
 mod resources;
 mod storage;
 mod store;
+#[cfg(feature = "component-model-async")]
+mod streams;
 pub mod types;
 mod values;
-pub use self::component::{Component, ComponentExportIndex};
+pub use self::component::{Component, ComponentExportIndex, CoreImport, CoreImportOrigin};
+pub use self::compose::{Compose, Composition};
+#[cfg(feature = "async")]
+pub use self::func::CallHandle;
 pub use self::func::{
     ComponentNamedList, ComponentType, Func, Lift, Lower, TypedFunc, WasmList, WasmStr,
 };
 pub use self::instance::{Instance, InstanceExportLookup, InstancePre};
-pub use self::linker::{Linker, LinkerInstance};
+pub use self::linker::{Linker, LinkerInstance, MissingImport, MissingImportsReport};
 pub use self::resource_table::{ResourceTable, ResourceTableError};
 pub use self::resources::{Resource, ResourceAny};
+#[cfg(feature = "component-model-async")]
+pub use self::streams::{
+    future, stream, FutureAny, FutureReader, FutureWriter, StreamAny, StreamReader, StreamWriter,
+};
 pub use self::types::{ResourceType, Type};
 pub use self::values::Val;
 
@@ -244,6 +254,31 @@ pub(crate) use self::store::ComponentStoreData;
 ///         }
 ///     ",
 ///
+///     // Additional paths searched for external WIT packages referenced by
+///     // `use` or `include` in the WIT at `path` or `inline`. Each entry is
+///     // either a single package (a `*.wit` file or a directory of them) or,
+///     // like a `deps` folder, a directory whose entries are packages.
+///     // Packages are resolved by name regardless of which entry they come
+///     // from, and a missing package is reported by name along with the paths
+///     // that were searched.
+///     //
+///     // This option defaults to an empty list.
+///     deps: ["../shared-wit", "vendor/wit"],
+///
+///     // Enable only the listed `@unstable(feature = ...)` gates. Items gated
+///     // behind other features are removed from the world before bindings are
+///     // generated.
+///     //
+///     // This option defaults to an empty list.
+///     features: ["my-experimental-feature"],
+///
+///     // Whether all `@unstable` features are enabled. When enabled the
+///     // generated `LinkOptions` can be used to toggle features at runtime.
+///     //
+///     // This option defaults to `true` unless `features` is specified, in
+///     // which case it defaults to `false`.
+///     all_features: false,
+///
 ///     // Add calls to `tracing::span!` before each import or export is called
 ///     // to log most arguments and return values. By default values
 ///     // containing lists are excluded; enable `verbose_tracing` to include
@@ -379,6 +414,26 @@ pub(crate) use self::store::ComponentStoreData;
 ///     // the `with` key then this may be required.
 ///     require_store_data_send: false,
 ///
+///     // Whether or not to generate a `MockHost` type in the module of each
+///     // imported interface. `MockHost` implements the interface's `Host`
+///     // trait by recording the name of each function called in its `calls`
+///     // field and producing return values from closures stored in fields
+///     // named after each function, which makes it possible to unit-test
+///     // components without hand-writing a fake for every interface.
+///     //
+///     // The mock also implements the traits of the interface's resources,
+///     // with closures in fields named `{resource}_{function}` and
+///     // `drop_{resource}`, where drops without a closure are only recorded,
+///     // and the `convert_*` functions of `trappable_error_type`s. Functions
+///     // and resources imported directly by the world are mocked the same
+///     // way by a `Mock{World}Imports` type at the root of the bindings.
+///     //
+///     // Interfaces which convert trappable errors defined in other
+///     // interfaces don't get a mock.
+///     //
+///     // This option defaults to false.
+///     mocks: false,
+///
 ///     // If the `wasmtime` crate is depended on at a nonstandard location
 ///     // or is renamed then this is the path to the root of the `wasmtime`
 ///     // crate. Much of the generated code needs to refer to `wasmtime` so
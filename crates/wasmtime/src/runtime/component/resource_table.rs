@@ -11,6 +11,9 @@ pub enum ResourceTableError {
     Full,
     /// Resource not present in table
     NotPresent,
+    /// Resource was deleted from the table, and its slot may have been reused
+    /// by another resource since.
+    Stale,
     /// Resource present in table, but with a different type
     WrongType,
     /// Resource cannot be deleted because child resources exist in the table. Consult wit docs for
@@ -21,8 +24,12 @@ pub enum ResourceTableError {
 impl fmt::Display for ResourceTableError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Full => write!(f, "resource table has no free keys"),
+            Self::Full => write!(
+                f,
+                "resource table is full, all keys for its index bits are in use or retired"
+            ),
             Self::NotPresent => write!(f, "resource not present"),
+            Self::Stale => write!(f, "resource was already deleted"),
             Self::WrongType => write!(f, "resource is of another type"),
             Self::HasChildren => write!(f, "resource has children"),
         }
@@ -33,32 +40,53 @@ impl fmt::Display for ResourceTableError {
 impl std::error::Error for ResourceTableError {}
 
 /// The `ResourceTable` type maps a `Resource<T>` to its `T`.
+///
+/// The slots of deleted resources are reused by later ones. To detect uses of
+/// a `Resource<T>` after it has been deleted, each slot has a generation
+/// which is incremented whenever it is reused, and the `rep` of a resource
+/// encodes both the index of its slot and the slot's generation at the time
+/// it was pushed. Using a deleted resource reliably fails with
+/// [`ResourceTableError::Stale`] rather than accessing whichever resource
+/// reused its slot. A slot whose generation is exhausted is retired instead
+/// of being reused.
+///
+/// How the bits of a `rep` are split between the index and the generation
+/// can be configured with [`ResourceTable::with_index_bits`]. Once every
+/// index is occupied or retired, pushing fails with
+/// [`ResourceTableError::Full`].
 #[derive(Debug)]
 pub struct ResourceTable {
     entries: Vec<Entry>,
     free_head: Option<usize>,
+    /// The number of occupied entries.
+    live: usize,
+    /// The generation of slots which are pushed onto the end of `entries`.
+    ///
+    /// This is raised by [`ResourceTable::compact`] so that handles to
+    /// truncated slots remain stale once those slots are used again.
+    fresh_generation: u32,
+    /// The number of bits of a resource's `rep` which are the index of its
+    /// slot, with the remaining bits holding the generation.
+    index_bits: u32,
 }
 
+/// The default number of index bits of a resource's `rep`.
+const DEFAULT_INDEX_BITS: u32 = 20;
+
 #[derive(Debug)]
 enum Entry {
-    Free { next: Option<usize> },
-    Occupied { entry: TableEntry },
-}
-
-impl Entry {
-    pub fn occupied(&self) -> Option<&TableEntry> {
-        match self {
-            Self::Occupied { entry } => Some(entry),
-            Self::Free { .. } => None,
-        }
-    }
-
-    pub fn occupied_mut(&mut self) -> Option<&mut TableEntry> {
-        match self {
-            Self::Occupied { entry } => Some(entry),
-            Self::Free { .. } => None,
-        }
-    }
+    /// A slot on the free list, which will next be occupied with
+    /// `generation`.
+    Free {
+        next: Option<usize>,
+        generation: u32,
+    },
+    Occupied {
+        entry: TableEntry,
+        generation: u32,
+    },
+    /// A slot whose generations were exhausted and which won't be reused.
+    Retired,
 }
 
 /// This structure tracks parent and child relationships for a given table entry.
@@ -101,10 +129,7 @@ impl TableEntry {
 impl ResourceTable {
     /// Create an empty table
     pub fn new() -> Self {
-        ResourceTable {
-            entries: Vec::new(),
-            free_head: None,
-        }
+        ResourceTable::with_capacity(0)
     }
 
     /// Create an empty table with at least the specified capacity.
@@ -112,6 +137,97 @@ impl ResourceTable {
         ResourceTable {
             entries: Vec::with_capacity(capacity),
             free_head: None,
+            live: 0,
+            fresh_generation: 0,
+            index_bits: DEFAULT_INDEX_BITS,
+        }
+    }
+
+    /// Create an empty table whose `rep`s use their low `index_bits` bits for
+    /// the index of a resource's slot, and the remaining bits for the slot's
+    /// generation.
+    ///
+    /// More index bits allow more resources to be in the table at once, while
+    /// fewer allow each slot to be reused more often before it's retired. By
+    /// default 20 bits are used for the index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index_bits` isn't between 1 and 31.
+    pub fn with_index_bits(index_bits: u32) -> Self {
+        assert!(
+            (1..32).contains(&index_bits),
+            "resource tables need between 1 and 31 index bits"
+        );
+        ResourceTable {
+            index_bits,
+            ..ResourceTable::new()
+        }
+    }
+
+    fn max_index(&self) -> usize {
+        (1 << self.index_bits) - 1
+    }
+
+    fn max_generation(&self) -> u32 {
+        u32::MAX >> self.index_bits
+    }
+
+    fn key(&self, index: usize, generation: u32) -> u32 {
+        debug_assert!(index <= self.max_index());
+        debug_assert!(generation <= self.max_generation());
+        (generation << self.index_bits) | index as u32
+    }
+
+    fn index(&self, key: u32) -> usize {
+        key as usize & self.max_index()
+    }
+
+    fn generation(&self, key: u32) -> u32 {
+        key >> self.index_bits
+    }
+
+    /// Returns the number of resources in this table.
+    pub fn len(&self) -> usize {
+        self.live
+    }
+
+    /// Returns whether this table contains no resources.
+    pub fn is_empty(&self) -> bool {
+        self.live == 0
+    }
+
+    /// Returns the number of resources this table can hold without
+    /// allocating.
+    ///
+    /// This includes the slots of deleted resources, which are reused before
+    /// the table grows. Comparing this with [`ResourceTable::len`] gives an
+    /// idea of how much memory [`ResourceTable::compact`] could reclaim.
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    /// Releases memory held for the slots of deleted resources where
+    /// possible.
+    ///
+    /// Resources are never moved by compaction since their `rep`s refer to
+    /// their slots, so only free slots at the end of the table are released.
+    /// Handles to resources which occupied those slots remain stale.
+    pub fn compact(&mut self) {
+        while let Some(Entry::Free { generation, .. }) = self.entries.last() {
+            self.fresh_generation = self.fresh_generation.max(*generation);
+            self.entries.pop();
+        }
+        self.entries.shrink_to_fit();
+
+        // Rebuild the free list since it may refer to released slots, in the
+        // order of lowest index first.
+        self.free_head = None;
+        for (ix, entry) in self.entries.iter_mut().enumerate().rev() {
+            if let Entry::Free { next, .. } = entry {
+                *next = self.free_head;
+                self.free_head = Some(ix);
+            }
         }
     }
 
@@ -125,33 +241,36 @@ impl ResourceTable {
         Ok(Resource::new_own(idx))
     }
 
-    /// Pop an index off of the free list, if it's not empty.
-    fn pop_free_list(&mut self) -> Option<usize> {
-        if let Some(ix) = self.free_head {
-            // Advance free_head to the next entry if one is available.
-            match &self.entries[ix] {
-                Entry::Free { next } => self.free_head = *next,
-                Entry::Occupied { .. } => unreachable!(),
+    /// Pop an index off of the free list, if it's not empty, along with the
+    /// generation its next occupant has.
+    fn pop_free_list(&mut self) -> Option<(usize, u32)> {
+        let ix = self.free_head?;
+        // Advance free_head to the next entry if one is available.
+        match &self.entries[ix] {
+            Entry::Free { next, generation } => {
+                self.free_head = *next;
+                Some((ix, *generation))
             }
-            Some(ix)
-        } else {
-            None
+            Entry::Occupied { .. } | Entry::Retired => unreachable!(),
         }
     }
 
-    /// Free an entry in the table, returning its [`TableEntry`]. Add the index to the free list.
+    /// Free an entry in the table, returning its [`TableEntry`]. Add the index to the free list,
+    /// unless its generations are exhausted.
     fn free_entry(&mut self, ix: usize) -> TableEntry {
-        let entry = match core::mem::replace(
-            &mut self.entries[ix],
-            Entry::Free {
-                next: self.free_head,
-            },
-        ) {
-            Entry::Occupied { entry } => entry,
-            Entry::Free { .. } => unreachable!(),
+        let (entry, generation) = match core::mem::replace(&mut self.entries[ix], Entry::Retired) {
+            Entry::Occupied { entry, generation } => (entry, generation),
+            Entry::Free { .. } | Entry::Retired => unreachable!(),
         };
 
-        self.free_head = Some(ix);
+        if generation < self.max_generation() {
+            self.entries[ix] = Entry::Free {
+                next: self.free_head,
+                generation: generation + 1,
+            };
+            self.free_head = Some(ix);
+        }
+        self.live -= 1;
 
         entry
     }
@@ -159,32 +278,68 @@ impl ResourceTable {
     /// Push a new entry into the table, returning its handle. This will prefer to use free entries
     /// if they exist, falling back on pushing new entries onto the end of the table.
     fn push_(&mut self, e: TableEntry) -> Result<u32, ResourceTableError> {
-        if let Some(free) = self.pop_free_list() {
-            self.entries[free] = Entry::Occupied { entry: e };
-            Ok(free.try_into().unwrap())
-        } else {
-            let ix = self
-                .entries
-                .len()
-                .try_into()
-                .map_err(|_| ResourceTableError::Full)?;
-            self.entries.push(Entry::Occupied { entry: e });
-            Ok(ix)
-        }
+        let (ix, generation) = match self.pop_free_list() {
+            Some((ix, generation)) => {
+                self.entries[ix] = Entry::Occupied {
+                    entry: e,
+                    generation,
+                };
+                (ix, generation)
+            }
+            None => {
+                let ix = self.entries.len();
+                if ix > self.max_index() {
+                    return Err(ResourceTableError::Full);
+                }
+                let generation = self.fresh_generation;
+                self.entries.push(Entry::Occupied {
+                    entry: e,
+                    generation,
+                });
+                (ix, generation)
+            }
+        };
+        self.live += 1;
+        Ok(self.key(ix, generation))
     }
 
     fn occupied(&self, key: u32) -> Result<&TableEntry, ResourceTableError> {
-        self.entries
-            .get(key as usize)
-            .and_then(Entry::occupied)
-            .ok_or(ResourceTableError::NotPresent)
+        match self.entries.get(self.index(key)) {
+            Some(Entry::Occupied {
+                entry,
+                generation: g,
+            }) if *g == self.generation(key) => Ok(entry),
+            _ => Err(self.missing(key)),
+        }
     }
 
     fn occupied_mut(&mut self, key: u32) -> Result<&mut TableEntry, ResourceTableError> {
-        self.entries
-            .get_mut(key as usize)
-            .and_then(Entry::occupied_mut)
-            .ok_or(ResourceTableError::NotPresent)
+        self.occupied(key)?;
+        let ix = self.index(key);
+        match &mut self.entries[ix] {
+            Entry::Occupied { entry, .. } => Ok(entry),
+            Entry::Free { .. } | Entry::Retired => unreachable!(),
+        }
+    }
+
+    /// Returns the error for `key` not referring to an occupied entry.
+    ///
+    /// Keys whose slot has since been occupied by a later generation, or
+    /// freed by the generation that `key` refers to, are stale.
+    fn missing(&self, key: u32) -> ResourceTableError {
+        let key_generation = self.generation(key);
+        let current = match self.entries.get(self.index(key)) {
+            Some(Entry::Occupied { generation, .. }) => *generation,
+            Some(Entry::Free { generation, .. }) => *generation,
+            Some(Entry::Retired) => return ResourceTableError::Stale,
+            None if key_generation < self.fresh_generation => return ResourceTableError::Stale,
+            None => return ResourceTableError::NotPresent,
+        };
+        if key_generation < current {
+            ResourceTableError::Stale
+        } else {
+            ResourceTableError::NotPresent
+        }
     }
 
     /// Insert a resource at the next available index, and track that it has a
@@ -272,7 +427,7 @@ impl ResourceTable {
         if !self.occupied(key)?.children.is_empty() {
             return Err(ResourceTableError::HasChildren);
         }
-        let e = self.free_entry(key as usize);
+        let e = self.free_entry(self.index(key));
         if let Some(parent) = e.parent {
             // Remove deleted resource from parent's child list.
             // Parent must still be present because it can't be deleted while still having
@@ -329,27 +484,136 @@ pub fn test_free_list() {
     let mut table = ResourceTable::new();
 
     let x = table.push(()).unwrap();
-    assert_eq!(x.rep(), 0);
+    assert_eq!(table.index(x.rep()), 0);
 
     let y = table.push(()).unwrap();
-    assert_eq!(y.rep(), 1);
+    assert_eq!(table.index(y.rep()), 1);
 
-    // Deleting x should put it on the free list, so the next entry should have the same rep.
+    // Deleting x should put it on the free list, so the next entry should have the same index.
     table.delete(x).unwrap();
     let x = table.push(()).unwrap();
-    assert_eq!(x.rep(), 0);
+    assert_eq!(table.index(x.rep()), 0);
 
     // Deleting x and then y should yield indices 1 and then 0 for new entries.
     table.delete(x).unwrap();
     table.delete(y).unwrap();
 
     let y = table.push(()).unwrap();
-    assert_eq!(y.rep(), 1);
+    assert_eq!(table.index(y.rep()), 1);
 
     let x = table.push(()).unwrap();
-    assert_eq!(x.rep(), 0);
+    assert_eq!(table.index(x.rep()), 0);
 
     // As the free list is empty, this entry will have a new id.
     let x = table.push(()).unwrap();
-    assert_eq!(x.rep(), 2);
+    assert_eq!(table.index(x.rep()), 2);
+}
+
+#[test]
+pub fn test_stale_handles() {
+    let mut table = ResourceTable::new();
+
+    let x = table.push(1u32).unwrap();
+    let stale = Resource::<u32>::new_borrow(x.rep());
+    table.delete(x).unwrap();
+    assert!(matches!(table.get(&stale), Err(ResourceTableError::Stale)));
+
+    // Reusing the slot doesn't make the old handle valid again.
+    let y = table.push(2u32).unwrap();
+    assert_eq!(table.index(y.rep()), table.index(stale.rep()));
+    assert_ne!(y.rep(), stale.rep());
+    assert!(matches!(table.get(&stale), Err(ResourceTableError::Stale)));
+    assert_eq!(*table.get(&y).unwrap(), 2);
+
+    // Handles which were never handed out aren't present.
+    let bogus = Resource::<u32>::new_borrow(table.key(0, 5));
+    assert!(matches!(
+        table.get(&bogus),
+        Err(ResourceTableError::NotPresent)
+    ));
+    let bogus = Resource::<u32>::new_borrow(table.key(7, 0));
+    assert!(matches!(
+        table.get(&bogus),
+        Err(ResourceTableError::NotPresent)
+    ));
+}
+
+#[test]
+pub fn test_retired_slots() {
+    let mut table = ResourceTable::new();
+
+    for _ in 0..table.max_generation() {
+        let x = table.push(()).unwrap();
+        assert_eq!(table.index(x.rep()), 0);
+        table.delete(x).unwrap();
+    }
+
+    // The last generation of the slot is used once, after which the slot is
+    // retired and new entries use other slots.
+    let x = table.push(()).unwrap();
+    assert_eq!(table.generation(x.rep()), table.max_generation());
+    let stale = Resource::<()>::new_borrow(x.rep());
+    table.delete(x).unwrap();
+    let y = table.push(()).unwrap();
+    assert_eq!(table.index(y.rep()), 1);
+    assert!(matches!(table.get(&stale), Err(ResourceTableError::Stale)));
+}
+
+#[test]
+pub fn test_len_and_compact() {
+    let mut table = ResourceTable::new();
+    assert!(table.is_empty());
+
+    let handles = (0..100).map(|i| table.push(i).unwrap()).collect::<Vec<_>>();
+    assert_eq!(table.len(), 100);
+    assert!(table.capacity() >= 100);
+
+    let mut handles = handles.into_iter();
+    let first = handles.next().unwrap();
+    let first_rep = first.rep();
+    let stale = handles
+        .map(|h| {
+            let rep = h.rep();
+            table.delete(h).unwrap();
+            rep
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(table.len(), 1);
+
+    // Only the slots after the last live entry can be released.
+    table.compact();
+    assert_eq!(table.len(), 1);
+    assert!(table.capacity() < 100);
+    assert_eq!(
+        *table.get(&Resource::<i32>::new_borrow(first_rep)).unwrap(),
+        0
+    );
+
+    // Handles to released slots remain stale once the slots are used again.
+    let reused = (0..10).map(|i| table.push(i).unwrap()).collect::<Vec<_>>();
+    assert_eq!(table.index(reused[0].rep()), table.index(stale[0]));
+    for rep in &stale {
+        let handle = Resource::<i32>::new_borrow(*rep);
+        assert!(matches!(table.get(&handle), Err(ResourceTableError::Stale)));
+    }
+    table.delete(first).unwrap();
+    assert_eq!(table.len(), 10);
+}
+
+#[test]
+pub fn test_full() {
+    let mut table = ResourceTable::with_index_bits(4);
+    let mut handles = (0..16).map(|i| table.push(i).unwrap()).collect::<Vec<_>>();
+    assert_eq!(table.index(handles[15].rep()), 15);
+
+    let error = table.push(16).unwrap_err();
+    assert!(matches!(error, ResourceTableError::Full));
+    assert!(error.to_string().contains("full"));
+
+    // Deleting a resource makes room again, with the slot's generation in the
+    // remaining bits of the `rep`.
+    table.delete(handles.swap_remove(3)).unwrap();
+    let x = table.push(16).unwrap();
+    assert_eq!(x.rep(), (1 << 4) | 3);
+    assert!(matches!(table.push(17), Err(ResourceTableError::Full)));
 }
@@ -1,20 +1,22 @@
 use crate::component::func::{bad_type_info, desc, LiftContext, LowerContext};
 use crate::component::matching::InstanceType;
 use crate::component::{ComponentType, Lift, Lower};
+use crate::hash_map::HashMap;
 use crate::prelude::*;
 use crate::runtime::vm::component::{ComponentInstance, InstanceFlags, ResourceTables};
 use crate::runtime::vm::{SendSyncPtr, VMFuncRef, ValRaw};
 use crate::store::{StoreId, StoreOpaque};
 use crate::{AsContextMut, StoreContextMut, Trap};
-use core::any::TypeId;
+use alloc::collections::VecDeque;
+use core::any::{type_name, TypeId};
 use core::fmt;
 use core::marker;
 use core::mem::MaybeUninit;
 use core::ptr::NonNull;
 use core::sync::atomic::{AtomicU64, Ordering::Relaxed};
 use wasmtime_environ::component::{
-    CanonicalAbiInfo, ComponentTypes, DefinedResourceIndex, InterfaceType, ResourceIndex,
-    TypeResourceTableIndex,
+    CanonicalAbiInfo, ComponentTypes, DefinedResourceIndex, Export, ExportIndex, InterfaceType,
+    NameMap, ResourceIndex, TypeDef, TypeResourceTableIndex,
 };
 
 /// Representation of a resource type in the component model.
@@ -78,6 +80,75 @@ impl ResourceType {
             },
         }
     }
+
+    /// Returns a human-readable description of this type for use in error
+    /// messages.
+    ///
+    /// The name of the resource is searched for in the exports of `instance`,
+    /// which is the instance the type is being used with. Note that `self` may
+    /// belong to some other instance, or even some other store, so this never
+    /// looks at the instance that `self` was defined in.
+    pub(crate) fn describe(&self, instance: &ComponentInstance) -> String {
+        let kind = match self.kind {
+            ResourceTypeKind::Host(_) => "host",
+            ResourceTypeKind::Guest { .. } => "guest",
+            ResourceTypeKind::Uninstantiated { .. } => "uninstantiated",
+        };
+        let component = instance.component().env_component();
+        match self.export_name(instance, &component.exports, None) {
+            Some(name) => format!("{kind} resource `{name}`"),
+            None => format!("an unexported {kind} resource"),
+        }
+    }
+
+    /// Returns whether `self` and `other` are both guest-defined resources
+    /// which were defined by different component instances.
+    pub(crate) fn defined_by_different_instance(&self, other: &ResourceType) -> bool {
+        match (self.kind, other.kind) {
+            (
+                ResourceTypeKind::Guest {
+                    store: a_store,
+                    instance: a,
+                    ..
+                },
+                ResourceTypeKind::Guest {
+                    store: b_store,
+                    instance: b,
+                    ..
+                },
+            ) => a_store != b_store || a != b,
+            _ => false,
+        }
+    }
+
+    fn export_name(
+        &self,
+        instance: &ComponentInstance,
+        exports: &NameMap<String, ExportIndex>,
+        prefix: Option<&str>,
+    ) -> Option<String> {
+        let items = &instance.component().env_component().export_items;
+        for (name, index) in exports.raw_iter() {
+            let name = match prefix {
+                Some(prefix) => format!("{prefix}#{name}"),
+                None => name.clone(),
+            };
+            match &items[*index] {
+                Export::Type(TypeDef::Resource(id))
+                    if InstanceType::new(instance).resource_type(*id) == *self =>
+                {
+                    return Some(name);
+                }
+                Export::Instance { exports, .. } => {
+                    if let Some(name) = self.export_name(instance, exports, Some(&name)) {
+                        return Some(name);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -332,10 +403,112 @@ pub struct HostResourceTables<'a> {
 /// checked against the generation of the index. Whenever a slot is deallocated
 /// the generation is incremented. Put together this means that any access of a
 /// deallocated slot should deterministically provide an error.
+///
+/// Additionally when enabled with `Store::track_resource_origins` this records
+/// where each `ResourceAny` came from to produce more descriptive errors when
+/// a handle is misused.
 #[derive(Default)]
 pub struct HostResourceData {
     cur_generation: u32,
     table_slot_metadata: Vec<TableSlot>,
+    origins: Option<Box<ResourceOrigins>>,
+}
+
+/// The maximum number of origins of moved or dropped handles remembered by
+/// `ResourceOrigins`, after which the oldest are forgotten.
+const MAX_RETIRED_ORIGINS: usize = 1024;
+
+/// Descriptions of where host-table handles came from, keyed by the raw bits
+/// of their `HostResourceIndex`.
+///
+/// Generations make each `HostResourceIndex` unique within a store, so an
+/// entry continues to describe a handle after it's been removed from the host
+/// table. This is what enables diagnosing use-after-drop of a `ResourceAny`.
+#[derive(Default)]
+struct ResourceOrigins {
+    origins: HashMap<u64, ResourceOrigin>,
+    retired: VecDeque<u64>,
+}
+
+struct ResourceOrigin {
+    /// Where the handle came from, e.g. "`own` handle to guest resource `t`
+    /// lifted from a component instance".
+    origin: String,
+    /// How the handle left the host table, if it has.
+    fate: Option<&'static str>,
+}
+
+impl HostResourceData {
+    /// Enables or disables recording the origin of handles, see
+    /// `Store::track_resource_origins`.
+    pub(crate) fn track_origins(&mut self, enable: bool) {
+        if !enable {
+            self.origins = None;
+        } else if self.origins.is_none() {
+            self.origins = Some(Default::default());
+        }
+    }
+
+    /// Records that the handle `idx` was created as described by `origin`.
+    ///
+    /// This does nothing if origins aren't being tracked.
+    pub(crate) fn record_origin(
+        &mut self,
+        idx: HostResourceIndex,
+        origin: impl FnOnce() -> String,
+    ) {
+        if let Some(origins) = &mut self.origins {
+            origins.origins.insert(
+                idx.0,
+                ResourceOrigin {
+                    origin: origin(),
+                    fate: None,
+                },
+            );
+        }
+    }
+
+    /// Records that the handle `idx` has left the host table, either by being
+    /// moved out of it or dropped, as described by `fate`.
+    fn retire_origin(&mut self, idx: HostResourceIndex, fate: &'static str) {
+        let Some(origins) = &mut self.origins else {
+            return;
+        };
+        let Some(entry) = origins.origins.get_mut(&idx.0) else {
+            return;
+        };
+        if entry.fate.is_some() {
+            return;
+        }
+        entry.fate = Some(fate);
+        origins.retired.push_back(idx.0);
+        if origins.retired.len() > MAX_RETIRED_ORIGINS {
+            let oldest = origins.retired.pop_front().unwrap();
+            origins.origins.remove(&oldest);
+        }
+    }
+
+    /// Returns the recorded description of where `idx` came from, if any.
+    pub(crate) fn origin(&self, idx: HostResourceIndex) -> Option<&str> {
+        let origin = self.origins.as_ref()?.origins.get(&idx.0)?;
+        Some(&origin.origin)
+    }
+
+    /// Adds context to `err`, an error from using the handle `idx`, if `idx`
+    /// was previously moved or dropped.
+    fn annotate_error(&self, idx: HostResourceIndex, err: Error) -> Error {
+        let origin = self
+            .origins
+            .as_ref()
+            .and_then(|origins| origins.origins.get(&idx.0));
+        match origin {
+            Some(ResourceOrigin {
+                origin,
+                fate: Some(fate),
+            }) => err.context(format!("use of {origin} after it was {fate}")),
+            _ => err,
+        }
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -399,14 +572,21 @@ impl<'a> HostResourceTables<'a> {
     /// Returns an error if `idx` doesn't point to a valid owned resource, or
     /// if `idx` can't be lifted as an `own` (e.g. it has active borrows).
     pub fn host_resource_lift_own(&mut self, idx: HostResourceIndex) -> Result<u32> {
-        let (idx, _) = self.validate_host_index(idx, true)?;
-        self.tables.resource_lift_own(None, idx)
+        let (index, _) = self.validate_host_index(idx, true)?;
+        let rep = self
+            .tables
+            .resource_lift_own(None, index)
+            .map_err(|e| self.host_resource_data.annotate_error(idx, e))?;
+        self.host_resource_data.retire_origin(idx, "moved");
+        Ok(rep)
     }
 
     /// See [`HostResourceTables::host_resource_lift_own`].
     pub fn host_resource_lift_borrow(&mut self, idx: HostResourceIndex) -> Result<u32> {
-        let (idx, _) = self.validate_host_index(idx, false)?;
-        self.tables.resource_lift_borrow(None, idx)
+        let (index, _) = self.validate_host_index(idx, false)?;
+        self.tables
+            .resource_lift_borrow(None, index)
+            .map_err(|e| self.host_resource_data.annotate_error(idx, e))
     }
 
     /// Lowers an `own` resource to be owned by the host.
@@ -454,7 +634,8 @@ impl<'a> HostResourceTables<'a> {
         // precise error, such as a lift operation.
         if let Some(actual) = actual {
             if actual.generation != idx.generation() {
-                bail!("host-owned resource is being used with the wrong type");
+                let err = anyhow!("host-owned resource is being used with the wrong type");
+                return Err(self.host_resource_data.annotate_error(idx, err));
             }
         }
 
@@ -519,8 +700,13 @@ impl<'a> HostResourceTables<'a> {
     /// an `own` with active borrows, or if it doesn't have the type `expected`
     /// in the host tables.
     fn host_resource_drop(&mut self, idx: HostResourceIndex) -> Result<Option<(u32, TableSlot)>> {
-        let (idx, slot) = self.validate_host_index(idx, true)?;
-        match self.tables.resource_drop(None, idx)? {
+        let (index, slot) = self.validate_host_index(idx, true)?;
+        let rep = self
+            .tables
+            .resource_drop(None, index)
+            .map_err(|e| self.host_resource_data.annotate_error(idx, e))?;
+        self.host_resource_data.retire_origin(idx, "dropped");
+        match rep {
             Some(rep) => Ok(Some((rep, slot.unwrap()))),
             None => Ok(None),
         }
@@ -929,6 +1115,13 @@ impl ResourceAny {
             ResourceState::Taken => bail!("host resource already consumed"),
             ResourceState::Index(idx) => (idx, true),
         };
+        tables.host_resource_data.record_origin(idx, || {
+            let kind = if owned { "own" } else { "borrow" };
+            format!(
+                "`{kind}` handle to a host resource converted from a `Resource<{}>`",
+                type_name::<T>()
+            )
+        });
         Ok(Self {
             idx,
             ty: ResourceType::host::<T>(),
@@ -1040,16 +1233,12 @@ impl ResourceAny {
     fn lower_to_index<U>(&self, cx: &mut LowerContext<'_, U>, ty: InterfaceType) -> Result<u32> {
         match ty {
             InterfaceType::Own(t) => {
-                if cx.resource_type(t) != self.ty {
-                    bail!("mismatched resource types");
-                }
+                self.typecheck_lower(cx, t)?;
                 let rep = cx.host_resource_lift_own(self.idx)?;
                 cx.guest_resource_lower_own(t, rep)
             }
             InterfaceType::Borrow(t) => {
-                if cx.resource_type(t) != self.ty {
-                    bail!("mismatched resource types");
-                }
+                self.typecheck_lower(cx, t)?;
                 let rep = cx.host_resource_lift_borrow(self.idx)?;
                 cx.guest_resource_lower_borrow(t, rep)
             }
@@ -1057,12 +1246,37 @@ impl ResourceAny {
         }
     }
 
+    /// Checks that this resource can be lowered into the table `t`.
+    fn typecheck_lower<U>(
+        &self,
+        cx: &mut LowerContext<'_, U>,
+        t: TypeResourceTableIndex,
+    ) -> Result<()> {
+        let expected = cx.resource_type(t);
+        if expected == self.ty {
+            return Ok(());
+        }
+        let Some(origin) = cx.resource_origin(self.idx) else {
+            bail!("mismatched resource types");
+        };
+        let suffix = if self.ty.defined_by_different_instance(&expected) {
+            ", which belongs to a different component instance"
+        } else {
+            ""
+        };
+        bail!(
+            "mismatched resource types: expected {}, but found {origin}{suffix}",
+            cx.describe_resource_type(expected),
+        )
+    }
+
     fn lift_from_index(cx: &mut LiftContext<'_>, ty: InterfaceType, index: u32) -> Result<Self> {
         match ty {
             InterfaceType::Own(t) => {
                 let ty = cx.resource_type(t);
                 let (rep, dtor, flags) = cx.guest_resource_lift_own(t, index)?;
                 let idx = cx.host_resource_lower_own(rep, dtor, flags)?;
+                cx.record_resource_origin(idx, ty, true);
                 Ok(ResourceAny {
                     idx,
                     ty,
@@ -1073,6 +1287,7 @@ impl ResourceAny {
                 let ty = cx.resource_type(t);
                 let rep = cx.guest_resource_lift_borrow(t, index)?;
                 let idx = cx.host_resource_lower_borrow(rep)?;
+                cx.record_resource_origin(idx, ty, false);
                 Ok(ResourceAny {
                     idx,
                     ty,
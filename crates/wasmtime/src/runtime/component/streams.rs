@@ -0,0 +1,657 @@
+//! Preview support for the `stream<T>` and `future<T>` types of the async
+//! component model.
+//!
+//! The readable end of a stream or future is what's passed around as a
+//! `stream<T>` or `future<T>` value, and the writable end always stays with
+//! the host. Like an owned resource, passing a readable end to a component
+//! moves it into a table owned by the receiving component instance, and the
+//! handle the component sees is an index into that table. Once it's been
+//! passed to a component the host can't read from it again until it's passed
+//! back out.
+//!
+//! Both ends share their state directly rather than through the store, so the
+//! host can read and write without access to the store. This is what allows
+//! `wasmtime-wasi` to use them as `wasi:io` streams.
+//!
+//! The canonical built-ins which components would use to create, read, and
+//! write streams and futures themselves are not supported yet, so at this time
+//! all reading and writing happens on the host.
+
+use crate::component::func::{bad_type_info, desc, LiftContext, LowerContext};
+use crate::component::matching::InstanceType;
+use crate::component::{ComponentType, Lift, Lower};
+use crate::prelude::*;
+use crate::runtime::vm::component::{AsyncHandle, AsyncHandleKind};
+use crate::runtime::vm::ValRaw;
+use crate::sync::RwLock;
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use core::any::Any;
+use core::fmt;
+use core::future::poll_fn;
+use core::marker;
+use core::mem::MaybeUninit;
+use core::ops::DerefMut;
+use core::task::{Context, Poll, Waker};
+use wasmtime_environ::component::{
+    CanonicalAbiInfo, ComponentTypes, InterfaceType, RuntimeComponentInstanceIndex,
+};
+
+/// Creates a new stream whose elements are of type `T`.
+///
+/// The returned [`StreamWriter`] is used by the host to produce elements and
+/// the [`StreamReader`] may be passed to components as a `stream<T>` value.
+pub fn stream<T>() -> (StreamWriter<T>, StreamReader<T>)
+where
+    T: Send + Sync + 'static,
+{
+    let channel = Channel::new(Box::new(VecDeque::<T>::new()));
+    (
+        StreamWriter {
+            channel: channel.clone(),
+            _marker: marker::PhantomData,
+        },
+        StreamReader {
+            channel,
+            _marker: marker::PhantomData,
+        },
+    )
+}
+
+/// Creates a new future whose value is of type `T`.
+///
+/// The returned [`FutureWriter`] is used by the host to produce the value and
+/// the [`FutureReader`] may be passed to components as a `future<T>` value.
+/// Use `T = ()` for a `future` without a payload.
+pub fn future<T>() -> (FutureWriter<T>, FutureReader<T>)
+where
+    T: Send + Sync + 'static,
+{
+    let channel = Channel::new(Box::new(None::<T>));
+    (
+        FutureWriter {
+            channel: channel.clone(),
+            _marker: marker::PhantomData,
+        },
+        FutureReader {
+            channel,
+            _marker: marker::PhantomData,
+        },
+    )
+}
+
+/// The writable end of a stream created with [`stream`].
+///
+/// Dropping this closes the stream, signaling to the reader that no more
+/// elements will be written.
+pub struct StreamWriter<T> {
+    channel: Arc<Channel>,
+    _marker: marker::PhantomData<fn() -> T>,
+}
+
+impl<T: Send + Sync + 'static> StreamWriter<T> {
+    /// Appends `items` to the stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the readable end of this stream has been closed.
+    pub fn write(&self, items: impl IntoIterator<Item = T>) -> Result<()> {
+        let mut state = self.channel.lock();
+        ensure!(state.readable, "the readable end of this stream was closed");
+        state.payload::<VecDeque<T>>()?.extend(items);
+        state.wake();
+        Ok(())
+    }
+
+    /// Returns whether the readable end of this stream has been closed, after
+    /// which writes will fail.
+    pub fn is_closed(&self) -> bool {
+        !self.channel.lock().readable
+    }
+
+    /// Closes this end of the stream, signaling to the reader that no more
+    /// elements will be written.
+    pub fn close(self) {
+        drop(self);
+    }
+}
+
+impl<T> Drop for StreamWriter<T> {
+    fn drop(&mut self) {
+        self.channel.close_writable();
+    }
+}
+
+impl<T> fmt::Debug for StreamWriter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamWriter").finish_non_exhaustive()
+    }
+}
+
+/// The readable end of a stream, represented in components as a `stream<T>`.
+pub struct StreamReader<T> {
+    channel: Arc<Channel>,
+    _marker: marker::PhantomData<fn() -> T>,
+}
+
+impl<T: Send + Sync + 'static> StreamReader<T> {
+    /// Takes up to `max` elements which have been written to the stream so
+    /// far.
+    ///
+    /// Returns an empty list if no elements are available yet, see
+    /// [`StreamReader::ready`] to wait for some. Returns `None` once the
+    /// writable end has been closed and all elements have been read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this stream has been passed to a component.
+    pub fn read(&self, max: usize) -> Result<Option<Vec<T>>> {
+        let mut state = self.channel.lock();
+        state.check_host_reader(AsyncHandleKind::Stream)?;
+        let writable = state.writable;
+        let buffer = state.payload::<VecDeque<T>>()?;
+        if buffer.is_empty() && !writable {
+            return Ok(None);
+        }
+        let n = max.min(buffer.len());
+        Ok(Some(buffer.drain(..n).collect()))
+    }
+
+    /// Polls whether a [`StreamReader::read`] would return elements, the end
+    /// of the stream, or an error.
+    pub fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.channel.lock();
+        let has_elements = state
+            .payload::<VecDeque<T>>()
+            .map_or(true, |buffer| !buffer.is_empty());
+        state.poll_ready(cx, has_elements)
+    }
+
+    /// Waits until a [`StreamReader::read`] would return elements, the end of
+    /// the stream, or an error.
+    pub async fn ready(&self) {
+        poll_fn(|cx| self.poll_ready(cx)).await
+    }
+
+    /// Closes this end of the stream, discarding any unread elements.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this stream has been passed to a component.
+    pub fn close(self) -> Result<()> {
+        self.channel
+            .close_readable::<VecDeque<T>>(AsyncHandleKind::Stream)
+    }
+
+    /// Converts this stream into its untyped representation.
+    pub fn into_stream_any(self) -> StreamAny {
+        StreamAny {
+            channel: self.channel,
+        }
+    }
+
+    /// Attempts to convert a [`StreamAny`] into a typed [`StreamReader`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the elements of `stream` aren't of type `T`.
+    pub fn try_from_stream_any(stream: StreamAny) -> Result<Self> {
+        stream.channel.lock().payload::<VecDeque<T>>()?;
+        Ok(StreamReader {
+            channel: stream.channel,
+            _marker: marker::PhantomData,
+        })
+    }
+}
+
+impl<T> fmt::Debug for StreamReader<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamReader").finish_non_exhaustive()
+    }
+}
+
+/// The writable end of a future created with [`future`].
+///
+/// Dropping this without calling [`FutureWriter::write`] closes the future
+/// without resolving it.
+pub struct FutureWriter<T> {
+    channel: Arc<Channel>,
+    _marker: marker::PhantomData<fn() -> T>,
+}
+
+impl<T: Send + Sync + 'static> FutureWriter<T> {
+    /// Resolves the future with `value`, closing this end.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the readable end of this future has been closed.
+    pub fn write(self, value: T) -> Result<()> {
+        let mut state = self.channel.lock();
+        ensure!(state.readable, "the readable end of this future was closed");
+        *state.payload::<Option<T>>()? = Some(value);
+        Ok(())
+    }
+
+    /// Closes this end of the future without resolving it.
+    pub fn close(self) {
+        drop(self);
+    }
+}
+
+impl<T> Drop for FutureWriter<T> {
+    fn drop(&mut self) {
+        self.channel.close_writable();
+    }
+}
+
+impl<T> fmt::Debug for FutureWriter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FutureWriter").finish_non_exhaustive()
+    }
+}
+
+/// The readable end of a future, represented in components as a `future<T>`.
+pub struct FutureReader<T> {
+    channel: Arc<Channel>,
+    _marker: marker::PhantomData<fn() -> T>,
+}
+
+impl<T: Send + Sync + 'static> FutureReader<T> {
+    /// Takes the value of the future if it has been resolved.
+    ///
+    /// Returns `Ok(None)` if the value hasn't been written yet, see
+    /// [`FutureReader::ready`] to wait for it, or if it was already taken by a
+    /// previous read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the writable end was closed without resolving the
+    /// future, or if this future has been passed to a component.
+    pub fn read(&self) -> Result<Option<T>> {
+        let mut state = self.channel.lock();
+        state.check_host_reader(AsyncHandleKind::Future)?;
+        let writable = state.writable;
+        let read = state.read;
+        let value = state.payload::<Option<T>>()?.take();
+        if value.is_none() && !writable && !read {
+            bail!("the writable end of this future was closed without a value");
+        }
+        state.read |= value.is_some();
+        Ok(value)
+    }
+
+    /// Polls whether a [`FutureReader::read`] would return the value or an
+    /// error.
+    pub fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.channel.lock();
+        let has_value = state
+            .payload::<Option<T>>()
+            .map_or(true, |value| value.is_some());
+        state.poll_ready(cx, has_value)
+    }
+
+    /// Waits until a [`FutureReader::read`] would return the value or an
+    /// error.
+    pub async fn ready(&self) {
+        poll_fn(|cx| self.poll_ready(cx)).await
+    }
+
+    /// Closes this end of the future, discarding its value if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this future has been passed to a component.
+    pub fn close(self) -> Result<()> {
+        self.channel
+            .close_readable::<Option<T>>(AsyncHandleKind::Future)
+    }
+
+    /// Converts this future into its untyped representation.
+    pub fn into_future_any(self) -> FutureAny {
+        FutureAny {
+            channel: self.channel,
+        }
+    }
+
+    /// Attempts to convert a [`FutureAny`] into a typed [`FutureReader`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value of `future` isn't of type `T`.
+    pub fn try_from_future_any(future: FutureAny) -> Result<Self> {
+        future.channel.lock().payload::<Option<T>>()?;
+        Ok(FutureReader {
+            channel: future.channel,
+            _marker: marker::PhantomData,
+        })
+    }
+}
+
+impl<T> fmt::Debug for FutureReader<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FutureReader").finish_non_exhaustive()
+    }
+}
+
+/// The readable end of a stream of any element type.
+///
+/// This is how `stream<T>` values are represented in [`Val`], and it can be
+/// converted to a typed [`StreamReader`] with
+/// [`StreamReader::try_from_stream_any`]. Note that the element type of a
+/// `StreamAny` isn't checked when it's passed to a component, only when it's
+/// converted back to a [`StreamReader`].
+///
+/// Clones of a `StreamAny` refer to the same stream, so only one of them can
+/// be passed to a component.
+///
+/// [`Val`]: crate::component::Val
+#[derive(Clone)]
+pub struct StreamAny {
+    channel: Arc<Channel>,
+}
+
+/// The readable end of a future of any value type.
+///
+/// This is how `future<T>` values are represented in [`Val`], and it can be
+/// converted to a typed [`FutureReader`] with
+/// [`FutureReader::try_from_future_any`]. Note that the value type of a
+/// `FutureAny` isn't checked when it's passed to a component, only when it's
+/// converted back to a [`FutureReader`].
+///
+/// Clones of a `FutureAny` refer to the same future, so only one of them can
+/// be passed to a component.
+///
+/// [`Val`]: crate::component::Val
+#[derive(Clone)]
+pub struct FutureAny {
+    channel: Arc<Channel>,
+}
+
+macro_rules! any_common_impls {
+    ($($ty:ident)*) => ($(
+        impl fmt::Debug for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($ty)).finish_non_exhaustive()
+            }
+        }
+
+        impl PartialEq for $ty {
+            fn eq(&self, other: &Self) -> bool {
+                Arc::ptr_eq(&self.channel, &other.channel)
+            }
+        }
+
+        impl Eq for $ty {}
+    )*)
+}
+
+any_common_impls! {
+    StreamAny
+    FutureAny
+}
+
+/// State shared between the two ends of a stream or future.
+///
+/// This is what's stored in the `AsyncHandleTable` of a component instance
+/// while the readable end is owned by that instance.
+struct Channel {
+    state: RwLock<ChannelState>,
+}
+
+struct ChannelState {
+    /// A `VecDeque<T>` of buffered elements for streams and an `Option<T>` for
+    /// futures.
+    payload: Box<dyn Any + Send + Sync>,
+    /// Whether the readable end is owned by the host, as opposed to having
+    /// been passed to a component instance.
+    host_owned: bool,
+    readable: bool,
+    writable: bool,
+    /// Whether a future's value has already been read.
+    read: bool,
+    /// The task waiting for the readable end to become ready, if any.
+    waker: Option<Waker>,
+}
+
+impl Channel {
+    fn new(payload: Box<dyn Any + Send + Sync>) -> Arc<Channel> {
+        Arc::new(Channel {
+            state: RwLock::new(ChannelState {
+                payload,
+                host_owned: true,
+                readable: true,
+                writable: true,
+                read: false,
+                waker: None,
+            }),
+        })
+    }
+
+    fn lock(&self) -> impl DerefMut<Target = ChannelState> + '_ {
+        self.state.write()
+    }
+
+    fn close_writable(&self) {
+        let mut state = self.lock();
+        state.writable = false;
+        state.wake();
+    }
+
+    /// Closes the readable end, discarding the `S` payload.
+    fn close_readable<S: Default + 'static>(&self, kind: AsyncHandleKind) -> Result<()> {
+        let mut state = self.lock();
+        state.check_host_reader(kind)?;
+        state.readable = false;
+        *state.payload::<S>()? = S::default();
+        Ok(())
+    }
+}
+
+impl ChannelState {
+    fn payload<S: 'static>(&mut self) -> Result<&mut S> {
+        match self.payload.downcast_mut() {
+            Some(payload) => Ok(payload),
+            None => bail!("stream or future has a different payload type"),
+        }
+    }
+
+    /// Checks that the readable end is still open and owned by the host.
+    fn check_host_reader(&self, kind: AsyncHandleKind) -> Result<()> {
+        ensure!(self.readable, "the {} was closed", kind.desc());
+        ensure!(
+            self.host_owned,
+            "the {} was passed to a component and is no longer owned by the host",
+            kind.desc()
+        );
+        Ok(())
+    }
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>, has_payload: bool) -> Poll<()> {
+        if has_payload || !self.writable || !self.readable || !self.host_owned {
+            return Poll::Ready(());
+        }
+        self.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Returns the kind of handle that `ty` refers to along with the runtime
+/// component instance whose table holds handles of that type.
+fn handle_table(
+    types: &ComponentTypes,
+    ty: InterfaceType,
+) -> (AsyncHandleKind, RuntimeComponentInstanceIndex) {
+    match ty {
+        InterfaceType::Future(t) => (AsyncHandleKind::Future, types[t].instance),
+        InterfaceType::Stream(t) => (AsyncHandleKind::Stream, types[t].instance),
+        _ => bad_type_info(),
+    }
+}
+
+/// Moves the readable end `channel` from the host into the table of the
+/// component instance that's being lowered into, returning its handle there.
+fn lower_handle<U>(
+    cx: &mut LowerContext<'_, U>,
+    ty: InterfaceType,
+    channel: &Arc<Channel>,
+) -> Result<u32> {
+    let (kind, instance) = handle_table(cx.types, ty);
+    let mut state = channel.lock();
+    state.check_host_reader(kind)?;
+    let handle = cx.async_handle_table(instance).insert(AsyncHandle {
+        kind,
+        state: channel.clone(),
+    })?;
+    state.host_owned = false;
+    Ok(handle)
+}
+
+/// Moves the readable end at `handle` out of the table of the component
+/// instance that's being lifted from and gives it to the host.
+fn lift_handle(cx: &mut LiftContext<'_>, ty: InterfaceType, handle: u32) -> Result<Arc<Channel>> {
+    let (kind, instance) = handle_table(cx.types, ty);
+    let handle = cx.async_handle_table(instance).remove(handle, kind)?;
+    let channel = match handle.state.downcast::<Channel>() {
+        Ok(channel) => channel,
+        Err(_) => bad_type_info(),
+    };
+    channel.lock().host_owned = true;
+    Ok(channel)
+}
+
+unsafe impl<T: ComponentType> ComponentType for StreamReader<T> {
+    const ABI: CanonicalAbiInfo = CanonicalAbiInfo::SCALAR4;
+
+    type Lower = <u32 as ComponentType>::Lower;
+
+    fn typecheck(ty: &InterfaceType, types: &InstanceType<'_>) -> Result<()> {
+        match ty {
+            InterfaceType::Stream(t) => {
+                let ty = types.types[*t].ty;
+                T::typecheck(&types.types[ty].payload, types)
+            }
+            other => bail!("expected `stream`, found `{}`", desc(other)),
+        }
+    }
+}
+
+unsafe impl<T: ComponentType> ComponentType for FutureReader<T> {
+    const ABI: CanonicalAbiInfo = CanonicalAbiInfo::SCALAR4;
+
+    type Lower = <u32 as ComponentType>::Lower;
+
+    fn typecheck(ty: &InterfaceType, types: &InstanceType<'_>) -> Result<()> {
+        match ty {
+            InterfaceType::Future(t) => {
+                let ty = types.types[*t].ty;
+                match &types.types[ty].payload {
+                    Some(ty) => T::typecheck(ty, types),
+                    None if T::IS_RUST_UNIT_TYPE => Ok(()),
+                    None => bail!("expected no payload type"),
+                }
+            }
+            other => bail!("expected `future`, found `{}`", desc(other)),
+        }
+    }
+}
+
+macro_rules! handle_lift_lower {
+    ($(($ty:ty, $state:ty))*) => ($(
+        unsafe impl<T: ComponentType + Send + Sync + 'static> Lower for $ty {
+            fn lower<U>(
+                &self,
+                cx: &mut LowerContext<'_, U>,
+                ty: InterfaceType,
+                dst: &mut MaybeUninit<Self::Lower>,
+            ) -> Result<()> {
+                lower_handle(cx, ty, &self.channel)?.lower(cx, InterfaceType::U32, dst)
+            }
+
+            fn store<U>(
+                &self,
+                cx: &mut LowerContext<'_, U>,
+                ty: InterfaceType,
+                offset: usize,
+            ) -> Result<()> {
+                lower_handle(cx, ty, &self.channel)?.store(cx, InterfaceType::U32, offset)
+            }
+        }
+
+        unsafe impl<T: ComponentType + Send + Sync + 'static> Lift for $ty {
+            fn lift(cx: &mut LiftContext<'_>, ty: InterfaceType, src: &Self::Lower) -> Result<Self> {
+                let handle = u32::lift(cx, InterfaceType::U32, src)?;
+                Self::lift_from_handle(cx, ty, handle)
+            }
+
+            fn load(cx: &mut LiftContext<'_>, ty: InterfaceType, bytes: &[u8]) -> Result<Self> {
+                let handle = u32::load(cx, InterfaceType::U32, bytes)?;
+                Self::lift_from_handle(cx, ty, handle)
+            }
+        }
+
+        impl<T: Send + Sync + 'static> $ty {
+            fn lift_from_handle(
+                cx: &mut LiftContext<'_>,
+                ty: InterfaceType,
+                handle: u32,
+            ) -> Result<Self> {
+                let channel = lift_handle(cx, ty, handle)?;
+                channel.lock().payload::<$state>()?;
+                Ok(Self {
+                    channel,
+                    _marker: marker::PhantomData,
+                })
+            }
+        }
+    )*)
+}
+
+handle_lift_lower! {
+    (StreamReader<T>, VecDeque<T>)
+    (FutureReader<T>, Option<T>)
+}
+
+macro_rules! any_lift_lower {
+    ($($ty:ident)*) => ($(
+        impl $ty {
+            pub(crate) fn lower<U>(
+                &self,
+                cx: &mut LowerContext<'_, U>,
+                ty: InterfaceType,
+                dst: &mut MaybeUninit<ValRaw>,
+            ) -> Result<()> {
+                lower_handle(cx, ty, &self.channel)?.lower(cx, InterfaceType::U32, dst)
+            }
+
+            pub(crate) fn store<U>(
+                &self,
+                cx: &mut LowerContext<'_, U>,
+                ty: InterfaceType,
+                offset: usize,
+            ) -> Result<()> {
+                lower_handle(cx, ty, &self.channel)?.store(cx, InterfaceType::U32, offset)
+            }
+
+            pub(crate) fn lift(cx: &mut LiftContext<'_>, ty: InterfaceType, src: &ValRaw) -> Result<Self> {
+                let handle = u32::lift(cx, InterfaceType::U32, src)?;
+                Ok($ty { channel: lift_handle(cx, ty, handle)? })
+            }
+
+            pub(crate) fn load(cx: &mut LiftContext<'_>, ty: InterfaceType, bytes: &[u8]) -> Result<Self> {
+                let handle = u32::load(cx, InterfaceType::U32, bytes)?;
+                Ok($ty { channel: lift_handle(cx, ty, handle)? })
+            }
+        }
+    )*)
+}
+
+any_lift_lower! {
+    StreamAny
+    FutureAny
+}
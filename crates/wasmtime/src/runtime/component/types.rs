@@ -7,9 +7,9 @@ use core::fmt;
 use core::ops::Deref;
 use wasmtime_environ::component::{
     ComponentTypes, InterfaceType, ResourceIndex, TypeComponentIndex, TypeComponentInstanceIndex,
-    TypeDef, TypeEnumIndex, TypeFlagsIndex, TypeFuncIndex, TypeListIndex, TypeModuleIndex,
-    TypeOptionIndex, TypeRecordIndex, TypeResourceTableIndex, TypeResultIndex, TypeTupleIndex,
-    TypeVariantIndex,
+    TypeDef, TypeEnumIndex, TypeFlagsIndex, TypeFuncIndex, TypeFutureIndex, TypeListIndex,
+    TypeModuleIndex, TypeOptionIndex, TypeRecordIndex, TypeResourceTableIndex, TypeResultIndex,
+    TypeStreamIndex, TypeTupleIndex, TypeVariantIndex,
 };
 use wasmtime_environ::PrimaryMap;
 
@@ -119,6 +119,14 @@ impl TypeChecker<'_> {
             (InterfaceType::Tuple(_), _) => false,
             (InterfaceType::Flags(f1), InterfaceType::Flags(f2)) => self.flags_equal(f1, f2),
             (InterfaceType::Flags(_), _) => false,
+            (InterfaceType::Future(f1), InterfaceType::Future(f2)) => {
+                self.futures_equal(self.a_types[f1].ty, self.b_types[f2].ty)
+            }
+            (InterfaceType::Future(_), _) => false,
+            (InterfaceType::Stream(s1), InterfaceType::Stream(s2)) => {
+                self.streams_equal(self.a_types[s1].ty, self.b_types[s2].ty)
+            }
+            (InterfaceType::Stream(_), _) => false,
             (InterfaceType::Bool, InterfaceType::Bool) => true,
             (InterfaceType::Bool, _) => false,
             (InterfaceType::U8, InterfaceType::U8) => true,
@@ -154,6 +162,22 @@ impl TypeChecker<'_> {
         self.interface_types_equal(a.element, b.element)
     }
 
+    fn futures_equal(&self, f1: TypeFutureIndex, f2: TypeFutureIndex) -> bool {
+        let a = &self.a_types[f1];
+        let b = &self.b_types[f2];
+        match (a.payload, b.payload) {
+            (Some(a), Some(b)) => self.interface_types_equal(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    fn streams_equal(&self, s1: TypeStreamIndex, s2: TypeStreamIndex) -> bool {
+        let a = &self.a_types[s1];
+        let b = &self.b_types[s2];
+        self.interface_types_equal(a.payload, b.payload)
+    }
+
     fn resources_equal(&self, o1: TypeResourceTableIndex, o2: TypeResourceTableIndex) -> bool {
         let a = &self.a_types[o1];
         let b = &self.b_types[o2];
@@ -476,6 +500,55 @@ impl PartialEq for Flags {
 
 impl Eq for Flags {}
 
+/// A `future` interface type
+#[derive(Clone, Debug)]
+pub struct FutureType(Handle<TypeFutureIndex>);
+
+impl FutureType {
+    pub(crate) fn from(index: TypeFutureIndex, ty: &InstanceType<'_>) -> Self {
+        FutureType(Handle::new(index, ty))
+    }
+
+    /// Retrieve the type of the value this future resolves to, if any.
+    pub fn payload(&self) -> Option<Type> {
+        self.0.types[self.0.index]
+            .payload
+            .as_ref()
+            .map(|ty| Type::from(ty, &self.0.instance()))
+    }
+}
+
+impl PartialEq for FutureType {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.equivalent(&other.0, TypeChecker::futures_equal)
+    }
+}
+
+impl Eq for FutureType {}
+
+/// A `stream` interface type
+#[derive(Clone, Debug)]
+pub struct StreamType(Handle<TypeStreamIndex>);
+
+impl StreamType {
+    pub(crate) fn from(index: TypeStreamIndex, ty: &InstanceType<'_>) -> Self {
+        StreamType(Handle::new(index, ty))
+    }
+
+    /// Retrieve the type of the elements of this stream.
+    pub fn payload(&self) -> Type {
+        Type::from(&self.0.types[self.0.index].payload, &self.0.instance())
+    }
+}
+
+impl PartialEq for StreamType {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.equivalent(&other.0, TypeChecker::streams_equal)
+    }
+}
+
+impl Eq for StreamType {}
+
 /// Represents a component model interface type
 #[derive(Clone, PartialEq, Eq, Debug)]
 #[allow(missing_docs)]
@@ -503,6 +576,8 @@ pub enum Type {
     Flags(Flags),
     Own(ResourceType),
     Borrow(ResourceType),
+    Future(FutureType),
+    Stream(StreamType),
 }
 
 impl Type {
@@ -634,6 +709,30 @@ impl Type {
         }
     }
 
+    /// Retrieve the inner [`FutureType`] of a [`Type::Future`].
+    ///
+    /// # Panics
+    ///
+    /// This will panic if `self` is not a [`Type::Future`].
+    pub fn unwrap_future(&self) -> &FutureType {
+        match self {
+            Type::Future(ty) => ty,
+            _ => panic!("attempted to unwrap a {} as a future", self.desc()),
+        }
+    }
+
+    /// Retrieve the inner [`StreamType`] of a [`Type::Stream`].
+    ///
+    /// # Panics
+    ///
+    /// This will panic if `self` is not a [`Type::Stream`].
+    pub fn unwrap_stream(&self) -> &StreamType {
+        match self {
+            Type::Stream(ty) => ty,
+            _ => panic!("attempted to unwrap a {} as a stream", self.desc()),
+        }
+    }
+
     /// Convert the specified `InterfaceType` to a `Type`.
     pub(crate) fn from(ty: &InterfaceType, instance: &InstanceType<'_>) -> Self {
         match ty {
@@ -660,6 +759,12 @@ impl Type {
             InterfaceType::Flags(index) => Type::Flags(Flags::from(*index, instance)),
             InterfaceType::Own(index) => Type::Own(instance.resource_type(*index)),
             InterfaceType::Borrow(index) => Type::Borrow(instance.resource_type(*index)),
+            InterfaceType::Future(index) => {
+                Type::Future(FutureType::from(instance.types[*index].ty, instance))
+            }
+            InterfaceType::Stream(index) => {
+                Type::Stream(StreamType::from(instance.types[*index].ty, instance))
+            }
         }
     }
 
@@ -688,6 +793,8 @@ impl Type {
             Type::Flags(_) => "flags",
             Type::Own(_) => "own",
             Type::Borrow(_) => "borrow",
+            Type::Future(_) => "future",
+            Type::Stream(_) => "stream",
         }
     }
 }
@@ -1,5 +1,9 @@
+#[cfg(not(feature = "component-model-async"))]
+use crate::component::func::bad_type_info;
 use crate::component::func::{desc, Lift, LiftContext, Lower, LowerContext};
 use crate::component::ResourceAny;
+#[cfg(feature = "component-model-async")]
+use crate::component::{FutureAny, StreamAny};
 use crate::prelude::*;
 use crate::ValRaw;
 use core::mem::MaybeUninit;
@@ -44,6 +48,9 @@ use wasmtime_environ::component::{
 ///   if they contain the same value, but will never be considered equal when
 ///   compared as `Val::Resource`s).
 ///
+/// * Streams and futures are tested if they refer to the same stream or
+///   future.
+///
 /// In general if a strict guarantee about equality is required here it's
 /// recommended to "build your own" as this equality intended for fuzzing
 /// Wasmtime may not be suitable for you.
@@ -86,6 +93,10 @@ pub enum Val {
     Result(Result<Option<Box<Val>>, Option<Box<Val>>>),
     Flags(Vec<String>),
     Resource(ResourceAny),
+    #[cfg(feature = "component-model-async")]
+    Future(FutureAny),
+    #[cfg(feature = "component-model-async")]
+    Stream(StreamAny),
 }
 
 impl Val {
@@ -111,6 +122,12 @@ impl Val {
             InterfaceType::Own(_) | InterfaceType::Borrow(_) => {
                 Val::Resource(ResourceAny::lift(cx, ty, next(src))?)
             }
+            #[cfg(feature = "component-model-async")]
+            InterfaceType::Future(_) => Val::Future(FutureAny::lift(cx, ty, next(src))?),
+            #[cfg(feature = "component-model-async")]
+            InterfaceType::Stream(_) => Val::Stream(StreamAny::lift(cx, ty, next(src))?),
+            #[cfg(not(feature = "component-model-async"))]
+            InterfaceType::Future(_) | InterfaceType::Stream(_) => bad_type_info(),
             InterfaceType::String => Val::String(<_>::lift(cx, ty, &[*next(src), *next(src)])?),
             InterfaceType::List(i) => {
                 // FIXME(#4311): needs memory64 treatment
@@ -220,6 +237,12 @@ impl Val {
             InterfaceType::Own(_) | InterfaceType::Borrow(_) => {
                 Val::Resource(ResourceAny::load(cx, ty, bytes)?)
             }
+            #[cfg(feature = "component-model-async")]
+            InterfaceType::Future(_) => Val::Future(FutureAny::load(cx, ty, bytes)?),
+            #[cfg(feature = "component-model-async")]
+            InterfaceType::Stream(_) => Val::Stream(StreamAny::load(cx, ty, bytes)?),
+            #[cfg(not(feature = "component-model-async"))]
+            InterfaceType::Future(_) | InterfaceType::Stream(_) => bad_type_info(),
             InterfaceType::List(i) => {
                 // FIXME(#4311): needs memory64 treatment
                 let ptr = u32::from_le_bytes(bytes[..4].try_into().unwrap()) as usize;
@@ -360,6 +383,11 @@ impl Val {
                 value.lower(cx, ty, next_mut(dst))
             }
             (InterfaceType::Borrow(_) | InterfaceType::Own(_), _) => unexpected(ty, self),
+            #[cfg(feature = "component-model-async")]
+            (InterfaceType::Future(_), Val::Future(value)) => value.lower(cx, ty, next_mut(dst)),
+            #[cfg(feature = "component-model-async")]
+            (InterfaceType::Stream(_), Val::Stream(value)) => value.lower(cx, ty, next_mut(dst)),
+            (InterfaceType::Future(_) | InterfaceType::Stream(_), _) => unexpected(ty, self),
             (InterfaceType::String, Val::String(value)) => {
                 let my_dst = &mut MaybeUninit::<[ValRaw; 2]>::uninit();
                 value.lower(cx, ty, my_dst)?;
@@ -474,6 +502,11 @@ impl Val {
                 value.store(cx, ty, offset)
             }
             (InterfaceType::Borrow(_) | InterfaceType::Own(_), _) => unexpected(ty, self),
+            #[cfg(feature = "component-model-async")]
+            (InterfaceType::Future(_), Val::Future(value)) => value.store(cx, ty, offset),
+            #[cfg(feature = "component-model-async")]
+            (InterfaceType::Stream(_), Val::Stream(value)) => value.store(cx, ty, offset),
+            (InterfaceType::Future(_) | InterfaceType::Stream(_), _) => unexpected(ty, self),
             (InterfaceType::List(ty), Val::List(values)) => {
                 let ty = &cx.types[ty];
                 let (ptr, len) = lower_list(cx, ty.element, values)?;
@@ -591,6 +624,10 @@ impl Val {
             Val::Result(_) => "result",
             Val::Resource(_) => "resource",
             Val::Flags(_) => "flags",
+            #[cfg(feature = "component-model-async")]
+            Val::Future(_) => "future",
+            #[cfg(feature = "component-model-async")]
+            Val::Stream(_) => "stream",
         }
     }
 
@@ -608,6 +645,12 @@ impl Val {
     }
 }
 
+impl From<ResourceAny> for Val {
+    fn from(resource: ResourceAny) -> Val {
+        Val::Resource(resource)
+    }
+}
+
 impl PartialEq for Val {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -669,6 +712,14 @@ impl PartialEq for Val {
             (Self::Flags(_), _) => false,
             (Self::Resource(l), Self::Resource(r)) => l == r,
             (Self::Resource(_), _) => false,
+            #[cfg(feature = "component-model-async")]
+            (Self::Future(l), Self::Future(r)) => l == r,
+            #[cfg(feature = "component-model-async")]
+            (Self::Future(_), _) => false,
+            #[cfg(feature = "component-model-async")]
+            (Self::Stream(l), Self::Stream(r)) => l == r,
+            #[cfg(feature = "component-model-async")]
+            (Self::Stream(_), _) => false,
         }
     }
 }
@@ -0,0 +1,315 @@
+//! Host-driven WasmFX continuations.
+//!
+//! Continuations are normally created, resumed, and suspended exclusively by
+//! wasm code. The [`Continuation`] type here lets the host create a
+//! continuation from any [`Func`] and resume it directly, observing each
+//! suspension to one of a set of tags. This is intended for hosts which
+//! implement schedulers or other effect handlers outside of wasm.
+//!
+//! Host continuations are allocated like those created by `cont.new`, and the
+//! host resumes them by switching stacks in the same way as `resume` does,
+//! taking the place of its handlers. Suspensions to the host's tags reach it
+//! through the runtime's path for suspensions which wasm doesn't handle.
+
+use crate::prelude::*;
+use crate::runtime::func::invoke_wasm_and_catch_traps;
+use crate::runtime::vm::continuation::stack_chain::StackChain;
+use crate::runtime::vm::continuation::{optimized, HostResumption};
+use crate::runtime::vm::{Instance, SendSyncPtr, TrapReason, VMOpaqueContext, ValRaw};
+use crate::store::{StoreOpaque, Stored};
+use crate::{AsContext, AsContextMut, Func, FuncType, StoreContextMut, Tag, Val};
+use core::ptr::NonNull;
+use wasmtime_environ::WasmFeatures;
+
+/// A WasmFX continuation which is created and resumed by the host.
+///
+/// A continuation starts out suspended at the beginning of the [`Func`] it
+/// was created from. Each call to [`Continuation::resume`] runs it until it
+/// either returns or suspends to one of the tags that were provided when it
+/// was created. Suspensions to other tags propagate past the host and trap,
+/// as they would for a wasm `resume` without a matching handler.
+///
+/// When the continuation suspends, the next call to `resume` provides the
+/// values that the `suspend` instruction returns, as described by the result
+/// types of the tag it suspended to. Once the function returns, or if a
+/// resumption traps, the continuation is finished and can't be resumed again.
+///
+/// Like [`Func`] and other items, a `Continuation` is a handle to an object
+/// owned by its [`Store`](crate::Store), and remains valid as long as the
+/// store does. The stack of a continuation which is never resumed to
+/// completion isn't reclaimed until the store is dropped, as for suspended
+/// continuations which are no longer referenced by wasm.
+///
+/// This requires [`Config::wasm_stack_switching`](crate::Config::wasm_stack_switching)
+/// to be enabled, and doesn't support continuations in parameters, results,
+/// or payloads.
+#[derive(Copy, Clone, Debug)]
+pub struct Continuation(Stored<ContinuationData>);
+
+/// The store-owned state of a [`Continuation`].
+pub(crate) struct ContinuationData {
+    contref: SendSyncPtr<optimized::VMContRef>,
+    ty: FuncType,
+    tags: Vec<Tag>,
+    tag_tys: Vec<FuncType>,
+    state: State,
+}
+
+#[derive(Copy, Clone)]
+enum State {
+    Fresh,
+    Suspended(usize),
+    Finished,
+}
+
+/// The outcome of [`Continuation::resume`].
+#[derive(Debug)]
+pub enum Resumed {
+    /// The continuation's function returned with these results, and the
+    /// continuation is finished.
+    Returned(Vec<Val>),
+    /// The continuation suspended to `tag` with `payload`.
+    ///
+    /// The continuation can be resumed again with values matching the result
+    /// types of `tag`.
+    Suspended {
+        /// The tag that the continuation suspended to.
+        tag: Tag,
+        /// The values provided to the `suspend` instruction.
+        payload: Vec<Val>,
+    },
+}
+
+/// A host function registered with [`Linker::tag_handler`].
+///
+/// [`Linker::tag_handler`]: crate::Linker::tag_handler
+pub(crate) type TagHandler<T> =
+    dyn Fn(StoreContextMut<'_, T>, Continuation, &[Val]) -> Result<()> + Send + Sync;
+
+impl Continuation {
+    /// Creates a new continuation which will run `func` when first resumed.
+    ///
+    /// Suspensions to any of `tags` are returned to the host from
+    /// [`Continuation::resume`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the types of `func` or of any of `tags` include
+    /// continuations, if stack switching isn't enabled, or if the
+    /// continuation's stack can't be allocated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `func` or any of `tags` don't belong to `store`, or if `store`
+    /// has async support enabled.
+    pub fn new(mut store: impl AsContextMut, func: &Func, tags: &[Tag]) -> Result<Continuation> {
+        let store = store.as_context_mut().0;
+        assert!(
+            !store.async_support(),
+            "cannot use host continuations when async support is enabled on the config"
+        );
+        ensure!(
+            store
+                .engine()
+                .features()
+                .contains(WasmFeatures::STACK_SWITCHING),
+            "host continuations require stack switching to be enabled"
+        );
+        if cfg!(all(
+            feature = "wasmfx_baseline",
+            not(feature = "wasmfx_no_baseline")
+        )) {
+            bail!("host continuations are not supported by the baseline implementation");
+        }
+
+        assert!(tags.iter().all(|tag| tag.comes_from_same_store(store)));
+        let ty = func.load_ty(store);
+        let tag_tys = tags
+            .iter()
+            .map(|tag| tag.func_ty(store))
+            .collect::<Vec<_>>();
+        if core::iter::once(&ty)
+            .chain(&tag_tys)
+            .flat_map(|ty| ty.params().chain(ty.results()))
+            .any(|ty| ty.as_ref().is_some_and(|r| r.heap_type().top().is_cont()))
+        {
+            bail!("continuations can't be passed to or from host continuations");
+        }
+
+        let func_ref = func.vm_func_ref(store);
+        let param_count = u32::try_from(ty.params().len()).unwrap();
+        let result_count = u32::try_from(ty.results().len()).unwrap();
+        let contref = unsafe {
+            let vmstore = &mut *store.traitobj();
+            Instance::from_vmctx(store.default_caller(), |instance| {
+                optimized::cont_new(
+                    vmstore,
+                    instance,
+                    func_ref.as_ptr().cast(),
+                    param_count,
+                    result_count,
+                )
+            })
+        }
+        .map_err(|reason| match reason {
+            TrapReason::User(error) => error,
+            _ => unreachable!("creating a continuation only fails with user errors"),
+        })?;
+
+        let data = ContinuationData {
+            contref: SendSyncPtr::new(NonNull::new(contref).unwrap()),
+            ty,
+            tags: tags.to_vec(),
+            tag_tys,
+            state: State::Fresh,
+        };
+        Ok(Continuation(store.store_data_mut().insert(data)))
+    }
+
+    /// Resumes this continuation with `args`.
+    ///
+    /// The first resumption passes `args` as the parameters of the
+    /// continuation's function. Subsequent resumptions pass `args` as the
+    /// results of the `suspend` instruction that last suspended it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this continuation is finished, if `args` don't
+    /// match the expected types, or if the continuation traps. A trap
+    /// finishes the continuation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` isn't the store this continuation was created in, or
+    /// if `store` has async support enabled.
+    pub fn resume(&self, mut store: impl AsContextMut, args: &[Val]) -> Result<Resumed> {
+        let mut store = store.as_context_mut();
+        assert!(
+            store.0.store_data().contains(self.0),
+            "continuation used with the wrong store"
+        );
+        assert!(
+            !store.0.async_support(),
+            "cannot use host continuations when async support is enabled on the config"
+        );
+
+        let data = &store.0.store_data()[self.0];
+        let expected = match data.state {
+            State::Fresh => data.ty.params().collect::<Vec<_>>(),
+            State::Suspended(tag) => data.tag_tys[tag].results().collect(),
+            State::Finished => bail!("cannot resume a finished continuation"),
+        };
+        if expected.len() != args.len() {
+            bail!(
+                "expected {} resumption arguments, got {}",
+                expected.len(),
+                args.len()
+            );
+        }
+        for (arg, ty) in args.iter().zip(&expected) {
+            if !arg.matches_ty(&store, ty)? {
+                bail!("resumption argument does not match expected type `{ty}`");
+            }
+        }
+        // Like wasm, the host can only resume a continuation from the main
+        // stack.
+        let chain = unsafe { (*store.0.stack_chain()).0.get() };
+        if !unsafe { (*chain).is_main_stack() } {
+            bail!("cannot resume a continuation while running in a continuation");
+        }
+        let args = args
+            .iter()
+            .map(|arg| unsafe { arg.to_raw(&mut store) })
+            .collect::<Result<Vec<_>>>()?;
+
+        // The continuation is only resumable again once it has suspended.
+        let data = &mut store.0.store_data_mut()[self.0];
+        data.state = State::Finished;
+        let contref = data.contref.as_ptr();
+        let ty = data.ty.clone();
+        let tags = data.tags.clone();
+        let tag_tys = data.tag_tys.clone();
+        let handled = tags
+            .iter()
+            .map(|tag| SendSyncPtr::new(NonNull::new(tag.vmimport(store.0).from).unwrap()))
+            .collect();
+        *store.0.host_resumption_mut() = Some(HostResumption {
+            tags: handled,
+            suspension: None,
+        });
+
+        let opaque: *mut StoreOpaque = store.0;
+        let mut outcome = None;
+        let callee = store.0.default_caller();
+        let result = invoke_wasm_and_catch_traps(
+            &mut store,
+            |_caller, _vm| {
+                outcome =
+                    Some(unsafe { optimized::resume_from_host(&mut *opaque, contref, &args) });
+                true
+            },
+            VMOpaqueContext::from_vmcontext(callee),
+        );
+        let resumption = store.0.host_resumption_mut().take().unwrap();
+
+        if outcome.is_none() {
+            // The resumption trapped, abandoning the stacks of the
+            // continuation, and the main stack is running again.
+            unsafe {
+                *chain = StackChain::MainStack(store.0.main_stack_information());
+                (*store.0.main_stack_information()).state = optimized::State::Running;
+            }
+        }
+        result?;
+
+        match outcome.unwrap() {
+            (active, optimized::ControlEffect::Return) => {
+                let results = unsafe {
+                    let data = (*active).args.data.cast::<ValRaw>();
+                    let raw = (0..ty.results().len())
+                        .map(|i| data.add(i).read())
+                        .collect::<Vec<_>>();
+                    Instance::from_vmctx(store.0.default_caller(), |instance| {
+                        optimized::drop_cont_ref(instance, active)
+                    });
+                    raw
+                };
+                let results = results
+                    .into_iter()
+                    .zip(ty.results())
+                    .map(|(raw, ty)| unsafe { Val::from_raw(&mut store, raw, ty) })
+                    .collect();
+                Ok(Resumed::Returned(results))
+            }
+            (active, _) => {
+                let (index, payload) = resumption
+                    .suspension
+                    .expect("a continuation suspending to the host records its suspension");
+                let index = usize::try_from(index).unwrap();
+                let payload = payload
+                    .into_iter()
+                    .zip(tag_tys[index].params())
+                    .map(|(raw, ty)| unsafe { Val::from_raw(&mut store, raw, ty) })
+                    .collect();
+                let data = &mut store.0.store_data_mut()[self.0];
+                data.contref = SendSyncPtr::new(NonNull::new(active).unwrap());
+                data.state = State::Suspended(index);
+                Ok(Resumed::Suspended {
+                    tag: tags[index],
+                    payload,
+                })
+            }
+        }
+    }
+
+    /// Returns whether this continuation is finished and can no longer be
+    /// resumed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` isn't the store this continuation was created in.
+    pub fn is_finished(&self, store: impl AsContext) -> bool {
+        let store = store.as_context().0;
+        matches!(store.store_data()[self.0].state, State::Finished)
+    }
+}
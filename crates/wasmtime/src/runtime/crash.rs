@@ -0,0 +1,100 @@
+use crate::module::ModuleRegistry;
+use crate::runtime::store::StoreOpaque;
+
+/// Context about a fatal fault in JIT-compiled wasm code, passed to the handler
+/// registered with [`Config::crash_handler`](crate::Config::crash_handler).
+///
+/// Faults in wasm code which are expected, such as out-of-bounds memory
+/// accesses, are turned into [`Trap`](crate::Trap)s. A fault anywhere else in
+/// JIT code is a bug in the code generator and Wasmtime won't attempt to
+/// recover from it: the signal is forwarded to whatever handler was installed
+/// before Wasmtime's, typically the process's crash reporter, or the default
+/// disposition which terminates the process. The crash handler runs right
+/// before that happens.
+#[derive(Debug)]
+pub struct WasmCrashInfo<'a> {
+    pc: usize,
+    faulting_addr: Option<usize>,
+    store_id: u64,
+    module_name: Option<&'a str>,
+    module_offset: Option<usize>,
+}
+
+impl WasmCrashInfo<'_> {
+    /// Returns the program counter of the faulting instruction.
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    /// Returns the address whose access caused the fault, if known.
+    pub fn faulting_addr(&self) -> Option<usize> {
+        self.faulting_addr
+    }
+
+    /// Returns an identifier for the [`Store`](crate::Store) which was
+    /// executing the faulting code.
+    ///
+    /// Identifiers are unique among all stores created in this process.
+    pub fn store_id(&self) -> u64 {
+        self.store_id
+    }
+
+    /// Returns the name of the module containing the faulting code, if it was
+    /// given one, as with [`Module::name`](crate::Module::name).
+    pub fn module_name(&self) -> Option<&str> {
+        self.module_name
+    }
+
+    /// Returns the offset within the original wasm module of the instruction
+    /// which was executing.
+    ///
+    /// Note that `None` may be returned if the module was not compiled with
+    /// [`Config::generate_address_map`](crate::Config::generate_address_map).
+    pub fn module_offset(&self) -> Option<usize> {
+        self.module_offset
+    }
+}
+
+/// The crash handler of an engine along with the state of the store it's
+/// reporting crashes for, captured when calling into wasm.
+pub(crate) struct CrashReporter {
+    handler: fn(&WasmCrashInfo<'_>),
+    modules: *const ModuleRegistry,
+    store_id: u64,
+}
+
+impl CrashReporter {
+    /// Returns a reporter for faults while `store` executes wasm, if its engine
+    /// has a crash handler.
+    pub(crate) fn new(store: &StoreOpaque) -> Option<CrashReporter> {
+        let handler = store.engine().config().crash_handler?;
+        Some(CrashReporter {
+            handler,
+            modules: store.modules(),
+            store_id: store.id().as_raw().get(),
+        })
+    }
+
+    /// Invokes the crash handler for a fault at `pc`.
+    ///
+    /// This is called from a signal handler and so must not allocate or take
+    /// locks.
+    ///
+    /// # Safety
+    ///
+    /// The store this reporter was created for must still be executing wasm,
+    /// meaning that its module registry is alive and not being modified.
+    pub(crate) unsafe fn report(&self, pc: usize, faulting_addr: Option<usize>) {
+        let (module_name, module_offset) = match (*self.modules).lookup_module_and_offset(pc) {
+            Some((module, offset)) => (module.name(), offset),
+            None => (None, None),
+        };
+        (self.handler)(&WasmCrashInfo {
+            pc,
+            faulting_addr,
+            store_id: self.store_id,
+            module_name,
+            module_offset,
+        });
+    }
+}
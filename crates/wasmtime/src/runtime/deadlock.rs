@@ -0,0 +1,257 @@
+use crate::hash_map::HashMap;
+use crate::prelude::*;
+use alloc::sync::Arc;
+use core::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Detects probable deadlocks between WebAssembly threads blocked in
+/// `memory.atomic.wait` instructions.
+///
+/// Each [`Store`](crate::Store) which runs a guest thread joins the monitor
+/// with [`Store::join_deadlock_monitor`](crate::Store::join_deadlock_monitor)
+/// and is assigned a thread id, see [`DeadlockMonitor::members`]. The monitor
+/// then tracks which of these threads are waiting on which shared memory
+/// locations, and which threads have notified each location in the past.
+///
+/// Whether a waiting thread will ever be notified can't be known, so the
+/// monitor assumes that a thread waiting on a location depends on the
+/// threads that have notified that location before, as is the case for the
+/// locks and condition variables which guests typically build out of atomic
+/// waits. When a thread starts an untimed wait the monitor reports a
+/// [`DeadlockReport`] to its callback if either:
+///
+/// * every member of the monitor is now blocked in an untimed wait, so none
+///   of them can notify the others, or
+/// * the new waiter closes a cycle of waiting threads, each of which depends
+///   on the next.
+///
+/// Waits with a timeout, and waits which return immediately because the
+/// location doesn't hold the expected value, are never reported. The
+/// callback is invoked on the thread that started the wait, just before it
+/// blocks, so it must not block itself. This type is cheaply clonable and
+/// all clones refer to the same monitor.
+///
+/// This requires the `threads` feature.
+#[derive(Clone)]
+pub struct DeadlockMonitor(Arc<DeadlockMonitorInner>);
+
+struct DeadlockMonitorInner {
+    state: Mutex<State>,
+    callback: Box<dyn Fn(&DeadlockReport) + Send + Sync>,
+}
+
+#[derive(Default)]
+struct State {
+    next_thread: u64,
+    members: usize,
+    /// The untimed waits in progress, keyed by thread.
+    waiting: HashMap<u64, Wait>,
+    /// The threads which have notified each location, keyed by its host
+    /// address.
+    notifiers: HashMap<usize, Vec<u64>>,
+}
+
+struct Wait {
+    address: usize,
+    wasm_address: u64,
+    since: Instant,
+}
+
+/// A probable deadlock detected by a [`DeadlockMonitor`].
+#[derive(Debug, Clone)]
+pub struct DeadlockReport {
+    waits: Vec<BlockedThread>,
+}
+
+impl DeadlockReport {
+    /// The threads involved in the deadlock.
+    ///
+    /// If the deadlock is a cycle then the threads are in cycle order,
+    /// starting with the thread whose wait completed it, and each thread
+    /// depends on the next one.
+    pub fn threads(&self) -> &[BlockedThread] {
+        &self.waits
+    }
+}
+
+impl fmt::Display for DeadlockReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "probable deadlock between {} threads:", self.waits.len())?;
+        for wait in &self.waits {
+            write!(
+                f,
+                "\n  thread {} waiting on address {:#x} for {:?}",
+                wait.thread, wait.address, wait.waited
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A thread blocked in a `memory.atomic.wait` instruction, as part of a
+/// [`DeadlockReport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockedThread {
+    /// The id of the thread's store within the monitor.
+    pub thread: u64,
+    /// The address in the shared linear memory the thread is waiting on.
+    pub address: u64,
+    /// How long the thread has been waiting.
+    pub waited: Duration,
+}
+
+impl DeadlockMonitor {
+    /// Creates a new monitor which reports probable deadlocks to `callback`.
+    pub fn new(callback: impl Fn(&DeadlockReport) + Send + Sync + 'static) -> DeadlockMonitor {
+        DeadlockMonitor(Arc::new(DeadlockMonitorInner {
+            state: Mutex::new(State::default()),
+            callback: Box::new(callback),
+        }))
+    }
+
+    /// Returns the number of stores currently monitored.
+    pub fn members(&self) -> usize {
+        self.0.state.lock().unwrap().members
+    }
+}
+
+impl fmt::Debug for DeadlockMonitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeadlockMonitor")
+            .field("members", &self.members())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A store's membership in a [`DeadlockMonitor`].
+pub(crate) struct DeadlockMember {
+    monitor: DeadlockMonitor,
+    thread: u64,
+}
+
+impl DeadlockMember {
+    pub(crate) fn join(monitor: &DeadlockMonitor) -> DeadlockMember {
+        let mut state = monitor.0.state.lock().unwrap();
+        let thread = state.next_thread;
+        state.next_thread += 1;
+        state.members += 1;
+        DeadlockMember {
+            monitor: monitor.clone(),
+            thread,
+        }
+    }
+
+    /// The id of this member's thread within the monitor.
+    pub(crate) fn thread(&self) -> u64 {
+        self.thread
+    }
+
+    /// Records that this thread notified the location at host address
+    /// `address`.
+    pub(crate) fn notify(&self, address: *mut u8) {
+        let mut state = self.monitor.0.state.lock().unwrap();
+        let notifiers = state.notifiers.entry(address as usize).or_default();
+        if !notifiers.contains(&self.thread) {
+            notifiers.push(self.thread);
+        }
+    }
+
+    /// Records that this thread starts an untimed wait on the location at
+    /// host address `address`, which is `wasm_address` in its memory, and
+    /// reports a deadlock if the wait completes one.
+    ///
+    /// The wait lasts until the returned guard is dropped.
+    pub(crate) fn wait(&self, address: *mut u8, wasm_address: u64) -> DeadlockWait<'_> {
+        let report = {
+            let mut state = self.monitor.0.state.lock().unwrap();
+            state.waiting.insert(
+                self.thread,
+                Wait {
+                    address: address as usize,
+                    wasm_address,
+                    since: Instant::now(),
+                },
+            );
+            state.detect(self.thread)
+        };
+        if let Some(report) = report {
+            log::warn!("{report}");
+            (self.monitor.0.callback)(&report);
+        }
+        DeadlockWait(self)
+    }
+}
+
+impl Drop for DeadlockMember {
+    fn drop(&mut self) {
+        let mut state = self.monitor.0.state.lock().unwrap();
+        state.members -= 1;
+        state.waiting.remove(&self.thread);
+        // A thread which is gone can't notify anyone anymore.
+        for notifiers in state.notifiers.values_mut() {
+            notifiers.retain(|t| *t != self.thread);
+        }
+    }
+}
+
+/// An untimed wait in progress, see [`DeadlockMember::wait`].
+pub(crate) struct DeadlockWait<'a>(&'a DeadlockMember);
+
+impl Drop for DeadlockWait<'_> {
+    fn drop(&mut self) {
+        let mut state = self.0.monitor.0.state.lock().unwrap();
+        state.waiting.remove(&self.0.thread);
+    }
+}
+
+impl State {
+    /// Returns a report of the deadlock that `thread`, which just started
+    /// waiting, is part of, if any.
+    fn detect(&self, thread: u64) -> Option<DeadlockReport> {
+        let now = Instant::now();
+        let blocked = |thread: u64| {
+            let wait = &self.waiting[&thread];
+            BlockedThread {
+                thread,
+                address: wait.wasm_address,
+                waited: now.duration_since(wait.since),
+            }
+        };
+
+        if self.waiting.len() == self.members {
+            let mut threads = self.waiting.keys().copied().collect::<Vec<_>>();
+            threads.sort_unstable();
+            return Some(DeadlockReport {
+                waits: threads.into_iter().map(blocked).collect(),
+            });
+        }
+
+        let cycle = self.find_cycle(thread, &mut vec![thread])?;
+        Some(DeadlockReport {
+            waits: cycle.into_iter().map(blocked).collect(),
+        })
+    }
+
+    /// Searches for a path of waiting threads from the last thread of `path`
+    /// back to `start`, each depending on the next.
+    fn find_cycle(&self, start: u64, path: &mut Vec<u64>) -> Option<Vec<u64>> {
+        let current = *path.last().unwrap();
+        let wait = &self.waiting[&current];
+        let notifiers = self.notifiers.get(&wait.address)?;
+        for &next in notifiers {
+            if next == start && path.len() > 1 {
+                return Some(path.clone());
+            }
+            if next == current || path.contains(&next) || !self.waiting.contains_key(&next) {
+                continue;
+            }
+            path.push(next);
+            if let Some(cycle) = self.find_cycle(start, path) {
+                return Some(cycle);
+            }
+            path.pop();
+        }
+        None
+    }
+}
@@ -0,0 +1,523 @@
+//! Support for dynamic linking of core wasm modules following the
+//! [shared-library ABI] from the WebAssembly tool conventions.
+//!
+//! Shared libraries are core wasm modules with a `dylink.0` custom section
+//! which describes how much linear memory and how many table slots they need.
+//! Every library loaded into a [`Store`] shares one linear memory and one
+//! indirect function table with the main module, and symbols exported by one
+//! library are visible to every library loaded after it. This mirrors
+//! `dlopen`/`dlsym` on native platforms and is intended for language runtimes
+//! which load native extensions at runtime.
+//!
+//! [shared-library ABI]: https://github.com/WebAssembly/tool-conventions/blob/main/DynamicLinking.md
+
+use crate::hash_map::HashMap;
+use crate::prelude::*;
+use crate::{
+    AsContext, AsContextMut, Extern, ExternType, Func, Global, GlobalType, Instance, Linker,
+    Memory, Module, Mutability, Ref, Store, StoreContextMut, Table, Val, ValType,
+};
+use core::mem;
+
+/// A core wasm module compiled as a shared library.
+///
+/// Shared libraries are loaded into a store with [`Store::dlopen`], after the
+/// store's shared memory and table have been configured with
+/// [`Store::dylink_init`].
+#[derive(Clone)]
+pub struct SharedLibrary {
+    module: Module,
+    info: DylinkInfo,
+}
+
+/// The contents of a `dylink.0` custom section that are relevant to loading a
+/// library.
+///
+/// Note that the memory alignment requested by a library is always satisfied
+/// since each library's data is placed at the start of freshly grown pages.
+#[derive(Clone, Default)]
+struct DylinkInfo {
+    memory_size: u32,
+    table_size: u32,
+    needed: Vec<String>,
+}
+
+impl SharedLibrary {
+    /// Compiles `bytes`, in either the binary or text format, as a shared
+    /// library.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the module fails to compile or if its leading
+    /// custom section isn't a valid `dylink.0` section.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn new(engine: &crate::Engine, bytes: impl AsRef<[u8]>) -> Result<SharedLibrary> {
+        #[cfg(feature = "wat")]
+        let bytes = wat::parse_bytes(bytes.as_ref())?;
+        #[cfg(not(feature = "wat"))]
+        let bytes = bytes.as_ref();
+
+        let info = DylinkInfo::parse(&bytes)?;
+        let module = Module::from_binary(engine, &bytes)?;
+        Ok(SharedLibrary { module, info })
+    }
+
+    /// Returns the underlying compiled module.
+    pub fn module(&self) -> &Module {
+        &self.module
+    }
+
+    /// Returns the number of bytes of linear memory this library requires for
+    /// its static data.
+    pub fn memory_size(&self) -> u32 {
+        self.info.memory_size
+    }
+
+    /// Returns the number of indirect function table slots this library
+    /// requires.
+    pub fn table_size(&self) -> u32 {
+        self.info.table_size
+    }
+
+    /// Returns the names of the libraries which must be loaded before this
+    /// one.
+    pub fn needed(&self) -> impl ExactSizeIterator<Item = &str> + '_ {
+        self.info.needed.iter().map(|s| s.as_str())
+    }
+}
+
+impl DylinkInfo {
+    fn parse(bytes: &[u8]) -> Result<DylinkInfo> {
+        use wasmparser::{Dylink0Subsection, KnownCustom, Parser, Payload};
+
+        for payload in Parser::new(0).parse_all(bytes) {
+            let section = match payload? {
+                Payload::Version { .. } => continue,
+                Payload::CustomSection(section) if section.name() == "dylink.0" => section,
+                // The `dylink.0` section is required to be the first section
+                // in a shared library.
+                _ => break,
+            };
+            let KnownCustom::Dylink0(reader) = section.as_known() else {
+                bail!("failed to parse `dylink.0` custom section");
+            };
+            let mut info = DylinkInfo::default();
+            for subsection in reader {
+                match subsection? {
+                    Dylink0Subsection::MemInfo(mem_info) => {
+                        info.memory_size = mem_info.memory_size;
+                        info.table_size = mem_info.table_size;
+                    }
+                    Dylink0Subsection::Needed(needed) => {
+                        info.needed.extend(needed.iter().map(|s| s.to_string()));
+                    }
+                    _ => {}
+                }
+            }
+            return Ok(info);
+        }
+        bail!("module is not a shared library: missing `dylink.0` custom section")
+    }
+}
+
+/// A handle to a library loaded into a store with [`Store::dlopen`].
+///
+/// Handles are only meaningful for the store they were returned from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LibraryHandle {
+    index: usize,
+}
+
+/// A symbol resolved with [`Store::dlsym`].
+#[derive(Clone, Debug)]
+pub enum LibrarySymbol {
+    /// An exported function.
+    Func(Func),
+    /// An exported data symbol, as an absolute address in the shared linear
+    /// memory.
+    Data(u32),
+}
+
+/// Per-store state of all libraries loaded with [`Store::dlopen`].
+#[derive(Default)]
+pub(crate) struct DylinkState {
+    env: Option<(Memory, Table)>,
+    libraries: Vec<LoadedLibrary>,
+    /// The global symbol namespace, in which the main module and then the
+    /// first library to export a name wins. Each symbol is paired with the
+    /// memory base of the module defining it.
+    symbols: HashMap<String, (u32, Extern)>,
+    /// Table slots allocated for `GOT.func` entries, keyed by symbol name, so
+    /// each function only ever has one address.
+    func_slots: HashMap<String, u32>,
+}
+
+struct LoadedLibrary {
+    name: String,
+    instance: Instance,
+    memory_base: u32,
+}
+
+/// Where a symbol referenced by a library being loaded is defined.
+enum Definition {
+    /// In the global namespace, by a module with the given memory base.
+    Global(u32, Extern),
+    /// By the library being loaded itself.
+    Own(String),
+}
+
+/// An import of a library being loaded, resolved before any memory or table
+/// space is reserved for it.
+enum Import {
+    Extern(Extern),
+    MemoryBase,
+    TableBase,
+    GotMem(Definition),
+    /// A `GOT.func` entry and the table slot it refers to, relative to the
+    /// end of this library's table segment if it's a new slot.
+    GotFunc(Slot),
+}
+
+enum Slot {
+    Existing(u32),
+    New(u32, Definition),
+}
+
+impl DylinkState {
+    fn init(&mut self, mut store: impl AsContextMut, main: Instance, env: (Memory, Table)) {
+        self.env = Some(env);
+        self.symbols.clear();
+        self.func_slots.clear();
+        let exports = main
+            .exports(&mut store)
+            .map(|e| (e.name().to_string(), e.into_extern()))
+            .collect::<Vec<_>>();
+        for (symbol, item) in exports {
+            if symbol.starts_with("__wasm_") {
+                continue;
+            }
+            // The main module's data symbols are absolute addresses.
+            self.symbols.entry(symbol).or_insert((0, item));
+        }
+    }
+
+    fn open<T>(
+        &mut self,
+        mut store: StoreContextMut<'_, T>,
+        linker: &Linker<T>,
+        name: &str,
+        library: &SharedLibrary,
+    ) -> Result<LibraryHandle> {
+        if let Some(index) = self.libraries.iter().position(|l| l.name == name) {
+            return Ok(LibraryHandle { index });
+        }
+        let (memory, table) = self.env.ok_or_else(|| {
+            anyhow!("dynamic linking has not been initialized, see `Store::dylink_init`")
+        })?;
+        for needed in library.needed() {
+            if !self.libraries.iter().any(|l| l.name == *needed) {
+                bail!("shared library `{name}` depends on `{needed}` which has not been loaded");
+            }
+        }
+
+        // Resolve every import, including the targets of the global offset
+        // table, before growing the shared memory and table, which can't be
+        // undone if loading fails.
+        let mut resolved = Vec::new();
+        let mut new_slots = HashMap::new();
+        for import in library.module.imports() {
+            let item = match (import.module(), import.name()) {
+                ("env", "memory") => Import::Extern(memory.into()),
+                ("env", "__indirect_function_table") => Import::Extern(table.into()),
+                ("env", "__memory_base") => Import::MemoryBase,
+                ("env", "__table_base") => Import::TableBase,
+                ("GOT.mem", symbol) => match self.resolve(&store, library, symbol, true) {
+                    Some(def) => Import::GotMem(def),
+                    None => {
+                        bail!("shared library `{name}` references undefined data symbol `{symbol}`")
+                    }
+                },
+                ("GOT.func", symbol) => match self.func_slots.get(symbol) {
+                    Some(slot) => Import::GotFunc(Slot::Existing(*slot)),
+                    None => match self.resolve(&store, library, symbol, false) {
+                        Some(def) => {
+                            let next = u32::try_from(new_slots.len()).unwrap();
+                            let offset = *new_slots.entry(symbol.to_string()).or_insert(next);
+                            Import::GotFunc(Slot::New(offset, def))
+                        }
+                        None => bail!(
+                            "shared library `{name}` references undefined function `{symbol}`"
+                        ),
+                    },
+                },
+                ("env", symbol) if import.ty().func().is_some() => match self.symbols.get(symbol) {
+                    Some((_, item @ Extern::Func(_))) => Import::Extern(item.clone()),
+                    _ => Import::Extern(linker.get_by_import(&mut store, &import).ok_or_else(
+                        || {
+                            anyhow!(
+                                "shared library `{name}` references undefined symbol `{symbol}`"
+                            )
+                        },
+                    )?),
+                },
+                (module, symbol) => {
+                    Import::Extern(linker.get_by_import(&mut store, &import).ok_or_else(|| {
+                        anyhow!("unknown import: `{module}::{symbol}` has not been defined")
+                    })?)
+                }
+            };
+            resolved.push(item);
+        }
+
+        // Reserve this library's static data at the end of linear memory.
+        let memory_size = library.memory_size();
+        let memory_base = if memory_size > 0 {
+            let page_size = memory.page_size(&store);
+            let pages = u64::from(memory_size).div_ceil(page_size);
+            let base = memory.grow(&mut store, pages)? * page_size;
+            u32::try_from(base + u64::from(memory_size))
+                .map_err(|_| anyhow!("shared library `{name}` does not fit in a 32-bit memory"))?;
+            u32::try_from(base).unwrap()
+        } else {
+            0
+        };
+        // Reserve its table segment followed by a slot for each function its
+        // global offset table newly refers to.
+        let table_size = library.table_size();
+        let slots = u32::try_from(new_slots.len()).unwrap();
+        let table_base = table.grow(
+            &mut store,
+            u64::from(table_size) + u64::from(slots),
+            Ref::Func(None),
+        )?;
+        let slot_base = u32::try_from(table_base)
+            .ok()
+            .and_then(|base| base.checked_add(table_size))
+            .filter(|base| base.checked_add(slots).is_some())
+            .ok_or_else(|| anyhow!("indirect function table index overflow"))?;
+        let table_base = slot_base - table_size;
+
+        let base_ty = GlobalType::new(ValType::I32, Mutability::Const);
+        let got_ty = GlobalType::new(ValType::I32, Mutability::Var);
+        let mut got = Vec::new();
+        let mut imports = Vec::new();
+        for import in resolved {
+            let item: Extern = match import {
+                Import::Extern(item) => item,
+                Import::MemoryBase => {
+                    Global::new(&mut store, base_ty.clone(), Val::I32(memory_base as i32))?.into()
+                }
+                Import::TableBase => {
+                    Global::new(&mut store, base_ty.clone(), Val::I32(table_base as i32))?.into()
+                }
+                Import::GotMem(def) => {
+                    let global = Global::new(&mut store, got_ty.clone(), Val::I32(0))?;
+                    got.push((global, Import::GotMem(def)));
+                    global.into()
+                }
+                Import::GotFunc(slot) => {
+                    let index = match &slot {
+                        Slot::Existing(index) => *index,
+                        Slot::New(offset, _) => slot_base + offset,
+                    };
+                    let global = Global::new(&mut store, got_ty.clone(), Val::I32(index as i32))?;
+                    got.push((global, Import::GotFunc(slot)));
+                    global.into()
+                }
+            };
+            imports.push(item);
+        }
+
+        let instance = Instance::new(&mut store, &library.module, &imports)?;
+        let index = self.libraries.len();
+        let exports = instance
+            .exports(&mut store)
+            .map(|e| (e.name().to_string(), e.into_extern()))
+            .collect::<HashMap<_, _>>();
+
+        // Relocate the global offset table now that this library's own
+        // exports are known.
+        let lookup = |def: Definition| match def {
+            Definition::Global(base, item) => (base, item),
+            Definition::Own(symbol) => (memory_base, exports[&symbol].clone()),
+        };
+        for (global, entry) in got {
+            match entry {
+                Import::GotMem(def) => {
+                    let (base, item) = lookup(def);
+                    let address = data_address(&mut store, base, item.unwrap_global())?;
+                    global.set(&mut store, Val::I32(address as i32))?;
+                }
+                Import::GotFunc(Slot::New(offset, def)) => {
+                    let (_, item) = lookup(def);
+                    let func = *item.unwrap_func();
+                    table.set(&mut store, u64::from(slot_base + offset), func.into())?;
+                }
+                _ => {}
+            }
+        }
+        for (symbol, offset) in new_slots {
+            self.func_slots.insert(symbol, slot_base + offset);
+        }
+
+        for init in ["__wasm_apply_data_relocs", "__wasm_call_ctors"] {
+            if let Some(Extern::Func(f)) = exports.get(init) {
+                f.typed::<(), ()>(&store)?.call(&mut store, ())?;
+            }
+        }
+
+        for (symbol, item) in exports {
+            // Linker-synthesized helpers are private to each library.
+            if symbol.starts_with("__wasm_") {
+                continue;
+            }
+            self.symbols.entry(symbol).or_insert((memory_base, item));
+        }
+        self.libraries.push(LoadedLibrary {
+            name: name.to_string(),
+            instance,
+            memory_base,
+        });
+        Ok(LibraryHandle { index })
+    }
+
+    /// Finds the definition of the data symbol (if `data`) or function
+    /// `symbol` referenced by `library`, preferring symbols which are already
+    /// in the global namespace.
+    fn resolve(
+        &self,
+        store: &impl AsContext,
+        library: &SharedLibrary,
+        symbol: &str,
+        data: bool,
+    ) -> Option<Definition> {
+        let is_data = |ty: &GlobalType| matches!(ty.content(), ValType::I32);
+        match self.symbols.get(symbol) {
+            Some((base, item @ Extern::Global(g))) if data && is_data(&g.ty(store)) => {
+                return Some(Definition::Global(*base, item.clone()));
+            }
+            Some((base, item @ Extern::Func(_))) if !data => {
+                return Some(Definition::Global(*base, item.clone()));
+            }
+            _ => {}
+        }
+        match library.module.get_export(symbol)? {
+            ExternType::Global(ty) if data && is_data(&ty) => {}
+            ExternType::Func(_) if !data => {}
+            _ => return None,
+        }
+        Some(Definition::Own(symbol.to_string()))
+    }
+
+    fn sym(
+        &self,
+        mut store: impl AsContextMut,
+        library: Option<LibraryHandle>,
+        name: &str,
+    ) -> Option<LibrarySymbol> {
+        let (memory_base, item) = match library {
+            Some(handle) => {
+                let library = self.libraries.get(handle.index)?;
+                let item = library.instance.get_export(&mut store, name)?;
+                (library.memory_base, item)
+            }
+            None => {
+                let (memory_base, item) = self.symbols.get(name)?;
+                (*memory_base, item.clone())
+            }
+        };
+        match item {
+            Extern::Func(f) => Some(LibrarySymbol::Func(f)),
+            Extern::Global(g) => data_address(&mut store, memory_base, &g)
+                .ok()
+                .map(LibrarySymbol::Data),
+            _ => None,
+        }
+    }
+}
+
+/// Data symbols are exported as globals holding an offset relative to the
+/// `__memory_base` of the library defining them.
+fn data_address(mut store: impl AsContextMut, memory_base: u32, global: &Global) -> Result<u32> {
+    match global.get(&mut store) {
+        Val::I32(offset) => Ok(memory_base.wrapping_add(offset as u32)),
+        _ => bail!("data symbol is not an `i32` global"),
+    }
+}
+
+impl<T> Store<T> {
+    /// Configures the main module of an application, and the linear memory
+    /// and indirect function table shared by all libraries subsequently
+    /// loaded with [`Store::dlopen`].
+    ///
+    /// The memory and table are typically those exported by `main`. All
+    /// functions and data symbols exported by `main` are added to the global
+    /// symbol namespace, taking precedence over those of libraries. Data
+    /// symbols of the main module are absolute addresses.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if libraries have already been loaded into this store.
+    pub fn dylink_init(&mut self, main: Instance, memory: Memory, table: Table) -> Result<()> {
+        let mut state = mem::take(self.inner.dylink_mut());
+        let result = if state.libraries.is_empty() {
+            state.init(&mut *self, main, (memory, table));
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "cannot reinitialize dynamic linking after libraries have been loaded"
+            ))
+        };
+        *self.inner.dylink_mut() = state;
+        result
+    }
+
+    /// Loads `library` into this store under `name`, returning a handle to it.
+    ///
+    /// The library's static data is placed at the end of the shared linear
+    /// memory and its table segment at the end of the shared table. Its
+    /// imports are resolved first from symbols exported by the main module and
+    /// previously loaded libraries and then from `linker`, after which its
+    /// global offset table is relocated and its constructors are run. All of
+    /// its exports are then added to the global symbol namespace unless
+    /// another module already defines them.
+    ///
+    /// Loading a library with the same `name` as one already loaded returns
+    /// the existing handle. Libraries cannot be unloaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if [`Store::dylink_init`] has not been called, if a
+    /// library listed in [`SharedLibrary::needed`] has not been loaded yet, if
+    /// a symbol cannot be resolved, or if instantiating the library or running
+    /// its constructors fails. Symbols are resolved before the shared memory
+    /// and table are grown, so they're left untouched if that fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this store is configured for async support, in the same way
+    /// as [`Instance::new`].
+    pub fn dlopen(
+        &mut self,
+        linker: &Linker<T>,
+        name: &str,
+        library: &SharedLibrary,
+    ) -> Result<LibraryHandle> {
+        let mut state = mem::take(self.inner.dylink_mut());
+        let result = state.open(self.as_context_mut(), linker, name, library);
+        *self.inner.dylink_mut() = state;
+        result
+    }
+
+    /// Looks up `name` among the exports of `library`, or in the global symbol
+    /// namespace if `library` is `None`.
+    ///
+    /// Returns `None` if the symbol isn't defined or is neither a function nor
+    /// a data symbol.
+    pub fn dlsym(&mut self, library: Option<LibraryHandle>, name: &str) -> Option<LibrarySymbol> {
+        let state = mem::take(self.inner.dylink_mut());
+        let result = state.sym(&mut *self, library, name);
+        *self.inner.dylink_mut() = state;
+        result
+    }
+}
@@ -0,0 +1,96 @@
+use crate::WasmRet;
+
+/// How errors returned by host functions are reported to wasm, configured for
+/// each namespace of a linker.
+///
+/// This applies to functions defined with
+/// [`Linker::func_wrap_fallible`](crate::Linker::func_wrap_fallible) and, for
+/// components, `LinkerInstance::func_wrap_fallible`, which return a
+/// domain-specific `Result<T, E>`. This allows the same host
+/// implementation to be exposed to guests which expect different conventions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// Errors trap, as if the host function had returned them as an
+    /// [`anyhow::Error`] from [`Func::wrap`](crate::Func::wrap).
+    ///
+    /// The function's wasm signature has the results of `T`.
+    #[default]
+    Trap,
+    /// Errors are returned to core wasm as a negative error code, such as a
+    /// negated errno, with [`HostError::code`].
+    ///
+    /// The function's wasm signature has a single result as described by
+    /// [`ErrnoRet`]. This is only supported for core wasm linkers.
+    ErrorCode,
+    /// Errors are returned to the component as the error case of a
+    /// `result<T, E>`.
+    ///
+    /// The function's WIT signature returns `result<T, E>`. This is only
+    /// supported for component linkers.
+    Result,
+}
+
+/// An error returned by a host function which can be converted to an error
+/// code with [`ErrorPolicy::ErrorCode`].
+pub trait HostError: core::error::Error + Send + Sync + 'static {
+    /// Returns the error code for this error, which should be positive.
+    ///
+    /// The code is negated before it's returned to wasm.
+    fn code(&self) -> i32;
+}
+
+/// A trait implemented for the success values of host functions which can be
+/// combined with an error code into a single wasm result, as done by
+/// [`ErrorPolicy::ErrorCode`].
+///
+/// * `()` returns `0` on success.
+/// * `i32` and `i64` return the value itself on success, which should not be
+///   negative.
+///
+/// In all cases an error returns its negated [`HostError::code`].
+///
+/// This trait should not be implemented by user types.
+pub trait ErrnoRet: WasmRet {
+    #[doc(hidden)]
+    type Ret: WasmRet;
+    #[doc(hidden)]
+    fn ok(self) -> Self::Ret;
+    #[doc(hidden)]
+    fn err(code: i32) -> Self::Ret;
+}
+
+impl ErrnoRet for () {
+    type Ret = i32;
+
+    fn ok(self) -> i32 {
+        0
+    }
+
+    fn err(code: i32) -> i32 {
+        code.wrapping_neg()
+    }
+}
+
+impl ErrnoRet for i32 {
+    type Ret = i32;
+
+    fn ok(self) -> i32 {
+        self
+    }
+
+    fn err(code: i32) -> i32 {
+        code.wrapping_neg()
+    }
+}
+
+impl ErrnoRet for i64 {
+    type Ret = i64;
+
+    fn ok(self) -> i64 {
+        self
+    }
+
+    fn err(code: i32) -> i64 {
+        -i64::from(code)
+    }
+}
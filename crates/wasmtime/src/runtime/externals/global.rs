@@ -1,10 +1,10 @@
 use crate::prelude::*;
-use crate::runtime::vm::{GcRootsList, SendSyncPtr};
+use crate::runtime::vm::{GcRootsList, SendSyncPtr, VMGlobalDefinition};
 use crate::{
     store::{AutoAssertNoGc, StoreData, StoreOpaque, Stored},
-    trampoline::generate_global_export,
+    trampoline::{generate_global_export, generate_global_export_with_storage},
     AnyRef, AsContext, AsContextMut, ExternRef, Func, GlobalType, HeapType, Mutability, Ref,
-    RootedGcRefImpl, Val, ValType,
+    RootedGcRefImpl, StoreContextMut, Val, ValType,
 };
 use core::ptr;
 use core::ptr::NonNull;
@@ -85,6 +85,100 @@ impl Global {
         }
     }
 
+    /// Creates a new WebAssembly `global` value with the type `ty` whose value
+    /// is stored in `storage`, which is owned by the embedder.
+    ///
+    /// Unlike [`Global::new`], which stores the value of the global within
+    /// `store`, this allows the value to live in memory chosen by the
+    /// embedder, such as a page of configuration values shared by many stores
+    /// and updated by another thread. WebAssembly reads the value directly
+    /// from `storage` on `global.get`, so updating it is immediately visible
+    /// to every instance importing the global without calling into any of
+    /// them.
+    ///
+    /// Only the numeric types `i32`, `i64`, `f32`, and `f64` are supported,
+    /// and the value is stored at the start of `storage` in the host's
+    /// native byte order. WebAssembly accesses the value with a single
+    /// load or store of its size, so concurrent updates made with atomic
+    /// operations of that size, such as with [`AtomicU32`], are never
+    /// observed torn. Note that compiled code may read an immutable global
+    /// once and reuse its value, for example for the rest of a function
+    /// call, so it sees a snapshot which may lag behind updates. Use a
+    /// mutable global if every `global.get` must observe the latest value.
+    ///
+    /// [`AtomicU32`]: core::sync::atomic::AtomicU32
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `ty` isn't a numeric type or if `storage` isn't
+    /// 16-byte aligned.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that:
+    ///
+    /// * `storage` is valid for reads of 16 bytes, and additionally for
+    ///   writes if `ty` is mutable, for as long as `store` is alive.
+    /// * `storage` holds a valid value of the global's type.
+    /// * Any modifications of the value made concurrently with WebAssembly,
+    ///   or with [`Global::get`] and [`Global::set`], are made with atomic
+    ///   operations of the value's size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # use core::ptr::NonNull;
+    /// # use core::sync::atomic::{AtomicU32, Ordering};
+    /// # fn main() -> anyhow::Result<()> {
+    /// #[repr(C, align(16))]
+    /// struct Slot(AtomicU32, [u32; 3]);
+    ///
+    /// static MAX_ITEMS: Slot = Slot(AtomicU32::new(10), [0; 3]);
+    ///
+    /// let engine = Engine::default();
+    /// let mut store = Store::new(&engine, ());
+    /// let ty = GlobalType::new(ValType::I32, Mutability::Const);
+    /// let global =
+    ///     unsafe { Global::new_with_storage(&mut store, ty, NonNull::from(&MAX_ITEMS).cast())? };
+    ///
+    /// let module = Module::new(
+    ///     &engine,
+    ///     r#"(module
+    ///         (global $max (import "" "max-items") i32)
+    ///         (func (export "max-items") (result i32) global.get $max)
+    ///     )"#,
+    /// )?;
+    /// let instance = Instance::new(&mut store, &module, &[global.into()])?;
+    /// let max_items = instance.get_typed_func::<(), i32>(&mut store, "max-items")?;
+    /// assert_eq!(max_items.call(&mut store, ())?, 10);
+    ///
+    /// // Updates are visible without calling into the instance.
+    /// MAX_ITEMS.0.store(20, Ordering::Relaxed);
+    /// assert_eq!(max_items.call(&mut store, ())?, 20);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub unsafe fn new_with_storage(
+        mut store: impl AsContextMut,
+        ty: GlobalType,
+        storage: NonNull<u8>,
+    ) -> Result<Global> {
+        let store = store.as_context_mut().0;
+        if !ty.content().is_num() {
+            bail!(
+                "globals with embedder-provided storage must have a numeric type, found `{}`",
+                ty.content()
+            );
+        }
+        let storage = storage.cast::<VMGlobalDefinition>();
+        if !storage.is_aligned() {
+            bail!("storage for a global must be 16-byte aligned");
+        }
+        let wasmtime_export = generate_global_export_with_storage(&ty, storage);
+        Ok(Global::from_wasmtime_global(wasmtime_export, store))
+    }
+
     /// Returns the underlying type of this `global`.
     ///
     /// # Panics
@@ -219,6 +313,59 @@ impl Global {
         Ok(())
     }
 
+    /// Registers `callback` to be invoked every time WebAssembly executes a
+    /// `global.set` of this global, with the newly written value.
+    ///
+    /// This is a debugging aid intended for tooling which wants to observe
+    /// guest globals such as `__stack_pointer` or allocator counters without
+    /// polling. It requires the engine to be configured with
+    /// [`Config::global_watchpoints`](crate::Config::global_watchpoints),
+    /// which makes every `global.set` in compiled code notify the host.
+    /// Writes made by the host through [`Global::set`] do not invoke
+    /// `callback`.
+    ///
+    /// Multiple callbacks may be registered for the same global and they're
+    /// invoked in the order they were registered. If `callback` returns an
+    /// error then it's raised as a trap in the WebAssembly that wrote the
+    /// global.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if global watchpoints are not enabled in the engine's
+    /// configuration or if this global is immutable.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this global.
+    pub fn watch<T>(
+        &self,
+        mut store: impl AsContextMut<Data = T>,
+        callback: impl FnMut(StoreContextMut<'_, T>, Val) -> Result<()> + Send + Sync + 'static,
+    ) -> Result<()> {
+        let store = store.as_context_mut().0;
+        if !store.engine().tunables().global_watchpoints {
+            bail!("global watchpoints are not enabled, see `Config::global_watchpoints`");
+        }
+        if self._ty(store).mutability() != Mutability::Var {
+            bail!("immutable global cannot be watched");
+        }
+        let definition = store[self.0].definition;
+        store.watch_global(*self, definition, Box::new(callback));
+        Ok(())
+    }
+
+    /// Removes all callbacks registered for this global with
+    /// [`Global::watch`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this global.
+    pub fn unwatch(&self, mut store: impl AsContextMut) {
+        let store = store.as_context_mut().0;
+        let definition = store[self.0].definition;
+        store.unwatch_global(definition);
+    }
+
     pub(crate) fn trace_root(&self, store: &mut StoreOpaque, gc_roots_list: &mut GcRootsList) {
         if let Some(ref_ty) = self._ty(store).content().as_ref() {
             if !ref_ty.is_vmgcref_type_and_points_to_object() {
@@ -219,6 +219,55 @@ impl Table {
         }
     }
 
+    /// Returns the continuation object at `index` of a table of
+    /// continuations, which can't be represented as a [`Ref`] yet.
+    ///
+    /// Returns `None` if `index` is out of bounds.
+    pub(crate) fn get_cont(
+        &self,
+        store: &mut StoreOpaque,
+        index: u64,
+    ) -> Option<Option<runtime::continuation::VMContObj>> {
+        let table = self.wasmtime_table(store, iter::empty());
+        match unsafe { (*table).get(None, index)? } {
+            runtime::TableElement::ContRef(c) => Some(c),
+            _ => unreachable!("not a table of continuations"),
+        }
+    }
+
+    /// Writes the continuation object `val` into `index` of a table of
+    /// continuations.
+    ///
+    /// # Safety
+    ///
+    /// `val` must be of this table's element type.
+    pub(crate) unsafe fn set_cont(
+        &self,
+        store: &mut StoreOpaque,
+        index: u64,
+        val: Option<runtime::continuation::VMContObj>,
+    ) -> Result<()> {
+        let table = self.wasmtime_table(store, iter::empty());
+        (*table)
+            .set(index, runtime::TableElement::ContRef(val))
+            .map_err(|()| anyhow!("table element index out of bounds"))
+    }
+
+    /// Grows a table of continuations by `delta` elements, initialized to
+    /// `init`.
+    ///
+    /// # Safety
+    ///
+    /// `init` must be of this table's element type.
+    pub(crate) unsafe fn grow_cont(
+        &self,
+        store: &mut StoreOpaque,
+        delta: u64,
+        init: Option<runtime::continuation::VMContObj>,
+    ) -> Result<u64> {
+        self.grow_element(store, delta, runtime::TableElement::ContRef(init))
+    }
+
     /// Returns the current size of this table.
     ///
     /// # Panics
@@ -259,6 +308,15 @@ impl Table {
         let store = store.as_context_mut().0;
         let ty = self.ty(&store);
         let init = init.into_table_element(store, ty.element())?;
+        self.grow_element(store, delta, init)
+    }
+
+    fn grow_element(
+        &self,
+        store: &mut StoreOpaque,
+        delta: u64,
+        init: runtime::TableElement,
+    ) -> Result<u64> {
         let table = self.wasmtime_table(store, iter::empty());
         unsafe {
             match (*table).grow(delta, init, store)? {
@@ -1,8 +1,16 @@
+use crate::prelude::*;
 use crate::runtime::types::TagType;
+use crate::trampoline::generate_tag_export;
 use crate::{
     store::{StoreData, StoreOpaque, Stored},
-    AsContext,
+    AsContext, AsContextMut, FuncType,
 };
+#[cfg(feature = "async")]
+use crate::{StoreContextMut, Val};
+#[cfg(feature = "async")]
+use alloc::sync::Arc;
+#[cfg(feature = "async")]
+use core::future::Future;
 
 /// A WebAssembly `tag`.
 #[derive(Copy, Clone, Debug)]
@@ -26,14 +34,124 @@ impl Tag {
         Tag(store.store_data_mut().insert(wasmtime_export))
     }
 
-    pub(crate) fn ty(&self, _store: impl AsContext) -> TagType {
-        todo!()
+    /// Creates a new tag of type `ty` defined by the host.
+    ///
+    /// Host-defined tags can be imported by modules like any other tag, and
+    /// are distinct from every other tag, including other tags created with
+    /// the same type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ty` isn't associated with the same engine as `store`.
+    pub fn new(mut store: impl AsContextMut, ty: &TagType) -> Result<Tag> {
+        Tag::_new(store.as_context_mut().0, ty)
+    }
+
+    fn _new(store: &mut StoreOpaque, ty: &TagType) -> Result<Tag> {
+        assert!(ty.comes_from_same_engine(store.engine()));
+        unsafe {
+            let wasmtime_export = generate_tag_export(store, ty)?;
+            Ok(Tag::from_wasmtime_tag(wasmtime_export, store))
+        }
+    }
+
+    /// Returns the type of this tag.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this tag.
+    pub fn ty(&self, store: impl AsContext) -> TagType {
+        TagType::from_wasmtime_tag(self.func_ty(store.as_context().0).into_registered_type())
+    }
+
+    /// Returns whether `a` and `b` refer to the same tag.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either tag does not belong to `store`.
+    pub fn eq(a: &Tag, b: &Tag, store: impl AsContext) -> bool {
+        let store = store.as_context().0;
+        store[a.0].definition == store[b.0].definition
+    }
+
+    /// Makes suspensions to this tag which aren't handled in WebAssembly
+    /// yield to the host's async executor.
+    ///
+    /// When WebAssembly executes a `suspend` of this tag and none of the
+    /// continuations it's running in handles the tag, rather than trapping,
+    /// `handler` is called with the suspend payload. The future it returns is
+    /// driven as part of the current call, such as [`Func::call_async`], which
+    /// returns `Poll::Pending` whenever the future does. Once the future
+    /// completes the WebAssembly which suspended is resumed with the values
+    /// `handler` stored in its results slice, or the future's error is raised
+    /// as a trap.
+    ///
+    /// Registering a handler for a tag replaces any handler previously
+    /// registered for it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if async support isn't enabled in the store's config,
+    /// or if this tag's payload or resumption values include continuations,
+    /// which can't be passed to the host.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this tag.
+    ///
+    /// [`Func::call_async`]: crate::Func::call_async
+    #[cfg(feature = "async")]
+    pub fn yield_to_host<T, F>(
+        &self,
+        mut store: impl AsContextMut<Data = T>,
+        handler: F,
+    ) -> Result<()>
+    where
+        F: for<'a> Fn(
+                StoreContextMut<'a, T>,
+                &'a [Val],
+                &'a mut [Val],
+            ) -> Box<dyn Future<Output = Result<()>> + Send + 'a>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let store = store.as_context_mut().0;
+        ensure!(
+            store.async_support(),
+            "cannot use `yield_to_host` without enabling async support in the config"
+        );
+        let ty = self.func_ty(store);
+        if ty
+            .params()
+            .chain(ty.results())
+            .any(|ty| ty.as_ref().is_some_and(|r| r.heap_type().top().is_cont()))
+        {
+            bail!("tags with continuations in their payload can't be handled by the host");
+        }
+        let definition = store[self.0].definition;
+        store.set_suspend_handler(*self, definition, Arc::new(handler));
+        Ok(())
     }
 
     pub(crate) fn wasmtime_ty<'a>(&self, data: &'a StoreData) -> &'a wasmtime_environ::Tag {
         &data[self.0].tag
     }
 
+    /// Returns the function type describing this tag's payload and the
+    /// values its suspensions are resumed with.
+    pub(crate) fn func_ty(&self, store: &StoreOpaque) -> FuncType {
+        let index = self
+            .wasmtime_ty(store.store_data())
+            .signature
+            .unwrap_engine_type_index();
+        FuncType::from_shared_type_index(store.engine(), index)
+    }
+
+    pub(crate) fn comes_from_same_store(&self, store: &StoreOpaque) -> bool {
+        store.store_data().contains(self.0)
+    }
+
     pub(crate) fn vmimport(&self, store: &StoreOpaque) -> crate::runtime::vm::VMTagImport {
         let export = &store[self.0];
         crate::runtime::vm::VMTagImport {
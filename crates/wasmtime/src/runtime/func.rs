@@ -7,8 +7,8 @@ use crate::runtime::Uninhabited;
 use crate::store::{AutoAssertNoGc, StoreData, StoreOpaque, Stored};
 use crate::type_registry::RegisteredType;
 use crate::{
-    AsContext, AsContextMut, CallHook, Engine, Extern, FuncType, Instance, Module, ModuleExport,
-    Ref, StoreContext, StoreContextMut, Val, ValRaw, ValType,
+    AsContext, AsContextMut, CallHook, CallerFrame, Engine, Extern, FuncType, Instance, Module,
+    ModuleExport, Ref, StoreContext, StoreContextMut, Val, ValRaw, ValType,
 };
 use alloc::sync::Arc;
 use core::ffi::c_void;
@@ -17,7 +17,7 @@ use core::mem::{self, MaybeUninit};
 use core::num::NonZeroUsize;
 use core::pin::Pin;
 use core::ptr::NonNull;
-use wasmtime_environ::VMSharedTypeIndex;
+use wasmtime_environ::{EntityType, FuncIndex, VMSharedTypeIndex};
 
 /// A reference to the abstract `nofunc` heap value.
 ///
@@ -1578,6 +1578,52 @@ impl Func {
         unsafe { Ok(TypedFunc::_new_unchecked(store, *self)) }
     }
 
+    /// Attempts to extract a statically typed, non-null function reference
+    /// from `r`.
+    ///
+    /// This is intended for function references read out of guest tables or
+    /// globals, such as the elements of a `(table (ref $t))`. The same static
+    /// type check as [`Func::typed`] is performed once up-front, after which
+    /// the returned [`TypedFuncRef`] can be called any number of times.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `r` is not a function reference, if it is a null
+    /// reference, or if the referenced function's type does not match `Params`
+    /// and `Results`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `r` is a function reference that does not belong to `store`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn foo(table: &Table, mut store: Store<()>) -> anyhow::Result<()> {
+    /// let r = table.get(&mut store, 0).unwrap();
+    /// let f = Func::from_typed_ref::<i32, i32>(&store, &r)?;
+    /// let result = f.call(&mut store, 1)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_typed_ref<Params, Results>(
+        store: impl AsContext,
+        r: &Ref,
+    ) -> Result<TypedFuncRef<Params, Results>>
+    where
+        Params: WasmParams,
+        Results: WasmResults,
+    {
+        let func = match r.as_func() {
+            Some(Some(func)) => func,
+            Some(None) => bail!("cannot call a null function reference"),
+            None => bail!("expected a function reference"),
+        };
+        let func = func.typed(store)?;
+        Ok(TypedFuncRef::new(func))
+    }
+
     /// Get a stable hash key for this function.
     ///
     /// Even if the same underlying function is added to the `StoreData`
@@ -1637,6 +1683,10 @@ pub(crate) fn invoke_wasm_and_catch_traps<T>(
             }
         }
 
+        if let Some(metrics) = store.engine().metrics() {
+            metrics.calls().add(1);
+        }
+
         let exit = enter_wasm(store);
 
         if let Err(trap) = store.0.call_hook(CallHook::CallingWasm) {
@@ -1645,6 +1695,7 @@ pub(crate) fn invoke_wasm_and_catch_traps<T>(
         }
         let result = crate::runtime::vm::catch_traps(store, callee, closure);
         exit_wasm(store, exit);
+        store.0.record_fuel_consumed();
         store.0.call_hook(CallHook::ReturningFromWasm)?;
         result.map_err(|t| crate::trap::from_runtime_box(store.0, t))
     }
@@ -2062,7 +2113,7 @@ for_each_function_signature!(impl_wasm_ty_list);
 /// recommended to use this type.
 pub struct Caller<'a, T> {
     pub(crate) store: StoreContextMut<'a, T>,
-    caller: &'a crate::runtime::vm::Instance,
+    caller: &'a mut crate::runtime::vm::Instance,
 }
 
 impl<T> Caller<'_, T> {
@@ -2083,7 +2134,7 @@ impl<T> Caller<'_, T> {
 
                 let ret = f(Caller {
                     store: store.as_context_mut(),
-                    caller: &instance,
+                    caller: instance,
                 });
 
                 (gc_lifo_scope, ret)
@@ -2100,7 +2151,73 @@ impl<T> Caller<'_, T> {
     fn sub_caller(&mut self) -> Caller<'_, T> {
         Caller {
             store: self.store.as_context_mut(),
-            caller: self.caller,
+            caller: &mut *self.caller,
+        }
+    }
+
+    /// Implementation of the stubs defined by
+    /// [`Linker::define_unknown_imports_as_lazy`]: resolves `module::name`,
+    /// patches the caller's import to call the resolved function directly, and
+    /// then forwards this call to it.
+    ///
+    /// `stub` is the `vmctx` of the stub itself, which is how it's recognized
+    /// in the caller's imports.
+    pub(crate) fn call_lazy_import(
+        &mut self,
+        stub: *mut VMOpaqueContext,
+        module: &str,
+        name: &str,
+        ty: &FuncType,
+        params: &[Val],
+        results: &mut [Val],
+    ) -> Result<()> {
+        if self.store.0.async_support() {
+            bail!("lazily resolved imports are not supported with async stores");
+        }
+        let func = self.store.0.resolve_lazy_import(module, name, ty)?;
+        self.patch_import(stub, module, name, &func);
+        func.call(self, params, results)
+    }
+
+    /// Replaces the caller's function import `module::name` with `func`, if
+    /// the caller has such an import of exactly `func`'s type which still
+    /// refers to the lazy `stub`.
+    ///
+    /// The caller may not be the instance which imported the lazy stub, for
+    /// example if the stub was passed to it through a table, or the caller may
+    /// have imported a different function under the same name, in which case
+    /// its imports are left alone.
+    fn patch_import(&mut self, stub: *mut VMOpaqueContext, module: &str, name: &str, func: &Func) {
+        let Some(runtime_module) = self.caller.runtime_module() else {
+            return;
+        };
+        let env_module = self.caller.env_module();
+        let import = env_module
+            .imports()
+            .filter_map(|(m, n, ty)| match ty {
+                EntityType::Function(ty) => Some((m, n, ty)),
+                _ => None,
+            })
+            .enumerate()
+            .find(|(_, (m, n, _))| *m == module && *n == name);
+        let Some((index, (_, _, expected))) = import else {
+            return;
+        };
+        let index = FuncIndex::new(index);
+        if self.caller.imported_function_vmctx(index) != stub {
+            return;
+        }
+        let expected = self
+            .caller
+            .engine_type_index(expected.unwrap_module_type_index());
+        if func.type_index(self.store.0.store_data()) != expected {
+            return;
+        }
+        let import = func.vmimport(self.store.0, runtime_module);
+        // SAFETY: `func` belongs to this store and has the type of the import
+        // as checked above.
+        unsafe {
+            self.caller.set_imported_function(index, import);
         }
     }
 
@@ -2249,6 +2366,20 @@ impl<T> Caller<'_, T> {
         self.store.gc_async().await;
     }
 
+    /// Returns information about the WebAssembly function which called into
+    /// this host function.
+    ///
+    /// This is much cheaper than capturing a full
+    /// [`WasmBacktrace`](crate::WasmBacktrace) since only the innermost wasm
+    /// frame is inspected, making it suitable for attributing work to call
+    /// sites on every call, for example in logging or profiling.
+    ///
+    /// Returns `None` if this host function was not called from WebAssembly,
+    /// for example when it is invoked directly with [`Func::call`].
+    pub fn current_frame(&self) -> Option<CallerFrame> {
+        CallerFrame::current(&self.store.0)
+    }
+
     /// Returns the remaining fuel in the store.
     ///
     /// For more information see [`Store::get_fuel`](crate::Store::get_fuel)
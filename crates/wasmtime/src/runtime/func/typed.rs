@@ -3,8 +3,8 @@ use crate::prelude::*;
 use crate::runtime::vm::{VMFuncRef, VMOpaqueContext};
 use crate::store::{AutoAssertNoGc, StoreOpaque};
 use crate::{
-    AsContext, AsContextMut, Engine, Func, FuncType, HeapType, NoFunc, RefType, StoreContextMut,
-    ValRaw, ValType,
+    AsContext, AsContextMut, Engine, Func, FuncType, HeapType, NoFunc, Ref, RefType,
+    StoreContextMut, ValRaw, ValType,
 };
 use core::ffi::c_void;
 use core::marker;
@@ -245,6 +245,93 @@ where
     }
 }
 
+/// A non-null, statically typed reference to a WebAssembly function.
+///
+/// This is the host-side counterpart of a `(ref $t)` value from the
+/// function-references proposal, for example a function reference read out of
+/// a guest table. Its function is statically known to have `Params` as its
+/// parameters and `Results` as its results, so it can be invoked directly
+/// without going through [`Ref::as_func`] and [`Func::typed`] first.
+///
+/// This structure is created via [`Func::from_typed_ref`].
+pub struct TypedFuncRef<Params, Results> {
+    func: TypedFunc<Params, Results>,
+}
+
+impl<Params, Results> Clone for TypedFuncRef<Params, Results> {
+    fn clone(&self) -> TypedFuncRef<Params, Results> {
+        Self {
+            func: self.func.clone(),
+        }
+    }
+}
+
+impl<Params, Results> TypedFuncRef<Params, Results>
+where
+    Params: WasmParams,
+    Results: WasmResults,
+{
+    pub(crate) fn new(func: TypedFunc<Params, Results>) -> TypedFuncRef<Params, Results> {
+        TypedFuncRef { func }
+    }
+
+    /// Returns the concrete type of the referenced function.
+    pub fn ty(&self) -> &FuncType {
+        &self.func.ty
+    }
+
+    /// Returns the underlying [`Func`] that this is referencing, losing the
+    /// static type information in the process.
+    pub fn func(&self) -> &Func {
+        &self.func.func
+    }
+
+    /// Returns the [`TypedFunc`] through which the referenced function is
+    /// invoked.
+    pub fn typed_func(&self) -> &TypedFunc<Params, Results> {
+        &self.func
+    }
+
+    /// Converts this reference back into a [`Ref`], for example to store it in
+    /// a table.
+    pub fn to_ref(&self) -> Ref {
+        Ref::Func(Some(self.func.func))
+    }
+
+    /// Invokes the referenced function with the specified parameters.
+    ///
+    /// This is the host equivalent of the `call_ref` instruction. For more
+    /// information see [`TypedFunc::call`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if it is called when the underlying [`Func`] is
+    /// connected to an asynchronous store.
+    pub fn call(&self, store: impl AsContextMut, params: Params) -> Result<Results> {
+        self.func.call(store, params)
+    }
+
+    /// Invokes the referenced function with the specified parameters.
+    ///
+    /// For more information see [`TypedFunc::call_async`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if it is called when the underlying [`Func`] is
+    /// connected to a synchronous store.
+    #[cfg(feature = "async")]
+    pub async fn call_async<T>(
+        &self,
+        store: impl AsContextMut<Data = T>,
+        params: Params,
+    ) -> Result<Results>
+    where
+        T: Send,
+    {
+        self.func.call_async(store, params).await
+    }
+}
+
 #[doc(hidden)]
 #[derive(Copy, Clone)]
 pub enum TypeCheckPosition {
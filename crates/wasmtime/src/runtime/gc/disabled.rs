@@ -13,6 +13,7 @@ mod arrayref;
 mod eqref;
 mod externref;
 mod i31;
+mod pinned;
 mod rooting;
 mod structref;
 
@@ -21,5 +22,6 @@ pub use arrayref::*;
 pub use eqref::*;
 pub use externref::*;
 pub use i31::*;
+pub use pinned::*;
 pub use rooting::*;
 pub use structref::*;
@@ -0,0 +1,45 @@
+use crate::runtime::Uninhabited;
+use crate::{AsContextMut, GcRef, ManuallyRooted, Result, Rooted};
+use core::fmt::{self, Debug};
+use core::marker;
+
+/// This type has been disabled because the `gc` cargo feature was not enabled
+/// at compile time.
+pub struct Pinned<T: GcRef> {
+    inner: Uninhabited,
+    _phantom: marker::PhantomData<T>,
+}
+
+impl<T: GcRef> Debug for Pinned<T> {
+    fn fmt(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.inner {}
+    }
+}
+
+impl<T: GcRef> Rooted<T> {
+    pub fn pin(&self, _store: impl AsContextMut) -> Result<Pinned<T>> {
+        match self.inner {}
+    }
+}
+
+impl<T: GcRef> Pinned<T> {
+    pub fn as_ptr(&self) -> *mut u8 {
+        match self.inner {}
+    }
+
+    pub fn len(&self) -> usize {
+        match self.inner {}
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self.inner {}
+    }
+
+    pub fn root(&self) -> &ManuallyRooted<T> {
+        match self.inner {}
+    }
+
+    pub fn unpin(self, _store: impl AsContextMut) {
+        match self.inner {}
+    }
+}
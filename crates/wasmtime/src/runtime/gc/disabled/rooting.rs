@@ -169,6 +169,52 @@ where
     }
 }
 
+/// This type has been disabled because the `gc` cargo feature was not enabled
+/// at compile time.
+pub struct AsyncRootScope<C>
+where
+    C: AsContextMut,
+{
+    inner: Uninhabited,
+    _phantom: marker::PhantomData<C>,
+}
+
+impl<C> AsyncRootScope<C>
+where
+    C: AsContextMut,
+{
+    pub fn reserve(&mut self, _additional: usize) {
+        match self.inner {}
+    }
+
+    pub async fn suspend<F>(&mut self, _future: F) -> F::Output
+    where
+        F: core::future::Future,
+    {
+        match self.inner {}
+    }
+}
+
+impl<T> AsContext for AsyncRootScope<T>
+where
+    T: AsContextMut,
+{
+    type Data = T::Data;
+
+    fn as_context(&self) -> crate::StoreContext<'_, Self::Data> {
+        match self.inner {}
+    }
+}
+
+impl<T> AsContextMut for AsyncRootScope<T>
+where
+    T: AsContextMut,
+{
+    fn as_context_mut(&mut self) -> crate::StoreContextMut<'_, Self::Data> {
+        match self.inner {}
+    }
+}
+
 /// This type has been disabled because the `gc` cargo feature was not enabled
 /// at compile time.
 pub struct ManuallyRooted<T>
@@ -227,3 +273,36 @@ impl<T: GcRef> RootedGcRefImpl<T> for ManuallyRooted<T> {
         match self.inner {}
     }
 }
+
+/// This type has been disabled because the `gc` cargo feature was not enabled
+/// at compile time.
+pub struct WeakRooted<T>
+where
+    T: GcRef,
+{
+    pub(crate) inner: Uninhabited,
+    _phantom: marker::PhantomData<T>,
+}
+
+impl<T: GcRef> Debug for WeakRooted<T> {
+    fn fmt(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.inner {}
+    }
+}
+
+impl<T> WeakRooted<T>
+where
+    T: GcRef,
+{
+    pub fn new(_store: impl AsContextMut, rooted: &impl RootedGcRef<T>) -> Result<Self> {
+        rooted.assert_unreachable()
+    }
+
+    pub fn upgrade(&self, _context: impl AsContextMut) -> Option<Rooted<T>> {
+        match self.inner {}
+    }
+
+    pub fn release(self, _store: impl AsContextMut) {
+        match self.inner {}
+    }
+}
@@ -6,7 +6,9 @@ mod arrayref;
 mod eqref;
 mod externref;
 mod i31;
+mod pinned;
 mod rooting;
+mod stats;
 mod structref;
 
 pub use anyref::*;
@@ -14,5 +16,7 @@ pub use arrayref::*;
 pub use eqref::*;
 pub use externref::*;
 pub use i31::*;
+pub use pinned::*;
 pub use rooting::*;
+pub use stats::*;
 pub use structref::*;
@@ -6,8 +6,8 @@ use crate::vm::{VMArrayRef, VMGcHeader};
 use crate::{
     prelude::*,
     store::{AutoAssertNoGc, StoreContextMut, StoreOpaque},
-    ArrayType, AsContext, AsContextMut, EqRef, GcHeapOutOfMemory, GcRefImpl, GcRootIndex, HeapType,
-    ManuallyRooted, RefType, Rooted, Val, ValRaw, ValType, WasmTy,
+    ArrayType, AsContext, AsContextMut, Engine, EqRef, GcHeapOutOfMemory, GcRefImpl, GcRootIndex,
+    HeapType, ManuallyRooted, RefType, Rooted, Val, ValRaw, ValType, WasmTy,
 };
 use crate::{AnyRef, FieldType};
 use core::mem::{self, MaybeUninit};
@@ -307,6 +307,44 @@ impl ArrayRef {
         }
     }
 
+    /// Allocate a new `array` of the given type and length, with every element
+    /// initialized to the element type's default value.
+    ///
+    /// Numeric elements are initialized to zero and nullable reference
+    /// elements to null. This is similar to the `array.new_default`
+    /// instruction.
+    ///
+    /// # Errors
+    ///
+    /// If `ty`'s element type has no default value, for example because it is
+    /// a non-nullable reference type, an error is returned.
+    ///
+    /// If the allocation cannot be satisfied because the GC heap is currently
+    /// out of memory, but performing a garbage collection might free up space
+    /// such that retrying the allocation afterwards might succeed, then a
+    /// [`GcHeapOutOfMemory<()>`][crate::GcHeapOutOfMemory] error is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ty` is not associated with the given store's engine.
+    pub fn new_with_len(
+        mut store: impl AsContextMut,
+        ty: &ArrayType,
+        len: u32,
+    ) -> Result<Rooted<ArrayRef>> {
+        let store = store.as_context_mut().0;
+        assert!(
+            Engine::same(ty.engine(), store.engine()),
+            "attempted to use an `ArrayType` with the wrong engine"
+        );
+        let elem_ty = ty.element_type();
+        let elem = Val::default_for_ty(elem_ty.unpack()).ok_or_else(|| {
+            anyhow!("array element type `{elem_ty}` does not have a default value")
+        })?;
+        let allocator = ArrayRefPre::_new(store, ty.clone());
+        Self::_new(store, &allocator, &elem, len)
+    }
+
     /// Allocate a new array of the given elements, without checking that the
     /// elements' types match the array's element type.
     fn _new_unchecked<'a>(
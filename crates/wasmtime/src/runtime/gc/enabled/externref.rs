@@ -217,6 +217,84 @@ impl ExternRef {
         Ok(Self::from_cloned_gc_ref(&mut ctx, gc_ref.into()))
     }
 
+    /// Creates a new instance of `ExternRef` wrapping the given value, and
+    /// registers a finalizer that is given that value back once the `externref`
+    /// has become unreachable and been collected.
+    ///
+    /// The finalizer is never run in the middle of a garbage collection.
+    /// Instead, it is deferred until after the collection that reclaimed the
+    /// `externref` has finished, for example at the end of [`Store::gc`]. An
+    /// `externref` can also be reclaimed outside of a collection, such as when
+    /// Wasm overwrites the last table slot referencing it; in that case its
+    /// finalizer runs at the end of the next collection. Any finalizers that
+    /// have not run by the time the store is dropped are run when the store's
+    /// GC heap is deallocated, and this is the only time that finalizers run
+    /// when using a collector that never reclaims objects, such as the null
+    /// collector.
+    ///
+    /// The finalizer does not have access to the store, and it is not given
+    /// the value if the `externref` was never reclaimed because allocating it
+    /// failed.
+    ///
+    /// [`Store::gc`]: crate::Store::gc
+    ///
+    /// # Errors
+    ///
+    /// This function returns the same errors in the same scenarios as
+    /// [`ExternRef::new`][crate::ExternRef::new].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
+    /// # use std::sync::Arc;
+    /// # fn _foo() -> Result<()> {
+    /// let mut store = Store::<()>::default();
+    /// let finalized = Arc::new(AtomicBool::new(false));
+    ///
+    /// {
+    ///     let mut scope = RootScope::new(&mut store);
+    ///     let finalized = finalized.clone();
+    ///     let _externref = ExternRef::new_with_finalizer(&mut scope, "hello!", move |s| {
+    ///         assert_eq!(s, "hello!");
+    ///         finalized.store(true, SeqCst);
+    ///     })?;
+    /// }
+    ///
+    /// // The `externref` is unrooted, so it is reclaimed by the next GC, after
+    /// // which its finalizer runs.
+    /// store.gc();
+    /// assert!(finalized.load(SeqCst));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_with_finalizer<T>(
+        mut context: impl AsContextMut,
+        value: T,
+        finalizer: impl FnOnce(T) + Send + Sync + 'static,
+    ) -> Result<Rooted<ExternRef>>
+    where
+        T: 'static + Any + Send + Sync,
+    {
+        let ctx = context.as_context_mut().0;
+
+        let value: Box<dyn Any + Send + Sync> = Box::new(value);
+        let gc_store = ctx.gc_store_mut()?;
+        let gc_ref = gc_store
+            .alloc_externref(value)
+            .context("unrecoverable error when allocating new `externref`")?
+            .map_err(|x| GcHeapOutOfMemory::<T>::new(*x.downcast().unwrap()))
+            .context("failed to allocate `externref`")?;
+        gc_store.set_externref_finalizer(
+            &gc_ref,
+            Box::new(move |data| finalizer(*data.downcast::<T>().unwrap())),
+        );
+
+        let mut ctx = AutoAssertNoGc::new(ctx);
+        Ok(Self::from_cloned_gc_ref(&mut ctx, gc_ref.into()))
+    }
+
     /// Convert an `anyref` into an `externref`.
     ///
     /// This is equivalent to the `extern.convert_any` instruction in Wasm.
@@ -0,0 +1,181 @@
+//! Pinning GC objects so that their contents can be accessed through raw
+//! pointers.
+
+use crate::prelude::*;
+use crate::runtime::vm::SendSyncPtr;
+use crate::store::{AutoAssertNoGc, StoreOpaque};
+use crate::{ArrayType, AsContextMut, GcRef, ManuallyRooted, Rooted, RootedGcRefImpl};
+use core::ptr::NonNull;
+use wasmtime_environ::{GcLayout, VMGcKind};
+
+/// A pinned GC object, created with [`Rooted::pin`].
+///
+/// While an object is pinned its payload, the elements of an array, stays at
+/// the same address in the GC heap and the object is kept alive, even across
+/// garbage collections. This allows handing a pointer to the payload to
+/// foreign code, such as a C library, without first copying the payload out of
+/// the GC heap.
+///
+/// The pointer remains valid until the object is unpinned with
+/// [`Pinned::unpin`] or its store is dropped. Like a [`ManuallyRooted<T>`],
+/// failing to unpin an object leaks it, and keeps it pinned, for the rest of
+/// its store's lifetime.
+///
+/// Note that the payload is shared with WebAssembly, which may read or write
+/// it whenever it's executing in the object's store. Accesses through the
+/// pointer must not overlap with the execution of WebAssembly in the store.
+pub struct Pinned<T: GcRef> {
+    root: ManuallyRooted<T>,
+    payload: SendSyncPtr<u8>,
+    len: usize,
+}
+
+impl<T: GcRef> core::fmt::Debug for Pinned<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Pinned")
+            .field("root", &self.root)
+            .field("payload", &self.payload.as_ptr())
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl<T: GcRef> Rooted<T> {
+    /// Pin this GC object, so that its payload can be accessed through a raw
+    /// pointer until it's unpinned.
+    ///
+    /// See [`Pinned`] for more details.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this reference has been unrooted, or if the object
+    /// isn't an array of non-reference elements. Arrays of references can't
+    /// be pinned since writing to them requires GC barriers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this reference is associated with a different store.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn _foo() -> Result<()> {
+    /// let mut config = Config::new();
+    /// config.wasm_function_references(true);
+    /// config.wasm_gc(true);
+    /// let engine = Engine::new(&config)?;
+    /// let mut store = Store::new(&engine, ());
+    ///
+    /// let ty = ArrayType::new(&engine, FieldType::new(Mutability::Var, StorageType::I8));
+    /// let allocator = ArrayRefPre::new(&mut store, ty);
+    /// let array = ArrayRef::new(&mut store, &allocator, &Val::I32(0), 16)?;
+    ///
+    /// let pinned = array.pin(&mut store)?;
+    /// assert_eq!(pinned.len(), 16);
+    /// // The payload can be written through the pointer, for example by a C
+    /// // library.
+    /// unsafe {
+    ///     core::ptr::write_bytes(pinned.as_ptr(), 42, pinned.len());
+    /// }
+    /// pinned.unpin(&mut store);
+    ///
+    /// assert_eq!(array.get(&mut store, 0)?.unwrap_i32(), 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pin(&self, mut store: impl AsContextMut) -> Result<Pinned<T>> {
+        Pinned::new(store.as_context_mut().0, self)
+    }
+}
+
+impl<T: GcRef> Pinned<T> {
+    fn new(store: &mut StoreOpaque, rooted: &Rooted<T>) -> Result<Pinned<T>> {
+        assert!(
+            rooted.comes_from_same_store(store),
+            "object used with wrong store"
+        );
+        let mut store = AutoAssertNoGc::new(store);
+        let gc_ref = rooted.try_gc_ref(&store)?.unchecked_copy();
+        if gc_ref.is_i31() {
+            bail!("`i31ref`s cannot be pinned");
+        }
+
+        let header = store.unwrap_gc_store().header(&gc_ref);
+        if !header.kind().matches(VMGcKind::ArrayRef) {
+            bail!("only arrays can be pinned");
+        }
+        let ty = header.ty().expect("arrayrefs should have concrete types");
+        let elem_ty = ArrayType::from_shared_type_index(store.engine(), ty).element_type();
+        if elem_ty.unpack().is_ref() {
+            bail!("arrays of references cannot be pinned");
+        }
+        let layout = match store.engine().signatures().layout(ty) {
+            Some(GcLayout::Array(layout)) => layout,
+            _ => unreachable!("array types should have array layouts"),
+        };
+
+        let gc_store = store.unwrap_gc_store_mut();
+        let array_len = gc_store.array_len(gc_ref.as_arrayref_unchecked());
+        let start = usize::try_from(gc_ref.as_heap_index().unwrap().get()).unwrap()
+            + usize::try_from(layout.base_size).unwrap();
+        let len = usize::try_from(layout.elem_size).unwrap() * usize::try_from(array_len).unwrap();
+        let payload = &mut gc_store.gc_heap.heap_slice_mut()[start..][..len];
+        let payload = SendSyncPtr::new(NonNull::new(payload.as_mut_ptr()).unwrap());
+
+        gc_store.pin(&gc_ref);
+        let gc_ref = gc_store.clone_gc_ref(&gc_ref);
+        Ok(Pinned {
+            root: ManuallyRooted::new(&mut store, gc_ref),
+            payload,
+            len,
+        })
+    }
+
+    /// Get a pointer to the payload of the pinned object.
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.payload.as_ptr()
+    }
+
+    /// Get the length of the payload of the pinned object, in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Is the payload of the pinned object empty?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the root which keeps the pinned object alive.
+    pub fn root(&self) -> &ManuallyRooted<T> {
+        &self.root
+    }
+
+    /// Unpin this GC object, after which pointers to its payload must no
+    /// longer be used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this object is associated with a different store.
+    pub fn unpin(self, mut store: impl AsContextMut) {
+        self._unpin(store.as_context_mut().0)
+    }
+
+    pub(crate) fn _unpin(self, store: &mut StoreOpaque) {
+        assert!(
+            self.root.comes_from_same_store(store),
+            "object used with wrong store"
+        );
+        {
+            let mut store = AutoAssertNoGc::new(store);
+            let gc_ref = self
+                .root
+                .get_gc_ref(&store)
+                .expect("ManuallyRooted always has a gc ref")
+                .unchecked_copy();
+            store.unwrap_gc_store_mut().unpin(&gc_ref);
+        }
+        self.root._unroot(store);
+    }
+}
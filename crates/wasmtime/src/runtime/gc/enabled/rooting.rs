@@ -78,6 +78,13 @@
 //!
 //!    [`PersistentRooted<T>`]: http://devdoc.net/web/developer.mozilla.org/en-US/docs/Mozilla/Projects/SpiderMonkey/JSAPI_reference/JS::PersistentRooted.html
 //!
+//! Additionally, `WeakRooted<T>` is a weak reference to a GC object: it has an
+//! arbitrary lifetime like `ManuallyRooted<T>`, but it does not keep its
+//! referent alive. It must be upgraded to a `Rooted<T>` before the object can be
+//! used, which fails once the object has been collected. It is not an index
+//! into the `RootSet`; instead it is an index into the GC heap's table of weak
+//! references, which the collector clears whenever it reclaims an object.
+//!
 //! At the end of the day, both `Rooted<T>` and `ManuallyRooted<T>` are just
 //! tagged indices into the store's `RootSet`. This indirection allows working
 //! with Rust's borrowing discipline (we use `&mut Store` to represent mutable
@@ -102,7 +109,7 @@
 //! can. However, if you really must, consider also using an `AutoAssertNoGc`
 //! across the block of code that is manipulating raw GC references.
 
-use crate::runtime::vm::{GcRootsList, GcStore, VMGcRef};
+use crate::runtime::vm::{GcRootsList, GcStore, VMGcRef, WeakGcRefId};
 use crate::vm::VMStore;
 use crate::{prelude::*, ValRaw};
 use crate::{
@@ -110,9 +117,11 @@ use crate::{
     AsContext, AsContextMut, GcRef, Result, RootedGcRef,
 };
 use core::any;
+use core::future::{poll_fn, Future};
 use core::marker;
 use core::mem::{self, MaybeUninit};
 use core::num::NonZeroU64;
+use core::pin::pin;
 use core::{
     fmt::{self, Debug},
     hash::{Hash, Hasher},
@@ -1204,6 +1213,127 @@ where
     }
 }
 
+/// A [`RootScope`] for use in async code, such as async host functions.
+///
+/// GC references rooted in this scope stay rooted across `.await` points, for
+/// as long as the scope is alive. Awaiting futures which don't need access to
+/// the store should be done through [`AsyncRootScope::suspend`], which borrows
+/// the scope for the duration of the `.await` so that it can't be dropped, or
+/// the store's roots otherwise modified, while the future is suspended.
+///
+/// In debug builds the scope checks its invariants at every poll boundary of
+/// a suspended future, and whenever it's used as a store context, panicking if
+/// roots were created or unrooted behind its back, such as through another
+/// handle to the same store.
+///
+/// # Example
+///
+/// ```
+/// # use wasmtime::*;
+/// # async fn foo() -> Result<()> {
+/// let mut store = Store::<()>::default();
+/// let mut scope = AsyncRootScope::new(&mut store);
+///
+/// let x = ExternRef::new(&mut scope, 42_u32)?;
+/// scope.suspend(async { /* some I/O */ }).await;
+/// assert!(x.data(&scope).is_ok());
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncRootScope<C>
+where
+    C: AsContextMut,
+{
+    scope: RootScope<C>,
+}
+
+impl<C> AsyncRootScope<C>
+where
+    C: AsContextMut,
+{
+    /// Construct a new scope for rooting GC objects in async code.
+    pub fn new(store: C) -> Self {
+        AsyncRootScope {
+            scope: RootScope::new(store),
+        }
+    }
+
+    /// Reserve enough capacity for `additional` GC roots in this scope.
+    pub fn reserve(&mut self, additional: usize) {
+        self.scope.reserve(additional);
+    }
+
+    /// Awaits `future` while keeping everything rooted in this scope alive.
+    ///
+    /// The store can't be used by `future`, and in debug builds each poll of
+    /// it asserts that the store's roots are the same as when it was
+    /// suspended.
+    pub async fn suspend<F>(&mut self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        let mut future = pin!(future);
+        let snapshot = self.snapshot();
+        poll_fn(|cx| {
+            self.assert_unchanged(snapshot);
+            let result = future.as_mut().poll(cx);
+            self.assert_unchanged(snapshot);
+            result
+        })
+        .await
+    }
+
+    /// Returns the number of LIFO roots and the LIFO generation of the store.
+    fn snapshot(&mut self) -> (usize, u32) {
+        let roots = self.scope.gc_roots();
+        (roots.lifo_roots.len(), roots.lifo_generation)
+    }
+
+    fn assert_unchanged(&mut self, snapshot: (usize, u32)) {
+        if cfg!(debug_assertions) {
+            assert_eq!(
+                self.snapshot(),
+                snapshot,
+                "GC roots were modified while an `AsyncRootScope` was suspended"
+            );
+        }
+    }
+
+    /// Asserts that roots of this scope haven't been unrooted by something
+    /// other than this scope.
+    fn assert_live(&self) {
+        if cfg!(debug_assertions) {
+            let len = self.scope.store.as_context().0.gc_roots().lifo_roots.len();
+            assert!(
+                len >= self.scope.scope,
+                "roots of an `AsyncRootScope` were unrooted while it was alive"
+            );
+        }
+    }
+}
+
+impl<T> AsContext for AsyncRootScope<T>
+where
+    T: AsContextMut,
+{
+    type Data = T::Data;
+
+    fn as_context(&self) -> crate::StoreContext<'_, Self::Data> {
+        self.assert_live();
+        self.scope.as_context()
+    }
+}
+
+impl<T> AsContextMut for AsyncRootScope<T>
+where
+    T: AsContextMut,
+{
+    fn as_context_mut(&mut self) -> crate::StoreContextMut<'_, Self::Data> {
+        self.assert_live();
+        self.scope.as_context_mut()
+    }
+}
+
 pub(crate) trait AsStoreOpaqueMut {
     fn as_store_opaque_mut(&mut self) -> &mut StoreOpaque;
 }
@@ -1832,6 +1962,153 @@ impl<T: GcRef> RootedGcRefImpl<T> for ManuallyRooted<T> {
     }
 }
 
+/// A weak reference to a garbage-collected `T`.
+///
+/// Unlike [`Rooted<T>`][crate::Rooted] and
+/// [`ManuallyRooted<T>`][crate::ManuallyRooted], a `WeakRooted<T>` does not
+/// keep its referent alive: once nothing else references the underlying GC
+/// object, it may be collected, even while `WeakRooted<T>`s to it
+/// exist. `WeakRooted<T>`s are not tied to any rooting scope, which makes them
+/// suitable for things like host-side caches of guest objects, where holding
+/// strong references would leak those objects for the lifetime of the store.
+///
+/// To use the referent, first [`upgrade`][crate::WeakRooted::upgrade] the
+/// `WeakRooted<T>` into a `Rooted<T>` within a store context. Upgrading returns
+/// `None` once the referent has been collected.
+///
+/// # Example
+///
+/// ```
+/// # use wasmtime::*;
+/// # fn _foo() -> Result<()> {
+/// let mut store = Store::<()>::default();
+///
+/// let weak = {
+///     let mut scope = RootScope::new(&mut store);
+///     let x = ExternRef::new(&mut scope, 1234)?;
+///     let weak = WeakRooted::new(&mut scope, &x)?;
+///
+///     // While `x` is rooted, the weak reference can be upgraded.
+///     let y = weak.upgrade(&mut scope).expect("`x` is still alive");
+///     assert!(Rooted::ref_eq(&scope, &x, &y)?);
+///
+///     weak
+/// };
+///
+/// // Once `x` is no longer rooted, a GC collects it and the weak reference
+/// // can no longer be upgraded.
+/// store.gc();
+/// assert!(weak.upgrade(&mut store).is_none());
+///
+/// // Release the weak reference once we are done with it.
+/// weak.release(&mut store);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Releasing Weak References
+///
+/// Similar to `ManuallyRooted<T>`, dropping a `WeakRooted<T>` without calling
+/// [`release`][crate::WeakRooted::release] leaks a small entry in the store's
+/// GC heap until the store is dropped. It does not, however, leak the
+/// referent.
+pub struct WeakRooted<T>
+where
+    T: GcRef,
+{
+    store_id: StoreId,
+    id: WeakGcRefId,
+    _phantom: marker::PhantomData<T>,
+}
+
+impl<T: GcRef> Debug for WeakRooted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = format!("WeakRooted<{}>", any::type_name::<T>());
+        f.debug_struct(&name)
+            .field("store_id", &self.store_id)
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+impl<T> WeakRooted<T>
+where
+    T: GcRef,
+{
+    /// Create a weak reference to the GC object that the given rooted
+    /// reference refers to.
+    ///
+    /// Returns an error if `rooted` has been unrooted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rooted` is not associated with the given store.
+    pub fn new(mut store: impl AsContextMut, rooted: &impl RootedGcRef<T>) -> Result<Self> {
+        Self::_new(store.as_context_mut().0, rooted)
+    }
+
+    pub(crate) fn _new(store: &mut StoreOpaque, rooted: &impl RootedGcRef<T>) -> Result<Self> {
+        let store_id = store.id();
+        let gc_ref = rooted.try_gc_ref(store)?.unchecked_copy();
+        let id = store.gc_store_mut()?.alloc_weak_gc_ref(&gc_ref);
+        Ok(WeakRooted {
+            store_id,
+            id,
+            _phantom: marker::PhantomData,
+        })
+    }
+
+    #[inline]
+    pub(crate) fn comes_from_same_store(&self, store: &StoreOpaque) -> bool {
+        self.store_id == store.id()
+    }
+
+    /// Get a [`Rooted<T>`][crate::Rooted] to this weak reference's referent,
+    /// rooted in the given context's scope.
+    ///
+    /// Returns `None` if the referent has been collected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is not associated with the given context's store.
+    pub fn upgrade(&self, mut context: impl AsContextMut) -> Option<Rooted<T>> {
+        self._upgrade(context.as_context_mut().0)
+    }
+
+    pub(crate) fn _upgrade(&self, store: &mut StoreOpaque) -> Option<Rooted<T>> {
+        assert!(
+            self.comes_from_same_store(store),
+            "object used with wrong store"
+        );
+        let mut store = AutoAssertNoGc::new(store);
+        let gc_store = store.unwrap_gc_store_mut();
+        let gc_ref = gc_store.weak_gc_ref_target(self.id)?.unchecked_copy();
+        let gc_ref = gc_store.clone_gc_ref(&gc_ref);
+        Some(Rooted::new(&mut store, gc_ref))
+    }
+
+    /// Release this weak reference.
+    ///
+    /// This never affects the referent, but failure to call this method will
+    /// leak this weak reference's entry in the GC heap for the entirety of the
+    /// store's lifetime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is not associated with the given store.
+    pub fn release(self, mut store: impl AsContextMut) {
+        self._release(store.as_context_mut().0)
+    }
+
+    pub(crate) fn _release(self, store: &mut StoreOpaque) {
+        assert!(
+            self.comes_from_same_store(store),
+            "object used with wrong store"
+        );
+        store.unwrap_gc_store_mut().dealloc_weak_gc_ref(self.id);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::ExternRef;
@@ -0,0 +1,113 @@
+//! Statistics about a store's GC heap and its collections.
+
+use crate::prelude::*;
+use core::time::Duration;
+
+/// A snapshot of statistics about a store's GC heap and the garbage
+/// collections performed within it.
+///
+/// Returned by [`Store::gc_stats`][crate::Store::gc_stats] and passed to hooks
+/// registered with [`Store::on_gc`][crate::Store::on_gc].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct GcStats {
+    /// The number of bytes currently allocated in the GC heap.
+    ///
+    /// This includes unreachable objects that have not been collected yet, as
+    /// well as the collector's per-object overhead.
+    pub allocated_bytes: usize,
+
+    /// The number of bytes that were still allocated in the GC heap at the end
+    /// of the most recent collection, or `None` if no collection has happened
+    /// yet.
+    pub live_bytes_after_last_gc: Option<usize>,
+
+    /// The number of collections that have been performed in this store.
+    pub collections: u64,
+
+    /// The duration of the most recent collection, or `None` if no collection
+    /// has happened yet.
+    ///
+    /// Durations are only measured when the `std` Cargo feature is enabled and
+    /// are otherwise always zero.
+    pub last_pause: Option<Duration>,
+
+    /// The total duration of all collections performed in this store.
+    pub total_pause: Duration,
+}
+
+/// The point in a garbage collection at which a hook registered with
+/// [`Store::on_gc`][crate::Store::on_gc] is invoked.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GcEvent {
+    /// A collection is about to begin.
+    BeforeCollection,
+    /// A collection has just finished.
+    AfterCollection,
+}
+
+/// Per-store bookkeeping behind [`GcStats`] and [`GcEvent`] hooks.
+#[derive(Default)]
+pub(crate) struct GcStatsState {
+    stats: GcStats,
+    hook: Option<Box<dyn FnMut(GcEvent, &GcStats) + Send + Sync>>,
+}
+
+impl GcStatsState {
+    /// Get a snapshot of this store's statistics, given the number of bytes
+    /// currently allocated in its GC heap.
+    pub(crate) fn snapshot(&self, allocated_bytes: usize) -> GcStats {
+        GcStats {
+            allocated_bytes,
+            ..self.stats
+        }
+    }
+
+    pub(crate) fn set_hook(&mut self, hook: Box<dyn FnMut(GcEvent, &GcStats) + Send + Sync>) {
+        self.hook = Some(hook);
+    }
+
+    /// Invoke the registered hook, if any, for the given event.
+    pub(crate) fn notify(&mut self, event: GcEvent, allocated_bytes: usize) {
+        let stats = self.snapshot(allocated_bytes);
+        if let Some(hook) = &mut self.hook {
+            hook(event, &stats);
+        }
+    }
+
+    /// Begin timing a collection.
+    pub(crate) fn start(&self) -> GcPause {
+        GcPause {
+            #[cfg(feature = "std")]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    /// Record a finished collection, given the number of bytes still
+    /// allocated in the GC heap afterwards.
+    pub(crate) fn finish(&mut self, pause: GcPause, live_bytes: usize) {
+        let duration = pause.elapsed();
+        self.stats.collections += 1;
+        self.stats.live_bytes_after_last_gc = Some(live_bytes);
+        self.stats.last_pause = Some(duration);
+        self.stats.total_pause += duration;
+    }
+}
+
+/// The start of an in-progress collection, see [`GcStatsState::start`].
+pub(crate) struct GcPause {
+    #[cfg(feature = "std")]
+    start: std::time::Instant,
+}
+
+impl GcPause {
+    fn elapsed(&self) -> Duration {
+        #[cfg(feature = "std")]
+        {
+            self.start.elapsed()
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            Duration::ZERO
+        }
+    }
+}
@@ -6,7 +6,7 @@ use crate::vm::{VMGcHeader, VMStructRef};
 use crate::{
     prelude::*,
     store::{AutoAssertNoGc, StoreContextMut, StoreOpaque},
-    AsContext, AsContextMut, EqRef, GcHeapOutOfMemory, GcRefImpl, GcRootIndex, HeapType,
+    AsContext, AsContextMut, Engine, EqRef, GcHeapOutOfMemory, GcRefImpl, GcRootIndex, HeapType,
     ManuallyRooted, RefType, Rooted, StructType, Val, ValRaw, ValType, WasmTy,
 };
 use crate::{AnyRef, FieldType};
@@ -236,6 +236,40 @@ impl StructRef {
         Self::_new(store.as_context_mut().0, allocator, fields)
     }
 
+    /// Allocate a new `struct` of the given type and get a reference to it.
+    ///
+    /// This is a convenience for creating a one-off [`StructRefPre`] and
+    /// calling [`StructRef::new`] with it. Prefer reusing a `StructRefPre`
+    /// when allocating many structs of the same type.
+    ///
+    /// # Errors
+    ///
+    /// If the given `fields` values' types do not match the field types of
+    /// `ty`, an error is returned.
+    ///
+    /// If the allocation cannot be satisfied because the GC heap is currently
+    /// out of memory, but performing a garbage collection might free up space
+    /// such that retrying the allocation afterwards might succeed, then a
+    /// [`GcHeapOutOfMemory<()>`][crate::GcHeapOutOfMemory] error is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ty` is not associated with the given store's engine, or if
+    /// any of the field values is not associated with the given store.
+    pub fn new_with_type(
+        mut store: impl AsContextMut,
+        ty: &StructType,
+        fields: &[Val],
+    ) -> Result<Rooted<StructRef>> {
+        let store = store.as_context_mut().0;
+        assert!(
+            Engine::same(ty.engine(), store.engine()),
+            "attempted to use a `StructType` with the wrong engine"
+        );
+        let allocator = StructRefPre::_new(store, ty.clone());
+        Self::_new(store, &allocator, fields)
+    }
+
     pub(crate) fn _new(
         store: &mut StoreOpaque,
         allocator: &StructRefPre,
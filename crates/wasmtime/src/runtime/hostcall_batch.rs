@@ -0,0 +1,228 @@
+use crate::hash_map::HashMap;
+use crate::prelude::*;
+use crate::{Caller, Extern};
+use alloc::sync::Arc;
+use core::fmt;
+
+/// A set of host operations which guests can submit in batches, through a
+/// ring in their linear memory, instead of with one host call each.
+///
+/// Each operation is identified by an opcode and implemented by a handler
+/// registered with [`HostcallBatch::op`]. Defining the batch in a linker with
+/// [`Linker::define_hostcall_batch`](crate::Linker::define_hostcall_batch)
+/// provides the function `submit` in the `wasmtime:runtime/batch` module, of
+/// type `(func (param i32) (result i32))`, which guests call with the address
+/// of a ring in their exported memory named `memory`.
+///
+/// # Ring layout
+///
+/// Similar to `io_uring`, the ring consists of a submission queue which the
+/// guest fills with operations and a completion queue which the host fills
+/// with their results. All fields are little-endian, and the ring is laid
+/// out as follows:
+///
+/// | Offset | Size | Field |
+/// |--------|------|-------|
+/// | 0 | 4 | `sq_head`: index of the next submission the host consumes |
+/// | 4 | 4 | `sq_tail`: index of the next submission the guest produces |
+/// | 8 | 4 | `cq_head`: index of the next completion the guest consumes |
+/// | 12 | 4 | `cq_tail`: index of the next completion the host produces |
+/// | 16 | 4 | `entries`: capacity of each queue, a power of two |
+/// | 20 | 12 | reserved |
+/// | 32 | `entries * 32` | submission queue |
+/// | `32 + entries * 32` | `entries * 16` | completion queue |
+///
+/// Indices are free-running and wrap around at `u32::MAX`, and the entry for
+/// index `i` is at position `i % entries` in its queue. A submission is laid
+/// out as a [`BatchOp`]: a `u32` opcode, `u32` flags, `u64` user data, and
+/// two `u64` arguments. A completion is the `u64` user data of its
+/// submission followed by the `i64` result of the operation.
+///
+/// On each call to `submit` the host processes submissions in order until
+/// the submission queue is empty or the completion queue is full, updating
+/// `sq_head` and `cq_tail` as it goes, and returns the number of operations
+/// processed. Submissions with an opcode which has no handler complete with
+/// the result [`HostcallBatch::UNSUPPORTED`].
+///
+/// # Examples
+///
+/// ```
+/// # use wasmtime::*;
+/// # fn main() -> anyhow::Result<()> {
+/// let engine = Engine::default();
+/// let mut batch = HostcallBatch::new();
+/// batch.op(1, |_caller: &mut Caller<'_, u64>, op| Ok((op.args[0] + op.args[1]) as i64))?;
+/// batch.op(2, |caller: &mut Caller<'_, u64>, op| {
+///     *caller.data_mut() += op.args[0];
+///     Ok(0)
+/// })?;
+///
+/// let mut linker = Linker::new(&engine);
+/// linker.define_hostcall_batch(&batch)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct HostcallBatch<T> {
+    handlers: HashMap<u32, Arc<BatchHandler<T>>>,
+}
+
+type BatchHandler<T> = dyn Fn(&mut Caller<'_, T>, BatchOp) -> Result<i64> + Send + Sync;
+
+/// An operation submitted to a [`HostcallBatch`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BatchOp {
+    /// The opcode selecting the handler of the operation.
+    pub opcode: u32,
+    /// Flags for the operation, whose meaning is up to the handler.
+    pub flags: u32,
+    /// A value which is passed back to the guest with the result.
+    pub user_data: u64,
+    /// The arguments of the operation.
+    pub args: [u64; 2],
+}
+
+const HEADER_SIZE: usize = 32;
+const SQE_SIZE: usize = 32;
+const CQE_SIZE: usize = 16;
+
+impl<T> HostcallBatch<T> {
+    /// The result of operations with an opcode which has no handler.
+    pub const UNSUPPORTED: i64 = -1;
+
+    /// Creates a new batch without any operations.
+    pub fn new() -> HostcallBatch<T> {
+        HostcallBatch {
+            handlers: HashMap::new(),
+        }
+    }
+
+    /// Registers `handler` to perform operations with the opcode `opcode`.
+    ///
+    /// The handler is given the submitted operation and returns its result,
+    /// which is written to the completion queue. It may access the guest's
+    /// memory, for example to read buffers pointed to by the arguments,
+    /// through `caller`. If the handler returns an error then processing of
+    /// the batch stops and the error is raised as a trap from `submit`; the
+    /// operation's submission has been consumed but no completion is written
+    /// for it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a handler for `opcode` is already registered.
+    pub fn op(
+        &mut self,
+        opcode: u32,
+        handler: impl Fn(&mut Caller<'_, T>, BatchOp) -> Result<i64> + Send + Sync + 'static,
+    ) -> Result<&mut Self> {
+        ensure!(
+            !self.handlers.contains_key(&opcode),
+            "handler for batch opcode {opcode} defined twice"
+        );
+        self.handlers.insert(opcode, Arc::new(handler));
+        Ok(self)
+    }
+
+    /// Processes the submissions of the ring at `ring` in the caller's
+    /// memory, returning the number of operations processed.
+    pub(crate) fn submit(&self, caller: &mut Caller<'_, T>, ring: u32) -> Result<u32> {
+        let memory = match caller.get_export("memory") {
+            Some(Extern::Memory(memory)) => memory,
+            _ => bail!("batch submitted by a module without an exported `memory`"),
+        };
+        let ring = usize::try_from(ring).unwrap();
+        let field = |data: &[u8], offset: usize| {
+            u32::from_le_bytes(data[ring + offset..][..4].try_into().unwrap())
+        };
+
+        let data = memory.data(&*caller);
+        ensure!(
+            data.len() >= HEADER_SIZE && ring <= data.len() - HEADER_SIZE,
+            "batch ring out of bounds"
+        );
+        let entries = field(data, 16);
+        ensure!(
+            entries.is_power_of_two(),
+            "batch ring has {entries} entries, which isn't a power of two"
+        );
+        let entries = usize::try_from(entries).unwrap();
+        let sq = ring + HEADER_SIZE;
+        let cq = entries
+            .checked_mul(SQE_SIZE)
+            .and_then(|size| sq.checked_add(size))
+            .ok_or_else(|| anyhow!("batch ring out of bounds"))?;
+        let end = entries
+            .checked_mul(CQE_SIZE)
+            .and_then(|size| cq.checked_add(size))
+            .ok_or_else(|| anyhow!("batch ring out of bounds"))?;
+        ensure!(end <= data.len(), "batch ring out of bounds");
+
+        let mask = entries - 1;
+        let mut processed = 0;
+        loop {
+            let data = memory.data_mut(&mut *caller);
+            let sq_head = field(data, 0);
+            let sq_tail = field(data, 4);
+            let cq_head = field(data, 8);
+            let cq_tail = field(data, 12);
+            let pending = usize::try_from(sq_tail.wrapping_sub(sq_head)).unwrap();
+            let completed = usize::try_from(cq_tail.wrapping_sub(cq_head)).unwrap();
+            ensure!(
+                pending <= entries && completed <= entries,
+                "batch ring indices are corrupt"
+            );
+            if pending == 0 || completed == entries {
+                return Ok(processed);
+            }
+
+            let sqe = &data[sq + (sq_head as usize & mask) * SQE_SIZE..][..SQE_SIZE];
+            let u64_at = |offset: usize| u64::from_le_bytes(sqe[offset..][..8].try_into().unwrap());
+            let op = BatchOp {
+                opcode: u32::from_le_bytes(sqe[0..4].try_into().unwrap()),
+                flags: u32::from_le_bytes(sqe[4..8].try_into().unwrap()),
+                user_data: u64_at(8),
+                args: [u64_at(16), u64_at(24)],
+            };
+            data[ring..][..4].copy_from_slice(&sq_head.wrapping_add(1).to_le_bytes());
+
+            let result = match self.handlers.get(&op.opcode) {
+                Some(handler) => handler(caller, op)?,
+                None => Self::UNSUPPORTED,
+            };
+
+            // The handler may have changed the memory, including the ring,
+            // so the indices are checked again before writing the completion.
+            let data = memory.data_mut(&mut *caller);
+            ensure!(end <= data.len(), "batch ring out of bounds");
+            let cq_tail = field(data, 12);
+            let cqe = &mut data[cq + (cq_tail as usize & mask) * CQE_SIZE..][..CQE_SIZE];
+            cqe[..8].copy_from_slice(&op.user_data.to_le_bytes());
+            cqe[8..].copy_from_slice(&result.to_le_bytes());
+            data[ring + 12..][..4].copy_from_slice(&cq_tail.wrapping_add(1).to_le_bytes());
+            processed += 1;
+        }
+    }
+}
+
+impl<T> Default for HostcallBatch<T> {
+    fn default() -> HostcallBatch<T> {
+        HostcallBatch::new()
+    }
+}
+
+impl<T> Clone for HostcallBatch<T> {
+    fn clone(&self) -> HostcallBatch<T> {
+        HostcallBatch {
+            handlers: self.handlers.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for HostcallBatch<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut opcodes = self.handlers.keys().collect::<Vec<_>>();
+        opcodes.sort();
+        f.debug_struct("HostcallBatch")
+            .field("opcodes", &opcodes)
+            .finish()
+    }
+}
@@ -1,8 +1,10 @@
 use crate::linker::{Definition, DefinitionType};
 use crate::prelude::*;
+use crate::runtime::metrics::{Metrics, Timer};
 use crate::runtime::vm::{
-    Imports, InstanceAllocationRequest, ModuleRuntimeInfo, StorePtr, VMFuncRef, VMFunctionImport,
-    VMGlobalImport, VMMemoryImport, VMOpaqueContext, VMTableImport, VMTagImport,
+    Imports, InstanceAllocationRequest, InstanceBatch, InstanceHandle, ModuleRuntimeInfo, StorePtr,
+    VMFuncRef, VMFunctionImport, VMGlobalImport, VMMemoryImport, VMOpaqueContext, VMTableImport,
+    VMTagImport,
 };
 use crate::store::{InstanceId, StoreOpaque, Stored};
 use crate::types::matching;
@@ -11,11 +13,12 @@ use crate::{
     StoreContext, StoreContextMut, Table, TypedFunc,
 };
 use alloc::sync::Arc;
+use core::ops::Range;
 use core::ptr::NonNull;
 use wasmparser::WasmFeatures;
 use wasmtime_environ::{
-    EntityIndex, EntityType, FuncIndex, GlobalIndex, MemoryIndex, PrimaryMap, TableIndex, TagIndex,
-    TypeTrace,
+    DataIndex, EntityIndex, EntityRef, EntityType, FuncIndex, GlobalIndex, MemoryIndex,
+    MemoryInitialization, PrimaryMap, TableIndex, TagIndex, TypeTrace,
 };
 
 /// An instantiated WebAssembly module.
@@ -156,7 +159,7 @@ impl Instance {
         let mut store = store.as_context_mut();
         let imports = Instance::typecheck_externs(store.0, module, imports)?;
         // See `new` for notes on this unsafety
-        unsafe { Instance::new_started_async(&mut store, module, imports.as_ref()).await }
+        unsafe { Instance::new_started_async(&mut store, module, imports.as_ref(), None).await }
     }
 
     fn typecheck_externs(
@@ -166,7 +169,9 @@ impl Instance {
     ) -> Result<OwnedImports> {
         for import in imports {
             if !import.comes_from_same_store(store) {
-                bail!("cross-`Store` instantiation is not currently supported");
+                return Err(crate::ErrorKind::Link.tag(format_err!(
+                    "cross-`Store` instantiation is not currently supported"
+                )));
             }
         }
         typecheck(module, imports, |cx, ty, item| {
@@ -175,7 +180,7 @@ impl Instance {
         })?;
         let mut owned_imports = OwnedImports::new(module);
         for import in imports {
-            owned_imports.push(import, store, module);
+            owned_imports.push(import, store, module)?;
         }
         Ok(owned_imports)
     }
@@ -192,7 +197,7 @@ impl Instance {
             !store.0.async_support(),
             "must use async instantiation when async support is enabled",
         );
-        Self::new_started_impl(store, module, imports)
+        Self::new_started_impl(store, module, imports, None)
     }
 
     /// Internal function to create an instance and run the start function.
@@ -203,11 +208,14 @@ impl Instance {
         store: &mut StoreContextMut<'_, T>,
         module: &Module,
         imports: Imports<'_>,
+        batch: Option<&mut InstanceBatch<'_>>,
     ) -> Result<Instance> {
-        let (instance, start) = Instance::new_raw(store.0, module, imports)?;
+        let timer = Timer::start(store.engine().metrics());
+        let (instance, start) = Instance::new_raw(store.0, module, imports, batch)?;
         if let Some(start) = start {
             instance.start_raw(store, start)?;
         }
+        timer.finish(store.engine().metrics(), Metrics::instantiation_time);
         Ok(instance)
     }
 
@@ -219,6 +227,7 @@ impl Instance {
         store: &mut StoreContextMut<'_, T>,
         module: &Module,
         imports: Imports<'_>,
+        batch: Option<&mut InstanceBatch<'_>>,
     ) -> Result<Instance>
     where
         T: Send,
@@ -229,7 +238,7 @@ impl Instance {
         );
 
         store
-            .on_fiber(|store| Self::new_started_impl(store, module, imports))
+            .on_fiber(|store| Self::new_started_impl(store, module, imports, batch))
             .await?
     }
 
@@ -252,15 +261,59 @@ impl Instance {
         store: &mut StoreOpaque,
         module: &Module,
         imports: Imports<'_>,
+        batch: Option<&mut InstanceBatch<'_>>,
     ) -> Result<(Instance, Option<FuncIndex>)> {
+        let (instance, mut instance_handle) =
+            Instance::allocate_raw(store, module, imports, batch)?;
+        let compiled_module = module.compiled_module();
+
+        // Now that we've recorded all information we need to about this
+        // instance within a `Store` we can start performing fallible
+        // initialization. Note that we still defer the `start` function to
+        // later since that may need to run asynchronously.
+        //
+        // If this returns an error (or if the start function traps) then
+        // any other initialization which may have succeeded which placed
+        // items from this instance into other instances should be ok when
+        // those items are loaded and run we'll have all the metadata to
+        // look at them.
+        let bulk_memory = store
+            .engine()
+            .features()
+            .contains(WasmFeatures::BULK_MEMORY);
+        instance_handle.initialize(store, compiled_module.module(), bulk_memory)?;
+
+        Ok((instance, compiled_module.module().start_func))
+    }
+
+    /// Internal function to allocate an instance and insert it into `store`,
+    /// without performing any of its initialization.
+    ///
+    /// If `batch` is provided then this instance takes its share of the batch
+    /// which was reserved ahead of time.
+    ///
+    /// This function's unsafety is the same as `Instance::new_raw`.
+    unsafe fn allocate_raw(
+        store: &mut StoreOpaque,
+        module: &Module,
+        imports: Imports<'_>,
+        batch: Option<&mut InstanceBatch<'_>>,
+    ) -> Result<(Instance, InstanceHandle)> {
         if !Engine::same(store.engine(), module.engine()) {
             bail!("cross-`Engine` instantiation is not currently supported");
         }
+        // Anything charged to the store's quota group for this instance,
+        // including the instance itself and its memories, is refunded if it
+        // fails to be allocated.
+        let quota = store.quota_checkpoint();
         store.bump_resource_counts(module)?;
 
         // Allocate the GC heap, if necessary.
         if store.engine().features().gc_types() {
-            let _ = store.gc_store_mut()?;
+            if let Err(e) = store.gc_store_mut() {
+                store.quota_rollback(quota);
+                return Err(e);
+            }
         }
 
         let compiled_module = module.compiled_module();
@@ -280,20 +333,28 @@ impl Instance {
         // this instance, so we determine what the ID is and then assert
         // it's the same later when we do actually insert it.
         let instance_to_be = store.store_data().next_id::<InstanceData>();
+        let reserved = batch.map(|batch| batch.take());
 
-        let mut instance_handle =
-            store
-                .engine()
-                .allocator()
-                .allocate_module(InstanceAllocationRequest {
-                    runtime_info: &ModuleRuntimeInfo::Module(module.clone()),
-                    imports,
-                    host_state: Box::new(Instance(instance_to_be)),
-                    store: StorePtr::new(store.traitobj()),
-                    wmemcheck: store.engine().config().wmemcheck,
-                    pkey: store.get_pkey(),
-                    tunables: store.engine().tunables(),
-                })?;
+        let result = store
+            .engine()
+            .allocator()
+            .allocate_module(InstanceAllocationRequest {
+                runtime_info: &ModuleRuntimeInfo::Module(module.clone()),
+                imports,
+                host_state: Box::new(Instance(instance_to_be)),
+                store: StorePtr::new(store.traitobj()),
+                wmemcheck: store.engine().config().wmemcheck,
+                pkey: store.get_pkey(),
+                tunables: store.engine().tunables(),
+                reserved,
+            });
+        let mut instance_handle = match result {
+            Ok(handle) => handle,
+            Err(e) => {
+                store.quota_rollback(quota);
+                return Err(e);
+            }
+        };
 
         // The instance still has lots of setup, for example
         // data/elements/start/etc. This can all fail, but even on failure
@@ -331,23 +392,72 @@ impl Instance {
         // was actually correct.
         assert_eq!(instance.0, instance_to_be);
 
-        // Now that we've recorded all information we need to about this
-        // instance within a `Store` we can start performing fallible
-        // initialization. Note that we still defer the `start` function to
-        // later since that may need to run asynchronously.
-        //
-        // If this returns an error (or if the start function traps) then
-        // any other initialization which may have succeeded which placed
-        // items from this instance into other instances should be ok when
-        // those items are loaded and run we'll have all the metadata to
-        // look at them.
-        let bulk_memory = store
-            .engine()
-            .features()
-            .contains(WasmFeatures::BULK_MEMORY);
-        instance_handle.initialize(store, compiled_module.module(), bulk_memory)?;
+        Ok((instance, instance_handle))
+    }
 
-        Ok((instance, compiled_module.module().start_func))
+    /// Internal function to create an instance whose globals are initialized
+    /// but whose segments are left to `AllocatedInstance::initialize`.
+    ///
+    /// This function's unsafety is the same as `Instance::new_raw`.
+    unsafe fn new_allocated(
+        store: &mut StoreOpaque,
+        module: &Module,
+        imports: Imports<'_>,
+    ) -> Result<AllocatedInstance> {
+        let (instance, mut instance_handle) = Instance::allocate_raw(store, module, imports, None)?;
+        let env_module = module.compiled_module().module();
+        instance_handle.initialize_globals(store, env_module)?;
+
+        // The offset of each active data segment is only known once globals
+        // are initialized, unless the segments were merged into images of
+        // their memories at compile time.
+        let (locations, offsets) = match &env_module.memory_initialization {
+            MemoryInitialization::Segmented(initializers) => (
+                initializers
+                    .iter()
+                    .map(|init| (init.memory_index, init.data.clone()))
+                    .collect::<Vec<_>>(),
+                instance_handle.data_segment_offsets(store, initializers)?,
+            ),
+            MemoryInitialization::Static { segments, .. } => (
+                segments
+                    .iter()
+                    .map(|(memory_index, init)| (*memory_index, init.data.clone()))
+                    .collect(),
+                segments.iter().map(|(_, init)| init.offset).collect(),
+            ),
+        };
+        let memories = instance_handle
+            .all_memories()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|(_, memory)| {
+                Extern::from_wasmtime_export(crate::runtime::vm::Export::Memory(memory), store)
+            })
+            .collect::<Vec<_>>();
+        let mut indices = (0..)
+            .map(DataIndex::from_u32)
+            .filter(|index| !env_module.passive_data_map.contains_key(index));
+        let segments = locations
+            .into_iter()
+            .zip(offsets)
+            .map(|((memory_index, data), offset)| DataSegment {
+                index: indices.next().unwrap().as_u32(),
+                memory: memories[memory_index.index()].clone(),
+                memory_index,
+                offset,
+                module: module.clone(),
+                data,
+            })
+            .collect::<Vec<_>>();
+        let data = segments.iter().map(|_| SegmentData::default()).collect();
+
+        Ok(AllocatedInstance {
+            instance,
+            module: module.clone(),
+            segments,
+            data,
+        })
     }
 
     pub(crate) fn from_wasmtime(handle: InstanceData, store: &mut StoreOpaque) -> Instance {
@@ -383,7 +493,7 @@ impl Instance {
         self._module(store.into().0)
     }
 
-    fn _module<'a>(&self, store: &'a StoreOpaque) -> &'a Module {
+    pub(crate) fn _module<'a>(&self, store: &'a StoreOpaque) -> &'a Module {
         let InstanceData { id, .. } = store[self.0];
         store.module_for_instance(id).unwrap()
     }
@@ -643,6 +753,18 @@ impl Instance {
             .into_iter()
             .map(|(i, m)| (i, unsafe { Memory::from_wasmtime_memory(m, store) }))
     }
+
+    /// Get the `VMFuncRef`s of all functions within this instance, indexed by
+    /// their index in the function index space.
+    ///
+    /// Returns both import and defined functions.
+    pub(crate) fn all_func_refs(&self, store: &mut StoreOpaque) -> Vec<NonNull<VMFuncRef>> {
+        let id = store[self.0].id;
+        let instance = store.instance_mut(id);
+        (0..instance.module().functions.len())
+            .map(|i| instance.get_exported_func(FuncIndex::new(i)).func_ref)
+            .collect()
+    }
 }
 
 pub(crate) struct OwnedImports {
@@ -679,7 +801,6 @@ impl OwnedImports {
         self.tags.reserve(raw.num_imported_tags);
     }
 
-    #[cfg(feature = "component-model")]
     pub(crate) fn clear(&mut self) {
         self.functions.clear();
         self.tables.clear();
@@ -688,7 +809,7 @@ impl OwnedImports {
         self.tags.clear();
     }
 
-    fn push(&mut self, item: &Extern, store: &mut StoreOpaque, module: &Module) {
+    fn push(&mut self, item: &Extern, store: &mut StoreOpaque, module: &Module) -> Result<()> {
         match item {
             Extern::Func(i) => {
                 self.functions.push(i.vmimport(store, module));
@@ -703,12 +824,14 @@ impl OwnedImports {
                 self.memories.push(i.vmimport(store));
             }
             Extern::SharedMemory(i) => {
+                i.reserve_quota(store)?;
                 self.memories.push(i.vmimport(store));
             }
             Extern::Tag(i) => {
                 self.tags.push(i.vmimport(store));
             }
         }
+        Ok(())
     }
 
     /// Note that this is unsafe as the validity of `item` is not verified and
@@ -922,7 +1045,570 @@ impl<T> InstancePre<T> {
         // This unsafety should be handled by the type-checking performed by the
         // constructor of `InstancePre` to assert that all the imports we're passing
         // in match the module we're instantiating.
-        unsafe { Instance::new_started_async(&mut store, &self.module, imports.as_ref()).await }
+        unsafe {
+            Instance::new_started_async(&mut store, &self.module, imports.as_ref(), None).await
+        }
+    }
+
+    /// Performs the first half of instantiating this instance, allocating it
+    /// within `store` without applying its element and data segments or
+    /// running its start function.
+    ///
+    /// The returned [`AllocatedInstance`] can be used to inspect the data
+    /// segments of the module and to replace or defer selected segments
+    /// before finishing instantiation with
+    /// [`AllocatedInstance::initialize`]. Globals of the instance have
+    /// already been initialized at this point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any import closed over by this [`InstancePre`] isn't owned by
+    /// `store`, or if `store` has async support enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn main() -> anyhow::Result<()> {
+    /// let engine = Engine::default();
+    /// let module = Module::new(&engine, r#"
+    ///     (module
+    ///         (memory (export "memory") 1)
+    ///         (data (i32.const 0) "debug=0")
+    ///     )
+    /// "#)?;
+    /// let linker = Linker::new(&engine);
+    /// let pre = linker.instantiate_pre(&module)?;
+    ///
+    /// let mut store = Store::new(&engine, ());
+    /// let mut allocated = pre.allocate(&mut store)?;
+    /// allocated.set_data(0, b"debug=1".to_vec())?;
+    /// let instance = allocated.initialize(&mut store)?;
+    ///
+    /// let memory = instance.get_memory(&mut store, "memory").unwrap();
+    /// assert_eq!(&memory.data(&store)[..7], b"debug=1");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn allocate(&self, mut store: impl AsContextMut<Data = T>) -> Result<AllocatedInstance> {
+        let mut store = store.as_context_mut();
+        assert!(
+            !store.0.async_support(),
+            "must use async instantiation when async support is enabled",
+        );
+        let imports = pre_instantiate_raw(
+            &mut store.0,
+            &self.module,
+            &self.items,
+            self.host_funcs,
+            &self.func_refs,
+        )?;
+
+        // See `instantiate` for the unsafety here.
+        unsafe { Instance::new_allocated(store.0, &self.module, imports.as_ref()) }
+    }
+
+    /// Same as [`InstancePre::allocate`], but for stores with async support
+    /// enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any import closed over by this [`InstancePre`] isn't owned by
+    /// `store`, or if `store` does not have async support enabled.
+    #[cfg(feature = "async")]
+    pub async fn allocate_async(
+        &self,
+        mut store: impl AsContextMut<Data = T>,
+    ) -> Result<AllocatedInstance>
+    where
+        T: Send,
+    {
+        let mut store = store.as_context_mut();
+        assert!(
+            store.0.async_support(),
+            "must use sync instantiation when async support is disabled",
+        );
+        let imports = pre_instantiate_raw(
+            &mut store.0,
+            &self.module,
+            &self.items,
+            self.host_funcs,
+            &self.func_refs,
+        )?;
+        let imports = imports.as_ref();
+        let module = &self.module;
+
+        // See `instantiate` for the unsafety here. Allocation happens on a
+        // fiber as an async resource limiter may need to yield.
+        store
+            .on_fiber(|store| unsafe { Instance::new_allocated(store.0, module, imports) })
+            .await?
+    }
+
+    /// Instantiates this instance once in each of `stores`, returning the new
+    /// instances in the same order as the stores.
+    ///
+    /// This is intended for workloads which create many instances of the same
+    /// module at once, such as one per request fanned out to many workers.
+    /// Every store is checked before any instance is created, so if a store
+    /// comes from a different [`Engine`] or has async support enabled an error
+    /// is returned without instantiating anything. Room for all of the
+    /// instances is also reserved up front in the engine's instance allocator,
+    /// so with the pooling allocator a batch which would exceed its limit on
+    /// the number of core instances fails before any instance is created,
+    /// rather than partway through. Otherwise instances are created in order
+    /// as with [`InstancePre::instantiate`], and the first error that occurs,
+    /// such as a trap in a start function, is returned. Instances created
+    /// before the error remain within their stores.
+    ///
+    /// The buffers used to prepare the imports of each instance are reused
+    /// across the whole batch rather than allocated for every instance. With
+    /// the on-demand allocator and the default memory creator the linear
+    /// memories of the whole batch are also reserved up front, which on Unix
+    /// takes a single mapping for each memory of the module.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any import closed over by this [`InstancePre`] isn't owned by
+    /// the store being instantiated into.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn main() -> anyhow::Result<()> {
+    /// let engine = Engine::default();
+    /// let module = Module::new(&engine, r#"(module (global (export "g") i32 (i32.const 1)))"#)?;
+    /// let linker = Linker::new(&engine);
+    /// let pre = linker.instantiate_pre(&module)?;
+    ///
+    /// let mut stores = (0..100).map(|_| Store::new(&engine, ())).collect::<Vec<_>>();
+    /// let instances = pre.instantiate_batch(&mut stores)?;
+    /// assert_eq!(instances.len(), 100);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn instantiate_batch<S>(&self, stores: impl IntoIterator<Item = S>) -> Result<Vec<Instance>>
+    where
+        S: AsContextMut<Data = T>,
+    {
+        let mut stores = stores.into_iter().collect::<Vec<_>>();
+        for store in stores.iter_mut() {
+            let store = store.as_context_mut();
+            if store.0.async_support() {
+                bail!("must use async instantiation when async support is enabled");
+            }
+            if !Engine::same(store.engine(), self.module.engine()) {
+                bail!("cross-`Engine` instantiation is not currently supported");
+            }
+        }
+
+        let mut batch = self.reserve_batch(stores.len())?;
+        let mut imports = OwnedImports::empty();
+        let mut instances = Vec::with_capacity(stores.len());
+        for store in stores.iter_mut() {
+            let mut store = store.as_context_mut();
+            pre_instantiate_raw_into(
+                &mut imports,
+                &mut store.0,
+                &self.module,
+                &self.items,
+                self.host_funcs,
+                &self.func_refs,
+            )?;
+
+            // This unsafety should be handled by the type-checking performed
+            // by the constructor of `InstancePre` to assert that all the
+            // imports we're passing in match the module we're instantiating.
+            let instance = unsafe {
+                Instance::new_started_impl(
+                    &mut store,
+                    &self.module,
+                    imports.as_ref(),
+                    Some(&mut batch),
+                )?
+            };
+            instances.push(instance);
+        }
+        Ok(instances)
+    }
+
+    /// Reserves room for `count` instances in the engine's instance allocator
+    /// at once, so that a batch which would exceed the allocator's limits
+    /// fails before anything is instantiated.
+    ///
+    /// The returned batch is used up one instance at a time as the batch is
+    /// instantiated, and the rest of it is released when it's dropped.
+    fn reserve_batch(&self, count: usize) -> Result<InstanceBatch<'_>> {
+        let count = u32::try_from(count).context("too many stores in a batch")?;
+        let engine = self.module.engine();
+        InstanceBatch::new(
+            engine.allocator(),
+            self.module.env_module(),
+            engine.tunables(),
+            count,
+        )
+    }
+
+    /// Same as [`InstancePre::instantiate_batch`], but running start functions
+    /// asynchronously as with [`InstancePre::instantiate_async`].
+    ///
+    /// Instances are created one after another, not concurrently.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any import closed over by this [`InstancePre`] isn't owned by
+    /// the store being instantiated into.
+    #[cfg(feature = "async")]
+    pub async fn instantiate_batch_async<S>(
+        &self,
+        stores: impl IntoIterator<Item = S>,
+    ) -> Result<Vec<Instance>>
+    where
+        S: AsContextMut<Data = T>,
+        T: Send,
+    {
+        let mut stores = stores.into_iter().collect::<Vec<_>>();
+        for store in stores.iter_mut() {
+            let store = store.as_context_mut();
+            if !store.0.async_support() {
+                bail!("must use sync instantiation when async support is disabled");
+            }
+            if !Engine::same(store.engine(), self.module.engine()) {
+                bail!("cross-`Engine` instantiation is not currently supported");
+            }
+        }
+
+        // Note that if this future is dropped partway through then dropping
+        // `batch` releases the instances which haven't been created yet.
+        let mut batch = self.reserve_batch(stores.len())?;
+        let mut imports = OwnedImports::empty();
+        let mut instances = Vec::with_capacity(stores.len());
+        for store in stores.iter_mut() {
+            let mut store = store.as_context_mut();
+            pre_instantiate_raw_into(
+                &mut imports,
+                &mut store.0,
+                &self.module,
+                &self.items,
+                self.host_funcs,
+                &self.func_refs,
+            )?;
+
+            // See `instantiate_batch` for the unsafety here.
+            let instance = unsafe {
+                Instance::new_started_async(
+                    &mut store,
+                    &self.module,
+                    imports.as_ref(),
+                    Some(&mut batch),
+                )
+                .await?
+            };
+            instances.push(instance);
+        }
+        Ok(instances)
+    }
+}
+
+/// An instance which has been allocated, but not yet fully initialized.
+///
+/// This is created with [`InstancePre::allocate`] and represents the first
+/// half of instantiation: the instance's memories, tables and globals exist
+/// within the store but its element and data segments haven't been applied
+/// and its start function hasn't run. Before finishing instantiation with
+/// [`AllocatedInstance::initialize`] the embedder can change how data segments
+/// are applied:
+///
+/// * [`AllocatedInstance::set_data`] replaces the contents of a segment, for
+///   example to patch a configuration blob embedded in a module without
+///   recompiling it.
+/// * [`AllocatedInstance::defer_data`] applies a segment lazily, so that the
+///   pages of memory it covers are only populated once they're accessed.
+///
+/// This works the same whether or not the module's data segments were merged
+/// into an image of each memory at compile time, see
+/// [`Config::memory_init_cow`](crate::Config::memory_init_cow). Segments
+/// which are replaced are written over the image.
+///
+/// Dropping an `AllocatedInstance` without initializing it leaves the
+/// partially initialized instance within its store, as with an instantiation
+/// which fails partway through.
+pub struct AllocatedInstance {
+    instance: Instance,
+    module: Module,
+    segments: Vec<DataSegment>,
+    data: Vec<SegmentData>,
+}
+
+/// How an active data segment of an [`AllocatedInstance`] is applied.
+#[derive(Default)]
+struct SegmentData {
+    /// The contents replacing the module's own, from `set_data`.
+    replacement: Option<Vec<u8>>,
+    /// Whether the segment is applied lazily, from `defer_data`.
+    deferred: bool,
+}
+
+impl AllocatedInstance {
+    /// Returns the instance being initialized.
+    ///
+    /// The exports of the instance can already be accessed, but its tables
+    /// and memories don't have their segments applied yet.
+    pub fn instance(&self) -> Instance {
+        self.instance
+    }
+
+    /// Returns the module being instantiated.
+    pub fn module(&self) -> &Module {
+        &self.module
+    }
+
+    /// Returns the active data segments of the module, in the order they're
+    /// applied.
+    pub fn data_segments(&self) -> &[DataSegment] {
+        &self.segments
+    }
+
+    /// Replaces the contents of the active data segment `index` with `data`.
+    ///
+    /// The replacement must be the same length as the original segment.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `index` isn't an active data segment of the module
+    /// or if `data` is the wrong length.
+    pub fn set_data(&mut self, index: u32, data: impl Into<Vec<u8>>) -> Result<&mut Self> {
+        let data = data.into();
+        let i = self.segment_position(index)?;
+        let len = self.segments[i].data().len();
+        if data.len() != len {
+            bail!(
+                "data segment {index} is {len} bytes long but {} bytes were provided",
+                data.len()
+            );
+        }
+        self.data[i].replacement = Some(data);
+        Ok(self)
+    }
+
+    /// Applies the active data segment `index` lazily.
+    ///
+    /// Segments of shared memories can't be applied lazily, since other
+    /// threads may already be accessing them.
+    ///
+    /// Rather than being copied into memory during initialization, the host
+    /// pages entirely covered by the segment's contents, including contents
+    /// provided with [`AllocatedInstance::set_data`], are mapped
+    /// copy-on-write and only populated once they're first accessed. This
+    /// relies on the same support as
+    /// [`Config::memory_init_cow`](crate::Config::memory_init_cow) and on
+    /// memories created with an image. Otherwise, and for the parts of the
+    /// segment which don't cover whole pages, the contents are written during
+    /// initialization as usual.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `index` isn't an active data segment of the module
+    /// or if it's applied to a shared memory.
+    pub fn defer_data(&mut self, index: u32) -> Result<&mut Self> {
+        let i = self.segment_position(index)?;
+        if let Extern::SharedMemory(_) = self.segments[i].memory {
+            bail!("data segment {index} of a shared memory can't be applied lazily");
+        }
+        self.data[i].deferred = true;
+        Ok(self)
+    }
+
+    fn segment_position(&self, index: u32) -> Result<usize> {
+        match self
+            .segments
+            .iter()
+            .position(|segment| segment.index == index)
+        {
+            Some(i) => Ok(i),
+            None => bail!("module has no active data segment with index {index}"),
+        }
+    }
+
+    /// Returns the contents that segment `i` is applied with.
+    fn contents(&self, i: usize) -> &[u8] {
+        match &self.data[i].replacement {
+            Some(data) => data,
+            None => self.segments[i].data(),
+        }
+    }
+
+    /// Finishes instantiation, applying element and data segments and running
+    /// the module's start function, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this instance or if `store` has async
+    /// support enabled.
+    pub fn initialize(self, mut store: impl AsContextMut) -> Result<Instance> {
+        let mut store = store.as_context_mut();
+        assert!(
+            !store.0.async_support(),
+            "must use async instantiation when async support is enabled",
+        );
+        self.initialize_impl(&mut store)
+    }
+
+    /// Same as [`AllocatedInstance::initialize`], but for stores with async
+    /// support enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this instance or if `store` does not
+    /// have async support enabled.
+    #[cfg(feature = "async")]
+    pub async fn initialize_async<T>(
+        self,
+        mut store: impl AsContextMut<Data = T>,
+    ) -> Result<Instance>
+    where
+        T: Send,
+    {
+        let mut store = store.as_context_mut();
+        assert!(
+            store.0.async_support(),
+            "must use sync instantiation when async support is disabled",
+        );
+        store.on_fiber(|store| self.initialize_impl(store)).await?
+    }
+
+    fn initialize_impl<T>(self, store: &mut StoreContextMut<'_, T>) -> Result<Instance> {
+        let id = store.0.store_data()[self.instance.0].id;
+        let mut instance_handle = unsafe { store.0.instance(id).clone() };
+        let module = self.module.compiled_module().module();
+
+        // Segments the embedder changed are left out of the module's own
+        // initialization and applied afterwards. Images of memories already
+        // contain the module's contents for every segment, so only replaced
+        // segments need to be written over them.
+        let segmented = module.memory_initialization.is_segmented();
+        let changed = self
+            .data
+            .iter()
+            .map(|data| data.replacement.is_some() || (segmented && data.deferred))
+            .collect::<Vec<_>>();
+        let skip = (segmented && changed.contains(&true)).then_some(&changed[..]);
+
+        let bulk_memory = store
+            .engine()
+            .features()
+            .contains(WasmFeatures::BULK_MEMORY);
+        instance_handle.initialize_segments(store.0, module, bulk_memory, skip)?;
+
+        for (i, changed) in changed.iter().enumerate() {
+            if *changed {
+                self.apply_segment(store, i)?;
+            }
+        }
+
+        if let Some(start) = module.start_func {
+            self.instance.start_raw(store, start)?;
+        }
+        Ok(self.instance)
+    }
+
+    /// Writes the contents of segment `i` to memory, along with those of any
+    /// later segments overlapping it, so that its range of memory ends up as
+    /// if all segments were applied in order.
+    fn apply_segment<T>(&self, store: &mut StoreContextMut<'_, T>, i: usize) -> Result<()> {
+        let segment = &self.segments[i];
+        let mut contents = self.contents(i).to_vec();
+        let start = segment.offset;
+        let end = start + u64::try_from(contents.len()).unwrap();
+        for (j, later) in self.segments.iter().enumerate().skip(i + 1) {
+            if later.memory_index != segment.memory_index {
+                continue;
+            }
+            let later_contents = self.contents(j);
+            let later_end = later.offset + u64::try_from(later_contents.len()).unwrap();
+            let overlap = later.offset.max(start)..later_end.min(end);
+            if overlap.start >= overlap.end {
+                continue;
+            }
+            let dst = usize::try_from(overlap.start - start).unwrap();
+            let src = usize::try_from(overlap.start - later.offset).unwrap();
+            let len = usize::try_from(overlap.end - overlap.start).unwrap();
+            contents[dst..dst + len].copy_from_slice(&later_contents[src..src + len]);
+        }
+
+        let offset = usize::try_from(start).unwrap();
+        match &segment.memory {
+            Extern::Memory(memory) if self.data[i].deferred => {
+                memory.write_lazily(store, offset, &contents)
+            }
+            Extern::Memory(memory) => {
+                let memory = memory.data_mut(store);
+                memory[offset..offset + contents.len()].copy_from_slice(&contents);
+                Ok(())
+            }
+            // Other threads may already be accessing a shared memory, so it's
+            // written with atomic stores rather than through a `&mut [u8]`.
+            Extern::SharedMemory(memory) => {
+                let memory = &memory.data()[offset..offset + contents.len()];
+                for (cell, byte) in memory.iter().zip(&contents) {
+                    let cell = unsafe { &*cell.get().cast::<core::sync::atomic::AtomicU8>() };
+                    cell.store(*byte, core::sync::atomic::Ordering::Relaxed);
+                }
+                Ok(())
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// An active data segment of a module, as found through
+/// [`AllocatedInstance::data_segments`].
+#[derive(Clone)]
+pub struct DataSegment {
+    index: u32,
+    memory: Extern,
+    memory_index: MemoryIndex,
+    offset: u64,
+    module: Module,
+    data: Range<u32>,
+}
+
+impl DataSegment {
+    /// Returns the index of this segment within the module's data segments.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Returns the memory that this segment is applied to, which is either an
+    /// [`Extern::Memory`] or, for shared memories, an
+    /// [`Extern::SharedMemory`].
+    pub fn memory(&self) -> Extern {
+        self.memory.clone()
+    }
+
+    /// Returns the offset within [`DataSegment::memory`] that this segment is
+    /// applied at.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the contents of this segment as defined by the module.
+    pub fn data(&self) -> &[u8] {
+        let start = usize::try_from(self.data.start).unwrap();
+        let end = usize::try_from(self.data.end).unwrap();
+        &self.module.compiled_module().code_memory().wasm_data()[start..end]
+    }
+}
+
+impl core::fmt::Debug for DataSegment {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DataSegment")
+            .field("index", &self.index)
+            .field("memory", &self.memory)
+            .field("offset", &self.offset)
+            .field("len", &self.data.len())
+            .finish()
     }
 }
 
@@ -939,6 +1625,21 @@ fn pre_instantiate_raw(
     host_funcs: usize,
     func_refs: &Arc<[VMFuncRef]>,
 ) -> Result<OwnedImports> {
+    let mut imports = OwnedImports::empty();
+    pre_instantiate_raw_into(&mut imports, store, module, items, host_funcs, func_refs)?;
+    Ok(imports)
+}
+
+/// Same as `pre_instantiate_raw`, but reusing the allocations of `imports`,
+/// which is cleared first, as done by `InstancePre::instantiate_batch`.
+fn pre_instantiate_raw_into(
+    imports: &mut OwnedImports,
+    store: &mut StoreOpaque,
+    module: &Module,
+    items: &Arc<[Definition]>,
+    host_funcs: usize,
+    func_refs: &Arc<[VMFuncRef]>,
+) -> Result<()> {
     if host_funcs > 0 {
         // Any linker-defined function of the `Definition::HostFunc` variant
         // will insert a function into the store automatically as part of
@@ -955,7 +1656,8 @@ fn pre_instantiate_raw(
     }
 
     let mut func_refs = func_refs.iter().map(|f| NonNull::from(f));
-    let mut imports = OwnedImports::new(module);
+    imports.clear();
+    imports.reserve(module);
     for import in items.iter() {
         if !import.comes_from_same_store(store) {
             bail!("cross-`Store` instantiation is not currently supported");
@@ -978,16 +1680,24 @@ fn pre_instantiate_raw(
                 .into()
             },
         };
-        imports.push(&item, store, module);
+        imports.push(&item, store, module)?;
     }
 
-    Ok(imports)
+    Ok(())
 }
 
 fn typecheck<I>(
     module: &Module,
     import_args: &[I],
     check: impl Fn(&matching::MatchCx<'_>, &EntityType, &I) -> Result<()>,
+) -> Result<()> {
+    typecheck_imports(module, import_args, check).map_err(|e| crate::ErrorKind::Link.tag(e))
+}
+
+fn typecheck_imports<I>(
+    module: &Module,
+    import_args: &[I],
+    check: impl Fn(&matching::MatchCx<'_>, &EntityType, &I) -> Result<()>,
 ) -> Result<()> {
     let env_module = module.compiled_module().module();
     let expected_len = env_module.imports().count();
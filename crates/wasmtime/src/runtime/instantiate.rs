@@ -285,6 +285,20 @@ impl CompiledModule {
         self.meta.has_unparsed_debuginfo
     }
 
+    /// Returns the statically-determined maximum native stack size of a call
+    /// into this module, if it could be bounded.
+    pub fn max_stack_size(&self) -> Option<u32> {
+        self.meta.max_stack_size
+    }
+
+    /// Returns the WebAssembly proposals found to be used by this module when
+    /// it was translated.
+    pub fn required_features(&self) -> wasmparser::WasmFeatures {
+        wasmparser::WasmFeatures::from_bits_truncate(
+            self.meta.required_features.try_into().unwrap(),
+        )
+    }
+
     /// Indicates whether this module came with n address map such that lookups
     /// via `wasmtime_environ::lookup_file_pos` will succeed.
     ///
@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use core::mem;
 
 /// Value returned by [`ResourceLimiter::instances`] default method
 pub const DEFAULT_INSTANCE_LIMIT: usize = 10000;
@@ -125,6 +126,55 @@ pub trait ResourceLimiter {
         Ok(())
     }
 
+    /// Notifies the resource limiter that the store's GC heap has been
+    /// requested to grow.
+    ///
+    /// * `current` is the current size of the GC heap in bytes.
+    /// * `desired` is the desired size of the GC heap in bytes.
+    /// * `maximum` is the maximum size of the GC heap in bytes, if any.
+    ///
+    /// Currently a store's GC heap is allocated at a fixed size the first time
+    /// that it's needed, so this is invoked at most once per store with a
+    /// `current` size of zero.
+    ///
+    /// If `Ok(false)` is returned then the allocation of the GC object which
+    /// required the GC heap fails with an error, which is raised as a trap if
+    /// it was requested by WebAssembly. Otherwise see the details on the
+    /// return values for `memory_growing`.
+    ///
+    /// This defaults to permitting all growth.
+    fn gc_heap_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        maximum: Option<usize>,
+    ) -> Result<bool> {
+        let _ = (current, desired, maximum);
+        Ok(true)
+    }
+
+    /// Notifies the resource limiter that a stack has been requested for a
+    /// new continuation, with the `cont.new` instruction.
+    ///
+    /// * `current` is the total size in bytes of the stacks of the store's
+    ///   live continuations.
+    /// * `desired` is that total after the new stack has been allocated.
+    ///
+    /// Each stack is counted at its full size,
+    /// [`Config::wasmfx_stack_size`](crate::Config::wasmfx_stack_size), even
+    /// if it starts out smaller. A continuation's stack is released once the
+    /// continuation returns, and stacks are counted whether they're freshly
+    /// allocated or reused from a pool or cache.
+    ///
+    /// If `Ok(false)` is returned then `cont.new` traps. Otherwise see the
+    /// details on the return values for `memory_growing`.
+    ///
+    /// This defaults to permitting all allocations.
+    fn continuation_stack_allocating(&mut self, current: usize, desired: usize) -> Result<bool> {
+        let _ = (current, desired);
+        Ok(true)
+    }
+
     /// The maximum number of instances that can be created for a `Store`.
     ///
     /// Module instantiation will fail if this limit is exceeded.
@@ -201,6 +251,26 @@ pub trait ResourceLimiterAsync {
         Ok(())
     }
 
+    /// Identical to [`ResourceLimiter::gc_heap_growing`]
+    ///
+    /// This isn't `async` because GC heaps may be allocated outside of any
+    /// asynchronous WebAssembly call.
+    fn gc_heap_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        maximum: Option<usize>,
+    ) -> Result<bool> {
+        let _ = (current, desired, maximum);
+        Ok(true)
+    }
+
+    /// Identical to [`ResourceLimiter::continuation_stack_allocating`]
+    fn continuation_stack_allocating(&mut self, current: usize, desired: usize) -> Result<bool> {
+        let _ = (current, desired);
+        Ok(true)
+    }
+
     /// Identical to [`ResourceLimiter::instances`]`
     fn instances(&self) -> usize {
         DEFAULT_INSTANCE_LIMIT
@@ -254,6 +324,26 @@ impl StoreLimitsBuilder {
         self
     }
 
+    /// The maximum number of bytes that a store's linear memories, tables, GC
+    /// heap, and continuation stacks can consume in total.
+    ///
+    /// Growing a linear memory, table, or GC heap or creating a continuation
+    /// beyond this limit will fail. Unlike [`StoreLimitsBuilder::memory_size`]
+    /// this limit applies to the store as a whole, so it can be used to give
+    /// each guest a single memory budget. Table elements are counted as a
+    /// pointer's worth of bytes each, see
+    /// [`ResourceLimiter::table_growing`].
+    ///
+    /// Note that bytes are counted when they're requested from the limiter,
+    /// so [`Store::memory_consumed`](crate::Store::memory_consumed) may
+    /// report a slightly different figure.
+    ///
+    /// By default, total memory consumption will not be limited.
+    pub fn total_memory_size(mut self, limit: usize) -> Self {
+        self.0.total_memory_size = Some(limit);
+        self
+    }
+
     /// The maximum number of instances that can be created for a [`Store`](crate::Store).
     ///
     /// Module instantiation will fail if this limit is exceeded.
@@ -316,10 +406,34 @@ impl StoreLimitsBuilder {
 pub struct StoreLimits {
     memory_size: Option<usize>,
     table_elements: Option<usize>,
+    total_memory_size: Option<usize>,
     instances: usize,
     tables: usize,
     memories: usize,
     trap_on_grow_failure: bool,
+    consumed: Consumed,
+}
+
+/// The bytes counted towards [`StoreLimitsBuilder::total_memory_size`].
+#[derive(Clone, Debug, Default)]
+struct Consumed {
+    memories: usize,
+    tables: usize,
+    gc_heap: usize,
+    continuation_stacks: usize,
+    /// The most recent growth of a memory or table, which is undone if the
+    /// growth fails.
+    last_memory_growth: usize,
+    last_table_growth: usize,
+}
+
+impl Consumed {
+    fn total(&self) -> usize {
+        self.memories
+            .saturating_add(self.tables)
+            .saturating_add(self.gc_heap)
+            .saturating_add(self.continuation_stacks)
+    }
 }
 
 impl Default for StoreLimits {
@@ -327,10 +441,30 @@ impl Default for StoreLimits {
         Self {
             memory_size: None,
             table_elements: None,
+            total_memory_size: None,
             instances: DEFAULT_INSTANCE_LIMIT,
             tables: DEFAULT_TABLE_LIMIT,
             memories: DEFAULT_MEMORY_LIMIT,
             trap_on_grow_failure: false,
+            consumed: Consumed::default(),
+        }
+    }
+}
+
+impl StoreLimits {
+    /// Returns whether `bytes` more can be consumed within the total limit.
+    fn fits_in_total(&self, bytes: usize) -> bool {
+        match self.total_memory_size {
+            Some(limit) => self.consumed.total().saturating_add(bytes) <= limit,
+            None => true,
+        }
+    }
+
+    fn deny(&self, what: &str) -> Result<bool> {
+        if self.trap_on_grow_failure {
+            bail!("forcing trap when {what}")
+        } else {
+            Ok(false)
         }
     }
 }
@@ -338,25 +472,28 @@ impl Default for StoreLimits {
 impl ResourceLimiter for StoreLimits {
     fn memory_growing(
         &mut self,
-        _current: usize,
+        current: usize,
         desired: usize,
         maximum: Option<usize>,
     ) -> Result<bool> {
+        let growth = desired.saturating_sub(current);
         let allow = match self.memory_size {
             Some(limit) if desired > limit => false,
             _ => match maximum {
                 Some(max) if desired > max => false,
-                _ => true,
+                _ => self.fits_in_total(growth),
             },
         };
-        if !allow && self.trap_on_grow_failure {
-            bail!("forcing trap when growing memory to {desired} bytes")
-        } else {
-            Ok(allow)
+        if !allow {
+            return self.deny(&format!("growing memory to {desired} bytes"));
         }
+        self.consumed.memories += growth;
+        self.consumed.last_memory_growth = growth;
+        Ok(true)
     }
 
     fn memory_grow_failed(&mut self, error: anyhow::Error) -> Result<()> {
+        self.consumed.memories -= mem::take(&mut self.consumed.last_memory_growth);
         if self.trap_on_grow_failure {
             Err(error.context("forcing a memory growth failure to be a trap"))
         } else {
@@ -367,25 +504,30 @@ impl ResourceLimiter for StoreLimits {
 
     fn table_growing(
         &mut self,
-        _current: usize,
+        current: usize,
         desired: usize,
         maximum: Option<usize>,
     ) -> Result<bool> {
+        let growth = desired
+            .saturating_sub(current)
+            .saturating_mul(mem::size_of::<usize>());
         let allow = match self.table_elements {
             Some(limit) if desired > limit => false,
             _ => match maximum {
                 Some(max) if desired > max => false,
-                _ => true,
+                _ => self.fits_in_total(growth),
             },
         };
-        if !allow && self.trap_on_grow_failure {
-            bail!("forcing trap when growing table to {desired} elements")
-        } else {
-            Ok(allow)
+        if !allow {
+            return self.deny(&format!("growing table to {desired} elements"));
         }
+        self.consumed.tables += growth;
+        self.consumed.last_table_growth = growth;
+        Ok(true)
     }
 
     fn table_grow_failed(&mut self, error: anyhow::Error) -> Result<()> {
+        self.consumed.tables -= mem::take(&mut self.consumed.last_table_growth);
         if self.trap_on_grow_failure {
             Err(error.context("forcing a table growth failure to be a trap"))
         } else {
@@ -394,6 +536,37 @@ impl ResourceLimiter for StoreLimits {
         }
     }
 
+    fn gc_heap_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        maximum: Option<usize>,
+    ) -> Result<bool> {
+        let growth = desired.saturating_sub(current);
+        let allow = match maximum {
+            Some(max) if desired > max => false,
+            _ => self.fits_in_total(growth),
+        };
+        if !allow {
+            return self.deny(&format!("growing the GC heap to {desired} bytes"));
+        }
+        self.consumed.gc_heap += growth;
+        Ok(true)
+    }
+
+    fn continuation_stack_allocating(&mut self, current: usize, desired: usize) -> Result<bool> {
+        // `current` accounts for stacks released since the last allocation.
+        self.consumed.continuation_stacks = current;
+        let growth = desired.saturating_sub(current);
+        if !self.fits_in_total(growth) {
+            return self.deny(&format!(
+                "allocating continuation stacks totalling {desired} bytes"
+            ));
+        }
+        self.consumed.continuation_stacks = desired;
+        Ok(true)
+    }
+
     fn instances(&self) -> usize {
         self.instances
     }
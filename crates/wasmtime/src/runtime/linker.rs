@@ -2,10 +2,13 @@ use crate::func::HostFunc;
 use crate::hash_map::{Entry, HashMap};
 use crate::instance::InstancePre;
 use crate::store::StoreOpaque;
+use crate::types::matching::MatchCx;
+use crate::{continuation::TagHandler, Continuation, Resumed, Tag};
 use crate::{prelude::*, IntoFunc};
 use crate::{
-    AsContext, AsContextMut, Caller, Engine, Extern, ExternType, Func, FuncType, ImportType,
-    Instance, Module, StoreContextMut, Val, ValRaw, ValType, WasmTyList,
+    AsContext, AsContextMut, Caller, Engine, ErrnoRet, ErrorPolicy, Extern, ExternType, Func,
+    FuncType, GlobalType, HostError, HostcallBatch, ImportType, Instance, MemoryType, Module,
+    StoreContextMut, TableType, TagType, Val, ValRaw, ValType, WasmTyList,
 };
 use alloc::sync::Arc;
 use core::fmt;
@@ -14,6 +17,8 @@ use core::future::Future;
 use core::marker;
 #[cfg(feature = "async")]
 use core::pin::Pin;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
 use log::warn;
 
 /// Structure used to link wasm modules/instances together.
@@ -89,6 +94,9 @@ pub struct Linker<T> {
     map: HashMap<ImportKey, Definition>,
     allow_shadowing: bool,
     allow_unknown_exports: bool,
+    strict_imports: bool,
+    error_policies: HashMap<usize, ErrorPolicy>,
+    tag_handlers: Vec<(Tag, Arc<TagHandler<T>>)>,
     _marker: marker::PhantomData<fn() -> T>,
 }
 
@@ -101,6 +109,9 @@ impl<T> Clone for Linker<T> {
             map: self.map.clone(),
             allow_shadowing: self.allow_shadowing,
             allow_unknown_exports: self.allow_unknown_exports,
+            strict_imports: self.strict_imports,
+            error_policies: self.error_policies.clone(),
+            tag_handlers: self.tag_handlers.clone(),
             _marker: self._marker,
         }
     }
@@ -149,6 +160,9 @@ impl<T> Linker<T> {
             strings: Vec::new(),
             allow_shadowing: false,
             allow_unknown_exports: false,
+            strict_imports: false,
+            error_policies: HashMap::new(),
+            tag_handlers: Vec::new(),
             _marker: marker::PhantomData,
         }
     }
@@ -215,6 +229,64 @@ impl<T> Linker<T> {
         self
     }
 
+    /// Configures whether this [`Linker`] checks all imports of a module
+    /// before reporting any of them as unresolvable.
+    ///
+    /// By default [`Linker::instantiate`] and related methods fail on the
+    /// first import of a module which isn't defined or whose definition has
+    /// an incompatible type. With strict imports enabled they instead check
+    /// every import and fail with an [`ImportErrors`] describing all of the
+    /// problems, including the expected and provided types of each mis-typed
+    /// import.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn main() -> anyhow::Result<()> {
+    /// # let engine = Engine::default();
+    /// # let mut store = Store::new(&engine, ());
+    /// let mut linker = Linker::new(&engine);
+    /// linker.strict_imports(true);
+    /// linker.func_wrap("host", "double", |x: i64| x * 2)?;
+    ///
+    /// let wat = r#"
+    ///     (module
+    ///         (import "host" "double" (func (param i32) (result i32)))
+    ///         (import "host" "log" (func (param i32)))
+    ///     )
+    /// "#;
+    /// let module = Module::new(&engine, wat)?;
+    /// let err = linker.instantiate(&mut store, &module).unwrap_err();
+    /// let errors = err.downcast_ref::<ImportErrors>().unwrap();
+    /// assert_eq!(errors.errors().len(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn strict_imports(&mut self, strict: bool) -> &mut Self {
+        self.strict_imports = strict;
+        self
+    }
+
+    /// Configures how errors of functions defined in `module` with
+    /// [`Linker::func_wrap_fallible`] are reported to wasm.
+    ///
+    /// This only affects functions defined after the policy is configured.
+    /// By default errors trap, as with [`ErrorPolicy::Trap`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for [`ErrorPolicy::Result`], which is only supported by
+    /// component linkers.
+    pub fn error_policy(&mut self, module: &str, policy: ErrorPolicy) -> Result<&mut Self> {
+        if policy == ErrorPolicy::Result {
+            bail!("`ErrorPolicy::Result` is only supported for components");
+        }
+        let module = self.intern_str(module);
+        self.error_policies.insert(module, policy);
+        Ok(self)
+    }
+
     /// Implement any imports of the given [`Module`] with a function which traps.
     ///
     /// By default a [`Linker`] will error when unknown imports are encountered
@@ -249,6 +321,50 @@ impl<T> Linker<T> {
         Ok(())
     }
 
+    /// Implement any function imports of the given [`Module`] with stubs
+    /// which are resolved lazily, per-store, the first time they're called.
+    ///
+    /// When a stub is first called within a [`Store`](crate::Store) it invokes
+    /// the store's [`Store::import_resolver`](crate::Store::import_resolver)
+    /// to get the function which implements the
+    /// import. The calling instance's import is then patched to call that
+    /// function directly, so only the first call pays the cost of resolution.
+    /// This allows modules with very large import surfaces, such as emulated
+    /// system call tables, to be instantiated without defining every import
+    /// up front.
+    ///
+    /// Calling a stub in a store without an import resolver traps, as does
+    /// calling it in a store with async support enabled.
+    ///
+    /// See [`Store::import_resolver`](crate::Store::import_resolver) for an
+    /// example.
+    pub fn define_unknown_imports_as_lazy(&mut self, module: &Module) -> anyhow::Result<()> {
+        for import in module.imports() {
+            if let Err(import_err) = self._get_by_import(&import) {
+                if let ExternType::Func(func_ty) = import_err.ty() {
+                    let module = import.module().to_string();
+                    let name = import.name().to_string();
+                    let ty = func_ty.clone();
+                    // The stub needs to know its own `vmctx` to recognize
+                    // itself in its caller's imports, which is only known
+                    // once it's been created.
+                    let stub = Arc::new(AtomicPtr::new(ptr::null_mut()));
+                    let func = HostFunc::new(&self.engine, func_ty, {
+                        let stub = stub.clone();
+                        move |mut caller: Caller<'_, T>, params: &[Val], results: &mut [Val]| {
+                            let stub = stub.load(Ordering::Relaxed);
+                            caller.call_lazy_import(stub, &module, &name, &ty, params, results)
+                        }
+                    });
+                    stub.store(func.func_ref().vmctx, Ordering::Relaxed);
+                    let key = self.import_key(import.module(), Some(import.name()));
+                    self.insert(key, Definition::HostFunc(Arc::new(func)))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Implement any function imports of the [`Module`] with a function that
     /// ignores its arguments and returns default values.
     ///
@@ -361,6 +477,35 @@ impl<T> Linker<T> {
         Ok(self)
     }
 
+    /// Same as [`Linker::define`], except that an existing definition of
+    /// `module::name` is always an error, even if
+    /// [shadowing](Linker::allow_shadowing) is allowed.
+    ///
+    /// The error describes both the existing definition and `item`, which
+    /// helps to track down which of several sources of definitions, such as
+    /// modules added with [`Linker::module`], provide the same names.
+    pub fn define_checked(
+        &mut self,
+        store: impl AsContext<Data = T>,
+        module: &str,
+        name: &str,
+        item: impl Into<Extern>,
+    ) -> Result<&mut Self> {
+        let store = store.as_context();
+        let item = Definition::new(store.0, item.into());
+        if let Some(existing) = self._get(module, name) {
+            return Err(crate::ErrorKind::Link.tag(format_err!(
+                "import of `{module}::{name}` defined twice: already defined as {}, \
+                 now defined as {}",
+                existing.ty().describe(&self.engine),
+                item.ty().describe(&self.engine),
+            )));
+        }
+        let key = self.import_key(module, Some(name));
+        self.insert(key, item)?;
+        Ok(self)
+    }
+
     /// Same as [`Linker::define`], except only the name of the import is
     /// provided, not a module name as well.
     ///
@@ -542,6 +687,113 @@ impl<T> Linker<T> {
         Ok(self)
     }
 
+    /// Define a host function returning a domain-specific error within this
+    /// linker.
+    ///
+    /// This is like [`Linker::func_wrap`] except that `func` returns a
+    /// `Result<R, E>` whose errors are reported to wasm according to the
+    /// [`ErrorPolicy`] of `module`, see [`Linker::error_policy`]. With
+    /// [`ErrorPolicy::Trap`] the wasm function has the results of `R` and an
+    /// error traps, while with [`ErrorPolicy::ErrorCode`] its result combines
+    /// `R` with the error's code as described by [`ErrnoRet`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in the same situations as [`Linker::func_wrap`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// #[derive(Debug)]
+    /// struct NotFound;
+    ///
+    /// impl std::fmt::Display for NotFound {
+    ///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    ///         f.write_str("not found")
+    ///     }
+    /// }
+    ///
+    /// impl std::error::Error for NotFound {}
+    ///
+    /// impl HostError for NotFound {
+    ///     fn code(&self) -> i32 {
+    ///         44
+    ///     }
+    /// }
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// # let engine = Engine::default();
+    /// let lookup = |_caller: Caller<'_, ()>, (key,): (i32,)| -> Result<i32, NotFound> {
+    ///     if key == 1 { Ok(100) } else { Err(NotFound) }
+    /// };
+    ///
+    /// let mut linker = Linker::new(&engine);
+    /// linker.error_policy("errno", ErrorPolicy::ErrorCode)?;
+    /// linker.func_wrap_fallible("errno", "lookup", lookup)?;
+    /// linker.func_wrap_fallible("trap", "lookup", lookup)?;
+    ///
+    /// let wat = r#"
+    ///     (module
+    ///         (import "errno" "lookup" (func $errno (param i32) (result i32)))
+    ///         (import "trap" "lookup" (func $trap (param i32) (result i32)))
+    ///         (func (export "errno") (param i32) (result i32)
+    ///             (call $errno (local.get 0)))
+    ///         (func (export "trap") (param i32) (result i32)
+    ///             (call $trap (local.get 0)))
+    ///     )
+    /// "#;
+    /// let module = Module::new(&engine, wat)?;
+    /// let mut store = Store::new(&engine, ());
+    /// let instance = linker.instantiate(&mut store, &module)?;
+    ///
+    /// let errno = instance.get_typed_func::<i32, i32>(&mut store, "errno")?;
+    /// assert_eq!(errno.call(&mut store, 1)?, 100);
+    /// assert_eq!(errno.call(&mut store, 2)?, -44);
+    ///
+    /// let trap = instance.get_typed_func::<i32, i32>(&mut store, "trap")?;
+    /// let err = trap.call(&mut store, 2).unwrap_err();
+    /// assert!(err.downcast_ref::<NotFound>().is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn func_wrap_fallible<F, Params, R, E>(
+        &mut self,
+        module: &str,
+        name: &str,
+        func: F,
+    ) -> Result<&mut Self>
+    where
+        F: Fn(Caller<'_, T>, Params) -> Result<R, E> + Send + Sync + 'static,
+        Params: WasmTyList,
+        R: ErrnoRet,
+        E: HostError,
+    {
+        let key = self.import_key(module, Some(name));
+        let policy = self
+            .error_policies
+            .get(&key.module)
+            .copied()
+            .unwrap_or_default();
+        let func = match policy {
+            ErrorPolicy::Trap => {
+                HostFunc::wrap_inner(&self.engine, move |caller: Caller<'_, T>, args| {
+                    func(caller, args).map_err(Error::new)
+                })
+            }
+            ErrorPolicy::ErrorCode => HostFunc::wrap_inner(
+                &self.engine,
+                move |caller: Caller<'_, T>, args| match func(caller, args) {
+                    Ok(ret) => ret.ok(),
+                    Err(e) => R::err(e.code()),
+                },
+            ),
+            ErrorPolicy::Result => unreachable!(),
+        };
+        self.insert(key, Definition::HostFunc(Arc::new(func)))?;
+        Ok(self)
+    }
+
     /// Asynchronous analog of [`Linker::func_wrap`].
     #[cfg(feature = "async")]
     pub fn func_wrap_async<F, Params: WasmTyList, Args: crate::WasmRet>(
@@ -581,6 +833,266 @@ impl<T> Linker<T> {
         Ok(self)
     }
 
+    /// Defines a function in this linker for each host intrinsic configured
+    /// for this linker's engine.
+    ///
+    /// Each function is defined with the module and name that the intrinsic
+    /// was registered with in [`Config::host_intrinsic`] and invokes the
+    /// intrinsic. This ensures that uses of an intrinsic's import which aren't
+    /// compiled to direct calls behave the same as those which are.
+    ///
+    /// [`Config::host_intrinsic`]: crate::Config::host_intrinsic
+    pub fn define_host_intrinsics(&mut self) -> Result<&mut Self> {
+        let engine = self.engine.clone();
+        for (module, name, intrinsic) in engine.config().host_intrinsics.iter() {
+            let ty = intrinsic.ty();
+            let ty = FuncType::new(
+                &engine,
+                ty.params()
+                    .iter()
+                    .map(|t| ValType::from_wasm_type(&engine, t)),
+                ty.returns()
+                    .iter()
+                    .map(|t| ValType::from_wasm_type(&engine, t)),
+            );
+            let intrinsic = intrinsic.clone();
+            self.func_new(module, name, ty, move |_caller, params, results| {
+                intrinsic.call(params, results);
+                Ok(())
+            })?;
+        }
+        Ok(self)
+    }
+
+    /// Defines the `wasmtime:runtime/alloc-stats` interface in this linker,
+    /// which lets guests query the [`MemoryGrowthStats`] of their store.
+    ///
+    /// The interface consists of the functions `grows`, `denied`, `failed`,
+    /// and `grown-bytes`, each of type `(func (result i64))` and returning the
+    /// corresponding field of [`Store::memory_growth_stats`]. Embedders can
+    /// observe the same statistics, and each individual growth, without
+    /// defining this interface, see [`Store::on_memory_grow`].
+    ///
+    /// [`MemoryGrowthStats`]: crate::MemoryGrowthStats
+    /// [`Store::memory_growth_stats`]: crate::Store::memory_growth_stats
+    /// [`Store::on_memory_grow`]: crate::Store::on_memory_grow
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the functions are already defined and
+    /// shadowing is disallowed.
+    pub fn define_alloc_stats(&mut self) -> Result<&mut Self>
+    where
+        T: 'static,
+    {
+        const MODULE: &str = "wasmtime:runtime/alloc-stats";
+        fn stat<T>(caller: &Caller<'_, T>, field: fn(crate::MemoryGrowthStats) -> u64) -> i64 {
+            field(caller.as_context().0.memory_growth_stats()) as i64
+        }
+        self.func_wrap(MODULE, "grows", |caller: Caller<'_, T>| {
+            stat(&caller, |s| s.grows)
+        })?;
+        self.func_wrap(MODULE, "denied", |caller: Caller<'_, T>| {
+            stat(&caller, |s| s.denied)
+        })?;
+        self.func_wrap(MODULE, "failed", |caller: Caller<'_, T>| {
+            stat(&caller, |s| s.failed)
+        })?;
+        self.func_wrap(MODULE, "grown-bytes", |caller: Caller<'_, T>| {
+            stat(&caller, |s| s.grown_bytes)
+        })?;
+        Ok(self)
+    }
+
+    /// Defines the `wasmtime:runtime/batch` interface in this linker, which
+    /// lets guests submit the operations of `batch` in batches.
+    ///
+    /// The interface consists of the function `submit`, of type
+    /// `(func (param i32) (result i32))`, which processes the operations
+    /// queued in the ring at the given address in the guest's memory. See
+    /// [`HostcallBatch`] for the layout of the ring.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `submit` is already defined and shadowing is
+    /// disallowed.
+    pub fn define_hostcall_batch(&mut self, batch: &HostcallBatch<T>) -> Result<&mut Self>
+    where
+        T: 'static,
+    {
+        let batch = batch.clone();
+        self.func_wrap(
+            "wasmtime:runtime/batch",
+            "submit",
+            move |mut caller: Caller<'_, T>, ring: u32| batch.submit(&mut caller, ring),
+        )?;
+        Ok(self)
+    }
+
+    /// Registers `handler` to be invoked when a continuation suspends to
+    /// `tag`.
+    ///
+    /// Handlers apply to continuations created with [`Linker::continuation`]
+    /// and resumed with [`Linker::resume`]. When such a continuation suspends
+    /// to `tag`, and no handler for `tag` is installed by a `resume` in wasm,
+    /// the suspension reaches the host and the handler is given the suspended
+    /// [`Continuation`] along with the payload of the suspension. The
+    /// continuation is owned by the store, and the handler may resume it
+    /// right away or keep it, for example in the store's data, to resume it
+    /// later. This allows implementing schedulers, async I/O, and other effect
+    /// handlers entirely on the host, with guests importing `tag` and
+    /// suspending to it as usual.
+    ///
+    /// Note that like [`Linker::define`] with store-owned items, registering a
+    /// handler ties this linker to the store that `tag` belongs to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a handler for `tag` is already registered and
+    /// shadowing is disallowed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tag`, or the tag of any handler registered previously, does
+    /// not belong to `store`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut config = Config::new();
+    /// config.wasm_stack_switching(true);
+    /// let engine = Engine::new(&config)?;
+    /// let mut store = Store::new(&engine, Vec::new());
+    ///
+    /// let ty = TagType::new(FuncType::new(&engine, [ValType::I32], []));
+    /// let tag = Tag::new(&mut store, &ty)?;
+    ///
+    /// let mut linker = Linker::new(&engine);
+    /// linker.define(&store, "host", "yield", tag)?;
+    /// linker.tag_handler(&store, tag, |mut store, continuation, payload| {
+    ///     store.data_mut().push((continuation, payload[0].unwrap_i32()));
+    ///     Ok(())
+    /// })?;
+    ///
+    /// let module = Module::new(
+    ///     &engine,
+    ///     r#"
+    ///         (module
+    ///             (import "host" "yield" (tag $yield (param i32)))
+    ///             (func (export "run")
+    ///                 (suspend $yield (i32.const 1))
+    ///                 (suspend $yield (i32.const 2)))
+    ///         )
+    ///     "#,
+    /// )?;
+    /// let instance = linker.instantiate(&mut store, &module)?;
+    /// let run = instance.get_func(&mut store, "run").unwrap();
+    ///
+    /// let continuation = linker.continuation(&mut store, &run)?;
+    /// assert!(linker.resume(&mut store, continuation, &[])?.is_none());
+    ///
+    /// let (continuation, value) = store.data_mut().pop().unwrap();
+    /// assert_eq!(value, 1);
+    /// assert!(linker.resume(&mut store, continuation, &[])?.is_none());
+    ///
+    /// let (continuation, value) = store.data_mut().pop().unwrap();
+    /// assert_eq!(value, 2);
+    /// assert!(linker.resume(&mut store, continuation, &[])?.is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tag_handler(
+        &mut self,
+        store: impl AsContext<Data = T>,
+        tag: Tag,
+        handler: impl Fn(StoreContextMut<'_, T>, Continuation, &[Val]) -> Result<()>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Result<&mut Self> {
+        let store = store.as_context();
+        assert!(tag.comes_from_same_store(store.0));
+        let handler: Arc<TagHandler<T>> = Arc::new(handler);
+        match self
+            .tag_handlers
+            .iter_mut()
+            .find(|(t, _)| Tag::eq(t, &tag, &store))
+        {
+            Some(_) if !self.allow_shadowing => bail!("handler for tag defined twice"),
+            Some(entry) => entry.1 = handler,
+            None => self.tag_handlers.push((tag, handler)),
+        }
+        Ok(self)
+    }
+
+    /// Creates a continuation which will run `func` when first resumed with
+    /// [`Linker::resume`].
+    ///
+    /// Suspensions of the continuation to any tag with a handler registered
+    /// through [`Linker::tag_handler`] at this time are passed to that
+    /// handler. See [`Continuation::new`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the types of `func` or of any handled tag aren't
+    /// supported, or if stack switching isn't enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `func` or any handled tag does not belong to `store`.
+    pub fn continuation(
+        &self,
+        store: impl AsContextMut<Data = T>,
+        func: &Func,
+    ) -> Result<Continuation> {
+        let tags = self
+            .tag_handlers
+            .iter()
+            .map(|(tag, _)| *tag)
+            .collect::<Vec<_>>();
+        Continuation::new(store, func, &tags)
+    }
+
+    /// Resumes `continuation` with `args`, dispatching its next suspension to
+    /// the handler registered for the tag it suspends to.
+    ///
+    /// Returns the results of the continuation's function if it returns, or
+    /// `None` if it suspended, in which case the continuation has been passed
+    /// to a handler registered with [`Linker::tag_handler`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if [`Continuation::resume`] fails, if the handler
+    /// returns an error, or if the continuation suspends to a tag whose
+    /// handler has since been removed from this linker.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `continuation` does not belong to `store`.
+    pub fn resume(
+        &self,
+        mut store: impl AsContextMut<Data = T>,
+        continuation: Continuation,
+        args: &[Val],
+    ) -> Result<Option<Vec<Val>>> {
+        let mut store = store.as_context_mut();
+        match continuation.resume(&mut store, args)? {
+            Resumed::Returned(results) => Ok(Some(results)),
+            Resumed::Suspended { tag, payload } => {
+                let handler = self
+                    .tag_handlers
+                    .iter()
+                    .find(|(t, _)| Tag::eq(t, &tag, &store))
+                    .map(|(_, handler)| handler.clone())
+                    .ok_or_else(|| anyhow!("no handler registered for suspended tag"))?;
+                handler(store, continuation, &payload)?;
+                Ok(None)
+            }
+        }
+    }
+
     /// Convenience wrapper to define an entire [`Instance`] in this linker.
     ///
     /// This function is a convenience wrapper around [`Linker::define`] which
@@ -980,7 +1492,13 @@ impl<T> Linker<T> {
         let dst = self.import_key(as_module, Some(as_name));
         match self.map.get(&src).cloned() {
             Some(item) => self.insert(dst, item)?,
-            None => bail!("no item named `{}::{}` defined", module, name),
+            None => {
+                return Err(crate::ErrorKind::Link.tag(format_err!(
+                    "no item named `{}::{}` defined",
+                    module,
+                    name
+                )))
+            }
         }
         Ok(self)
     }
@@ -1023,7 +1541,9 @@ impl<T> Linker<T> {
                     Some(name) => format!("{module}::{name}"),
                     None => module.to_string(),
                 };
-                bail!("import of `{}` defined twice", desc)
+                return Err(
+                    crate::ErrorKind::Link.tag(format_err!("import of `{}` defined twice", desc))
+                );
             }
             Entry::Occupied(mut o) => {
                 o.insert(item);
@@ -1141,7 +1661,8 @@ impl<T> Linker<T> {
     /// # Errors
     ///
     /// Returns an error which may be downcast to an [`UnknownImportError`] if
-    /// the module has any unresolvable imports.
+    /// the module has any unresolvable imports, or to an [`ImportErrors`] if
+    /// [strict imports](Linker::strict_imports) are enabled.
     ///
     /// # Examples
     ///
@@ -1197,6 +1718,9 @@ impl<T> Linker<T> {
         module: &Module,
         store: Option<&StoreOpaque>,
     ) -> Result<InstancePre<T>> {
+        if self.strict_imports {
+            self.check_imports(module, store)?;
+        }
         let mut imports = module
             .imports()
             .map(|import| self._get_by_import(&import))
@@ -1209,6 +1733,51 @@ impl<T> Linker<T> {
         unsafe { InstancePre::new(module, imports) }
     }
 
+    /// Checks all imports of `module` against their definitions in this
+    /// linker, returning every import which is undefined or mis-typed.
+    fn check_imports(&self, module: &Module, store: Option<&StoreOpaque>) -> Result<()> {
+        let cx = MatchCx::new(&self.engine);
+        let env_module = module.compiled_module().module();
+        let mut errors = Vec::new();
+        for (import, (_, _, mut expected)) in module.imports().zip(env_module.imports()) {
+            let Some(item) = self._get(import.module(), import.name()) else {
+                errors.push(ImportError::new(&import, None, None));
+                continue;
+            };
+            let mut item = item.clone();
+            if let Some(store) = store {
+                item.update_size(store);
+            }
+            expected.canonicalize_for_runtime_usage(&mut |module_index| {
+                module.signatures().shared_type(module_index).unwrap()
+            });
+            if let Err(e) = cx.definition(&expected, &item.ty()) {
+                let provided = item.ty().extern_type(&self.engine);
+                errors.push(ImportError::new(&import, Some(provided), Some(e)));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ImportErrors { errors }.into())
+        }
+    }
+
+    /// Returns an iterator over the imports of `module` which aren't defined
+    /// in this linker.
+    ///
+    /// Only the names of imports are looked up, so imports which are defined
+    /// with an incompatible type aren't included; use [strict
+    /// imports](Linker::strict_imports) to find those too.
+    pub fn unresolved_imports<'a>(
+        &'a self,
+        module: &'a Module,
+    ) -> impl Iterator<Item = ImportType<'a>> + 'a {
+        module
+            .imports()
+            .filter(|import| self._get(import.module(), import.name()).is_none())
+    }
+
     /// Returns an iterator over all items defined in this `Linker`, in
     /// arbitrary order.
     ///
@@ -1259,6 +1828,11 @@ impl<T> Linker<T> {
         Some(unsafe { self._get(module, name)?.to_extern(store) })
     }
 
+    /// Whether an item named `module::name` is defined in this linker.
+    pub(crate) fn is_defined(&self, module: &str, name: &str) -> bool {
+        self._get(module, name).is_some()
+    }
+
     fn _get(&self, module: &str, name: &str) -> Option<&Definition> {
         let key = ImportKey {
             module: *self.string2idx.get(module)?,
@@ -1404,6 +1978,32 @@ impl DefinitionType {
             DefinitionType::Tag(_) => "tag",
         }
     }
+
+    /// Returns the public type of a definition of this type, which must have
+    /// been created within `engine`.
+    fn extern_type(&self, engine: &Engine) -> ExternType {
+        match self {
+            DefinitionType::Func(index) => FuncType::from_shared_type_index(engine, *index).into(),
+            DefinitionType::Global(global) => {
+                GlobalType::from_wasmtime_global(engine, global).into()
+            }
+            DefinitionType::Table(table, _) => TableType::from_wasmtime_table(engine, table).into(),
+            DefinitionType::Memory(memory, _) => MemoryType::from_wasmtime_memory(memory).into(),
+            DefinitionType::Tag(tag) => TagType::new(FuncType::from_shared_type_index(
+                engine,
+                tag.signature.unwrap_engine_type_index(),
+            ))
+            .into(),
+        }
+    }
+
+    /// Describes a definition of this type for error messages.
+    fn describe(&self, engine: &Engine) -> String {
+        match self.extern_type(engine) {
+            ExternType::Func(ty) => format!("function `{ty}`"),
+            ty => format!("{} `{ty:?}`", self.desc()),
+        }
+    }
 }
 
 /// Modules can be interpreted either as Commands or Reactors.
@@ -1497,3 +2097,90 @@ impl fmt::Display for UnknownImportError {
 }
 
 impl core::error::Error for UnknownImportError {}
+
+/// Error for a module with imports which are undefined or have incompatible
+/// definitions.
+///
+/// Returned - wrapped in an [`anyhow::Error`] - by [`Linker::instantiate`] and
+/// related methods when [strict imports](Linker::strict_imports) are enabled.
+#[derive(Clone, Debug)]
+pub struct ImportErrors {
+    errors: Vec<ImportError>,
+}
+
+impl ImportErrors {
+    /// Returns the problematic imports, in the order they are imported by the
+    /// module.
+    pub fn errors(&self) -> &[ImportError] {
+        &self.errors
+    }
+}
+
+impl fmt::Display for ImportErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} imports could not be resolved:", self.errors.len())?;
+        for error in &self.errors {
+            write!(f, "\n  {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for ImportErrors {}
+
+/// An import which is undefined or has an incompatible definition, as part of
+/// [`ImportErrors`].
+#[derive(Clone, Debug)]
+pub struct ImportError {
+    module: String,
+    name: String,
+    expected: ExternType,
+    provided: Option<ExternType>,
+    reason: Option<String>,
+}
+
+impl ImportError {
+    fn new(import: &ImportType, provided: Option<ExternType>, reason: Option<Error>) -> Self {
+        Self {
+            module: import.module().to_string(),
+            name: import.name().to_string(),
+            expected: import.ty(),
+            provided,
+            reason: reason.map(|e| format!("{e:#}")),
+        }
+    }
+
+    /// Returns the module name of the import.
+    pub fn module(&self) -> &str {
+        &self.module
+    }
+
+    /// Returns the field name of the import.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the type the module expects for the import.
+    pub fn expected(&self) -> &ExternType {
+        &self.expected
+    }
+
+    /// Returns the type of the linker's definition of the import, or `None`
+    /// if it isn't defined.
+    pub fn provided(&self) -> Option<&ExternType> {
+        self.provided.as_ref()
+    }
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.reason {
+            None => write!(f, "`{}::{}` has not been defined", self.module, self.name),
+            Some(reason) => write!(
+                f,
+                "incompatible import type for `{}::{}`: {reason}",
+                self.module, self.name,
+            ),
+        }
+    }
+}
@@ -11,17 +11,36 @@ use core::time::Duration;
 
 pub use crate::runtime::vm::WaitResult;
 
-/// Error for out of bounds [`Memory`] access.
+mod growth;
+mod typed;
+pub use growth::*;
+pub use typed::*;
+
+/// Error for out of bounds or misaligned [`Memory`] access.
 #[derive(Debug)]
 #[non_exhaustive]
 pub struct MemoryAccessError {
     // Keep struct internals private for future extensibility.
-    _private: (),
+    misaligned: bool,
+}
+
+impl MemoryAccessError {
+    pub(crate) fn out_of_bounds() -> MemoryAccessError {
+        MemoryAccessError { misaligned: false }
+    }
+
+    pub(crate) fn misaligned() -> MemoryAccessError {
+        MemoryAccessError { misaligned: true }
+    }
 }
 
 impl fmt::Display for MemoryAccessError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "out of bounds memory access")
+        if self.misaligned {
+            write!(f, "misaligned memory access")
+        } else {
+            write!(f, "out of bounds memory access")
+        }
     }
 }
 
@@ -325,7 +344,7 @@ impl Memory {
             .data(&store)
             .get(offset..)
             .and_then(|s| s.get(..buffer.len()))
-            .ok_or(MemoryAccessError { _private: () })?;
+            .ok_or_else(MemoryAccessError::out_of_bounds)?;
         buffer.copy_from_slice(slice);
         Ok(())
     }
@@ -349,7 +368,7 @@ impl Memory {
         self.data_mut(&mut context)
             .get_mut(offset..)
             .and_then(|s| s.get_mut(..buffer.len()))
-            .ok_or(MemoryAccessError { _private: () })?
+            .ok_or_else(MemoryAccessError::out_of_bounds)?
             .copy_from_slice(buffer);
         Ok(())
     }
@@ -630,6 +649,43 @@ impl Memory {
         }
     }
 
+    /// Writes `data` into this memory at `offset`, mapping the host pages
+    /// that it covers entirely copy-on-write so that they're only populated
+    /// once accessed.
+    ///
+    /// Memories which can't map data this way, see
+    /// [`Config::memory_init_cow`](crate::Config::memory_init_cow), have all
+    /// of `data` written directly.
+    pub(crate) fn write_lazily(
+        &self,
+        mut store: impl AsContextMut,
+        offset: usize,
+        data: &[u8],
+    ) -> Result<()> {
+        let mut store = store.as_context_mut();
+        let end = match offset.checked_add(data.len()) {
+            Some(end) if end <= self.data_size(&store) => end,
+            _ => bail!("{}", MemoryAccessError::out_of_bounds()),
+        };
+
+        let page_size = crate::runtime::vm::host_page_size();
+        let pages = offset.next_multiple_of(page_size)..end / page_size * page_size;
+        if pages.start < pages.end {
+            let mem = self.wasmtime_memory(store.0);
+            let mapped = unsafe {
+                (*mem).map_data(pages.start, &data[pages.start - offset..pages.end - offset])?
+            };
+            if mapped {
+                let memory = self.data_mut(&mut store);
+                memory[offset..pages.start].copy_from_slice(&data[..pages.start - offset]);
+                memory[pages.end..end].copy_from_slice(&data[pages.end - offset..]);
+                return Ok(());
+            }
+        }
+        self.data_mut(&mut store)[offset..end].copy_from_slice(data);
+        Ok(())
+    }
+
     pub(crate) unsafe fn from_wasmtime_memory(
         wasmtime_export: crate::runtime::vm::ExportMemory,
         store: &mut StoreOpaque,
@@ -995,6 +1051,12 @@ impl SharedMemory {
         &self.engine
     }
 
+    /// Charges this memory to `store`'s quota group, if it's in one, before
+    /// it's imported into `store`.
+    pub(crate) fn reserve_quota(&self, store: &mut StoreOpaque) -> Result<()> {
+        store.reserve_shared_memory(&self.vm)
+    }
+
     /// Construct a single-memory instance to provide a way to import
     /// [`SharedMemory`] into other modules.
     pub(crate) fn vmimport(&self, store: &mut StoreOpaque) -> crate::runtime::vm::VMMemoryImport {
@@ -0,0 +1,116 @@
+//! Observability of linear memory growth: the hook registered with
+//! [`Store::on_memory_grow`](crate::Store::on_memory_grow) and the statistics
+//! behind [`Store::memory_growth_stats`](crate::Store::memory_growth_stats).
+
+use crate::prelude::*;
+
+/// An attempt to grow a linear memory, passed to hooks registered with
+/// [`Store::on_memory_grow`](crate::Store::on_memory_grow).
+///
+/// Sizes are in units of the memory's pages, see
+/// [`MemoryType::page_size`](crate::MemoryType::page_size).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryGrowth {
+    /// The size of a page of the memory, in bytes.
+    pub page_size: u64,
+
+    /// The size of the memory before the attempt, in pages.
+    pub old_pages: u64,
+
+    /// The size that the memory was requested to grow to, in pages.
+    pub requested_pages: u64,
+
+    /// The number of pages the memory can grow to without its contents being
+    /// moved, after the attempt.
+    ///
+    /// This is the memory's reservation, which is at least as large as its
+    /// size and is determined by
+    /// [`Config::memory_reservation`](crate::Config::memory_reservation) and
+    /// related settings.
+    pub reserved_pages: u64,
+
+    /// Whether the memory grew, and if not, why.
+    pub outcome: MemoryGrowthOutcome,
+}
+
+/// The outcome of a [`MemoryGrowth`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MemoryGrowthOutcome {
+    /// The memory grew to the requested size.
+    Grown,
+
+    /// The growth was rejected by the store's
+    /// [`ResourceLimiter`](crate::ResourceLimiter) or
+    /// [`QuotaGroup`](crate::QuotaGroup).
+    Denied,
+
+    /// The requested size exceeds the memory's maximum size or, for memories
+    /// which aren't allowed to move, its reservation.
+    ExceedsMaximum,
+
+    /// The memory's allocation failed to grow, for example because the system
+    /// is out of memory. Contains the error that was reported to the store's
+    /// resource limiter.
+    AllocationFailed(String),
+}
+
+/// Statistics about the growth of a store's linear memories, as returned by
+/// [`Store::memory_growth_stats`](crate::Store::memory_growth_stats).
+///
+/// Growth is counted whether it's requested with `memory.grow` instructions
+/// in the store's instances or with [`Memory::grow`](crate::Memory::grow).
+/// Neither the initial allocation of memories nor
+/// [`SharedMemory::grow`](crate::SharedMemory::grow), which isn't associated
+/// with a store, is counted.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryGrowthStats {
+    /// The number of times a memory grew.
+    pub grows: u64,
+
+    /// The number of growth attempts rejected with
+    /// [`MemoryGrowthOutcome::Denied`].
+    pub denied: u64,
+
+    /// The number of growth attempts which failed for any other reason.
+    pub failed: u64,
+
+    /// The total number of bytes that memories grew by.
+    pub grown_bytes: u64,
+}
+
+/// Per-store bookkeeping behind [`MemoryGrowthStats`] and
+/// [`Store::on_memory_grow`](crate::Store::on_memory_grow) hooks.
+#[derive(Default)]
+pub(crate) struct MemoryGrowthState {
+    stats: MemoryGrowthStats,
+    hook: Option<Box<dyn FnMut(&MemoryGrowth) + Send + Sync>>,
+}
+
+impl MemoryGrowthState {
+    pub(crate) fn stats(&self) -> MemoryGrowthStats {
+        self.stats
+    }
+
+    pub(crate) fn set_hook(&mut self, hook: Box<dyn FnMut(&MemoryGrowth) + Send + Sync>) {
+        self.hook = Some(hook);
+    }
+
+    /// Record an attempt to grow a memory and invoke the registered hook, if
+    /// any.
+    pub(crate) fn record(&mut self, growth: MemoryGrowth) {
+        match growth.outcome {
+            MemoryGrowthOutcome::Grown => {
+                self.stats.grows += 1;
+                self.stats.grown_bytes +=
+                    (growth.requested_pages - growth.old_pages) * growth.page_size;
+            }
+            MemoryGrowthOutcome::Denied => self.stats.denied += 1,
+            MemoryGrowthOutcome::ExceedsMaximum | MemoryGrowthOutcome::AllocationFailed(_) => {
+                self.stats.failed += 1
+            }
+        }
+        if let Some(hook) = &mut self.hook {
+            hook(&growth);
+        }
+    }
+}
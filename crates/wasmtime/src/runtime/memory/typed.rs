@@ -0,0 +1,296 @@
+//! Typed views of the contents of a linear memory.
+
+use crate::{AsContext, AsContextMut, Memory, MemoryAccessError};
+use core::fmt;
+use core::mem;
+use core::ptr;
+use core::slice;
+
+/// Plain-old-data types which can be stored in WebAssembly linear memory.
+///
+/// WebAssembly memory is always little-endian, so values are stored in memory
+/// in their little-endian representation and [`Pod::from_le`] and
+/// [`Pod::to_le`] convert to and from the host's byte order. These are no-ops
+/// on little-endian hosts.
+///
+/// This trait is implemented for the primitive integer and floating-point
+/// types as well as arrays of other `Pod` types, and is used by
+/// [`Memory::typed_slice`], [`Memory::read_le`], and friends.
+///
+/// # Safety
+///
+/// Implementors must be `#[repr(C)]` or `#[repr(transparent)]` types for which
+/// every bit pattern is a valid value and which have no padding bytes, since
+/// values are created from arbitrary bytes in linear memory and all of their
+/// bytes are copied into linear memory.
+pub unsafe trait Pod: Copy + Send + Sync + 'static {
+    /// Converts `le`, in its little-endian representation, to the host's byte
+    /// order.
+    fn from_le(le: Self) -> Self;
+
+    /// Converts `self` from the host's byte order to its little-endian
+    /// representation.
+    fn to_le(self) -> Self;
+}
+
+macro_rules! integers {
+    ($($t:ty)*) => ($(
+        unsafe impl Pod for $t {
+            #[inline]
+            fn from_le(le: Self) -> Self {
+                <$t>::from_le(le)
+            }
+
+            #[inline]
+            fn to_le(self) -> Self {
+                <$t>::to_le(self)
+            }
+        }
+    )*)
+}
+
+integers! {
+    u8 u16 u32 u64 u128 i8 i16 i32 i64 i128
+}
+
+macro_rules! floats {
+    ($($t:ty)*) => ($(
+        unsafe impl Pod for $t {
+            #[inline]
+            fn from_le(le: Self) -> Self {
+                <$t>::from_bits(Pod::from_le(le.to_bits()))
+            }
+
+            #[inline]
+            fn to_le(self) -> Self {
+                <$t>::from_bits(Pod::to_le(self.to_bits()))
+            }
+        }
+    )*)
+}
+
+floats! {
+    f32 f64
+}
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {
+    #[inline]
+    fn from_le(le: Self) -> Self {
+        le.map(T::from_le)
+    }
+
+    #[inline]
+    fn to_le(self) -> Self {
+        self.map(T::to_le)
+    }
+}
+
+/// A `T` stored in linear memory in its little-endian representation.
+///
+/// This is the element type of the slices returned by [`Memory::typed_slice`]
+/// and [`Memory::typed_slice_mut`]. It has the same size and alignment as `T`,
+/// and [`Le::get`] and [`Le::set`] take care of any byte swapping required on
+/// big-endian hosts.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Le<T: Pod>(T);
+
+impl<T: Pod> Le<T> {
+    /// Creates a new `Le` holding `val`.
+    #[inline]
+    pub fn new(val: T) -> Le<T> {
+        Le(val.to_le())
+    }
+
+    /// Returns the value stored in this `Le`, in the host's byte order.
+    #[inline]
+    pub fn get(&self) -> T {
+        T::from_le(self.0)
+    }
+
+    /// Stores `val` into this `Le`.
+    #[inline]
+    pub fn set(&mut self, val: T) {
+        self.0 = val.to_le();
+    }
+}
+
+impl<T: Pod> From<T> for Le<T> {
+    fn from(val: T) -> Le<T> {
+        Le::new(val)
+    }
+}
+
+impl<T: Pod + fmt::Debug> fmt::Debug for Le<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+/// Checks that `len` values of type `T` at `offset` fit within `data` and,
+/// optionally, that they're suitably aligned in the host's address space.
+fn check_access<T>(
+    data: &[u8],
+    offset: usize,
+    len: usize,
+    aligned: bool,
+) -> Result<(), MemoryAccessError> {
+    let end = len
+        .checked_mul(mem::size_of::<T>())
+        .and_then(|size| offset.checked_add(size))
+        .ok_or_else(MemoryAccessError::out_of_bounds)?;
+    if end > data.len() {
+        return Err(MemoryAccessError::out_of_bounds());
+    }
+    if aligned && (data.as_ptr() as usize).wrapping_add(offset) % mem::align_of::<T>() != 0 {
+        return Err(MemoryAccessError::misaligned());
+    }
+    Ok(())
+}
+
+impl Memory {
+    /// Returns a view of the `len` values of type `T` starting at byte
+    /// `offset` in this memory.
+    ///
+    /// This is a bounds-checked and typed alternative to slicing
+    /// [`Memory::data`] and decoding values with `T::from_le_bytes`. Elements
+    /// of the returned slice are [`Le`] values whose [`Le::get`] method returns
+    /// the value in the host's byte order.
+    ///
+    /// As with [`Memory::data`], `store` is considered borrowed for the
+    /// lifetime of the returned slice.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MemoryAccessError`] if the values don't lie entirely within
+    /// this memory or if `offset` isn't suitably aligned for `T`. Use
+    /// [`Memory::read_le`] to read values at unaligned offsets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this memory doesn't belong to `store`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut store = Store::<()>::default();
+    /// let memory = Memory::new(&mut store, MemoryType::new(1, None))?;
+    /// memory.write(&mut store, 8, &[1, 0, 0, 0, 2, 0, 0, 0])?;
+    ///
+    /// let values = memory.typed_slice::<u32>(&store, 8, 2)?;
+    /// assert_eq!(values[0].get(), 1);
+    /// assert_eq!(values[1].get(), 2);
+    ///
+    /// assert!(memory.typed_slice::<u32>(&store, 9, 1).is_err());
+    /// assert!(memory.typed_slice::<u32>(&store, 65536, 1).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn typed_slice<'a, T: Pod>(
+        &self,
+        store: &'a impl AsContext,
+        offset: usize,
+        len: usize,
+    ) -> Result<&'a [Le<T>], MemoryAccessError> {
+        let data = self.data(store.as_context());
+        check_access::<T>(data, offset, len, true)?;
+        // SAFETY: the range was bounds-checked and its alignment checked
+        // above, `Le<T>` has the same layout as `T`, and `Pod` guarantees that
+        // any bytes are a valid `T`.
+        unsafe {
+            Ok(slice::from_raw_parts(
+                data.as_ptr().add(offset).cast::<Le<T>>(),
+                len,
+            ))
+        }
+    }
+
+    /// Same as [`Memory::typed_slice`], but returns a mutable view of this
+    /// memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MemoryAccessError`] if the values don't lie entirely within
+    /// this memory or if `offset` isn't suitably aligned for `T`. Use
+    /// [`Memory::write_le`] to write values at unaligned offsets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this memory doesn't belong to `store`.
+    pub fn typed_slice_mut<'a, T: Pod>(
+        &self,
+        store: &'a mut impl AsContextMut,
+        offset: usize,
+        len: usize,
+    ) -> Result<&'a mut [Le<T>], MemoryAccessError> {
+        let data = self.data_mut(store.as_context_mut());
+        check_access::<T>(data, offset, len, true)?;
+        // SAFETY: see `typed_slice`, and additionally `Pod` guarantees that
+        // `T` has no padding, so writes through the slice never write
+        // uninitialized bytes into this memory.
+        unsafe {
+            Ok(slice::from_raw_parts_mut(
+                data.as_mut_ptr().add(offset).cast::<Le<T>>(),
+                len,
+            ))
+        }
+    }
+
+    /// Reads the little-endian `T` at byte `offset` in this memory.
+    ///
+    /// Unlike [`Memory::typed_slice`], `offset` need not be aligned.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MemoryAccessError`] if the value doesn't lie entirely
+    /// within this memory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this memory doesn't belong to `store`.
+    pub fn read_le<T: Pod>(
+        &self,
+        store: impl AsContext,
+        offset: usize,
+    ) -> Result<T, MemoryAccessError> {
+        let store = store.as_context();
+        let data = self.data(&store);
+        check_access::<T>(data, offset, 1, false)?;
+        // SAFETY: the range was bounds-checked above and `Pod` guarantees that
+        // any bytes are a valid `T`.
+        let le = unsafe { ptr::read_unaligned(data.as_ptr().add(offset).cast::<T>()) };
+        Ok(T::from_le(le))
+    }
+
+    /// Writes `val` at byte `offset` in this memory in its little-endian
+    /// representation.
+    ///
+    /// Unlike [`Memory::typed_slice_mut`], `offset` need not be aligned.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MemoryAccessError`] if the value doesn't lie entirely
+    /// within this memory, in which case the memory is left untouched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this memory doesn't belong to `store`.
+    pub fn write_le<T: Pod>(
+        &self,
+        mut store: impl AsContextMut,
+        offset: usize,
+        val: T,
+    ) -> Result<(), MemoryAccessError> {
+        let mut store = store.as_context_mut();
+        let data = self.data_mut(&mut store);
+        check_access::<T>(data, offset, 1, false)?;
+        // SAFETY: the range was bounds-checked above and `Pod` guarantees that
+        // `T` has no padding.
+        unsafe {
+            ptr::write_unaligned(data.as_mut_ptr().add(offset).cast::<T>(), val.to_le());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,282 @@
+use crate::prelude::*;
+use crate::Trap;
+use core::fmt::{self, Write};
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+/// A monotonically increasing count of events, such as the number of calls
+/// into wasm.
+#[derive(Debug, Default)]
+pub struct Counter(AtomicU64);
+
+impl Counter {
+    /// Returns the current value of this counter.
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+}
+
+/// A distribution of durations, such as the time taken by instantiations.
+///
+/// Observations are sorted into buckets with the fixed upper bounds in
+/// [`Histogram::BUCKETS`], plus one final bucket for everything larger.
+#[derive(Debug)]
+pub struct Histogram {
+    buckets: [AtomicU64; Histogram::BUCKETS.len() + 1],
+    count: AtomicU64,
+    sum_nanos: AtomicU64,
+}
+
+impl Default for Histogram {
+    fn default() -> Histogram {
+        Histogram {
+            buckets: Default::default(),
+            count: AtomicU64::new(0),
+            sum_nanos: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Histogram {
+    /// The upper bounds, inclusive, of the buckets of every histogram.
+    pub const BUCKETS: [Duration; 8] = [
+        Duration::from_micros(1),
+        Duration::from_micros(10),
+        Duration::from_micros(100),
+        Duration::from_millis(1),
+        Duration::from_millis(10),
+        Duration::from_millis(100),
+        Duration::from_secs(1),
+        Duration::from_secs(10),
+    ];
+
+    /// Returns the number of observations recorded in this histogram.
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Returns the sum of all observations recorded in this histogram.
+    pub fn sum(&self) -> Duration {
+        Duration::from_nanos(self.sum_nanos.load(Ordering::Relaxed))
+    }
+
+    /// Returns, for each bound in [`Histogram::BUCKETS`], the number of
+    /// observations which were at most that long.
+    ///
+    /// Like Prometheus buckets these counts are cumulative, and observations
+    /// larger than the last bound are only included in
+    /// [`Histogram::count`].
+    pub fn buckets(&self) -> impl Iterator<Item = (Duration, u64)> + '_ {
+        let mut total = 0;
+        Histogram::BUCKETS
+            .iter()
+            .zip(&self.buckets)
+            .map(move |(bound, count)| {
+                total += count.load(Ordering::Relaxed);
+                (*bound, total)
+            })
+    }
+
+    pub(crate) fn record(&self, duration: Duration) {
+        let bucket = Histogram::BUCKETS
+            .iter()
+            .position(|bound| duration <= *bound)
+            .unwrap_or(Histogram::BUCKETS.len());
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.sum_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+/// Metrics collected for all stores of an [`Engine`](crate::Engine).
+///
+/// Collection is opt-in with [`Config::metrics`](crate::Config::metrics), after
+/// which this registry is available from
+/// [`Engine::metrics`](crate::Engine::metrics). All values are updated with
+/// relaxed atomic operations, so they can be read from any thread at any time,
+/// for example by a thread which periodically exports them with
+/// [`Metrics::to_prometheus`].
+///
+/// Durations are only measured when the `std` feature of this crate is
+/// enabled; without it histograms remain empty.
+#[derive(Debug)]
+pub struct Metrics {
+    instantiation_time: Histogram,
+    calls: Counter,
+    traps: Box<[Counter]>,
+    gc_pauses: Histogram,
+    fuel_consumed: Counter,
+    epoch_deadlines: Counter,
+}
+
+impl Metrics {
+    pub(crate) fn new() -> Metrics {
+        let trap_codes = (0..=u8::MAX).map_while(Trap::from_u8).count();
+        Metrics {
+            instantiation_time: Histogram::default(),
+            calls: Counter::default(),
+            traps: (0..trap_codes).map(|_| Counter::default()).collect(),
+            gc_pauses: Histogram::default(),
+            fuel_consumed: Counter::default(),
+            epoch_deadlines: Counter::default(),
+        }
+    }
+
+    /// Time taken to instantiate modules, including running their start
+    /// functions.
+    ///
+    /// Every core instance created while instantiating a component is recorded
+    /// individually.
+    pub fn instantiation_time(&self) -> &Histogram {
+        &self.instantiation_time
+    }
+
+    /// Number of calls from the host into wasm, such as with
+    /// [`Func::call`](crate::Func::call).
+    pub fn calls(&self) -> &Counter {
+        &self.calls
+    }
+
+    /// Number of wasm traps raised with the given `code`.
+    ///
+    /// A trap is counted once where it is raised, even if it then propagates
+    /// through several host and wasm frames.
+    pub fn traps(&self, code: Trap) -> u64 {
+        self.traps.get(code as usize).map_or(0, Counter::get)
+    }
+
+    /// Time spent in garbage collections.
+    pub fn gc_pauses(&self) -> &Histogram {
+        &self.gc_pauses
+    }
+
+    /// Units of fuel consumed by wasm.
+    ///
+    /// Consumption is recorded whenever a call into wasm returns and when
+    /// [`Store::set_fuel`](crate::Store::set_fuel) is called.
+    pub fn fuel_consumed(&self) -> &Counter {
+        &self.fuel_consumed
+    }
+
+    /// Number of times that wasm reached its epoch deadline, see
+    /// [`Config::epoch_interruption`](crate::Config::epoch_interruption).
+    pub fn epoch_deadlines(&self) -> &Counter {
+        &self.epoch_deadlines
+    }
+
+    pub(crate) fn record_trap(&self, code: Trap) {
+        if let Some(counter) = self.traps.get(code as usize) {
+            counter.add(1);
+        }
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format.
+    ///
+    /// All metric names are prefixed with `wasmtime_`, and durations are
+    /// reported in seconds.
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        self.write_prometheus(&mut out).unwrap();
+        out
+    }
+
+    fn write_prometheus(&self, out: &mut String) -> fmt::Result {
+        write_histogram(
+            out,
+            "wasmtime_instantiation_duration_seconds",
+            "Time taken to instantiate modules.",
+            &self.instantiation_time,
+        )?;
+        write_counter(
+            out,
+            "wasmtime_calls_total",
+            "Calls from the host into wasm.",
+            self.calls.get(),
+        )?;
+
+        writeln!(
+            out,
+            "# HELP wasmtime_traps_total Wasm traps raised, by code."
+        )?;
+        writeln!(out, "# TYPE wasmtime_traps_total counter")?;
+        for (code, counter) in (0..=u8::MAX).map_while(Trap::from_u8).zip(&*self.traps) {
+            writeln!(
+                out,
+                "wasmtime_traps_total{{code=\"{code:?}\"}} {}",
+                counter.get()
+            )?;
+        }
+
+        write_histogram(
+            out,
+            "wasmtime_gc_pause_duration_seconds",
+            "Time spent in garbage collections.",
+            &self.gc_pauses,
+        )?;
+        write_counter(
+            out,
+            "wasmtime_fuel_consumed_total",
+            "Units of fuel consumed by wasm.",
+            self.fuel_consumed.get(),
+        )?;
+        write_counter(
+            out,
+            "wasmtime_epoch_deadlines_total",
+            "Times that wasm reached its epoch deadline.",
+            self.epoch_deadlines.get(),
+        )
+    }
+}
+
+fn write_counter(out: &mut String, name: &str, help: &str, value: u64) -> fmt::Result {
+    writeln!(out, "# HELP {name} {help}")?;
+    writeln!(out, "# TYPE {name} counter")?;
+    writeln!(out, "{name} {value}")
+}
+
+fn write_histogram(out: &mut String, name: &str, help: &str, histogram: &Histogram) -> fmt::Result {
+    writeln!(out, "# HELP {name} {help}")?;
+    writeln!(out, "# TYPE {name} histogram")?;
+    for (bound, count) in histogram.buckets() {
+        writeln!(
+            out,
+            "{name}_bucket{{le=\"{}\"}} {count}",
+            bound.as_secs_f64()
+        )?;
+    }
+    writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {}", histogram.count())?;
+    writeln!(out, "{name}_sum {}", histogram.sum().as_secs_f64())?;
+    writeln!(out, "{name}_count {}", histogram.count())
+}
+
+/// Measures the duration of an operation for a [`Histogram`] if metrics are
+/// enabled.
+pub(crate) struct Timer {
+    #[cfg(feature = "std")]
+    start: Option<std::time::Instant>,
+}
+
+impl Timer {
+    pub(crate) fn start(metrics: Option<&Metrics>) -> Timer {
+        let _ = metrics;
+        Timer {
+            #[cfg(feature = "std")]
+            start: metrics.map(|_| std::time::Instant::now()),
+        }
+    }
+
+    /// Records the time since this timer was started into the histogram
+    /// selected by `histogram`.
+    pub(crate) fn finish(self, metrics: Option<&Metrics>, histogram: fn(&Metrics) -> &Histogram) {
+        let _ = (metrics, histogram);
+        #[cfg(feature = "std")]
+        if let (Some(metrics), Some(start)) = (metrics, self.start) {
+            histogram(metrics).record(start.elapsed());
+        }
+    }
+}
@@ -23,8 +23,12 @@ use wasmtime_environ::{
     CompiledModuleInfo, EntityIndex, HostPtr, ModuleTypes, ObjectKind, TypeTrace, VMOffsets,
     VMSharedTypeIndex,
 };
+#[cfg(feature = "disas")]
+mod disas;
 mod registry;
 
+#[cfg(feature = "disas")]
+pub use disas::*;
 pub use registry::*;
 
 /// A compiled WebAssembly module, ready to be instantiated.
@@ -510,6 +514,16 @@ impl Module {
             .allocator()
             .validate_module(module.module(), &offsets)?;
 
+        if let Some(size) = module.max_stack_size() {
+            let max = engine.config().max_wasm_stack;
+            if usize::try_from(size).unwrap() > max {
+                log::warn!(
+                    "module may use up to {size} bytes of stack which exceeds the \
+                     configured `max_wasm_stack` of {max} bytes"
+                );
+            }
+        }
+
         Ok(Self {
             inner: Arc::new(ModuleInner {
                 engine: engine.clone(),
@@ -925,6 +939,87 @@ impl Module {
         }
     }
 
+    /// Returns the maximum native stack space, in bytes, that a call into any
+    /// of this module's functions may use, as determined when it was compiled.
+    ///
+    /// This accounts for the stack frames of all functions which may be
+    /// called directly from the function being called, and can be used to
+    /// choose a [`Config::max_wasm_stack`] or
+    /// [`Config::async_stack_size`](crate::Config::async_stack_size) based on
+    /// what a module actually needs rather than a guess. A warning is logged
+    /// when a module is created if its maximum exceeds
+    /// [`Config::max_wasm_stack`].
+    ///
+    /// Returns `None` if the stack usage can't be bounded statically, which
+    /// is the case for modules with recursive functions, `call_indirect` or
+    /// `call_ref` instructions, calls to imported functions, or instructions
+    /// implemented by calling into the host such as `memory.grow`. It's also
+    /// always `None` for modules compiled with Winch.
+    ///
+    /// [`Config::max_wasm_stack`]: crate::Config::max_wasm_stack
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> wasmtime::Result<()> {
+    /// use wasmtime::{Engine, Module};
+    ///
+    /// let engine = Engine::default();
+    /// let module = Module::new(&engine, r#"
+    ///     (module
+    ///         (func $leaf (result i32) i32.const 1)
+    ///         (func (export "f") (result i32) call $leaf)
+    ///     )
+    /// "#)?;
+    /// assert!(module.max_stack_size().is_some());
+    ///
+    /// let module = Module::new(&engine, r#"
+    ///     (module
+    ///         (func $f (export "f") call $f)
+    ///     )
+    /// "#)?;
+    /// assert!(module.max_stack_size().is_none());
+    /// # Ok(()) }
+    /// ```
+    pub fn max_stack_size(&self) -> Option<usize> {
+        let size = self.compiled_module().max_stack_size()?;
+        Some(usize::try_from(size).unwrap())
+    }
+
+    /// Returns the set of WebAssembly proposals that this module uses.
+    ///
+    /// This is detected when the module is compiled by inspecting its types,
+    /// memories, tables, and instructions. It can be used, for example, to
+    /// route modules using stack switching to engines configured with
+    /// [`Config::wasm_stack_switching`](crate::Config::wasm_stack_switching)
+    /// and reject them elsewhere.
+    ///
+    /// Every proposal in the returned set is required by the module, but the
+    /// set may be incomplete: constant expressions are not inspected, so for
+    /// example use of the extended-const proposal is not reported.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> wasmtime::Result<()> {
+    /// use wasmtime::{Engine, Module};
+    /// use wasmtime::wasmparser::WasmFeatures;
+    ///
+    /// let engine = Engine::default();
+    /// let module = Module::new(&engine, r#"
+    ///     (module
+    ///         (func (export "f") (result i32 i32)
+    ///             i32.const 1
+    ///             i32.const 2)
+    ///     )
+    /// "#)?;
+    /// assert_eq!(module.required_features(), WasmFeatures::MULTI_VALUE);
+    /// # Ok(()) }
+    /// ```
+    pub fn required_features(&self) -> wasmparser::WasmFeatures {
+        self.compiled_module().required_features()
+    }
+
     /// Returns the range of bytes in memory where this module's compilation
     /// image resides.
     ///
@@ -0,0 +1,197 @@
+//! Disassembly of a module's compiled functions.
+
+use crate::prelude::*;
+use crate::Module;
+use capstone::arch::BuildsCapstone;
+use capstone::Capstone;
+use target_lexicon::Architecture;
+use wasmtime_environ::FuncIndex;
+
+/// A machine instruction in the compiled code of a WebAssembly function, as
+/// returned by [`Module::disassemble`].
+#[derive(Clone, Debug)]
+pub struct DisassembledInstruction {
+    /// The offset of this instruction within the module's
+    /// [`text`][Module::text] section.
+    pub address: usize,
+    /// The encoded bytes of this instruction.
+    pub bytes: Vec<u8>,
+    /// The instruction's mnemonic, if it could be decoded.
+    pub mnemonic: Option<String>,
+    /// The instruction's operands, if it could be decoded.
+    pub operands: Option<String>,
+    /// The offset, within the original WebAssembly binary, of the WebAssembly
+    /// instruction that this machine instruction was compiled from.
+    ///
+    /// This is `None` for instructions that don't correspond to a WebAssembly
+    /// instruction, such as those in function prologues, or if address maps
+    /// were disabled with [`Config::generate_address_map`][crate::Config::generate_address_map].
+    pub wasm_offset: Option<u32>,
+}
+
+impl Module {
+    /// Disassembles the compiled code of the function at `func_index` in this
+    /// module.
+    ///
+    /// The `func_index` is an index into the module's function index space,
+    /// which includes imported functions. Each returned instruction is
+    /// annotated with the offset of the WebAssembly instruction it was
+    /// compiled from, when known.
+    ///
+    /// This requires the `disas` Cargo feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `func_index` is out of bounds or refers to an
+    /// imported function, or if this module was compiled for an architecture
+    /// that cannot be disassembled, such as Pulley.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn main() -> Result<()> {
+    /// let engine = Engine::default();
+    /// let module = Module::new(&engine, r#"
+    ///     (module
+    ///         (func (param i32) (result i32)
+    ///             local.get 0
+    ///             i32.const 1
+    ///             i32.add))
+    /// "#)?;
+    /// for inst in module.disassemble(0)? {
+    ///     println!(
+    ///         "{:#x}: {} {} ;; wasm offset {:?}",
+    ///         inst.address,
+    ///         inst.mnemonic.as_deref().unwrap_or("?"),
+    ///         inst.operands.as_deref().unwrap_or(""),
+    ///         inst.wasm_offset,
+    ///     );
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn disassemble(&self, func_index: u32) -> Result<Vec<DisassembledInstruction>> {
+        let module = self.compiled_module();
+        let func_index = FuncIndex::from_u32(func_index);
+        let defined = module
+            .module()
+            .defined_func_index(func_index)
+            .filter(|i| i.index() < module.finished_functions().len())
+            .ok_or_else(|| {
+                anyhow!(
+                    "function index {} is not a function defined in this module",
+                    func_index.as_u32()
+                )
+            })?;
+        let loc = module.func_loc(defined);
+        let start = loc.start as usize;
+        let end = start + loc.length as usize;
+        let body = &self.text()[start..end];
+
+        let mut cs = capstone_for(&self.engine().target())?;
+        let insts = cs
+            .disasm_all(body, start as u64)
+            .map_err(|e| anyhow!("{e}"))?;
+
+        // The address map is sorted by text offset, and each entry applies to
+        // all code up to the next entry.
+        let address_map: Vec<_> = self
+            .address_map()
+            .map(|map| map.take_while(|(offset, _)| *offset < end).collect())
+            .unwrap_or_default();
+        let wasm_offset_for_address = |address: usize| {
+            let i = address_map.partition_point(|(offset, _)| *offset <= address);
+            match i.checked_sub(1).map(|i| address_map[i]) {
+                Some((offset, wasm_offset)) if offset >= start => wasm_offset,
+                _ => None,
+            }
+        };
+
+        Ok(insts
+            .iter()
+            .map(|inst| {
+                let address = usize::try_from(inst.address()).unwrap();
+                DisassembledInstruction {
+                    address,
+                    bytes: inst.bytes().to_vec(),
+                    mnemonic: inst.mnemonic().map(ToString::to_string),
+                    operands: inst.op_str().map(ToString::to_string),
+                    wasm_offset: wasm_offset_for_address(address),
+                }
+            })
+            .collect())
+    }
+}
+
+/// Creates a Capstone disassembler for code compiled for `target`.
+fn capstone_for(target: &target_lexicon::Triple) -> Result<Capstone> {
+    let mut cs = match target.architecture {
+        Architecture::Aarch64(_) => Capstone::new()
+            .arm64()
+            .mode(capstone::arch::arm64::ArchMode::Arm)
+            .build(),
+        Architecture::Riscv64(_) => Capstone::new()
+            .riscv()
+            .mode(capstone::arch::riscv::ArchMode::RiscV64)
+            .build(),
+        Architecture::S390x => Capstone::new()
+            .sysz()
+            .mode(capstone::arch::sysz::ArchMode::Default)
+            .build(),
+        Architecture::X86_64 => Capstone::new()
+            .x86()
+            .mode(capstone::arch::x86::ArchMode::Mode64)
+            .build(),
+        _ => bail!("cannot disassemble code compiled for {target}"),
+    }
+    .map_err(|e| anyhow!("{e}"))?;
+
+    // Skip over anything that looks like data, such as inline constant pools,
+    // and trapping instructions on AArch64.
+    cs.set_skipdata(true).map_err(|e| anyhow!("{e}"))?;
+    Ok(cs)
+}
+
+#[cfg(all(
+    test,
+    any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "riscv64",
+        target_arch = "s390x"
+    )
+))]
+mod tests {
+    use crate::{Engine, Module};
+
+    #[test]
+    fn disassemble() {
+        let engine = Engine::default();
+        let module = Module::new(
+            &engine,
+            r#"
+                (module
+                    (import "" "" (func))
+                    (func (param i32) (result i32)
+                        local.get 0
+                        i32.const 1
+                        i32.add)
+                )
+            "#,
+        )
+        .unwrap();
+
+        let insts = module.disassemble(1).unwrap();
+        assert!(!insts.is_empty());
+        assert!(insts.iter().any(|i| i.wasm_offset.is_some()));
+        let func = module.functions().next().unwrap();
+        for inst in &insts {
+            assert!(inst.address >= func.offset);
+            assert!(inst.address + inst.bytes.len() <= func.offset + func.len);
+        }
+
+        assert!(module.disassemble(0).is_err());
+        assert!(module.disassemble(2).is_err());
+    }
+}
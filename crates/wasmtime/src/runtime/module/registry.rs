@@ -6,7 +6,7 @@ use crate::component::Component;
 use crate::prelude::*;
 use crate::runtime::vm::VMWasmCallFunction;
 use crate::sync::{OnceLock, RwLock};
-use crate::{code_memory::CodeMemory, FrameInfo, Module};
+use crate::{code_memory::CodeMemory, CallerFrame, FrameInfo, Module};
 use alloc::collections::btree_map::{BTreeMap, Entry};
 use alloc::sync::Arc;
 use core::ptr::NonNull;
@@ -177,6 +177,29 @@ impl ModuleRegistry {
         Some((info, module))
     }
 
+    /// Fetches the module containing `pc` along with the offset of the
+    /// instruction at `pc` within the original wasm module, if known.
+    ///
+    /// Unlike `lookup_frame_info` this doesn't allocate, so it may be used from
+    /// a signal handler.
+    pub(crate) fn lookup_module_and_offset(&self, pc: usize) -> Option<(&Module, Option<usize>)> {
+        let (module, offset) = self.module_and_offset(pc)?;
+        let instr = wasmtime_environ::lookup_file_pos(
+            module.compiled_module().code_memory().address_map_data(),
+            offset,
+        );
+        let wasm_offset = instr.and_then(|i| i.file_offset()).map(|o| o as usize);
+        Some((module, wasm_offset))
+    }
+
+    /// Fetches a lightweight description of the frame at `pc`.
+    ///
+    /// Returns `None` if the pc isn't known to this registry.
+    pub(crate) fn lookup_caller_frame(&self, pc: usize) -> Option<CallerFrame> {
+        let (module, offset) = self.module_and_offset(pc)?;
+        CallerFrame::new(module.clone(), offset)
+    }
+
     pub fn wasm_to_array_trampoline(
         &self,
         sig: VMSharedTypeIndex,
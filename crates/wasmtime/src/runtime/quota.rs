@@ -0,0 +1,441 @@
+use crate::prelude::*;
+use crate::runtime::vm::SharedMemory;
+use crate::Engine;
+use alloc::sync::Arc;
+use core::fmt;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// How the limits of a [`QuotaGroup`] are divided between its member stores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuotaPolicy {
+    /// Resources are handed out on a first-come, first-served basis and a
+    /// single store may use up the entire limit of the group.
+    #[default]
+    Shared,
+    /// Each store may use at most its fair share of each limit, the limit
+    /// divided by the current number of stores in the group (rounded up).
+    ///
+    /// Shares are computed when a resource is requested, so a store which
+    /// acquired resources while the group was smaller keeps them when more
+    /// stores join, but can't acquire more until it's back within its share.
+    FairShare,
+}
+
+/// Used to build a [`QuotaGroup`].
+#[derive(Debug, Clone, Default)]
+pub struct QuotaGroupBuilder {
+    memory_size: Option<usize>,
+    instances: Option<usize>,
+    #[cfg(feature = "std")]
+    fuel_per_second: Option<u64>,
+    policy: QuotaPolicy,
+}
+
+impl QuotaGroupBuilder {
+    /// Creates a new [`QuotaGroupBuilder`] with no limits configured.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The maximum number of bytes of linear memory, summed over all memories
+    /// of all stores in the group.
+    ///
+    /// Creating or growing a linear memory beyond this limit will fail, in the
+    /// same way as when a [`ResourceLimiter`](crate::ResourceLimiter) rejects
+    /// the request.
+    ///
+    /// Shared memories are counted as well: those defined by a module, or
+    /// grown by wasm, are charged like any other memory, and a
+    /// [`SharedMemory`](crate::SharedMemory) created by the host is charged
+    /// its size at the time it's first imported into a store of the group.
+    /// Growth with [`SharedMemory::grow`](crate::SharedMemory::grow) isn't
+    /// associated with a store and isn't charged.
+    ///
+    /// By default, memory will not be limited.
+    pub fn memory_size(mut self, limit: usize) -> Self {
+        self.memory_size = Some(limit);
+        self
+    }
+
+    /// The maximum number of instances, summed over all stores in the group.
+    ///
+    /// Module instantiation will fail if this limit is exceeded. Instances are
+    /// only released when the store which owns them is dropped.
+    ///
+    /// By default, instances will not be limited.
+    pub fn instances(mut self, limit: usize) -> Self {
+        self.instances = Some(limit);
+        self
+    }
+
+    /// The maximum rate, in units of fuel per second, at which all stores in
+    /// the group may consume fuel.
+    ///
+    /// When a store in the group runs out of the fuel configured with
+    /// [`Store::set_fuel`](crate::Store::set_fuel) it's refilled from a bucket
+    /// shared by the whole group which holds at most one second's worth of
+    /// fuel. Each refill takes at most a tenth of `rate`, divided between the
+    /// stores in the group under [`QuotaPolicy::FairShare`], and once the
+    /// bucket is empty wasm traps with [`Trap::OutOfFuel`](crate::Trap).
+    ///
+    /// This requires [`Config::consume_fuel`](crate::Config::consume_fuel) to
+    /// be enabled.
+    ///
+    /// By default, fuel consumption will not be limited.
+    #[cfg(feature = "std")]
+    pub fn fuel_per_second(mut self, rate: u64) -> Self {
+        self.fuel_per_second = Some(rate);
+        self
+    }
+
+    /// How limits are divided between the stores of the group.
+    ///
+    /// This defaults to [`QuotaPolicy::Shared`].
+    pub fn policy(mut self, policy: QuotaPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Consumes this builder and returns a [`QuotaGroup`] for stores of
+    /// `engine`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a fuel rate is configured but `engine` doesn't have
+    /// fuel consumption enabled.
+    pub fn build(self, engine: &Engine) -> Result<QuotaGroup> {
+        #[cfg(feature = "std")]
+        let fuel = match self.fuel_per_second {
+            Some(rate) => {
+                ensure!(
+                    engine.tunables().consume_fuel,
+                    "a quota group fuel rate requires fuel consumption to be enabled"
+                );
+                Some(FuelBucket::new(rate))
+            }
+            None => None,
+        };
+        Ok(QuotaGroup(Arc::new(QuotaGroupInner {
+            engine: engine.clone(),
+            memory_limit: self.memory_size.unwrap_or(usize::MAX),
+            instance_limit: self.instances.unwrap_or(usize::MAX),
+            policy: self.policy,
+            members: AtomicUsize::new(0),
+            memory: AtomicUsize::new(0),
+            instances: AtomicUsize::new(0),
+            #[cfg(feature = "std")]
+            fuel,
+        })))
+    }
+}
+
+/// Aggregate resource limits shared by several [`Store`](crate::Store)s.
+///
+/// Limits configured with [`Store::limiter`](crate::Store::limiter) only apply
+/// to a single store, so a tenant which is able to create several stores can
+/// multiply its resources. Stores which join the same group with
+/// [`Store::join_quota_group`](crate::Store::join_quota_group) instead share
+/// the limits configured with a [`QuotaGroupBuilder`], in addition to their own
+/// per-store limits.
+///
+/// A store leaves its group, and releases everything charged to it, when it's
+/// dropped. This type is cheaply clonable and all clones refer to the same
+/// group.
+///
+/// # Examples
+///
+/// ```
+/// use wasmtime::*;
+///
+/// # fn main() -> Result<()> {
+/// let engine = Engine::default();
+/// let group = QuotaGroupBuilder::new().instances(2).build(&engine)?;
+/// let module = Module::new(&engine, "(module)")?;
+///
+/// let mut a = Store::new(&engine, ());
+/// a.join_quota_group(&group)?;
+/// let mut b = Store::new(&engine, ());
+/// b.join_quota_group(&group)?;
+///
+/// Instance::new(&mut a, &module, &[])?;
+/// Instance::new(&mut b, &module, &[])?;
+///
+/// // The group's limit is reached even though each store only has a single
+/// // instance.
+/// assert!(Instance::new(&mut a, &module, &[]).is_err());
+///
+/// // Dropping a store releases its instances.
+/// drop(b);
+/// Instance::new(&mut a, &module, &[])?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct QuotaGroup(Arc<QuotaGroupInner>);
+
+struct QuotaGroupInner {
+    engine: Engine,
+    memory_limit: usize,
+    instance_limit: usize,
+    policy: QuotaPolicy,
+    members: AtomicUsize,
+    memory: AtomicUsize,
+    instances: AtomicUsize,
+    #[cfg(feature = "std")]
+    fuel: Option<FuelBucket>,
+}
+
+impl QuotaGroup {
+    /// Returns the number of stores currently in this group.
+    pub fn members(&self) -> usize {
+        self.0.members.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of bytes of linear memory currently charged to this
+    /// group.
+    pub fn memory_size(&self) -> usize {
+        self.0.memory.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of instances currently charged to this group.
+    pub fn instances(&self) -> usize {
+        self.0.instances.load(Ordering::Relaxed)
+    }
+
+    /// Returns the part of `limit` which each member is held to.
+    fn share(&self, limit: usize) -> usize {
+        match self.0.policy {
+            QuotaPolicy::Shared => limit,
+            QuotaPolicy::FairShare => limit.div_ceil(self.members().max(1)),
+        }
+    }
+}
+
+impl fmt::Debug for QuotaGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuotaGroup")
+            .field("policy", &self.0.policy)
+            .field("members", &self.members())
+            .field("memory_size", &self.memory_size())
+            .field("instances", &self.instances())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A store's membership in a [`QuotaGroup`], tracking what has been charged to
+/// the group on the store's behalf.
+pub(crate) struct QuotaMember {
+    group: QuotaGroup,
+    memory: usize,
+    instances: usize,
+    // The amount of memory charged by the last successful `reserve_memory`,
+    // refunded if the growth it permitted then fails.
+    pending_memory: usize,
+    // The shared memories which have been charged to the group, so that a
+    // shared memory imported several times is only charged once.
+    shared_memories: Vec<SharedMemory>,
+}
+
+/// What had been charged to a [`QuotaMember`]'s group at some point, used to
+/// refund everything charged since then if an operation fails partway.
+pub(crate) struct QuotaCheckpoint {
+    memory: usize,
+    instances: usize,
+    shared_memories: usize,
+}
+
+impl QuotaMember {
+    pub(crate) fn join(engine: &Engine, group: &QuotaGroup) -> Result<QuotaMember> {
+        ensure!(
+            Engine::same(engine, &group.0.engine),
+            "cross-`Engine` quota group membership is not supported"
+        );
+        group.0.members.fetch_add(1, Ordering::Relaxed);
+        Ok(QuotaMember {
+            group: group.clone(),
+            memory: 0,
+            instances: 0,
+            pending_memory: 0,
+            shared_memories: Vec::new(),
+        })
+    }
+
+    /// Charges a new instance to the group.
+    ///
+    /// Nothing is charged if this fails.
+    pub(crate) fn reserve_instance(&mut self) -> Result<()> {
+        let limit = self.group.0.instance_limit;
+        if self.instances >= self.group.share(limit) || !reserve(&self.group.0.instances, 1, limit)
+        {
+            return Err(crate::ErrorKind::ResourceLimit.tag(format_err!(
+                "resource limit exceeded: quota group instance limit of {limit} reached"
+            )));
+        }
+        self.instances += 1;
+        Ok(())
+    }
+
+    /// Charges the current size of `memory` to the group unless it has been
+    /// charged already.
+    ///
+    /// Nothing is charged if this fails.
+    pub(crate) fn reserve_shared_memory(&mut self, memory: &SharedMemory) -> Result<()> {
+        if self.has_shared_memory(memory) {
+            return Ok(());
+        }
+        let bytes = memory.byte_size();
+        if !self.reserve_memory(bytes) {
+            let limit = self.group.0.memory_limit;
+            return Err(crate::ErrorKind::ResourceLimit.tag(format_err!(
+                "resource limit exceeded: quota group memory limit of {limit} bytes reached"
+            )));
+        }
+        self.pending_memory = 0;
+        self.shared_memories.push(memory.clone());
+        Ok(())
+    }
+
+    /// Records that `memory`, which was just created with its size charged by
+    /// `reserve_memory`, is a shared memory so that it's not charged again if
+    /// it's imported.
+    pub(crate) fn shared_memory_created(&mut self, memory: &SharedMemory) {
+        if !self.has_shared_memory(memory) {
+            self.shared_memories.push(memory.clone());
+        }
+    }
+
+    fn has_shared_memory(&self, memory: &SharedMemory) -> bool {
+        self.shared_memories
+            .iter()
+            .any(|m| m.vmmemory_ptr() == memory.vmmemory_ptr())
+    }
+
+    /// Charges `bytes` of memory growth to the group, returning whether the
+    /// growth is permitted.
+    pub(crate) fn reserve_memory(&mut self, bytes: usize) -> bool {
+        let limit = self.group.0.memory_limit;
+        let within_share = self
+            .memory
+            .checked_add(bytes)
+            .is_some_and(|n| n <= self.group.share(limit));
+        if !within_share || !reserve(&self.group.0.memory, bytes, limit) {
+            return false;
+        }
+        self.memory += bytes;
+        self.pending_memory = bytes;
+        true
+    }
+
+    /// Refunds the memory charged for a growth which then failed.
+    pub(crate) fn memory_grow_failed(&mut self) {
+        let bytes = core::mem::take(&mut self.pending_memory);
+        self.memory -= bytes;
+        self.group.0.memory.fetch_sub(bytes, Ordering::Relaxed);
+    }
+
+    /// Returns what has been charged to the group so far.
+    pub(crate) fn checkpoint(&self) -> QuotaCheckpoint {
+        QuotaCheckpoint {
+            memory: self.memory,
+            instances: self.instances,
+            shared_memories: self.shared_memories.len(),
+        }
+    }
+
+    /// Refunds everything charged to the group since `checkpoint` was taken.
+    pub(crate) fn rollback(&mut self, checkpoint: QuotaCheckpoint) {
+        let group = &self.group.0;
+        if let Some(memory) = self.memory.checked_sub(checkpoint.memory) {
+            group.memory.fetch_sub(memory, Ordering::Relaxed);
+            self.memory = checkpoint.memory;
+        }
+        if let Some(instances) = self.instances.checked_sub(checkpoint.instances) {
+            group.instances.fetch_sub(instances, Ordering::Relaxed);
+            self.instances = checkpoint.instances;
+        }
+        self.shared_memories.truncate(checkpoint.shared_memories);
+        self.pending_memory = 0;
+    }
+
+    /// Takes fuel for this store from the group, returning zero if the group
+    /// has no fuel left or doesn't limit fuel.
+    pub(crate) fn take_fuel(&mut self) -> u64 {
+        #[cfg(feature = "std")]
+        if let Some(bucket) = &self.group.0.fuel {
+            let members = match self.group.0.policy {
+                QuotaPolicy::Shared => 1,
+                QuotaPolicy::FairShare => self.group.members().max(1),
+            };
+            return bucket.take(members);
+        }
+        0
+    }
+}
+
+impl Drop for QuotaMember {
+    fn drop(&mut self) {
+        let group = &self.group.0;
+        group.memory.fetch_sub(self.memory, Ordering::Relaxed);
+        group.instances.fetch_sub(self.instances, Ordering::Relaxed);
+        group.members.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Atomically adds `amount` to `counter` unless that would exceed `limit`.
+fn reserve(counter: &AtomicUsize, amount: usize, limit: usize) -> bool {
+    counter
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+            n.checked_add(amount).filter(|n| *n <= limit)
+        })
+        .is_ok()
+}
+
+/// A token bucket holding at most one second's worth of fuel.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct FuelBucket {
+    rate: u64,
+    state: std::sync::Mutex<FuelBucketState>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct FuelBucketState {
+    fuel: u64,
+    refilled_at: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl FuelBucket {
+    fn new(rate: u64) -> FuelBucket {
+        FuelBucket {
+            rate,
+            state: std::sync::Mutex::new(FuelBucketState {
+                fuel: rate,
+                refilled_at: std::time::Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes up to a tenth of the rate, divided between `members`, from this
+    /// bucket.
+    fn take(&self, members: usize) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        let now = std::time::Instant::now();
+        let elapsed = now.duration_since(state.refilled_at);
+        let refill = elapsed.as_nanos() * u128::from(self.rate) / 1_000_000_000;
+        // Only move the refill time forward once some fuel has been added so
+        // that frequent calls don't lose fractional amounts of fuel.
+        if refill > 0 {
+            let refill = u64::try_from(refill).unwrap_or(u64::MAX);
+            state.fuel = state.fuel.saturating_add(refill).min(self.rate);
+            state.refilled_at = now;
+        }
+
+        let members = u64::try_from(members).unwrap_or(u64::MAX);
+        let slice = (self.rate / 10 / members).max(1);
+        let fuel = slice.min(state.fuel);
+        state.fuel -= fuel;
+        fuel
+    }
+}
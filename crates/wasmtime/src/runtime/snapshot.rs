@@ -0,0 +1,541 @@
+//! Snapshots of the WebAssembly state of a store, see [`Store::snapshot`].
+//!
+//! A snapshot records the contents of every memory, table, and global in a
+//! store, in the order that they were created. It's restored into a store in
+//! which the same modules were instantiated in the same order, which
+//! recreates the same entities, by overwriting their contents.
+//!
+//! Function references can't be serialized directly, so they're recorded as
+//! the index of the instance, in creation order, whose function index space
+//! they were found in, along with their index in that space. Each instance is
+//! identified by a hash of its module's compiled code and data, so a snapshot
+//! is only restored into instances of the same modules.
+//!
+//! Continuations in tables are recorded as the function that they'll run if
+//! they haven't been resumed yet, and restored by creating new continuations
+//! of those functions. Consumed continuations are restored as new consumed
+//! continuations. The state of a continuation which has been resumed lives on
+//! its native stack, which can't be serialized, so a snapshot can't be taken
+//! while a table holds one, or one with arguments bound by `cont.bind`.
+//! Globals of continuation types aren't supported yet, and host
+//! [`Continuation`](crate::Continuation)s are neither recorded nor affected by
+//! a restore.
+//!
+//! Shared memories can be accessed concurrently by other threads, and other
+//! stores, while a snapshot is taken or restored, so stores with shared
+//! memories can't be snapshotted.
+//!
+//! [`Store::snapshot`]: crate::Store::snapshot
+
+use crate::hash_map::HashMap;
+use crate::prelude::*;
+use crate::runtime::vm::continuation::optimized::{self, ContSnapshot};
+use crate::runtime::vm::continuation::VMContObj;
+use crate::runtime::vm::{self, TrapReason, VMFuncRef};
+use crate::store::StoreOpaque;
+use crate::{
+    Func, Global, HeapType, Instance, Memory, MemoryType, Mutability, Ref, RefType,
+    StoreContextMut, Table, Val, V128,
+};
+use core::ptr::NonNull;
+use serde_derive::{Deserialize, Serialize};
+
+/// The version of the snapshot format, bumped whenever it changes.
+const VERSION: u32 = 2;
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    version: u32,
+    /// The hash of each instance's module, used to check that the store
+    /// being restored into has the same instances.
+    instances: Vec<u64>,
+    memories: Vec<MemorySnapshot>,
+    tables: Vec<Vec<SnapshotRef>>,
+    /// The values of mutable globals, or `None` for immutable globals.
+    globals: Vec<Option<SnapshotVal>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MemorySnapshot {
+    page_size: u64,
+    data: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum SnapshotVal {
+    I32(i32),
+    I64(i64),
+    F32(u32),
+    F64(u64),
+    V128(u128),
+    Ref(SnapshotRef),
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+enum SnapshotRef {
+    Null,
+    Func {
+        instance: u32,
+        index: u32,
+    },
+    /// A continuation which hasn't been resumed yet and will run the function
+    /// at `index` of `instance`.
+    FreshCont {
+        instance: u32,
+        index: u32,
+    },
+    ConsumedCont,
+}
+
+/// The instances of a store along with the hashes of their modules and the
+/// `VMFuncRef`s of their functions, indexed by instance and function index.
+struct Instances {
+    modules: Vec<u64>,
+    funcs: Vec<Vec<NonNull<VMFuncRef>>>,
+}
+
+impl Instances {
+    fn new(store: &mut StoreOpaque) -> Instances {
+        let instances = store.all_instances().collect::<Vec<Instance>>();
+        Instances {
+            modules: instances
+                .iter()
+                .map(|instance| {
+                    let module = instance._module(store);
+                    module.code_object().code_memory().content_hash()
+                })
+                .collect(),
+            funcs: instances
+                .iter()
+                .map(|instance| instance.all_func_refs(store))
+                .collect(),
+        }
+    }
+
+    fn func_ref(&self, instance: u32, index: u32) -> Result<NonNull<VMFuncRef>> {
+        self.funcs
+            .get(instance as usize)
+            .and_then(|funcs| funcs.get(index as usize))
+            .copied()
+            .ok_or_else(|| anyhow!("invalid function reference in snapshot"))
+    }
+}
+
+/// Captures the state of `store`, see [`Store::snapshot`](crate::Store::snapshot).
+pub(crate) fn snapshot<T>(mut store: StoreContextMut<'_, T>) -> Result<Vec<u8>> {
+    let instances = Instances::new(store.0);
+    let func_refs = instances
+        .funcs
+        .iter()
+        .enumerate()
+        .flat_map(|(i, funcs)| {
+            funcs.iter().enumerate().map(move |(j, f)| {
+                let location = SnapshotRef::Func {
+                    instance: u32::try_from(i).unwrap(),
+                    index: u32::try_from(j).unwrap(),
+                };
+                (f.as_ptr() as usize, location)
+            })
+        })
+        .rev()
+        .collect::<HashMap<_, _>>();
+    let func_location = |func_ref: NonNull<VMFuncRef>| -> Result<SnapshotRef> {
+        func_refs
+            .get(&(func_ref.as_ptr() as usize))
+            .copied()
+            .ok_or_else(|| anyhow!("cannot snapshot a reference to a host function"))
+    };
+    let snapshot_ref = |store: &mut StoreOpaque, r: Ref| -> Result<SnapshotRef> {
+        match r {
+            Ref::Func(None) | Ref::Extern(None) | Ref::Any(None) => Ok(SnapshotRef::Null),
+            Ref::Func(Some(f)) => func_location(f.vm_func_ref(store)),
+            Ref::Extern(Some(_)) | Ref::Any(Some(_)) => {
+                bail!("cannot snapshot a reference to a GC object")
+            }
+        }
+    };
+    let snapshot_cont = |contobj: Option<VMContObj>| -> Result<SnapshotRef> {
+        let Some(contobj) = contobj else {
+            return Ok(SnapshotRef::Null);
+        };
+        // Like `resume`, this relies on the `VMContRef`s of consumed
+        // continuations not having been deallocated yet, see
+        // `optimized::drop_cont_ref`.
+        match unsafe { optimized::snapshot_cont(contobj) } {
+            ContSnapshot::Consumed => Ok(SnapshotRef::ConsumedCont),
+            ContSnapshot::Fresh(func_ref) => {
+                match func_location(NonNull::new(func_ref).unwrap())? {
+                    SnapshotRef::Func { instance, index } => {
+                        Ok(SnapshotRef::FreshCont { instance, index })
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            ContSnapshot::Bound => bail!("cannot snapshot a continuation with bound arguments"),
+            ContSnapshot::Started => bail!("cannot snapshot a continuation that has been resumed"),
+        }
+    };
+
+    let mut memories = Vec::new();
+    for memory in collect_memories(store.0) {
+        check_not_shared(&memory.ty(&store))?;
+        memories.push(MemorySnapshot {
+            page_size: memory.page_size(&store),
+            data: memory.data(&store).to_vec(),
+        });
+    }
+
+    let mut tables = Vec::new();
+    for table in collect_tables(store.0) {
+        let elements = if matches!(table.ty(&store).element().heap_type().top(), HeapType::Cont) {
+            check_cont_supported()?;
+            (0..table.size(&store))
+                .map(|i| snapshot_cont(table.get_cont(store.0, i).unwrap()))
+                .collect::<Result<_>>()?
+        } else {
+            (0..table.size(&store))
+                .map(|i| {
+                    let r = table.get(&mut store, i).unwrap();
+                    snapshot_ref(store.0, r)
+                })
+                .collect::<Result<_>>()?
+        };
+        tables.push(elements);
+    }
+
+    let mut globals = Vec::new();
+    for global in collect_globals(store.0) {
+        let ty = global.ty(&store);
+        if ty.mutability() == Mutability::Const {
+            globals.push(None);
+            continue;
+        }
+        if let Some(r) = ty.content().as_ref() {
+            if matches!(r.heap_type().top(), HeapType::Cont) {
+                bail!("cannot snapshot a global of a continuation type");
+            }
+        }
+        let val = match global.get(&mut store) {
+            Val::I32(x) => SnapshotVal::I32(x),
+            Val::I64(x) => SnapshotVal::I64(x),
+            Val::F32(x) => SnapshotVal::F32(x),
+            Val::F64(x) => SnapshotVal::F64(x),
+            Val::V128(x) => SnapshotVal::V128(x.as_u128()),
+            Val::FuncRef(f) => SnapshotVal::Ref(snapshot_ref(store.0, Ref::Func(f))?),
+            Val::ExternRef(r) => SnapshotVal::Ref(snapshot_ref(store.0, Ref::Extern(r))?),
+            Val::AnyRef(r) => SnapshotVal::Ref(snapshot_ref(store.0, Ref::Any(r))?),
+        };
+        globals.push(Some(val));
+    }
+
+    let snapshot = Snapshot {
+        version: VERSION,
+        instances: instances.modules,
+        memories,
+        tables,
+        globals,
+    };
+    Ok(postcard::to_allocvec(&snapshot)?)
+}
+
+/// Restores the state of `store` from `bytes`, see
+/// [`Store::restore`](crate::Store::restore).
+pub(crate) fn restore<T>(mut store: StoreContextMut<'_, T>, bytes: &[u8]) -> Result<()> {
+    let snapshot: Snapshot = postcard::from_bytes(bytes).context("invalid snapshot")?;
+    ensure!(
+        snapshot.version == VERSION,
+        "unsupported snapshot version {}, expected {VERSION}",
+        snapshot.version
+    );
+
+    // Check that this store's entities match the snapshot's, and resolve the
+    // snapshot's references and values against them, before modifying any of
+    // them. Only growing memories and tables can fail after this point.
+    let instances = Instances::new(store.0);
+    ensure!(
+        instances.modules == snapshot.instances,
+        "the instances of this store don't match the snapshot: they must be \
+         instances of the same modules, created in the same order"
+    );
+    let memories = collect_memories(store.0);
+    let tables = collect_tables(store.0);
+    let globals = collect_globals(store.0);
+    ensure!(
+        memories.len() == snapshot.memories.len()
+            && tables.len() == snapshot.tables.len()
+            && globals.len() == snapshot.globals.len(),
+        "the memories, tables, and globals of this store don't match the snapshot"
+    );
+
+    let restore_ref = |store: &mut StoreOpaque, r: &SnapshotRef, ty: &HeapType| -> Result<Ref> {
+        match *r {
+            SnapshotRef::Null => Ok(Ref::null(ty)),
+            SnapshotRef::Func { instance, index } => {
+                let func_ref = instances.func_ref(instance, index)?;
+                Ok(Ref::Func(Some(unsafe {
+                    Func::from_vm_func_ref(store, func_ref)
+                })))
+            }
+            SnapshotRef::FreshCont { .. } | SnapshotRef::ConsumedCont => {
+                bail!("continuation in snapshot where a reference was expected")
+            }
+        }
+    };
+
+    let mut memory_pages = Vec::new();
+    for (memory, saved) in memories.iter().zip(&snapshot.memories) {
+        let ty = memory.ty(&store);
+        check_not_shared(&ty)?;
+        ensure!(
+            ty.page_size() == saved.page_size,
+            "memory page size doesn't match the snapshot"
+        );
+        let len = u64::try_from(saved.data.len()).unwrap();
+        ensure!(
+            len % saved.page_size == 0,
+            "memory size in snapshot isn't a whole number of pages"
+        );
+        let pages = len / saved.page_size;
+        ensure!(
+            memory.size(&store) <= pages,
+            "memory is larger than in the snapshot"
+        );
+        ensure!(
+            ty.maximum().map_or(true, |max| pages <= max),
+            "memory's maximum size is smaller than in the snapshot"
+        );
+        memory_pages.push(pages);
+    }
+
+    let mut table_elements = Vec::new();
+    for (table, saved) in tables.iter().zip(&snapshot.tables) {
+        let ty = table.ty(&store);
+        let len = u64::try_from(saved.len()).unwrap();
+        ensure!(
+            table.size(&store) <= len,
+            "table is larger than in the snapshot"
+        );
+        ensure!(
+            ty.maximum().map_or(true, |max| len <= max),
+            "table's maximum size is smaller than in the snapshot"
+        );
+        let elements = if matches!(ty.element().heap_type().top(), HeapType::Cont) {
+            check_cont_supported()?;
+            let elements = saved
+                .iter()
+                .map(|r| restore_cont(store.0, &instances, r, ty.element()))
+                .collect::<Result<Vec<_>>>()?;
+            TableElements::Conts(elements)
+        } else {
+            let elements = saved
+                .iter()
+                .map(|r| {
+                    let r = restore_ref(store.0, r, ty.element().heap_type())?;
+                    ensure!(
+                        r._matches_ty(store.0, ty.element())?,
+                        "table element type doesn't match the snapshot"
+                    );
+                    Ok(r)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            TableElements::Refs(elements)
+        };
+        table_elements.push(elements);
+    }
+
+    let mut global_vals = Vec::new();
+    for (global, saved) in globals.iter().zip(&snapshot.globals) {
+        let Some(saved) = saved else {
+            global_vals.push(None);
+            continue;
+        };
+        let ty = global.ty(&store);
+        ensure!(
+            ty.mutability() == Mutability::Var,
+            "global mutability doesn't match the snapshot"
+        );
+        let val = match saved {
+            SnapshotVal::I32(x) => Val::I32(*x),
+            SnapshotVal::I64(x) => Val::I64(*x),
+            SnapshotVal::F32(x) => Val::F32(*x),
+            SnapshotVal::F64(x) => Val::F64(*x),
+            SnapshotVal::V128(x) => Val::V128(V128::from(*x)),
+            SnapshotVal::Ref(r) => {
+                let ty = ty
+                    .content()
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("global type doesn't match the snapshot"))?;
+                restore_ref(store.0, r, ty.heap_type())?.into()
+            }
+        };
+        ensure!(
+            val._matches_ty(store.0, ty.content())?,
+            "global type doesn't match the snapshot"
+        );
+        global_vals.push(Some(val));
+    }
+
+    // Grow everything before overwriting any contents, so that a growth
+    // failure leaves the existing contents as they were.
+    for (memory, pages) in memories.iter().zip(&memory_pages) {
+        let current = memory.size(&store);
+        if *pages > current {
+            memory.grow(&mut store, pages - current)?;
+        }
+    }
+    for (table, elements) in tables.iter().zip(&table_elements) {
+        let current = table.size(&store);
+        let len = elements.len();
+        if len > current {
+            // Grow with one of the snapshot's own elements, which is valid
+            // even for tables of non-nullable references.
+            let init = usize::try_from(current).unwrap();
+            match elements {
+                TableElements::Refs(refs) => {
+                    table.grow(&mut store, len - current, refs[init].clone())?;
+                }
+                TableElements::Conts(conts) => unsafe {
+                    table.grow_cont(store.0, len - current, conts[init])?;
+                },
+            }
+        }
+    }
+
+    for (memory, saved) in memories.iter().zip(&snapshot.memories) {
+        memory.data_mut(&mut store).copy_from_slice(&saved.data);
+    }
+    for (table, elements) in tables.iter().zip(table_elements) {
+        match elements {
+            TableElements::Refs(refs) => {
+                for (i, r) in (0..).zip(refs) {
+                    table.set(&mut store, i, r)?;
+                }
+            }
+            TableElements::Conts(conts) => {
+                for (i, c) in (0..).zip(conts) {
+                    unsafe { table.set_cont(store.0, i, c)? };
+                }
+            }
+        }
+    }
+    for (global, val) in globals.iter().zip(global_vals) {
+        if let Some(val) = val {
+            global.set(&mut store, val)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates the continuation object to restore from `r` into a table whose
+/// elements are of type `ty`.
+fn restore_cont(
+    store: &mut StoreOpaque,
+    instances: &Instances,
+    r: &SnapshotRef,
+    ty: &RefType,
+) -> Result<Option<VMContObj>> {
+    let (func_ref, param_count, result_count) = match *r {
+        SnapshotRef::Null => {
+            ensure!(
+                ty.is_nullable(),
+                "table element type doesn't match the snapshot"
+            );
+            return Ok(None);
+        }
+        SnapshotRef::Func { .. } => {
+            bail!("reference in snapshot where a continuation was expected")
+        }
+        SnapshotRef::FreshCont { instance, index } => {
+            let func_ref = instances.func_ref(instance, index)?;
+            let func = unsafe { Func::from_vm_func_ref(store, func_ref) };
+            if let HeapType::ConcreteCont(cont_ty) = ty.heap_type() {
+                ensure!(
+                    func._matches_ty(store, &cont_ty.func_type()),
+                    "table element type doesn't match the snapshot"
+                );
+            }
+            let func_ty = func.load_ty(store);
+            let param_count = u32::try_from(func_ty.params().len()).unwrap();
+            let result_count = u32::try_from(func_ty.results().len()).unwrap();
+            (func_ref.as_ptr(), param_count, result_count)
+        }
+        // A consumed continuation is restored as a new continuation which is
+        // consumed right away, and so never runs its function.
+        SnapshotRef::ConsumedCont => (core::ptr::null_mut(), 0, 0),
+    };
+    let contref = unsafe {
+        let vmstore = &mut *store.traitobj();
+        vm::Instance::from_vmctx(store.default_caller(), |instance| {
+            optimized::cont_new(
+                vmstore,
+                instance,
+                func_ref.cast(),
+                param_count,
+                result_count,
+            )
+        })
+    }
+    .map_err(|reason| match reason {
+        TrapReason::User(error) => error,
+        _ => unreachable!("creating a continuation only fails with user errors"),
+    })?;
+    let contref = NonNull::new(contref).unwrap();
+    let revision = unsafe { contref.as_ref().revision };
+    let revision = match r {
+        SnapshotRef::ConsumedCont => revision.wrapping_add(1),
+        _ => revision,
+    };
+    Ok(Some(VMContObj::new(contref, revision)))
+}
+
+/// The elements to restore into a table, which are continuation objects for
+/// tables of continuations since those can't be represented as `Ref`s.
+enum TableElements {
+    Refs(Vec<Ref>),
+    Conts(Vec<Option<VMContObj>>),
+}
+
+impl TableElements {
+    fn len(&self) -> u64 {
+        let len = match self {
+            TableElements::Refs(refs) => refs.len(),
+            TableElements::Conts(conts) => conts.len(),
+        };
+        u64::try_from(len).unwrap()
+    }
+}
+
+fn check_not_shared(ty: &MemoryType) -> Result<()> {
+    ensure!(
+        !ty.is_shared(),
+        "cannot snapshot a store with a shared memory"
+    );
+    Ok(())
+}
+
+fn check_cont_supported() -> Result<()> {
+    if cfg!(all(
+        feature = "wasmfx_baseline",
+        not(feature = "wasmfx_no_baseline")
+    )) {
+        bail!("continuations can't be snapshotted with the baseline implementation");
+    }
+    Ok(())
+}
+
+fn collect_memories(store: &mut StoreOpaque) -> Vec<Memory> {
+    store.all_memories().collect()
+}
+
+fn collect_tables(store: &mut StoreOpaque) -> Vec<Table> {
+    let mut tables = Vec::new();
+    store.for_each_table(|_, table| tables.push(table));
+    tables
+}
+
+fn collect_globals(store: &mut StoreOpaque) -> Vec<Global> {
+    let mut globals = Vec::new();
+    store.for_each_global(|_, global| globals.push(global));
+    globals
+}
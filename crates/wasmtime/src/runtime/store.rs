@@ -76,38 +76,48 @@
 //! contents of `StoreOpaque`. This is an invariant that we, as the authors of
 //! `wasmtime`, must uphold for the public interface to be safe.
 
+use crate::hash_map::HashMap;
 use crate::hash_set::HashSet;
 use crate::instance::InstanceData;
 use crate::linker::Definition;
 use crate::module::RegisteredModuleId;
 use crate::prelude::*;
+use crate::runtime::backtrace_throttle::BacktraceThrottleState;
+use crate::runtime::quota::{QuotaCheckpoint, QuotaMember};
 use crate::runtime::vm::continuation::stack_chain::{StackChain, StackChainCell};
 use crate::runtime::vm::mpk::{self, ProtectionKey, ProtectionMask};
 use crate::runtime::vm::{
     Backtrace, ExportGlobal, GcRootsList, GcStore, InstanceAllocationRequest, InstanceAllocator,
     InstanceHandle, Interpreter, InterpreterRef, ModuleRuntimeInfo, OnDemandInstanceAllocator,
-    SignalHandler, StoreBox, StorePtr, Unwind, UnwindHost, UnwindPulley, VMContext, VMFuncRef,
-    VMGcRef, VMRuntimeLimits,
+    SendSyncPtr, SignalHandler, StoreBox, StorePtr, Unwind, UnwindHost, UnwindPulley, VMContext,
+    VMFuncRef, VMGcRef, VMGlobalDefinition, VMRuntimeLimits, VMTagDefinition, WasmFXStackCache,
 };
 use crate::trampoline::VMHostGlobalContext;
 use crate::type_registry::RegisteredType;
 use crate::RootSet;
-use crate::{module::ModuleRegistry, Engine, Module, Trap, Val, ValRaw};
-use crate::{Global, Instance, Memory, RootScope, Table, Uninhabited};
+use crate::{module::ModuleRegistry, Engine, Func, FuncType, Module, Trap, Val, ValRaw};
+use crate::{
+    BacktraceCaptureStats, BacktraceThrottle, Global, Instance, Memory, RootScope, Table,
+    Uninhabited, WasmFXStackCacheStats,
+};
 use alloc::sync::Arc;
 use core::cell::UnsafeCell;
 use core::fmt;
 use core::future::Future;
 use core::marker;
 use core::mem::{self, ManuallyDrop};
-use core::num::NonZeroU64;
+use core::num::{NonZeroU32, NonZeroU64};
 use core::ops::{Deref, DerefMut, Range};
 use core::pin::Pin;
-use core::ptr;
+use core::ptr::{self, NonNull};
 use core::task::{Context, Poll};
 use wasmtime_continuations::{CommonStackInformation, WasmFXConfig};
 use wasmtime_environ::TripleExt;
 
+#[cfg(feature = "async")]
+mod async_poll;
+#[cfg(feature = "async")]
+pub use self::async_poll::*;
 mod context;
 pub use self::context::*;
 mod data;
@@ -226,6 +236,17 @@ pub struct StoreInner<T> {
     call_hook: Option<CallHookInner<T>>,
     epoch_deadline_behavior:
         Option<Box<dyn FnMut(StoreContextMut<T>) -> Result<UpdateDeadline> + Send + Sync>>,
+    global_watchers: Vec<GlobalWatcher<T>>,
+    single_step_behavior:
+        Option<Box<dyn FnMut(StoreContextMut<T>, u32) -> Result<UpdateDeadline> + Send + Sync>>,
+    import_resolver: Option<Box<ImportResolver<T>>>,
+    /// Imports resolved so far by `import_resolver`, keyed by module and name.
+    lazy_imports: HashMap<(String, String), Func>,
+    /// Handlers registered with
+    /// [`Tag::yield_to_host`](crate::Tag::yield_to_host), keyed by the
+    /// address of the tag's definition.
+    #[cfg(feature = "async")]
+    suspend_handlers: HashMap<usize, (crate::Tag, Arc<SuspendHandler<T>>)>,
     // for comments about `ManuallyDrop`, see `Store::into_data`
     data: ManuallyDrop<T>,
 }
@@ -236,6 +257,27 @@ enum ResourceLimiterInner<T> {
     Async(Box<dyn FnMut(&mut T) -> &mut (dyn crate::ResourceLimiterAsync) + Send + Sync>),
 }
 
+/// The callback registered with [`Store::import_resolver`].
+type ImportResolver<T> =
+    dyn FnMut(StoreContextMut<'_, T>, &str, &str, &FuncType) -> Result<Func> + Send + Sync;
+
+/// A handler registered with [`Tag::yield_to_host`](crate::Tag::yield_to_host).
+#[cfg(feature = "async")]
+pub(crate) type SuspendHandler<T> = dyn for<'a> Fn(
+        StoreContextMut<'a, T>,
+        &'a [Val],
+        &'a mut [Val],
+    ) -> Box<dyn Future<Output = Result<()>> + Send + 'a>
+    + Send
+    + Sync;
+
+/// A callback registered with [`Global::watch`].
+struct GlobalWatcher<T> {
+    global: Global,
+    definition: SendSyncPtr<VMGlobalDefinition>,
+    callback: Box<dyn FnMut(StoreContextMut<'_, T>, Val) -> Result<()> + Send + Sync>,
+}
+
 /// An object that can take callbacks when the runtime enters or exits hostcalls.
 #[cfg(all(feature = "async", feature = "call-hook"))]
 #[async_trait::async_trait]
@@ -344,6 +386,9 @@ pub struct StoreOpaque {
     gc_roots_list: GcRootsList,
     // Types for which the embedder has created an allocator for.
     gc_host_alloc_types: HashSet<RegisteredType>,
+    #[cfg(feature = "gc")]
+    gc_stats: crate::GcStatsState,
+    memory_growth: crate::MemoryGrowthState,
 
     // Numbers of resources instantiated in this store, and their limits
     instance_count: usize,
@@ -352,6 +397,12 @@ pub struct StoreOpaque {
     memory_limit: usize,
     table_count: usize,
     table_limit: usize,
+    // The quota group this store has joined, if any, which additionally limits
+    // resources across all of the group's stores.
+    quota: Option<QuotaMember>,
+    // The deadlock monitor this store's thread has joined, if any.
+    #[cfg(feature = "threads")]
+    deadlock: Option<crate::runtime::deadlock::DeadlockMember>,
     #[cfg(feature = "async")]
     async_state: AsyncState,
 
@@ -361,6 +412,9 @@ pub struct StoreOpaque {
     // until the reserve is empty.
     fuel_reserve: u64,
     fuel_yield_interval: Option<NonZeroU64>,
+    // The amount of fuel in this store when fuel consumption was last recorded
+    // in the engine's metrics, see `record_fuel_consumed`.
+    fuel_recorded: u64,
     /// Indexed data within this `Store`, used to store information about
     /// globals, functions, memories, etc.
     ///
@@ -406,6 +460,40 @@ pub struct StoreOpaque {
     component_calls: crate::runtime::vm::component::CallContexts,
     #[cfg(feature = "component-model")]
     host_resource_data: crate::component::HostResourceData,
+    /// The cancellation flag of the cancellable component call in progress,
+    /// if any, see `component::Func::call_async_cancellable`.
+    #[cfg(all(feature = "component-model", feature = "async"))]
+    call_cancellation: Option<Arc<core::sync::atomic::AtomicBool>>,
+    /// Whether canonical ABI operations performed on behalf of components in
+    /// this store are logged, see [`Store::trace_canonical_abi`].
+    #[cfg(feature = "component-model")]
+    component_abi_trace: bool,
+
+    /// Shared libraries loaded with `Store::dlopen`.
+    dylink: crate::runtime::dylink::DylinkState,
+    /// Set while the host is resuming a `Continuation`.
+    host_resumption: Option<crate::runtime::vm::continuation::HostResumption>,
+
+    /// The number of wasm instructions which may execute before the
+    /// single-step callback is invoked, see `Store::set_single_step_budget`.
+    single_step_budget: u64,
+
+    /// The counter which code compiled with coverage instrumentation
+    /// increments when no coverage map is configured, see
+    /// `Store::set_coverage_map`.
+    coverage_scratch: UnsafeCell<u8>,
+
+    /// State of the throttle configured with `Store::set_backtrace_throttle`,
+    /// if one was ever configured.
+    backtrace_throttle: Option<Box<BacktraceThrottleState>>,
+
+    /// Stacks of returned continuations kept for reuse, see
+    /// `Config::wasmfx_stack_cache_size`.
+    wasmfx_stack_cache: WasmFXStackCache,
+
+    /// The total size in bytes of the stacks of live continuations, as
+    /// reported to `ResourceLimiter::continuation_stack_allocating`.
+    continuation_stack_bytes: usize,
 
     /// State related to the Pulley interpreter if that's enabled and configured
     /// for this store's `Engine`. This is `None` if pulley was disabled at
@@ -419,6 +507,8 @@ struct AsyncState {
     current_poll_cx: UnsafeCell<PollContext>,
     /// The last fiber stack that was in use by this store.
     last_fiber_stack: Option<wasmtime_fiber::FiberStack>,
+    /// The poll budget for async host calls, see `Store::async_poll_budget`.
+    poll_budget: UnsafeCell<PollBudget>,
 }
 
 #[cfg(feature = "async")]
@@ -572,6 +662,9 @@ impl<T> Store<T> {
                 gc_roots: RootSet::default(),
                 gc_roots_list: GcRootsList::default(),
                 gc_host_alloc_types: HashSet::default(),
+                #[cfg(feature = "gc")]
+                gc_stats: Default::default(),
+                memory_growth: Default::default(),
                 modules: ModuleRegistry::default(),
                 func_refs: FuncRefs::default(),
                 host_globals: Vec::new(),
@@ -581,14 +674,19 @@ impl<T> Store<T> {
                 memory_limit: crate::DEFAULT_MEMORY_LIMIT,
                 table_count: 0,
                 table_limit: crate::DEFAULT_TABLE_LIMIT,
+                quota: None,
+                #[cfg(feature = "threads")]
+                deadlock: None,
                 #[cfg(feature = "async")]
                 async_state: AsyncState {
                     current_suspend: UnsafeCell::new(ptr::null_mut()),
                     current_poll_cx: UnsafeCell::new(PollContext::default()),
                     last_fiber_stack: None,
+                    poll_budget: UnsafeCell::new(PollBudget::default()),
                 },
                 fuel_reserve: 0,
                 fuel_yield_interval: None,
+                fuel_recorded: 0,
                 store_data: ManuallyDrop::new(StoreData::new()),
                 default_caller: InstanceHandle::null(),
                 hostcall_val_storage: Vec::new(),
@@ -601,6 +699,19 @@ impl<T> Store<T> {
                 component_calls: Default::default(),
                 #[cfg(feature = "component-model")]
                 host_resource_data: Default::default(),
+                #[cfg(all(feature = "component-model", feature = "async"))]
+                call_cancellation: None,
+                #[cfg(feature = "component-model")]
+                component_abi_trace: false,
+                dylink: Default::default(),
+                host_resumption: None,
+                single_step_budget: 0,
+                coverage_scratch: UnsafeCell::new(0),
+                backtrace_throttle: None,
+                wasmfx_stack_cache: WasmFXStackCache::new(
+                    engine.config().wasmfx_config.stack_cache_size,
+                ),
+                continuation_stack_bytes: 0,
                 interpreter: if cfg!(feature = "pulley") && engine.target().is_pulley() {
                     Some(Interpreter::new(engine))
                 } else {
@@ -610,6 +721,12 @@ impl<T> Store<T> {
             limiter: None,
             call_hook: None,
             epoch_deadline_behavior: None,
+            global_watchers: Vec::new(),
+            single_step_behavior: None,
+            import_resolver: None,
+            lazy_imports: HashMap::new(),
+            #[cfg(feature = "async")]
+            suspend_handlers: HashMap::new(),
             data: ManuallyDrop::new(data),
         });
 
@@ -637,6 +754,7 @@ impl<T> Store<T> {
                         wmemcheck: engine.config().wmemcheck,
                         pkey: None,
                         tunables: engine.tunables(),
+                        reserved: None,
                     })
                     .expect("failed to allocate default callee")
             };
@@ -663,6 +781,7 @@ impl<T> Store<T> {
             let stack_chain = inner.stack_chain.0.get();
             *stack_chain = StackChain::MainStack(inner.main_stack_information());
         }
+        inner.set_coverage_map(None).unwrap();
 
         Self {
             inner: ManuallyDrop::new(inner),
@@ -833,6 +952,37 @@ impl<T> Store<T> {
         inner.limiter = Some(ResourceLimiterInner::Async(Box::new(limiter)));
     }
 
+    /// Adds this store to the given [`QuotaGroup`](crate::QuotaGroup), sharing
+    /// its aggregate limits with all other stores in the group.
+    ///
+    /// The group's limits apply in addition to any limits configured with
+    /// [`Store::limiter`]. This store stays in the group until it's dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this store already belongs to a quota group, if
+    /// it already contains instances, or if `group` was created for a
+    /// different [`Engine`].
+    pub fn join_quota_group(&mut self, group: &crate::QuotaGroup) -> Result<()> {
+        self.inner.join_quota_group(group)
+    }
+
+    /// Adds the guest thread running in this store to the given
+    /// [`DeadlockMonitor`](crate::DeadlockMonitor), which then reports
+    /// probable deadlocks between it and the monitor's other threads.
+    ///
+    /// Returns the id of this store's thread within the monitor, as used in
+    /// [`DeadlockReport`](crate::DeadlockReport)s. This store stays in the
+    /// monitor until it's dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this store already belongs to a deadlock monitor.
+    #[cfg(feature = "threads")]
+    pub fn join_deadlock_monitor(&mut self, monitor: &crate::DeadlockMonitor) -> Result<u64> {
+        self.inner.join_deadlock_monitor(monitor)
+    }
+
     /// Configures an async function that runs on calls and returns between
     /// WebAssembly and host code. For the non-async equivalent of this method,
     /// see [`Store::call_hook`].
@@ -907,6 +1057,133 @@ impl<T> Store<T> {
         self.inner.gc_async().await;
     }
 
+    /// Returns statistics about this store's GC heap and the garbage
+    /// collections performed in it so far.
+    ///
+    /// See [`GcStats`](crate::GcStats) for more information.
+    ///
+    /// This method is only available when the `gc` Cargo feature is enabled.
+    #[cfg(feature = "gc")]
+    pub fn gc_stats(&self) -> crate::GcStats {
+        self.inner.gc_stats()
+    }
+
+    /// Registers a hook which is invoked immediately before and after every
+    /// garbage collection in this store.
+    ///
+    /// The hook is passed the [`GcEvent`](crate::GcEvent) that occurred along
+    /// with a snapshot of this store's [`GcStats`](crate::GcStats) at that
+    /// point, which can be used to export metrics or to implement policies
+    /// reacting to memory pressure. This includes collections triggered
+    /// automatically by Wasmtime, not just those from [`Store::gc`].
+    ///
+    /// Registering a hook replaces any previously registered hook. The hook
+    /// must not panic, as it may be invoked while Wasm is on the stack.
+    ///
+    /// This method is only available when the `gc` Cargo feature is enabled.
+    #[cfg(feature = "gc")]
+    pub fn on_gc(
+        &mut self,
+        hook: impl FnMut(crate::GcEvent, &crate::GcStats) + Send + Sync + 'static,
+    ) {
+        self.inner.on_gc(Box::new(hook));
+    }
+
+    /// Returns statistics about the growth of this store's linear memories
+    /// so far.
+    ///
+    /// See [`MemoryGrowthStats`](crate::MemoryGrowthStats) for more
+    /// information.
+    pub fn memory_growth_stats(&self) -> crate::MemoryGrowthStats {
+        self.inner.memory_growth_stats()
+    }
+
+    /// Registers a hook which is invoked after every attempt to grow one of
+    /// this store's linear memories, whether it succeeded or not.
+    ///
+    /// The hook is passed a [`MemoryGrowth`](crate::MemoryGrowth) describing
+    /// the memory's size and reservation along with the outcome of the
+    /// attempt, including why it failed. This covers `memory.grow`
+    /// instructions, so it can be used to observe a guest's heap allocator
+    /// without modifying the guest. Unlike a
+    /// [`ResourceLimiter`](crate::ResourceLimiter) the hook can't affect the
+    /// outcome, and is also invoked for failures and rejections.
+    ///
+    /// Registering a hook replaces any previously registered hook. The hook
+    /// must not panic, as it may be invoked while Wasm is on the stack.
+    pub fn on_memory_grow(
+        &mut self,
+        hook: impl FnMut(&crate::MemoryGrowth) + Send + Sync + 'static,
+    ) {
+        self.inner.on_memory_grow(Box::new(hook));
+    }
+
+    /// Serializes the contents of all of this store's memories, tables, and
+    /// globals into a blob which can later be passed to [`Store::restore`],
+    /// possibly in another process.
+    ///
+    /// This is only available on [`Store`], rather than on contexts given to
+    /// host functions, so no WebAssembly or host frames are live when a
+    /// snapshot is taken. Memory contents are copied verbatim, so snapshots
+    /// are portable between hosts with the same engine configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store contains state which can't be
+    /// serialized:
+    ///
+    /// * Continuations in tables that have been resumed, or that have
+    ///   arguments bound by `cont.bind`. Their state lives on native stacks,
+    ///   which can't be captured in a portable way. Continuations that haven't
+    ///   been resumed are captured as the function they'll run, and consumed
+    ///   continuations are captured as such.
+    /// * Globals of continuation types. Host
+    ///   [`Continuation`](crate::Continuation)s aren't captured either.
+    /// * Shared memories, which other threads may access concurrently.
+    /// * Non-null references to GC objects.
+    /// * References to host functions, created with [`Func::new`] or
+    ///   [`Func::wrap`](crate::Func::wrap), stored in tables or globals.
+    pub fn snapshot(&mut self) -> Result<Vec<u8>> {
+        crate::runtime::snapshot::snapshot(self.as_context_mut())
+    }
+
+    /// Restores the contents of this store's memories, tables, and globals
+    /// from a blob produced by [`Store::snapshot`].
+    ///
+    /// The same modules, compiled with the same engine configuration, must
+    /// have been instantiated in this store, in the same order, as in the
+    /// store that the snapshot was taken from; the
+    /// snapshot overwrites the state of the entities those instantiations
+    /// created. Memories and tables are grown to the size recorded in the
+    /// snapshot, which is subject to this store's
+    /// [`ResourceLimiter`](crate::ResourceLimiter), and immutable globals
+    /// are left as they are.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `snapshot` is malformed, if this store's
+    /// instances, memories, tables, or globals don't match the ones in the
+    /// snapshot, or if any memory or table is larger than in the snapshot.
+    /// These, along with the snapshot's references and global values, are
+    /// all checked before anything is modified. Memories and tables are then
+    /// grown before any contents are overwritten, so failure to grow one
+    /// leaves their contents as they were, though some may have been grown.
+    pub fn restore(&mut self, snapshot: &[u8]) -> Result<()> {
+        crate::runtime::snapshot::restore(self.as_context_mut(), snapshot)
+    }
+
+    /// Returns the number of bytes consumed by this store's linear memories,
+    /// tables, GC heap, and the stacks of its live continuations.
+    ///
+    /// This is the total that a single per-guest memory budget would apply
+    /// to, see [`ResourceLimiter`](crate::ResourceLimiter) and
+    /// [`StoreLimitsBuilder::total_memory_size`](crate::StoreLimitsBuilder::total_memory_size).
+    /// Table elements are counted as a pointer's worth of bytes each. Memory
+    /// used by Wasmtime's own bookkeeping and by the embedder isn't included.
+    pub fn memory_consumed(&mut self) -> usize {
+        self.inner.memory_consumed()
+    }
+
     /// Returns the amount fuel in this [`Store`]. When fuel is enabled, it must
     /// be configured via [`Store::set_fuel`].
     ///
@@ -973,6 +1250,48 @@ impl<T> Store<T> {
         self.inner.fuel_async_yield_interval(interval)
     }
 
+    /// Configures how many async host calls WebAssembly in this [`Store`] may
+    /// make in a row before execution is forced to yield.
+    ///
+    /// The futures of async host functions often complete immediately, for
+    /// example because the I/O they wait on is already done. WebAssembly which
+    /// makes many such calls never yields back to the executor polling it, so
+    /// it can monopolize an executor thread that is shared with many other
+    /// tasks, such as other stores. With a `budget` configured, once that many
+    /// host calls in a row have completed without yielding, the next one
+    /// yields back to the executor before WebAssembly resumes, similar to
+    /// [`Store::fuel_async_yield_interval`]. The count restarts whenever
+    /// execution yields for any reason.
+    ///
+    /// A `budget` of `None`, the default, never forces a yield. See
+    /// [`Store::async_poll_stats`] for a way to observe how often this
+    /// happens.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it is not called on a store associated with
+    /// an [async config](crate::Config::async_support), or if `budget` is
+    /// `Some(0)`.
+    #[cfg(feature = "async")]
+    pub fn async_poll_budget(&mut self, budget: Option<u32>) -> Result<()> {
+        self.inner.async_poll_budget(budget)
+    }
+
+    /// Returns statistics about the async host calls made by WebAssembly in
+    /// this store, which can be used to detect whether it is starving other
+    /// tasks of its executor thread.
+    ///
+    /// See [`AsyncPollStats`](crate::AsyncPollStats) for more information.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if it is not called on a store associated with
+    /// an [async config](crate::Config::async_support).
+    #[cfg(feature = "async")]
+    pub fn async_poll_stats(&self) -> crate::AsyncPollStats {
+        self.inner.async_poll_stats()
+    }
+
     /// Sets the epoch deadline to a certain number of ticks in the future.
     ///
     /// When the Wasm guest code is compiled with epoch-interruption
@@ -1033,6 +1352,87 @@ impl<T> Store<T> {
         self.inner.epoch_deadline_trap();
     }
 
+    /// Limits how often backtraces are captured for traps in this store.
+    ///
+    /// This only has an effect when
+    /// [`Config::wasm_backtrace`](crate::Config::wasm_backtrace) is enabled.
+    /// Passing `None` removes a previously configured throttle so a backtrace
+    /// is captured for every trap again. See [`BacktraceThrottle`] for more
+    /// information.
+    ///
+    /// Configuring a throttle resets the counters returned by
+    /// [`Store::backtrace_capture_stats`].
+    pub fn set_backtrace_throttle(&mut self, throttle: Option<BacktraceThrottle>) {
+        let state = BacktraceThrottleState::new(throttle);
+        match &mut self.inner.backtrace_throttle {
+            // Wasm may be on the stack with a pointer to the existing state,
+            // so update it in place rather than reallocating.
+            Some(existing) => **existing = state,
+            None => self.inner.backtrace_throttle = Some(Box::new(state)),
+        }
+    }
+
+    /// Returns counters of captured and suppressed trap backtraces, or `None`
+    /// if no throttle is configured with [`Store::set_backtrace_throttle`].
+    pub fn backtrace_capture_stats(&self) -> Option<BacktraceCaptureStats> {
+        self.inner.backtrace_throttle.as_ref()?.stats()
+    }
+
+    /// Returns counters describing this store's cache of continuation stacks,
+    /// see [`Config::wasmfx_stack_cache_size`](crate::Config::wasmfx_stack_cache_size).
+    pub fn wasmfx_stack_cache_stats(&self) -> WasmFXStackCacheStats {
+        self.inner.wasmfx_stack_cache.stats()
+    }
+
+    /// Frees the stacks in this store's cache of continuation stacks until at
+    /// most `keep` remain.
+    ///
+    /// This can be used to give memory back after a burst of continuations
+    /// without disabling the cache, see
+    /// [`Config::wasmfx_stack_cache_size`](crate::Config::wasmfx_stack_cache_size).
+    pub fn trim_wasmfx_stack_cache(&mut self, keep: usize) {
+        self.inner.wasmfx_stack_cache.trim(keep);
+    }
+
+    /// Configures whether canonical ABI operations performed on behalf of
+    /// components in this store are logged.
+    ///
+    /// When enabled every `realloc` invocation, string transfer, and resource
+    /// handle creation, transfer, or destruction is logged at the `debug`
+    /// level under the `wasmtime::component::abi` target, along with the
+    /// sizes, alignments, and handle indices involved. This is intended for
+    /// debugging mismatches between a component and its host, or between
+    /// two components linked through an adapter, and is off by default.
+    ///
+    /// This covers both lifting and lowering performed by the host and the
+    /// `realloc` calls and string transcodes performed by fused adapters
+    /// between two components.
+    #[cfg(feature = "component-model")]
+    pub fn trace_canonical_abi(&mut self, enable: bool) {
+        self.inner.component_abi_trace = enable;
+    }
+
+    /// Configures whether the origin of each
+    /// [`ResourceAny`](crate::component::ResourceAny) created in this store is
+    /// recorded to produce more descriptive errors.
+    ///
+    /// When enabled this store remembers, for each resource handle it hands
+    /// out, which resource type it refers to, named as it's exported from the
+    /// component instance it was lifted from, and whether it was an `own` or a
+    /// `borrow`. Errors from passing a handle to an instance expecting a
+    /// different resource type, or from using a handle after it was dropped
+    /// or moved into a component, then describe the handle in question rather
+    /// than reporting a generic "unknown handle index" or "mismatched resource
+    /// types" error.
+    ///
+    /// This costs an allocation per handle and is intended for debugging, so
+    /// it's off by default. Only the most recent 1024 moved or dropped handles
+    /// are remembered. Disabling this discards everything recorded so far.
+    #[cfg(feature = "component-model")]
+    pub fn track_resource_origins(&mut self, enable: bool) {
+        self.inner.host_resource_data.track_origins(enable);
+    }
+
     /// Configures epoch-deadline expiration to invoke a custom callback
     /// function.
     ///
@@ -1067,6 +1467,61 @@ impl<T> Store<T> {
         self.inner.epoch_deadline_callback(Box::new(callback));
     }
 
+    /// Configures the resolver for imports defined with
+    /// [`Linker::define_unknown_imports_as_lazy`](crate::Linker::define_unknown_imports_as_lazy).
+    ///
+    /// The first time such an import is called in this store `resolver` is
+    /// invoked with the import's module name, name and type, and must return
+    /// the function implementing the import. The function is then used for all
+    /// later calls to the import within this store, and the calling instance's
+    /// import is patched to call it directly so later calls don't go through
+    /// the lazy stub at all.
+    ///
+    /// Returning an error from `resolver` traps the call which triggered
+    /// resolution. The import will be resolved again the next time it's
+    /// called.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn main() -> Result<()> {
+    /// let engine = Engine::default();
+    /// let module = Module::new(
+    ///     &engine,
+    ///     r#"
+    ///         (module
+    ///             (import "syscalls" "getpid" (func $getpid (result i32)))
+    ///             (func (export "run") (result i32) call $getpid)
+    ///         )
+    ///     "#,
+    /// )?;
+    ///
+    /// let mut linker = Linker::new(&engine);
+    /// linker.define_unknown_imports_as_lazy(&module)?;
+    ///
+    /// let mut store = Store::new(&engine, ());
+    /// store.import_resolver(|mut store, module, name, _ty| match (module, name) {
+    ///     ("syscalls", "getpid") => Ok(Func::wrap(&mut store, || 42)),
+    ///     _ => anyhow::bail!("unknown import `{module}::{name}`"),
+    /// });
+    ///
+    /// let instance = linker.instantiate(&mut store, &module)?;
+    /// let run = instance.get_typed_func::<(), i32>(&mut store, "run")?;
+    /// assert_eq!(run.call(&mut store, ())?, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn import_resolver(
+        &mut self,
+        resolver: impl FnMut(StoreContextMut<'_, T>, &str, &str, &FuncType) -> Result<Func>
+            + Send
+            + Sync
+            + 'static,
+    ) {
+        self.inner.import_resolver = Some(Box::new(resolver));
+    }
+
     /// Configures epoch-deadline expiration to yield to the async
     /// caller and the update the deadline.
     ///
@@ -1094,6 +1549,73 @@ impl<T> Store<T> {
     pub fn epoch_deadline_async_yield_and_update(&mut self, delta: u64) {
         self.inner.epoch_deadline_async_yield_and_update(delta);
     }
+
+    /// Sets the number of WebAssembly instructions which may execute in this
+    /// store before the single-step callback is invoked.
+    ///
+    /// This only has an effect for code compiled with
+    /// [`Config::wasm_single_step`](crate::Config::wasm_single_step) enabled.
+    /// Once `instructions` instructions have executed, the callback configured
+    /// with [`Store::single_step_callback`] is invoked before the next one,
+    /// or a [`Trap::Interrupt`] is raised if there is no callback.
+    ///
+    /// The budget is zero by default, meaning that the first instruction
+    /// executed invokes the callback.
+    pub fn set_single_step_budget(&mut self, instructions: u64) {
+        self.inner.single_step_budget = instructions;
+    }
+
+    /// Configures a callback to be invoked when the single-step budget of
+    /// this store is exhausted.
+    ///
+    /// The callback is given the offset, within its original wasm module, of
+    /// the instruction about to be executed. It may inspect or modify the
+    /// store and should either return an [`UpdateDeadline`] or an error,
+    /// which will terminate execution with a trap.
+    ///
+    /// [`UpdateDeadline::Continue`] sets the budget to the given number of
+    /// instructions, including the one about to be executed, so returning
+    /// `UpdateDeadline::Continue(1)` invokes the callback again before the
+    /// next instruction. A budget of zero is treated the same as one. If the
+    /// [`Store`] is configured with async support, then the callback may
+    /// instead return [`UpdateDeadline::Yield`] to yield to the async
+    /// executor before continuing.
+    ///
+    /// See [`Config::wasm_single_step`](crate::Config::wasm_single_step) for
+    /// more information.
+    pub fn single_step_callback(
+        &mut self,
+        callback: impl FnMut(StoreContextMut<T>, u32) -> Result<UpdateDeadline> + Send + Sync + 'static,
+    ) {
+        self.inner.single_step_behavior = Some(Box::new(callback));
+    }
+
+    /// Configures the map of edge counters which code compiled with
+    /// [`Config::coverage_instrumentation`](crate::Config::coverage_instrumentation)
+    /// increments as it executes in this store.
+    ///
+    /// The length of `map` must be a power of two no larger than 2<sup>32</sup>,
+    /// such as the 64 KiB of AFL's default map. Configuring a map also
+    /// resets the location edges are recorded from, so that the first block
+    /// executed afterwards doesn't record an edge from wherever execution
+    /// last stopped. Fuzzing harnesses typically call this before each run
+    /// of the fuzz target. Passing `None`, the default, stops recording
+    /// coverage.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the length of `map` isn't a power of two or is
+    /// larger than 2<sup>32</sup>.
+    ///
+    /// # Safety
+    ///
+    /// `map` must be valid for reads and writes until it's replaced by
+    /// another call to this method or the store is dropped, and it must not
+    /// be accessed by anything else, including other stores, while
+    /// WebAssembly executes in this store.
+    pub unsafe fn set_coverage_map(&mut self, map: Option<NonNull<[u8]>>) -> Result<()> {
+        self.inner.set_coverage_map(map)
+    }
 }
 
 impl<'a, T> StoreContext<'a, T> {
@@ -1164,6 +1686,54 @@ impl<'a, T> StoreContextMut<'a, T> {
         self.0.gc_async().await;
     }
 
+    /// Returns statistics about this store's GC heap and its collections.
+    ///
+    /// Same as [`Store::gc_stats`].
+    ///
+    /// This method is only available when the `gc` Cargo feature is enabled.
+    #[cfg(feature = "gc")]
+    pub fn gc_stats(&self) -> crate::GcStats {
+        self.0.gc_stats()
+    }
+
+    /// Registers a hook invoked before and after every garbage collection.
+    ///
+    /// Same as [`Store::on_gc`].
+    ///
+    /// This method is only available when the `gc` Cargo feature is enabled.
+    #[cfg(feature = "gc")]
+    pub fn on_gc(
+        &mut self,
+        hook: impl FnMut(crate::GcEvent, &crate::GcStats) + Send + Sync + 'static,
+    ) {
+        self.0.on_gc(Box::new(hook));
+    }
+
+    /// Returns statistics about the growth of this store's linear memories.
+    ///
+    /// Same as [`Store::memory_growth_stats`].
+    pub fn memory_growth_stats(&self) -> crate::MemoryGrowthStats {
+        self.0.memory_growth_stats()
+    }
+
+    /// Registers a hook invoked after every attempt to grow a linear memory.
+    ///
+    /// Same as [`Store::on_memory_grow`].
+    pub fn on_memory_grow(
+        &mut self,
+        hook: impl FnMut(&crate::MemoryGrowth) + Send + Sync + 'static,
+    ) {
+        self.0.on_memory_grow(Box::new(hook));
+    }
+
+    /// Returns the number of bytes consumed by this store's linear memories,
+    /// tables, GC heap, and continuation stacks.
+    ///
+    /// For more information see [`Store::memory_consumed`].
+    pub fn memory_consumed(&mut self) -> usize {
+        self.0.memory_consumed()
+    }
+
     /// Returns remaining fuel in this store.
     ///
     /// For more information see [`Store::get_fuel`]
@@ -1185,6 +1755,23 @@ impl<'a, T> StoreContextMut<'a, T> {
         self.0.fuel_async_yield_interval(interval)
     }
 
+    /// Configures how many async host calls may be made in a row before
+    /// execution is forced to yield.
+    ///
+    /// For more information see [`Store::async_poll_budget`]
+    #[cfg(feature = "async")]
+    pub fn async_poll_budget(&mut self, budget: Option<u32>) -> Result<()> {
+        self.0.async_poll_budget(budget)
+    }
+
+    /// Returns statistics about the async host calls made in this store.
+    ///
+    /// For more information see [`Store::async_poll_stats`]
+    #[cfg(feature = "async")]
+    pub fn async_poll_stats(&self) -> crate::AsyncPollStats {
+        self.0.async_poll_stats()
+    }
+
     /// Sets the epoch deadline to a certain number of ticks in the future.
     ///
     /// For more information see [`Store::set_epoch_deadline`].
@@ -1199,6 +1786,30 @@ impl<'a, T> StoreContextMut<'a, T> {
         self.0.epoch_deadline_trap();
     }
 
+    /// Sets the number of WebAssembly instructions which may execute before
+    /// the single-step callback is invoked.
+    ///
+    /// For more information see [`Store::set_single_step_budget`].
+    pub fn set_single_step_budget(&mut self, instructions: u64) {
+        self.0.single_step_budget = instructions;
+    }
+
+    /// Configures whether canonical ABI operations are logged.
+    ///
+    /// For more information see [`Store::trace_canonical_abi`].
+    #[cfg(feature = "component-model")]
+    pub fn trace_canonical_abi(&mut self, enable: bool) {
+        self.0.component_abi_trace = enable;
+    }
+
+    /// Configures whether the origins of resource handles are recorded.
+    ///
+    /// For more information see [`Store::track_resource_origins`].
+    #[cfg(feature = "component-model")]
+    pub fn track_resource_origins(&mut self, enable: bool) {
+        self.0.host_resource_data.track_origins(enable);
+    }
+
     /// Configures epoch-deadline expiration to yield to the async
     /// caller and the update the deadline.
     ///
@@ -1221,6 +1832,110 @@ impl<T> StoreInner<T> {
         &mut self.data
     }
 
+    pub(crate) fn watch_global(
+        &mut self,
+        global: Global,
+        definition: *mut VMGlobalDefinition,
+        callback: Box<dyn FnMut(StoreContextMut<'_, T>, Val) -> Result<()> + Send + Sync>,
+    ) {
+        self.global_watchers.push(GlobalWatcher {
+            global,
+            definition: SendSyncPtr::new(NonNull::new(definition).unwrap()),
+            callback,
+        });
+    }
+
+    pub(crate) fn unwatch_global(&mut self, definition: *mut VMGlobalDefinition) {
+        self.global_watchers
+            .retain(|w| w.definition.as_ptr() != definition);
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn set_suspend_handler(
+        &mut self,
+        tag: crate::Tag,
+        definition: *mut VMTagDefinition,
+        handler: Arc<SuspendHandler<T>>,
+    ) {
+        self.suspend_handlers
+            .insert(definition as usize, (tag, handler));
+    }
+
+    /// Runs the handler registered for `tag` on the suspend payload in
+    /// `values`, and replaces them with the values it resumes with.
+    #[cfg(feature = "async")]
+    fn run_suspend_handler(
+        &mut self,
+        tag: crate::Tag,
+        handler: &SuspendHandler<T>,
+        values: &mut Vec<ValRaw>,
+    ) -> Result<()> {
+        let mut scope = RootScope::new(self);
+        let ty = tag.func_ty(scope.as_context().0);
+        let nparams = ty.params().len();
+        let mut vals = Vec::with_capacity(nparams + ty.results().len());
+        for (raw, ty) in values.iter().zip(ty.params()) {
+            vals.push(unsafe { Val::from_raw(&mut scope, *raw, ty) });
+        }
+        vals.extend(ty.results().map(|_| Val::null_func_ref()));
+        let (params, results) = vals.split_at_mut(nparams);
+
+        let async_cx = scope
+            .as_context_mut()
+            .0
+            .async_cx()
+            .expect("attempted to pull async context during shutdown");
+        let result = {
+            let mut future = Pin::from(handler(scope.as_context_mut(), params, results));
+            unsafe { async_cx.block_on(future.as_mut()) }
+        };
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(trap)) | Err(trap) => return Err(trap),
+        }
+
+        values.clear();
+        for (ret, ty) in results.iter().zip(ty.results()) {
+            ret.ensure_matches_ty(scope.as_context().0, &ty)
+                .context("suspend handler attempted to resume with an incompatible value")?;
+            values.push(unsafe { ret.to_raw(&mut scope)? });
+        }
+        Ok(())
+    }
+
+    /// Returns the function implementing the lazy import `module::name`,
+    /// invoking the import resolver the first time it's requested.
+    pub(crate) fn resolve_lazy_import(
+        &mut self,
+        module: &str,
+        name: &str,
+        ty: &FuncType,
+    ) -> Result<Func> {
+        let key = (module.to_string(), name.to_string());
+        if let Some(func) = self.lazy_imports.get(&key) {
+            return Ok(*func);
+        }
+
+        // Temporarily take the resolver to avoid mutably borrowing multiple
+        // times.
+        let Some(mut resolver) = self.import_resolver.take() else {
+            return Err(crate::ErrorKind::Link.tag(format_err!(
+                "cannot resolve import `{module}::{name}`: no import resolver is \
+                 configured for this store"
+            )));
+        };
+        let result = resolver((&mut *self).as_context_mut(), module, name, ty);
+        self.import_resolver = Some(resolver);
+
+        let func =
+            result.with_context(|| format!("failed to resolve import `{module}::{name}`"))?;
+        func.ensure_matches_ty(&self.inner, ty)
+            .with_context(|| format!("failed to resolve import `{module}::{name}`"))
+            .map_err(|e| crate::ErrorKind::Link.tag(e))?;
+        self.lazy_imports.insert(key, func);
+        Ok(func)
+    }
+
     #[inline]
     pub fn call_hook(&mut self, s: CallHook) -> Result<()> {
         if self.inner.pkey.is_none() && self.call_hook.is_none() {
@@ -1324,35 +2039,114 @@ impl StoreOpaque {
     }
 
     pub fn bump_resource_counts(&mut self, module: &Module) -> Result<()> {
-        fn bump(slot: &mut usize, max: usize, amt: usize, desc: &str) -> Result<()> {
+        fn bump(slot: usize, max: usize, amt: usize, desc: &str) -> Result<usize> {
             let new = slot.saturating_add(amt);
             if new > max {
-                bail!(
+                return Err(crate::ErrorKind::ResourceLimit.tag(format_err!(
                     "resource limit exceeded: {} count too high at {}",
                     desc,
                     new
-                );
+                )));
             }
-            *slot = new;
-            Ok(())
+            Ok(new)
         }
 
         let module = module.env_module();
         let memories = module.num_defined_memories();
         let tables = module.num_defined_tables();
 
-        bump(&mut self.instance_count, self.instance_limit, 1, "instance")?;
-        bump(
-            &mut self.memory_count,
-            self.memory_limit,
-            memories,
-            "memory",
-        )?;
-        bump(&mut self.table_count, self.table_limit, tables, "table")?;
+        // Nothing is updated until all of the limits, including the quota
+        // group's, are known to be respected.
+        let instance_count = bump(self.instance_count, self.instance_limit, 1, "instance")?;
+        let memory_count = bump(self.memory_count, self.memory_limit, memories, "memory")?;
+        let table_count = bump(self.table_count, self.table_limit, tables, "table")?;
+
+        if let Some(quota) = &mut self.quota {
+            quota.reserve_instance()?;
+        }
+
+        self.instance_count = instance_count;
+        self.memory_count = memory_count;
+        self.table_count = table_count;
+        Ok(())
+    }
+
+    /// Returns what has been charged to this store's quota group so far, if
+    /// it's in one.
+    pub(crate) fn quota_checkpoint(&self) -> Option<QuotaCheckpoint> {
+        self.quota.as_ref().map(|quota| quota.checkpoint())
+    }
+
+    /// Refunds everything charged to this store's quota group since
+    /// `checkpoint` was taken.
+    pub(crate) fn quota_rollback(&mut self, checkpoint: Option<QuotaCheckpoint>) {
+        if let (Some(quota), Some(checkpoint)) = (&mut self.quota, checkpoint) {
+            quota.rollback(checkpoint);
+        }
+    }
+
+    /// Charges `memory` to this store's quota group, if it's in one, when it's
+    /// imported.
+    pub(crate) fn reserve_shared_memory(
+        &mut self,
+        memory: &crate::runtime::vm::SharedMemory,
+    ) -> Result<()> {
+        match &mut self.quota {
+            Some(quota) => quota.reserve_shared_memory(memory),
+            None => Ok(()),
+        }
+    }
 
+    /// Notes that `memory` was created in this store, and charged to its
+    /// quota group as any other memory, so it's not charged again if it's
+    /// imported.
+    pub(crate) fn shared_memory_created(&mut self, memory: &crate::runtime::vm::SharedMemory) {
+        if let Some(quota) = &mut self.quota {
+            quota.shared_memory_created(memory);
+        }
+    }
+
+    pub fn join_quota_group(&mut self, group: &crate::QuotaGroup) -> Result<()> {
+        ensure!(
+            self.quota.is_none(),
+            "store already belongs to a quota group"
+        );
+        ensure!(
+            self.instance_count == 0,
+            "a store must join a quota group before it's used to instantiate modules"
+        );
+        self.quota = Some(QuotaMember::join(&self.engine, group)?);
         Ok(())
     }
 
+    #[cfg(feature = "threads")]
+    pub fn join_deadlock_monitor(&mut self, monitor: &crate::DeadlockMonitor) -> Result<u64> {
+        ensure!(
+            self.deadlock.is_none(),
+            "store already belongs to a deadlock monitor"
+        );
+        let member = crate::runtime::deadlock::DeadlockMember::join(monitor);
+        let thread = member.thread();
+        self.deadlock = Some(member);
+        Ok(thread)
+    }
+
+    /// Returns this store's membership in a deadlock monitor, if any.
+    #[cfg(feature = "threads")]
+    pub(crate) fn deadlock_member(&self) -> Option<&crate::runtime::deadlock::DeadlockMember> {
+        self.deadlock.as_ref()
+    }
+
+    /// Refills fuel from this store's quota group, returning whether any fuel
+    /// was available.
+    fn refuel_from_quota(&mut self) -> bool {
+        let fuel = match &mut self.quota {
+            Some(quota) => quota.take_fuel(),
+            None => 0,
+        };
+        fuel > 0 && self.set_fuel(fuel).is_ok()
+    }
+
     #[inline]
     pub fn async_support(&self) -> bool {
         cfg!(feature = "async") && self.engine().config().async_support
@@ -1578,12 +2372,13 @@ impl StoreOpaque {
     #[inline(never)]
     pub(crate) fn allocate_gc_heap(&mut self) -> Result<()> {
         assert!(self.gc_store.is_none());
-        let gc_store = allocate_gc_store(self.engine())?;
+        let gc_store = allocate_gc_store(self)?;
         self.gc_store = Some(gc_store);
         return Ok(());
 
         #[cfg(feature = "gc")]
-        fn allocate_gc_store(engine: &Engine) -> Result<GcStore> {
+        fn allocate_gc_store(store: &mut StoreOpaque) -> Result<GcStore> {
+            let engine = store.engine().clone();
             ensure!(
                 engine.features().gc_types(),
                 "cannot allocate a GC store when GC is disabled at configuration time"
@@ -1591,11 +2386,27 @@ impl StoreOpaque {
             let (index, heap) = engine
                 .allocator()
                 .allocate_gc_heap(&**engine.gc_runtime()?)?;
-            Ok(GcStore::new(index, heap))
+
+            // GC heaps don't grow yet, so the limiter is consulted once for
+            // the heap's whole capacity.
+            let capacity = heap.heap_slice().len();
+            let allowed =
+                unsafe { (*store.traitobj()).gc_heap_growing(0, capacity, Some(capacity)) };
+            match allowed {
+                Ok(true) => Ok(GcStore::new(index, heap)),
+                Ok(false) => {
+                    engine.allocator().deallocate_gc_heap(index, heap);
+                    bail!("GC heap allocation denied by the resource limiter")
+                }
+                Err(e) => {
+                    engine.allocator().deallocate_gc_heap(index, heap);
+                    Err(e)
+                }
+            }
         }
 
         #[cfg(not(feature = "gc"))]
-        fn allocate_gc_store(_engine: &Engine) -> Result<GcStore> {
+        fn allocate_gc_store(_store: &mut StoreOpaque) -> Result<GcStore> {
             bail!("cannot allocate a GC store: the `gc` feature was disabled at compile time")
         }
     }
@@ -1665,7 +2476,11 @@ impl StoreOpaque {
             return;
         }
 
+        self.notify_gc_hook(crate::GcEvent::BeforeCollection);
+
         log::trace!("============ Begin GC ===========");
+        let timer = crate::runtime::metrics::Timer::start(self.engine.metrics());
+        let pause = self.gc_stats.start();
 
         // Take the GC roots out of `self` so we can borrow it mutably but still
         // call mutable methods on `self`.
@@ -1678,7 +2493,13 @@ impl StoreOpaque {
         roots.clear();
         self.gc_roots_list = roots;
 
+        let live_bytes = self.unwrap_gc_store().allocated_bytes();
+        self.gc_stats.finish(pause, live_bytes);
+        timer.finish(self.engine.metrics(), crate::Metrics::gc_pauses);
         log::trace!("============ End GC ===========");
+
+        self.notify_gc_hook(crate::GcEvent::AfterCollection);
+        self.run_externref_finalizers();
     }
 
     #[inline]
@@ -1691,6 +2512,57 @@ impl StoreOpaque {
         // don't have to `cfg` every call site.
     }
 
+    #[cfg(feature = "gc")]
+    pub(crate) fn gc_stats(&self) -> crate::GcStats {
+        let allocated_bytes = self.gc_store.as_ref().map_or(0, |s| s.allocated_bytes());
+        self.gc_stats.snapshot(allocated_bytes)
+    }
+
+    #[cfg(feature = "gc")]
+    pub(crate) fn on_gc(
+        &mut self,
+        hook: Box<dyn FnMut(crate::GcEvent, &crate::GcStats) + Send + Sync>,
+    ) {
+        self.gc_stats.set_hook(hook);
+    }
+
+    pub(crate) fn memory_growth_stats(&self) -> crate::MemoryGrowthStats {
+        self.memory_growth.stats()
+    }
+
+    pub(crate) fn on_memory_grow(
+        &mut self,
+        hook: Box<dyn FnMut(&crate::MemoryGrowth) + Send + Sync>,
+    ) {
+        self.memory_growth.set_hook(hook);
+    }
+
+    pub(crate) fn record_memory_growth(&mut self, growth: crate::MemoryGrowth) {
+        self.memory_growth.record(growth);
+    }
+
+    #[cfg(feature = "gc")]
+    fn notify_gc_hook(&mut self, event: crate::GcEvent) {
+        let allocated_bytes = self.unwrap_gc_store().allocated_bytes();
+        self.gc_stats.notify(event, allocated_bytes);
+    }
+
+    /// Run the finalizers of `externref`s whose host data has been reclaimed
+    /// since the last time this was called.
+    ///
+    /// This happens after the collection has completely finished, so that
+    /// finalizers never observe a GC heap in an intermediate state.
+    #[cfg(feature = "gc")]
+    fn run_externref_finalizers(&mut self) {
+        let pending = self
+            .unwrap_gc_store_mut()
+            .host_data_table
+            .take_pending_finalizers();
+        for (finalizer, data) in pending {
+            finalizer(data);
+        }
+    }
+
     #[cfg(feature = "gc")]
     fn trace_roots(&mut self, gc_roots_list: &mut GcRootsList) {
         log::trace!("Begin trace GC roots");
@@ -1717,7 +2589,11 @@ impl StoreOpaque {
             return;
         }
 
+        self.notify_gc_hook(crate::GcEvent::BeforeCollection);
+
         log::trace!("============ Begin Async GC ===========");
+        let timer = crate::runtime::metrics::Timer::start(self.engine.metrics());
+        let pause = self.gc_stats.start();
 
         // Take the GC roots out of `self` so we can borrow it mutably but still
         // call mutable methods on `self`.
@@ -1732,7 +2608,13 @@ impl StoreOpaque {
         roots.clear();
         self.gc_roots_list = roots;
 
+        let live_bytes = self.unwrap_gc_store().allocated_bytes();
+        self.gc_stats.finish(pause, live_bytes);
+        timer.finish(self.engine.metrics(), crate::Metrics::gc_pauses);
         log::trace!("============ End Async GC ===========");
+
+        self.notify_gc_hook(crate::GcEvent::AfterCollection);
+        self.run_externref_finalizers();
     }
 
     #[inline]
@@ -1866,9 +2748,30 @@ impl StoreOpaque {
             current_suspend: self.async_state.current_suspend.get(),
             current_poll_cx: unsafe { &raw mut (*poll_cx_box_ptr).future_context },
             track_pkey_context_switch: self.pkey.is_some(),
+            poll_budget: self.async_state.poll_budget.get(),
         })
     }
 
+    #[cfg(feature = "async")]
+    pub fn async_poll_budget(&mut self, budget: Option<u32>) -> Result<()> {
+        anyhow::ensure!(
+            self.engine().config().async_support,
+            "async support is not configured in this store"
+        );
+        anyhow::ensure!(budget != Some(0), "async_poll_budget must not be 0");
+        self.async_state
+            .poll_budget
+            .get_mut()
+            .set_budget(budget.and_then(NonZeroU32::new));
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    pub fn async_poll_stats(&self) -> AsyncPollStats {
+        assert!(self.async_support());
+        unsafe { (*self.async_state.poll_budget.get()).stats() }
+    }
+
     pub fn get_fuel(&self) -> Result<u64> {
         anyhow::ensure!(
             self.engine().tunables().consume_fuel,
@@ -1892,6 +2795,7 @@ impl StoreOpaque {
             self.engine().tunables().consume_fuel,
             "fuel is not configured in this store"
         );
+        self.record_fuel_consumed();
         let injected_fuel = unsafe { &mut *self.runtime_limits.fuel_consumed.get() };
         set_fuel(
             injected_fuel,
@@ -1899,9 +2803,27 @@ impl StoreOpaque {
             self.fuel_yield_interval,
             fuel,
         );
+        self.fuel_recorded = fuel;
         Ok(())
     }
 
+    /// Records the fuel consumed since the last time this was called into the
+    /// engine's metrics, if enabled.
+    pub(crate) fn record_fuel_consumed(&mut self) {
+        let Some(metrics) = self.engine.metrics() else {
+            return;
+        };
+        if !self.engine.tunables().consume_fuel {
+            return;
+        }
+        let injected_fuel = unsafe { *self.runtime_limits.fuel_consumed.get() };
+        let fuel = get_fuel(injected_fuel, self.fuel_reserve);
+        metrics
+            .fuel_consumed()
+            .add(self.fuel_recorded.saturating_sub(fuel));
+        self.fuel_recorded = fuel;
+    }
+
     pub fn fuel_async_yield_interval(&mut self, interval: Option<u64>) -> Result<()> {
         anyhow::ensure!(
             self.engine().tunables().consume_fuel,
@@ -1955,6 +2877,27 @@ impl StoreOpaque {
         &self.runtime_limits as *const VMRuntimeLimits as *mut VMRuntimeLimits
     }
 
+    /// Points the counters of coverage instrumentation at `map`, or at
+    /// `coverage_scratch` if there's none, see `Store::set_coverage_map`.
+    pub(crate) fn set_coverage_map(&mut self, map: Option<NonNull<[u8]>>) -> Result<()> {
+        let (map, mask) = match map {
+            Some(map) => {
+                let len = map.len();
+                ensure!(
+                    len.is_power_of_two() && u32::try_from(len - 1).is_ok(),
+                    "coverage map length {len} isn't a power of two no larger than 2^32"
+                );
+                (map.cast::<u8>().as_ptr(), u32::try_from(len - 1).unwrap())
+            }
+            None => (self.coverage_scratch.get(), 0),
+        };
+        let limits = &mut self.runtime_limits;
+        *limits.coverage_map.get_mut() = map;
+        *limits.coverage_mask.get_mut() = mask;
+        *limits.coverage_prev.get_mut() = 0;
+        Ok(())
+    }
+
     #[inline]
     pub fn main_stack_information(&self) -> *mut CommonStackInformation {
         // NOTE(frank-emrich) This looks dogdy, but follows the same pattern as
@@ -1962,6 +2905,38 @@ impl StoreOpaque {
         &self.main_stack_information as *const CommonStackInformation as *mut CommonStackInformation
     }
 
+    #[inline]
+    pub(crate) fn wasmfx_stack_cache(&mut self) -> &mut WasmFXStackCache {
+        &mut self.wasmfx_stack_cache
+    }
+
+    /// The total size in bytes of the stacks of this store's live
+    /// continuations.
+    pub(crate) fn continuation_stack_bytes_mut(&mut self) -> &mut usize {
+        &mut self.continuation_stack_bytes
+    }
+
+    /// See [`Store::memory_consumed`].
+    pub(crate) fn memory_consumed(&mut self) -> usize {
+        let memories = self.all_memories().collect::<Vec<_>>();
+        let mut bytes = memories
+            .iter()
+            .map(|memory| memory.internal_data_size(self))
+            .fold(0usize, usize::saturating_add);
+        let mut table_elements = 0u64;
+        self.for_each_table(|store, table| table_elements += table.internal_size(store));
+        bytes = bytes.saturating_add(
+            usize::try_from(table_elements)
+                .unwrap_or(usize::MAX)
+                .saturating_mul(mem::size_of::<usize>()),
+        );
+        #[cfg(feature = "gc")]
+        if let Some(gc_store) = &self.gc_store {
+            bytes = bytes.saturating_add(gc_store.gc_heap.heap_slice().len());
+        }
+        bytes.saturating_add(self.continuation_stack_bytes)
+    }
+
     #[inline]
     pub fn stack_chain(&self) -> *mut StackChainCell {
         // NOTE(frank-emrich) This looks dogdy, but follows the same pattern as
@@ -2126,6 +3101,30 @@ at https://bytecodealliance.org/security.
         self.pkey
     }
 
+    /// Returns a pointer to this store's backtrace throttle state, or null if
+    /// a throttle was never configured.
+    ///
+    /// The pointer remains valid for the lifetime of this store.
+    #[inline]
+    pub(crate) fn backtrace_throttle(&self) -> *const BacktraceThrottleState {
+        match &self.backtrace_throttle {
+            Some(state) => &**state,
+            None => ptr::null(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn dylink_mut(&mut self) -> &mut crate::runtime::dylink::DylinkState {
+        &mut self.dylink
+    }
+
+    #[inline]
+    pub(crate) fn host_resumption_mut(
+        &mut self,
+    ) -> &mut Option<crate::runtime::vm::continuation::HostResumption> {
+        &mut self.host_resumption
+    }
+
     #[inline]
     #[cfg(feature = "component-model")]
     pub(crate) fn component_resource_state(
@@ -2142,6 +3141,34 @@ at https://bytecodealliance.org/security.
         )
     }
 
+    /// Replaces the cancellation flag of the current cancellable component
+    /// call, returning the previous one.
+    #[cfg(all(feature = "component-model", feature = "async"))]
+    pub(crate) fn replace_call_cancellation(
+        &mut self,
+        cancellation: Option<Arc<core::sync::atomic::AtomicBool>>,
+    ) -> Option<Arc<core::sync::atomic::AtomicBool>> {
+        mem::replace(&mut self.call_cancellation, cancellation)
+    }
+
+    /// Returns whether the current cancellable component call, if any, has
+    /// been cancelled.
+    fn call_cancelled(&self) -> bool {
+        #[cfg(all(feature = "component-model", feature = "async"))]
+        if let Some(cancellation) = &self.call_cancellation {
+            return cancellation.load(core::sync::atomic::Ordering::Relaxed);
+        }
+        false
+    }
+
+    /// Returns whether canonical ABI operations should be logged, see
+    /// [`Store::trace_canonical_abi`].
+    #[inline]
+    #[cfg(feature = "component-model")]
+    pub(crate) fn component_abi_trace(&self) -> bool {
+        self.component_abi_trace
+    }
+
     #[cfg(feature = "component-model")]
     pub(crate) fn push_component_instance(&mut self, instance: crate::component::Instance) {
         // We don't actually need the instance itself right now, but it seems
@@ -2511,6 +3538,7 @@ pub struct AsyncCx {
     current_suspend: *mut *mut wasmtime_fiber::Suspend<Result<()>, (), Result<()>>,
     current_poll_cx: *mut *mut Context<'static>,
     track_pkey_context_switch: bool,
+    poll_budget: *mut PollBudget,
 }
 
 #[cfg(feature = "async")]
@@ -2557,6 +3585,7 @@ impl AsyncCx {
         *self.current_suspend = ptr::null_mut();
         assert!(!suspend.is_null());
 
+        let mut yielded = false;
         loop {
             let future_result = {
                 let poll_cx = *self.current_poll_cx;
@@ -2566,10 +3595,24 @@ impl AsyncCx {
                 future.as_mut().poll(&mut *poll_cx)
             };
 
-            match future_result {
-                Poll::Ready(t) => break Ok(t),
-                Poll::Pending => {}
-            }
+            let ready = match future_result {
+                Poll::Ready(t) => {
+                    if yielded || !(*self.poll_budget).ready() {
+                        break Ok(t);
+                    }
+                    // This future was ready without yielding, but the store's
+                    // poll budget is exhausted, so yield anyway to give other
+                    // tasks on this thread a chance to run. There's nothing to
+                    // wait for, so arrange to be polled again right away.
+                    (**self.current_poll_cx).waker().wake_by_ref();
+                    Some(t)
+                }
+                Poll::Pending => {
+                    yielded = true;
+                    (*self.poll_budget).pending();
+                    None
+                }
+            };
 
             // In order to prevent this fiber's MPK state from being munged by
             // other fibers while it is suspended, we save and restore it once
@@ -2586,6 +3629,10 @@ impl AsyncCx {
             if self.track_pkey_context_switch {
                 mpk::allow(previous_mask);
             }
+
+            if let Some(t) = ready {
+                break Ok(t);
+            }
         }
     }
 }
@@ -2613,9 +3660,9 @@ unsafe impl<T> crate::runtime::vm::VMStore for StoreInner<T> {
         desired: usize,
         maximum: Option<usize>,
     ) -> Result<bool, anyhow::Error> {
-        match self.limiter {
+        let allowed = match self.limiter {
             Some(ResourceLimiterInner::Sync(ref mut limiter)) => {
-                limiter(&mut self.data).memory_growing(current, desired, maximum)
+                limiter(&mut self.data).memory_growing(current, desired, maximum)?
             }
             #[cfg(feature = "async")]
             Some(ResourceLimiterInner::Async(ref mut limiter)) => unsafe {
@@ -2626,13 +3673,22 @@ unsafe impl<T> crate::runtime::vm::VMStore for StoreInner<T> {
                         limiter(&mut self.data)
                             .memory_growing(current, desired, maximum)
                             .as_mut(),
-                    )?
+                    )??
             },
-            None => Ok(true),
+            None => true,
+        };
+        // The quota group is only charged once the store's own limiter has
+        // permitted the growth.
+        match &mut self.inner.quota {
+            Some(quota) if allowed => Ok(quota.reserve_memory(desired.saturating_sub(current))),
+            _ => Ok(allowed),
         }
     }
 
     fn memory_grow_failed(&mut self, error: anyhow::Error) -> Result<()> {
+        if let Some(quota) = &mut self.inner.quota {
+            quota.memory_grow_failed();
+        }
         match self.limiter {
             Some(ResourceLimiterInner::Sync(ref mut limiter)) => {
                 limiter(&mut self.data).memory_grow_failed(error)
@@ -2700,8 +3756,43 @@ unsafe impl<T> crate::runtime::vm::VMStore for StoreInner<T> {
         }
     }
 
+    fn gc_heap_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        maximum: Option<usize>,
+    ) -> Result<bool, anyhow::Error> {
+        match self.limiter {
+            Some(ResourceLimiterInner::Sync(ref mut limiter)) => {
+                limiter(&mut self.data).gc_heap_growing(current, desired, maximum)
+            }
+            #[cfg(feature = "async")]
+            Some(ResourceLimiterInner::Async(ref mut limiter)) => {
+                limiter(&mut self.data).gc_heap_growing(current, desired, maximum)
+            }
+            None => Ok(true),
+        }
+    }
+
+    fn continuation_stack_allocating(
+        &mut self,
+        current: usize,
+        desired: usize,
+    ) -> Result<bool, anyhow::Error> {
+        match self.limiter {
+            Some(ResourceLimiterInner::Sync(ref mut limiter)) => {
+                limiter(&mut self.data).continuation_stack_allocating(current, desired)
+            }
+            #[cfg(feature = "async")]
+            Some(ResourceLimiterInner::Async(ref mut limiter)) => {
+                limiter(&mut self.data).continuation_stack_allocating(current, desired)
+            }
+            None => Ok(true),
+        }
+    }
+
     fn out_of_gas(&mut self) -> Result<()> {
-        if !self.refuel() {
+        if !self.refuel() && !self.inner.refuel_from_quota() {
             return Err(Trap::OutOfFuel.into());
         }
         #[cfg(feature = "async")]
@@ -2711,7 +3802,90 @@ unsafe impl<T> crate::runtime::vm::VMStore for StoreInner<T> {
         Ok(())
     }
 
+    fn global_written(&mut self, definition: *mut VMGlobalDefinition) -> Result<()> {
+        if self.global_watchers.is_empty() {
+            return Ok(());
+        }
+        // Temporarily take the watchers so callbacks can be given the store.
+        let mut watchers = mem::take(&mut self.global_watchers);
+        let mut result = Ok(());
+        for watcher in watchers
+            .iter_mut()
+            .filter(|w| w.definition.as_ptr() == definition)
+        {
+            let val = watcher.global.get(&mut *self);
+            result = (watcher.callback)((&mut *self).as_context_mut(), val);
+            if result.is_err() {
+                break;
+            }
+        }
+        // Preserve any watchers registered from within a callback.
+        watchers.append(&mut self.global_watchers);
+        self.global_watchers = watchers;
+        result
+    }
+
+    fn suspend_to_host(
+        &mut self,
+        tag: *mut VMTagDefinition,
+        values: &mut Vec<ValRaw>,
+    ) -> Result<bool> {
+        #[cfg(feature = "async")]
+        if let Some((tag, handler)) = self.suspend_handlers.get(&(tag as usize)).cloned() {
+            self.run_suspend_handler(tag, &*handler, values)?;
+            return Ok(true);
+        }
+        let _ = (tag, values);
+        Ok(false)
+    }
+
+    fn single_step(&mut self, offset: u32) -> Result<()> {
+        if self.single_step_budget == 0 {
+            // Temporarily take the configured behavior to avoid mutably
+            // borrowing multiple times.
+            let mut behavior = self.single_step_behavior.take();
+            let result = match &mut behavior {
+                None => Err(Trap::Interrupt.into()),
+                Some(callback) => {
+                    callback((&mut *self).as_context_mut(), offset).and_then(|update| {
+                        let budget = match update {
+                            UpdateDeadline::Continue(budget) => budget,
+
+                            #[cfg(feature = "async")]
+                            UpdateDeadline::Yield(budget) => {
+                                assert!(
+                                    self.async_support(),
+                                    "cannot use `UpdateDeadline::Yield` without enabling async support in the config"
+                                );
+                                self.async_yield_impl()?;
+                                budget
+                            }
+                        };
+                        self.single_step_budget = budget.max(1);
+                        Ok(())
+                    })
+                }
+            };
+            // Put back the original behavior which was replaced by `take`.
+            self.single_step_behavior = behavior;
+            result?;
+        }
+        self.single_step_budget -= 1;
+        Ok(())
+    }
+
     fn new_epoch(&mut self) -> Result<u64, anyhow::Error> {
+        if let Some(metrics) = self.engine().metrics() {
+            metrics.epoch_deadlines().add(1);
+        }
+
+        // A cancelled call traps at its next epoch deadline regardless of the
+        // configured behavior, including if it's cancelled while yielding.
+        let cancelled = || anyhow::Error::from(Trap::Interrupt).context("call was cancelled");
+        if self.inner.call_cancelled() {
+            return Err(cancelled());
+        }
+
         // Temporarily take the configured behavior to avoid mutably borrowing
         // multiple times.
         let mut behavior = self.epoch_deadline_behavior.take();
@@ -2743,6 +3917,9 @@ unsafe impl<T> crate::runtime::vm::VMStore for StoreInner<T> {
 
         // Put back the original behavior which was replaced by `take`.
         self.epoch_deadline_behavior = behavior;
+        if delta_result.is_ok() && self.inner.call_cancelled() {
+            return Err(cancelled());
+        }
         delta_result
     }
 
@@ -0,0 +1,106 @@
+//! Fairness of async host calls: the poll budget configured with
+//! [`Store::async_poll_budget`](crate::Store::async_poll_budget) and the
+//! statistics behind [`Store::async_poll_stats`](crate::Store::async_poll_stats).
+
+use core::num::NonZeroU32;
+
+/// Statistics about the async host calls made by a store's WebAssembly, as
+/// returned by [`Store::async_poll_stats`](crate::Store::async_poll_stats).
+///
+/// A host call is "ready" if its future completed without the store yielding
+/// back to the executor. Long runs of ready host calls mean that the store
+/// kept its executor thread busy, starving other tasks on that thread; a
+/// growing [`budget_yields`](AsyncPollStats::budget_yields) count is a sign
+/// that this happens regularly.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AsyncPollStats {
+    /// The number of async host calls whose futures were ready without
+    /// yielding.
+    pub ready_calls: u64,
+
+    /// The number of times execution yielded back to the executor because a
+    /// future was pending, including yields for fuel and epoch interruption.
+    pub pending_yields: u64,
+
+    /// The number of times execution was forced to yield back to the executor
+    /// because the poll budget was exhausted.
+    pub budget_yields: u64,
+
+    /// The longest run of consecutive ready host calls seen so far.
+    pub longest_ready_streak: u64,
+}
+
+/// Per-store bookkeeping behind [`AsyncPollStats`] and the poll budget.
+#[derive(Default)]
+pub(crate) struct PollBudget {
+    budget: Option<NonZeroU32>,
+    ready_streak: u64,
+    stats: AsyncPollStats,
+}
+
+impl PollBudget {
+    pub(crate) fn set_budget(&mut self, budget: Option<NonZeroU32>) {
+        self.budget = budget;
+        self.ready_streak = 0;
+    }
+
+    pub(crate) fn stats(&self) -> AsyncPollStats {
+        self.stats
+    }
+
+    /// Record that execution is yielding because a future was pending.
+    pub(crate) fn pending(&mut self) {
+        self.stats.pending_yields += 1;
+        self.ready_streak = 0;
+    }
+
+    /// Record that a host call's future was ready without yielding, returning
+    /// whether execution must now yield because the budget is exhausted.
+    pub(crate) fn ready(&mut self) -> bool {
+        self.stats.ready_calls += 1;
+        self.ready_streak += 1;
+        self.stats.longest_ready_streak = self.stats.longest_ready_streak.max(self.ready_streak);
+        match self.budget {
+            Some(budget) if self.ready_streak >= u64::from(budget.get()) => {
+                self.stats.budget_yields += 1;
+                self.ready_streak = 0;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_forces_yields() {
+        let mut budget = PollBudget::default();
+        for _ in 0..10 {
+            assert!(!budget.ready());
+        }
+        assert_eq!(budget.stats().longest_ready_streak, 10);
+
+        budget.set_budget(NonZeroU32::new(3));
+        assert!(!budget.ready());
+        assert!(!budget.ready());
+        assert!(budget.ready());
+        assert!(!budget.ready());
+        budget.pending();
+        assert!(!budget.ready());
+        assert!(!budget.ready());
+        assert!(budget.ready());
+
+        assert_eq!(
+            budget.stats(),
+            AsyncPollStats {
+                ready_calls: 17,
+                pending_yields: 1,
+                budget_yields: 2,
+                longest_ready_streak: 10,
+            }
+        );
+    }
+}
@@ -28,6 +28,7 @@ pub struct StoreData {
     instances: Vec<crate::instance::InstanceData>,
     memories: Vec<crate::runtime::vm::ExportMemory>,
     tags: Vec<crate::runtime::vm::ExportTag>,
+    continuations: Vec<crate::runtime::continuation::ContinuationData>,
     #[cfg(feature = "component-model")]
     pub(crate) components: crate::component::ComponentStoreData,
 }
@@ -55,6 +56,7 @@ impl_store_data! {
     instances => crate::instance::InstanceData,
     memories => crate::runtime::vm::ExportMemory,
     tags => crate::runtime::vm::ExportTag,
+    continuations => crate::runtime::continuation::ContinuationData,
 }
 
 impl StoreData {
@@ -67,6 +69,7 @@ impl StoreData {
             instances: Vec::new(),
             memories: Vec::new(),
             tags: Vec::new(),
+            continuations: Vec::new(),
             #[cfg(feature = "component-model")]
             components: Default::default(),
         }
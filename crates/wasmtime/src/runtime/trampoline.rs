@@ -4,6 +4,7 @@ mod func;
 mod global;
 mod memory;
 mod table;
+mod tag;
 
 pub use self::func::*;
 pub use self::global::*;
@@ -11,16 +12,17 @@ pub(crate) use memory::MemoryCreatorProxy;
 
 use self::memory::create_memory;
 use self::table::create_table;
+use self::tag::create_tag;
 use crate::prelude::*;
 use crate::runtime::vm::{
     Imports, InstanceAllocationRequest, InstanceAllocator, ModuleRuntimeInfo,
     OnDemandInstanceAllocator, SharedMemory, StorePtr, VMFunctionImport,
 };
 use crate::store::{InstanceId, StoreOpaque};
-use crate::{MemoryType, TableType};
+use crate::{MemoryType, TableType, TagType};
 use alloc::sync::Arc;
 use core::any::Any;
-use wasmtime_environ::{MemoryIndex, Module, TableIndex, VMSharedTypeIndex};
+use wasmtime_environ::{MemoryIndex, Module, TableIndex, TagIndex, VMSharedTypeIndex};
 
 fn create_handle(
     module: Module,
@@ -48,6 +50,7 @@ fn create_handle(
             wmemcheck: false,
             pkey: None,
             tunables: store.engine().tunables(),
+            reserved: None,
         })?;
 
         Ok(store.add_dummy_instance(handle))
@@ -74,3 +77,13 @@ pub fn generate_table_export(
         .instance_mut(instance)
         .get_exported_table(TableIndex::from_u32(0)))
 }
+
+pub fn generate_tag_export(
+    store: &mut StoreOpaque,
+    t: &TagType,
+) -> Result<crate::runtime::vm::ExportTag> {
+    let instance = create_tag(store, t)?;
+    Ok(store
+        .instance_mut(instance)
+        .get_exported_tag(TagIndex::from_u32(0)))
+}
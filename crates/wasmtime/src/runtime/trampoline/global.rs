@@ -1,7 +1,7 @@
 use crate::runtime::vm::{StoreBox, VMGlobalDefinition};
 use crate::store::{AutoAssertNoGc, StoreOpaque};
 use crate::{GlobalType, Mutability, Result, RootedGcRefImpl, Val};
-use core::ptr;
+use core::ptr::{self, NonNull};
 
 #[repr(C)]
 pub struct VMHostGlobalContext {
@@ -9,18 +9,22 @@ pub struct VMHostGlobalContext {
     pub(crate) global: VMGlobalDefinition,
 }
 
-pub fn generate_global_export(
-    store: &mut StoreOpaque,
-    ty: GlobalType,
-    val: Val,
-) -> Result<crate::runtime::vm::ExportGlobal> {
-    let global = wasmtime_environ::Global {
+fn wasmtime_global(ty: &GlobalType) -> wasmtime_environ::Global {
+    wasmtime_environ::Global {
         wasm_ty: ty.content().to_wasm_type(),
         mutability: match ty.mutability() {
             Mutability::Const => false,
             Mutability::Var => true,
         },
-    };
+    }
+}
+
+pub fn generate_global_export(
+    store: &mut StoreOpaque,
+    ty: GlobalType,
+    val: Val,
+) -> Result<crate::runtime::vm::ExportGlobal> {
+    let global = wasmtime_global(&ty);
     let ctx = StoreBox::new(VMHostGlobalContext {
         ty,
         global: VMGlobalDefinition::new(),
@@ -68,3 +72,20 @@ pub fn generate_global_export(
         global,
     })
 }
+
+/// Creates a global whose value lives in `storage`, which is owned by the
+/// embedder rather than the store.
+///
+/// # Safety
+///
+/// See `Global::new_with_storage`.
+pub unsafe fn generate_global_export_with_storage(
+    ty: &GlobalType,
+    storage: NonNull<VMGlobalDefinition>,
+) -> crate::runtime::vm::ExportGlobal {
+    crate::runtime::vm::ExportGlobal {
+        definition: storage.as_ptr(),
+        vmctx: ptr::null_mut(),
+        global: wasmtime_global(ty),
+    }
+}
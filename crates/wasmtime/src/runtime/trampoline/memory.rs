@@ -59,14 +59,25 @@ pub fn create_memory(
         wmemcheck: false,
         pkey: None,
         tunables: store.engine().tunables(),
+        reserved: None,
     };
 
+    // Memory charged to the store's quota group is refunded if the memory
+    // fails to be created.
+    let quota = store.quota_checkpoint();
     unsafe {
-        let handle = SingleMemoryInstance {
+        let result = SingleMemoryInstance {
             preallocation,
             ondemand: OnDemandInstanceAllocator::default(),
         }
-        .allocate_module(request)?;
+        .allocate_module(request);
+        let handle = match result {
+            Ok(handle) => handle,
+            Err(e) => {
+                store.quota_rollback(quota);
+                return Err(e);
+            }
+        };
         let instance_id = store.add_dummy_instance(handle.clone());
         Ok(instance_id)
     }
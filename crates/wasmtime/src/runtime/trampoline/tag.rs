@@ -0,0 +1,22 @@
+use crate::prelude::*;
+use crate::store::{InstanceId, StoreOpaque};
+use crate::trampoline::create_handle;
+use crate::TagType;
+use wasmtime_environ::{EngineOrModuleTypeIndex, EntityIndex, Module, Tag, TypeIndex};
+
+pub fn create_tag(store: &mut StoreOpaque, ty: &TagType) -> Result<InstanceId> {
+    let mut module = Module::new();
+
+    let tag_id = module.tags.push(Tag::new(
+        TypeIndex::from_u32(0),
+        EngineOrModuleTypeIndex::Engine(ty.type_index()),
+    ));
+
+    module
+        .exports
+        .insert(String::new(), EntityIndex::Tag(tag_id));
+
+    // The instance's host state keeps the tag's signature registered in the
+    // engine for as long as the tag may be used.
+    create_handle(module, store, Box::new(ty.clone()), &[], None)
+}
@@ -4,6 +4,7 @@ use crate::prelude::*;
 use crate::store::StoreOpaque;
 use crate::{AsContext, Module};
 use core::fmt;
+use core::ops::ControlFlow;
 use wasmtime_environ::{demangle_function_name, demangle_function_name_or_index, FilePos};
 
 /// Representation of a WebAssembly trap and what caused it to occur.
@@ -78,6 +79,25 @@ pub(crate) fn from_runtime_box(
         backtrace,
         coredumpstack,
     } = *runtime_trap;
+    if let Some(metrics) = store.engine().metrics() {
+        match &reason {
+            crate::runtime::vm::TrapReason::Jit { trap, .. }
+            | crate::runtime::vm::TrapReason::Wasm(trap) => metrics.record_trap(*trap),
+            // Errors raised by the host are only counted if they are traps,
+            // and not if they are propagating a trap which was already
+            // counted when it was raised by a nested call into wasm, which
+            // is indicated by its backtrace.
+            crate::runtime::vm::TrapReason::User(error)
+                if error.downcast_ref::<WasmBacktrace>().is_none() =>
+            {
+                if let Some(trap) = error.downcast_ref::<Trap>() {
+                    metrics.record_trap(*trap);
+                }
+            }
+            crate::runtime::vm::TrapReason::User(_) => {}
+        }
+    }
+
     let (mut error, pc) = match reason {
         // For user-defined errors they're already an `anyhow::Error` so no
         // conversion is really necessary here, but a `backtrace` may have
@@ -400,6 +420,77 @@ impl fmt::Display for WasmBacktrace {
     }
 }
 
+/// Lightweight description of the WebAssembly frame that called into the host.
+///
+/// This is returned by [`Caller::current_frame`](crate::Caller::current_frame)
+/// and, unlike [`FrameInfo`], is cheap to produce: only the innermost wasm
+/// frame is inspected and no symbolication or function name lookup is
+/// performed. This makes it suitable for attributing work to call sites in
+/// host functions, e.g. for logging or profiling.
+#[derive(Clone, Debug)]
+pub struct CallerFrame {
+    module: Module,
+    func_index: u32,
+    instr: Option<FilePos>,
+}
+
+impl CallerFrame {
+    /// Returns the frame of the most recent WebAssembly function on this
+    /// thread's stack for `store`, if any.
+    pub(crate) fn current(store: &StoreOpaque) -> Option<CallerFrame> {
+        let mut pc = None;
+        crate::runtime::vm::Backtrace::trace(store, |frame| {
+            pc = Some(frame.pc());
+            ControlFlow::Break(())
+        });
+
+        // The pc of a frame which called into the host is a return address,
+        // so look up the previous instruction, the call itself, instead. See
+        // `WasmBacktrace::from_captured` for more details.
+        store.modules().lookup_caller_frame(pc? - 1)
+    }
+
+    pub(crate) fn new(module: Module, text_offset: usize) -> Option<CallerFrame> {
+        let compiled_module = module.compiled_module();
+        let (index, _func_offset) = compiled_module.func_by_text_offset(text_offset)?;
+        let instr = wasmtime_environ::lookup_file_pos(
+            compiled_module.code_memory().address_map_data(),
+            text_offset,
+        );
+        let func_index = compiled_module.module().func_index(index).as_u32();
+        Some(CallerFrame {
+            module,
+            func_index,
+            instr,
+        })
+    }
+
+    /// Returns the WebAssembly function index of the calling function.
+    ///
+    /// This function index is the index in the function index space of the
+    /// WebAssembly module returned by [`CallerFrame::module`].
+    pub fn func_index(&self) -> u32 {
+        self.func_index
+    }
+
+    /// Returns the module whose code made the call.
+    pub fn module(&self) -> &Module {
+        &self.module
+    }
+
+    /// Returns the offset within the original wasm module of the call
+    /// instruction that called into the host.
+    ///
+    /// Note that `None` may be returned if the original module was not
+    /// compiled with mapping information to yield this information. This is
+    /// controlled by the
+    /// [`Config::generate_address_map`](crate::Config::generate_address_map)
+    /// configuration option.
+    pub fn module_offset(&self) -> Option<usize> {
+        Some(self.instr?.file_offset()? as usize)
+    }
+}
+
 /// Description of a frame in a backtrace for a [`WasmBacktrace`].
 ///
 /// Whenever an error happens while WebAssembly is executing a
@@ -553,7 +644,7 @@ impl FrameInfo {
     /// configuration option.
     pub fn func_offset(&self) -> Option<usize> {
         let instr_offset = self.instr?.file_offset()?;
-        Some((instr_offset - self.func_start.file_offset()?) as usize)
+        Some(instr_offset.checked_sub(self.func_start.file_offset()?)? as usize)
     }
 
     /// Returns the debug symbols found, if any, for this function frame.
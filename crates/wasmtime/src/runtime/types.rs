@@ -10,6 +10,8 @@ use wasmtime_environ::{
 use crate::{type_registry::RegisteredType, Engine};
 
 pub(crate) mod matching;
+mod shared_registry;
+pub use shared_registry::{SharedTypeRegistry, TypeToken};
 
 // Type Representations
 
@@ -2565,6 +2567,16 @@ impl ContType {
         self.registered_type.index()
     }
 
+    /// Get the type of the functions that continuations of this type run.
+    pub(crate) fn func_type(&self) -> FuncType {
+        let index = self
+            .registered_type
+            .unwrap_cont()
+            .func_type_index()
+            .unwrap_engine_type_index();
+        FuncType::from_shared_type_index(self.engine(), index)
+    }
+
     /// Does this continuation type match the other continuation type?
     ///
     /// That is, is this continuation type a subtype of the other continuation type?
@@ -3090,9 +3102,32 @@ pub struct TagType {
 }
 
 impl TagType {
+    /// Creates a new tag type described by the function type `ty`.
+    ///
+    /// The parameters of `ty` are the payload of the tag, the values provided
+    /// when suspending to it, and the results of `ty` are the values that a
+    /// suspension to it is resumed with.
+    pub fn new(ty: FuncType) -> TagType {
+        Self::from_wasmtime_tag(ty.into_registered_type())
+    }
+
+    /// Returns the function type describing this tag's payload and resumption
+    /// values.
+    pub fn ty(&self) -> FuncType {
+        FuncType::from_registered_type(self.ty.clone())
+    }
+
     pub(crate) fn from_wasmtime_tag(ty: RegisteredType) -> Self {
         Self { ty }
     }
+
+    pub(crate) fn comes_from_same_engine(&self, engine: &Engine) -> bool {
+        Engine::same(self.ty.engine(), engine)
+    }
+
+    pub(crate) fn type_index(&self) -> VMSharedTypeIndex {
+        self.ty.index()
+    }
 }
 
 // Import Types
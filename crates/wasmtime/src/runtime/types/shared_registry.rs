@@ -0,0 +1,176 @@
+use crate::hash_map::HashMap;
+use crate::prelude::*;
+use crate::sync::RwLock;
+use crate::{Engine, FuncType, MemoryType, ValType};
+use alloc::sync::Arc;
+use wasmtime_environ::WasmFuncType;
+
+/// A registry of function and memory types shared by a group of engines.
+///
+/// Each [`Engine`] has its own type registry and a [`FuncType`] is only valid
+/// for the engine it was created with, so embedders running several engines
+/// (for example with different tunables) would otherwise create and register
+/// the same function type once per engine and have no cheap way to tell that
+/// two types from different engines are the same.
+///
+/// A `SharedTypeRegistry` instead interns types structurally and hands out a
+/// [`TypeToken`] for each distinct type. Tokens are small, `Copy`, and
+/// implement `Eq`, `Hash`, and `Ord` so they can be used as keys in host-side
+/// routing tables regardless of which engine a type came from. The registry
+/// additionally caches the [`FuncType`] registered in each engine of the group
+/// so that [`SharedTypeRegistry::func_type`] only registers a type with a
+/// given engine once.
+///
+/// Only function types which don't reference concrete (user-defined) heap
+/// types can be shared, since concrete types are defined relative to a single
+/// engine's registry. Note that the cached registrations keep their engines
+/// alive for as long as the registry is alive.
+///
+/// Cloning a `SharedTypeRegistry` is cheap and produces a handle to the same
+/// registry.
+#[derive(Clone, Default)]
+pub struct SharedTypeRegistry {
+    inner: Arc<RwLock<Inner>>,
+}
+
+/// A token identifying a type interned in a [`SharedTypeRegistry`].
+///
+/// Two tokens returned from the same registry are equal if and only if the
+/// types they were created from are structurally equal. Tokens from different
+/// registries must not be compared.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TypeToken(u32);
+
+#[derive(Default)]
+struct Inner {
+    funcs: HashMap<WasmFuncType, TypeToken>,
+    memories: HashMap<MemoryType, TypeToken>,
+    entries: Vec<Entry>,
+}
+
+enum Entry {
+    Func {
+        params: Vec<ValType>,
+        results: Vec<ValType>,
+        per_engine: Vec<FuncType>,
+    },
+    Memory(MemoryType),
+}
+
+impl TypeToken {
+    fn index(self) -> usize {
+        usize::try_from(self.0).unwrap()
+    }
+}
+
+impl Inner {
+    fn push(&mut self, entry: Entry) -> TypeToken {
+        let token = TypeToken(u32::try_from(self.entries.len()).unwrap());
+        self.entries.push(entry);
+        token
+    }
+}
+
+impl SharedTypeRegistry {
+    /// Creates a new, empty registry.
+    pub fn new() -> SharedTypeRegistry {
+        SharedTypeRegistry::default()
+    }
+
+    /// Interns the function type `ty`, returning its token.
+    ///
+    /// The `ty` provided is also cached as the registration of this type for
+    /// its engine.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `ty` references a concrete heap type.
+    pub fn intern_func(&self, ty: &FuncType) -> Result<TypeToken> {
+        let is_concrete = |t: &ValType| t.as_ref().is_some_and(|r| r.heap_type().is_concrete());
+        if ty.params().any(|t| is_concrete(&t)) || ty.results().any(|t| is_concrete(&t)) {
+            bail!("function types referencing concrete heap types cannot be shared across engines");
+        }
+
+        let mut inner = self.inner.write();
+        let key = ty.as_wasm_func_type();
+        if let Some(token) = inner.funcs.get(key).copied() {
+            if let Entry::Func { per_engine, .. } = &mut inner.entries[token.index()] {
+                if !per_engine
+                    .iter()
+                    .any(|f| Engine::same(f.engine(), ty.engine()))
+                {
+                    per_engine.push(ty.clone());
+                }
+            }
+            return Ok(token);
+        }
+        let token = inner.push(Entry::Func {
+            params: ty.params().collect(),
+            results: ty.results().collect(),
+            per_engine: vec![ty.clone()],
+        });
+        inner.funcs.insert(key.clone(), token);
+        Ok(token)
+    }
+
+    /// Interns the memory type `ty`, returning its token.
+    pub fn intern_memory(&self, ty: &MemoryType) -> TypeToken {
+        let mut inner = self.inner.write();
+        if let Some(token) = inner.memories.get(ty) {
+            return *token;
+        }
+        let token = inner.push(Entry::Memory(ty.clone()));
+        inner.memories.insert(ty.clone(), token);
+        token
+    }
+
+    /// Returns the function type identified by `token`, registered with
+    /// `engine`.
+    ///
+    /// The first request for a given engine registers the type with that
+    /// engine and subsequent requests return the cached registration.
+    ///
+    /// Returns `None` if `token` doesn't identify a function type in this
+    /// registry.
+    pub fn func_type(&self, engine: &Engine, token: TypeToken) -> Option<FuncType> {
+        let mut inner = self.inner.write();
+        let Entry::Func {
+            params,
+            results,
+            per_engine,
+        } = inner.entries.get_mut(token.index())?
+        else {
+            return None;
+        };
+        if let Some(ty) = per_engine
+            .iter()
+            .find(|ty| Engine::same(ty.engine(), engine))
+        {
+            return Some(ty.clone());
+        }
+        let ty = FuncType::new(engine, params.iter().cloned(), results.iter().cloned());
+        per_engine.push(ty.clone());
+        Some(ty)
+    }
+
+    /// Returns the memory type identified by `token`.
+    ///
+    /// Returns `None` if `token` doesn't identify a memory type in this
+    /// registry.
+    pub fn memory_type(&self, token: TypeToken) -> Option<MemoryType> {
+        match self.inner.read().entries.get(token.index())? {
+            Entry::Memory(ty) => Some(ty.clone()),
+            Entry::Func { .. } => None,
+        }
+    }
+
+    /// Returns the number of distinct types interned in this registry.
+    pub fn len(&self) -> usize {
+        self.inner.read().entries.len()
+    }
+
+    /// Returns whether no types have been interned in this registry.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
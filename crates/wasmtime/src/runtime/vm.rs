@@ -67,8 +67,9 @@ pub use crate::runtime::vm::gc::*;
 pub use crate::runtime::vm::imports::Imports;
 pub use crate::runtime::vm::instance::{
     GcHeapAllocationIndex, Instance, InstanceAllocationRequest, InstanceAllocator,
-    InstanceAllocatorImpl, InstanceAndStore, InstanceHandle, MemoryAllocationIndex,
-    OnDemandInstanceAllocator, StorePtr, TableAllocationIndex,
+    InstanceAllocatorImpl, InstanceAndStore, InstanceBatch, InstanceHandle, MemoryAllocationIndex,
+    OnDemandInstanceAllocator, StorePtr, TableAllocationIndex, WasmFXStackCache,
+    WasmFXStackCacheStats,
 };
 #[cfg(feature = "pooling-allocator")]
 pub use crate::runtime::vm::instance::{
@@ -91,7 +92,8 @@ pub use crate::runtime::vm::unwind::*;
 pub use crate::runtime::vm::vmcontext::{
     VMArrayCallFunction, VMArrayCallHostFuncContext, VMContext, VMFuncRef, VMFunctionBody,
     VMFunctionImport, VMGlobalDefinition, VMGlobalImport, VMMemoryDefinition, VMMemoryImport,
-    VMOpaqueContext, VMRuntimeLimits, VMTableImport, VMTagImport, VMWasmCallFunction, ValRaw,
+    VMOpaqueContext, VMRuntimeLimits, VMTableImport, VMTagDefinition, VMTagImport,
+    VMWasmCallFunction, ValRaw,
 };
 
 pub use send_sync_ptr::SendSyncPtr;
@@ -179,6 +181,25 @@ pub unsafe trait VMStore {
     /// Note that this is not invoked if `table_growing` returns an error.
     fn table_grow_failed(&mut self, error: Error) -> Result<()>;
 
+    /// Callback invoked to allow the store's resource limiter to reject the
+    /// growth of the store's GC heap.
+    fn gc_heap_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        maximum: Option<usize>,
+    ) -> Result<bool, Error>;
+
+    /// Callback invoked to allow the store's resource limiter to reject the
+    /// allocation of a stack for a new continuation, which would bring the
+    /// total size of the store's continuation stacks from `current` to
+    /// `desired` bytes.
+    fn continuation_stack_allocating(
+        &mut self,
+        current: usize,
+        desired: usize,
+    ) -> Result<bool, Error>;
+
     /// Callback invoked whenever fuel runs out by a wasm instance. If an error
     /// is returned that's raised as a trap. Otherwise wasm execution will
     /// continue as normal.
@@ -189,6 +210,27 @@ pub unsafe trait VMStore {
     /// completely semantically transparent. Returns the new deadline.
     fn new_epoch(&mut self) -> Result<u64, Error>;
 
+    /// Callback invoked after wasm writes to the global at `definition` when
+    /// global watchpoints are enabled. If an error is returned that's raised
+    /// as a trap.
+    fn global_written(&mut self, definition: *mut VMGlobalDefinition) -> Result<(), Error>;
+
+    /// Callback invoked before executing the wasm instruction at `offset` in
+    /// its original module when single-stepping is enabled. If an error is
+    /// returned that's raised as a trap.
+    fn single_step(&mut self, offset: u32) -> Result<(), Error>;
+
+    /// Callback invoked when wasm suspends to the tag at `tag` without a
+    /// handler for it in wasm. Returns `false` if the host hasn't registered
+    /// a handler for the tag either. Otherwise the handler is run with the
+    /// suspend payload in `values`, which is replaced with the values to
+    /// resume wasm with.
+    fn suspend_to_host(
+        &mut self,
+        tag: *mut VMTagDefinition,
+        values: &mut Vec<ValRaw>,
+    ) -> Result<bool, Error>;
+
     /// Callback invoked whenever an instance needs to trigger a GC.
     ///
     /// Optionally given a GC reference that is rooted for the collection, and
@@ -61,3 +61,40 @@ pub fn assert_fp_is_aligned(_fp: usize) {
     //
     // [0]: https://github.com/ARM-software/abi-aa/blob/2022Q1/aapcs64/aapcs64.rst#the-frame-pointer
 }
+
+// Bits of `fpcr` which control rounding (RMode), flush-to-zero (FZ), and
+// half-precision flush-to-zero (FZ16). All of these are clear in the default
+// environment, which is round-to-nearest with denormals preserved.
+const FPCR_NON_DEFAULT_MODES: usize = (0b11 << 22) | (1 << 24) | (1 << 19);
+
+/// Resets the floating-point environment to what WebAssembly expects,
+/// returning the previous environment for `restore_fp_environment`.
+pub fn reset_fp_environment() -> usize {
+    let fpcr: usize;
+    unsafe {
+        core::arch::asm!(
+            "mrs {}, fpcr",
+            out(reg) fpcr,
+            options(nomem, nostack, preserves_flags),
+        );
+    }
+    let reset = fpcr & !FPCR_NON_DEFAULT_MODES;
+    if reset != fpcr {
+        unsafe {
+            core::arch::asm!(
+                "msr fpcr, {}",
+                in(reg) reset,
+                options(nomem, nostack, preserves_flags),
+            );
+        }
+    }
+    fpcr
+}
+
+pub unsafe fn restore_fp_environment(prev: usize) {
+    core::arch::asm!(
+        "msr fpcr, {}",
+        in(reg) prev,
+        options(nomem, nostack, preserves_flags),
+    );
+}
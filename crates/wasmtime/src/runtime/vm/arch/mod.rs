@@ -46,3 +46,11 @@ pub const NEXT_OLDER_FP_FROM_FP_OFFSET: usize = imp::NEXT_OLDER_FP_FROM_FP_OFFSE
 pub fn assert_fp_is_aligned(fp: usize) {
     imp::assert_fp_is_aligned(fp)
 }
+
+pub fn reset_fp_environment() -> usize {
+    imp::reset_fp_environment()
+}
+
+pub unsafe fn restore_fp_environment(prev: usize) {
+    imp::restore_fp_environment(prev)
+}
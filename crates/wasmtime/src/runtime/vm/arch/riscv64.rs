@@ -24,3 +24,11 @@ pub const NEXT_OLDER_FP_FROM_FP_OFFSET: usize = 0;
 pub fn assert_fp_is_aligned(fp: usize) {
     assert_eq!(fp % 16, 0, "stack should always be aligned to 16");
 }
+
+pub fn reset_fp_environment() -> usize {
+    // Not currently supported on this architecture, the floating-point
+    // environment is left as-is.
+    0
+}
+
+pub unsafe fn restore_fp_environment(_prev: usize) {}
@@ -26,3 +26,10 @@ pub const NEXT_OLDER_FP_FROM_FP_OFFSET: usize = 0;
 pub fn assert_fp_is_aligned(_fp: usize) {
     panic!()
 }
+
+pub fn reset_fp_environment() -> usize {
+    // The floating-point environment is left as-is on unsupported targets.
+    0
+}
+
+pub unsafe fn restore_fp_environment(_prev: usize) {}
@@ -37,3 +37,56 @@ pub fn assert_fp_is_aligned(fp: usize) {
     let align = 2 * size_of::<usize>();
     assert_eq!(fp % align, 0, "stack should always be aligned to {align}");
 }
+
+// Bits of `mxcsr` which control rounding (RC), flush-to-zero (FTZ), and
+// denormals-are-zero (DAZ). All of these are clear in the default
+// environment, which is round-to-nearest with denormals preserved.
+#[cfg(any(target_arch = "x86_64", target_feature = "sse"))]
+const MXCSR_NON_DEFAULT_MODES: u32 = (0b11 << 13) | (1 << 15) | (1 << 6);
+
+/// Resets the floating-point environment to what WebAssembly expects,
+/// returning the previous environment for `restore_fp_environment`.
+pub fn reset_fp_environment() -> usize {
+    #[cfg(any(target_arch = "x86_64", target_feature = "sse"))]
+    {
+        let mut mxcsr: u32 = 0;
+        unsafe {
+            core::arch::asm!(
+                "stmxcsr [{}]",
+                in(reg) &mut mxcsr,
+                options(nostack, preserves_flags),
+            );
+        }
+        let reset = mxcsr & !MXCSR_NON_DEFAULT_MODES;
+        if reset != mxcsr {
+            unsafe {
+                core::arch::asm!(
+                    "ldmxcsr [{}]",
+                    in(reg) &reset,
+                    options(nostack, preserves_flags, readonly),
+                );
+            }
+        }
+        mxcsr as usize
+    }
+    #[cfg(not(any(target_arch = "x86_64", target_feature = "sse")))]
+    {
+        0
+    }
+}
+
+pub unsafe fn restore_fp_environment(prev: usize) {
+    #[cfg(any(target_arch = "x86_64", target_feature = "sse"))]
+    {
+        let prev = prev as u32;
+        core::arch::asm!(
+            "ldmxcsr [{}]",
+            in(reg) &prev,
+            options(nostack, preserves_flags, readonly),
+        );
+    }
+    #[cfg(not(any(target_arch = "x86_64", target_feature = "sse")))]
+    {
+        let _ = prev;
+    }
+}
@@ -27,9 +27,11 @@ use wasmtime_environ::{HostPtr, PrimaryMap, VMSharedTypeIndex};
                                            // 32-bit platforms
 const INVALID_PTR: usize = 0xdead_dead_beef_beef_u64 as usize;
 
+mod async_handles;
 mod libcalls;
 mod resources;
 
+pub use self::async_handles::{AsyncHandle, AsyncHandleKind, AsyncHandleTable};
 pub use self::resources::{CallContexts, ResourceTable, ResourceTables};
 
 /// Runtime representation of a component instance and all state necessary for
@@ -58,6 +60,10 @@ pub struct ComponentInstance {
     /// is how this field is manipulated.
     component_resource_tables: PrimaryMap<TypeResourceTableIndex, ResourceTable>,
 
+    /// The futures and streams owned by each runtime component instance
+    /// within this component.
+    async_handle_tables: PrimaryMap<RuntimeComponentInstanceIndex, AsyncHandleTable>,
+
     /// Storage for the type information about resources within this component
     /// instance.
     ///
@@ -203,6 +209,12 @@ impl ComponentInstance {
             component_resource_tables.push(ResourceTable::default());
         }
 
+        let num_instances = offsets.num_runtime_component_instances as usize;
+        let mut async_handle_tables = PrimaryMap::with_capacity(num_instances);
+        for _ in 0..num_instances {
+            async_handle_tables.push(AsyncHandleTable::default());
+        }
+
         ptr::write(
             ptr.as_ptr(),
             ComponentInstance {
@@ -216,6 +228,7 @@ impl ComponentInstance {
                     .unwrap(),
                 ),
                 component_resource_tables,
+                async_handle_tables,
                 runtime_info,
                 resource_types,
                 vmctx: VMComponentContext {
@@ -532,7 +545,15 @@ impl ComponentInstance {
     /// Implementation of the `resource.new` intrinsic for `i32`
     /// representations.
     pub fn resource_new32(&mut self, resource: TypeResourceTableIndex, rep: u32) -> Result<u32> {
-        self.resource_tables().resource_new(Some(resource), rep)
+        let idx = self.resource_tables().resource_new(Some(resource), rep)?;
+        if self.abi_trace() {
+            log::debug!(
+                target: "wasmtime::component::abi",
+                "resource.new: rep {rep} => handle {idx} in table {}",
+                resource.as_u32(),
+            );
+        }
+        Ok(idx)
     }
 
     /// Implementation of the `resource.rep` intrinsic for `i32`
@@ -547,7 +568,21 @@ impl ComponentInstance {
         resource: TypeResourceTableIndex,
         idx: u32,
     ) -> Result<Option<u32>> {
-        self.resource_tables().resource_drop(Some(resource), idx)
+        let rep = self.resource_tables().resource_drop(Some(resource), idx)?;
+        if self.abi_trace() {
+            log::debug!(
+                target: "wasmtime::component::abi",
+                "resource.drop: handle {idx} in table {} => rep {rep:?}",
+                resource.as_u32(),
+            );
+        }
+        Ok(rep)
+    }
+
+    /// Returns whether canonical ABI operations should be logged for the store
+    /// that owns this instance.
+    fn abi_trace(&self) -> bool {
+        unsafe { (*self.store()).store_opaque().component_abi_trace() }
     }
 
     /// NB: this is intended to be a private method. This does not have
@@ -573,6 +608,15 @@ impl ComponentInstance {
         &mut self.component_resource_tables
     }
 
+    /// Returns the table of futures and streams owned by `instance`.
+    #[inline]
+    pub fn async_handle_table(
+        &mut self,
+        instance: RuntimeComponentInstanceIndex,
+    ) -> &mut AsyncHandleTable {
+        &mut self.async_handle_tables[instance]
+    }
+
     /// Returns the destructor and instance flags for the specified resource
     /// table type.
     ///
@@ -600,7 +644,16 @@ impl ComponentInstance {
     ) -> Result<u32> {
         let mut tables = self.resource_tables();
         let rep = tables.resource_lift_own(Some(src), idx)?;
-        tables.resource_lower_own(Some(dst), rep)
+        let ret = tables.resource_lower_own(Some(dst), rep)?;
+        if self.abi_trace() {
+            log::debug!(
+                target: "wasmtime::component::abi",
+                "transfer own: handle {idx} in table {} => handle {ret} in table {} (rep {rep})",
+                src.as_u32(),
+                dst.as_u32(),
+            );
+        }
+        Ok(ret)
     }
 
     pub(crate) fn resource_transfer_borrow(
@@ -620,10 +673,64 @@ impl ComponentInstance {
         // this check at runtime since we know at compile time whether the
         // destination type owns the resource, but that's left as a future
         // refactoring if truly necessary.
-        if dst_owns_resource {
-            return Ok(rep);
+        let ret = if dst_owns_resource {
+            rep
+        } else {
+            tables.resource_lower_borrow(Some(dst), rep)?
+        };
+        if self.abi_trace() {
+            log::debug!(
+                target: "wasmtime::component::abi",
+                "transfer borrow: handle {idx} in table {} => {} {ret} in table {} (rep {rep})",
+                src.as_u32(),
+                if dst_owns_resource { "rep" } else { "handle" },
+                dst.as_u32(),
+            );
+        }
+        Ok(ret)
+    }
+
+    pub(crate) fn future_transfer(
+        &mut self,
+        idx: u32,
+        src: TypeFutureTableIndex,
+        dst: TypeFutureTableIndex,
+    ) -> Result<u32> {
+        let types = self.component_types();
+        let (src, dst) = (types[src].instance, types[dst].instance);
+        self.async_handle_transfer(AsyncHandleKind::Future, idx, src, dst)
+    }
+
+    pub(crate) fn stream_transfer(
+        &mut self,
+        idx: u32,
+        src: TypeStreamTableIndex,
+        dst: TypeStreamTableIndex,
+    ) -> Result<u32> {
+        let types = self.component_types();
+        let (src, dst) = (types[src].instance, types[dst].instance);
+        self.async_handle_transfer(AsyncHandleKind::Stream, idx, src, dst)
+    }
+
+    fn async_handle_transfer(
+        &mut self,
+        kind: AsyncHandleKind,
+        idx: u32,
+        src: RuntimeComponentInstanceIndex,
+        dst: RuntimeComponentInstanceIndex,
+    ) -> Result<u32> {
+        let handle = self.async_handle_tables[src].remove(idx, kind)?;
+        let ret = self.async_handle_tables[dst].insert(handle)?;
+        if self.abi_trace() {
+            log::debug!(
+                target: "wasmtime::component::abi",
+                "transfer {}: handle {idx} in instance {} => handle {ret} in instance {}",
+                kind.desc(),
+                src.as_u32(),
+                dst.as_u32(),
+            );
         }
-        tables.resource_lower_borrow(Some(dst), rep)
+        Ok(ret)
     }
 
     pub(crate) fn resource_enter_call(&mut self) {
@@ -0,0 +1,110 @@
+//! Tables of handles to the readable ends of futures and streams.
+//!
+//! Each component instance has one `AsyncHandleTable` which holds the futures
+//! and streams it currently owns. Handles are indices into that instance's
+//! table, so a handle is only meaningful to the instance it was given to and
+//! can't be used to reach into another instance's futures or streams. Passing
+//! a future or stream to another component or the host moves it out of the
+//! source table and, for components, into the destination's table.
+//!
+//! The state behind a handle is opaque to this module and is defined in
+//! `crate::component::streams` instead.
+
+use crate::prelude::*;
+use alloc::sync::Arc;
+use core::any::Any;
+use core::mem;
+
+/// Same maximum as `MAX_RESOURCE_HANDLE`, which keeps the upper bit free for
+/// use in the component.
+const MAX_ASYNC_HANDLE: u32 = 1 << 30;
+
+/// Whether an `AsyncHandle` refers to a future or a stream.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum AsyncHandleKind {
+    /// The readable end of a `future<T>`.
+    Future,
+    /// The readable end of a `stream<T>`.
+    Stream,
+}
+
+impl AsyncHandleKind {
+    /// Returns the name of this kind for use in error messages.
+    pub fn desc(&self) -> &'static str {
+        match self {
+            AsyncHandleKind::Future => "future",
+            AsyncHandleKind::Stream => "stream",
+        }
+    }
+}
+
+/// The readable end of a future or stream stored in an `AsyncHandleTable`.
+pub struct AsyncHandle {
+    /// Whether this is a future or a stream.
+    pub kind: AsyncHandleKind,
+    /// State shared with the writable end.
+    pub state: Arc<dyn Any + Send + Sync>,
+}
+
+/// A slab of future and stream handles owned by a single component instance.
+#[derive(Default)]
+pub struct AsyncHandleTable {
+    /// Next slot to allocate, or `self.slots.len()` if they're all full.
+    next: u32,
+    /// Runtime state of all slots.
+    slots: Vec<Slot>,
+}
+
+enum Slot {
+    /// This slot is free and points to the next free slot, forming a linked
+    /// list of free slots.
+    Free { next: u32 },
+
+    /// This slot holds the readable end of a future or stream.
+    Handle(AsyncHandle),
+}
+
+impl AsyncHandleTable {
+    /// Inserts `handle` into this table, returning the index that the owning
+    /// component instance refers to it by.
+    pub fn insert(&mut self, handle: AsyncHandle) -> Result<u32> {
+        let next = self.next as usize;
+        if next == self.slots.len() {
+            self.slots.push(Slot::Free {
+                next: self.next.checked_add(1).unwrap(),
+            });
+        }
+        // Like resource handles, index 0 is never allocated so numbering
+        // starts at 1 instead.
+        let ret = self.next + 1;
+        if ret >= MAX_ASYNC_HANDLE {
+            bail!("cannot allocate another handle: index overflow");
+        }
+        self.next = match mem::replace(&mut self.slots[next], Slot::Handle(handle)) {
+            Slot::Free { next } => next,
+            Slot::Handle(_) => unreachable!(),
+        };
+        Ok(ret)
+    }
+
+    /// Removes the handle at `idx`, which must be of the `kind` specified, from
+    /// this table.
+    pub fn remove(&mut self, idx: u32, kind: AsyncHandleKind) -> Result<AsyncHandle> {
+        let slot = idx
+            .checked_sub(1)
+            .and_then(|i| self.slots.get_mut(usize::try_from(i).ok()?));
+        let Some(slot) = slot else {
+            bail!("unknown {} handle index {idx}", kind.desc());
+        };
+        match slot {
+            Slot::Handle(handle) if handle.kind == kind => {}
+            Slot::Handle(_) => bail!("handle index {idx} is not a {}", kind.desc()),
+            Slot::Free { .. } => bail!("unknown {} handle index {idx}", kind.desc()),
+        }
+        let Slot::Handle(handle) = mem::replace(slot, Slot::Free { next: self.next }) else {
+            unreachable!()
+        };
+        self.next = idx - 1;
+        Ok(handle)
+    }
+}
@@ -6,10 +6,22 @@ use crate::runtime::vm::HostResultHasUnwindSentinel;
 use core::cell::Cell;
 use core::convert::Infallible;
 use core::slice;
-use wasmtime_environ::component::TypeResourceTableIndex;
+use wasmtime_environ::component::{
+    TypeFutureTableIndex, TypeResourceTableIndex, TypeStreamTableIndex,
+};
 
 const UTF16_TAG: usize = 1 << 31;
 
+/// Logs a canonical ABI operation performed by an adapter module if the store
+/// it's running within has `Store::trace_canonical_abi` enabled.
+macro_rules! abi_trace {
+    ($vmctx:ident, $($arg:tt)*) => {
+        if ComponentInstance::from_vmctx($vmctx, |instance| instance.abi_trace()) {
+            log::debug!(target: "wasmtime::component::abi", $($arg)*);
+        }
+    };
+}
+
 macro_rules! signature {
     (@ty size) => (usize);
     (@ty ptr_u8) => (*mut u8);
@@ -140,11 +152,16 @@ fn assert_no_overlap<T, U>(a: &[T], b: &[U]) {
 /// The length provided is length of both the source and the destination
 /// buffers. No value is returned other than whether an invalid string was
 /// found.
-unsafe fn utf8_to_utf8(src: *mut u8, len: usize, dst: *mut u8) -> Result<()> {
+unsafe fn utf8_to_utf8(
+    vmctx: *mut VMComponentContext,
+    src: *mut u8,
+    len: usize,
+    dst: *mut u8,
+) -> Result<()> {
     let src = slice::from_raw_parts(src, len);
     let dst = slice::from_raw_parts_mut(dst, len);
     assert_no_overlap(src, dst);
-    log::trace!("utf8-to-utf8 {len}");
+    abi_trace!(vmctx, "utf8-to-utf8 {len}");
     let src = core::str::from_utf8(src).map_err(|_| anyhow!("invalid utf8 encoding"))?;
     dst.copy_from_slice(src.as_bytes());
     Ok(())
@@ -155,11 +172,16 @@ unsafe fn utf8_to_utf8(src: *mut u8, len: usize, dst: *mut u8) -> Result<()> {
 /// The length provided is length of both the source and the destination
 /// buffers. No value is returned other than whether an invalid string was
 /// found.
-unsafe fn utf16_to_utf16(src: *mut u16, len: usize, dst: *mut u16) -> Result<()> {
+unsafe fn utf16_to_utf16(
+    vmctx: *mut VMComponentContext,
+    src: *mut u16,
+    len: usize,
+    dst: *mut u16,
+) -> Result<()> {
     let src = slice::from_raw_parts(src, len);
     let dst = slice::from_raw_parts_mut(dst, len);
     assert_no_overlap(src, dst);
-    log::trace!("utf16-to-utf16 {len}");
+    abi_trace!(vmctx, "utf16-to-utf16 {len}");
     run_utf16_to_utf16(src, dst)?;
     Ok(())
 }
@@ -185,11 +207,16 @@ fn run_utf16_to_utf16(src: &[u16], mut dst: &mut [u16]) -> Result<bool> {
 ///
 /// Given that all byte sequences are valid latin1 strings this is simply a
 /// memory copy.
-unsafe fn latin1_to_latin1(src: *mut u8, len: usize, dst: *mut u8) -> Result<()> {
+unsafe fn latin1_to_latin1(
+    vmctx: *mut VMComponentContext,
+    src: *mut u8,
+    len: usize,
+    dst: *mut u8,
+) -> Result<()> {
     let src = slice::from_raw_parts(src, len);
     let dst = slice::from_raw_parts_mut(dst, len);
     assert_no_overlap(src, dst);
-    log::trace!("latin1-to-latin1 {len}");
+    abi_trace!(vmctx, "latin1-to-latin1 {len}");
     dst.copy_from_slice(src);
     Ok(())
 }
@@ -198,14 +225,19 @@ unsafe fn latin1_to_latin1(src: *mut u8, len: usize, dst: *mut u8) -> Result<()>
 ///
 /// This simply inflates the latin1 characters to the u16 code points. The
 /// length provided is the same length of the source and destination buffers.
-unsafe fn latin1_to_utf16(src: *mut u8, len: usize, dst: *mut u16) -> Result<()> {
+unsafe fn latin1_to_utf16(
+    vmctx: *mut VMComponentContext,
+    src: *mut u8,
+    len: usize,
+    dst: *mut u16,
+) -> Result<()> {
     let src = slice::from_raw_parts(src, len);
     let dst = slice::from_raw_parts_mut(dst, len);
     assert_no_overlap(src, dst);
     for (src, dst) in src.iter().zip(dst) {
         *dst = u16::from(*src).to_le();
     }
-    log::trace!("latin1-to-utf16 {len}");
+    abi_trace!(vmctx, "latin1-to-utf16 {len}");
     Ok(())
 }
 
@@ -223,13 +255,18 @@ unsafe impl HostResultHasUnwindSentinel for CopySizeReturn {
 ///
 /// The length provided is the same unit length of both buffers, and the
 /// returned value from this function is how many u16 units were written.
-unsafe fn utf8_to_utf16(src: *mut u8, len: usize, dst: *mut u16) -> Result<CopySizeReturn> {
+unsafe fn utf8_to_utf16(
+    vmctx: *mut VMComponentContext,
+    src: *mut u8,
+    len: usize,
+    dst: *mut u16,
+) -> Result<CopySizeReturn> {
     let src = slice::from_raw_parts(src, len);
     let dst = slice::from_raw_parts_mut(dst, len);
     assert_no_overlap(src, dst);
 
     let result = run_utf8_to_utf16(src, dst)?;
-    log::trace!("utf8-to-utf16 {len} => {result}");
+    abi_trace!(vmctx, "utf8-to-utf16 {len} => {result}");
     Ok(CopySizeReturn(result))
 }
 
@@ -263,6 +300,7 @@ unsafe impl HostResultHasUnwindSentinel for SizePair {
 /// a partial transcode if the destination buffer is not large enough to hold
 /// the entire contents.
 unsafe fn utf16_to_utf8(
+    vmctx: *mut VMComponentContext,
     src: *mut u16,
     src_len: usize,
     dst: *mut u8,
@@ -302,7 +340,10 @@ unsafe fn utf16_to_utf8(
         dst = &mut dst[len..];
     }
 
-    log::trace!("utf16-to-utf8 {src_len}/{dst_len} => {src_read}/{dst_written}");
+    abi_trace!(
+        vmctx,
+        "utf16-to-utf8 {src_len}/{dst_len} => {src_read}/{dst_written}"
+    );
     Ok(SizePair {
         src_read,
         dst_written,
@@ -316,6 +357,7 @@ unsafe fn utf16_to_utf8(
 ///
 /// This may perform a partial encoding if the destination is not large enough.
 unsafe fn latin1_to_utf8(
+    vmctx: *mut VMComponentContext,
     src: *mut u8,
     src_len: usize,
     dst: *mut u8,
@@ -325,7 +367,10 @@ unsafe fn latin1_to_utf8(
     let dst = slice::from_raw_parts_mut(dst, dst_len);
     assert_no_overlap(src, dst);
     let (read, written) = encoding_rs::mem::convert_latin1_to_utf8_partial(src, dst);
-    log::trace!("latin1-to-utf8 {src_len}/{dst_len} => ({read}, {written})");
+    abi_trace!(
+        vmctx,
+        "latin1-to-utf8 {src_len}/{dst_len} => ({read}, {written})"
+    );
     Ok(SizePair {
         src_read: read,
         dst_written: written,
@@ -340,6 +385,7 @@ unsafe fn latin1_to_utf8(
 /// returned. Otherwise the string is "deflated" from a utf16 string to a latin1
 /// string and the latin1 length is returned.
 unsafe fn utf16_to_compact_probably_utf16(
+    vmctx: *mut VMComponentContext,
     src: *mut u16,
     len: usize,
     dst: *mut u16,
@@ -355,10 +401,16 @@ unsafe fn utf16_to_compact_probably_utf16(
         for i in 0..len {
             dst[i] = dst[2 * i];
         }
-        log::trace!("utf16-to-compact-probably-utf16 {len} => latin1 {len}");
+        abi_trace!(
+            vmctx,
+            "utf16-to-compact-probably-utf16 {len} => latin1 {len}"
+        );
         Ok(CopySizeReturn(len))
     } else {
-        log::trace!("utf16-to-compact-probably-utf16 {len} => utf16 {len}");
+        abi_trace!(
+            vmctx,
+            "utf16-to-compact-probably-utf16 {len} => utf16 {len}"
+        );
         Ok(CopySizeReturn(len | UTF16_TAG))
     }
 }
@@ -373,13 +425,18 @@ unsafe fn utf16_to_compact_probably_utf16(
 ///
 /// Note that this may not convert the entire source into the destination if the
 /// original utf8 string has usvs not representable in latin1.
-unsafe fn utf8_to_latin1(src: *mut u8, len: usize, dst: *mut u8) -> Result<SizePair> {
+unsafe fn utf8_to_latin1(
+    vmctx: *mut VMComponentContext,
+    src: *mut u8,
+    len: usize,
+    dst: *mut u8,
+) -> Result<SizePair> {
     let src = slice::from_raw_parts(src, len);
     let dst = slice::from_raw_parts_mut(dst, len);
     assert_no_overlap(src, dst);
     let read = encoding_rs::mem::utf8_latin1_up_to(src);
     let written = encoding_rs::mem::convert_utf8_to_latin1_lossy(&src[..read], dst);
-    log::trace!("utf8-to-latin1 {len} => ({read}, {written})");
+    abi_trace!(vmctx, "utf8-to-latin1 {len} => ({read}, {written})");
     Ok(SizePair {
         src_read: read,
         dst_written: written,
@@ -389,7 +446,12 @@ unsafe fn utf8_to_latin1(src: *mut u8, len: usize, dst: *mut u8) -> Result<SizeP
 /// Converts a utf16 string to latin1
 ///
 /// This is the same as `utf8_to_latin1` in terms of parameters/results.
-unsafe fn utf16_to_latin1(src: *mut u16, len: usize, dst: *mut u8) -> Result<SizePair> {
+unsafe fn utf16_to_latin1(
+    vmctx: *mut VMComponentContext,
+    src: *mut u16,
+    len: usize,
+    dst: *mut u8,
+) -> Result<SizePair> {
     let src = slice::from_raw_parts(src, len);
     let dst = slice::from_raw_parts_mut(dst, len);
     assert_no_overlap(src, dst);
@@ -403,7 +465,7 @@ unsafe fn utf16_to_latin1(src: *mut u16, len: usize, dst: *mut u8) -> Result<Siz
         }
         size += 1;
     }
-    log::trace!("utf16-to-latin1 {len} => {size}");
+    abi_trace!(vmctx, "utf16-to-latin1 {len} => {size}");
     Ok(SizePair {
         src_read: size,
         dst_written: size,
@@ -426,6 +488,7 @@ unsafe fn utf16_to_latin1(src: *mut u16, len: usize, dst: *mut u8) -> Result<Siz
 /// After the initial latin1 code units have been inflated the entirety of `src`
 /// is then transcoded into the remaining space within `dst`.
 unsafe fn utf8_to_compact_utf16(
+    vmctx: *mut VMComponentContext,
     src: *mut u8,
     src_len: usize,
     dst: *mut u16,
@@ -438,12 +501,16 @@ unsafe fn utf8_to_compact_utf16(
 
     let dst = inflate_latin1_bytes(dst, latin1_bytes_so_far);
     let result = run_utf8_to_utf16(src, dst)?;
-    log::trace!("utf8-to-compact-utf16 {src_len}/{dst_len}/{latin1_bytes_so_far} => {result}");
+    abi_trace!(
+        vmctx,
+        "utf8-to-compact-utf16 {src_len}/{dst_len}/{latin1_bytes_so_far} => {result}"
+    );
     Ok(CopySizeReturn(result + latin1_bytes_so_far))
 }
 
 /// Same as `utf8_to_compact_utf16` but for utf16 source strings.
 unsafe fn utf16_to_compact_utf16(
+    vmctx: *mut VMComponentContext,
     src: *mut u16,
     src_len: usize,
     dst: *mut u16,
@@ -457,7 +524,10 @@ unsafe fn utf16_to_compact_utf16(
     let dst = inflate_latin1_bytes(dst, latin1_bytes_so_far);
     run_utf16_to_utf16(src, dst)?;
     let result = src.len();
-    log::trace!("utf16-to-compact-utf16 {src_len}/{dst_len}/{latin1_bytes_so_far} => {result}");
+    abi_trace!(
+        vmctx,
+        "utf16-to-compact-utf16 {src_len}/{dst_len}/{latin1_bytes_so_far} => {result}"
+    );
     Ok(CopySizeReturn(result + latin1_bytes_so_far))
 }
 
@@ -546,6 +616,32 @@ unsafe fn resource_transfer_borrow(
     })
 }
 
+unsafe fn future_transfer(
+    vmctx: *mut VMComponentContext,
+    src_idx: u32,
+    src_table: u32,
+    dst_table: u32,
+) -> Result<u32> {
+    let src_table = TypeFutureTableIndex::from_u32(src_table);
+    let dst_table = TypeFutureTableIndex::from_u32(dst_table);
+    ComponentInstance::from_vmctx(vmctx, |instance| {
+        instance.future_transfer(src_idx, src_table, dst_table)
+    })
+}
+
+unsafe fn stream_transfer(
+    vmctx: *mut VMComponentContext,
+    src_idx: u32,
+    src_table: u32,
+    dst_table: u32,
+) -> Result<u32> {
+    let src_table = TypeStreamTableIndex::from_u32(src_table);
+    let dst_table = TypeStreamTableIndex::from_u32(dst_table);
+    ComponentInstance::from_vmctx(vmctx, |instance| {
+        instance.stream_transfer(src_idx, src_table, dst_table)
+    })
+}
+
 unsafe fn resource_enter_call(vmctx: *mut VMComponentContext) {
     ComponentInstance::from_vmctx(vmctx, |instance| instance.resource_enter_call())
 }
@@ -554,6 +650,20 @@ unsafe fn resource_exit_call(vmctx: *mut VMComponentContext) -> Result<()> {
     ComponentInstance::from_vmctx(vmctx, |instance| instance.resource_exit_call())
 }
 
+unsafe fn trace_realloc(
+    vmctx: *mut VMComponentContext,
+    old_ptr: u64,
+    old_size: u64,
+    align: u64,
+    new_size: u64,
+    ret: u64,
+) {
+    abi_trace!(
+        vmctx,
+        "realloc({old_ptr:#x}, {old_size}, {align}, {new_size}) => {ret:#x}"
+    );
+}
+
 unsafe fn trap(_vmctx: *mut VMComponentContext, code: u8) -> Result<Infallible> {
     Err(wasmtime_environ::Trap::from_u8(code).unwrap().into())
 }
@@ -107,6 +107,13 @@ pub struct CallContexts {
     scopes: Vec<CallContext>,
 }
 
+impl CallContexts {
+    /// Returns the number of calling contexts which are currently active.
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+}
+
 #[derive(Default)]
 struct CallContext {
     lenders: Vec<Lender>,
@@ -288,6 +295,24 @@ impl ResourceTables<'_> {
         }
         Ok(())
     }
+
+    /// Exits all calling contexts entered after `depth` contexts were active,
+    /// without checking for remaining borrows.
+    ///
+    /// This is used to clean up after a call into a component failed part
+    /// way through, which leaves the component instance poisoned. Only lends
+    /// from the host table are undone, since those owned by the instance can
+    /// no longer be used anyway.
+    pub fn abandon_calls(&mut self, depth: usize) {
+        while self.calls.scopes.len() > depth {
+            let cx = self.calls.scopes.pop().unwrap();
+            for lender in cx.lenders.iter().filter(|l| l.ty.is_none()) {
+                if let Ok(Slot::Own { lend_count, .. }) = self.table(None).get_mut(lender.idx) {
+                    *lend_count -= 1;
+                }
+            }
+        }
+    }
 }
 
 impl ResourceTable {
@@ -40,6 +40,18 @@ cfg_if::cfg_if! {
 /// For performance reasons, the VMContRef at the bottom of this chain
 /// (i.e., the one pointed to by the VMContObj) has a pointer to the
 /// other end of the chain (i.e., its last ancestor).
+/// Bookkeeping for a continuation being resumed by the host rather than by a
+/// `resume` instruction, see `optimized::resume_from_host`.
+#[derive(Default)]
+pub struct HostResumption {
+    /// The tags whose suspensions are returned to the host. The handler index
+    /// of a suspension is the index of its tag in this list.
+    pub tags: Vec<crate::runtime::vm::SendSyncPtr<crate::runtime::vm::VMTagDefinition>>,
+    /// The handler index and payload of the suspension which returned to the
+    /// host, if any.
+    pub suspension: Option<(u32, Vec<crate::runtime::vm::ValRaw>)>,
+}
+
 pub mod safe_vm_contobj {
     use super::imp::VMContRef;
     use core::ptr::NonNull;
@@ -68,16 +80,18 @@ unsafe impl Sync for VMContObj {}
 #[cfg(any(not(feature = "wasmfx_baseline"), feature = "wasmfx_no_baseline"))]
 pub mod optimized {
     use super::stack_chain::StackChain;
+    use super::VMContObj;
     use crate::runtime::vm::{
-        vmcontext::{VMFuncRef, ValRaw},
+        vmcontext::{VMFuncRef, VMTagDefinition, ValRaw},
         Instance, TrapReason, VMStore,
     };
+    use crate::store::StoreOpaque;
     use core::cmp;
     use std::marker::PhantomPinned;
     use wasmtime_continuations::HandlerList;
     #[allow(unused)]
     use wasmtime_continuations::{debug_println, CommonStackInformation, ENABLE_DEBUG_PRINTING};
-    pub use wasmtime_continuations::{Payloads, StackLimits, State};
+    pub use wasmtime_continuations::{ControlEffect, Payloads, StackLimits, State};
 
     /// Fibers used for continuations
     pub type FiberStack = crate::runtime::vm::fibre::FiberStack;
@@ -118,6 +132,10 @@ pub mod optimized {
         /// Revision counter.
         pub revision: u64,
 
+        /// The function passed to `cont.new`, which is only accessed from
+        /// Rust and so doesn't need an offset for generated code.
+        pub func_ref: *mut VMFuncRef,
+
         /// Tell the compiler that this structure has potential self-references
         /// through the `last_ancestor` pointer.
         _marker: core::marker::PhantomPinned,
@@ -152,6 +170,7 @@ pub mod optimized {
             let args = Payloads::new(0);
             let values = Payloads::new(0);
             let revision = 0;
+            let func_ref = std::ptr::null_mut();
             let _marker = PhantomPinned;
 
             Self {
@@ -162,6 +181,7 @@ pub mod optimized {
                 args,
                 values,
                 revision,
+                func_ref,
                 _marker,
             }
         }
@@ -249,9 +269,9 @@ pub mod optimized {
         let stack_size = wasmfx_config.stack_size;
         let red_zone_size = wasmfx_config.red_zone_size;
 
-        let (contref, mut stack) = instance.wasmfx_allocate_continuation().map_err(|_error| {
-            TrapReason::User(anyhow::anyhow!("Fiber stack allocation failed!"))
-        })?;
+        let (contref, mut stack) = instance
+            .wasmfx_allocate_continuation()
+            .map_err(|error| TrapReason::User(error.context("Fiber stack allocation failed!")))?;
 
         let tsp = stack.top().unwrap();
         let stack_limit = unsafe { tsp.sub(stack_size - red_zone_size) } as usize;
@@ -267,6 +287,7 @@ pub mod optimized {
             // The continuation is fresh, which is a special case of being suspended.
             // Thus we need to set the correct end of the continuation chain: itself.
             contref.last_ancestor = contref;
+            contref.func_ref = func.cast::<VMFuncRef>();
 
             // In order to give the pool a uniform interface for the optimized
             // and baseline implementation, it returns the `FiberStack` as a
@@ -292,6 +313,220 @@ pub mod optimized {
         Ok(contref)
     }
 
+    /// What `Store::snapshot` can record about a continuation object.
+    pub enum ContSnapshot {
+        /// The continuation object has already been consumed.
+        Consumed,
+        /// The continuation hasn't been resumed yet and will run this
+        /// function.
+        Fresh(*mut VMFuncRef),
+        /// The continuation hasn't been resumed yet but has arguments bound
+        /// by `cont.bind`.
+        Bound,
+        /// The continuation has been resumed, so its state is on its stack.
+        Started,
+    }
+
+    /// Inspects `contobj` for `Store::snapshot`.
+    ///
+    /// # Safety
+    ///
+    /// As for `resume`, the `VMContRef` that `contobj` points to must not
+    /// have been deallocated.
+    pub unsafe fn snapshot_cont(contobj: VMContObj) -> ContSnapshot {
+        let contref = contobj.contref.as_ref();
+        if contref.revision != contobj.revision {
+            return ContSnapshot::Consumed;
+        }
+        match contref.common_stack_information.state {
+            State::Fresh if contref.args.length == 0 => ContSnapshot::Fresh(contref.func_ref),
+            State::Fresh => ContSnapshot::Bound,
+            _ => ContSnapshot::Started,
+        }
+    }
+
+    /// Hands a suspension to `tag`, for which no handler was found in wasm,
+    /// over to the host. If the host resumed the running continuation and
+    /// handles `tag`, the continuation is suspended back to it. Otherwise the
+    /// host handler registered for the tag is run, trapping if there is none.
+    ///
+    /// The suspend payload is taken from the instance's payloads buffer, and
+    /// the values to resume with are stored back into it.
+    pub fn suspend_to_host(
+        store: &mut dyn VMStore,
+        instance: &mut Instance,
+        tag: *mut VMTagDefinition,
+    ) -> Result<(), TrapReason> {
+        let mut values = {
+            let payloads = unsafe { &mut *instance.typed_continuations_payloads() };
+            let values = (0..payloads.length as usize)
+                .map(|i| unsafe { payloads.data.add(i).cast::<ValRaw>().read() })
+                .collect::<Vec<_>>();
+            payloads.clear();
+            values
+        };
+
+        let host_handler = store
+            .store_opaque_mut()
+            .host_resumption_mut()
+            .as_ref()
+            .and_then(|resumption| resumption.tags.iter().position(|t| t.as_ptr() == tag));
+        if let Some(handler_index) = host_handler {
+            let handler_index = u32::try_from(handler_index).unwrap();
+            let store = store.store_opaque_mut();
+            store.host_resumption_mut().as_mut().unwrap().suspension =
+                Some((handler_index, values));
+            values = unsafe { suspend_to_resuming_host(store, handler_index) };
+        } else if !store.suspend_to_host(tag, &mut values)? {
+            return Err(TrapReason::Wasm(wasmtime_environ::Trap::UnhandledTag));
+        }
+
+        if !values.is_empty() {
+            let payloads = unsafe { &mut *instance.typed_continuations_payloads() };
+            let length = u32::try_from(values.len()).unwrap();
+            payloads.ensure_capacity(length);
+            for (i, value) in values.into_iter().enumerate() {
+                unsafe { payloads.data.add(i).cast::<ValRaw>().write(value) };
+            }
+            payloads.length = length;
+        }
+        Ok(())
+    }
+
+    /// Resumes `contref` from the host with `args`, following the protocol of
+    /// the code generated for `resume` without installing any handlers on the
+    /// main stack. Instead, suspensions to the tags of the store's
+    /// `HostResumption` are returned to here by `suspend_to_host`.
+    ///
+    /// Returns the continuation which was running when control returned to
+    /// the host, along with the effect it returned with. If the effect is
+    /// `Return`, the continuation's results are in its `args`.
+    ///
+    /// # Safety
+    ///
+    /// This must be called from within `catch_traps` while running on the
+    /// main stack, and `contref` must be a fresh or suspended continuation of
+    /// `store` which expects `args`.
+    pub unsafe fn resume_from_host(
+        store: &mut StoreOpaque,
+        contref: *mut VMContRef,
+        args: &[ValRaw],
+    ) -> (*mut VMContRef, ControlEffect) {
+        unsafe {
+            let csi = &mut (*contref).common_stack_information;
+            let buffer = if csi.state == State::Fresh {
+                &mut (*contref).args
+            } else {
+                (*contref)
+                    .values
+                    .ensure_capacity(u32::try_from(args.len()).unwrap());
+                &mut (*contref).values
+            };
+            debug_assert!(buffer.length as usize + args.len() <= buffer.capacity as usize);
+            for arg in args {
+                buffer
+                    .data
+                    .add(buffer.length as usize)
+                    .cast::<ValRaw>()
+                    .write(*arg);
+                buffer.length += 1;
+            }
+
+            let chain = (*store.stack_chain()).0.get();
+            let main_stack = store.main_stack_information();
+            let limits = store.vmruntime_limits();
+
+            let last_ancestor = (*contref).last_ancestor;
+            (*last_ancestor).parent_chain = (*chain).clone();
+            (*contref).last_ancestor = core::ptr::null_mut();
+            csi.state = State::Running;
+            *chain = StackChain::Continuation(contref);
+            (*main_stack).state = State::Parent;
+
+            // The frame switching to the continuation is the boundary of the
+            // main stack's frames that backtraces walk, so its frame pointer
+            // becomes the main stack's entry frame pointer.
+            let stack_limit = *(*limits).stack_limit.get();
+            let last_wasm_entry_fp = *(*limits).last_wasm_entry_fp.get();
+            (*main_stack).limits.stack_limit = stack_limit;
+            *(*limits).stack_limit.get() = csi.limits.stack_limit;
+            *(*limits).last_wasm_entry_fp.get() = csi.limits.last_wasm_entry_fp;
+
+            let effect = (*last_ancestor).stack.switch(
+                ControlEffect::Resume.into(),
+                &mut (*main_stack).limits.last_wasm_entry_fp,
+            );
+            let effect = ControlEffect::from(effect);
+
+            let StackChain::Continuation(active) =
+                core::mem::replace(&mut *chain, StackChain::MainStack(main_stack))
+            else {
+                unreachable!("only a continuation can switch back to the host")
+            };
+            (*main_stack).state = State::Running;
+            match effect {
+                ControlEffect::Suspend { .. } => {
+                    (*active).common_stack_information.limits.last_wasm_entry_fp =
+                        *(*limits).last_wasm_entry_fp.get();
+                }
+                ControlEffect::Return => {
+                    (*active).common_stack_information.state = State::Returned;
+                }
+                ControlEffect::Resume | ControlEffect::Switch => {
+                    unreachable!("unexpected effect {effect:?} returned to the host")
+                }
+            }
+            *(*limits).stack_limit.get() = stack_limit;
+            *(*limits).last_wasm_entry_fp.get() = last_wasm_entry_fp;
+
+            (active, effect)
+        }
+    }
+
+    /// Suspends the running continuation to the host which resumed it with
+    /// `resume_from_host`, following the protocol of the code generated for
+    /// `suspend` when finding a handler with index `handler_index` on the
+    /// main stack.
+    ///
+    /// Returns the values that the continuation is resumed with.
+    ///
+    /// # Safety
+    ///
+    /// This must only be called from a libcall made by a continuation which
+    /// was resumed by the host.
+    unsafe fn suspend_to_resuming_host(store: &mut StoreOpaque, handler_index: u32) -> Vec<ValRaw> {
+        unsafe {
+            let chain = (*store.stack_chain()).0.get();
+            let StackChain::Continuation(active) = *chain else {
+                unreachable!("the host only handles suspensions of continuations")
+            };
+            let mut end_of_chain = active;
+            while let StackChain::Continuation(parent) = (*end_of_chain).parent_chain {
+                end_of_chain = parent;
+            }
+            debug_assert!((*end_of_chain).parent_chain.is_main_stack());
+
+            (*active).last_ancestor = end_of_chain;
+            (*active).common_stack_information.state = State::Suspended;
+            (*end_of_chain).parent_chain = StackChain::Absent;
+
+            // Backtraces never walk the frames of a suspended continuation, so
+            // the entry frame pointer isn't needed.
+            let mut entry_fp = 0;
+            (*end_of_chain).stack.switch(
+                ControlEffect::Suspend { handler_index }.into(),
+                &mut entry_fp,
+            );
+
+            let values = &mut (*active).values;
+            let resumed = (0..values.length as usize)
+                .map(|i| values.data.add(i).cast::<ValRaw>().read())
+                .collect();
+            values.clear();
+            resumed
+        }
+    }
+
     // Tests
     #[test]
     fn offset_and_size_constants() {
@@ -895,6 +1130,29 @@ pub mod optimized {
 
     pub type VMContRef = super::baseline::VMContRef;
 
+    pub use wasmtime_continuations::ControlEffect;
+
+    pub enum ContSnapshot {
+        Consumed,
+        Fresh(*mut crate::runtime::vm::VMFuncRef),
+        Bound,
+        Started,
+    }
+
+    #[inline(always)]
+    pub unsafe fn snapshot_cont(_contobj: super::VMContObj) -> ContSnapshot {
+        panic!("attempt to execute continuation::optimized::snapshot_cont with `typed_continuation_baseline_implementation` toggled!")
+    }
+
+    #[inline(always)]
+    pub unsafe fn resume_from_host(
+        _store: &mut crate::store::StoreOpaque,
+        _contref: *mut VMContRef,
+        _args: &[crate::runtime::vm::ValRaw],
+    ) -> (*mut VMContRef, ControlEffect) {
+        panic!("attempt to execute continuation::optimized::resume_from_host with `typed_continuation_baseline_implementation` toggled!")
+    }
+
     #[inline(always)]
     pub fn drop_cont_ref(_instance: &mut Instance, _contref: *mut VMContRef) {
         panic!("attempt to execute continuation::optimized::drop_cont_ref with `typed_continuation_baseline_implementation` toggled!")
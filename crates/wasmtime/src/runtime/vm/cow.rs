@@ -158,7 +158,7 @@ impl ModuleMemoryImages {
         mmap: Option<&MmapVec>,
     ) -> Result<Option<ModuleMemoryImages>> {
         let map = match &module.memory_initialization {
-            MemoryInitialization::Static { map } => map,
+            MemoryInitialization::Static { map, .. } => map,
             _ => return Ok(None),
         };
         let mut memories = PrimaryMap::with_capacity(map.len());
@@ -318,6 +318,13 @@ pub struct MemoryImageSlot {
     /// `self.accessible` and `self.static_size` is inaccessible.
     dirty: bool,
 
+    /// Whether data other than `image` has been mapped into this slot with
+    /// `map_data()` since it was last cleared.
+    ///
+    /// Such mappings can't be reset in place, so the next
+    /// `clear_and_remain_ready()` replaces all of memory instead.
+    overlaid: bool,
+
     /// Whether this MemoryImageSlot is responsible for mapping anonymous
     /// memory (to hold the reservation while overwriting mappings
     /// specific to this slot) in place when it is dropped. Default
@@ -345,6 +352,7 @@ impl MemoryImageSlot {
             accessible,
             image: None,
             dirty: false,
+            overlaid: false,
             clear_on_drop: true,
         }
     }
@@ -475,6 +483,32 @@ impl MemoryImageSlot {
         Ok(())
     }
 
+    /// Maps `data` copy-on-write into linear memory at `offset`, so that its
+    /// pages are only populated once they're first accessed.
+    ///
+    /// Both `offset` and the length of `data` must be multiples of the host
+    /// page size. Returns `false`, without changing anything, if the platform
+    /// can't map `data` this way.
+    pub(crate) fn map_data(&mut self, offset: usize, data: &[u8]) -> Result<bool> {
+        assert!(self.dirty);
+        let offset = HostAlignedByteCount::new(offset).expect("offset is page-aligned");
+        let len = HostAlignedByteCount::new(data.len()).expect("data is page-aligned");
+        assert!(offset.checked_add(len).unwrap() <= self.accessible);
+        if len.is_zero() {
+            return Ok(true);
+        }
+
+        let page_size = u32::try_from(host_page_size()).unwrap();
+        let Some(image) = MemoryImage::new(page_size, offset, data, None)? else {
+            return Ok(false);
+        };
+        unsafe {
+            image.map_at(&self.base)?;
+        }
+        self.overlaid = true;
+        Ok(true)
+    }
+
     pub(crate) fn remove_image(&mut self) -> Result<()> {
         if let Some(image) = &self.image {
             unsafe {
@@ -500,8 +534,13 @@ impl MemoryImageSlot {
     ) -> Result<()> {
         assert!(self.dirty);
 
-        unsafe {
-            self.reset_all_memory_contents(keep_resident, decommit)?;
+        if self.overlaid {
+            self.reset_with_anon_memory()?;
+            self.overlaid = false;
+        } else {
+            unsafe {
+                self.reset_all_memory_contents(keep_resident, decommit)?;
+            }
         }
 
         self.dirty = false;
@@ -43,4 +43,8 @@ impl MemoryImageSlot {
     pub(crate) fn has_image(&self) -> bool {
         match *self {}
     }
+
+    pub(crate) fn map_data(&mut self, _offset: usize, _data: &[u8]) -> Result<bool> {
+        match *self {}
+    }
 }
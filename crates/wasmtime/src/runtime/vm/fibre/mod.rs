@@ -31,6 +31,20 @@ cfg_if::cfg_if! {
                 Ok(Self(imp::FiberStack::new(size)?))
             }
 
+            /// Creates a new fiber stack of the given size, of which only the
+            /// topmost `initial` bytes are initially accessible. See
+            /// [`FiberStack::grow_to`].
+            pub fn new_growable(size: usize, initial: usize) -> io::Result<Self> {
+                Ok(Self(imp::FiberStack::new_growable(size, initial)?))
+            }
+
+            /// Grows a stack created by `new_growable` such that `addr` becomes
+            /// accessible, returning whether `addr` was in the growable part
+            /// of this stack.
+            pub fn grow_to(&mut self, addr: usize) -> bool {
+                self.0.grow_to(addr)
+            }
+
             /// Returns a stack of size 0.
             pub fn unallocated() -> Self {
                 Self(imp::FiberStack::unallocated())
@@ -92,6 +106,13 @@ cfg_if::cfg_if! {
                 self.0.control_context_frame_pointer()
             }
 
+            /// Switches from native code to the stack described by this
+            /// stack's control context, returning the payload of the switch
+            /// back. See [`imp::FiberStack::switch`].
+            pub unsafe fn switch(&self, payload: u64, entry_fp: *mut usize) -> u64 {
+                unsafe { self.0.switch(payload, entry_fp) }
+            }
+
             pub fn initialize(
                 &self,
                 func_ref: *const VMFuncRef,
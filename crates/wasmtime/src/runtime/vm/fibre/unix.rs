@@ -60,6 +60,9 @@ use crate::runtime::vm::{VMContext, VMFuncRef, VMOpaqueContext, ValRaw};
 pub enum Allocator {
     Malloc,
     Mmap,
+    /// Like `Mmap`, but only the top `committed` bytes of the stack are
+    /// accessible, the rest is reserved and made accessible on demand.
+    Growable,
     Custom,
 }
 
@@ -73,6 +76,9 @@ pub struct FiberStack {
     len: usize,
     // allocation strategy
     allocator: Allocator,
+    // For growable stacks, the number of bytes below `top` that are currently
+    // accessible. Unused by all other allocation strategies.
+    committed: usize,
 }
 
 impl FiberStack {
@@ -106,10 +112,83 @@ impl FiberStack {
                 top: mmap.cast::<u8>().add(mmap_len),
                 len: mmap_len,
                 allocator: Allocator::Mmap,
+                committed: 0,
             })
         }
     }
 
+    /// Creates a stack which reserves `size` bytes (plus a guard page) of
+    /// address space, but only makes the topmost `initial` bytes accessible.
+    /// The remaining space is made accessible by `grow_to`.
+    pub fn new_growable(size: usize, initial: usize) -> io::Result<Self> {
+        let page_size = rustix::param::page_size();
+        let round_up = |n: usize| (n + (page_size - 1)) & (!(page_size - 1));
+        let size = round_up(size).max(page_size);
+        let initial = round_up(initial).clamp(page_size, size);
+
+        unsafe {
+            // Add in one page for a guard page, which is never made accessible.
+            let mmap_len = size + page_size;
+            let mmap = rustix::mm::mmap_anonymous(
+                ptr::null_mut(),
+                mmap_len,
+                rustix::mm::ProtFlags::empty(),
+                rustix::mm::MapFlags::PRIVATE,
+            )?;
+            let top = mmap.cast::<u8>().add(mmap_len);
+
+            rustix::mm::mprotect(
+                top.sub(initial).cast(),
+                initial,
+                rustix::mm::MprotectFlags::READ | rustix::mm::MprotectFlags::WRITE,
+            )?;
+
+            Ok(Self {
+                top,
+                len: mmap_len,
+                allocator: Allocator::Growable,
+                committed: initial,
+            })
+        }
+    }
+
+    /// If `addr` lies in the reserved but not yet accessible part of a
+    /// growable stack, makes the stack accessible down to at least `addr`
+    /// and returns `true`. Otherwise, returns `false`.
+    ///
+    /// The accessible part of the stack at least doubles on each growth, so
+    /// that deep recursion only faults a logarithmic number of times.
+    ///
+    /// This function is called from the signal handler, and must therefore
+    /// stay async-signal-safe.
+    pub fn grow_to(&mut self, addr: usize) -> bool {
+        if self.allocator != Allocator::Growable {
+            return false;
+        }
+        let page_size = rustix::param::page_size();
+        let top = self.top as usize;
+        let usable = self.len - page_size;
+        let accessible_bottom = top - self.committed;
+        if addr < top - usable || addr >= accessible_bottom {
+            return false;
+        }
+
+        let needed = top - (addr & !(page_size - 1));
+        let committed = needed.max(self.committed.saturating_mul(2)).min(usable);
+        let ret = unsafe {
+            rustix::mm::mprotect(
+                (top - committed) as *mut _,
+                committed - self.committed,
+                rustix::mm::MprotectFlags::READ | rustix::mm::MprotectFlags::WRITE,
+            )
+        };
+        if ret.is_err() {
+            return false;
+        }
+        self.committed = committed;
+        true
+    }
+
     pub fn malloc(size: usize) -> io::Result<Self> {
         unsafe {
             let layout = Layout::array::<u8>(size).unwrap();
@@ -118,6 +197,7 @@ impl FiberStack {
                 top: base.add(size),
                 len: size,
                 allocator: Allocator::Malloc,
+                committed: 0,
             })
         }
     }
@@ -127,6 +207,7 @@ impl FiberStack {
             top: std::ptr::null_mut(),
             len: 0,
             allocator: Allocator::Custom,
+            committed: 0,
         }
     }
 
@@ -141,6 +222,7 @@ impl FiberStack {
             top: base.add(len),
             len,
             allocator: Allocator::Custom,
+            committed: 0,
         })
     }
 
@@ -176,6 +258,21 @@ impl FiberStack {
         }
     }
 
+    /// Switches from native code to the stack described by this stack's
+    /// control context, like a `stack_switch` instruction with `payload`
+    /// would, and returns the payload of the switch back to the caller.
+    ///
+    /// The frame pointer of the switching frame is written to `entry_fp`
+    /// first.
+    ///
+    /// # Safety
+    ///
+    /// The control context must describe a valid stack to switch to, which
+    /// will eventually switch back to this stack's control context.
+    pub unsafe fn switch(&self, payload: u64, entry_fp: *mut usize) -> u64 {
+        unsafe { wasmtime_fibre_switch(self.top, payload, entry_fp) }
+    }
+
     /// This function installs the launchpad for the computation to run on the
     /// fiber, such that executing a `stack_switch` instruction on the stack
     /// actually runs the desired computation.
@@ -247,7 +344,7 @@ impl Drop for FiberStack {
     fn drop(&mut self) {
         unsafe {
             match self.allocator {
-                Allocator::Mmap => {
+                Allocator::Mmap | Allocator::Growable => {
                     let ret = rustix::mm::munmap(self.top.sub(self.len) as _, self.len);
                     debug_assert!(ret.is_ok());
                 }
@@ -263,6 +360,7 @@ impl Drop for FiberStack {
 
 unsafe extern "C" {
     fn wasmtime_fibre_switch_to_parent(top_of_stack: *mut u8);
+    fn wasmtime_fibre_switch(top_of_stack: *mut u8, payload: u64, entry_fp: *mut usize) -> u64;
     #[allow(dead_code)] // only used in inline assembly for some platforms
     fn wasmtime_fibre_start();
 }
@@ -35,6 +35,72 @@ asm_func!(
 );
 
 
+// fn(
+//    top_of_stack(rdi): *mut u8,
+//    payload(rsi): u64,
+//    entry_fp(rdx): *mut usize,
+// ) -> u64
+//
+// Switches from native code to the stack described by the control context
+// below `top_of_stack`, exchanging it for the current stack in the same way as
+// the stack_switch instruction does. This is used by the host to resume a
+// continuation, and to suspend a continuation to the host from within a
+// libcall.
+//
+// The frame pointer of this function is written to `entry_fp` before
+// switching, so that it can serve as the boundary of the frames to walk on
+// this stack.
+//
+// Once a stack_switch instruction (or `wasmtime_fibre_switch_to_parent`)
+// switches back, this function returns the payload it received.
+asm_func!(
+    "wasmtime_fibre_switch",
+    "
+        // stack_switch clobbers all registers, so save the callee-saved
+        // ones. The frame set up here is what the control context refers
+        // to while we are switched away.
+        push rbp
+        mov rbp, rsp
+        push rbx
+        push r12
+        push r13
+        push r14
+        push r15
+        // Keep the stack 16-byte aligned.
+        sub rsp, 8
+
+        mov [rdx], rbp
+
+        // Load the control context of the stack to switch to, and store
+        // our own in its place, resuming at label 2 below.
+        mov r8, -0x18[rdi]
+        mov r9, -0x10[rdi]
+        mov r10, -0x08[rdi]
+        lea rax, 2f[rip]
+        mov -0x18[rdi], rsp
+        mov -0x10[rdi], rbp
+        mov -0x08[rdi], rax
+
+        // The stack_switch instruction uses register RDI for the payload.
+        mov rdi, rsi
+        mov rsp, r8
+        mov rbp, r9
+        jmp r10
+
+    2:
+        // We were switched back to, with the payload in RDI.
+        mov rax, rdi
+        add rsp, 8
+        pop r15
+        pop r14
+        pop r13
+        pop r12
+        pop rbx
+        pop rbp
+        ret
+    ",
+);
+
 // This is a pretty special function that has no real signature. Its use is to
 // be the "base" function of all fibers. This entrypoint is used in
 // `wasmtime_fibre_init` to bootstrap the execution of a new fiber.
@@ -61,8 +127,39 @@ asm_func!(
 asm_func!(
     "wasmtime_fibre_start",
     "
-        // TODO(frank-emrich): Restore DWARF information for this function. In
-        // the meantime, debugging is possible using frame pointer walking.
+        // Use the `simple` directive on the startproc here which indicates that
+        // some default settings for the platform are omitted, since this
+        // function is so nonstandard.
+        .cfi_startproc simple
+        .cfi_def_cfa_offset 0
+
+        // Describe the parent of this continuation as the caller of this
+        // function, mirroring what frame pointer walking sees. RBP points at
+        // the RBP field of the control context throughout this function (and
+        // `fiber_start` restores it before unwinding back here), so all rules
+        // are expressed relative to RBP rather than RSP, which changes below.
+        //
+        // The CFA, the parent's RSP, is stored in the RSP field of the control
+        // context, which is 8 bytes below RBP:
+        //
+        //        *($rbp - 8)
+        .cfi_escape 0x0f, /* DW_CFA_def_cfa_expression */ \
+            3,            /* the byte length of this expression */ \
+            0x76, 0x78,   /* DW_OP_breg6 (rbp) -8 */ \
+            0x06          /* DW_OP_deref */
+
+        // The parent's RIP and RBP are stored in the PC and RBP fields of the
+        // control context, at `$rbp + 8` and `$rbp` respectively. These can't
+        // be described relative to the CFA as they're on this stack rather
+        // than the parent's.
+        .cfi_escape 0x10, /* DW_CFA_expression */ \
+            0x10,         /* rip */ \
+            2,            /* the byte length of this expression */ \
+            0x76, 0x08    /* DW_OP_breg6 (rbp) 8 */
+        .cfi_escape 0x10, /* DW_CFA_expression */ \
+            0x06,         /* rbp */ \
+            2,            /* the byte length of this expression */ \
+            0x76, 0x00    /* DW_OP_breg6 (rbp) 0 */
 
 
         //
@@ -86,6 +183,7 @@ asm_func!(
 
         // We should never get here and purposely emit an invalid instruction.
         ud2
+        .cfi_endproc
     ",
     fiber_start = sym super::fiber_start,
 );
@@ -13,12 +13,14 @@ mod gc_ref;
 mod gc_runtime;
 mod host_data;
 mod i31;
+mod weak;
 
 pub use func_ref::*;
 pub use gc_ref::*;
 pub use gc_runtime::*;
 pub use host_data::*;
 pub use i31::*;
+pub use weak::*;
 
 use crate::prelude::*;
 use crate::runtime::vm::GcHeapAllocationIndex;
@@ -75,6 +77,11 @@ impl GcStore {
         collect_async(collection).await;
     }
 
+    /// Get the number of bytes currently allocated in this heap.
+    pub fn allocated_bytes(&self) -> usize {
+        self.gc_heap.allocated_bytes()
+    }
+
     /// Get the kind of the given GC reference.
     pub fn kind(&self, gc_ref: &VMGcRef) -> VMGcKind {
         debug_assert!(!gc_ref.is_i31());
@@ -87,6 +94,20 @@ impl GcStore {
         self.gc_heap.header(gc_ref)
     }
 
+    /// Pin the given GC reference, so that the object it points to isn't
+    /// moved until it's unpinned.
+    pub fn pin(&mut self, gc_ref: &VMGcRef) {
+        debug_assert!(!gc_ref.is_i31());
+        self.gc_heap.pin(gc_ref);
+    }
+
+    /// Unpin the given GC reference, which was previously pinned with
+    /// `GcStore::pin`.
+    pub fn unpin(&mut self, gc_ref: &VMGcRef) {
+        debug_assert!(!gc_ref.is_i31());
+        self.gc_heap.unpin(gc_ref);
+    }
+
     /// Clone a GC reference, calling GC write barriers as necessary.
     pub fn clone_gc_ref(&mut self, gc_ref: &VMGcRef) -> VMGcRef {
         if gc_ref.is_i31() {
@@ -164,6 +185,37 @@ impl GcStore {
         }
     }
 
+    /// Register a finalizer to be given the given `externref`'s host data once
+    /// the `externref` is reclaimed.
+    ///
+    /// Passing invalid `VMExternRef`s (eg garbage values or `externref`s
+    /// associated with a different heap is memory safe but will lead to general
+    /// incorrectness such as panics and wrong results.
+    pub fn set_externref_finalizer(
+        &mut self,
+        externref: &VMExternRef,
+        finalizer: ExternRefFinalizer,
+    ) {
+        let host_data_id = self.gc_heap.externref_host_data(externref);
+        self.host_data_table.set_finalizer(host_data_id, finalizer);
+    }
+
+    /// Create a weak reference to the given GC object.
+    pub fn alloc_weak_gc_ref(&mut self, gc_ref: &VMGcRef) -> WeakGcRefId {
+        self.gc_heap.weak_refs_mut().alloc(gc_ref)
+    }
+
+    /// Deallocate the given weak reference.
+    pub fn dealloc_weak_gc_ref(&mut self, id: WeakGcRefId) {
+        self.gc_heap.weak_refs_mut().dealloc(id);
+    }
+
+    /// Get the GC object that the given weak reference refers to, or `None` if
+    /// that object has been reclaimed.
+    pub fn weak_gc_ref_target(&self, id: WeakGcRefId) -> Option<&VMGcRef> {
+        self.gc_heap.weak_refs().get(id)
+    }
+
     /// Get a shared borrow of the given `externref`'s host data.
     ///
     /// Passing invalid `VMExternRef`s (eg garbage values or `externref`s
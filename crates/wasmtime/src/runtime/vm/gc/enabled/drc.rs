@@ -48,7 +48,7 @@ use crate::prelude::*;
 use crate::runtime::vm::{
     mmap::AlignedLength, ExternRefHostDataId, ExternRefHostDataTable, GarbageCollection, GcHeap,
     GcHeapObject, GcProgress, GcRootsIter, GcRuntime, Mmap, TypedGcRef, VMExternRef, VMGcHeader,
-    VMGcRef,
+    VMGcRef, WeakGcRefTable,
 };
 use core::ops::{Deref, DerefMut, Range};
 use core::{
@@ -93,6 +93,7 @@ struct DrcHeap {
     activations_table: Box<VMGcRefActivationsTable>,
     heap: Mmap<AlignedLength>,
     free_list: FreeList,
+    weak_refs: WeakGcRefTable,
 }
 
 impl DrcHeap {
@@ -110,10 +111,12 @@ impl DrcHeap {
             activations_table: Box::new(VMGcRefActivationsTable::default()),
             heap,
             free_list,
+            weak_refs: WeakGcRefTable::default(),
         })
     }
 
     fn dealloc(&mut self, gc_ref: VMGcRef) {
+        self.weak_refs.clear(&gc_ref);
         let drc_ref = drc_ref(&gc_ref);
         let size = self.index(drc_ref).object_size();
         let layout = FreeList::layout(size);
@@ -193,7 +196,7 @@ impl DrcHeap {
             // the host data table.
             if let Some(externref) = gc_ref.as_typed::<VMDrcExternRef>(self) {
                 let host_data_id = self.index(externref).host_data;
-                host_data_table.dealloc(host_data_id);
+                host_data_table.reclaim(host_data_id);
             }
 
             // TODO: `dec_ref_and_maybe_dealloc` each `VMGcRef` inside this
@@ -679,11 +682,33 @@ unsafe impl GcHeap for DrcHeap {
             activations_table,
             free_list,
             heap: _,
+            weak_refs,
         } = self;
 
         *no_gc_count = 0;
         free_list.reset();
         activations_table.reset();
+        *weak_refs = WeakGcRefTable::default();
+    }
+
+    fn pin(&mut self, _gc_ref: &VMGcRef) {
+        // The DRC collector never moves objects.
+    }
+
+    fn unpin(&mut self, _gc_ref: &VMGcRef) {
+        // The DRC collector never moves objects.
+    }
+
+    fn weak_refs(&self) -> &WeakGcRefTable {
+        &self.weak_refs
+    }
+
+    fn weak_refs_mut(&mut self) -> &mut WeakGcRefTable {
+        &mut self.weak_refs
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.free_list.allocated_bytes()
     }
 
     fn heap_slice(&self) -> &[UnsafeCell<u8>] {
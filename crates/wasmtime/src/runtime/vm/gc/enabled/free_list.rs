@@ -259,6 +259,26 @@ impl FreeList {
 
     /// Reset this free list, making the whole range available for allocation.
     pub fn reset(&mut self) {
+        let entire_range = self.entire_range();
+        self.free_block_index_to_len.clear();
+        self.free_block_index_to_len.extend(entire_range);
+    }
+
+    /// Get the number of bytes that are currently allocated out of this free
+    /// list.
+    pub fn allocated_bytes(&self) -> usize {
+        let total = self.entire_range().map_or(0, |(_index, len)| len);
+        let free: u64 = self
+            .free_block_index_to_len
+            .values()
+            .map(|len| u64::from(*len))
+            .sum();
+        usize::try_from(u64::from(total) - free).unwrap()
+    }
+
+    /// Get the index and length of the single block covering all of the
+    /// memory this free list manages, if it is large enough to allocate from.
+    fn entire_range(&self) -> Option<(u32, u32)> {
         let end = u32::try_from(self.capacity).unwrap_or_else(|_| {
             assert!(self.capacity > usize::try_from(u32::MAX).unwrap());
             u32::MAX
@@ -271,14 +291,11 @@ impl FreeList {
 
         let len = round_u32_down_to_pow2(end.saturating_sub(start), ALIGN_U32);
 
-        let entire_range = if len >= MIN_BLOCK_SIZE {
+        if len >= MIN_BLOCK_SIZE {
             Some((start, len))
         } else {
             None
-        };
-
-        self.free_block_index_to_len.clear();
-        self.free_block_index_to_len.extend(entire_range);
+        }
     }
 }
 
@@ -505,6 +522,25 @@ mod tests {
         assert_eq!(free_list.free_block_index_to_len.len(), 0);
     }
 
+    #[test]
+    fn allocated_bytes() {
+        let mut free_list = FreeList::new(ALIGN_USIZE + 1024);
+        assert_eq!(free_list.allocated_bytes(), 0);
+
+        let layout = Layout::from_size_align(20, ALIGN_USIZE).unwrap();
+        let a = free_list.alloc(layout).unwrap().unwrap();
+        assert_eq!(free_list.allocated_bytes(), 24);
+
+        let b = free_list.alloc(layout).unwrap().unwrap();
+        assert_eq!(free_list.allocated_bytes(), 48);
+
+        free_list.dealloc(a, layout);
+        assert_eq!(free_list.allocated_bytes(), 24);
+
+        free_list.dealloc(b, layout);
+        assert_eq!(free_list.allocated_bytes(), 0);
+    }
+
     #[test]
     fn allocate_and_split() {
         // Create a free list with the capacity to allocate three blocks of size
@@ -10,7 +10,7 @@ use crate::{
     vm::{
         mmap::AlignedLength, ExternRefHostDataId, ExternRefHostDataTable, GarbageCollection,
         GcHeap, GcHeapObject, GcProgress, GcRootsIter, Mmap, SendSyncUnsafeCell, TypedGcRef,
-        VMGcHeader, VMGcRef,
+        VMGcHeader, VMGcRef, WeakGcRefTable,
     },
     GcHeapOutOfMemory,
 };
@@ -56,6 +56,10 @@ struct NullHeap {
 
     /// The actual GC heap.
     heap: Mmap<AlignedLength>,
+
+    /// Weak references into this heap. The null collector never reclaims
+    /// objects, so these are never cleared.
+    weak_refs: WeakGcRefTable,
 }
 
 /// The common header for all arrays in the null collector.
@@ -127,6 +131,7 @@ impl NullHeap {
             no_gc_count: 0,
             next: SendSyncUnsafeCell::new(NonZeroU32::new(1).unwrap()),
             heap,
+            weak_refs: WeakGcRefTable::default(),
         })
     }
 
@@ -201,6 +206,29 @@ unsafe impl GcHeap for NullHeap {
         self.no_gc_count -= 1;
     }
 
+    fn pin(&mut self, _gc_ref: &VMGcRef) {
+        // The null collector never moves objects.
+    }
+
+    fn unpin(&mut self, _gc_ref: &VMGcRef) {
+        // The null collector never moves objects.
+    }
+
+    fn weak_refs(&self) -> &WeakGcRefTable {
+        &self.weak_refs
+    }
+
+    fn weak_refs_mut(&mut self) -> &mut WeakGcRefTable {
+        &mut self.weak_refs
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        // Safety: compiled Wasm code may bump this finger, but it is not
+        // running while we have a shared borrow of the heap.
+        let next = unsafe { *self.next.get() };
+        usize::try_from(next.get() - 1).unwrap()
+    }
+
     fn heap_slice(&self) -> &[UnsafeCell<u8>] {
         let ptr = self.heap.as_ptr().cast();
         let len = self.heap.len();
@@ -319,10 +347,12 @@ unsafe impl GcHeap for NullHeap {
             next,
             no_gc_count,
             heap: _,
+            weak_refs,
         } = self;
 
         *next.get_mut() = NonZeroU32::new(1).unwrap();
         *no_gc_count = 0;
+        *weak_refs = WeakGcRefTable::default();
     }
 }
 
@@ -3,7 +3,7 @@
 use crate::prelude::*;
 use crate::runtime::vm::{
     ExternRefHostDataId, ExternRefHostDataTable, GcHeapObject, SendSyncPtr, TypedGcRef, VMArrayRef,
-    VMExternRef, VMGcHeader, VMGcObjectDataMut, VMGcRef, VMStructRef,
+    VMExternRef, VMGcHeader, VMGcObjectDataMut, VMGcRef, VMStructRef, WeakGcRefTable,
 };
 use core::{
     alloc::Layout, any::Any, cell::UnsafeCell, marker, mem, num::NonZeroUsize, ops::Range, ptr,
@@ -342,6 +342,46 @@ pub unsafe trait GcHeap: 'static + Send + Sync {
         host_data_table: &'a mut ExternRefHostDataTable,
     ) -> Box<dyn GarbageCollection<'a> + 'a>;
 
+    ////////////////////////////////////////////////////////////////////////////
+    // Pinning Methods
+
+    /// Pin the given object, so that its address in the heap remains stable
+    /// until it's unpinned.
+    ///
+    /// Objects may be pinned multiple times, and are unpinned once `unpin` has
+    /// been called as many times as `pin`. Pinning doesn't keep an object
+    /// alive; callers must root pinned objects themselves.
+    ///
+    /// Collectors which move objects must not move pinned objects. Collectors
+    /// which never move objects need not do anything here.
+    fn pin(&mut self, gc_ref: &VMGcRef);
+
+    /// Unpin the given object, which was previously pinned with `pin`.
+    fn unpin(&mut self, gc_ref: &VMGcRef);
+
+    ////////////////////////////////////////////////////////////////////////////
+    // Weak Reference Methods
+
+    /// Get a shared borrow of this heap's weak references.
+    fn weak_refs(&self) -> &WeakGcRefTable;
+
+    /// Get an exclusive borrow of this heap's weak references.
+    ///
+    /// Collectors must call [`WeakGcRefTable::clear`] on this table whenever
+    /// they reclaim an object, so that weak references never observe a
+    /// reclaimed object or another object allocated in its place.
+    fn weak_refs_mut(&mut self) -> &mut WeakGcRefTable;
+
+    ////////////////////////////////////////////////////////////////////////////
+    // Statistics Methods
+
+    /// Get the number of bytes of this heap that are currently allocated.
+    ///
+    /// This includes objects that are unreachable but have not been reclaimed
+    /// yet, as well as any padding and bookkeeping overhead of the
+    /// allocations.
+    fn allocated_bytes(&self) -> usize;
+
     ////////////////////////////////////////////////////////////////////////////
     // JIT-Code Interaction Methods
 
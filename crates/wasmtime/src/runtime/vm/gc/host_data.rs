@@ -13,14 +13,30 @@
 //! less catastrophic than doing an indirect call to an attacker-controlled
 //! function pointer.
 
+use crate::hash_map::HashMap;
 use crate::prelude::*;
 use core::any::Any;
+use core::mem;
 use wasmtime_slab::{Id, Slab};
 
+/// A callback which is given an `externref`'s host data once that `externref`
+/// has been reclaimed.
+pub type ExternRefFinalizer = Box<dyn FnOnce(Box<dyn Any + Send + Sync>) + Send + Sync>;
+
 /// Side table for each `externref`'s host data value.
 #[derive(Default)]
 pub struct ExternRefHostDataTable {
     slab: Slab<Box<dyn Any + Send + Sync>>,
+
+    /// Finalizers for host data values which have one.
+    finalizers: HashMap<ExternRefHostDataId, ExternRefFinalizer>,
+
+    /// Reclaimed host data values whose finalizers have not been run yet.
+    ///
+    /// Host data is reclaimed in the middle of collections or write barriers,
+    /// where it isn't safe to run arbitrary host code, so finalizers are
+    /// deferred until the store explicitly runs them.
+    pending_finalizers: Vec<(ExternRefFinalizer, Box<dyn Any + Send + Sync>)>,
 }
 
 /// ID into the `externref` host data table.
@@ -51,6 +67,34 @@ impl ExternRefHostDataTable {
         self.slab.dealloc(id.0)
     }
 
+    /// Register a finalizer to be given the host data value associated with
+    /// the given ID once it is reclaimed.
+    pub fn set_finalizer(&mut self, id: ExternRefHostDataId, finalizer: ExternRefFinalizer) {
+        let old = self.finalizers.insert(id, finalizer);
+        debug_assert!(old.is_none());
+    }
+
+    /// Reclaim an `externref` host data value because its `externref` is
+    /// unreachable.
+    ///
+    /// The value is dropped immediately, unless it has a finalizer, in which
+    /// case it is queued until [`ExternRefHostDataTable::take_pending_finalizers`].
+    pub fn reclaim(&mut self, id: ExternRefHostDataId) {
+        let data = self.dealloc(id);
+        if let Some(finalizer) = self.finalizers.remove(&id) {
+            log::trace!("queued finalizer for externref host data: {id:?}");
+            self.pending_finalizers.push((finalizer, data));
+        }
+    }
+
+    /// Take the finalizers of reclaimed host data values, along with the
+    /// values to pass them, so that the caller can run them.
+    pub fn take_pending_finalizers(
+        &mut self,
+    ) -> Vec<(ExternRefFinalizer, Box<dyn Any + Send + Sync>)> {
+        mem::take(&mut self.pending_finalizers)
+    }
+
     /// Get a shared borrow of the host data associated with the given ID.
     pub fn get(&self, id: ExternRefHostDataId) -> &(dyn Any + Send + Sync) {
         let data: &Box<dyn Any + Send + Sync> = self.slab.get(id.0).unwrap();
@@ -64,6 +108,20 @@ impl ExternRefHostDataTable {
     }
 }
 
+impl Drop for ExternRefHostDataTable {
+    fn drop(&mut self) {
+        // Everything is unreachable once the heap is torn down, so give all
+        // finalizers their host data values now, rather than silently dropping
+        // them.
+        for (finalizer, data) in self.take_pending_finalizers() {
+            finalizer(data);
+        }
+        for (id, finalizer) in mem::take(&mut self.finalizers) {
+            finalizer(self.slab.dealloc(id.0));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -79,4 +137,42 @@ mod tests {
         assert!(table.get_mut(id).is::<u32>());
         assert_eq!(*table.get_mut(id).downcast_ref::<u32>().unwrap(), 42);
     }
+
+    #[test]
+    fn finalizers_are_deferred() {
+        use alloc::sync::Arc;
+        use core::sync::atomic::{AtomicU32, Ordering::SeqCst};
+
+        let finalized = Arc::new(AtomicU32::new(0));
+        let mut table = ExternRefHostDataTable::default();
+
+        let id = table.alloc(Box::new(42_u32));
+        table.set_finalizer(id, {
+            let finalized = finalized.clone();
+            Box::new(move |data| {
+                let data = *data.downcast::<u32>().unwrap();
+                finalized.fetch_add(data, SeqCst);
+            })
+        });
+
+        table.reclaim(id);
+        assert_eq!(finalized.load(SeqCst), 0);
+        for (finalizer, data) in table.take_pending_finalizers() {
+            finalizer(data);
+        }
+        assert_eq!(finalized.load(SeqCst), 42);
+
+        // Finalizers of values that are still alive run when the table is
+        // dropped.
+        let id = table.alloc(Box::new(1_u32));
+        table.set_finalizer(id, {
+            let finalized = finalized.clone();
+            Box::new(move |data| {
+                let data = *data.downcast::<u32>().unwrap();
+                finalized.fetch_add(data, SeqCst);
+            })
+        });
+        drop(table);
+        assert_eq!(finalized.load(SeqCst), 43);
+    }
 }
@@ -0,0 +1,120 @@
+//! Weak references to GC objects.
+//!
+//! A weak reference refers to a GC object without keeping it alive. Each GC
+//! heap owns a `WeakGcRefTable` and clears the table's entries for an object
+//! when it reclaims that object, so that a weak reference never observes a
+//! reclaimed object, nor a new object that was allocated in its place.
+
+use crate::hash_map::HashMap;
+use crate::prelude::*;
+use crate::runtime::vm::VMGcRef;
+use wasmtime_slab::{Id, Slab};
+
+/// A table of weak references into a single GC heap.
+#[derive(Default)]
+pub struct WeakGcRefTable {
+    /// Each weak reference's referent, or `None` if it has been reclaimed.
+    slab: Slab<Option<VMGcRef>>,
+
+    /// The weak references pointing to each object, keyed by the object's raw
+    /// GC reference.
+    ///
+    /// `i31ref`s are never reclaimed, so weak references to them are not
+    /// tracked here.
+    by_referent: HashMap<u32, Vec<WeakGcRefId>>,
+}
+
+/// ID into a `WeakGcRefTable`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct WeakGcRefId(Id);
+
+impl WeakGcRefTable {
+    /// Allocate a new weak reference to the given object.
+    pub fn alloc(&mut self, gc_ref: &VMGcRef) -> WeakGcRefId {
+        let id = WeakGcRefId(self.slab.alloc(Some(gc_ref.unchecked_copy())));
+        if !gc_ref.is_i31() {
+            self.by_referent
+                .entry(gc_ref.as_raw_u32())
+                .or_default()
+                .push(id);
+        }
+        log::trace!("allocated weak ref {id:?} to {gc_ref:#p}");
+        id
+    }
+
+    /// Deallocate a weak reference.
+    pub fn dealloc(&mut self, id: WeakGcRefId) {
+        log::trace!("deallocated weak ref {id:?}");
+        let Some(gc_ref) = self.slab.dealloc(id.0) else {
+            return;
+        };
+        if gc_ref.is_i31() {
+            return;
+        }
+        let raw = gc_ref.as_raw_u32();
+        let ids = self.by_referent.get_mut(&raw).unwrap();
+        ids.retain(|x| *x != id);
+        if ids.is_empty() {
+            self.by_referent.remove(&raw);
+        }
+    }
+
+    /// Get the given weak reference's referent, or `None` if it has been
+    /// reclaimed.
+    pub fn get(&self, id: WeakGcRefId) -> Option<&VMGcRef> {
+        self.slab
+            .get(id.0)
+            .expect("id from different weak ref table")
+            .as_ref()
+    }
+
+    /// Clear all weak references to the given object, because it is being
+    /// reclaimed.
+    ///
+    /// Collectors must call this whenever they reclaim an object.
+    #[inline]
+    pub fn clear(&mut self, gc_ref: &VMGcRef) {
+        if self.by_referent.is_empty() {
+            return;
+        }
+        if let Some(ids) = self.by_referent.remove(&gc_ref.as_raw_u32()) {
+            for id in ids {
+                log::trace!("cleared weak ref {id:?} to {gc_ref:#p}");
+                self.slab[id.0] = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clear_weak_refs() {
+        let mut table = WeakGcRefTable::default();
+        let a = VMGcRef::from_raw_u32(8).unwrap();
+        let b = VMGcRef::from_raw_u32(16).unwrap();
+
+        let a1 = table.alloc(&a);
+        let a2 = table.alloc(&a);
+        let b1 = table.alloc(&b);
+        assert_eq!(table.get(a1), Some(&a));
+        assert_eq!(table.get(a2), Some(&a));
+        assert_eq!(table.get(b1), Some(&b));
+
+        table.dealloc(a2);
+        table.clear(&a);
+        assert_eq!(table.get(a1), None);
+        assert_eq!(table.get(b1), Some(&b));
+
+        // Clearing an object without weak references is a no-op.
+        table.clear(&a);
+        assert_eq!(table.get(b1), Some(&b));
+
+        table.dealloc(a1);
+        table.dealloc(b1);
+        assert!(table.by_referent.is_empty());
+    }
+}
@@ -28,10 +28,11 @@ use core::{mem, ptr};
 use sptr::Strict;
 use wasmtime_environ::{
     packed_option::ReservedValue, DataIndex, DefinedGlobalIndex, DefinedMemoryIndex,
-    DefinedTableIndex, DefinedTagIndex, ElemIndex, EntityIndex, EntityRef, EntitySet, FuncIndex,
-    GlobalIndex, HostPtr, MemoryIndex, Module, ModuleInternedTypeIndex, PrimaryMap, PtrSize,
-    TableIndex, TableInitialValue, TableSegmentElements, TagIndex, Trap, VMOffsets,
-    VMSharedTypeIndex, WasmHeapTopType, VMCONTEXT_MAGIC,
+    DefinedTableIndex, DefinedTagIndex, ElemIndex, EngineOrModuleTypeIndex, EntityIndex, EntityRef,
+    EntitySet, FuncIndex, GlobalIndex, HostPtr, MemoryIndex, MemoryInitializer, Module,
+    ModuleInternedTypeIndex, PrimaryMap, PtrSize, TableIndex, TableInitialValue,
+    TableSegmentElements, TagIndex, Trap, VMOffsets, VMSharedTypeIndex, WasmHeapTopType,
+    VMCONTEXT_MAGIC,
 };
 #[cfg(feature = "wmemcheck")]
 use wasmtime_wmemcheck::Wmemcheck;
@@ -427,6 +428,30 @@ impl Instance {
         unsafe { &*self.vmctx_plus_offset(self.offsets().vmctx_vmfunction_import(index)) }
     }
 
+    /// Replace the indexed `VMFunctionImport`, which is used to patch lazily
+    /// resolved imports.
+    ///
+    /// Note that any `VMFuncRef`s already created for this import continue to
+    /// refer to the previous function.
+    ///
+    /// # Safety
+    ///
+    /// `import` must be a valid function of the import's type which lives as
+    /// long as this instance's store.
+    pub(crate) unsafe fn set_imported_function(
+        &mut self,
+        index: FuncIndex,
+        import: VMFunctionImport,
+    ) {
+        let offset = self.offsets().vmctx_vmfunction_import(index);
+        *self.vmctx_plus_offset_mut::<VMFunctionImport>(offset) = import;
+    }
+
+    /// Returns the `vmctx` of the function currently imported at `index`.
+    pub(crate) fn imported_function_vmctx(&self, index: FuncIndex) -> *mut VMOpaqueContext {
+        self.imported_function(index).vmctx
+    }
+
     /// Return the index `VMTableImport`.
     fn imported_table(&self, index: TableIndex) -> &VMTableImport {
         unsafe { &*self.vmctx_plus_offset(self.offsets().vmctx_vmtable_import(index)) }
@@ -589,6 +614,12 @@ impl Instance {
         }
     }
 
+    /// Return a pointer to the buffer used to pass tag payloads.
+    #[inline]
+    pub fn typed_continuations_payloads(&mut self) -> *mut wasmtime_continuations::Payloads {
+        unsafe { self.vmctx_plus_offset_mut(self.offsets().vmctx_typed_continuations_payloads()) }
+    }
+
     /// Return a pointer to the global epoch counter used by this instance.
     pub fn epoch_ptr(&mut self) -> *mut *const AtomicU64 {
         unsafe { self.vmctx_plus_offset_mut(self.offsets().ptr.vmctx_epoch_ptr()) }
@@ -1467,12 +1498,13 @@ impl Instance {
             let tag_index = module.tag_index(defined_index);
             let tag = module.tags[tag_index];
             let to = self.tag_ptr(defined_index);
-            ptr::write(
-                to,
-                VMTagDefinition::new(
-                    self.engine_type_index(tag.signature.unwrap_module_type_index()),
-                ),
-            );
+            let signature = match tag.signature {
+                EngineOrModuleTypeIndex::Module(index) => self.engine_type_index(index),
+                // Tags created by the host already refer to engine-level types.
+                EngineOrModuleTypeIndex::Engine(index) => index,
+                EngineOrModuleTypeIndex::RecGroup(_) => unreachable!(),
+            };
+            ptr::write(to, VMTagDefinition::new(signature));
         }
 
         // Zero-initialize the globals so that nothing is uninitialized memory
@@ -1510,7 +1542,9 @@ impl Instance {
         }
     }
 
-    // TODO
+    /// Allocates a continuation with this instance's WasmFX allocator, which
+    /// is created on first use, taking its stack from the store's stack cache
+    /// if possible.
     pub(crate) fn wasmfx_allocate_continuation(
         &mut self,
     ) -> Result<
@@ -1520,20 +1554,31 @@ impl Instance {
         ),
         Error,
     > {
-        match &mut self.wasmfx_allocator {
-            Some(allocator) => allocator.allocate(),
-            None => unsafe {
-                InstanceAndStore::from_vmctx(
-                    (&mut self.vmctx) as *mut VMContext,
-                    |i| -> Result<_, Error> {
-                        let (_, store) = i.unpack_mut();
-                        let wasmfx_config = &*(store.wasmfx_config());
-                        self.wasmfx_allocator =
+        unsafe {
+            InstanceAndStore::from_vmctx(
+                (&mut self.vmctx) as *mut VMContext,
+                |i| -> Result<_, Error> {
+                    let (instance, store) = i.unpack_mut();
+                    let wasmfx_config = &*(store.wasmfx_config());
+                    let current = *store.store_opaque_mut().continuation_stack_bytes_mut();
+                    let desired = current.saturating_add(wasmfx_config.stack_size);
+                    if !store.continuation_stack_allocating(current, desired)? {
+                        bail!("continuation stack allocation denied by the resource limiter");
+                    }
+                    if instance.wasmfx_allocator.is_none() {
+                        instance.wasmfx_allocator =
                             Some(Box::new(WasmFXAllocator::new(wasmfx_config)?));
-                        self.wasmfx_allocator.as_mut().unwrap().allocate()
-                    },
-                )
-            },
+                    }
+                    let cache = store.store_opaque_mut().wasmfx_stack_cache();
+                    let allocation = instance
+                        .wasmfx_allocator
+                        .as_mut()
+                        .unwrap()
+                        .allocate(cache)?;
+                    *store.store_opaque_mut().continuation_stack_bytes_mut() = desired;
+                    Ok(allocation)
+                },
+            )
         }
     }
 
@@ -1541,7 +1586,20 @@ impl Instance {
         &mut self,
         contref: *mut wasmfx_allocator::VMContRef,
     ) {
-        self.wasmfx_allocator.as_mut().unwrap().deallocate(contref)
+        unsafe {
+            InstanceAndStore::from_vmctx((&mut self.vmctx) as *mut VMContext, |i| {
+                let (instance, store) = i.unpack_mut();
+                let stack_size = (*store.wasmfx_config()).stack_size;
+                let bytes = store.store_opaque_mut().continuation_stack_bytes_mut();
+                *bytes = bytes.saturating_sub(stack_size);
+                let cache = store.store_opaque_mut().wasmfx_stack_cache();
+                instance
+                    .wasmfx_allocator
+                    .as_mut()
+                    .unwrap()
+                    .deallocate(cache, contref)
+            })
+        }
     }
 }
 
@@ -1783,6 +1841,46 @@ impl InstanceHandle {
         allocator::initialize_instance(store, self.instance_mut(), module, is_bulk_memory)
     }
 
+    /// Performs only the global initialization steps of
+    /// [`InstanceHandle::initialize`], for instances whose segments are
+    /// applied separately with [`InstanceHandle::initialize_segments`].
+    pub fn initialize_globals(&mut self, store: &mut StoreOpaque, module: &Module) -> Result<()> {
+        allocator::initialize_instance_globals(store, self.instance_mut(), module)
+    }
+
+    /// Applies the element and data segments of this instance, the remaining
+    /// steps of [`InstanceHandle::initialize`] after
+    /// [`InstanceHandle::initialize_globals`].
+    ///
+    /// When `skip` is provided it has an entry for each active data segment
+    /// of a module with segmented memory initialization, and segments whose
+    /// entries are `true` aren't applied.
+    pub fn initialize_segments(
+        &mut self,
+        store: &mut StoreOpaque,
+        module: &Module,
+        is_bulk_memory: bool,
+        skip: Option<&[bool]>,
+    ) -> Result<()> {
+        allocator::initialize_instance_segments(
+            store,
+            self.instance_mut(),
+            module,
+            is_bulk_memory,
+            skip,
+        )
+    }
+
+    /// Evaluates the offsets of all active data segments of a module with
+    /// segmented memory initialization.
+    pub fn data_segment_offsets(
+        &mut self,
+        store: &mut StoreOpaque,
+        initializers: &[MemoryInitializer],
+    ) -> Result<Vec<u64>> {
+        allocator::data_segment_offsets(store, self.instance_mut(), initializers)
+    }
+
     /// Attempts to convert from the host `addr` specified to a WebAssembly
     /// based address recorded in `WasmFault`.
     ///
@@ -8,6 +8,8 @@ use crate::runtime::vm::table::Table;
 use crate::runtime::vm::{CompiledModuleId, ModuleRuntimeInfo, VMFuncRef, VMGcRef, VMStore};
 use crate::store::{AutoAssertNoGc, StoreOpaque};
 use crate::vm::VMGlobalDefinition;
+#[cfg(has_virtual_memory)]
+use crate::vm::{mmap::AlignedLength, Mmap};
 use core::ptr::NonNull;
 use core::{any::Any, mem, ptr};
 use wasmtime_environ::{
@@ -37,6 +39,7 @@ pub use self::pooling::{
 };
 
 pub mod wasmfx_allocator;
+pub use self::wasmfx_allocator::{WasmFXStackCache, WasmFXStackCacheStats};
 
 /// Represents a request for a new runtime instance.
 pub struct InstanceAllocationRequest<'a> {
@@ -80,6 +83,89 @@ pub struct InstanceAllocationRequest<'a> {
 
     /// Tunable configuration options the engine is using.
     pub tunables: &'a Tunables,
+
+    /// This instance's share of an `InstanceBatch`, if it's part of one, in
+    /// which case it was already counted by the allocator and isn't counted
+    /// again.
+    pub reserved: Option<ReservedInstance>,
+}
+
+/// Room reserved in an instance allocator for a batch of instances of the
+/// same module.
+///
+/// Each instance of the batch takes its share with `InstanceBatch::take`.
+/// Whatever hasn't been taken when the batch is dropped, for example because
+/// instantiating the batch failed or was cancelled partway through, is
+/// released again.
+pub struct InstanceBatch<'a> {
+    allocator: &'a (dyn InstanceAllocator + Send + Sync),
+    remaining: u32,
+    #[cfg(has_virtual_memory)]
+    memories: PrimaryMap<DefinedMemoryIndex, Vec<Mmap<AlignedLength>>>,
+}
+
+impl<'a> InstanceBatch<'a> {
+    /// Reserves room for `count` instances of `module` in `allocator`.
+    ///
+    /// Either all of the instances fit within the allocator's limits or an
+    /// error is returned.
+    pub fn new(
+        allocator: &'a (dyn InstanceAllocator + Send + Sync),
+        module: &Module,
+        tunables: &Tunables,
+        count: u32,
+    ) -> Result<InstanceBatch<'a>> {
+        allocator.increment_core_instance_count_by(count)?;
+        #[cfg_attr(not(has_virtual_memory), allow(unused_mut))]
+        let mut batch = InstanceBatch {
+            allocator,
+            remaining: count,
+            #[cfg(has_virtual_memory)]
+            memories: PrimaryMap::new(),
+        };
+
+        // If this fails then dropping `batch` releases the count from above.
+        #[cfg(has_virtual_memory)]
+        {
+            batch.memories = allocator.reserve_batch_memories(module, tunables, count)?;
+        }
+        #[cfg(not(has_virtual_memory))]
+        let _ = (module, tunables);
+
+        Ok(batch)
+    }
+
+    /// Takes the share of the next instance of the batch, to be allocated
+    /// with `InstanceAllocationRequest::reserved` set to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every instance of the batch has already been taken.
+    pub fn take(&mut self) -> ReservedInstance {
+        self.remaining = self
+            .remaining
+            .checked_sub(1)
+            .expect("every instance of the batch was taken already");
+        ReservedInstance {
+            #[cfg(has_virtual_memory)]
+            memories: self.memories.values_mut().map(|m| m.pop()).collect(),
+        }
+    }
+}
+
+impl Drop for InstanceBatch<'_> {
+    fn drop(&mut self) {
+        self.allocator
+            .decrement_core_instance_count_by(self.remaining);
+    }
+}
+
+/// One instance's share of an `InstanceBatch`.
+pub struct ReservedInstance {
+    /// The regions reserved for the instance's defined memories, for those
+    /// memories that the allocator reserved ahead of time.
+    #[cfg(has_virtual_memory)]
+    pub memories: PrimaryMap<DefinedMemoryIndex, Option<Mmap<AlignedLength>>>,
 }
 
 /// A pointer to a Store. This Option<*mut dyn Store> is wrapped in a struct
@@ -233,6 +319,51 @@ pub unsafe trait InstanceAllocatorImpl {
     /// The dual of `increment_core_instance_count`.
     fn decrement_core_instance_count(&self);
 
+    /// Increment the count of concurrent core module instances by `count` at
+    /// once, reserving room for a batch of instances.
+    ///
+    /// Either all of the instances are counted or, if they don't all fit
+    /// within the allocator's limits, none are and an error is returned. Each
+    /// instance of the batch is then allocated with
+    /// `InstanceAllocationRequest::reserved` set, and the count of those which
+    /// end up not being allocated is released with
+    /// `decrement_core_instance_count_by`. This is managed by `InstanceBatch`.
+    fn increment_core_instance_count_by(&self, count: u32) -> Result<()> {
+        for i in 0..count {
+            if let Err(e) = self.increment_core_instance_count() {
+                self.decrement_core_instance_count_by(i);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// The dual of `increment_core_instance_count_by`.
+    fn decrement_core_instance_count_by(&self, count: u32) {
+        for _ in 0..count {
+            self.decrement_core_instance_count();
+        }
+    }
+
+    /// Reserve the defined memories of a batch of `count` instances of
+    /// `module` ahead of time.
+    ///
+    /// Returns, for each defined memory, either `count` regions which
+    /// `allocate_memory` will later find in
+    /// `InstanceAllocationRequest::reserved`, or no regions if this allocator
+    /// doesn't reserve that memory ahead of time. The default implementation
+    /// reserves nothing.
+    #[cfg(has_virtual_memory)]
+    fn reserve_batch_memories(
+        &self,
+        module: &Module,
+        tunables: &Tunables,
+        count: u32,
+    ) -> Result<PrimaryMap<DefinedMemoryIndex, Vec<Mmap<AlignedLength>>>> {
+        let _ = (module, tunables, count);
+        Ok(PrimaryMap::new())
+    }
+
     /// Allocate a memory for an instance.
     ///
     /// # Unsafety
@@ -388,7 +519,9 @@ pub trait InstanceAllocator: InstanceAllocatorImpl {
         InstanceAllocatorImpl::validate_module_impl(self, module, request.runtime_info.offsets())
             .expect("module should have already been validated before allocation");
 
-        self.increment_core_instance_count()?;
+        if request.reserved.is_none() {
+            self.increment_core_instance_count()?;
+        }
 
         let num_defined_memories = module.num_defined_memories();
         let mut memories = PrimaryMap::with_capacity(num_defined_memories);
@@ -680,6 +813,7 @@ fn initialize_memories(
     context: &mut ConstEvalContext<'_>,
     const_evaluator: &mut ConstExprEvaluator,
     module: &Module,
+    skip: Option<&[bool]>,
 ) -> Result<()> {
     // Delegates to the `init_memory` method which is sort of a duplicate of
     // `instance.memory_init_segment` but is used at compile-time in other
@@ -695,6 +829,8 @@ fn initialize_memories(
         store: &'a mut StoreOpaque,
         context: &'a mut ConstEvalContext<'b>,
         const_evaluator: &'a mut ConstExprEvaluator,
+        skip: Option<&'a [bool]>,
+        segment: usize,
     }
 
     impl InitMemory for InitMemoryAtInstantiation<'_, '_> {
@@ -727,6 +863,14 @@ fn initialize_memories(
             memory_index: wasmtime_environ::MemoryIndex,
             init: &wasmtime_environ::StaticMemoryInitializer,
         ) -> bool {
+            // Embedders may have asked for this segment to not be applied, in
+            // which case they apply it themselves afterwards.
+            let skip = self.skip.is_some_and(|skip| skip[self.segment]);
+            self.segment += 1;
+            if skip {
+                return true;
+            }
+
             // If this initializer applies to a defined memory but that memory
             // doesn't need initialization, due to something like copy-on-write
             // pre-initializing it via mmap magic, then this initializer can be
@@ -761,6 +905,8 @@ fn initialize_memories(
             store,
             context,
             const_evaluator,
+            skip,
+            segment: 0,
         });
     if !ok {
         return Err(Trap::MemoryOutOfBounds.into());
@@ -846,11 +992,67 @@ pub(super) fn initialize_instance(
 
     initialize_globals(store, &mut context, &mut const_evaluator, module)?;
     initialize_tables(store, &mut context, &mut const_evaluator, module)?;
-    initialize_memories(store, &mut context, &mut const_evaluator, &module)?;
+    initialize_memories(store, &mut context, &mut const_evaluator, &module, None)?;
+
+    Ok(())
+}
+
+/// The first half of `initialize_instance`, only initializing globals.
+///
+/// This is followed by `initialize_instance_segments` for embedders which
+/// control how the data segments of the instance are applied.
+pub(super) fn initialize_instance_globals(
+    store: &mut StoreOpaque,
+    instance: &mut Instance,
+    module: &Module,
+) -> Result<()> {
+    let mut context = ConstEvalContext::new(instance);
+    let mut const_evaluator = ConstExprEvaluator::default();
+    initialize_globals(store, &mut context, &mut const_evaluator, module)
+}
+
+/// The second half of `initialize_instance`, applying element and data
+/// segments.
+///
+/// The `skip` list has an entry for each active data segment of a module
+/// with `MemoryInitialization::Segmented` initialization, in order, and the
+/// segments whose entries are `true` aren't applied. Their bounds are still
+/// checked.
+pub(super) fn initialize_instance_segments(
+    store: &mut StoreOpaque,
+    instance: &mut Instance,
+    module: &Module,
+    is_bulk_memory: bool,
+    skip: Option<&[bool]>,
+) -> Result<()> {
+    if !is_bulk_memory {
+        check_init_bounds(store, instance, module)?;
+    }
+
+    let mut context = ConstEvalContext::new(instance);
+    let mut const_evaluator = ConstExprEvaluator::default();
+
+    initialize_tables(store, &mut context, &mut const_evaluator, module)?;
+    initialize_memories(store, &mut context, &mut const_evaluator, module, skip)?;
 
     Ok(())
 }
 
+/// Returns the offsets of the data segments of a module with
+/// `MemoryInitialization::Segmented` initialization, in order.
+///
+/// Globals must have already been initialized.
+pub(super) fn data_segment_offsets(
+    store: &mut StoreOpaque,
+    instance: &mut Instance,
+    initializers: &[MemoryInitializer],
+) -> Result<Vec<u64>> {
+    initializers
+        .iter()
+        .map(|init| get_memory_init_start(store, init, instance))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
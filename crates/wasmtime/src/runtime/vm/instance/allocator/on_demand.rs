@@ -12,6 +12,11 @@ use wasmtime_environ::{
     DefinedMemoryIndex, DefinedTableIndex, HostPtr, Module, Tunables, VMOffsets,
 };
 
+#[cfg(has_virtual_memory)]
+use crate::runtime::vm::{memory::MmapMemory, mmap::AlignedLength, Mmap};
+#[cfg(has_virtual_memory)]
+use wasmtime_environ::PrimaryMap;
+
 #[cfg(feature = "gc")]
 use crate::runtime::vm::{GcHeap, GcHeapAllocationIndex, GcRuntime};
 
@@ -103,6 +108,33 @@ unsafe impl InstanceAllocatorImpl for OnDemandInstanceAllocator {
 
     fn decrement_core_instance_count(&self) {}
 
+    #[cfg(has_virtual_memory)]
+    fn reserve_batch_memories(
+        &self,
+        module: &Module,
+        tunables: &Tunables,
+        count: u32,
+    ) -> Result<PrimaryMap<DefinedMemoryIndex, Vec<Mmap<AlignedLength>>>> {
+        let mut memories = PrimaryMap::new();
+
+        // Only memories of the default memory creator are known ahead of time
+        // to be an `MmapMemory`, which can be placed in a reservation.
+        if self.mem_creator.is_some() || !DefaultMemoryCreator::uses_mmap(tunables) {
+            return Ok(memories);
+        }
+
+        let count = usize::try_from(count).unwrap();
+        for ty in module.memories.values().skip(module.num_imported_memories) {
+            // Memories which are too large to allocate are left to fail when
+            // each instance is allocated.
+            memories.push(match MmapMemory::reservation_size(ty, tunables)? {
+                Some(size) => Mmap::reserve_many(size, count)?,
+                None => Vec::new(),
+            });
+        }
+        Ok(memories)
+    }
+
     unsafe fn allocate_memory(
         &self,
         request: &mut InstanceAllocationRequest,
@@ -110,12 +142,33 @@ unsafe impl InstanceAllocatorImpl for OnDemandInstanceAllocator {
         tunables: &Tunables,
         memory_index: DefinedMemoryIndex,
     ) -> Result<(MemoryAllocationIndex, Memory)> {
+        let image = request.runtime_info.memory_image(memory_index)?;
+        let allocation_index = MemoryAllocationIndex::default();
+
+        #[cfg(has_virtual_memory)]
+        if let Some(reservation) = request
+            .reserved
+            .as_mut()
+            .and_then(|reserved| reserved.memories.get_mut(memory_index))
+            .and_then(|reservation| reservation.take())
+        {
+            let memory = Memory::new_dynamic_in(
+                reservation,
+                ty,
+                tunables,
+                request
+                    .store
+                    .get()
+                    .expect("if module has memory plans, store is not empty"),
+                image,
+            )?;
+            return Ok((allocation_index, memory));
+        }
+
         let creator = self
             .mem_creator
             .as_deref()
             .unwrap_or_else(|| &DefaultMemoryCreator);
-        let image = request.runtime_info.memory_image(memory_index)?;
-        let allocation_index = MemoryAllocationIndex::default();
         let memory = Memory::new_dynamic(
             ty,
             tunables,
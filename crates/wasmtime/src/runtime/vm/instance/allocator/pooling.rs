@@ -578,9 +578,18 @@ unsafe impl InstanceAllocatorImpl for PoolingInstanceAllocator {
     }
 
     fn increment_core_instance_count(&self) -> Result<()> {
-        let old_count = self.live_core_instances.fetch_add(1, Ordering::AcqRel);
-        if old_count >= u64::from(self.limits.total_core_instances) {
-            self.decrement_core_instance_count();
+        self.increment_core_instance_count_by(1)
+    }
+
+    fn decrement_core_instance_count(&self) {
+        self.decrement_core_instance_count_by(1);
+    }
+
+    fn increment_core_instance_count_by(&self, count: u32) -> Result<()> {
+        let count = u64::from(count);
+        let old_count = self.live_core_instances.fetch_add(count, Ordering::AcqRel);
+        if old_count + count > u64::from(self.limits.total_core_instances) {
+            self.decrement_core_instance_count_by(u32::try_from(count).unwrap());
             return Err(PoolConcurrencyLimitError::new(
                 usize::try_from(self.limits.total_core_instances).unwrap(),
                 "core instances",
@@ -590,8 +599,9 @@ unsafe impl InstanceAllocatorImpl for PoolingInstanceAllocator {
         Ok(())
     }
 
-    fn decrement_core_instance_count(&self) {
-        self.live_core_instances.fetch_sub(1, Ordering::AcqRel);
+    fn decrement_core_instance_count_by(&self, count: u32) {
+        self.live_core_instances
+            .fetch_sub(u64::from(count), Ordering::AcqRel);
     }
 
     unsafe fn allocate_memory(
@@ -5,6 +5,7 @@
 //
 
 use crate::prelude::*;
+use alloc::collections::BTreeMap;
 use anyhow::Result;
 use wasmtime_continuations::WasmFXConfig;
 
@@ -18,21 +19,37 @@ pub mod wasmfx_on_demand {
     #[derive(Debug)]
     pub struct InnerAllocator {
         stack_size: usize,
+        initial_stack_size: Option<usize>,
     }
 
     impl InnerAllocator {
         pub fn new(config: &WasmFXConfig) -> Result<Self> {
             Ok(InnerAllocator {
                 stack_size: config.stack_size,
+                initial_stack_size: config.initial_stack_size,
             })
         }
 
-        pub fn allocate(&mut self) -> Result<(*mut VMContRef, FiberStack)> {
+        pub fn allocate(
+            &mut self,
+            cache: &mut WasmFXStackCache,
+        ) -> Result<(*mut VMContRef, FiberStack)> {
+            if let Some(stack) = cache.take(self.stack_size) {
+                let contref = Box::into_raw(Box::new(VMContRef::empty()));
+                return Ok((contref, stack));
+            }
             let stack = {
                 cfg_if::cfg_if! {
                     if #[cfg(all(feature = "unsafe_wasmfx_stacks", any(not(feature = "wasmfx_baseline"), feature = "wasmfx_no_baseline")))] {
                         super::FiberStack::malloc(self.stack_size)
+                    } else if #[cfg(all(unix, any(not(feature = "wasmfx_baseline"), feature = "wasmfx_no_baseline")))] {
+                        match self.initial_stack_size {
+                            Some(initial) => super::FiberStack::new_growable(self.stack_size, initial),
+                            None => super::FiberStack::new(self.stack_size, false /* whether zeroed */),
+                        }
                     } else {
+                        // Growable stacks are rejected by `Config::validate` here.
+                        debug_assert!(self.initial_stack_size.is_none());
                         super::FiberStack::new(self.stack_size, false /* whether zeroed */)
                     }
                 }
@@ -42,9 +59,21 @@ pub mod wasmfx_on_demand {
             Ok((contref, stack?))
         }
 
-        pub fn deallocate(&mut self, contref: *mut VMContRef) {
-            // In on-demand mode, we actually deallocate the continuation.
-            unsafe { core::mem::drop(Box::from_raw(contref)) };
+        pub fn deallocate(&mut self, cache: &mut WasmFXStackCache, contref: *mut VMContRef) {
+            // In on-demand mode, we actually deallocate the continuation, but
+            // its stack may be kept around for the next one.
+            let contref = unsafe { Box::from_raw(contref) };
+            cfg_if::cfg_if! {
+                if #[cfg(any(not(feature = "wasmfx_baseline"), feature = "wasmfx_no_baseline"))] {
+                    let mut contref = contref;
+                    cache.put(self.stack_size, contref.detach_stack());
+                } else {
+                    // The baseline implementation keeps the stack inside of a
+                    // `Fiber`, which can't be taken apart again.
+                    let _ = cache;
+                }
+            }
+            core::mem::drop(contref);
         }
     }
 }
@@ -147,7 +176,12 @@ pub mod wasmfx_pooling {
         }
 
         /// Allocate a new fiber.
-        pub fn allocate(&mut self) -> Result<(*mut VMContRef, FiberStack)> {
+        ///
+        /// The stack cache is unused, as stacks are always reused by the pool.
+        pub fn allocate(
+            &mut self,
+            _cache: &mut WasmFXStackCache,
+        ) -> Result<(*mut VMContRef, FiberStack)> {
             if self.stack_size == 0 {
                 bail!("pooling allocator not configured to enable fiber stack allocation");
             }
@@ -193,7 +227,7 @@ pub mod wasmfx_pooling {
         ///
         /// The fiber must have been allocated by this pool, must be in an allocated
         /// state, and must never be used again.
-        pub fn deallocate(&mut self, continuation: *mut VMContRef) {
+        pub fn deallocate(&mut self, _cache: &mut WasmFXStackCache, continuation: *mut VMContRef) {
             let continuation = unsafe { continuation.as_mut().unwrap() };
 
             // While in storage, the continuation only stores a dummy stack.
@@ -310,13 +344,111 @@ impl WasmFXAllocator {
     ///
     /// Note that the `revision` counter of the returned `VMContRef` may be
     /// non-zero and must not be decremented.
-    pub fn allocate(&mut self) -> Result<(*mut VMContRef, FiberStack)> {
-        self.inner.allocate()
+    ///
+    /// Stacks are taken from `cache` when possible.
+    pub fn allocate(
+        &mut self,
+        cache: &mut WasmFXStackCache,
+    ) -> Result<(*mut VMContRef, FiberStack)> {
+        self.inner.allocate(cache)
     }
 
     /// This may not actually deallocate the underlying memory, but simply
-    /// return the `VMContRef` to a pool.
-    pub fn deallocate(&mut self, contref: *mut VMContRef) {
-        self.inner.deallocate(contref)
+    /// return the `VMContRef` to a pool, or its stack to `cache`.
+    pub fn deallocate(&mut self, cache: &mut WasmFXStackCache, contref: *mut VMContRef) {
+        self.inner.deallocate(cache, contref)
+    }
+}
+
+/// Counters describing a store's cache of continuation stacks, see
+/// [`Config::wasmfx_stack_cache_size`](crate::Config::wasmfx_stack_cache_size).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WasmFXStackCacheStats {
+    /// The number of stacks currently in the cache.
+    pub cached_stacks: usize,
+    /// The total size in bytes of the stacks currently in the cache, including
+    /// their guard pages.
+    pub cached_bytes: usize,
+    /// The number of continuations whose stack was taken from the cache.
+    pub hits: u64,
+    /// The number of continuations for which a new stack had to be allocated
+    /// since the cache had none of the right size.
+    pub misses: u64,
+    /// The number of stacks which were freed rather than cached since the
+    /// cache was full.
+    pub evictions: u64,
+}
+
+/// A per-store cache of the stacks of continuations which have returned,
+/// grouped by the stack size they were allocated with.
+///
+/// At most `capacity` stacks are kept in total; once the cache is full,
+/// further stacks are freed immediately.
+pub struct WasmFXStackCache {
+    capacity: usize,
+    stacks: BTreeMap<usize, Vec<FiberStack>>,
+    stats: WasmFXStackCacheStats,
+}
+
+// The cached stacks are owned by the cache and not in use by any continuation,
+// so they may be moved between threads along with their store.
+unsafe impl Send for WasmFXStackCache {}
+unsafe impl Sync for WasmFXStackCache {}
+
+impl WasmFXStackCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            stacks: BTreeMap::new(),
+            stats: WasmFXStackCacheStats::default(),
+        }
+    }
+
+    /// Takes a cached stack allocated with `size`, if any.
+    pub fn take(&mut self, size: usize) -> Option<FiberStack> {
+        let stack = self.stacks.get_mut(&size).and_then(|stacks| stacks.pop());
+        match &stack {
+            Some(stack) => {
+                self.stats.hits += 1;
+                self.stats.cached_stacks -= 1;
+                self.stats.cached_bytes -= Self::stack_bytes(stack);
+            }
+            None => self.stats.misses += 1,
+        }
+        stack
+    }
+
+    /// Caches `stack`, which was allocated with `size`, or frees it if the
+    /// cache is full.
+    pub fn put(&mut self, size: usize, stack: FiberStack) {
+        if self.stats.cached_stacks >= self.capacity {
+            self.stats.evictions += 1;
+            return;
+        }
+        self.stats.cached_stacks += 1;
+        self.stats.cached_bytes += Self::stack_bytes(&stack);
+        self.stacks.entry(size).or_default().push(stack);
+    }
+
+    /// Frees cached stacks until at most `keep` remain, starting with the
+    /// largest ones.
+    pub fn trim(&mut self, keep: usize) {
+        while self.stats.cached_stacks > keep {
+            let mut entry = self.stacks.last_entry().unwrap();
+            let stack = entry.get_mut().pop().unwrap();
+            if entry.get().is_empty() {
+                entry.remove();
+            }
+            self.stats.cached_stacks -= 1;
+            self.stats.cached_bytes -= Self::stack_bytes(&stack);
+        }
+    }
+
+    pub fn stats(&self) -> WasmFXStackCacheStats {
+        self.stats
+    }
+
+    fn stack_bytes(stack: &FiberStack) -> usize {
+        stack.range().map_or(0, |range| range.len())
     }
 }
@@ -317,6 +317,7 @@ impl InterpreterRef<'_> {
             (@get u8 $reg:ident) => (self.0[$reg].get_i32() as u8);
             (@get u32 $reg:ident) => (self.0[$reg].get_u32());
             (@get i32 $reg:ident) => (self.0[$reg].get_i32());
+            (@get u64 $reg:ident) => (self.0[$reg].get_u64());
             (@get i64 $reg:ident) => (self.0[$reg].get_i64());
             (@get vmctx $reg:ident) => (self.0[$reg].get_ptr());
             (@get pointer $reg:ident) => (self.0[$reg].get_ptr());
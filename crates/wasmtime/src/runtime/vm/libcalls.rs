@@ -64,7 +64,9 @@ use core::convert::Infallible;
 use core::ptr::NonNull;
 #[cfg(feature = "threads")]
 use core::time::Duration;
-use wasmtime_environ::{DataIndex, ElemIndex, FuncIndex, MemoryIndex, TableIndex, Trap};
+use wasmtime_environ::{
+    DataIndex, ElemIndex, FuncIndex, GlobalIndex, MemoryIndex, TableIndex, Trap,
+};
 #[cfg(feature = "wmemcheck")]
 use wasmtime_wmemcheck::AccessError::{
     DoubleMalloc, InvalidFree, InvalidRead, InvalidWrite, OutOfBounds,
@@ -1091,22 +1093,45 @@ unsafe fn is_subtype(
 // Implementation of `memory.atomic.notify` for locally defined memories.
 #[cfg(feature = "threads")]
 fn memory_atomic_notify(
-    _store: &mut dyn VMStore,
+    store: &mut dyn VMStore,
     instance: &mut Instance,
     memory_index: u32,
     addr_index: u64,
     count: u32,
 ) -> Result<u32, Trap> {
-    let memory = MemoryIndex::from_u32(memory_index);
-    instance
-        .get_runtime_memory(memory)
-        .atomic_notify(addr_index, count)
+    let memory = instance.get_runtime_memory(MemoryIndex::from_u32(memory_index));
+    if let Some(member) = store.store_opaque().deadlock_member() {
+        if let Some(addr) = memory.shared_atomic_addr(addr_index, 4) {
+            member.notify(addr);
+        }
+    }
+    memory.atomic_notify(addr_index, count)
+}
+
+/// Records an untimed wait on the `size`-byte location at `addr_index` in a
+/// shared memory with the store's deadlock monitor, if any, unless `blocks`
+/// says that the wait returns immediately.
+#[cfg(feature = "threads")]
+fn deadlock_wait<'a>(
+    store: &'a dyn VMStore,
+    memory: &mut crate::runtime::vm::Memory,
+    addr_index: u64,
+    size: u64,
+    timeout: Option<Duration>,
+    blocks: impl FnOnce(*mut u8) -> bool,
+) -> Option<crate::runtime::deadlock::DeadlockWait<'a>> {
+    let member = store.store_opaque().deadlock_member()?;
+    if timeout.is_some() {
+        return None;
+    }
+    let addr = memory.shared_atomic_addr(addr_index, size)?;
+    blocks(addr).then(|| member.wait(addr, addr_index))
 }
 
 // Implementation of `memory.atomic.wait32` for locally defined memories.
 #[cfg(feature = "threads")]
 fn memory_atomic_wait32(
-    _store: &mut dyn VMStore,
+    store: &mut dyn VMStore,
     instance: &mut Instance,
     memory_index: u32,
     addr_index: u64,
@@ -1114,16 +1139,18 @@ fn memory_atomic_wait32(
     timeout: u64,
 ) -> Result<u32, Trap> {
     let timeout = (timeout as i64 >= 0).then(|| Duration::from_nanos(timeout));
-    let memory = MemoryIndex::from_u32(memory_index);
-    Ok(instance
-        .get_runtime_memory(memory)
-        .atomic_wait32(addr_index, expected, timeout)? as u32)
+    let memory = instance.get_runtime_memory(MemoryIndex::from_u32(memory_index));
+    let _wait = deadlock_wait(store, memory, addr_index, 4, timeout, |addr| {
+        let atomic = unsafe { core::sync::atomic::AtomicU32::from_ptr(addr.cast()) };
+        atomic.load(core::sync::atomic::Ordering::SeqCst) == expected
+    });
+    Ok(memory.atomic_wait32(addr_index, expected, timeout)? as u32)
 }
 
 // Implementation of `memory.atomic.wait64` for locally defined memories.
 #[cfg(feature = "threads")]
 fn memory_atomic_wait64(
-    _store: &mut dyn VMStore,
+    store: &mut dyn VMStore,
     instance: &mut Instance,
     memory_index: u32,
     addr_index: u64,
@@ -1131,10 +1158,12 @@ fn memory_atomic_wait64(
     timeout: u64,
 ) -> Result<u32, Trap> {
     let timeout = (timeout as i64 >= 0).then(|| Duration::from_nanos(timeout));
-    let memory = MemoryIndex::from_u32(memory_index);
-    Ok(instance
-        .get_runtime_memory(memory)
-        .atomic_wait64(addr_index, expected, timeout)? as u32)
+    let memory = instance.get_runtime_memory(MemoryIndex::from_u32(memory_index));
+    let _wait = deadlock_wait(store, memory, addr_index, 8, timeout, |addr| {
+        let atomic = unsafe { core::sync::atomic::AtomicU64::from_ptr(addr.cast()) };
+        atomic.load(core::sync::atomic::Ordering::SeqCst) == expected
+    });
+    Ok(memory.atomic_wait64(addr_index, expected, timeout)? as u32)
 }
 
 // Hook for when an instance runs out of fuel.
@@ -1147,6 +1176,22 @@ fn new_epoch(store: &mut dyn VMStore, _instance: &mut Instance) -> Result<NextEp
     store.new_epoch().map(NextEpoch)
 }
 
+// Hook for when wasm writes a global while watchpoints are enabled.
+fn global_set_notify(
+    store: &mut dyn VMStore,
+    instance: &mut Instance,
+    global_index: u32,
+) -> Result<()> {
+    let definition = instance.defined_or_imported_global_ptr(GlobalIndex::from_u32(global_index));
+    store.global_written(definition)
+}
+
+// Hook for when wasm is about to execute an instruction while single-stepping
+// is enabled.
+fn single_step(store: &mut dyn VMStore, _instance: &mut Instance, offset: u32) -> Result<()> {
+    store.single_step(offset)
+}
+
 struct NextEpoch(u64);
 
 unsafe impl HostResultHasUnwindSentinel for NextEpoch {
@@ -1499,6 +1544,18 @@ fn tc_reallocate(
     tc_allocate(store, instance, new_size, align)
 }
 
+fn tc_suspend_to_host(
+    store: &mut dyn VMStore,
+    instance: &mut Instance,
+    tag: *mut u8,
+) -> Result<(), TrapReason> {
+    crate::vm::continuation::optimized::suspend_to_host(
+        store,
+        instance,
+        tag.cast::<crate::vm::VMTagDefinition>(),
+    )
+}
+
 fn tc_print_str(_store: &mut dyn VMStore, _instance: &mut Instance, s: *const u8, len: u64) {
     let len =
         usize::try_from(len).map_err(|_error| TrapReason::User(anyhow::anyhow!("len too large!")));
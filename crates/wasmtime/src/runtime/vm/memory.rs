@@ -77,8 +77,9 @@
 use crate::prelude::*;
 use crate::runtime::vm::vmcontext::VMMemoryDefinition;
 #[cfg(has_virtual_memory)]
-use crate::runtime::vm::{HostAlignedByteCount, MmapOffset};
+use crate::runtime::vm::{mmap::AlignedLength, HostAlignedByteCount, Mmap, MmapOffset};
 use crate::runtime::vm::{MemoryImage, MemoryImageSlot, SendSyncPtr, VMStore, WaitResult};
+use crate::{MemoryGrowth, MemoryGrowthOutcome};
 use alloc::sync::Arc;
 use core::time::Duration;
 use core::{ops::Range, ptr::NonNull};
@@ -130,11 +131,7 @@ impl RuntimeMemoryCreator for DefaultMemoryCreator {
         maximum: Option<usize>,
     ) -> Result<Box<dyn RuntimeLinearMemory>> {
         #[cfg(has_virtual_memory)]
-        if tunables.signals_based_traps
-            || tunables.memory_guard_size > 0
-            || tunables.memory_reservation > 0
-            || tunables.memory_init_cow
-        {
+        if Self::uses_mmap(tunables) {
             return Ok(Box::new(MmapMemory::new(ty, tunables, minimum, maximum)?));
         }
 
@@ -143,6 +140,18 @@ impl RuntimeMemoryCreator for DefaultMemoryCreator {
     }
 }
 
+impl DefaultMemoryCreator {
+    /// Returns whether memories are created as an [`MmapMemory`] with these
+    /// `tunables`.
+    #[cfg(has_virtual_memory)]
+    pub fn uses_mmap(tunables: &Tunables) -> bool {
+        tunables.signals_based_traps
+            || tunables.memory_guard_size > 0
+            || tunables.memory_reservation > 0
+            || tunables.memory_init_cow
+    }
+}
+
 /// A linear memory and its backing storage.
 pub trait RuntimeLinearMemory: Send + Sync {
     /// Returns the number bytes that this linear memory can access.
@@ -224,12 +233,46 @@ impl Memory {
         store: &mut dyn VMStore,
         memory_image: Option<&Arc<MemoryImage>>,
     ) -> Result<Self> {
-        let (minimum, maximum) = Self::limit_new(ty, Some(store))?;
+        let (minimum, maximum) = Self::limit_new(ty, Some(&mut *store))?;
         let allocation = creator.new_memory(ty, tunables, minimum, maximum)?;
+        Self::from_dynamic_allocation(ty, tunables, allocation, memory_image, store)
+    }
 
+    /// Same as [`Memory::new_dynamic`] with the [`DefaultMemoryCreator`], but
+    /// placing the memory within `reservation`, which was reserved ahead of
+    /// time with the size that [`MmapMemory::reservation_size`] returned.
+    #[cfg(has_virtual_memory)]
+    pub fn new_dynamic_in(
+        reservation: Mmap<AlignedLength>,
+        ty: &wasmtime_environ::Memory,
+        tunables: &Tunables,
+        store: &mut dyn VMStore,
+        memory_image: Option<&Arc<MemoryImage>>,
+    ) -> Result<Self> {
+        debug_assert!(DefaultMemoryCreator::uses_mmap(tunables));
+        let (minimum, maximum) = Self::limit_new(ty, Some(&mut *store))?;
+        let allocation = Box::new(MmapMemory::new_in(
+            reservation,
+            ty,
+            tunables,
+            minimum,
+            maximum,
+        )?);
+        Self::from_dynamic_allocation(ty, tunables, allocation, memory_image, store)
+    }
+
+    fn from_dynamic_allocation(
+        ty: &wasmtime_environ::Memory,
+        tunables: &Tunables,
+        allocation: Box<dyn RuntimeLinearMemory>,
+        memory_image: Option<&Arc<MemoryImage>>,
+        store: &mut dyn VMStore,
+    ) -> Result<Self> {
         let memory = LocalMemory::new(ty, tunables, allocation, memory_image)?;
         Ok(if ty.shared {
-            Memory::Shared(SharedMemory::wrap(ty, memory)?)
+            let memory = SharedMemory::wrap(ty, memory)?;
+            store.store_opaque_mut().shared_memory_created(&memory);
+            Memory::Shared(memory)
         } else {
             Memory::Local(memory)
         })
@@ -318,10 +361,10 @@ impl Memory {
         // now the expected uses of limiter means that's ok.
         if let Some(store) = store {
             if !store.memory_growing(0, minimum.unwrap_or(absolute_max), maximum)? {
-                bail!(
+                return Err(crate::ErrorKind::ResourceLimit.tag(format_err!(
                     "memory minimum size of {} pages exceeds memory limits",
                     ty.limits.min
-                );
+                )));
             }
         }
 
@@ -363,6 +406,20 @@ impl Memory {
         }
     }
 
+    /// Maps `data` copy-on-write into this memory at `offset`, so that its
+    /// pages are only populated once they're first accessed.
+    ///
+    /// Both `offset` and the length of `data` must be multiples of the host
+    /// page size. Returns `false`, without changing anything, if this memory
+    /// doesn't support such mappings, which requires it to have been created
+    /// with a copy-on-write image.
+    pub(crate) fn map_data(&mut self, offset: usize, data: &[u8]) -> Result<bool> {
+        match self {
+            Memory::Local(mem) => mem.map_data(offset, data),
+            Memory::Shared(_) => Ok(false),
+        }
+    }
+
     /// Grow memory by the specified amount of wasm pages.
     ///
     /// Returns `None` if memory can't be grown by the specified amount
@@ -427,6 +484,14 @@ impl Memory {
         }
     }
 
+    /// Returns the host address of the `size`-byte atomic at `addr` if this is
+    /// a shared memory and the access is valid, which identifies the location
+    /// across all threads.
+    pub fn shared_atomic_addr(&mut self, addr: u64, size: u64) -> Option<*mut u8> {
+        let memory = self.as_shared_memory()?;
+        validate_atomic_addr(unsafe { &*memory.vmmemory_ptr() }, addr, size, size).ok()
+    }
+
     /// Implementation of `memory.atomic.notify` for all memories.
     pub fn atomic_notify(&mut self, addr: u64, count: u32) -> Result<u32, Trap> {
         match self.as_shared_memory() {
@@ -614,6 +679,12 @@ impl LocalMemory {
         // Store limiter gets first chance to reject memory_growing.
         if let Some(store) = &mut store {
             if !store.memory_growing(old_byte_size, new_byte_size, maximum)? {
+                self.record_growth(
+                    &mut **store,
+                    old_byte_size,
+                    new_byte_size,
+                    MemoryGrowthOutcome::Denied,
+                );
                 return Ok(None);
             }
         }
@@ -622,11 +693,13 @@ impl LocalMemory {
         // to the byte capacity never relocates the base pointer.
         let base_ptr_before = self.alloc.base().as_mut_ptr();
         let required_to_not_move_memory = new_byte_size <= self.alloc.byte_capacity();
+        let mut exceeds_maximum = false;
 
         let result = (|| -> Result<()> {
             // Never exceed maximum, even if limiter permitted it.
             if let Some(max) = maximum {
                 if new_byte_size > max {
+                    exceeds_maximum = true;
                     bail!("Memory maximum size exceeded");
                 }
             }
@@ -634,6 +707,7 @@ impl LocalMemory {
             // If memory isn't allowed to move then don't let growth happen
             // beyond the initial capacity
             if !self.memory_may_move && new_byte_size > self.alloc.byte_capacity() {
+                exceeds_maximum = true;
                 bail!("Memory maximum size exceeded");
             }
 
@@ -672,6 +746,14 @@ impl LocalMemory {
                     assert_eq!(base_ptr_before, self.alloc.base().as_mut_ptr());
                 }
 
+                if let Some(store) = store {
+                    self.record_growth(
+                        store,
+                        old_byte_size,
+                        new_byte_size,
+                        MemoryGrowthOutcome::Grown,
+                    );
+                }
                 Ok(Some((old_byte_size, new_byte_size)))
             }
             Err(e) => {
@@ -680,6 +762,12 @@ impl LocalMemory {
                 // dropped
                 // (https://github.com/bytecodealliance/wasmtime/issues/4240).
                 if let Some(store) = store {
+                    let outcome = if exceeds_maximum {
+                        MemoryGrowthOutcome::ExceedsMaximum
+                    } else {
+                        MemoryGrowthOutcome::AllocationFailed(format!("{e:#}"))
+                    };
+                    self.record_growth(store, old_byte_size, new_byte_size, outcome);
                     store.memory_grow_failed(e)?;
                 }
                 Ok(None)
@@ -687,6 +775,26 @@ impl LocalMemory {
         }
     }
 
+    /// Records an attempt to grow this memory from `old_byte_size` to
+    /// `new_byte_size` in the store's memory growth statistics.
+    fn record_growth(
+        &self,
+        store: &mut dyn VMStore,
+        old_byte_size: usize,
+        new_byte_size: usize,
+        outcome: MemoryGrowthOutcome,
+    ) {
+        let page_size = self.page_size();
+        let pages = |bytes: usize| u64::try_from(bytes).unwrap() / page_size;
+        store.store_opaque_mut().record_memory_growth(MemoryGrowth {
+            page_size,
+            old_pages: pages(old_byte_size),
+            requested_pages: pages(new_byte_size),
+            reserved_pages: pages(self.alloc.byte_capacity()),
+            outcome,
+        });
+    }
+
     pub fn vmmemory(&mut self) -> VMMemoryDefinition {
         VMMemoryDefinition {
             base: self.alloc.base().as_mut_ptr(),
@@ -705,6 +813,14 @@ impl LocalMemory {
         }
     }
 
+    pub fn map_data(&mut self, offset: usize, data: &[u8]) -> Result<bool> {
+        assert!(offset.checked_add(data.len()).unwrap() <= self.alloc.byte_size());
+        match &mut self.memory_image {
+            Some(image) => image.map_data(offset, data),
+            None => Ok(false),
+        }
+    }
+
     pub fn wasm_accessible(&self) -> Range<usize> {
         let base = self.alloc.base().as_mut_ptr() as usize;
         // From the base add:
@@ -54,6 +54,100 @@ impl MmapMemory {
         minimum: usize,
         maximum: Option<usize>,
     ) -> Result<Self> {
+        let layout = Layout::new(ty, tunables, minimum)?;
+        let mmap = Mmap::accessible_reserved(HostAlignedByteCount::ZERO, layout.request_bytes)?;
+        Self::from_layout(mmap, layout, minimum, maximum)
+    }
+
+    /// Same as [`MmapMemory::new`], but placing the linear memory within
+    /// `reservation` instead of mapping a new region of memory for it.
+    ///
+    /// The `reservation` must be an inaccessible mapping which is exactly
+    /// [`MmapMemory::reservation_size`] bytes large.
+    pub fn new_in(
+        reservation: Mmap<AlignedLength>,
+        ty: &wasmtime_environ::Memory,
+        tunables: &Tunables,
+        minimum: usize,
+        maximum: Option<usize>,
+    ) -> Result<Self> {
+        let layout = Layout::new(ty, tunables, minimum)?;
+        assert_eq!(reservation.len_aligned(), layout.request_bytes);
+        Self::from_layout(reservation, layout, minimum, maximum)
+    }
+
+    /// Returns the size of the mapping that [`MmapMemory::new`] would create
+    /// for a new linear memory of type `ty`, or `None` if the memory's
+    /// minimum size is too large to be allocated at all.
+    pub fn reservation_size(
+        ty: &wasmtime_environ::Memory,
+        tunables: &Tunables,
+    ) -> Result<Option<HostAlignedByteCount>> {
+        let minimum = match ty
+            .minimum_byte_size()
+            .ok()
+            .and_then(|m| usize::try_from(m).ok())
+        {
+            Some(minimum) => minimum,
+            None => return Ok(None),
+        };
+        Ok(Some(Layout::new(ty, tunables, minimum)?.request_bytes))
+    }
+
+    fn from_layout(
+        mmap: Mmap<AlignedLength>,
+        layout: Layout,
+        minimum: usize,
+        maximum: Option<usize>,
+    ) -> Result<Self> {
+        if minimum > 0 {
+            let accessible = HostAlignedByteCount::new_rounded_up(minimum)?;
+            // SAFETY: mmap is not in use right now so it's safe to make it accessible.
+            unsafe {
+                mmap.make_accessible(layout.pre_guard_bytes, accessible)?;
+            }
+        }
+
+        Ok(Self {
+            mmap: Arc::new(mmap),
+            len: minimum,
+            maximum,
+            pre_guard_size: layout.pre_guard_bytes,
+            offset_guard_size: layout.offset_guard_bytes,
+            extra_to_reserve_on_growth: layout.extra_to_reserve_on_growth,
+        })
+    }
+
+    /// Get the length of the accessible portion of the underlying `mmap`. This
+    /// is the same region as `self.len` but rounded up to a multiple of the
+    /// host page size.
+    fn accessible(&self) -> HostAlignedByteCount {
+        let accessible = HostAlignedByteCount::new_rounded_up(self.len)
+            .expect("accessible region always fits in usize");
+        debug_assert!(accessible <= self.current_capacity());
+        accessible
+    }
+
+    /// Get the amount to which this memory can grow.
+    fn current_capacity(&self) -> HostAlignedByteCount {
+        let mmap_len = self.mmap.len_aligned();
+        mmap_len
+            .checked_sub(self.offset_guard_size)
+            .and_then(|i| i.checked_sub(self.pre_guard_size))
+            .expect("guard regions fit in mmap.len")
+    }
+}
+
+/// How the mapping of a new [`MmapMemory`] is laid out.
+struct Layout {
+    pre_guard_bytes: HostAlignedByteCount,
+    offset_guard_bytes: HostAlignedByteCount,
+    extra_to_reserve_on_growth: HostAlignedByteCount,
+    request_bytes: HostAlignedByteCount,
+}
+
+impl Layout {
+    fn new(ty: &wasmtime_environ::Memory, tunables: &Tunables, minimum: usize) -> Result<Layout> {
         // It's a programmer error for these two configuration values to exceed
         // the host available address space, so panic if such a configuration is
         // found (mostly an issue for hypothetical 32-bit hosts).
@@ -104,44 +198,13 @@ impl MmapMemory {
             .and_then(|i| i.checked_add(offset_guard_bytes))
             .with_context(|| format!("cannot allocate {minimum} with guard regions"))?;
 
-        let mmap = Mmap::accessible_reserved(HostAlignedByteCount::ZERO, request_bytes)?;
-
-        if minimum > 0 {
-            let accessible = HostAlignedByteCount::new_rounded_up(minimum)?;
-            // SAFETY: mmap is not in use right now so it's safe to make it accessible.
-            unsafe {
-                mmap.make_accessible(pre_guard_bytes, accessible)?;
-            }
-        }
-
-        Ok(Self {
-            mmap: Arc::new(mmap),
-            len: minimum,
-            maximum,
-            pre_guard_size: pre_guard_bytes,
-            offset_guard_size: offset_guard_bytes,
+        Ok(Layout {
+            pre_guard_bytes,
+            offset_guard_bytes,
             extra_to_reserve_on_growth,
+            request_bytes,
         })
     }
-
-    /// Get the length of the accessible portion of the underlying `mmap`. This
-    /// is the same region as `self.len` but rounded up to a multiple of the
-    /// host page size.
-    fn accessible(&self) -> HostAlignedByteCount {
-        let accessible = HostAlignedByteCount::new_rounded_up(self.len)
-            .expect("accessible region always fits in usize");
-        debug_assert!(accessible <= self.current_capacity());
-        accessible
-    }
-
-    /// Get the amount to which this memory can grow.
-    fn current_capacity(&self) -> HostAlignedByteCount {
-        let mmap_len = self.mmap.len_aligned();
-        mmap_len
-            .checked_sub(self.offset_guard_size)
-            .and_then(|i| i.checked_sub(self.pre_guard_size))
-            .expect("guard regions fit in mmap.len")
-    }
 }
 
 impl RuntimeLinearMemory for MmapMemory {
@@ -113,6 +113,39 @@ impl Mmap<AlignedLength> {
         }
     }
 
+    /// Create `count` new inaccessible `Mmap`s of `mapping_size` bytes each.
+    ///
+    /// On Unix this reserves all of them with a single mapping which is then
+    /// split up, since part of a mapping may be unmapped on its own there.
+    /// Elsewhere each `Mmap` is reserved separately.
+    pub fn reserve_many(mapping_size: HostAlignedByteCount, count: usize) -> Result<Vec<Self>> {
+        #[cfg(all(unix, not(miri)))]
+        if !mapping_size.is_zero() && count > 1 {
+            let total_size = mapping_size
+                .checked_mul(count)
+                .context("too many mappings to reserve at once")?;
+            let mut sys = mmap::Mmap::reserve(total_size)
+                .context(format!("mmap failed to reserve {total_size:#x} bytes"))?;
+            let mut result = Vec::with_capacity(count);
+            for _ in 1..count {
+                let rest = sys.split_off(mapping_size);
+                result.push(Mmap {
+                    sys: core::mem::replace(&mut sys, rest),
+                    data: AlignedLength {},
+                });
+            }
+            result.push(Mmap {
+                sys,
+                data: AlignedLength {},
+            });
+            return Ok(result);
+        }
+
+        (0..count)
+            .map(|_| Self::accessible_reserved(HostAlignedByteCount::ZERO, mapping_size))
+            .collect()
+    }
+
     /// Converts this `Mmap` into a `Mmap<UnalignedLength>`.
     ///
     /// `UnalignedLength` really means "_possibly_ unaligned length", so it can
@@ -82,6 +82,37 @@ impl Mmap {
         Ok(Mmap { memory })
     }
 
+    /// Splits this mapping in two at `at`, returning the mapping of the bytes
+    /// from `at` onwards and keeping the ones before it.
+    ///
+    /// Each half is unmapped independently when it's dropped, which POSIX
+    /// allows for any page-aligned part of a mapping.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is greater than the length of this mapping.
+    pub fn split_off(&mut self, at: HostAlignedByteCount) -> Mmap {
+        let len = self.len();
+        let at = at.byte_count();
+        assert!(at <= len);
+        let base = self.memory.as_ptr().cast::<u8>();
+        let tail = if at == len {
+            crate::vm::sys::empty_mmap()
+        } else {
+            let ptr = unsafe { base.add(at) };
+            let tail = std::ptr::slice_from_raw_parts_mut(ptr, len - at);
+            SendSyncPtr::new(NonNull::new(tail).unwrap())
+        };
+        let head = if at == 0 {
+            crate::vm::sys::empty_mmap()
+        } else {
+            let head = std::ptr::slice_from_raw_parts_mut(base, at);
+            SendSyncPtr::new(NonNull::new(head).unwrap())
+        };
+        self.memory = head;
+        Mmap { memory: tail }
+    }
+
     #[cfg(feature = "std")]
     pub fn from_file(file: &File) -> Result<Self> {
         let len = file
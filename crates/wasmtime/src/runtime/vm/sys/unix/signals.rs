@@ -157,6 +157,14 @@ unsafe extern "C" fn trap_handler(
             libc::SIGSEGV | libc::SIGBUS => Some((*siginfo).si_addr() as usize),
             _ => None,
         };
+        // Faults in the reserved part of a growable continuation stack are
+        // handled by growing the stack and retrying the faulting instruction.
+        #[cfg(any(not(feature = "wasmfx_baseline"), feature = "wasmfx_no_baseline"))]
+        if let Some(addr) = faulting_addr {
+            if info.grow_continuation_stack(addr) {
+                return true;
+            }
+        }
         let regs = get_trap_registers(context, signum);
         let test = info.test_if_trap(regs, faulting_addr, |handler| {
             handler(signum, siginfo, context)
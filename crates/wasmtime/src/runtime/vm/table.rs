@@ -498,10 +498,10 @@ impl Table {
 
         // Inform the store's limiter what's about to happen.
         if !store.table_growing(0, minimum.unwrap_or(absolute_max), maximum)? {
-            bail!(
+            return Err(crate::ErrorKind::ResourceLimit.tag(format_err!(
                 "table minimum size of {} elements exceeds table limits",
                 ty.limits.min
-            );
+            )));
         }
 
         // At this point we need to actually handle overflows, so bail out with
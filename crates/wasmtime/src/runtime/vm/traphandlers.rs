@@ -16,8 +16,11 @@ mod signals;
 pub use self::signals::*;
 
 use crate::prelude::*;
+use crate::runtime::backtrace_throttle::BacktraceThrottleState;
+use crate::runtime::crash::CrashReporter;
 use crate::runtime::module::lookup_code;
 use crate::runtime::store::StoreOpaque;
+use crate::runtime::vm::arch;
 use crate::runtime::vm::continuation::stack_chain::StackChainCell;
 use crate::runtime::vm::sys::traphandlers;
 use crate::runtime::vm::{Instance, InterpreterRef, VMContext, VMOpaqueContext, VMRuntimeLimits};
@@ -361,6 +364,14 @@ where
     let callee_stack_chain = VMContext::try_from_opaque(callee)
         .map(|vmctx| Instance::from_vmctx(vmctx, |i| *i.stack_chain() as *const StackChainCell));
 
+    // If requested then make sure wasm runs with the default floating-point
+    // environment regardless of what the host has configured.
+    let prev_fp_environment = if store.0.engine().config().reset_fp_environment {
+        Some(arch::reset_fp_environment())
+    } else {
+        None
+    };
+
     let caller = store.0.default_caller();
     let result = CallThreadState::new(store.0, caller, callee_stack_chain).with(|cx| {
         match store.0.interpreter() {
@@ -398,6 +409,12 @@ where
         }
     });
 
+    // Note that this is done before resuming any panic to ensure the host's
+    // environment is restored in that case too.
+    if let Some(prev) = prev_fp_environment {
+        arch::restore_fp_environment(prev);
+    }
+
     return match result {
         Ok(x) => Ok(x),
         Err((UnwindReason::Trap(reason), backtrace, coredumpstack)) => Err(Box::new(Trap {
@@ -448,7 +465,10 @@ mod call_thread_state {
         pub(super) jmp_buf: Cell<*const u8>,
         #[cfg(all(has_native_signals))]
         pub(super) signal_handler: Option<*const SignalHandler>,
+        #[cfg(all(has_native_signals))]
+        pub(super) crash_reporter: Option<CrashReporter>,
         pub(super) capture_backtrace: bool,
+        pub(super) backtrace_throttle: *const BacktraceThrottleState,
         #[cfg(feature = "coredump")]
         pub(super) capture_coredump: bool,
 
@@ -511,7 +531,10 @@ mod call_thread_state {
                 jmp_buf: Cell::new(ptr::null()),
                 #[cfg(all(has_native_signals))]
                 signal_handler: store.signal_handler(),
+                #[cfg(all(has_native_signals))]
+                crash_reporter: CrashReporter::new(store),
                 capture_backtrace: store.engine().config().wasm_backtrace,
+                backtrace_throttle: store.backtrace_throttle(),
                 #[cfg(feature = "coredump")]
                 capture_coredump: store.engine().config().coredump_on_trap,
                 limits,
@@ -652,6 +675,13 @@ impl CallThreadState {
         if !self.capture_backtrace {
             return None;
         }
+        // Note that the throttle, if any, is owned by the store which is
+        // guaranteed to outlive this call into wasm.
+        if let Some(throttle) = unsafe { self.backtrace_throttle.as_ref() } {
+            if !throttle.should_capture() {
+                return None;
+            }
+        }
 
         Some(unsafe { Backtrace::new_with_trap_state(limits, self.unwinder, self, trap_pc_and_fp) })
     }
@@ -714,8 +744,15 @@ impl CallThreadState {
         // If the fault was at a location that was not marked as potentially
         // trapping, then that's a bug in Cranelift/Winch/etc. Don't try to
         // catch the trap and pretend this isn't wasm so the program likely
-        // aborts.
+        // aborts. Give the embedder's crash handler a chance to record what
+        // was executing first though.
         let Some(trap) = code.lookup_trap_code(text_offset) else {
+            #[cfg(all(has_native_signals, not(miri)))]
+            if let Some(reporter) = &self.crash_reporter {
+                // SAFETY: the store which this state was created for is
+                // blocked executing the wasm which faulted.
+                unsafe { reporter.report(regs.pc, faulting_addr) };
+            }
             return TrapTest::NotWasm;
         };
 
@@ -727,6 +764,34 @@ impl CallThreadState {
         }
     }
 
+    /// Attempts to handle a fault at `faulting_addr` by growing the stack of
+    /// the currently running continuation, see
+    /// `Config::wasmfx_initial_stack_size`.
+    ///
+    /// Returns `true` if the stack was grown, in which case the faulting
+    /// instruction can simply be executed again.
+    #[cfg(all(
+        has_native_signals,
+        unix,
+        any(not(feature = "wasmfx_baseline"), feature = "wasmfx_no_baseline")
+    ))]
+    pub(crate) fn grow_continuation_stack(&self, faulting_addr: usize) -> bool {
+        use crate::runtime::vm::continuation::stack_chain::StackChain;
+
+        let Some(chain) = self.callee_stack_chain else {
+            return false;
+        };
+        // SAFETY: the stack chain outlives this activation, and only the head
+        // of the chain, i.e., the continuation that is currently running, is
+        // inspected here.
+        unsafe {
+            match *(*chain).0.get() {
+                StackChain::Continuation(contref) => (*contref).stack.grow_to(faulting_addr),
+                StackChain::Absent | StackChain::MainStack(_) => false,
+            }
+        }
+    }
+
     pub(crate) fn take_jmp_buf(&self) -> *const u8 {
         self.jmp_buf.replace(ptr::null())
     }
@@ -1008,6 +1008,20 @@ pub struct VMRuntimeLimits {
     /// Used to find the end of a contiguous sequence of Wasm frames when
     /// walking the stack.
     pub last_wasm_entry_fp: UnsafeCell<usize>,
+
+    /// The map of edge counters which code compiled with coverage
+    /// instrumentation increments.
+    ///
+    /// Its length is `coverage_mask + 1`, a power of two. This is never null
+    /// in a store, see `Store::set_coverage_map`.
+    pub coverage_map: UnsafeCell<*mut u8>,
+
+    /// The mask applied to edge ids to index `coverage_map`.
+    pub coverage_mask: UnsafeCell<u32>,
+
+    /// The id of the last location recorded in `coverage_map`, shifted right
+    /// by one so that edges `A -> B` and `B -> A` are counted separately.
+    pub coverage_prev: UnsafeCell<u32>,
 }
 
 // The `VMRuntimeLimits` type is a pod-type with no destructor, and we don't
@@ -1026,6 +1040,9 @@ impl Default for VMRuntimeLimits {
             last_wasm_exit_fp: UnsafeCell::new(0),
             last_wasm_exit_pc: UnsafeCell::new(0),
             last_wasm_entry_fp: UnsafeCell::new(0),
+            coverage_map: UnsafeCell::new(ptr::null_mut()),
+            coverage_mask: UnsafeCell::new(0),
+            coverage_prev: UnsafeCell::new(0),
         }
     }
 }
@@ -1064,6 +1081,18 @@ mod test_vmruntime_limits {
             offset_of!(VMRuntimeLimits, last_wasm_entry_fp),
             usize::from(offsets.ptr.vmruntime_limits_last_wasm_entry_fp())
         );
+        assert_eq!(
+            offset_of!(VMRuntimeLimits, coverage_map),
+            usize::from(offsets.ptr.vmruntime_limits_coverage_map())
+        );
+        assert_eq!(
+            offset_of!(VMRuntimeLimits, coverage_mask),
+            usize::from(offsets.ptr.vmruntime_limits_coverage_mask())
+        );
+        assert_eq!(
+            offset_of!(VMRuntimeLimits, coverage_prev),
+            usize::from(offsets.ptr.vmruntime_limits_coverage_prev())
+        );
     }
 }
 
@@ -41,7 +41,9 @@ impl WasmType for component::Type {
             Self::Result(_) => WasmTypeKind::Result,
             Self::Flags(_) => WasmTypeKind::Flags,
 
-            Self::Own(_) | Self::Borrow(_) => WasmTypeKind::Unsupported,
+            Self::Own(_) | Self::Borrow(_) | Self::Future(_) | Self::Stream(_) => {
+                WasmTypeKind::Unsupported
+            }
         }
     }
 
@@ -135,6 +137,8 @@ impl WasmValue for component::Val {
             Self::Result(_) => WasmTypeKind::Result,
             Self::Flags(_) => WasmTypeKind::Flags,
             Self::Resource(_) => WasmTypeKind::Unsupported,
+            #[cfg(feature = "component-model-async")]
+            Self::Future(_) | Self::Stream(_) => WasmTypeKind::Unsupported,
         }
     }
 
@@ -384,6 +388,12 @@ fn ensure_type_val(ty: &component::Type, val: &component::Val) -> Result<(), Was
                 DisplayValue(val).to_string(),
             ))
         }
+        #[cfg(feature = "component-model-async")]
+        component::Val::Future(_) | component::Val::Stream(_) => {
+            return Err(WasmValueError::UnsupportedType(
+                DisplayValue(val).to_string(),
+            ))
+        }
 
         // Any leaf variant type has already had its kind compared above; nothing further to check.
         // Likewise, the component::Option(None) arm would have nothing left to check.
@@ -284,6 +284,10 @@ fn mismatch(expected: &WastVal<'_>, actual: &Val) -> Result<()> {
         Val::Result(..) => "result",
         Val::Flags(..) => "flags",
         Val::Resource(..) => "resource",
+        #[cfg(feature = "component-model-async")]
+        Val::Future(..) => "future",
+        #[cfg(feature = "component-model-async")]
+        Val::Stream(..) => "stream",
     };
     bail!("expected `{expected}` got `{actual}`")
 }
@@ -69,6 +69,18 @@ impl CompilerBuilder for Builder {
             bail!("Winch does not currently support generating native debug information");
         }
 
+        if tunables.global_watchpoints {
+            bail!("Winch does not currently support global watchpoints");
+        }
+
+        if tunables.single_step {
+            bail!("Winch does not currently support single-stepping");
+        }
+
+        if tunables.coverage_instrumentation {
+            bail!("Winch does not currently support coverage instrumentation");
+        }
+
         self.tunables = Some(tunables.clone());
         self.cranelift.set_tunables(tunables)?;
         Ok(())
@@ -134,6 +134,10 @@ impl wasmtime_environ::Compiler for Compiler {
             WasmFunctionInfo {
                 start_srcloc: func.metadata().address_map.start_srcloc,
                 stack_maps: Box::new([]),
+                // Winch doesn't report its frame sizes, so its functions'
+                // stack usage is never bounded.
+                frame_size: 0,
+                direct_calls: None,
             },
             Box::new(func),
         ))
@@ -175,6 +175,10 @@ pub struct Opts {
 
     /// Path to the `wasmtime` crate if it's not the default path.
     pub wasmtime_crate: Option<String>,
+
+    /// Whether or not to generate a `MockHost` implementation of the `Host`
+    /// trait for each imported interface, for use in tests.
+    pub mocks: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -1447,6 +1451,28 @@ impl Wasmtime {
             }
             uwriteln!(self.src, "}}");
         }
+
+        if self.opts.mocks {
+            let funcs = resolve.worlds[world]
+                .imports
+                .values()
+                .filter_map(|item| match item {
+                    WorldItem::Function(f) => Some(f),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            let resources = get_world_resources(resolve, world).collect::<Vec<_>>();
+            let mut generator = InterfaceGenerator::new(self, resolve);
+            generator.generate_mock(
+                &format!("Mock{world_camel}Imports"),
+                &format!("{world_camel}Imports"),
+                &funcs,
+                &resources,
+                &[],
+            );
+            let src = mem::take(&mut generator.src);
+            self.src.push_str(&src);
+        }
     }
 
     fn import_interface_paths(&self) -> Vec<(InterfaceId, String)> {
@@ -1673,8 +1699,8 @@ impl<'a> InterfaceGenerator<'a> {
             TypeDefKind::Result(r) => self.type_result(id, name, r, &ty.docs),
             TypeDefKind::List(t) => self.type_list(id, name, t, &ty.docs),
             TypeDefKind::Type(t) => self.type_alias(id, name, t, &ty.docs),
-            TypeDefKind::Future(_) => todo!("generate for future"),
-            TypeDefKind::Stream(_) => todo!("generate for stream"),
+            TypeDefKind::Future(t) => self.type_future(id, name, t.as_ref(), &ty.docs),
+            TypeDefKind::Stream(t) => self.type_stream(id, name, t, &ty.docs),
             TypeDefKind::ErrorContext => todo!("generate for error-context"),
             TypeDefKind::Handle(handle) => self.type_handle(id, name, handle, &ty.docs),
             TypeDefKind::Resource => self.type_resource(id, name, ty, &ty.docs),
@@ -2335,6 +2361,26 @@ impl<'a> InterfaceGenerator<'a> {
         }
     }
 
+    fn type_future(&mut self, id: TypeId, _name: &str, ty: Option<&Type>, docs: &Docs) {
+        for (name, _mode) in self.modes_of(id) {
+            self.rustdoc(docs);
+            self.push_str(&format!("pub type {name} = "));
+            self.print_future(ty);
+            self.push_str(";\n");
+            self.assert_type(id, &name);
+        }
+    }
+
+    fn type_stream(&mut self, id: TypeId, _name: &str, ty: &Type, docs: &Docs) {
+        for (name, _mode) in self.modes_of(id) {
+            self.rustdoc(docs);
+            self.push_str(&format!("pub type {name} = "));
+            self.print_stream(ty);
+            self.push_str(";\n");
+            self.assert_type(id, &name);
+        }
+    }
+
     fn print_result_ty(&mut self, results: &Results, mode: TypeMode) {
         match results {
             Results::Named(rs) => match rs.len() {
@@ -2477,6 +2523,7 @@ impl<'a> InterfaceGenerator<'a> {
         } else {
             ("", "Host".to_string())
         };
+        let has_foreign_conversions = !required_conversion_traits.is_empty();
         for ty in required_conversion_traits {
             uwrite!(host_bounds, " + {ty}");
         }
@@ -2580,7 +2627,7 @@ impl<'a> InterfaceGenerator<'a> {
                 }
                 uwriteln!(self.src, "}}");
             }
-            for (err_name, err_id) in errors_converted {
+            for (err_name, &err_id) in errors_converted.iter() {
                 uwriteln!(
                     self.src,
                     "fn convert_{err_snake}(&mut self, err: {root}{custom_name}) -> {wt}::Result<{err_camel}> {{
@@ -2593,6 +2640,202 @@ impl<'a> InterfaceGenerator<'a> {
             }
             uwriteln!(self.src, "}}");
         }
+
+        if self.generator.opts.mocks && !has_foreign_conversions {
+            self.generate_mock_host(id, &errors_converted);
+        }
+    }
+
+    /// Generates a `MockHost` type implementing this interface's `Host` trait
+    /// and the traits of its resources.
+    ///
+    /// Interfaces which require conversions of trappable errors defined in
+    /// other interfaces aren't supported as those require the mock to
+    /// implement the `Host` traits of those interfaces too.
+    fn generate_mock_host(&mut self, id: InterfaceId, errors_converted: &IndexMap<&str, TypeId>) {
+        let iface = &self.resolve.interfaces[id];
+        let conversions = errors_converted
+            .iter()
+            .map(|(name, id)| {
+                (
+                    name.to_string(),
+                    self.generator.trappable_errors[id].clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let resources = get_resources(self.resolve, id).collect::<Vec<_>>();
+        let funcs = iface.functions.values().collect::<Vec<_>>();
+        self.generate_mock("MockHost", "Host", &funcs, &resources, &conversions);
+    }
+
+    /// Generates a type named `mock` implementing the trait `host`, whose
+    /// required functions are the freestanding ones in `funcs`, along with
+    /// the traits of `resources`, whose functions are also in `funcs`.
+    ///
+    /// Each call made to the mock is recorded and forwarded to an optional,
+    /// user-provided closure to produce its return value. Resources dropped
+    /// without a closure configured for them are ignored, and the `convert_*`
+    /// functions of `conversions` are mocked like other functions.
+    fn generate_mock(
+        &mut self,
+        mock: &str,
+        host: &str,
+        funcs: &[&Function],
+        resources: &[(TypeId, &str)],
+        conversions: &[(String, String)],
+    ) {
+        let wt = self.generator.wasmtime_path();
+        let root = self.path_to_root();
+        let resolve = self.resolve;
+        let resource_funcs = |id: TypeId| {
+            funcs.iter().copied().filter(move |func| match func.kind {
+                FunctionKind::Freestanding => false,
+                FunctionKind::Method(resource)
+                | FunctionKind::Static(resource)
+                | FunctionKind::Constructor(resource) => resource == id,
+            })
+        };
+        let mock_field = |func: &Function| match func.kind {
+            FunctionKind::Freestanding => rust_function_name(func),
+            FunctionKind::Method(id) | FunctionKind::Static(id) | FunctionKind::Constructor(id) => {
+                let resource = resolve.types[id].name.as_ref().unwrap();
+                format!("{}_{}", resource.to_snake_case(), rust_function_name(func))
+            }
+        };
+
+        uwriteln!(
+            self.src,
+            "
+                /// A mock implementation of [`{host}`] for use in tests.
+                ///
+                /// Each call made to this mock is recorded in `calls` and the
+                /// return value is produced by the closure configured for the
+                /// function called, panicking if none was configured.
+                /// Resources are dropped by the closure configured for them,
+                /// if any, and otherwise the drop is only recorded.
+                #[derive(Default)]
+                pub struct {mock} {{
+                    /// The names of the functions called on this mock, in
+                    /// order.
+                    pub calls: {wt}::component::__internal::Vec<&'static str>,
+            "
+        );
+        for func in funcs.iter() {
+            uwriteln!(
+                self.src,
+                "/// The closure used to produce the return value of `{}`.",
+                func.name
+            );
+            uwrite!(
+                self.src,
+                "pub {}: Option<{wt}::component::__internal::Box<dyn FnMut(",
+                mock_field(func)
+            );
+            for (_, param) in func.params.iter() {
+                self.print_ty(param, TypeMode::Owned);
+                self.push_str(",");
+            }
+            self.push_str(") -> ");
+            self.print_host_result_ty(func);
+            self.push_str(" + Send>>,\n");
+        }
+        for (_, name) in resources {
+            uwriteln!(
+                self.src,
+                "
+                    /// The closure used to drop `{name}` resources.
+                    pub drop_{snake}: Option<{wt}::component::__internal::Box<dyn FnMut(
+                        {wt}::component::Resource<{camel}>,
+                    ) -> {wt}::Result<()> + Send>>,
+                ",
+                snake = name.to_snake_case(),
+                camel = name.to_upper_camel_case(),
+            );
+        }
+        for (err_name, custom_name) in conversions {
+            uwriteln!(
+                self.src,
+                "
+                    /// The closure used to convert `{custom_name}` errors.
+                    pub convert_{err_snake}: Option<{wt}::component::__internal::Box<dyn FnMut(
+                        {root}{custom_name},
+                    ) -> {wt}::Result<{err_camel}> + Send>>,
+                ",
+                err_snake = err_name.to_snake_case(),
+                err_camel = err_name.to_upper_camel_case(),
+            );
+        }
+        uwriteln!(self.src, "}}");
+
+        uwriteln!(self.src, "impl {host} for {mock} {{");
+        for func in funcs.iter() {
+            if let FunctionKind::Freestanding = func.kind {
+                self.generate_mock_function(func, &mock_field(func));
+            }
+        }
+        for (err_name, custom_name) in conversions {
+            let err_snake = err_name.to_snake_case();
+            uwriteln!(
+                self.src,
+                "fn convert_{err_snake}(&mut self, err: {root}{custom_name}) -> {wt}::Result<{err_camel}> {{
+                    self.calls.push(\"convert-{err_name}\");
+                    match &mut self.convert_{err_snake} {{
+                        Some(f) => f(err),
+                        None => panic!(\"no mock configured for `convert-{err_name}`\"),
+                    }}
+                }}",
+                err_camel = err_name.to_upper_camel_case(),
+            );
+        }
+        uwriteln!(self.src, "}}");
+
+        for (id, name) in resources {
+            let camel = name.to_upper_camel_case();
+            let snake = name.to_snake_case();
+            uwriteln!(self.src, "impl Host{camel} for {mock} {{");
+            for func in resource_funcs(*id) {
+                self.generate_mock_function(func, &mock_field(func));
+            }
+            if self.generator.opts.async_.is_drop_async(name) {
+                uwrite!(self.src, "async ");
+            }
+            uwriteln!(
+                self.src,
+                "fn drop(&mut self, rep: {wt}::component::Resource<{camel}>) -> {wt}::Result<()> {{
+                    self.calls.push(\"[resource-drop]{name}\");
+                    match &mut self.drop_{snake} {{
+                        Some(f) => f(rep),
+                        None => Ok(()),
+                    }}
+                }}"
+            );
+            uwriteln!(self.src, "}}");
+        }
+    }
+
+    /// Generates the implementation of `func` for a mock generated by
+    /// `generate_mock`, which calls the closure in its field `field`.
+    fn generate_mock_function(&mut self, func: &Function, field: &str) {
+        self.generate_function_trait_sig(func);
+        uwrite!(
+            self.src,
+            "{{
+                self.calls.push(\"{}\");
+                match &mut self.{field} {{
+                    Some(f) => f(",
+            func.name,
+        );
+        for (param, _) in func.params.iter() {
+            uwrite!(self.src, "{},", to_rust_ident(param));
+        }
+        uwriteln!(
+            self.src,
+            "),
+                    None => panic!(\"no mock configured for `{}`\"),
+                }}
+            }}",
+            func.name,
+        );
     }
 
     fn generate_add_function_to_linker(&mut self, owner: TypeOwner, func: &Function, linker: &str) {
@@ -2778,7 +3021,6 @@ impl<'a> InterfaceGenerator<'a> {
     }
 
     fn generate_function_trait_sig(&mut self, func: &Function) {
-        let wt = self.generator.wasmtime_path();
         self.rustdoc(&func.docs);
 
         if self.generator.opts.async_.is_import_async(&func.name) {
@@ -2796,7 +3038,12 @@ impl<'a> InterfaceGenerator<'a> {
         }
         self.push_str(")");
         self.push_str(" -> ");
+        self.print_host_result_ty(func);
+    }
 
+    /// Prints the return type of `func` in the `Host` trait.
+    fn print_host_result_ty(&mut self, func: &Function) {
+        let wt = self.generator.wasmtime_path();
         if !self.generator.opts.trappable_imports.can_trap(func) {
             self.print_result_ty(&func.results, TypeMode::Owned);
         } else if let Some((r, _id, error_typename)) = self.special_case_trappable_error(func) {
@@ -3272,8 +3519,7 @@ fn type_contains_lists(ty: Type, resolve: &Resolve) -> bool {
                 .iter()
                 .any(|case| option_type_contains_lists(case.ty, resolve)),
             TypeDefKind::Type(ty) => type_contains_lists(*ty, resolve),
-            TypeDefKind::Future(_) => todo!(),
-            TypeDefKind::Stream(_) => todo!(),
+            TypeDefKind::Future(_) | TypeDefKind::Stream(_) => false,
             TypeDefKind::List(_) => true,
         },
 
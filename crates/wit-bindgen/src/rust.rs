@@ -166,8 +166,8 @@ pub trait RustGenerator<'a> {
             TypeDefKind::Enum(_) => {
                 panic!("unsupported anonymous type reference: enum")
             }
-            TypeDefKind::Future(_) => todo!(),
-            TypeDefKind::Stream(_) => todo!(),
+            TypeDefKind::Future(ty) => self.print_future(ty.as_ref()),
+            TypeDefKind::Stream(ty) => self.print_stream(ty),
             TypeDefKind::ErrorContext => todo!(),
 
             TypeDefKind::Handle(handle) => {
@@ -190,6 +190,20 @@ pub trait RustGenerator<'a> {
         self.push_str(name);
     }
 
+    fn print_future(&mut self, ty: Option<&Type>) {
+        let wt = self.wasmtime_path();
+        self.push_str(&format!("{wt}::component::FutureReader<"));
+        self.print_optional_ty(ty, TypeMode::Owned);
+        self.push_str(">");
+    }
+
+    fn print_stream(&mut self, ty: &Type) {
+        let wt = self.wasmtime_path();
+        self.push_str(&format!("{wt}::component::StreamReader<"));
+        self.print_ty(ty, TypeMode::Owned);
+        self.push_str(">");
+    }
+
     fn print_list(&mut self, ty: &Type, mode: TypeMode) {
         let next_mode = if matches!(self.ownership(), Ownership::Owning) {
             TypeMode::Owned
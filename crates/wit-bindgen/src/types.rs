@@ -158,8 +158,9 @@ impl Types {
                 info = self.optional_type_info(resolve, r.ok.as_ref());
                 info |= self.optional_type_info(resolve, r.err.as_ref());
             }
-            TypeDefKind::Future(_) => todo!(),
-            TypeDefKind::Stream(_) => todo!(),
+            // Payloads of futures and streams are always owned by the reader
+            // on the host, so they only contribute a handle here.
+            TypeDefKind::Future(_) | TypeDefKind::Stream(_) => info.has_handle = true,
             TypeDefKind::ErrorContext => todo!(),
             TypeDefKind::Handle(_) => info.has_handle = true,
             TypeDefKind::Resource => {}
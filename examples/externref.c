@@ -32,6 +32,8 @@ mkdir build && cd build && cmake .. && \
 static void exit_with_error(const char *message, wasmtime_error_t *error,
                             wasm_trap_t *trap);
 
+static void increment_finalized(void *data) { (*(int *)data)++; }
+
 int main() {
   bool ok = true;
   // Create a new configuration with Wasm reference types enabled.
@@ -185,6 +187,35 @@ int main() {
   printf("GCing within the store...\n");
   wasmtime_context_gc(context);
 
+  // An `externref` with a deferred finalizer, owned by a root scope, is
+  // finalized by the first GC after its scope is exited.
+  printf("Finalizing a scoped `externref`...\n");
+  {
+    static int finalized = 0;
+    size_t depth = wasmtime_context_root_scope_push(context);
+    wasmtime_externref_t scoped;
+    ok = wasmtime_externref_new_with_finalizer(context, &finalized,
+                                               increment_finalized, &scoped);
+    assert(ok);
+    assert(wasmtime_externref_data(context, &scoped) == &finalized);
+
+    // References created within a nested scope are unrooted with it, even if
+    // some were already unrooted explicitly.
+    size_t nested = wasmtime_context_root_scope_push(context);
+    assert(nested == depth + 1);
+    wasmtime_externref_t clone;
+    wasmtime_externref_clone(context, &scoped, &clone);
+    wasmtime_externref_unroot(context, &clone);
+    wasmtime_externref_clone(context, &scoped, &clone);
+
+    wasmtime_context_gc(context);
+    assert(finalized == 0);
+    wasmtime_context_root_scope_pop(context, depth);
+    assert(finalized == 0);
+    wasmtime_context_gc(context);
+    assert(finalized == 1);
+  }
+
   // Clean up after ourselves at this point
   printf("All finished!\n");
 
@@ -62,6 +62,10 @@ enum Subcommand {
     #[cfg(feature = "cache")]
     Config(wasmtime_cli::commands::ConfigCommand),
 
+    /// Lists the sources of nondeterminism a WebAssembly module can observe.
+    #[cfg(feature = "audit")]
+    Audit(wasmtime_cli::commands::AuditCommand),
+
     /// Compiles a WebAssembly module.
     #[cfg(feature = "compile")]
     Compile(wasmtime_cli::commands::CompileCommand),
@@ -102,6 +106,9 @@ impl Wasmtime {
             #[cfg(feature = "cache")]
             Subcommand::Config(c) => c.execute(),
 
+            #[cfg(feature = "audit")]
+            Subcommand::Audit(c) => c.execute(),
+
             #[cfg(feature = "compile")]
             Subcommand::Compile(c) => c.execute(),
 
@@ -25,6 +25,11 @@ mod config;
 #[cfg(feature = "cache")]
 pub use self::config::*;
 
+#[cfg(feature = "audit")]
+mod audit;
+#[cfg(feature = "audit")]
+pub use self::audit::*;
+
 #[cfg(feature = "compile")]
 mod compile;
 #[cfg(feature = "compile")]
@@ -0,0 +1,81 @@
+//! The module that implements the `wasmtime audit` command.
+
+use anyhow::{bail, Result};
+use clap::Parser;
+use std::path::PathBuf;
+use wasmtime::{CodeBuilder, CodeHint, Engine, Linker};
+use wasmtime_cli_flags::CommonOptions;
+use wasmtime_wasi::preview1::WasiP1Ctx;
+
+const AFTER_HELP: &str = "Imports are resolved against a linker which defines WASI preview1, and\n\
+        compilation settings such as `-Wrelaxed-simd-deterministic` and\n\
+        `-Wnan-canonicalization` are taken into account.\n\
+        \n\
+        Usage examples:\n\
+        \n\
+        Listing the sources of nondeterminism of a module:\n\
+        \n  \
+        wasmtime audit example.wasm\n\
+        \n\
+        Producing a machine-readable report:\n\
+        \n  \
+        wasmtime audit --json example.wasm\n";
+
+/// Lists the sources of nondeterminism a WebAssembly module can observe.
+#[derive(Parser)]
+#[command(
+    version,
+    after_help = AFTER_HELP,
+)]
+pub struct AuditCommand {
+    #[command(flatten)]
+    #[allow(missing_docs)]
+    pub common: CommonOptions,
+
+    /// Switch output format to JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// Exit with a nonzero status if any sources of nondeterminism are found
+    #[arg(long)]
+    pub deny: bool,
+
+    /// The path of the WebAssembly module to audit
+    #[arg(index = 1, value_name = "MODULE")]
+    pub module: PathBuf,
+}
+
+impl AuditCommand {
+    /// Executes the command.
+    pub fn execute(mut self) -> Result<()> {
+        self.common.init_logging()?;
+
+        let config = self.common.config(None)?;
+        let engine = Engine::new(&config)?;
+
+        let mut linker = Linker::<WasiP1Ctx>::new(&engine);
+        wasmtime_wasi::preview1::add_to_linker_sync(&mut linker, |t| t)?;
+
+        let mut code = CodeBuilder::new(&engine);
+        code.wasm_binary_or_text_file(&self.module)?;
+        if let Some(CodeHint::Component) = code.hint() {
+            bail!("only core wasm modules can be audited");
+        }
+        let report = code.audit_determinism(&linker)?;
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            println!("{report}");
+        }
+
+        if self.deny && !report.is_deterministic() {
+            bail!(
+                "found {} sources of nondeterminism in '{}'",
+                report.sources().len(),
+                self.module.display()
+            );
+        }
+        Ok(())
+    }
+}
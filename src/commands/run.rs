@@ -687,7 +687,7 @@ impl RunCommand {
                     }
                     #[cfg(feature = "component-model")]
                     CliLinker::Component(linker) => {
-                        wasmtime_wasi_nn::wit::add_to_linker(linker, |h: &mut Host| {
+                        wasmtime_wasi_nn::wit::add_to_linker_async(linker, |h: &mut Host| {
                             let preview2_ctx =
                                 h.preview2_ctx.as_mut().expect("wasip2 is not configured");
                             let preview2_ctx = Arc::get_mut(preview2_ctx)
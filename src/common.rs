@@ -317,6 +317,10 @@ impl RunCommon {
         if let Some(enable) = self.common.wasi.udp {
             builder.allow_udp(enable);
         }
+        if self.common.wasi.udp_multicast == Some(true) {
+            builder.allow_udp_multicast(true);
+            builder.allow_udp_reuse_address(true);
+        }
 
         Ok(())
     }
@@ -339,6 +343,7 @@ impl RunCommon {
         let mut options = LinkOptions::default();
         options.cli_exit_with_code(self.common.wasi.cli_exit_with_code.unwrap_or(false));
         options.network_error_code(self.common.wasi.network_error_code.unwrap_or(false));
+        options.udp_multicast(self.common.wasi.udp_multicast.unwrap_or(false));
         options
     }
 }
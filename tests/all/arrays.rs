@@ -40,6 +40,42 @@ fn array_new_with_elems() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn array_new_with_len() -> Result<()> {
+    let mut store = gc_store()?;
+    let array_ty = ArrayType::new(
+        store.engine(),
+        FieldType::new(Mutability::Var, StorageType::ValType(ValType::ANYREF)),
+    );
+    let array = ArrayRef::new_with_len(&mut store, &array_ty, 2)?;
+    assert!(array.matches_ty(&store, &array_ty)?);
+    assert_eq!(array.len(&store)?, 2);
+    assert!(array.get(&mut store, 1)?.unwrap_anyref().is_none());
+    let elem = array.to_anyref();
+    array.set(&mut store, 1, elem.into())?;
+    assert!(array.get(&mut store, 1)?.unwrap_anyref().is_some());
+
+    let array_ty = ArrayType::new(
+        store.engine(),
+        FieldType::new(Mutability::Const, StorageType::I8),
+    );
+    let array = ArrayRef::new_with_len(&mut store, &array_ty, 3)?;
+    for i in 0..3 {
+        assert_eq!(array.get(&mut store, i)?.unwrap_i32(), 0);
+    }
+
+    // Non-nullable references have no default value.
+    let array_ty = ArrayType::new(
+        store.engine(),
+        FieldType::new(
+            Mutability::Const,
+            StorageType::ValType(RefType::new(false, HeapType::Any).into()),
+        ),
+    );
+    assert!(ArrayRef::new_with_len(&mut store, &array_ty, 1).is_err());
+    Ok(())
+}
+
 #[test]
 fn array_new_unrooted_initial_elem() -> Result<()> {
     let mut store = gc_store()?;
@@ -313,6 +313,51 @@ async fn iloop_with_fuel() {
     assert_eq!(pending, 99);
 }
 
+#[tokio::test]
+async fn poll_budget() -> Result<()> {
+    let mut store = async_store();
+    let mut linker = Linker::new(store.engine());
+    linker.func_wrap_async("", "ready", |_caller, _: ()| Box::new(async { Ok(()) }))?;
+    let module = Module::new(
+        store.engine(),
+        r#"
+            (module
+                (import "" "ready" (func $ready))
+                (func (export "run")
+                    (local i32)
+                    (loop
+                        call $ready
+                        (local.tee 0 (i32.add (local.get 0) (i32.const 1)))
+                        (br_if 0 (i32.lt_u (i32.const 100)))))
+            )
+        "#,
+    )?;
+    let instance = linker.instantiate_async(&mut store, &module).await?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+
+    // Without a budget every ready host call completes in-line.
+    let (result, pending) = CountPending::new(Box::pin(run.call_async(&mut store, ()))).await;
+    result?;
+    assert_eq!(pending, 0);
+    let stats = store.async_poll_stats();
+    assert_eq!(stats.ready_calls, 100);
+    assert_eq!(stats.budget_yields, 0);
+    assert_eq!(stats.longest_ready_streak, 100);
+
+    // With a budget execution yields after every 10 ready host calls.
+    store.async_poll_budget(Some(10))?;
+    let (result, pending) = CountPending::new(Box::pin(run.call_async(&mut store, ()))).await;
+    result?;
+    assert_eq!(pending, 10);
+    let stats = store.async_poll_stats();
+    assert_eq!(stats.ready_calls, 200);
+    assert_eq!(stats.budget_yields, 10);
+    assert_eq!(stats.pending_yields, 0);
+
+    assert!(store.async_poll_budget(Some(0)).is_err());
+    Ok(())
+}
+
 #[tokio::test]
 async fn fuel_eventually_finishes() {
     let engine = Engine::new(Config::new().async_support(true).consume_fuel(true)).unwrap();
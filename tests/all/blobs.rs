@@ -0,0 +1,153 @@
+use wasmtime::*;
+
+#[test]
+fn dedup_and_quotas() -> Result<()> {
+    let engine = Engine::default();
+    let blobs = BlobStoreBuilder::new().tenant_quota(10).build(&engine);
+
+    let mut a1 = blobs.context("a");
+    let mut a2 = blobs.context("a");
+    let mut b = blobs.context("b");
+
+    let id = a1.intern(b"123456")?;
+    assert_eq!(a2.intern(b"123456")?, id);
+    assert_eq!(b.intern(b"123456")?, id);
+    assert_eq!(blobs.len(), 1);
+    assert_eq!(blobs.size(), 6);
+
+    // A blob is only charged once to each tenant.
+    assert_eq!(blobs.tenant_size("a"), 6);
+    assert_eq!(blobs.tenant_size("b"), 6);
+
+    // But distinct blobs of a tenant's contexts add up.
+    let err = a2.intern(b"78901").unwrap_err();
+    assert_eq!(ErrorKind::of(&err), Some(ErrorKind::ResourceLimit));
+    b.intern(b"7890")?;
+    assert_eq!(blobs.tenant_size("b"), 10);
+
+    drop(a1);
+    assert_eq!(blobs.tenant_size("a"), 6);
+    drop(a2);
+    assert_eq!(blobs.tenant_size("a"), 0);
+
+    // Tenants can acquire blobs they interned before.
+    let mut a = blobs.context("a");
+    assert!(a.get(id).is_none());
+    a.acquire(id)?;
+    assert_eq!(&a.get(id).unwrap()[..], b"123456");
+    assert!(a.release(id));
+    assert!(!a.release(id));
+    assert!(a.acquire(BlobId::from_u64(100)).is_err());
+
+    // But other tenants can only acquire blobs shared with them.
+    let mut c = blobs.context("c");
+    assert!(c.acquire(id).is_err());
+    assert!(c.share(id, "c").is_err());
+    b.share(id, "c")?;
+    c.acquire(id)?;
+    assert_eq!(&c.get(id).unwrap()[..], b"123456");
+    Ok(())
+}
+
+#[test]
+fn eviction() -> Result<()> {
+    let engine = Engine::default();
+    let blobs = BlobStoreBuilder::new().capacity(8).build(&engine);
+    let mut cx = blobs.context("a");
+
+    let one = cx.intern(b"1111")?;
+    let two = cx.intern(b"2222")?;
+    assert!(cx.intern(b"3333").is_err());
+
+    // Unreferenced blobs are kept until space is needed, least recently used
+    // first.
+    cx.release(one);
+    cx.release(two);
+    assert_eq!(blobs.len(), 2);
+    assert_eq!(cx.intern(b"2222")?, two);
+    cx.release(two);
+    let three = cx.intern(b"3333")?;
+    assert_eq!(blobs.len(), 2);
+    assert!(cx.acquire(one).is_err());
+    assert!(cx.get(three).is_some());
+
+    let blobs = BlobStoreBuilder::new()
+        .eviction(BlobEviction::OnRelease)
+        .build(&engine);
+    let mut cx = blobs.context("a");
+    let id = cx.intern(b"1111")?;
+    cx.release(id);
+    assert!(blobs.is_empty());
+    assert_ne!(cx.intern(b"1111")?, id);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn guest_interface() -> Result<()> {
+    let engine = Engine::default();
+    let blobs = BlobStoreBuilder::new().build(&engine);
+    let mut linker = Linker::<BlobContext>::new(&engine);
+    blobs.add_to_linker(&mut linker, |cx| cx)?;
+
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "wasmtime:blobs" "intern" (func $intern (param i32 i32) (result i64)))
+                (import "wasmtime:blobs" "acquire" (func $acquire (param i64) (result i32)))
+                (import "wasmtime:blobs" "size" (func $size (param i64) (result i64)))
+                (import "wasmtime:blobs" "read" (func $read (param i64 i64 i32 i32) (result i64)))
+                (import "wasmtime:blobs" "release" (func $release (param i64) (result i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 0) "hello, world")
+                (func (export "intern") (result i64)
+                    (call $intern (i32.const 0) (i32.const 12)))
+                (func (export "acquire") (param i64) (result i32)
+                    (call $acquire (local.get 0)))
+                (func (export "size") (param i64) (result i64)
+                    (call $size (local.get 0)))
+                (func (export "read") (param i64 i64) (result i64)
+                    (call $read (local.get 0) (local.get 1) (i32.const 100) (i32.const 5)))
+                (func (export "release") (param i64) (result i32)
+                    (call $release (local.get 0)))
+            )
+        "#,
+    )?;
+
+    let mut store1 = Store::new(&engine, blobs.context("a"));
+    let instance1 = linker.instantiate(&mut store1, &module)?;
+    let intern = instance1.get_typed_func::<(), i64>(&mut store1, "intern")?;
+    let id = intern.call(&mut store1, ())?;
+    assert_eq!(
+        store1.data().get(BlobId::from_u64(id as u64)).unwrap()[..],
+        *b"hello, world"
+    );
+
+    // Another store reads the blob by its identifier once it's shared with
+    // its tenant.
+    let mut store2 = Store::new(&engine, blobs.context("b"));
+    let instance2 = linker.instantiate(&mut store2, &module)?;
+    let acquire = instance2.get_typed_func::<i64, i32>(&mut store2, "acquire")?;
+    let size = instance2.get_typed_func::<i64, i64>(&mut store2, "size")?;
+    let read = instance2.get_typed_func::<(i64, i64), i64>(&mut store2, "read")?;
+    let release = instance2.get_typed_func::<i64, i32>(&mut store2, "release")?;
+    assert_eq!(size.call(&mut store2, id)?, -1);
+    assert_eq!(acquire.call(&mut store2, id)?, 0);
+    store1.data().share(BlobId::from_u64(id as u64), "b")?;
+    assert_eq!(acquire.call(&mut store2, id)?, 1);
+    assert_eq!(size.call(&mut store2, id)?, 12);
+    assert_eq!(read.call(&mut store2, (id, 7))?, 5);
+    let memory = instance2.get_memory(&mut store2, "memory").unwrap();
+    assert_eq!(&memory.data(&store2)[100..105], b"world");
+    assert_eq!(read.call(&mut store2, (id, 10))?, 2);
+    assert_eq!(read.call(&mut store2, (id, 13))?, -1);
+    assert_eq!(release.call(&mut store2, id)?, 1);
+    assert_eq!(release.call(&mut store2, id)?, 0);
+    assert_eq!(acquire.call(&mut store2, 12345)?, 0);
+
+    // Dropping a store releases its references.
+    drop(store1);
+    assert_eq!(blobs.tenant_size("a"), 0);
+    Ok(())
+}
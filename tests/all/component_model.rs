@@ -9,6 +9,8 @@ mod aot;
 mod r#async;
 mod bindgen;
 mod call_hook;
+mod compose;
+mod core_imports;
 mod dynamic;
 mod func;
 mod import;
@@ -18,6 +20,7 @@ mod macros;
 mod nested;
 mod post_return;
 mod resources;
+mod streams;
 mod strings;
 
 #[test]
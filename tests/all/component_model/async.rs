@@ -3,7 +3,7 @@
 use crate::async_functions::{execute_across_threads, PollOnce};
 use anyhow::Result;
 use wasmtime::component::*;
-use wasmtime::{Engine, Store, StoreContextMut, Trap};
+use wasmtime::{Config, Engine, Store, StoreContextMut, Trap};
 use wasmtime_component_util::REALLOC_AND_FREE;
 
 /// This is super::func::thunks, except with an async store.
@@ -311,3 +311,75 @@ async fn drop_resource_async() -> Result<()> {
 
     Ok(())
 }
+
+/// Cancel a call with `call_async_cancellable` and make sure that the store
+/// remains usable afterwards.
+#[tokio::test]
+async fn cancel_call() -> Result<()> {
+    struct MyResource;
+
+    let component = r#"
+        (component
+            (import "t" (type $t (sub resource)))
+
+            (core module $m
+                (func (export "spin") (param i32)
+                    (loop br 0))
+                (func (export "ok"))
+            )
+            (core instance $i (instantiate $m))
+            (func (export "spin") (param "x" (borrow $t))
+                (canon lift (core func $i "spin"))
+            )
+            (func (export "ok")
+                (canon lift (core func $i "ok"))
+            )
+        )
+    "#;
+
+    let mut config = Config::new();
+    config.async_support(true);
+    config.wasm_component_model(true);
+    config.epoch_interruption(true);
+    let engine = Engine::new(&config)?;
+    let component = Component::new(&engine, component)?;
+    let mut linker = Linker::new(&engine);
+    linker
+        .root()
+        .resource("t", ResourceType::host::<MyResource>(), |_, _| Ok(()))?;
+
+    let mut store = Store::new(&engine, ());
+    store.set_epoch_deadline(1);
+    store.epoch_deadline_async_yield_and_update(1);
+
+    let instance = linker.instantiate_async(&mut store, &component).await?;
+    let spin = instance.get_typed_func::<(ResourceAny,), ()>(&mut store, "spin")?;
+    let resource =
+        ResourceAny::try_from_resource(Resource::<MyResource>::new_own(100), &mut store)?;
+
+    // The call yields at each epoch deadline until it's cancelled.
+    let (handle, call) = spin.call_async_cancellable(&mut store, (resource,));
+    engine.increment_epoch();
+    let call = match PollOnce::new(Box::pin(call)).await {
+        Ok(_) => panic!("call should not have finished"),
+        Err(call) => call,
+    };
+    assert!(!handle.is_cancelled());
+    handle.cancel();
+    engine.increment_epoch();
+    let err = call.await.unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::Interrupt);
+
+    // The instance is poisoned, but the resource lent to it can be dropped
+    // and the store can still be used.
+    let err = spin.call_async(&mut store, (resource,)).await.unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::CannotEnterComponent);
+    resource.resource_drop_async(&mut store).await?;
+
+    let instance = linker.instantiate_async(&mut store, &component).await?;
+    let ok = instance.get_typed_func::<(), ()>(&mut store, "ok")?;
+    ok.call_async(&mut store, ()).await?;
+    ok.post_return_async(&mut store).await?;
+
+    Ok(())
+}
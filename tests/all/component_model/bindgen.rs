@@ -121,6 +121,151 @@ mod one_import {
     }
 }
 
+mod mocks {
+    use super::*;
+
+    wasmtime::component::bindgen!({
+        inline: "
+            package foo:foo;
+
+            world mocked {
+                import foo: interface {
+                    ping: func();
+                    double: func(x: u32) -> u32;
+                }
+
+                export run: func(x: u32) -> u32;
+            }
+        ",
+        mocks: true,
+    });
+
+    #[test]
+    fn run() -> Result<()> {
+        let engine = engine();
+
+        let component = Component::new(
+            &engine,
+            r#"
+                (component
+                    (import "foo" (instance $i
+                        (export "ping" (func))
+                        (export "double" (func (param "x" u32) (result u32)))
+                    ))
+                    (core func $ping (canon lower (func $i "ping")))
+                    (core func $double (canon lower (func $i "double")))
+                    (core module $m
+                        (import "" "ping" (func $ping))
+                        (import "" "double" (func $double (param i32) (result i32)))
+                        (func (export "run") (param i32) (result i32)
+                            call $ping
+                            local.get 0
+                            call $double
+                            call $double)
+                    )
+                    (core instance $i (instantiate $m
+                        (with "" (instance
+                            (export "ping" (func $ping))
+                            (export "double" (func $double))
+                        ))
+                    ))
+
+                    (func (export "run") (param "x" u32) (result u32)
+                        (canon lift (core func $i "run")))
+                )
+            "#,
+        )?;
+
+        let mut linker = Linker::new(&engine);
+        foo::add_to_linker(&mut linker, |f: &mut foo::MockHost| f)?;
+        let mut store = Store::new(&engine, foo::MockHost::default());
+        store.data_mut().ping = Some(Box::new(|| ()));
+        store.data_mut().double = Some(Box::new(|x| x * 2));
+        let mocked = Mocked::instantiate(&mut store, &component, &linker)?;
+        assert_eq!(mocked.call_run(&mut store, 3)?, 12);
+        assert_eq!(store.data().calls, ["ping", "double", "double"]);
+        Ok(())
+    }
+}
+
+mod mocks_with_resources {
+    use super::*;
+    use wasmtime::component::Resource;
+
+    wasmtime::component::bindgen!({
+        inline: "
+            package foo:foo;
+
+            world resources {
+                resource x {
+                    constructor();
+                }
+
+                export y: func(x: x);
+            }
+        ",
+        mocks: true,
+    });
+
+    #[test]
+    fn run() -> Result<()> {
+        let engine = engine();
+
+        let component = Component::new(
+            &engine,
+            r#"
+                (component
+                    (import "x" (type $x (sub resource)))
+                    (import "[constructor]x" (func $ctor (result (own $x))))
+
+                    (core func $dtor (canon resource.drop $x))
+                    (core func $ctor (canon lower (func $ctor)))
+
+                    (core module $m
+                        (import "" "ctor" (func $ctor (result i32)))
+                        (import "" "dtor" (func $dtor (param i32)))
+
+                        (func (export "x") (param i32)
+                            (call $dtor (local.get 0))
+                            (call $dtor (call $ctor))
+                        )
+                    )
+                    (core instance $i (instantiate $m
+                        (with "" (instance
+                            (export "ctor" (func $ctor))
+                            (export "dtor" (func $dtor))
+                        ))
+                    ))
+                    (func (export "y") (param "x" (own $x))
+                        (canon lift (core func $i "x")))
+                )
+            "#,
+        )?;
+
+        let mut linker = Linker::new(&engine);
+        Resources::add_to_linker(&mut linker, |f: &mut MockResourcesImports| f)?;
+        let mut store = Store::new(&engine, MockResourcesImports::default());
+        store.data_mut().x_new = Some(Box::new(|| Resource::new_own(80)));
+        let resources = Resources::instantiate(&mut store, &component, &linker)?;
+
+        // Without a closure drops are only recorded.
+        resources.call_y(&mut store, Resource::new_own(40))?;
+        assert_eq!(
+            store.data().calls,
+            ["[resource-drop]x", "[constructor]x", "[resource-drop]x"]
+        );
+
+        store.data_mut().calls.clear();
+        store.data_mut().drop_x = Some(Box::new(|x| {
+            assert!(x.rep() == 40 || x.rep() == 80);
+            Ok(())
+        }));
+        resources.call_y(&mut store, Resource::new_own(40))?;
+        assert_eq!(store.data().calls.len(), 3);
+        Ok(())
+    }
+}
+
 mod resources_at_world_level {
     use super::*;
     use wasmtime::component::Resource;
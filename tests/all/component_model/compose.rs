@@ -0,0 +1,179 @@
+use anyhow::Result;
+use std::sync::{Arc, Mutex};
+use wasmtime::component::*;
+use wasmtime::Store;
+
+const MATH: &str = r#"
+    (component
+        (core module $m
+            (func (export "add") (param i32 i32) (result i32)
+                (i32.add (local.get 0) (local.get 1)))
+            (func (export "neg") (param i32) (result i32)
+                (i32.sub (i32.const 0) (local.get 0)))
+        )
+        (core instance $i (instantiate $m))
+        (func $add (param "a" s32) (param "b" s32) (result s32)
+            (canon lift (core func $i "add")))
+        (func $neg (param "a" s32) (result s32)
+            (canon lift (core func $i "neg")))
+        (instance $math
+            (export "add" (func $add))
+            (export "neg" (func $neg))
+        )
+        (export "math" (instance $math))
+        (export "neg" (func $neg))
+    )
+"#;
+
+const APP: &str = r#"
+    (component
+        (import "calc" (instance $calc
+            (export "add" (func (param "a" s32) (param "b" s32) (result s32)))
+        ))
+        (import "log" (func $log (param "x" s32)))
+        (alias export $calc "add" (func $add))
+        (core func $add (canon lower (func $add)))
+        (core func $log (canon lower (func $log)))
+        (core module $m
+            (import "" "add" (func $add (param i32 i32) (result i32)))
+            (import "" "log" (func $log (param i32)))
+            (func (export "run") (param i32) (result i32)
+                (local $r i32)
+                (local.set $r (call $add (local.get 0) (i32.const 1)))
+                (call $log (local.get $r))
+                local.get $r)
+        )
+        (core instance $i (instantiate $m
+            (with "" (instance
+                (export "add" (func $add))
+                (export "log" (func $log))
+            ))
+        ))
+        (func (export "run") (param "x" s32) (result s32)
+            (canon lift (core func $i "run")))
+    )
+"#;
+
+// Re-exports its import `f` as `g`.
+const FORWARD: &str = r#"
+    (component
+        (import "f" (func $f (param "a" s32) (result s32)))
+        (export "g" (func $f))
+    )
+"#;
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn compose_instances_and_host_imports() -> Result<()> {
+    let engine = super::engine();
+    let math = Component::new(&engine, MATH)?;
+    let app = Component::new(&engine, APP)?;
+
+    let composed = Compose::new(&engine)
+        .component("math", &math)?
+        .component("app", &app)?
+        .wire("app", "calc", "math", "math")?
+        .build("app")?;
+    assert!(composed
+        .root()
+        .component_type()
+        .get_export(&engine, "run")
+        .is_some());
+
+    // `log` isn't wired, so it's provided by the linker.
+    let logged = Arc::new(Mutex::new(Vec::new()));
+    let mut linker = Linker::new(&engine);
+    linker.root().func_wrap("log", {
+        let logged = logged.clone();
+        move |_, (x,): (i32,)| {
+            logged.lock().unwrap().push(x);
+            Ok(())
+        }
+    })?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = composed.instantiate(&mut store, &linker)?;
+    let run = instance.get_typed_func::<(i32,), (i32,)>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, (41,))?, (42,));
+    run.post_return(&mut store)?;
+    assert_eq!(run.call(&mut store, (-2,))?, (-1,));
+    run.post_return(&mut store)?;
+    assert_eq!(*logged.lock().unwrap(), [42, -1]);
+
+    // Without a definition of `log` instantiation fails.
+    let mut store = Store::new(&engine, ());
+    assert!(composed
+        .instantiate(&mut store, &Linker::new(&engine))
+        .is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn compose_chain() -> Result<()> {
+    let engine = super::engine();
+    let math = Component::new(&engine, MATH)?;
+    let forward = Component::new(&engine, FORWARD)?;
+
+    let mut compose = Compose::new(&engine);
+    compose
+        .component("c", &forward)?
+        .component("b", &forward)?
+        .component("a", &math)?
+        .wire("c", "f", "b", "g")?
+        .wire("b", "f", "a", "neg")?;
+    let composed = compose.build("c")?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = composed.instantiate(&mut store, &Linker::new(&engine))?;
+    let g = instance.get_func(&mut store, "g").unwrap();
+    let mut results = [Val::S32(0)];
+    g.call(&mut store, &[Val::S32(5)], &mut results)?;
+    g.post_return(&mut store)?;
+    assert_eq!(results[0], Val::S32(-5));
+    Ok(())
+}
+
+#[test]
+fn compose_errors() -> Result<()> {
+    let engine = super::engine();
+    let math = Component::new(&engine, MATH)?;
+    let app = Component::new(&engine, APP)?;
+    let forward = Component::new(&engine, FORWARD)?;
+
+    let mut compose = Compose::new(&engine);
+    compose
+        .component("math", &math)?
+        .component("app", &app)?
+        .component("forward", &forward)?;
+
+    let err = |result: Result<&mut Compose>| format!("{:?}", result.err().unwrap());
+
+    let msg = err(compose.component("math", &math));
+    assert!(msg.contains("added to the composition twice"), "{msg}");
+    let msg = err(compose.wire("missing", "f", "math", "neg"));
+    assert!(msg.contains("no component named `missing`"), "{msg}");
+    let msg = err(compose.wire("app", "missing", "math", "neg"));
+    assert!(msg.contains("no import named `missing`"), "{msg}");
+    let msg = err(compose.wire("forward", "f", "math", "missing"));
+    assert!(msg.contains("no export named `missing`"), "{msg}");
+    let msg = err(compose.wire("forward", "f", "math", "math"));
+    assert!(msg.contains("expected function, found instance"), "{msg}");
+    let msg = err(compose.wire("app", "log", "math", "neg"));
+    assert!(msg.contains("function types are incompatible"), "{msg}");
+
+    compose.wire("forward", "f", "math", "neg")?;
+    let msg = err(compose.wire("forward", "f", "math", "neg"));
+    assert!(msg.contains("wired twice"), "{msg}");
+
+    // Two components importing from each other can't be instantiated.
+    let mut compose = Compose::new(&engine);
+    compose
+        .component("a", &forward)?
+        .component("b", &forward)?
+        .wire("a", "f", "b", "g")?
+        .wire("b", "f", "a", "g")?;
+    let msg = format!("{:?}", compose.build("a").err().unwrap());
+    assert!(msg.contains("wired in a cycle"), "{msg}");
+    Ok(())
+}
@@ -0,0 +1,168 @@
+#![cfg(not(miri))]
+
+use anyhow::Result;
+use wasmtime::component::{Component, CoreImportOrigin, Type};
+
+#[test]
+fn lowered_imports() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(
+        &engine,
+        r#"
+            (component
+                (import "my:pkg/host" (instance $host
+                    (export "log" (func (param "x" u32)))
+                ))
+                (import "tick" (func $tick))
+                (core func $log (canon lower (func $host "log")))
+                (core func $tick (canon lower (func $tick)))
+                (core module $m
+                    (import "$root" "tick" (func))
+                    (import "[export]my:pkg/host" "log-u32" (func (param i32)))
+                )
+                (core instance (instantiate $m
+                    (with "$root" (instance (export "tick" (func $tick))))
+                    (with "[export]my:pkg/host" (instance (export "log-u32" (func $log))))
+                ))
+            )
+        "#,
+    )?;
+
+    let imports = component.core_imports().collect::<Vec<_>>();
+    assert_eq!(imports.len(), 2);
+
+    assert_eq!(imports[0].instance(), 0);
+    assert_eq!(imports[0].module(), "$root");
+    assert_eq!(imports[0].name(), "tick");
+    match imports[0].origin() {
+        CoreImportOrigin::Lowered {
+            interface,
+            function,
+            ty,
+        } => {
+            assert_eq!(*interface, None);
+            assert_eq!(*function, "tick");
+            assert_eq!(ty.params().len(), 0);
+        }
+        other => panic!("unexpected origin {other:?}"),
+    }
+
+    assert_eq!(imports[1].module(), "[export]my:pkg/host");
+    assert_eq!(imports[1].name(), "log-u32");
+    match imports[1].origin() {
+        CoreImportOrigin::Lowered {
+            interface,
+            function,
+            ty,
+        } => {
+            assert_eq!(*interface, Some("my:pkg/host"));
+            assert_eq!(*function, "log");
+            let params = ty.params().collect::<Vec<_>>();
+            assert_eq!(params, [("x", Type::U32)]);
+        }
+        other => panic!("unexpected origin {other:?}"),
+    }
+    Ok(())
+}
+
+#[test]
+fn instance_exports_and_intrinsics() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(
+        &engine,
+        r#"
+            (component
+                (type $r (resource (rep i32)))
+                (core func $new (canon resource.new $r))
+                (core func $drop (canon resource.drop $r))
+
+                (core module $libc (memory (export "memory") 1))
+                (core instance $libc (instantiate $libc))
+
+                (core module $m
+                    (import "env" "memory" (memory 1))
+                    (import "[resource]r" "new" (func (param i32) (result i32)))
+                    (import "[resource]r" "drop" (func (param i32)))
+                )
+                (core instance (instantiate $m
+                    (with "env" (instance $libc))
+                    (with "[resource]r" (instance
+                        (export "new" (func $new))
+                        (export "drop" (func $drop))
+                    ))
+                ))
+            )
+        "#,
+    )?;
+
+    let imports = component.core_imports().collect::<Vec<_>>();
+    assert_eq!(imports.len(), 3);
+    assert!(imports.iter().all(|i| i.instance() == 1));
+    match imports[0].origin() {
+        CoreImportOrigin::CoreExport { instance, name } => {
+            assert_eq!(*instance, 0);
+            assert_eq!(*name, Some("memory"));
+        }
+        other => panic!("unexpected origin {other:?}"),
+    }
+    assert!(matches!(imports[1].origin(), CoreImportOrigin::ResourceNew));
+    assert!(matches!(
+        imports[2].origin(),
+        CoreImportOrigin::ResourceDrop
+    ));
+    Ok(())
+}
+
+#[test]
+fn adapter_modules() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(
+        &engine,
+        r#"
+            (component
+                (component $callee
+                    (core module $m (func (export "f")))
+                    (core instance $i (instantiate $m))
+                    (func (export "f") (canon lift (core func $i "f")))
+                )
+                (instance $callee (instantiate $callee))
+
+                (core func $f (canon lower (func $callee "f")))
+                (core module $caller (import "" "f" (func)))
+                (core instance (instantiate $caller
+                    (with "" (instance (export "f" (func $f))))
+                ))
+            )
+        "#,
+    )?;
+
+    // The caller's import is provided by a Wasmtime-generated adapter module
+    // rather than directly by the callee, and the adapter in turn imports the
+    // callee's core function.
+    let imports = component.core_imports().collect::<Vec<_>>();
+    let caller = imports
+        .iter()
+        .find(|i| i.module() == "" && i.name() == "f")
+        .unwrap();
+    let CoreImportOrigin::CoreExport {
+        instance: adapter, ..
+    } = *caller.origin()
+    else {
+        panic!("unexpected origin {:?}", caller.origin());
+    };
+    let adapter_imports = imports
+        .iter()
+        .filter(|i| i.instance() == adapter)
+        .collect::<Vec<_>>();
+    assert!(adapter_imports.iter().any(|i| matches!(
+        i.origin(),
+        CoreImportOrigin::CoreExport {
+            instance: 0,
+            name: Some("f")
+        }
+    )));
+    assert!(adapter_imports
+        .iter()
+        .any(|i| matches!(i.origin(), CoreImportOrigin::InstanceFlags)));
+    Ok(())
+}
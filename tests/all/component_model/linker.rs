@@ -1,7 +1,7 @@
 use anyhow::Result;
 use wasmtime::component::types::ComponentItem;
-use wasmtime::component::{Component, Linker, ResourceType};
-use wasmtime::{Engine, Store};
+use wasmtime::component::{Component, ComponentType, Lift, Linker, Lower, ResourceType};
+use wasmtime::{Engine, ErrorPolicy, Store, StoreContextMut};
 
 #[test]
 fn old_import_importing_new_item() -> Result<()> {
@@ -182,3 +182,202 @@ fn linker_fails_to_define_unknown_core_module_imports_as_traps() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn linker_check_reports_all_unsatisfied_imports() -> Result<()> {
+    let engine = Engine::default();
+    let mut linker = Linker::<()>::new(&engine);
+    linker
+        .root()
+        .func_wrap("f", |_, _: (u32,)| -> Result<()> { Ok(()) })?;
+    linker
+        .instance("i")?
+        .func_wrap("present", |_, _: ()| -> Result<()> { Ok(()) })?;
+
+    let component = Component::new(
+        &engine,
+        r#"(component
+            (import "f" (func (param "x" string)))
+            (import "g" (func))
+            (import "i" (instance
+                (export "present" (func))
+                (export "absent" (func))
+            ))
+            (import "j" (instance (export "x" (func))))
+        )"#,
+    )?;
+    assert!(linker.instantiate_pre(&component).is_err());
+
+    let report = linker.check(&component)?;
+    let imports = report
+        .iter()
+        .map(|i| (i.name(), i.is_missing()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        imports,
+        [("f", false), ("g", true), ("i#absent", true), ("j", true)]
+    );
+    let mismatch = report.iter().next().unwrap().to_string();
+    assert!(
+        mismatch.contains("type mismatch with parameters"),
+        "{mismatch}"
+    );
+    assert!(mismatch.contains("string"), "{mismatch}");
+    assert!(report.into_result().is_err());
+    Ok(())
+}
+
+#[test]
+fn linker_check_continues_past_missing_resources() -> Result<()> {
+    let engine = Engine::default();
+    let mut linker = Linker::<()>::new(&engine);
+    linker
+        .root()
+        .resource("b", ResourceType::host::<u32>(), |_, _| Ok(()))?;
+
+    let component = Component::new(
+        &engine,
+        r#"(component
+            (import "a" (type (sub resource)))
+            (import "b" (type (sub resource)))
+        )"#,
+    )?;
+    let report = linker.check(&component)?;
+    let imports = report.iter().map(|i| i.name()).collect::<Vec<_>>();
+    assert_eq!(imports, ["a"]);
+
+    linker
+        .root()
+        .resource("a", ResourceType::host::<i32>(), |_, _| Ok(()))?;
+    assert!(linker.check(&component)?.is_empty());
+    linker.instantiate_pre(&component)?;
+    Ok(())
+}
+
+#[derive(ComponentType, Lift, Lower, Debug, PartialEq)]
+#[component(enum)]
+#[repr(u8)]
+enum LookupError {
+    #[component(name = "not-found")]
+    NotFound,
+}
+
+impl std::fmt::Display for LookupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("not found")
+    }
+}
+
+impl std::error::Error for LookupError {}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn func_wrap_fallible_error_policies() -> Result<()> {
+    let engine = Engine::default();
+    let lookup = |_: StoreContextMut<'_, ()>, (x,): (u32,)| -> Result<u32, LookupError> {
+        if x == 0 {
+            Ok(1)
+        } else {
+            Err(LookupError::NotFound)
+        }
+    };
+
+    let mut linker = Linker::<()>::new(&engine);
+    assert!(linker.root().error_policy(ErrorPolicy::ErrorCode).is_err());
+    linker.root().func_wrap_fallible("trap", lookup)?;
+    {
+        let mut results = linker.instance("a:b/results")?;
+        results.error_policy(ErrorPolicy::Result)?;
+        results.func_wrap_fallible("lookup", lookup)?;
+        // Nested instances inherit the policy.
+        results
+            .instance("nested")?
+            .func_wrap_fallible("lookup", lookup)?;
+    }
+
+    // Each import is lowered and lifted back out again so it can be called
+    // from the host, with results written to address 8.
+    let component = Component::new(
+        &engine,
+        r#"(component
+            (import "trap" (func $trap (param "x" u32) (result u32)))
+            (import "a:b/results" (instance $results
+                (type $e (enum "not-found"))
+                (export "error" (type $e' (eq $e)))
+                (export "lookup" (func (param "x" u32) (result (result u32 (error $e')))))
+                (export "nested" (instance
+                    (type $e (enum "not-found"))
+                    (export "error" (type $e' (eq $e)))
+                    (export "lookup" (func (param "x" u32) (result (result u32 (error $e')))))
+                ))
+            ))
+            (alias export $results "error" (type $error))
+            (alias export $results "lookup" (func $lookup))
+            (alias export $results "nested" (instance $nested))
+            (alias export $nested "error" (type $nested-error))
+            (alias export $nested "lookup" (func $nested-lookup))
+
+            (core module $libc (memory (export "memory") 1))
+            (core instance $libc (instantiate $libc))
+            (core func $trap-lowered (canon lower (func $trap)))
+            (core func $lookup-lowered (canon lower (func $lookup) (memory $libc "memory")))
+            (core func $nested-lookup-lowered
+                (canon lower (func $nested-lookup) (memory $libc "memory")))
+
+            (core module $m
+                (import "" "trap" (func $trap (param i32) (result i32)))
+                (import "" "lookup" (func $lookup (param i32 i32)))
+                (import "" "nested-lookup" (func $nested-lookup (param i32 i32)))
+                (func (export "trap") (param i32) (result i32)
+                    (call $trap (local.get 0)))
+                (func (export "lookup") (param i32) (result i32)
+                    (call $lookup (local.get 0) (i32.const 8))
+                    i32.const 8)
+                (func (export "nested-lookup") (param i32) (result i32)
+                    (call $nested-lookup (local.get 0) (i32.const 8))
+                    i32.const 8)
+            )
+            (core instance $m (instantiate $m
+                (with "" (instance
+                    (export "trap" (func $trap-lowered))
+                    (export "lookup" (func $lookup-lowered))
+                    (export "nested-lookup" (func $nested-lookup-lowered))
+                ))
+            ))
+
+            (func (export "trap") (param "x" u32) (result u32)
+                (canon lift (core func $m "trap")))
+            (func (export "lookup") (param "x" u32) (result (result u32 (error $error)))
+                (canon lift (core func $m "lookup") (memory $libc "memory")))
+            (func (export "nested-lookup") (param "x" u32)
+                (result (result u32 (error $nested-error)))
+                (canon lift (core func $m "nested-lookup") (memory $libc "memory")))
+        )"#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &component)?;
+
+    // With the default policy errors trap.
+    let trap = instance.get_typed_func::<(u32,), (u32,)>(&mut store, "trap")?;
+    assert_eq!(trap.call(&mut store, (0,))?, (1,));
+    trap.post_return(&mut store)?;
+    let err = trap.call(&mut store, (1,)).unwrap_err();
+    assert!(err.downcast_ref::<LookupError>().is_some(), "{err:?}");
+
+    // With `ErrorPolicy::Result` they're returned to the guest instead. The
+    // trap above poisoned the instance, so a new one is used.
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &component)?;
+    for name in ["lookup", "nested-lookup"] {
+        let lookup =
+            instance.get_typed_func::<(u32,), (Result<u32, LookupError>,)>(&mut store, name)?;
+        assert_eq!(lookup.call(&mut store, (0,))?, (Ok(1),));
+        lookup.post_return(&mut store)?;
+        assert_eq!(
+            lookup.call(&mut store, (1,))?,
+            (Err(LookupError::NotFound),)
+        );
+        lookup.post_return(&mut store)?;
+    }
+    Ok(())
+}
@@ -278,6 +278,109 @@ fn mismatch_resource_types() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn track_resource_origins() -> Result<()> {
+    let engine = super::engine();
+    let c = Component::new(
+        &engine,
+        r#"
+            (component
+                (type $t' (resource (rep i32)))
+                (type $u' (resource (rep i32)))
+
+                (export $t "t" (type $t'))
+                (export $u "u" (type $u'))
+
+                (core func $t_ctor (canon resource.new $t))
+                (func (export "ctor") (param "x" u32) (result (own $t))
+                    (canon lift (core func $t_ctor)))
+
+                (core func $t_dtor (canon resource.drop $t))
+                (func (export "drop-t") (param "x" (own $t))
+                    (canon lift (core func $t_dtor)))
+
+                (core func $u_dtor (canon resource.drop $u))
+                (func (export "drop-u") (param "x" (own $u))
+                    (canon lift (core func $u_dtor)))
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    store.track_resource_origins(true);
+
+    // Note that a failed call poisons the instance it was made on, so each
+    // failure below uses a fresh instance.
+    let linker = Linker::new(&engine);
+    let instantiate = |store: &mut Store<()>| -> Result<_> {
+        let i = linker.instantiate(&mut *store, &c)?;
+        Ok((
+            i.get_typed_func::<(u32,), (ResourceAny,)>(&mut *store, "ctor")?,
+            i.get_typed_func::<(ResourceAny,), ()>(&mut *store, "drop-t")?,
+            i.get_typed_func::<(ResourceAny,), ()>(&mut *store, "drop-u")?,
+        ))
+    };
+
+    // Passing a `t` where a `u` is expected.
+    let (ctor, _, drop_u) = instantiate(&mut store)?;
+    let (t,) = ctor.call(&mut store, (100,))?;
+    ctor.post_return(&mut store)?;
+    assert_eq!(
+        drop_u.call(&mut store, (t,)).unwrap_err().to_string(),
+        "mismatched resource types: expected guest resource `u`, but found \
+         `own` handle to guest resource `t` lifted from a component instance"
+    );
+
+    // Passing a `t` to a different instantiation of the same component.
+    let (_, drop_t, _) = instantiate(&mut store)?;
+    assert_eq!(
+        drop_t.call(&mut store, (t,)).unwrap_err().to_string(),
+        "mismatched resource types: expected guest resource `t`, but found \
+         `own` handle to guest resource `t` lifted from a component instance, \
+         which belongs to a different component instance"
+    );
+
+    // Using a handle after it's been dropped.
+    let (ctor, drop_t, _) = instantiate(&mut store)?;
+    let (t,) = ctor.call(&mut store, (200,))?;
+    ctor.post_return(&mut store)?;
+    t.resource_drop(&mut store)?;
+    let err = drop_t.call(&mut store, (t,)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "use of `own` handle to guest resource `t` lifted from a component \
+         instance after it was dropped"
+    );
+    assert!(
+        format!("{err:?}").contains("unknown handle index"),
+        "bad error: {err:?}"
+    );
+
+    // Using a handle after it's been moved into a component.
+    let (ctor, drop_t, _) = instantiate(&mut store)?;
+    let (t,) = ctor.call(&mut store, (300,))?;
+    ctor.post_return(&mut store)?;
+    drop_t.call(&mut store, (t,))?;
+    drop_t.post_return(&mut store)?;
+    assert_eq!(
+        t.resource_drop(&mut store).unwrap_err().to_string(),
+        "use of `own` handle to guest resource `t` lifted from a component \
+         instance after it was moved"
+    );
+
+    // Without tracking the original errors are reported.
+    store.track_resource_origins(false);
+    let (ctor, _, drop_u) = instantiate(&mut store)?;
+    let (t,) = ctor.call(&mut store, (400,))?;
+    ctor.post_return(&mut store)?;
+    assert_eq!(
+        drop_u.call(&mut store, (t,)).unwrap_err().to_string(),
+        "mismatched resource types"
+    );
+
+    Ok(())
+}
+
 #[test]
 fn drop_in_different_places() -> Result<()> {
     let engine = super::engine();
@@ -1532,3 +1635,87 @@ fn resource_any_to_typed_handles_borrow() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn dynamic_resource_functions() -> Result<()> {
+    let engine = super::engine();
+    let c = Component::new(
+        &engine,
+        r#"
+            (component
+                (type $t' (resource (rep i32)))
+                (export $t "t" (type $t'))
+
+                (core module $m
+                    (func (export "id") (param i32) (result i32)
+                        local.get 0)
+                    (func (export "double") (param i32) (result i32)
+                        (i32.add (local.get 0) (local.get 0)))
+                )
+                (core instance $i (instantiate $m))
+                (core func $new (canon resource.new $t))
+                (core func $drop (canon resource.drop $t))
+
+                (func (export "[constructor]t") (param "rep" u32) (result (own $t))
+                    (canon lift (core func $new)))
+                (func (export "[method]t.rep") (param "self" (borrow $t)) (result u32)
+                    (canon lift (core func $i "id")))
+                (func (export "[method]t.double") (param "self" (borrow $t)) (result u32)
+                    (canon lift (core func $i "double")))
+                (func (export "[static]t.consume") (param "x" (own $t))
+                    (canon lift (core func $drop)))
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    let i = Linker::new(&engine).instantiate(&mut store, &c)?;
+    let t = i.get_resource(&mut store, "t").unwrap();
+
+    assert!(i.get_resource_constructor(&mut store, None, "u").is_none());
+    assert!(i
+        .get_resource_method(&mut store, None, "t", "missing")
+        .is_none());
+    let ctor = i.get_resource_constructor(&mut store, None, "t").unwrap();
+    let rep = i.get_resource_method(&mut store, None, "t", "rep").unwrap();
+    let double = i
+        .get_resource_method(&mut store, None, "t", "double")
+        .unwrap();
+    let consume = i
+        .get_resource_static(&mut store, None, "t", "consume")
+        .unwrap();
+
+    let mut results = [Val::Bool(false)];
+    ctor.call(&mut store, &[Val::U32(21)], &mut results)?;
+    ctor.post_return(&mut store)?;
+    let Val::Resource(a) = results[0] else {
+        panic!("expected a resource, found {:?}", results[0]);
+    };
+    assert_eq!(a.ty(), t);
+    assert!(a.owned());
+
+    // Owned handles are only lent to methods, so they can be used repeatedly.
+    for _ in 0..2 {
+        rep.call(&mut store, &[a.into()], &mut results)?;
+        rep.post_return(&mut store)?;
+        assert_eq!(results[0], Val::U32(21));
+    }
+    double.call(&mut store, &[a.into()], &mut results)?;
+    double.post_return(&mut store)?;
+    assert_eq!(results[0], Val::U32(42));
+
+    // Passing the handle for an `own` parameter transfers it to the guest.
+    consume.call(&mut store, &[a.into()], &mut [])?;
+    consume.post_return(&mut store)?;
+    assert!(rep.call(&mut store, &[a.into()], &mut results).is_err());
+
+    // Handles can also be dropped by the host.
+    ctor.call(&mut store, &[Val::U32(7)], &mut results)?;
+    ctor.post_return(&mut store)?;
+    let Val::Resource(b) = results[0] else {
+        panic!("expected a resource, found {:?}", results[0]);
+    };
+    b.resource_drop(&mut store)?;
+    assert!(rep.call(&mut store, &[b.into()], &mut results).is_err());
+    Ok(())
+}
@@ -0,0 +1,288 @@
+#![cfg(not(miri))]
+
+use anyhow::Result;
+use wasmtime::component::*;
+use wasmtime::{Config, Engine, Store};
+
+fn engine() -> Engine {
+    let mut config = Config::new();
+    config.wasm_component_model(true);
+    config.wasm_component_model_async(true);
+    Engine::new(&config).unwrap()
+}
+
+const PASSTHROUGH: &str = r#"
+    (component
+        (core module $m
+            (func (export "id") (param i32) (result i32) local.get 0)
+        )
+        (core instance $i (instantiate $m))
+        (type $s (stream u32))
+        (type $f (future string))
+        (func (export "stream") (param "x" $s) (result $s)
+            (canon lift (core func $i "id"))
+        )
+        (func (export "future") (param "x" $f) (result $f)
+            (canon lift (core func $i "id"))
+        )
+    )
+"#;
+
+#[test]
+fn requires_async_feature() -> Result<()> {
+    let engine = super::engine();
+    assert!(Component::new(&engine, PASSTHROUGH).is_err());
+    Ok(())
+}
+
+#[test]
+fn stream_round_trip() -> Result<()> {
+    let engine = engine();
+    let component = Component::new(&engine, PASSTHROUGH)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance
+        .get_typed_func::<(StreamReader<u32>,), (StreamReader<u32>,)>(&mut store, "stream")?;
+
+    let (writer, reader) = stream::<u32>();
+    let (reader,) = func.call(&mut store, (reader,))?;
+    func.post_return(&mut store)?;
+
+    assert_eq!(reader.read(10)?, Some(vec![]));
+    writer.write([1, 2, 3])?;
+    assert_eq!(reader.read(2)?, Some(vec![1, 2]));
+    writer.write([4])?;
+    writer.close();
+    assert_eq!(reader.read(10)?, Some(vec![3, 4]));
+    assert_eq!(reader.read(10)?, None);
+    reader.close()?;
+    Ok(())
+}
+
+#[test]
+fn future_round_trip() -> Result<()> {
+    let engine = engine();
+    let component = Component::new(&engine, PASSTHROUGH)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance
+        .get_typed_func::<(FutureReader<String>,), (FutureReader<String>,)>(&mut store, "future")?;
+
+    let (writer, reader) = future::<String>();
+    let (reader,) = func.call(&mut store, (reader,))?;
+    func.post_return(&mut store)?;
+
+    assert_eq!(reader.read()?, None);
+    writer.write("hello".to_string())?;
+    assert_eq!(reader.read()?.as_deref(), Some("hello"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn reader_waits_for_writer() -> Result<()> {
+    let (writer, reader) = stream::<u32>();
+    let read = tokio::spawn(async move {
+        reader.ready().await;
+        reader.read(10)
+    });
+    tokio::task::yield_now().await;
+    writer.write([5])?;
+    assert_eq!(read.await??, Some(vec![5]));
+
+    let (writer, reader) = future::<u32>();
+    let read = tokio::spawn(async move {
+        reader.ready().await;
+        reader.read()
+    });
+    tokio::task::yield_now().await;
+    writer.write(6)?;
+    assert_eq!(read.await??, Some(6));
+    Ok(())
+}
+
+#[test]
+fn dynamic_stream() -> Result<()> {
+    let engine = engine();
+    let component = Component::new(&engine, PASSTHROUGH)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance.get_func(&mut store, "stream").unwrap();
+
+    let ty = func.params(&store)[0].1.unwrap_stream().clone();
+    assert_eq!(ty.payload(), Type::U32);
+
+    let (writer, reader) = stream::<u32>();
+    let mut results = [Val::Bool(false)];
+    func.call(
+        &mut store,
+        &[Val::Stream(reader.into_stream_any())],
+        &mut results,
+    )?;
+    func.post_return(&mut store)?;
+
+    let Val::Stream(any) = &results[0] else {
+        panic!("expected a stream, got {:?}", results[0]);
+    };
+
+    // The element type of the stream is checked on conversion.
+    assert!(StreamReader::<String>::try_from_stream_any(any.clone()).is_err());
+
+    let reader = StreamReader::<u32>::try_from_stream_any(any.clone())?;
+    writer.write([7])?;
+    assert_eq!(reader.read(10)?, Some(vec![7]));
+    Ok(())
+}
+
+#[test]
+fn closed_reader_rejects_writes() -> Result<()> {
+    let (writer, reader) = stream::<u32>();
+    reader.close()?;
+    assert!(writer.is_closed());
+    assert!(writer.write([1]).is_err());
+
+    let (writer, reader) = future::<u32>();
+    writer.close();
+    assert!(reader.read().is_err());
+    Ok(())
+}
+
+#[test]
+fn closed_reader_cannot_be_passed() -> Result<()> {
+    let engine = engine();
+    let component = Component::new(&engine, PASSTHROUGH)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance.get_func(&mut store, "stream").unwrap();
+
+    let (_writer, reader) = stream::<u32>();
+    let any = reader.into_stream_any();
+    StreamReader::<u32>::try_from_stream_any(any.clone())?.close()?;
+
+    let mut results = [Val::Bool(false)];
+    let err = func
+        .call(&mut store, &[Val::Stream(any)], &mut results)
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("stream was closed"), "{err:?}");
+    Ok(())
+}
+
+const KEEP_AND_FORGE: &str = r#"
+    (component
+        (core module $m
+            (func (export "keep") (param i32))
+            (func (export "forge") (result i32) i32.const 1)
+        )
+        (core instance $i (instantiate $m))
+        (type $s (stream u32))
+        (func (export "keep") (param "x" $s)
+            (canon lift (core func $i "keep"))
+        )
+        (func (export "forge") (result $s)
+            (canon lift (core func $i "forge"))
+        )
+    )
+"#;
+
+#[test]
+fn passed_reader_is_owned_by_component() -> Result<()> {
+    let engine = engine();
+    let component = Component::new(&engine, KEEP_AND_FORGE)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let keep = instance.get_typed_func::<(StreamReader<u32>,), ()>(&mut store, "keep")?;
+
+    // Once a stream is passed to a component the host can no longer read from
+    // it or pass it along again through another copy of it.
+    let (_writer, reader) = stream::<u32>();
+    let any = reader.into_stream_any();
+    let reader = StreamReader::<u32>::try_from_stream_any(any.clone())?;
+    keep.call(&mut store, (reader,))?;
+    keep.post_return(&mut store)?;
+
+    let copy = StreamReader::<u32>::try_from_stream_any(any)?;
+    let err = copy.read(10).unwrap_err();
+    assert!(
+        format!("{err:?}").contains("no longer owned by the host"),
+        "{err:?}"
+    );
+    let err = keep.call(&mut store, (copy,)).unwrap_err();
+    assert!(
+        format!("{err:?}").contains("no longer owned by the host"),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn handles_are_local_to_an_instance() -> Result<()> {
+    let engine = engine();
+    let component = Component::new(&engine, KEEP_AND_FORGE)?;
+    let mut store = Store::new(&engine, ());
+    let linker = Linker::new(&engine);
+    let a = linker.instantiate(&mut store, &component)?;
+    let b = linker.instantiate(&mut store, &component)?;
+
+    // Instance `a` now owns a stream at handle 1.
+    let keep = a.get_typed_func::<(StreamReader<u32>,), ()>(&mut store, "keep")?;
+    let (_writer, reader) = stream::<u32>();
+    keep.call(&mut store, (reader,))?;
+    keep.post_return(&mut store)?;
+
+    // Instance `b` can't use that handle to take the stream from `a`.
+    let forge = b.get_typed_func::<(), (StreamReader<u32>,)>(&mut store, "forge")?;
+    let err = forge.call(&mut store, ()).unwrap_err();
+    assert!(
+        format!("{err:?}").contains("unknown stream handle index 1"),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn stream_passed_between_components() -> Result<()> {
+    let engine = engine();
+    let component = Component::new(
+        &engine,
+        r#"
+            (component
+                (type $s (stream u32))
+                (component $inner
+                    (type $s (stream u32))
+                    (core module $m
+                        (func (export "id") (param i32) (result i32) local.get 0)
+                    )
+                    (core instance $i (instantiate $m))
+                    (func (export "id") (param "x" $s) (result $s)
+                        (canon lift (core func $i "id"))
+                    )
+                )
+                (instance $a (instantiate $inner))
+                (core func $id (canon lower (func $a "id")))
+                (core module $m
+                    (import "" "id" (func $id (param i32) (result i32)))
+                    (func (export "call") (param i32) (result i32)
+                        local.get 0
+                        call $id)
+                )
+                (core instance $i (instantiate $m
+                    (with "" (instance (export "id" (func $id))))
+                ))
+                (func (export "call") (param "x" $s) (result $s)
+                    (canon lift (core func $i "call"))
+                )
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance
+        .get_typed_func::<(StreamReader<u32>,), (StreamReader<u32>,)>(&mut store, "call")?;
+
+    let (writer, reader) = stream::<u32>();
+    let (reader,) = func.call(&mut store, (reader,))?;
+    func.post_return(&mut store)?;
+
+    writer.write([8, 9])?;
+    assert_eq!(reader.read(10)?, Some(vec![8, 9]));
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use std::ptr::NonNull;
+use wasmtime::*;
+
+const WAT: &str = r#"
+    (module
+        (func (export "run") (param i32) (result i32)
+            (local $i i32)
+            (if (local.get 0)
+                (then (local.set $i (i32.const 10)))
+                (else (local.set $i (i32.const 1))))
+            (loop $l
+                (local.set $i (i32.sub (local.get $i) (i32.const 1)))
+                (br_if $l (local.get $i)))
+            (local.get 0))
+    )
+"#;
+
+fn run(store: &mut Store<()>, run: &TypedFunc<i32, i32>, map: &mut [u8], arg: i32) -> Result<()> {
+    map.fill(0);
+    unsafe {
+        store.set_coverage_map(Some(NonNull::from(&mut *map)))?;
+    }
+    run.call(&mut *store, arg)?;
+    unsafe {
+        store.set_coverage_map(None)?;
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn records_edges() -> Result<()> {
+    let mut config = Config::new();
+    config.coverage_instrumentation(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let func = instance.get_typed_func::<i32, i32>(&mut store, "run")?;
+
+    // Without a map execution isn't affected.
+    assert_eq!(func.call(&mut store, 1)?, 1);
+
+    let mut map = vec![0; 1 << 16];
+    run(&mut store, &func, &mut map, 0)?;
+    let edges = |map: &[u8]| {
+        map.iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>()
+    };
+    let zero = edges(&map);
+    assert!(!zero.is_empty());
+
+    // The same input takes the same edges.
+    run(&mut store, &func, &mut map, 0)?;
+    assert_eq!(edges(&map), zero);
+
+    // The other branch takes different edges, including the loop's back
+    // edge nine times.
+    run(&mut store, &func, &mut map, 1)?;
+    assert_ne!(edges(&map), zero);
+    assert_eq!(map.iter().copied().max(), Some(9));
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn map_size_must_be_power_of_two() -> Result<()> {
+    let mut config = Config::new();
+    config.coverage_instrumentation(true);
+    let engine = Engine::new(&config)?;
+    let mut store = Store::new(&engine, ());
+    let mut map = vec![0; 1000];
+    unsafe {
+        assert!(store
+            .set_coverage_map(Some(NonNull::from(&mut map[..])))
+            .is_err());
+        assert!(store
+            .set_coverage_map(Some(NonNull::from(&mut map[..0])))
+            .is_err());
+        store.set_coverage_map(Some(NonNull::from(&mut map[..512])))?;
+    }
+    Ok(())
+}
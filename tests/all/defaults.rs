@@ -68,3 +68,80 @@ fn test_tail_call_default() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_target_capabilities() -> Result<()> {
+    use FeatureSupport::*;
+
+    let mut sse41 = Config::new();
+    sse41.strategy(Strategy::Cranelift).target("x86_64")?;
+    unsafe {
+        sse41.cranelift_flag_enable("has_sse41");
+    }
+
+    let mut stack_switching = Config::new();
+    stack_switching
+        .strategy(Strategy::Cranelift)
+        .wasm_function_references(true)
+        .wasm_exceptions(true)
+        .wasm_stack_switching(true);
+
+    for (line, expected, cfg) in [
+        (
+            line!(),
+            [Emulated, Native, Native, Unsupported],
+            Config::new()
+                .strategy(Strategy::Cranelift)
+                .target("x86_64")?,
+        ),
+        (line!(), [Native, Native, Native, Unsupported], &mut sse41),
+        (
+            line!(),
+            [Native, Native, Native, Native],
+            stack_switching.clone().target("x86_64-unknown-linux-gnu")?,
+        ),
+        (
+            line!(),
+            [Native, Native, Native, Unsupported],
+            stack_switching
+                .clone()
+                .target("aarch64-unknown-linux-gnu")?,
+        ),
+        (
+            line!(),
+            [Unsupported, Native, Native, Unsupported],
+            Config::new()
+                .strategy(Strategy::Cranelift)
+                .target("riscv64")?,
+        ),
+        (
+            line!(),
+            [Unsupported, Native, Unsupported, Unsupported],
+            Config::new().strategy(Strategy::Winch).target("x86_64")?,
+        ),
+        (
+            line!(),
+            [Unsupported, Native, Native, Unsupported],
+            Config::new()
+                .strategy(Strategy::Cranelift)
+                .wasm_simd(false)
+                .wasm_relaxed_simd(false)
+                .target("aarch64")?,
+        ),
+    ] {
+        let capabilities = Engine::new(cfg)?.target_capabilities();
+        eprintln!("for config on line {line}, got: {capabilities:?}");
+        assert_eq!(
+            expected,
+            [
+                capabilities.simd(),
+                capabilities.threads(),
+                capabilities.tail_calls(),
+                capabilities.stack_switching(),
+            ]
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use wasmtime::*;
+use wasmtime_wasi::preview1::WasiP1Ctx;
+
+fn audit(config: &Config, wat: &str) -> Result<DeterminismReport> {
+    let engine = Engine::new(config)?;
+    let mut linker = Linker::<WasiP1Ctx>::new(&engine);
+    wasmtime_wasi::preview1::add_to_linker_sync(&mut linker, |t| t)?;
+    linker.func_wrap("host", "log", |_: i32| {})?;
+    CodeBuilder::new(&engine)
+        .wasm_binary_or_text(wat.as_bytes(), None)?
+        .audit_determinism(&linker)
+}
+
+#[test]
+fn deterministic_module() -> Result<()> {
+    let report = audit(
+        &Config::new(),
+        r#"
+            (module
+                (import "wasi_snapshot_preview1" "fd_write"
+                    (func (param i32 i32 i32 i32) (result i32)))
+                (memory 1)
+                (func (param i32 i32) (result i32)
+                    (i32.add (local.get 0) (local.get 1)))
+                (func (param f32) (result f32)
+                    (f32.neg (local.get 0)))
+            )
+        "#,
+    )?;
+    assert!(report.is_deterministic(), "{report}");
+    Ok(())
+}
+
+#[test]
+fn imports() -> Result<()> {
+    let report = audit(
+        &Config::new(),
+        r#"
+            (module
+                (import "wasi_snapshot_preview1" "clock_time_get"
+                    (func (param i32 i64 i32) (result i32)))
+                (import "wasi_snapshot_preview1" "random_get"
+                    (func (param i32 i32) (result i32)))
+                (import "host" "log" (func (param i32)))
+                (import "host" "missing" (func))
+                (import "host" "global" (global i32))
+            )
+        "#,
+    )?;
+    let import = |module: &str, name: &str| (module.to_string(), name.to_string());
+    let sources = report
+        .sources()
+        .iter()
+        .map(|source| match source {
+            NondeterminismSource::Clock { module, name } => ("clock", import(module, name)),
+            NondeterminismSource::Random { module, name } => ("random", import(module, name)),
+            NondeterminismSource::HostImport { module, name } => ("host", import(module, name)),
+            NondeterminismSource::UnresolvedImport { module, name } => {
+                ("unresolved", import(module, name))
+            }
+            _ => panic!("unexpected source {source:?}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        sources,
+        [
+            ("clock", import("wasi_snapshot_preview1", "clock_time_get")),
+            ("random", import("wasi_snapshot_preview1", "random_get")),
+            ("host", import("host", "log")),
+            ("unresolved", import("host", "missing")),
+            ("unresolved", import("host", "global")),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn instructions() -> Result<()> {
+    let wat = r#"
+        (module
+            (import "host" "log" (func (param i32)))
+            (memory 1)
+            (table 1 funcref)
+            (func (param f64) (result f64)
+                (f64.add (local.get 0) (local.get 0))
+                (f64.sqrt))
+            (func (param v128) (result v128)
+                (f32x4.relaxed_madd (local.get 0) (local.get 0) (local.get 0)))
+            (func (result i32)
+                (drop (memory.grow (i32.const 1)))
+                (table.grow (ref.null func) (i32.const 1)))
+        )
+    "#;
+
+    let report = audit(&Config::new(), wat)?;
+    let sources = report.sources();
+    assert_eq!(sources.len(), 5, "{report}");
+    assert!(matches!(
+        sources[0],
+        NondeterminismSource::HostImport { .. }
+    ));
+    assert!(matches!(
+        sources[1],
+        NondeterminismSource::NanBits { func_index: 1, .. }
+    ));
+    assert!(matches!(
+        sources[2],
+        NondeterminismSource::RelaxedSimd { func_index: 2, .. }
+    ));
+    assert!(matches!(
+        sources[3],
+        NondeterminismSource::MemoryGrowth { func_index: 3, .. }
+    ));
+    assert!(matches!(
+        sources[4],
+        NondeterminismSource::TableGrowth { func_index: 3, .. }
+    ));
+
+    // Configuring the engine to make floats deterministic removes them from
+    // the report.
+    let mut config = Config::new();
+    config.cranelift_nan_canonicalization(true);
+    config.relaxed_simd_deterministic(true);
+    let report = audit(&config, wat)?;
+    let sources = report.sources();
+    assert_eq!(sources.len(), 3, "{report}");
+    assert!(matches!(
+        sources[1],
+        NondeterminismSource::MemoryGrowth { func_index: 3, .. }
+    ));
+    Ok(())
+}
+
+#[test]
+fn shared_memory() -> Result<()> {
+    let report = audit(
+        &Config::new(),
+        r#"
+            (module
+                (memory 1)
+                (memory 1 1 shared)
+            )
+        "#,
+    )?;
+    assert!(matches!(
+        report.sources(),
+        [NondeterminismSource::SharedMemory { memory_index: 1 }]
+    ));
+    Ok(())
+}
+
+#[test]
+fn json_report() -> Result<()> {
+    let report = audit(
+        &Config::new(),
+        r#"
+            (module
+                (import "wasi_snapshot_preview1" "random_get"
+                    (func (param i32 i32) (result i32)))
+                (memory 1 1 shared)
+            )
+        "#,
+    )?;
+    assert_eq!(
+        serde_json::to_value(&report)?,
+        serde_json::json!({
+            "sources": [
+                {
+                    "kind": "random",
+                    "module": "wasi_snapshot_preview1",
+                    "name": "random_get",
+                },
+                {
+                    "kind": "shared-memory",
+                    "memory_index": 0,
+                },
+            ],
+        })
+    );
+    Ok(())
+}
+
+#[test]
+fn components_are_rejected() -> Result<()> {
+    let err = audit(&Config::new(), "(component)").unwrap_err();
+    assert!(
+        err.to_string().contains("only core wasm modules"),
+        "{err:?}"
+    );
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use wasmtime::*;
+
+const MAIN: &str = r#"
+    (module
+        (memory (export "memory") 1)
+        (table (export "table") 0 funcref)
+        (global (export "main_data") i32 (i32.const 16)))
+"#;
+
+const LIBA: &str = r#"
+    (module
+        (@dylink.0 (mem-info (memory 4 2)))
+        (import "env" "memory" (memory 0))
+        (import "env" "__memory_base" (global $base i32))
+        (global (export "counter") i32 (i32.const 0))
+        (func (export "add") (param i32 i32) (result i32)
+            (i32.add (local.get 0) (local.get 1)))
+        (func (export "__wasm_call_ctors")
+            (i32.store (global.get $base) (i32.const 10))))
+"#;
+
+const LIBB: &str = r#"
+    (module
+        (@dylink.0 (needed "liba.so"))
+        (import "env" "memory" (memory 0))
+        (import "env" "add" (func $add (param i32 i32) (result i32)))
+        (import "GOT.mem" "counter" (global $counter (mut i32)))
+        (import "GOT.func" "add" (global $add_slot (mut i32)))
+        (func (export "bump") (result i32)
+            (i32.store
+                (global.get $counter)
+                (call $add (i32.load (global.get $counter)) (i32.const 1)))
+            (i32.load (global.get $counter)))
+        (func (export "add-slot") (result i32)
+            (global.get $add_slot)))
+"#;
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn dlopen_and_dlsym() -> Result<()> {
+    let mut store = Store::<()>::default();
+    let linker = Linker::new(store.engine());
+    let liba = SharedLibrary::new(store.engine(), LIBA)?;
+    let libb = SharedLibrary::new(store.engine(), LIBB)?;
+    assert_eq!(liba.memory_size(), 4);
+    assert_eq!(libb.needed().collect::<Vec<_>>(), ["liba.so"]);
+
+    let err = store.dlopen(&linker, "liba.so", &liba).unwrap_err();
+    assert!(err.to_string().contains("Store::dylink_init"), "{err}");
+
+    let main = Module::new(store.engine(), MAIN)?;
+    let main = linker.instantiate(&mut store, &main)?;
+    let memory = main.get_memory(&mut store, "memory").unwrap();
+    let table = main.get_table(&mut store, "table").unwrap();
+    store.dylink_init(main, memory, table)?;
+
+    let err = store.dlopen(&linker, "libb.so", &libb).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "shared library `libb.so` depends on `liba.so` which has not been loaded"
+    );
+
+    let a = store.dlopen(&linker, "liba.so", &liba)?;
+    assert_eq!(store.dlopen(&linker, "liba.so", &liba)?, a);
+    let b = store.dlopen(&linker, "libb.so", &libb)?;
+    assert_ne!(a, b);
+
+    // `liba.so`'s data was placed in a fresh page and initialized by its
+    // constructor.
+    let Some(LibrarySymbol::Data(counter)) = store.dlsym(Some(a), "counter") else {
+        panic!("`counter` should be a data symbol");
+    };
+    assert_eq!(counter, 65536);
+    assert!(matches!(
+        store.dlsym(None, "counter"),
+        Some(LibrarySymbol::Data(addr)) if addr == counter
+    ));
+
+    let Some(LibrarySymbol::Func(bump)) = store.dlsym(Some(b), "bump") else {
+        panic!("`bump` should be a function");
+    };
+    let bump = bump.typed::<(), i32>(&store)?;
+    assert_eq!(bump.call(&mut store, ())?, 11);
+    assert_eq!(bump.call(&mut store, ())?, 12);
+    let mut bytes = [0; 4];
+    memory.read(&store, counter as usize, &mut bytes)?;
+    assert_eq!(i32::from_le_bytes(bytes), 12);
+
+    // `GOT.func` entries point at a table slot holding the function.
+    let Some(LibrarySymbol::Func(add_slot)) = store.dlsym(Some(b), "add-slot") else {
+        panic!("`add-slot` should be a function");
+    };
+    let slot = add_slot.typed::<(), i32>(&store)?.call(&mut store, ())?;
+    let add = table.get(&mut store, slot as u64).unwrap();
+    let add = add
+        .unwrap_func()
+        .unwrap()
+        .typed::<(i32, i32), i32>(&store)?;
+    assert_eq!(add.call(&mut store, (2, 3))?, 5);
+
+    assert!(store.dlsym(None, "__wasm_call_ctors").is_none());
+    assert!(store.dlsym(None, "missing").is_none());
+    Ok(())
+}
+
+const LIBC: &str = r#"
+    (module
+        (@dylink.0 (mem-info (memory 4 2) (table 1 0)))
+        (import "env" "memory" (memory 0))
+        (import "GOT.mem" "main_data" (global $main_data (mut i32)))
+        (func (export "main-data") (result i32)
+            (global.get $main_data)))
+"#;
+
+const LIBD: &str = r#"
+    (module
+        (@dylink.0 (mem-info (memory 4 2) (table 1 0)))
+        (import "env" "memory" (memory 0))
+        (import "GOT.func" "main_data" (global (mut i32)))
+        (import "GOT.mem" "missing" (global (mut i32))))
+"#;
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn dlopen_resolves_main_module_symbols() -> Result<()> {
+    let mut store = Store::<()>::default();
+    let linker = Linker::new(store.engine());
+    let libc = SharedLibrary::new(store.engine(), LIBC)?;
+    let libd = SharedLibrary::new(store.engine(), LIBD)?;
+
+    let main = Module::new(store.engine(), MAIN)?;
+    let main = linker.instantiate(&mut store, &main)?;
+    let memory = main.get_memory(&mut store, "memory").unwrap();
+    let table = main.get_table(&mut store, "table").unwrap();
+    store.dylink_init(main, memory, table)?;
+
+    // Data symbols of the main module are absolute addresses.
+    assert!(matches!(
+        store.dlsym(None, "main_data"),
+        Some(LibrarySymbol::Data(16))
+    ));
+    let c = store.dlopen(&linker, "libc.so", &libc)?;
+    let Some(LibrarySymbol::Func(main_data)) = store.dlsym(Some(c), "main-data") else {
+        panic!("`main-data` should be a function");
+    };
+    let main_data = main_data.typed::<(), i32>(&store)?;
+    assert_eq!(main_data.call(&mut store, ())?, 16);
+
+    // A library with unresolvable symbols doesn't reserve any memory or
+    // table space.
+    let pages = memory.size(&store);
+    let slots = table.size(&store);
+    let err = store.dlopen(&linker, "libd.so", &libd).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "shared library `libd.so` references undefined function `main_data`"
+    );
+    assert_eq!(memory.size(&store), pages);
+    assert_eq!(table.size(&store), slots);
+    Ok(())
+}
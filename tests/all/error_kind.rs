@@ -0,0 +1,95 @@
+use wasmtime::*;
+
+#[test]
+fn configuration() {
+    let mut config = Config::new();
+    config.max_wasm_stack(0);
+    let err = Engine::new(&config).unwrap_err();
+    assert_eq!(ErrorKind::of(&err), Some(ErrorKind::Configuration));
+    assert_eq!(err.to_string(), "max_wasm_stack size cannot be zero");
+
+    // The tagged error itself is the first cause in the chain.
+    let source = err.source().expect("tagged errors have a source");
+    assert_eq!(source.to_string(), "max_wasm_stack size cannot be zero");
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn validate() {
+    let engine = Engine::default();
+    let err = Module::new(&engine, "(module (func (drop)))").unwrap_err();
+    assert_eq!(ErrorKind::of(&err), Some(ErrorKind::Validate));
+
+    let err = Module::validate(&engine, b"\0asm\x02\0\0\0").unwrap_err();
+    assert_eq!(ErrorKind::of(&err), Some(ErrorKind::Validate));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn link() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, r#"(module (import "m" "f" (func)))"#)?;
+    let mut store = Store::new(&engine, ());
+
+    let err = Instance::new(&mut store, &module, &[]).unwrap_err();
+    assert_eq!(ErrorKind::of(&err), Some(ErrorKind::Link));
+
+    let mut linker = Linker::new(&engine);
+    let err = linker.instantiate(&mut store, &module).unwrap_err();
+    assert_eq!(ErrorKind::of(&err), Some(ErrorKind::Link));
+    assert!(err.is::<UnknownImportError>());
+
+    linker.func_wrap("m", "f", || {})?;
+    let err = linker.func_wrap("m", "f", || {}).unwrap_err();
+    assert_eq!(ErrorKind::of(&err), Some(ErrorKind::Link));
+    assert_eq!(err.to_string(), "import of `m::f` defined twice");
+
+    let global = Global::new(
+        &mut store,
+        GlobalType::new(ValType::I32, Mutability::Const),
+        0.into(),
+    )?;
+    let err = Instance::new(&mut store, &module, &[global.into()]).unwrap_err();
+    assert_eq!(ErrorKind::of(&err), Some(ErrorKind::Link));
+    assert!(err
+        .to_string()
+        .contains("incompatible import type for `m::f`"));
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn trap() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, r#"(module (func (export "f") unreachable))"#)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let f = instance.get_typed_func::<(), ()>(&mut store, "f")?;
+    let err = f.call(&mut store, ()).unwrap_err();
+    assert_eq!(ErrorKind::of(&err), Some(ErrorKind::Trap));
+
+    // Context added by the embedder doesn't hide the kind.
+    let err = err.context("calling `f`");
+    assert_eq!(ErrorKind::of(&err), Some(ErrorKind::Trap));
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn resource_limit() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, "(module (memory 2))")?;
+    let mut store = Store::new(
+        &engine,
+        StoreLimitsBuilder::new().memory_size(1 << 16).build(),
+    );
+    store.limiter(|s| s as &mut dyn ResourceLimiter);
+    let err = Instance::new(&mut store, &module, &[]).unwrap_err();
+    assert_eq!(ErrorKind::of(&err), Some(ErrorKind::ResourceLimit));
+    Ok(())
+}
+
+#[test]
+fn host_errors_have_no_kind() {
+    assert_eq!(ErrorKind::of(&anyhow::anyhow!("host error")), None);
+}
@@ -724,6 +724,50 @@ fn trap_import(config: &mut Config) -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[cfg_attr(miri, ignore)]
+fn caller_current_frame() -> Result<()> {
+    let mut store = Store::<Vec<(u32, Option<String>, Option<usize>)>>::default();
+    let module = Module::new(
+        store.engine(),
+        r#"
+            (module $m
+                (import "" "" (func $host))
+                (func (export "a") call $host)
+                (func (export "b") nop call $host)
+            )
+        "#,
+    )?;
+    let host = Func::wrap(&mut store, |mut caller: Caller<'_, _>| {
+        let frame = caller.current_frame().unwrap();
+        let name = frame.module().name().map(|s| s.to_string());
+        let entry = (frame.func_index(), name, frame.module_offset());
+        caller.data_mut().push(entry);
+    });
+    let instance = Instance::new(&mut store, &module, &[host.into()])?;
+    let a = instance.get_typed_func::<(), ()>(&mut store, "a")?;
+    let b = instance.get_typed_func::<(), ()>(&mut store, "b")?;
+    a.call(&mut store, ())?;
+    b.call(&mut store, ())?;
+    a.call(&mut store, ())?;
+
+    let frames = store.data();
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[0].0, 1);
+    assert_eq!(frames[1].0, 2);
+    assert!(frames.iter().all(|f| f.1.as_deref() == Some("m")));
+    assert!(frames.iter().all(|f| f.2.is_some()));
+    assert_ne!(frames[0].2, frames[1].2);
+    assert_eq!(frames[0].2, frames[2].2);
+
+    // Host-to-host calls have no calling wasm frame.
+    let f = Func::wrap(&mut store, |caller: Caller<'_, _>| {
+        assert!(caller.current_frame().is_none());
+    });
+    f.call(&mut store, &[], &mut [])?;
+    Ok(())
+}
+
 #[test]
 fn get_from_wrapper() {
     let mut store = Store::<()>::default();
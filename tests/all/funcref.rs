@@ -159,3 +159,58 @@ fn func_new_returns_wrong_store() -> anyhow::Result<()> {
         }
     }
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn typed_func_ref_from_table() -> anyhow::Result<()> {
+    let mut config = Config::new();
+    config.wasm_function_references(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $t (func (param i32) (result i32)))
+                (func $double (type $t) local.get 0 local.get 0 i32.add)
+                (func $other (param i64))
+                (table (export "typed") 1 1 (ref $t) (ref.func $double))
+                (table (export "untyped") 2 2 funcref)
+                (elem (table 1) (i32.const 1) func $other)
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+
+    let typed = instance.get_table(&mut store, "typed").unwrap();
+    let r = typed.get(&mut store, 0).unwrap();
+    let f = Func::from_typed_ref::<i32, i32>(&store, &r)?;
+    assert_eq!(f.call(&mut store, 21)?, 42);
+    assert_eq!(f.ty().params().len(), 1);
+
+    // Writing the reference back preserves it.
+    typed.set(&mut store, 0, f.to_ref())?;
+    let r = typed.get(&mut store, 0).unwrap();
+    let f = Func::from_typed_ref::<i32, i32>(&store, &r)?;
+    assert_eq!(f.call(&mut store, 2)?, 4);
+
+    // The wrong static type is rejected.
+    assert!(Func::from_typed_ref::<i64, ()>(&store, &r).is_err());
+
+    let untyped = instance.get_table(&mut store, "untyped").unwrap();
+
+    // Null references are rejected.
+    let r = untyped.get(&mut store, 0).unwrap();
+    let err = Func::from_typed_ref::<(), ()>(&store, &r).unwrap_err();
+    assert!(
+        err.to_string().contains("null function reference"),
+        "{err:?}"
+    );
+
+    let r = untyped.get(&mut store, 1).unwrap();
+    assert!(Func::from_typed_ref::<i32, i32>(&store, &r).is_err());
+    let f = Func::from_typed_ref::<i64, ()>(&store, &r)?;
+    f.call(&mut store, 0)?;
+
+    Ok(())
+}
@@ -1003,3 +1003,214 @@ fn ref_matches() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+#[cfg_attr(miri, ignore)]
+async fn async_root_scope_keeps_roots_across_suspension() -> Result<()> {
+    let mut store = Store::<()>::default();
+
+    let x;
+    {
+        let mut scope = AsyncRootScope::new(&mut store);
+        x = ExternRef::new(&mut scope, 42_u32)?;
+        let y = scope
+            .suspend(async {
+                tokio::task::yield_now().await;
+                7_u32
+            })
+            .await;
+        assert_eq!(y, 7);
+        let data = x.data(&scope)?.unwrap().downcast_ref::<u32>().copied();
+        assert_eq!(data, Some(42));
+    }
+
+    // Dropping the scope unroots `x`, just like a `RootScope`.
+    assert!(x.data(&store).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn pinned_arrays_keep_their_address() -> Result<()> {
+    let mut store = Store::<()>::default();
+    let engine = store.engine().clone();
+
+    let pre = ArrayRefPre::new(
+        &mut store,
+        ArrayType::new(&engine, FieldType::new(Mutability::Var, StorageType::I16)),
+    );
+    let pinned = {
+        let mut scope = RootScope::new(&mut store);
+        let array = ArrayRef::new(&mut scope, &pre, &Val::I32(0), 4)?;
+        array.pin(&mut scope)?
+    };
+    assert_eq!(pinned.len(), 8);
+    let ptr = pinned.as_ptr();
+
+    // The pin keeps the array alive and in place after its scope is exited.
+    store.gc();
+    unsafe {
+        ptr.cast::<u16>().add(3).write_unaligned(0x1234);
+    }
+    let array = pinned.root().to_rooted(&mut store);
+    assert_eq!(array.get(&mut store, 3)?.unwrap_i32(), 0x1234);
+    let again = array.pin(&mut store)?;
+    assert_eq!(again.as_ptr(), ptr);
+    again.unpin(&mut store);
+    pinned.unpin(&mut store);
+
+    // Only arrays of non-reference elements can be pinned.
+    let pre = ArrayRefPre::new(
+        &mut store,
+        ArrayType::new(
+            &engine,
+            FieldType::new(Mutability::Var, ValType::EXTERNREF.into()),
+        ),
+    );
+    let refs = ArrayRef::new(&mut store, &pre, &Val::ExternRef(None), 1)?;
+    assert!(refs.pin(&mut store).is_err());
+
+    let pre = StructRefPre::new(&mut store, StructType::new(&engine, [])?);
+    let s = StructRef::new(&mut store, &pre, &[])?;
+    assert!(s.pin(&mut store).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn gc_stats_and_hooks() -> Result<()> {
+    let mut store = Store::<()>::default();
+
+    let stats = store.gc_stats();
+    assert_eq!(stats.collections, 0);
+    assert_eq!(stats.live_bytes_after_last_gc, None);
+    assert_eq!(stats.last_pause, None);
+
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+    store.on_gc({
+        let events = events.clone();
+        move |event, stats| events.lock().unwrap().push((event, *stats))
+    });
+
+    let live = ExternRef::new_manually_rooted(&mut store, 1234)?;
+    {
+        let mut scope = RootScope::new(&mut store);
+        for _ in 0..10 {
+            ExternRef::new(&mut scope, 5678)?;
+        }
+    }
+    let allocated = store.gc_stats().allocated_bytes;
+    assert!(allocated > 0);
+
+    store.gc();
+
+    let stats = store.gc_stats();
+    assert_eq!(stats.collections, 1);
+    let live_bytes = stats.live_bytes_after_last_gc.unwrap();
+    assert!(live_bytes > 0);
+    assert!(live_bytes < allocated);
+    assert_eq!(stats.allocated_bytes, live_bytes);
+    assert_eq!(stats.last_pause, Some(stats.total_pause));
+
+    {
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].0, GcEvent::BeforeCollection);
+        assert_eq!(events[0].1.allocated_bytes, allocated);
+        assert_eq!(events[0].1.collections, 0);
+        assert_eq!(events[1].0, GcEvent::AfterCollection);
+        assert_eq!(events[1].1, stats);
+    }
+
+    live.unroot(&mut store);
+    store.gc();
+
+    let stats = store.gc_stats();
+    assert_eq!(stats.collections, 2);
+    assert_eq!(stats.live_bytes_after_last_gc, Some(0));
+    assert!(stats.total_pause >= stats.last_pause.unwrap());
+    assert_eq!(events.lock().unwrap().len(), 4);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn externref_finalizers() -> Result<()> {
+    let finalized = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let finalizer = |finalized: &Arc<std::sync::Mutex<Vec<u32>>>| {
+        let finalized = finalized.clone();
+        move |x| finalized.lock().unwrap().push(x)
+    };
+
+    let mut store = Store::<()>::default();
+    {
+        let mut scope = RootScope::new(&mut store);
+        ExternRef::new_with_finalizer(&mut scope, 1_u32, finalizer(&finalized))?;
+        ExternRef::new_with_finalizer(&mut scope, 2_u32, finalizer(&finalized))?;
+    }
+    let live = ExternRef::new_with_finalizer(&mut store, 3_u32, finalizer(&finalized))?
+        .to_manually_rooted(&mut store)?;
+
+    // Finalizers only run once their `externref` has been collected.
+    assert!(finalized.lock().unwrap().is_empty());
+
+    store.gc();
+    finalized.lock().unwrap().sort();
+    assert_eq!(*finalized.lock().unwrap(), [1, 2]);
+
+    store.gc();
+    assert_eq!(*finalized.lock().unwrap(), [1, 2]);
+
+    // Finalizers of `externref`s that are still alive run when the store is
+    // dropped.
+    let _ = live;
+    drop(store);
+    assert_eq!(*finalized.lock().unwrap(), [1, 2, 3]);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn weak_rooted() -> Result<()> {
+    let mut store = Store::<()>::default();
+
+    let live = ExternRef::new_manually_rooted(&mut store, 1_u32)?;
+    let weak_live = WeakRooted::new(&mut store, &live)?;
+
+    let weak_dead = {
+        let mut scope = RootScope::new(&mut store);
+        let dead = ExternRef::new(&mut scope, 2_u32)?;
+        let weak_dead = WeakRooted::new(&mut scope, &dead)?;
+        let upgraded = weak_dead.upgrade(&mut scope).unwrap();
+        assert!(Rooted::ref_eq(&scope, &dead, &upgraded)?);
+        weak_dead
+    };
+
+    store.gc();
+
+    // The weak reference didn't keep its referent alive.
+    assert!(weak_dead.upgrade(&mut store).is_none());
+
+    // Allocating a new object, which may reuse the dead object's memory, does
+    // not revive the weak reference.
+    let _new = ExternRef::new(&mut store, 3_u32)?;
+    assert!(weak_dead.upgrade(&mut store).is_none());
+
+    // Weak references to live objects can still be upgraded after a GC, and
+    // the upgraded reference keeps the object alive.
+    {
+        let mut scope = RootScope::new(&mut store);
+        let upgraded = weak_live.upgrade(&mut scope).unwrap();
+        live.unroot(&mut scope);
+        scope.as_context_mut().gc();
+        let data = upgraded.data(&scope)?.unwrap();
+        assert_eq!(*data.downcast_ref::<u32>().unwrap(), 1);
+    }
+
+    store.gc();
+    assert!(weak_live.upgrade(&mut store).is_none());
+
+    weak_live.release(&mut store);
+    weak_dead.release(&mut store);
+    Ok(())
+}
@@ -350,3 +350,157 @@ fn i31ref_as_anyref_global_ty() -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn watch_global_set() -> Result<()> {
+    let mut config = Config::new();
+    config.global_watchpoints(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (global $counter (export "counter") (mut i32) (i32.const 0))
+                (global $other (export "other") (mut i32) (i32.const 0))
+                (func (export "bump") (param i32)
+                    (global.set $counter (i32.add (global.get $counter) (local.get 0)))
+                    (global.set $other (i32.const 1)))
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, Vec::new());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let counter = instance.get_global(&mut store, "counter").unwrap();
+    let bump = instance.get_typed_func::<i32, ()>(&mut store, "bump")?;
+
+    counter.watch(&mut store, |mut store, val| {
+        let val = val.unwrap_i32();
+        store.data_mut().push(val);
+        if val > 100 {
+            anyhow::bail!("counter overflow");
+        }
+        Ok(())
+    })?;
+
+    bump.call(&mut store, 1)?;
+    bump.call(&mut store, 2)?;
+    assert_eq!(store.data(), &[1, 3]);
+
+    // Errors from the callback are raised as traps.
+    assert!(bump.call(&mut store, 200).is_err());
+    assert_eq!(store.data(), &[1, 3, 203]);
+
+    // Host writes don't notify watchers.
+    counter.set(&mut store, Val::I32(0))?;
+    counter.unwatch(&mut store);
+    bump.call(&mut store, 1)?;
+    assert_eq!(store.data(), &[1, 3, 203]);
+
+    // Watchpoints must be enabled in the configuration.
+    let mut store = Store::<()>::default();
+    let g = Global::new(
+        &mut store,
+        GlobalType::new(ValType::I32, Mutability::Var),
+        0.into(),
+    )?;
+    assert!(g.watch(&mut store, |_, _| Ok(())).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn global_with_storage() -> Result<()> {
+    use std::ptr::NonNull;
+    use std::sync::atomic::{AtomicI64, Ordering};
+
+    #[repr(C, align(16))]
+    struct Slot(AtomicI64, i64);
+
+    let slots = [Slot(AtomicI64::new(1), 0), Slot(AtomicI64::new(2), 0)];
+    let engine = Engine::default();
+    let module = Module::new(
+        &engine,
+        r#"(module
+            (global $config (import "" "config") i64)
+            (global $counter (import "" "counter") (mut i64))
+            (func (export "config") (result i64) global.get $config)
+            (func (export "bump") (global.set $counter (i64.add (global.get $counter) (i64.const 1))))
+        )"#,
+    )?;
+
+    // Two stores share the same storage for both globals.
+    let mut stores = Vec::new();
+    for _ in 0..2 {
+        let mut store = Store::new(&engine, ());
+        let config = unsafe {
+            Global::new_with_storage(
+                &mut store,
+                GlobalType::new(ValType::I64, Mutability::Const),
+                NonNull::from(&slots[0]).cast(),
+            )?
+        };
+        let counter = unsafe {
+            Global::new_with_storage(
+                &mut store,
+                GlobalType::new(ValType::I64, Mutability::Var),
+                NonNull::from(&slots[1]).cast(),
+            )?
+        };
+        let instance = Instance::new(&mut store, &module, &[config.into(), counter.into()])?;
+        stores.push((store, instance, config, counter));
+    }
+
+    for (store, instance, config, _) in stores.iter_mut() {
+        let get = instance.get_typed_func::<(), i64>(&mut *store, "config")?;
+        assert_eq!(get.call(&mut *store, ())?, 1);
+        assert_eq!(config.get(&mut *store).unwrap_i64(), 1);
+    }
+    slots[0].0.store(42, Ordering::SeqCst);
+    for (store, instance, config, _) in stores.iter_mut() {
+        let get = instance.get_typed_func::<(), i64>(&mut *store, "config")?;
+        assert_eq!(get.call(&mut *store, ())?, 42);
+        assert_eq!(config.get(&mut *store).unwrap_i64(), 42);
+    }
+
+    // Writes from either store, wasm or host, land in the shared storage.
+    for (store, instance, _, _) in stores.iter_mut() {
+        let bump = instance.get_typed_func::<(), ()>(&mut *store, "bump")?;
+        bump.call(&mut *store, ())?;
+    }
+    assert_eq!(slots[1].0.load(Ordering::SeqCst), 4);
+    let (store, _, config, counter) = &mut stores[0];
+    counter.set(&mut *store, Val::I64(10))?;
+    assert_eq!(slots[1].0.load(Ordering::SeqCst), 10);
+    assert!(config.set(&mut *store, Val::I64(0)).is_err());
+    drop(stores);
+    Ok(())
+}
+
+#[test]
+fn global_with_storage_errors() -> Result<()> {
+    #[repr(C, align(16))]
+    struct Slot([u8; 32]);
+
+    let mut slot = Slot([0; 32]);
+    let mut store = Store::<()>::default();
+    let storage = std::ptr::NonNull::from(&mut slot).cast::<u8>();
+
+    for ty in [ValType::V128, ValType::FUNCREF, ValType::EXTERNREF] {
+        let err = unsafe {
+            Global::new_with_storage(&mut store, GlobalType::new(ty, Mutability::Var), storage)
+                .unwrap_err()
+        };
+        assert!(
+            err.to_string().contains("must have a numeric type"),
+            "{err}"
+        );
+    }
+
+    let misaligned = unsafe { storage.add(4) };
+    let ty = GlobalType::new(ValType::I32, Mutability::Const);
+    let err = unsafe { Global::new_with_storage(&mut store, ty, misaligned).unwrap_err() };
+    assert!(err.to_string().contains("16-byte aligned"), "{err}");
+    Ok(())
+}
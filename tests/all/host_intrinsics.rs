@@ -0,0 +1,132 @@
+use wasmtime::*;
+
+extern "C" fn add(a: i32, b: i32) -> i32 {
+    a.wrapping_add(b)
+}
+
+extern "C" fn hypot(x: f64, y: f64) -> f64 {
+    x.hypot(y)
+}
+
+fn engine() -> Result<Engine> {
+    let mut config = Config::new();
+    config.host_intrinsic(
+        "env",
+        "add",
+        HostIntrinsic::new(add as extern "C" fn(i32, i32) -> i32),
+    );
+    config.host_intrinsic(
+        "math",
+        "hypot",
+        HostIntrinsic::new(hypot as extern "C" fn(f64, f64) -> f64),
+    );
+    Engine::new(&config)
+}
+
+const WAT: &str = r#"
+    (module
+        (import "env" "add" (func $add (param i32 i32) (result i32)))
+        (import "math" "hypot" (func $hypot (param f64 f64) (result f64)))
+        (table funcref (elem $add))
+        (type $add_ty (func (param i32 i32) (result i32)))
+
+        (func (export "add") (param i32 i32) (result i32)
+            (call $add (local.get 0) (local.get 1)))
+        (func (export "tail-add") (param i32 i32) (result i32)
+            (return_call $add (local.get 0) (local.get 1)))
+        (func (export "indirect-add") (param i32 i32) (result i32)
+            (call_indirect (type $add_ty) (local.get 0) (local.get 1) (i32.const 0)))
+        (func (export "hypot") (param f64 f64) (result f64)
+            (call $hypot (local.get 0) (local.get 1)))
+    )
+"#;
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn intrinsics_called() -> Result<()> {
+    let engine = engine()?;
+    let module = Module::new(&engine, WAT)?;
+    let mut linker = Linker::new(&engine);
+    linker.define_host_intrinsics()?;
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &module)?;
+
+    for export in ["add", "tail-add", "indirect-add"] {
+        let add = instance.get_typed_func::<(i32, i32), i32>(&mut store, export)?;
+        assert_eq!(add.call(&mut store, (1, 2))?, 3);
+        assert_eq!(add.call(&mut store, (i32::MAX, 1))?, i32::MIN);
+    }
+    let hypot = instance.get_typed_func::<(f64, f64), f64>(&mut store, "hypot")?;
+    assert_eq!(hypot.call(&mut store, (3.0, 4.0))?, 5.0);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn direct_calls_bypass_import() -> Result<()> {
+    let engine = engine()?;
+    if engine.is_pulley() {
+        return Ok(());
+    }
+    let module = Module::new(&engine, WAT)?;
+    let mut linker = Linker::new(&engine);
+    linker.define_host_intrinsics()?;
+    linker.allow_shadowing(true);
+    linker.func_wrap("env", "add", |_: i32, _: i32| -> i32 { -1 })?;
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &module)?;
+
+    let add = instance.get_typed_func::<(i32, i32), i32>(&mut store, "add")?;
+    assert_eq!(add.call(&mut store, (1, 2))?, 3);
+
+    // Calls which don't go directly to the import use whatever was provided
+    // at instantiation time.
+    let indirect_add = instance.get_typed_func::<(i32, i32), i32>(&mut store, "indirect-add")?;
+    assert_eq!(indirect_add.call(&mut store, (1, 2))?, -1);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn mismatched_type_uses_import() -> Result<()> {
+    let engine = engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "env" "add" (func $add (param i64 i64) (result i64)))
+                (func (export "add") (param i64 i64) (result i64)
+                    (call $add (local.get 0) (local.get 1)))
+            )
+        "#,
+    )?;
+    let mut linker = Linker::new(&engine);
+    linker.func_wrap("env", "add", |a: i64, b: i64| a * b)?;
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &module)?;
+    let add = instance.get_typed_func::<(i64, i64), i64>(&mut store, "add")?;
+    assert_eq!(add.call(&mut store, (3, 4))?, 12);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn serialized_modules_require_same_intrinsics() -> Result<()> {
+    let engine = engine()?;
+    let bytes = Module::new(&engine, WAT)?.serialize()?;
+
+    let module = unsafe { Module::deserialize(&engine, &bytes)? };
+    let mut linker = Linker::new(&engine);
+    linker.define_host_intrinsics()?;
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &module)?;
+    let add = instance.get_typed_func::<(i32, i32), i32>(&mut store, "add")?;
+    assert_eq!(add.call(&mut store, (20, 22))?, 42);
+
+    let err = unsafe { Module::deserialize(&Engine::default(), &bytes).unwrap_err() };
+    assert!(
+        format!("{err:?}").contains("different host intrinsics"),
+        "bad error: {err:?}"
+    );
+    Ok(())
+}
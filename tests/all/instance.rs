@@ -79,3 +79,241 @@ fn linear_memory_limits() -> Result<()> {
         Ok(())
     }
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn instantiate_batch() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "" "id" (func $id (result i32)))
+                (global $g (export "g") (mut i32) (i32.const 0))
+                (func $start
+                    call $id
+                    global.set $g)
+                (start $start)
+            )
+        "#,
+    )?;
+    let mut linker = Linker::new(&engine);
+    linker.func_wrap("", "id", |caller: Caller<'_, i32>| *caller.data())?;
+    let pre = linker.instantiate_pre(&module)?;
+
+    let mut stores = (0..10).map(|i| Store::new(&engine, i)).collect::<Vec<_>>();
+    let instances = pre.instantiate_batch(&mut stores)?;
+    assert_eq!(instances.len(), stores.len());
+    for (i, (store, instance)) in stores.iter_mut().zip(instances).enumerate() {
+        let g = instance.get_global(&mut *store, "g").unwrap();
+        assert_eq!(g.get(&mut *store).unwrap_i32(), i32::try_from(i)?);
+    }
+
+    // Stores from other engines are rejected.
+    let mut stores = vec![Store::new(&engine, 0), Store::new(&Engine::default(), 1)];
+    assert!(pre.instantiate_batch(&mut stores).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn instantiate_batch_memories() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (memory (export "memory") 1)
+                (data (i32.const 0) "abc")
+            )
+        "#,
+    )?;
+    let pre = Linker::new(&engine).instantiate_pre(&module)?;
+
+    // Each instance of a batch gets a memory of its own, which can grow
+    // independently of the others.
+    let mut stores = (0..4).map(|_| Store::new(&engine, ())).collect::<Vec<_>>();
+    let instances = pre.instantiate_batch(&mut stores)?;
+    for (i, (store, instance)) in stores.iter_mut().zip(&instances).enumerate() {
+        let memory = instance.get_memory(&mut *store, "memory").unwrap();
+        assert_eq!(&memory.data(&*store)[..3], b"abc");
+        memory.data_mut(&mut *store)[0] = u8::try_from(i)?;
+        memory.grow(&mut *store, u64::try_from(i)?)?;
+    }
+    for (i, (store, instance)) in stores.iter_mut().zip(&instances).enumerate() {
+        let memory = instance.get_memory(&mut *store, "memory").unwrap();
+        assert_eq!(memory.data(&*store)[0], u8::try_from(i)?);
+        assert_eq!(memory.size(&*store), 1 + u64::try_from(i)?);
+    }
+
+    // Dropping stores in any order releases their memories.
+    stores.remove(1);
+    drop(stores);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn allocate_and_initialize() -> Result<()> {
+    let mut config = Config::new();
+    config.memory_init_cow(false);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "" "base" (global $base i32))
+                (memory (export "memory") 1)
+                (data (i32.const 0) "abc")
+                (data "passive")
+                (data (global.get $base) "def")
+                (data (i32.const 16) "ghi")
+                (global $g (export "g") (mut i32) (i32.const 0))
+                (func $start
+                    (global.set $g (i32.load8_u (i32.const 0))))
+                (start $start)
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    let base = Global::new(
+        &mut store,
+        GlobalType::new(ValType::I32, Mutability::Const),
+        Val::I32(8),
+    )?;
+    linker.define(&mut store, "", "base", base)?;
+    let pre = linker.instantiate_pre(&module)?;
+
+    let mut allocated = pre.allocate(&mut store)?;
+    let segments = allocated
+        .data_segments()
+        .iter()
+        .map(|s| (s.index(), s.offset(), s.data().to_vec()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        segments,
+        [
+            (0, 0, b"abc".to_vec()),
+            (2, 8, b"def".to_vec()),
+            (3, 16, b"ghi".to_vec()),
+        ]
+    );
+
+    // Passive segments and the wrong length of data are rejected.
+    assert!(allocated.set_data(1, b"passive".to_vec()).is_err());
+    assert!(allocated.set_data(0, b"abcd".to_vec()).is_err());
+
+    allocated.set_data(0, b"xyz".to_vec())?;
+    allocated.defer_data(3)?;
+    let instance = allocated.initialize(&mut store)?;
+
+    // The start function observes replaced segments.
+    let g = instance.get_global(&mut store, "g").unwrap();
+    assert_eq!(g.get(&mut store).unwrap_i32(), i32::from(b'x'));
+
+    // Deferred segments are still applied, even without any image to map
+    // them from.
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    assert_eq!(&memory.data(&store)[..19], b"xyz\0\0\0\0\0def\0\0\0\0\0ghi");
+    Ok(())
+}
+
+#[test]
+fn allocate_with_shared_memory() -> Result<()> {
+    let mut config = Config::new();
+    config.wasm_threads(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (memory (export "memory") 1 1 shared)
+                (data (i32.const 0) "abc")
+                (data (i32.const 2) "def")
+            )
+        "#,
+    )?;
+    let linker = Linker::new(&engine);
+    let pre = linker.instantiate_pre(&module)?;
+
+    let mut store = Store::new(&engine, ());
+    let mut allocated = pre.allocate(&mut store)?;
+    assert!(matches!(
+        allocated.data_segments()[0].memory(),
+        Extern::SharedMemory(_)
+    ));
+
+    // Other threads may access shared memories, so their segments can't be
+    // mapped lazily, but they can be replaced.
+    assert!(allocated.defer_data(0).is_err());
+    allocated.set_data(0, b"xyz".to_vec())?;
+    let instance = allocated.initialize(&mut store)?;
+
+    let memory = instance.get_shared_memory(&mut store, "memory").unwrap();
+    let contents = memory.data()[..5]
+        .iter()
+        .map(|cell| unsafe { *cell.get() })
+        .collect::<Vec<_>>();
+    assert_eq!(contents, b"xydef");
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn allocate_with_memory_image() -> Result<()> {
+    // The default configuration merges data segments into images of memory.
+    let engine = Engine::default();
+    let module = Module::new(
+        &engine,
+        format!(
+            r#"
+                (module
+                    (memory (export "memory") 2)
+                    (data (i32.const 0) "abc")
+                    (data (i32.const 16) "defghi")
+                    (data (i32.const 20) "jk")
+                    (data (i32.const 100) "{}")
+                )
+            "#,
+            "x".repeat(0x10000),
+        ),
+    )?;
+    let linker = Linker::new(&engine);
+    let pre = linker.instantiate_pre(&module)?;
+
+    let mut store = Store::new(&engine, ());
+    let mut allocated = pre.allocate(&mut store)?;
+    let segments = allocated
+        .data_segments()
+        .iter()
+        .map(|s| (s.index(), s.offset(), s.data().len()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        segments,
+        [(0, 0, 3), (1, 16, 6), (2, 20, 2), (3, 100, 0x10000)]
+    );
+
+    // The contents of segments partially overwritten by later ones are still
+    // available.
+    assert_eq!(allocated.data_segments()[1].data(), b"defghi");
+
+    // Replacing such a segment keeps the contents of the later segment, and
+    // contents provided at runtime can be applied lazily.
+    allocated.set_data(1, b"DEFGHI".to_vec())?;
+    let contents = (0..0x10000).map(|i| i as u8).collect::<Vec<_>>();
+    allocated.set_data(3, contents.clone())?.defer_data(3)?;
+    let instance = allocated.initialize(&mut store)?;
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    assert_eq!(&memory.data(&store)[..3], b"abc");
+    assert_eq!(&memory.data(&store)[16..22], b"DEFGjk");
+    assert_eq!(&memory.data(&store)[100..0x10064], &contents[..]);
+    assert_eq!(memory.data(&store)[0x10064], 0);
+
+    // Other instances of the module are unaffected.
+    let instance = pre.instantiate(&mut store)?;
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    assert_eq!(&memory.data(&store)[16..22], b"defgjk");
+    assert!(memory.data(&store)[100..0x10064].iter().all(|b| *b == b'x'));
+    Ok(())
+}
@@ -1175,3 +1175,50 @@ fn growth_trap() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn total_memory_size() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(
+        &engine,
+        r#"(module
+            (memory $m (export "m") 1)
+            (table (export "t") 0 funcref)
+           )"#,
+    )?;
+
+    let mut store = Store::new(
+        &engine,
+        StoreLimitsBuilder::new()
+            .total_memory_size(3 * WASM_PAGE_SIZE)
+            .build(),
+    );
+    store.limiter(|s| s as &mut dyn ResourceLimiter);
+
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let memory = instance.get_memory(&mut store, "m").unwrap();
+    let host_memory = Memory::new(&mut store, MemoryType::new(1, None))?;
+    assert_eq!(store.memory_consumed(), 2 * WASM_PAGE_SIZE);
+
+    // The budget is shared by all memories and tables in the store.
+    memory.grow(&mut store, 1)?;
+    assert_eq!(store.memory_consumed(), 3 * WASM_PAGE_SIZE);
+    assert!(host_memory.grow(&mut store, 1).is_err());
+    assert!(memory.grow(&mut store, 1).is_err());
+    let table = instance.get_table(&mut store, "t").unwrap();
+    assert!(table.grow(&mut store, 1, Ref::Func(None)).is_err());
+    assert_eq!(store.memory_consumed(), 3 * WASM_PAGE_SIZE);
+
+    // A store without a limit counts table elements too.
+    let mut store = Store::new(&engine, StoreLimits::default());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let table = instance.get_table(&mut store, "t").unwrap();
+    table.grow(&mut store, 4, Ref::Func(None))?;
+    assert_eq!(
+        store.memory_consumed(),
+        WASM_PAGE_SIZE + 4 * std::mem::size_of::<usize>()
+    );
+
+    Ok(())
+}
@@ -769,3 +769,378 @@ fn linker_defines_table_subtype_err() -> Result<()> {
 
     Ok(())
 }
+
+#[derive(Debug)]
+struct NotFound;
+
+impl std::fmt::Display for NotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("not found")
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+impl HostError for NotFound {
+    fn code(&self) -> i32 {
+        44
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn func_wrap_fallible_error_policies() -> Result<()> {
+    let engine = Engine::default();
+    let mut linker = Linker::<()>::new(&engine);
+    linker.error_policy("errno", ErrorPolicy::ErrorCode)?;
+    assert!(linker.error_policy("errno", ErrorPolicy::Result).is_err());
+
+    let check = |_: Caller<'_, ()>, (x,): (i32,)| -> Result<(), NotFound> {
+        if x == 0 {
+            Ok(())
+        } else {
+            Err(NotFound)
+        }
+    };
+    let get = |_: Caller<'_, ()>, (x,): (i64,)| -> Result<i64, NotFound> {
+        if x == 0 {
+            Ok(7)
+        } else {
+            Err(NotFound)
+        }
+    };
+    linker.func_wrap_fallible("errno", "check", check)?;
+    linker.func_wrap_fallible("errno", "get", get)?;
+    linker.func_wrap_fallible("trap", "check", check)?;
+
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "errno" "check" (func $errno_check (param i32) (result i32)))
+                (import "errno" "get" (func $errno_get (param i64) (result i64)))
+                (import "trap" "check" (func $trap_check (param i32)))
+                (func (export "errno-check") (param i32) (result i32)
+                    (call $errno_check (local.get 0)))
+                (func (export "errno-get") (param i64) (result i64)
+                    (call $errno_get (local.get 0)))
+                (func (export "trap-check") (param i32)
+                    (call $trap_check (local.get 0)))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &module)?;
+
+    let errno_check = instance.get_typed_func::<i32, i32>(&mut store, "errno-check")?;
+    assert_eq!(errno_check.call(&mut store, 0)?, 0);
+    assert_eq!(errno_check.call(&mut store, 1)?, -44);
+
+    let errno_get = instance.get_typed_func::<i64, i64>(&mut store, "errno-get")?;
+    assert_eq!(errno_get.call(&mut store, 0)?, 7);
+    assert_eq!(errno_get.call(&mut store, 1)?, -44);
+
+    let trap_check = instance.get_typed_func::<i32, ()>(&mut store, "trap-check")?;
+    trap_check.call(&mut store, 0)?;
+    let err = trap_check.call(&mut store, 1).unwrap_err();
+    assert!(err.downcast_ref::<NotFound>().is_some(), "{err:?}");
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn lazy_imports() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "sys" "add" (func $add (param i32 i32) (result i32)))
+                (import "sys" "missing" (func $missing))
+                (func (export "add") (param i32 i32) (result i32)
+                    (call $add (local.get 0) (local.get 1)))
+                (func (export "missing") call $missing)
+            )
+        "#,
+    )?;
+    let mut linker = Linker::new(&engine);
+    linker.define_unknown_imports_as_lazy(&module)?;
+
+    // Without a resolver lazy imports trap when called, but instantiation
+    // still succeeds.
+    let mut store = Store::new(&engine, 0);
+    let instance = linker.instantiate(&mut store, &module)?;
+    let add = instance.get_typed_func::<(i32, i32), i32>(&mut store, "add")?;
+    let err = add.call(&mut store, (1, 2)).unwrap_err();
+    assert!(
+        format!("{err:?}").contains("no import resolver is configured"),
+        "{err:?}"
+    );
+
+    store.import_resolver(|mut store, module, name, _ty| {
+        *store.data_mut() += 1;
+        match (module, name) {
+            ("sys", "add") => Ok(Func::wrap(&mut store, |a: i32, b: i32| a + b)),
+            _ => Err(anyhow::anyhow!("no such import")),
+        }
+    });
+    assert_eq!(add.call(&mut store, (1, 2))?, 3);
+    assert_eq!(add.call(&mut store, (3, 4))?, 7);
+    assert_eq!(*store.data(), 1);
+
+    // Other instances in the same store reuse the resolved function.
+    let instance = linker.instantiate(&mut store, &module)?;
+    let add = instance.get_typed_func::<(i32, i32), i32>(&mut store, "add")?;
+    assert_eq!(add.call(&mut store, (5, 6))?, 11);
+    assert_eq!(*store.data(), 1);
+
+    // Failed resolutions trap and are retried on the next call.
+    let missing = instance.get_typed_func::<(), ()>(&mut store, "missing")?;
+    let err = missing.call(&mut store, ()).unwrap_err();
+    assert!(format!("{err:?}").contains("no such import"), "{err:?}");
+    assert!(missing.call(&mut store, ()).is_err());
+    assert_eq!(*store.data(), 3);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn lazy_imports_only_patch_stub() -> Result<()> {
+    let engine = Engine::default();
+    let lazy = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "sys" "add" (func $add (param i32 i32) (result i32)))
+                (export "add" (func $add))
+            )
+        "#,
+    )?;
+    let mut linker = Linker::new(&engine);
+    linker.define_unknown_imports_as_lazy(&lazy)?;
+    let mut store = Store::new(&engine, ());
+    store.import_resolver(|mut store, _module, _name, _ty| {
+        Ok(Func::wrap(&mut store, |a: i32, b: i32| a + b))
+    });
+    let stub = linker
+        .instantiate(&mut store, &lazy)?
+        .get_func(&mut store, "add")
+        .unwrap();
+
+    // This module calls the lazy stub, but imports a different function
+    // under the stub's name which must not be replaced.
+    let caller = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "lazy" "add" (func $lazy (param i32 i32) (result i32)))
+                (import "sys" "add" (func $add (param i32 i32) (result i32)))
+                (func (export "lazy") (param i32 i32) (result i32)
+                    (call $lazy (local.get 0) (local.get 1)))
+                (func (export "add") (param i32 i32) (result i32)
+                    (call $add (local.get 0) (local.get 1)))
+            )
+        "#,
+    )?;
+    let sub = Func::wrap(&mut store, |a: i32, b: i32| a - b);
+    let instance = Instance::new(&mut store, &caller, &[stub.into(), sub.into()])?;
+    let lazy = instance.get_typed_func::<(i32, i32), i32>(&mut store, "lazy")?;
+    let add = instance.get_typed_func::<(i32, i32), i32>(&mut store, "add")?;
+    assert_eq!(lazy.call(&mut store, (5, 3))?, 8);
+    assert_eq!(add.call(&mut store, (5, 3))?, 2);
+    assert_eq!(lazy.call(&mut store, (5, 3))?, 8);
+    Ok(())
+}
+
+#[test]
+fn define_checked_reports_shadowing() -> Result<()> {
+    let mut store = Store::<()>::default();
+    let mut linker = Linker::new(store.engine());
+    linker.allow_shadowing(true);
+    let global = Global::new(
+        &mut store,
+        GlobalType::new(ValType::I32, Mutability::Const),
+        Val::I32(0),
+    )?;
+    linker.func_wrap("host", "item", |x: i32| x)?;
+
+    let err = linker
+        .define_checked(&store, "host", "item", global)
+        .unwrap_err();
+    let msg = format!("{err:?}");
+    assert!(msg.contains("`host::item` defined twice"), "{msg}");
+    assert!(msg.contains("already defined as function"), "{msg}");
+    assert!(msg.contains("now defined as global"), "{msg}");
+    assert!(linker
+        .get(&mut store, "host", "item")
+        .unwrap()
+        .into_func()
+        .is_some());
+
+    linker.define_checked(&store, "host", "other", global)?;
+    assert!(linker
+        .get(&mut store, "host", "other")
+        .unwrap()
+        .into_global()
+        .is_some());
+    Ok(())
+}
+
+#[test]
+fn strict_imports_report_all_errors() -> Result<()> {
+    let mut store = Store::<()>::default();
+    let mut linker = Linker::new(store.engine());
+    linker.func_wrap("host", "ok", || {})?;
+    linker.func_wrap("host", "mistyped", |x: i64| x)?;
+    let module = Module::new(
+        store.engine(),
+        r#"
+            (module
+                (import "host" "missing" (func))
+                (import "host" "ok" (func))
+                (import "host" "mistyped" (func (param i32) (result i32)))
+                (import "host" "not-a-memory" (memory 1))
+            )
+        "#,
+    )?;
+    linker.func_wrap("host", "not-a-memory", || {})?;
+
+    let unresolved = linker
+        .unresolved_imports(&module)
+        .map(|import| import.name())
+        .collect::<Vec<_>>();
+    assert_eq!(unresolved, ["missing"]);
+
+    // By default only the first error is reported.
+    let err = linker.instantiate(&mut store, &module).unwrap_err();
+    assert!(err.downcast_ref::<UnknownImportError>().is_some());
+
+    linker.strict_imports(true);
+    let err = linker.instantiate(&mut store, &module).unwrap_err();
+    assert_eq!(ErrorKind::of(&err), Some(ErrorKind::Link));
+    let errors = err.downcast_ref::<ImportErrors>().unwrap();
+    let errors = errors.errors();
+    assert_eq!(errors.len(), 3);
+
+    assert_eq!(errors[0].module(), "host");
+    assert_eq!(errors[0].name(), "missing");
+    errors[0].expected().unwrap_func();
+    assert!(errors[0].provided().is_none());
+
+    assert_eq!(errors[1].name(), "mistyped");
+    let expected = errors[1].expected().unwrap_func();
+    assert!(expected.params().next().unwrap().is_i32());
+    let provided = errors[1].provided().unwrap().unwrap_func();
+    assert!(provided.params().next().unwrap().is_i64());
+
+    assert_eq!(errors[2].name(), "not-a-memory");
+    errors[2].expected().unwrap_memory();
+    errors[2].provided().unwrap().unwrap_func();
+
+    let msg = err.to_string();
+    assert!(msg.contains("3 imports could not be resolved"), "{msg}");
+    assert!(
+        msg.contains("`host::missing` has not been defined"),
+        "{msg}"
+    );
+    assert!(
+        msg.contains("incompatible import type for `host::mistyped`"),
+        "{msg}"
+    );
+
+    assert!(linker.instantiate_pre(&module).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn hostcall_batch() -> Result<()> {
+    fn write_u32(memory: &Memory, store: &mut Store<u64>, offset: usize, value: u32) {
+        memory.write(store, offset, &value.to_le_bytes()).unwrap();
+    }
+    fn submit(memory: &Memory, store: &mut Store<u64>, index: u32, op: [u64; 4]) {
+        let mut sqe = [0; 32];
+        sqe[0..4].copy_from_slice(&(op[0] as u32).to_le_bytes());
+        sqe[8..16].copy_from_slice(&op[1].to_le_bytes());
+        sqe[16..24].copy_from_slice(&op[2].to_le_bytes());
+        sqe[24..32].copy_from_slice(&op[3].to_le_bytes());
+        let offset = RING + 32 + (index as usize % ENTRIES) * 32;
+        memory.write(&mut *store, offset, &sqe).unwrap();
+        write_u32(memory, store, RING + 4, index + 1);
+    }
+    fn completion(memory: &Memory, store: &Store<u64>, index: usize) -> (u64, i64) {
+        let offset = RING + 32 + ENTRIES * 32 + (index % ENTRIES) * 16;
+        let data = &memory.data(store)[offset..][..16];
+        (
+            u64::from_le_bytes(data[..8].try_into().unwrap()),
+            i64::from_le_bytes(data[8..].try_into().unwrap()),
+        )
+    }
+    const RING: usize = 64;
+    const ENTRIES: usize = 4;
+
+    let engine = Engine::default();
+    let mut batch = HostcallBatch::new();
+    batch.op(1, |_, op| Ok((op.args[0] + op.args[1]) as i64))?;
+    batch.op(2, |caller: &mut Caller<'_, u64>, op| {
+        *caller.data_mut() += op.args[0];
+        Ok(*caller.data_mut() as i64)
+    })?;
+    batch.op(3, |_, _| bail!("failed operation"))?;
+    assert!(batch.op(1, |_, _| Ok(0)).is_err());
+
+    let mut linker = Linker::new(&engine);
+    linker.define_hostcall_batch(&batch)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "wasmtime:runtime/batch" "submit" (func $submit (param i32) (result i32)))
+                (memory (export "memory") 1)
+                (func (export "submit") (param i32) (result i32)
+                    (call $submit (local.get 0)))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, 0);
+    let instance = linker.instantiate(&mut store, &module)?;
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    let run = instance.get_typed_func::<u32, u32>(&mut store, "submit")?;
+
+    // A ring whose size isn't a power of two is rejected.
+    write_u32(&memory, &mut store, RING + 16, 3);
+    assert!(run.call(&mut store, RING as u32).is_err());
+    write_u32(&memory, &mut store, RING + 16, ENTRIES as u32);
+    assert_eq!(run.call(&mut store, RING as u32)?, 0);
+
+    submit(&memory, &mut store, 0, [1, 10, 2, 3]);
+    submit(&memory, &mut store, 1, [2, 11, 5, 0]);
+    submit(&memory, &mut store, 2, [99, 12, 0, 0]);
+    assert_eq!(run.call(&mut store, RING as u32)?, 3);
+    assert_eq!(completion(&memory, &store, 0), (10, 5));
+    assert_eq!(completion(&memory, &store, 1), (11, 5));
+    assert_eq!(
+        completion(&memory, &store, 2),
+        (12, HostcallBatch::<u64>::UNSUPPORTED)
+    );
+    assert_eq!(*store.data(), 5);
+
+    // Only one completion fits until the guest consumes the others.
+    for i in 3..6 {
+        submit(&memory, &mut store, i, [2, u64::from(i), 1, 0]);
+    }
+    assert_eq!(run.call(&mut store, RING as u32)?, 1);
+    write_u32(&memory, &mut store, RING + 8, 4);
+    assert_eq!(run.call(&mut store, RING as u32)?, 2);
+    assert_eq!(completion(&memory, &store, 5), (5, 8));
+
+    // Errors from handlers trap, after consuming the submission.
+    submit(&memory, &mut store, 6, [3, 13, 0, 0]);
+    let err = run.call(&mut store, RING as u32).unwrap_err();
+    assert!(format!("{err:?}").contains("failed operation"), "{err:?}");
+    assert_eq!(run.call(&mut store, RING as u32)?, 0);
+
+    // Rings must fit in memory.
+    assert!(run.call(&mut store, 0x10000 - 16).is_err());
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use wasmtime::*;
+
+fn lint(wat: &str, config: &LintConfig) -> Result<Vec<Lint>> {
+    let engine = Engine::default();
+    CodeBuilder::new(&engine)
+        .wasm_binary_or_text(wat.as_bytes(), None)?
+        .lint(config)
+}
+
+#[test]
+fn clean_module() -> Result<()> {
+    let lints = lint(
+        r#"
+            (module
+                (import "wasi_snapshot_preview1" "fd_write"
+                    (func (param i32 i32 i32 i32) (result i32)))
+                (memory 1)
+                (data (i32.const 0) "hello")
+                (func $start
+                    (loop
+                        (br_if 0 (i32.const 0))))
+                (start $start)
+            )
+        "#,
+        &LintConfig::new(),
+    )?;
+    assert_eq!(lints, []);
+    Ok(())
+}
+
+#[test]
+fn large_items() -> Result<()> {
+    let config = LintConfig::new()
+        .max_data_segment_size(4)
+        .max_br_table_targets(2)
+        .max_function_size(20);
+    let lints = lint(
+        r#"
+            (module
+                (import "" "" (func))
+                (memory 1)
+                (data (i32.const 0) "abcd")
+                (data (i32.const 0) "abcde")
+                (func (param i32)
+                    block block block
+                        (br_table 0 1 2 0 (local.get 0))
+                    end end end)
+                (func
+                    nop nop nop nop nop nop nop nop nop nop
+                    nop nop nop nop nop nop nop nop nop nop)
+            )
+        "#,
+        &config,
+    )?;
+    assert_eq!(lints.len(), 3, "{lints:?}");
+    assert!(matches!(
+        lints[0],
+        Lint::LargeBrTable {
+            func_index: 1,
+            targets: 3,
+            ..
+        }
+    ));
+    // The body includes its local declarations and final `end`.
+    assert_eq!(
+        lints[1],
+        Lint::LargeFunction {
+            func_index: 2,
+            size: 22,
+        }
+    );
+    assert_eq!(lints[2], Lint::LargeDataSegment { index: 1, size: 5 });
+    Ok(())
+}
+
+#[test]
+fn non_terminating_start() -> Result<()> {
+    let lints = lint(
+        r#"
+            (module
+                (import "" "" (func))
+                (func $start
+                    (loop $l
+                        (block
+                            (br $l))))
+                (start $start)
+            )
+        "#,
+        &LintConfig::new(),
+    )?;
+    assert_eq!(lints, [Lint::NonTerminatingStart { func_index: 1 }]);
+
+    // Calls may leave the loop by trapping or exiting the process.
+    let lints = lint(
+        r#"
+            (module
+                (import "" "" (func $f))
+                (func $start
+                    (loop
+                        (call $f)
+                        (br 0)))
+                (start $start)
+            )
+        "#,
+        &LintConfig::new(),
+    )?;
+    assert_eq!(lints, []);
+    Ok(())
+}
+
+#[test]
+fn shadowed_wasi_imports() -> Result<()> {
+    let lints = lint(
+        r#"
+            (module
+                (import "wasi_snapshot_preview1" "fd_wirte" (func))
+                (import "wasi_unstable" "proc_exit" (func (param i32)))
+                (import "wasi_unstable" "fd_write" (global i32))
+                (import "env" "fd_wirte" (func))
+            )
+        "#,
+        &LintConfig::new(),
+    )?;
+    assert_eq!(
+        lints,
+        [
+            Lint::ShadowedWasiImport {
+                module: "wasi_snapshot_preview1".to_string(),
+                name: "fd_wirte".to_string(),
+            },
+            Lint::ShadowedWasiImport {
+                module: "wasi_unstable".to_string(),
+                name: "fd_write".to_string(),
+            },
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn components_are_rejected() -> Result<()> {
+    assert!(lint("(component)", &LintConfig::new()).is_err());
+    Ok(())
+}
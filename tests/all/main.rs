@@ -3,15 +3,20 @@
 
 mod arrays;
 mod async_functions;
+mod blobs;
 mod call_hook;
 mod cli_tests;
 mod code_too_large;
 mod component_model;
 mod coredump;
+mod coverage;
 mod custom_code_memory;
 mod debug;
 mod defaults;
+mod determinism;
+mod dylink;
 mod epoch_interruption;
+mod error_kind;
 mod externals;
 mod fuel;
 mod func;
@@ -19,6 +24,7 @@ mod funcref;
 mod gc;
 mod globals;
 mod host_funcs;
+mod host_intrinsics;
 mod i31ref;
 mod iloop;
 mod import_calling_export;
@@ -27,8 +33,10 @@ mod instance;
 mod invoke_func_via_table;
 mod limits;
 mod linker;
+mod lint;
 mod memory;
 mod memory_creator;
+mod metrics;
 mod module;
 mod module_serialize;
 mod name;
@@ -36,7 +44,9 @@ mod noextern;
 mod piped_tests;
 mod pooling_allocator;
 mod pulley;
+mod quota;
 mod relocs;
+mod single_step;
 mod stack_creator;
 mod stack_overflow;
 mod stack_switching;
@@ -46,6 +56,7 @@ mod table;
 mod threads;
 mod traps;
 mod types;
+mod unwind_info;
 mod wait_notify;
 mod wasi_testsuite;
 mod winch_engine_features;
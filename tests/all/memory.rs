@@ -1,5 +1,6 @@
 use rayon::prelude::*;
 use std::sync::atomic::{AtomicU32, Ordering::SeqCst};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use wasmtime::*;
 use wasmtime_test_macros::wasmtime_test;
@@ -756,3 +757,131 @@ fn configure_zero(config: &mut Config) -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn typed_views() -> Result<()> {
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, ());
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "load") (param i32) (result i64)
+                    (i64.load (local.get 0)))
+                (func (export "store") (param i32 f32)
+                    (f32.store (local.get 0) (local.get 1)))
+            )
+        "#,
+    )?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    let load = instance.get_typed_func::<u32, i64>(&mut store, "load")?;
+    let store_f32 = instance.get_typed_func::<(u32, f32), ()>(&mut store, "store")?;
+
+    // Values written from the host are seen by wasm, and vice versa.
+    let values = memory.typed_slice_mut::<i64>(&mut store, 16, 2)?;
+    values[0].set(-2);
+    values[1] = Le::new(0x0102_0304_0506_0708);
+    assert_eq!(load.call(&mut store, 16)?, -2);
+    assert_eq!(load.call(&mut store, 24)?, 0x0102_0304_0506_0708);
+    assert_eq!(memory.data(&store)[24..32], [8, 7, 6, 5, 4, 3, 2, 1]);
+
+    store_f32.call(&mut store, (33, 1.5))?;
+    assert_eq!(memory.read_le::<f32>(&store, 33)?, 1.5);
+    memory.write_le(&mut store, 35, [1u16, 2u16])?;
+    assert_eq!(memory.read_le::<u32>(&store, 35)?, 0x0002_0001);
+
+    let values = memory.typed_slice::<[u8; 4]>(&store, 35, 1)?;
+    assert_eq!(values[0].get(), [1, 0, 2, 0]);
+
+    // Bounds and alignment are checked.
+    assert!(memory.typed_slice::<u16>(&store, 65534, 1).is_ok());
+    let err = memory.typed_slice::<u16>(&store, 65534, 2).unwrap_err();
+    assert_eq!(err.to_string(), "out of bounds memory access");
+    assert!(memory.typed_slice::<u8>(&store, 0, usize::MAX).is_err());
+    assert!(memory.typed_slice::<u64>(&store, usize::MAX, 1).is_err());
+    let err = memory.typed_slice::<u32>(&store, 2, 1).unwrap_err();
+    assert_eq!(err.to_string(), "misaligned memory access");
+    assert!(memory.read_le::<u64>(&store, 65529).is_err());
+    assert!(memory.write_le(&mut store, 65529, 0u64).is_err());
+    assert!(memory.write_le(&mut store, 65528, 0u64).is_ok());
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn memory_growth_hook_and_stats() -> Result<()> {
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, StoreLimitsBuilder::new().build());
+    let events = Arc::new(Mutex::new(Vec::new()));
+    store.on_memory_grow({
+        let events = events.clone();
+        move |growth| events.lock().unwrap().push(growth.clone())
+    });
+
+    let mut linker = Linker::new(&engine);
+    linker.define_alloc_stats()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "wasmtime:runtime/alloc-stats" "grows" (func $grows (result i64)))
+                (import "wasmtime:runtime/alloc-stats" "denied" (func $denied (result i64)))
+                (import "wasmtime:runtime/alloc-stats" "failed" (func $failed (result i64)))
+                (import "wasmtime:runtime/alloc-stats" "grown-bytes" (func $grown_bytes (result i64)))
+                (memory 1 2)
+                (func (export "grow") (param i32) (result i32)
+                    (memory.grow (local.get 0)))
+                (func (export "stats") (result i64 i64 i64 i64)
+                    (call $grows) (call $denied) (call $failed) (call $grown_bytes))
+            )
+        "#,
+    )?;
+    let instance = linker.instantiate(&mut store, &module)?;
+    let grow = instance.get_typed_func::<u32, i32>(&mut store, "grow")?;
+    let stats = instance.get_typed_func::<(), (i64, i64, i64, i64)>(&mut store, "stats")?;
+
+    // Neither instantiation nor growing by zero pages counts as growth.
+    assert_eq!(grow.call(&mut store, 0)?, 1);
+    assert_eq!(store.memory_growth_stats(), MemoryGrowthStats::default());
+
+    assert_eq!(grow.call(&mut store, 1)?, 1);
+    assert_eq!(grow.call(&mut store, 1)?, -1);
+
+    // Rejections by the store's limiter are distinguished from failures.
+    *store.data_mut() = StoreLimitsBuilder::new().memory_size(1 << 16).build();
+    store.limiter(|s| s as &mut dyn ResourceLimiter);
+    assert_eq!(grow.call(&mut store, 1)?, -1);
+
+    assert_eq!(
+        store.memory_growth_stats(),
+        MemoryGrowthStats {
+            grows: 1,
+            denied: 1,
+            failed: 1,
+            grown_bytes: 1 << 16,
+        }
+    );
+    assert_eq!(stats.call(&mut store, ())?, (1, 1, 1, 1 << 16));
+
+    let events = events.lock().unwrap();
+    let summary = events
+        .iter()
+        .map(|g| (g.old_pages, g.requested_pages, g.outcome.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        [
+            (1, 2, MemoryGrowthOutcome::Grown),
+            (2, 3, MemoryGrowthOutcome::ExceedsMaximum),
+            (2, 3, MemoryGrowthOutcome::Denied),
+        ]
+    );
+    assert!(events.iter().all(|g| g.page_size == 1 << 16));
+    assert!(events.iter().all(|g| g.reserved_pages >= 2));
+
+    Ok(())
+}
@@ -0,0 +1,196 @@
+#![cfg(not(miri))]
+
+use wasmtime::*;
+
+fn engine(f: impl FnOnce(&mut Config)) -> Result<Engine> {
+    let mut config = Config::new();
+    config.metrics(true);
+    f(&mut config);
+    Engine::new(&config)
+}
+
+#[test]
+fn disabled_by_default() {
+    assert!(Engine::default().metrics().is_none());
+}
+
+#[test]
+fn calls_and_instantiations() -> Result<()> {
+    let engine = engine(|_| {})?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "f"))
+                (func $start)
+                (start $start)
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    let metrics = engine.metrics().unwrap();
+    let instance = Instance::new(&mut store, &module, &[])?;
+    Instance::new(&mut store, &module, &[])?;
+    assert_eq!(metrics.instantiation_time().count(), 2);
+    let calls = metrics.calls().get();
+
+    let f = instance.get_typed_func::<(), ()>(&mut store, "f")?;
+    for _ in 0..3 {
+        f.call(&mut store, ())?;
+    }
+    assert_eq!(metrics.calls().get(), calls + 3);
+    Ok(())
+}
+
+#[test]
+fn traps_are_counted_once() -> Result<()> {
+    let engine = engine(|_| {})?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "" "host" (func $host))
+                (func (export "trap") unreachable)
+                (func (export "outer") call $host)
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    let host = Func::wrap(&mut store, |mut caller: Caller<'_, ()>| {
+        let trap = caller
+            .get_export("trap")
+            .and_then(|e| e.into_func())
+            .unwrap();
+        trap.call(&mut caller, &[], &mut [])
+    });
+    let instance = Instance::new(&mut store, &module, &[host.into()])?;
+    let metrics = engine.metrics().unwrap();
+
+    let trap = instance.get_typed_func::<(), ()>(&mut store, "trap")?;
+    assert!(trap.call(&mut store, ()).is_err());
+    assert_eq!(metrics.traps(Trap::UnreachableCodeReached), 1);
+
+    // A trap which propagates out through a host function and further wasm
+    // frames is still only counted where it was raised.
+    let outer = instance.get_typed_func::<(), ()>(&mut store, "outer")?;
+    let err = outer.call(&mut store, ()).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<Trap>(),
+        Some(&Trap::UnreachableCodeReached)
+    );
+    assert_eq!(metrics.traps(Trap::UnreachableCodeReached), 2);
+    assert_eq!(metrics.traps(Trap::StackOverflow), 0);
+    Ok(())
+}
+
+#[test]
+fn fuel_consumed() -> Result<()> {
+    let engine = engine(|config| {
+        config.consume_fuel(true);
+    })?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "f")
+                    (local i32)
+                    (local.set 0 (i32.const 100))
+                    (loop
+                        (local.tee 0 (i32.sub (local.get 0) (i32.const 1)))
+                        br_if 0))
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    store.set_fuel(100_000)?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let f = instance.get_typed_func::<(), ()>(&mut store, "f")?;
+    f.call(&mut store, ())?;
+
+    let metrics = engine.metrics().unwrap();
+    let consumed = 100_000 - store.get_fuel()?;
+    assert!(consumed > 0);
+    assert_eq!(metrics.fuel_consumed().get(), consumed);
+
+    // Replacing the fuel doesn't count as consumption.
+    store.set_fuel(10)?;
+    store.set_fuel(100_000)?;
+    assert_eq!(metrics.fuel_consumed().get(), consumed);
+    Ok(())
+}
+
+#[test]
+fn epoch_deadlines() -> Result<()> {
+    let engine = engine(|config| {
+        config.epoch_interruption(true);
+    })?;
+    let module = Module::new(&engine, r#"(module (func (export "f")))"#)?;
+
+    let mut store = Store::new(&engine, ());
+    store.set_epoch_deadline(1);
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let f = instance.get_typed_func::<(), ()>(&mut store, "f")?;
+    engine.increment_epoch();
+    let err = f.call(&mut store, ()).unwrap_err();
+    assert_eq!(err.downcast_ref::<Trap>(), Some(&Trap::Interrupt));
+
+    let metrics = engine.metrics().unwrap();
+    assert_eq!(metrics.epoch_deadlines().get(), 1);
+    assert_eq!(metrics.traps(Trap::Interrupt), 1);
+    Ok(())
+}
+
+#[test]
+fn gc_pauses() -> Result<()> {
+    let engine = engine(|_| {})?;
+    let mut store = Store::new(&engine, ());
+    let _ = ExternRef::new(&mut store, 42)?;
+    store.gc();
+    store.gc();
+    assert_eq!(engine.metrics().unwrap().gc_pauses().count(), 2);
+    Ok(())
+}
+
+#[test]
+fn prometheus_format() -> Result<()> {
+    let engine = engine(|_| {})?;
+    let module = Module::new(&engine, r#"(module (func (export "f") unreachable))"#)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let f = instance.get_typed_func::<(), ()>(&mut store, "f")?;
+    assert!(f.call(&mut store, ()).is_err());
+
+    let text = engine.metrics().unwrap().to_prometheus();
+    for line in [
+        "# TYPE wasmtime_calls_total counter",
+        "wasmtime_calls_total 1",
+        "wasmtime_traps_total{code=\"UnreachableCodeReached\"} 1",
+        "wasmtime_traps_total{code=\"StackOverflow\"} 0",
+        "# TYPE wasmtime_instantiation_duration_seconds histogram",
+        "wasmtime_instantiation_duration_seconds_bucket{le=\"+Inf\"} 1",
+        "wasmtime_instantiation_duration_seconds_count 1",
+        "wasmtime_gc_pause_duration_seconds_count 0",
+        "wasmtime_fuel_consumed_total 0",
+        "wasmtime_epoch_deadlines_total 0",
+    ] {
+        assert!(
+            text.lines().any(|l| l == line),
+            "missing {line:?} in:\n{text}"
+        );
+    }
+
+    // Buckets are cumulative.
+    let buckets = engine
+        .metrics()
+        .unwrap()
+        .instantiation_time()
+        .buckets()
+        .map(|(_, count)| count)
+        .collect::<Vec<_>>();
+    assert_eq!(buckets.len(), Histogram::BUCKETS.len());
+    assert!(buckets.windows(2).all(|w| w[0] <= w[1]));
+    Ok(())
+}
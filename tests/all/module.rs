@@ -580,3 +580,434 @@ fn validate_deterministic() {
         .to_string();
     assert_eq!(result_parallel, result_sequential);
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn per_module_nan_canonicalization() -> Result<()> {
+    let wat = r#"
+        (module
+            (func (export "nan") (param f32) (result i32)
+                (i32.reinterpret_f32 (f32.div (local.get 0) (local.get 0))))
+        )
+    "#;
+    let engine = Engine::default();
+    let module = CodeBuilder::new(&engine)
+        .cranelift_nan_canonicalization(true)
+        .wasm_binary_or_text(wat.as_bytes(), None)?
+        .compile_module()?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let nan = instance.get_typed_func::<f32, i32>(&mut store, "nan")?;
+    assert_eq!(nan.call(&mut store, 0.0)? as u32, 0x7fc00000);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn inline_small_functions() -> Result<()> {
+    let wat = r#"
+        (module
+            (memory 1)
+            (func $abs (param i32) (result i32)
+                (if (result i32) (i32.lt_s (local.get 0) (i32.const 0))
+                    (then (i32.sub (i32.const 0) (local.get 0)))
+                    (else (local.get 0))))
+            (func $load (param i32) (result i32)
+                (i32.load (local.get 0)))
+            (func $fact (param i64) (result i64)
+                (if (result i64) (i64.eqz (local.get 0))
+                    (then (i64.const 1))
+                    (else (i64.mul (local.get 0)
+                        (call $fact (i64.sub (local.get 0) (i64.const 1)))))))
+            (func $tail (param i32) (result i32)
+                (return_call $abs (local.get 0)))
+            (func (export "run") (param i32) (result i32)
+                (i32.store (i32.const 8) (call $abs (local.get 0)))
+                (i32.add (call $load (i32.const 8)) (call $tail (local.get 0))))
+            (func (export "fact") (param i64) (result i64)
+                (call $fact (local.get 0)))
+            (func (export "oob") (result i32)
+                (call $load (i32.const -1)))
+        )
+    "#;
+    let mut config = Config::new();
+    config
+        .cranelift_opt_level(OptLevel::Speed)
+        .cranelift_inline_threshold(100)
+        .cranelift_inline_max_depth(3)
+        .wasm_backtrace(false)
+        .generate_address_map(false);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, wat)?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<i32, i32>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, -21)?, 42);
+    assert_eq!(run.call(&mut store, 5)?, 10);
+    let fact = instance.get_typed_func::<i64, i64>(&mut store, "fact")?;
+    assert_eq!(fact.call(&mut store, 10)?, 3628800);
+    let oob = instance.get_typed_func::<(), i32>(&mut store, "oob")?;
+    let trap = oob.call(&mut store, ()).unwrap_err().downcast::<Trap>()?;
+    assert_eq!(trap, Trap::MemoryOutOfBounds);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn redundant_check_elimination() -> Result<()> {
+    // Only the first load's bounds check is needed, the others are implied
+    // by it.
+    let wat = r#"
+        (module
+            (memory 1)
+            (data (i32.const 0) "\01\02\03\04")
+            (func (export "sum") (param i32) (result i32)
+                (i32.add
+                    (i32.add
+                        (i32.load8_u offset=3 (local.get 0))
+                        (i32.load8_u offset=2 (local.get 0)))
+                    (i32.add
+                        (i32.load8_u offset=1 (local.get 0))
+                        (i32.load8_u (local.get 0)))))
+        )
+    "#;
+    let mut config = Config::new();
+    config
+        .cranelift_opt_level(OptLevel::Speed)
+        .cranelift_redundant_check_elimination(true)
+        .memory_reservation(0)
+        .memory_guard_size(0);
+    // Bounds checks with Spectre mitigations are never removed.
+    unsafe {
+        config.cranelift_flag_set("enable_heap_access_spectre_mitigation", "false");
+    }
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, wat)?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let sum = instance.get_typed_func::<i32, i32>(&mut store, "sum")?;
+    assert_eq!(sum.call(&mut store, 0)?, 10);
+    assert_eq!(sum.call(&mut store, 65532)?, 0);
+    for addr in [65533, 65535, -1] {
+        let trap = sum.call(&mut store, addr).unwrap_err().downcast::<Trap>()?;
+        assert_eq!(trap, Trap::MemoryOutOfBounds);
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn inlining_removes_frames() -> Result<()> {
+    // `$rec` and `$step` call each other until the stack overflows, recording
+    // the depth reached. When `$step` is inlined into `$rec` each level of
+    // recursion only needs one frame instead of two, so it gets deeper.
+    let wat = r#"
+        (module
+            (global $depth (export "depth") (mut i32) (i32.const 0))
+            (func $step (param i32)
+                (call $rec (i32.add (local.get 0) (i32.const 1))))
+            (func $rec (export "rec") (param i32)
+                (global.set $depth (local.get 0))
+                (call $step (local.get 0)))
+        )
+    "#;
+    let depth = |threshold: u32| -> Result<i32> {
+        let mut config = Config::new();
+        config
+            .cranelift_opt_level(OptLevel::Speed)
+            .cranelift_inline_threshold(threshold)
+            .wasm_backtrace(false)
+            .generate_address_map(false)
+            .max_wasm_stack(64 << 10);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, wat)?;
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let rec = instance.get_typed_func::<i32, ()>(&mut store, "rec")?;
+        let trap = rec.call(&mut store, 0).unwrap_err().downcast::<Trap>()?;
+        assert_eq!(trap, Trap::StackOverflow);
+        let depth = instance.get_global(&mut store, "depth").unwrap();
+        Ok(depth.get(&mut store).unwrap_i32())
+    };
+    let without = depth(0)?;
+    let with = depth(32)?;
+    assert!(with > without, "{with} <= {without}");
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn inline_threshold_keeps_backtraces() -> Result<()> {
+    // The callee is defined before its caller, so an offset within it is
+    // before the start of the caller.
+    let wat = r#"
+        (module
+            (memory 1)
+            (func $load (param i32) (result i32)
+                (i32.load (local.get 0)))
+            (func $run (export "run") (result i32)
+                (call $load (i32.const -1)))
+        )
+    "#;
+    let mut config = Config::new();
+    config
+        .cranelift_opt_level(OptLevel::Speed)
+        .cranelift_inline_threshold(100);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, wat)?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(), i32>(&mut store, "run")?;
+    let error = run.call(&mut store, ()).unwrap_err();
+    assert_eq!(
+        *error.downcast_ref::<Trap>().unwrap(),
+        Trap::MemoryOutOfBounds
+    );
+
+    // With backtraces enabled the callee isn't inlined, so it still has a
+    // frame of its own, and offsets are within each frame's function.
+    let trace = error.downcast_ref::<WasmBacktrace>().unwrap().frames();
+    assert_eq!(trace.len(), 2);
+    assert_eq!(trace[0].func_name(), Some("load"));
+    assert_eq!(trace[1].func_name(), Some("run"));
+    for frame in trace {
+        let func_offset = frame.func_offset().unwrap();
+        assert!(func_offset > 0 && func_offset < 16, "{func_offset}");
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn reset_fp_environment() -> Result<()> {
+    let mut config = Config::new();
+    config.reset_fp_environment(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "div") (param f64 f64) (result f64)
+                    (f64.div (local.get 0) (local.get 1)))
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let div = instance.get_typed_func::<(f64, f64), f64>(&mut store, "div")?;
+    // The smallest normal `f64` divided by two is a denormal, which must not be
+    // flushed to zero.
+    let result = div.call(&mut store, (f64::MIN_POSITIVE, 2.0))?;
+    assert_eq!(result, f64::MIN_POSITIVE / 2.0);
+    assert_ne!(result, 0.0);
+
+    // The same holds when the host flushes denormals itself, and the host's
+    // environment is restored once wasm returns.
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    {
+        let prev = host_fp::get();
+        let flushing = prev | host_fp::FLUSH_DENORMALS;
+        unsafe { host_fp::set(flushing) };
+        let result = div.call(&mut store, (f64::MIN_POSITIVE, 2.0));
+        let after = host_fp::get();
+        unsafe { host_fp::set(prev) };
+        // Compare bits since comparisons are affected by the host's
+        // environment too.
+        assert_eq!(result?.to_bits(), (f64::MIN_POSITIVE / 2.0).to_bits());
+        assert_eq!(after, flushing);
+    }
+    Ok(())
+}
+
+/// Access to the host's floating-point control register.
+#[cfg(target_arch = "x86_64")]
+mod host_fp {
+    use std::arch::asm;
+
+    /// The flush-to-zero (FTZ) and denormals-are-zero (DAZ) bits of `mxcsr`.
+    pub const FLUSH_DENORMALS: u32 = (1 << 15) | (1 << 6);
+
+    pub fn get() -> u32 {
+        let mut mxcsr = 0u32;
+        unsafe {
+            asm!(
+                "stmxcsr [{}]",
+                in(reg) &mut mxcsr,
+                options(nostack, preserves_flags),
+            );
+        }
+        mxcsr
+    }
+
+    pub unsafe fn set(mxcsr: u32) {
+        asm!(
+            "ldmxcsr [{}]",
+            in(reg) &mxcsr,
+            options(nostack, preserves_flags, readonly),
+        );
+    }
+}
+
+/// Access to the host's floating-point control register.
+#[cfg(target_arch = "aarch64")]
+mod host_fp {
+    use std::arch::asm;
+
+    /// The flush-to-zero (FZ) bit of `fpcr`.
+    pub const FLUSH_DENORMALS: u64 = 1 << 24;
+
+    pub fn get() -> u64 {
+        let fpcr: u64;
+        unsafe {
+            asm!(
+                "mrs {}, fpcr",
+                out(reg) fpcr,
+                options(nomem, nostack, preserves_flags),
+            );
+        }
+        fpcr
+    }
+
+    pub unsafe fn set(fpcr: u64) {
+        asm!(
+            "msr fpcr, {}",
+            in(reg) fpcr,
+            options(nomem, nostack, preserves_flags),
+        );
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn max_stack_size_follows_direct_calls() -> Result<()> {
+    let engine = Engine::default();
+    let leaf = Module::new(&engine, r#"(module (func (export "f")))"#)?;
+    let leaf_size = leaf.max_stack_size().unwrap();
+    assert!(leaf_size > 0);
+
+    // A caller's bound includes its callee's frame too.
+    let caller = Module::new(
+        &engine,
+        r#"
+            (module
+                (func $leaf)
+                (func (export "f") call $leaf)
+            )
+        "#,
+    )?;
+    assert!(caller.max_stack_size().unwrap() > leaf_size);
+
+    // The stack usage of recursive, indirect or imported calls is unknown.
+    for wat in [
+        r#"(module (func $f call $f))"#,
+        r#"(module (func $a call $b) (func $b call $a))"#,
+        r#"(module (table 1 funcref) (func (call_indirect (i32.const 0))))"#,
+        r#"(module (import "" "" (func $f)) (func call $f))"#,
+    ] {
+        let module = Module::new(&engine, wat)?;
+        assert_eq!(module.max_stack_size(), None, "{wat}");
+    }
+
+    // The bound survives serialization.
+    let serialized = caller.serialize()?;
+    let deserialized = unsafe { Module::deserialize(&engine, &serialized)? };
+    assert_eq!(deserialized.max_stack_size(), caller.max_stack_size());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn required_features_are_detected() -> Result<()> {
+    use wasmtime::wasmparser::WasmFeatures;
+
+    let mut config = Config::new();
+    config.wasm_function_references(true);
+    config.wasm_exceptions(true);
+    config.wasm_stack_switching(true);
+    config.wasm_memory64(true);
+    let engine = Engine::new(&config)?;
+
+    let mvp = Module::new(
+        &engine,
+        r#"(module (func (export "f") (result i32) i32.const 1))"#,
+    )?;
+    assert_eq!(mvp.required_features(), WasmFeatures::empty());
+
+    let simd = Module::new(
+        &engine,
+        r#"(module (func (result v128) v128.const i64x2 0 0))"#,
+    )?;
+    assert_eq!(simd.required_features(), WasmFeatures::SIMD);
+
+    let memory64 = Module::new(&engine, r#"(module (memory i64 1))"#)?;
+    assert_eq!(memory64.required_features(), WasmFeatures::MEMORY64);
+
+    let stack_switching = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $ft (func))
+                (type $ct (cont $ft))
+                (func $f)
+                (elem declare func $f)
+                (func (export "run")
+                    (resume $ct (cont.new $ct (ref.func $f))))
+            )
+        "#,
+    )?;
+    let features = stack_switching.required_features();
+    assert!(
+        features.contains(WasmFeatures::STACK_SWITCHING),
+        "{features:?}"
+    );
+    assert!(!features.contains(WasmFeatures::SIMD), "{features:?}");
+
+    // The set survives serialization.
+    let serialized = stack_switching.serialize()?;
+    let deserialized = unsafe { Module::deserialize(&engine, &serialized)? };
+    assert_eq!(deserialized.required_features(), features);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn compile_modules_serialized_batch() -> Result<()> {
+    let engine = Engine::default();
+    let wasms = (0..8)
+        .map(|i| {
+            wat::parse_str(format!(
+                r#"(module (func (export "f") (result i32) i32.const {i}))"#
+            ))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let artifacts = CodeBuilder::new(&engine)
+        .wasm_binaries(wasms.iter().map(|w| &w[..]))
+        .compile_modules_serialized()?;
+    assert_eq!(artifacts.len(), wasms.len());
+
+    let mut store = Store::new(&engine, ());
+    for (i, artifact) in artifacts.iter().enumerate() {
+        let module = unsafe { Module::deserialize(&engine, artifact)? };
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let f = instance.get_typed_func::<(), i32>(&mut store, "f")?;
+        assert_eq!(f.call(&mut store, ())?, i32::try_from(i)?);
+    }
+
+    // Errors identify which module of the batch failed.
+    let err = CodeBuilder::new(&engine)
+        .wasm_binaries([&wasms[0][..], b"not wasm"])
+        .compile_modules_serialized()
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("module 1 of batch"), "{err:?}");
+
+    assert!(CodeBuilder::new(&engine)
+        .compile_modules_serialized()
+        .is_err());
+    Ok(())
+}
@@ -437,6 +437,118 @@ fn total_core_instances_limit() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn instantiate_batch_reserves_core_instances() -> Result<()> {
+    const INSTANCE_LIMIT: u32 = 10;
+    let mut pool = crate::small_pool_config();
+    pool.total_core_instances(INSTANCE_LIMIT);
+    let mut config = Config::new();
+    config.allocation_strategy(pool);
+    config.memory_guard_size(0);
+    config.memory_reservation(1 << 16);
+
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "" "check" (func $check))
+                (start $check)
+            )
+        "#,
+    )?;
+    let mut linker = Linker::new(&engine);
+    linker.func_wrap("", "check", |caller: Caller<'_, bool>| {
+        if *caller.data() {
+            anyhow::bail!("trap");
+        }
+        Ok(())
+    })?;
+    let pre = linker.instantiate_pre(&module)?;
+    let stores = |n: u32| {
+        (0..n)
+            .map(|_| Store::new(&engine, false))
+            .collect::<Vec<_>>()
+    };
+
+    // A batch which doesn't fit fails without instantiating anything.
+    let mut batch = stores(INSTANCE_LIMIT + 1);
+    match pre.instantiate_batch(&mut batch) {
+        Ok(_) => panic!("instantiation should fail"),
+        Err(e) => assert!(e.is::<PoolConcurrencyLimitError>()),
+    }
+    let mut full = stores(INSTANCE_LIMIT);
+    pre.instantiate_batch(&mut full)?;
+    assert!(Instance::new(Store::new(&engine, false), &module, &[])
+        .unwrap_err()
+        .is::<PoolConcurrencyLimitError>());
+    drop(full);
+
+    // The instances of a batch which fails partway through that weren't
+    // created are released.
+    let mut batch = stores(INSTANCE_LIMIT);
+    *batch[2].data_mut() = true;
+    assert!(pre.instantiate_batch(&mut batch).is_err());
+    drop(batch);
+    pre.instantiate_batch(&mut stores(INSTANCE_LIMIT))?;
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(not(miri))]
+async fn instantiate_batch_async_releases_reservation_when_dropped() -> Result<()> {
+    use super::async_functions::PollOnce;
+
+    const INSTANCE_LIMIT: u32 = 4;
+    let mut pool = crate::small_pool_config();
+    pool.total_core_instances(INSTANCE_LIMIT)
+        .total_stacks(INSTANCE_LIMIT);
+    let mut config = Config::new();
+    config.async_support(true);
+    config.allocation_strategy(pool);
+
+    let engine = Engine::new(&config)?;
+    let mut linker = Linker::new(&engine);
+    linker.func_new_async(
+        "async",
+        "yield",
+        FuncType::new(&engine, [], []),
+        |_caller, _params, _results| {
+            Box::new(async {
+                tokio::task::yield_now().await;
+                Ok(())
+            })
+        },
+    )?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "async" "yield" (func $yield))
+                (start $yield)
+            )
+        "#,
+    )?;
+    let pre = linker.instantiate_pre(&module)?;
+    let stores = || {
+        (0..INSTANCE_LIMIT)
+            .map(|_| Store::new(&engine, ()))
+            .collect::<Vec<_>>()
+    };
+
+    // Cancel the batch while the first instance's start function is
+    // suspended, which releases the instances that weren't created.
+    let mut batch = stores();
+    let future = PollOnce::new(Box::pin(pre.instantiate_batch_async(&mut batch)))
+        .await
+        .unwrap_err();
+    drop(future);
+    drop(batch);
+
+    pre.instantiate_batch_async(&mut stores()).await?;
+    Ok(())
+}
+
 #[test]
 fn preserve_data_segments() -> Result<()> {
     let mut pool = crate::small_pool_config();
@@ -1365,3 +1477,37 @@ fn instantiate_non_page_aligned_sizes() -> Result<()> {
     Instance::new(&mut store, &module, &[])?;
     Ok(())
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn lazily_applied_data_is_reset() -> Result<()> {
+    let mut config = Config::new();
+    config.allocation_strategy(crate::small_pool_config());
+    config.memory_guard_size(0);
+    config.memory_reservation(1 << 16);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        format!(
+            r#"(module (memory (export "memory") 1) (data (i32.const 0) "{}"))"#,
+            "a".repeat(0x8000),
+        ),
+    )?;
+    let pre = Linker::new(&engine).instantiate_pre(&module)?;
+
+    let mut store = Store::new(&engine, ());
+    let mut allocated = pre.allocate(&mut store)?;
+    allocated.set_data(0, vec![b'b'; 0x8000])?.defer_data(0)?;
+    let instance = allocated.initialize(&mut store)?;
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    assert!(memory.data(&store)[..0x8000].iter().all(|b| *b == b'b'));
+    drop(store);
+
+    // The memory's slot is reused without the data mapped into it for the
+    // previous instance.
+    let mut store = Store::new(&engine, ());
+    let instance = pre.instantiate(&mut store)?;
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    assert!(memory.data(&store)[..0x8000].iter().all(|b| *b == b'a'));
+    Ok(())
+}
@@ -0,0 +1,250 @@
+use wasmtime::*;
+
+const WASM_PAGE_SIZE: usize = wasmtime_environ::Memory::DEFAULT_PAGE_SIZE as usize;
+
+fn store(engine: &Engine, group: &QuotaGroup) -> Result<Store<()>> {
+    let mut store = Store::new(engine, ());
+    store.join_quota_group(group)?;
+    Ok(store)
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn instances_are_limited_across_stores() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, "(module)")?;
+    let group = QuotaGroupBuilder::new().instances(3).build(&engine)?;
+
+    let mut a = store(&engine, &group)?;
+    let mut b = store(&engine, &group)?;
+    assert_eq!(group.members(), 2);
+    Instance::new(&mut a, &module, &[])?;
+    Instance::new(&mut a, &module, &[])?;
+    Instance::new(&mut b, &module, &[])?;
+    assert_eq!(group.instances(), 3);
+
+    let err = Instance::new(&mut b, &module, &[]).unwrap_err();
+    assert!(
+        err.to_string().contains("quota group instance limit"),
+        "{err:?}"
+    );
+
+    // A new store doesn't get a fresh set of instances.
+    let mut c = store(&engine, &group)?;
+    assert!(Instance::new(&mut c, &module, &[]).is_err());
+
+    drop(a);
+    assert_eq!(group.members(), 2);
+    assert_eq!(group.instances(), 1);
+    Instance::new(&mut c, &module, &[])?;
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn memory_is_limited_across_stores() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(
+        &engine,
+        r#"(module
+            (memory (export "m") 1)
+            (func (export "grow") (param i32) (result i32)
+              (memory.grow (local.get 0)))
+           )"#,
+    )?;
+    let group = QuotaGroupBuilder::new()
+        .memory_size(4 * WASM_PAGE_SIZE)
+        .build(&engine)?;
+
+    let mut a = store(&engine, &group)?;
+    let mut b = store(&engine, &group)?;
+    let instance_a = Instance::new(&mut a, &module, &[])?;
+    let instance_b = Instance::new(&mut b, &module, &[])?;
+    assert_eq!(group.memory_size(), 2 * WASM_PAGE_SIZE);
+
+    let grow_a = instance_a.get_typed_func::<i32, i32>(&mut a, "grow")?;
+    let grow_b = instance_b.get_typed_func::<i32, i32>(&mut b, "grow")?;
+    assert_eq!(grow_a.call(&mut a, 1)?, 1);
+    assert_eq!(grow_b.call(&mut b, 2)?, -1);
+    assert_eq!(grow_b.call(&mut b, 1)?, 1);
+    assert_eq!(grow_a.call(&mut a, 1)?, -1);
+    assert_eq!(group.memory_size(), 4 * WASM_PAGE_SIZE);
+
+    // Host-created memories are charged too.
+    assert!(Memory::new(&mut a, MemoryType::new(1, None)).is_err());
+
+    drop(b);
+    assert_eq!(group.memory_size(), 2 * WASM_PAGE_SIZE);
+    assert_eq!(grow_a.call(&mut a, 2)?, 2);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn failed_growth_is_refunded() -> Result<()> {
+    let engine = Engine::default();
+    let group = QuotaGroupBuilder::new()
+        .memory_size(10 * WASM_PAGE_SIZE)
+        .build(&engine)?;
+    let mut store = store(&engine, &group)?;
+
+    // Growth beyond the memory's own maximum is permitted by the group but
+    // then fails, and mustn't leave anything charged.
+    let memory = Memory::new(&mut store, MemoryType::new(1, Some(2)))?;
+    assert!(memory.grow(&mut store, 5).is_err());
+    assert_eq!(group.memory_size(), WASM_PAGE_SIZE);
+    memory.grow(&mut store, 1)?;
+    assert_eq!(group.memory_size(), 2 * WASM_PAGE_SIZE);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn fair_share() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, "(module)")?;
+    let group = QuotaGroupBuilder::new()
+        .instances(4)
+        .policy(QuotaPolicy::FairShare)
+        .build(&engine)?;
+
+    let mut a = store(&engine, &group)?;
+    let mut b = store(&engine, &group)?;
+    Instance::new(&mut a, &module, &[])?;
+    Instance::new(&mut a, &module, &[])?;
+    // `a` has used up its half of the group even though the group isn't full.
+    assert!(Instance::new(&mut a, &module, &[]).is_err());
+    Instance::new(&mut b, &module, &[])?;
+
+    // Once `b` leaves `a` may use the whole group.
+    drop(b);
+    Instance::new(&mut a, &module, &[])?;
+    Instance::new(&mut a, &module, &[])?;
+    assert!(Instance::new(&mut a, &module, &[]).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn fuel_rate_is_shared() -> Result<()> {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, r#"(module (func (export "spin") (loop br 0)))"#)?;
+    let group = QuotaGroupBuilder::new()
+        .fuel_per_second(1_000)
+        .build(&engine)?;
+
+    let mut a = store(&engine, &group)?;
+    let mut b = store(&engine, &group)?;
+    let spin_a = Instance::new(&mut a, &module, &[])?.get_typed_func::<(), ()>(&mut a, "spin")?;
+    let spin_b = Instance::new(&mut b, &module, &[])?.get_typed_func::<(), ()>(&mut b, "spin")?;
+
+    // The first store drains the group's bucket, after which the second one
+    // immediately runs out of fuel too.
+    let err = spin_a.call(&mut a, ()).unwrap_err();
+    assert_eq!(err.downcast_ref::<Trap>(), Some(&Trap::OutOfFuel));
+    let err = spin_b.call(&mut b, ()).unwrap_err();
+    assert_eq!(err.downcast_ref::<Trap>(), Some(&Trap::OutOfFuel));
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn failed_reservation_leaves_store_counts_alone() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, "(module)")?;
+    let group = QuotaGroupBuilder::new().instances(1).build(&engine)?;
+
+    let mut a = store(&engine, &group)?;
+    Instance::new(&mut a, &module, &[])?;
+
+    // `b` may only have a single instance of its own, which the instantiation
+    // rejected by the group mustn't count.
+    let mut b = Store::new(&engine, StoreLimitsBuilder::new().instances(1).build());
+    b.limiter(|limits| limits);
+    b.join_quota_group(&group)?;
+    assert!(Instance::new(&mut b, &module, &[]).is_err());
+    drop(a);
+    Instance::new(&mut b, &module, &[])?;
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn failed_instantiation_is_refunded() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, "(module (memory 1) (table 10 funcref))")?;
+    let group = QuotaGroupBuilder::new()
+        .instances(1)
+        .memory_size(WASM_PAGE_SIZE)
+        .build(&engine)?;
+
+    // The memory is charged to the group before the table is rejected by the
+    // store's own limiter.
+    let mut store = Store::new(&engine, StoreLimitsBuilder::new().table_elements(0).build());
+    store.limiter(|limits| limits);
+    store.join_quota_group(&group)?;
+    assert!(Instance::new(&mut store, &module, &[]).is_err());
+    assert_eq!(group.instances(), 0);
+    assert_eq!(group.memory_size(), 0);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn shared_memories_are_counted() -> Result<()> {
+    let mut config = Config::new();
+    config.wasm_threads(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, r#"(module (memory (import "" "") 1 4 shared))"#)?;
+    let group = QuotaGroupBuilder::new()
+        .memory_size(2 * WASM_PAGE_SIZE)
+        .build(&engine)?;
+
+    // A host-created shared memory is charged once, when it's first imported.
+    let memory = SharedMemory::new(&engine, MemoryType::shared(1, 4))?;
+    let mut a = store(&engine, &group)?;
+    Instance::new(&mut a, &module, &[memory.clone().into()])?;
+    Instance::new(&mut a, &module, &[memory.clone().into()])?;
+    assert_eq!(group.memory_size(), WASM_PAGE_SIZE);
+
+    // Shared memories defined by a module are charged like any other memory.
+    let defined = Module::new(&engine, "(module (memory 1 4 shared))")?;
+    Instance::new(&mut a, &defined, &[])?;
+    assert_eq!(group.memory_size(), 2 * WASM_PAGE_SIZE);
+
+    // Other stores are charged for the memory as well.
+    let mut b = store(&engine, &group)?;
+    assert!(Instance::new(&mut b, &module, &[memory.clone().into()]).is_err());
+    drop(a);
+    assert_eq!(group.memory_size(), 0);
+    Instance::new(&mut b, &module, &[memory.into()])?;
+    assert_eq!(group.memory_size(), WASM_PAGE_SIZE);
+    Ok(())
+}
+
+#[test]
+fn invalid_membership() -> Result<()> {
+    let engine = Engine::default();
+    let group = QuotaGroupBuilder::new().build(&engine)?;
+
+    let mut store = Store::new(&engine, ());
+    store.join_quota_group(&group)?;
+    assert!(store.join_quota_group(&group).is_err());
+
+    let mut store = Store::new(&Engine::default(), ());
+    assert!(store.join_quota_group(&group).is_err());
+
+    let mut store = Store::new(&engine, ());
+    let module = Module::new(&engine, "(module)")?;
+    Instance::new(&mut store, &module, &[])?;
+    assert!(store.join_quota_group(&group).is_err());
+
+    // A fuel rate requires fuel to be enabled.
+    assert!(QuotaGroupBuilder::new()
+        .fuel_per_second(100)
+        .build(&engine)
+        .is_err());
+    Ok(())
+}
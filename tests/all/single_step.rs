@@ -0,0 +1,92 @@
+use std::sync::{Arc, Mutex};
+use wasmtime::*;
+
+fn engine() -> Engine {
+    let mut config = Config::new();
+    config.wasm_single_step(true);
+    Engine::new(&config).unwrap()
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn steps_every_instruction() -> Result<()> {
+    let engine = engine();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "run") (result i32)
+                    i32.const 1
+                    i32.const 2
+                    i32.add)
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let offsets = Arc::new(Mutex::new(Vec::new()));
+    let offsets2 = offsets.clone();
+    store.single_step_callback(move |_, offset| {
+        offsets2.lock().unwrap().push(offset);
+        Ok(UpdateDeadline::Continue(1))
+    });
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(), i32>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, ())?, 3);
+
+    // Two constants, the add, and the final `end`.
+    let offsets = offsets.lock().unwrap();
+    assert_eq!(offsets.len(), 4);
+    assert!(offsets.windows(2).all(|w| w[0] < w[1]));
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn budget_without_callback_traps() -> Result<()> {
+    let engine = engine();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "run")
+                    (loop $l br $l))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    store.set_single_step_budget(100);
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    let err = run.call(&mut store, ()).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::Interrupt);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn callback_can_stop_execution() -> Result<()> {
+    let engine = engine();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "run")
+                    (loop $l br $l))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, 0);
+    store.single_step_callback(|mut store, _offset| {
+        *store.data_mut() += 1;
+        if *store.data() == 10 {
+            anyhow::bail!("stopped");
+        }
+        Ok(UpdateDeadline::Continue(5))
+    });
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    let err = run.call(&mut store, ()).unwrap_err();
+    assert!(err.to_string().contains("stopped"));
+    assert_eq!(*store.data(), 10);
+    Ok(())
+}
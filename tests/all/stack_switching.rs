@@ -1258,3 +1258,1204 @@ mod misc {
         Ok(())
     }
 }
+
+mod host_continuation {
+    use super::test_utils::*;
+    use wasmtime::*;
+
+    fn get_tag(store: &mut Store<()>, instance: &Instance, name: &str) -> Tag {
+        match instance.get_export(store, name) {
+            Some(Extern::Tag(tag)) => tag,
+            _ => panic!("expected a tag export named `{name}`"),
+        }
+    }
+
+    fn unwrap_suspended(resumed: Resumed) -> Vec<Val> {
+        match resumed {
+            Resumed::Suspended { payload, .. } => payload,
+            Resumed::Returned(_) => panic!("expected the continuation to suspend"),
+        }
+    }
+
+    #[test]
+    fn host_driven_generator() -> Result<()> {
+        let wat = r#"
+        (module
+            (tag $yield (export "yield") (param i32) (result i32))
+
+            ;; Yields `n`, `n - 1`, ..., `1` and returns the sum of the values
+            ;; it was resumed with.
+            (func (export "gen") (param $n i32) (result i32)
+                (local $sum i32)
+                (loop $l
+                    (local.set $sum
+                        (i32.add (local.get $sum) (suspend $yield (local.get $n))))
+                    (br_if $l (local.tee $n (i32.sub (local.get $n) (i32.const 1))))
+                )
+                (local.get $sum)
+            )
+        )
+    "#;
+
+        let mut runner = Runner::new();
+        let module = Module::new(&runner.engine, wat)?;
+        let instance = Instance::new(&mut runner.store, &module, &[])?;
+        let func = instance.get_func(&mut runner.store, "gen").unwrap();
+        let tag = get_tag(&mut runner.store, &instance, "yield");
+
+        let k = Continuation::new(&mut runner.store, &func, &[tag])?;
+        let payload = unwrap_suspended(k.resume(&mut runner.store, &[Val::I32(3)])?);
+        assert_eq!(payload[0].unwrap_i32(), 3);
+        let payload = unwrap_suspended(k.resume(&mut runner.store, &[Val::I32(10)])?);
+        assert_eq!(payload[0].unwrap_i32(), 2);
+        let payload = unwrap_suspended(k.resume(&mut runner.store, &[Val::I32(20)])?);
+        assert_eq!(payload[0].unwrap_i32(), 1);
+        assert!(!k.is_finished(&runner.store));
+
+        match k.resume(&mut runner.store, &[Val::I32(30)])? {
+            Resumed::Returned(results) => assert_eq!(results[0].unwrap_i32(), 60),
+            Resumed::Suspended { .. } => panic!("expected the continuation to return"),
+        }
+        assert!(k.is_finished(&runner.store));
+        assert!(k.resume(&mut runner.store, &[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn multiple_tags() -> Result<()> {
+        let wat = r#"
+        (module
+            (tag $a (export "a") (param i64 f32))
+            (tag $b (export "b") (result f64))
+
+            (func (export "f") (result f64)
+                (suspend $a (i64.const 1) (f32.const 2))
+                (f64.add (suspend $b) (f64.const 0.5))
+            )
+        )
+    "#;
+
+        let mut runner = Runner::new();
+        let module = Module::new(&runner.engine, wat)?;
+        let instance = Instance::new(&mut runner.store, &module, &[])?;
+        let func = instance.get_func(&mut runner.store, "f").unwrap();
+        let a = get_tag(&mut runner.store, &instance, "a");
+        let b = get_tag(&mut runner.store, &instance, "b");
+
+        let k = Continuation::new(&mut runner.store, &func, &[a, b])?;
+        let payload = unwrap_suspended(k.resume(&mut runner.store, &[])?);
+        assert_eq!(payload.len(), 2);
+        assert_eq!(payload[0].unwrap_i64(), 1);
+        assert_eq!(payload[1].unwrap_f32(), 2.0);
+
+        let payload = unwrap_suspended(k.resume(&mut runner.store, &[])?);
+        assert!(payload.is_empty());
+
+        // Resuming with the wrong types is an error but leaves the
+        // continuation suspended.
+        assert!(k.resume(&mut runner.store, &[Val::I32(0)]).is_err());
+        assert!(!k.is_finished(&runner.store));
+
+        match k.resume(&mut runner.store, &[Val::F64(1.0f64.to_bits())])? {
+            Resumed::Returned(results) => assert_eq!(results[0].unwrap_f64(), 1.5),
+            Resumed::Suspended { .. } => panic!("expected the continuation to return"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn trap_finishes_continuation() -> Result<()> {
+        let wat = r#"
+        (module
+            (tag $yield (export "yield"))
+
+            (func (export "f")
+                (suspend $yield)
+                (unreachable)
+            )
+        )
+    "#;
+
+        let mut runner = Runner::new();
+        let module = Module::new(&runner.engine, wat)?;
+        let instance = Instance::new(&mut runner.store, &module, &[])?;
+        let func = instance.get_func(&mut runner.store, "f").unwrap();
+        let tag = get_tag(&mut runner.store, &instance, "yield");
+
+        let k = Continuation::new(&mut runner.store, &func, &[tag])?;
+        unwrap_suspended(k.resume(&mut runner.store, &[])?);
+        let err = k.resume(&mut runner.store, &[]).unwrap_err();
+        assert_eq!(err.downcast::<Trap>()?, Trap::UnreachableCodeReached);
+        assert!(k.is_finished(&runner.store));
+        Ok(())
+    }
+}
+
+mod host_tags {
+    use super::test_utils::*;
+    use wasmtime::*;
+
+    #[test]
+    fn host_tag_identity() -> Result<()> {
+        let mut runner = Runner::new();
+        let ty = TagType::new(FuncType::new(
+            &runner.engine,
+            [ValType::I32],
+            [ValType::I64],
+        ));
+        let a = Tag::new(&mut runner.store, &ty)?;
+        let b = Tag::new(&mut runner.store, &ty)?;
+
+        assert!(Tag::eq(&a, &a, &runner.store));
+        assert!(!Tag::eq(&a, &b, &runner.store));
+        assert!(FuncType::eq(&a.ty(&runner.store).ty(), &ty.ty()));
+
+        // Re-exporting a host tag from a module preserves its identity.
+        let module = Module::new(
+            &runner.engine,
+            r#"
+            (module
+                (import "" "t" (tag $t (param i32) (result i64)))
+                (export "t" (tag $t))
+            )
+        "#,
+        )?;
+        let instance = Instance::new(&mut runner.store, &module, &[a.into()])?;
+        let exported = match instance.get_export(&mut runner.store, "t") {
+            Some(Extern::Tag(tag)) => tag,
+            _ => panic!("expected a tag export"),
+        };
+        assert!(Tag::eq(&a, &exported, &runner.store));
+        assert!(!Tag::eq(&b, &exported, &runner.store));
+        Ok(())
+    }
+
+    #[test]
+    fn host_tag_type_mismatch() -> Result<()> {
+        let mut runner = Runner::new();
+        let ty = TagType::new(FuncType::new(&runner.engine, [ValType::I32], []));
+        let tag = Tag::new(&mut runner.store, &ty)?;
+
+        let module = Module::new(
+            &runner.engine,
+            r#"(module (import "" "t" (tag (param i64))))"#,
+        )?;
+        assert!(Instance::new(&mut runner.store, &module, &[tag.into()]).is_err());
+        Ok(())
+    }
+
+    #[derive(Default)]
+    struct Scheduler {
+        queue: std::collections::VecDeque<(Continuation, i32)>,
+        log: Vec<i32>,
+    }
+
+    /// A cooperative scheduler which runs tasks until they yield and queues
+    /// them to be resumed afterwards.
+    #[test]
+    fn round_robin_scheduler() -> Result<()> {
+        let runner = Runner::new();
+        let mut store = Store::new(&runner.engine, Scheduler::default());
+
+        let ty = TagType::new(FuncType::new(&runner.engine, [ValType::I32], []));
+        let yield_ = Tag::new(&mut store, &ty)?;
+
+        let mut linker = Linker::new(&runner.engine);
+        linker.define(&store, "host", "yield", yield_)?;
+        linker.func_wrap(
+            "host",
+            "log",
+            |mut caller: Caller<'_, Scheduler>, x: i32| {
+                caller.data_mut().log.push(x);
+            },
+        )?;
+        linker.tag_handler(&store, yield_, |mut store, k, payload| {
+            let id = payload[0].unwrap_i32();
+            store.data_mut().queue.push_back((k, id));
+            Ok(())
+        })?;
+
+        let module = Module::new(
+            &runner.engine,
+            r#"
+            (module
+                (import "host" "yield" (tag $yield (param i32)))
+                (import "host" "log" (func $log (param i32)))
+
+                ;; Logs `10 * id + n` and yields `n` times.
+                (func $task (param $id i32) (param $n i32)
+                    (loop $l
+                        (call $log (i32.add (i32.mul (local.get $id) (i32.const 10))
+                                            (local.get $n)))
+                        (suspend $yield (local.get $id))
+                        (br_if $l (local.tee $n (i32.sub (local.get $n) (i32.const 1))))
+                    )
+                )
+                (func (export "a") (call $task (i32.const 1) (i32.const 2)))
+                (func (export "b") (call $task (i32.const 2) (i32.const 3)))
+            )
+        "#,
+        )?;
+        let instance = linker.instantiate(&mut store, &module)?;
+        for name in ["a", "b"] {
+            let func = instance.get_func(&mut store, name).unwrap();
+            let k = linker.continuation(&mut store, &func)?;
+            assert!(linker.resume(&mut store, k, &[])?.is_none());
+        }
+
+        let mut finished = Vec::new();
+        while let Some((k, id)) = store.data_mut().queue.pop_front() {
+            if linker.resume(&mut store, k, &[])?.is_some() {
+                finished.push(id);
+            }
+        }
+
+        assert_eq!(finished, [1, 2]);
+        assert_eq!(store.data().log, [12, 23, 11, 22, 21]);
+        Ok(())
+    }
+
+    #[test]
+    fn handler_resumes_immediately() -> Result<()> {
+        let runner = Runner::new();
+        let mut store = Store::new(&runner.engine, ());
+
+        let ty = TagType::new(FuncType::new(
+            &runner.engine,
+            [ValType::I32],
+            [ValType::I32],
+        ));
+        let double = Tag::new(&mut store, &ty)?;
+
+        let mut linker = Linker::new(&runner.engine);
+        linker.define(&store, "host", "double", double)?;
+        let inner = linker.clone();
+        linker.tag_handler(&store, double, move |mut store, k, payload| {
+            let x = payload[0].unwrap_i32();
+            let results = inner.resume(&mut store, k, &[Val::I32(x * 2)])?;
+            assert!(results.is_some());
+            Ok(())
+        })?;
+
+        let module = Module::new(
+            &runner.engine,
+            r#"
+            (module
+                (import "host" "double" (tag $double (param i32) (result i32)))
+                (global $result (export "result") (mut i32) (i32.const 0))
+                (func (export "f")
+                    (global.set $result (suspend $double (i32.const 21))))
+            )
+        "#,
+        )?;
+        let instance = linker.instantiate(&mut store, &module)?;
+        let f = instance.get_func(&mut store, "f").unwrap();
+        let k = linker.continuation(&mut store, &f)?;
+        assert!(linker.resume(&mut store, k, &[])?.is_none());
+        let result = instance.get_global(&mut store, "result").unwrap();
+        assert_eq!(result.get(&mut store).unwrap_i32(), 42);
+        Ok(())
+    }
+
+    /// A suspension which no `resume` in wasm handles reaches the host
+    /// handler, capturing the continuations the guest created itself.
+    #[test]
+    fn guest_suspend_reaches_host_handler() -> Result<()> {
+        let runner = Runner::new();
+        let mut store = Store::new(&runner.engine, Vec::<(Continuation, i32)>::new());
+
+        let ty = TagType::new(FuncType::new(
+            &runner.engine,
+            [ValType::I32],
+            [ValType::I32],
+        ));
+        let ask = Tag::new(&mut store, &ty)?;
+
+        let mut linker = Linker::new(&runner.engine);
+        linker.define(&store, "host", "ask", ask)?;
+        linker.tag_handler(&store, ask, |mut store, k, payload| {
+            store.data_mut().push((k, payload[0].unwrap_i32()));
+            Ok(())
+        })?;
+
+        let module = Module::new(
+            &runner.engine,
+            r#"
+            (module
+                (import "host" "ask" (tag $ask (param i32) (result i32)))
+                (tag $local (result i32))
+                (type $ft (func (result i32)))
+                (type $ct (cont $ft))
+                (type $ft2 (func (param i32) (result i32)))
+                (type $ct2 (cont $ft2))
+
+                (func $inner (result i32)
+                    (i32.add (suspend $ask (i32.const 1)) (suspend $local)))
+                (elem declare func $inner)
+
+                ;; Runs `$inner` in a continuation handling `$local` only.
+                (func (export "run") (result i32)
+                    (local $k (ref null $ct2))
+                    (local.set $k
+                        (block $h (result (ref $ct2))
+                            (return
+                                (resume $ct (on $local $h) (cont.new $ct (ref.func $inner))))))
+                    (resume $ct2 (i32.const 100) (local.get $k)))
+
+                (func (export "direct") (result i32)
+                    (suspend $ask (i32.const 2)))
+            )
+        "#,
+        )?;
+        let instance = linker.instantiate(&mut store, &module)?;
+
+        let run = instance.get_func(&mut store, "run").unwrap();
+        let k = linker.continuation(&mut store, &run)?;
+        assert!(linker.resume(&mut store, k, &[])?.is_none());
+        let (k, value) = store.data_mut().pop().unwrap();
+        assert_eq!(value, 1);
+        let results = linker.resume(&mut store, k, &[Val::I32(10)])?.unwrap();
+        assert_eq!(results[0].unwrap_i32(), 110);
+        assert!(k.is_finished(&store));
+
+        // Outside of a host continuation there is no one to suspend to.
+        let direct = instance.get_typed_func::<(), i32>(&mut store, "direct")?;
+        let error = direct.call(&mut store, ()).unwrap_err();
+        assert_eq!(error.downcast::<Trap>()?, Trap::UnhandledTag);
+        assert!(store.data().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn duplicate_handler() -> Result<()> {
+        let mut runner = Runner::new();
+        let ty = TagType::new(FuncType::new(&runner.engine, [], []));
+        let tag = Tag::new(&mut runner.store, &ty)?;
+
+        let mut linker = Linker::new(&runner.engine);
+        linker.tag_handler(&runner.store, tag, |_, _, _| Ok(()))?;
+        assert!(linker
+            .tag_handler(&runner.store, tag, |_, _, _| Ok(()))
+            .is_err());
+        linker.allow_shadowing(true);
+        linker.tag_handler(&runner.store, tag, |_, _, _| Ok(()))?;
+        Ok(())
+    }
+}
+
+#[cfg(all(
+    unix,
+    any(not(feature = "wasmfx_baseline"), feature = "wasmfx_no_baseline"),
+    not(feature = "wasmfx_pooling_allocator"),
+    not(feature = "unsafe_wasmfx_stacks")
+))]
+mod growable_stacks {
+    use super::test_utils::*;
+    use wasmtime::*;
+
+    fn runner(initial_stack_size: usize) -> Runner {
+        let mut config = Config::default();
+        config.wasm_function_references(true);
+        config.wasm_exceptions(true);
+        config.wasm_stack_switching(true);
+        config.wasm_gc(true);
+        config.wasmfx_stack_size(1 << 20);
+        config.wasmfx_initial_stack_size(initial_stack_size);
+
+        let engine = Engine::new(&config).unwrap();
+        let store = Store::<()>::new(&engine, ());
+        Runner { engine, store }
+    }
+
+    const RECURSE: &str = r#"
+(module
+  (type $ft (func (result i32)))
+  (type $ct (cont $ft))
+
+  (global $depth (mut i32) (i32.const 0))
+
+  (func $rec (param $n i32) (result i32)
+    (if (result i32) (i32.eqz (local.get $n))
+      (then (i32.const 0))
+      (else
+        (i32.add
+          (i32.const 1)
+          (call $rec (i32.sub (local.get $n) (i32.const 1)))))))
+
+  (func $body (result i32)
+    (call $rec (global.get $depth)))
+  (elem declare func $body)
+
+  (func (export "entry") (param $n i32) (result i32)
+    (global.set $depth (local.get $n))
+    (resume $ct (cont.new $ct (ref.func $body))))
+)
+"#;
+
+    fn recurse(runner: Runner, depth: i32) -> Result<i32> {
+        let Runner { engine, mut store } = runner;
+        let module = Module::new(&engine, RECURSE)?;
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let entry = instance.get_typed_func::<i32, i32>(&mut store, "entry")?;
+        entry.call(&mut store, depth)
+    }
+
+    #[test]
+    fn deep_recursion_grows_stack() -> Result<()> {
+        assert_eq!(recurse(runner(4096), 10_000)?, 10_000);
+        Ok(())
+    }
+
+    #[test]
+    fn overflow_still_traps() -> Result<()> {
+        let error = recurse(runner(4096), i32::MAX).expect_err("expected a stack overflow");
+        assert_eq!(error.downcast_ref::<Trap>(), Some(&Trap::StackOverflow));
+        Ok(())
+    }
+
+    #[test]
+    fn many_suspended_continuations() -> Result<()> {
+        let wat = r#"
+(module
+  (type $ft (func))
+  (type $ct (cont $ft))
+  (tag $yield)
+
+  (table $conts 1000 (ref null $ct))
+
+  (func $task
+    (suspend $yield))
+  (elem declare func $task)
+
+  (func (export "entry") (result i32)
+    (local $i i32)
+    (local $done i32)
+    ;; Suspend all continuations once, so that their stacks are live at the
+    ;; same time.
+    (loop $spawn
+      (block $on-yield (result (ref $ct))
+        (resume $ct (on $yield $on-yield) (cont.new $ct (ref.func $task)))
+        (unreachable))
+      (table.set $conts (local.get $i))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br_if $spawn (i32.lt_u (local.get $i) (i32.const 1000))))
+    ;; Then run all of them to completion.
+    (local.set $i (i32.const 0))
+    (loop $finish
+      (resume $ct (table.get $conts (local.get $i)))
+      (local.set $done (i32.add (local.get $done) (i32.const 1)))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br_if $finish (i32.lt_u (local.get $i) (i32.const 1000))))
+    (local.get $done))
+)
+"#;
+
+        let result = runner(4096).run_test::<i32>(wat, &[])?;
+        assert_eq!(result, 1000);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_configurations() {
+        let mut config = Config::default();
+        config.wasm_stack_switching(true);
+        config.wasmfx_stack_size(1 << 16);
+        config.wasmfx_initial_stack_size(1 << 17);
+        assert!(Engine::new(&config).is_err());
+
+        let mut config = Config::default();
+        config.wasm_stack_switching(true);
+        config.wasmfx_initial_stack_size(4096);
+        config.signals_based_traps(false);
+        assert!(Engine::new(&config).is_err());
+    }
+}
+
+mod interruption {
+    use super::test_utils::*;
+    use wasmtime::*;
+
+    fn runner(f: impl FnOnce(&mut Config)) -> Runner {
+        let mut config = Config::default();
+        config.wasm_function_references(true);
+        config.wasm_exceptions(true);
+        config.wasm_stack_switching(true);
+        config.wasm_gc(true);
+        f(&mut config);
+
+        let engine = Engine::new(&config).unwrap();
+        let store = Store::<()>::new(&engine, ());
+        Runner { engine, store }
+    }
+
+    #[test]
+    fn fuel_consumed_by_continuation_is_recorded() -> Result<()> {
+        let wat = r#"
+        (module
+            (type $ft (func))
+            (type $ct (cont $ft))
+
+            (func $spin
+                (local $i i32)
+                (local.set $i (i32.const 1000))
+                (loop $l
+                    (br_if $l (local.tee $i (i32.sub (local.get $i) (i32.const 1))))))
+            (elem declare func $spin)
+
+            (func (export "entry")
+                (resume $ct (cont.new $ct (ref.func $spin))))
+        )
+    "#;
+
+        let mut runner = runner(|config| {
+            config.consume_fuel(true);
+        });
+        runner.store.set_fuel(100_000)?;
+        let module = Module::new(&runner.engine, wat)?;
+        let instance = Instance::new(&mut runner.store, &module, &[])?;
+        let entry = instance.get_typed_func::<(), ()>(&mut runner.store, "entry")?;
+        entry.call(&mut runner.store, ())?;
+        assert!(100_000 - runner.store.get_fuel()? >= 1000);
+        Ok(())
+    }
+
+    #[test]
+    fn suspended_continuations_cannot_escape_fuel() -> Result<()> {
+        // Each iteration creates a fresh continuation which does some work and
+        // suspends. The fuel it consumed must still be accounted for.
+        let wat = r#"
+        (module
+            (type $ft (func))
+            (type $ct (cont $ft))
+            (tag $yield)
+
+            (global $iterations (export "iterations") (mut i32) (i32.const 0))
+
+            (func $work
+                (local $i i32)
+                (local.set $i (i32.const 1000))
+                (loop $l
+                    (br_if $l (local.tee $i (i32.sub (local.get $i) (i32.const 1)))))
+                (suspend $yield))
+            (elem declare func $work)
+
+            (func (export "entry")
+                (loop $l
+                    (global.set $iterations
+                        (i32.add (global.get $iterations) (i32.const 1)))
+                    (block $on_yield (result (ref $ct))
+                        (resume $ct (on $yield $on_yield) (cont.new $ct (ref.func $work)))
+                        (unreachable))
+                    (drop)
+                    (br $l)))
+        )
+    "#;
+
+        let mut runner = runner(|config| {
+            config.consume_fuel(true);
+        });
+        runner.store.set_fuel(100_000)?;
+        let module = Module::new(&runner.engine, wat)?;
+        let instance = Instance::new(&mut runner.store, &module, &[])?;
+        let entry = instance.get_typed_func::<(), ()>(&mut runner.store, "entry")?;
+        let error = entry.call(&mut runner.store, ()).unwrap_err();
+        assert_eq!(error.downcast_ref::<Trap>(), Some(&Trap::OutOfFuel));
+
+        let iterations = instance
+            .get_global(&mut runner.store, "iterations")
+            .unwrap()
+            .get(&mut runner.store)
+            .unwrap_i32();
+        assert!(iterations <= 100, "ran {iterations} iterations");
+        Ok(())
+    }
+
+    #[test]
+    fn epoch_deadline_updated_while_suspended() -> Result<()> {
+        let wat = r#"
+        (module
+            (import "" "bump" (func $bump))
+            (tag $yield (export "yield"))
+
+            (func (export "body")
+                (suspend $yield)
+                (call $bump)
+                (loop $l (br $l)))
+        )
+    "#;
+
+        let mut runner = runner(|config| {
+            config.epoch_interruption(true);
+        });
+        let engine = runner.engine.clone();
+        let bump = Func::wrap(&mut runner.store, move || engine.increment_epoch());
+        let module = Module::new(&runner.engine, wat)?;
+        let instance = Instance::new(&mut runner.store, &module, &[bump.into()])?;
+        let body = instance.get_func(&mut runner.store, "body").unwrap();
+        let tag = match instance.get_export(&mut runner.store, "yield") {
+            Some(Extern::Tag(tag)) => tag,
+            _ => panic!("expected a tag export"),
+        };
+
+        // Run up to the suspension with a deadline which is never reached.
+        runner.store.set_epoch_deadline(1_000_000);
+        let k = Continuation::new(&mut runner.store, &body, &[tag])?;
+        assert!(matches!(
+            k.resume(&mut runner.store, &[])?,
+            Resumed::Suspended { .. }
+        ));
+
+        // Lowering the deadline while the continuation is suspended must be
+        // respected once it is resumed.
+        runner.store.set_epoch_deadline(1);
+        let error = k.resume(&mut runner.store, &[]).unwrap_err();
+        assert_eq!(error.downcast_ref::<Trap>(), Some(&Trap::Interrupt));
+        Ok(())
+    }
+}
+
+mod dispatch_tables {
+    use super::test_utils::*;
+    use wasmtime::*;
+
+    fn runner(dispatch_tables: bool) -> Runner {
+        let mut config = Config::default();
+        config.wasm_function_references(true);
+        config.wasm_exceptions(true);
+        config.wasm_stack_switching(true);
+        config.wasm_gc(true);
+        config.wasmfx_tag_dispatch_tables(dispatch_tables);
+
+        let engine = Engine::new(&config).unwrap();
+        let store = Store::<()>::new(&engine, ());
+        Runner { engine, store }
+    }
+
+    const HANDLERS: &str = r#"
+(module
+  (type $ft (func))
+  (type $ct (cont $ft))
+  (tag $t0) (tag $t1) (tag $t2) (tag $t3) (tag $t4) (tag $t5)
+
+  (global $which (mut i32) (i32.const 0))
+
+  (func $suspend
+    (block $b5
+      (block $b4
+        (block $b3
+          (block $b2
+            (block $b1
+              (block $b0
+                (br_table $b0 $b1 $b2 $b3 $b4 $b5 (global.get $which)))
+              (suspend $t0)
+              (return))
+            (suspend $t1)
+            (return))
+          (suspend $t2)
+          (return))
+        (suspend $t3)
+        (return))
+      (suspend $t4)
+      (return))
+    (suspend $t5))
+  (elem declare func $suspend)
+
+  ;; Only handles $t5, all other tags are forwarded to the outer handlers.
+  (func $inner
+    (block $h5 (result (ref $ct))
+      (resume $ct (on $t5 $h5) (cont.new $ct (ref.func $suspend)))
+      (return))
+    (drop)
+    (global.set $which (i32.const 55)))
+  (elem declare func $inner)
+
+  (func (export "entry") (param $which i32) (result i32)
+    (global.set $which (local.get $which))
+    (block $h0 (result (ref $ct))
+      (block $h1 (result (ref $ct))
+        (block $h2 (result (ref $ct))
+          (block $h2-shadowed (result (ref $ct))
+            (block $h3 (result (ref $ct))
+              (block $h4 (result (ref $ct))
+                (resume $ct
+                  (on $t0 $h0) (on $t1 $h1) (on $t2 $h2) (on $t2 $h2-shadowed)
+                  (on $t3 $h3) (on $t4 $h4)
+                  (cont.new $ct (ref.func $inner)))
+                (return (global.get $which)))
+              (drop)
+              (return (i32.const 4)))
+            (drop)
+            (return (i32.const 3)))
+          (drop)
+          (return (i32.const 22)))
+        (drop)
+        (return (i32.const 2)))
+      (drop)
+      (return (i32.const 1)))
+    (drop)
+    (i32.const 0))
+)
+"#;
+
+    fn handled_by(dispatch_tables: bool, which: i32) -> Result<i32> {
+        let Runner { engine, mut store } = runner(dispatch_tables);
+        let module = Module::new(&engine, HANDLERS)?;
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let entry = instance.get_typed_func::<i32, i32>(&mut store, "entry")?;
+        entry.call(&mut store, which)
+    }
+
+    #[test]
+    fn handlers_are_found() -> Result<()> {
+        for dispatch_tables in [false, true] {
+            for which in 0..5 {
+                assert_eq!(handled_by(dispatch_tables, which)?, which);
+            }
+            assert_eq!(handled_by(dispatch_tables, 5)?, 55);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn many_handlers() -> Result<()> {
+        const TAGS: usize = 40;
+
+        let tags: String = (0..TAGS).map(|i| format!("(tag $t{i})\n")).collect();
+        let suspends: String = (0..TAGS)
+            .map(|i| {
+                format!("(if (i32.eq (local.get 0) (i32.const {i})) (then (suspend $t{i})))\n")
+            })
+            .collect();
+        let clauses: String = (0..TAGS).map(|i| format!("(on $t{i} $h{i}) ")).collect();
+        let mut handlers = format!(
+            "(resume $ct {clauses} (cont.bind $ct-arg $ct (local.get 0) (cont.new $ct-arg (ref.func $suspend))))
+             (return (i32.const -1))"
+        );
+        for i in (0..TAGS).rev() {
+            handlers = format!(
+                "(block $h{i} (result (ref $ct)) {handlers}) (drop) (return (i32.const {i}))"
+            );
+        }
+        let wat = format!(
+            r#"
+(module
+  (type $ft (func))
+  (type $ct (cont $ft))
+  (type $ft-arg (func (param i32)))
+  (type $ct-arg (cont $ft-arg))
+  {tags}
+  (func $suspend (param i32)
+    {suspends})
+  (elem declare func $suspend)
+
+  (func (export "entry") (param i32) (result i32)
+    {handlers}
+    (unreachable))
+)
+"#
+        );
+
+        for dispatch_tables in [false, true] {
+            let Runner { engine, mut store } = runner(dispatch_tables);
+            let module = Module::new(&engine, &wat)?;
+            let instance = Instance::new(&mut store, &module, &[])?;
+            let entry = instance.get_typed_func::<i32, i32>(&mut store, "entry")?;
+            for which in 0..TAGS as i32 {
+                assert_eq!(entry.call(&mut store, which)?, which);
+            }
+            assert_eq!(entry.call(&mut store, TAGS as i32)?, -1);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn imported_tags() -> Result<()> {
+        // The outer handlers include an imported tag, so they are searched
+        // linearly, while the inner handler's dispatch table is laid out
+        // while compiling. Suspensions are found across both kinds of lists.
+        let wat = r#"
+(module
+  (type $ft (func))
+  (type $ct (cont $ft))
+  (import "tags" "imported" (tag $imported))
+  (tag $local) (tag $inner)
+
+  (global $which (mut i32) (i32.const 0))
+
+  (func $suspend
+    (block $b1
+      (block $b0
+        (br_table $b0 $b1 (global.get $which))
+        (suspend $inner)
+        (return))
+      (suspend $local)
+      (return))
+    (suspend $imported))
+  (elem declare func $suspend)
+
+  (func $inner
+    (block $h (result (ref $ct))
+      (resume $ct (on $inner $h) (cont.new $ct (ref.func $suspend)))
+      (return))
+    (drop)
+    (global.set $which (i32.const 10)))
+  (elem declare func $inner)
+
+  (func (export "entry") (param $which i32) (result i32)
+    (global.set $which (local.get $which))
+    (block $h-imported (result (ref $ct))
+      (block $h-local (result (ref $ct))
+        (resume $ct (on $imported $h-imported) (on $local $h-local)
+          (cont.new $ct (ref.func $inner)))
+        (return (global.get $which)))
+      (drop)
+      (return (i32.const 1)))
+    (drop)
+    (i32.const 2))
+)
+"#;
+
+        for dispatch_tables in [false, true] {
+            let Runner { engine, mut store } = runner(dispatch_tables);
+            let tags = Module::new(&engine, r#"(module (tag (export "imported")))"#)?;
+            let tags = Instance::new(&mut store, &tags, &[])?;
+            let imported = tags.get_export(&mut store, "imported").unwrap();
+            let module = Module::new(&engine, wat)?;
+            let instance = Instance::new(&mut store, &module, &[imported])?;
+            let entry = instance.get_typed_func::<i32, i32>(&mut store, "entry")?;
+            assert_eq!(entry.call(&mut store, 0)?, 10);
+            assert_eq!(entry.call(&mut store, 1)?, 1);
+            assert_eq!(entry.call(&mut store, 2)?, 2);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(
+    any(not(feature = "wasmfx_baseline"), feature = "wasmfx_no_baseline"),
+    not(feature = "wasmfx_pooling_allocator")
+))]
+mod stack_cache {
+    use wasmtime::*;
+
+    const CONTINUATIONS: &str = r#"
+(module
+  (type $ft (func))
+  (type $ct (cont $ft))
+  (tag $yield)
+
+  (table $conts 16 (ref null $ct))
+
+  (func $task
+    (suspend $yield))
+  (elem declare func $task)
+
+  ;; Runs `$n` continuations one after another.
+  (func (export "sequential") (param $n i32)
+    (loop $next
+      (block $on-yield (result (ref $ct))
+        (resume $ct (on $yield $on-yield) (cont.new $ct (ref.func $task)))
+        (unreachable))
+      (resume $ct)
+      (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+      (br_if $next (local.get $n))))
+
+  ;; Suspends `$n` continuations before running all of them to completion.
+  (func (export "concurrent") (param $n i32)
+    (local $i i32)
+    (loop $spawn
+      (block $on-yield (result (ref $ct))
+        (resume $ct (on $yield $on-yield) (cont.new $ct (ref.func $task)))
+        (unreachable))
+      (table.set $conts (local.get $i))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br_if $spawn (i32.lt_u (local.get $i) (local.get $n))))
+    (loop $finish
+      (local.set $i (i32.sub (local.get $i) (i32.const 1)))
+      (resume $ct (table.get $conts (local.get $i)))
+      (br_if $finish (local.get $i))))
+)
+"#;
+
+    fn instantiate(cache_size: usize) -> Result<(Store<()>, Instance)> {
+        let mut config = Config::default();
+        config.wasm_function_references(true);
+        config.wasm_exceptions(true);
+        config.wasm_stack_switching(true);
+        config.wasm_gc(true);
+        config.wasmfx_stack_cache_size(cache_size);
+
+        let engine = Engine::new(&config)?;
+        let mut store = Store::new(&engine, ());
+        let module = Module::new(&engine, CONTINUATIONS)?;
+        let instance = Instance::new(&mut store, &module, &[])?;
+        Ok((store, instance))
+    }
+
+    #[test]
+    fn stacks_are_reused() -> Result<()> {
+        let (mut store, instance) = instantiate(4)?;
+        let sequential = instance.get_typed_func::<i32, ()>(&mut store, "sequential")?;
+        sequential.call(&mut store, 100)?;
+
+        let stats = store.wasmfx_stack_cache_stats();
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.hits, 99);
+        assert_eq!(stats.evictions, 0);
+        assert_eq!(stats.cached_stacks, 1);
+        assert!(stats.cached_bytes > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn cache_size_is_bounded() -> Result<()> {
+        let (mut store, instance) = instantiate(4)?;
+        let concurrent = instance.get_typed_func::<i32, ()>(&mut store, "concurrent")?;
+        concurrent.call(&mut store, 10)?;
+
+        let stats = store.wasmfx_stack_cache_stats();
+        assert_eq!(stats.misses, 10);
+        assert_eq!(stats.evictions, 6);
+        assert_eq!(stats.cached_stacks, 4);
+
+        // The cached stacks are used for the next continuations.
+        concurrent.call(&mut store, 10)?;
+        let stats = store.wasmfx_stack_cache_stats();
+        assert_eq!(stats.hits, 4);
+        assert_eq!(stats.misses, 16);
+        assert_eq!(stats.cached_stacks, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn trim() -> Result<()> {
+        let (mut store, instance) = instantiate(8)?;
+        let concurrent = instance.get_typed_func::<i32, ()>(&mut store, "concurrent")?;
+        concurrent.call(&mut store, 8)?;
+        let bytes = store.wasmfx_stack_cache_stats().cached_bytes;
+
+        store.trim_wasmfx_stack_cache(2);
+        let stats = store.wasmfx_stack_cache_stats();
+        assert_eq!(stats.cached_stacks, 2);
+        assert_eq!(stats.cached_bytes, bytes / 4);
+
+        store.trim_wasmfx_stack_cache(0);
+        let stats = store.wasmfx_stack_cache_stats();
+        assert_eq!(stats.cached_stacks, 0);
+        assert_eq!(stats.cached_bytes, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn disabled() -> Result<()> {
+        let (mut store, instance) = instantiate(0)?;
+        let sequential = instance.get_typed_func::<i32, ()>(&mut store, "sequential")?;
+        sequential.call(&mut store, 10)?;
+
+        let stats = store.wasmfx_stack_cache_stats();
+        assert_eq!(stats.hits, 0);
+        assert_eq!(stats.misses, 10);
+        assert_eq!(stats.cached_stacks, 0);
+        Ok(())
+    }
+}
+
+#[test]
+fn continuation_stack_limiter() -> Result<()> {
+    struct Limiter {
+        allocations: Vec<(usize, usize)>,
+        max: usize,
+    }
+
+    impl ResourceLimiter for Limiter {
+        fn memory_growing(&mut self, _: usize, _: usize, _: Option<usize>) -> Result<bool> {
+            Ok(true)
+        }
+
+        fn table_growing(&mut self, _: usize, _: usize, _: Option<usize>) -> Result<bool> {
+            Ok(true)
+        }
+
+        fn continuation_stack_allocating(
+            &mut self,
+            current: usize,
+            desired: usize,
+        ) -> Result<bool> {
+            self.allocations.push((current, desired));
+            Ok(desired <= self.max)
+        }
+    }
+
+    let stack_size = 128 << 10;
+    let mut config = Config::default();
+    config.wasm_function_references(true);
+    config.wasm_exceptions(true);
+    config.wasm_stack_switching(true);
+    config.wasmfx_stack_size(stack_size);
+    let engine = Engine::new(&config)?;
+
+    let module = Module::new(
+        &engine,
+        r#"
+        (module
+          (type $ft (func))
+          (type $ct (cont $ft))
+          (func $f)
+          (elem declare func $f)
+
+          (func (export "new")
+            (drop (cont.new $ct (ref.func $f)))
+          )
+        )
+    "#,
+    )?;
+
+    let mut store = Store::new(
+        &engine,
+        Limiter {
+            allocations: Vec::new(),
+            max: stack_size,
+        },
+    );
+    store.limiter(|l| l as &mut dyn ResourceLimiter);
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let new = instance.get_typed_func::<(), ()>(&mut store, "new")?;
+
+    // The continuation is never resumed, so its stack stays allocated and the
+    // second one exceeds the limit.
+    new.call(&mut store, ())?;
+    assert_eq!(store.memory_consumed(), stack_size);
+    assert!(new.call(&mut store, ()).is_err());
+    assert_eq!(store.memory_consumed(), stack_size);
+    assert_eq!(
+        store.data().allocations,
+        [(0, stack_size), (stack_size, 2 * stack_size)]
+    );
+
+    Ok(())
+}
+
+mod yield_to_host {
+    use crate::async_functions::PollOnce;
+    use wasmtime::*;
+
+    fn engine() -> Engine {
+        let mut config = Config::default();
+        config.wasm_function_references(true);
+        config.wasm_exceptions(true);
+        config.wasm_stack_switching(true);
+        config.async_support(true);
+        Engine::new(&config).unwrap()
+    }
+
+    const WAT: &str = r#"
+    (module
+        (type $ft (func (result i32)))
+        (type $ct (cont $ft))
+        (tag $io (export "io") (param i32) (result i32))
+        (tag $other)
+
+        (func $task (result i32)
+            (suspend $io (i32.const 1)))
+        (elem declare func $task)
+
+        ;; Suspends to $io from the main stack.
+        (func (export "main") (result i32)
+            (suspend $io (i32.const 10)))
+
+        ;; Suspends to $io from within a continuation which only handles $other.
+        (func (export "nested") (result i32)
+            (block $h (result (ref $ct))
+                (return (resume $ct (on $other $h) (cont.new $ct (ref.func $task)))))
+            (unreachable))
+
+        (func (export "other")
+            (suspend $other))
+    )
+    "#;
+
+    fn io_tag(store: &mut Store<()>, instance: &Instance) -> Tag {
+        match instance.get_export(store, "io") {
+            Some(Extern::Tag(tag)) => tag,
+            _ => panic!("expected a tag export"),
+        }
+    }
+
+    #[tokio::test]
+    async fn suspend_yields_to_host() -> Result<()> {
+        let engine = engine();
+        let mut store = Store::new(&engine, ());
+        let module = Module::new(&engine, WAT)?;
+        let instance = Instance::new_async(&mut store, &module, &[]).await?;
+        io_tag(&mut store, &instance).yield_to_host(&mut store, |_store, params, results| {
+            Box::new(async move {
+                tokio::task::yield_now().await;
+                results[0] = Val::I32(params[0].unwrap_i32() + 1);
+                Ok(())
+            })
+        })?;
+
+        for (name, expected) in [("main", 11), ("nested", 2)] {
+            let func = instance.get_typed_func::<(), i32>(&mut store, name)?;
+
+            // The call is pending until the host future completes.
+            let future = PollOnce::new(Box::pin(func.call_async(&mut store, ())))
+                .await
+                .err()
+                .expect("call should yield to the host");
+            assert_eq!(future.await?, expected);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn host_future_error_traps() -> Result<()> {
+        let engine = engine();
+        let mut store = Store::new(&engine, ());
+        let module = Module::new(&engine, WAT)?;
+        let instance = Instance::new_async(&mut store, &module, &[]).await?;
+        io_tag(&mut store, &instance).yield_to_host(&mut store, |_store, _params, _results| {
+            Box::new(async { Err(anyhow::anyhow!("io failed")) })
+        })?;
+
+        let main = instance.get_typed_func::<(), i32>(&mut store, "main")?;
+        let error = main.call_async(&mut store, ()).await.unwrap_err();
+        assert!(format!("{error:?}").contains("io failed"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn other_tags_still_trap() -> Result<()> {
+        let engine = engine();
+        let mut store = Store::new(&engine, ());
+        let module = Module::new(&engine, WAT)?;
+        let instance = Instance::new_async(&mut store, &module, &[]).await?;
+        io_tag(&mut store, &instance).yield_to_host(&mut store, |_store, _params, results| {
+            Box::new(async move {
+                results[0] = Val::I32(0);
+                Ok(())
+            })
+        })?;
+
+        let other = instance.get_typed_func::<(), ()>(&mut store, "other")?;
+        let error = other.call_async(&mut store, ()).await.unwrap_err();
+        assert_eq!(error.downcast_ref::<Trap>(), Some(&Trap::UnhandledTag));
+        Ok(())
+    }
+
+    #[test]
+    fn requires_async_support() -> Result<()> {
+        let mut config = Config::default();
+        config.wasm_function_references(true);
+        config.wasm_exceptions(true);
+        config.wasm_stack_switching(true);
+        let engine = Engine::new(&config)?;
+        let mut store = Store::new(&engine, ());
+        let module = Module::new(&engine, WAT)?;
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let result = io_tag(&mut store, &instance)
+            .yield_to_host(&mut store, |_, _, _| Box::new(async { Ok(()) }));
+        assert!(result.is_err());
+        Ok(())
+    }
+}
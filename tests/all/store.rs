@@ -1,5 +1,5 @@
 use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
-use wasmtime::{Engine, Store};
+use wasmtime::{Config, Engine, Func, Instance, Module, Ref, Store};
 
 #[test]
 fn into_inner() {
@@ -20,3 +20,210 @@ fn into_inner() {
     Store::new(&engine, A).into_data();
     assert_eq!(HITS.load(SeqCst), 2);
 }
+
+const SNAPSHOT_MODULE: &str = r#"
+    (module
+        (memory (export "memory") 1 10)
+        (global (export "g") (mut i32) (i32.const 0))
+        (table (export "table") 2 funcref)
+        (func $a (result i32) i32.const 1)
+        (func $b (result i32) i32.const 2)
+        (elem declare func $a $b)
+        (func (export "mutate")
+            (i32.store (i32.const 100) (i32.const 42))
+            (drop (memory.grow (i32.const 1)))
+            (global.set 0 (i32.const 7))
+            (table.set (i32.const 1) (ref.func $b))
+            (drop (table.grow (ref.func $a) (i32.const 1))))
+        (func (export "call") (param i32) (result i32)
+            (call_indirect (result i32) (local.get 0)))
+    )
+"#;
+
+#[test]
+fn snapshot_and_restore() -> wasmtime::Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, SNAPSHOT_MODULE)?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    instance
+        .get_typed_func::<(), ()>(&mut store, "mutate")?
+        .call(&mut store, ())?;
+    let snapshot = store.snapshot()?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    store.restore(&snapshot)?;
+
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    assert_eq!(memory.size(&store), 2);
+    assert_eq!(memory.data(&store)[100], 42);
+    let global = instance.get_global(&mut store, "g").unwrap();
+    assert_eq!(global.get(&mut store).i32(), Some(7));
+    let table = instance.get_table(&mut store, "table").unwrap();
+    assert_eq!(table.size(&store), 3);
+
+    let call = instance.get_typed_func::<i32, i32>(&mut store, "call")?;
+    assert!(call.call(&mut store, 0).is_err());
+    assert_eq!(call.call(&mut store, 1)?, 2);
+    assert_eq!(call.call(&mut store, 2)?, 1);
+
+    // The restored store can be snapshotted again, with the same result.
+    assert_eq!(store.snapshot()?, snapshot);
+    Ok(())
+}
+
+#[test]
+fn restore_mismatched_store() -> wasmtime::Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, SNAPSHOT_MODULE)?;
+
+    let mut store = Store::new(&engine, ());
+    Instance::new(&mut store, &module, &[])?;
+    let snapshot = store.snapshot()?;
+
+    let mut store = Store::new(&engine, ());
+    assert!(store.restore(&snapshot).is_err());
+    Instance::new(&mut store, &module, &[])?;
+    Instance::new(&mut store, &module, &[])?;
+    assert!(store.restore(&snapshot).is_err());
+
+    assert!(store.restore(b"not a snapshot").is_err());
+    Ok(())
+}
+
+#[test]
+fn snapshot_host_func_ref() -> wasmtime::Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, SNAPSHOT_MODULE)?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let func = Func::wrap(&mut store, || 3i32);
+    let table = instance.get_table(&mut store, "table").unwrap();
+    table.set(&mut store, 0, Ref::Func(Some(func)))?;
+
+    let err = store.snapshot().unwrap_err();
+    assert!(err.to_string().contains("host function"), "{err:?}");
+    Ok(())
+}
+
+#[test]
+fn restore_into_different_module() -> wasmtime::Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, SNAPSHOT_MODULE)?;
+    // The same entities as `SNAPSHOT_MODULE`, but different code.
+    let other = Module::new(
+        &engine,
+        &SNAPSHOT_MODULE.replace("(i32.const 42)", "(i32.const 43)"),
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    Instance::new(&mut store, &module, &[])?;
+    let snapshot = store.snapshot()?;
+
+    let mut store = Store::new(&engine, ());
+    Instance::new(&mut store, &other, &[])?;
+    let err = store.restore(&snapshot).unwrap_err();
+    assert!(err.to_string().contains("same modules"), "{err:?}");
+    Ok(())
+}
+
+#[test]
+fn bad_snapshot_leaves_store_unchanged() -> wasmtime::Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, SNAPSHOT_MODULE)?;
+
+    let mut store = Store::new(&engine, ());
+    Instance::new(&mut store, &module, &[])?;
+    let snapshot = store.snapshot()?;
+
+    // The table of this store is larger than in the snapshot, which is only
+    // detected after the memory has been checked.
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    memory.data_mut(&mut store)[100] = 1;
+    let table = instance.get_table(&mut store, "table").unwrap();
+    table.grow(&mut store, 1, Ref::Func(None))?;
+    let err = store.restore(&snapshot).unwrap_err();
+    assert!(err.to_string().contains("table is larger"), "{err:?}");
+    assert_eq!(memory.data(&store)[100], 1);
+    Ok(())
+}
+
+#[test]
+fn snapshot_shared_memory() -> wasmtime::Result<()> {
+    let mut config = Config::new();
+    config.wasm_threads(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, r#"(module (memory 1 1 shared))"#)?;
+
+    let mut store = Store::new(&engine, ());
+    Instance::new(&mut store, &module, &[])?;
+    let err = store.snapshot().unwrap_err();
+    assert!(err.to_string().contains("shared memory"), "{err:?}");
+    Ok(())
+}
+
+#[test]
+fn snapshot_continuations() -> wasmtime::Result<()> {
+    let mut config = Config::new();
+    config.wasm_function_references(true);
+    config.wasm_exceptions(true);
+    config.wasm_stack_switching(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $ft (func (result i32)))
+                (type $ct (cont $ft))
+                (tag $yield)
+                (table $t 3 (ref null $ct))
+                (func $seven (result i32) i32.const 7)
+                (func $yield (result i32) (suspend $yield) i32.const 8)
+                (elem declare func $seven $yield)
+                (func (export "init")
+                    (table.set $t (i32.const 0) (cont.new $ct (ref.func $seven)))
+                    (table.set $t (i32.const 1) (cont.new $ct (ref.func $seven))))
+                (func (export "resume") (param i32) (result i32)
+                    (resume $ct (table.get $t (local.get 0))))
+                (func (export "start")
+                    (block $h (result (ref $ct))
+                        (resume $ct (on $yield $h) (cont.new $ct (ref.func $yield)))
+                        unreachable)
+                    (table.set $t (i32.const 2)))
+            )
+        "#,
+    )?;
+
+    // A fresh continuation, a consumed continuation, and a null entry.
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    instance
+        .get_typed_func::<(), ()>(&mut store, "init")?
+        .call(&mut store, ())?;
+    let resume = instance.get_typed_func::<i32, i32>(&mut store, "resume")?;
+    assert_eq!(resume.call(&mut store, 1)?, 7);
+    let snapshot = store.snapshot()?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    store.restore(&snapshot)?;
+    let resume = instance.get_typed_func::<i32, i32>(&mut store, "resume")?;
+    assert!(resume.call(&mut store, 1).is_err());
+    assert!(resume.call(&mut store, 2).is_err());
+    assert_eq!(resume.call(&mut store, 0)?, 7);
+
+    // A suspended continuation's state can't be captured.
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    instance
+        .get_typed_func::<(), ()>(&mut store, "start")?
+        .call(&mut store, ())?;
+    let err = store.snapshot().unwrap_err();
+    assert!(err.to_string().contains("has been resumed"), "{err:?}");
+    Ok(())
+}
@@ -40,6 +40,28 @@ fn struct_new_with_fields() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn struct_new_with_type() -> Result<()> {
+    let mut store = gc_store()?;
+    let struct_ty = StructType::new(
+        store.engine(),
+        [
+            FieldType::new(Mutability::Var, StorageType::I16),
+            FieldType::new(Mutability::Const, StorageType::ValType(ValType::F64)),
+        ],
+    )?;
+    let s = StructRef::new_with_type(&mut store, &struct_ty, &[Val::I32(-1), 1.5f64.into()])?;
+    assert!(s.matches_ty(&store, &struct_ty)?);
+    assert_eq!(s.field(&mut store, 0)?.unwrap_i32(), 0xffff);
+    assert_eq!(s.field(&mut store, 1)?.unwrap_f64(), 1.5);
+    s.set_field(&mut store, 0, Val::I32(7))?;
+    assert_eq!(s.field(&mut store, 0)?.unwrap_i32(), 7);
+
+    let err = StructRef::new_with_type(&mut store, &struct_ty, &[Val::I32(0)]).unwrap_err();
+    assert!(err.to_string().contains("expected 2 fields, got 1"));
+    Ok(())
+}
+
 #[test]
 fn struct_new_unrooted_field() -> Result<()> {
     let mut store = gc_store()?;
@@ -291,3 +291,108 @@ fn test_memory_size_accessibility() -> Result<()> {
 
     Ok(())
 }
+
+const DEADLOCK_WAT: &str = r#"(module
+    (import "env" "memory" (memory 1 1 shared))
+    (func (export "notify") (param i32)
+        (drop (memory.atomic.notify (local.get 0) (i32.const 0))))
+    (func (export "wait") (param i32 i64) (result i32)
+        (memory.atomic.wait32 (local.get 0) (i32.const 0) (local.get 1)))
+)"#;
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn deadlock_monitor_reports_cycle() -> Result<()> {
+    let Some(engine) = engine() else {
+        return Ok(());
+    };
+    let module = Module::new(&engine, DEADLOCK_WAT)?;
+    let memory = SharedMemory::new(&engine, MemoryType::shared(1, 1))?;
+    let (tx, rx) = std::sync::mpsc::channel();
+    let tx = std::sync::Mutex::new(tx);
+    let monitor = DeadlockMonitor::new(move |report| {
+        tx.lock().unwrap().send(report.clone()).unwrap();
+    });
+    let barrier = Arc::new(std::sync::Barrier::new(2));
+
+    // Each thread notifies the location that the other one waits on, so
+    // they depend on each other once both are waiting.
+    let threads = [(0, 4), (4, 0)].map(|(notify, wait)| {
+        let mut store = Store::new(&engine, ());
+        let thread = store.join_deadlock_monitor(&monitor).unwrap();
+        let module = module.clone();
+        let memory = memory.clone();
+        let barrier = barrier.clone();
+        let handle = std::thread::spawn(move || {
+            let instance = Instance::new(&mut store, &module, &[memory.into()]).unwrap();
+            instance
+                .get_typed_func::<i32, ()>(&mut store, "notify")
+                .unwrap()
+                .call(&mut store, notify)
+                .unwrap();
+            barrier.wait();
+            instance
+                .get_typed_func::<(i32, i64), i32>(&mut store, "wait")
+                .unwrap()
+                .call(&mut store, (wait, -1))
+                .unwrap()
+        });
+        (thread, handle)
+    });
+    assert_eq!(monitor.members(), 2);
+
+    let report = rx.recv().unwrap();
+    let mut waits = report
+        .threads()
+        .iter()
+        .map(|t| (t.thread, t.address))
+        .collect::<Vec<_>>();
+    waits.sort();
+    assert_eq!(waits, [(threads[0].0, 4), (threads[1].0, 0)]);
+    assert!(report.to_string().contains("probable deadlock"));
+
+    // Break the deadlock from the host.
+    for (_, handle) in threads {
+        while !handle.is_finished() {
+            memory.atomic_notify(0, u32::MAX)?;
+            memory.atomic_notify(4, u32::MAX)?;
+            std::thread::yield_now();
+        }
+        assert_eq!(handle.join().unwrap(), 0);
+    }
+    assert_eq!(monitor.members(), 0);
+    assert!(rx.try_recv().is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn deadlock_monitor_ignores_timed_waits() -> Result<()> {
+    let Some(engine) = engine() else {
+        return Ok(());
+    };
+    let module = Module::new(&engine, DEADLOCK_WAT)?;
+    let memory = SharedMemory::new(&engine, MemoryType::shared(1, 1))?;
+    let reported = Arc::new(AtomicBool::new(false));
+    let monitor = DeadlockMonitor::new({
+        let reported = reported.clone();
+        move |_| reported.store(true, Ordering::SeqCst)
+    });
+    let mut store = Store::new(&engine, ());
+    store.join_deadlock_monitor(&monitor)?;
+    assert!(store.join_deadlock_monitor(&monitor).is_err());
+
+    let instance = Instance::new(&mut store, &module, &[memory.clone().into()])?;
+    let wait = instance.get_typed_func::<(i32, i64), i32>(&mut store, "wait")?;
+    // A timed wait times out, and is never a deadlock.
+    assert_eq!(wait.call(&mut store, (0, 1_000))?, 2);
+    // An untimed wait on a location without the expected value returns
+    // immediately.
+    unsafe { *memory.data()[8].get() = 1 };
+    assert_eq!(wait.call(&mut store, (8, -1))?, 1);
+    assert!(!reported.load(Ordering::SeqCst));
+
+    drop(store);
+    assert_eq!(monitor.members(), 0);
+    Ok(())
+}
@@ -233,6 +233,47 @@ fn test_trap_backtrace_disabled() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_trap_backtrace_throttle() -> Result<()> {
+    let engine = Engine::default();
+    let mut store = Store::<()>::new(&engine, ());
+    let wat = r#"
+        (module
+            (func (export "run") unreachable)
+        )
+    "#;
+
+    let module = Module::new(store.engine(), wat)?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run_func = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+
+    assert!(store.backtrace_capture_stats().is_none());
+    store.set_backtrace_throttle(Some(BacktraceThrottle::new().sample_every(2).clone()));
+
+    for i in 0..4 {
+        let e = run_func.call(&mut store, ()).unwrap_err();
+        assert_eq!(
+            e.downcast_ref::<Trap>(),
+            Some(&Trap::UnreachableCodeReached)
+        );
+        assert_eq!(e.downcast_ref::<WasmBacktrace>().is_some(), i % 2 == 0);
+    }
+    assert_eq!(
+        store.backtrace_capture_stats(),
+        Some(BacktraceCaptureStats {
+            captured: 2,
+            suppressed: 2,
+        })
+    );
+
+    store.set_backtrace_throttle(None);
+    assert!(store.backtrace_capture_stats().is_none());
+    let e = run_func.call(&mut store, ()).unwrap_err();
+    assert!(e.downcast_ref::<WasmBacktrace>().is_some());
+    Ok(())
+}
+
 #[test]
 fn test_trap_trace_cb() -> Result<()> {
     let mut store = Store::<()>::default();
@@ -775,6 +816,61 @@ fn parse_dwarf_info() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn parse_external_dwarf_info() -> Result<()> {
+    let wasm = rustc(
+        "
+            fn main() {
+                panic!();
+            }
+        ",
+    );
+
+    // Move all DWARF sections out of the module into a separate file, as
+    // toolchains splitting out debug information do.
+    let mut stripped = wasm[..8].to_vec();
+    let mut debug = wasm[..8].to_vec();
+    let mut rest = &wasm[8..];
+    while !rest.is_empty() {
+        let (mut size, mut len) = (0, 1);
+        while rest[len] & 0x80 != 0 {
+            size |= usize::from(rest[len] & 0x7f) << (7 * (len - 1));
+            len += 1;
+        }
+        size |= usize::from(rest[len]) << (7 * (len - 1));
+        let (section, next) = rest.split_at(1 + len + size);
+        let payload = &section[1 + len..];
+        let is_debug = section[0] == 0 && payload[1..].starts_with(b".debug_");
+        if is_debug { &mut debug } else { &mut stripped }.extend_from_slice(section);
+        rest = next;
+    }
+    assert!(debug.len() > 8);
+
+    let mut config = Config::new();
+    config.wasm_backtrace_details(WasmBacktraceDetails::Enable);
+    let engine = Engine::new(&config)?;
+    let module = CodeBuilder::new(&engine)
+        .wasm_binary(&stripped, None)?
+        .external_debug_info(&debug)?
+        .compile_module()?;
+    let mut linker = Linker::new(&engine);
+    wasmtime_wasi::preview1::add_to_linker_sync(&mut linker, |t| t)?;
+    let mut store = Store::new(&engine, wasmtime_wasi::WasiCtxBuilder::new().build_p1());
+    linker.module(&mut store, "", &module)?;
+    let run = linker.get_default(&mut store, "")?;
+    let trap = run.call(&mut store, &[], &mut []).unwrap_err();
+
+    let frames = trap.downcast_ref::<WasmBacktrace>().unwrap().frames();
+    let found = frames
+        .iter()
+        .flat_map(|frame| frame.symbols())
+        .any(|symbol| {
+            symbol.file().is_some_and(|f| f.ends_with("input.rs")) && symbol.line() == Some(3)
+        });
+    assert!(found);
+    Ok(())
+}
+
 #[test]
 fn no_hint_even_with_dwarf_info() -> Result<()> {
     let mut config = Config::new();
@@ -1859,3 +1955,36 @@ fn return_call_to_aborting_wasm_function_with_stack_adjustments() -> Result<()>
 
     Ok(())
 }
+
+#[test]
+fn crash_handler_not_called_for_traps() -> Result<()> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CRASHES: AtomicUsize = AtomicUsize::new(0);
+
+    let mut config = Config::new();
+    unsafe {
+        config.crash_handler(|_| {
+            CRASHES.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+    let engine = Engine::new(&config)?;
+    let mut store = Store::new(&engine, ());
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+              (memory 1)
+              (func (export "oob") (drop (i32.load (i32.const 1000000))))
+            )
+        "#,
+    )?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let oob = instance.get_typed_func::<(), ()>(&mut store, "oob")?;
+
+    // Faults which Wasmtime expects are traps, not crashes.
+    let err = oob.call(&mut store, ()).unwrap_err();
+    assert_eq!(err.downcast_ref::<Trap>(), Some(&Trap::MemoryOutOfBounds));
+    assert_eq!(CRASHES.load(Ordering::SeqCst), 0);
+    Ok(())
+}
@@ -681,3 +681,49 @@ fn func_subtyping() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn shared_type_registry() -> Result<()> {
+    let engine1 = Engine::default();
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    let engine2 = Engine::new(&config)?;
+    let registry = SharedTypeRegistry::new();
+
+    let f1 = FuncType::new(&engine1, [ValType::I32], [ValType::I64]);
+    let f2 = FuncType::new(&engine2, [ValType::I32], [ValType::I64]);
+    let g = FuncType::new(&engine1, [ValType::I64], []);
+    let t1 = registry.intern_func(&f1)?;
+    let t2 = registry.intern_func(&f2)?;
+    let tg = registry.intern_func(&g)?;
+    assert_eq!(t1, t2);
+    assert_ne!(t1, tg);
+
+    let m1 = registry.intern_memory(&MemoryType::new(1, None));
+    let m2 = registry.intern_memory(&MemoryType::new(1, None));
+    let m3 = registry.intern_memory(&MemoryType::new(1, Some(2)));
+    assert_eq!(m1, m2);
+    assert_ne!(m1, m3);
+    assert_ne!(m1, t1);
+    assert_eq!(registry.len(), 4);
+
+    // Registrations are cached per engine, and created on demand for new
+    // engines in the group.
+    let engine3 = Engine::default();
+    let from2 = registry.func_type(&engine2, t1).unwrap();
+    assert!(Engine::same(from2.engine(), &engine2));
+    assert!(FuncType::eq(&from2, &f2));
+    let from3 = registry.func_type(&engine3, t1).unwrap();
+    assert!(Engine::same(from3.engine(), &engine3));
+    assert!(from3.matches(&registry.func_type(&engine3, t1).unwrap()));
+    assert!(registry.func_type(&engine1, m1).is_none());
+
+    assert_eq!(registry.memory_type(m3).unwrap().maximum(), Some(2));
+    assert!(registry.memory_type(t1).is_none());
+
+    // Types referencing concrete types are engine-specific.
+    let concrete = FuncType::new(&engine1, [], []);
+    let uses_concrete = FuncType::new(&engine1, [valty(HeapType::ConcreteFunc(concrete))], []);
+    assert!(registry.intern_func(&uses_concrete).is_err());
+    Ok(())
+}
@@ -0,0 +1,73 @@
+#![cfg(not(miri))]
+
+use object::{Object, ObjectSection, ObjectSymbol, SymbolKind};
+use wasmtime::*;
+
+/// Returns the number of frame description entries in an `.eh_frame` section.
+fn count_fdes(mut eh_frame: &[u8]) -> usize {
+    let mut fdes = 0;
+    while eh_frame.len() >= 4 {
+        let len = u32::from_le_bytes(eh_frame[..4].try_into().unwrap()) as usize;
+        if len == 0 {
+            break;
+        }
+        // Entries with a nonzero id, the offset back to their CIE, are FDEs.
+        let id = u32::from_le_bytes(eh_frame[4..8].try_into().unwrap());
+        if id != 0 {
+            fdes += 1;
+        }
+        eh_frame = &eh_frame[4 + len..];
+    }
+    fdes
+}
+
+// External profilers unwind through wasm frames with `.eh_frame`, so every
+// function in the text section, including trampolines, must have an entry.
+#[test]
+fn every_function_has_unwind_info() -> Result<()> {
+    let wat = r#"
+        (module
+            (import "" "host" (func $host (param i32) (result i32)))
+            (func $callee (param i32) (result i32)
+                (call $host (local.get 0)))
+            (func (export "run") (param i32) (result i32)
+                (call $callee (local.get 0)))
+        )
+    "#;
+
+    for strategy in [Strategy::Cranelift, Strategy::Winch] {
+        for target in ["x86_64-unknown-linux-gnu", "aarch64-unknown-linux-gnu"] {
+            let mut config = Config::new();
+            config
+                .strategy(strategy)
+                .native_unwind_info(true)
+                .target(target)?;
+            let engine = Engine::new(&config)?;
+            let elf = engine.precompile_module(wat.as_bytes())?;
+
+            let elf = object::File::parse(&elf[..])?;
+            let text = elf.section_by_name(".text").unwrap().index();
+            let functions = elf
+                .symbols()
+                .filter(|sym| {
+                    sym.kind() == SymbolKind::Text
+                        && sym.section_index() == Some(text)
+                        && sym.size() > 0
+                })
+                .count();
+            let eh_frame = elf
+                .section_by_name(".eh_frame")
+                .map(|section| section.data())
+                .transpose()?
+                .unwrap_or(&[]);
+
+            assert!(functions > 0);
+            assert_eq!(
+                count_fdes(eh_frame),
+                functions,
+                "missing unwind info for {strategy:?} on {target}"
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,282 @@
+//! Tests for `Store::trace_canonical_abi`.
+//!
+//! This is its own test binary as it installs a global logger to capture the
+//! events which are traced.
+
+#![cfg(not(miri))]
+
+use anyhow::Result;
+use std::cell::RefCell;
+use std::sync::Once;
+use wasmtime::component::{Component, Linker, ResourceAny};
+use wasmtime::{Engine, Store, StoreContextMut};
+use wasmtime_component_util::REALLOC_AND_FREE;
+
+const TARGET: &str = "wasmtime::component::abi";
+
+thread_local! {
+    // Tests run on separate threads and wasm is invoked on the calling
+    // thread, so events are kept per-thread to keep tests isolated.
+    static EVENTS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+struct AbiLogger;
+
+impl log::Log for AbiLogger {
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        metadata.target() == TARGET
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if self.enabled(record.metadata()) {
+            EVENTS.with(|e| e.borrow_mut().push(record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn install_logger() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        log::set_logger(&AbiLogger).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+    });
+}
+
+fn take_events() -> Vec<String> {
+    EVENTS.with(|e| e.borrow_mut().drain(..).collect())
+}
+
+/// Asserts that each of `events` starts with the prefix at the same position
+/// in `expected`, with nothing else traced.
+#[track_caller]
+fn assert_events(events: &[String], expected: &[&str]) {
+    assert_eq!(
+        events.len(),
+        expected.len(),
+        "unexpected events: {events:#?}"
+    );
+    for (event, prefix) in events.iter().zip(expected) {
+        assert!(
+            event.starts_with(prefix),
+            "event {event:?} doesn't start with {prefix:?}, all events: {events:#?}"
+        );
+    }
+}
+
+#[test]
+fn resources() -> Result<()> {
+    install_logger();
+    let engine = component_test_util::engine();
+    let c = Component::new(
+        &engine,
+        r#"
+            (component
+                (type $t' (resource (rep i32)))
+                (export $t "t" (type $t'))
+
+                (core func $t_ctor (canon resource.new $t))
+                (func (export "[constructor]t") (param "x" u32) (result (own $t))
+                    (canon lift (core func $t_ctor)))
+
+                (core func $t_drop (canon resource.drop $t))
+                (func (export "drop-t") (param "x" (own $t))
+                    (canon lift (core func $t_drop)))
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    store.trace_canonical_abi(true);
+    let i = Linker::new(&engine).instantiate(&mut store, &c)?;
+    let t_ctor = i.get_typed_func::<(u32,), (ResourceAny,)>(&mut store, "[constructor]t")?;
+    let t_dtor = i.get_typed_func::<(ResourceAny,), ()>(&mut store, "drop-t")?;
+
+    let (t,) = t_ctor.call(&mut store, (100,))?;
+    t_ctor.post_return(&mut store)?;
+    let events = take_events();
+    assert!(
+        events
+            .iter()
+            .any(|e| e.starts_with("resource.new: rep 100 => handle ")),
+        "missing resource.new: {events:#?}"
+    );
+
+    t_dtor.call(&mut store, (t,))?;
+    t_dtor.post_return(&mut store)?;
+    let events = take_events();
+    assert!(
+        events
+            .iter()
+            .any(|e| e.starts_with("resource.drop: handle ")),
+        "missing resource.drop: {events:#?}"
+    );
+
+    // Tracing is purely observational: errors are still reported.
+    assert!(t_dtor.call(&mut store, (t,)).is_err());
+    take_events();
+
+    // Nothing is traced once tracing is disabled.
+    store.trace_canonical_abi(false);
+    let (t,) = t_ctor.call(&mut store, (200,))?;
+    t_ctor.post_return(&mut store)?;
+    t_dtor.call(&mut store, (t,))?;
+    t_dtor.post_return(&mut store)?;
+    assert_events(&take_events(), &[]);
+
+    Ok(())
+}
+
+#[test]
+fn fused_adapter_strings() -> Result<()> {
+    install_logger();
+    let engine = component_test_util::engine();
+
+    let echo = |name: &str| {
+        format!(
+            r#"
+(component {name}
+    (import "echo" (func $echo (param "a" string) (result string)))
+    (core instance $libc (instantiate $libc))
+    (core func $echo (canon lower (func $echo)
+        (memory $libc "memory")
+        (realloc (func $libc "realloc"))
+    ))
+    (core instance $echo (instantiate $echo
+        (with "libc" (instance $libc))
+        (with "" (instance (export "echo" (func $echo))))
+    ))
+    (func (export "echo2") (param "a" string) (result string)
+        (canon lift
+            (core func $echo "echo")
+            (memory $libc "memory")
+            (realloc (func $libc "realloc"))
+        )
+    )
+)
+            "#
+        )
+    };
+    let src = echo("$src");
+    let dst = echo("$dst");
+    let component = format!(
+        r#"
+(component
+    (import "host" (func $host (param "a" string) (result string)))
+
+    (core module $libc
+        (memory (export "memory") 1)
+        {REALLOC_AND_FREE}
+    )
+    (core module $echo
+        (import "" "echo" (func $echo (param i32 i32 i32)))
+        (import "libc" "memory" (memory 0))
+        (import "libc" "realloc" (func $realloc (param i32 i32 i32 i32) (result i32)))
+
+        (func (export "echo") (param i32 i32) (result i32)
+            (local $retptr i32)
+            (local.set $retptr
+                (call $realloc
+                    (i32.const 0)
+                    (i32.const 0)
+                    (i32.const 4)
+                    (i32.const 8)))
+            (call $echo
+                (local.get 0)
+                (local.get 1)
+                (local.get $retptr))
+            local.get $retptr
+        )
+    )
+
+    {src}
+    {dst}
+
+    (instance $dst (instantiate $dst (with "echo" (func $host))))
+    (instance $src (instantiate $src (with "echo" (func $dst "echo2"))))
+    (export "echo" (func $src "echo2"))
+)
+"#
+    );
+    let component = Component::new(&engine, &component)?;
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    linker
+        .root()
+        .func_wrap("host", |_: StoreContextMut<()>, (arg,): (String,)| {
+            Ok((arg,))
+        })?;
+    let instance = linker.instantiate(&mut store, &component)?;
+    let func = instance.get_typed_func::<(&str,), (String,)>(&mut store, "echo")?;
+
+    store.trace_canonical_abi(true);
+    take_events();
+    let (ret,) = func.call(&mut store, ("hello",))?;
+    assert_eq!(ret, "hello");
+    func.post_return(&mut store)?;
+    assert_events(
+        &take_events(),
+        &[
+            // The host lowers the argument into `$src`...
+            "lower string: 5 utf-8 bytes from host as Utf8",
+            "realloc(0x0, 0, 1, 5) => ",
+            // ... the adapter between `$src` and `$dst` copies it over ...
+            "realloc(0x0, 0, 1, 5) => ",
+            "utf8-to-utf8 5",
+            // ... the host import lifts it out of `$dst` and lowers the
+            // result back in ...
+            "lift string: ",
+            "lower string: 5 utf-8 bytes from host as Utf8",
+            "realloc(0x0, 0, 1, 5) => ",
+            // ... the adapter copies the result back to `$src` ...
+            "realloc(0x0, 0, 1, 5) => ",
+            "utf8-to-utf8 5",
+            // ... and finally the host lifts it out of `$src`.
+            "lift string: ",
+        ],
+    );
+
+    store.trace_canonical_abi(false);
+    let (ret,) = func.call(&mut store, ("hello",))?;
+    assert_eq!(ret, "hello");
+    func.post_return(&mut store)?;
+    assert_events(&take_events(), &[]);
+
+    Ok(())
+}
+
+#[test]
+fn engine_is_shared_between_stores() -> Result<()> {
+    install_logger();
+    let engine = Engine::new(&component_test_util::config())?;
+    let component = Component::new(
+        &engine,
+        r#"
+            (component
+                (type $t' (resource (rep i32)))
+                (export $t "t" (type $t'))
+                (core func $t_ctor (canon resource.new $t))
+                (func (export "[constructor]t") (param "x" u32) (result (own $t))
+                    (canon lift (core func $t_ctor)))
+            )
+        "#,
+    )?;
+
+    // Tracing is configured per-store, so only the store with it enabled
+    // reports anything even though both share the same compiled component.
+    let mut traced = Store::new(&engine, ());
+    traced.trace_canonical_abi(true);
+    let mut untraced = Store::new(&engine, ());
+    for (store, expect_events) in [(&mut traced, true), (&mut untraced, false)] {
+        let i = Linker::new(&engine).instantiate(&mut *store, &component)?;
+        let ctor = i.get_typed_func::<(u32,), (ResourceAny,)>(&mut *store, "[constructor]t")?;
+        take_events();
+        let (t,) = ctor.call(&mut *store, (1,))?;
+        ctor.post_return(&mut *store)?;
+        t.resource_drop(&mut *store)?;
+        assert_eq!(!take_events().is_empty(), expect_events);
+    }
+
+    Ok(())
+}
@@ -20,6 +20,9 @@
 //! * No specifier - the output CLIF from translation is inspected.
 //! * `optimize = true` - CLIF is emitted, then optimized, then inspected.
 //! * `compile = true` - backends are run to produce machine code and that's inspected.
+//! * `test = "optimize-trace"` - CLIF is emitted, then optimized, and the
+//!   optimized CLIF is inspected along with how many times each mid-end
+//!   rewrite rule fired.
 //!
 //! Tests may also have a `flags` directive which are CLI flags to Wasmtime
 //! itself:
@@ -41,6 +44,7 @@
 
 use anyhow::{bail, Context, Result};
 use clap::Parser;
+use cranelift_codegen::control::ControlPlane;
 use cranelift_codegen::ir::{Function, UserExternalName, UserFuncName};
 use cranelift_codegen::isa::{lookup_by_name, TargetIsa};
 use cranelift_codegen::settings::{Configurable, Flags, SetError};
@@ -144,6 +148,10 @@ enum TestKind {
     Optimize,
     /// Alias for "compile" plus `-C compiler=winch`
     Winch,
+    /// Test the optimized CLIF output along with the mid-end rules which
+    /// fired while optimizing it.
+    #[serde(rename = "optimize-trace")]
+    OptimizeTrace,
 }
 
 impl Test {
@@ -178,7 +186,7 @@ impl Test {
         let mut config = self.opts.config(None)?;
         config.target(&self.config.target)?;
         match self.config.test {
-            TestKind::Clif => {
+            TestKind::Clif | TestKind::OptimizeTrace => {
                 config.emit_clif(tempdir.path());
                 config.cranelift_opt_level(OptLevel::None);
             }
@@ -197,7 +205,7 @@ impl Test {
             .context("failed to compile module")?;
 
         match self.config.test {
-            TestKind::Clif | TestKind::Optimize => {
+            TestKind::Clif | TestKind::Optimize | TestKind::OptimizeTrace => {
                 // Read all `*.clif` files from the clif directory that the
                 // compilation process just emitted.
                 let mut clifs = Vec::new();
@@ -253,6 +261,9 @@ impl Test {
         };
         flags.set("opt_level", opt_level)?;
         flags.set("preserve_frame_pointers", "true")?;
+        if let TestKind::OptimizeTrace = self.config.test {
+            flags.enable("egraph_rule_stats")?;
+        }
         for (key, val) in self.opts.codegen.cranelift.iter() {
             let key = &key.replace("-", "_");
             let target_res = match val {
@@ -297,6 +308,18 @@ fn assert_output(
                         func.dfg.resolve_all_aliases();
                         writeln!(&mut actual, "{}", func.display()).unwrap();
                     }
+                    TestKind::OptimizeTrace => {
+                        // Optimize the unoptimized CLIF emitted by Wasmtime
+                        // here so the rule statistics can be captured.
+                        let mut ctx = cranelift_codegen::Context::for_function(func);
+                        ctx.optimize(isa, &mut ControlPlane::default())
+                            .context("failed to optimize CLIF")?;
+                        ctx.func.dfg.resolve_all_aliases();
+                        let stats = ctx.egraph_rule_stats();
+                        writeln!(&mut actual, "{}", ctx.func.display()).unwrap();
+                        writeln!(&mut actual, "rewrites: {}", stats.total()).unwrap();
+                        writeln!(&mut actual, "{stats}").unwrap();
+                    }
                 }
             }
         }
@@ -0,0 +1,52 @@
+;;! target = "x86_64"
+;;! test = "optimize"
+;;! flags = "-O inline-threshold=32 -D address-map=n,backtrace=n"
+
+;; The call to `$add1` is replaced with its body.
+
+(module
+  (func $add1 (param i32) (result i32)
+    (i32.add (local.get 0) (i32.const 1)))
+  (func (param i32) (result i32)
+    (call $add1 (local.get 0)))
+)
+;; function u0:0(i64 vmctx, i64, i32) -> i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32):
+;; @001c                               v4 = iconst.i32 1
+;; @001e                               v5 = iadd v2, v4  ; v4 = 1
+;; @001f                               jump block1
+;;
+;;                                 block1:
+;; @001f                               return v5
+;; }
+;;
+;; function u0:1(i64 vmctx, i64, i32) -> i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     gv3 = vmctx
+;;     gv4 = load.i64 notrap aligned readonly gv3+8
+;;     gv5 = load.i64 notrap aligned gv4+16
+;;     sig0 = (i64 vmctx, i64, i32) -> i32 tail
+;;     fn0 = colocated u0:0 sig0
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32):
+;; @001c                               v7 = iconst.i32 1
+;; @001e                               v8 = iadd v2, v7  ; v7 = 1
+;; @001f                               jump block3
+;;
+;;                                 block3:
+;; @001f                               jump block2
+;;
+;;                                 block2:
+;; @0026                               jump block1
+;;
+;;                                 block1:
+;; @0026                               return v8
+;; }
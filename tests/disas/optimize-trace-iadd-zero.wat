@@ -0,0 +1,26 @@
+;;! target = "x86_64"
+;;! test = "optimize-trace"
+
+(module
+  (func (param i32) (result i32)
+    local.get 0
+    i32.const 0
+    i32.add
+  )
+)
+;; function u0:0(i64 vmctx, i64, i32) -> i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32):
+;; @001e                               jump block1
+;;
+;;                                 block1:
+;; @001e                               return v2
+;; }
+;;
+;; rewrites: 2
+;;      1 src/opts/arithmetic.isle line 7
+;;      1 src/opts/remat.isle line 5
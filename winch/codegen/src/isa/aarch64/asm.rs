@@ -22,6 +22,7 @@ use cranelift_codegen::{
         FPUOpRIMod, FPURightShiftImm, FpuRoundMode, Imm12, ImmLogic, ImmShift, Inst, IntToFpuOp,
         PairAMode, ScalarSize, VecLanesOp, VecMisc2, VectorSize,
     },
+    isa::unwind::UnwindInst,
     settings, Final, MachBuffer, MachBufferFinalized, MachInst, MachInstEmit, MachInstEmitState,
     MachLabel, Writable,
 };
@@ -109,6 +110,11 @@ impl Assembler {
         stencil.apply_base_srcloc(loc.unwrap_or_default())
     }
 
+    /// Emit an unwind instruction.
+    pub fn unwind_inst(&mut self, inst: UnwindInst) {
+        self.emit(Inst::Unwind { inst })
+    }
+
     fn emit(&mut self, inst: Inst) {
         self.emit_with_island(inst, Inst::worst_case_size());
     }
@@ -23,6 +23,7 @@ use cranelift_codegen::{
     binemit::CodeOffset,
     ir::{MemFlags, RelSourceLoc, SourceLoc},
     isa::aarch64::inst::{Cond, VectorSize},
+    isa::unwind::UnwindInst,
     settings, Final, MachBufferFinalized, MachLabel,
 };
 use regalloc2::RegClass;
@@ -36,15 +37,18 @@ pub(crate) struct MacroAssembler {
     sp_offset: u32,
     /// The target pointer size.
     ptr_size: OperandSize,
+    /// Shared flags.
+    shared_flags: settings::Flags,
 }
 
 impl MacroAssembler {
     /// Create an Aarch64 MacroAssembler.
     pub fn new(ptr_size: impl PtrSize, shared_flags: settings::Flags) -> Result<Self> {
         Ok(Self {
-            asm: Assembler::new(shared_flags),
+            asm: Assembler::new(shared_flags.clone()),
             sp_offset: 0u32,
             ptr_size: ptr_type_from_ptr_size(ptr_size.size()).try_into()?,
+            shared_flags,
         })
     }
 }
@@ -61,7 +65,22 @@ impl Masm for MacroAssembler {
         let addr = Address::pre_indexed_from_sp(-16);
 
         self.asm.stp(fp, lr, addr);
+        if self.shared_flags.unwind_info() {
+            self.asm.unwind_inst(UnwindInst::PushFrameRegs {
+                offset_upward_to_caller_sp: <Self::ABI as abi::ABI>::arg_base_offset().into(),
+            })
+        }
+
         self.asm.mov_rr(sp, writable!(fp), OperandSize::S64);
+        if self.shared_flags.unwind_info() {
+            self.asm.unwind_inst(UnwindInst::DefineNewFrame {
+                offset_upward_to_caller_sp: <Self::ABI as abi::ABI>::arg_base_offset().into(),
+
+                // The Winch calling convention has no callee-save registers, so nothing will be
+                // clobbered.
+                offset_downward_to_clobbers: 0,
+            })
+        }
         self.move_sp_to_shadow_sp();
         Ok(())
     }
@@ -149,11 +149,12 @@ impl TargetIsa for Aarch64 {
 
     fn emit_unwind_info(
         &self,
-        _result: &MachBufferFinalized<Final>,
-        _kind: cranelift_codegen::isa::unwind::UnwindInfoKind,
+        buffer: &MachBufferFinalized<Final>,
+        kind: cranelift_codegen::isa::unwind::UnwindInfoKind,
     ) -> Result<Option<cranelift_codegen::isa::unwind::UnwindInfo>> {
-        // TODO: should fill this in with an actual implementation
-        Ok(None)
+        Ok(cranelift_codegen::isa::aarch64::emit_unwind_info(
+            buffer, kind,
+        )?)
     }
 
     fn page_size_align_log2(&self) -> u8 {